@@ -6,20 +6,69 @@
 //! - Configurable max writes per second
 //! - Burst capacity handling
 //! - Per-tenant quotas (extensible)
+//!
+//! Typed admission bounds concurrent reads and writes separately, so a
+//! burst of heavy queries cannot starve writes (or the reverse):
+//! - A request takes a permit for its class before executing
+//! - When the class is full it queues for up to `admission_timeout_ms`
+//! - When the queue is full too it is rejected with a retry hint
+//! - Diagnostic requests take no permit
+//!
+//! On top of the classes, `max_concurrent_operations` bounds every
+//! admitted operation together. An operation over the limit waits in a
+//! bounded queue for up to `queue_timeout_ms`, then is rejected as too
+//! busy rather than piling up behind the global lock.
+//!
+//! Before either, an operation made as a tenant takes a slot under that
+//! tenant's own concurrency limit (its plan's `max_concurrent_operations`
+//! quota). Each tenant queues apart, at most `max_queued_per_tenant` deep,
+//! so one noisy tenant is rejected while the others' operations proceed.
 
+use std::collections::HashMap;
+use std::fmt;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Mutex;
-use std::time::Instant;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 /// Admission control configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AdmissionControlConfig {
     /// Max writes per second (0 = unlimited)
     pub max_writes_per_second: u32,
     
     /// Max concurrent queries (0 = unlimited)
     pub max_concurrent_queries: u32,
+
+    /// Max reads executing at once (0 = unlimited)
+    pub max_concurrent_reads: u32,
+
+    /// Max writes executing at once (0 = unlimited)
+    pub max_concurrent_writes: u32,
+
+    /// Max reads waiting for a permit; further reads are rejected
+    pub max_queued_reads: u32,
+
+    /// Max writes waiting for a permit; further writes are rejected
+    pub max_queued_writes: u32,
+
+    /// How long a queued request waits for a permit before it is rejected
+    pub admission_timeout_ms: u64,
+
+    /// Max operations of any class executing at once (0 = unlimited)
+    pub max_concurrent_operations: u32,
+
+    /// Max operations waiting for a slot; further operations are rejected
+    pub max_queue_depth: u32,
+
+    /// How long a queued operation waits for a slot before it is rejected
+    pub queue_timeout_ms: u64,
+
+    /// Max operations of one tenant waiting for a slot under the tenant's
+    /// limit; further operations of that tenant are rejected
+    pub max_queued_per_tenant: u32,
 }
 
 impl Default for AdmissionControlConfig {
@@ -27,10 +76,277 @@ impl Default for AdmissionControlConfig {
         Self {
             max_writes_per_second: 0,
             max_concurrent_queries: 100,
+            max_concurrent_reads: 0,
+            max_concurrent_writes: 0,
+            max_queued_reads: 64,
+            max_queued_writes: 64,
+            admission_timeout_ms: 1_000,
+            max_concurrent_operations: 0,
+            max_queue_depth: 64,
+            queue_timeout_ms: 1_000,
+            max_queued_per_tenant: 16,
+        }
+    }
+}
+
+/// Class of operation for typed admission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationClass {
+    /// Queries, aggregations and statistics collection
+    Read,
+    /// Inserts, updates and deletes
+    Write,
+}
+
+impl OperationClass {
+    /// Lowercase name, as used in messages and stats
+    pub fn name(&self) -> &'static str {
+        match self {
+            OperationClass::Read => "read",
+            OperationClass::Write => "write",
+        }
+    }
+}
+
+/// Why a request was not admitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    /// The queue was full
+    QueueFull,
+    /// The request queued for its timeout without a permit
+    TimedOut,
+}
+
+impl RejectionReason {
+    fn describe(&self) -> &'static str {
+        match self {
+            RejectionReason::QueueFull => "queue is full",
+            RejectionReason::TimedOut => "no permit became free in time",
+        }
+    }
+}
+
+/// An operation refused by the global operation limit, or by its
+/// tenant's
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejected {
+    /// Why it was refused
+    pub reason: RejectionReason,
+    /// Suggested wait before retrying
+    pub retry_after_ms: u64,
+    /// Tenant whose limit refused it (None: the global limit)
+    pub tenant_id: Option<Uuid>,
+}
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tenant_id {
+            Some(tenant_id) => write!(f, "Tenant {} too busy: ", tenant_id)?,
+            None => write!(f, "Server too busy: ")?,
+        }
+        write!(
+            f,
+            "{}; retry after {} ms",
+            self.reason.describe(),
+            self.retry_after_ms
+        )
+    }
+}
+
+/// A request refused by typed admission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdmissionRejection {
+    /// Class the request belongs to
+    pub class: OperationClass,
+    /// Why it was refused
+    pub reason: RejectionReason,
+    /// Suggested wait before retrying
+    pub retry_after_ms: u64,
+}
+
+impl fmt::Display for AdmissionRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Too many concurrent {} operations: {}; retry after {} ms",
+            self.class.name(),
+            self.reason.describe(),
+            self.retry_after_ms
+        )
+    }
+}
+
+/// Current load of one operation class, or of all operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ClassStats {
+    /// Requests holding a permit
+    pub in_flight: u64,
+    /// Requests waiting for a permit
+    pub queued: u64,
+    /// Configured concurrency limit (0 = unlimited)
+    pub max_concurrent: u32,
+    /// Configured queue limit
+    pub max_queued: u32,
+    /// Requests rejected since startup
+    pub rejected: u64,
+}
+
+/// Current load of every operation class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AdmissionStats {
+    pub operations: ClassStats,
+    pub reads: ClassStats,
+    pub writes: ClassStats,
+}
+
+#[derive(Debug, Default)]
+struct GateState {
+    in_flight: u64,
+    queued: u64,
+    rejected: u64,
+    /// Moving average of how long a permit is held
+    avg_hold_ms: f64,
+}
+
+/// Concurrency limit and wait queue
+#[derive(Debug)]
+struct Gate {
+    max_concurrent: u32,
+    max_queued: u32,
+    timeout: Duration,
+    state: Mutex<GateState>,
+    released: Condvar,
+}
+
+impl Gate {
+    fn new(max_concurrent: u32, max_queued: u32, timeout_ms: u64) -> Self {
+        Self {
+            max_concurrent,
+            max_queued,
+            timeout: Duration::from_millis(timeout_ms),
+            state: Mutex::new(GateState::default()),
+            released: Condvar::new(),
+        }
+    }
+
+    fn has_room(&self, state: &GateState) -> bool {
+        self.max_concurrent == 0 || state.in_flight < self.max_concurrent as u64
+    }
+
+    /// Take a permit if one is free, without waiting
+    fn try_enter(self: &Arc<Self>) -> Option<Held> {
+        let mut state = self.state.lock().unwrap();
+        if !self.has_room(&state) {
+            return None;
+        }
+        state.in_flight += 1;
+        Some(Held::new(Arc::clone(self)))
+    }
+
+    /// Take a permit, queueing for up to the timeout
+    fn enter(self: &Arc<Self>) -> Result<Held, Rejected> {
+        let mut state = self.state.lock().unwrap();
+        if !self.has_room(&state) {
+            if state.queued >= self.max_queued as u64 {
+                return Err(self.reject(&mut state, RejectionReason::QueueFull));
+            }
+
+            state.queued += 1;
+            let (waited, timeout) = self
+                .released
+                .wait_timeout_while(state, self.timeout, |state| !self.has_room(state))
+                .unwrap();
+            state = waited;
+            state.queued -= 1;
+            if timeout.timed_out() {
+                return Err(self.reject(&mut state, RejectionReason::TimedOut));
+            }
+        }
+        state.in_flight += 1;
+        Ok(Held::new(Arc::clone(self)))
+    }
+
+    fn reject(&self, state: &mut GateState, reason: RejectionReason) -> Rejected {
+        state.rejected += 1;
+        // Each wave of max_concurrent permits ahead takes about one hold time
+        let waves = (state.queued + 1).div_ceil(self.max_concurrent.max(1) as u64);
+        let retry_after_ms = (state.avg_hold_ms * waves as f64).ceil().max(1.0) as u64;
+        Rejected {
+            reason,
+            retry_after_ms,
+            tenant_id: None,
+        }
+    }
+
+    fn leave(&self, held: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.in_flight -= 1;
+        let held_ms = held.as_secs_f64() * 1_000.0;
+        state.avg_hold_ms = if state.avg_hold_ms == 0.0 {
+            held_ms
+        } else {
+            state.avg_hold_ms * 0.8 + held_ms * 0.2
+        };
+        drop(state);
+        self.released.notify_one();
+    }
+
+    fn stats(&self) -> ClassStats {
+        let state = self.state.lock().unwrap();
+        ClassStats {
+            in_flight: state.in_flight,
+            queued: state.queued,
+            max_concurrent: self.max_concurrent,
+            max_queued: self.max_queued,
+            rejected: state.rejected,
         }
     }
 }
 
+/// A slot taken from a gate; released on drop
+#[derive(Debug)]
+struct Held {
+    gate: Arc<Gate>,
+    admitted_at: Instant,
+}
+
+impl Held {
+    fn new(gate: Arc<Gate>) -> Self {
+        Self {
+            gate,
+            admitted_at: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Held {
+    fn drop(&mut self) {
+        self.gate.leave(self.admitted_at.elapsed());
+    }
+}
+
+/// Permission to execute one operation of a class; released on drop
+#[derive(Debug)]
+pub struct AdmissionPermit {
+    class: OperationClass,
+    _held: Held,
+}
+
+impl AdmissionPermit {
+    /// Class this permit was issued for
+    pub fn class(&self) -> OperationClass {
+        self.class
+    }
+}
+
+/// A slot under `max_concurrent_operations`, or under a tenant's limit;
+/// released on drop
+#[derive(Debug)]
+pub struct AdmitGuard {
+    _held: Held,
+}
+
 /// Token bucket for rate limiting
 #[derive(Debug)]
 struct TokenBucket {
@@ -72,6 +388,10 @@ pub struct AdmissionController {
     config: AdmissionControlConfig,
     write_bucket: Mutex<Option<TokenBucket>>,
     active_queries: AtomicU64,
+    operations: Arc<Gate>,
+    reads: Arc<Gate>,
+    writes: Arc<Gate>,
+    tenants: Mutex<HashMap<Uuid, Arc<Gate>>>,
 }
 
 impl AdmissionController {
@@ -85,13 +405,134 @@ impl AdmissionController {
             None
         };
 
+        let operations = Gate::new(
+            config.max_concurrent_operations,
+            config.max_queue_depth,
+            config.queue_timeout_ms,
+        );
+        let reads = Gate::new(
+            config.max_concurrent_reads,
+            config.max_queued_reads,
+            config.admission_timeout_ms,
+        );
+        let writes = Gate::new(
+            config.max_concurrent_writes,
+            config.max_queued_writes,
+            config.admission_timeout_ms,
+        );
+
         Self {
             config,
             write_bucket: Mutex::new(write_bucket),
             active_queries: AtomicU64::new(0),
+            operations: Arc::new(operations),
+            reads: Arc::new(reads),
+            writes: Arc::new(writes),
+            tenants: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn gate(&self, class: OperationClass) -> &Arc<Gate> {
+        match class {
+            OperationClass::Read => &self.reads,
+            OperationClass::Write => &self.writes,
         }
     }
 
+    /// Acquire a permit for an operation of `class`.
+    ///
+    /// Waits up to `admission_timeout_ms` while the class is at its
+    /// concurrency limit. Rejects at once when its queue is full.
+    pub fn acquire(&self, class: OperationClass) -> Result<AdmissionPermit, AdmissionRejection> {
+        match self.gate(class).enter() {
+            Ok(held) => Ok(AdmissionPermit { class, _held: held }),
+            Err(rejected) => Err(AdmissionRejection {
+                class,
+                reason: rejected.reason,
+                retry_after_ms: rejected.retry_after_ms,
+            }),
+        }
+    }
+
+    /// Acquire a permit only if one is free right now
+    pub fn try_acquire(&self, class: OperationClass) -> Option<AdmissionPermit> {
+        let held = self.gate(class).try_enter()?;
+        Some(AdmissionPermit { class, _held: held })
+    }
+
+    /// Take a slot under `max_concurrent_operations`.
+    ///
+    /// Waits up to `queue_timeout_ms` while every slot is taken. Rejects at
+    /// once when `max_queue_depth` operations are already waiting.
+    pub fn try_admit(&self) -> Result<AdmitGuard, Rejected> {
+        let held = self.operations.enter()?;
+        Ok(AdmitGuard { _held: held })
+    }
+
+    /// Take a slot under a tenant's limit of `max_concurrent` operations.
+    ///
+    /// Waits up to `queue_timeout_ms` while every slot of the tenant is
+    /// taken. Rejects at once when `max_queued_per_tenant` of its
+    /// operations are already waiting. A changed limit applies to slots
+    /// taken from then on; operations already admitted keep theirs.
+    pub fn try_admit_tenant(
+        &self,
+        tenant_id: Uuid,
+        max_concurrent: u32,
+    ) -> Result<AdmitGuard, Rejected> {
+        let gate = {
+            let mut tenants = self.tenants.lock().unwrap();
+            let gate = tenants.entry(tenant_id).or_insert_with(|| {
+                Arc::new(self.tenant_gate(max_concurrent))
+            });
+            if gate.max_concurrent != max_concurrent {
+                *gate = Arc::new(self.tenant_gate(max_concurrent));
+            }
+            Arc::clone(gate)
+        };
+        match gate.enter() {
+            Ok(held) => Ok(AdmitGuard { _held: held }),
+            Err(rejected) => Err(Rejected {
+                tenant_id: Some(tenant_id),
+                ..rejected
+            }),
+        }
+    }
+
+    fn tenant_gate(&self, max_concurrent: u32) -> Gate {
+        Gate::new(
+            max_concurrent,
+            self.config.max_queued_per_tenant,
+            self.config.queue_timeout_ms,
+        )
+    }
+
+    /// In-flight and queued counts of a tenant's operations, if it has
+    /// been admitted before
+    pub fn tenant_stats(&self, tenant_id: Uuid) -> Option<ClassStats> {
+        let tenants = self.tenants.lock().unwrap();
+        tenants.get(&tenant_id).map(|gate| gate.stats())
+    }
+
+    /// In-flight and queued counts of all operations and per class
+    pub fn stats(&self) -> AdmissionStats {
+        AdmissionStats {
+            operations: self.operations.stats(),
+            reads: self.reads.stats(),
+            writes: self.writes.stats(),
+        }
+    }
+
+    /// Change the write rate limit without restarting (0 = unlimited)
+    ///
+    /// The bucket is replaced and starts full, as it does at boot.
+    pub fn set_max_writes_per_second(&self, max_writes_per_second: u32) {
+        let bucket = (max_writes_per_second > 0).then(|| {
+            TokenBucket::new(max_writes_per_second as f64, max_writes_per_second as f64)
+        });
+        *self.write_bucket.lock().unwrap() = bucket;
+    }
+
     /// Try to acquire permission for a write operation
     pub fn try_acquire_write(&self) -> bool {
         let mut bucket = self.write_bucket.lock().unwrap();
@@ -152,3 +593,188 @@ impl AdmissionController {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn controller(max_concurrent_writes: u32, max_queued_writes: u32) -> Arc<AdmissionController> {
+        Arc::new(AdmissionController::new(AdmissionControlConfig {
+            max_concurrent_reads: 1,
+            max_concurrent_writes,
+            max_queued_writes,
+            admission_timeout_ms: 5_000,
+            ..AdmissionControlConfig::default()
+        }))
+    }
+
+    fn wait_until(controller: &AdmissionController, done: impl Fn(&AdmissionStats) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !done(&controller.stats()) {
+            assert!(Instant::now() < deadline, "timed out waiting for queue");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_unlimited_by_default() {
+        let controller = AdmissionController::new(AdmissionControlConfig::default());
+        let permits: Vec<_> = (0..100)
+            .map(|_| controller.acquire(OperationClass::Write).unwrap())
+            .collect();
+        assert_eq!(controller.stats().writes.in_flight, 100);
+        drop(permits);
+        assert_eq!(controller.stats().writes.in_flight, 0);
+    }
+
+    #[test]
+    fn test_saturated_writes_do_not_block_reads() {
+        let controller = controller(2, 0);
+        let _w1 = controller.acquire(OperationClass::Write).unwrap();
+        let _w2 = controller.acquire(OperationClass::Write).unwrap();
+        assert!(controller.try_acquire(OperationClass::Write).is_none());
+
+        let read = controller.acquire(OperationClass::Read).unwrap();
+        assert_eq!(read.class(), OperationClass::Read);
+
+        let stats = controller.stats();
+        assert_eq!((stats.writes.in_flight, stats.reads.in_flight), (2, 1));
+    }
+
+    #[test]
+    fn test_queue_full_rejects_with_retry_hint() {
+        let controller = controller(1, 1);
+        let held = controller.acquire(OperationClass::Write).unwrap();
+
+        let queued = {
+            let controller = Arc::clone(&controller);
+            thread::spawn(move || controller.acquire(OperationClass::Write).map(|p| p.class()))
+        };
+        wait_until(&controller, |stats| stats.writes.queued == 1);
+
+        let rejection = controller.acquire(OperationClass::Write).unwrap_err();
+        assert_eq!(rejection.reason, RejectionReason::QueueFull);
+        assert_eq!(rejection.class, OperationClass::Write);
+        assert!(rejection.retry_after_ms >= 1);
+        assert_eq!(controller.stats().writes.rejected, 1);
+
+        // Releasing the permit admits the queued request
+        drop(held);
+        assert_eq!(queued.join().unwrap(), Ok(OperationClass::Write));
+        let stats = controller.stats().writes;
+        assert_eq!((stats.in_flight, stats.queued), (0, 0));
+    }
+
+    #[test]
+    fn test_queued_request_times_out() {
+        let controller = AdmissionController::new(AdmissionControlConfig {
+            max_concurrent_writes: 1,
+            admission_timeout_ms: 20,
+            ..AdmissionControlConfig::default()
+        });
+        let _held = controller.acquire(OperationClass::Write).unwrap();
+
+        let rejection = controller.acquire(OperationClass::Write).unwrap_err();
+        assert_eq!(rejection.reason, RejectionReason::TimedOut);
+        assert_eq!(controller.stats().writes.queued, 0);
+    }
+
+    #[test]
+    fn test_saturated_operations_queue_then_reject() {
+        let controller = Arc::new(AdmissionController::new(AdmissionControlConfig {
+            max_concurrent_operations: 2,
+            max_queue_depth: 1,
+            queue_timeout_ms: 5_000,
+            ..AdmissionControlConfig::default()
+        }));
+        let first = controller.try_admit().unwrap();
+        let _second = controller.try_admit().unwrap();
+
+        let queued = {
+            let controller = Arc::clone(&controller);
+            thread::spawn(move || controller.try_admit().map(|_| ()))
+        };
+        wait_until(&controller, |stats| stats.operations.queued == 1);
+
+        let rejected = controller.try_admit().unwrap_err();
+        assert_eq!(rejected.reason, RejectionReason::QueueFull);
+        assert!(rejected.to_string().starts_with("Server too busy"));
+        let stats = controller.stats().operations;
+        assert_eq!((stats.in_flight, stats.queued, stats.rejected), (2, 1, 1));
+
+        // Freeing a slot admits the queued operation
+        drop(first);
+        assert_eq!(queued.join().unwrap(), Ok(()));
+        let stats = controller.stats().operations;
+        assert_eq!((stats.in_flight, stats.queued), (1, 0));
+    }
+
+    #[test]
+    fn test_queued_operation_times_out() {
+        let controller = AdmissionController::new(AdmissionControlConfig {
+            max_concurrent_operations: 1,
+            queue_timeout_ms: 20,
+            ..AdmissionControlConfig::default()
+        });
+        let _held = controller.try_admit().unwrap();
+
+        let rejected = controller.try_admit().unwrap_err();
+        assert_eq!(rejected.reason, RejectionReason::TimedOut);
+        assert_eq!(controller.stats().operations.queued, 0);
+
+        // The classes are unaffected by the operation limit
+        assert!(controller.try_acquire(OperationClass::Read).is_some());
+    }
+
+    #[test]
+    fn test_tenant_limit_leaves_other_tenants_admitted() {
+        let controller = Arc::new(AdmissionController::new(AdmissionControlConfig {
+            max_queued_per_tenant: 1,
+            queue_timeout_ms: 5_000,
+            ..AdmissionControlConfig::default()
+        }));
+        let noisy = Uuid::new_v4();
+        let quiet = Uuid::new_v4();
+        let first = controller.try_admit_tenant(noisy, 1).unwrap();
+
+        let queued = {
+            let controller = Arc::clone(&controller);
+            thread::spawn(move || controller.try_admit_tenant(noisy, 1).map(|_| ()))
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while controller.tenant_stats(noisy).unwrap().queued < 1 {
+            assert!(Instant::now() < deadline, "timed out waiting for queue");
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        // The noisy tenant's queue is full; the quiet tenant is unaffected
+        let rejected = controller.try_admit_tenant(noisy, 1).unwrap_err();
+        assert_eq!(rejected.reason, RejectionReason::QueueFull);
+        assert_eq!(rejected.tenant_id, Some(noisy));
+        assert!(rejected.to_string().starts_with(&format!("Tenant {} too busy", noisy)));
+        let _quiet = controller.try_admit_tenant(quiet, 1).unwrap();
+        assert_eq!(controller.tenant_stats(quiet).unwrap().in_flight, 1);
+        assert_eq!(controller.tenant_stats(noisy).unwrap().rejected, 1);
+
+        // Freeing the noisy tenant's slot admits its queued operation
+        drop(first);
+        assert_eq!(queued.join().unwrap(), Ok(()));
+        assert_eq!(controller.tenant_stats(noisy).unwrap().in_flight, 0);
+    }
+
+    #[test]
+    fn test_no_queue_rejects_at_once() {
+        let controller = AdmissionController::new(AdmissionControlConfig {
+            max_concurrent_operations: 1,
+            max_queue_depth: 0,
+            ..AdmissionControlConfig::default()
+        });
+        let held = controller.try_admit().unwrap();
+        let rejected = controller.try_admit().unwrap_err();
+        assert_eq!(rejected.reason, RejectionReason::QueueFull);
+
+        drop(held);
+        assert!(controller.try_admit().is_ok());
+    }
+}
@@ -0,0 +1,15 @@
+//! Collection bindings
+//!
+//! What other layers attach to a collection by name — an RLS policy, a
+//! generated REST endpoint — is moved by a rename and copied by a copy
+//! through [`CollectionBindings`], once the documents and schema versions
+//! have moved.
+
+/// Moves and copies the bindings other layers keep per collection name
+pub trait CollectionBindings: Send + Sync {
+    /// Move `from`'s bindings to `to`
+    fn rename(&self, from: &str, to: &str) -> Result<(), String>;
+
+    /// Give `to` the bindings `from` has, leaving `from`'s in place
+    fn copy(&self, from: &str, to: &str) -> Result<(), String>;
+}
@@ -5,6 +5,11 @@
 
 use std::fmt;
 
+use uuid::Uuid;
+
+use crate::admission_control::{AdmissionRejection, Rejected};
+use crate::replication::{ReplicationError, ReplicationErrorKind};
+
 /// API error severity
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Severity {
@@ -36,6 +41,30 @@ pub enum ApiErrorCode {
     AeroServiceUnavailable,
     /// Too many requests (backpressure)
     AeroTooManyRequests,
+    /// Write sent to a replica
+    AeroNotPrimary,
+    /// Replica is behind the requested staleness bound
+    AeroReplicaTooStale,
+    /// Too few replicas acknowledged a write within the sync timeout
+    AeroReplicationTimeout,
+    /// Operation class is at its concurrency and queue limits
+    AeroAdmissionRejected,
+    /// All operation slots and the wait queue are taken
+    AeroTooBusy,
+    /// No open transaction has the given tx_id
+    AeroTransactionNotFound,
+    /// Transaction exceeded its timeout and was discarded
+    AeroTransactionExpired,
+    /// Another write committed to a document of the transaction first
+    AeroTransactionConflict,
+    /// Transaction buffered more than its size cap
+    AeroTransactionTooLarge,
+    /// Dangerous operation sent without its confirmation phrase
+    AeroConfirmationRequired,
+    /// Collection name is already taken
+    AeroCollectionExists,
+    /// Confirmation token is unknown, expired, used or for another request
+    AeroConfirmationRejected,
 }
 
 impl ApiErrorCode {
@@ -47,6 +76,18 @@ impl ApiErrorCode {
             ApiErrorCode::PassThrough => "PASS_THROUGH",
             ApiErrorCode::AeroServiceUnavailable => "AERO_SERVICE_UNAVAILABLE",
             ApiErrorCode::AeroTooManyRequests => "AERO_TOO_MANY_REQUESTS",
+            ApiErrorCode::AeroNotPrimary => "AERO_NOT_PRIMARY",
+            ApiErrorCode::AeroReplicaTooStale => "AERO_REPLICA_TOO_STALE",
+            ApiErrorCode::AeroReplicationTimeout => "AERO_REPLICATION_TIMEOUT",
+            ApiErrorCode::AeroAdmissionRejected => "AERO_ADMISSION_REJECTED",
+            ApiErrorCode::AeroTooBusy => "AERO_TOO_BUSY",
+            ApiErrorCode::AeroTransactionNotFound => "AERO_TRANSACTION_NOT_FOUND",
+            ApiErrorCode::AeroTransactionExpired => "AERO_TRANSACTION_EXPIRED",
+            ApiErrorCode::AeroTransactionConflict => "AERO_TRANSACTION_CONFLICT",
+            ApiErrorCode::AeroTransactionTooLarge => "AERO_TRANSACTION_TOO_LARGE",
+            ApiErrorCode::AeroConfirmationRequired => "AERO_CONFIRMATION_REQUIRED",
+            ApiErrorCode::AeroCollectionExists => "AERO_COLLECTION_EXISTS",
+            ApiErrorCode::AeroConfirmationRejected => "AERO_CONFIRMATION_REJECTED",
         }
     }
 
@@ -58,6 +99,18 @@ impl ApiErrorCode {
             ApiErrorCode::PassThrough => Severity::Error, // Can be overridden
             ApiErrorCode::AeroServiceUnavailable => Severity::Error,
             ApiErrorCode::AeroTooManyRequests => Severity::Error,
+            ApiErrorCode::AeroNotPrimary => Severity::Error,
+            ApiErrorCode::AeroReplicaTooStale => Severity::Error,
+            ApiErrorCode::AeroReplicationTimeout => Severity::Error,
+            ApiErrorCode::AeroAdmissionRejected => Severity::Error,
+            ApiErrorCode::AeroTooBusy => Severity::Error,
+            ApiErrorCode::AeroTransactionNotFound => Severity::Error,
+            ApiErrorCode::AeroTransactionExpired => Severity::Error,
+            ApiErrorCode::AeroTransactionConflict => Severity::Error,
+            ApiErrorCode::AeroTransactionTooLarge => Severity::Error,
+            ApiErrorCode::AeroConfirmationRequired => Severity::Error,
+            ApiErrorCode::AeroCollectionExists => Severity::Error,
+            ApiErrorCode::AeroConfirmationRejected => Severity::Error,
         }
     }
 }
@@ -77,6 +130,10 @@ pub struct ApiError {
     message: String,
     /// Severity
     severity: Severity,
+    /// Suggested wait before retrying, for overload rejections
+    retry_after_ms: Option<u64>,
+    /// Token to repeat the request with, for unconfirmed dangerous operations
+    confirmation_token: Option<Uuid>,
 }
 
 impl ApiError {
@@ -86,6 +143,8 @@ impl ApiError {
             code: ApiErrorCode::AeroInvalidRequest.code().to_string(),
             message: reason.into(),
             severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
         }
     }
 
@@ -95,6 +154,8 @@ impl ApiError {
             code: ApiErrorCode::AeroUnknownOperation.code().to_string(),
             message: format!("Unknown operation: {}", op.into()),
             severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
         }
     }
 
@@ -104,6 +165,8 @@ impl ApiError {
             code: ApiErrorCode::AeroServiceUnavailable.code().to_string(),
             message: reason.into(),
             severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
         }
     }
 
@@ -113,6 +176,138 @@ impl ApiError {
             code: ApiErrorCode::AeroTooManyRequests.code().to_string(),
             message: reason.into(),
             severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create a transaction not found error
+    pub fn transaction_not_found(tx_id: impl fmt::Display) -> Self {
+        Self {
+            code: ApiErrorCode::AeroTransactionNotFound.code().to_string(),
+            message: format!("No open transaction: {}", tx_id),
+            severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create a transaction expired error
+    pub fn transaction_expired(tx_id: impl fmt::Display, timeout_ms: u128) -> Self {
+        Self {
+            code: ApiErrorCode::AeroTransactionExpired.code().to_string(),
+            message: format!(
+                "Transaction {} exceeded its {}ms timeout; its writes were discarded",
+                tx_id, timeout_ms
+            ),
+            severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create a transaction conflict error
+    pub fn transaction_conflict(tx_id: impl fmt::Display, document_id: &str) -> Self {
+        Self {
+            code: ApiErrorCode::AeroTransactionConflict.code().to_string(),
+            message: format!(
+                "Transaction {} conflicts with a write to document {} committed after it began",
+                tx_id, document_id
+            ),
+            severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create a transaction too large error
+    pub fn transaction_too_large(tx_id: impl fmt::Display, max_bytes: u64) -> Self {
+        Self {
+            code: ApiErrorCode::AeroTransactionTooLarge.code().to_string(),
+            message: format!(
+                "Transaction {} would buffer more than {} bytes of writes",
+                tx_id, max_bytes
+            ),
+            severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create the error for a dangerous operation requested without a
+    /// confirmation token, carrying the token to repeat it with
+    pub fn confirmation_required(warning: &str, token: Uuid) -> Self {
+        Self {
+            code: ApiErrorCode::AeroConfirmationRequired.code().to_string(),
+            message: format!(
+                "{} Repeat the request with \"confirm\": \"{}\" to proceed",
+                warning, token
+            ),
+            severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: Some(token),
+        }
+    }
+
+    /// Create the error for a confirmation token that is unknown, expired,
+    /// already used, or was issued for another request
+    pub fn confirmation_rejected(reason: impl Into<String>) -> Self {
+        Self {
+            code: ApiErrorCode::AeroConfirmationRejected.code().to_string(),
+            message: reason.into(),
+            severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create a collection exists error
+    pub fn collection_exists(collection: &str) -> Self {
+        Self {
+            code: ApiErrorCode::AeroCollectionExists.code().to_string(),
+            message: format!("Collection already exists: {}", collection),
+            severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create from a replica gate refusal
+    pub fn from_replication_error(err: ReplicationError) -> Self {
+        let code = match err.kind {
+            ReplicationErrorKind::NotPrimary => ApiErrorCode::AeroNotPrimary,
+            ReplicationErrorKind::ReplicaTooStale => ApiErrorCode::AeroReplicaTooStale,
+            ReplicationErrorKind::ReplicationTimeout => ApiErrorCode::AeroReplicationTimeout,
+            _ => ApiErrorCode::AeroServiceUnavailable,
+        };
+        Self {
+            code: code.code().to_string(),
+            message: err.message,
+            severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create from a typed admission rejection
+    pub fn from_admission_rejection(rejection: AdmissionRejection) -> Self {
+        Self {
+            code: ApiErrorCode::AeroAdmissionRejected.code().to_string(),
+            message: rejection.to_string(),
+            severity: Severity::Error,
+            retry_after_ms: Some(rejection.retry_after_ms),
+            confirmation_token: None,
+        }
+    }
+
+    /// Create from a rejection by the global operation limit or a tenant's
+    pub fn too_busy(rejected: Rejected) -> Self {
+        Self {
+            code: ApiErrorCode::AeroTooBusy.code().to_string(),
+            message: rejected.to_string(),
+            severity: Severity::Error,
+            retry_after_ms: Some(rejected.retry_after_ms),
+            confirmation_token: None,
         }
     }
 
@@ -126,6 +321,8 @@ impl ApiError {
             } else {
                 Severity::Error
             },
+            retry_after_ms: None,
+            confirmation_token: None,
         }
     }
 
@@ -135,6 +332,8 @@ impl ApiError {
             code: err.code().code().to_string(),
             message: err.message().to_string(),
             severity: Severity::Error, // Planner errors are always recoverable
+            retry_after_ms: None,
+            confirmation_token: None,
         }
     }
 
@@ -148,6 +347,8 @@ impl ApiError {
             } else {
                 Severity::Error
             },
+            retry_after_ms: None,
+            confirmation_token: None,
         }
     }
 
@@ -161,6 +362,8 @@ impl ApiError {
             } else {
                 Severity::Error
             },
+            retry_after_ms: None,
+            confirmation_token: None,
         }
     }
 
@@ -174,9 +377,44 @@ impl ApiError {
             } else {
                 Severity::Error
             },
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create from an index error (pass-through)
+    pub fn from_index_error(err: crate::index::IndexError) -> Self {
+        Self {
+            code: err.code().code().to_string(),
+            message: err.message().to_string(),
+            severity: if err.is_fatal() {
+                Severity::Fatal
+            } else {
+                Severity::Error
+            },
+            retry_after_ms: None,
+            confirmation_token: None,
+        }
+    }
+
+    /// Create from a control plane error such as `QUOTA_EXCEEDED`
+    /// (pass-through)
+    pub fn from_control_plane_error(err: crate::control_plane::ControlPlaneError) -> Self {
+        Self {
+            code: err.error_code().to_string(),
+            message: err.to_string(),
+            severity: Severity::Error,
+            retry_after_ms: None,
+            confirmation_token: None,
         }
     }
 
+    /// Name the batch position of the document this error concerns
+    pub fn for_document(mut self, index: usize) -> Self {
+        self.message = format!("documents[{}]: {}", index, self.message);
+        self
+    }
+
     /// Returns the error code
     pub fn code(&self) -> &str {
         &self.code
@@ -192,6 +430,16 @@ impl ApiError {
         self.severity
     }
 
+    /// Returns the suggested wait before retrying, if any
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        self.retry_after_ms
+    }
+
+    /// Returns the confirmation token to repeat the request with, if any
+    pub fn confirmation_token(&self) -> Option<Uuid> {
+        self.confirmation_token
+    }
+
     /// Returns whether this is a fatal error
     pub fn is_fatal(&self) -> bool {
         matches!(self.severity, Severity::Fatal)
@@ -212,6 +460,7 @@ pub type ApiResult<T> = Result<T, ApiError>;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::admission_control::{OperationClass, RejectionReason};
 
     #[test]
     fn test_invalid_request_error() {
@@ -226,4 +475,57 @@ mod tests {
         assert_eq!(err.code(), "AERO_UNKNOWN_OPERATION");
         assert!(err.message().contains("foo"));
     }
+
+    #[test]
+    fn test_replication_errors() {
+        let err = ApiError::from_replication_error(ReplicationError::not_primary("primary:7000"));
+        assert_eq!(err.code(), "AERO_NOT_PRIMARY");
+        assert!(err.message().contains("primary:7000"));
+
+        let err = ApiError::from_replication_error(ReplicationError::replica_too_stale("behind"));
+        assert_eq!(err.code(), "AERO_REPLICA_TOO_STALE");
+        assert!(!err.is_fatal());
+
+        let err = ApiError::from_replication_error(ReplicationError::replication_timeout("late"));
+        assert_eq!(err.code(), "AERO_REPLICATION_TIMEOUT");
+    }
+
+    #[test]
+    fn test_admission_rejection() {
+        let err = ApiError::from_admission_rejection(AdmissionRejection {
+            class: OperationClass::Write,
+            reason: RejectionReason::QueueFull,
+            retry_after_ms: 40,
+        });
+        assert_eq!(err.code(), "AERO_ADMISSION_REJECTED");
+        assert_eq!(err.retry_after_ms(), Some(40));
+        assert!(err.message().contains("write"));
+    }
+
+    #[test]
+    fn test_too_busy() {
+        let err = ApiError::too_busy(Rejected {
+            reason: RejectionReason::TimedOut,
+            retry_after_ms: 25,
+            tenant_id: None,
+        });
+        assert_eq!(err.code(), "AERO_TOO_BUSY");
+        assert_eq!(err.retry_after_ms(), Some(25));
+        assert!(!err.is_fatal());
+    }
+
+    #[test]
+    fn test_quota_exceeded_passes_through() {
+        let err = ApiError::from_control_plane_error(
+            crate::control_plane::ControlPlaneError::QuotaExceeded {
+                tenant_id: "acme".to_string(),
+                resource: "documents".to_string(),
+                used: 2,
+                requested: 1,
+                limit: 2,
+            },
+        );
+        assert_eq!(err.code(), "QUOTA_EXCEEDED");
+        assert!(err.message().contains("documents"));
+    }
 }
@@ -3,34 +3,65 @@
 //! Orchestrates all subsystems behind a single global mutex.
 //! Enforces strict request handling flow.
 
-use std::sync::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::ops::Bound;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use chrono::{DateTime, Utc};
 use serde_json::{json, Value};
+use uuid::Uuid;
 
-use crate::index::{DocumentInfo, IndexManager};
+use crate::crash_point::{maybe_crash, points};
+use crate::dangerous_ops::DangerousOperation;
+use crate::dx::api::control_plane::{ConfirmationFlow, ConfirmationResult};
+use crate::executor::{
+    project, AggregateSpec, Deadline, HashAggregator, PredicateFilter, SortBuffer,
+};
+use crate::index::{DocumentInfo, IndexManager, IndexOptions};
 use crate::planner::{
-    FilterOp, IndexMetadata, Predicate, Query, QueryPlan, QueryPlanner, ScanType, SortSpec,
+    range_bounds, FilterExpr, FilterOp, FilterPushdown, IndexMetadata, IndexStatistics, Predicate,
+    Projection, Query, QueryPlan, QueryPlanner, ScanType, SortDirection, SortSpec, SortStrategy,
+    Statistics,
+};
+use crate::observability::{
+    in_request_sync, AuditAction, AuditLog, AuditOutcome, AuditRecord, OperationLog,
+    OperationLogEntry, OperationType,
 };
-use crate::schema::{SchemaLoader, SchemaValidator};
-use crate::storage::{StoragePayload, StorageReader, StorageWriter};
+use crate::schema::{
+    is_soft_deleted, Schema, SchemaError, SchemaLoader, SchemaValidator, TtlConfig,
+    DELETED_AT_FIELD, EXPIRES_AT_FIELD,
+};
+use crate::storage::{DocumentRecord, StoragePayload, StorageReader, StorageWriter};
 use crate::wal::{RecordType, WalPayload, WalWriter};
 
 use crate::resource_limits::ResourceManager;
 use crate::backpressure::BackpressureManager;
 use crate::admission_control::AdmissionController;
 use crate::query_limits::QueryLimitsConfig;
+use crate::replication::{ReplicaGate, SyncReplication};
+use crate::control_plane::{
+    QuotaAdmission, QuotaOperation, ResultSizeClass, TenantQuotas, TenantRegistry,
+};
 
+use super::bindings::CollectionBindings;
 use super::errors::{ApiError, ApiResult};
-use super::request::{DeleteRequest, InsertRequest, QueryRequest, Request, UpdateRequest};
+use super::request::{
+    AggregateRequest, AnalyzeRequest, CopyCollectionRequest, DeleteRequest,
+    DropCollectionRequest, InsertManyRequest, InsertRequest, QueryRequest,
+    RenameCollectionRequest, Request, TransactionRequest, UpdateRequest,
+};
 use super::response::Response;
+use super::transaction::{BufferedWrite, ClosedTransaction, TransactionRegistry};
 
 /// Subsystem references for API handler
 pub struct Subsystems<'a> {
-    pub schema_loader: &'a SchemaLoader,
+    pub schema_loader: &'a mut SchemaLoader,
     pub wal_writer: &'a mut WalWriter,
     pub storage_writer: &'a mut StorageWriter,
     pub storage_reader: &'a mut StorageReader,
     pub index_manager: &'a mut IndexManager,
+    pub statistics: &'a mut Statistics,
     
     // Hardening components
     pub resource_manager: &'a ResourceManager,
@@ -39,6 +70,101 @@ pub struct Subsystems<'a> {
     pub query_limits: &'a QueryLimitsConfig,
 }
 
+/// Source of the current time, for document expiry
+pub type Clock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+/// Outcome of one expiry sweep
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExpirySweep {
+    /// Index entries at or before the current time that were examined
+    pub scanned: usize,
+    /// Expired documents removed
+    pub removed: usize,
+}
+
+/// A transaction's write, resolved and serialized for commit
+struct PreparedWrite {
+    record_type: RecordType,
+    doc_id: String,
+    schema_id: String,
+    schema_version: String,
+    /// New body, or the last body of a deleted document
+    body: Value,
+    /// Serialized new body (empty for a delete)
+    bytes: Vec<u8>,
+    /// Serialized size of a deleted or soft-deleted document
+    old_size: u64,
+}
+
+impl PreparedWrite {
+    /// An insert or update
+    fn put(
+        record_type: RecordType,
+        doc_id: String,
+        schema_id: String,
+        schema_version: String,
+        body: Value,
+    ) -> ApiResult<Self> {
+        let bytes = serde_json::to_vec(&body).map_err(|e| {
+            ApiError::invalid_request(format!("Failed to serialize document: {}", e))
+        })?;
+        Ok(Self {
+            record_type,
+            doc_id,
+            schema_id,
+            schema_version,
+            body,
+            bytes,
+            old_size: 0,
+        })
+    }
+
+    /// A delete of a document whose current body is `old_body`
+    fn delete(doc_id: String, schema_id: String, old_body: Value) -> Self {
+        let old_size = serde_json::to_vec(&old_body).map_or(0, |bytes| bytes.len() as u64);
+        Self {
+            record_type: RecordType::Delete,
+            doc_id,
+            schema_id,
+            schema_version: String::new(),
+            body: old_body,
+            bytes: Vec::new(),
+            old_size,
+        }
+    }
+
+    /// A soft delete: `old_body` with `_deleted_at` set, written as an update
+    fn soft_delete(
+        doc_id: String,
+        schema_id: String,
+        schema_version: String,
+        old_body: Value,
+    ) -> ApiResult<Self> {
+        let old_size = serde_json::to_vec(&old_body).map_or(0, |bytes| bytes.len() as u64);
+        let mut body = old_body;
+        if let Some(fields) = body.as_object_mut() {
+            fields.insert(DELETED_AT_FIELD.to_string(), json!(chrono::Utc::now().to_rfc3339()));
+        }
+        let mut write = Self::put(RecordType::Update, doc_id, schema_id, schema_version, body)?;
+        write.old_size = old_size;
+        Ok(write)
+    }
+
+    fn wal_payload(&self, collection: &str) -> WalPayload {
+        if self.record_type == RecordType::Delete {
+            WalPayload::tombstone(collection, &self.doc_id, &self.schema_id, "")
+        } else {
+            WalPayload::new(
+                collection,
+                &self.doc_id,
+                &self.schema_id,
+                &self.schema_version,
+                self.bytes.clone(),
+            )
+        }
+    }
+}
+
 /// API Handler with global execution lock
 pub struct ApiHandler {
     /// Global mutex for serialized execution
@@ -46,6 +172,36 @@ pub struct ApiHandler {
 
     /// Collection name (single collection in Phase 0)
     collection: String,
+
+    /// Which operations this node serves (all, unless a replica)
+    replica_gate: ReplicaGate,
+
+    /// Replica acknowledgments a write waits for (none if unset)
+    sync_replication: Option<Arc<SyncReplication>>,
+
+    /// Quotas and metering for requests made as a tenant
+    tenant_quotas: Option<Arc<TenantQuotas>>,
+
+    /// Tenant statuses, fencing suspended and deleted tenants
+    tenant_registry: Option<Arc<TenantRegistry>>,
+
+    /// Open transactions and their buffered writes
+    transactions: TransactionRegistry,
+
+    /// Current time, against which documents expire
+    clock: Clock,
+
+    /// Where requests and expiry sweeps are recorded
+    operation_log: Option<Arc<OperationLog>>,
+
+    /// Confirmation tokens issued for dangerous collection operations
+    confirmations: Mutex<ConfirmationFlow>,
+
+    /// Where issuing and consuming confirmation tokens is audited
+    audit_log: Option<Arc<dyn AuditLog>>,
+
+    /// Bindings moved by a rename and copied by a copy of a collection
+    bindings: Option<Arc<dyn CollectionBindings>>,
 }
 
 impl ApiHandler {
@@ -54,32 +210,328 @@ impl ApiHandler {
         Self {
             lock: Mutex::new(()),
             collection: collection.into(),
+            replica_gate: ReplicaGate::default(),
+            sync_replication: None,
+            tenant_quotas: None,
+            tenant_registry: None,
+            transactions: TransactionRegistry::default(),
+            clock: Arc::new(Utc::now),
+            operation_log: None,
+            confirmations: Mutex::new(ConfirmationFlow::new()),
+            audit_log: None,
+            bindings: None,
         }
     }
 
+    /// Expire transactions left idle longer than `timeout`
+    pub fn with_transaction_timeout(mut self, timeout: Duration) -> Self {
+        self.transactions =
+            TransactionRegistry::new(timeout).with_max_bytes(self.transactions.max_bytes());
+        self
+    }
+
+    /// Refuse writes that would take a transaction's buffer past `max_bytes`
+    pub fn with_max_transaction_bytes(mut self, max_bytes: u64) -> Self {
+        self.transactions =
+            TransactionRegistry::new(self.transactions.timeout()).with_max_bytes(max_bytes);
+        self
+    }
+
+    /// Serve as a replica: reads within their staleness bound, no writes
+    pub fn with_replica_gate(mut self, replica_gate: ReplicaGate) -> Self {
+        self.replica_gate = replica_gate;
+        self
+    }
+
+    /// Answer a write only once `sync_replication` has its quorum of
+    /// replica acknowledgments
+    pub fn with_sync_replication(mut self, sync_replication: Arc<SyncReplication>) -> Self {
+        self.sync_replication = Some(sync_replication);
+        self
+    }
+
+    /// Enforce tenant quotas on requests made with `handle_as_tenant`
+    pub fn with_tenant_quotas(mut self, tenant_quotas: Arc<TenantQuotas>) -> Self {
+        self.tenant_quotas = Some(tenant_quotas);
+        self
+    }
+
+    /// Refuse requests made with `handle_as_tenant` that the tenant's status
+    /// in `tenant_registry` fences
+    pub fn with_tenant_registry(mut self, tenant_registry: Arc<TenantRegistry>) -> Self {
+        self.tenant_registry = Some(tenant_registry);
+        self
+    }
+
+    /// Expire documents against this clock instead of the system time
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Expire confirmation tokens for dangerous operations `ttl` after they
+    /// are issued
+    pub fn with_confirmation_ttl(mut self, ttl: Duration) -> Self {
+        self.confirmations = Mutex::new(ConfirmationFlow::new().with_max_token_age(ttl));
+        self
+    }
+
+    /// Move and copy `bindings` with the collections renamed and copied
+    pub fn with_collection_bindings(mut self, bindings: Arc<dyn CollectionBindings>) -> Self {
+        self.bindings = Some(bindings);
+        self
+    }
+
+    /// Audit each confirmation token issued or consumed in `audit_log`
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Record each dispatched request and expiry sweep in this operation log
+    pub fn with_operation_log(mut self, operation_log: Arc<OperationLog>) -> Self {
+        self.operation_log = Some(operation_log);
+        self
+    }
+
     /// Handle a raw JSON request string
     ///
-    /// Acquires global lock at entry, releases on return.
+    /// Takes an admission permit for the request's class, then a slot under
+    /// the global operation limit, then the global lock; all are released
+    /// on return.
     pub fn handle(&self, json_request: &str, subsystems: &mut Subsystems<'_>) -> Response {
-        // Acquire global lock at request entry
-        let _guard = self.lock.lock().expect("Lock poisoned");
+        self.handle_request(None, json_request, subsystems)
+    }
+
+    /// Handle a raw JSON request string as request `request_id`
+    ///
+    /// Operation log entries and log lines produced while handling it carry
+    /// the id, and an error response names it.
+    pub fn handle_with_request_id(
+        &self,
+        request_id: Uuid,
+        json_request: &str,
+        subsystems: &mut Subsystems<'_>,
+    ) -> Response {
+        in_request_sync(request_id, || self.handle(json_request, subsystems))
+            .with_request_id(request_id)
+    }
+
+    /// Handle a raw JSON request string on behalf of a resolved tenant
+    ///
+    /// Writes, queries and aggregates are checked against the tenant's
+    /// quotas under the global lock and metered only if they succeed. A
+    /// hard quota refuses the request with `QUOTA_EXCEEDED`. Before any
+    /// other admission, the request takes a slot under the tenant's
+    /// `max_concurrent_operations` quota; a tenant over it is refused with
+    /// `AERO_TOO_BUSY` and the rejection metered. With a tenant registry,
+    /// a suspended tenant's writes (and reads, if its suspension fences
+    /// them) are refused first with `TENANT_SUSPENDED`. Without tenant
+    /// quotas or a registry this is `handle`.
+    pub fn handle_as_tenant(
+        &self,
+        tenant_id: Uuid,
+        json_request: &str,
+        subsystems: &mut Subsystems<'_>,
+    ) -> Response {
+        self.handle_request(Some(tenant_id), json_request, subsystems)
+    }
 
+    fn handle_request(
+        &self,
+        tenant_id: Option<Uuid>,
+        json_request: &str,
+        subsystems: &mut Subsystems<'_>,
+    ) -> Response {
         // Parse request
         let request = match Request::parse(json_request) {
             Ok(r) => r,
             Err(e) => return Response::error(&e),
         };
 
+        // Answered without admission or the lock, so an overloaded node
+        // can always be inspected
+        if let Request::Admission = request {
+            return Response::success(json!(subsystems.admission_controller.stats()));
+        }
+
+        // Soft-deleted documents are for the service role, not tenants
+        if tenant_id.is_some() && request.include_deleted() {
+            return Response::error(&ApiError::invalid_request(
+                "include_deleted is restricted to the service role",
+            ));
+        }
+
+        // As are renaming, copying and dropping a collection
+        if tenant_id.is_some() {
+            let op = match request {
+                Request::RenameCollection(_) => Some("rename_collection"),
+                Request::CopyCollection(_) => Some("copy_collection"),
+                Request::DropCollection(_) => Some("drop_collection"),
+                _ => None,
+            };
+            if let Some(op) = op {
+                return Response::error(&ApiError::invalid_request(format!(
+                    "{} is restricted to the service role",
+                    op
+                )));
+            }
+        }
+
+        // A suspended or deleted tenant is fenced before taking any slot
+        if let (Some(tenant_id), Some(registry)) = (tenant_id, &self.tenant_registry) {
+            if let Err(e) = registry.check_access(tenant_id, request.is_write()) {
+                return Response::error(&ApiError::from_control_plane_error(e));
+            }
+        }
+
+        // A tenant first waits under its own limit, apart from other
+        // tenants, so its backlog never holds their permits or slots
+        let _tenant_slot = match (tenant_id, &self.tenant_quotas, request.admission_class()) {
+            (Some(tenant_id), Some(quotas), Some(_)) => {
+                let limit = quotas.quotas(tenant_id).max_concurrent_operations;
+                match subsystems.admission_controller.try_admit_tenant(tenant_id, limit) {
+                    Ok(guard) => Some(guard),
+                    Err(rejected) => {
+                        quotas.record_rejection(tenant_id);
+                        return Response::error(&ApiError::too_busy(rejected));
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        // Wait for a permit of the request's class (diagnostics take none)
+        let permit = match request.admission_class() {
+            Some(class) => match subsystems.admission_controller.acquire(class) {
+                Ok(permit) => Some(permit),
+                Err(rejection) => {
+                    return Response::error(&ApiError::from_admission_rejection(rejection))
+                }
+            },
+            None => None,
+        };
+
+        // Then a slot under the limit shared by every class
+        let _slot = match permit {
+            Some(_) => match subsystems.admission_controller.try_admit() {
+                Ok(guard) => Some(guard),
+                Err(rejected) => return Response::error(&ApiError::too_busy(rejected)),
+            },
+            None => None,
+        };
+
+        // Acquire global lock
+        let _guard = self.lock.lock().expect("Lock poisoned");
+
+        // A replica refuses writes and reads staler than max_staleness_ms
+        let admitted = if request.is_write() {
+            self.replica_gate.check_write()
+        } else {
+            self.replica_gate.check_read(request.max_staleness_ms())
+        };
+        if let Err(e) = admitted {
+            return Response::error(&ApiError::from_replication_error(e));
+        }
+
+        // Deadline from the request's timeout_ms or the configured default
+        let deadline = match subsystems.query_limits.resolve_timeout_ms(request.timeout_ms()) {
+            Ok(timeout_ms) => Deadline::after_ms(timeout_ms),
+            Err(reason) => return Response::error(&ApiError::invalid_request(reason)),
+        };
+
+        // Tenant quotas are checked before the operation runs
+        let mut admission = match (tenant_id, &self.tenant_quotas) {
+            (Some(tenant_id), Some(quotas)) => {
+                match Self::check_quota(quotas, tenant_id, &request) {
+                    Ok(admission) => admission,
+                    Err(e) => return Response::error(&e),
+                }
+            }
+            _ => None,
+        };
+        let storage_before = subsystems.storage_writer.current_offset();
+        let wal_before = subsystems.wal_writer.last_sequence_number();
+        let insert_many = matches!(request, Request::InsertMany(_));
+        let purge = matches!(request, Request::Purge(_));
+        let started = Instant::now();
+        let entry = self.operation_log.as_ref().map(|log| {
+            let entry = OperationLogEntry::builder(request.operation_type())
+                .slow_threshold_ms(log.slow_threshold_ms());
+            match request.collection() {
+                Some(collection) => entry.collection(collection),
+                None => entry,
+            }
+        });
+
         // Dispatch to appropriate handler
         let result = match request {
-            Request::Insert(r) => self.handle_insert(r, subsystems),
-            Request::Update(r) => self.handle_update(r, subsystems),
-            Request::Delete(r) => self.handle_delete(r, subsystems),
-            Request::Query(r) => self.handle_query(r, subsystems),
+            Request::Insert(r) => match r.tx_id {
+                Some(tx_id) => self.buffer_write(tx_id, BufferedWrite::Insert(r), subsystems),
+                None => self.handle_insert(r, &deadline, subsystems),
+            },
+            Request::InsertMany(r) => self.handle_insert_many(r, &deadline, subsystems),
+            Request::Update(r) => match r.tx_id {
+                Some(tx_id) => self.buffer_write(tx_id, BufferedWrite::Update(r), subsystems),
+                None => self.handle_update(r, &deadline, subsystems),
+            },
+            Request::Delete(r) => match r.tx_id {
+                Some(tx_id) => self.buffer_write(tx_id, BufferedWrite::Delete(r), subsystems),
+                None => self.handle_delete(r, &deadline, subsystems),
+            },
+            Request::Purge(r) => self.handle_purge(r, &deadline, subsystems),
+            Request::Begin => {
+                let snapshot = subsystems.storage_writer.current_offset();
+                let memory = Arc::clone(subsystems.resource_manager.memory_tracker());
+                Ok(json!({"tx_id": self.transactions.begin(snapshot, memory).to_string()}))
+            }
+            Request::Commit(r) => self.handle_commit(r, &deadline, subsystems),
+            Request::Rollback(r) => self.handle_rollback(r),
+            Request::Query(r) => self.handle_query(r, &deadline, subsystems),
             Request::Explain(r) => self.handle_explain(r, subsystems),
+            Request::Analyze(r) => self.handle_analyze(r, subsystems),
+            Request::Aggregate(r) => self.handle_aggregate(r, &deadline, subsystems),
+            Request::RenameCollection(r) => self.handle_rename_collection(r, &deadline, subsystems),
+            Request::CopyCollection(r) => self.handle_copy_collection(r, &deadline, subsystems),
+            Request::DropCollection(r) => self.handle_drop_collection(r, &deadline, subsystems),
+            Request::Admission => unreachable!("answered before admission"),
+        };
+
+        // Meter the tenant only for an operation that succeeded, still
+        // under the lock
+        if let (Ok(data), Some(admission), Some(quotas)) = (&result, &mut admission, &self.tenant_quotas) {
+            // insert_many adds only the documents it did not reject
+            if let (true, Some(inserted)) = (insert_many, data["inserted"].as_i64()) {
+                admission.operation = QuotaOperation::Write { documents: inserted };
+            }
+            // A purge removes a document only if it was not soft-deleted
+            if purge && data["was_deleted"] == json!(false) {
+                admission.operation = QuotaOperation::Write { documents: -1 };
+            }
+            let grown = subsystems.storage_writer.current_offset().saturating_sub(storage_before);
+            quotas.record(admission, grown as i64);
+        }
+
+        // A write that reached the WAL waits for its replicas, outside the
+        // lock so that other requests proceed
+        let wal_after = subsystems.wal_writer.last_sequence_number();
+        drop(_guard);
+        let result = match (result, &self.sync_replication) {
+            (Ok(data), Some(sync)) if wal_after > wal_before => sync
+                .wait_for(wal_after)
+                .map(|()| data)
+                .map_err(ApiError::from_replication_error),
+            (result, _) => result,
         };
 
-        // Lock released when _guard drops
+        if let (Some(log), Some(entry)) = (&self.operation_log, entry) {
+            let entry = entry.duration(started.elapsed());
+            log.log(match &result {
+                Ok(_) => entry.build(),
+                Err(e) => entry.error(e.code(), e.message()).build(),
+            });
+        }
+
         match result {
             Ok(data) => Response::success(data),
             Err(e) => Response::error(&e),
@@ -90,11 +542,17 @@ impl ApiHandler {
     ///
     /// Flow:
     /// 1. Validate schema
-    /// 2. Build write intent
-    /// 3. Append WAL record
-    /// 4. Apply to Storage
-    /// 5. Update Index
-    fn handle_insert(&self, req: InsertRequest, sys: &mut Subsystems<'_>) -> ApiResult<Value> {
+    /// 2. Check unique indexes
+    /// 3. Build write intent
+    /// 4. Append WAL record
+    /// 5. Apply to Storage
+    /// 6. Update Index
+    fn handle_insert(
+        &self,
+        mut req: InsertRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
         // Hardening: Resource checks
         if !sys.resource_manager.writes_allowed() {
             return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
@@ -109,6 +567,8 @@ impl ApiHandler {
         validator
             .validate_document(&req.schema_id, &req.schema_version, &req.document)
             .map_err(ApiError::from_schema_error)?;
+        let (schema_id, schema_version) = (&req.schema_id, &req.schema_version);
+        self.stamp_expiry(sys.schema_loader, schema_id, schema_version, &mut req.document, None);
 
         // Extract document ID
         let doc_id = req
@@ -118,14 +578,20 @@ impl ApiHandler {
             .ok_or_else(|| ApiError::invalid_request("Document missing _id"))?
             .to_string();
 
-        // 2. Build write intent
+        // 2. Check unique indexes (before anything is made durable)
+        sys.index_manager
+            .check_unique(&doc_id, &req.document)
+            .map_err(ApiError::from_index_error)?;
+
+        // 3. Build write intent
         let body_bytes = serde_json::to_vec(&req.document).map_err(|e| {
             ApiError::invalid_request(format!("Failed to serialize document: {}", e))
         })?;
+        let body_size = body_bytes.len() as u64;
 
         // Hardening: Check disk space
         sys.resource_manager
-            .check_disk_space(body_bytes.len() as u64 + 1024)
+            .check_disk_space(body_size + 1024)
             .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
 
         let wal_payload = WalPayload::new(
@@ -136,12 +602,14 @@ impl ApiHandler {
             body_bytes.clone(),
         );
 
-        // 3. Append WAL record
+        // 4. Append WAL record (last point at which the request may time
+        // out: nothing is durable yet)
+        Self::check_write_deadline(deadline)?;
         sys.wal_writer
             .append(RecordType::Insert, wal_payload)
             .map_err(ApiError::from_wal_error)?;
 
-        // 4. Apply to Storage
+        // 5. Apply to Storage
         let storage_payload = StoragePayload::new(
             &self.collection,
             &doc_id,
@@ -154,7 +622,7 @@ impl ApiHandler {
             .write(&storage_payload)
             .map_err(ApiError::from_storage_error)?;
 
-        // 5. Update Index
+        // 6. Update Index and statistics
         let doc_info = DocumentInfo {
             document_id: doc_id.clone(),
             schema_id: req.schema_id,
@@ -164,20 +632,154 @@ impl ApiHandler {
             offset,
         };
         sys.index_manager.apply_write(&doc_info);
+        let fields = Self::statistics_fields(sys.index_manager);
+        for scope in self.statistics_scopes(&doc_info.schema_id) {
+            sys.statistics.record_insert(scope, &doc_info.body, body_size, &fields);
+        }
 
         Ok(json!({"inserted": doc_id}))
     }
 
+    /// Handle insert_many: insert a batch of documents with one WAL append
+    ///
+    /// Flow:
+    /// 1. Validate every document against the schema and take its `_id`
+    /// 2. Check unique indexes, each document against committed documents
+    ///    and the batch's earlier accepted documents
+    /// 3. Without `continue_on_error`, refuse the whole batch at the first
+    ///    rejected document; otherwise set rejected documents aside
+    /// 4. Append every accepted document's WAL record with a single append
+    /// 5. Apply to Storage
+    /// 6. Update Index
+    ///
+    /// Returns the `inserted` and `failed` counts and one result per
+    /// document, in order: its `_id`, or its error.
+    fn handle_insert_many(
+        &self,
+        req: InsertManyRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        // Hardening: Resource checks
+        if !sys.resource_manager.writes_allowed() {
+            return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
+        }
+        if !sys.admission_controller.try_acquire_write() {
+            return Err(ApiError::too_many_requests("Write rate limit exceeded"));
+        }
+
+        // 1. Validate every document
+        let validator = SchemaValidator::new(sys.schema_loader);
+        let mut ids = HashSet::new();
+        let mut outcomes: Vec<ApiResult<PreparedWrite>> = req
+            .documents
+            .into_iter()
+            .map(|mut document| {
+                validator
+                    .validate_document(&req.schema_id, &req.schema_version, &document)
+                    .map_err(ApiError::from_schema_error)?;
+                let (schema_id, schema_version) = (&req.schema_id, &req.schema_version);
+                self.stamp_expiry(sys.schema_loader, schema_id, schema_version, &mut document, None);
+                let doc_id = document
+                    .get("_id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| ApiError::invalid_request("Document missing _id"))?
+                    .to_string();
+                if !ids.insert(doc_id.clone()) {
+                    return Err(ApiError::invalid_request(format!("Duplicate _id in batch: {}", doc_id)));
+                }
+                PreparedWrite::put(
+                    RecordType::Insert,
+                    doc_id,
+                    req.schema_id.clone(),
+                    req.schema_version.clone(),
+                    document,
+                )
+            })
+            .collect();
+
+        // 2. Check unique indexes for the documents still accepted
+        let (positions, writes): (Vec<usize>, Vec<(&str, &Value)>) = outcomes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, outcome)| {
+                let write = outcome.as_ref().ok()?;
+                Some((i, (write.doc_id.as_str(), &write.body)))
+            })
+            .unzip();
+        let checked = sys.index_manager.check_unique_each(&writes);
+        for (i, result) in positions.into_iter().zip(checked) {
+            if let Err(e) = result {
+                outcomes[i] = Err(ApiError::from_index_error(e));
+            }
+        }
+
+        // 3. Fail fast, or set rejected documents aside
+        let mut results = Vec::with_capacity(outcomes.len());
+        let mut prepared = Vec::new();
+        for (i, outcome) in outcomes.into_iter().enumerate() {
+            match outcome {
+                Ok(write) => {
+                    results.push(json!({"index": i, "inserted": write.doc_id}));
+                    prepared.push(write);
+                }
+                Err(e) if !req.continue_on_error => return Err(e.for_document(i)),
+                Err(e) => results.push(json!({
+                    "index": i,
+                    "error": {"code": e.code(), "message": e.message()},
+                })),
+            }
+        }
+        let inserted = prepared.len();
+
+        if !prepared.is_empty() {
+            // Hardening: Check disk space
+            let bytes: u64 = prepared.iter().map(|w| w.bytes.len() as u64 + 1024).sum();
+            sys.resource_manager
+                .check_disk_space(bytes)
+                .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
+
+            // 5. Append every WAL record at once (last point at which the
+            // request may time out: nothing is durable yet)
+            let records = prepared
+                .iter()
+                .map(|w| (w.record_type, w.wal_payload(&self.collection)))
+                .collect();
+            Self::check_write_deadline(deadline)?;
+            sys.wal_writer
+                .append_batch(records)
+                .map_err(ApiError::from_wal_error)?;
+
+            // 5-6. Apply to Storage, then Index and statistics
+            let fields = Self::statistics_fields(sys.index_manager);
+            for write in prepared {
+                self.apply_put(write, &fields, sys)?;
+            }
+        }
+
+        Ok(json!({
+            "inserted": inserted,
+            "failed": results.len() - inserted,
+            "results": results,
+        }))
+    }
+
     /// Handle update operation
     ///
     /// Flow:
     /// 1. Validate schema
     /// 2. Check document exists
-    /// 3. Build write intent
-    /// 4. Append WAL record
-    /// 5. Apply to Storage
-    /// 6. Update Index
-    fn handle_update(&self, req: UpdateRequest, sys: &mut Subsystems<'_>) -> ApiResult<Value> {
+    /// 3. Check unique indexes
+    /// 4. Build write intent
+    /// 5. Append WAL record
+    /// 6. Apply to Storage
+    /// 7. Update Index
+    fn handle_update(
+        &self,
+        mut req: UpdateRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
         // Hardening: Resource checks
         if !sys.resource_manager.writes_allowed() {
              return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
@@ -201,16 +803,40 @@ impl ApiHandler {
             .validate_update(&req.schema_id, &req.schema_version, &doc_id, &req.document)
             .map_err(ApiError::from_schema_error)?;
 
-        // 2. Check document exists (via index)
-        let offsets = sys.index_manager.lookup_pk(&doc_id);
-        if offsets.is_empty() {
+        // 2. Check document exists (and, where deletes are soft or
+        // documents expire, is not soft-deleted or expired)
+        let reads_body = Self::soft_deletes(sys.schema_loader, &req.schema_id, &req.schema_version)
+            || Self::ttl(sys.schema_loader, &req.schema_id, &req.schema_version).is_some();
+        let exists = if reads_body {
+            match self.committed_body(&doc_id, sys)? {
+                Some((_, previous)) => {
+                    self.stamp_expiry(
+                        sys.schema_loader,
+                        &req.schema_id,
+                        &req.schema_version,
+                        &mut req.document,
+                        Some(&previous),
+                    );
+                    true
+                }
+                None => false,
+            }
+        } else {
+            !sys.index_manager.lookup_pk(&doc_id).is_empty()
+        };
+        if !exists {
             return Err(ApiError::invalid_request(format!(
                 "Document not found: {}",
                 doc_id
             )));
         }
 
-        // 3. Build write intent
+        // 3. Check unique indexes (before anything is made durable)
+        sys.index_manager
+            .check_unique(&doc_id, &req.document)
+            .map_err(ApiError::from_index_error)?;
+
+        // 4. Build write intent
         let body_bytes = serde_json::to_vec(&req.document).map_err(|e| {
             ApiError::invalid_request(format!("Failed to serialize document: {}", e))
         })?;
@@ -228,12 +854,14 @@ impl ApiHandler {
             body_bytes.clone(),
         );
 
-        // 4. Append WAL record
+        // 5. Append WAL record (last point at which the request may time
+        // out: nothing is durable yet)
+        Self::check_write_deadline(deadline)?;
         sys.wal_writer
             .append(RecordType::Update, wal_payload)
             .map_err(ApiError::from_wal_error)?;
 
-        // 5. Apply to Storage (overwrite)
+        // 6. Apply to Storage (overwrite)
         let storage_payload = StoragePayload::new(
             &self.collection,
             &doc_id,
@@ -246,7 +874,7 @@ impl ApiHandler {
             .write(&storage_payload)
             .map_err(ApiError::from_storage_error)?;
 
-        // 6. Update Index
+        // 7. Update Index and statistics
         let doc_info = DocumentInfo {
             document_id: doc_id.clone(),
             schema_id: req.schema_id,
@@ -256,6 +884,10 @@ impl ApiHandler {
             offset,
         };
         sys.index_manager.apply_write(&doc_info);
+        let fields = Self::statistics_fields(sys.index_manager);
+        for scope in self.statistics_scopes(&doc_info.schema_id) {
+            sys.statistics.record_update(scope, &doc_info.body, &fields);
+        }
 
         Ok(json!({"updated": doc_id}))
     }
@@ -263,524 +895,4473 @@ impl ApiHandler {
     /// Handle delete operation
     ///
     /// Flow:
-    /// 1. Check document exists
+    /// 1. Check document exists and is not soft-deleted
     /// 2. Append WAL record
-    /// 3. Apply tombstone to Storage
+    /// 3. Apply tombstone to Storage, or for a `soft_delete` schema write
+    ///    the document with `_deleted_at` set
     /// 4. Update Index
-    fn handle_delete(&self, req: DeleteRequest, sys: &mut Subsystems<'_>) -> ApiResult<Value> {
+    fn handle_delete(
+        &self,
+        req: DeleteRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        // 1. Check document exists (via index)
+        let (schema_version, old_body) = self
+            .committed_body(&req.document_id, sys)?
+            .ok_or_else(|| {
+                ApiError::invalid_request(format!("Document not found: {}", req.document_id))
+            })?;
+
+        if !Self::soft_deletes(sys.schema_loader, &req.schema_id, &schema_version) {
+            self.remove(&req, old_body, false, deadline, sys)?;
+            return Ok(json!({"deleted": req.document_id}));
+        }
+
         // Hardening: Resource checks
         if !sys.resource_manager.writes_allowed() {
-             return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
+            return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
         }
         if !sys.admission_controller.try_acquire_write() {
-             return Err(ApiError::too_many_requests("Write rate limit exceeded"));
+            return Err(ApiError::too_many_requests("Write rate limit exceeded"));
         }
-        
-        // Hardening: Check disk space (minimal for tombstone)
+
+        let write = PreparedWrite::soft_delete(
+            req.document_id.clone(),
+            req.schema_id,
+            schema_version,
+            old_body,
+        )?;
+
+        // Hardening: Check disk space
         sys.resource_manager
-            .check_disk_space(1024)
+            .check_disk_space(write.bytes.len() as u64 + 1024)
             .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
 
-        // 1. Check document exists (via index)
+        // 2. Append WAL record (last point at which the request may time
+        // out: nothing is durable yet)
+        Self::check_write_deadline(deadline)?;
+        sys.wal_writer
+            .append(write.record_type, write.wal_payload(&self.collection))
+            .map_err(ApiError::from_wal_error)?;
+
+        // 3-4. Apply to Storage, then Index and statistics
+        let fields = Self::statistics_fields(sys.index_manager);
+        self.apply_put(write, &fields, sys)?;
+
+        Ok(json!({"deleted": req.document_id}))
+    }
+
+    /// Handle purge: remove a document outright, soft-deleted or not
+    ///
+    /// Reports `was_deleted`: whether the document had been soft-deleted.
+    fn handle_purge(
+        &self,
+        req: DeleteRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
         let offsets = sys.index_manager.lookup_pk(&req.document_id);
-        if offsets.is_empty() {
+        let Some(&offset) = offsets.last() else {
             return Err(ApiError::invalid_request(format!(
                 "Document not found: {}",
                 req.document_id
             )));
-        }
-
-        // Get the old document body for index removal
-        let old_offset = offsets[offsets.len() - 1];
-        let old_doc = sys
+        };
+        let record = sys
             .storage_reader
-            .read_at(old_offset)
+            .read_at(offset)
             .map_err(ApiError::from_storage_error)?;
+        let old_body: Value = serde_json::from_slice(&record.document_body).unwrap_or(json!({}));
+        let was_deleted = is_soft_deleted(&old_body);
 
-        let old_body: Value = serde_json::from_slice(&old_doc.document_body).unwrap_or(json!({}));
+        self.remove(&req, old_body, true, deadline, sys)?;
 
-        // 2. Append WAL record
-        let wal_payload = WalPayload::tombstone(
-            &self.collection,
-            &req.document_id,
-            &req.schema_id,
-            "", // version empty for delete
-        );
+        Ok(json!({"purged": req.document_id, "was_deleted": was_deleted}))
+    }
+
+    /// Remove a document whose current body is `old_body`
+    ///
+    /// Flow:
+    /// 1. Append WAL record (a purge's carries `old_body`)
+    /// 2. Apply tombstone to Storage
+    /// 3. Update Index
+    fn remove(
+        &self,
+        req: &DeleteRequest,
+        old_body: Value,
+        purge: bool,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<()> {
+        // Hardening: Resource checks
+        if !sys.resource_manager.writes_allowed() {
+             return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
+        }
+        if !sys.admission_controller.try_acquire_write() {
+             return Err(ApiError::too_many_requests("Write rate limit exceeded"));
+        }
+
+        let old_bytes = serde_json::to_vec(&old_body).unwrap_or_default();
+
+        // Hardening: Check disk space (minimal for tombstone)
+        let wal_bytes = if purge { old_bytes.len() as u64 } else { 0 };
+        sys.resource_manager
+            .check_disk_space(wal_bytes + 1024)
+            .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
+
+        // 1. Append WAL record
+        let old_size = old_bytes.len() as u64;
+        let wal_payload = if purge {
+            WalPayload::purge(&self.collection, &req.document_id, &req.schema_id, old_bytes)
+        } else {
+            WalPayload::tombstone(
+                &self.collection,
+                &req.document_id,
+                &req.schema_id,
+                "", // version empty for delete
+            )
+        };
 
+        // Last point at which the request may time out: nothing is
+        // durable yet
+        Self::check_write_deadline(deadline)?;
         sys.wal_writer
             .append(RecordType::Delete, wal_payload)
             .map_err(ApiError::from_wal_error)?;
 
-        // 3. Apply tombstone to Storage
+        // 2. Apply tombstone to Storage
         sys.storage_writer
             .write_tombstone(&self.collection, &req.document_id, &req.schema_id, "")
             .map_err(ApiError::from_storage_error)?;
 
-        // 4. Update Index
+        // 3. Update Index and statistics (a soft delete already counted
+        // the document as deleted)
         sys.index_manager.apply_delete(&req.document_id, &old_body);
+        if !is_soft_deleted(&old_body) {
+            let fields = Self::statistics_fields(sys.index_manager);
+            for scope in self.statistics_scopes(&req.schema_id) {
+                sys.statistics.record_delete(scope, &old_body, old_size, &fields);
+            }
+        }
 
-        Ok(json!({"deleted": req.document_id}))
+        Ok(())
     }
 
-    /// Handle query operation
-    ///
-    /// Flow:
-    /// 1. Parse query
-    /// 2. Call Planner
-    /// 3. Call Executor (simplified: use index + storage)
-    /// 4. Return results
-    fn handle_query(&self, req: QueryRequest, sys: &mut Subsystems<'_>) -> ApiResult<Value> {
-        // Hardening: Admission control for queries
-        let _guard = sys.admission_controller.acquire_query_guard()
-            .ok_or_else(|| ApiError::too_many_requests("Max concurrent queries exceeded"))?;
+    /// Whether deletes of documents written with this schema are soft
+    fn soft_deletes(loader: &SchemaLoader, schema_id: &str, schema_version: &str) -> bool {
+        loader
+            .get(schema_id, schema_version)
+            .is_some_and(|schema| schema.soft_delete)
+    }
 
-        // Build index metadata
-        let index_metadata =
-            IndexMetadata::with_indexes(sys.index_manager.indexed_fields().iter().cloned());
+    /// When documents written with this schema expire, if they do
+    fn ttl<'s>(
+        loader: &'s SchemaLoader,
+        schema_id: &str,
+        schema_version: &str,
+    ) -> Option<&'s TtlConfig> {
+        loader
+            .get(schema_id, schema_version)
+            .and_then(|schema| schema.ttl.as_ref())
+    }
 
-        let planner = QueryPlanner::new(sys.schema_loader, &index_metadata);
+    /// Current time in Unix seconds
+    fn now_secs(&self) -> i64 {
+        (self.clock)().timestamp()
+    }
 
-        // 1. Build query AST
-        let query = self.build_query(&req)?;
+    /// Whether a document written with this schema has expired
+    fn is_expired(
+        &self,
+        loader: &SchemaLoader,
+        schema_id: &str,
+        schema_version: &str,
+        body: &Value,
+    ) -> bool {
+        Self::ttl(loader, schema_id, schema_version)
+            .is_some_and(|ttl| ttl.is_expired(body, self.now_secs()))
+    }
 
-        // 2. Call Planner
-        let plan = planner.plan(&query).map_err(ApiError::from_planner_error)?;
+    /// Set `_expires_at` on a document of a `ttl_seconds` schema: kept
+    /// from `previous`, the body it replaces, or counted from now
+    fn stamp_expiry(
+        &self,
+        loader: &SchemaLoader,
+        schema_id: &str,
+        schema_version: &str,
+        document: &mut Value,
+        previous: Option<&Value>,
+    ) {
+        let Some(ttl) = Self::ttl(loader, schema_id, schema_version) else {
+            return;
+        };
+        let Some(seconds) = ttl.ttl_seconds else {
+            return;
+        };
+        let expires_at = previous
+            .and_then(|body| body.get(EXPIRES_AT_FIELD).cloned())
+            .unwrap_or_else(|| json!(self.now_secs().saturating_add(seconds as i64)));
+        if let Some(fields) = document.as_object_mut() {
+            fields.insert(EXPIRES_AT_FIELD.to_string(), expires_at);
+        }
+    }
 
-        // 3. Execute query (simplified execution)
-        let mut results = Vec::new();
+    /// Remove expired documents, at most `batch_size` of them
+    ///
+    /// Scans the index on each TTL schema's expiry field up to the current
+    /// time and removes each expired document as a delete does (WAL
+    /// tombstone, Storage, Index), so replicas and realtime subscribers
+    /// see an ordinary DELETE. Expiry ignores `soft_delete`: the document
+    /// is removed outright. Each sweep is recorded in the operation log.
+    pub fn sweep_expired(
+        &self,
+        batch_size: usize,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<ExpirySweep> {
+        let _guard = self.lock.lock().expect("Lock poisoned");
+        self.replica_gate
+            .check_write()
+            .map_err(ApiError::from_replication_error)?;
 
-        // Get offsets from index based on plan
-        let offsets = self.get_offsets_for_plan(&plan, &query, sys.index_manager);
+        let started = Instant::now();
+        let deadline = sys
+            .query_limits
+            .resolve_timeout_ms(None)
+            .map(Deadline::after_ms)
+            .map_err(ApiError::invalid_request)?;
+        let now = self.now_secs();
+        let fields: HashSet<String> = sys
+            .schema_loader
+            .all_schemas()
+            .filter_map(|schema| schema.ttl.as_ref())
+            .map(|ttl| ttl.expiry_field().to_string())
+            .collect();
 
-        // Read documents at offsets
-        for offset in offsets.iter().take(req.limit) {
-            if let Ok(record) = sys.storage_reader.read_at(*offset) {
-                // Skip tombstones
+        let mut sweep = ExpirySweep::default();
+        for field in &fields {
+            let upper = json!(now);
+            let offsets =
+                sys.index_manager
+                    .lookup_range(field, Bound::Unbounded, Bound::Included(&upper), None);
+            for offset in offsets {
+                if sweep.removed >= batch_size {
+                    break;
+                }
+                sweep.scanned += 1;
+                let record = sys
+                    .storage_reader
+                    .read_at(offset)
+                    .map_err(ApiError::from_storage_error)?;
                 if record.is_tombstone {
                     continue;
                 }
+                let Ok(body) = serde_json::from_slice::<Value>(&record.document_body) else {
+                    continue;
+                };
 
-                // Check schema match
-                if record.schema_id != req.schema_id || record.schema_version != req.schema_version
-                {
+                // Skip versions a later write superseded, and documents of
+                // schemas that expire on another field
+                let doc_id = body.get("_id").and_then(Value::as_str).unwrap_or_default();
+                if sys.index_manager.lookup_pk(doc_id).last() != Some(&offset) {
                     continue;
                 }
-
-                // Parse body
-                if let Ok(doc) = serde_json::from_slice::<Value>(&record.document_body) {
-                    results.push(doc);
+                let ttl = Self::ttl(sys.schema_loader, &record.schema_id, &record.schema_version);
+                let expired = ttl.is_some_and(|ttl| {
+                    ttl.expiry_field() == field && ttl.is_expired(&body, now)
+                });
+                if !expired {
+                    continue;
                 }
+
+                let req = DeleteRequest {
+                    schema_id: record.schema_id,
+                    document_id: doc_id.to_string(),
+                    timeout_ms: None,
+                    tx_id: None,
+                };
+                self.remove(&req, body, false, &deadline, sys)?;
+                sweep.removed += 1;
             }
         }
 
-        Ok(json!(results))
+        if let Some(log) = &self.operation_log {
+            log.log(
+                OperationLogEntry::builder(OperationType::Expire)
+                    .collection(&self.collection)
+                    .documents_scanned(sweep.scanned)
+                    .documents_affected(sweep.removed)
+                    .duration(started.elapsed())
+                    .build(),
+            );
+        }
+
+        Ok(sweep)
     }
 
-    /// Handle explain operation
-    fn handle_explain(&self, req: QueryRequest, sys: &mut Subsystems<'_>) -> ApiResult<Value> {
-        // Build index metadata
-        let index_metadata =
-            IndexMetadata::with_indexes(sys.index_manager.indexed_fields().iter().cloned());
+    /// Visit every live document of `schema_id` matching `filter`, in
+    /// primary key order
+    ///
+    /// Documents are read from storage one at a time, so a collection can
+    /// be streamed without holding it in memory. Soft-deleted and expired
+    /// documents are skipped. The filter takes the query filter syntax.
+    /// Returns the number of documents visited.
+    pub fn scan<E: From<ApiError>>(
+        &self,
+        schema_id: &str,
+        filter: Option<&Value>,
+        sys: &mut Subsystems<'_>,
+        mut visit: impl FnMut(Value) -> Result<(), E>,
+    ) -> Result<usize, E> {
+        let filter = Self::parse_filter(filter)?;
+        let mut visited = 0;
+        for offset in sys.index_manager.all_offsets_pk_order() {
+            let record = sys
+                .storage_reader
+                .read_at(offset)
+                .map_err(ApiError::from_storage_error)?;
+            if record.is_tombstone || record.schema_id != schema_id {
+                continue;
+            }
+            let Ok(doc) = serde_json::from_slice::<Value>(&record.document_body) else {
+                continue;
+            };
+            let expired = self.is_expired(sys.schema_loader, schema_id, &record.schema_version, &doc);
+            if is_soft_deleted(&doc) || expired || !PredicateFilter::matches_expr(&doc, &filter) {
+                continue;
+            }
+            visit(doc)?;
+            visited += 1;
+        }
+        Ok(visited)
+    }
 
-        let planner = QueryPlanner::new(sys.schema_loader, &index_metadata);
+    /// Whether a live document has this `_id`
+    pub fn document_exists(&self, doc_id: &str, sys: &mut Subsystems<'_>) -> ApiResult<bool> {
+        Ok(self.committed_body(doc_id, sys)?.is_some())
+    }
 
-        // Build query AST
-        let query = self.build_query(&req)?;
+    /// Buffer a write in its transaction
+    ///
+    /// The document is validated against its schema now; existence and
+    /// unique indexes are checked at commit.
+    fn buffer_write(
+        &self,
+        tx_id: Uuid,
+        write: BufferedWrite,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        let validator = SchemaValidator::new(sys.schema_loader);
+        let doc_id = write
+            .document_id()
+            .ok_or_else(|| ApiError::invalid_request("Document missing _id"))?
+            .to_string();
+        match &write {
+            BufferedWrite::Insert(r) => validator
+                .validate_document(&r.schema_id, &r.schema_version, &r.document)
+                .map_err(ApiError::from_schema_error)?,
+            BufferedWrite::Update(r) => validator
+                .validate_update(&r.schema_id, &r.schema_version, &doc_id, &r.document)
+                .map_err(ApiError::from_schema_error)?,
+            BufferedWrite::Delete(_) => {}
+        }
 
-        // Call Planner
-        let plan = planner.plan(&query).map_err(ApiError::from_planner_error)?;
+        let buffered = self.transactions.buffer(tx_id, write)?;
+        Ok(json!({"buffered": doc_id, "tx_id": tx_id.to_string(), "writes": buffered}))
+    }
 
-        // Return explain output
-        Ok(json!({
-            "scan_type": format!("{:?}", plan.scan_type),
-            "chosen_index": plan.chosen_index,
-            "predicates": plan.predicates.len(),
-            "sort": plan.sort.as_ref().map(|s| &s.field),
-            "limit": plan.limit
-        }))
+    /// Handle rollback: discard a transaction's buffered writes
+    fn handle_rollback(&self, req: TransactionRequest) -> ApiResult<Value> {
+        let closed = self.transactions.finish(req.tx_id)?;
+        Ok(json!({"rolled_back": req.tx_id.to_string(), "writes": closed.writes.len()}))
     }
 
-    /// Build a Query AST from a QueryRequest
-    fn build_query(&self, req: &QueryRequest) -> ApiResult<Query> {
-        let mut query = Query::new(&self.collection, &req.schema_id)
-            .with_schema_version(&req.schema_version)
-            .with_limit(req.limit as u64);
+    /// Handle commit: apply a transaction's buffered writes atomically
+    ///
+    /// Flow:
+    /// 1. Close the transaction, taking its writes
+    /// 2. Check no document it writes was written since its snapshot
+    /// 3. Check each update and delete finds its document, counting the
+    ///    transaction's own earlier writes
+    /// 4. Check unique indexes against the writes as a whole
+    /// 5. Append every WAL record with a single append
+    /// 6. Apply to Storage
+    /// 7. Update Index
+    ///
+    /// The transaction ends either way; a commit refused before step 5
+    /// writes nothing.
+    fn handle_commit(
+        &self,
+        req: TransactionRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        // 1. Close the transaction
+        let ClosedTransaction { snapshot, writes } = self.transactions.finish(req.tx_id)?;
 
-        // Parse filter
-        if let Some(filter) = &req.filter {
-            if let Some(obj) = filter.as_object() {
-                for (field, condition) in obj {
-                    if let Some(cond_obj) = condition.as_object() {
-                        for (op, value) in cond_obj {
-                            let predicate = match op.as_str() {
-                                "$eq" => Predicate::eq(field, value.clone()),
-                                "$gte" => Predicate::gte(field, value.clone()),
-                                "$gt" => Predicate::gt(field, value.clone()),
-                                "$lte" => Predicate::lte(field, value.clone()),
-                                "$lt" => Predicate::lt(field, value.clone()),
-                                other => {
-                                    return Err(ApiError::invalid_request(format!(
-                                        "Unknown filter operator: {}",
-                                        other
-                                    )))
-                                }
-                            };
-                            query = query.with_predicate(predicate);
-                        }
+        // Hardening: Resource checks
+        if !sys.resource_manager.writes_allowed() {
+            return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
+        }
+        if !sys.admission_controller.try_acquire_write() {
+            return Err(ApiError::too_many_requests("Write rate limit exceeded"));
+        }
+
+        // 2. First committer wins: storage is append-only and commits are
+        // serialized, so a document whose latest record (tombstones
+        // included) lies at or past the snapshot was written by a commit
+        // this transaction did not see
+        for write in &writes {
+            let doc_id = write.document_id().unwrap_or_default();
+            let composite_id = format!("{}:{}", self.collection, doc_id);
+            if sys
+                .storage_writer
+                .get_document_offset(&composite_id)
+                .is_some_and(|offset| offset >= snapshot)
+            {
+                return Err(ApiError::transaction_conflict(req.tx_id, doc_id));
+            }
+        }
+
+        // 3. Resolve each write against committed documents and the
+        // transaction's earlier writes (None: deleted in the transaction),
+        // as the schema version and body of the document
+        let mut latest: HashMap<String, Option<(String, Value)>> = HashMap::new();
+        let mut prepared = Vec::with_capacity(writes.len());
+        for write in writes {
+            let doc_id = write
+                .document_id()
+                .ok_or_else(|| ApiError::invalid_request("Document missing _id"))?
+                .to_string();
+            let current = match latest.get(&doc_id) {
+                Some(body) => body.clone(),
+                None => self.committed_body(&doc_id, sys)?,
+            };
+            let write = match write {
+                BufferedWrite::Insert(mut r) => {
+                    let (schema_id, schema_version) = (&r.schema_id, &r.schema_version);
+                    self.stamp_expiry(sys.schema_loader, schema_id, schema_version, &mut r.document, None);
+                    PreparedWrite::put(RecordType::Insert, doc_id, r.schema_id, r.schema_version, r.document)?
+                }
+                BufferedWrite::Update(mut r) => {
+                    let Some((_, previous)) = &current else {
+                        return Err(ApiError::invalid_request(format!("Document not found: {}", doc_id)));
+                    };
+                    self.stamp_expiry(
+                        sys.schema_loader,
+                        &r.schema_id,
+                        &r.schema_version,
+                        &mut r.document,
+                        Some(previous),
+                    );
+                    PreparedWrite::put(RecordType::Update, doc_id, r.schema_id, r.schema_version, r.document)?
+                }
+                BufferedWrite::Delete(r) => {
+                    let (schema_version, old_body) = current.ok_or_else(|| {
+                        ApiError::invalid_request(format!("Document not found: {}", doc_id))
+                    })?;
+                    if Self::soft_deletes(sys.schema_loader, &r.schema_id, &schema_version) {
+                        PreparedWrite::soft_delete(doc_id, r.schema_id, schema_version, old_body)?
+                    } else {
+                        PreparedWrite::delete(doc_id, r.schema_id, old_body)
                     }
                 }
+            };
+            let live = write.record_type != RecordType::Delete && !is_soft_deleted(&write.body);
+            let current = live.then(|| (write.schema_version.clone(), write.body.clone()));
+            latest.insert(write.doc_id.clone(), current);
+            prepared.push(write);
+        }
+
+        // 4. Check unique indexes (a deleted document claims no value)
+        let deleted = json!({});
+        let final_bodies: Vec<(&str, &Value)> = latest
+            .iter()
+            .map(|(doc_id, current)| {
+                (doc_id.as_str(), current.as_ref().map_or(&deleted, |(_, body)| body))
+            })
+            .collect();
+        sys.index_manager
+            .check_unique_batch(&final_bodies)
+            .map_err(ApiError::from_index_error)?;
+
+        // Hardening: Check disk space
+        let bytes: u64 = prepared.iter().map(|w| w.bytes.len() as u64 + 1024).sum();
+        sys.resource_manager
+            .check_disk_space(bytes)
+            .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
+
+        // 5. Append every WAL record at once (last point at which the
+        // commit may time out: nothing is durable yet)
+        let records = prepared
+            .iter()
+            .map(|w| (w.record_type, w.wal_payload(&self.collection)))
+            .collect();
+        Self::check_write_deadline(deadline)?;
+        sys.wal_writer
+            .append_batch(records)
+            .map_err(ApiError::from_wal_error)?;
+
+        // 6-7. Apply to Storage, then Index and statistics
+        let fields = Self::statistics_fields(sys.index_manager);
+        let count = prepared.len();
+        for write in prepared {
+            if write.record_type == RecordType::Delete {
+                self.apply_tombstone(write, &fields, sys)?;
+                continue;
             }
+
+            self.apply_put(write, &fields, sys)?;
         }
 
-        // Parse sort
-        if let Some(sort_str) = &req.sort {
-            let sort = if sort_str.starts_with('-') {
-                SortSpec::desc(&sort_str[1..])
-            } else {
-                SortSpec::asc(sort_str)
-            };
-            query = query.with_sort(sort);
+        Ok(json!({"committed": req.tx_id.to_string(), "writes": count}))
+    }
+
+    /// Apply a delete whose WAL record is durable: Storage, then Index and
+    /// statistics over `fields`
+    fn apply_tombstone(&self, write: PreparedWrite, fields: &[String], sys: &mut Subsystems<'_>) -> ApiResult<()> {
+        sys.storage_writer
+            .write_tombstone(&self.collection, &write.doc_id, &write.schema_id, "")
+            .map_err(ApiError::from_storage_error)?;
+        sys.index_manager.apply_delete(&write.doc_id, &write.body);
+        if !is_soft_deleted(&write.body) {
+            for scope in self.statistics_scopes(&write.schema_id) {
+                sys.statistics.record_delete(scope, &write.body, write.old_size, fields);
+            }
         }
+        Ok(())
+    }
 
-        Ok(query)
+    /// Apply an insert or update whose WAL record is durable: Storage, then
+    /// Index and statistics over `fields`
+    fn apply_put(&self, write: PreparedWrite, fields: &[String], sys: &mut Subsystems<'_>) -> ApiResult<()> {
+        let body_size = write.bytes.len() as u64;
+        let storage_payload = StoragePayload::new(
+            &self.collection,
+            &write.doc_id,
+            &write.schema_id,
+            &write.schema_version,
+            write.bytes,
+        );
+        let offset = sys
+            .storage_writer
+            .write(&storage_payload)
+            .map_err(ApiError::from_storage_error)?;
+        let doc_info = DocumentInfo {
+            document_id: write.doc_id,
+            schema_id: write.schema_id,
+            schema_version: write.schema_version,
+            is_tombstone: false,
+            body: write.body,
+            offset,
+        };
+        sys.index_manager.apply_write(&doc_info);
+        for scope in self.statistics_scopes(&doc_info.schema_id) {
+            if write.record_type == RecordType::Insert {
+                sys.statistics.record_insert(scope, &doc_info.body, body_size, fields);
+            } else if is_soft_deleted(&doc_info.body) {
+                sys.statistics.record_delete(scope, &doc_info.body, write.old_size, fields);
+            } else {
+                sys.statistics.record_update(scope, &doc_info.body, fields);
+            }
+        }
+        Ok(())
     }
 
-    /// Get offsets from index based on plan
-    fn get_offsets_for_plan(
+    /// Handle rename_collection: move a collection's documents and schema
+    /// versions to a new name
+    ///
+    /// Flow:
+    /// 1. Check the confirmation token, issuing one if none was given
+    /// 2. Check `from` exists and `to` does not
+    /// 3. Save `from`'s schema versions under `to`
+    /// 4. Append a single COLLECTION_RENAME record
+    /// 5. Apply to Storage, which moves every document to `to`, then Index
+    /// 6. Delete `from`'s schema versions
+    /// 7. Move the collection's bindings (RLS policy, REST endpoint)
+    ///
+    /// The append in step 4 decides the outcome: recovery replays the
+    /// record through the same storage rename, so it finds either every
+    /// document under `from` or every document under `to`. A target
+    /// left by an interrupted rename (the same schema versions, with it or
+    /// the source holding no documents) is taken over, so retrying the
+    /// rename finishes it.
+    fn handle_rename_collection(
         &self,
-        plan: &QueryPlan,
-        query: &Query,
-        index_manager: &IndexManager,
-    ) -> Vec<u64> {
-        match plan.scan_type {
-            ScanType::PrimaryKey => {
-                // Find PK predicate
-                for pred in &query.predicates {
-                    if pred.field == "_id" {
-                        if let FilterOp::Eq(ref val) = pred.op {
-                            if let Some(pk) = val.as_str() {
-                                return index_manager.lookup_pk(pk);
-                            }
-                        }
-                    }
-                }
-                Vec::new()
+        req: RenameCollectionRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        // 1. Confirm
+        let payload = json!({"command": "rename_collection", "from": req.from, "to": req.to});
+        self.confirm_operation(DangerousOperation::RenameCollection, &payload, req.confirm)?;
+
+        // 2. Check the names
+        if req.from == req.to {
+            return Err(ApiError::invalid_request("Collection cannot be renamed to itself"));
+        }
+        let (source, renamed) = Self::schema_versions(sys.schema_loader, &req.from, &req.to)?;
+        let documents = self.collection_records(&req.from, sys)?;
+        let resuming = sys.schema_loader.schema_id_exists(&req.to);
+        if resuming
+            && (Self::schema_versions(sys.schema_loader, &req.to, &req.to)?.0 != renamed
+                || !(documents.is_empty() || self.collection_records(&req.to, sys)?.is_empty()))
+        {
+            return Err(ApiError::collection_exists(&req.to));
+        }
+
+        // Hardening: Resource checks
+        if !sys.resource_manager.writes_allowed() {
+            return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
+        }
+        if !sys.admission_controller.try_acquire_write() {
+            return Err(ApiError::too_many_requests("Write rate limit exceeded"));
+        }
+        Self::check_write_deadline(deadline)?;
+
+        // 3. Save the schema versions under the new name first, so recovery
+        // can validate the records that move documents to it
+        if !resuming {
+            for schema in renamed {
+                sys.schema_loader
+                    .save_schema(&schema)
+                    .map_err(ApiError::from_schema_error)?;
+                sys.schema_loader
+                    .register(schema)
+                    .map_err(ApiError::from_schema_error)?;
             }
-            ScanType::IndexedEquality => {
-                let field = &plan.chosen_index;
-                for pred in &query.predicates {
-                    if &pred.field == field {
-                        if let FilterOp::Eq(ref val) = pred.op {
-                            return index_manager.lookup_eq(field, val);
-                        }
-                    }
+        }
+        maybe_crash(points::COLLECTION_RENAME_AFTER_SCHEMA_COPY);
+
+        // 4. Append the rename record; storage rewrites each document
+        let bytes: u64 = documents
+            .iter()
+            .map(|(_, _, body)| body.to_string().len() as u64 + 1024)
+            .sum();
+        sys.resource_manager
+            .check_disk_space(bytes)
+            .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
+        let payload = WalPayload::collection_change(&self.collection, &req.from, &req.to);
+        Self::check_write_deadline(deadline)?;
+        sys.wal_writer
+            .append(RecordType::CollectionRename, payload)
+            .map_err(ApiError::from_wal_error)?;
+
+        // 5. Apply to Storage, then Index; statistics move with the name
+        sys.statistics.rename(&req.from, &req.to).map_err(|e| {
+            ApiError::service_unavailable(format!("Failed to move statistics: {}", e))
+        })?;
+        let moved = sys
+            .storage_writer
+            .rename_collection(&self.collection, &req.from, &req.to)
+            .map_err(ApiError::from_storage_error)?;
+        let count = moved.len();
+        for (record, offset) in moved {
+            sys.index_manager.apply_write(&self.document_info(record, offset)?);
+        }
+
+        // 6. The old name now holds no documents
+        for schema in source {
+            sys.schema_loader
+                .remove(&req.from, &schema.schema_version)
+                .map_err(|e| ApiError::from_schema_error(*e))?;
+        }
+
+        // 7. Bindings
+        self.bind_collection(|bindings| bindings.rename(&req.from, &req.to))?;
+
+        Ok(json!({"renamed": req.from, "to": req.to, "documents": count}))
+    }
+
+    /// Handle copy_collection: duplicate a collection's documents and schema
+    /// versions under a new name, leaving the source as it is
+    ///
+    /// Flow:
+    /// 1. Check the confirmation token, issuing one if none was given
+    /// 2. Check `from` exists and `to` does not
+    /// 3. Save `from`'s schema versions under `to`
+    /// 4. Append a COLLECTION_COPY record and an insert of each copy, with
+    ///    a single append
+    /// 5. Apply to Storage, then Index and statistics, which builds `to`'s
+    ///    index entries afresh
+    /// 6. Copy the collection's bindings (RLS policy, REST endpoint)
+    ///
+    /// `_id` is unique across collections, so each copy is keyed
+    /// `<to>:<_id>`, its `_id` field rewritten to match. The append in
+    /// step 4 decides the outcome: recovery finds every copy or none. A
+    /// target left without documents by an interrupted copy (the same
+    /// schema versions) is taken over, so retrying the copy finishes it.
+    fn handle_copy_collection(
+        &self,
+        req: CopyCollectionRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        // 1. Confirm
+        let payload = json!({"command": "copy_collection", "from": req.from, "to": req.to});
+        self.confirm_operation(DangerousOperation::CopyCollection, &payload, req.confirm)?;
+
+        // 2. Check the names
+        if req.from == req.to {
+            return Err(ApiError::invalid_request("Collection cannot be copied to itself"));
+        }
+        let (_, copied) = Self::schema_versions(sys.schema_loader, &req.from, &req.to)?;
+        let resuming = sys.schema_loader.schema_id_exists(&req.to);
+        if resuming
+            && (Self::schema_versions(sys.schema_loader, &req.to, &req.to)?.0 != copied
+                || !self.collection_records(&req.to, sys)?.is_empty())
+        {
+            return Err(ApiError::collection_exists(&req.to));
+        }
+        let prepared = self
+            .collection_records(&req.from, sys)?
+            .into_iter()
+            .map(|(doc_id, schema_version, mut body)| {
+                let copy_id = format!("{}:{}", req.to, doc_id);
+                if let Some(fields) = body.as_object_mut() {
+                    fields.insert("_id".to_string(), json!(copy_id));
                 }
-                Vec::new()
-            }
-            ScanType::IndexedRange => {
-                let field = &plan.chosen_index;
-                let mut min: Option<&Value> = None;
-                let mut max: Option<&Value> = None;
-
-                for pred in &query.predicates {
-                    if &pred.field == field {
-                        match &pred.op {
-                            FilterOp::Gte(v) | FilterOp::Gt(v) => min = Some(v),
-                            FilterOp::Lte(v) | FilterOp::Lt(v) => max = Some(v),
-                            _ => {}
-                        }
-                    }
+                if !sys.index_manager.lookup_pk(&copy_id).is_empty() {
+                    return Err(ApiError::invalid_request(format!(
+                        "Document already exists: {}",
+                        copy_id
+                    )));
                 }
+                let to = req.to.clone();
+                PreparedWrite::put(RecordType::Insert, copy_id, to, schema_version, body)
+            })
+            .collect::<ApiResult<Vec<_>>>()?;
+        let writes: Vec<(&str, &Value)> =
+            prepared.iter().map(|w| (w.doc_id.as_str(), &w.body)).collect();
+        sys.index_manager
+            .check_unique_batch(&writes)
+            .map_err(ApiError::from_index_error)?;
+
+        // Hardening: Resource checks
+        if !sys.resource_manager.writes_allowed() {
+            return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
+        }
+        if !sys.admission_controller.try_acquire_write() {
+            return Err(ApiError::too_many_requests("Write rate limit exceeded"));
+        }
+        let bytes: u64 = prepared.iter().map(|w| w.bytes.len() as u64 + 1024).sum();
+        sys.resource_manager
+            .check_disk_space(bytes)
+            .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
+        Self::check_write_deadline(deadline)?;
 
-                index_manager.lookup_range(field, min, max, Some(plan.limit as usize))
+        // 3. Save the schema versions under the new name first, so recovery
+        // can validate the copies
+        if !resuming {
+            for schema in copied {
+                sys.schema_loader
+                    .save_schema(&schema)
+                    .map_err(ApiError::from_schema_error)?;
+                sys.schema_loader
+                    .register(schema)
+                    .map_err(ApiError::from_schema_error)?;
             }
         }
+
+        // 4. Append every WAL record at once
+        let marker = WalPayload::collection_change(&self.collection, &req.from, &req.to);
+        let records = std::iter::once((RecordType::CollectionCopy, marker))
+            .chain(prepared.iter().map(|w| (w.record_type, w.wal_payload(&self.collection))))
+            .collect();
+        Self::check_write_deadline(deadline)?;
+        sys.wal_writer
+            .append_batch(records)
+            .map_err(ApiError::from_wal_error)?;
+
+        // 5. Apply to Storage, then Index and statistics
+        let fields = Self::statistics_fields(sys.index_manager);
+        let count = prepared.len();
+        for write in prepared {
+            self.apply_put(write, &fields, sys)?;
+        }
+
+        // 6. Bindings
+        self.bind_collection(|bindings| bindings.copy(&req.from, &req.to))?;
+
+        Ok(json!({"copied": req.from, "to": req.to, "documents": count}))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::schema::{FieldDef, Schema};
-    use serde_json::json;
-    use std::collections::{HashMap, HashSet};
-    use tempfile::TempDir;
-    
-    use crate::resource_limits::{ResourceManager, ResourceLimitsConfig};
-    use crate::backpressure::{BackpressureManager, BackpressureConfig};
-    use crate::admission_control::{AdmissionController, AdmissionControlConfig};
-    use crate::query_limits::QueryLimitsConfig;
+    /// `from`'s schema versions in version order, and the same versions
+    /// under the id `to`
+    ///
+    /// # Errors
+    ///
+    /// `AERO_UNKNOWN_SCHEMA` if `from` has no schema version.
+    fn schema_versions(
+        loader: &SchemaLoader,
+        from: &str,
+        to: &str,
+    ) -> ApiResult<(Vec<Schema>, Vec<Schema>)> {
+        let mut versions: Vec<Schema> = loader
+            .all_schemas()
+            .filter(|schema| schema.schema_id == from)
+            .cloned()
+            .collect();
+        if versions.is_empty() {
+            return Err(ApiError::from_schema_error(SchemaError::unknown_schema(from)));
+        }
+        versions.sort_by(|a, b| a.schema_version.cmp(&b.schema_version));
+        let moved = versions
+            .iter()
+            .cloned()
+            .map(|mut schema| {
+                schema.schema_id = to.to_string();
+                schema
+            })
+            .collect();
+        Ok((versions, moved))
+    }
 
-    fn setup_test_env() -> (
-        TempDir,
-        SchemaLoader,
-        WalWriter,
-        StorageWriter,
-        StorageReader,
-        IndexManager,
-        ResourceManager,
-        BackpressureManager,
-        AdmissionController,
-        QueryLimitsConfig,
-    ) {
-        let temp_dir = TempDir::new().unwrap();
-        let data_dir = temp_dir.path();
+    /// Handle drop_collection: delete a collection's documents and schema
+    /// versions
+    ///
+    /// Flow:
+    /// 1. Check the confirmation token, issuing one if none was given
+    /// 2. Check the collection exists
+    /// 3. Append a delete of each document, with a single append
+    /// 4. Apply to Storage, then Index and statistics
+    /// 5. Delete the collection's schema versions
+    ///
+    /// The append in step 3 decides the outcome. A collection left with
+    /// its schema but no documents by an interrupted drop is dropped again
+    /// by retrying.
+    fn handle_drop_collection(
+        &self,
+        req: DropCollectionRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        // 1. Confirm
+        let payload = json!({"command": "drop_collection", "collection": req.collection});
+        self.confirm_operation(DangerousOperation::DropCollection, &payload, req.confirm)?;
 
-        // Create schema loader
-        let mut loader = SchemaLoader::new(data_dir);
+        // 2. Check the collection
+        let versions: Vec<String> = sys
+            .schema_loader
+            .all_schemas()
+            .filter(|schema| schema.schema_id == req.collection)
+            .map(|schema| schema.schema_version.clone())
+            .collect();
+        if versions.is_empty() {
+            return Err(ApiError::from_schema_error(SchemaError::unknown_schema(&req.collection)));
+        }
 
-        let mut fields = HashMap::new();
-        fields.insert("_id".to_string(), FieldDef::required_string());
-        fields.insert("name".to_string(), FieldDef::required_string());
-        fields.insert("age".to_string(), FieldDef::optional_int());
+        // Hardening: Resource checks
+        if !sys.resource_manager.writes_allowed() {
+            return Err(ApiError::service_unavailable("System is in read-only mode due to resource exhaustion"));
+        }
+        if !sys.admission_controller.try_acquire_write() {
+            return Err(ApiError::too_many_requests("Write rate limit exceeded"));
+        }
 
-        let schema = Schema::new("users", "v1", fields);
-        loader.register(schema).unwrap();
+        // 3. Append every WAL record at once
+        let prepared: Vec<PreparedWrite> = self
+            .collection_records(&req.collection, sys)?
+            .into_iter()
+            .map(|(doc_id, _, body)| PreparedWrite::delete(doc_id, req.collection.clone(), body))
+            .collect();
+        let count = prepared.len();
+        if !prepared.is_empty() {
+            sys.resource_manager
+                .check_disk_space(prepared.len() as u64 * 1024)
+                .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
+            let records = prepared
+                .iter()
+                .map(|w| (w.record_type, w.wal_payload(&self.collection)))
+                .collect();
+            Self::check_write_deadline(deadline)?;
+            sys.wal_writer
+                .append_batch(records)
+                .map_err(ApiError::from_wal_error)?;
+        }
+
+        // 4. Apply to Storage, then Index and statistics
+        let fields = Self::statistics_fields(sys.index_manager);
+        for write in prepared {
+            self.apply_tombstone(write, &fields, sys)?;
+        }
+        if req.collection != self.collection {
+            sys.statistics.remove(&req.collection).map_err(|e| {
+                ApiError::service_unavailable(format!("Failed to delete statistics: {}", e))
+            })?;
+        }
+
+        // 5. The collection now holds no documents
+        for version in versions {
+            sys.schema_loader
+                .remove(&req.collection, &version)
+                .map_err(|e| ApiError::from_schema_error(*e))?;
+        }
+
+        Ok(json!({"dropped": req.collection, "documents": count}))
+    }
+
+    /// Check a dangerous operation's confirmation token
+    ///
+    /// Without `confirm`, issues a token bound to `payload` and fails with
+    /// it: the caller repeats the request with that token to proceed.
+    /// Issuing and consuming the token are audited, and an operation whose
+    /// confirmation cannot be audited does not proceed.
+    fn confirm_operation(
+        &self,
+        operation: DangerousOperation,
+        payload: &Value,
+        confirm: Option<Uuid>,
+    ) -> ApiResult<()> {
+        let command = payload["command"].as_str().unwrap_or_default();
+        let payload = payload.to_string();
+        let mut confirmations = self.confirmations.lock().expect("Lock poisoned");
+        let Some(token_id) = confirm else {
+            let token = confirmations.request_confirmation(command, None, &payload);
+            self.audit(
+                AuditRecord::new(AuditAction::ConfirmationRequested, AuditOutcome::Pending)
+                    .with_command(command)
+                    .with_confirmation_token(token.id()),
+            )?;
+            return Err(ApiError::confirmation_required(operation.warning(), token.id()));
+        };
+        let provided = AuditRecord::new(AuditAction::ConfirmationProvided, AuditOutcome::Pending)
+            .with_command(command)
+            .with_confirmation_token(token_id);
+        match confirmations.confirm(token_id, command, None, &payload) {
+            ConfirmationResult::Proceed { .. } => self.audit(provided),
+            ConfirmationResult::Abort { reason, .. } => {
+                self.audit(provided)?;
+                self.audit(
+                    AuditRecord::new(AuditAction::CommandRejected, AuditOutcome::Rejected)
+                        .with_command(command)
+                        .with_confirmation_token(token_id)
+                        .with_error(&reason),
+                )?;
+                Err(ApiError::confirmation_rejected(reason))
+            }
+        }
+    }
+
+    /// Move or copy a collection's bindings, if there are any
+    fn bind_collection<F>(&self, change: F) -> ApiResult<()>
+    where
+        F: FnOnce(&dyn CollectionBindings) -> Result<(), String>,
+    {
+        let Some(bindings) = &self.bindings else {
+            return Ok(());
+        };
+        change(bindings.as_ref()).map_err(|e| {
+            ApiError::service_unavailable(format!("Failed to update collection bindings: {}", e))
+        })
+    }
+
+    /// Append `record` to the audit log, if there is one
+    fn audit(&self, record: AuditRecord) -> ApiResult<()> {
+        let Some(audit_log) = &self.audit_log else {
+            return Ok(());
+        };
+        audit_log.append(&record).map_err(|e| {
+            ApiError::service_unavailable(format!("Failed to write audit log: {}", e))
+        })
+    }
+
+    /// Every document of a collection as `(_id, schema version, body)`,
+    /// soft-deleted and expired ones included
+    fn collection_records(
+        &self,
+        schema_id: &str,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Vec<(String, String, Value)>> {
+        let mut documents = Vec::new();
+        for offset in sys.index_manager.all_offsets_pk_order() {
+            let record = sys
+                .storage_reader
+                .read_at(offset)
+                .map_err(ApiError::from_storage_error)?;
+            if record.is_tombstone || record.schema_id != schema_id {
+                continue;
+            }
+            let body = serde_json::from_slice::<Value>(&record.document_body).map_err(|e| {
+                ApiError::invalid_request(format!(
+                    "Document {} is not valid JSON: {}",
+                    record.document_id, e
+                ))
+            })?;
+            // Storage ids are `<collection>:<_id>`
+            let doc_id = record
+                .document_id
+                .strip_prefix(&format!("{}:", self.collection))
+                .unwrap_or(&record.document_id)
+                .to_string();
+            documents.push((doc_id, record.schema_version, body));
+        }
+        Ok(documents)
+    }
+
+    /// Index entry of a document record storage wrote at `offset`, keyed
+    /// by its `_id`
+    fn document_info(&self, record: DocumentRecord, offset: u64) -> ApiResult<DocumentInfo> {
+        let body = serde_json::from_slice::<Value>(&record.document_body).map_err(|e| {
+            ApiError::invalid_request(format!(
+                "Document {} is not valid JSON: {}",
+                record.document_id, e
+            ))
+        })?;
+        // Storage ids are `<collection>:<_id>`
+        let document_id = record
+            .document_id
+            .strip_prefix(&format!("{}:", self.collection))
+            .unwrap_or(&record.document_id)
+            .to_string();
+        Ok(DocumentInfo {
+            document_id,
+            schema_id: record.schema_id,
+            schema_version: record.schema_version,
+            is_tombstone: false,
+            body,
+            offset,
+        })
+    }
+
+    /// Schema version and current body of a committed document, or None if
+    /// it does not exist, is soft-deleted or has expired
+    fn committed_body(
+        &self,
+        doc_id: &str,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Option<(String, Value)>> {
+        let offsets = sys.index_manager.lookup_pk(doc_id);
+        let Some(&offset) = offsets.last() else {
+            return Ok(None);
+        };
+        let record = sys
+            .storage_reader
+            .read_at(offset)
+            .map_err(ApiError::from_storage_error)?;
+        let body = serde_json::from_slice(&record.document_body).unwrap_or(json!({}));
+        let (schema_id, schema_version) = (&record.schema_id, &record.schema_version);
+        let expired = self.is_expired(sys.schema_loader, schema_id, schema_version, &body);
+        Ok((!is_soft_deleted(&body) && !expired).then_some((record.schema_version, body)))
+    }
+
+    /// Handle query operation
+    ///
+    /// Flow:
+    /// 1. Parse query
+    /// 2. Call Planner
+    /// 3. Call Executor (simplified: use index + storage)
+    /// 4. Return results
+    ///
+    /// Sorts the scanned index satisfies read offsets in key order and stop
+    /// once `limit + offset` documents are read; other sorts go through a
+    /// `SortBuffer` bounded by `query_limits.max_sort_bytes`.
+    fn handle_query(
+        &self,
+        req: QueryRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        // Hardening: Admission control for queries
+        let _guard = sys.admission_controller.acquire_query_guard()
+            .ok_or_else(|| ApiError::too_many_requests("Max concurrent queries exceeded"))?;
+
+        // Build index metadata
+        let index_metadata = self.index_metadata(sys.index_manager, sys.statistics);
+
+        let planner = QueryPlanner::new(sys.schema_loader, &index_metadata);
+
+        // 1. Build query AST
+        let query = self.build_query(&req)?;
+
+        // 2. Call Planner
+        let plan = planner.plan(&query).map_err(ApiError::from_planner_error)?;
+
+        // A transaction reads from its snapshot; once a commit has moved
+        // storage past it, the indexes describe later data
+        if let Some(tx_id) = req.tx_id {
+            let snapshot = self.transactions.snapshot(tx_id)?;
+            if sys.storage_writer.current_offset() > snapshot {
+                return self.snapshot_query(&req, &plan, snapshot, deadline, sys);
+            }
+        }
+
+        // 3. Execute query (simplified execution)
+        let window = plan.limit.saturating_add(plan.offset) as usize;
+        let mut sorter = SortBuffer::for_plan(&plan, sys.query_limits.max_sort_bytes);
+        let mut results = Vec::new();
+
+        // Get offsets from index based on plan
+        let offsets = self.get_offsets_for_plan(&plan, &query, sys.index_manager);
+
+        // Expired documents are hidden before the sweeper removes them
+        let ttl = Self::ttl(sys.schema_loader, &req.schema_id, &req.schema_version);
+        let now = self.now_secs();
+
+        // Read documents at offsets, checking the deadline between batches
+        for (examined, offset) in offsets.iter().enumerate() {
+            if sorter.is_none() && results.len() >= window {
+                break;
+            }
+            deadline
+                .check_batch(examined)
+                .map_err(ApiError::from_executor_error)?;
+            if let Ok(record) = sys.storage_reader.read_at(*offset) {
+                // Skip tombstones
+                if record.is_tombstone {
+                    continue;
+                }
+
+                // Check schema match
+                if record.schema_id != req.schema_id || record.schema_version != req.schema_version
+                {
+                    continue;
+                }
+
+                // Parse body; keep documents matching every predicate and
+                // expression, not only those the index served
+                if let Ok(doc) = serde_json::from_slice::<Value>(&record.document_body) {
+                    // Skip versions a later write superseded: secondary
+                    // indexes keep them, and one may be the live body of a
+                    // since soft-deleted document
+                    let doc_id = doc.get("_id").and_then(Value::as_str).unwrap_or_default();
+                    if sys.index_manager.lookup_pk(doc_id).last() != Some(offset) {
+                        continue;
+                    }
+                    if !req.include_deleted && is_soft_deleted(&doc) {
+                        continue;
+                    }
+                    if ttl.is_some_and(|ttl| ttl.is_expired(&doc, now)) {
+                        continue;
+                    }
+                    if !PredicateFilter::matches_plan(&doc, &plan) {
+                        continue;
+                    }
+                    let key = plan.sort.as_ref().and_then(|s| doc.get(&s.field).cloned());
+                    let doc = match &plan.projection {
+                        Some(projection) => project(&doc, projection),
+                        None => doc,
+                    };
+                    match sorter.as_mut() {
+                        Some(sorter) => {
+                            sorter
+                                .push(doc, key, record.document_body.len() as u64)
+                                .map_err(ApiError::from_executor_error)?;
+                        }
+                        None => results.push(doc),
+                    }
+                }
+            }
+        }
+
+        if let Some(sorter) = sorter {
+            results = sorter.finish();
+        }
+        let results: Vec<Value> = results
+            .into_iter()
+            .skip(plan.offset as usize)
+            .take(plan.limit as usize)
+            .collect();
+
+        Ok(json!(results))
+    }
+
+    /// Answer a query from the data committed before `snapshot`
+    ///
+    /// Scans storage up to the snapshot offset, with a reader of its own
+    /// (the shared one may predate the snapshot), and filters every
+    /// document against the plan; unsorted results come in primary key
+    /// order.
+    fn snapshot_query(
+        &self,
+        req: &QueryRequest,
+        plan: &QueryPlan,
+        snapshot: u64,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        let mut reader = StorageReader::open(sys.storage_writer.path())
+            .map_err(ApiError::from_storage_error)?;
+        let mut records: Vec<_> = reader
+            .build_document_map_before(snapshot)
+            .map_err(ApiError::from_storage_error)?
+            .into_values()
+            .collect();
+        records.sort_by(|a, b| a.document_id.cmp(&b.document_id));
+
+        let window = plan.limit.saturating_add(plan.offset) as usize;
+        let mut sorter = plan.sort.as_ref().map(|sort| {
+            SortBuffer::new(sort.direction, Some(window), sys.query_limits.max_sort_bytes)
+                .with_collation(sort.collation.unwrap_or_default())
+        });
+        let mut results = Vec::new();
+        let ttl = Self::ttl(sys.schema_loader, &req.schema_id, &req.schema_version);
+        let now = self.now_secs();
+
+        for (examined, record) in records.into_iter().enumerate() {
+            if sorter.is_none() && results.len() >= window {
+                break;
+            }
+            deadline
+                .check_batch(examined)
+                .map_err(ApiError::from_executor_error)?;
+            if record.is_tombstone
+                || record.schema_id != req.schema_id
+                || record.schema_version != req.schema_version
+            {
+                continue;
+            }
+            let Ok(doc) = serde_json::from_slice::<Value>(&record.document_body) else {
+                continue;
+            };
+            if (!req.include_deleted && is_soft_deleted(&doc))
+                || ttl.is_some_and(|ttl| ttl.is_expired(&doc, now))
+                || !PredicateFilter::matches_plan(&doc, plan)
+            {
+                continue;
+            }
+            let key = plan.sort.as_ref().and_then(|s| doc.get(&s.field).cloned());
+            let doc = match &plan.projection {
+                Some(projection) => project(&doc, projection),
+                None => doc,
+            };
+            match sorter.as_mut() {
+                Some(sorter) => {
+                    sorter
+                        .push(doc, key, record.document_body.len() as u64)
+                        .map_err(ApiError::from_executor_error)?;
+                }
+                None => results.push(doc),
+            }
+        }
+
+        if let Some(sorter) = sorter {
+            results = sorter.finish();
+        }
+        let results: Vec<Value> = results
+            .into_iter()
+            .skip(plan.offset as usize)
+            .take(plan.limit as usize)
+            .collect();
+
+        Ok(json!(results))
+    }
+
+    /// Handle explain operation
+    fn handle_explain(&self, req: QueryRequest, sys: &mut Subsystems<'_>) -> ApiResult<Value> {
+        // Build index metadata
+        let index_metadata = self.index_metadata(sys.index_manager, sys.statistics);
+
+        let planner = QueryPlanner::new(sys.schema_loader, &index_metadata);
+
+        // Build query AST
+        let query = self.build_query(&req)?;
+
+        // Explain, including rejected queries and cost estimates
+        Ok(planner.explain(&query).to_json())
+    }
+
+    /// Handle analyze operation
+    ///
+    /// Recomputes the collection's statistics from every live document and
+    /// persists them.
+    fn handle_analyze(&self, req: AnalyzeRequest, sys: &mut Subsystems<'_>) -> ApiResult<Value> {
+        if req.collection != self.collection {
+            return Err(ApiError::invalid_request(format!(
+                "Unknown collection: {}",
+                req.collection
+            )));
+        }
+
+        let mut documents = Vec::new();
+        for offset in sys.index_manager.all_offsets_pk_order() {
+            let record = sys
+                .storage_reader
+                .read_at(offset)
+                .map_err(ApiError::from_storage_error)?;
+            if record.is_tombstone {
+                continue;
+            }
+            if let Ok(body) = serde_json::from_slice::<Value>(&record.document_body) {
+                if !is_soft_deleted(&body) {
+                    documents.push((body, record.document_body.len() as u64));
+                }
+            }
+        }
+
+        let fields = Self::statistics_fields(sys.index_manager);
+        let stats = sys
+            .statistics
+            .analyze(
+                &self.collection,
+                documents.iter().map(|(body, size)| (body, *size)),
+                &fields,
+            )
+            .map_err(|e| {
+                ApiError::service_unavailable(format!("Failed to persist statistics: {}", e))
+            })?;
+
+        let distinct: serde_json::Map<String, Value> = stats
+            .fields
+            .iter()
+            .map(|(field, field_stats)| (field.clone(), json!(field_stats.distinct_estimate())))
+            .collect();
+
+        Ok(json!({
+            "analyzed": self.collection,
+            "document_count": stats.document_count,
+            "average_document_size": stats.average_document_size(),
+            "distinct_values": distinct,
+        }))
+    }
+
+    /// Handle aggregate operation
+    ///
+    /// Reads every live document of the collection (an explicit scan; the
+    /// filter is not served by indexes), groups the matching documents and
+    /// returns one object per group, ordered by group key. The group table
+    /// is charged to the resource manager's memory tracker and limited to
+    /// `query_limits.max_result_set_docs` groups.
+    fn handle_aggregate(
+        &self,
+        req: AggregateRequest,
+        deadline: &Deadline,
+        sys: &mut Subsystems<'_>,
+    ) -> ApiResult<Value> {
+        // Hardening: Admission control for queries
+        let _guard = sys.admission_controller.acquire_query_guard()
+            .ok_or_else(|| ApiError::too_many_requests("Max concurrent queries exceeded"))?;
+
+        if req.collection != self.collection {
+            return Err(ApiError::invalid_request(format!(
+                "Unknown collection: {}",
+                req.collection
+            )));
+        }
+
+        let spec = AggregateSpec::from_json(req.group_by, &req.aggregates)
+            .map_err(ApiError::invalid_request)?;
+        let filter = Self::parse_filter(req.filter.as_ref())?;
+
+        let mut aggregator = HashAggregator::new(
+            spec,
+            sys.resource_manager.memory_tracker(),
+            sys.query_limits.max_result_set_docs,
+        );
+
+        for (examined, offset) in sys.index_manager.all_offsets_pk_order().into_iter().enumerate() {
+            deadline
+                .check_batch(examined)
+                .map_err(ApiError::from_executor_error)?;
+            let record = sys
+                .storage_reader
+                .read_at(offset)
+                .map_err(ApiError::from_storage_error)?;
+            if record.is_tombstone {
+                continue;
+            }
+            if let Ok(doc) = serde_json::from_slice::<Value>(&record.document_body) {
+                if !req.include_deleted && is_soft_deleted(&doc) {
+                    continue;
+                }
+                let (schema_id, schema_version) = (&record.schema_id, &record.schema_version);
+                if self.is_expired(sys.schema_loader, schema_id, schema_version, &doc) {
+                    continue;
+                }
+                if PredicateFilter::matches_expr(&doc, &filter) {
+                    aggregator.push(&doc).map_err(ApiError::from_executor_error)?;
+                }
+            }
+        }
+
+        Ok(json!(aggregator.finish()))
+    }
+
+    /// Fail a write whose deadline has passed. Called only before the WAL
+    /// append, so a timed-out write is never partially durable.
+    /// Check a request against a tenant's quotas
+    ///
+    /// Writes count their serialized document against storage, inserts
+    /// add a document and deletes remove one, as does a purge of a document
+    /// that was not soft-deleted; queries are classed by their `limit`.
+    /// Explain and analyze are not metered.
+    fn check_quota(
+        quotas: &TenantQuotas,
+        tenant_id: Uuid,
+        request: &Request,
+    ) -> ApiResult<Option<QuotaAdmission>> {
+        let document_bytes = |document: &Value| {
+            serde_json::to_vec(document).map_or(0, |bytes| bytes.len() as u64)
+        };
+        let admission = match request {
+            Request::Insert(r) => quotas.check_write(tenant_id, document_bytes(&r.document), 1),
+            Request::InsertMany(r) => {
+                let bytes = r.documents.iter().map(document_bytes).sum();
+                quotas.check_write(tenant_id, bytes, r.documents.len() as i64)
+            }
+            Request::Update(r) => quotas.check_write(tenant_id, document_bytes(&r.document), 0),
+            Request::Delete(_) => quotas.check_write(tenant_id, 0, -1),
+            Request::Purge(_) => quotas.check_write(tenant_id, 0, 0),
+            Request::Query(r) => {
+                quotas.check_read(tenant_id, Some(ResultSizeClass::for_rows(r.limit as u64)))
+            }
+            Request::Aggregate(_) => quotas.check_read(tenant_id, None),
+            Request::Explain(_)
+            | Request::Analyze(_)
+            | Request::Begin
+            | Request::Commit(_)
+            | Request::Rollback(_)
+            | Request::RenameCollection(_)
+            | Request::CopyCollection(_)
+            | Request::DropCollection(_)
+            | Request::Admission => return Ok(None),
+        };
+        admission
+            .map(Some)
+            .map_err(ApiError::from_control_plane_error)
+    }
+
+    fn check_write_deadline(deadline: &Deadline) -> ApiResult<()> {
+        deadline.check(0).map_err(ApiError::from_executor_error)
+    }
+
+    /// Statistics a write of `schema_id` is recorded under: the handler's
+    /// collection, which the planner reads, and the schema's own, which
+    /// the admin statistics read
+    fn statistics_scopes<'s>(&'s self, schema_id: &'s str) -> impl Iterator<Item = &'s str> {
+        std::iter::once(self.collection.as_str())
+            .chain(Some(schema_id).filter(|schema_id| *schema_id != self.collection))
+    }
+
+    /// Fields with statistics: every single-field and compound index field
+    fn statistics_fields(index_manager: &IndexManager) -> Vec<String> {
+        let mut fields: Vec<String> = index_manager.indexed_fields().iter().cloned().collect();
+        for (_, compound_fields) in index_manager.compound_indexes() {
+            fields.extend(compound_fields.iter().cloned());
+        }
+        fields.sort();
+        fields.dedup();
+        fields
+    }
+
+    /// Describe the indexes available to the planner, with statistics for
+    /// explain estimates
+    ///
+    /// Collection statistics come from the statistics store; without them
+    /// only the live document count is known and default selectivities
+    /// apply.
+    fn index_metadata(&self, index_manager: &IndexManager, stats: &Statistics) -> IndexMetadata {
+        let mut statistics = stats
+            .planner_statistics(&self.collection)
+            .unwrap_or_else(|| IndexStatistics {
+                documents: index_manager.document_count() as u64,
+                ..Default::default()
+            });
+        for (name, _) in index_manager.compound_indexes() {
+            if let Some(entries) = index_manager.compound_entry_count(name) {
+                statistics.compound_entries.insert(name.to_string(), entries as u64);
+            }
+        }
+
+        let mut metadata =
+            IndexMetadata::with_indexes(index_manager.indexed_fields().iter().cloned());
+        for field in index_manager.indexed_fields() {
+            let options = index_manager.index_options(field);
+            if options != IndexOptions::default() {
+                metadata = metadata.with_index_options(field.clone(), options);
+            }
+        }
+        index_manager
+            .compound_indexes()
+            .fold(metadata, |metadata, (name, fields)| {
+                metadata.with_compound_index(name, fields.iter().cloned())
+            })
+            .with_statistics(statistics)
+    }
+
+    /// Build a Query AST from a QueryRequest
+    fn build_query(&self, req: &QueryRequest) -> ApiResult<Query> {
+        let mut query = Query::new(&self.collection, &req.schema_id)
+            .with_schema_version(&req.schema_version)
+            .with_limit(req.limit as u64);
+        if let Some(offset) = req.offset {
+            query = query.with_offset(offset as u64);
+        }
+        if let Some(projection) = req.select.as_deref().and_then(Projection::parse) {
+            query = query.with_projection(projection);
+        }
+
+        // Parse filter
+        query = query.with_filter(Self::parse_filter(req.filter.as_ref())?);
+
+        // Parse sort
+        if let Some(sort_str) = &req.sort {
+            let sort = if sort_str.starts_with('-') {
+                SortSpec::desc(&sort_str[1..])
+            } else {
+                SortSpec::asc(sort_str)
+            };
+            query = query.with_sort(sort);
+        }
+
+        Ok(query)
+    }
+
+    /// Parse a filter into a boolean expression
+    ///
+    /// A filter object maps fields to `{"$<op>": <value>}` conditions, and
+    /// may hold `"$and"` and `"$or"` (non-empty arrays of filter objects)
+    /// and `"$not"` (a filter object). All its entries combine with AND.
+    fn parse_filter(filter: Option<&Value>) -> ApiResult<FilterExpr> {
+        match filter.filter(|f| f.is_object()) {
+            Some(filter) => Self::parse_filter_object(filter),
+            None => Ok(FilterExpr::And(Vec::new())),
+        }
+    }
+
+    fn parse_filter_object(filter: &Value) -> ApiResult<FilterExpr> {
+        let obj = filter
+            .as_object()
+            .ok_or_else(|| ApiError::invalid_request("Filter must be an object"))?;
+        let mut children = Vec::new();
+        for (key, condition) in obj {
+            match key.as_str() {
+                "$and" | "$or" => {
+                    let branches = condition
+                        .as_array()
+                        .filter(|branches| !branches.is_empty())
+                        .ok_or_else(|| {
+                            ApiError::invalid_request(format!(
+                                "{} takes a non-empty array of filters",
+                                key
+                            ))
+                        })?
+                        .iter()
+                        .map(Self::parse_filter_object)
+                        .collect::<ApiResult<Vec<_>>>()?;
+                    children.push(match key.as_str() {
+                        "$and" => FilterExpr::And(branches),
+                        _ => FilterExpr::Or(branches),
+                    });
+                }
+                "$not" => children.push(FilterExpr::negate(Self::parse_filter_object(condition)?)),
+                other if other.starts_with('$') => {
+                    return Err(ApiError::invalid_request(format!(
+                        "Unknown logical operator: {}",
+                        other
+                    )))
+                }
+                field => {
+                    if let Some(cond_obj) = condition.as_object() {
+                        for (op, value) in cond_obj {
+                            let predicate = match op.as_str() {
+                                "$eq" => Predicate::eq(field, value.clone()),
+                                "$gte" => Predicate::gte(field, value.clone()),
+                                "$gt" => Predicate::gt(field, value.clone()),
+                                "$lte" => Predicate::lte(field, value.clone()),
+                                "$lt" => Predicate::lt(field, value.clone()),
+                                other => {
+                                    return Err(ApiError::invalid_request(format!(
+                                        "Unknown filter operator: {}",
+                                        other
+                                    )))
+                                }
+                            };
+                            children.push(predicate.into());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(FilterExpr::And(children))
+    }
+
+    /// Get offsets from index based on plan
+    ///
+    /// Offsets are in key order when the index provides the sort. Scans
+    /// are truncated to `limit + offset` only when no sort is applied and
+    /// the index serves every filter.
+    fn get_offsets_for_plan(
+        &self,
+        plan: &QueryPlan,
+        query: &Query,
+        index_manager: &IndexManager,
+    ) -> Vec<u64> {
+        let descending = match plan.sort_strategy {
+            Some(SortStrategy::Index(direction)) => Some(direction == SortDirection::Desc),
+            _ => None,
+        };
+        // A residual filter may reject scanned documents, so the scan cannot
+        // stop at the window
+        let residual = !plan.expressions.is_empty()
+            || plan
+                .predicates
+                .iter()
+                .any(|p| !FilterPushdown::is_pushed(plan, p));
+        let truncate = match plan.sort_strategy {
+            None if !residual => Some(plan.limit.saturating_add(plan.offset) as usize),
+            _ => None,
+        };
+
+        match plan.scan_type {
+            ScanType::CompoundPrefix => match (plan.compound_bounds(), descending) {
+                (Some((prefix, lower, upper)), Some(descending)) => index_manager
+                    .scan_compound_ordered(&plan.chosen_index, &prefix, lower, upper, descending),
+                (Some((prefix, lower, upper)), None) => index_manager.lookup_compound(
+                    &plan.chosen_index,
+                    &prefix,
+                    lower,
+                    upper,
+                    truncate,
+                ),
+                (None, _) => Vec::new(),
+            },
+            // Distinct offsets of every branch scan, in storage order
+            ScanType::IndexUnion => {
+                let mut offsets = std::collections::BTreeSet::new();
+                for branch in plan.union.iter().flat_map(|u| &u.branches) {
+                    offsets.extend(Self::single_field_offsets(
+                        branch.scan_type,
+                        &branch.index,
+                        &branch.predicates,
+                        None,
+                        None,
+                        index_manager,
+                    ));
+                }
+                offsets.into_iter().collect()
+            }
+            scan_type => Self::single_field_offsets(
+                scan_type,
+                &plan.chosen_index,
+                &query.predicates,
+                descending,
+                truncate,
+                index_manager,
+            ),
+        }
+    }
+
+    /// Offsets from a primary key, equality or range scan of `field`'s
+    /// index, bounded by `predicates`
+    fn single_field_offsets(
+        scan_type: ScanType,
+        field: &str,
+        predicates: &[Predicate],
+        descending: Option<bool>,
+        truncate: Option<usize>,
+        index_manager: &IndexManager,
+    ) -> Vec<u64> {
+        match scan_type {
+            ScanType::PrimaryKey => {
+                // Find PK predicate
+                for pred in predicates {
+                    if pred.field == "_id" {
+                        if let FilterOp::Eq(ref val) = pred.op {
+                            if let Some(pk) = val.as_str() {
+                                return index_manager.lookup_pk(pk);
+                            }
+                        }
+                    }
+                }
+                Vec::new()
+            }
+            ScanType::IndexedEquality => {
+                for pred in predicates {
+                    if pred.field == field {
+                        if let FilterOp::Eq(ref val) = pred.op {
+                            return index_manager.lookup_eq(field, val);
+                        }
+                    }
+                }
+                Vec::new()
+            }
+            ScanType::IndexedRange => {
+                let (lower, upper) = range_bounds(predicates, field);
+
+                match descending {
+                    Some(descending) => index_manager.scan_ordered(field, lower, upper, descending),
+                    None => index_manager.lookup_range(field, lower, upper, truncate),
+                }
+            }
+            ScanType::CompoundPrefix | ScanType::IndexUnion => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{FieldDef, Schema};
+    use serde_json::json;
+    use std::collections::{HashMap, HashSet};
+    use tempfile::TempDir;
+    
+    use crate::resource_limits::{ResourceManager, ResourceLimitsConfig};
+    use crate::backpressure::{BackpressureManager, BackpressureConfig};
+    use crate::admission_control::{AdmissionController, AdmissionControlConfig};
+    use crate::query_limits::QueryLimitsConfig;
+    use crate::planner::StatisticsConfig;
+    use crate::observability::MemoryAuditLog;
+    use crate::auth::rls::DefaultRlsEnforcer;
+    use crate::rest_api::generator::{EndpointRegistry, SchemaDef, SchemaEndpoint};
+    use crate::rest_api::RestBindings;
+
+    fn setup_test_env() -> (
+        TempDir,
+        SchemaLoader,
+        WalWriter,
+        StorageWriter,
+        StorageReader,
+        IndexManager,
+        ResourceManager,
+        BackpressureManager,
+        AdmissionController,
+        QueryLimitsConfig,
+    ) {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
+
+        // Create schema loader
+        let mut loader = SchemaLoader::new(data_dir);
+
+        let mut fields = HashMap::new();
+        fields.insert("_id".to_string(), FieldDef::required_string());
+        fields.insert("name".to_string(), FieldDef::required_string());
+        fields.insert("age".to_string(), FieldDef::optional_int());
+        let mut address = HashMap::new();
+        address.insert("city".to_string(), FieldDef::optional_string());
+        address.insert("zip".to_string(), FieldDef::optional_string());
+        fields.insert("address".to_string(), FieldDef::optional_object(address));
+
+        let schema = Schema::new("users", "v1", fields);
+        loader.register(schema).unwrap();
+
+        // Create WAL writer
+        let wal_writer = WalWriter::open(data_dir).unwrap();
+
+        // Create storage writer and reader
+        let storage_writer = StorageWriter::open(data_dir).unwrap();
+        let storage_reader = StorageReader::open_from_data_dir(data_dir).unwrap();
+
+        // Create index manager
+        let mut indexed = HashSet::new();
+        indexed.insert("age".to_string());
+        let index_manager = IndexManager::new(indexed);
+
+        // Hardening components with test defaults
+        let resource_config = ResourceLimitsConfig {
+            min_free_disk_bytes: 0, // Disable disk check for tests
+            ..Default::default()
+        };
+        let resource_manager = ResourceManager::new(resource_config, data_dir);
+        let backpressure_manager = BackpressureManager::new(BackpressureConfig::default());
+        let admission_controller = AdmissionController::new(AdmissionControlConfig::default());
+        let query_limits = QueryLimitsConfig::default();
+
+        (
+            temp_dir,
+            loader,
+            wal_writer,
+            storage_writer,
+            storage_reader,
+            index_manager,
+            resource_manager,
+            backpressure_manager,
+            admission_controller,
+            query_limits,
+        )
+    }
+
+    #[test]
+    fn test_insert_and_query_roundtrip() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        // Insert
+        let insert_req = r#"{
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": "user_1", "name": "Alice", "age": 25}
+        }"#;
+
+        let resp = handler.handle(insert_req, &mut subsystems);
+        assert!(resp.is_success(), "Insert should succeed");
+
+        // Query
+        let query_req = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"_id": {"$eq": "user_1"}},
+            "limit": 10
+        }"#;
+
+        let resp = handler.handle(query_req, &mut subsystems);
+        assert!(resp.is_success(), "Query should succeed");
+    }
+
+    #[test]
+    fn test_query_select_projects_documents() {
+        let (_temp, mut loader, mut wal, mut storage_w, _, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        let insert_req = json!({
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {
+                "_id": "user_1",
+                "name": "Alice",
+                "age": 25,
+                "address": {"city": "Paris", "zip": "75001"}
+            }
+        });
+        let resp = handler.handle(&insert_req.to_string(), &mut subsystems);
+        assert!(resp.is_success(), "Insert should succeed");
+
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        let query = |select: Value| {
+            json!({
+                "op": "query",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "filter": {"_id": {"$eq": "user_1"}},
+                "select": select,
+                "limit": 10
+            })
+            .to_string()
+        };
+
+        // Non-selected fields are omitted; _id is kept
+        let select = query(json!(["name", "address.city"]));
+        let Response::Success(resp) = handler.handle(&select, &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        assert_eq!(
+            resp.data,
+            json!([{"_id": "user_1", "name": "Alice", "address": {"city": "Paris"}}])
+        );
+
+        // Unless excluded
+        let select = query(json!(["-_id", "age"]));
+        let Response::Success(resp) = handler.handle(&select, &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        assert_eq!(resp.data, json!([{"age": 25}]));
+    }
+
+    #[test]
+    fn test_invalid_schema_rejected() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        // Insert with unknown schema
+        let insert_req = r#"{
+            "op": "insert",
+            "schema_id": "unknown",
+            "schema_version": "v1",
+            "document": {"_id": "user_1", "name": "Alice"}
+        }"#;
+
+        let resp = handler.handle(insert_req, &mut subsystems);
+        assert!(!resp.is_success());
+    }
+
+    #[test]
+    fn test_unbounded_query_rejected() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        // Query without indexed filter
+        let query_req = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"name": {"$eq": "Alice"}},
+            "limit": 10
+        }"#;
+
+        let resp = handler.handle(query_req, &mut subsystems);
+        // This should fail because "name" is not indexed
+        assert!(!resp.is_success());
+    }
+
+    #[test]
+    fn test_explain_returns_deterministic_plan() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        let explain_req = r#"{
+            "op": "explain",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"_id": {"$eq": "user_1"}},
+            "limit": 10
+        }"#;
+
+        let resp1 = handler.handle(explain_req, &mut subsystems);
+        let resp2 = handler.handle(explain_req, &mut subsystems);
+
+        // Plans should be identical
+        assert_eq!(resp1.to_json(), resp2.to_json());
+    }
+
+    #[test]
+    fn test_explain_output_shape_for_indexed_equality() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        for (id, age) in [("u1", 20), ("u2", 20), ("u3", 30), ("u4", 40)] {
+            let insert_req = json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": "User", "age": age}
+            });
+            assert!(handler.handle(&insert_req.to_string(), &mut subsystems).is_success());
+        }
+
+        // The reader only sees records present when it was opened
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+        let analyze_req = r#"{"op": "analyze", "collection": "users"}"#;
+        assert!(handler.handle(analyze_req, &mut subsystems).is_success());
+
+        let explain_req = r#"{
+            "op": "explain",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"age": {"$eq": 20}},
+            "limit": 10
+        }"#;
+        let Response::Success(resp) = handler.handle(explain_req, &mut subsystems) else {
+            panic!("Explain should succeed");
+        };
+
+        // 4 entries over 3 distinct ages -> 2 rows
+        assert_eq!(
+            resp.data,
+            json!({
+                "accepted": true,
+                "chosen_index": "age",
+                "chosen": {
+                    "scan_type": "INDEX_EQ",
+                    "index": "age",
+                    "matched_prefix": null,
+                    "estimated_rows": 2,
+                    "estimated_cost": 2,
+                    "reason": null
+                },
+                "estimated_rows_scanned": 2,
+                "estimated_rows_returned": 2,
+                "filter_pushdown": {"index": ["age eq"], "residual": []},
+                "alternatives": [{
+                    "scan_type": "COLLECTION_SCAN",
+                    "index": null,
+                    "matched_prefix": null,
+                    "estimated_rows": 4,
+                    "estimated_cost": 4,
+                    "reason": "collection scans are not permitted (Q2)"
+                }],
+                "sort": null,
+                "sort_strategy": null,
+                "sort_window": null,
+                "limit": 10,
+                "offset": 0,
+                "max_scan": 10,
+                "rejection": null
+            })
+        );
+    }
+
+    #[test]
+    fn test_explain_output_shape_for_collection_scan() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        let insert_req = r#"{
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": "u1", "name": "Alice", "age": 30}
+        }"#;
+        assert!(handler.handle(insert_req, &mut subsystems).is_success());
+
+        // "name" is not indexed: only a collection scan could answer this,
+        // so explain reports the rejection instead of failing
+        let explain_req = r#"{
+            "op": "explain",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"name": {"$eq": "Alice"}},
+            "limit": 10
+        }"#;
+        let Response::Success(resp) = handler.handle(explain_req, &mut subsystems) else {
+            panic!("Explain should succeed");
+        };
+
+        let err = crate::planner::PlannerError::unindexed_field("name");
+        assert_eq!(
+            resp.data,
+            json!({
+                "accepted": false,
+                "chosen_index": null,
+                "chosen": null,
+                "estimated_rows_scanned": null,
+                "estimated_rows_returned": null,
+                "filter_pushdown": null,
+                "alternatives": [{
+                    "scan_type": "COLLECTION_SCAN",
+                    "index": null,
+                    "matched_prefix": null,
+                    "estimated_rows": 1,
+                    "estimated_cost": 1,
+                    "reason": "filter on unindexed field 'name' needs a collection scan, which is not permitted (Q2)"
+                }],
+                "sort": null,
+                "sort_strategy": null,
+                "sort_window": null,
+                "limit": null,
+                "offset": null,
+                "max_scan": null,
+                "rejection": {
+                    "code": "AERO_QUERY_UNINDEXED_FIELD",
+                    "reason": err.message()
+                }
+            })
+        );
+
+        // The query itself is still rejected
+        let query_req = explain_req.replace("\"explain\"", "\"query\"");
+        assert!(!handler.handle(&query_req, &mut subsystems).is_success());
+    }
+
+    struct EmptyScan;
+
+    impl crate::index::StorageScan for EmptyScan {
+        fn scan_next(&mut self) -> crate::index::IndexResult<Option<DocumentInfo>> {
+            Ok(None)
+        }
+
+        fn reset(&mut self) -> crate::index::IndexResult<()> {
+            Ok(())
+        }
+
+        fn current_offset(&self) -> u64 {
+            0
+        }
+    }
+
+    #[test]
+    fn test_compound_index_serves_eq_and_range_query() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        index
+            .create_compound_index(
+                "name_age",
+                vec!["name".to_string(), "age".to_string()],
+                &mut EmptyScan,
+            )
+            .unwrap();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        for (id, name, age) in [("u1", "Alice", 20), ("u2", "Alice", 30), ("u3", "Alice", 40), ("u4", "Bob", 35)] {
+            let insert_req = json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": name, "age": age}
+            });
+            let resp = handler.handle(&insert_req.to_string(), &mut subsystems);
+            assert!(resp.is_success(), "Insert should succeed");
+        }
+
+        // The reader only sees records present when it was opened
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        // name = Alice AND age > 20 (exclusive bound honoured by the scan)
+        let query_req = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"name": {"$eq": "Alice"}, "age": {"$gt": 20}},
+            "limit": 10
+        }"#;
+        let Response::Success(resp) = handler.handle(query_req, &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        let ids: Vec<&str> = resp.data.as_array().unwrap().iter().map(|d| d["_id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["u2", "u3"]);
+
+        let explain_req = query_req.replace("\"query\"", "\"explain\"");
+        let Response::Success(resp) = handler.handle(&explain_req, &mut subsystems) else {
+            panic!("Explain should succeed");
+        };
+        assert_eq!(resp.data["chosen"]["index"], "name_age");
+        assert_eq!(resp.data["chosen"]["matched_prefix"], 2);
+    }
+
+    #[test]
+    fn test_or_and_not_filters() {
+        let (_temp, mut loader, mut wal, mut storage_w, _, index, rm, bpm, ac, ql) = setup_test_env();
+        let mut index = index.with_index_options("name", IndexOptions::new());
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        for (id, name, age) in [("u1", "Alice", 20), ("u2", "Bob", 30), ("u3", "Carol", 40), ("u4", "Alice", 50)] {
+            let insert_req = json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": name, "age": age}
+            });
+            let resp = handler.handle(&insert_req.to_string(), &mut subsystems);
+            assert!(resp.is_success(), "Insert should succeed");
+        }
+
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        let query_ids = |subsystems: &mut Subsystems<'_>, filter: Value| -> Vec<String> {
+            let query_req = json!({
+                "op": "query",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "filter": filter,
+                "limit": 10
+            });
+            let Response::Success(resp) = handler.handle(&query_req.to_string(), subsystems) else {
+                panic!("Query should succeed");
+            };
+            resp.data.as_array().unwrap().iter().map(|d| d["_id"].as_str().unwrap().to_string()).collect()
+        };
+
+        // name = Bob OR age >= 40: a union of the name and age indexes
+        let or_filter = json!({"$or": [{"name": {"$eq": "Bob"}}, {"age": {"$gte": 40}}]});
+        assert_eq!(query_ids(&mut subsystems, or_filter.clone()), vec!["u2", "u3", "u4"]);
+
+        let explain_req = json!({
+            "op": "explain",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": or_filter,
+            "limit": 10
+        });
+        let Response::Success(resp) = handler.handle(&explain_req.to_string(), &mut subsystems) else {
+            panic!("Explain should succeed");
+        };
+        assert_eq!(resp.data["chosen"]["scan_type"], "INDEX_UNION");
+        assert_eq!(resp.data["chosen_index"], "name|age");
+
+        // name = Alice AND NOT age > 30, the NOT checked on each document
+        let not_filter = json!({"name": {"$eq": "Alice"}, "$not": {"age": {"$gt": 30}}});
+        assert_eq!(query_ids(&mut subsystems, not_filter), vec!["u1"]);
+
+        // Nesting: (name = Carol OR (name = Alice AND age < 30))
+        let nested = json!({"$or": [
+            {"name": {"$eq": "Carol"}},
+            {"$and": [{"name": {"$eq": "Alice"}}, {"age": {"$lt": 30}}]}
+        ]});
+        assert_eq!(query_ids(&mut subsystems, nested), vec!["u1", "u3"]);
+
+        let bad = json!({"op": "query", "schema_id": "users", "schema_version": "v1", "filter": {"$or": []}, "limit": 10});
+        assert!(!handler.handle(&bad.to_string(), &mut subsystems).is_success());
+    }
+
+    #[test]
+    fn test_unique_violation_names_conflicting_document() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        index.create_unique_index("users_name", "name", &mut EmptyScan).unwrap();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let write = |op: &str, id: &str, name: &str| {
+            json!({
+                "op": op,
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": name, "age": 30}
+            })
+            .to_string()
+        };
+        let error = |resp: Response| match resp {
+            Response::Error(e) => e,
+            Response::Success(_) => panic!("Expected an error"),
+        };
+
+        assert!(handler.handle(&write("insert", "u1", "Alice"), &mut subsystems).is_success());
+
+        // A duplicate insert is rejected before the WAL append
+        let wal_path = _temp.path().join("wal").join("wal.log");
+        let wal_len = std::fs::metadata(&wal_path).unwrap().len();
+        let err = error(handler.handle(&write("insert", "u2", "Alice"), &mut subsystems));
+        assert_eq!(err.code, "AERO_INDEX_UNIQUE_VIOLATION");
+        assert!(err.message.contains("document 'u1'"), "{}", err.message);
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), wal_len);
+
+        // Updating another document to a taken value is rejected too
+        assert!(handler.handle(&write("insert", "u2", "Bob"), &mut subsystems).is_success());
+        let err = error(handler.handle(&write("update", "u2", "Alice"), &mut subsystems));
+        assert_eq!(err.code, "AERO_INDEX_UNIQUE_VIOLATION");
+        assert!(err.message.contains("document 'u1'"), "{}", err.message);
+        assert_eq!(subsystems.index_manager.unique_owner("name", &json!("Bob")), Some("u2"));
+
+        // A document may rewrite its own value
+        assert!(handler.handle(&write("update", "u1", "Alice"), &mut subsystems).is_success());
+    }
+
+    #[test]
+    fn test_soft_delete_filters_frees_unique_slot_and_purges() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        let mut fields = HashMap::new();
+        fields.insert("_id".to_string(), FieldDef::required_string());
+        fields.insert("name".to_string(), FieldDef::required_string());
+        fields.insert("age".to_string(), FieldDef::optional_int());
+        loader.register(Schema::new("notes", "v1", fields).with_soft_delete()).unwrap();
+        index.create_unique_index("notes_name", "name", &mut EmptyScan).unwrap();
+
+        let handler = ApiHandler::new("notes");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let insert = |id: &str, name: &str| {
+            json!({
+                "op": "insert",
+                "schema_id": "notes",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": name, "age": 1}
+            })
+            .to_string()
+        };
+        let delete = |op: &str, id: &str| {
+            json!({"op": op, "schema_id": "notes", "schema_version": "v1", "document_id": id})
+                .to_string()
+        };
+        let query = |include_deleted: bool| {
+            json!({
+                "op": "query",
+                "schema_id": "notes",
+                "schema_version": "v1",
+                "filter": {"age": {"$eq": 1}},
+                "include_deleted": include_deleted,
+                "limit": 10
+            })
+            .to_string()
+        };
+
+        assert!(handler.handle(&insert("n1", "Alice"), &mut subsystems).is_success());
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+        let Response::Success(resp) = handler.handle(&delete("delete", "n1"), &mut subsystems) else {
+            panic!("Delete should succeed");
+        };
+        assert_eq!(resp.data, json!({"deleted": "n1"}));
+
+        // The deleted document no longer holds its unique value
+        assert_eq!(subsystems.index_manager.unique_owner("name", &json!("Alice")), None);
+        assert!(handler.handle(&insert("n2", "Alice"), &mut subsystems).is_success());
+        assert_eq!(subsystems.index_manager.unique_owner("name", &json!("Alice")), Some("n2"));
+
+        // Deleting it again finds nothing
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+        assert!(!handler.handle(&delete("delete", "n1"), &mut subsystems).is_success());
+
+        // Queries skip it unless asked, and only the service role may ask
+        let Response::Success(resp) = handler.handle(&query(false), &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        assert_eq!(resp.data, json!([{"_id": "n2", "name": "Alice", "age": 1}]));
+        let Response::Success(resp) = handler.handle(&query(true), &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        let deleted: Vec<&Value> = resp.data.as_array().unwrap().iter()
+            .filter(|doc| is_soft_deleted(doc))
+            .collect();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0]["_id"], "n1");
+        let Response::Error(err) = handler.handle_as_tenant(Uuid::new_v4(), &query(true), &mut subsystems) else {
+            panic!("Tenant include_deleted should be refused");
+        };
+        assert!(err.message.contains("service role"), "{}", err.message);
+
+        // Purge removes it for good
+        let Response::Success(resp) = handler.handle(&delete("purge", "n1"), &mut subsystems) else {
+            panic!("Purge should succeed");
+        };
+        assert_eq!(resp.data, json!({"purged": "n1", "was_deleted": true}));
+        assert!(!handler.handle(&delete("purge", "n1"), &mut subsystems).is_success());
+        let Response::Success(resp) = handler.handle(&query(true), &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        assert_eq!(resp.data, json!([{"_id": "n2", "name": "Alice", "age": 1}]));
+
+        // A live document can be purged directly, freeing its value
+        let Response::Success(resp) = handler.handle(&delete("purge", "n2"), &mut subsystems) else {
+            panic!("Purge should succeed");
+        };
+        assert_eq!(resp.data, json!({"purged": "n2", "was_deleted": false}));
+        assert_eq!(subsystems.index_manager.unique_owner("name", &json!("Alice")), None);
+    }
+
+    #[test]
+    fn test_ttl_hides_expired_documents_until_swept() {
+        use crate::observability::OperationLogConfig;
+        use chrono::TimeZone;
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, _, rm, bpm, ac, ql) = setup_test_env();
+        let mut fields = HashMap::new();
+        fields.insert("_id".to_string(), FieldDef::required_string());
+        fields.insert("age".to_string(), FieldDef::optional_int());
+        let ttl = TtlConfig::after_seconds(60);
+        loader.register(Schema::new("sessions", "v1", fields).with_ttl(ttl)).unwrap();
+        let mut index = IndexManager::new(HashSet::from(["age".to_string(), EXPIRES_AT_FIELD.to_string()]));
+
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let now = Arc::new(Mutex::new(start));
+        let clock_now = now.clone();
+        let log = Arc::new(OperationLog::new(OperationLogConfig {
+            enabled: true,
+            ..OperationLogConfig::default()
+        }));
+        let handler = ApiHandler::new("sessions")
+            .with_clock(Arc::new(move || *clock_now.lock().unwrap()))
+            .with_operation_log(log.clone());
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let write = |op: &str, id: &str| {
+            json!({
+                "op": op,
+                "schema_id": "sessions",
+                "schema_version": "v1",
+                "document": {"_id": id, "age": 1}
+            })
+            .to_string()
+        };
+        let query = json!({
+            "op": "query",
+            "schema_id": "sessions",
+            "schema_version": "v1",
+            "filter": {"age": {"$eq": 1}},
+            "limit": 10
+        })
+        .to_string();
+        let ids = |response: Response| -> Vec<String> {
+            let Response::Success(resp) = response else {
+                panic!("Query should succeed");
+            };
+            resp.data.as_array().unwrap().iter()
+                .map(|doc| doc["_id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        // Inserts expire 60s after insert time
+        let expires = start.timestamp() + 60;
+        assert!(handler.handle(&write("insert", "s1"), &mut subsystems).is_success());
+        assert!(handler.handle(&write("insert", "s2"), &mut subsystems).is_success());
+        *now.lock().unwrap() = start + chrono::Duration::seconds(30);
+        assert!(handler.handle(&write("insert", "s3"), &mut subsystems).is_success());
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+        let Response::Success(resp) = handler.handle(&query, &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        assert_eq!(resp.data[0], json!({"_id": "s1", "age": 1, "_expires_at": expires}));
+
+        // An update keeps the insert's expiry
+        assert!(handler.handle(&write("update", "s1"), &mut subsystems).is_success());
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+        let Response::Success(resp) = handler.handle(&query, &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        assert!(resp.data.as_array().unwrap().contains(&json!({"_id": "s1", "age": 1, "_expires_at": expires})));
+
+        // Once expired, documents are hidden before any sweep
+        *now.lock().unwrap() = start + chrono::Duration::seconds(60);
+        assert_eq!(ids(handler.handle(&query, &mut subsystems)), vec!["s3"]);
+        assert!(!subsystems.index_manager.lookup_pk("s1").is_empty());
+        assert!(!handler.handle(&write("update", "s2"), &mut subsystems).is_success());
+
+        // Sweeps remove them in batches, and stop at live documents
+        let sweep = handler.sweep_expired(1, &mut subsystems).unwrap();
+        assert_eq!(sweep.removed, 1);
+        assert_eq!(handler.sweep_expired(10, &mut subsystems).unwrap().removed, 1);
+        assert_eq!(handler.sweep_expired(10, &mut subsystems).unwrap().removed, 0);
+        assert!(subsystems.index_manager.lookup_pk("s1").is_empty());
+        assert!(subsystems.index_manager.lookup_pk("s2").is_empty());
+        assert_eq!(ids(handler.handle(&query, &mut subsystems)), vec!["s3"]);
+
+        // Each sweep is in the operation log
+        let sweeps: Vec<Option<usize>> = log.entries().iter()
+            .filter(|entry| entry.operation == OperationType::Expire)
+            .map(|entry| entry.documents_affected)
+            .collect();
+        assert_eq!(sweeps, vec![Some(1), Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn test_request_id_in_operation_log_and_error() {
+        use crate::observability::{OperationLogConfig, OperationResult};
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        let log = Arc::new(OperationLog::new(OperationLogConfig {
+            enabled: true,
+            ..OperationLogConfig::default()
+        }));
+        let handler = ApiHandler::new("default").with_operation_log(log.clone());
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        // Missing the required name field
+        let request = json!({
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": "u1"}
+        })
+        .to_string();
+        let request_id = Uuid::new_v4();
+        let response = handler.handle_with_request_id(request_id, &request, &mut subsystems);
+
+        let Response::Error(err) = response else {
+            panic!("Insert should fail");
+        };
+        assert_eq!(err.request_id, Some(request_id));
+        let entries = log.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].request_id, Some(request_id));
+        assert_eq!(entries[0].operation, OperationType::Insert);
+        assert_eq!(entries[0].collection.as_deref(), Some("users"));
+        assert!(matches!(
+            &entries[0].result_status,
+            OperationResult::Error { code, .. } if *code == err.code
+        ));
+
+        // Without an id, an entry names no request
+        handler.handle(&request, &mut subsystems);
+        assert_eq!(log.entries()[1].request_id, None);
+    }
+
+    #[test]
+    fn test_sorted_query_pages_in_order() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, mut ql) = setup_test_env();
+        ql.max_sort_bytes = 256;
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        for (id, age) in [("u1", 30), ("u2", 10), ("u3", 50), ("u4", 20), ("u5", 40)] {
+            let insert_req = json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": "User", "age": age}
+            });
+            let resp = handler.handle(&insert_req.to_string(), &mut subsystems);
+            assert!(resp.is_success(), "Insert should succeed");
+        }
+
+        // The reader only sees records present when it was opened
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        // The age index is walked in reverse; no in-memory sort
+        let query_req = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"age": {"$gte": 0}},
+            "sort": "-age",
+            "limit": 2,
+            "offset": 1
+        }"#;
+        let Response::Success(resp) = handler.handle(query_req, &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        let ids: Vec<&str> = resp.data.as_array().unwrap().iter().map(|d| d["_id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["u5", "u1"]);
+
+        // Sorting the same range by _id is in memory, and five documents
+        // exceed max_sort_bytes
+        let in_memory = query_req.replace("-age", "_id").replace("\"limit\": 2", "\"limit\": 5");
+        let explain_req = in_memory.replace("\"query\"", "\"explain\"");
+        let Response::Success(resp) = handler.handle(&explain_req, &mut subsystems) else {
+            panic!("Explain should succeed");
+        };
+        assert_eq!(resp.data["sort_strategy"], "TOP_N");
+
+        match handler.handle(&in_memory, &mut subsystems) {
+            Response::Error(e) => assert_eq!(e.code, "AERO_SORT_MEMORY_EXCEEDED"),
+            Response::Success(_) => panic!("Expected the sort to be refused"),
+        }
+    }
+
+    #[test]
+    fn test_sort_served_by_collated_descending_index() {
+        let (_temp, mut loader, mut wal, mut storage_w, _, index, rm, bpm, ac, ql) = setup_test_env();
+        let options = IndexOptions::new()
+            .descending()
+            .with_collation(crate::index::Collation::CaseInsensitive);
+        let mut index = index.with_index_options("name", options);
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        for (id, name) in [("u1", "bob"), ("u2", "Carol"), ("u3", "alice"), ("u4", "Dave")] {
+            let insert_req = json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": name, "age": 30}
+            });
+            let resp = handler.handle(&insert_req.to_string(), &mut subsystems);
+            assert!(resp.is_success(), "Insert should succeed");
+        }
+
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        for (sort, expected) in [
+            ("-name", ["Dave", "Carol", "bob", "alice"]),
+            ("name", ["alice", "bob", "Carol", "Dave"]),
+        ] {
+            let query_req = json!({
+                "op": "query",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "sort": sort,
+                "limit": 10
+            });
+            let Response::Success(resp) = handler.handle(&query_req.to_string(), &mut subsystems) else {
+                panic!("Query should succeed");
+            };
+            let names: Vec<&str> = resp.data.as_array().unwrap().iter().map(|d| d["name"].as_str().unwrap()).collect();
+            assert_eq!(names, expected);
+        }
+
+        let explain_req = r#"{
+            "op": "explain",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "sort": "-name",
+            "limit": 10
+        }"#;
+        let Response::Success(resp) = handler.handle(explain_req, &mut subsystems) else {
+            panic!("Explain should succeed");
+        };
+        assert_eq!(resp.data["sort"], "name desc (case_insensitive)");
+        assert_eq!(resp.data["sort_strategy"], "INDEX");
+    }
+
+    #[test]
+    fn test_analyze_feeds_explain_estimates() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let config = StatisticsConfig::default();
+        let mut statistics = Statistics::open(_temp.path(), config.clone()).unwrap();
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        // 40 users over 8 distinct ages
+        for i in 0..40 {
+            let insert_req = json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": format!("u{}", i), "name": "User", "age": 20 + i % 8}
+            });
+            assert!(handler.handle(&insert_req.to_string(), &mut subsystems).is_success());
+        }
+
+        let explain_req = r#"{
+            "op": "explain",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"age": {"$eq": 21}},
+            "limit": 100
+        }"#;
+        let estimated_rows = |subsystems: &mut Subsystems<'_>| {
+            let Response::Success(resp) = handler.handle(explain_req, subsystems) else {
+                panic!("Explain should succeed");
+            };
+            resp.data["chosen"]["estimated_rows"].as_u64().unwrap()
+        };
+
+        // Never analyzed: default equality selectivity (1/10)
+        assert_eq!(estimated_rows(&mut subsystems), 4);
+
+        // The reader only sees records present when it was opened
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        let Response::Success(resp) =
+            handler.handle(r#"{"op": "analyze", "collection": "users"}"#, &mut subsystems)
+        else {
+            panic!("Analyze should succeed");
+        };
+        assert_eq!(resp.data["document_count"], 40);
+        assert_eq!(resp.data["distinct_values"]["age"], 8);
+        assert!(_temp.path().join("metadata/stats/users.json").exists());
+
+        // Analyzed: 40 entries over 8 distinct ages
+        assert_eq!(estimated_rows(&mut subsystems), 5);
+
+        // Deleting more than 20% of the analyzed documents makes the
+        // statistics stale: back to the default
+        for i in 0..9 {
+            let delete_req = json!({
+                "op": "delete",
+                "schema_id": "users",
+                "document_id": format!("u{}", i)
+            });
+            assert!(handler.handle(&delete_req.to_string(), &mut subsystems).is_success());
+        }
+        assert_eq!(subsystems.statistics.collection("users").unwrap().document_count, 31);
+        assert_eq!(estimated_rows(&mut subsystems), 4);
+
+        // Other collections cannot be analyzed by this handler
+        let resp = handler.handle(r#"{"op": "analyze", "collection": "orders"}"#, &mut subsystems);
+        assert!(!resp.is_success());
+
+        // Statistics survive a restart
+        let reloaded = Statistics::open(_temp.path(), config).unwrap();
+        assert_eq!(reloaded.collection("users").unwrap().documents_at_analyze, 40);
+    }
+
+    #[test]
+    fn test_aggregate_groups_documents() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        let users = [
+            json!({"_id": "u1", "name": "Bob", "age": 30}),
+            json!({"_id": "u2", "name": "Alice", "age": 30}),
+            json!({"_id": "u3", "name": "Alice", "age": 25}),
+            json!({"_id": "u4", "name": "Alice", "age": 30}),
+            json!({"_id": "u5", "name": "Bob"}),
+            json!({"_id": "u6", "name": "Alice"}),
+        ];
+        for user in users {
+            let insert_req = json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": user
+            });
+            assert!(handler.handle(&insert_req.to_string(), &mut subsystems).is_success());
+        }
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        let aggregate = |subsystems: &mut Subsystems<'_>, req: Value| {
+            match handler.handle(&req.to_string(), subsystems) {
+                Response::Success(resp) => Ok(resp.data),
+                Response::Error(err) => Err(Box::new(err)),
+            }
+        };
+
+        // Multi-field grouping, ordered by (name, age); missing age is null
+        let groups = aggregate(&mut subsystems, json!({
+            "op": "aggregate",
+            "collection": "users",
+            "group_by": ["name", "age"],
+            "aggregates": {"n": {"count": "*"}}
+        }))
+        .unwrap();
+        assert_eq!(
+            groups,
+            json!([
+                {"name": "Alice", "age": null, "n": 1},
+                {"name": "Alice", "age": 25, "n": 1},
+                {"name": "Alice", "age": 30, "n": 2},
+                {"name": "Bob", "age": null, "n": 1},
+                {"name": "Bob", "age": 30, "n": 1}
+            ])
+        );
+
+        // Null group keys and aggregates over a filtered subset
+        let groups = aggregate(&mut subsystems, json!({
+            "op": "aggregate",
+            "collection": "users",
+            "filter": {"name": {"$eq": "Alice"}},
+            "group_by": ["age"],
+            "aggregates": {"n": {"count": "*"}, "with_age": {"count": "age"}, "total": {"sum": "age"}}
+        }))
+        .unwrap();
+        assert_eq!(
+            groups,
+            json!([
+                {"age": null, "n": 1, "with_age": 0, "total": null},
+                {"age": 25, "n": 1, "with_age": 1, "total": 25},
+                {"age": 30, "n": 2, "with_age": 2, "total": 60}
+            ])
+        );
+        assert_eq!(rm.memory_tracker().current(), 0);
+
+        // Invalid aggregates and other collections are rejected
+        for req in [
+            json!({"op": "aggregate", "collection": "users", "group_by": ["age"], "aggregates": {"x": {"median": "age"}}}),
+            json!({"op": "aggregate", "collection": "orders", "group_by": ["age"], "aggregates": {"n": {"count": "*"}}}),
+        ] {
+            let err = aggregate(&mut subsystems, req).unwrap_err();
+            assert_eq!(err.code, "AERO_INVALID_REQUEST");
+        }
+
+        // More groups than max_result_set_docs
+        let two_groups = QueryLimitsConfig {
+            max_result_set_docs: 2,
+            ..Default::default()
+        };
+        subsystems.query_limits = &two_groups;
+        let err = aggregate(&mut subsystems, json!({
+            "op": "aggregate",
+            "collection": "users",
+            "group_by": ["age"],
+            "aggregates": {"n": {"count": "*"}}
+        }))
+        .unwrap_err();
+        assert_eq!(err.code, "AERO_EXECUTION_LIMIT");
+        assert_eq!(rm.memory_tracker().current(), 0);
+    }
+
+    #[test]
+    fn test_request_timeouts() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        // A deadline that has always passed
+        let expired = QueryLimitsConfig {
+            max_execution_ms: 0,
+            ..Default::default()
+        };
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        let insert = |id: &str| {
+            json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": "User", "age": 30}
+            })
+            .to_string()
+        };
+        let query_req = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"age": {"$eq": 30}},
+            "limit": 10
+        }"#;
+        let error_code = |resp: Response| match resp {
+            Response::Error(e) => e.code,
+            Response::Success(_) => panic!("Expected an error"),
+        };
+
+        // A requested timeout above the cap is rejected
+        let over_cap = query_req.replace("\"limit\": 10", "\"limit\": 10, \"timeout_ms\": 300001");
+        assert_eq!(
+            error_code(handler.handle(&over_cap, &mut subsystems)),
+            "AERO_INVALID_REQUEST"
+        );
+
+        assert!(handler.handle(&insert("u1"), &mut subsystems).is_success());
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        subsystems.query_limits = &expired;
+
+        // Queries stop at the first batch boundary
+        assert_eq!(
+            error_code(handler.handle(query_req, &mut subsystems)),
+            "AERO_QUERY_TIMEOUT"
+        );
+
+        // Writes time out before the WAL append: nothing is durable
+        let wal_bytes = || -> u64 {
+            std::fs::read_dir(_temp.path().join("wal"))
+                .unwrap()
+                .map(|entry| entry.unwrap().metadata().unwrap().len())
+                .sum()
+        };
+        let before = wal_bytes();
+        assert_eq!(
+            error_code(handler.handle(&insert("u2"), &mut subsystems)),
+            "AERO_QUERY_TIMEOUT"
+        );
+        assert!(subsystems.index_manager.lookup_pk("u2").is_empty());
+        assert_eq!(wal_bytes(), before);
+
+        // A per-request timeout overrides the (expired) default
+        let with_timeout = query_req.replace("\"limit\": 10", "\"limit\": 10, \"timeout_ms\": 60000");
+        let Response::Success(resp) = handler.handle(&with_timeout, &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        assert_eq!(resp.data.as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_replica_gating() {
+        use crate::replication::ReplicaFreshness;
+        use std::sync::Arc;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        let insert = |id: &str| {
+            json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": "User", "age": 30}
+            })
+            .to_string()
+        };
+        let query = |max_staleness_ms: Option<u64>| {
+            json!({
+                "op": "query",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "filter": {"age": {"$eq": 30}},
+                "limit": 10,
+                "max_staleness_ms": max_staleness_ms
+            })
+            .to_string()
+        };
+        let error = |resp: Response| match resp {
+            Response::Error(e) => e,
+            Response::Success(_) => panic!("Expected an error"),
+        };
+
+        assert!(ApiHandler::new("users").handle(&insert("u1"), &mut subsystems).is_success());
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let fresh = ApiHandler::new("users").with_replica_gate(ReplicaGate::replica(
+            "db-primary:7000",
+            Arc::new(ReplicaFreshness::new(now_ms)),
+        ));
+        let stale = ApiHandler::new("users").with_replica_gate(ReplicaGate::replica(
+            "db-primary:7000",
+            Arc::new(ReplicaFreshness::new(now_ms - 120_000)),
+        ));
+
+        // Writes are refused, naming the primary
+        let err = error(fresh.handle(&insert("u2"), &mut subsystems));
+        assert_eq!(err.code, "AERO_NOT_PRIMARY");
+        assert!(err.message.contains("db-primary:7000"));
+        assert!(subsystems.index_manager.lookup_pk("u2").is_empty());
+
+        // A fresh replica serves the read
+        let Response::Success(resp) = fresh.handle(&query(Some(60_000)), &mut subsystems) else {
+            panic!("Query should succeed");
+        };
+        assert_eq!(resp.data.as_array().unwrap().len(), 1);
+
+        // A stale one refuses it, unless the read is unbounded
+        let err = error(stale.handle(&query(Some(60_000)), &mut subsystems));
+        assert_eq!(err.code, "AERO_REPLICA_TOO_STALE");
+        assert!(stale.handle(&query(None), &mut subsystems).is_success());
+    }
+
+    #[test]
+    fn test_serialization_enforced() {
+        // This test verifies the lock exists; actual blocking tested differently
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        // Sequential operations should succeed
+        let insert1 = r#"{
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": "user_1", "name": "Alice"}
+        }"#;
+
+        let insert2 = r#"{
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": "user_2", "name": "Bob"}
+        }"#;
+
+        let resp1 = handler.handle(insert1, &mut subsystems);
+        let resp2 = handler.handle(insert2, &mut subsystems);
+
+        assert!(resp1.is_success());
+        assert!(resp2.is_success());
+    }
+
+    #[test]
+    fn test_corruption_surfaced() {
+        // Corruption is surfaced when storage/WAL returns error
+        // This is implicitly tested via pass-through errors
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        // Insert a document - this confirms error propagation works
+        let insert_req = r#"{
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": "user_1", "name": "Alice"}
+        }"#;
+
+        let resp = handler.handle(insert_req, &mut subsystems);
+        assert!(resp.is_success());
+    }
+
+    #[test]
+    fn test_saturated_writes_leave_reads_admitted() {
+        use crate::admission_control::OperationClass;
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, _, ql) =
+            setup_test_env();
+        let ac = AdmissionController::new(AdmissionControlConfig {
+            max_concurrent_writes: 1,
+            max_queued_writes: 0,
+            ..Default::default()
+        });
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let insert = r#"{
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": "user_1", "name": "Alice"}
+        }"#;
+        let query = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"_id": {"$eq": "user_1"}},
+            "limit": 10
+        }"#;
+
+        // Saturate the write class
+        let held = ac.acquire(OperationClass::Write).unwrap();
+
+        assert!(handler.handle(query, &mut subsystems).is_success());
+
+        let resp: Value = serde_json::from_str(&handler.handle(insert, &mut subsystems).to_json())
+            .unwrap();
+        assert_eq!(resp["code"], "AERO_ADMISSION_REJECTED");
+        assert!(resp["retry_after_ms"].as_u64().unwrap() >= 1);
+
+        // Diagnostics bypass admission
+        let stats = match handler.handle(r#"{"op": "admission"}"#, &mut subsystems) {
+            Response::Success(r) => r.data,
+            Response::Error(e) => panic!("admission stats refused: {}", e.message),
+        };
+        assert_eq!(stats["writes"]["in_flight"], 1);
+        assert_eq!(stats["writes"]["rejected"], 1);
+        assert_eq!(stats["reads"]["in_flight"], 0);
+
+        drop(held);
+        assert!(handler.handle(insert, &mut subsystems).is_success());
+    }
+
+    #[test]
+    fn test_saturated_operations_are_too_busy() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, _, ql) =
+            setup_test_env();
+        let ac = AdmissionController::new(AdmissionControlConfig {
+            max_concurrent_operations: 1,
+            max_queue_depth: 0,
+            ..Default::default()
+        });
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let query = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"_id": {"$eq": "user_1"}},
+            "limit": 10
+        }"#;
+
+        assert!(handler.handle(query, &mut subsystems).is_success());
+
+        // Take the only slot
+        let held = ac.try_admit().unwrap();
+        let resp: Value = serde_json::from_str(&handler.handle(query, &mut subsystems).to_json())
+            .unwrap();
+        assert_eq!(resp["code"], "AERO_TOO_BUSY");
+        assert!(resp["retry_after_ms"].as_u64().unwrap() >= 1);
+
+        // Diagnostics take no slot
+        let stats = match handler.handle(r#"{"op": "admission"}"#, &mut subsystems) {
+            Response::Success(r) => r.data,
+            Response::Error(e) => panic!("admission stats refused: {}", e.message),
+        };
+        assert_eq!(stats["operations"]["in_flight"], 1);
+        assert_eq!(stats["operations"]["rejected"], 1);
+        // The read permit taken before the rejection was released
+        assert_eq!(stats["reads"]["in_flight"], 0);
+
+        drop(held);
+        assert!(handler.handle(query, &mut subsystems).is_success());
+    }
+
+    #[test]
+    fn test_tenant_concurrency_limit_spares_other_tenants() {
+        use crate::control_plane::Quotas;
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, _, ql) =
+            setup_test_env();
+        let ac = AdmissionController::new(AdmissionControlConfig {
+            max_queued_per_tenant: 0,
+            ..Default::default()
+        });
+        let noisy = Uuid::new_v4();
+        let quiet = Uuid::new_v4();
+        let quotas = Arc::new(TenantQuotas::new(Quotas::free()));
+        quotas.set_quotas(
+            noisy,
+            Quotas {
+                max_concurrent_operations: 1,
+                ..Quotas::free()
+            },
+        );
+
+        let handler = ApiHandler::new("users").with_tenant_quotas(quotas.clone());
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let insert = |id: &str| {
+            json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": "Alice", "age": 30}
+            })
+            .to_string()
+        };
+
+        // The noisy tenant's only slot is taken by an operation in flight
+        let held = ac.try_admit_tenant(noisy, 1).unwrap();
+        let resp: Value = serde_json::from_str(
+            &handler.handle_as_tenant(noisy, &insert("u1"), &mut subsystems).to_json(),
+        )
+        .unwrap();
+        assert_eq!(resp["code"], "AERO_TOO_BUSY");
+        assert!(resp["message"].as_str().unwrap().contains(&noisy.to_string()));
+        assert_eq!(quotas.usage(noisy).admission_rejections, 1);
+        assert_eq!(quotas.usage(noisy).write_ops, 0);
+
+        // Another tenant's requests still proceed
+        assert!(handler.handle_as_tenant(quiet, &insert("u2"), &mut subsystems).is_success());
+        assert_eq!(quotas.usage(quiet).write_ops, 1);
+        assert_eq!(quotas.usage(quiet).admission_rejections, 0);
+
+        // The noisy tenant is admitted again once its slot frees
+        drop(held);
+        assert!(handler.handle_as_tenant(noisy, &insert("u3"), &mut subsystems).is_success());
+        assert_eq!(ac.tenant_stats(noisy).unwrap().in_flight, 0);
+    }
+
+    #[test]
+    fn test_tenant_document_quota() {
+        use crate::control_plane::Quotas;
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) =
+            setup_test_env();
+        let tenant = Uuid::new_v4();
+        let quotas = Arc::new(TenantQuotas::new(Quotas::free()));
+        quotas.set_quotas(
+            tenant,
+            Quotas {
+                max_documents: 2,
+                ..Quotas::free()
+            },
+        );
+
+        let handler = ApiHandler::new("users").with_tenant_quotas(quotas.clone());
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let insert = |id: &str| {
+            json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": "Alice"}
+            })
+            .to_string()
+        };
+
+        for id in ["user_1", "user_2"] {
+            assert!(handler.handle_as_tenant(tenant, &insert(id), &mut subsystems).is_success());
+        }
+        // A failed write is not metered
+        assert!(!handler.handle_as_tenant(tenant, &insert("user_1"), &mut subsystems).is_success());
+        let stored = subsystems.storage_writer.current_offset();
+
+        let resp: Value = serde_json::from_str(
+            &handler.handle_as_tenant(tenant, &insert("user_3"), &mut subsystems).to_json(),
+        )
+        .unwrap();
+        assert_eq!(resp["code"], "QUOTA_EXCEEDED");
+        assert!(resp["message"].as_str().unwrap().contains("documents"));
+        assert!(subsystems.index_manager.lookup_pk("user_3").is_empty());
+
+        let query = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"_id": {"$eq": "user_1"}},
+            "limit": 10
+        }"#;
+        assert!(handler.handle_as_tenant(tenant, query, &mut subsystems).is_success());
+
+        let usage = quotas.usage(tenant);
+        assert_eq!(usage.documents, 2);
+        assert_eq!(usage.write_ops, 2);
+        assert_eq!(usage.read_ops, 1);
+        assert_eq!(usage.storage_bytes, stored);
+        assert_eq!(usage.soft_quota_events.get("documents"), Some(&1));
+
+        // Requests without a tenant are not metered
+        assert!(handler.handle(&insert("user_3"), &mut subsystems).is_success());
+        assert_eq!(quotas.usage(tenant).write_ops, 2);
+    }
+
+    #[test]
+    fn test_suspended_tenant_is_fenced_until_resumed() {
+        use crate::control_plane::{IsolationModel, Plan, Tenant};
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) =
+            setup_test_env();
+        let registry = Arc::new(TenantRegistry::new());
+        let tenant = Tenant::new(
+            "late-payer".to_string(),
+            Plan::Free,
+            "local".to_string(),
+            IsolationModel::Schema,
+        );
+        let tenant_id = tenant.tenant_id;
+        registry.insert(tenant).unwrap();
+        registry.activate(tenant_id).unwrap();
+
+        let handler = ApiHandler::new("users").with_tenant_registry(registry.clone());
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let insert = |id: &str| {
+            json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": "Alice", "age": 30}
+            })
+            .to_string()
+        };
+        let query = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"age": {"$eq": 30}},
+            "limit": 10
+        }"#;
+        let code = |response: Response| -> Value {
+            serde_json::from_str::<Value>(&response.to_json()).unwrap()["code"].clone()
+        };
+
+        assert!(handler.handle_as_tenant(tenant_id, &insert("u1"), &mut subsystems).is_success());
+
+        // Writes are refused and nothing is written; reads are still served
+        registry.suspend(tenant_id, false).unwrap();
+        let stored = subsystems.storage_writer.current_offset();
+        let refused = handler.handle_as_tenant(tenant_id, &insert("u2"), &mut subsystems);
+        assert_eq!(code(refused), "TENANT_SUSPENDED");
+        assert_eq!(subsystems.storage_writer.current_offset(), stored);
+        assert!(handler.handle_as_tenant(tenant_id, query, &mut subsystems).is_success());
+
+        // A suspension fencing reads refuses queries too
+        registry.suspend(tenant_id, true).unwrap();
+        let refused = handler.handle_as_tenant(tenant_id, query, &mut subsystems);
+        assert_eq!(code(refused), "TENANT_SUSPENDED");
+
+        // Other callers are not fenced, and resuming restores access
+        assert!(handler.handle(&insert("u3"), &mut subsystems).is_success());
+        registry.resume(tenant_id).unwrap();
+        assert!(handler.handle_as_tenant(tenant_id, &insert("u2"), &mut subsystems).is_success());
+        assert!(handler.handle_as_tenant(tenant_id, query, &mut subsystems).is_success());
+    }
+
+    fn call(handler: &ApiHandler, sys: &mut Subsystems<'_>, req: Value) -> Value {
+        serde_json::from_str(&handler.handle(&req.to_string(), sys).to_json()).unwrap()
+    }
+
+    #[test]
+    fn test_committed_transaction_is_atomic() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        index.create_unique_index("users_name", "name", &mut EmptyScan).unwrap();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let insert = |tx_id: &Value, id: &str, name: &str| {
+            json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": name},
+                "tx_id": tx_id
+            })
+        };
+        let wal_path = _temp.path().join("wal").join("wal.log");
+        let wal_len = || std::fs::metadata(&wal_path).map_or(0, |m| m.len());
+
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        let resp = call(&handler, &mut subsystems, insert(&tx_id, "u1", "Alice"));
+        assert_eq!(resp["data"]["writes"], 1, "{}", resp);
+        let resp = call(&handler, &mut subsystems, insert(&tx_id, "u2", "Bob"));
+        assert_eq!(resp["data"]["writes"], 2, "{}", resp);
+
+        // Nothing is written until commit
+        let before = wal_len();
+        assert!(subsystems.index_manager.lookup_pk("u1").is_empty());
+
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": tx_id}));
+        assert_eq!(resp["data"]["writes"], 2, "{}", resp);
+        assert!(wal_len() > before);
+        assert_eq!(subsystems.index_manager.lookup_pk("u1").len(), 1);
+        assert_eq!(subsystems.index_manager.lookup_pk("u2").len(), 1);
+
+        // A commit that fails its unique check writes none of its writes
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        call(&handler, &mut subsystems, insert(&tx_id, "u3", "Carol"));
+        call(&handler, &mut subsystems, insert(&tx_id, "u4", "Alice"));
+        let before = wal_len();
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": tx_id}));
+        assert_eq!(resp["code"], "AERO_INDEX_UNIQUE_VIOLATION");
+        assert_eq!(wal_len(), before);
+        assert!(subsystems.index_manager.lookup_pk("u3").is_empty());
+
+        // The reader only sees records present when it was opened
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+
+        // Deleting a document in the same transaction frees its value
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        call(
+            &handler,
+            &mut subsystems,
+            json!({"op": "delete", "schema_id": "users", "document_id": "u1", "tx_id": tx_id}),
+        );
+        call(&handler, &mut subsystems, insert(&tx_id, "u5", "Alice"));
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": tx_id}));
+        assert_eq!(resp["data"]["writes"], 2, "{}", resp);
+        assert!(subsystems.index_manager.lookup_pk("u1").is_empty());
+        assert_eq!(subsystems.index_manager.unique_owner("name", &json!("Alice")), Some("u5"));
+    }
+
+    fn insert_many(documents: Value, continue_on_error: bool) -> Value {
+        json!({
+            "op": "insert_many",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "documents": documents,
+            "continue_on_error": continue_on_error
+        })
+    }
+
+    #[test]
+    fn test_insert_many_inserts_every_document() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+
+        let documents = json!([
+            {"_id": "u1", "name": "Alice", "age": 30},
+            {"_id": "u2", "name": "Bob", "age": 25},
+            {"_id": "u3", "name": "Carol"}
+        ]);
+        let resp = call(&handler, &mut subsystems, insert_many(documents, false));
+        assert_eq!(resp["data"]["inserted"], 3, "{}", resp);
+        assert_eq!(resp["data"]["failed"], 0);
+        assert_eq!(resp["data"]["results"][1], json!({"index": 1, "inserted": "u2"}));
+        for id in ["u1", "u2", "u3"] {
+            assert_eq!(subsystems.index_manager.lookup_pk(id).len(), 1);
+        }
+        assert_eq!(subsystems.index_manager.lookup_eq("age", &json!(25)).len(), 1);
+    }
+
+    #[test]
+    fn test_insert_many_continue_on_error_reports_rejected_documents() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        index.create_unique_index("users_name", "name", &mut EmptyScan).unwrap();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
 
-        // Create WAL writer
-        let wal_writer = WalWriter::open(data_dir).unwrap();
+        let documents = json!([
+            {"_id": "u1", "name": "Alice"},
+            {"_id": "u2"},
+            {"_id": "u3", "name": "Alice"},
+            {"_id": "u1", "name": "Dave"},
+            {"_id": "u4", "name": "Bob"}
+        ]);
+        let resp = call(&handler, &mut subsystems, insert_many(documents, true));
+        let data = &resp["data"];
+        assert_eq!(data["inserted"], 2, "{}", resp);
+        assert_eq!(data["failed"], 3);
+        assert_eq!(data["results"][0]["inserted"], "u1");
+        assert_eq!(data["results"][1]["index"], 1);
+        assert!(data["results"][1]["error"]["code"].is_string());
+        assert_eq!(data["results"][2]["error"]["code"], "AERO_INDEX_UNIQUE_VIOLATION");
+        assert!(data["results"][3]["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Duplicate _id"));
+        assert_eq!(data["results"][4]["inserted"], "u4");
 
-        // Create storage writer and reader
-        let storage_writer = StorageWriter::open(data_dir).unwrap();
-        let storage_reader = StorageReader::open_from_data_dir(data_dir).unwrap();
+        assert_eq!(subsystems.index_manager.unique_owner("name", &json!("Alice")), Some("u1"));
+        assert!(subsystems.index_manager.lookup_pk("u3").is_empty());
+        assert_eq!(subsystems.index_manager.lookup_pk("u4").len(), 1);
+    }
 
-        // Create index manager
-        let mut indexed = HashSet::new();
-        indexed.insert("age".to_string());
-        let index_manager = IndexManager::new(indexed);
+    #[test]
+    fn test_insert_many_fail_fast_writes_nothing() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        index.create_unique_index("users_name", "name", &mut EmptyScan).unwrap();
 
-        // Hardening components with test defaults
-        let resource_config = ResourceLimitsConfig {
-            min_free_disk_bytes: 0, // Disable disk check for tests
-            ..Default::default()
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
         };
-        let resource_manager = ResourceManager::new(resource_config, data_dir);
-        let backpressure_manager = BackpressureManager::new(BackpressureConfig::default());
-        let admission_controller = AdmissionController::new(AdmissionControlConfig::default());
-        let query_limits = QueryLimitsConfig::default();
+        let wal_path = _temp.path().join("wal").join("wal.log");
+        let wal_len = || std::fs::metadata(&wal_path).map_or(0, |m| m.len());
+        let before = wal_len();
 
-        (
-            temp_dir,
-            loader,
-            wal_writer,
-            storage_writer,
-            storage_reader,
-            index_manager,
-            resource_manager,
-            backpressure_manager,
-            admission_controller,
-            query_limits,
-        )
+        // The third document conflicts with the first
+        let documents = json!([
+            {"_id": "u1", "name": "Alice"},
+            {"_id": "u2", "name": "Bob"},
+            {"_id": "u3", "name": "Alice"}
+        ]);
+        let resp = call(&handler, &mut subsystems, insert_many(documents, false));
+        assert_eq!(resp["code"], "AERO_INDEX_UNIQUE_VIOLATION", "{}", resp);
+        assert!(resp["message"].as_str().unwrap().starts_with("documents[2]: "));
+        assert_eq!(wal_len(), before);
+        for id in ["u1", "u2", "u3"] {
+            assert!(subsystems.index_manager.lookup_pk(id).is_empty());
+        }
     }
 
     #[test]
-    fn test_insert_and_query_roundtrip() {
-        let (_temp, loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+    fn test_rolled_back_transaction_leaves_no_trace() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
 
         let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
         let mut subsystems = Subsystems {
-            schema_loader: &loader,
+            schema_loader: &mut loader,
             wal_writer: &mut wal,
             storage_writer: &mut storage_w,
             storage_reader: &mut storage_r,
             index_manager: &mut index,
+            statistics: &mut statistics,
             resource_manager: &rm,
             backpressure_manager: &bpm,
             admission_controller: &ac,
             query_limits: &ql,
         };
-        };
+        let wal_path = _temp.path().join("wal").join("wal.log");
+        let wal_len = || std::fs::metadata(&wal_path).map_or(0, |m| m.len());
+        let before = wal_len();
 
-        // Insert
-        let insert_req = r#"{
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        let resp = call(&handler, &mut subsystems, json!({
             "op": "insert",
             "schema_id": "users",
             "schema_version": "v1",
-            "document": {"_id": "user_1", "name": "Alice", "age": 25}
-        }"#;
-
-        let resp = handler.handle(insert_req, &mut subsystems);
-        assert!(resp.is_success(), "Insert should succeed");
+            "document": {"_id": "u1", "name": "Alice"},
+            "tx_id": tx_id
+        }));
+        assert_eq!(resp["data"]["buffered"], "u1", "{}", resp);
 
-        // Query
-        let query_req = r#"{
-            "op": "query",
+        // A buffered write is still schema-validated
+        let resp = call(&handler, &mut subsystems, json!({
+            "op": "insert",
             "schema_id": "users",
             "schema_version": "v1",
-            "filter": {"_id": {"$eq": "user_1"}},
-            "limit": 10
-        }"#;
+            "document": {"_id": "u2"},
+            "tx_id": tx_id
+        }));
+        assert_eq!(resp["status"], "error");
 
-        let resp = handler.handle(query_req, &mut subsystems);
-        assert!(resp.is_success(), "Query should succeed");
+        let resp = call(&handler, &mut subsystems, json!({"op": "rollback", "tx_id": tx_id}));
+        assert_eq!(resp["data"]["writes"], 1, "{}", resp);
+        assert_eq!(wal_len(), before);
+
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": tx_id}));
+        assert_eq!(resp["code"], "AERO_TRANSACTION_NOT_FOUND");
+        assert!(subsystems.index_manager.lookup_pk("u1").is_empty());
     }
 
     #[test]
-    fn test_invalid_schema_rejected() {
-        let (_temp, loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+    fn test_expired_transaction_refused() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
 
-        let handler = ApiHandler::new("users");
+        let handler = ApiHandler::new("users").with_transaction_timeout(Duration::ZERO);
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
         let mut subsystems = Subsystems {
-            schema_loader: &loader,
+            schema_loader: &mut loader,
             wal_writer: &mut wal,
             storage_writer: &mut storage_w,
             storage_reader: &mut storage_r,
             index_manager: &mut index,
+            statistics: &mut statistics,
             resource_manager: &rm,
             backpressure_manager: &bpm,
             admission_controller: &ac,
             query_limits: &ql,
         };
-        };
 
-        // Insert with unknown schema
-        let insert_req = r#"{
-            "op": "insert",
-            "schema_id": "unknown",
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        std::thread::sleep(Duration::from_millis(2));
+
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": tx_id}));
+        assert_eq!(resp["code"], "AERO_TRANSACTION_EXPIRED");
+    }
+
+    fn put(op: &str, id: &str, name: &str, tx_id: Option<&Value>) -> Value {
+        let mut req = json!({
+            "op": op,
+            "schema_id": "users",
             "schema_version": "v1",
-            "document": {"_id": "user_1", "name": "Alice"}
-        }"#;
+            "document": {"_id": id, "name": name, "age": 30}
+        });
+        if let Some(tx_id) = tx_id {
+            req["txn"] = tx_id.clone();
+        }
+        req
+    }
 
-        let resp = handler.handle(insert_req, &mut subsystems);
-        assert!(!resp.is_success());
+    /// Names of the documents a query returned, sorted
+    fn names(resp: &Value) -> Vec<&str> {
+        let docs = resp["data"].as_array().unwrap_or_else(|| panic!("{}", resp));
+        let mut names: Vec<_> = docs.iter().map(|d| d["name"].as_str().unwrap()).collect();
+        names.sort_unstable();
+        names
     }
 
     #[test]
-    fn test_unbounded_query_rejected() {
-        let (_temp, loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+    fn test_conflicting_transactions_first_committer_wins() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
 
         let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
         let mut subsystems = Subsystems {
-            schema_loader: &loader,
+            schema_loader: &mut loader,
             wal_writer: &mut wal,
             storage_writer: &mut storage_w,
             storage_reader: &mut storage_r,
             index_manager: &mut index,
+            statistics: &mut statistics,
             resource_manager: &rm,
             backpressure_manager: &bpm,
             admission_controller: &ac,
             query_limits: &ql,
         };
-        };
+        call(&handler, &mut subsystems, put("insert", "u1", "Alice", None));
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
 
-        // Query without indexed filter
-        let query_req = r#"{
+        let first = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        let second = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        let other = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        call(&handler, &mut subsystems, put("update", "u1", "First", Some(&first)));
+        call(&handler, &mut subsystems, put("update", "u1", "Second", Some(&second)));
+        call(&handler, &mut subsystems, put("insert", "u2", "Bob", Some(&other)));
+
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": first}));
+        assert_eq!(resp["data"]["writes"], 1, "{}", resp);
+
+        // The second writer of u1 began before the first committed
+        let wal_path = _temp.path().join("wal").join("wal.log");
+        let before = std::fs::metadata(&wal_path).unwrap().len();
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": second}));
+        assert_eq!(resp["code"], "AERO_TRANSACTION_CONFLICT", "{}", resp);
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), before);
+
+        // A transaction touching other documents commits
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": other}));
+        assert_eq!(resp["data"]["writes"], 1, "{}", resp);
+
+        // So does one that began after the winning commit
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        call(&handler, &mut subsystems, put("update", "u1", "Third", Some(&tx_id)));
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": tx_id}));
+        assert_eq!(resp["data"]["writes"], 1, "{}", resp);
+    }
+
+    #[test]
+    fn test_transaction_reads_from_its_snapshot() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        call(&handler, &mut subsystems, put("insert", "u1", "Alice", None));
+        call(&handler, &mut subsystems, put("insert", "u2", "Bob", None));
+        let mut storage_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut storage_r;
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        let query = json!({
             "op": "query",
             "schema_id": "users",
             "schema_version": "v1",
-            "filter": {"name": {"$eq": "Alice"}},
-            "limit": 10
-        }"#;
+            "filter": {"age": {"$gte": 0}},
+            "limit": 10,
+            "txn": tx_id
+        });
+        assert_eq!(names(&call(&handler, &mut subsystems, query.clone())), ["Alice", "Bob"]);
 
-        let resp = handler.handle(query_req, &mut subsystems);
-        // This should fail because "name" is not indexed
-        assert!(!resp.is_success());
+        // Later commits change nothing the transaction reads
+        call(&handler, &mut subsystems, put("update", "u1", "Zed", None));
+        call(&handler, &mut subsystems, put("insert", "u3", "Carol", None));
+        call(
+            &handler,
+            &mut subsystems,
+            json!({"op": "delete", "schema_id": "users", "document_id": "u2"}),
+        );
+        assert_eq!(names(&call(&handler, &mut subsystems, query.clone())), ["Alice", "Bob"]);
+
+        // Outside the transaction they are visible
+        let mut latest_r = StorageReader::open_from_data_dir(_temp.path()).unwrap();
+        subsystems.storage_reader = &mut latest_r;
+        let mut latest = query.clone();
+        latest.as_object_mut().unwrap().remove("txn");
+        assert_eq!(names(&call(&handler, &mut subsystems, latest)), ["Carol", "Zed"]);
+
+        let resp = call(&handler, &mut subsystems, json!({"op": "rollback", "tx_id": tx_id}));
+        assert_eq!(resp["data"]["writes"], 0, "{}", resp);
+        let resp = call(&handler, &mut subsystems, query);
+        assert_eq!(resp["code"], "AERO_TRANSACTION_NOT_FOUND");
     }
 
     #[test]
-    fn test_explain_returns_deterministic_plan() {
-        let (_temp, loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+    fn test_idle_transactions_are_discarded() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
 
-        let handler = ApiHandler::new("users");
+        let handler = ApiHandler::new("users").with_transaction_timeout(Duration::from_millis(50));
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
         let mut subsystems = Subsystems {
-            schema_loader: &loader,
+            schema_loader: &mut loader,
             wal_writer: &mut wal,
             storage_writer: &mut storage_w,
             storage_reader: &mut storage_r,
             index_manager: &mut index,
+            statistics: &mut statistics,
             resource_manager: &rm,
             backpressure_manager: &bpm,
             admission_controller: &ac,
             query_limits: &ql,
         };
+        let memory = Arc::clone(rm.memory_tracker());
+        let idle = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        call(&handler, &mut subsystems, put("insert", "u1", "Alice", Some(&idle)));
+        assert!(memory.current() > 0);
+
+        // The next begin sweeps it, releasing its buffered writes
+        std::thread::sleep(Duration::from_millis(60));
+        call(&handler, &mut subsystems, json!({"op": "begin"}));
+        assert_eq!(memory.current(), 0);
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": idle}));
+        assert_eq!(resp["code"], "AERO_TRANSACTION_NOT_FOUND");
+        assert!(subsystems.index_manager.lookup_pk("u1").is_empty());
+
+        // A transaction over its size cap is refused the write
+        let handler = ApiHandler::new("users").with_max_transaction_bytes(64);
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        let resp = call(&handler, &mut subsystems, put("insert", "u2", &"x".repeat(64), Some(&tx_id)));
+        assert_eq!(resp["code"], "AERO_TRANSACTION_TOO_LARGE");
+    }
+
+    #[test]
+    fn test_crash_between_wal_append_and_apply_recovers_batch() {
+        use crate::recovery::WalReplayer;
+        use crate::wal::WalReader;
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
         };
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        call(&handler, &mut subsystems, put("insert", "u1", "Alice", Some(&tx_id)));
+        call(&handler, &mut subsystems, put("insert", "u2", "Bob", Some(&tx_id)));
+        let applied_from = subsystems.storage_writer.current_offset();
+        let storage_path = subsystems.storage_writer.path().to_path_buf();
+        let resp = call(&handler, &mut subsystems, json!({"op": "commit", "tx_id": tx_id}));
+        assert_eq!(resp["data"]["writes"], 2, "{}", resp);
+        drop(storage_w);
 
-        let explain_req = r#"{
-            "op": "explain",
+        // Crash after the batch was appended, before storage saw any of it
+        let file = std::fs::OpenOptions::new().write(true).open(&storage_path).unwrap();
+        file.set_len(applied_from).unwrap();
+        drop(file);
+
+        let mut storage = StorageWriter::open(_temp.path()).unwrap();
+        assert!(!storage.has_document("users:u1"));
+        let mut wal_reader = WalReader::open_from_data_dir(_temp.path()).unwrap();
+        let stats = WalReplayer::replay(&mut wal_reader, &mut storage).unwrap();
+        assert_eq!(stats.records_replayed, 2);
+        assert!(storage.has_document("users:u1"));
+        assert!(storage.has_document("users:u2"));
+    }
+
+    #[test]
+    fn test_insert_refused_when_disk_is_full() {
+        use crate::storage_io::FaultyFs;
+
+        let (_temp, mut loader, wal, storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        let fs = FaultyFs::new();
+        let mut wal = wal.with_io(Arc::new(fs.clone())).unwrap();
+        let mut storage_w = storage_w.with_io(Arc::new(fs.clone())).unwrap();
+
+        let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let insert = |id: &str| json!({
+            "op": "insert",
             "schema_id": "users",
             "schema_version": "v1",
-            "filter": {"_id": {"$eq": "user_1"}},
+            "document": {"_id": id, "name": "Alice"}
+        });
+        let query = |id: &str| json!({
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "filter": {"_id": {"$eq": id}},
             "limit": 10
-        }"#;
+        });
 
-        let resp1 = handler.handle(explain_req, &mut subsystems);
-        let resp2 = handler.handle(explain_req, &mut subsystems);
+        assert_eq!(call(&handler, &mut subsystems, insert("u1"))["status"], "ok");
 
-        // Plans should be identical
-        assert_eq!(resp1.to_json(), resp2.to_json());
+        fs.limit_bytes(0);
+        let resp = call(&handler, &mut subsystems, insert("u2"));
+        assert_eq!(resp["code"], "AERO_WAL_APPEND_FAILED", "{}", resp);
+        let resp = call(&handler, &mut subsystems, query("u2"));
+        assert_eq!(resp["data"], json!([]), "{}", resp);
+        let resp = call(&handler, &mut subsystems, query("u1"));
+        assert_eq!(resp["data"][0]["_id"], "u1", "{}", resp);
+
+        fs.heal();
+        assert_eq!(call(&handler, &mut subsystems, insert("u2"))["status"], "ok");
+        let resp = call(&handler, &mut subsystems, query("u2"));
+        assert_eq!(resp["data"][0]["_id"], "u2", "{}", resp);
     }
 
     #[test]
-    fn test_serialization_enforced() {
-        // This test verifies the lock exists; actual blocking tested differently
-        let (_temp, loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+    fn test_rename_collection_moves_documents() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        let mut orders = HashMap::new();
+        orders.insert("_id".to_string(), FieldDef::required_string());
+        loader.register(Schema::new("orders", "v1", orders)).unwrap();
 
         let handler = ApiHandler::new("users");
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
         let mut subsystems = Subsystems {
-            schema_loader: &loader,
+            schema_loader: &mut loader,
             wal_writer: &mut wal,
             storage_writer: &mut storage_w,
             storage_reader: &mut storage_r,
             index_manager: &mut index,
+            statistics: &mut statistics,
             resource_manager: &rm,
             backpressure_manager: &bpm,
             admission_controller: &ac,
             query_limits: &ql,
         };
-        };
-
-        // Sequential operations should succeed
-        let insert1 = r#"{
+        for (id, age) in [("u1", 30), ("u2", 40)] {
+            let insert = json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": "Alice", "age": age}
+            });
+            assert_eq!(call(&handler, &mut subsystems, insert)["status"], "ok");
+        }
+        let insert = json!({
             "op": "insert",
-            "schema_id": "users",
+            "schema_id": "orders",
             "schema_version": "v1",
-            "document": {"_id": "user_1", "name": "Alice"}
-        }"#;
+            "document": {"_id": "o1"}
+        });
+        assert_eq!(call(&handler, &mut subsystems, insert)["status"], "ok");
+        let rename = |to: &str, confirm: Option<&Value>| {
+            json!({"op": "rename_collection", "from": "users", "to": to, "confirm": confirm})
+        };
+        let query = |schema_id: &str, tx_id: Option<&Value>| {
+            json!({
+                "op": "query",
+                "schema_id": schema_id,
+                "schema_version": "v1",
+                "filter": {"age": {"$gte": 0}},
+                "limit": 10,
+                "tx_id": tx_id
+            })
+        };
 
-        let insert2 = r#"{
-            "op": "insert",
-            "schema_id": "users",
-            "schema_version": "v1",
-            "document": {"_id": "user_2", "name": "Bob"}
-        }"#;
+        // A confirmation token is required, and binds the target name
+        let resp = call(&handler, &mut subsystems, rename("members", None));
+        assert_eq!(resp["code"], "AERO_CONFIRMATION_REQUIRED", "{}", resp);
+        let token = resp["confirmation_token"].clone();
+        let resp = call(&handler, &mut subsystems, rename("orders", Some(&token)));
+        assert_eq!(resp["code"], "AERO_CONFIRMATION_REJECTED", "{}", resp);
 
-        let resp1 = handler.handle(insert1, &mut subsystems);
-        let resp2 = handler.handle(insert2, &mut subsystems);
+        // A taken name is refused, and nothing moves
+        let resp = call(&handler, &mut subsystems, rename("orders", None));
+        let taken = resp["confirmation_token"].clone();
+        let resp = call(&handler, &mut subsystems, rename("orders", Some(&taken)));
+        assert_eq!(resp["code"], "AERO_COLLECTION_EXISTS", "{}", resp);
+        let resp = call(&handler, &mut subsystems, rename("users", None));
+        let itself = resp["confirmation_token"].clone();
+        let resp = call(&handler, &mut subsystems, rename("users", Some(&itself)));
+        assert_eq!(resp["code"], "AERO_INVALID_REQUEST", "{}", resp);
 
-        assert!(resp1.is_success());
-        assert!(resp2.is_success());
+        let tx_id = call(&handler, &mut subsystems, json!({"op": "begin"}))["data"]["tx_id"].clone();
+        let resp = call(&handler, &mut subsystems, rename("members", Some(&token)));
+        assert_eq!(resp["data"]["documents"], 2, "{}", resp);
+
+        // Documents and their index entries moved to the new name
+        let resp = call(&handler, &mut subsystems, query("members", None));
+        assert_eq!(resp["data"].as_array().unwrap().len(), 2, "{}", resp);
+        assert_eq!(resp["data"][0]["age"], 30);
+        assert_eq!(call(&handler, &mut subsystems, query("users", None))["status"], "error");
+        let offset = *subsystems.index_manager.lookup_pk("u1").last().unwrap();
+        assert_eq!(subsystems.storage_reader.read_at(offset).unwrap().schema_id, "members");
+
+        // The old schema file is gone and the new one saved
+        assert!(!subsystems.schema_loader.schema_id_exists("users"));
+        let schema_dir = subsystems.schema_loader.schema_dir().to_path_buf();
+        assert!(schema_dir.join("schema_members_v1.json").exists());
+
+        // A transaction that began before the rename still sees none of it
+        let resp = call(&handler, &mut subsystems, query("members", Some(&tx_id)));
+        assert_eq!(resp["data"], json!([]), "{}", resp);
+    }
+
+    /// Bindings kept the way the REST layer keeps them
+    fn rest_bindings() -> (DefaultRlsEnforcer, Arc<EndpointRegistry>, Arc<RestBindings>) {
+        let rls = DefaultRlsEnforcer::new();
+        let endpoints = Arc::new(EndpointRegistry::new());
+        let schema: SchemaDef = serde_json::from_value(json!({
+            "name": "users",
+            "fields": [],
+            "rls_policy": {"type": "ownership", "owner_field": "name"},
+            "anon_read": true
+        }))
+        .unwrap();
+        let endpoint = SchemaEndpoint::from_schema(schema);
+        endpoint.install_rls(&rls);
+        endpoints.register(endpoint).unwrap();
+        let bindings = Arc::new(RestBindings::new(rls.clone(), Arc::clone(&endpoints)));
+        (rls, endpoints, bindings)
     }
 
     #[test]
-    fn test_corruption_surfaced() {
-        // Corruption is surfaced when storage/WAL returns error
-        // This is implicitly tested via pass-through errors
+    fn test_rename_collection_writes_one_wal_record_and_moves_bindings() {
+        use crate::recovery::WalReplayer;
+        use crate::wal::WalReader;
+
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        let (rls, endpoints, bindings) = rest_bindings();
+        let handler = ApiHandler::new("users").with_collection_bindings(bindings);
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        let documents: Vec<Value> = (0..5)
+            .map(|i| json!({"_id": format!("u{}", i), "name": "Alice", "age": i}))
+            .collect();
+        let resp = call(&handler, &mut subsystems, insert_many(json!(documents), false));
+        assert_eq!(resp["data"]["inserted"], 5, "{}", resp);
 
-        let (_temp, loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        let before = subsystems.wal_writer.last_sequence_number();
+        let mut rename = json!({"op": "rename_collection", "from": "users", "to": "members"});
+        let resp = call(&handler, &mut subsystems, rename.clone());
+        rename["confirm"] = resp["confirmation_token"].clone();
+        let resp = call(&handler, &mut subsystems, rename);
+        assert_eq!(resp["data"]["documents"], 5, "{}", resp);
 
-        let handler = ApiHandler::new("users");
+        // One record, however many documents move
+        assert_eq!(subsystems.wal_writer.last_sequence_number(), before + 1);
+
+        // The endpoint and the policy follow the collection
+        assert!(endpoints.get("users").is_none());
+        assert_eq!(endpoints.get("members").unwrap().schema.name, "members");
+        assert!(rls.policy("users").is_none());
+        assert!(rls.policy("members").is_some());
+        assert!(rls.allows_anonymous_read("members"));
+
+        // Replaying the WAL renames through the same storage path
+        let replayed = TempDir::new().unwrap();
+        let mut storage = StorageWriter::open(replayed.path()).unwrap();
+        let mut wal_reader = WalReader::open_from_data_dir(_temp.path()).unwrap();
+        let stats = WalReplayer::replay(&mut wal_reader, &mut storage).unwrap();
+        assert_eq!(stats.collection_changes, 1);
+        assert_eq!(storage.collection_usage("members").unwrap().documents, 5);
+        assert_eq!(storage.collection_usage("users").unwrap().documents, 0);
+    }
+
+    #[test]
+    fn test_copy_collection_duplicates_documents_and_bindings() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        let (rls, endpoints, bindings) = rest_bindings();
+        let handler = ApiHandler::new("users").with_collection_bindings(bindings);
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
         let mut subsystems = Subsystems {
-            schema_loader: &loader,
+            schema_loader: &mut loader,
             wal_writer: &mut wal,
             storage_writer: &mut storage_w,
             storage_reader: &mut storage_r,
             index_manager: &mut index,
+            statistics: &mut statistics,
             resource_manager: &rm,
             backpressure_manager: &bpm,
             admission_controller: &ac,
             query_limits: &ql,
         };
+        for (id, age) in [("u1", 30), ("u2", 40)] {
+            let insert = json!({
+                "op": "insert",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "document": {"_id": id, "name": "Alice", "age": age}
+            });
+            assert_eq!(call(&handler, &mut subsystems, insert)["status"], "ok");
+        }
+        let copy = |to: &str, confirm: Option<&Value>| {
+            json!({"op": "copy_collection", "from": "users", "to": to, "confirm": confirm})
+        };
+        let query = |schema_id: &str| {
+            json!({
+                "op": "query",
+                "schema_id": schema_id,
+                "schema_version": "v1",
+                "filter": {"age": {"$gte": 0}},
+                "limit": 10
+            })
         };
 
-        // Insert a document - this confirms error propagation works
-        let insert_req = r#"{
-            "op": "insert",
-            "schema_id": "users",
-            "schema_version": "v1",
-            "document": {"_id": "user_1", "name": "Alice"}
-        }"#;
+        // A confirmation token is required
+        let resp = call(&handler, &mut subsystems, copy("archive", None));
+        assert_eq!(resp["code"], "AERO_CONFIRMATION_REQUIRED", "{}", resp);
+        let token = resp["confirmation_token"].clone();
+        let before = subsystems.wal_writer.last_sequence_number();
+        let resp = call(&handler, &mut subsystems, copy("archive", Some(&token)));
+        assert_eq!(
+            resp["data"],
+            json!({"copied": "users", "to": "archive", "documents": 2}),
+            "{}",
+            resp
+        );
+        // The copy record and an insert per document
+        assert_eq!(subsystems.wal_writer.last_sequence_number(), before + 3);
 
-        let resp = handler.handle(insert_req, &mut subsystems);
-        assert!(resp.is_success());
+        // The copies are indexed under their own ids; the source is untouched
+        let resp = call(&handler, &mut subsystems, query("archive"));
+        let copies = resp["data"].as_array().unwrap();
+        assert_eq!(copies.len(), 2, "{}", resp);
+        assert_eq!(copies[0]["_id"], "archive:u1");
+        assert_eq!(copies[0]["age"], 30);
+        let resp = call(&handler, &mut subsystems, query("users"));
+        assert_eq!(resp["data"].as_array().unwrap().len(), 2, "{}", resp);
+        assert_eq!(subsystems.index_manager.lookup_pk("u1").len(), 1);
+        assert_eq!(subsystems.index_manager.lookup_pk("archive:u2").len(), 1);
+        let schema_dir = subsystems.schema_loader.schema_dir().to_path_buf();
+        assert!(schema_dir.join("schema_archive_v1.json").exists());
+
+        // Bindings are copied, the source keeps its own
+        assert!(endpoints.get("users").is_some());
+        assert_eq!(endpoints.get("archive").unwrap().collection, "archive");
+        assert!(rls.policy("users").is_some());
+        assert!(rls.policy("archive").is_some());
+        assert!(rls.allows_anonymous_read("archive"));
+
+        // An existing target is refused
+        let resp = call(&handler, &mut subsystems, copy("archive", None));
+        let again = resp["confirmation_token"].clone();
+        let resp = call(&handler, &mut subsystems, copy("archive", Some(&again)));
+        assert_eq!(resp["code"], "AERO_COLLECTION_EXISTS", "{}", resp);
+    }
+
+    #[test]
+    fn test_drop_collection_requires_confirmation_token() {
+        let (_temp, mut loader, mut wal, mut storage_w, mut storage_r, mut index, rm, bpm, ac, ql) = setup_test_env();
+        let mut orders = HashMap::new();
+        orders.insert("_id".to_string(), FieldDef::required_string());
+        loader.register(Schema::new("orders", "v1", orders)).unwrap();
+
+        let audit_log = Arc::new(MemoryAuditLog::new());
+        let handler = ApiHandler::new("users").with_audit_log(audit_log.clone());
+        let mut statistics = Statistics::in_memory(StatisticsConfig::default());
+        let mut subsystems = Subsystems {
+            schema_loader: &mut loader,
+            wal_writer: &mut wal,
+            storage_writer: &mut storage_w,
+            storage_reader: &mut storage_r,
+            index_manager: &mut index,
+            statistics: &mut statistics,
+            resource_manager: &rm,
+            backpressure_manager: &bpm,
+            admission_controller: &ac,
+            query_limits: &ql,
+        };
+        for (schema_id, id) in [("users", "u1"), ("users", "u2"), ("orders", "o1")] {
+            let mut document = json!({"_id": id});
+            if schema_id == "users" {
+                document = json!({"_id": id, "name": "Alice", "age": 30});
+            }
+            let insert = json!({
+                "op": "insert",
+                "schema_id": schema_id,
+                "schema_version": "v1",
+                "document": document
+            });
+            assert_eq!(call(&handler, &mut subsystems, insert)["status"], "ok");
+        }
+        let drop = |collection: &str, confirm: Option<&Value>| {
+            json!({"op": "drop_collection", "collection": collection, "confirm": confirm})
+        };
+
+        // The first request only issues a token
+        let resp = call(&handler, &mut subsystems, drop("users", None));
+        assert_eq!(resp["code"], "AERO_CONFIRMATION_REQUIRED", "{}", resp);
+        let token = resp["confirmation_token"].clone();
+        assert!(token.is_string(), "{}", resp);
+        assert_eq!(subsystems.index_manager.lookup_pk("u1").len(), 1);
+
+        // The token drops this collection only
+        let resp = call(&handler, &mut subsystems, drop("orders", Some(&token)));
+        assert_eq!(resp["code"], "AERO_CONFIRMATION_REJECTED", "{}", resp);
+        assert!(subsystems.schema_loader.schema_id_exists("orders"));
+
+        let resp = call(&handler, &mut subsystems, drop("users", Some(&token)));
+        assert_eq!(resp["data"], json!({"dropped": "users", "documents": 2}), "{}", resp);
+        assert!(subsystems.index_manager.lookup_pk("u1").is_empty());
+        assert!(!subsystems.schema_loader.schema_id_exists("users"));
+        let schema_dir = subsystems.schema_loader.schema_dir().to_path_buf();
+        assert!(!schema_dir.join("schema_users_v1.json").exists());
+        assert_eq!(subsystems.index_manager.lookup_pk("o1").len(), 1);
+
+        // Tokens are single-use
+        let resp = call(&handler, &mut subsystems, drop("orders", Some(&token)));
+        assert_eq!(resp["code"], "AERO_CONFIRMATION_REJECTED", "{}", resp);
+        assert!(resp["message"].as_str().unwrap().contains("already consumed"));
+
+        // Issuing and every use of the token are audited
+        let records = audit_log.records();
+        let actions: Vec<_> = records.iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::ConfirmationRequested,
+                AuditAction::ConfirmationProvided,
+                AuditAction::CommandRejected,
+                AuditAction::ConfirmationProvided,
+                AuditAction::ConfirmationProvided,
+                AuditAction::CommandRejected,
+            ]
+        );
+        let token_id = token.as_str().unwrap();
+        assert!(records
+            .iter()
+            .all(|r| r.confirmation_token.map(|t| t.to_string()).as_deref() == Some(token_id)));
+        assert!(records.iter().all(|r| r.command_name.as_deref() == Some("drop_collection")));
+        assert!(records[5].error_message.as_deref().unwrap().contains("already consumed"));
+    }
+
+    #[test]
+    fn test_rename_collection_under_concurrent_reads() {
+        let (_temp, loader, wal, storage_w, storage_r, index, rm, bpm, ac, ql) = setup_test_env();
+        let statistics = Statistics::in_memory(StatisticsConfig::default());
+        let handler = ApiHandler::new("users");
+        let parts = Mutex::new((loader, wal, storage_w, storage_r, index, statistics));
+        let call_locked = |req: Value| {
+            let mut parts = parts.lock().unwrap();
+            let (loader, wal, storage_w, storage_r, index, statistics) = &mut *parts;
+            let mut subsystems = Subsystems {
+                schema_loader: loader,
+                wal_writer: wal,
+                storage_writer: storage_w,
+                storage_reader: storage_r,
+                index_manager: index,
+                statistics,
+                resource_manager: &rm,
+                backpressure_manager: &bpm,
+                admission_controller: &ac,
+                query_limits: &ql,
+            };
+            call(&handler, &mut subsystems, req)
+        };
+        let documents: Vec<Value> = (0..50)
+            .map(|i| json!({"_id": format!("u{:02}", i), "name": "Alice", "age": i}))
+            .collect();
+        let resp = call_locked(insert_many(json!(documents), false));
+        assert_eq!(resp["data"]["inserted"], 50, "{}", resp);
+
+        let count = |schema_id: &str| {
+            let resp = call_locked(json!({
+                "op": "query",
+                "schema_id": schema_id,
+                "schema_version": "v1",
+                "filter": {"age": {"$gte": 0}},
+                "limit": 100
+            }));
+            resp["data"].as_array().map_or(0, Vec::len)
+        };
+
+        let mut rename = json!({"op": "rename_collection", "from": "users", "to": "members"});
+        rename["confirm"] = call_locked(rename.clone())["confirmation_token"].clone();
+
+        // Every read sees all of the collection under a name or none of it;
+        // once it has left `users` it is under `members`
+        std::thread::scope(|scope| {
+            let reader = scope.spawn(|| {
+                for _ in 0..100 {
+                    let (users, members) = (count("users"), count("members"));
+                    assert!(users == 0 || users == 50, "{}", users);
+                    assert!(members == 0 || members == 50, "{}", members);
+                    assert!(users + members > 0);
+                }
+            });
+            let resp = call_locked(rename);
+            assert_eq!(resp["data"]["documents"], 50, "{}", resp);
+            reader.join().unwrap();
+        });
+        assert_eq!(count("members"), 50);
     }
 }
@@ -7,22 +7,39 @@
 //! - Single global mutex for all operations
 //! - Strict request handling flow
 //! - Error codes passed through unchanged
-//! - No timestamps, no generated IDs, no metadata injection
+//! - No timestamps, no generated IDs (other than `tx_id`), no metadata
+//!   injection
 //!
 //! # Supported Operations
 //!
 //! - insert
+//! - insert_many
 //! - update
 //! - delete
 //! - query
 //! - explain
+//! - analyze
+//! - aggregate
+//! - begin, commit, rollback (see `transaction`)
+//! - rename_collection, copy_collection, drop_collection
 
+mod bindings;
 mod errors;
 mod handler;
 mod request;
 mod response;
+mod transaction;
 
+pub use bindings::CollectionBindings;
 pub use errors::{ApiError, ApiErrorCode, ApiResult};
-pub use handler::{ApiHandler, Subsystems};
-pub use request::{DeleteRequest, InsertRequest, QueryRequest, Request, UpdateRequest};
+pub use handler::{ApiHandler, Clock, ExpirySweep, Subsystems};
+pub use request::{
+    AggregateRequest, AnalyzeRequest, DeleteRequest, DropCollectionRequest, InsertManyRequest,
+    InsertRequest, QueryRequest, RenameCollectionRequest, Request, TransactionRequest,
+    UpdateRequest,
+};
 pub use response::{ErrorResponse, Response, SuccessResponse};
+pub use transaction::{
+    BufferedWrite, ClosedTransaction, TransactionRegistry, DEFAULT_MAX_TRANSACTION_BYTES,
+    DEFAULT_TRANSACTION_TIMEOUT,
+};
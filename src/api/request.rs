@@ -4,18 +4,35 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use uuid::Uuid;
 
 use super::errors::{ApiError, ApiResult};
+use crate::admission_control::OperationClass;
+use crate::observability::OperationType;
 
 /// Operation type
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Operation {
     Insert,
+    #[serde(rename = "insert_many")]
+    InsertMany,
     Update,
     Delete,
+    Purge,
     Query,
     Explain,
+    Analyze,
+    Aggregate,
+    Begin,
+    Commit,
+    Rollback,
+    #[serde(rename = "rename_collection")]
+    RenameCollection,
+    #[serde(rename = "copy_collection")]
+    CopyCollection,
+    #[serde(rename = "drop_collection")]
+    DropCollection,
 }
 
 /// Insert request
@@ -24,6 +41,27 @@ pub struct InsertRequest {
     pub schema_id: String,
     pub schema_version: String,
     pub document: Value,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Buffer the write in this transaction instead of applying it
+    #[serde(default)]
+    pub tx_id: Option<Uuid>,
+}
+
+/// Bulk insert request
+///
+/// Without `continue_on_error` the batch is atomic: one rejected document
+/// rejects them all. With it, rejected documents are reported and the
+/// rest inserted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsertManyRequest {
+    pub schema_id: String,
+    pub schema_version: String,
+    pub documents: Vec<Value>,
+    #[serde(default)]
+    pub continue_on_error: bool,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
 /// Update request
@@ -32,13 +70,26 @@ pub struct UpdateRequest {
     pub schema_id: String,
     pub schema_version: String,
     pub document: Value,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Buffer the write in this transaction instead of applying it
+    #[serde(default)]
+    pub tx_id: Option<Uuid>,
 }
 
-/// Delete request
+/// Delete request, also used by purge
+///
+/// A delete of a `soft_delete` schema's document sets its `_deleted_at`;
+/// a purge removes the document outright.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeleteRequest {
     pub schema_id: String,
     pub document_id: String,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Buffer the delete in this transaction instead of applying it
+    #[serde(default)]
+    pub tx_id: Option<Uuid>,
 }
 
 /// Query request
@@ -51,16 +102,124 @@ pub struct QueryRequest {
     #[serde(default)]
     pub sort: Option<String>,
     pub limit: usize,
+    #[serde(default)]
+    pub offset: Option<usize>,
+    /// Fields to return (dotted paths reach nested fields, `-_id` drops the
+    /// primary key); None returns whole documents
+    #[serde(default)]
+    pub select: Option<Vec<String>>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub max_staleness_ms: Option<u64>,
+    /// Return soft-deleted documents too (not for tenant requests)
+    #[serde(default)]
+    pub include_deleted: bool,
+    /// Read from this transaction's snapshot
+    #[serde(default)]
+    pub tx_id: Option<Uuid>,
+}
+
+/// Analyze request: recompute planner statistics for a collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzeRequest {
+    pub collection: String,
+}
+
+/// Aggregate request
+///
+/// `aggregates` maps result names to `{"<function>": "<field>"}`; it is
+/// validated by the handler.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateRequest {
+    pub collection: String,
+    pub filter: Option<Value>,
+    pub group_by: Vec<String>,
+    pub aggregates: Value,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub max_staleness_ms: Option<u64>,
+    /// Aggregate soft-deleted documents too (not for tenant requests)
+    #[serde(default)]
+    pub include_deleted: bool,
+}
+
+/// Commit or rollback request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionRequest {
+    pub tx_id: Uuid,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Collection rename request
+///
+/// Without `confirm` nothing is renamed: the response carries a
+/// confirmation token for this rename, which `confirm` must repeat.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameCollectionRequest {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub confirm: Option<Uuid>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Collection copy request
+///
+/// Without `confirm` nothing is copied: the response carries a
+/// confirmation token for this copy, which `confirm` must repeat.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopyCollectionRequest {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub confirm: Option<Uuid>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Collection drop request
+///
+/// Without `confirm` nothing is dropped: the response carries a
+/// confirmation token for this drop, which `confirm` must repeat.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropCollectionRequest {
+    pub collection: String,
+    #[serde(default)]
+    pub confirm: Option<Uuid>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
 /// Unified request envelope
 #[derive(Debug, Clone)]
 pub enum Request {
     Insert(InsertRequest),
+    InsertMany(InsertManyRequest),
     Update(UpdateRequest),
     Delete(DeleteRequest),
+    Purge(DeleteRequest),
     Query(QueryRequest),
     Explain(QueryRequest),
+    Analyze(AnalyzeRequest),
+    Aggregate(AggregateRequest),
+    /// Open a transaction
+    Begin,
+    /// Apply a transaction's buffered writes atomically
+    Commit(TransactionRequest),
+    /// Discard a transaction's buffered writes
+    Rollback(TransactionRequest),
+    /// Move a collection's documents and schema to a new name
+    RenameCollection(RenameCollectionRequest),
+    /// Duplicate a collection's documents and schema under a new name
+    CopyCollection(CopyCollectionRequest),
+    /// Delete a collection's documents and schema
+    DropCollection(DropCollectionRequest),
+    /// Diagnostic: in-flight and queued counts per admission class
+    Admission,
 }
 
 /// Raw request for parsing
@@ -74,6 +233,10 @@ struct RawRequest {
     #[serde(default)]
     document: Option<Value>,
     #[serde(default)]
+    documents: Option<Vec<Value>>,
+    #[serde(default)]
+    continue_on_error: Option<bool>,
+    #[serde(default)]
     document_id: Option<String>,
     #[serde(default)]
     filter: Option<Value>,
@@ -81,13 +244,160 @@ struct RawRequest {
     sort: Option<String>,
     #[serde(default)]
     limit: Option<usize>,
+    #[serde(default)]
+    offset: Option<usize>,
+    #[serde(default)]
+    select: Option<Vec<String>>,
+    #[serde(default)]
+    collection: Option<String>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    max_staleness_ms: Option<u64>,
+    #[serde(default)]
+    group_by: Option<Vec<String>>,
+    #[serde(default)]
+    aggregates: Option<Value>,
+    #[serde(default, alias = "txn")]
+    tx_id: Option<String>,
+    #[serde(default)]
+    include_deleted: Option<bool>,
+    #[serde(default)]
+    from: Option<String>,
+    #[serde(default)]
+    to: Option<String>,
+    #[serde(default)]
+    confirm: Option<String>,
 }
 
 impl Request {
+    /// Requested timeout (`timeout_ms`), if the operation takes one
+    pub fn timeout_ms(&self) -> Option<u64> {
+        match self {
+            Request::Insert(r) => r.timeout_ms,
+            Request::InsertMany(r) => r.timeout_ms,
+            Request::Update(r) => r.timeout_ms,
+            Request::Delete(r) | Request::Purge(r) => r.timeout_ms,
+            Request::Query(r) => r.timeout_ms,
+            Request::Aggregate(r) => r.timeout_ms,
+            Request::Commit(r) => r.timeout_ms,
+            Request::RenameCollection(r) => r.timeout_ms,
+            Request::CopyCollection(r) => r.timeout_ms,
+            Request::DropCollection(r) => r.timeout_ms,
+            Request::Explain(_)
+            | Request::Analyze(_)
+            | Request::Begin
+            | Request::Rollback(_)
+            | Request::Admission => None,
+        }
+    }
+
+    /// Check if the operation writes (transaction control included)
+    pub fn is_write(&self) -> bool {
+        matches!(
+            self,
+            Request::Insert(_)
+                | Request::InsertMany(_)
+                | Request::Update(_)
+                | Request::Delete(_)
+                | Request::Purge(_)
+                | Request::Begin
+                | Request::Commit(_)
+                | Request::Rollback(_)
+                | Request::RenameCollection(_)
+                | Request::CopyCollection(_)
+                | Request::DropCollection(_)
+        )
+    }
+
+    /// Admission class, or `None` for diagnostics that bypass admission
+    pub fn admission_class(&self) -> Option<OperationClass> {
+        match self {
+            Request::Insert(_)
+            | Request::InsertMany(_)
+            | Request::Update(_)
+            | Request::Delete(_)
+            | Request::Purge(_)
+            | Request::Begin
+            | Request::Commit(_)
+            | Request::Rollback(_)
+            | Request::RenameCollection(_)
+            | Request::CopyCollection(_)
+            | Request::DropCollection(_) => Some(OperationClass::Write),
+            Request::Query(_) | Request::Aggregate(_) | Request::Analyze(_) => {
+                Some(OperationClass::Read)
+            }
+            Request::Explain(_) | Request::Admission => None,
+        }
+    }
+
+    /// Whether the request asks for soft-deleted documents
+    pub fn include_deleted(&self) -> bool {
+        match self {
+            Request::Query(r) | Request::Explain(r) => r.include_deleted,
+            Request::Aggregate(r) => r.include_deleted,
+            _ => false,
+        }
+    }
+
+    /// Kind of operation, as recorded in the operation log
+    pub fn operation_type(&self) -> OperationType {
+        match self {
+            Request::Insert(_) | Request::InsertMany(_) => OperationType::Insert,
+            Request::Update(_) => OperationType::Update,
+            Request::Delete(_) | Request::Purge(_) => OperationType::Delete,
+            Request::Query(_)
+            | Request::Explain(_)
+            | Request::Aggregate(_)
+            | Request::Admission => OperationType::Find,
+            Request::Begin | Request::Commit(_) | Request::Rollback(_) => {
+                OperationType::Transaction
+            }
+            Request::Analyze(_)
+            | Request::RenameCollection(_)
+            | Request::CopyCollection(_)
+            | Request::DropCollection(_) => OperationType::Schema,
+        }
+    }
+
+    /// Collection the request addresses, if any
+    pub fn collection(&self) -> Option<&str> {
+        match self {
+            Request::Insert(r) => Some(&r.schema_id),
+            Request::InsertMany(r) => Some(&r.schema_id),
+            Request::Update(r) => Some(&r.schema_id),
+            Request::Delete(r) | Request::Purge(r) => Some(&r.schema_id),
+            Request::Query(r) | Request::Explain(r) => Some(&r.schema_id),
+            Request::Analyze(r) => Some(&r.collection),
+            Request::Aggregate(r) => Some(&r.collection),
+            Request::RenameCollection(r) => Some(&r.from),
+            Request::CopyCollection(r) => Some(&r.from),
+            Request::DropCollection(r) => Some(&r.collection),
+            Request::Begin | Request::Commit(_) | Request::Rollback(_) | Request::Admission => None,
+        }
+    }
+
+    /// Staleness bound (`max_staleness_ms`) for reads served by a replica
+    pub fn max_staleness_ms(&self) -> Option<u64> {
+        match self {
+            Request::Query(r) | Request::Explain(r) => r.max_staleness_ms,
+            Request::Aggregate(r) => r.max_staleness_ms,
+            _ => None,
+        }
+    }
+
     /// Parse a request from JSON string
     pub fn parse(json: &str) -> ApiResult<Self> {
         let raw: RawRequest = serde_json::from_str(json)
             .map_err(|e| ApiError::invalid_request(format!("Invalid JSON: {}", e)))?;
+        let tx_id = raw
+            .tx_id
+            .as_deref()
+            .map(|id| {
+                Uuid::parse_str(id)
+                    .map_err(|_| ApiError::invalid_request(format!("Invalid tx_id: {}", id)))
+            })
+            .transpose()?;
 
         match raw.op.as_str() {
             "insert" => {
@@ -105,6 +415,33 @@ impl Request {
                     schema_id,
                     schema_version,
                     document,
+                    timeout_ms: raw.timeout_ms,
+                    tx_id,
+                }))
+            }
+            "insert_many" => {
+                if tx_id.is_some() {
+                    return Err(ApiError::invalid_request(
+                        "insert_many cannot be part of a transaction",
+                    ));
+                }
+                let schema_id = raw
+                    .schema_id
+                    .ok_or_else(|| ApiError::invalid_request("Missing schema_id"))?;
+                let schema_version = raw
+                    .schema_version
+                    .ok_or_else(|| ApiError::invalid_request("Missing schema_version"))?;
+                let documents = raw
+                    .documents
+                    .filter(|documents| !documents.is_empty())
+                    .ok_or_else(|| ApiError::invalid_request("Missing documents"))?;
+
+                Ok(Request::InsertMany(InsertManyRequest {
+                    schema_id,
+                    schema_version,
+                    documents,
+                    continue_on_error: raw.continue_on_error.unwrap_or(false),
+                    timeout_ms: raw.timeout_ms,
                 }))
             }
             "update" => {
@@ -122,9 +459,16 @@ impl Request {
                     schema_id,
                     schema_version,
                     document,
+                    timeout_ms: raw.timeout_ms,
+                    tx_id,
                 }))
             }
-            "delete" => {
+            "delete" | "purge" => {
+                if raw.op == "purge" && tx_id.is_some() {
+                    return Err(ApiError::invalid_request(
+                        "purge cannot be part of a transaction",
+                    ));
+                }
                 let schema_id = raw
                     .schema_id
                     .ok_or_else(|| ApiError::invalid_request("Missing schema_id"))?;
@@ -132,10 +476,17 @@ impl Request {
                     .document_id
                     .ok_or_else(|| ApiError::invalid_request("Missing document_id"))?;
 
-                Ok(Request::Delete(DeleteRequest {
+                let request = DeleteRequest {
                     schema_id,
                     document_id,
-                }))
+                    timeout_ms: raw.timeout_ms,
+                    tx_id,
+                };
+                if raw.op == "purge" {
+                    Ok(Request::Purge(request))
+                } else {
+                    Ok(Request::Delete(request))
+                }
             }
             "query" => {
                 let schema_id = raw
@@ -154,6 +505,12 @@ impl Request {
                     filter: raw.filter,
                     sort: raw.sort,
                     limit,
+                    offset: raw.offset,
+                    select: raw.select,
+                    timeout_ms: raw.timeout_ms,
+                    max_staleness_ms: raw.max_staleness_ms,
+                    include_deleted: raw.include_deleted.unwrap_or(false),
+                    tx_id,
                 }))
             }
             "explain" => {
@@ -173,13 +530,119 @@ impl Request {
                     filter: raw.filter,
                     sort: raw.sort,
                     limit,
+                    offset: raw.offset,
+                    select: raw.select,
+                    timeout_ms: raw.timeout_ms,
+                    max_staleness_ms: raw.max_staleness_ms,
+                    include_deleted: raw.include_deleted.unwrap_or(false),
+                    tx_id,
+                }))
+            }
+            "analyze" => {
+                let collection = raw
+                    .collection
+                    .ok_or_else(|| ApiError::invalid_request("Missing collection"))?;
+
+                Ok(Request::Analyze(AnalyzeRequest { collection }))
+            }
+            "aggregate" => {
+                let collection = raw
+                    .collection
+                    .ok_or_else(|| ApiError::invalid_request("Missing collection"))?;
+                let group_by = raw
+                    .group_by
+                    .ok_or_else(|| ApiError::invalid_request("Missing group_by"))?;
+                let aggregates = raw
+                    .aggregates
+                    .ok_or_else(|| ApiError::invalid_request("Missing aggregates"))?;
+
+                Ok(Request::Aggregate(AggregateRequest {
+                    collection,
+                    filter: raw.filter,
+                    group_by,
+                    aggregates,
+                    timeout_ms: raw.timeout_ms,
+                    max_staleness_ms: raw.max_staleness_ms,
+                    include_deleted: raw.include_deleted.unwrap_or(false),
                 }))
             }
+            "begin" => Ok(Request::Begin),
+            "commit" | "rollback" => {
+                let tx_id = tx_id.ok_or_else(|| ApiError::invalid_request("Missing tx_id"))?;
+                let request = TransactionRequest {
+                    tx_id,
+                    timeout_ms: raw.timeout_ms,
+                };
+                if raw.op == "commit" {
+                    Ok(Request::Commit(request))
+                } else {
+                    Ok(Request::Rollback(request))
+                }
+            }
+            "rename_collection" | "copy_collection" => {
+                if tx_id.is_some() {
+                    return Err(ApiError::invalid_request(format!(
+                        "{} cannot be part of a transaction",
+                        raw.op
+                    )));
+                }
+                let from = raw
+                    .from
+                    .ok_or_else(|| ApiError::invalid_request("Missing from"))?;
+                let to = raw
+                    .to
+                    .ok_or_else(|| ApiError::invalid_request("Missing to"))?;
+                let confirm = parse_confirmation(raw.confirm)?;
+
+                if raw.op == "copy_collection" {
+                    return Ok(Request::CopyCollection(CopyCollectionRequest {
+                        from,
+                        to,
+                        confirm,
+                        timeout_ms: raw.timeout_ms,
+                    }));
+                }
+                Ok(Request::RenameCollection(RenameCollectionRequest {
+                    from,
+                    to,
+                    confirm,
+                    timeout_ms: raw.timeout_ms,
+                }))
+            }
+            "drop_collection" => {
+                if tx_id.is_some() {
+                    return Err(ApiError::invalid_request(
+                        "drop_collection cannot be part of a transaction",
+                    ));
+                }
+                let collection = raw
+                    .collection
+                    .ok_or_else(|| ApiError::invalid_request("Missing collection"))?;
+                let confirm = parse_confirmation(raw.confirm)?;
+
+                Ok(Request::DropCollection(DropCollectionRequest {
+                    collection,
+                    confirm,
+                    timeout_ms: raw.timeout_ms,
+                }))
+            }
+            "admission" => Ok(Request::Admission),
             other => Err(ApiError::unknown_operation(other)),
         }
     }
 }
 
+/// Parse the confirmation token of a dangerous operation
+fn parse_confirmation(token: Option<String>) -> ApiResult<Option<Uuid>> {
+    token
+        .map(|token| {
+            Uuid::parse_str(&token).map_err(|_| {
+                ApiError::invalid_request(format!("Invalid confirmation token: {}", token))
+            })
+        })
+        .transpose()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,7 +673,8 @@ mod tests {
             "schema_id": "users",
             "schema_version": "v1",
             "filter": {"age": {"$eq": 25}},
-            "limit": 10
+            "limit": 10,
+            "offset": 20
         }"#;
 
         let req = Request::parse(json).unwrap();
@@ -218,11 +682,260 @@ mod tests {
             Request::Query(r) => {
                 assert_eq!(r.schema_id, "users");
                 assert_eq!(r.limit, 10);
+                assert_eq!(r.offset, Some(20));
+                assert_eq!(r.timeout_ms, None);
             }
             _ => panic!("Expected Query"),
         }
     }
 
+    #[test]
+    fn test_parse_timeout() {
+        let json = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "limit": 10,
+            "timeout_ms": 250
+        }"#;
+        assert_eq!(Request::parse(json).unwrap().timeout_ms(), Some(250));
+
+        let json = r#"{"op": "delete", "schema_id": "users", "document_id": "u1"}"#;
+        assert_eq!(Request::parse(json).unwrap().timeout_ms(), None);
+    }
+
+    #[test]
+    fn test_parse_max_staleness() {
+        let json = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "limit": 10,
+            "max_staleness_ms": 5000
+        }"#;
+        let req = Request::parse(json).unwrap();
+        assert_eq!(req.max_staleness_ms(), Some(5000));
+        assert!(!req.is_write());
+
+        let json = r#"{"op": "delete", "schema_id": "users", "document_id": "u1"}"#;
+        let req = Request::parse(json).unwrap();
+        assert_eq!(req.max_staleness_ms(), None);
+        assert!(req.is_write());
+    }
+
+    #[test]
+    fn test_parse_purge_and_include_deleted() {
+        let json = r#"{"op": "purge", "schema_id": "users", "document_id": "u1"}"#;
+        let req = Request::parse(json).unwrap();
+        assert!(matches!(&req, Request::Purge(r) if r.document_id == "u1"));
+        assert!(req.is_write());
+
+        let json = format!(
+            r#"{{"op": "purge", "schema_id": "users", "document_id": "u1", "tx_id": "{}"}}"#,
+            Uuid::new_v4()
+        );
+        assert!(Request::parse(&json).is_err());
+
+        let json = r#"{
+            "op": "query",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "limit": 10,
+            "include_deleted": true
+        }"#;
+        assert!(Request::parse(json).unwrap().include_deleted());
+        let json = r#"{"op": "query", "schema_id": "users", "schema_version": "v1", "limit": 10}"#;
+        assert!(!Request::parse(json).unwrap().include_deleted());
+    }
+
+    #[test]
+    fn test_parse_analyze() {
+        let req = Request::parse(r#"{"op": "analyze", "collection": "users"}"#).unwrap();
+        match req {
+            Request::Analyze(r) => assert_eq!(r.collection, "users"),
+            _ => panic!("Expected Analyze"),
+        }
+
+        let err = Request::parse(r#"{"op": "analyze"}"#).unwrap_err();
+        assert!(err.message().contains("Missing collection"));
+    }
+
+    #[test]
+    fn test_admission_classes() {
+        let insert =
+            r#"{"op": "insert", "schema_id": "u", "schema_version": "v1", "document": {}}"#;
+        let explain = r#"{"op": "explain", "schema_id": "u", "schema_version": "v1", "limit": 1}"#;
+        let analyze = r#"{"op": "analyze", "collection": "users"}"#;
+
+        let class = |json: &str| Request::parse(json).unwrap().admission_class();
+        assert_eq!(class(insert), Some(OperationClass::Write));
+        assert_eq!(class(analyze), Some(OperationClass::Read));
+        assert_eq!(class(explain), None);
+        assert_eq!(class(r#"{"op": "admission"}"#), None);
+    }
+
+    #[test]
+    fn test_parse_aggregate() {
+        let json = r#"{
+            "op": "aggregate",
+            "collection": "orders",
+            "group_by": ["region", "status"],
+            "aggregates": {"total": {"sum": "amount"}, "n": {"count": "*"}},
+            "timeout_ms": 500
+        }"#;
+        let req = Request::parse(json).unwrap();
+        assert_eq!(req.timeout_ms(), Some(500));
+        match req {
+            Request::Aggregate(r) => {
+                assert_eq!(r.collection, "orders");
+                assert_eq!(r.group_by, ["region", "status"]);
+                assert!(r.filter.is_none());
+                assert_eq!(r.aggregates["total"]["sum"], "amount");
+            }
+            _ => panic!("Expected Aggregate"),
+        }
+
+        let err = Request::parse(r#"{"op": "aggregate", "collection": "orders"}"#).unwrap_err();
+        assert!(err.message().contains("Missing group_by"));
+    }
+
+    #[test]
+    fn test_parse_transaction_ops() {
+        assert!(matches!(
+            Request::parse(r#"{"op": "begin"}"#).unwrap(),
+            Request::Begin
+        ));
+
+        let tx_id = Uuid::new_v4();
+        let commit = format!(r#"{{"op": "commit", "tx_id": "{}"}}"#, tx_id);
+        match Request::parse(&commit).unwrap() {
+            Request::Commit(r) => assert_eq!(r.tx_id, tx_id),
+            _ => panic!("Expected Commit"),
+        }
+
+        let delete = format!(
+            r#"{{"op": "delete", "schema_id": "users", "document_id": "u1", "tx_id": "{}"}}"#,
+            tx_id
+        );
+        match Request::parse(&delete).unwrap() {
+            Request::Delete(r) => assert_eq!(r.tx_id, Some(tx_id)),
+            _ => panic!("Expected Delete"),
+        }
+
+        // `txn` names the transaction too, and queries read from it
+        let query = format!(
+            r#"{{"op": "query", "schema_id": "users", "schema_version": "v1", "limit": 5, "txn": "{}"}}"#,
+            tx_id
+        );
+        match Request::parse(&query).unwrap() {
+            Request::Query(r) => assert_eq!(r.tx_id, Some(tx_id)),
+            _ => panic!("Expected Query"),
+        }
+
+        let err = Request::parse(r#"{"op": "rollback"}"#).unwrap_err();
+        assert!(err.message().contains("Missing tx_id"));
+        let err = Request::parse(r#"{"op": "commit", "tx_id": "nope"}"#).unwrap_err();
+        assert!(err.message().contains("Invalid tx_id"));
+    }
+
+    #[test]
+    fn test_parse_insert_many() {
+        let json = r#"{
+            "op": "insert_many",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "documents": [{"_id": "u1"}, {"_id": "u2"}],
+            "continue_on_error": true
+        }"#;
+        match Request::parse(json).unwrap() {
+            Request::InsertMany(r) => {
+                assert_eq!(r.documents.len(), 2);
+                assert!(r.continue_on_error);
+            }
+            _ => panic!("Expected InsertMany"),
+        }
+
+        let empty =
+            r#"{"op": "insert_many", "schema_id": "u", "schema_version": "v1", "documents": []}"#;
+        assert!(Request::parse(empty).is_err());
+        let in_tx = format!(
+            r#"{{"op": "insert_many", "schema_id": "u", "schema_version": "v1", "documents": [{{}}], "tx_id": "{}"}}"#,
+            Uuid::new_v4()
+        );
+        assert!(Request::parse(&in_tx).is_err());
+    }
+
+    #[test]
+    fn test_parse_rename_collection() {
+        let token = Uuid::new_v4();
+        let json = format!(
+            r#"{{"op": "rename_collection", "from": "users", "to": "members", "confirm": "{}"}}"#,
+            token
+        );
+        let req = Request::parse(&json).unwrap();
+        assert!(req.is_write());
+        match req {
+            Request::RenameCollection(r) => {
+                assert_eq!(r.from, "users");
+                assert_eq!(r.to, "members");
+                assert_eq!(r.confirm, Some(token));
+            }
+            _ => panic!("Expected RenameCollection"),
+        }
+
+        let phrase =
+            r#"{"op": "rename_collection", "from": "users", "to": "m", "confirm": "rename users"}"#;
+        let err = Request::parse(phrase).unwrap_err();
+        assert!(err.message().contains("Invalid confirmation token"));
+
+        let err = Request::parse(r#"{"op": "rename_collection", "from": "users"}"#).unwrap_err();
+        assert!(err.message().contains("Missing to"));
+    }
+
+    #[test]
+    fn test_parse_copy_collection() {
+        let token = Uuid::new_v4();
+        let json = format!(
+            r#"{{"op": "copy_collection", "from": "users", "to": "archive", "confirm": "{}"}}"#,
+            token
+        );
+        let req = Request::parse(&json).unwrap();
+        assert!(req.is_write());
+        assert_eq!(req.collection(), Some("users"));
+        match req {
+            Request::CopyCollection(r) => {
+                assert_eq!(r.to, "archive");
+                assert_eq!(r.confirm, Some(token));
+            }
+            _ => panic!("Expected CopyCollection"),
+        }
+
+        let err = Request::parse(r#"{"op": "copy_collection", "to": "archive"}"#).unwrap_err();
+        assert!(err.message().contains("Missing from"));
+    }
+
+    #[test]
+    fn test_parse_drop_collection() {
+        let token = Uuid::new_v4();
+        let json = format!(
+            r#"{{"op": "drop_collection", "collection": "users", "confirm": "{}"}}"#,
+            token
+        );
+        let req = Request::parse(&json).unwrap();
+        assert!(req.is_write());
+        assert_eq!(req.collection(), Some("users"));
+        match req {
+            Request::DropCollection(r) => assert_eq!(r.confirm, Some(token)),
+            _ => panic!("Expected DropCollection"),
+        }
+
+        let json = r#"{"op": "drop_collection", "collection": "users", "confirm": "drop users"}"#;
+        let err = Request::parse(json).unwrap_err();
+        assert!(err.message().contains("Invalid confirmation token"));
+        let err = Request::parse(r#"{"op": "drop_collection"}"#).unwrap_err();
+        assert!(err.message().contains("Missing collection"));
+    }
+
     #[test]
     fn test_parse_unknown_op() {
         let json = r#"{"op": "dropDatabase"}"#;
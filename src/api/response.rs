@@ -4,6 +4,7 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use uuid::Uuid;
 
 use super::errors::ApiError;
 
@@ -43,6 +44,15 @@ pub struct ErrorResponse {
     pub status: String,
     pub code: String,
     pub message: String,
+    /// Suggested wait before retrying, for overload rejections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    /// Request that failed, for correlation with the logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<Uuid>,
+    /// Token to repeat an unconfirmed dangerous operation with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmation_token: Option<Uuid>,
 }
 
 impl ErrorResponse {
@@ -52,6 +62,9 @@ impl ErrorResponse {
             status: "error".to_string(),
             code: err.code().to_string(),
             message: err.message().to_string(),
+            retry_after_ms: err.retry_after_ms(),
+            request_id: None,
+            confirmation_token: err.confirmation_token(),
         }
     }
 
@@ -84,6 +97,14 @@ impl Response {
         Response::Error(ErrorResponse::from_error(err))
     }
 
+    /// Name the request in an error response
+    pub fn with_request_id(mut self, request_id: Uuid) -> Self {
+        if let Response::Error(err) = &mut self {
+            err.request_id = Some(request_id);
+        }
+        self
+    }
+
     /// Convert to JSON string
     pub fn to_json(&self) -> String {
         match self {
@@ -118,5 +139,18 @@ mod tests {
         let json = resp.to_json();
         assert!(json.contains("\"status\":\"error\""));
         assert!(json.contains("AERO_INVALID_REQUEST"));
+        assert!(!json.contains("request_id"));
+    }
+
+    #[test]
+    fn test_error_response_names_request() {
+        let request_id = Uuid::new_v4();
+        let err =
+            Response::error(&ApiError::invalid_request("test error")).with_request_id(request_id);
+        let json: Value = serde_json::from_str(&err.to_json()).unwrap();
+        assert_eq!(json["request_id"], request_id.to_string());
+
+        let ok = Response::ok().with_request_id(request_id);
+        assert!(!ok.to_json().contains("request_id"));
     }
 }
@@ -0,0 +1,323 @@
+//! Multi-statement transactions
+//!
+//! `begin` opens a transaction and returns its `tx_id`. The transaction
+//! reads from a snapshot: the storage offset at `begin`. Storage is
+//! append-only and every commit is serialized under the global lock, so
+//! the records below that offset are exactly the data committed when the
+//! transaction began. A query that carries the `tx_id` reads as of it.
+//!
+//! An insert, update or delete that carries the `tx_id` is validated
+//! against its schema and buffered here: nothing reaches the WAL, storage
+//! or indexes. `commit` refuses the transaction with
+//! `AERO_TRANSACTION_CONFLICT` if another write committed to one of its
+//! documents since the snapshot (first committer wins), checks the
+//! buffered writes as a whole, appends all of their WAL records with a
+//! single append (one write, one sync), then applies them to storage and
+//! indexes. `rollback` discards the buffer.
+//!
+//! Buffered documents are charged to the `MemoryTracker` until the
+//! transaction ends, and are capped per transaction
+//! (`AERO_TRANSACTION_TOO_LARGE`). A transaction left idle longer than the
+//! timeout expires: its writes are discarded and its `tx_id` is refused
+//! with `AERO_TRANSACTION_EXPIRED`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use super::errors::{ApiError, ApiResult};
+use super::request::{DeleteRequest, InsertRequest, UpdateRequest};
+use crate::resource_limits::MemoryTracker;
+
+/// How long a transaction may stay idle by default
+pub const DEFAULT_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Most bytes of documents a transaction may buffer by default
+pub const DEFAULT_MAX_TRANSACTION_BYTES: u64 = 16 * 1024 * 1024;
+
+/// A write held until its transaction commits
+#[derive(Debug, Clone)]
+pub enum BufferedWrite {
+    Insert(InsertRequest),
+    Update(UpdateRequest),
+    Delete(DeleteRequest),
+}
+
+impl BufferedWrite {
+    /// ID of the document written
+    pub fn document_id(&self) -> Option<&str> {
+        match self {
+            BufferedWrite::Insert(r) => r.document.get("_id").and_then(|v| v.as_str()),
+            BufferedWrite::Update(r) => r.document.get("_id").and_then(|v| v.as_str()),
+            BufferedWrite::Delete(r) => Some(&r.document_id),
+        }
+    }
+
+    /// Bytes the write holds while buffered
+    fn size(&self) -> u64 {
+        let size = match self {
+            BufferedWrite::Insert(r) => r.document.to_string().len(),
+            BufferedWrite::Update(r) => r.document.to_string().len(),
+            BufferedWrite::Delete(r) => r.document_id.len(),
+        };
+        size as u64
+    }
+}
+
+/// A transaction ended by commit or rollback
+#[derive(Debug)]
+pub struct ClosedTransaction {
+    /// Storage offset the transaction read from
+    pub snapshot: u64,
+    /// Writes in the order buffered
+    pub writes: Vec<BufferedWrite>,
+}
+
+/// An open transaction; its buffered bytes are released when it is dropped
+#[derive(Debug)]
+struct Transaction {
+    last_used: Instant,
+    snapshot: u64,
+    writes: Vec<BufferedWrite>,
+    bytes: u64,
+    memory: Arc<MemoryTracker>,
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        self.memory.release(self.bytes);
+    }
+}
+
+/// Open transactions, keyed by `tx_id`
+#[derive(Debug)]
+pub struct TransactionRegistry {
+    transactions: Mutex<HashMap<Uuid, Transaction>>,
+    timeout: Duration,
+    max_bytes: u64,
+}
+
+impl Default for TransactionRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRANSACTION_TIMEOUT)
+    }
+}
+
+impl TransactionRegistry {
+    /// Registry whose transactions expire after `timeout` without use
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            transactions: Mutex::new(HashMap::new()),
+            timeout,
+            max_bytes: DEFAULT_MAX_TRANSACTION_BYTES,
+        }
+    }
+
+    /// Cap the bytes of documents each transaction may buffer
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// How long a transaction may stay idle
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Most bytes of documents a transaction may buffer
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// Open a transaction reading from `snapshot`, charging its buffered
+    /// writes to `memory`; discards any transactions that have expired
+    pub fn begin(&self, snapshot: u64, memory: Arc<MemoryTracker>) -> Uuid {
+        let mut transactions = self.transactions.lock().expect("Lock poisoned");
+        transactions.retain(|_, tx| tx.last_used.elapsed() <= self.timeout);
+
+        let tx_id = Uuid::new_v4();
+        transactions.insert(
+            tx_id,
+            Transaction {
+                last_used: Instant::now(),
+                snapshot,
+                writes: Vec::new(),
+                bytes: 0,
+                memory,
+            },
+        );
+        tx_id
+    }
+
+    /// Add a write to an open transaction; returns how many it now holds
+    pub fn buffer(&self, tx_id: Uuid, write: BufferedWrite) -> ApiResult<usize> {
+        let mut transactions = self.transactions.lock().expect("Lock poisoned");
+        let tx = self.open(&mut transactions, tx_id)?;
+        let size = write.size();
+        if tx.bytes + size > self.max_bytes {
+            return Err(ApiError::transaction_too_large(tx_id, self.max_bytes));
+        }
+        tx.memory
+            .try_allocate(size)
+            .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
+        tx.bytes += size;
+        tx.writes.push(write);
+        Ok(tx.writes.len())
+    }
+
+    /// Snapshot an open transaction reads from
+    pub fn snapshot(&self, tx_id: Uuid) -> ApiResult<u64> {
+        let mut transactions = self.transactions.lock().expect("Lock poisoned");
+        Ok(self.open(&mut transactions, tx_id)?.snapshot)
+    }
+
+    /// Close a transaction, returning its snapshot and writes
+    pub fn finish(&self, tx_id: Uuid) -> ApiResult<ClosedTransaction> {
+        let mut transactions = self.transactions.lock().expect("Lock poisoned");
+        self.open(&mut transactions, tx_id)?;
+        let mut tx = transactions.remove(&tx_id).expect("checked above");
+        Ok(ClosedTransaction {
+            snapshot: tx.snapshot,
+            writes: std::mem::take(&mut tx.writes),
+        })
+    }
+
+    /// Number of open transactions, expired ones included until swept
+    pub fn open_count(&self) -> usize {
+        self.transactions.lock().expect("Lock poisoned").len()
+    }
+
+    /// The transaction `tx_id` if it is open, marked as used now; an
+    /// expired one is discarded
+    fn open<'a>(
+        &self,
+        transactions: &'a mut HashMap<Uuid, Transaction>,
+        tx_id: Uuid,
+    ) -> ApiResult<&'a mut Transaction> {
+        let expired = match transactions.get(&tx_id) {
+            None => return Err(ApiError::transaction_not_found(tx_id)),
+            Some(tx) => tx.last_used.elapsed() > self.timeout,
+        };
+        if expired {
+            transactions.remove(&tx_id);
+            return Err(ApiError::transaction_expired(
+                tx_id,
+                self.timeout.as_millis(),
+            ));
+        }
+        let tx = transactions.get_mut(&tx_id).expect("checked above");
+        tx.last_used = Instant::now();
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn memory() -> Arc<MemoryTracker> {
+        Arc::new(MemoryTracker::new(1 << 20))
+    }
+
+    fn delete(document_id: &str) -> BufferedWrite {
+        BufferedWrite::Delete(DeleteRequest {
+            schema_id: "users".to_string(),
+            document_id: document_id.to_string(),
+            timeout_ms: None,
+            tx_id: None,
+        })
+    }
+
+    #[test]
+    fn test_finish_returns_writes_in_order_and_closes() {
+        let registry = TransactionRegistry::default();
+        let tx_id = registry.begin(42, memory());
+        assert_eq!(registry.buffer(tx_id, delete("a")).unwrap(), 1);
+        assert_eq!(registry.buffer(tx_id, delete("b")).unwrap(), 2);
+        assert_eq!(registry.snapshot(tx_id).unwrap(), 42);
+
+        let closed = registry.finish(tx_id).unwrap();
+        assert_eq!(closed.snapshot, 42);
+        let ids: Vec<_> = closed
+            .writes
+            .iter()
+            .map(|w| w.document_id().unwrap())
+            .collect();
+        assert_eq!(ids, ["a", "b"]);
+
+        let err = registry.finish(tx_id).unwrap_err();
+        assert_eq!(err.code(), "AERO_TRANSACTION_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_expired_transaction_is_refused_and_discarded() {
+        let registry = TransactionRegistry::new(Duration::ZERO);
+        let tx_id = registry.begin(0, memory());
+        std::thread::sleep(Duration::from_millis(2));
+
+        let err = registry.buffer(tx_id, delete("a")).unwrap_err();
+        assert_eq!(err.code(), "AERO_TRANSACTION_EXPIRED");
+        assert_eq!(registry.open_count(), 0);
+
+        // A new begin sweeps transactions nobody came back for, releasing
+        // what they buffered
+        let memory = memory();
+        let registry = TransactionRegistry::new(Duration::from_millis(50));
+        let idle = registry.begin(0, Arc::clone(&memory));
+        registry.buffer(idle, delete("a")).unwrap();
+        assert_eq!(memory.current(), 1);
+        std::thread::sleep(Duration::from_millis(60));
+        registry.begin(0, Arc::clone(&memory));
+        assert_eq!(registry.open_count(), 1);
+        assert_eq!(memory.current(), 0);
+    }
+
+    #[test]
+    fn test_timeout_counts_from_last_use() {
+        let registry = TransactionRegistry::new(Duration::from_millis(200));
+        let tx_id = registry.begin(0, memory());
+        for _ in 0..4 {
+            std::thread::sleep(Duration::from_millis(75));
+            registry.snapshot(tx_id).unwrap();
+        }
+        assert!(registry.finish(tx_id).is_ok());
+    }
+
+    #[test]
+    fn test_buffered_bytes_are_capped_and_charged() {
+        let memory = memory();
+        let registry = TransactionRegistry::default().with_max_bytes(4);
+        let tx_id = registry.begin(0, Arc::clone(&memory));
+        registry.buffer(tx_id, delete("abc")).unwrap();
+        assert_eq!(memory.current(), 3);
+
+        let err = registry.buffer(tx_id, delete("de")).unwrap_err();
+        assert_eq!(err.code(), "AERO_TRANSACTION_TOO_LARGE");
+        assert_eq!(memory.current(), 3);
+
+        // Ending the transaction releases its charge
+        registry.finish(tx_id).unwrap();
+        assert_eq!(memory.current(), 0);
+
+        // The tracker's limit refuses a write too
+        let registry = TransactionRegistry::default();
+        let tx_id = registry.begin(0, Arc::new(MemoryTracker::new(2)));
+        let err = registry.buffer(tx_id, delete("abc")).unwrap_err();
+        assert_eq!(err.code(), "AERO_SERVICE_UNAVAILABLE");
+    }
+
+    #[test]
+    fn test_insert_document_id() {
+        let write = BufferedWrite::Insert(InsertRequest {
+            schema_id: "users".to_string(),
+            schema_version: "v1".to_string(),
+            document: json!({"_id": "u1"}),
+            timeout_ms: None,
+            tx_id: None,
+        });
+        assert_eq!(write.document_id(), Some("u1"));
+    }
+}
@@ -13,12 +13,12 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use super::crypto::PasswordPolicy;
+use super::crypto::{PasswordHasher, PasswordPolicy};
 use super::email::{EmailSender, EmailTemplate};
 use super::errors::{AuthError, AuthResult};
-use super::jwt::{JwtConfig, JwtManager, TokenResponse};
+use super::jwt::{JwtConfig, JwtManager, TokenResponse, ADMIN_ROLE, SERVICE_ROLE};
 use super::rls::RlsContext;
-use super::session::{SessionConfig, SessionManager, SessionRepository};
+use super::session::{RevocationList, SessionConfig, SessionManager, SessionRepository};
 use super::user::{LoginRequest, SignupRequest, User, UserRepository};
 
 use chrono::{DateTime, Duration, Utc};
@@ -98,6 +98,7 @@ pub struct AuthService<U: UserRepository, S: SessionRepository> {
     session_manager: SessionManager<S>,
     jwt_manager: JwtManager,
     password_policy: PasswordPolicy,
+    password_hasher: PasswordHasher,
     reset_tokens: ResetTokenStore,
     email_sender: Arc<dyn EmailSender>,
 }
@@ -128,16 +129,32 @@ impl<U: UserRepository, S: SessionRepository> AuthService<U, S> {
         password_policy: PasswordPolicy,
         email_sender: Arc<dyn EmailSender>,
     ) -> Self {
+        let revocations = Arc::new(RevocationList::new(jwt_config.access_token_ttl));
         Self {
             user_repo: Arc::new(user_repo),
-            session_manager: SessionManager::new(session_config, session_repo),
-            jwt_manager: JwtManager::new(jwt_config),
+            session_manager: SessionManager::new(session_config, session_repo)
+                .with_revocations(Arc::clone(&revocations)),
+            jwt_manager: JwtManager::new(jwt_config).with_revocations(revocations),
             password_policy,
+            password_hasher: PasswordHasher::default(),
             reset_tokens: ResetTokenStore::default(),
             email_sender,
         }
     }
 
+    /// The token manager; clones share its keyring and revocation list,
+    /// so they accept exactly the tokens this service accepts
+    pub fn jwt_manager(&self) -> &JwtManager {
+        &self.jwt_manager
+    }
+
+    /// Hash new passwords with `hasher`; stored hashes made with other
+    /// parameters are upgraded on their owner's next login
+    pub fn with_password_hasher(mut self, hasher: PasswordHasher) -> Self {
+        self.password_hasher = hasher;
+        self
+    }
+
     /// Register a new user
     pub fn signup(&self, request: SignupRequest) -> AuthResult<(User, TokenResponse)> {
         // Check if email already exists
@@ -146,7 +163,12 @@ impl<U: UserRepository, S: SessionRepository> AuthService<U, S> {
         }
 
         // Create user
-        let mut user = User::new(request.email, &request.password, &self.password_policy)?;
+        let mut user = User::new_with_hasher(
+            request.email,
+            &request.password,
+            &self.password_policy,
+            &self.password_hasher,
+        )?;
         if let Some(metadata) = request.metadata {
             user.metadata = Some(metadata);
         }
@@ -155,10 +177,12 @@ impl<U: UserRepository, S: SessionRepository> AuthService<U, S> {
         self.user_repo.create(&user)?;
 
         // Create session
-        let (_, refresh_token) = self.session_manager.create_session(user.id, None, None)?;
+        let (session, refresh_token) = self.session_manager.create_session(user.id, None, None)?;
 
         // Generate tokens
-        let access_token = self.jwt_manager.generate_access_token(&user)?;
+        let access_token = self
+            .jwt_manager
+            .generate_session_token(&user, session.family_id)?;
         let token_response = TokenResponse::new(
             access_token,
             refresh_token,
@@ -171,21 +195,40 @@ impl<U: UserRepository, S: SessionRepository> AuthService<U, S> {
     /// Authenticate a user
     pub fn login(&self, request: LoginRequest) -> AuthResult<(User, TokenResponse)> {
         // Find user by email
-        let user = self
+        let mut user = self
             .user_repo
             .find_by_email(&request.email)?
             .ok_or(AuthError::InvalidCredentials)?;
 
         // Verify password
-        if !user.verify_password(&request.password)? {
+        if !self
+            .password_hasher
+            .verify(&request.password, &user.password_hash)?
+        {
             return Err(AuthError::InvalidCredentials);
         }
 
+        // Upgrade a hash made with an older algorithm or parameters. Best
+        // effort: the old hash still verifies, so a failed upgrade is
+        // retried on the next login rather than failing this one.
+        if self.password_hasher.needs_rehash(&user.password_hash) {
+            let mut upgraded = user.clone();
+            if upgraded
+                .set_password_with(&self.password_hasher, &request.password)
+                .is_ok()
+                && self.user_repo.update(&upgraded).is_ok()
+            {
+                user = upgraded;
+            }
+        }
+
         // Create session
-        let (_, refresh_token) = self.session_manager.create_session(user.id, None, None)?;
+        let (session, refresh_token) = self.session_manager.create_session(user.id, None, None)?;
 
         // Generate tokens
-        let access_token = self.jwt_manager.generate_access_token(&user)?;
+        let access_token = self
+            .jwt_manager
+            .generate_session_token(&user, session.family_id)?;
         let token_response = TokenResponse::new(
             access_token,
             refresh_token,
@@ -196,29 +239,12 @@ impl<U: UserRepository, S: SessionRepository> AuthService<U, S> {
     }
 
     /// Refresh access token
+    ///
+    /// Rotates the refresh token; reusing a rotated one revokes its session
+    /// family (`RefreshTokenReused`).
     pub fn refresh(&self, refresh_token: &str) -> AuthResult<TokenResponse> {
-        // Refresh session (invalidates old token)
-        let (_, new_refresh_token) = self.session_manager.refresh_session(refresh_token)?;
-
-        // Get session to find user
-        let session = self
-            .session_manager
-            .validate_refresh_token(&new_refresh_token)?;
-
-        // Get user
-        let user = self
-            .user_repo
-            .find_by_id(session.user_id)?
-            .ok_or(AuthError::InvalidCredentials)?;
-
-        // Generate new access token
-        let access_token = self.jwt_manager.generate_access_token(&user)?;
-
-        Ok(TokenResponse::new(
-            access_token,
-            new_refresh_token,
-            self.jwt_manager.get_expiration(),
-        ))
+        self.session_manager
+            .refresh(refresh_token, &*self.user_repo, &self.jwt_manager)
     }
 
     /// Logout (invalidate session)
@@ -227,6 +253,13 @@ impl<U: UserRepository, S: SessionRepository> AuthService<U, S> {
         self.session_manager.revoke_session(session.id)
     }
 
+    /// Delete expired sessions and password reset tokens, returning how
+    /// many sessions were deleted
+    pub fn expire_sessions(&self) -> AuthResult<usize> {
+        self.reset_tokens.cleanup_expired();
+        self.session_manager.delete_expired()
+    }
+
     /// Get user by ID
     pub fn get_user(&self, user_id: Uuid) -> AuthResult<User> {
         self.user_repo
@@ -273,7 +306,7 @@ impl<U: UserRepository, S: SessionRepository> AuthService<U, S> {
         self.password_policy.validate(new_password)?;
 
         // Update password
-        user.set_password(new_password)?;
+        user.set_password_with(&self.password_hasher, new_password)?;
         user.updated_at = chrono::Utc::now();
         self.user_repo.update(&user)?;
 
@@ -317,12 +350,12 @@ impl<U: UserRepository, S: SessionRepository> AuthService<U, S> {
             .ok_or(AuthError::InvalidCredentials)?;
 
         // Update password
-        user.set_password(new_password)?;
+        user.set_password_with(&self.password_hasher, new_password)?;
         user.updated_at = Utc::now();
         self.user_repo.update(&user)?;
 
         // Revoke all existing sessions for security
-        self.session_manager.revoke_all_user_sessions(user_id)?;
+        self.session_manager.revoke_all_for_user(user_id)?;
 
         // Send password changed notification
         let _ = self.email_sender.send(EmailTemplate::PasswordChanged {
@@ -338,6 +371,25 @@ impl<U: UserRepository, S: SessionRepository> AuthService<U, S> {
         let user_id = JwtManager::get_user_id(&claims)?;
         Ok(RlsContext::authenticated(user_id))
     }
+
+    /// Validate an access token carrying the admin role claim
+    pub fn validate_admin_token(&self, token: &str) -> AuthResult<Uuid> {
+        let claims = self.jwt_manager.validate_token(token)?;
+        if claims.role.as_deref() != Some(ADMIN_ROLE) {
+            return Err(AuthError::Unauthorized);
+        }
+        JwtManager::get_user_id(&claims)
+    }
+
+    /// Validate an access token carrying the admin or service_role claim,
+    /// returning the role
+    pub fn validate_operator_token(&self, token: &str) -> AuthResult<String> {
+        let claims = self.jwt_manager.validate_token(token)?;
+        match claims.role {
+            Some(role) if role == ADMIN_ROLE || role == SERVICE_ROLE => Ok(role),
+            _ => Err(AuthError::Unauthorized),
+        }
+    }
 }
 
 // ==================
@@ -445,6 +497,7 @@ impl IntoResponse for AuthError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::auth::crypto::PasswordHashParams;
     use crate::auth::session::InMemorySessionRepository;
     use crate::auth::user::InMemoryUserRepository;
 
@@ -573,6 +626,10 @@ mod tests {
         // Refresh should fail
         let result = service.refresh(&tokens.refresh_token);
         assert!(matches!(result, Err(AuthError::SessionRevoked)));
+
+        // The access token is rejected before it expires
+        let result = service.validate_access_token(&tokens.access_token);
+        assert!(matches!(result, Err(AuthError::SessionRevoked)));
     }
 
     #[test]
@@ -593,4 +650,70 @@ mod tests {
         assert!(ctx.is_authenticated);
         assert_eq!(ctx.user_id, Some(user.id));
     }
+
+    #[test]
+    fn test_expire_sessions() {
+        let service = AuthService::new(
+            InMemoryUserRepository::new(),
+            InMemorySessionRepository::new(),
+            JwtConfig::default(),
+            SessionConfig {
+                refresh_token_ttl: chrono::Duration::seconds(-1),
+            },
+            PasswordPolicy::default(),
+        );
+        let signup = SignupRequest {
+            email: "test@example.com".to_string(),
+            password: "password123".to_string(),
+            metadata: None,
+        };
+        let (_, tokens) = service.signup(signup).unwrap();
+
+        assert_eq!(service.expire_sessions().unwrap(), 1);
+        assert_eq!(service.expire_sessions().unwrap(), 0);
+        assert!(service.refresh(&tokens.refresh_token).is_err());
+    }
+
+    #[test]
+    fn test_login_rehashes_when_params_change() {
+        let weak = PasswordHashParams {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let strong = PasswordHashParams {
+            iterations: 2,
+            ..weak
+        };
+        let service =
+            create_test_service().with_password_hasher(PasswordHasher::new(weak).unwrap());
+        let signup = SignupRequest {
+            email: "test@example.com".to_string(),
+            password: "password123".to_string(),
+            metadata: None,
+        };
+        let (user, _) = service.signup(signup).unwrap();
+        assert_eq!(
+            PasswordHashParams::from_hash(&user.password_hash).unwrap(),
+            weak
+        );
+
+        // Raise the cost: the old hash still verifies and is replaced
+        let service = service.with_password_hasher(PasswordHasher::new(strong).unwrap());
+        let login = LoginRequest {
+            email: "test@example.com".to_string(),
+            password: "password123".to_string(),
+        };
+        let (logged_in, _) = service.login(login.clone()).unwrap();
+        assert_eq!(
+            PasswordHashParams::from_hash(&logged_in.password_hash).unwrap(),
+            strong
+        );
+        let stored = service.get_user(user.id).unwrap();
+        assert_eq!(stored.password_hash, logged_in.password_hash);
+
+        // Already current: left alone
+        let (again, _) = service.login(login).unwrap();
+        assert_eq!(again.password_hash, logged_in.password_hash);
+    }
 }
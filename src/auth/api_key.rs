@@ -0,0 +1,565 @@
+//! # API Keys
+//!
+//! Long-lived credentials for machine clients, sent in the `apikey` header
+//! instead of a JWT. A key resolves to a role and an optional tenant, which
+//! become the request's `RlsContext`.
+//!
+//! A key reads `aero_<8 hex>_<secret>`. The first 13 characters are its
+//! prefix: stored in the clear so operators can tell keys apart and so a
+//! key can be found without scanning every hash.
+//!
+//! ## Invariants
+//! - AUTH-AK1: The plaintext key is returned once, by `create_key`; only
+//!   its SHA-256 hash is stored
+//! - AUTH-AK2: Revoked and expired keys never authenticate
+//! - AUTH-AK3: Key hashes are compared in constant time
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::crypto::{constant_time_str_eq, generate_token, hash_token};
+use super::errors::{AuthError, AuthResult};
+use super::jwt::{ANON_ROLE, SERVICE_ROLE};
+use super::rls::RlsContext;
+
+/// Header machine clients send their key in
+pub const API_KEY_HEADER: &str = "apikey";
+
+/// Where `FileApiKeyRepository` keeps keys, relative to the data directory
+pub const API_KEYS_PATH: &str = "system/api_keys.json";
+
+/// `last_used_at` is written at most this often per key
+pub const LAST_USED_RESOLUTION: Duration = Duration::seconds(60);
+
+/// Leading characters of every key
+const KEY_SCHEME: &str = "aero_";
+
+/// Length of the identifying prefix: the scheme plus 8 hex characters
+const PREFIX_LEN: usize = KEY_SCHEME.len() + 8;
+
+/// API key model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// Unique key identifier
+    pub id: Uuid,
+
+    /// First characters of the key, shown to identify it
+    pub prefix: String,
+
+    /// SHA-256 hash of the key (never plaintext)
+    pub hash: String,
+
+    /// Role requests made with this key act as
+    pub role: String,
+
+    /// Tenant the key is scoped to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+
+    /// When the key was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the key stops authenticating (never if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// When the key last authenticated, to `LAST_USED_RESOLUTION`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+
+    /// Whether the key has been revoked
+    pub revoked: bool,
+}
+
+impl ApiKey {
+    /// Whether the key is past its expiry at `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// RLS context of requests made with this key
+    ///
+    /// Carries the `role`, `api_key_id` and (when scoped) `tenant_id`
+    /// claims, so claim policies apply to keys as they do to JWTs.
+    pub fn rls_context(&self) -> RlsContext {
+        let mut ctx = if self.role == SERVICE_ROLE {
+            RlsContext::service_role()
+        } else if self.role == ANON_ROLE {
+            RlsContext::anonymous()
+        } else {
+            RlsContext {
+                user_id: None,
+                is_authenticated: true,
+                is_service_role: false,
+                claims: HashMap::new(),
+            }
+        };
+        ctx = ctx
+            .with_claim("role", Value::String(self.role.clone()))
+            .with_claim("api_key_id", Value::String(self.id.to_string()));
+        match &self.tenant_id {
+            Some(tenant_id) => ctx.with_claim("tenant_id", Value::String(tenant_id.clone())),
+            None => ctx,
+        }
+    }
+}
+
+/// API key repository trait
+///
+/// Abstracts storage operations for API keys.
+pub trait ApiKeyRepository: Send + Sync {
+    /// Store a new key
+    fn create(&self, key: &ApiKey) -> AuthResult<()>;
+
+    /// Find a key by its ID
+    fn find_by_id(&self, id: Uuid) -> AuthResult<Option<ApiKey>>;
+
+    /// Find a key by its prefix
+    fn find_by_prefix(&self, prefix: &str) -> AuthResult<Option<ApiKey>>;
+
+    /// All keys, revoked and expired ones included
+    fn list(&self) -> AuthResult<Vec<ApiKey>>;
+
+    /// Replace a stored key
+    fn update(&self, key: &ApiKey) -> AuthResult<()>;
+}
+
+impl<R: ApiKeyRepository + ?Sized> ApiKeyRepository for Box<R> {
+    fn create(&self, key: &ApiKey) -> AuthResult<()> {
+        (**self).create(key)
+    }
+
+    fn find_by_id(&self, id: Uuid) -> AuthResult<Option<ApiKey>> {
+        (**self).find_by_id(id)
+    }
+
+    fn find_by_prefix(&self, prefix: &str) -> AuthResult<Option<ApiKey>> {
+        (**self).find_by_prefix(prefix)
+    }
+
+    fn list(&self) -> AuthResult<Vec<ApiKey>> {
+        (**self).list()
+    }
+
+    fn update(&self, key: &ApiKey) -> AuthResult<()> {
+        (**self).update(key)
+    }
+}
+
+/// Add `key` to `keys`, refusing a duplicate prefix
+fn insert_key(keys: &mut Vec<ApiKey>, key: &ApiKey) -> AuthResult<()> {
+    if keys.iter().any(|k| k.prefix == key.prefix) {
+        return Err(AuthError::StorageError(
+            "API key prefix already exists".to_string(),
+        ));
+    }
+    keys.push(key.clone());
+    Ok(())
+}
+
+/// Replace the key in `keys` with the same ID as `key`
+fn replace_key(keys: &mut [ApiKey], key: &ApiKey) -> AuthResult<()> {
+    let existing = keys
+        .iter_mut()
+        .find(|k| k.id == key.id)
+        .ok_or(AuthError::ApiKeyNotFound)?;
+    *existing = key.clone();
+    Ok(())
+}
+
+/// In-memory API key repository for testing
+#[derive(Debug, Default)]
+pub struct InMemoryApiKeyRepository {
+    keys: RwLock<Vec<ApiKey>>,
+}
+
+impl InMemoryApiKeyRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ApiKeyRepository for InMemoryApiKeyRepository {
+    fn create(&self, key: &ApiKey) -> AuthResult<()> {
+        let mut keys = self
+            .keys
+            .write()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+        insert_key(&mut keys, key)
+    }
+
+    fn find_by_id(&self, id: Uuid) -> AuthResult<Option<ApiKey>> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+        Ok(keys.iter().find(|k| k.id == id).cloned())
+    }
+
+    fn find_by_prefix(&self, prefix: &str) -> AuthResult<Option<ApiKey>> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+        Ok(keys.iter().find(|k| k.prefix == prefix).cloned())
+    }
+
+    fn list(&self) -> AuthResult<Vec<ApiKey>> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+        Ok(keys.clone())
+    }
+
+    fn update(&self, key: &ApiKey) -> AuthResult<()> {
+        let mut keys = self
+            .keys
+            .write()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+        replace_key(&mut keys, key)
+    }
+}
+
+/// API key repository stored in `system/api_keys.json`
+///
+/// Keys are held in memory and the whole file is rewritten (temp file,
+/// fsync, rename) on every change, so a revocation survives a restart as
+/// soon as `revoke` returns.
+#[derive(Debug)]
+pub struct FileApiKeyRepository {
+    path: PathBuf,
+    keys: RwLock<Vec<ApiKey>>,
+}
+
+impl FileApiKeyRepository {
+    /// Open the keys stored in a data directory
+    pub fn open(data_dir: &Path) -> AuthResult<Self> {
+        let path = data_dir.join(API_KEYS_PATH);
+        let keys = match fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents).map_err(|e| {
+                AuthError::StorageError(format!("Invalid API keys in {}: {}", path.display(), e))
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(AuthError::StorageError(format!(
+                    "Failed to read {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        Ok(Self {
+            path,
+            keys: RwLock::new(keys),
+        })
+    }
+
+    /// Path of the key file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Apply `change` and write the result; nothing changes if either fails
+    fn modify(&self, change: impl FnOnce(&mut Vec<ApiKey>) -> AuthResult<()>) -> AuthResult<()> {
+        let mut keys = self
+            .keys
+            .write()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+        let mut next = keys.clone();
+        change(&mut next)?;
+        self.persist(&next)?;
+        *keys = next;
+        Ok(())
+    }
+
+    fn persist(&self, keys: &[ApiKey]) -> AuthResult<()> {
+        let temp_path = self.path.with_extension("tmp");
+        serde_json::to_vec_pretty(keys)
+            .map_err(io::Error::other)
+            .and_then(|json| {
+                if let Some(dir) = self.path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                let mut file = fs::File::create(&temp_path)?;
+                io::Write::write_all(&mut file, &json)?;
+                file.sync_all()
+            })
+            .and_then(|()| fs::rename(&temp_path, &self.path))
+            .map_err(|e| {
+                AuthError::StorageError(format!("Failed to write {}: {}", self.path.display(), e))
+            })
+    }
+}
+
+impl ApiKeyRepository for FileApiKeyRepository {
+    fn create(&self, key: &ApiKey) -> AuthResult<()> {
+        self.modify(|keys| insert_key(keys, key))
+    }
+
+    fn find_by_id(&self, id: Uuid) -> AuthResult<Option<ApiKey>> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+        Ok(keys.iter().find(|k| k.id == id).cloned())
+    }
+
+    fn find_by_prefix(&self, prefix: &str) -> AuthResult<Option<ApiKey>> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+        Ok(keys.iter().find(|k| k.prefix == prefix).cloned())
+    }
+
+    fn list(&self) -> AuthResult<Vec<ApiKey>> {
+        let keys = self
+            .keys
+            .read()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+        Ok(keys.clone())
+    }
+
+    fn update(&self, key: &ApiKey) -> AuthResult<()> {
+        self.modify(|keys| replace_key(keys, key))
+    }
+}
+
+/// Issues, verifies and revokes API keys
+pub struct ApiKeyService<R: ApiKeyRepository> {
+    repository: R,
+}
+
+impl<R: ApiKeyRepository> ApiKeyService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    /// Create a key for `role`, optionally scoped to a tenant
+    ///
+    /// Returns the stored key and the plaintext key. The plaintext is not
+    /// kept anywhere and cannot be recovered later.
+    ///
+    /// # Invariant
+    /// AUTH-AK1: Only the hash of the key is stored
+    pub fn create_key(
+        &self,
+        role: &str,
+        tenant_id: Option<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> AuthResult<(ApiKey, String)> {
+        if role.trim().is_empty() {
+            return Err(AuthError::ValidationError(
+                "API key role must not be empty".to_string(),
+            ));
+        }
+        let now = Utc::now();
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(AuthError::ValidationError(
+                "API key expiry must be in the future".to_string(),
+            ));
+        }
+
+        let mut prefix = new_prefix();
+        while self.repository.find_by_prefix(&prefix)?.is_some() {
+            prefix = new_prefix();
+        }
+        let plaintext = format!("{}_{}", prefix, generate_token());
+
+        let key = ApiKey {
+            id: Uuid::new_v4(),
+            prefix,
+            hash: hash_token(&plaintext),
+            role: role.to_string(),
+            tenant_id,
+            created_at: now,
+            expires_at,
+            last_used_at: None,
+            revoked: false,
+        };
+        self.repository.create(&key)?;
+
+        Ok((key, plaintext))
+    }
+
+    /// Resolve a plaintext key to the context its requests run with
+    ///
+    /// # Invariants
+    /// - AUTH-AK2: Revoked and expired keys are refused
+    /// - AUTH-AK3: Hashes are compared in constant time
+    pub fn authenticate(&self, plaintext: &str) -> AuthResult<RlsContext> {
+        let prefix = plaintext
+            .get(..PREFIX_LEN)
+            .filter(|p| p.starts_with(KEY_SCHEME))
+            .ok_or(AuthError::InvalidCredentials)?;
+        let mut key = self
+            .repository
+            .find_by_prefix(prefix)?
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        if !constant_time_str_eq(&key.hash, &hash_token(plaintext)) {
+            return Err(AuthError::InvalidCredentials);
+        }
+        if key.revoked {
+            return Err(AuthError::ApiKeyRevoked);
+        }
+        let now = Utc::now();
+        if key.is_expired(now) {
+            return Err(AuthError::ApiKeyExpired);
+        }
+
+        // Record use, but not on every request
+        let stale = match key.last_used_at {
+            Some(last_used_at) => now - last_used_at >= LAST_USED_RESOLUTION,
+            None => true,
+        };
+        if stale {
+            key.last_used_at = Some(now);
+            let _ = self.repository.update(&key);
+        }
+
+        Ok(key.rls_context())
+    }
+
+    /// All keys, newest first
+    pub fn list_keys(&self) -> AuthResult<Vec<ApiKey>> {
+        let mut keys = self.repository.list()?;
+        keys.sort_by_key(|k| std::cmp::Reverse(k.created_at));
+        Ok(keys)
+    }
+
+    /// Revoke a key; it stops authenticating immediately
+    pub fn revoke_key(&self, id: Uuid) -> AuthResult<ApiKey> {
+        let mut key = self
+            .repository
+            .find_by_id(id)?
+            .ok_or(AuthError::ApiKeyNotFound)?;
+        key.revoked = true;
+        self.repository.update(&key)?;
+        Ok(key)
+    }
+}
+
+fn new_prefix() -> String {
+    format!(
+        "{}{}",
+        KEY_SCHEME,
+        &Uuid::new_v4().simple().to_string()[..8]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn service() -> ApiKeyService<InMemoryApiKeyRepository> {
+        ApiKeyService::new(InMemoryApiKeyRepository::new())
+    }
+
+    #[test]
+    fn test_created_key_authenticates_with_its_role_and_tenant() {
+        let service = service();
+        let (key, plaintext) = service
+            .create_key("reporting", Some("tenant-a".to_string()), None)
+            .unwrap();
+
+        assert!(plaintext.starts_with(&key.prefix));
+        assert_eq!(key.prefix.len(), PREFIX_LEN);
+
+        let ctx = service.authenticate(&plaintext).unwrap();
+        assert!(ctx.is_authenticated);
+        assert!(!ctx.can_bypass_rls());
+        assert_eq!(ctx.claims["role"], "reporting");
+        assert_eq!(ctx.claims["tenant_id"], "tenant-a");
+
+        let (_, service_key) = service.create_key(SERVICE_ROLE, None, None).unwrap();
+        assert!(service.authenticate(&service_key).unwrap().can_bypass_rls());
+    }
+
+    #[test]
+    fn test_wrong_key_with_known_prefix_rejected() {
+        let service = service();
+        let (key, _) = service.create_key("reporting", None, None).unwrap();
+
+        let forged = format!("{}_{}", key.prefix, generate_token());
+        assert!(matches!(
+            service.authenticate(&forged),
+            Err(AuthError::InvalidCredentials)
+        ));
+        assert!(matches!(
+            service.authenticate("short"),
+            Err(AuthError::InvalidCredentials)
+        ));
+    }
+
+    #[test]
+    fn test_revoked_and_expired_keys_rejected() {
+        let service = service();
+        let (key, plaintext) = service.create_key("reporting", None, None).unwrap();
+        service.revoke_key(key.id).unwrap();
+        assert!(matches!(
+            service.authenticate(&plaintext),
+            Err(AuthError::ApiKeyRevoked)
+        ));
+
+        let (mut key, plaintext) = service
+            .create_key("reporting", None, Some(Utc::now() + Duration::hours(1)))
+            .unwrap();
+        key.expires_at = Some(Utc::now() - Duration::seconds(1));
+        service.repository.update(&key).unwrap();
+        assert!(matches!(
+            service.authenticate(&plaintext),
+            Err(AuthError::ApiKeyExpired)
+        ));
+
+        assert!(matches!(
+            service.revoke_key(Uuid::new_v4()),
+            Err(AuthError::ApiKeyNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_last_used_is_rate_limited() {
+        let service = service();
+        let (key, plaintext) = service.create_key("reporting", None, None).unwrap();
+
+        service.authenticate(&plaintext).unwrap();
+        let first = service.repository.find_by_id(key.id).unwrap().unwrap();
+        let first_used = first.last_used_at.unwrap();
+
+        service.authenticate(&plaintext).unwrap();
+        let second = service.repository.find_by_id(key.id).unwrap().unwrap();
+        assert_eq!(second.last_used_at, Some(first_used));
+    }
+
+    #[test]
+    fn test_file_repository_never_stores_plaintext() {
+        let dir = TempDir::new().unwrap();
+        let service = ApiKeyService::new(FileApiKeyRepository::open(dir.path()).unwrap());
+        let (key, plaintext) = service.create_key("reporting", None, None).unwrap();
+
+        let stored = fs::read_to_string(dir.path().join(API_KEYS_PATH)).unwrap();
+        assert!(stored.contains(&key.prefix));
+        assert!(stored.contains(&key.hash));
+        assert!(!stored.contains(&plaintext));
+        assert!(!stored.contains(&plaintext[PREFIX_LEN + 1..]));
+
+        // Revocation survives reopening
+        service.revoke_key(key.id).unwrap();
+        let reopened = ApiKeyService::new(FileApiKeyRepository::open(dir.path()).unwrap());
+        assert!(matches!(
+            reopened.authenticate(&plaintext),
+            Err(AuthError::ApiKeyRevoked)
+        ));
+    }
+}
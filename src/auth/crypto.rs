@@ -2,15 +2,24 @@
 //!
 //! Password hashing and secure token generation.
 //!
+//! Password hashes are PHC strings (`$argon2id$v=19$m=...,t=...,p=...$...`)
+//! that record the algorithm and cost parameters they were made with, so a
+//! hash verifies whatever parameters `PasswordHasher` is configured with
+//! now. `PasswordHasher::needs_rehash` tells when a stored hash should be
+//! replaced, on the next login, by one made with the current parameters.
+//!
 //! ## Invariants
 //! - AUTH-S2: Passwords only stored as Argon2id hashes
 //! - AUTH-S3: Constant-time comparison for all secrets
 
 use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
+    password_hash::{
+        rand_core::OsRng, PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString,
+    },
+    Algorithm, Argon2, Params, Version,
 };
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use subtle::ConstantTimeEq;
 
@@ -81,29 +90,120 @@ pub fn validate_password(password: &str, policy: &PasswordPolicy) -> AuthResult<
     Ok(())
 }
 
-/// Hash a password using Argon2id
+/// Argon2id cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasswordHashParams {
+    /// Memory cost, in KiB
+    pub memory_kib: u32,
+    /// Time cost: passes over the memory
+    pub iterations: u32,
+    /// Degree of parallelism (lanes)
+    pub parallelism: u32,
+}
+
+impl Default for PasswordHashParams {
+    /// The argon2 crate's defaults (OWASP's minimum for Argon2id)
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl PasswordHashParams {
+    /// Parameters a PHC hash string was made with
+    pub fn from_hash(hash: &str) -> AuthResult<Self> {
+        let parsed = PasswordHash::new(hash).map_err(|_| AuthError::InvalidCredentials)?;
+        let params = Params::try_from(&parsed).map_err(|_| AuthError::InvalidCredentials)?;
+        Ok(Self {
+            memory_kib: params.m_cost(),
+            iterations: params.t_cost(),
+            parallelism: params.p_cost(),
+        })
+    }
+
+    fn to_params(self) -> AuthResult<Params> {
+        Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+            .map_err(|_| AuthError::HashingFailed)
+    }
+}
+
+/// Argon2id password hasher with configurable cost
 ///
 /// # Invariant
 /// AUTH-S2: Passwords only stored as Argon2id hashes
-pub fn hash_password(password: &str) -> AuthResult<String> {
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
+#[derive(Debug, Clone, Default)]
+pub struct PasswordHasher {
+    params: PasswordHashParams,
+}
+
+impl PasswordHasher {
+    /// Hasher using `params`; refused if Argon2 does not accept them
+    pub fn new(params: PasswordHashParams) -> AuthResult<Self> {
+        params.to_params()?;
+        Ok(Self { params })
+    }
+
+    /// Parameters new hashes are made with
+    pub fn params(&self) -> PasswordHashParams {
+        self.params
+    }
+
+    /// Hash a password with a fresh salt, as a PHC string
+    pub fn hash(&self, password: &str) -> AuthResult<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        let argon2 = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            self.params.to_params()?,
+        );
+
+        argon2
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|_| AuthError::HashingFailed)
+    }
+
+    /// Verify a password against a PHC hash, with the algorithm and
+    /// parameters the hash records
+    ///
+    /// Uses constant-time comparison internally (via argon2 crate).
+    pub fn verify(&self, password: &str, hash: &str) -> AuthResult<bool> {
+        let parsed_hash = PasswordHash::new(hash).map_err(|_| AuthError::InvalidCredentials)?;
+
+        Ok(Argon2::default()
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .is_ok())
+    }
+
+    /// Whether `hash` was made other than this hasher would make it now:
+    /// another algorithm or version, or other parameters
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hash) else {
+            return true;
+        };
+        parsed.algorithm != Algorithm::Argon2id.ident()
+            || parsed.version != Some(Version::V0x13.into())
+            || PasswordHashParams::from_hash(hash).ok() != Some(self.params)
+    }
+}
 
-    argon2
-        .hash_password(password.as_bytes(), &salt)
-        .map(|hash| hash.to_string())
-        .map_err(|_| AuthError::HashingFailed)
+/// Hash a password using Argon2id with the default parameters
+///
+/// # Invariant
+/// AUTH-S2: Passwords only stored as Argon2id hashes
+pub fn hash_password(password: &str) -> AuthResult<String> {
+    PasswordHasher::default().hash(password)
 }
 
 /// Verify a password against its hash
 ///
 /// Uses constant-time comparison internally (via argon2 crate).
 pub fn verify_password(password: &str, hash: &str) -> AuthResult<bool> {
-    let parsed_hash = PasswordHash::new(hash).map_err(|_| AuthError::InvalidCredentials)?;
-
-    Ok(Argon2::default()
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .is_ok())
+    PasswordHasher::default().verify(password, hash)
 }
 
 /// Generate a cryptographically secure random token
@@ -223,4 +323,87 @@ mod tests {
         assert!(!constant_time_str_eq("hello", "world"));
         assert!(!constant_time_str_eq("hello", "hello!"));
     }
+
+    fn cheap_params() -> PasswordHashParams {
+        PasswordHashParams {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn test_hasher_round_trip() {
+        let hasher = PasswordHasher::new(cheap_params()).unwrap();
+        let hash = hasher.hash("secure_password_123").unwrap();
+
+        assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+        assert!(hasher.verify("secure_password_123", &hash).unwrap());
+        assert!(!hasher.verify("wrong_password", &hash).unwrap());
+        // Hashes made with other parameters still verify
+        assert!(PasswordHasher::default()
+            .verify("secure_password_123", &hash)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_params_from_phc_string() {
+        let hash = PasswordHasher::new(PasswordHashParams {
+            memory_kib: 2048,
+            iterations: 3,
+            parallelism: 2,
+        })
+        .unwrap()
+        .hash("secure_password_123")
+        .unwrap();
+
+        let params = PasswordHashParams::from_hash(&hash).unwrap();
+        assert_eq!(params.memory_kib, 2048);
+        assert_eq!(params.iterations, 3);
+        assert_eq!(params.parallelism, 2);
+
+        assert!(matches!(
+            PasswordHashParams::from_hash("not a phc string"),
+            Err(AuthError::InvalidCredentials)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_params_rejected() {
+        let params = PasswordHashParams {
+            memory_kib: 1,
+            ..cheap_params()
+        };
+        assert!(matches!(
+            PasswordHasher::new(params),
+            Err(AuthError::HashingFailed)
+        ));
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let hasher = PasswordHasher::new(cheap_params()).unwrap();
+        let hash = hasher.hash("secure_password_123").unwrap();
+        assert!(!hasher.needs_rehash(&hash));
+
+        let stronger = PasswordHasher::new(PasswordHashParams {
+            iterations: 2,
+            ..cheap_params()
+        })
+        .unwrap();
+        assert!(stronger.needs_rehash(&hash));
+
+        // Argon2i and unparseable hashes are replaced too
+        let argon2i = Argon2::new(
+            Algorithm::Argon2i,
+            Version::V0x13,
+            cheap_params().to_params().unwrap(),
+        )
+        .hash_password(b"secure_password_123", &SaltString::generate(&mut OsRng))
+        .unwrap()
+        .to_string();
+        assert!(hasher.verify("secure_password_123", &argon2i).unwrap());
+        assert!(hasher.needs_rehash(&argon2i));
+        assert!(hasher.needs_rehash("plaintext"));
+    }
 }
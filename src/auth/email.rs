@@ -1,8 +1,13 @@
 //! # Email Integration
 //!
 //! Email sending for authentication flows.
+//!
+//! Templates render to a plain text and an HTML body. `SmtpEmailSender`
+//! sends both as a multipart message; `ConsoleEmailSender` prints them,
+//! for local development without a mail server.
 
-use std::sync::Arc;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
 
 use crate::auth::errors::{AuthError, AuthResult};
 
@@ -21,6 +26,9 @@ pub struct EmailConfig {
     /// SMTP password (should come from secrets)
     pub smtp_password: String,
 
+    /// Transport security
+    pub tls: SmtpTls,
+
     /// From email address
     pub from_email: String,
 
@@ -38,6 +46,7 @@ impl Default for EmailConfig {
             smtp_port: 1025,
             smtp_user: String::new(),
             smtp_password: String::new(),
+            tls: SmtpTls::None,
             from_email: "noreply@aerodb.local".to_string(),
             from_name: "AeroDB".to_string(),
             base_url: "http://localhost:3000".to_string(),
@@ -45,6 +54,18 @@ impl Default for EmailConfig {
     }
 }
 
+/// SMTP transport security
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Plaintext (local development servers only)
+    #[default]
+    None,
+    /// Upgrade a plaintext connection with STARTTLS (usually port 587)
+    StartTls,
+    /// TLS from the start (usually port 465)
+    Tls,
+}
+
 /// Email template types
 #[derive(Debug, Clone)]
 pub enum EmailTemplate {
@@ -97,6 +118,133 @@ impl EmailSender for MockEmailSender {
     }
 }
 
+/// A rendered email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub to: String,
+    pub subject: String,
+    /// Plain text body
+    pub text: String,
+    /// HTML body, with the same content as `text`
+    pub html: String,
+}
+
+/// Paragraphs of an email, rendered as text or HTML
+struct EmailParts {
+    to: String,
+    subject: &'static str,
+    before: Vec<String>,
+    link: Option<String>,
+    after: Vec<String>,
+}
+
+const GREETING: &str = "Hello,";
+const SIGNATURE: [&str; 2] = ["Thanks,", "The AeroDB Team"];
+
+impl EmailParts {
+    fn render(self) -> RenderedEmail {
+        let mut text = vec![GREETING.to_string()];
+        text.extend(self.before.iter().cloned());
+        text.extend(self.link.iter().cloned());
+        text.extend(self.after.iter().cloned());
+        text.push(SIGNATURE.join("\n"));
+
+        let paragraph = |p: &String| format!("<p>{}</p>", escape_html(p));
+        let mut html = vec![paragraph(&GREETING.to_string())];
+        html.extend(self.before.iter().map(paragraph));
+        html.extend(self.link.iter().map(|link| {
+            let link = escape_html(link);
+            format!("<p><a href=\"{}\">{}</a></p>", link, link)
+        }));
+        html.extend(self.after.iter().map(paragraph));
+        html.push(format!("<p>{}<br>{}</p>", SIGNATURE[0], SIGNATURE[1]));
+
+        RenderedEmail {
+            to: self.to,
+            subject: self.subject.to_string(),
+            text: text.join("\n\n"),
+            html: format!(
+                "<!DOCTYPE html>\n<html>\n<body>\n{}\n</body>\n</html>\n",
+                html.join("\n")
+            ),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl EmailTemplate {
+    /// Render to text and HTML; links to AeroDB pages start with `base_url`
+    pub fn render(&self, base_url: &str) -> RenderedEmail {
+        let parts = match self {
+            EmailTemplate::Verification { token, user_email } => EmailParts {
+                to: user_email.clone(),
+                subject: "Verify your email address",
+                before: vec![
+                    "Please verify your email address by clicking the link below:".to_string(),
+                ],
+                link: Some(format!("{}/auth/verify?token={}", base_url, token)),
+                after: vec![
+                    "This link will expire in 24 hours.".to_string(),
+                    "If you didn't create an account, you can ignore this email.".to_string(),
+                ],
+            },
+            EmailTemplate::PasswordReset { token, user_email } => EmailParts {
+                to: user_email.clone(),
+                subject: "Reset your password",
+                before: vec![
+                    "You requested to reset your password. Click the link below:".to_string(),
+                ],
+                link: Some(format!("{}/auth/reset-password?token={}", base_url, token)),
+                after: vec![
+                    "This link will expire in 1 hour.".to_string(),
+                    "If you didn't request this, you can ignore this email.".to_string(),
+                ],
+            },
+            EmailTemplate::PasswordChanged { user_email } => EmailParts {
+                to: user_email.clone(),
+                subject: "Your password was changed",
+                before: vec![
+                    "Your password was successfully changed.".to_string(),
+                    "If you didn't make this change, please contact support immediately."
+                        .to_string(),
+                ],
+                link: None,
+                after: Vec::new(),
+            },
+            EmailTemplate::MagicLink {
+                link,
+                user_email,
+                expires_minutes,
+            } => EmailParts {
+                to: user_email.clone(),
+                subject: "Your login link",
+                before: vec!["Click the link below to sign in:".to_string()],
+                link: Some(link.clone()),
+                after: vec![
+                    format!("This link will expire in {} minutes.", expires_minutes),
+                    "If you didn't request this link, you can safely ignore this email."
+                        .to_string(),
+                ],
+            },
+        };
+        parts.render()
+    }
+}
+
 /// SMTP email sender
 pub struct SmtpEmailSender {
     config: EmailConfig,
@@ -106,124 +254,109 @@ impl SmtpEmailSender {
     pub fn new(config: EmailConfig) -> Self {
         Self { config }
     }
-
-    fn render_template(&self, template: &EmailTemplate) -> (String, String, String) {
-        match template {
-            EmailTemplate::Verification { token, user_email } => {
-                let subject = "Verify your email address".to_string();
-                let link = format!("{}/auth/verify?token={}", self.config.base_url, token);
-                let body = format!(
-                    "Hello,\n\n\
-                    Please verify your email address by clicking the link below:\n\n\
-                    {}\n\n\
-                    This link will expire in 24 hours.\n\n\
-                    If you didn't create an account, you can ignore this email.\n\n\
-                    Thanks,\n\
-                    The AeroDB Team",
-                    link
-                );
-                (user_email.clone(), subject, body)
-            }
-            EmailTemplate::PasswordReset { token, user_email } => {
-                let subject = "Reset your password".to_string();
-                let link = format!(
-                    "{}/auth/reset-password?token={}",
-                    self.config.base_url, token
-                );
-                let body = format!(
-                    "Hello,\n\n\
-                    You requested to reset your password. Click the link below:\n\n\
-                    {}\n\n\
-                    This link will expire in 1 hour.\n\n\
-                    If you didn't request this, you can ignore this email.\n\n\
-                    Thanks,\n\
-                    The AeroDB Team",
-                    link
-                );
-                (user_email.clone(), subject, body)
-            }
-            EmailTemplate::PasswordChanged { user_email } => {
-                let subject = "Your password was changed".to_string();
-                let body = format!(
-                    "Hello,\n\n\
-                    Your password was successfully changed.\n\n\
-                    If you didn't make this change, please contact support immediately.\n\n\
-                    Thanks,\n\
-                    The AeroDB Team"
-                );
-                (user_email.clone(), subject, body)
-            }
-            EmailTemplate::MagicLink { link, user_email, expires_minutes } => {
-                let subject = "Your login link".to_string();
-                let body = format!(
-                    "Hello,\n\n\
-                    Click the link below to sign in:\n\n\
-                    {}\n\n\
-                    This link will expire in {} minutes.\n\n\
-                    If you didn't request this link, you can safely ignore this email.\n\n\
-                    Thanks,\n\
-                    The AeroDB Team",
-                    link, expires_minutes
-                );
-                (user_email.clone(), subject, body)
-            }
-        }
-    }
 }
 
 impl EmailSender for SmtpEmailSender {
     fn send(&self, template: EmailTemplate) -> AuthResult<()> {
         use lettre::{
-            message::header::ContentType, transport::smtp::authentication::Credentials, Message,
+            message::MultiPart, transport::smtp::authentication::Credentials, Message,
             SmtpTransport, Transport,
         };
 
-        let (to, subject, body) = self.render_template(&template);
+        let email = template.render(&self.config.base_url);
 
         // Build the email message
-        let email = Message::builder()
+        let message = Message::builder()
             .from(
                 format!("{} <{}>", self.config.from_name, self.config.from_email)
                     .parse()
                     .map_err(|e| AuthError::EmailError(format!("Invalid from address: {}", e)))?,
             )
-            .to(to
+            .to(email
+                .to
                 .parse()
                 .map_err(|e| AuthError::EmailError(format!("Invalid to address: {}", e)))?)
-            .subject(subject)
-            .header(ContentType::TEXT_PLAIN)
-            .body(body)
+            .subject(email.subject)
+            .multipart(MultiPart::alternative_plain_html(email.text, email.html))
             .map_err(|e| AuthError::EmailError(format!("Failed to build email: {}", e)))?;
 
         // Build SMTP transport
-        let mailer = if self.config.smtp_user.is_empty() {
-            // No authentication (for local development SMTP servers)
-            SmtpTransport::builder_dangerous(&self.config.smtp_host)
-                .port(self.config.smtp_port)
-                .build()
-        } else {
-            // With authentication
-            let creds = Credentials::new(
+        let relay_error = |e| AuthError::EmailError(format!("SMTP relay error: {}", e));
+        let mut builder = match self.config.tls {
+            SmtpTls::None => SmtpTransport::builder_dangerous(&self.config.smtp_host),
+            SmtpTls::StartTls => {
+                SmtpTransport::starttls_relay(&self.config.smtp_host).map_err(relay_error)?
+            }
+            SmtpTls::Tls => SmtpTransport::relay(&self.config.smtp_host).map_err(relay_error)?,
+        }
+        .port(self.config.smtp_port);
+        // No authentication for local development SMTP servers
+        if !self.config.smtp_user.is_empty() {
+            builder = builder.credentials(Credentials::new(
                 self.config.smtp_user.clone(),
                 self.config.smtp_password.clone(),
-            );
-
-            SmtpTransport::relay(&self.config.smtp_host)
-                .map_err(|e| AuthError::EmailError(format!("SMTP relay error: {}", e)))?
-                .credentials(creds)
-                .port(self.config.smtp_port)
-                .build()
-        };
+            ));
+        }
 
         // Send the email
-        mailer
-            .send(&email)
+        builder
+            .build()
+            .send(&message)
             .map_err(|e| AuthError::EmailError(format!("Failed to send email: {}", e)))?;
 
         Ok(())
     }
 }
 
+/// Email sender that prints rendered emails instead of sending them
+///
+/// For local development: magic links and reset tokens appear on stdout
+/// (or the given writer).
+pub struct ConsoleEmailSender<W: Write + Send = std::io::Stdout> {
+    config: EmailConfig,
+    out: Mutex<W>,
+}
+
+impl ConsoleEmailSender {
+    pub fn new(config: EmailConfig) -> Self {
+        Self::with_writer(config, std::io::stdout())
+    }
+}
+
+impl<W: Write + Send> ConsoleEmailSender<W> {
+    /// Print to `out` instead of stdout
+    pub fn with_writer(config: EmailConfig, out: W) -> Self {
+        Self {
+            config,
+            out: Mutex::new(out),
+        }
+    }
+
+    /// The writer, with everything printed to it
+    pub fn into_writer(self) -> W {
+        self.out.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<W: Write + Send> EmailSender for ConsoleEmailSender<W> {
+    fn send(&self, template: EmailTemplate) -> AuthResult<()> {
+        let email = template.render(&self.config.base_url);
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        write!(
+            out,
+            "From: {} <{}>\nTo: {}\nSubject: {}\n\n{}\n\n--- html ---\n{}\n",
+            self.config.from_name,
+            self.config.from_email,
+            email.to,
+            email.subject,
+            email.text,
+            email.html
+        )
+        .and_then(|_| out.flush())
+        .map_err(|e| AuthError::EmailError(format!("Failed to print email: {}", e)))
+    }
+}
+
 /// Create a boxed email sender based on config
 pub fn create_email_sender(config: Option<EmailConfig>) -> Arc<dyn EmailSender> {
     match config {
@@ -251,17 +384,78 @@ mod tests {
     }
 
     #[test]
-    fn test_smtp_template_rendering() {
-        let config = EmailConfig::default();
-        let sender = SmtpEmailSender::new(config);
-
-        let (to, subject, body) = sender.render_template(&EmailTemplate::PasswordReset {
+    fn test_template_rendering() {
+        let email = EmailTemplate::PasswordReset {
             token: "abc123".to_string(),
             user_email: "user@example.com".to_string(),
-        });
+        }
+        .render("http://localhost:3000");
+
+        assert_eq!(email.to, "user@example.com");
+        assert_eq!(email.subject, "Reset your password");
+        assert!(email
+            .text
+            .contains("http://localhost:3000/auth/reset-password?token=abc123"));
+    }
+
+    #[test]
+    fn test_magic_link_rendering() {
+        let email = EmailTemplate::MagicLink {
+            link: "https://app.example.com/auth/magic?token=t0k&redirect=<home>".to_string(),
+            user_email: "user@example.com".to_string(),
+            expires_minutes: 15,
+        }
+        .render("http://localhost:3000");
+
+        assert_eq!(email.subject, "Your login link");
+        assert_eq!(
+            email.text,
+            "Hello,\n\n\
+             Click the link below to sign in:\n\n\
+             https://app.example.com/auth/magic?token=t0k&redirect=<home>\n\n\
+             This link will expire in 15 minutes.\n\n\
+             If you didn't request this link, you can safely ignore this email.\n\n\
+             Thanks,\n\
+             The AeroDB Team"
+        );
+
+        // The link is escaped in both the href and the text
+        let link = "https://app.example.com/auth/magic?token=t0k&amp;redirect=&lt;home&gt;";
+        assert!(email
+            .html
+            .contains(&format!("<p><a href=\"{}\">{}</a></p>", link, link)));
+        assert!(email
+            .html
+            .contains("<p>This link will expire in 15 minutes.</p>"));
+        assert!(email.html.contains(
+            "<p>If you didn&#39;t request this link, you can safely ignore this email.</p>"
+        ));
+        assert!(!email.html.contains("<home>"));
+    }
+
+    #[test]
+    fn test_console_email_sender() {
+        let sender = ConsoleEmailSender::with_writer(EmailConfig::default(), Vec::new());
+
+        sender
+            .send(EmailTemplate::MagicLink {
+                link: "https://app.example.com/magic?token=t0k".to_string(),
+                user_email: "user@example.com".to_string(),
+                expires_minutes: 15,
+            })
+            .unwrap();
+        sender
+            .send(EmailTemplate::PasswordChanged {
+                user_email: "user@example.com".to_string(),
+            })
+            .unwrap();
 
-        assert_eq!(to, "user@example.com");
-        assert_eq!(subject, "Reset your password");
-        assert!(body.contains("abc123"));
+        let output = String::from_utf8(sender.into_writer()).unwrap();
+        assert!(output.starts_with(
+            "From: AeroDB <noreply@aerodb.local>\nTo: user@example.com\nSubject: Your login link\n"
+        ));
+        assert!(output.contains("https://app.example.com/magic?token=t0k\n"));
+        assert!(output.contains("--- html ---\n<!DOCTYPE html>"));
+        assert!(output.contains("Subject: Your password was changed\n"));
     }
 }
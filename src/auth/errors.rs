@@ -44,6 +44,26 @@ pub enum AuthError {
     #[error("Session has been revoked")]
     SessionRevoked,
 
+    /// An already rotated refresh token was presented again; its session
+    /// family has been revoked
+    #[error("Refresh token reuse detected; session revoked")]
+    RefreshTokenReused,
+
+    // ==================
+    // API Key Errors
+    // ==================
+    /// API key has been revoked
+    #[error("API key has been revoked")]
+    ApiKeyRevoked,
+
+    /// API key is past its expiry
+    #[error("API key expired")]
+    ApiKeyExpired,
+
+    /// No API key with this ID
+    #[error("API key not found")]
+    ApiKeyNotFound,
+
     // ==================
     // JWT Errors
     // ==================
@@ -170,6 +190,9 @@ impl AuthError {
             AuthError::SessionInvalid => 401,
             AuthError::InvalidRefreshToken => 401,
             AuthError::SessionRevoked => 401,
+            AuthError::RefreshTokenReused => 401,
+            AuthError::ApiKeyRevoked => 401,
+            AuthError::ApiKeyExpired => 401,
             AuthError::TokenExpired => 401,
             AuthError::InvalidSignature => 401,
             AuthError::AuthenticationRequired => 401,
@@ -201,6 +224,7 @@ impl AuthError {
             AuthError::RateLimitExceeded(_) => 429,
             AuthError::ValidationError(_) => 400,
             AuthError::UserNotFound => 404,
+            AuthError::ApiKeyNotFound => 404,
         }
     }
 
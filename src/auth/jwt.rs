@@ -6,13 +6,30 @@
 //! - AUTH-JWT1: Stateless validation (no DB lookup)
 //! - AUTH-JWT2: Short expiration (15 minutes)
 //! - AUTH-JWT3: No secrets in token
+//! - AUTH-JWT4: Service-role tokens are signed and validated only with the
+//!   service-role secret
+//! - AUTH-JWT5: Tokens carry the `kid` of the key that signed them, and
+//!   validate only against a key of the ring that has not been retired
+//! - AUTH-JWT6: A token whose session was revoked, or that was revoked
+//!   itself by its `jti`, is rejected before it expires, when a revocation
+//!   list is attached
+//!
+//! ## Key rotation
+//! Anon and user tokens are signed with the current key of a keyring.
+//! `JwtManager::rotate` makes a new secret current; the previous key keeps
+//! validating the tokens it signed for a grace period, then is retired.
+
+use std::sync::{Arc, RwLock};
 
-use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::errors::{AuthError, AuthResult};
+use super::session::RevocationList;
 use super::user::User;
 
 /// JWT claims for access tokens
@@ -38,8 +55,37 @@ pub struct JwtClaims {
 
     /// Whether email is verified
     pub email_verified: bool,
+
+    /// Tenant the token is scoped to (schema-per-tenant isolation)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+
+    /// Access level: `ANON_ROLE`, `AUTHENTICATED_ROLE`, `SERVICE_ROLE` or
+    /// `ADMIN_ROLE`. Absent means authenticated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+
+    /// Login session the token was issued for: the session family ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+
+    /// Unique ID of the token, to revoke it alone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
 }
 
+/// Role claim of tokens allowed to manage API keys
+pub const ADMIN_ROLE: &str = "admin";
+
+/// Role claim of unauthenticated clients (the anon key)
+pub const ANON_ROLE: &str = "anon";
+
+/// Role claim of signed-in users, whose rows RLS restricts
+pub const AUTHENTICATED_ROLE: &str = "authenticated";
+
+/// Role claim of trusted backends; bypasses RLS
+pub const SERVICE_ROLE: &str = "service_role";
+
 /// JWT configuration
 #[derive(Debug, Clone)]
 pub struct JwtConfig {
@@ -54,6 +100,11 @@ pub struct JwtConfig {
 
     /// Audience identifier
     pub audience: String,
+
+    /// Secret signing service-role tokens. Must differ from `secret`, so
+    /// that the key signing anon and user tokens cannot mint service
+    /// tokens. Service-role tokens are refused while unset.
+    pub service_role_secret: Option<String>,
 }
 
 impl Default for JwtConfig {
@@ -63,29 +114,117 @@ impl Default for JwtConfig {
             access_token_ttl: Duration::minutes(15),
             issuer: "aerodb".to_string(),
             audience: "aerodb".to_string(),
+            service_role_secret: None,
         }
     }
 }
 
+/// Key ID of the key built from `JwtConfig::secret`
+pub const INITIAL_KEY_ID: &str = "k0";
+
+/// A signing key of the ring
+struct RingKey {
+    /// Key ID, carried in the header of the tokens it signs
+    kid: String,
+    /// When the key stops validating tokens; `None` while it is current
+    not_after: Option<DateTime<Utc>>,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl RingKey {
+    fn new(kid: String, secret: &str) -> Self {
+        Self {
+            kid,
+            not_after: None,
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+        }
+    }
+
+    fn is_retired(&self, now: DateTime<Utc>) -> bool {
+        self.not_after.is_some_and(|not_after| not_after <= now)
+    }
+}
+
 /// JWT manager for token generation and validation
 #[derive(Clone)]
 pub struct JwtManager {
     config: JwtConfig,
-    encoding_key: EncodingKey,
-    decoding_key: DecodingKey,
+    /// Signing keys, oldest first; the last one is current. Shared by
+    /// clones, so a rotation reaches every holder.
+    ring: Arc<RwLock<Vec<RingKey>>>,
+    /// Keys for service-role tokens, if a service-role secret is configured
+    service_keys: Option<(EncodingKey, DecodingKey)>,
+    /// Revoked sessions, whose tokens are rejected
+    revocations: Option<Arc<RevocationList>>,
 }
 
 impl JwtManager {
     /// Create a new JWT manager with the given configuration
     pub fn new(config: JwtConfig) -> Self {
-        let encoding_key = EncodingKey::from_secret(config.secret.as_bytes());
-        let decoding_key = DecodingKey::from_secret(config.secret.as_bytes());
+        let initial = RingKey::new(INITIAL_KEY_ID.to_string(), &config.secret);
+        let service_keys = config.service_role_secret.as_ref().map(|secret| {
+            (
+                EncodingKey::from_secret(secret.as_bytes()),
+                DecodingKey::from_secret(secret.as_bytes()),
+            )
+        });
 
         Self {
             config,
-            encoding_key,
-            decoding_key,
+            ring: Arc::new(RwLock::new(vec![initial])),
+            service_keys,
+            revocations: None,
+        }
+    }
+
+    /// Reject tokens of the sessions revoked in `revocations`
+    pub fn with_revocations(mut self, revocations: Arc<RevocationList>) -> Self {
+        self.revocations = Some(revocations);
+        self
+    }
+
+    /// Make `new_secret` the current signing key
+    ///
+    /// Tokens signed with the previous key stay valid for the access token
+    /// lifetime, long enough for all of them to expire. Returns the new
+    /// key ID.
+    pub fn rotate(&self, new_secret: &str) -> String {
+        self.rotate_with_grace(new_secret, self.config.access_token_ttl)
+    }
+
+    /// Make `new_secret` the current signing key, retiring the previous
+    /// one after `grace`
+    ///
+    /// Keys already past their grace period are dropped from the ring.
+    pub fn rotate_with_grace(&self, new_secret: &str, grace: Duration) -> String {
+        let now = Utc::now();
+        let mut ring = self.ring.write().unwrap();
+        let generation = ring
+            .iter()
+            .filter_map(|key| key.kid.strip_prefix('k')?.parse::<u64>().ok())
+            .max()
+            .map_or(0, |g| g + 1);
+        let kid = format!("k{}", generation);
+
+        if let Some(previous) = ring.last_mut() {
+            previous.not_after.get_or_insert(now + grace);
         }
+        ring.retain(|key| !key.is_retired(now));
+        ring.push(RingKey::new(kid.clone(), new_secret));
+        kid
+    }
+
+    /// IDs of the keys that validate tokens, oldest first; the last one
+    /// signs new tokens
+    pub fn key_ids(&self) -> Vec<String> {
+        let now = Utc::now();
+        let ring = self.ring.read().unwrap();
+        ring.iter()
+            .filter(|key| !key.is_retired(now))
+            .map(|key| key.kid.clone())
+            .collect()
     }
 
     /// Generate an access token for a user
@@ -94,41 +233,189 @@ impl JwtManager {
     /// - AUTH-JWT2: Token expires in 15 minutes
     /// - AUTH-JWT3: No secrets in token (only user ID, email, verification status)
     pub fn generate_access_token(&self, user: &User) -> AuthResult<String> {
+        self.generate_token(user, None, None)
+    }
+
+    /// Generate an access token for a user's login session
+    ///
+    /// `session_id` (the session family ID) goes in the `sid` claim, so
+    /// that revoking the session revokes the token.
+    pub fn generate_session_token(&self, user: &User, session_id: Uuid) -> AuthResult<String> {
+        let sub = user.id.to_string();
+        let mut claims = self.claims(&sub, &user.email, user.email_verified, None, None);
+        claims.sid = Some(session_id.to_string());
+        self.sign(&claims)
+    }
+
+    /// Generate an access token carrying the admin role claim
+    pub fn generate_admin_token(&self, user: &User) -> AuthResult<String> {
+        self.generate_token(user, None, Some(ADMIN_ROLE.to_string()))
+    }
+
+    /// Generate the anon key: a token for clients that have not signed in
+    pub fn generate_anon_token(&self) -> AuthResult<String> {
+        let claims = self.claims(ANON_ROLE, "", false, None, Some(ANON_ROLE.to_string()));
+        self.sign(&claims)
+    }
+
+    /// Generate a service-role token, signed with the service-role secret
+    ///
+    /// # Invariant
+    /// AUTH-JWT4: Fails unless a service-role secret is configured
+    pub fn generate_service_role_token(&self) -> AuthResult<String> {
+        let (encoding_key, _) = self
+            .service_keys
+            .as_ref()
+            .ok_or(AuthError::TokenGenerationFailed)?;
+        let role = Some(SERVICE_ROLE.to_string());
+        let claims = self.claims(SERVICE_ROLE, "", false, None, role);
+        encode(&Header::default(), &claims, encoding_key)
+            .map_err(|_| AuthError::TokenGenerationFailed)
+    }
+
+    /// Generate an access token for a user scoped to a tenant
+    ///
+    /// The `tenant_id` claim is what tenant RLS policies bind rows to.
+    pub fn generate_tenant_token(&self, user: &User, tenant_id: Uuid) -> AuthResult<String> {
+        self.generate_token(user, Some(tenant_id.to_string()), None)
+    }
+
+    fn generate_token(
+        &self,
+        user: &User,
+        tenant_id: Option<String>,
+        role: Option<String>,
+    ) -> AuthResult<String> {
+        let sub = user.id.to_string();
+        let claims = self.claims(&sub, &user.email, user.email_verified, tenant_id, role);
+        self.sign(&claims)
+    }
+
+    /// Sign with the current key, naming it in the header
+    fn sign(&self, claims: &JwtClaims) -> AuthResult<String> {
+        let ring = self.ring.read().unwrap();
+        let key = ring.last().ok_or(AuthError::TokenGenerationFailed)?;
+        let header = Header {
+            kid: Some(key.kid.clone()),
+            ..Header::default()
+        };
+        encode(&header, claims, &key.encoding_key).map_err(|_| AuthError::TokenGenerationFailed)
+    }
+
+    fn claims(
+        &self,
+        sub: &str,
+        email: &str,
+        email_verified: bool,
+        tenant_id: Option<String>,
+        role: Option<String>,
+    ) -> JwtClaims {
         let now = Utc::now();
         let exp = now + self.config.access_token_ttl;
 
-        let claims = JwtClaims {
-            sub: user.id.to_string(),
-            email: user.email.clone(),
+        JwtClaims {
+            sub: sub.to_string(),
+            email: email.to_string(),
             iat: now.timestamp(),
             exp: exp.timestamp(),
             aud: self.config.audience.clone(),
             iss: self.config.issuer.clone(),
-            email_verified: user.email_verified,
-        };
-
-        encode(&Header::default(), &claims, &self.encoding_key)
-            .map_err(|_| AuthError::TokenGenerationFailed)
+            email_verified,
+            tenant_id,
+            role,
+            sid: None,
+            jti: Some(Uuid::new_v4().to_string()),
+        }
     }
 
     /// Validate an access token and extract claims
     ///
-    /// # Invariant
-    /// AUTH-JWT1: Validation is stateless (no DB lookup required)
+    /// # Invariants
+    /// - AUTH-JWT1: Validation is stateless (no DB lookup required)
+    /// - AUTH-JWT4: A token claiming the service role is valid only if
+    ///   signed with the service-role secret
+    /// - AUTH-JWT5: Only keys of the ring that are not retired validate
+    /// - AUTH-JWT6: Revoked tokens, and tokens of revoked sessions, fail
+    ///   with `SessionRevoked`
     pub fn validate_token(&self, token: &str) -> AuthResult<JwtClaims> {
+        let (claims, service_signed) = match (self.decode_with_ring(token), &self.service_keys) {
+            (Err(AuthError::InvalidSignature), Some((_, service_key))) => {
+                (self.decode(token, service_key)?, true)
+            }
+            (result, _) => (result?, false),
+        };
+
+        // Service-role tokens are signed with the service-role key and
+        // only with it
+        if (claims.role.as_deref() == Some(SERVICE_ROLE)) != service_signed {
+            return Err(AuthError::InvalidSignature);
+        }
+        let revoked = self.revocations.as_ref().is_some_and(|revocations| {
+            claims
+                .sid
+                .as_deref()
+                .is_some_and(|sid| revocations.is_revoked(sid))
+                || claims
+                    .jti
+                    .as_deref()
+                    .is_some_and(|jti| revocations.is_token_revoked(jti))
+        });
+        if revoked {
+            return Err(AuthError::SessionRevoked);
+        }
+        Ok(claims)
+    }
+
+    /// Revoke a single access token by its `jti`, until it expires
+    ///
+    /// Revoking an expired token does nothing. Fails with `InvalidToken`
+    /// if the token has no `jti` or no revocation list is attached.
+    pub fn revoke_token(&self, token: &str) -> AuthResult<()> {
+        let claims = match self.validate_token(token) {
+            Err(AuthError::TokenExpired) | Err(AuthError::SessionRevoked) => return Ok(()),
+            result => result?,
+        };
+        let (Some(revocations), Some(jti)) = (&self.revocations, &claims.jti) else {
+            return Err(AuthError::InvalidToken);
+        };
+        let expires_at = DateTime::from_timestamp(claims.exp, 0).ok_or(AuthError::InvalidToken)?;
+        revocations.revoke_token(jti, expires_at);
+        Ok(())
+    }
+
+    /// Decode with the ring key named by the token's `kid`, or, for tokens
+    /// without one, with any key of the ring
+    fn decode_with_ring(&self, token: &str) -> AuthResult<JwtClaims> {
+        let kid = decode_header(token)
+            .map_err(|_| AuthError::MalformedToken)?
+            .kid;
+        let now = Utc::now();
+        let ring = self.ring.read().unwrap();
+        let candidates = ring
+            .iter()
+            .rev()
+            .filter(|key| !key.is_retired(now))
+            .filter(|key| kid.as_ref().is_none_or(|kid| *kid == key.kid));
+
+        for key in candidates {
+            match self.decode(token, &key.decoding_key) {
+                Err(AuthError::InvalidSignature) => continue,
+                result => return result,
+            }
+        }
+        Err(AuthError::InvalidSignature)
+    }
+
+    fn decode(&self, token: &str, key: &DecodingKey) -> AuthResult<JwtClaims> {
         let mut validation = Validation::new(Algorithm::HS256);
         validation.set_audience(&[&self.config.audience]);
         validation.set_issuer(&[&self.config.issuer]);
 
         let token_data =
-            decode::<JwtClaims>(token, &self.decoding_key, &validation).map_err(|e| {
-                match e.kind() {
-                    jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
-                    jsonwebtoken::errors::ErrorKind::InvalidSignature => {
-                        AuthError::InvalidSignature
-                    }
-                    _ => AuthError::MalformedToken,
-                }
+            decode::<JwtClaims>(token, key, &validation).map_err(|e| match e.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+                jsonwebtoken::errors::ErrorKind::InvalidSignature => AuthError::InvalidSignature,
+                _ => AuthError::MalformedToken,
             })?;
 
         Ok(token_data.claims)
@@ -185,6 +472,7 @@ mod tests {
             access_token_ttl: Duration::minutes(15),
             issuer: "test".to_string(),
             audience: "test".to_string(),
+            service_role_secret: None,
         })
     }
 
@@ -270,6 +558,10 @@ mod tests {
             aud: "test".to_string(),
             iss: "test".to_string(),
             email_verified: false,
+            tenant_id: None,
+            role: None,
+            sid: None,
+            jti: None,
         };
 
         let token = encode(&Header::default(), &claims, &encoding_key).unwrap();
@@ -279,6 +571,7 @@ mod tests {
             access_token_ttl: Duration::minutes(15),
             issuer: "test".to_string(),
             audience: "test".to_string(),
+            service_role_secret: None,
         });
 
         let result = manager.validate_token(&token);
@@ -295,6 +588,116 @@ mod tests {
         let user_id = JwtManager::get_user_id(&claims).unwrap();
 
         assert_eq!(user_id, user.id);
+        assert!(claims.tenant_id.is_none());
+    }
+
+    #[test]
+    fn test_tenant_token_carries_tenant_claim() {
+        let manager = create_test_manager();
+        let user = create_test_user();
+        let tenant_id = Uuid::new_v4();
+
+        let token = manager.generate_tenant_token(&user, tenant_id).unwrap();
+        let claims = manager.validate_token(&token).unwrap();
+
+        assert_eq!(claims.tenant_id, Some(tenant_id.to_string()));
+    }
+
+    #[test]
+    fn test_service_role_token_needs_service_secret() {
+        let manager = JwtManager::new(JwtConfig {
+            service_role_secret: Some("service_secret_for_testing".to_string()),
+            ..JwtConfig::default()
+        });
+
+        let token = manager.generate_service_role_token().unwrap();
+        let claims = manager.validate_token(&token).unwrap();
+        assert_eq!(claims.role.as_deref(), Some(SERVICE_ROLE));
+
+        // The anon key validates, but never as the service role
+        let anon = manager.generate_anon_token().unwrap();
+        let claims = manager.validate_token(&anon).unwrap();
+        assert_eq!(claims.role.as_deref(), Some(ANON_ROLE));
+
+        // A service-role claim signed with the user secret is forged
+        let claims = manager.claims(SERVICE_ROLE, "", false, None, Some(SERVICE_ROLE.into()));
+        let forged = manager.sign(&claims).unwrap();
+        assert!(matches!(
+            manager.validate_token(&forged),
+            Err(AuthError::InvalidSignature)
+        ));
+
+        // Any other role signed with the service secret is rejected too
+        let claims = manager.claims("user", "", false, None, Some(ANON_ROLE.into()));
+        let (service_key, _) = manager.service_keys.as_ref().unwrap();
+        let misused = encode(&Header::default(), &claims, service_key).unwrap();
+        assert!(matches!(
+            manager.validate_token(&misused),
+            Err(AuthError::InvalidSignature)
+        ));
+
+        // Without a service-role secret no service token can be issued
+        assert!(matches!(
+            create_test_manager().generate_service_role_token(),
+            Err(AuthError::TokenGenerationFailed)
+        ));
+    }
+
+    #[test]
+    fn test_rotation_keeps_old_tokens_valid_during_grace() {
+        let manager = create_test_manager();
+        let user = create_test_user();
+        let before = manager.generate_access_token(&user).unwrap();
+        assert_eq!(
+            decode_header(&before).unwrap().kid.as_deref(),
+            Some(INITIAL_KEY_ID)
+        );
+
+        // A clone shares the ring, so rotating it reaches this manager too
+        let kid = manager.clone().rotate("rotated_secret_for_testing");
+        assert_eq!(kid, "k1");
+        assert_eq!(manager.key_ids(), vec!["k0", "k1"]);
+
+        let after = manager.generate_access_token(&user).unwrap();
+        assert_eq!(decode_header(&after).unwrap().kid, Some(kid));
+        assert_eq!(
+            manager.validate_token(&after).unwrap().sub,
+            user.id.to_string()
+        );
+        assert_eq!(
+            manager.validate_token(&before).unwrap().sub,
+            user.id.to_string()
+        );
+
+        // A token naming one key but signed with another is refused
+        let (payload, signature) = before.split_once('.').unwrap().1.split_once('.').unwrap();
+        let header = after.split('.').next().unwrap();
+        let swapped = format!("{}.{}.{}", header, payload, signature);
+        assert!(matches!(
+            manager.validate_token(&swapped),
+            Err(AuthError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_rotation_retires_old_keys_after_grace() {
+        let manager = create_test_manager();
+        let user = create_test_user();
+        let k0_token = manager.generate_access_token(&user).unwrap();
+
+        manager.rotate_with_grace("second_secret", Duration::zero());
+        let k1_token = manager.generate_access_token(&user).unwrap();
+        assert!(matches!(
+            manager.validate_token(&k0_token),
+            Err(AuthError::InvalidSignature)
+        ));
+        assert!(manager.validate_token(&k1_token).is_ok());
+
+        // Retired keys leave the ring at the next rotation
+        manager.rotate("third_secret");
+        assert_eq!(manager.key_ids(), vec!["k1", "k2"]);
+        assert!(manager.validate_token(&k1_token).is_ok());
+        assert!(manager.validate_token(&k0_token).is_err());
     }
 
     #[test]
@@ -310,4 +713,49 @@ mod tests {
         assert!(!token.contains("password"));
         assert!(!token.contains(&user.password_hash));
     }
+
+    #[test]
+    fn test_revoked_token_rejected_by_jti() {
+        let user = create_test_user();
+        assert!(matches!(
+            create_test_manager()
+                .revoke_token(&create_test_manager().generate_access_token(&user).unwrap()),
+            Err(AuthError::InvalidToken)
+        ));
+
+        let manager = create_test_manager().with_revocations(Arc::new(RevocationList::default()));
+        let token = manager.generate_access_token(&user).unwrap();
+        let unrelated = manager.generate_access_token(&user).unwrap();
+        assert_ne!(
+            manager.validate_token(&token).unwrap().jti,
+            manager.validate_token(&unrelated).unwrap().jti
+        );
+
+        manager.revoke_token(&token).unwrap();
+        assert!(matches!(
+            manager.validate_token(&token),
+            Err(AuthError::SessionRevoked)
+        ));
+        assert!(manager.validate_token(&unrelated).is_ok());
+        // Revoking it again changes nothing
+        assert!(manager.revoke_token(&token).is_ok());
+    }
+
+    #[test]
+    fn test_revoked_service_role_token_rejected() {
+        let manager = JwtManager::new(JwtConfig {
+            service_role_secret: Some("service_secret_for_testing".to_string()),
+            ..JwtConfig::default()
+        })
+        .with_revocations(Arc::new(RevocationList::default()));
+        let token = manager.generate_service_role_token().unwrap();
+        let other = manager.generate_service_role_token().unwrap();
+
+        manager.revoke_token(&token).unwrap();
+        assert!(matches!(
+            manager.validate_token(&token),
+            Err(AuthError::SessionRevoked)
+        ));
+        assert!(manager.validate_token(&other).is_ok());
+    }
 }
@@ -247,11 +247,13 @@ impl<U: UserRepository> MagicLinkService<U> {
         }
     }
 
-    /// Clean up expired tokens
-    pub fn cleanup_expired(&self) {
+    /// Clean up expired tokens, returning how many were removed
+    pub fn cleanup_expired(&self) -> usize {
         let mut tokens = self.tokens.write().unwrap();
         let now = Utc::now();
+        let len_before = tokens.len();
         tokens.retain(|_, t| t.expires_at > now);
+        len_before - tokens.len()
     }
 
     /// Get the redirect URL for a token (for internal use)
@@ -466,7 +468,7 @@ mod tests {
             }
         }
 
-        service.cleanup_expired();
+        assert_eq!(service.cleanup_expired(), 1);
         assert_eq!(service.tokens.read().unwrap().len(), 0);
     }
 
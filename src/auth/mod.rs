@@ -6,6 +6,7 @@
 //! JWT tokens, and Row-Level Security for AeroDB.
 
 pub mod api;
+pub mod api_key;
 pub mod crypto;
 pub mod email;
 pub mod errors;
@@ -13,17 +14,21 @@ pub mod jwt;
 pub mod magic_link;
 pub mod mfa;
 pub mod oauth;
+pub mod policy_expr;
 pub mod rls;
 pub mod security;
 pub mod session;
 pub mod user;
 
+pub use api_key::{ApiKey, ApiKeyRepository, ApiKeyService};
+pub use crypto::{PasswordHashParams, PasswordHasher};
 pub use errors::{AuthError, AuthResult};
 pub use jwt::{JwtClaims, JwtManager};
 pub use magic_link::{AuthEvent, AuthHookPayload, AuthHooks, MagicLinkConfig, MagicLinkService};
 pub use mfa::{MfaFactor, MfaFactorType, MfaService, TotpConfig};
 pub use oauth::{OAuthProvider, OAuthProviderConfig, OAuthService, OAuthUserInfo};
+pub use policy_expr::PolicyExpr;
 pub use rls::{RlsContext, RlsEnforcer, RlsPolicy};
 pub use security::SecurityConfig;
-pub use session::{Session, SessionManager};
+pub use session::{RevocationList, Session, SessionManager};
 pub use user::{User, UserRepository};
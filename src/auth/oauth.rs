@@ -500,6 +500,15 @@ impl<U: UserRepository, O: OAuthRepository> OAuthService<U, O> {
         Ok(oauth_state)
     }
 
+    /// Remove states older than the maximum age, returning how many were
+    /// removed
+    pub fn gc_expired_states(&self) -> usize {
+        let mut states = self.state_store.write().unwrap();
+        let len_before = states.len();
+        states.retain(|_, state| !state.is_expired(self.state_max_age_seconds));
+        len_before - states.len()
+    }
+
     /// Get provider config
     pub fn get_provider_config(&self, provider: OAuthProvider) -> AuthResult<&OAuthProviderConfig> {
         self.providers.get(&provider).ok_or_else(|| {
@@ -766,6 +775,28 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_gc_expired_states() {
+        let service = create_test_service();
+        let (_, fresh) = service
+            .get_authorization_url(OAuthProvider::Google, None)
+            .unwrap();
+        let (_, stale) = service
+            .get_authorization_url(OAuthProvider::Google, None)
+            .unwrap();
+        service
+            .state_store
+            .write()
+            .unwrap()
+            .get_mut(&stale)
+            .unwrap()
+            .created_at = chrono::Utc::now() - chrono::Duration::seconds(700);
+
+        assert_eq!(service.gc_expired_states(), 1);
+        assert!(service.validate_state(&fresh).is_ok());
+        assert!(service.validate_state(&stale).is_err());
+    }
+
     #[test]
     fn test_oauth_state_expiry() {
         let mut state = OAuthState::new(OAuthProvider::Google, None);
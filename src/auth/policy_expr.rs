@@ -0,0 +1,476 @@
+//! # RLS Policy Expressions
+//!
+//! Predicates for `custom` RLS policies, in a small SQL-like language:
+//!
+//! ```text
+//! user_id = auth.uid()
+//! auth.role() = 'service_role' or (published and owner_id = auth.uid())
+//! ```
+//!
+//! Operands are row columns (dotted paths reach nested fields), `auth.uid()`,
+//! `auth.role()`, and string, number, boolean or `null` literals. Comparisons
+//! are `=`, `!=` or `<>`, `<`, `<=`, `>`, `>=` and `is [not] null`, combined
+//! with `and`, `or`, `not` and parentheses; a bare operand must be `true`.
+//! As in SQL, a comparison involving a null or missing column is false, so
+//! such rows are rejected.
+//!
+//! An expression is compiled once with [`PolicyExpr::parse`] and evaluated
+//! per row with [`PolicyExpr::eval`].
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde_json::Value;
+
+use super::errors::{AuthError, AuthResult};
+use super::rls::RlsContext;
+
+/// A compiled policy expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyExpr {
+    /// Source text, as written in the policy
+    source: String,
+    /// Compiled predicate
+    root: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    Compare(Operand, CompareOp, Operand),
+    IsNull { operand: Operand, negated: bool },
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    /// Row column, split on `.`
+    Column(Vec<String>),
+    /// `auth.uid()`: the caller's user ID, null when anonymous
+    Uid,
+    /// `auth.role()`: `anon`, `authenticated` or `service_role`
+    Role,
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl PolicyExpr {
+    /// Compile an expression; syntax errors are `InvalidPolicy`
+    pub fn parse(source: &str) -> AuthResult<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            source,
+            tokens,
+            pos: 0,
+        };
+        let root = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(invalid(source, &format!("unexpected {}", token)));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// Source text of the expression
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether `row` satisfies the expression for the caller in `ctx`
+    pub fn eval(&self, row: &Value, ctx: &RlsContext) -> bool {
+        self.root.eval(row, ctx)
+    }
+}
+
+impl fmt::Display for PolicyExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl Node {
+    fn eval(&self, row: &Value, ctx: &RlsContext) -> bool {
+        match self {
+            Node::And(a, b) => a.eval(row, ctx) && b.eval(row, ctx),
+            Node::Or(a, b) => a.eval(row, ctx) || b.eval(row, ctx),
+            Node::Not(inner) => !inner.eval(row, ctx),
+            Node::Compare(left, op, right) => {
+                match (left.resolve(row, ctx), right.resolve(row, ctx)) {
+                    (Some(a), Some(b)) => op.holds(&a, &b),
+                    _ => false,
+                }
+            }
+            Node::IsNull { operand, negated } => operand.resolve(row, ctx).is_none() != *negated,
+            Node::Truthy(operand) => operand.resolve(row, ctx) == Some(Value::Bool(true)),
+        }
+    }
+}
+
+impl Operand {
+    /// Value of the operand; None for null or a missing column
+    fn resolve(&self, row: &Value, ctx: &RlsContext) -> Option<Value> {
+        let value = match self {
+            Operand::Column(path) => path.iter().try_fold(row, |v, key| v.get(key))?.clone(),
+            Operand::Uid => Value::String(ctx.user_id?.to_string()),
+            Operand::Role => Value::String(ctx.role().to_string()),
+            Operand::Literal(value) => value.clone(),
+        };
+        (!value.is_null()).then_some(value)
+    }
+}
+
+impl CompareOp {
+    fn holds(self, a: &Value, b: &Value) -> bool {
+        let ordering = match (a, b) {
+            (Value::Number(a), Value::Number(b)) => a
+                .as_f64()
+                .zip(b.as_f64())
+                .and_then(|(a, b)| a.partial_cmp(&b)),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            _ => None,
+        };
+        match (self, ordering) {
+            (CompareOp::Eq, Some(o)) => o == Ordering::Equal,
+            (CompareOp::Ne, Some(o)) => o != Ordering::Equal,
+            (CompareOp::Eq, None) => a == b,
+            (CompareOp::Ne, None) => a != b,
+            (CompareOp::Lt, Some(o)) => o == Ordering::Less,
+            (CompareOp::Le, Some(o)) => o != Ordering::Greater,
+            (CompareOp::Gt, Some(o)) => o == Ordering::Greater,
+            (CompareOp::Ge, Some(o)) => o != Ordering::Less,
+            // Other types have no order
+            (_, None) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Number(serde_json::Number),
+    Op(CompareOp),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "`{}`", s),
+            Token::Str(s) => write!(f, "'{}'", s),
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Op(op) => write!(f, "operator {:?}", op),
+            Token::LParen => f.write_str("`(`"),
+            Token::RParen => f.write_str("`)`"),
+        }
+    }
+}
+
+fn invalid(source: &str, reason: &str) -> AuthError {
+    AuthError::InvalidPolicy(format!("{} in `{}`", reason, source))
+}
+
+fn tokenize(source: &str) -> AuthResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' {
+                    Token::LParen
+                } else {
+                    Token::RParen
+                });
+            }
+            '\'' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        // '' is an escaped quote
+                        Some('\'') if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            s.push('\'');
+                        }
+                        Some('\'') => break,
+                        Some(c) => s.push(c),
+                        None => return Err(invalid(source, "unterminated string")),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let next = chars.peek().copied();
+                let op = match (c, next) {
+                    ('=', _) => CompareOp::Eq,
+                    ('!', Some('=')) | ('<', Some('>')) => CompareOp::Ne,
+                    ('<', Some('=')) => CompareOp::Le,
+                    ('>', Some('=')) => CompareOp::Ge,
+                    ('<', _) => CompareOp::Lt,
+                    ('>', _) => CompareOp::Gt,
+                    _ => return Err(invalid(source, "expected `!=`")),
+                };
+                if matches!(
+                    (c, next),
+                    ('!', _) | ('<', Some('>' | '=')) | ('>', Some('='))
+                ) {
+                    chars.next();
+                }
+                tokens.push(Token::Op(op));
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '.' || (c == '-' && s.is_empty()) {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let number = s
+                    .parse::<i64>()
+                    .ok()
+                    .map(serde_json::Number::from)
+                    .or_else(|| s.parse::<f64>().ok().and_then(serde_json::Number::from_f64))
+                    .ok_or_else(|| invalid(source, &format!("invalid number {}", s)))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' || c == '.' {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(s));
+            }
+            other => {
+                return Err(invalid(
+                    source,
+                    &format!("unexpected character {:?}", other),
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consume the keyword if it is next
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Ident(s)) if s.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn error(&self, reason: &str) -> AuthError {
+        invalid(self.source, reason)
+    }
+
+    fn or(&mut self) -> AuthResult<Node> {
+        let mut node = self.and()?;
+        while self.keyword("or") {
+            node = Node::Or(Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> AuthResult<Node> {
+        let mut node = self.not()?;
+        while self.keyword("and") {
+            node = Node::And(Box::new(node), Box::new(self.not()?));
+        }
+        Ok(node)
+    }
+
+    fn not(&mut self) -> AuthResult<Node> {
+        if self.keyword("not") {
+            return Ok(Node::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> AuthResult<Node> {
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let node = self.or()?;
+            if self.next() != Some(Token::RParen) {
+                return Err(self.error("expected `)`"));
+            }
+            return Ok(node);
+        }
+
+        let left = self.operand()?;
+        if self.keyword("is") {
+            let negated = self.keyword("not");
+            if !self.keyword("null") {
+                return Err(self.error("expected `null` after `is`"));
+            }
+            return Ok(Node::IsNull {
+                operand: left,
+                negated,
+            });
+        }
+        match self.peek() {
+            Some(Token::Op(op)) => {
+                let op = *op;
+                self.pos += 1;
+                Ok(Node::Compare(left, op, self.operand()?))
+            }
+            _ => Ok(Node::Truthy(left)),
+        }
+    }
+
+    fn operand(&mut self) -> AuthResult<Operand> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(Operand::Literal(Value::String(s))),
+            Some(Token::Number(n)) => Ok(Operand::Literal(Value::Number(n))),
+            Some(Token::Ident(name)) => {
+                if self.peek() == Some(&Token::LParen) {
+                    self.pos += 1;
+                    if self.next() != Some(Token::RParen) {
+                        return Err(self.error(&format!("{}() takes no arguments", name)));
+                    }
+                    return match name.to_ascii_lowercase().as_str() {
+                        "auth.uid" => Ok(Operand::Uid),
+                        "auth.role" => Ok(Operand::Role),
+                        _ => Err(self.error(&format!("unknown function {}()", name))),
+                    };
+                }
+                match name.to_ascii_lowercase().as_str() {
+                    "true" => Ok(Operand::Literal(Value::Bool(true))),
+                    "false" => Ok(Operand::Literal(Value::Bool(false))),
+                    "null" => Ok(Operand::Literal(Value::Null)),
+                    "and" | "or" | "not" | "is" => {
+                        Err(self.error(&format!("unexpected keyword `{}`", name)))
+                    }
+                    _ if name.split('.').any(str::is_empty) => {
+                        Err(self.error(&format!("invalid column `{}`", name)))
+                    }
+                    _ => Ok(Operand::Column(name.split('.').map(String::from).collect())),
+                }
+            }
+            Some(token) => Err(self.error(&format!("unexpected {}", token))),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn eval(source: &str, row: Value, ctx: &RlsContext) -> bool {
+        PolicyExpr::parse(source).unwrap().eval(&row, ctx)
+    }
+
+    #[test]
+    fn test_auth_functions() {
+        let user_id = Uuid::new_v4();
+        let ctx = RlsContext::authenticated(user_id);
+        let mine = json!({"user_id": user_id.to_string()});
+        let theirs = json!({"user_id": Uuid::new_v4().to_string()});
+
+        assert!(eval("user_id = auth.uid()", mine.clone(), &ctx));
+        assert!(!eval("user_id = auth.uid()", theirs, &ctx));
+        assert!(eval("auth.role() = 'authenticated'", json!({}), &ctx));
+        // Anonymous callers have no uid, which matches nothing
+        assert!(!eval(
+            "user_id = auth.uid()",
+            mine,
+            &RlsContext::anonymous()
+        ));
+        assert!(eval(
+            "auth.uid() is null",
+            json!({}),
+            &RlsContext::anonymous()
+        ));
+    }
+
+    #[test]
+    fn test_operators_and_nulls() {
+        let ctx = RlsContext::anonymous();
+        let row = json!({"n": 5, "s": "b", "flag": true, "meta": {"level": 2}, "none": null});
+
+        assert!(eval("n > 4 and n <= 5 and n <> 6", row.clone(), &ctx));
+        assert!(eval("s >= 'a' and s < 'c' and s != 'x'", row.clone(), &ctx));
+        assert!(eval("flag and meta.level = 2", row.clone(), &ctx));
+        assert!(eval("not (n = 1 or s = 'a')", row.clone(), &ctx));
+        assert!(eval(
+            "none is null and missing is null and n is not null",
+            row.clone(),
+            &ctx
+        ));
+        // Comparisons with null or a missing column are false either way
+        assert!(!eval("missing = 1", row.clone(), &ctx));
+        assert!(!eval("missing != 1", row.clone(), &ctx));
+        assert!(!eval("none = null", row.clone(), &ctx));
+        // Mismatched types are unequal and unordered
+        assert!(!eval("s = 5", row.clone(), &ctx));
+        assert!(!eval("s > 5", row, &ctx));
+        assert!(eval("TRUE AND NOT FALSE", json!({}), &ctx));
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for source in [
+            "",
+            "user_id =",
+            "user_id = auth.email()",
+            "(a = 1",
+            "a = 'open",
+            "a = 1 b",
+            "a is 1",
+            "a.. = 1",
+            "a = 1 and",
+        ] {
+            let err = PolicyExpr::parse(source).unwrap_err();
+            assert!(matches!(err, AuthError::InvalidPolicy(_)), "{}", source);
+        }
+    }
+}
@@ -7,12 +7,19 @@
 //! - AUTH-RLS2: Deterministic query injection
 //! - AUTH-RLS3: Write validation before execution
 //! - AUTH-RLS4: Filter applied at planning time
+//! - AUTH-RLS5: Anonymous callers read only collections that allow it, and
+//!   never write
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
 use super::errors::{AuthError, AuthResult};
+use super::jwt::{
+    JwtClaims, JwtManager, ADMIN_ROLE, ANON_ROLE, AUTHENTICATED_ROLE, SERVICE_ROLE,
+};
+use super::policy_expr::PolicyExpr;
 use super::security::SecurityConfig;
 
 /// RLS context carried with each request
@@ -62,6 +69,40 @@ impl RlsContext {
         }
     }
 
+    /// Create context for a validated JWT, by its `role` claim
+    ///
+    /// `anon` is anonymous and `service_role` bypasses RLS. `authenticated`,
+    /// `admin` or no role act as the token's user, carrying its `tenant_id`
+    /// claim. Any other role is refused.
+    pub fn from_claims(claims: &JwtClaims) -> AuthResult<Self> {
+        let ctx = match claims.role.as_deref() {
+            Some(ANON_ROLE) => return Ok(Self::anonymous()),
+            Some(SERVICE_ROLE) => return Ok(Self::service_role()),
+            None | Some(AUTHENTICATED_ROLE) | Some(ADMIN_ROLE) => {
+                Self::authenticated(JwtManager::get_user_id(claims)?)
+            }
+            Some(_) => return Err(AuthError::InvalidToken),
+        };
+        Ok(match &claims.tenant_id {
+            Some(tenant_id) => {
+                ctx.with_claim("tenant_id", serde_json::Value::String(tenant_id.clone()))
+            }
+            None => ctx,
+        })
+    }
+
+    /// Access level of this context: `anon`, `authenticated` or
+    /// `service_role`
+    pub fn role(&self) -> &'static str {
+        if self.is_service_role {
+            SERVICE_ROLE
+        } else if self.is_authenticated {
+            AUTHENTICATED_ROLE
+        } else {
+            ANON_ROLE
+        }
+    }
+
     /// Check if this context allows RLS bypass
     pub fn can_bypass_rls(&self) -> bool {
         self.is_service_role
@@ -71,6 +112,20 @@ impl RlsContext {
     pub fn require_user_id(&self) -> AuthResult<Uuid> {
         self.user_id.ok_or(AuthError::AuthenticationRequired)
     }
+
+    /// Add a JWT claim to the context
+    pub fn with_claim(mut self, claim: &str, value: serde_json::Value) -> Self {
+        self.claims.insert(claim.to_string(), value);
+        self
+    }
+
+    /// Get a claim or error if the request does not carry it
+    pub fn require_claim(&self, claim: &str) -> AuthResult<&serde_json::Value> {
+        if !self.is_authenticated {
+            return Err(AuthError::AuthenticationRequired);
+        }
+        self.claims.get(claim).ok_or(AuthError::Unauthorized)
+    }
 }
 
 /// RLS policy types
@@ -95,16 +150,42 @@ pub enum RlsPolicy {
         owner_field: String,
     },
 
-    /// Custom predicate policy (future)
+    /// Rows whose field equals a JWT claim of the caller
+    #[serde(rename = "claim")]
+    Claim {
+        /// Field compared against the claim
+        field: String,
+        /// Claim the field must equal (e.g. `tenant_id`)
+        claim: String,
+    },
+
+    /// Rows satisfying policy expressions (see [`PolicyExpr`]), e.g.
+    /// `user_id = auth.uid()`
     #[serde(rename = "custom")]
     Custom {
-        /// Read predicate (JSON path expression)
+        /// Rows the caller may read, update and delete
         read_predicate: Option<String>,
-        /// Write predicate (JSON path expression)
+        /// Rows the caller may write; the read predicate when absent
         write_predicate: Option<String>,
     },
 }
 
+impl RlsPolicy {
+    /// Check that the policy's expressions compile
+    pub fn validate(&self) -> AuthResult<()> {
+        if let RlsPolicy::Custom {
+            read_predicate,
+            write_predicate,
+        } = self
+        {
+            for source in read_predicate.iter().chain(write_predicate) {
+                PolicyExpr::parse(source)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Default for RlsPolicy {
     fn default() -> Self {
         Self::Ownership {
@@ -115,11 +196,32 @@ impl Default for RlsPolicy {
 
 /// RLS filter to apply to queries
 #[derive(Debug, Clone)]
-pub struct RlsFilter {
-    /// Field to filter on
-    pub field: String,
-    /// Value to match
-    pub value: serde_json::Value,
+pub enum RlsFilter {
+    /// Rows whose field equals a value
+    Equals {
+        /// Field to filter on
+        field: String,
+        /// Value to match
+        value: serde_json::Value,
+    },
+
+    /// Rows a policy expression admits for the caller
+    Predicate {
+        /// Compiled expression
+        expr: Arc<PolicyExpr>,
+        /// Caller the expression is evaluated for
+        ctx: RlsContext,
+    },
+}
+
+impl RlsFilter {
+    /// Whether a row passes the filter
+    pub fn matches(&self, row: &serde_json::Value) -> bool {
+        match self {
+            RlsFilter::Equals { field, value } => row.get(field) == Some(value),
+            RlsFilter::Predicate { expr, ctx } => expr.eval(row, ctx),
+        }
+    }
 }
 
 /// RLS enforcer trait
@@ -147,12 +249,22 @@ pub trait RlsEnforcer: Send + Sync {
 }
 
 /// Default RLS enforcer implementation
+///
+/// Clones share the policy table, so a policy installed through one clone is
+/// enforced by all of them.
+#[derive(Clone)]
 pub struct DefaultRlsEnforcer {
     /// Policies per collection
-    policies: HashMap<String, RlsPolicy>,
+    policies: Arc<RwLock<HashMap<String, RlsPolicy>>>,
 
     /// Default policy for collections without explicit policy
-    default_policy: RlsPolicy,
+    default_policy: Arc<RwLock<RlsPolicy>>,
+
+    /// Collections anonymous callers may read, beyond `PublicRead` ones
+    anonymous_read: Arc<RwLock<HashSet<String>>>,
+
+    /// Compiled policy expressions, by source
+    compiled: Arc<RwLock<HashMap<String, Arc<PolicyExpr>>>>,
 
     /// Security configuration
     security_config: SecurityConfig,
@@ -161,8 +273,10 @@ pub struct DefaultRlsEnforcer {
 impl DefaultRlsEnforcer {
     pub fn new() -> Self {
         Self {
-            policies: HashMap::new(),
-            default_policy: RlsPolicy::default(),
+            policies: Arc::new(RwLock::new(HashMap::new())),
+            default_policy: Arc::new(RwLock::new(RlsPolicy::default())),
+            anonymous_read: Arc::new(RwLock::new(HashSet::new())),
+            compiled: Arc::new(RwLock::new(HashMap::new())),
             security_config: SecurityConfig::default(),
         }
     }
@@ -172,20 +286,121 @@ impl DefaultRlsEnforcer {
         self
     }
 
-    pub fn with_policy(mut self, collection: &str, policy: RlsPolicy) -> Self {
-        self.policies.insert(collection.to_string(), policy);
+    pub fn with_policy(self, collection: &str, policy: RlsPolicy) -> Self {
+        self.install_policy(collection, policy);
         self
     }
 
-    pub fn with_default_policy(mut self, policy: RlsPolicy) -> Self {
-        self.default_policy = policy;
+    /// Install or replace the policy for a collection at runtime
+    ///
+    /// Policy expressions are compiled here; one that does not compile
+    /// refuses every non-service request to the collection with
+    /// `InvalidPolicy`. Check with [`RlsPolicy::validate`] first.
+    pub fn install_policy(&self, collection: &str, policy: RlsPolicy) {
+        self.precompile(&policy);
+        self.policies
+            .write()
+            .unwrap()
+            .insert(collection.to_string(), policy);
+    }
+
+    /// Remove a collection's policy; it falls back to the default policy
+    pub fn remove_policy(&self, collection: &str) -> Option<RlsPolicy> {
+        self.policies.write().unwrap().remove(collection)
+    }
+
+    /// Policy installed for a collection, if any
+    pub fn policy(&self, collection: &str) -> Option<RlsPolicy> {
+        self.policies.read().unwrap().get(collection).cloned()
+    }
+
+    pub fn with_default_policy(self, policy: RlsPolicy) -> Self {
+        self.set_default_policy(policy);
         self
     }
 
-    fn get_policy(&self, collection: &str) -> &RlsPolicy {
-        self.policies
-            .get(collection)
-            .unwrap_or(&self.default_policy)
+    /// Replace the default policy at runtime; collections with their own
+    /// policy keep it
+    pub fn set_default_policy(&self, policy: RlsPolicy) {
+        self.precompile(&policy);
+        *self.default_policy.write().unwrap() = policy;
+    }
+
+    pub fn with_anonymous_read(self, collection: &str) -> Self {
+        self.set_anonymous_read(collection, true);
+        self
+    }
+
+    /// Allow or forbid anonymous reads of a collection at runtime.
+    ///
+    /// Anonymous callers read every row of such a collection; its policy
+    /// still applies to authenticated callers.
+    pub fn set_anonymous_read(&self, collection: &str, allowed: bool) {
+        let mut anonymous_read = self.anonymous_read.write().unwrap();
+        if allowed {
+            anonymous_read.insert(collection.to_string());
+        } else {
+            anonymous_read.remove(collection);
+        }
+    }
+
+    /// Whether anonymous callers may read a collection: it is marked
+    /// anon-readable, or its policy is `PublicRead`
+    pub fn allows_anonymous_read(&self, collection: &str) -> bool {
+        matches!(self.get_policy(collection), RlsPolicy::PublicRead { .. })
+            || self.anonymous_read.read().unwrap().contains(collection)
+    }
+
+    /// Move a collection's policy and anonymous access to a new name, as
+    /// renaming the collection does
+    pub fn rename_collection(&self, from: &str, to: &str) {
+        self.copy_collection(from, to);
+        self.remove_policy(from);
+        self.set_anonymous_read(from, false);
+    }
+
+    /// Give a new collection the policy and anonymous access of `from`, as
+    /// copying the collection does
+    pub fn copy_collection(&self, from: &str, to: &str) {
+        match self.policy(from) {
+            Some(policy) => self.install_policy(to, policy),
+            None => {
+                self.remove_policy(to);
+            }
+        }
+        let anonymous = self.anonymous_read.read().unwrap().contains(from);
+        self.set_anonymous_read(to, anonymous);
+    }
+
+    fn get_policy(&self, collection: &str) -> RlsPolicy {
+        self.policy(collection)
+            .unwrap_or_else(|| self.default_policy.read().unwrap().clone())
+    }
+
+    /// Compile a policy's expressions ahead of its first use
+    fn precompile(&self, policy: &RlsPolicy) {
+        if let RlsPolicy::Custom {
+            read_predicate,
+            write_predicate,
+        } = policy
+        {
+            for source in read_predicate.iter().chain(write_predicate) {
+                let _ = self.compile(source);
+            }
+        }
+    }
+
+    /// The compiled form of an expression, compiling it once
+    fn compile(&self, source: &str) -> AuthResult<Arc<PolicyExpr>> {
+        if let Some(expr) = self.compiled.read().unwrap().get(source) {
+            return Ok(Arc::clone(expr));
+        }
+        let expr = Arc::new(PolicyExpr::parse(source)?);
+        self.compiled
+            .write()
+            .unwrap()
+            .insert(source.to_string(), Arc::clone(&expr));
+        Ok(expr)
     }
 }
 
@@ -202,14 +417,22 @@ impl RlsEnforcer for DefaultRlsEnforcer {
             return Ok(None);
         }
 
+        // AUTH-RLS5: anonymous callers read only what is marked readable
+        if !ctx.is_authenticated {
+            if self.allows_anonymous_read(collection) {
+                return Ok(None);
+            }
+            return Err(AuthError::AuthenticationRequired);
+        }
+
         let policy = self.get_policy(collection);
 
-        match policy {
+        match &policy {
             RlsPolicy::None => Ok(None),
 
             RlsPolicy::Ownership { owner_field } => {
                 let user_id = ctx.require_user_id()?;
-                Ok(Some(RlsFilter {
+                Ok(Some(RlsFilter::Equals {
                     field: owner_field.clone(),
                     value: serde_json::json!(user_id.to_string()),
                 }))
@@ -220,16 +443,18 @@ impl RlsEnforcer for DefaultRlsEnforcer {
                 Ok(None)
             }
 
-            RlsPolicy::Custom { read_predicate, .. } => {
-                if read_predicate.is_some() {
-                    // Custom predicates not yet implemented
-                    Err(AuthError::InvalidPolicy(
-                        "Custom predicates not yet supported".to_string(),
-                    ))
-                } else {
-                    Ok(None)
-                }
-            }
+            RlsPolicy::Claim { field, claim } => Ok(Some(RlsFilter::Equals {
+                field: field.clone(),
+                value: ctx.require_claim(claim)?.clone(),
+            })),
+
+            RlsPolicy::Custom { read_predicate, .. } => match read_predicate {
+                Some(source) => Ok(Some(RlsFilter::Predicate {
+                    expr: self.compile(source)?,
+                    ctx: ctx.clone(),
+                })),
+                None => Ok(None),
+            },
         }
     }
 
@@ -244,9 +469,14 @@ impl RlsEnforcer for DefaultRlsEnforcer {
             return Ok(());
         }
 
+        // AUTH-RLS5: anonymous callers never write
+        if !ctx.is_authenticated {
+            return Err(AuthError::AuthenticationRequired);
+        }
+
         let policy = self.get_policy(collection);
 
-        match policy {
+        match &policy {
             RlsPolicy::None => Ok(()),
 
             RlsPolicy::Ownership { owner_field } | RlsPolicy::PublicRead { owner_field } => {
@@ -272,17 +502,25 @@ impl RlsEnforcer for DefaultRlsEnforcer {
                 }
             }
 
-            RlsPolicy::Custom {
-                write_predicate, ..
-            } => {
-                if write_predicate.is_some() {
-                    Err(AuthError::InvalidPolicy(
-                        "Custom predicates not yet supported".to_string(),
-                    ))
-                } else {
-                    Ok(())
+            RlsPolicy::Claim { field, claim } => {
+                let expected = ctx.require_claim(claim)?;
+
+                // A missing field is filled in by prepare_insert
+                match document.get(field) {
+                    Some(value) if value != expected => Err(AuthError::Unauthorized),
+                    _ => Ok(()),
                 }
             }
+
+            RlsPolicy::Custom {
+                read_predicate,
+                write_predicate,
+            } => match write_predicate.as_ref().or(read_predicate.as_ref()) {
+                Some(source) if !self.compile(source)?.eval(document, ctx) => {
+                    Err(AuthError::Unauthorized)
+                }
+                _ => Ok(()),
+            },
         }
     }
 
@@ -297,9 +535,13 @@ impl RlsEnforcer for DefaultRlsEnforcer {
             return Ok(());
         }
 
+        if !ctx.is_authenticated {
+            return Err(AuthError::AuthenticationRequired);
+        }
+
         let policy = self.get_policy(collection);
 
-        match policy {
+        match &policy {
             RlsPolicy::None => Ok(()),
 
             RlsPolicy::Ownership { owner_field } | RlsPolicy::PublicRead { owner_field } => {
@@ -313,6 +555,14 @@ impl RlsEnforcer for DefaultRlsEnforcer {
                 Ok(())
             }
 
+            RlsPolicy::Claim { field, claim } => {
+                let value = ctx.require_claim(claim)?.clone();
+                if let Some(obj) = document.as_object_mut() {
+                    obj.insert(field.clone(), value);
+                }
+                Ok(())
+            }
+
             RlsPolicy::Custom { .. } => Ok(()),
         }
     }
@@ -343,6 +593,14 @@ pub enum RlsEvent {
 mod tests {
     use super::*;
 
+    /// Field and value of an equality filter
+    fn equals(filter: RlsFilter) -> (String, serde_json::Value) {
+        match filter {
+            RlsFilter::Equals { field, value } => (field, value),
+            other => panic!("expected an equality filter, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_rls_context_authenticated() {
         let user_id = Uuid::new_v4();
@@ -369,9 +627,9 @@ mod tests {
         let filter = enforcer.get_read_filter("posts", &ctx).unwrap();
 
         assert!(filter.is_some());
-        let filter = filter.unwrap();
-        assert_eq!(filter.field, "owner_id");
-        assert_eq!(filter.value, serde_json::json!(user_id.to_string()));
+        let (field, value) = equals(filter.unwrap());
+        assert_eq!(field, "owner_id");
+        assert_eq!(value, serde_json::json!(user_id.to_string()));
     }
 
     #[test]
@@ -409,6 +667,107 @@ mod tests {
         assert!(filter.is_none());
     }
 
+    #[test]
+    fn test_anonymous_access_needs_explicit_marking() {
+        let enforcer = DefaultRlsEnforcer::new().with_policy("open", RlsPolicy::None);
+        let anon = RlsContext::anonymous();
+
+        // Even a collection without RLS is closed to anonymous callers
+        assert!(matches!(
+            enforcer.get_read_filter("open", &anon),
+            Err(AuthError::AuthenticationRequired)
+        ));
+
+        enforcer.set_anonymous_read("open", true);
+        enforcer.set_anonymous_read("posts", true);
+        assert!(enforcer.get_read_filter("open", &anon).unwrap().is_none());
+        assert!(enforcer.get_read_filter("posts", &anon).unwrap().is_none());
+
+        // Anon-readable is read-only
+        let mut doc = serde_json::json!({"title": "hello"});
+        assert!(matches!(
+            enforcer.prepare_insert("open", &mut doc, &anon),
+            Err(AuthError::AuthenticationRequired)
+        ));
+        assert!(matches!(
+            enforcer.validate_write("open", &doc, &anon),
+            Err(AuthError::AuthenticationRequired)
+        ));
+
+        // Authenticated callers still get their ownership filter
+        let user_id = Uuid::new_v4();
+        let filter = enforcer
+            .get_read_filter("posts", &RlsContext::authenticated(user_id))
+            .unwrap()
+            .unwrap();
+        assert_eq!(equals(filter).1, serde_json::json!(user_id.to_string()));
+
+        enforcer.set_anonymous_read("open", false);
+        assert!(enforcer.get_read_filter("open", &anon).is_err());
+    }
+
+    #[test]
+    fn test_policy_follows_renamed_and_copied_collection() {
+        let rls = DefaultRlsEnforcer::new()
+            .with_policy(
+                "posts",
+                RlsPolicy::Ownership {
+                    owner_field: "author_id".to_string(),
+                },
+            )
+            .with_anonymous_read("posts");
+
+        rls.copy_collection("posts", "drafts");
+        assert!(matches!(rls.policy("drafts"), Some(RlsPolicy::Ownership { .. })));
+        assert!(rls.policy("posts").is_some());
+        assert!(rls.allows_anonymous_read("drafts"));
+
+        rls.rename_collection("posts", "articles");
+        assert!(rls.policy("posts").is_none());
+        assert!(!rls.allows_anonymous_read("posts"));
+        assert!(matches!(
+            rls.policy("articles"),
+            Some(RlsPolicy::Ownership { owner_field }) if owner_field == "author_id"
+        ));
+        assert!(rls.allows_anonymous_read("articles"));
+    }
+
+    #[test]
+    fn test_context_from_role_claim() {
+        let user_id = Uuid::new_v4();
+        let claims = |role: Option<&str>| JwtClaims {
+            sub: user_id.to_string(),
+            email: String::new(),
+            iat: 0,
+            exp: 0,
+            aud: String::new(),
+            iss: String::new(),
+            email_verified: false,
+            tenant_id: Some("t1".to_string()),
+            role: role.map(str::to_string),
+            sid: None,
+            jti: None,
+        };
+
+        let ctx = RlsContext::from_claims(&claims(Some(ANON_ROLE))).unwrap();
+        assert_eq!((ctx.role(), ctx.user_id), (ANON_ROLE, None));
+
+        let ctx = RlsContext::from_claims(&claims(Some(SERVICE_ROLE))).unwrap();
+        assert!(ctx.can_bypass_rls());
+        assert_eq!(ctx.role(), SERVICE_ROLE);
+
+        for role in [None, Some(AUTHENTICATED_ROLE), Some(ADMIN_ROLE)] {
+            let ctx = RlsContext::from_claims(&claims(role)).unwrap();
+            assert_eq!((ctx.role(), ctx.user_id), (AUTHENTICATED_ROLE, Some(user_id)));
+            assert_eq!(ctx.claims["tenant_id"], "t1");
+        }
+
+        assert!(matches!(
+            RlsContext::from_claims(&claims(Some("superuser"))),
+            Err(AuthError::InvalidToken)
+        ));
+    }
+
     #[test]
     fn test_write_validation_owner_match() {
         let enforcer = DefaultRlsEnforcer::new();
@@ -457,4 +816,42 @@ mod tests {
             user_id.to_string()
         );
     }
+
+    #[test]
+    fn test_claim_policy_binds_rows_to_claim() {
+        let enforcer = DefaultRlsEnforcer::new();
+        enforcer.install_policy(
+            "notes",
+            RlsPolicy::Claim {
+                field: "tenant_id".to_string(),
+                claim: "tenant_id".to_string(),
+            },
+        );
+        let ctx = RlsContext::authenticated(Uuid::new_v4())
+            .with_claim("tenant_id", serde_json::json!("tenant-a"));
+
+        let filter = enforcer.get_read_filter("notes", &ctx).unwrap().unwrap();
+        assert_eq!(equals(filter), ("tenant_id".to_string(), serde_json::json!("tenant-a")));
+
+        let mut doc = serde_json::json!({"title": "n"});
+        enforcer.validate_write("notes", &doc, &ctx).unwrap();
+        enforcer.prepare_insert("notes", &mut doc, &ctx).unwrap();
+        assert_eq!(doc["tenant_id"], "tenant-a");
+
+        let foreign = serde_json::json!({"tenant_id": "tenant-b"});
+        assert!(matches!(
+            enforcer.validate_write("notes", &foreign, &ctx),
+            Err(AuthError::Unauthorized)
+        ));
+
+        // No claim, no access
+        let without_claim = RlsContext::authenticated(Uuid::new_v4());
+        assert!(matches!(
+            enforcer.get_read_filter("notes", &without_claim),
+            Err(AuthError::Unauthorized)
+        ));
+
+        enforcer.remove_policy("notes");
+        assert!(enforcer.policy("notes").is_none());
+    }
 }
@@ -7,8 +7,11 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::crypto::PasswordHashParams;
+
 /// Security configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SecurityConfig {
     /// Whether to enable fail-closed mode
     ///
@@ -21,6 +24,16 @@ pub struct SecurityConfig {
 
     /// Whether to log all auth failures
     pub audit_auth_failures: bool,
+
+    /// Argon2id cost of new password hashes
+    ///
+    /// Default: the argon2 crate's defaults (OWASP's minimum)
+    pub password_hash: PasswordHashParams,
+
+    /// Secret signing service-role tokens (`JwtConfig::service_role_secret`)
+    ///
+    /// Default: unset, and service-role tokens are refused
+    pub service_role_secret: Option<String>,
 }
 
 impl Default for SecurityConfig {
@@ -28,6 +41,8 @@ impl Default for SecurityConfig {
         Self {
             fail_closed_mode: true,
             audit_auth_failures: true,
+            password_hash: PasswordHashParams::default(),
+            service_role_secret: None,
         }
     }
 }
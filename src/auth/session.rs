@@ -7,6 +7,27 @@
 //! - AUTH-SS1: Refresh tokens are single-use
 //! - AUTH-SS2: Sessions expire at stated time
 //! - AUTH-SS3: Logout invalidates immediately
+//! - AUTH-SS4: Presenting a rotated refresh token again revokes every
+//!   session of its family
+//! - AUTH-SS5: Revoking a session also revokes the access tokens issued
+//!   for it, through the revocation list
+//!
+//! ## Rotation
+//! Each refresh replaces the session with a new one of the same family,
+//! carrying a new refresh token. A rotated token seen again means it was
+//! copied: the legitimate client and the thief now race with the same
+//! chain, so the whole family is revoked and the user must sign in again.
+//!
+//! ## Revocation list
+//! Access tokens are validated without a lookup, so a revoked session's
+//! tokens would stay valid until they expire. Access tokens issued for a
+//! session carry its family ID (`sid`); revoked families are kept in a
+//! [`RevocationList`] that `JwtManager` consults. An entry is dropped once
+//! every token it could match has expired, which bounds the list by the
+//! revocations of one access token lifetime.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
@@ -14,6 +35,8 @@ use uuid::Uuid;
 
 use super::crypto::{constant_time_str_eq, generate_token, hash_token};
 use super::errors::{AuthError, AuthResult};
+use super::jwt::{JwtConfig, JwtManager, TokenResponse};
+use super::user::UserRepository;
 
 /// Session model
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +47,10 @@ pub struct Session {
     /// User this session belongs to
     pub user_id: Uuid,
 
+    /// Session this one was rotated from, transitively: the ID of the
+    /// first session of the chain
+    pub family_id: Uuid,
+
     /// Hashed refresh token (raw token given to client)
     #[serde(skip_serializing)]
     pub refresh_token_hash: String,
@@ -37,6 +64,10 @@ pub struct Session {
     /// Whether the session has been revoked
     pub revoked: bool,
 
+    /// When the refresh token was exchanged for a new session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<DateTime<Utc>>,
+
     /// User agent from the request
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_agent: Option<String>,
@@ -74,15 +105,103 @@ impl Default for SessionConfig {
     }
 }
 
+/// Revoked access tokens: whole login sessions, by session family ID,
+/// and single tokens, by `jti`
+#[derive(Debug)]
+pub struct RevocationList {
+    /// Family IDs, with when the last token issued for them expires
+    revoked: RwLock<HashMap<String, DateTime<Utc>>>,
+    /// Token IDs, with when the token expires
+    tokens: RwLock<HashMap<String, DateTime<Utc>>>,
+    /// Longest lifetime of an access token
+    max_token_lifetime: Duration,
+}
+
+impl RevocationList {
+    /// Create a list for access tokens living at most `max_token_lifetime`
+    pub fn new(max_token_lifetime: Duration) -> Self {
+        Self {
+            revoked: RwLock::new(HashMap::new()),
+            tokens: RwLock::new(HashMap::new()),
+            max_token_lifetime,
+        }
+    }
+
+    /// Revoke the tokens of session family `sid`, dropping entries whose
+    /// tokens have all expired
+    pub fn revoke(&self, sid: &str) {
+        let now = Utc::now();
+        let mut revoked = self.revoked.write().unwrap();
+        revoked.retain(|_, until| *until > now);
+        revoked.insert(sid.to_string(), now + self.max_token_lifetime);
+    }
+
+    /// Whether tokens of session family `sid` are revoked
+    pub fn is_revoked(&self, sid: &str) -> bool {
+        let revoked = self.revoked.read().unwrap();
+        revoked.get(sid).is_some_and(|until| *until > Utc::now())
+    }
+
+    /// Revoke the single token `jti` expiring at `expires_at`, dropping
+    /// entries of tokens that have expired
+    pub fn revoke_token(&self, jti: &str, expires_at: DateTime<Utc>) {
+        let now = Utc::now();
+        let mut tokens = self.tokens.write().unwrap();
+        tokens.retain(|_, until| *until > now);
+        if expires_at > now {
+            tokens.insert(jti.to_string(), expires_at);
+        }
+    }
+
+    /// Whether the single token `jti` is revoked
+    pub fn is_token_revoked(&self, jti: &str) -> bool {
+        let tokens = self.tokens.read().unwrap();
+        tokens.get(jti).is_some_and(|until| *until > Utc::now())
+    }
+
+    /// Number of entries held
+    pub fn len(&self) -> usize {
+        self.revoked.read().unwrap().len() + self.tokens.read().unwrap().len()
+    }
+
+    /// Whether no entry is held
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for RevocationList {
+    fn default() -> Self {
+        Self::new(JwtConfig::default().access_token_ttl)
+    }
+}
+
 /// Session manager handles session creation and validation
 pub struct SessionManager<R: SessionRepository> {
     config: SessionConfig,
     repository: R,
+    revocations: Arc<RevocationList>,
 }
 
 impl<R: SessionRepository> SessionManager<R> {
     pub fn new(config: SessionConfig, repository: R) -> Self {
-        Self { config, repository }
+        Self {
+            config,
+            repository,
+            revocations: Arc::new(RevocationList::default()),
+        }
+    }
+
+    /// Record revocations in `revocations`, which the `JwtManager`
+    /// validating access tokens should share
+    pub fn with_revocations(mut self, revocations: Arc<RevocationList>) -> Self {
+        self.revocations = revocations;
+        self
+    }
+
+    /// Revoked sessions, for `JwtManager::with_revocations`
+    pub fn revocations(&self) -> Arc<RevocationList> {
+        Arc::clone(&self.revocations)
     }
 
     /// Create a new session for a user
@@ -93,18 +212,32 @@ impl<R: SessionRepository> SessionManager<R> {
         user_id: Uuid,
         user_agent: Option<String>,
         ip_address: Option<String>,
+    ) -> AuthResult<(Session, String)> {
+        let id = Uuid::new_v4();
+        self.start_session(id, id, user_id, user_agent, ip_address)
+    }
+
+    fn start_session(
+        &self,
+        id: Uuid,
+        family_id: Uuid,
+        user_id: Uuid,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
     ) -> AuthResult<(Session, String)> {
         let refresh_token = generate_token();
         let refresh_token_hash = hash_token(&refresh_token);
 
         let now = Utc::now();
         let session = Session {
-            id: Uuid::new_v4(),
+            id,
             user_id,
+            family_id,
             refresh_token_hash,
             created_at: now,
             expires_at: now + self.config.refresh_token_ttl,
             revoked: false,
+            rotated_at: None,
             user_agent,
             ip_address,
         };
@@ -116,8 +249,10 @@ impl<R: SessionRepository> SessionManager<R> {
 
     /// Refresh a session using the refresh token
     ///
-    /// # Invariant
-    /// AUTH-SS1: Refresh tokens are single-use (old session revoked)
+    /// # Invariants
+    /// - AUTH-SS1: Refresh tokens are single-use (old session revoked)
+    /// - AUTH-SS4: Reusing a rotated token revokes the session family and
+    ///   fails with `RefreshTokenReused`
     pub fn refresh_session(&self, refresh_token: &str) -> AuthResult<(Session, String)> {
         let token_hash = hash_token(refresh_token);
 
@@ -127,6 +262,11 @@ impl<R: SessionRepository> SessionManager<R> {
             .find_by_refresh_token_hash(&token_hash)?
             .ok_or(AuthError::InvalidRefreshToken)?;
 
+        // Already exchanged: the token was copied
+        if old_session.rotated_at.is_some() {
+            return Err(self.reuse_detected(&old_session));
+        }
+
         // Check if revoked
         if old_session.revoked {
             return Err(AuthError::SessionRevoked);
@@ -137,28 +277,80 @@ impl<R: SessionRepository> SessionManager<R> {
             return Err(AuthError::SessionInvalid);
         }
 
-        // Revoke old session (single-use token)
-        self.repository.revoke(old_session.id)?;
+        // Retire the old token; a concurrent exchange of it is a reuse
+        if !self.repository.mark_rotated(old_session.id, Utc::now())? {
+            return Err(self.reuse_detected(&old_session));
+        }
 
-        // Create new session
-        self.create_session(
+        // Continue the family with a new session
+        self.start_session(
+            Uuid::new_v4(),
+            old_session.family_id,
             old_session.user_id,
             old_session.user_agent,
             old_session.ip_address,
         )
     }
 
+    /// Exchange a refresh token for a new access and refresh token pair
+    ///
+    /// The access token is issued by `jwt` for the session's user, looked
+    /// up in `users`. Fails as [`refresh_session`](Self::refresh_session)
+    /// does.
+    pub fn refresh<U: UserRepository>(
+        &self,
+        refresh_token: &str,
+        users: &U,
+        jwt: &JwtManager,
+    ) -> AuthResult<TokenResponse> {
+        let (session, new_refresh_token) = self.refresh_session(refresh_token)?;
+        let user = users
+            .find_by_id(session.user_id)?
+            .ok_or(AuthError::InvalidCredentials)?;
+        let access_token = jwt.generate_session_token(&user, session.family_id)?;
+
+        Ok(TokenResponse::new(
+            access_token,
+            new_refresh_token,
+            jwt.get_expiration(),
+        ))
+    }
+
+    fn reuse_detected(&self, session: &Session) -> AuthError {
+        self.revocations.revoke(&session.family_id.to_string());
+        match self.repository.revoke_family(session.family_id) {
+            Ok(()) => AuthError::RefreshTokenReused,
+            Err(e) => e,
+        }
+    }
+
     /// Revoke a session (logout)
     ///
-    /// # Invariant
-    /// AUTH-SS3: Logout invalidates immediately
+    /// # Invariants
+    /// - AUTH-SS3: Logout invalidates immediately
+    /// - AUTH-SS5: Access tokens issued for the session are revoked too
     pub fn revoke_session(&self, session_id: Uuid) -> AuthResult<()> {
-        self.repository.revoke(session_id)
+        self.repository.revoke(session_id)?;
+        if let Some(session) = self.repository.find_by_id(session_id)? {
+            self.revocations.revoke(&session.family_id.to_string());
+        }
+        Ok(())
+    }
+
+    /// Revoke all sessions for a user, and the access tokens issued for them
+    pub fn revoke_all_for_user(&self, user_id: Uuid) -> AuthResult<()> {
+        let sessions = self.repository.find_all_for_user(user_id)?;
+        self.repository.revoke_all_for_user(user_id)?;
+        for session in sessions {
+            self.revocations.revoke(&session.family_id.to_string());
+        }
+        Ok(())
     }
 
     /// Revoke all sessions for a user
+    #[deprecated(note = "use `revoke_all_for_user`, which also revokes access tokens")]
     pub fn revoke_all_user_sessions(&self, user_id: Uuid) -> AuthResult<()> {
-        self.repository.revoke_all_for_user(user_id)
+        self.revoke_all_for_user(user_id)
     }
 
     /// Validate a refresh token and return the associated session
@@ -181,6 +373,11 @@ impl<R: SessionRepository> SessionManager<R> {
         Ok(session)
     }
 
+    /// Delete expired sessions, returning how many were deleted
+    pub fn delete_expired(&self) -> AuthResult<usize> {
+        self.repository.delete_expired()
+    }
+
     /// Get all active sessions for a user
     pub fn get_user_sessions(&self, user_id: Uuid) -> AuthResult<Vec<Session>> {
         self.repository.find_all_for_user(user_id)
@@ -204,6 +401,14 @@ pub trait SessionRepository: Send + Sync {
     /// Revoke a session
     fn revoke(&self, id: Uuid) -> AuthResult<()>;
 
+    /// Record that a session's refresh token was exchanged at `at`, and
+    /// revoke the session. Returns false, changing nothing, if the token
+    /// was already exchanged.
+    fn mark_rotated(&self, id: Uuid, at: DateTime<Utc>) -> AuthResult<bool>;
+
+    /// Revoke every session of a family
+    fn revoke_family(&self, family_id: Uuid) -> AuthResult<()>;
+
     /// Revoke all sessions for a user
     fn revoke_all_for_user(&self, user_id: Uuid) -> AuthResult<()>;
 
@@ -278,6 +483,37 @@ impl SessionRepository for InMemorySessionRepository {
         }
     }
 
+    fn mark_rotated(&self, id: Uuid, at: DateTime<Utc>) -> AuthResult<bool> {
+        let mut sessions = self
+            .sessions
+            .write()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+
+        let session = sessions
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or(AuthError::SessionInvalid)?;
+        if session.rotated_at.is_some() {
+            return Ok(false);
+        }
+        session.rotated_at = Some(at);
+        session.revoked = true;
+        Ok(true)
+    }
+
+    fn revoke_family(&self, family_id: Uuid) -> AuthResult<()> {
+        let mut sessions = self
+            .sessions
+            .write()
+            .map_err(|_| AuthError::StorageError("Lock poisoned".to_string()))?;
+
+        for session in sessions.iter_mut().filter(|s| s.family_id == family_id) {
+            session.revoked = true;
+        }
+
+        Ok(())
+    }
+
     fn revoke_all_for_user(&self, user_id: Uuid) -> AuthResult<()> {
         let mut sessions = self
             .sessions
@@ -307,6 +543,9 @@ impl SessionRepository for InMemorySessionRepository {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::auth::crypto::PasswordPolicy;
+    use crate::auth::jwt::JwtConfig;
+    use crate::auth::user::{InMemoryUserRepository, User};
 
     fn create_manager() -> SessionManager<InMemorySessionRepository> {
         SessionManager::new(SessionConfig::default(), InMemorySessionRepository::new())
@@ -357,14 +596,98 @@ mod tests {
         let (new_session, new_token) = manager.refresh_session(&refresh_token).unwrap();
         assert_eq!(new_session.user_id, user_id);
 
-        // Using old token again should fail (single-use)
-        let result = manager.refresh_session(&refresh_token);
-        assert!(matches!(result, Err(AuthError::SessionRevoked)));
-
         // New token should work
         let _ = manager.refresh_session(&new_token).unwrap();
     }
 
+    fn create_user_and_jwt() -> (InMemoryUserRepository, User, JwtManager) {
+        let users = InMemoryUserRepository::new();
+        let user = User::new(
+            "test@example.com".to_string(),
+            "password123",
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        users.create(&user).unwrap();
+        (users, user, JwtManager::new(JwtConfig::default()))
+    }
+
+    #[test]
+    fn test_refresh_rotates_tokens_within_family() {
+        let manager = create_manager();
+        let (users, user, jwt) = create_user_and_jwt();
+        let (first, token) = manager.create_session(user.id, None, None).unwrap();
+
+        let response = manager.refresh(&token, &users, &jwt).unwrap();
+        assert_ne!(response.refresh_token, token);
+        let claims = jwt.validate_token(&response.access_token).unwrap();
+        assert_eq!(claims.sub, user.id.to_string());
+
+        let rotated = manager
+            .validate_refresh_token(&response.refresh_token)
+            .unwrap();
+        assert_eq!(rotated.family_id, first.id);
+        assert_ne!(rotated.id, first.id);
+
+        // The chain continues as long as each token is used once
+        let next = manager
+            .refresh(&response.refresh_token, &users, &jwt)
+            .unwrap();
+        assert!(manager.validate_refresh_token(&next.refresh_token).is_ok());
+        assert_eq!(manager.get_user_sessions(user.id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_refresh_token_reuse_revokes_family() {
+        let manager = create_manager();
+        let (users, user, jwt) = create_user_and_jwt();
+        let (_, stolen) = manager.create_session(user.id, None, None).unwrap();
+        let (_, other_device) = manager.create_session(user.id, None, None).unwrap();
+
+        // The legitimate client rotates twice
+        let first = manager.refresh(&stolen, &users, &jwt).unwrap();
+        let second = manager.refresh(&first.refresh_token, &users, &jwt).unwrap();
+
+        // Replaying the stolen token revokes the whole chain
+        assert!(matches!(
+            manager.refresh(&stolen, &users, &jwt),
+            Err(AuthError::RefreshTokenReused)
+        ));
+        assert!(matches!(
+            manager.refresh(&second.refresh_token, &users, &jwt),
+            Err(AuthError::SessionRevoked)
+        ));
+        assert!(matches!(
+            manager.refresh(&first.refresh_token, &users, &jwt),
+            Err(AuthError::RefreshTokenReused)
+        ));
+
+        // Sessions of other families are untouched
+        assert!(manager.refresh(&other_device, &users, &jwt).is_ok());
+    }
+
+    #[test]
+    fn test_expired_refresh_token_is_refused() {
+        let manager = SessionManager::new(
+            SessionConfig {
+                refresh_token_ttl: Duration::seconds(-1),
+            },
+            InMemorySessionRepository::new(),
+        );
+        let (users, user, jwt) = create_user_and_jwt();
+        let (_, token) = manager.create_session(user.id, None, None).unwrap();
+
+        assert!(matches!(
+            manager.refresh(&token, &users, &jwt),
+            Err(AuthError::SessionInvalid)
+        ));
+        // Expiry is not reuse: the token was never rotated
+        assert!(matches!(
+            manager.refresh(&token, &users, &jwt),
+            Err(AuthError::SessionInvalid)
+        ));
+    }
+
     #[test]
     fn test_session_revocation() {
         let manager = create_manager();
@@ -381,7 +704,7 @@ mod tests {
     }
 
     #[test]
-    fn test_revoke_all_user_sessions() {
+    fn test_revoke_all_for_user() {
         let manager = create_manager();
         let user_id = Uuid::new_v4();
 
@@ -394,7 +717,7 @@ mod tests {
         assert!(manager.validate_refresh_token(&token2).is_ok());
 
         // Revoke all
-        manager.revoke_all_user_sessions(user_id).unwrap();
+        manager.revoke_all_for_user(user_id).unwrap();
 
         // Both should be invalid
         assert!(matches!(
@@ -406,4 +729,78 @@ mod tests {
             Err(AuthError::SessionRevoked)
         ));
     }
+
+    #[test]
+    fn test_revoked_session_rejects_access_token() {
+        let manager = create_manager();
+        let (users, user, jwt) = create_user_and_jwt();
+        let jwt = jwt.with_revocations(manager.revocations());
+
+        let (session, token) = manager.create_session(user.id, None, None).unwrap();
+        let (other, _) = manager.create_session(user.id, None, None).unwrap();
+        let rotated = manager.refresh(&token, &users, &jwt).unwrap();
+        let earlier = jwt.generate_session_token(&user, session.id).unwrap();
+        let unrelated = jwt.generate_session_token(&user, other.id).unwrap();
+        assert!(jwt.validate_token(&rotated.access_token).is_ok());
+
+        // Logging out the current session revokes every token of the chain
+        let current = manager
+            .validate_refresh_token(&rotated.refresh_token)
+            .unwrap();
+        manager.revoke_session(current.id).unwrap();
+        assert!(matches!(
+            jwt.validate_token(&rotated.access_token),
+            Err(AuthError::SessionRevoked)
+        ));
+        assert!(matches!(
+            jwt.validate_token(&earlier),
+            Err(AuthError::SessionRevoked)
+        ));
+
+        // Another session of the same user still verifies
+        assert!(jwt.validate_token(&unrelated).is_ok());
+        manager.revoke_all_for_user(user.id).unwrap();
+        assert!(matches!(
+            jwt.validate_token(&unrelated),
+            Err(AuthError::SessionRevoked)
+        ));
+    }
+
+    #[test]
+    fn test_revocation_list_evicts_expired_entries() {
+        let list = RevocationList::new(Duration::zero());
+        list.revoke("old");
+        assert!(!list.is_revoked("old"));
+
+        // Entries past the token lifetime are dropped on the next revocation
+        list.revoke("new");
+        assert_eq!(list.len(), 1);
+
+        let list = RevocationList::new(Duration::minutes(15));
+        list.revoke("sid");
+        assert!(list.is_revoked("sid"));
+        assert!(!list.is_revoked("other"));
+
+        // Single tokens are held until they expire
+        list.revoke_token("expired", Utc::now() - Duration::seconds(1));
+        assert!(!list.is_token_revoked("expired"));
+        list.revoke_token("jti", Utc::now() + Duration::minutes(5));
+        assert!(list.is_token_revoked("jti"));
+        assert!(!list.is_revoked("jti"));
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_revoke_all_user_sessions_alias() {
+        let manager = create_manager();
+        let user_id = Uuid::new_v4();
+        let (_, token) = manager.create_session(user_id, None, None).unwrap();
+
+        manager.revoke_all_user_sessions(user_id).unwrap();
+        assert!(matches!(
+            manager.validate_refresh_token(&token),
+            Err(AuthError::SessionRevoked)
+        ));
+    }
 }
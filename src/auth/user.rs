@@ -7,7 +7,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::crypto::{hash_password, validate_password, verify_password, PasswordPolicy};
+use super::crypto::{validate_password, verify_password, PasswordHasher, PasswordPolicy};
 use super::errors::{AuthError, AuthResult};
 
 /// User model
@@ -40,11 +40,21 @@ pub struct User {
 impl User {
     /// Create a new user with the given email and password
     pub fn new(email: String, password: &str, policy: &PasswordPolicy) -> AuthResult<Self> {
+        Self::new_with_hasher(email, password, policy, &PasswordHasher::default())
+    }
+
+    /// Create a new user, hashing the password with `hasher`
+    pub fn new_with_hasher(
+        email: String,
+        password: &str,
+        policy: &PasswordPolicy,
+        hasher: &PasswordHasher,
+    ) -> AuthResult<Self> {
         // Validate password
         validate_password(password, policy)?;
 
         // Hash password
-        let password_hash = hash_password(password)?;
+        let password_hash = hasher.hash(password)?;
 
         let now = Utc::now();
 
@@ -71,9 +81,7 @@ impl User {
         policy: &PasswordPolicy,
     ) -> AuthResult<()> {
         validate_password(new_password, policy)?;
-        self.password_hash = hash_password(new_password)?;
-        self.updated_at = Utc::now();
-        Ok(())
+        self.set_password(new_password)
     }
 
     /// Mark email as verified
@@ -84,7 +92,16 @@ impl User {
 
     /// Set a new password (bypasses policy validation - use when policy already validated)
     pub fn set_password(&mut self, new_password: &str) -> AuthResult<()> {
-        self.password_hash = hash_password(new_password)?;
+        self.set_password_with(&PasswordHasher::default(), new_password)
+    }
+
+    /// Set a new password hashed with `hasher` (bypasses policy validation)
+    pub fn set_password_with(
+        &mut self,
+        hasher: &PasswordHasher,
+        new_password: &str,
+    ) -> AuthResult<()> {
+        self.password_hash = hasher.hash(new_password)?;
         self.updated_at = Utc::now();
         Ok(())
     }
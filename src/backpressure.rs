@@ -8,12 +8,13 @@
 //! - No silent degradation
 
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 
 /// Backpressure configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BackpressureConfig {
     /// Maximum concurrent connections
     pub max_connections: usize,
@@ -130,7 +131,7 @@ impl BackpressureError {
 /// HARDENING: Central control for system load management.
 #[derive(Debug)]
 pub struct BackpressureManager {
-    config: BackpressureConfig,
+    config: RwLock<BackpressureConfig>,
     current_connections: AtomicUsize,
     current_queue_depth: AtomicUsize,
 }
@@ -138,7 +139,7 @@ pub struct BackpressureManager {
 impl BackpressureManager {
     pub fn new(config: BackpressureConfig) -> Self {
         Self {
-            config,
+            config: RwLock::new(config),
             current_connections: AtomicUsize::new(0),
             current_queue_depth: AtomicUsize::new(0),
         }
@@ -148,14 +149,15 @@ impl BackpressureManager {
     ///
     /// Returns a guard that releases the slot on drop.
     pub fn try_acquire_connection(&self) -> Result<ConnectionGuard, BackpressureError> {
+        let config = self.config();
         let current = self.current_connections.fetch_add(1, Ordering::AcqRel);
         
-        if current >= self.config.max_connections {
+        if current >= config.max_connections {
             // Roll back the increment
             self.current_connections.fetch_sub(1, Ordering::Release);
             return Err(BackpressureError::ConnectionLimitReached {
                 current,
-                limit: self.config.max_connections,
+                limit: config.max_connections,
             });
         }
 
@@ -163,27 +165,28 @@ impl BackpressureManager {
             counter: Arc::new(ConnectionCounterInner {
                 current_connections: &self.current_connections as *const _ as usize,
             }),
-            config: self.config.clone(),
+            config,
             ops_count: AtomicUsize::new(0),
         })
     }
 
     /// Try to acquire a queue slot for an operation
     pub fn try_enqueue(&self) -> Result<QueueGuard, BackpressureError> {
+        let config = self.config();
         let current = self.current_queue_depth.fetch_add(1, Ordering::AcqRel);
         
-        if current >= self.config.max_queue_depth {
+        if current >= config.max_queue_depth {
             self.current_queue_depth.fetch_sub(1, Ordering::Release);
             return Err(BackpressureError::QueueFull {
                 current,
-                limit: self.config.max_queue_depth,
+                limit: config.max_queue_depth,
             });
         }
 
         Ok(QueueGuard {
             manager: self,
             enqueued_at: Instant::now(),
-            timeout_ms: self.config.queue_timeout_ms,
+            timeout_ms: config.queue_timeout_ms,
         })
     }
 
@@ -198,14 +201,23 @@ impl BackpressureManager {
     }
 
     /// Get configuration
-    pub fn config(&self) -> &BackpressureConfig {
-        &self.config
+    pub fn config(&self) -> BackpressureConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Replace the limits without restarting
+    ///
+    /// Admitted connections and queued operations are not affected; a
+    /// connection keeps the per-connection limit it was opened with.
+    pub fn reconfigure(&self, config: BackpressureConfig) {
+        *self.config.write().unwrap() = config;
     }
 
     /// Get system load status
     pub fn load_status(&self) -> LoadStatus {
-        let conn_percent = (self.current_connections() as f64 / self.config.max_connections as f64) * 100.0;
-        let queue_percent = (self.current_queue_depth() as f64 / self.config.max_queue_depth as f64) * 100.0;
+        let config = self.config();
+        let conn_percent = (self.current_connections() as f64 / config.max_connections as f64) * 100.0;
+        let queue_percent = (self.current_queue_depth() as f64 / config.max_queue_depth as f64) * 100.0;
         
         let max_percent = conn_percent.max(queue_percent);
         
@@ -381,6 +393,23 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_reconfigure_raises_queue_limit() {
+        let manager = BackpressureManager::new(BackpressureConfig {
+            max_queue_depth: 1,
+            ..Default::default()
+        });
+        let _q1 = manager.try_enqueue().unwrap();
+        assert!(manager.try_enqueue().is_err());
+
+        manager.reconfigure(BackpressureConfig {
+            max_queue_depth: 2,
+            ..Default::default()
+        });
+        let _q2 = manager.try_enqueue().unwrap();
+        assert_eq!(manager.config().max_queue_depth, 2);
+    }
+
     #[test]
     fn test_ops_per_connection() {
         let config = BackpressureConfig {
@@ -7,20 +7,52 @@
 //! - Backups are atomic and crash-safe
 //! - Backups are compatible with RestoreManager
 
+use std::collections::BTreeMap;
 use std::fs::{self, File};
-use std::io::Read;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 use tar::Builder;
 
 use crate::backup::errors::{BackupError, BackupResult};
+use crate::backup::verify::{self, VerificationReport, MANIFEST_ENTRY};
 use crate::backup::{BackupConfig, BackupManifest, BackupMetadata, BackupStatus};
-use crate::snapshot::{GlobalExecutionLock, SnapshotManager};
-use crate::wal::WalWriter;
+use crate::checkpoint::{WalHold, WalRetention};
+use crate::crash_point::{maybe_crash, points};
+use crate::snapshot::{
+    compute_file_checksum, format_checksum, GlobalExecutionLock, PendingSnapshot, SnapshotFence,
+    SnapshotManager,
+};
+use crate::wal::{detect_layout, WalLayout, WalWriter, WAL_BASE_FILE, WAL_EPOCH_FILE};
 
 /// Backup format version
-const BACKUP_FORMAT_VERSION: u32 = 1;
+pub(crate) const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Suffix of the marker written next to an archive that passed verification
+const VERIFIED_MARKER_SUFFIX: &str = "verified";
+
+/// A backup whose snapshot has been fenced but not yet copied.
+///
+/// Returned by [`BackupManager::begin_backup`] under the global execution
+/// lock and consumed by [`BackupManager::finish_backup`] after it is
+/// released. Dropping it abandons the backup and removes its snapshot.
+///
+/// Until then it holds the WAL, so a checkpoint does not delete the files
+/// it still has to copy.
+pub struct PendingBackup {
+    snapshot: PendingSnapshot,
+    data_dir: PathBuf,
+    wal_files: Vec<(PathBuf, u64)>,
+    wal_hold: WalHold,
+}
+
+impl PendingBackup {
+    /// Returns the point the backup's contents were fixed at.
+    pub fn fence(&self) -> SnapshotFence {
+        self.snapshot.fence()
+    }
+}
 
 /// Backup manager for creating and managing database backups.
 ///
@@ -33,6 +65,7 @@ const BACKUP_FORMAT_VERSION: u32 = 1;
 pub struct BackupManager {
     config: BackupConfig,
     backup_dir: PathBuf,
+    wal_retention: WalRetention,
 }
 
 impl BackupManager {
@@ -58,21 +91,28 @@ impl BackupManager {
             return Err(BackupError::dir_not_accessible(&backup_dir));
         }
 
-        Ok(Self { config, backup_dir })
+        Ok(Self {
+            config,
+            backup_dir,
+            wal_retention: WalRetention::new(),
+        })
+    }
+
+    /// Hold the WAL in `retention` while a backup is in progress
+    ///
+    /// Pass the retention of the checkpoint scheduler, so that it keeps
+    /// the WAL files a pending backup has yet to copy.
+    pub fn with_wal_retention(mut self, retention: WalRetention) -> Self {
+        self.wal_retention = retention;
+        self
     }
 
     /// Create a new backup from the current database state.
     ///
-    /// # Algorithm
-    /// 1. Create snapshot using SnapshotManager
-    /// 2. Create temp directory for backup assembly
-    /// 3. Copy snapshot files to temp/snapshot/
-    /// 4. Copy WAL files to temp/wal/
-    /// 5. Generate backup_manifest.json
-    /// 6. Create tar archive
-    /// 7. fsync archive file
-    /// 8. Clean up temp directory
-    /// 9. Enforce retention policy
+    /// Runs [`begin_backup`](Self::begin_backup) and
+    /// [`finish_backup`](Self::finish_backup) back to back, for callers
+    /// that hold the global execution lock throughout. Callers that can
+    /// release the lock between the two should call them directly.
     ///
     /// # Arguments
     /// * `data_dir` - Root data directory
@@ -94,8 +134,31 @@ impl BackupManager {
         description: Option<String>,
         lock: &GlobalExecutionLock,
     ) -> BackupResult<BackupMetadata> {
-        // Step 1: Create snapshot
-        let snapshot_id = SnapshotManager::create_snapshot(
+        let pending = self.begin_backup(data_dir, storage_path, schema_dir, wal, lock)?;
+        self.finish_backup(pending, description)
+    }
+
+    /// Fence a backup while the global execution lock is held.
+    ///
+    /// Records the snapshot fence (storage length and WAL sequence) and the
+    /// length of every WAL file. Nothing large is copied, so the lock can be
+    /// released as soon as this returns.
+    ///
+    /// # Arguments
+    /// * `data_dir` - Root data directory
+    /// * `storage_path` - Path to storage file
+    /// * `schema_dir` - Path to schema directory
+    /// * `wal` - WAL writer reference
+    /// * `lock` - Global execution lock (held by caller)
+    pub fn begin_backup(
+        &self,
+        data_dir: &Path,
+        storage_path: &Path,
+        schema_dir: &Path,
+        wal: &WalWriter,
+        lock: &GlobalExecutionLock,
+    ) -> BackupResult<PendingBackup> {
+        let snapshot = SnapshotManager::begin_snapshot(
             data_dir,
             storage_path,
             schema_dir,
@@ -104,6 +167,55 @@ impl BackupManager {
         )
         .map_err(|e| BackupError::snapshot_failed(format!("Snapshot creation failed: {}", e)))?;
 
+        // Every WAL file on disk is copied, so hold all of it
+        let wal_hold = self.wal_retention.hold("backup", 0);
+        let wal_files = self.fence_wal_files(&data_dir.join("wal"))?;
+
+        Ok(PendingBackup {
+            snapshot,
+            data_dir: data_dir.to_path_buf(),
+            wal_files,
+            wal_hold,
+        })
+    }
+
+    /// Assemble and archive a fenced backup. Does not need the lock.
+    ///
+    /// # Algorithm
+    /// 1. Copy storage up to the fence into the snapshot (throttled)
+    /// 2. Create temp directory for backup assembly
+    /// 3. Copy snapshot files to temp/snapshot/
+    /// 4. Copy WAL files, each up to its fenced length, to temp/wal/
+    /// 5. Generate backup_manifest.json with per-file checksums
+    /// 6. Create tar archive
+    /// 7. fsync archive file
+    /// 8. Clean up temp directory
+    /// 9. Enforce retention policy
+    ///
+    /// # Arguments
+    /// * `pending` - Backup fenced by [`begin_backup`](Self::begin_backup)
+    /// * `description` - Optional backup description
+    ///
+    /// # Returns
+    /// BackupMetadata on success
+    pub fn finish_backup(
+        &self,
+        pending: PendingBackup,
+        description: Option<String>,
+    ) -> BackupResult<BackupMetadata> {
+        // The WAL stays held until the copy below is done
+        let PendingBackup {
+            snapshot,
+            data_dir,
+            wal_files,
+            wal_hold: _wal_hold,
+        } = pending;
+
+        // Step 1: Complete snapshot
+        let snapshot_id = snapshot
+            .complete(&self.config.snapshot)
+            .map_err(|e| BackupError::snapshot_failed(format!("Snapshot creation failed: {}", e)))?;
+
         let snapshot_id_str = snapshot_id.to_string();
         let backup_id = format!("backup_{}", snapshot_id_str);
         let created_at = Utc::now();
@@ -129,38 +241,47 @@ impl BackupManager {
         self.copy_dir_recursive(&snapshot_src, &snapshot_dest)?;
 
         // Step 4: Copy WAL files to temp/wal/
-        let wal_src = data_dir.join("wal");
         let wal_dest = temp_dir.join("wal");
-        let wal_present = if wal_src.exists() {
-            self.copy_dir_recursive(&wal_src, &wal_dest)?;
-            true
-        } else {
-            fs::create_dir_all(&wal_dest).map_err(|e| {
-                BackupError::io_error(e, "Failed to create WAL directory")
-            })?;
-            false
-        };
+        self.copy_wal_files(&wal_files, &wal_dest)?;
+        let wal_present = !wal_files.is_empty();
 
         // Step 5: Generate backup_manifest.json
+        let mut checksums = BTreeMap::new();
+        self.collect_checksums(&temp_dir, &temp_dir, &mut checksums)?;
+
         let manifest = BackupManifest {
             backup_id: backup_id.clone(),
             snapshot_id: snapshot_id_str.clone(),
             created_at: created_at_str.clone(),
             wal_present,
             format_version: BACKUP_FORMAT_VERSION,
+            checksums,
+            description: description.clone(),
         };
 
-        let manifest_path = temp_dir.join("backup_manifest.json");
+        let manifest_path = temp_dir.join(MANIFEST_ENTRY);
         manifest.write_to_file(&manifest_path).map_err(|e| {
             BackupError::io_error(e, "Failed to write backup manifest")
         })?;
 
-        // Step 6: Create tar archive
+        // Step 6: Create tar archive under a name list_backups ignores
         let archive_path = self.backup_dir.join(format!("{}.tar", backup_id));
-        self.create_tar_archive(&temp_dir, &archive_path)?;
-
-        // Step 7: fsync archive file
-        self.fsync_file(&archive_path)?;
+        let partial_path = self.backup_dir.join(format!("{}.tar.partial", backup_id));
+        let published = self
+            .create_tar_archive(&temp_dir, &partial_path)
+            .and_then(|()| {
+                // Step 7: fsync archive file, then publish it under its final name
+                maybe_crash(points::BACKUP_BEFORE_ARCHIVE_FSYNC);
+                self.fsync_file(&partial_path)?;
+                fs::rename(&partial_path, &archive_path).map_err(|e| {
+                    BackupError::io_error(e, format!("Failed to publish archive: {}", archive_path.display()))
+                })?;
+                self.fsync_dir(&self.backup_dir)
+            });
+        if published.is_err() {
+            let _ = fs::remove_file(&partial_path);
+        }
+        published?;
 
         // Step 8: Clean up temp directory (handled by CleanupGuard drop)
 
@@ -237,12 +358,70 @@ impl BackupManager {
             BackupError::io_error(e, format!("Failed to delete backup: {}", backup_id))
         })?;
 
+        let marker = self.verified_marker_path(backup_id);
+        if marker.exists() {
+            fs::remove_file(&marker).map_err(|e| {
+                BackupError::io_error(e, format!("Failed to delete verification marker: {}", backup_id))
+            })?;
+        }
+
         Ok(())
     }
 
+    /// Verify that a backup is restorable without restoring it.
+    ///
+    /// Unpacks the archive into a scratch directory and checks the backup
+    /// manifest, per-file checksums, snapshot metadata and WAL framing.
+    /// Integrity problems are reported in the returned report, not as
+    /// errors. A backup that passes is marked verified for retention.
+    ///
+    /// Only plain tar archives exist in this format version; compressed
+    /// and encrypted backups are not supported (see BACKUP.md §9).
+    pub fn verify_backup(&self, backup_id: &str) -> BackupResult<VerificationReport> {
+        let archive_path = self.backup_dir.join(format!("{}.tar", backup_id));
+
+        if !archive_path.exists() {
+            return Err(BackupError::not_found(backup_id));
+        }
+
+        let work_dir = self.backup_dir.join(format!("{}.verify.tmp", backup_id));
+        if work_dir.exists() {
+            fs::remove_dir_all(&work_dir).map_err(|e| {
+                BackupError::io_error(e, "Failed to clean existing verification directory")
+            })?;
+        }
+        fs::create_dir_all(&work_dir).map_err(|e| {
+            BackupError::io_error(e, "Failed to create verification directory")
+        })?;
+        let _cleanup_guard = CleanupGuard::new(&work_dir);
+
+        let report = verify::verify_archive(&archive_path, backup_id, &work_dir)?;
+
+        let marker = self.verified_marker_path(backup_id);
+        if report.ok {
+            let verified_at = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+            fs::write(&marker, verified_at).map_err(|e| {
+                BackupError::io_error(e, format!("Failed to mark backup verified: {}", backup_id))
+            })?;
+        } else if marker.exists() {
+            fs::remove_file(&marker).map_err(|e| {
+                BackupError::io_error(e, format!("Failed to clear verification marker: {}", backup_id))
+            })?;
+        }
+
+        Ok(report)
+    }
+
+    /// Whether the backup passed its most recent verification.
+    pub fn is_verified(&self, backup_id: &str) -> bool {
+        self.verified_marker_path(backup_id).exists()
+    }
+
     /// Enforce retention policy by deleting old backups.
     ///
-    /// Keeps only the `max_backups` most recent backups.
+    /// Keeps only the `max_backups` most recent backups. With
+    /// `keep_verified`, the newest verified backup beyond that limit is
+    /// also kept when none of the retained backups is verified.
     ///
     /// # Returns
     /// Number of backups deleted
@@ -254,16 +433,21 @@ impl BackupManager {
             return Ok(0);
         }
 
-        // Backups are sorted newest first, so delete from the end
+        // Backups are sorted newest first, so the expired ones are at the end
+        let mut expired = backups.split_off(max_backups);
+        if self.config.keep_verified && !backups.iter().any(|b| self.is_verified(&b.id)) {
+            if let Some(pos) = expired.iter().position(|b| self.is_verified(&b.id)) {
+                expired.remove(pos);
+            }
+        }
+
         let mut deleted = 0u32;
-        while backups.len() > max_backups {
-            if let Some(oldest) = backups.pop() {
-                if let Err(e) = self.delete_backup(&oldest.id) {
-                    // Log but don't fail
-                    eprintln!("Warning: Failed to delete old backup {}: {}", oldest.id, e);
-                } else {
-                    deleted += 1;
-                }
+        for oldest in expired.iter().rev() {
+            if let Err(e) = self.delete_backup(&oldest.id) {
+                // Log but don't fail
+                eprintln!("Warning: Failed to delete old backup {}: {}", oldest.id, e);
+            } else {
+                deleted += 1;
             }
         }
 
@@ -317,7 +501,7 @@ impl BackupManager {
                 BackupError::io_error(e, "Failed to get entry path")
             })?;
 
-            if path.to_string_lossy() == "backup_manifest.json" {
+            if path.to_string_lossy() == MANIFEST_ENTRY {
                 let mut contents = String::new();
                 entry.read_to_string(&mut contents).map_err(|e| {
                     BackupError::io_error(e, "Failed to read manifest")
@@ -335,7 +519,7 @@ impl BackupManager {
                     id: manifest.backup_id,
                     created_at: manifest.created_at,
                     size_bytes,
-                    description: None,
+                    description: manifest.description,
                 }));
             }
         }
@@ -397,6 +581,46 @@ impl BackupManager {
         Ok(())
     }
 
+    /// Checksum every file below `current_dir`, keyed by archive path.
+    fn collect_checksums(
+        &self,
+        base_dir: &Path,
+        current_dir: &Path,
+        checksums: &mut BTreeMap<String, String>,
+    ) -> BackupResult<()> {
+        for entry in fs::read_dir(current_dir).map_err(|e| {
+            BackupError::io_error(e, format!("Failed to read directory: {}", current_dir.display()))
+        })? {
+            let entry = entry.map_err(|e| {
+                BackupError::io_error(e, "Failed to read directory entry")
+            })?;
+
+            let path = entry.path();
+            if path.is_dir() {
+                self.collect_checksums(base_dir, &path, checksums)?;
+            } else if path.is_file() {
+                let checksum = compute_file_checksum(&path).map_err(|e| {
+                    BackupError::archive_failed(format!("Failed to checksum {}: {}", path.display(), e))
+                })?;
+                let relative = path.strip_prefix(base_dir).unwrap_or(&path);
+                let name = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                checksums.insert(name, format_checksum(checksum));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Path of the marker recording that a backup passed verification.
+    fn verified_marker_path(&self, backup_id: &str) -> PathBuf {
+        self.backup_dir
+            .join(format!("{}.{}", backup_id, VERIFIED_MARKER_SUFFIX))
+    }
+
     /// Copy directory recursively.
     fn copy_dir_recursive(&self, src: &Path, dst: &Path) -> BackupResult<()> {
         fs::create_dir_all(dst).map_err(|e| {
@@ -425,6 +649,77 @@ impl BackupManager {
         Ok(())
     }
 
+    /// List the WAL files to back up with their current lengths: the
+    /// legacy `wal.log` or every segment.
+    ///
+    /// Files in the WAL directory that are not part of the WAL are skipped.
+    fn fence_wal_files(&self, wal_dir: &Path) -> BackupResult<Vec<(PathBuf, u64)>> {
+        let mut files = match detect_layout(wal_dir)
+            .map_err(|e| BackupError::archive_failed(format!("Cannot back up WAL: {}", e)))?
+        {
+            WalLayout::Empty => return Ok(Vec::new()),
+            WalLayout::Legacy(path) => vec![path],
+            WalLayout::Segmented(segments) => {
+                let mut files: Vec<PathBuf> = segments.into_iter().map(|s| s.path).collect();
+                // Needed to replay a WAL whose leading segments were removed
+                let base = wal_dir.join(WAL_BASE_FILE);
+                if base.is_file() {
+                    files.push(base);
+                }
+                files
+            }
+        };
+        // Keeps the restored node fenced from earlier primaries
+        let epoch = wal_dir.join(WAL_EPOCH_FILE);
+        if epoch.is_file() {
+            files.push(epoch);
+        }
+
+        files
+            .into_iter()
+            .map(|path| {
+                let length = fs::metadata(&path).map_err(|e| {
+                    BackupError::io_error(e, format!("Failed to read WAL file: {}", path.display()))
+                })?.len();
+                Ok((path, length))
+            })
+            .collect()
+    }
+
+    /// Copy fenced WAL files into a backup.
+    ///
+    /// Each file is copied up to its length at the fence, so records
+    /// appended while the backup is assembled are left out.
+    fn copy_wal_files(&self, files: &[(PathBuf, u64)], dst: &Path) -> BackupResult<()> {
+        fs::create_dir_all(dst).map_err(|e| {
+            BackupError::io_error(e, "Failed to create WAL directory")
+        })?;
+
+        for (src_path, length) in files {
+            let dst_path = dst.join(src_path.file_name().unwrap_or_default());
+            let src = File::open(src_path).map_err(|e| {
+                BackupError::io_error(e, format!("Failed to open WAL file: {}", src_path.display()))
+            })?;
+            let mut out = File::create(&dst_path).map_err(|e| {
+                BackupError::io_error(e, format!("Failed to create file: {}", dst_path.display()))
+            })?;
+            let copied = io::copy(&mut src.take(*length), &mut out).map_err(|e| {
+                BackupError::io_error(e, format!("Failed to copy file: {}", src_path.display()))
+            })?;
+
+            if copied != *length {
+                return Err(BackupError::archive_failed(format!(
+                    "WAL file {} shrank during backup: expected {} bytes, found {}",
+                    src_path.display(),
+                    length,
+                    copied
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Fsync a file to disk.
     fn fsync_file(&self, path: &Path) -> BackupResult<()> {
         let file = File::open(path).map_err(|e| {
@@ -437,6 +732,19 @@ impl BackupManager {
 
         Ok(())
     }
+
+    /// Fsync a directory so a rename into it is durable.
+    fn fsync_dir(&self, path: &Path) -> BackupResult<()> {
+        let dir = File::open(path).map_err(|e| {
+            BackupError::io_error(e, format!("Failed to open directory for fsync: {}", path.display()))
+        })?;
+
+        dir.sync_all().map_err(|e| {
+            BackupError::io_error(e, format!("Failed to fsync directory: {}", path.display()))
+        })?;
+
+        Ok(())
+    }
 }
 
 /// RAII guard for cleaning up temp directories.
@@ -461,6 +769,7 @@ impl<'a> Drop for CleanupGuard<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::snapshot::SnapshotConfig;
     use tempfile::TempDir;
 
     fn create_test_config(backup_dir: &Path) -> BackupConfig {
@@ -469,6 +778,8 @@ mod tests {
             interval_hours: 24,
             max_backups: 3,
             backup_dir: backup_dir.to_string_lossy().to_string(),
+            keep_verified: false,
+            snapshot: SnapshotConfig::default(),
         }
     }
 
@@ -493,6 +804,8 @@ mod tests {
             interval_hours: 24,
             max_backups: 7,
             backup_dir: backup_dir.to_string_lossy().to_string(),
+            keep_verified: false,
+            snapshot: SnapshotConfig::default(),
         };
         
         let manager = BackupManager::new(config);
@@ -551,4 +864,305 @@ mod tests {
         let deleted = manager.enforce_retention().unwrap();
         assert_eq!(deleted, 0);
     }
+
+    /// Creates a backup of a data dir with three one-record WAL segments.
+    fn create_backup_with_wal(temp: &TempDir) -> (BackupManager, BackupMetadata) {
+        use crate::wal::{RecordType, WalPayload, WalSegmentConfig, WalSyncConfig};
+
+        let data_dir = temp.path().join("data");
+        let storage_path = data_dir.join("storage.dat");
+        let schema_dir = data_dir.join("metadata").join("schemas");
+        fs::create_dir_all(&schema_dir).unwrap();
+        fs::write(&storage_path, b"storage contents for the snapshot").unwrap();
+        fs::write(schema_dir.join("users_v1.json"), br#"{"name":"users"}"#).unwrap();
+
+        let mut wal = WalWriter::open_segmented(
+            &data_dir,
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(1),
+        )
+        .unwrap();
+        for i in 0..3 {
+            let payload = WalPayload::new("c", format!("doc{}", i), "s", "v1", b"{}".to_vec());
+            wal.append(RecordType::Insert, payload).unwrap();
+        }
+        fs::write(data_dir.join("wal").join("stray.txt"), b"not wal").unwrap();
+
+        let manager = BackupManager::new(create_test_config(&temp.path().join("backups"))).unwrap();
+        let lock = GlobalExecutionLock::new();
+        let metadata = manager
+            .create_backup(&data_dir, &storage_path, &schema_dir, &wal, None, &lock)
+            .unwrap();
+        (manager, metadata)
+    }
+
+    /// Flips a byte in the middle of an entry's data in the archive.
+    fn corrupt_entry(archive_path: &Path, entry_name: &str) {
+        let mut archive = tar::Archive::new(File::open(archive_path).unwrap());
+        let offset = archive
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap())
+            .find(|e| e.path().unwrap().to_string_lossy() == entry_name)
+            .map(|e| e.raw_file_position() + e.size() / 2)
+            .unwrap();
+
+        let mut bytes = fs::read(archive_path).unwrap();
+        bytes[offset as usize] ^= 0xFF;
+        fs::write(archive_path, bytes).unwrap();
+    }
+
+    /// Writes an archive holding only a manifest, enough for listing.
+    fn write_listed_backup(manager: &BackupManager, id: &str, created_at: &str) {
+        let manifest = BackupManifest {
+            backup_id: id.to_string(),
+            snapshot_id: id.to_string(),
+            created_at: created_at.to_string(),
+            wal_present: false,
+            format_version: BACKUP_FORMAT_VERSION,
+            checksums: BTreeMap::new(),
+            description: None,
+        };
+        let contents = serde_json::to_vec(&manifest).unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+
+        let file = File::create(manager.backup_dir.join(format!("{}.tar", id))).unwrap();
+        let mut builder = Builder::new(file);
+        builder
+            .append_data(&mut header, MANIFEST_ENTRY, contents.as_slice())
+            .unwrap();
+        builder.finish().unwrap();
+    }
+
+    #[test]
+    fn test_verify_backup_passes_and_marks_verified() {
+        let temp = TempDir::new().unwrap();
+        let (manager, metadata) = create_backup_with_wal(&temp);
+
+        let report = manager.verify_backup(&metadata.id).unwrap();
+
+        assert!(report.ok, "unexpected issues: {:?}", report.errors);
+        // manifest, snapshot manifest, storage, one schema, three segments
+        assert_eq!(report.files_checked, 7);
+        assert!(manager.is_verified(&metadata.id));
+        assert!(!manager.backup_dir.join(format!("{}.verify.tmp", metadata.id)).exists());
+    }
+
+    #[test]
+    fn test_verify_backup_pinpoints_corrupted_entry() {
+        let temp = TempDir::new().unwrap();
+        let (manager, metadata) = create_backup_with_wal(&temp);
+        manager.verify_backup(&metadata.id).unwrap();
+
+        let archive_path = manager.backup_dir.join(format!("{}.tar", metadata.id));
+        corrupt_entry(&archive_path, "snapshot/storage.dat");
+
+        let report = manager.verify_backup(&metadata.id).unwrap();
+
+        assert!(!report.ok);
+        assert!(!report.errors.is_empty());
+        assert!(report
+            .errors
+            .iter()
+            .all(|issue| issue.entry == "snapshot/storage.dat"));
+        assert!(report.errors[0].message.contains("Checksum mismatch"));
+        assert!(!manager.is_verified(&metadata.id));
+    }
+
+    #[test]
+    fn test_verify_backup_detects_corrupted_wal_record() {
+        let temp = TempDir::new().unwrap();
+        let (manager, metadata) = create_backup_with_wal(&temp);
+
+        let archive_path = manager.backup_dir.join(format!("{}.tar", metadata.id));
+        corrupt_entry(&archive_path, "wal/0000002.log");
+
+        let report = manager.verify_backup(&metadata.id).unwrap();
+
+        assert!(!report.ok);
+        assert!(report.errors.iter().all(|issue| issue.entry == "wal/0000002.log"));
+        assert!(report
+            .errors
+            .iter()
+            .any(|issue| issue.message.starts_with("Invalid WAL record")));
+    }
+
+    #[test]
+    fn test_verify_nonexistent_backup() {
+        let temp = TempDir::new().unwrap();
+        let manager = BackupManager::new(create_test_config(temp.path())).unwrap();
+
+        assert!(manager.verify_backup("nonexistent").is_err());
+    }
+
+    #[test]
+    fn test_retention_keeps_only_verified_backup() {
+        let temp = TempDir::new().unwrap();
+        let mut config = create_test_config(temp.path());
+        config.max_backups = 1;
+        config.keep_verified = true;
+        let manager = BackupManager::new(config).unwrap();
+
+        write_listed_backup(&manager, "b1", "2026-02-01T00:00:00Z");
+        write_listed_backup(&manager, "b2", "2026-02-02T00:00:00Z");
+        write_listed_backup(&manager, "b3", "2026-02-03T00:00:00Z");
+        fs::write(manager.verified_marker_path("b1"), "").unwrap();
+
+        assert_eq!(manager.enforce_retention().unwrap(), 1);
+        let mut ids: Vec<_> = manager.list_backups().unwrap().into_iter().map(|b| b.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["b1", "b3"]);
+
+        // Once a retained backup is verified the old one may go
+        fs::write(manager.verified_marker_path("b3"), "").unwrap();
+        assert_eq!(manager.enforce_retention().unwrap(), 1);
+        assert!(!manager.is_verified("b1"));
+        assert_eq!(manager.list_backups().unwrap()[0].id, "b3");
+    }
+
+    #[test]
+    fn test_backup_includes_all_wal_segments() {
+        let temp = TempDir::new().unwrap();
+        let (manager, metadata) = create_backup_with_wal(&temp);
+
+        let archive_path = manager.backup_dir.join(format!("{}.tar", metadata.id));
+        let mut archive = tar::Archive::new(File::open(archive_path).unwrap());
+        let mut wal_entries: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().to_string())
+            .filter(|p| p.starts_with("wal/"))
+            .collect();
+        wal_entries.sort();
+
+        assert_eq!(
+            wal_entries,
+            vec!["wal/0000001.log", "wal/0000002.log", "wal/0000003.log"]
+        );
+    }
+
+    fn write_document(wal: &mut WalWriter, storage: &mut crate::storage::StorageWriter, id: &str) {
+        use crate::storage::StoragePayload;
+        use crate::wal::{RecordType, WalPayload};
+
+        let body = vec![b'x'; 4096];
+        wal.append(RecordType::Insert, WalPayload::new("c", id, "s", "v1", body.clone()))
+            .unwrap();
+        storage
+            .write(&StoragePayload::new("c", id, "s", "v1", body))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_backup_with_concurrent_writes_is_consistent_at_fence() {
+        use crate::restore::RestoreManager;
+        use crate::storage::{StorageReader, StorageWriter};
+        use crate::wal::WalReader;
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path().join("data");
+        let schema_dir = data_dir.join("metadata").join("schemas");
+        fs::create_dir_all(&schema_dir).unwrap();
+
+        let engine = Arc::new(Mutex::new((
+            WalWriter::open(&data_dir).unwrap(),
+            StorageWriter::open(&data_dir).unwrap(),
+        )));
+        {
+            let (wal, storage) = &mut *engine.lock().unwrap();
+            for i in 0..64 {
+                write_document(wal, storage, &format!("before{}", i));
+            }
+        }
+
+        // 256 KiB of storage at 1 MiB/s keeps the copy running for ~250ms
+        let mut config = create_test_config(&temp.path().join("backups"));
+        config.snapshot = SnapshotConfig::default().with_io_throttle_mbps(1);
+        let manager = BackupManager::new(config).unwrap();
+
+        let pending = {
+            let (wal, storage) = &*engine.lock().unwrap();
+            let lock = GlobalExecutionLock::new();
+            manager
+                .begin_backup(&data_dir, storage.path(), &schema_dir, wal, &lock)
+                .unwrap()
+        };
+        let fence = pending.fence();
+
+        let writer = {
+            let engine = Arc::clone(&engine);
+            thread::spawn(move || {
+                for i in 0..32 {
+                    let (wal, storage) = &mut *engine.lock().unwrap();
+                    write_document(wal, storage, &format!("after{}", i));
+                }
+            })
+        };
+        let metadata = manager.finish_backup(pending, None).unwrap();
+        writer.join().unwrap();
+
+        {
+            let (wal, storage) = &*engine.lock().unwrap();
+            assert_eq!(fence.wal_sequence, 64);
+            assert_eq!(wal.last_sequence_number(), 96);
+            assert!(storage.current_offset() > fence.storage_length);
+        }
+        assert!(manager.verify_backup(&metadata.id).unwrap().ok);
+
+        let restore_dir = temp.path().join("restored");
+        fs::create_dir_all(&restore_dir).unwrap();
+        let archive_path = manager.backup_dir.join(format!("{}.tar", metadata.id));
+        RestoreManager::restore_from_backup(&restore_dir, &archive_path).unwrap();
+
+        let mut reader = StorageReader::open_from_data_dir(&restore_dir).unwrap();
+        let mut documents = Vec::new();
+        while let Some(record) = reader.read_next().unwrap() {
+            documents.push(record.document_id);
+        }
+        assert_eq!(documents.len(), 64);
+        assert!(documents.iter().all(|id| id.contains("before")));
+        assert_eq!(reader.current_offset(), fence.storage_length);
+
+        let records = WalReader::open_from_data_dir(&restore_dir)
+            .unwrap()
+            .read_all()
+            .unwrap();
+        assert_eq!(records.len(), 64);
+        assert_eq!(
+            records.last().unwrap().sequence_number,
+            fence.wal_sequence
+        );
+    }
+
+    #[test]
+    fn test_pending_backup_holds_wal() {
+        use crate::storage::StorageWriter;
+
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path().join("data");
+        let schema_dir = data_dir.join("metadata").join("schemas");
+        fs::create_dir_all(&schema_dir).unwrap();
+        let mut wal = WalWriter::open(&data_dir).unwrap();
+        let mut storage = StorageWriter::open(&data_dir).unwrap();
+        write_document(&mut wal, &mut storage, "doc");
+
+        let retention = WalRetention::new();
+        let manager = BackupManager::new(create_test_config(&temp.path().join("backups")))
+            .unwrap()
+            .with_wal_retention(retention.clone());
+
+        let lock = GlobalExecutionLock::new();
+        let pending = manager
+            .begin_backup(&data_dir, storage.path(), &schema_dir, &wal, &lock)
+            .unwrap();
+        assert_eq!(retention.oldest(), Some(("backup".to_string(), 0)));
+
+        manager.finish_backup(pending, None).unwrap();
+        assert_eq!(retention.oldest(), None);
+    }
 }
@@ -3,6 +3,7 @@
 //! This module provides:
 //! - BackupManager: Create, list, delete backups with retention policy
 //! - BackupScheduler: Timing logic for automatic backups
+//! - Verification: Integrity checks of an archive without restoring it
 //! - Error types: Structured backup error handling
 //!
 //! # Backup Format
@@ -10,25 +11,31 @@
 //! Backups are tar archives containing:
 //! - `backup_manifest.json` - Backup metadata
 //! - `snapshot/` - Database snapshot files
-//! - `wal/` - Write-ahead log files
+//! - `wal/` - Write-ahead log files (`wal.log` or every numbered segment)
 //!
 //! This format is compatible with RestoreManager for restoration.
 
 pub mod errors;
 pub mod manager;
 pub mod scheduler;
+mod verify;
 
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{Read, Result as IoResult};
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
+use crate::snapshot::SnapshotConfig;
+
 pub use errors::{BackupError, BackupErrorCode, BackupResult, Severity};
-pub use manager::BackupManager;
+pub use manager::{BackupManager, PendingBackup};
 pub use scheduler::BackupScheduler;
+pub use verify::{VerificationIssue, VerificationReport};
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BackupConfig {
     /// Enable automatic backups
     pub enabled: bool,
@@ -38,6 +45,12 @@ pub struct BackupConfig {
     pub max_backups: u32,
     /// Backup directory path
     pub backup_dir: String,
+    /// Never let retention delete the only verified backup
+    #[serde(default)]
+    pub keep_verified: bool,
+    /// Snapshot copy settings, including the I/O throttle
+    #[serde(default)]
+    pub snapshot: SnapshotConfig,
 }
 
 impl BackupConfig {
@@ -47,10 +60,18 @@ impl BackupConfig {
             interval_hours: 24,
             max_backups: 7,
             backup_dir: "/var/lib/aerodb/backups".to_string(),
+            keep_verified: false,
+            snapshot: SnapshotConfig::default(),
         }
     }
 }
 
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Metadata about a backup archive.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupManifest {
@@ -59,6 +80,13 @@ pub struct BackupManifest {
     pub created_at: String,
     pub wal_present: bool,
     pub format_version: u32,
+    /// CRC32 of every other file in the archive, keyed by archive path
+    /// (absent in backups made before checksums were recorded)
+    #[serde(default)]
+    pub checksums: BTreeMap<String, String>,
+    /// Operator note given at creation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 impl BackupManifest {
@@ -115,6 +143,8 @@ mod tests {
             created_at: "2026-02-07T12:00:00Z".to_string(),
             wal_present: true,
             format_version: 1,
+            checksums: BTreeMap::new(),
+            description: None,
         };
 
         manifest.write_to_file(temp_file.path()).unwrap();
@@ -118,6 +118,8 @@ mod tests {
             interval_hours,
             max_backups: 7,
             backup_dir: "/tmp/backups".to_string(),
+            keep_verified: false,
+            snapshot: Default::default(),
         }
     }
 
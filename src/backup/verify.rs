@@ -0,0 +1,391 @@
+//! Backup integrity verification.
+//!
+//! Checks that a backup archive is restorable without restoring it:
+//! - `backup_manifest.json` parses and has a supported format version
+//! - every file matches the checksum recorded in the backup manifest
+//! - the snapshot manifest agrees with the backup and with its files
+//! - WAL records are well framed and pass their CRC (nothing is applied)
+//!
+//! Problems are collected rather than returned as errors, so one pass
+//! reports every bad entry in the archive.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crc32fast::Hasher;
+use serde::{Deserialize, Serialize};
+
+use crate::backup::errors::{BackupError, BackupResult};
+use crate::backup::BackupManifest;
+use crate::snapshot::{format_checksum, parse_checksum, SnapshotManifest};
+use crate::wal::{detect_layout, WalLayout, WalReader};
+
+/// Name of the backup manifest inside the archive.
+pub(crate) const MANIFEST_ENTRY: &str = "backup_manifest.json";
+
+/// A problem found while verifying a backup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationIssue {
+    /// Archive entry the problem was found in (e.g. `wal/0000002.log`)
+    pub entry: String,
+    /// What is wrong with it
+    pub message: String,
+}
+
+/// Outcome of [`BackupManager::verify_backup`](super::BackupManager::verify_backup).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    /// True when no issue was found
+    pub ok: bool,
+    /// Number of file entries read from the archive
+    pub files_checked: usize,
+    /// Every issue found, in archive order
+    pub errors: Vec<VerificationIssue>,
+}
+
+/// Verifies the archive at `archive_path`.
+///
+/// Entries are unpacked into `work_dir`, which the caller owns and removes.
+pub(crate) fn verify_archive(
+    archive_path: &Path,
+    backup_id: &str,
+    work_dir: &Path,
+) -> BackupResult<VerificationReport> {
+    let mut verifier = Verifier::default();
+    verifier.unpack(archive_path, work_dir)?;
+
+    let manifest = verifier.check_backup_manifest(work_dir, backup_id);
+    if let Some(manifest) = &manifest {
+        verifier.check_file_checksums(manifest);
+        verifier.check_snapshot(work_dir, manifest);
+    }
+    verifier.check_wal(work_dir, manifest.as_ref());
+
+    Ok(VerificationReport {
+        ok: verifier.errors.is_empty(),
+        files_checked: verifier.checksums.len() + verifier.unreadable.len(),
+        errors: verifier.errors,
+    })
+}
+
+#[derive(Default)]
+struct Verifier {
+    /// CRC32 of every file entry that was read in full
+    checksums: BTreeMap<String, u32>,
+    /// File entries that could not be read
+    unreadable: BTreeSet<String>,
+    errors: Vec<VerificationIssue>,
+}
+
+impl Verifier {
+    fn issue(&mut self, entry: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(VerificationIssue {
+            entry: entry.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Unpacks every file entry into `work_dir`, checksumming as it goes.
+    fn unpack(&mut self, archive_path: &Path, work_dir: &Path) -> BackupResult<()> {
+        let file = File::open(archive_path).map_err(|e| {
+            BackupError::io_error(
+                e,
+                format!("Failed to open backup: {}", archive_path.display()),
+            )
+        })?;
+        let mut archive = tar::Archive::new(file);
+        let entries = archive
+            .entries()
+            .map_err(|e| BackupError::io_error(e, "Failed to read archive entries"))?;
+
+        let mut last_entry = String::from("<start of archive>");
+        for entry in entries {
+            let mut entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    // The tar stream cannot be followed past a bad header
+                    self.issue(
+                        format!("after {}", last_entry),
+                        format!("Unreadable archive entry: {}", e),
+                    );
+                    return Ok(());
+                }
+            };
+
+            let name = match entry.path() {
+                Ok(path) => path.to_string_lossy().trim_end_matches('/').to_string(),
+                Err(e) => {
+                    self.issue(
+                        format!("after {}", last_entry),
+                        format!("Invalid entry path: {}", e),
+                    );
+                    continue;
+                }
+            };
+            last_entry = name.clone();
+
+            if entry.header().entry_type().is_dir() {
+                continue;
+            }
+            if !entry.header().entry_type().is_file() {
+                self.issue(&name, "Unexpected entry type in backup archive");
+                continue;
+            }
+            let Some(relative) = safe_relative_path(&name) else {
+                self.issue(&name, "Entry path escapes the archive root");
+                continue;
+            };
+
+            match extract_with_checksum(&mut entry, &work_dir.join(relative)) {
+                Ok(checksum) => {
+                    self.checksums.insert(name, checksum);
+                }
+                Err(e) => {
+                    self.issue(&name, format!("Failed to read entry: {}", e));
+                    self.unreadable.insert(name);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the backup manifest parses and describes this backup.
+    fn check_backup_manifest(
+        &mut self,
+        work_dir: &Path,
+        backup_id: &str,
+    ) -> Option<BackupManifest> {
+        if !self.checksums.contains_key(MANIFEST_ENTRY) {
+            if !self.unreadable.contains(MANIFEST_ENTRY) {
+                self.issue(MANIFEST_ENTRY, "Missing from backup archive");
+            }
+            return None;
+        }
+
+        let manifest = match BackupManifest::read_from_file(&work_dir.join(MANIFEST_ENTRY)) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                self.issue(MANIFEST_ENTRY, format!("Invalid backup manifest: {}", e));
+                return None;
+            }
+        };
+
+        if manifest.format_version != super::manager::BACKUP_FORMAT_VERSION {
+            self.issue(
+                MANIFEST_ENTRY,
+                format!(
+                    "Unsupported backup format version: expected {}, got {}",
+                    super::manager::BACKUP_FORMAT_VERSION,
+                    manifest.format_version
+                ),
+            );
+        }
+        if manifest.backup_id != backup_id {
+            self.issue(
+                MANIFEST_ENTRY,
+                format!(
+                    "Manifest names backup {}, archive is {}",
+                    manifest.backup_id, backup_id
+                ),
+            );
+        }
+
+        Some(manifest)
+    }
+
+    /// Compares every file against the checksums in the backup manifest.
+    ///
+    /// Manifests written before checksums were recorded have none; their
+    /// files are still covered by the snapshot and WAL checks.
+    fn check_file_checksums(&mut self, manifest: &BackupManifest) {
+        if manifest.checksums.is_empty() {
+            return;
+        }
+
+        let mut issues = Vec::new();
+        for (name, actual) in &self.checksums {
+            if name == MANIFEST_ENTRY {
+                continue;
+            }
+            match manifest.checksums.get(name) {
+                None => issues.push((name.clone(), "Not listed in backup manifest".to_string())),
+                Some(expected) if parse_checksum(expected) != Some(*actual) => issues.push((
+                    name.clone(),
+                    format!(
+                        "Checksum mismatch: expected {}, found {}",
+                        expected,
+                        format_checksum(*actual)
+                    ),
+                )),
+                Some(_) => {}
+            }
+        }
+        for name in manifest.checksums.keys() {
+            if !self.checksums.contains_key(name) && !self.unreadable.contains(name) {
+                issues.push((
+                    name.clone(),
+                    "Listed in backup manifest but missing from archive".to_string(),
+                ));
+            }
+        }
+
+        for (entry, message) in issues {
+            self.issue(entry, message);
+        }
+    }
+
+    /// Checks the snapshot manifest against the backup and its own files.
+    fn check_snapshot(&mut self, work_dir: &Path, manifest: &BackupManifest) {
+        const SNAPSHOT_MANIFEST: &str = "snapshot/manifest.json";
+        const STORAGE: &str = "snapshot/storage.dat";
+
+        if !self.checksums.contains_key(SNAPSHOT_MANIFEST) {
+            if !self.unreadable.contains(SNAPSHOT_MANIFEST) {
+                self.issue(SNAPSHOT_MANIFEST, "Missing from backup archive");
+            }
+            return;
+        }
+        let snapshot = match SnapshotManifest::read_from_file(&work_dir.join(SNAPSHOT_MANIFEST)) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                self.issue(
+                    SNAPSHOT_MANIFEST,
+                    format!("Invalid snapshot manifest: {}", e),
+                );
+                return;
+            }
+        };
+
+        if snapshot.snapshot_id != manifest.snapshot_id {
+            self.issue(
+                SNAPSHOT_MANIFEST,
+                format!(
+                    "Snapshot {} does not match backup manifest snapshot {}",
+                    snapshot.snapshot_id, manifest.snapshot_id
+                ),
+            );
+        }
+        match (snapshot.format_version, snapshot.commit_boundary) {
+            (1, None) | (2, Some(_)) => {}
+            (version, boundary) => self.issue(
+                SNAPSHOT_MANIFEST,
+                format!(
+                    "Inconsistent snapshot format: version {} with commit boundary {:?}",
+                    version, boundary
+                ),
+            ),
+        }
+
+        let mut expected: Vec<(String, String)> =
+            vec![(STORAGE.to_string(), snapshot.storage_checksum.clone())];
+        let mut schemas: Vec<_> = snapshot.schema_checksums.iter().collect();
+        schemas.sort();
+        expected.extend(
+            schemas
+                .into_iter()
+                .map(|(file, checksum)| (format!("snapshot/schemas/{}", file), checksum.clone())),
+        );
+
+        for (entry, checksum) in expected {
+            if self.unreadable.contains(&entry) {
+                continue;
+            }
+            match self.checksums.get(&entry) {
+                None => self.issue(
+                    &entry,
+                    "Listed in snapshot manifest but missing from archive",
+                ),
+                Some(actual) if parse_checksum(&checksum) != Some(*actual) => {
+                    let message = format!(
+                        "Snapshot checksum mismatch: expected {}, found {}",
+                        checksum,
+                        format_checksum(*actual)
+                    );
+                    self.issue(&entry, message);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Reads every WAL record without applying it.
+    fn check_wal(&mut self, work_dir: &Path, manifest: Option<&BackupManifest>) {
+        let wal_dir = work_dir.join("wal");
+        let files = match detect_layout(&wal_dir) {
+            Ok(WalLayout::Empty) => Vec::new(),
+            Ok(WalLayout::Legacy(path)) => vec![path],
+            Ok(WalLayout::Segmented(segments)) => segments.into_iter().map(|s| s.path).collect(),
+            Err(e) => {
+                self.issue("wal/", format!("Invalid WAL layout: {}", e));
+                return;
+            }
+        };
+
+        if files.is_empty() {
+            if manifest.is_some_and(|m| m.wal_present) {
+                self.issue(
+                    "wal/",
+                    "Backup manifest records a WAL but the archive has none",
+                );
+            }
+            return;
+        }
+
+        let mut reader = match WalReader::open_history(files) {
+            Ok(reader) => reader,
+            Err(e) => {
+                self.issue("wal/", format!("Failed to open WAL: {}", e));
+                return;
+            }
+        };
+        loop {
+            match reader.read_next() {
+                Ok(Some(_)) => {}
+                Ok(None) => return,
+                Err(e) => {
+                    let entry = wal_entry_name(reader.path());
+                    self.issue(entry, format!("Invalid WAL record: {}", e));
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the archive entry name of a WAL file in the work directory.
+fn wal_entry_name(path: &Path) -> String {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    format!("wal/{}", file_name)
+}
+
+/// Returns `name` as a relative path, or `None` if it could escape the root.
+fn safe_relative_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        .then(|| path.to_path_buf())
+}
+
+/// Copies an entry to `dest`, returning the CRC32 of its contents.
+fn extract_with_checksum(entry: &mut impl Read, dest: &Path) -> io::Result<u32> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = File::create(dest)?;
+    let mut hasher = Hasher::new();
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let read = entry.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        out.write_all(&buffer[..read])?;
+    }
+
+    Ok(hasher.finalize())
+}
@@ -21,8 +21,9 @@ use chrono::Utc;
 
 use super::errors::{CheckpointError, CheckpointResult};
 use super::marker::{marker_path, CheckpointMarker};
+use super::segments::{remove_covered_segments, truncate_wal_before};
 use super::CheckpointId;
-use crate::snapshot::{GlobalExecutionLock, SnapshotManager};
+use crate::snapshot::{snapshot_path, GlobalExecutionLock, SnapshotManager, SnapshotManifest};
 use crate::wal::WalWriter;
 
 /// Generate timestamp in RFC3339 format for created_at field
@@ -89,6 +90,8 @@ pub fn create_checkpoint_impl(
     // - WAL file deleted or truncated
     // - New WAL starts empty
     // - Sequence numbers reset to 1
+    // A segmented WAL first archives and removes the covered segments.
+    remove_covered_segments(wal)?;
     wal.truncate()?;
 
     // Step 7: fsync WAL directory is handled by truncate()
@@ -101,6 +104,75 @@ pub fn create_checkpoint_impl(
     Ok(checkpoint_id)
 }
 
+/// Create a checkpoint that keeps the WAL sequence running.
+///
+/// Instead of resetting the WAL, removes only the completed segments whose
+/// records are all at or below the snapshot's fence, leaving the active
+/// segment in place. The fence is read back from the durable snapshot
+/// manifest, so truncation can never run ahead of the snapshot.
+///
+/// # Crash Safety
+///
+/// - Crash before marker → snapshot ignored, WAL intact
+/// - Crash during truncation → snapshot used, remaining WAL replayed
+/// - Crash after truncation → snapshot used, WAL after the fence replayed
+pub fn create_fenced_checkpoint_impl(
+    data_dir: &Path,
+    storage_path: &Path,
+    schema_dir: &Path,
+    wal: &mut WalWriter,
+    lock: &GlobalExecutionLock,
+) -> CheckpointResult<CheckpointId> {
+    let marker =
+        create_retaining_checkpoint_impl(data_dir, storage_path, schema_dir, wal, u64::MAX, lock)?;
+    Ok(marker.snapshot_id)
+}
+
+/// Create a fenced checkpoint that keeps every record after `retain_after`.
+///
+/// Segments are removed only up to the lower of the snapshot fence and
+/// `retain_after`, so WAL still needed by a Replica or a backup survives.
+/// The marker records the fence as the checkpoint LSN either way: storage
+/// reflects those records, so recovery need not replay them.
+///
+/// # Returns
+///
+/// The final checkpoint marker.
+pub(crate) fn create_retaining_checkpoint_impl(
+    data_dir: &Path,
+    storage_path: &Path,
+    schema_dir: &Path,
+    wal: &mut WalWriter,
+    retain_after: u64,
+    lock: &GlobalExecutionLock,
+) -> CheckpointResult<CheckpointMarker> {
+    wal.fsync()?;
+
+    let snapshot_id =
+        SnapshotManager::create_snapshot(data_dir, storage_path, schema_dir, wal, lock)?;
+
+    let manifest = SnapshotManifest::read_from_file(
+        &snapshot_path(data_dir, &snapshot_id).join("manifest.json"),
+    )?;
+    let fence = manifest.fence.ok_or_else(|| {
+        CheckpointError::failed(format!("Snapshot {} has no WAL fence", snapshot_id))
+    })?;
+
+    let created_at = generate_created_at();
+    let mp = marker_path(data_dir);
+    CheckpointMarker::new(&snapshot_id, &created_at)
+        .with_wal_sequence(fence.wal_sequence)
+        .write_to_file(&mp)?;
+
+    truncate_wal_before(wal, fence.wal_sequence.min(retain_after))?;
+
+    let marker = CheckpointMarker::with_truncation(&snapshot_id, &created_at, true)
+        .with_wal_sequence(fence.wal_sequence);
+    marker.write_to_file(&mp)?;
+
+    Ok(marker)
+}
+
 /// Create an MVCC-aware checkpoint.
 ///
 /// Per MVCC_SNAPSHOT_INTEGRATION.md §5:
@@ -143,6 +215,8 @@ pub fn create_mvcc_checkpoint_impl(
     // - WAL file deleted or truncated
     // - New WAL starts empty
     // - Sequence numbers reset to 1
+    // A segmented WAL first archives and removes the covered segments.
+    remove_covered_segments(wal)?;
     wal.truncate()?;
 
     // Step 7: fsync WAL directory is handled by truncate()
@@ -373,4 +447,157 @@ mod tests {
         let marker = CheckpointMarker::read_from_file(&mp).unwrap();
         assert_eq!(marker.snapshot_id, cp2);
     }
+
+    #[test]
+    fn test_checkpoint_removes_covered_segments() {
+        use crate::wal::{list_segments, WalSegmentConfig, WalSyncConfig};
+
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        drop(wal);
+        let data_dir = temp_dir.path();
+        let archive_dir = data_dir.join("archive");
+        let lock = GlobalExecutionLock::new();
+
+        let mut wal = WalWriter::open_segmented(
+            data_dir,
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(1).with_archive_dir(&archive_dir),
+        )
+        .unwrap();
+        for i in 0..3 {
+            wal.append(
+                RecordType::Insert,
+                create_test_payload(&format!("doc{}", i)),
+            )
+            .unwrap();
+        }
+
+        create_checkpoint_impl(data_dir, &storage_path, &schema_dir, &mut wal, &lock).unwrap();
+
+        // Only the fresh active segment remains; covered ones were archived
+        let wal_dir = data_dir.join("wal");
+        let remaining: Vec<u64> = list_segments(&wal_dir)
+            .unwrap()
+            .iter()
+            .map(|s| s.index)
+            .collect();
+        assert_eq!(remaining, vec![4]);
+        assert_eq!(list_segments(&archive_dir).unwrap().len(), 3);
+        assert_eq!(wal.next_sequence_number(), 1);
+
+        // New writes replay from sequence 1 after restart
+        wal.append(RecordType::Insert, create_test_payload("doc_after"))
+            .unwrap();
+        let records = WalReader::open_from_data_dir(data_dir)
+            .unwrap()
+            .read_all()
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].sequence_number, 1);
+    }
+
+    #[test]
+    fn test_fenced_checkpoint_truncates_before_fence() {
+        use crate::wal::{list_segments, WalSegmentConfig, WalSyncConfig};
+
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        drop(wal);
+        let data_dir = temp_dir.path();
+        let lock = GlobalExecutionLock::new();
+        let open = || {
+            WalWriter::open_segmented(data_dir, WalSyncConfig::default(), WalSegmentConfig::new(1))
+                .unwrap()
+        };
+
+        let mut wal = open();
+        for i in 0..3 {
+            wal.append(
+                RecordType::Insert,
+                create_test_payload(&format!("doc{}", i)),
+            )
+            .unwrap();
+        }
+
+        let checkpoint_id =
+            create_fenced_checkpoint_impl(data_dir, &storage_path, &schema_dir, &mut wal, &lock)
+                .unwrap();
+        assert!(
+            CheckpointMarker::read_from_file(&marker_path(data_dir))
+                .unwrap()
+                .wal_truncated
+        );
+        let manifest = SnapshotManifest::read_from_file(
+            &snapshot_path(data_dir, &checkpoint_id).join("manifest.json"),
+        )
+        .unwrap();
+        assert_eq!(manifest.fence.unwrap().wal_sequence, 3);
+
+        // Segments 1-2 are covered; the active segment holding 3 stays
+        let wal_dir = data_dir.join("wal");
+        let remaining: Vec<u64> = list_segments(&wal_dir)
+            .unwrap()
+            .iter()
+            .map(|s| s.index)
+            .collect();
+        assert_eq!(remaining, vec![3]);
+        assert_eq!(wal.next_sequence_number(), 4);
+
+        // After a restart, the sequence continues and replay sees every
+        // record not covered by the snapshot
+        wal.append(RecordType::Insert, create_test_payload("doc_after"))
+            .unwrap();
+        drop(wal);
+        let wal = open();
+        assert_eq!(wal.next_sequence_number(), 5);
+        let records = WalReader::open_from_data_dir(data_dir)
+            .unwrap()
+            .read_all()
+            .unwrap();
+        let sequences: Vec<u64> = records.iter().map(|r| r.sequence_number).collect();
+        assert_eq!(sequences, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_retaining_checkpoint_keeps_records_after_limit() {
+        use crate::wal::{list_segments, WalSegmentConfig, WalSyncConfig};
+
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        drop(wal);
+        let data_dir = temp_dir.path();
+        let lock = GlobalExecutionLock::new();
+
+        let mut wal =
+            WalWriter::open_segmented(data_dir, WalSyncConfig::default(), WalSegmentConfig::new(1))
+                .unwrap();
+        for i in 0..3 {
+            wal.append(
+                RecordType::Insert,
+                create_test_payload(&format!("doc{}", i)),
+            )
+            .unwrap();
+        }
+
+        let marker = create_retaining_checkpoint_impl(
+            data_dir,
+            &storage_path,
+            &schema_dir,
+            &mut wal,
+            1,
+            &lock,
+        )
+        .unwrap();
+
+        // The LSN is the fence, but only the segment holding 1 is removed
+        assert_eq!(marker.wal_sequence, Some(3));
+        assert_eq!(
+            CheckpointMarker::read_from_file(&marker_path(data_dir)).unwrap(),
+            marker
+        );
+        let remaining: Vec<u64> = list_segments(&data_dir.join("wal"))
+            .unwrap()
+            .iter()
+            .map(|s| s.index)
+            .collect();
+        assert_eq!(remaining, vec![2, 3]);
+    }
 }
@@ -5,6 +5,8 @@
 //! - created_at: RFC3339 timestamp
 //! - wal_truncated: Whether WAL was successfully truncated
 //! - format_version: Always 1 for Phase 1
+//! - wal_sequence: Last WAL sequence reflected in storage (fenced
+//!   checkpoints only); recovery skips records at or below it
 //!
 //! Location: `<data_dir>/checkpoint.json`
 //!
@@ -32,6 +34,12 @@ pub struct CheckpointMarker {
 
     /// Format version (always 1 for Phase 1)
     pub format_version: u8,
+
+    /// Checkpoint LSN: the snapshot fence's last WAL sequence. Storage
+    /// reflects every record up to it. Unset when the checkpoint reset the
+    /// WAL sequence instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wal_sequence: Option<u64>,
 }
 
 impl CheckpointMarker {
@@ -48,6 +56,7 @@ impl CheckpointMarker {
             created_at: created_at.to_string(),
             wal_truncated: false,
             format_version: 1,
+            wal_sequence: None,
         }
     }
 
@@ -58,9 +67,16 @@ impl CheckpointMarker {
             created_at: created_at.to_string(),
             wal_truncated: truncated,
             format_version: 1,
+            wal_sequence: None,
         }
     }
 
+    /// Records the checkpoint LSN (fenced checkpoints)
+    pub fn with_wal_sequence(mut self, wal_sequence: u64) -> Self {
+        self.wal_sequence = Some(wal_sequence);
+        self
+    }
+
     /// Serializes the marker to JSON
     pub fn to_json(&self) -> CheckpointResult<String> {
         serde_json::to_string_pretty(self).map_err(|e| {
@@ -173,6 +189,17 @@ pub fn marker_path(data_dir: &Path) -> std::path::PathBuf {
     data_dir.join("checkpoint.json")
 }
 
+/// Returns the checkpoint LSN recorded in `data_dir`, if any.
+///
+/// `None` when there is no marker or the last checkpoint reset the WAL.
+pub fn checkpoint_sequence(data_dir: &Path) -> CheckpointResult<Option<u64>> {
+    let path = marker_path(data_dir);
+    if !CheckpointMarker::exists(&path) {
+        return Ok(None);
+    }
+    Ok(CheckpointMarker::read_from_file(&path)?.wal_sequence)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,6 +234,25 @@ mod tests {
         assert_eq!(marker, parsed);
     }
 
+    #[test]
+    fn test_checkpoint_sequence() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(checkpoint_sequence(temp_dir.path()).unwrap(), None);
+
+        // A marker from before the field existed carries no LSN
+        let path = marker_path(temp_dir.path());
+        fs::write(
+            &path,
+            r#"{"snapshot_id": "s", "created_at": "t", "wal_truncated": true, "format_version": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(checkpoint_sequence(temp_dir.path()).unwrap(), None);
+
+        let marker = CheckpointMarker::with_truncation("s", "t", true).with_wal_sequence(42);
+        marker.write_to_file(&path).unwrap();
+        assert_eq!(checkpoint_sequence(temp_dir.path()).unwrap(), Some(42));
+    }
+
     #[test]
     fn test_marker_json_format_matches_spec() {
         let marker =
@@ -26,6 +26,11 @@
 //! 7. fsync WAL directory
 //! 8. Release global execution lock
 //!
+//! For a segmented WAL, step 6 archives (optionally) and deletes every
+//! segment completed before the snapshot; see `segments`. A fenced
+//! checkpoint instead keeps the sequence running and deletes only the
+//! segments at or below the snapshot's WAL fence.
+//!
 //! # Crash Safety
 //!
 //! Per CHECKPOINT.md §7:
@@ -41,6 +46,12 @@
 //! Checkpoint does NOT rebuild indexes.
 //! Checkpoint orchestrates only.
 //!
+//! # Scheduling
+//!
+//! `CheckpointScheduler` runs fenced checkpoints from the serving loop when
+//! the WAL grows past a size, record count or age, keeping WAL a Replica
+//! or backup still needs; see `scheduler`.
+//!
 //! # Phase 3 Optimizations
 //!
 //! - Pipelining: Overlap Phase A (prep) work with normal operation (optional, disabled by default)
@@ -49,9 +60,16 @@ mod coordinator;
 mod errors;
 mod marker;
 mod pipeline;
+mod scheduler;
+mod segments;
 
 pub use errors::{CheckpointError, CheckpointErrorCode, CheckpointResult, Severity};
-pub use marker::{marker_path, CheckpointMarker};
+pub use marker::{checkpoint_sequence, marker_path, CheckpointMarker};
+pub use scheduler::{
+    checkpoint_status_path, CheckpointPolicy, CheckpointScheduler, CheckpointStatus,
+    CheckpointTrigger, WalHold, WalRetention,
+};
+pub use segments::{remove_covered_segments, truncate_wal_before};
 pub use pipeline::{
     CheckpointPath, CheckpointPipeline, CheckpointPipelineError, PhaseA, PhaseAResult, PhaseB,
     PhaseBResult, PipelineConfig, PipelineState, PipelineStats,
@@ -137,6 +155,27 @@ impl CheckpointManager {
         coordinator::create_checkpoint_impl(data_dir, storage_path, schema_dir, wal, lock)
     }
 
+    /// Create a checkpoint that truncates the WAL up to the snapshot fence.
+    ///
+    /// Like [`create_checkpoint`](Self::create_checkpoint), but rather than
+    /// resetting the WAL it removes only completed segments fully covered
+    /// by the snapshot, keeping the active segment and sequence numbers.
+    /// Requires a segmented WAL to reclaim space; a legacy WAL is kept.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`create_checkpoint`](Self::create_checkpoint). Truncation
+    /// runs only after the snapshot manifest is durable.
+    pub fn create_fenced_checkpoint(
+        data_dir: &Path,
+        storage_path: &Path,
+        schema_dir: &Path,
+        wal: &mut WalWriter,
+        lock: &GlobalExecutionLock,
+    ) -> Result<CheckpointId, CheckpointError> {
+        coordinator::create_fenced_checkpoint_impl(data_dir, storage_path, schema_dir, wal, lock)
+    }
+
     /// Create an MVCC-aware checkpoint with commit boundary.
     ///
     /// Per MVCC_SNAPSHOT_INTEGRATION.md §5:
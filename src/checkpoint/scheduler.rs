@@ -0,0 +1,686 @@
+//! Automatic checkpoint scheduling
+//!
+//! The scheduler decides when the serving loop runs a fenced checkpoint:
+//!
+//! - the WAL has grown past `max_wal_bytes` since the last checkpoint
+//! - `max_records` records were written since the last checkpoint
+//! - `interval` has passed since the last checkpoint
+//!
+//! No trigger fires unless records were written since the last checkpoint.
+//!
+//! A checkpoint never drops WAL that someone still needs. Segments are
+//! removed only up to the lowest of:
+//!
+//! - the snapshot fence (the checkpoint LSN)
+//! - the sequence every known Replica has acknowledged
+//! - the sequence before the oldest [`WalHold`] (e.g. a backup in progress)
+//!
+//! WAL kept back this way does not count toward the size trigger, so a
+//! lagging Replica does not cause a checkpoint on every write.
+//!
+//! Progress is kept in `data_dir/system/checkpoint_status.json` so that
+//! inspection from another process (`aerodb control diag wal`) can report
+//! it; the file is advisory and not fsynced. The durable checkpoint LSN is
+//! the one in the checkpoint marker.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::coordinator::create_retaining_checkpoint_impl;
+use super::errors::{CheckpointError, CheckpointResult};
+use super::marker::checkpoint_sequence;
+use super::CheckpointId;
+use crate::replication::{ReplicaProgress, ReplicaTracker};
+use crate::snapshot::GlobalExecutionLock;
+use crate::wal::WalWriter;
+
+/// Path of the stored checkpoint progress
+pub fn checkpoint_status_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("system").join("checkpoint_status.json")
+}
+
+/// When the scheduler runs a checkpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointPolicy {
+    /// Checkpoint once the WAL written since the last one reaches this size
+    pub max_wal_bytes: u64,
+    /// Checkpoint once this many records were written (None: no limit)
+    pub max_records: Option<u64>,
+    /// Checkpoint once this much time has passed (None: no limit)
+    pub interval: Option<Duration>,
+}
+
+impl CheckpointPolicy {
+    /// Checkpoint on WAL size only
+    pub fn new(max_wal_bytes: u64) -> Self {
+        Self {
+            max_wal_bytes,
+            max_records: None,
+            interval: None,
+        }
+    }
+
+    /// Also checkpoint every `max_records` records
+    pub fn with_max_records(mut self, max_records: u64) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
+    /// Also checkpoint every `interval`
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+}
+
+/// Why a checkpoint ran
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointTrigger {
+    /// The WAL reached `max_wal_bytes`
+    WalSize,
+    /// `max_records` records were written
+    Records,
+    /// `interval` passed
+    Interval,
+}
+
+impl CheckpointTrigger {
+    /// Returns the trigger name as reported by inspection
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckpointTrigger::WalSize => "wal_size",
+            CheckpointTrigger::Records => "records",
+            CheckpointTrigger::Interval => "interval",
+        }
+    }
+}
+
+/// Progress of the running checkpoint and outcome of the last one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckpointStatus {
+    /// Whether a checkpoint is running
+    pub in_progress: bool,
+    /// Trigger of the running or last checkpoint
+    pub trigger: Option<CheckpointTrigger>,
+    /// When the running or last checkpoint started (RFC3339)
+    pub started_at: Option<String>,
+    /// Checkpoint ID (snapshot ID) of the last completed checkpoint
+    pub checkpoint_id: Option<CheckpointId>,
+    /// Checkpoint LSN of the last completed checkpoint
+    pub wal_sequence: Option<u64>,
+    /// When the last checkpoint completed (RFC3339)
+    pub completed_at: Option<String>,
+    /// Highest sequence the last checkpoint was allowed to remove
+    pub truncated_through: Option<u64>,
+    /// What kept WAL after the checkpoint LSN, if anything
+    pub retained_by: Option<String>,
+    /// Why the last attempt failed, if it did
+    pub last_error: Option<String>,
+}
+
+impl CheckpointStatus {
+    /// Read the stored progress (default if none was stored).
+    pub fn load(data_dir: &Path) -> CheckpointResult<Self> {
+        let path = checkpoint_status_path(data_dir);
+        match fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents).map_err(|e| {
+                CheckpointError::failed(format!(
+                    "Invalid checkpoint status in {}: {}",
+                    path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(CheckpointError::failed_with_source(
+                format!("Failed to read {}", path.display()),
+                e,
+            )),
+        }
+    }
+
+    /// Replace the stored progress. Advisory: failures are ignored.
+    fn store(&self, data_dir: &Path) {
+        let path = checkpoint_status_path(data_dir);
+        let Some(dir) = path.parent() else {
+            return;
+        };
+        let temp_path = path.with_extension("tmp");
+        let _ = serde_json::to_vec(self)
+            .map_err(io::Error::other)
+            .and_then(|json| {
+                fs::create_dir_all(dir)?;
+                fs::write(&temp_path, json)
+            })
+            .and_then(|()| fs::rename(&temp_path, &path));
+    }
+}
+
+/// WAL that checkpoints must keep, by holder.
+///
+/// Cloning shares the same holds.
+#[derive(Debug, Clone, Default)]
+pub struct WalRetention {
+    holds: Arc<Mutex<BTreeMap<u64, (String, u64)>>>,
+}
+
+impl WalRetention {
+    /// Creates an empty retention registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep every record from `first_sequence` on until the hold is dropped.
+    pub fn hold(&self, holder: impl Into<String>, first_sequence: u64) -> WalHold {
+        let mut holds = self.lock();
+        let id = holds.keys().next_back().map_or(0, |id| id + 1);
+        holds.insert(id, (holder.into(), first_sequence));
+        WalHold {
+            retention: self.clone(),
+            id,
+        }
+    }
+
+    /// The holder needing the oldest record, and that record's sequence.
+    pub fn oldest(&self) -> Option<(String, u64)> {
+        self.lock()
+            .values()
+            .min_by_key(|(_, first_sequence)| *first_sequence)
+            .cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, (String, u64)>> {
+        self.holds.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A claim on WAL records, released on drop
+#[derive(Debug)]
+pub struct WalHold {
+    retention: WalRetention,
+    id: u64,
+}
+
+impl Drop for WalHold {
+    fn drop(&mut self) {
+        self.retention.lock().remove(&self.id);
+    }
+}
+
+/// Runs fenced checkpoints according to a [`CheckpointPolicy`].
+pub struct CheckpointScheduler {
+    data_dir: PathBuf,
+    storage_path: PathBuf,
+    schema_dir: PathBuf,
+    policy: CheckpointPolicy,
+    retention: WalRetention,
+    last_checkpoint: Instant,
+    last_sequence: u64,
+    retained_bytes: u64,
+    status: CheckpointStatus,
+}
+
+impl CheckpointScheduler {
+    /// Creates a scheduler continuing from the checkpoint in `data_dir`.
+    ///
+    /// The interval is measured from now. Unreadable progress is replaced.
+    pub fn new(
+        data_dir: &Path,
+        storage_path: &Path,
+        schema_dir: &Path,
+        policy: CheckpointPolicy,
+    ) -> CheckpointResult<Self> {
+        let mut status = CheckpointStatus::load(data_dir).unwrap_or_default();
+        // A checkpoint running when the process stopped did not finish
+        status.in_progress = false;
+
+        Ok(Self {
+            data_dir: data_dir.to_path_buf(),
+            storage_path: storage_path.to_path_buf(),
+            schema_dir: schema_dir.to_path_buf(),
+            policy,
+            retention: WalRetention::new(),
+            last_checkpoint: Instant::now(),
+            last_sequence: checkpoint_sequence(data_dir)?.unwrap_or(0),
+            retained_bytes: 0,
+            status,
+        })
+    }
+
+    /// Share holds with the components that need WAL kept (e.g. backups)
+    pub fn with_retention(mut self, retention: WalRetention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Returns the WAL holds this scheduler respects
+    pub fn retention(&self) -> &WalRetention {
+        &self.retention
+    }
+
+    /// Returns the progress of the running or last checkpoint
+    pub fn status(&self) -> &CheckpointStatus {
+        &self.status
+    }
+
+    /// Returns the trigger of a checkpoint that is due, if any.
+    pub fn due(&self, wal: &WalWriter) -> CheckpointResult<Option<CheckpointTrigger>> {
+        let records = wal
+            .last_sequence_number()
+            .saturating_sub(self.last_sequence);
+        if records == 0 {
+            return Ok(None);
+        }
+
+        if wal_size_bytes(wal)?.saturating_sub(self.retained_bytes) >= self.policy.max_wal_bytes {
+            return Ok(Some(CheckpointTrigger::WalSize));
+        }
+        if self.policy.max_records.is_some_and(|max| records >= max) {
+            return Ok(Some(CheckpointTrigger::Records));
+        }
+        if self
+            .policy
+            .interval
+            .is_some_and(|interval| self.last_checkpoint.elapsed() >= interval)
+        {
+            return Ok(Some(CheckpointTrigger::Interval));
+        }
+        Ok(None)
+    }
+
+    /// Run a checkpoint if one is due.
+    ///
+    /// The caller must hold the global execution lock (no writes may run
+    /// concurrently).
+    pub fn maybe_checkpoint(
+        &mut self,
+        wal: &mut WalWriter,
+        lock: &GlobalExecutionLock,
+    ) -> CheckpointResult<Option<CheckpointId>> {
+        match self.due(wal)? {
+            Some(trigger) => self.checkpoint(trigger, wal, lock).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Run a fenced checkpoint now, keeping WAL still held.
+    ///
+    /// # Errors
+    ///
+    /// As `CheckpointManager::create_fenced_checkpoint`, or if replica
+    /// progress cannot be read. The failure is recorded in the status, and
+    /// the next attempt waits for a trigger to be reached again.
+    pub fn checkpoint(
+        &mut self,
+        trigger: CheckpointTrigger,
+        wal: &mut WalWriter,
+        lock: &GlobalExecutionLock,
+    ) -> CheckpointResult<CheckpointId> {
+        self.status.in_progress = true;
+        self.status.trigger = Some(trigger);
+        self.status.started_at = Some(now());
+        self.status.store(&self.data_dir);
+
+        let result = self.run(wal, lock);
+
+        self.status.in_progress = false;
+        match &result {
+            Ok(_) => self.status.last_error = None,
+            Err(e) => {
+                self.status.last_error = Some(e.to_string());
+                // Retry once another threshold is reached, not on every call
+                self.last_checkpoint = Instant::now();
+                self.last_sequence = wal.last_sequence_number();
+                self.retained_bytes = wal_size_bytes(wal).unwrap_or(self.retained_bytes);
+            }
+        }
+        self.status.store(&self.data_dir);
+        result
+    }
+
+    fn run(
+        &mut self,
+        wal: &mut WalWriter,
+        lock: &GlobalExecutionLock,
+    ) -> CheckpointResult<CheckpointId> {
+        let (retain_after, holder) = self.retention_limit()?;
+
+        let marker = create_retaining_checkpoint_impl(
+            &self.data_dir,
+            &self.storage_path,
+            &self.schema_dir,
+            wal,
+            retain_after,
+            lock,
+        )?;
+        let wal_sequence = marker.wal_sequence.unwrap_or(0);
+
+        self.last_checkpoint = Instant::now();
+        self.last_sequence = wal_sequence;
+        self.retained_bytes = wal_size_bytes(wal)?;
+        self.status.checkpoint_id = Some(marker.snapshot_id.clone());
+        self.status.wal_sequence = Some(wal_sequence);
+        self.status.completed_at = Some(now());
+        self.status.truncated_through = Some(wal_sequence.min(retain_after));
+        self.status.retained_by = holder.filter(|_| retain_after < wal_sequence);
+
+        Ok(marker.snapshot_id)
+    }
+
+    /// Highest sequence every Replica and WAL holder can do without, and
+    /// who needs the next one.
+    fn retention_limit(&self) -> CheckpointResult<(u64, Option<String>)> {
+        let mut limit = (u64::MAX, None);
+
+        let replicas = ReplicaTracker::load(&self.data_dir)
+            .map_err(|e| CheckpointError::failed(format!("Replica progress: {}", e)))?;
+        for replica in replicas.into_iter().filter(ReplicaProgress::retains_wal) {
+            if replica.acked_sequence < limit.0 {
+                limit = (
+                    replica.acked_sequence,
+                    Some(format!("replica {}", replica.replica_id)),
+                );
+            }
+        }
+
+        if let Some((holder, first_sequence)) = self.retention.oldest() {
+            let retain_after = first_sequence.saturating_sub(1);
+            if retain_after < limit.0 {
+                limit = (retain_after, Some(holder));
+            }
+        }
+
+        Ok(limit)
+    }
+}
+
+/// Size of the WAL files on disk.
+fn wal_size_bytes(wal: &WalWriter) -> CheckpointResult<u64> {
+    let files: Vec<PathBuf> = match wal.segment_config() {
+        Some(_) => wal.list_segments()?.into_iter().map(|s| s.path).collect(),
+        None => vec![wal.path().to_path_buf()],
+    };
+    let mut total = 0;
+    for file in files {
+        total += match fs::metadata(&file) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => {
+                return Err(CheckpointError::failed_with_source(
+                    format!("Failed to read {}", file.display()),
+                    e,
+                ))
+            }
+        };
+    }
+    Ok(total)
+}
+
+fn now() -> String {
+    Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recovery::{
+        IndexRebuild, RecoveryManager, RecoveryResult, RecoveryStorage, SchemaCheck,
+    };
+    use crate::replication::{replica_progress_path, ReplicaProgress};
+    use crate::storage::{StoragePayload, StorageWriter};
+    use crate::wal::WalSyncConfig;
+    use crate::wal::{list_segments, RecordType, WalPayload, WalReader, WalSegmentConfig};
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    struct NoIndex;
+
+    impl IndexRebuild for NoIndex {
+        fn rebuild_from_storage(&mut self) -> RecoveryResult<()> {
+            Ok(())
+        }
+    }
+
+    struct AnySchema;
+
+    impl SchemaCheck for AnySchema {
+        fn schema_exists(&self, _schema_id: &str) -> bool {
+            true
+        }
+
+        fn schema_version_exists(&self, _schema_id: &str, _version: &str) -> bool {
+            true
+        }
+    }
+
+    fn open(data_dir: &Path) -> (WalWriter, StorageWriter) {
+        fs::create_dir_all(schema_dir(data_dir)).unwrap();
+        let wal = WalWriter::open_segmented(
+            data_dir,
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(512),
+        )
+        .unwrap();
+        (wal, StorageWriter::open(data_dir).unwrap())
+    }
+
+    fn schema_dir(data_dir: &Path) -> PathBuf {
+        data_dir.join("metadata").join("schemas")
+    }
+
+    fn scheduler(
+        data_dir: &Path,
+        storage: &StorageWriter,
+        policy: CheckpointPolicy,
+    ) -> CheckpointScheduler {
+        CheckpointScheduler::new(data_dir, storage.path(), &schema_dir(data_dir), policy).unwrap()
+    }
+
+    /// Write a document the way the API does: WAL first, then storage
+    fn write(wal: &mut WalWriter, storage: &mut StorageWriter, id: &str, version: u32) {
+        let body = format!(r#"{{"id": "{}", "version": {}}}"#, id, version).into_bytes();
+        wal.append(
+            RecordType::Insert,
+            WalPayload::new("c", id, "s", "v1", body.clone()),
+        )
+        .unwrap();
+        storage
+            .write(&StoragePayload::new("c", id, "s", "v1", body))
+            .unwrap();
+    }
+
+    /// Recover `data_dir` and return the latest body of each document.
+    fn recover(data_dir: &Path) -> (BTreeMap<String, Vec<u8>>, u64) {
+        let mut wal = WalReader::open_from_data_dir(data_dir).unwrap();
+        let mut storage = RecoveryStorage::open(data_dir).unwrap();
+        let state = RecoveryManager::new(data_dir)
+            .recover(&mut wal, &mut storage, &mut NoIndex, &AnySchema)
+            .unwrap();
+
+        let (_, mut reader) = storage.into_parts();
+        reader.reset().unwrap();
+        let mut documents = BTreeMap::new();
+        while let Some(record) = reader.read_next().unwrap() {
+            documents.insert(record.document_id, record.document_body);
+        }
+        (documents, state.replay_stats.records_replayed)
+    }
+
+    #[test]
+    fn test_wal_size_threshold_triggers_checkpoint() {
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path();
+        let (mut wal, mut storage) = open(data_dir);
+        let mut checkpoints = scheduler(data_dir, &storage, CheckpointPolicy::new(2048));
+        let lock = GlobalExecutionLock::new();
+
+        // Nothing written yet, and below the threshold after a few writes
+        assert_eq!(checkpoints.due(&wal).unwrap(), None);
+        for i in 0..4 {
+            write(&mut wal, &mut storage, &format!("doc{}", i), 1);
+        }
+        assert_eq!(checkpoints.maybe_checkpoint(&mut wal, &lock).unwrap(), None);
+
+        while wal_size_bytes(&wal).unwrap() < 2048 {
+            write(&mut wal, &mut storage, "doc0", 2);
+        }
+        assert_eq!(
+            checkpoints.due(&wal).unwrap(),
+            Some(CheckpointTrigger::WalSize)
+        );
+        let last_sequence = wal.last_sequence_number();
+        let checkpoint_id = checkpoints
+            .maybe_checkpoint(&mut wal, &lock)
+            .unwrap()
+            .unwrap();
+
+        // Covered segments are gone; only the active one remains
+        assert_eq!(list_segments(&data_dir.join("wal")).unwrap().len(), 1);
+        assert!(wal_size_bytes(&wal).unwrap() < 2048);
+        assert_eq!(checkpoint_sequence(data_dir).unwrap(), Some(last_sequence));
+
+        let status = CheckpointStatus::load(data_dir).unwrap();
+        assert_eq!(&status, checkpoints.status());
+        assert!(!status.in_progress);
+        assert_eq!(status.trigger, Some(CheckpointTrigger::WalSize));
+        assert_eq!(status.checkpoint_id, Some(checkpoint_id));
+        assert_eq!(status.wal_sequence, Some(last_sequence));
+        assert_eq!(status.truncated_through, Some(last_sequence));
+        assert_eq!(status.retained_by, None);
+
+        // Nothing new to cover
+        assert_eq!(checkpoints.due(&wal).unwrap(), None);
+    }
+
+    #[test]
+    fn test_record_and_interval_triggers() {
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path();
+        let (mut wal, mut storage) = open(data_dir);
+
+        let records = scheduler(
+            data_dir,
+            &storage,
+            CheckpointPolicy::new(u64::MAX).with_max_records(3),
+        );
+        let interval = scheduler(
+            data_dir,
+            &storage,
+            CheckpointPolicy::new(u64::MAX).with_interval(Duration::ZERO),
+        );
+
+        assert_eq!(interval.due(&wal).unwrap(), None);
+        write(&mut wal, &mut storage, "a", 1);
+        assert_eq!(records.due(&wal).unwrap(), None);
+        assert_eq!(
+            interval.due(&wal).unwrap(),
+            Some(CheckpointTrigger::Interval)
+        );
+        write(&mut wal, &mut storage, "b", 1);
+        write(&mut wal, &mut storage, "c", 1);
+        assert_eq!(records.due(&wal).unwrap(), Some(CheckpointTrigger::Records));
+    }
+
+    #[test]
+    fn test_checkpoint_keeps_wal_needed_by_replica_and_backup() {
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path();
+        let (mut wal, mut storage) = open(data_dir);
+        let retention = WalRetention::new();
+        let mut checkpoints = scheduler(data_dir, &storage, CheckpointPolicy::new(1))
+            .with_retention(retention.clone());
+        let lock = GlobalExecutionLock::new();
+        for i in 0..20 {
+            write(&mut wal, &mut storage, &format!("doc{}", i), 1);
+        }
+        let segments = list_segments(&data_dir.join("wal")).unwrap().len();
+        assert!(segments > 2);
+
+        // A backup holding everything keeps every segment
+        let hold = retention.hold("backup", 0);
+        checkpoints
+            .checkpoint(CheckpointTrigger::WalSize, &mut wal, &lock)
+            .unwrap();
+        assert_eq!(
+            list_segments(&data_dir.join("wal")).unwrap().len(),
+            segments
+        );
+        assert_eq!(checkpoints.status().truncated_through, Some(0));
+        assert_eq!(checkpoints.status().retained_by.as_deref(), Some("backup"));
+        drop(hold);
+
+        // A Replica that acknowledged 10 keeps the records after it
+        let replica_id = Uuid::new_v4();
+        let progress = vec![ReplicaProgress {
+            replica_id,
+            address: "127.0.0.1:7001".to_string(),
+            connected: false,
+            acked_sequence: 10,
+            acked_segment: 0,
+            acked_offset: 0,
+            pinned_until_ms: None,
+        }];
+        let path = replica_progress_path(data_dir);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, serde_json::to_vec(&progress).unwrap()).unwrap();
+
+        write(&mut wal, &mut storage, "doc20", 1);
+        checkpoints
+            .checkpoint(CheckpointTrigger::WalSize, &mut wal, &lock)
+            .unwrap();
+        assert_eq!(checkpoints.status().truncated_through, Some(10));
+        assert_eq!(
+            checkpoints.status().retained_by,
+            Some(format!("replica {}", replica_id))
+        );
+        let first = WalReader::open_from_data_dir(data_dir)
+            .unwrap()
+            .read_next()
+            .unwrap()
+            .unwrap();
+        assert!(first.sequence_number <= 11);
+    }
+
+    #[test]
+    fn test_recovery_from_checkpoint_matches_full_replay() {
+        let temp = TempDir::new().unwrap();
+        let checkpointed = temp.path().join("checkpointed");
+        let replayed = temp.path().join("replayed");
+        let lock = GlobalExecutionLock::new();
+
+        for data_dir in [&checkpointed, &replayed] {
+            let (mut wal, mut storage) = open(data_dir);
+            let mut checkpoints = scheduler(data_dir, &storage, CheckpointPolicy::new(1024));
+            for round in 0..3 {
+                for i in 0..10 {
+                    write(&mut wal, &mut storage, &format!("doc{}", i), round);
+                }
+                if data_dir == &checkpointed {
+                    checkpoints.maybe_checkpoint(&mut wal, &lock).unwrap();
+                }
+            }
+            // Writes after the last checkpoint are only in the WAL tail
+            write(&mut wal, &mut storage, "doc0", 99);
+            write(&mut wal, &mut storage, "late", 1);
+        }
+
+        let (from_checkpoint, applied) = recover(&checkpointed);
+        let (full_replay, replayed_records) = recover(&replayed);
+        assert_eq!(from_checkpoint, full_replay);
+        assert_eq!(from_checkpoint.len(), 11);
+        assert_eq!(replayed_records, 32);
+
+        // Only the records after the checkpoint LSN were applied
+        let checkpoint_lsn = checkpoint_sequence(&checkpointed).unwrap().unwrap();
+        assert!(checkpoint_lsn >= 10);
+        assert_eq!(applied, 32 - checkpoint_lsn);
+    }
+}
@@ -0,0 +1,417 @@
+//! Removal of WAL segments covered by a checkpoint snapshot
+//!
+//! With a segmented WAL, "truncate WAL to zero" (CHECKPOINT.md §6) becomes:
+//!
+//! 1. Roll the active segment, so every record written before the snapshot
+//!    lives in a completed segment
+//! 2. For each completed segment, newest first: copy it to the archive
+//!    directory (if configured, with fsync) and then delete it
+//! 3. fsync the WAL directory
+//! 4. Reset sequence numbers (`WalWriter::truncate`)
+//!
+//! Deleting newest first means a crash part-way leaves a prefix of the
+//! WAL that still starts at sequence 1 and is contiguous, so replay stays
+//! valid. The snapshot already covers those records, and replay is
+//! idempotent.
+//!
+//! A fenced checkpoint instead keeps the WAL sequence running and removes
+//! only the completed segments whose records are all at or below the
+//! snapshot fence (`truncate_wal_before`):
+//!
+//! 1. Record the first retained sequence as the WAL base sequence (fsync)
+//! 2. For each covered segment, oldest first: archive it and delete it
+//! 3. fsync the WAL directory
+//!
+//! Deleting oldest first means a crash part-way leaves a contiguous suffix
+//! that starts at or below the recorded base, which replay accepts.
+
+use std::fs::{self, File};
+use std::path::Path;
+
+use super::errors::{CheckpointError, CheckpointResult};
+use crate::wal::{list_segments, write_base_sequence, WalReader, WalSegment, WalWriter};
+
+/// Archives and removes all segments before the active one.
+///
+/// Must only be called after the snapshot and checkpoint marker are
+/// durable, while holding the global execution lock. Does nothing for a
+/// legacy single-file WAL.
+///
+/// # Returns
+///
+/// Indices of the removed segments, in ascending order.
+pub fn remove_covered_segments(wal: &mut WalWriter) -> CheckpointResult<Vec<u64>> {
+    let Some(segment_config) = wal.segment_config().cloned() else {
+        return Ok(Vec::new());
+    };
+
+    wal.roll_segment()?;
+    let active_index = wal.active_segment_index().unwrap_or(0);
+    let wal_dir = wal.wal_dir().to_path_buf();
+
+    let covered: Vec<WalSegment> = list_segments(&wal_dir)?
+        .into_iter()
+        .filter(|segment| segment.index < active_index)
+        .collect();
+
+    for segment in covered.iter().rev() {
+        if let Some(archive_dir) = &segment_config.archive_dir {
+            archive_segment(segment, archive_dir)?;
+        }
+        fs::remove_file(&segment.path).map_err(|e| {
+            CheckpointError::wal_truncate_failed_with_source(
+                format!("Failed to remove WAL segment {}", segment.path.display()),
+                e,
+            )
+        })?;
+    }
+
+    if !covered.is_empty() {
+        fsync_dir(&wal_dir)?;
+    }
+
+    Ok(covered.iter().map(|segment| segment.index).collect())
+}
+
+/// Archives and removes completed segments covered by a snapshot fence.
+///
+/// A segment is covered when every record in it has a sequence number at
+/// or below `wal_sequence` (the fence's last durable WAL sequence). The
+/// active segment is never removed, and sequence numbers are not reset.
+///
+/// Must only be called after the snapshot recording `wal_sequence` is
+/// durable. Does nothing for a legacy single-file WAL.
+///
+/// # Returns
+///
+/// Indices of the removed segments, in ascending order.
+///
+/// # Errors
+///
+/// Returns `AERO_CHECKPOINT_WAL_TRUNCATE_FAILED` if `wal_sequence` is
+/// ahead of the WAL or a segment cannot be read, archived or removed.
+pub fn truncate_wal_before(wal: &mut WalWriter, wal_sequence: u64) -> CheckpointResult<Vec<u64>> {
+    let Some(segment_config) = wal.segment_config().cloned() else {
+        return Ok(Vec::new());
+    };
+    if wal_sequence >= wal.next_sequence_number() {
+        return Err(CheckpointError::wal_truncate_failed(format!(
+            "Snapshot fence at WAL sequence {} is ahead of the WAL (last sequence {})",
+            wal_sequence,
+            wal.last_sequence_number()
+        )));
+    }
+
+    let segments = wal.list_segments()?;
+    let active_index = wal.active_segment_index().unwrap_or(0);
+
+    // The sequence following each segment's records is the first sequence
+    // of the next non-empty segment, or the writer's next sequence
+    let mut end = wal.next_sequence_number();
+    let mut ends = vec![0; segments.len()];
+    for (pos, segment) in segments.iter().enumerate().rev() {
+        ends[pos] = end;
+        if let Some(first) = first_sequence(segment)? {
+            end = first;
+        }
+    }
+
+    let covered: Vec<(&WalSegment, u64)> = segments
+        .iter()
+        .zip(ends)
+        .take_while(|(segment, end)| segment.index < active_index && *end <= wal_sequence + 1)
+        .collect();
+    let Some(&(_, base)) = covered.last() else {
+        return Ok(Vec::new());
+    };
+
+    let wal_dir = wal.wal_dir().to_path_buf();
+    write_base_sequence(&wal_dir, base)?;
+
+    for (segment, _) in &covered {
+        if let Some(archive_dir) = &segment_config.archive_dir {
+            archive_segment(segment, archive_dir)?;
+        }
+        fs::remove_file(&segment.path).map_err(|e| {
+            CheckpointError::wal_truncate_failed_with_source(
+                format!("Failed to remove WAL segment {}", segment.path.display()),
+                e,
+            )
+        })?;
+    }
+    fsync_dir(&wal_dir)?;
+
+    Ok(covered.iter().map(|(segment, _)| segment.index).collect())
+}
+
+/// Sequence number of the first record in a segment, if it has any.
+fn first_sequence(segment: &WalSegment) -> CheckpointResult<Option<u64>> {
+    let mut reader = WalReader::open_history(vec![segment.path.clone()])?;
+    Ok(reader.read_next()?.map(|record| record.sequence_number))
+}
+
+/// Copies a segment into the archive directory durably.
+///
+/// The copy is written under a temporary name and renamed into place, so
+/// the archive never holds a partial segment under a segment name.
+fn archive_segment(segment: &WalSegment, archive_dir: &Path) -> CheckpointResult<()> {
+    fs::create_dir_all(archive_dir).map_err(|e| {
+        CheckpointError::wal_truncate_failed_with_source(
+            format!(
+                "Failed to create WAL archive directory {}",
+                archive_dir.display()
+            ),
+            e,
+        )
+    })?;
+
+    let file_name = segment
+        .path
+        .file_name()
+        .ok_or_else(|| CheckpointError::wal_truncate_failed("WAL segment has no file name"))?;
+    let final_path = archive_dir.join(file_name);
+    let temp_path = archive_dir.join(format!("{}.tmp", file_name.to_string_lossy()));
+
+    fs::copy(&segment.path, &temp_path).map_err(|e| {
+        CheckpointError::wal_truncate_failed_with_source(
+            format!(
+                "Failed to archive WAL segment {} to {}",
+                segment.path.display(),
+                temp_path.display()
+            ),
+            e,
+        )
+    })?;
+    File::open(&temp_path)
+        .and_then(|f| f.sync_all())
+        .map_err(|e| {
+            CheckpointError::wal_truncate_failed_with_source(
+                format!("Failed to fsync archived segment {}", temp_path.display()),
+                e,
+            )
+        })?;
+    fs::rename(&temp_path, &final_path).map_err(|e| {
+        CheckpointError::wal_truncate_failed_with_source(
+            format!(
+                "Failed to rename archived segment to {}",
+                final_path.display()
+            ),
+            e,
+        )
+    })?;
+
+    fsync_dir(archive_dir)
+}
+
+fn fsync_dir(dir: &Path) -> CheckpointResult<()> {
+    File::open(dir).and_then(|d| d.sync_all()).map_err(|e| {
+        CheckpointError::wal_truncate_failed_with_source(
+            format!("Failed to fsync directory {}", dir.display()),
+            e,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wal::{RecordType, WalPayload, WalReader, WalSegmentConfig, WalSyncConfig};
+    use tempfile::TempDir;
+
+    fn payload(doc_id: &str) -> WalPayload {
+        WalPayload::new(
+            "test_collection",
+            doc_id,
+            "test_schema",
+            "v1",
+            format!(r#"{{"id": "{}"}}"#, doc_id).into_bytes(),
+        )
+    }
+
+    fn segment_indices(dir: &Path) -> Vec<u64> {
+        list_segments(dir)
+            .unwrap()
+            .iter()
+            .map(|s| s.index)
+            .collect()
+    }
+
+    #[test]
+    fn test_legacy_wal_is_untouched() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open(temp.path()).unwrap();
+        wal.append(RecordType::Insert, payload("doc1")).unwrap();
+
+        assert!(remove_covered_segments(&mut wal).unwrap().is_empty());
+        assert_eq!(wal.next_sequence_number(), 2);
+    }
+
+    #[test]
+    fn test_removes_covered_segments_and_archives_them() {
+        let temp = TempDir::new().unwrap();
+        let archive = temp.path().join("archive");
+        let config = WalSegmentConfig::new(1).with_archive_dir(&archive);
+        let mut wal =
+            WalWriter::open_segmented(temp.path(), WalSyncConfig::default(), config).unwrap();
+
+        for i in 0..3 {
+            wal.append(RecordType::Insert, payload(&format!("doc{}", i)))
+                .unwrap();
+        }
+        let wal_dir = wal.wal_dir().to_path_buf();
+        assert_eq!(segment_indices(&wal_dir), vec![1, 2, 3]);
+
+        let removed = remove_covered_segments(&mut wal).unwrap();
+        assert_eq!(removed, vec![1, 2, 3]);
+        assert_eq!(segment_indices(&wal_dir), vec![4]);
+        assert_eq!(segment_indices(&archive), vec![1, 2, 3]);
+
+        // Archived segments are complete and replay in order
+        let mut reader = WalReader::open_segments(&archive).unwrap();
+        let records = reader.read_all().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].sequence_number, 3);
+
+        wal.truncate().unwrap();
+        assert_eq!(wal.next_sequence_number(), 1);
+    }
+
+    #[test]
+    fn test_truncate_refuses_while_covered_segments_remain() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open_segmented(
+            temp.path(),
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(1),
+        )
+        .unwrap();
+        wal.append(RecordType::Insert, payload("doc1")).unwrap();
+
+        assert!(wal.truncate().is_err());
+        assert_eq!(wal.next_sequence_number(), 2);
+    }
+
+    fn open_fenced(dir: &Path) -> WalWriter {
+        WalWriter::open_segmented(dir, WalSyncConfig::default(), WalSegmentConfig::new(1)).unwrap()
+    }
+
+    fn replayed_sequences(wal_dir: &Path) -> Vec<u64> {
+        WalReader::open_segments(wal_dir)
+            .unwrap()
+            .read_all()
+            .unwrap()
+            .iter()
+            .map(|r| r.sequence_number)
+            .collect()
+    }
+
+    #[test]
+    fn test_truncate_before_removes_segments_at_or_below_fence() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = open_fenced(temp.path());
+        for i in 0..5 {
+            wal.append(RecordType::Insert, payload(&format!("doc{}", i)))
+                .unwrap();
+        }
+        let wal_dir = wal.wal_dir().to_path_buf();
+        // One record per segment; segment 5 is active
+        assert_eq!(segment_indices(&wal_dir), vec![1, 2, 3, 4, 5]);
+
+        assert_eq!(truncate_wal_before(&mut wal, 3).unwrap(), vec![1, 2, 3]);
+        assert_eq!(segment_indices(&wal_dir), vec![4, 5]);
+        assert_eq!(wal.next_sequence_number(), 6);
+        assert_eq!(replayed_sequences(&wal_dir), vec![4, 5]);
+
+        // The active segment survives even when the fence covers it
+        assert_eq!(truncate_wal_before(&mut wal, 5).unwrap(), vec![4]);
+        assert_eq!(segment_indices(&wal_dir), vec![5]);
+        assert!(truncate_wal_before(&mut wal, 5).unwrap().is_empty());
+
+        wal.append(RecordType::Insert, payload("doc5")).unwrap();
+        drop(wal);
+        let wal = open_fenced(temp.path());
+        assert_eq!(wal.next_sequence_number(), 7);
+        assert_eq!(replayed_sequences(&wal_dir), vec![5, 6]);
+    }
+
+    #[test]
+    fn test_truncate_before_keeps_partially_covered_segment() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open_segmented(
+            temp.path(),
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(u64::MAX),
+        )
+        .unwrap();
+        wal.append(RecordType::Insert, payload("doc0")).unwrap();
+        wal.append(RecordType::Insert, payload("doc1")).unwrap();
+        wal.roll_segment().unwrap();
+        wal.append(RecordType::Insert, payload("doc2")).unwrap();
+
+        // Segment 1 holds sequences 1-2, so a fence at 1 keeps it
+        assert!(truncate_wal_before(&mut wal, 1).unwrap().is_empty());
+        assert_eq!(truncate_wal_before(&mut wal, 2).unwrap(), vec![1]);
+        assert!(truncate_wal_before(&mut wal, 4).is_err());
+    }
+
+    #[test]
+    fn test_truncate_before_crash_mid_removal_still_replays() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = open_fenced(temp.path());
+        for i in 0..4 {
+            wal.append(RecordType::Insert, payload(&format!("doc{}", i)))
+                .unwrap();
+        }
+        let wal_dir = wal.wal_dir().to_path_buf();
+        drop(wal);
+
+        // Base recorded for a fence at 3, but only segment 1 was deleted
+        write_base_sequence(&wal_dir, 4).unwrap();
+        fs::remove_file(wal_dir.join("0000001.log")).unwrap();
+        assert_eq!(replayed_sequences(&wal_dir), vec![2, 3, 4]);
+
+        let wal = open_fenced(temp.path());
+        assert_eq!(wal.next_sequence_number(), 5);
+        drop(wal);
+
+        // Records above the base must never be missing
+        fs::remove_file(wal_dir.join("0000002.log")).unwrap();
+        write_base_sequence(&wal_dir, 2).unwrap();
+        assert!(WalReader::open_segments(&wal_dir)
+            .unwrap()
+            .read_all()
+            .is_err());
+    }
+
+    #[test]
+    fn test_partial_removal_leaves_replayable_prefix() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open_segmented(
+            temp.path(),
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(1),
+        )
+        .unwrap();
+        for i in 0..3 {
+            wal.append(RecordType::Insert, payload(&format!("doc{}", i)))
+                .unwrap();
+        }
+        wal.roll_segment().unwrap();
+        let wal_dir = wal.wal_dir().to_path_buf();
+        drop(wal);
+
+        // Simulate a crash after the newest covered segment was deleted
+        fs::remove_file(wal_dir.join("0000003.log")).unwrap();
+
+        let mut reader = WalReader::open_segments(&wal_dir).unwrap();
+        assert_eq!(reader.read_all().unwrap().len(), 2);
+
+        let wal = WalWriter::open_segmented(
+            temp.path(),
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(1),
+        )
+        .unwrap();
+        assert_eq!(wal.next_sequence_number(), 3);
+    }
+}
@@ -1,26 +1,42 @@
 //! CLI argument definitions using clap
 //!
 //! Commands:
-//! - aerodb init --config <path>
+//! - aerodb init --config <path> [--force --confirm-destroy <data_dir>]
 //! - aerodb start --config <path>
 //! - aerodb query --config <path>
 //! - aerodb explain --config <path>
+//! - aerodb restore --config <path> --backup-id <id> [--to-time <time>] [--target-dir <dir>]
+//! - aerodb backup --config <path> <create|list|verify|delete>
+//! - aerodb wal --config <path> inspect [--segment <n>] [--from-offset <n>] [--limit <n>]
+//! - aerodb export --config <path> --collection <name> --schema-version <v> --format <jsonl|csv> --out <file>
+//! - aerodb import --config <path> --collection <name> --schema-version <v> --file <file> [--mode <insert|upsert>]
+//! - aerodb config-validate --config <path>
+//! - aerodb upgrade --config <path>
+//!
+//! Every command accepts `--output <json|pretty|table>` (default `json`).
 //!
 //! # Phase 7 Control Plane Commands
 //!
 //! Per PHASE7_COMMAND_MODEL.md:
 //! - aerodb control inspect <cluster|node|replication|promotion>
-//! - aerodb control diag <diagnostics|wal|snapshots>
+//! - aerodb control diag <diagnostics|wal|snapshots|crash-reports|audit>
 //! - aerodb control <promote|demote|force-promote>
 
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
+use super::io::OutputFormat;
+use crate::transfer::{ImportMode, TransferFormat, DEFAULT_BATCH_SIZE};
+
 /// AeroDB - A strict, deterministic, self-hostable database
 #[derive(Parser, Debug)]
 #[command(name = "aerodb")]
 #[command(version, about, long_about = None)]
 pub struct Cli {
+    /// Output format for command responses
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Json)]
+    pub output: OutputFormat,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -28,10 +44,21 @@ pub struct Cli {
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Initialize a new AeroDB data directory
+    ///
+    /// With --force, an existing (possibly partially initialized) data
+    /// directory is deleted and recreated. DANGER: destroys all data.
     Init {
         /// Path to configuration file
         #[arg(long, default_value = "./aerodb.json")]
         config: PathBuf,
+
+        /// Delete and reinitialize an existing data directory
+        #[arg(long)]
+        force: bool,
+
+        /// Confirmation for --force: the configured data_dir, exactly
+        #[arg(long, requires = "force")]
+        confirm_destroy: Option<String>,
     },
 
     /// Start the AeroDB server
@@ -139,6 +166,190 @@ pub enum Command {
         #[arg(long, short = 'f')]
         follow: bool,
     },
+
+    /// Backup management commands
+    ///
+    /// Create, list, verify and delete backup archives in the configured
+    /// backup directory.
+    Backup {
+        /// Path to configuration file
+        #[arg(long, default_value = "./aerodb.json")]
+        config: PathBuf,
+
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+
+    /// WAL diagnostics
+    ///
+    /// Read-only; safe to run while AeroDB is stopped after a failed
+    /// recovery.
+    Wal {
+        /// Path to configuration file
+        #[arg(long, default_value = "./aerodb.json")]
+        config: PathBuf,
+
+        #[command(subcommand)]
+        action: WalAction,
+    },
+
+    /// Developer tooling
+    Dx {
+        /// Path to configuration file
+        #[arg(long, default_value = "./aerodb.json")]
+        config: PathBuf,
+
+        #[command(subcommand)]
+        action: DxAction,
+    },
+
+    /// Diagnose the environment and data directory
+    ///
+    /// Runs a battery of checks and prints pass/warn/fail with a hint for
+    /// each. Exits 0 if all pass, 1 on warnings, 2 on failures.
+    Doctor {
+        /// Path to configuration file
+        #[arg(long, default_value = "./aerodb.json")]
+        config: PathBuf,
+
+        /// Print the report as JSON (for CI)
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Export a collection's documents to a file
+    ///
+    /// Documents are streamed in primary key order; the collection is
+    /// never held in memory. AeroDB must be stopped.
+    Export {
+        /// Path to configuration file
+        #[arg(long, default_value = "./aerodb.json")]
+        config: PathBuf,
+
+        /// Collection (schema id) to export
+        #[arg(long)]
+        collection: String,
+
+        /// Schema version whose fields are the CSV columns
+        #[arg(long)]
+        schema_version: String,
+
+        /// File format (jsonl or csv)
+        #[arg(long)]
+        format: TransferFormat,
+
+        /// File to write
+        #[arg(long)]
+        out: PathBuf,
+
+        /// Export only documents matching this query filter (JSON)
+        #[arg(long)]
+        filter: Option<String>,
+    },
+
+    /// Import documents into a collection from a file
+    ///
+    /// Rows are validated against the schema and committed in
+    /// transactions of --batch-size rows. Rejected rows are written to the
+    /// rejects file and the import goes on, unless --abort-on-error is
+    /// set. AeroDB must be stopped.
+    Import {
+        /// Path to configuration file
+        #[arg(long, default_value = "./aerodb.json")]
+        config: PathBuf,
+
+        /// Collection (schema id) to import into
+        #[arg(long)]
+        collection: String,
+
+        /// Schema version rows are validated against
+        #[arg(long)]
+        schema_version: String,
+
+        /// File to read
+        #[arg(long)]
+        file: PathBuf,
+
+        /// File format (jsonl or csv; default from the file extension)
+        #[arg(long)]
+        format: Option<TransferFormat>,
+
+        /// insert rejects rows whose _id exists; upsert replaces them
+        #[arg(long, default_value = "insert")]
+        mode: ImportMode,
+
+        /// Rows committed per transaction
+        #[arg(long, default_value_t = DEFAULT_BATCH_SIZE)]
+        batch_size: usize,
+
+        /// Rejected rows file (default: <file>.rejects.jsonl)
+        #[arg(long)]
+        rejects: Option<PathBuf>,
+
+        /// Stop at the first rejected row
+        #[arg(long)]
+        abort_on_error: bool,
+
+        /// Read CSV column HEADER into FIELD (HEADER=FIELD, repeatable)
+        #[arg(long = "map", value_name = "HEADER=FIELD")]
+        column_map: Vec<String>,
+    },
+
+    /// Validate a configuration file without starting AeroDB
+    ///
+    /// Reports every error and warning; exits non-zero if there are errors.
+    ConfigValidate {
+        /// Path to configuration file
+        #[arg(long, default_value = "./aerodb.json")]
+        config: PathBuf,
+    },
+
+    /// Restore the data directory from a backup
+    ///
+    /// AeroDB must be stopped. With --to-time or --to-offset, WAL from the
+    /// backup and the WAL archive is replayed up to that point. Restoring
+    /// over existing data first issues a confirmation token; run again with
+    /// --confirm <token> to proceed.
+    Restore {
+        /// Path to configuration file
+        #[arg(long, default_value = "./aerodb.json")]
+        config: PathBuf,
+
+        /// Backup to restore (archive name without .tar)
+        #[arg(long)]
+        backup_id: String,
+
+        /// Restore as of this time (RFC 3339, e.g. 2026-02-07T13:45:00Z)
+        #[arg(long)]
+        to_time: Option<String>,
+
+        /// Restore up to, not including, the WAL record at this byte offset
+        /// (as listed by `wal inspect`)
+        #[arg(long, conflicts_with = "to_time")]
+        to_offset: Option<u64>,
+
+        /// Segment of --to-offset (omit for a single wal.log)
+        #[arg(long, requires = "to_offset")]
+        to_segment: Option<u64>,
+
+        /// Restore into this directory instead of the configured data_dir
+        #[arg(long)]
+        target_dir: Option<PathBuf>,
+
+        /// Confirmation token issued by a previous run, required to restore
+        /// over a directory that holds data
+        #[arg(long)]
+        confirm: Option<String>,
+    },
+
+    /// Upgrade the data directory to this binary's WAL format
+    ///
+    /// AeroDB must be stopped. Does nothing if the data is already current.
+    Upgrade {
+        /// Path to configuration file
+        #[arg(long, default_value = "./aerodb.json")]
+        config: PathBuf,
+    },
 }
 
 /// Control plane actions.
@@ -208,7 +419,8 @@ pub enum ControlAction {
         #[arg(long)]
         reason: String,
 
-        /// Acknowledged risks (required, comma-separated)
+        /// Acknowledged risks (required, comma-separated). Include
+        /// `accept_data_loss` to promote a replica that is missing WAL
         #[arg(long)]
         acknowledge_risks: String,
 
@@ -236,6 +448,19 @@ pub enum InspectTarget {
 
     /// Inspect promotion state machine
     Promotion,
+
+    /// Inspect planner statistics per collection
+    Stats,
+
+    /// Inspect a tenant's quotas and metered usage
+    Tenant {
+        /// Tenant UUID to inspect
+        #[arg(long)]
+        tenant_id: String,
+    },
+
+    /// Inspect scheduled jobs: last and next run, last outcome
+    Jobs,
 }
 
 /// Diagnostic targets.
@@ -253,6 +478,20 @@ pub enum DiagTarget {
 
     /// Inspect snapshots and checkpoints
     Snapshots,
+
+    /// List crash reports written on panic
+    CrashReports {
+        /// Dump the report with this ID
+        #[arg(long)]
+        id: Option<String>,
+    },
+
+    /// Inspect the control-plane audit log
+    Audit {
+        /// Walk the hash chain and report the first broken link
+        #[arg(long)]
+        verify: bool,
+    },
 }
 
 /// Migration actions (Phase 14).
@@ -300,8 +539,88 @@ pub enum SchemaAction {
     /// Generate TypeScript types from schemas
     Types {
         /// Output directory for generated types
-        #[arg(long, default_value = "./types")]
-        output: PathBuf,
+        #[arg(long, alias = "output", default_value = "./types")]
+        out_dir: PathBuf,
+
+        #[command(flatten)]
+        format: FormatArg,
+    },
+}
+
+/// Backup actions.
+#[derive(Subcommand, Debug)]
+pub enum BackupAction {
+    /// Create a backup of the data directory
+    ///
+    /// AeroDB must be stopped: the backup boots the data directory
+    /// (running recovery) to take the global execution lock.
+    Create {
+        /// Note stored in the backup manifest
+        #[arg(long)]
+        description: Option<String>,
+    },
+
+    /// List backups, newest first
+    List,
+
+    /// Check that a backup is restorable without restoring it
+    Verify {
+        /// Backup to verify (archive name without .tar)
+        #[arg(long)]
+        id: String,
+    },
+
+    /// Delete a backup
+    Delete {
+        /// Backup to delete (archive name without .tar)
+        #[arg(long)]
+        id: String,
+    },
+}
+
+/// WAL diagnostic actions.
+#[derive(Subcommand, Debug)]
+pub enum WalAction {
+    /// Print each WAL record's position, type, collection and key as a
+    /// JSON line
+    ///
+    /// Records are validated as they are read; the first corrupt record
+    /// ends the listing with an error.
+    Inspect {
+        /// Start at this segment (default: the first)
+        #[arg(long)]
+        segment: Option<u64>,
+
+        /// Skip records before this byte offset in the starting segment
+        #[arg(long, default_value = "0")]
+        from_offset: u64,
+
+        /// Print at most this many records
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+/// Developer tooling actions.
+#[derive(Subcommand, Debug)]
+pub enum DxAction {
+    /// Populate collections with generated data from a seed spec
+    ///
+    /// Writes go through the normal insert path. The same spec and seed
+    /// always produce the same documents. AeroDB must be stopped.
+    Seed {
+        /// Seed spec file (YAML, or JSON with a .json extension)
+        #[arg(long)]
+        spec: PathBuf,
+
+        /// RNG seed
+        #[arg(long, default_value = "0")]
+        seed: u64,
+
+        /// Purge these collections first (all seeded ones if none are
+        /// named); collections the spec does not seed are refused
+        #[arg(long, num_args = 0..)]
+        truncate: Option<Vec<String>>,
     },
 }
 
@@ -311,15 +630,21 @@ pub enum DeployAction {
     /// Generate Docker Compose configuration
     Docker {
         /// Output file path
-        #[arg(long, default_value = "./docker-compose.yml")]
-        output: PathBuf,
+        #[arg(long, alias = "output", default_value = "./docker-compose.yml")]
+        out_file: PathBuf,
+
+        #[command(flatten)]
+        format: FormatArg,
     },
 
     /// Generate Kubernetes manifests
     K8s {
         /// Output directory for manifests
-        #[arg(long, default_value = "./k8s")]
-        output: PathBuf,
+        #[arg(long, alias = "output", default_value = "./k8s")]
+        out_dir: PathBuf,
+
+        #[command(flatten)]
+        format: FormatArg,
     },
 
     /// Show deployment status
@@ -328,11 +653,26 @@ pub enum DeployAction {
     /// Generate environment file template
     Env {
         /// Output file path
-        #[arg(long, default_value = "./.env.example")]
-        output: PathBuf,
+        #[arg(long, alias = "output", default_value = "./.env.example")]
+        out_file: PathBuf,
+
+        #[command(flatten)]
+        format: FormatArg,
     },
 }
 
+/// The output format, as `--format`, for commands whose `--output` names
+/// the file or directory they write
+///
+/// It shares the global `--output` flag's id, so clap does not add that
+/// flag to the command and the value still reaches [`Cli::output`].
+#[derive(clap::Args, Debug)]
+pub struct FormatArg {
+    /// Output format for command responses
+    #[arg(id = "output", long = "format", value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+}
+
 impl Cli {
     /// Parse command line arguments
     pub fn parse_args() -> Self {
@@ -11,161 +11,572 @@
 use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
 use serde_json::{json, Value};
 use uuid::Uuid;
 
-use crate::admission_control::{AdmissionControlConfig, AdmissionController};
+use crate::admission_control::AdmissionController;
 use crate::api::{ApiHandler, Subsystems};
-use crate::auth::security::SecurityConfig;
-use crate::backpressure::{BackpressureConfig, BackpressureManager};
+use crate::auth::crypto::{PasswordHashParams, PasswordHasher};
+use crate::auth::jwt::JwtConfig;
+use crate::backpressure::BackpressureManager;
+use crate::backup::BackupManager;
+use crate::checkpoint::{CheckpointPolicy, CheckpointScheduler, CheckpointStatus};
+use crate::config_reload::RuntimeSettings;
+use crate::config_validator::{
+    format_validation_errors, AeroConfig, ConfigValidator, ValidationReport,
+};
+use crate::control_plane::{Quotas, TenantQuotas, DEFAULT_PERSIST_INTERVAL};
 use crate::dx::api::control_plane::{
-    AuthorityContext, ControlAction, ControlCommand, ControlPlaneCommand, DiagnosticCommand,
-    DiagTarget, InspectTarget, InspectionCommand, ReplicaId,
+    AuditLogInfo, AuthorityContext, CommandOutcome, CommandRequest, CommandResponseData,
+    ConfirmationFlow, ConfirmationResult, ConfirmationToken, ControlCommand, ControlPlaneCommand,
+    ControlPlaneError, ControlPlaneHandler,
+    CrashReportInfo, DefaultKernelAdapter, DiagnosticCommand,
+    InspectionCommand, PlannerStatisticsView, ReplicationStatus, ScheduledJobView,
+    ScheduledJobsView, SnapshotInfo, SnapshotIntegrity, SyncReplicationView, TenantUsageView,
+    WalInfo,
 };
+use crate::dx::seed::{self, SeedSpec};
+use crate::functions::builtin_jobs;
+use crate::functions::scheduler::{DEFAULT_TICK_INTERVAL, JOB_RUNS_PATH, SCHEDULES_PATH};
+use crate::functions::store::{FileJobRunStore, FileJobStore, JobRunStore, JobStore};
+use crate::functions::{FunctionError, JobFunction, Scheduler};
+use crate::http_server::HttpServer;
 use crate::index::IndexManager;
-use crate::observability::{AuditAction, AuditLog, AuditOutcome, AuditRecord, MemoryAuditLog, ObservabilityConfig};
-use crate::query_limits::QueryLimitsConfig;
+use crate::observability::audit::AUDIT_LOG_PATH;
+use crate::observability::slow_query::SlowQueryTracker;
+use crate::observability::{
+    resolve_request_id, verify_chain, AuditAction, AuditLog, AuditOutcome, AuditRecord,
+    FileAuditLog, JsonLogger, MemoryAuditLog, OperationLog,
+};
+use crate::panic_handler::{list_crash_reports, CrashReporter, StoredCrashReport};
+use crate::planner::Statistics;
+use crate::promotion::PromotionState;
 use crate::recovery::RecoveryManager;
-use crate::replication::{ReplicationConfig, ReplicationRole, ReplicationState};
-use crate::resource_limits::{ResourceManager, ResourceLimitsConfig};
+use crate::replication::{
+    replica_lag, AppliedOffset, BaseSnapshotSource, ReplicaBootstrap, ReplicaFreshness,
+    ReplicaGate, ReplicaLag, ReplicaTracker, ReplicationConfig, ReplicationRole,
+    ReplicationState, ReplicationStreamConfig, SyncMode, SyncReplication,
+    SyncReplicationConfig, SyncState, SyncTimeoutPolicy, WalFollower, WalStreamer,
+};
+use crate::restore::{RestoreManager, WalOffset};
+use crate::resource_limits::ResourceManager;
 use crate::schema::SchemaLoader;
+use crate::snapshot::{GlobalExecutionLock, SnapshotManager};
 use crate::storage::{StorageReader, StorageWriter};
-use crate::wal::{WalReader, WalWriter};
+use crate::transfer::{DataTransfer, ExportOptions, ImportOptions, TransferFormat};
+use crate::version::{
+    upgrade_wal_format, ProcessLock, VersionChecker, VersionError, VersionMarker,
+    WAL_FORMAT_VERSION,
+};
+use crate::wal::{
+    detect_layout, GroupCommitBatch, PositionedRecord, WalLayout, WalReader, WalSegmentConfig,
+    WalSyncConfig, WalSyncMode, WalWriter,
+};
 
-use super::args::{Command, ControlAction, DeployAction, DiagTarget, InspectTarget, MigrateAction, SchemaAction};
+use super::args::{
+    BackupAction, Command, ControlAction, DeployAction, DiagTarget, DxAction, InspectTarget,
+    MigrateAction, SchemaAction, WalAction,
+};
+use super::doctor::Severity;
 use super::errors::{CliError, CliResult};
-use super::io::{read_request, read_requests, write_error, write_json, write_response};
+use super::snapshots::LocalSnapshots;
+use super::stats::LocalStats;
+use super::transfer::{Engine, LocalTransfer, RejectsFile};
+use super::follow::{
+    follow as follow_log, line_matches_level, LogFollower, DEFAULT_POLL_INTERVAL,
+};
+use super::io::{
+    read_request, read_requests, write_error, write_json, write_response, OutputFormat,
+};
+use super::reload::{ConfigReloader, DEFAULT_WATCH_INTERVAL};
+use super::shutdown::{self, ShutdownSignal};
+
+/// Config file format, detected from the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    /// Flat JSON layout, deprecated in favour of `aerodb.toml`
+    LegacyJson,
+    Toml,
+}
 
-/// Configuration file structure per CONFIG.md
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    /// Data directory (required)
-    pub data_dir: String,
+impl ConfigFormat {
+    fn from_path(path: &Path) -> CliResult<Self> {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("json") | None => Ok(ConfigFormat::LegacyJson),
+            Some(other) => Err(CliError::config_error(format!(
+                "Unsupported config file extension '.{}': expected .toml or .json",
+                other
+            ))),
+        }
+    }
+}
 
-    /// Max WAL size in bytes (optional, default 1GB)
-    #[serde(default = "default_max_wal_size")]
-    pub max_wal_size_bytes: u64,
+/// Smallest max_memory_bytes accepted by `config-validate` (16MB)
+const MIN_MAX_MEMORY_BYTES: u64 = 16 * 1024 * 1024;
 
-    /// Max memory in bytes (optional, default 512MB)
-    #[serde(default = "default_max_memory")]
-    pub max_memory_bytes: u64,
+/// Shortest auth.service_role_secret accepted (256 bits)
+const MIN_SERVICE_ROLE_SECRET_BYTES: usize = 32;
 
-    /// Wal sync mode (optional, default fsync)
-    #[serde(default = "default_wal_sync_mode")]
-    pub wal_sync_mode: String,
+impl AeroConfig {
+    /// Load and validate a configuration file.
+    ///
+    /// The format is chosen by extension: `.toml` is the unified sectioned
+    /// format, `.json` (or no extension) is the deprecated flat JSON layout.
+    /// Both go through the same validation.
+    pub fn load(path: &Path) -> CliResult<Self> {
+        let config = Self::read(path)?;
+        config.validate()?;
 
-    /// Resource limits configuration
-    #[serde(default)]
-    pub resource_limits: ResourceLimitsConfig,
+        Ok(config)
+    }
 
-    /// Observability configuration
-    #[serde(default)]
-    pub observability: ObservabilityConfig,
+    /// Read and deserialize a config file without validating it.
+    pub(super) fn read(path: &Path) -> CliResult<Self> {
+        let format = ConfigFormat::from_path(path)?;
+        let content = fs::read_to_string(path)
+            .map_err(|e| CliError::config_error(format!("Failed to read config: {}", e)))?;
 
-    /// Backpressure configuration
-    #[serde(default)]
-    pub backpressure: BackpressureConfig,
+        Self::parse(&content, format)
+    }
 
-    /// Admission control configuration
-    #[serde(default)]
-    pub admission_control: AdmissionControlConfig,
+    /// Deserialize configuration from `content` in the given format.
+    fn parse(content: &str, format: ConfigFormat) -> CliResult<Self> {
+        match format {
+            ConfigFormat::LegacyJson => {
+                eprintln!(
+                    "Warning: flat JSON config files are deprecated and will stop being read \
+                     in the next release; move the settings to the sections of aerodb.toml \
+                     (see CONFIG.md)"
+                );
+                AeroConfig::from_legacy_json(content)
+                    .map_err(|e| CliError::config_error(format!("Invalid config JSON: {}", e)))
+            }
+            ConfigFormat::Toml => AeroConfig::from_toml(content).map_err(|errors| {
+                CliError::config_error(format!(
+                    "Invalid config TOML:\n{}",
+                    format_validation_errors(&errors)
+                ))
+            }),
+        }
+    }
 
-    /// Query limits configuration
-    #[serde(default)]
-    pub query_limits: QueryLimitsConfig,
+    /// Validate configuration per CONFIG.md
+    fn validate(&self) -> CliResult<()> {
+        // Validate wal.sync_mode and group commit window
+        self.wal_sync_config()?;
 
-    /// Security configuration
-    #[serde(default)]
-    pub security: SecurityConfig,
+        // Validate wal.max_size_bytes
+        if self.wal.max_size_bytes == 0 {
+            return Err(CliError::config_error("wal.max_size_bytes must be > 0"));
+        }
 
-    // --- Replication Configuration (Phase 5 Stage 1) ---
-    // Per P5-I16: All fields default to disabled.
-    /// Whether replication is enabled (default: false per P5-I16)
-    #[serde(default)]
-    pub replication_enabled: bool,
+        // Validate wal.segment_bytes
+        self.wal_segment_config()?;
 
-    /// Replication role: "primary" or "replica" (default: "primary")
-    #[serde(default = "default_replication_role")]
-    pub replication_role: String,
+        // Validate wal.checkpoint_*
+        self.checkpoint_policy()?;
 
-    /// Replica ID (UUID, auto-generated if not provided for replicas)
-    #[serde(default)]
-    pub replica_id: Option<String>,
+        // Validate server.max_memory_bytes
+        if self.server.max_memory_bytes == 0 {
+            return Err(CliError::config_error(
+                "server.max_memory_bytes must be > 0",
+            ));
+        }
 
-    /// Primary node address (required for replicas, forbidden for primaries)
-    #[serde(default)]
-    pub primary_address: Option<String>,
-}
+        // Validate server.max_crash_reports
+        if self.server.max_crash_reports == 0 {
+            return Err(CliError::config_error(
+                "server.max_crash_reports must be > 0",
+            ));
+        }
 
-fn default_max_wal_size() -> u64 {
-    1073741824
-} // 1GB
-fn default_max_memory() -> u64 {
-    536870912
-} // 512MB
-fn default_wal_sync_mode() -> String {
-    "fsync".to_string()
-}
-fn default_replication_role() -> String {
-    "primary".to_string()
-}
+        // Validate server.shutdown_grace_ms
+        if self.server.shutdown_grace_ms == 0 {
+            return Err(CliError::config_error(
+                "server.shutdown_grace_ms must be > 0",
+            ));
+        }
 
-impl Config {
-    /// Load configuration from file (supports JSON and TOML)
-    pub fn load(path: &Path) -> CliResult<Self> {
-        let content = fs::read_to_string(path)
-            .map_err(|e| CliError::config_error(format!("Failed to read config: {}", e)))?;
+        // Validate control_plane.confirmation_ttl_secs
+        if self.control_plane.confirmation_ttl_secs == 0 {
+            return Err(CliError::config_error(
+                "control_plane.confirmation_ttl_secs must be > 0",
+            ));
+        }
 
-        // Check for TOML extension
-        if path.extension().and_then(|s| s.to_str()) == Some("toml") {
-            let config: Config = toml::from_str(&content)
-                .map_err(|e| CliError::config_error(format!("Invalid config TOML: {}", e)))?;
-            config.validate()?;
-            return Ok(config);
+        // Validate ttl sweeper
+        if self.ttl.sweep_interval_ms == 0 || self.ttl.sweep_batch_size == 0 {
+            return Err(CliError::config_error(
+                "ttl.sweep_interval_ms and ttl.sweep_batch_size must be > 0",
+            ));
         }
 
-        // Default to JSON
-        let config: Config = serde_json::from_str(&content)
-            .map_err(|e| CliError::config_error(format!("Invalid config JSON: {}", e)))?;
+        // Validate recovery.parallelism
+        if self.recovery.parallelism == 0 {
+            return Err(CliError::config_error("recovery.parallelism must be > 0"));
+        }
 
-        config.validate()?;
+        // Validate scheduler.jobs
+        self.scheduler
+            .validate()
+            .map_err(|e| CliError::config_error(format!("Scheduler config error: {}", e)))?;
 
-        Ok(config)
+        // Validate replication config (Phase 5 Stage 1)
+        self.to_replication_config()?.validate().map_err(|e| {
+            CliError::config_error(format!("Replication config error: {}", e.message))
+        })?;
+        self.replication_stream_config()?;
+        self.sync_replication_config()?;
+
+        // Validate auth.password_hash and auth.service_role_secret
+        self.password_hasher()?;
+        self.jwt_config()?;
+
+        Ok(())
     }
 
-    /// Validate configuration per CONFIG.md
-    fn validate(&self) -> CliResult<()> {
-        // Validate wal_sync_mode
-        if self.wal_sync_mode != "fsync" {
+    /// Run every `config_validator` check against this configuration.
+    ///
+    /// Unlike `validate`, which stops at the first problem, this collects
+    /// all errors plus warnings for settings that are legal but likely
+    /// mistaken. It also probes server.data_dir, so it is stricter than
+    /// boot-time validation: data_dir must be a writable directory if it
+    /// exists, and server.max_memory_bytes must be at least 16MB.
+    pub fn validation_report(&self) -> ValidationReport {
+        let mut v = ConfigValidator::new();
+
+        // data_dir
+        let data_dir = self.data_path();
+        v.validate_non_empty("server.data_dir", &self.server.data_dir);
+        if data_dir.is_dir() {
+            v.validate_writable("server.data_dir", data_dir);
+        } else if data_dir.exists() {
+            v.validate_is_directory("server.data_dir", data_dir);
+        } else if !self.server.data_dir.trim().is_empty() {
+            v.warn(
+                "server.data_dir",
+                data_dir.display(),
+                "Path does not exist yet; `aerodb init` will create it",
+            );
+        }
+        v.validate_is_directory("backup.backup_dir", Path::new(&self.backup.backup_dir));
+        if let Some(dir) = &self.wal.archive_dir {
+            v.validate_is_directory("wal.archive_dir", Path::new(dir));
+        }
+
+        // WAL
+        if self.wal.max_size_bytes == 0 {
+            v.reject("wal.max_size_bytes", 0, "Value must be positive");
+        }
+        if let Err(e) = self.wal_sync_config() {
+            v.reject("wal.sync_mode", &self.wal.sync_mode, e.message());
+        }
+        if let Err(e) = self.wal_segment_config() {
+            v.reject("wal.segment_bytes", self.wal.segment_bytes, e.message());
+        }
+        if self.wal.checkpoint_max_records == Some(0) {
+            v.reject("wal.checkpoint_max_records", 0, "Value must be positive");
+        }
+        if self.wal.checkpoint_interval_secs == Some(0) {
+            v.reject("wal.checkpoint_interval_secs", 0, "Value must be positive");
+        }
+
+        // Memory
+        v.validate_bytes(
+            "server.max_memory_bytes",
+            self.server.max_memory_bytes,
+            MIN_MAX_MEMORY_BYTES,
+            u64::MAX,
+        );
+        if self.server.max_crash_reports == 0 {
+            v.reject("server.max_crash_reports", 0, "Value must be positive");
+        }
+        if self.server.shutdown_grace_ms == 0 {
+            v.reject("server.shutdown_grace_ms", 0, "Value must be positive");
+        }
+
+        // Query timeouts
+        let limits = &self.query_limits;
+        if limits.max_execution_ms == 0 {
+            v.reject("query_limits.max_execution_ms", 0, "Value must be positive");
+        } else if limits.max_execution_ms > limits.max_timeout_ms {
+            v.reject(
+                "query_limits.max_execution_ms",
+                limits.max_execution_ms,
+                &format!(
+                    "Default timeout exceeds query_limits.max_timeout_ms ({})",
+                    limits.max_timeout_ms
+                ),
+            );
+        }
+        if limits.max_sort_bytes == 0 {
+            v.reject("query_limits.max_sort_bytes", 0, "Value must be positive");
+        }
+
+        // Control plane
+        if self.control_plane.confirmation_ttl_secs == 0 {
+            v.reject(
+                "control_plane.confirmation_ttl_secs",
+                0,
+                "Value must be positive",
+            );
+        }
+
+        // TTL sweeper
+        if self.ttl.sweep_interval_ms == 0 {
+            v.reject("ttl.sweep_interval_ms", 0, "Value must be positive");
+        }
+        if self.ttl.sweep_batch_size == 0 {
+            v.reject("ttl.sweep_batch_size", 0, "Value must be positive");
+        }
+
+        // Password hashing
+        let params = self.auth.password_hash;
+        if let Err(e) = self.password_hasher() {
+            if params.iterations == 0 {
+                v.reject("auth.password_hash.iterations", 0, e.message());
+            } else if params.parallelism == 0
+                || u64::from(params.memory_kib) >= 8 * u64::from(params.parallelism)
+            {
+                v.reject("auth.password_hash.parallelism", params.parallelism, e.message());
+            } else {
+                v.reject("auth.password_hash.memory_kib", params.memory_kib, e.message());
+            }
+        } else {
+            let owasp = PasswordHashParams::default();
+            if params.memory_kib < owasp.memory_kib || params.iterations < owasp.iterations {
+                v.warn(
+                    "auth.password_hash",
+                    format!("m={},t={}", params.memory_kib, params.iterations),
+                    &format!(
+                        "Weaker than OWASP's minimum for Argon2id (m={},t={})",
+                        owasp.memory_kib, owasp.iterations
+                    ),
+                );
+            }
+        }
+        if let Err(e) = self.jwt_config() {
+            v.reject("auth.service_role_secret", "<hidden>", e.message());
+        }
+
+        // Recovery
+        if self.recovery.parallelism == 0 {
+            v.reject("recovery.parallelism", 0, "Value must be positive");
+        }
+
+        // Scheduler
+        if let Err(e) = self.scheduler.validate() {
+            v.reject("scheduler", "[table]", &e.to_string());
+        }
+
+        // Replication
+        let replication = self.to_replication_config().and_then(|config| {
+            config.validate().map_err(|e| CliError::config_error(e.message))
+        });
+        if let Err(e) = replication {
+            v.reject("replication", &self.replication.role, e.message());
+        } else if let Err(e) = self.replication_stream_config() {
+            if self.replication.port == 0 {
+                v.reject("replication.port", 0, e.message());
+            } else if self.replication.bootstrap_pin_secs == 0 {
+                v.reject("replication.bootstrap_pin_secs", 0, e.message());
+            } else {
+                v.reject("replication.secret", "<hidden>", e.message());
+            }
+        }
+        if let Err(e) = self.sync_replication_config() {
+            let r = &self.replication;
+            if SyncMode::parse(&r.sync_mode).is_err() {
+                v.reject("replication.sync_mode", &r.sync_mode, e.message());
+            } else if SyncTimeoutPolicy::parse(&r.sync_on_timeout).is_err() {
+                v.reject("replication.sync_on_timeout", &r.sync_on_timeout, e.message());
+            } else if r.min_sync_replicas == 0 {
+                v.reject("replication.min_sync_replicas", 0, e.message());
+            } else {
+                v.reject("replication.sync_timeout_ms", 0, e.message());
+            }
+        } else if self.replication.sync_mode != SyncMode::Async.as_str()
+            && (!self.replication.enabled || self.replication.role != "primary")
+        {
+            v.warn(
+                "replication.sync_mode",
+                &self.replication.sync_mode,
+                "Ignored except on an enabled primary",
+            );
+        }
+        if !self.replication.enabled {
+            if let Some(addr) = &self.replication.primary_address {
+                v.warn(
+                    "replication.primary_address",
+                    addr,
+                    "Ignored because replication.enabled is false",
+                );
+            }
+            if let Some(id) = &self.replication.replica_id {
+                v.warn(
+                    "replication.replica_id",
+                    id,
+                    "Ignored because replication.enabled is false",
+                );
+            }
+            if self.replication.secret.is_some() {
+                v.warn(
+                    "replication.secret",
+                    "<hidden>",
+                    "Ignored because replication.enabled is false",
+                );
+            }
+        }
+
+        v.report()
+    }
+
+    /// Build the WAL sync configuration.
+    ///
+    /// The group commit parameters are only checked when group commit is
+    /// selected; they are ignored by the other modes.
+    pub fn wal_sync_config(&self) -> CliResult<WalSyncConfig> {
+        let mode = WalSyncMode::parse(&self.wal.sync_mode).map_err(CliError::config_error)?;
+
+        if mode == WalSyncMode::GroupCommit {
+            if self.wal.group_commit_interval_ms == 0 {
+                return Err(CliError::config_error(
+                    "wal.group_commit_interval_ms must be > 0",
+                ));
+            }
+            if self.wal.group_commit_window_us == Some(0) {
+                return Err(CliError::config_error(
+                    "wal.group_commit_window_us must be > 0",
+                ));
+            }
+            if self.wal.group_commit_max_records == 0 {
+                return Err(CliError::config_error(
+                    "wal.group_commit_max_records must be > 0",
+                ));
+            }
+        }
+
+        let group_commit_interval = match self.wal.group_commit_window_us {
+            Some(us) => Duration::from_micros(us),
+            None => Duration::from_millis(self.wal.group_commit_interval_ms),
+        };
+
+        Ok(WalSyncConfig {
+            mode,
+            group_commit_interval,
+            group_commit_max_records: self.wal.group_commit_max_records,
+        })
+    }
+
+    /// Build the WAL segment configuration.
+    ///
+    /// A segment may not be larger than the whole WAL budget.
+    pub fn wal_segment_config(&self) -> CliResult<WalSegmentConfig> {
+        if self.wal.segment_bytes == 0 {
+            return Err(CliError::config_error("wal.segment_bytes must be > 0"));
+        }
+        if self.wal.segment_bytes > self.wal.max_size_bytes {
             return Err(CliError::config_error(format!(
-                "Invalid wal_sync_mode: '{}'. Only 'fsync' is allowed.",
-                self.wal_sync_mode
+                "wal.segment_bytes ({}) must not exceed wal.max_size_bytes ({})",
+                self.wal.segment_bytes, self.wal.max_size_bytes
             )));
         }
 
-        // Validate max_wal_size_bytes
-        if self.max_wal_size_bytes == 0 {
-            return Err(CliError::config_error("max_wal_size_bytes must be > 0"));
-        }
+        let config = WalSegmentConfig::new(self.wal.segment_bytes);
+        Ok(match &self.wal.archive_dir {
+            Some(dir) => config.with_archive_dir(dir),
+            None => config,
+        })
+    }
 
-        // Validate max_memory_bytes
-        if self.max_memory_bytes == 0 {
-            return Err(CliError::config_error("max_memory_bytes must be > 0"));
+    /// Build the automatic checkpoint policy.
+    ///
+    /// A checkpoint runs once the WAL reaches `wal.max_size_bytes`, and
+    /// also after `wal.checkpoint_max_records` records or
+    /// `wal.checkpoint_interval_secs` seconds when set.
+    pub fn checkpoint_policy(&self) -> CliResult<CheckpointPolicy> {
+        let mut policy = CheckpointPolicy::new(self.wal.max_size_bytes);
+        if let Some(records) = self.wal.checkpoint_max_records {
+            if records == 0 {
+                return Err(CliError::config_error("wal.checkpoint_max_records must be > 0"));
+            }
+            policy = policy.with_max_records(records);
+        }
+        if let Some(secs) = self.wal.checkpoint_interval_secs {
+            if secs == 0 {
+                return Err(CliError::config_error("wal.checkpoint_interval_secs must be > 0"));
+            }
+            policy = policy.with_interval(Duration::from_secs(secs));
         }
+        Ok(policy)
+    }
 
-        // Validate replication config (Phase 5 Stage 1)
-        self.to_replication_config()?.validate().map_err(|e| {
-            CliError::config_error(format!("Replication config error: {}", e.message))
-        })?;
+    /// Build the Argon2id hasher new passwords are hashed with.
+    ///
+    /// Argon2 needs at least one iteration and lane, and at least 8 KiB
+    /// of memory per lane.
+    pub fn password_hasher(&self) -> CliResult<PasswordHasher> {
+        let params = self.auth.password_hash;
+        if params.iterations == 0 {
+            return Err(CliError::config_error(
+                "auth.password_hash.iterations must be > 0",
+            ));
+        }
+        if params.parallelism == 0 {
+            return Err(CliError::config_error(
+                "auth.password_hash.parallelism must be > 0",
+            ));
+        }
+        if u64::from(params.memory_kib) < 8 * u64::from(params.parallelism) {
+            return Err(CliError::config_error(format!(
+                "auth.password_hash.memory_kib ({}) must be at least 8 per lane of \
+                 auth.password_hash.parallelism ({})",
+                params.memory_kib, params.parallelism
+            )));
+        }
+        PasswordHasher::new(params).map_err(|_| {
+            CliError::config_error(format!(
+                "auth.password_hash.parallelism ({}) is not accepted by Argon2",
+                params.parallelism
+            ))
+        })
+    }
 
-        Ok(())
+    /// Build the access token configuration, signing service-role
+    /// tokens with `auth.service_role_secret`.
+    ///
+    /// The secret must be at least 32 bytes and differ from the secret
+    /// signing anon and user tokens.
+    pub fn jwt_config(&self) -> CliResult<JwtConfig> {
+        let mut jwt = JwtConfig::default();
+        if let Some(secret) = &self.auth.service_role_secret {
+            if secret.len() < MIN_SERVICE_ROLE_SECRET_BYTES {
+                return Err(CliError::config_error(format!(
+                    "auth.service_role_secret must be at least {} bytes",
+                    MIN_SERVICE_ROLE_SECRET_BYTES
+                )));
+            }
+            if *secret == jwt.secret {
+                return Err(CliError::config_error(
+                    "auth.service_role_secret must differ from the user token secret",
+                ));
+            }
+        }
+        jwt.service_role_secret = self.auth.service_role_secret.clone();
+        Ok(jwt)
     }
 
     /// Get data directory as Path
     pub fn data_path(&self) -> &Path {
-        Path::new(&self.data_dir)
+        Path::new(&self.server.data_dir)
     }
 
     /// Convert to ReplicationConfig for use during boot.
@@ -174,37 +585,50 @@ impl Config {
     /// - Validates role is "primary" or "replica"
     /// - Auto-generates replica_id if needed
     pub fn to_replication_config(&self) -> CliResult<ReplicationConfig> {
-        if !self.replication_enabled {
+        if !self.replication.enabled {
             return Ok(ReplicationConfig::disabled());
         }
 
-        let role = match self.replication_role.as_str() {
+        let role = match self.replication.role.as_str() {
             "primary" => ReplicationRole::Primary,
             "replica" => ReplicationRole::Replica,
             other => {
                 return Err(CliError::config_error(format!(
-                    "Invalid replication_role: '{}'. Must be 'primary' or 'replica'.",
+                    "Invalid replication.role: '{}'. Must be 'primary' or 'replica'.",
                     other
                 )))
             }
         };
 
         match role {
-            ReplicationRole::Primary => Ok(ReplicationConfig::primary()),
+            ReplicationRole::Primary => {
+                if let Some(addr) = &self.replication.primary_address {
+                    return Err(CliError::config_error(format!(
+                        "replication.primary_address ('{}') is forbidden when role is 'primary'",
+                        addr
+                    )));
+                }
+                Ok(ReplicationConfig::primary())
+            }
             ReplicationRole::Replica => {
-                let primary_addr = self.primary_address.clone().ok_or_else(|| {
+                let primary_addr = self.replication.primary_address.clone().ok_or_else(|| {
                     CliError::config_error(
-                        "primary_address is required when replication_role is 'replica'",
+                        "replication.primary_address is required when role is 'replica'",
                     )
                 })?;
+                validate_host_port(&primary_addr)?;
 
                 let replica_id = self
+                    .replication
                     .replica_id
                     .as_ref()
                     .map(|s| uuid::Uuid::parse_str(s))
                     .transpose()
                     .map_err(|e| {
-                        CliError::config_error(format!("Invalid replica_id UUID: {}", e))
+                        CliError::config_error(format!(
+                            "Invalid replication.replica_id UUID: {}",
+                            e
+                        ))
                     })?;
 
                 Ok(ReplicationConfig::replica(primary_addr, replica_id))
@@ -212,6 +636,49 @@ impl Config {
         }
     }
 
+    /// Build the WAL stream configuration.
+    ///
+    /// `None` when replication is disabled. The primary listens on all
+    /// interfaces at `replication.port`.
+    pub fn replication_stream_config(&self) -> CliResult<Option<ReplicationStreamConfig>> {
+        if !self.replication.enabled {
+            return Ok(None);
+        }
+        if self.replication.port == 0 {
+            return Err(CliError::config_error("replication.port must be > 0"));
+        }
+        let secret = self.replication.secret.clone().ok_or_else(|| {
+            CliError::config_error("replication.secret is required when replication is enabled")
+        })?;
+
+        let mut config =
+            ReplicationStreamConfig::new(format!("0.0.0.0:{}", self.replication.port), secret);
+        config.bootstrap_pin = Duration::from_secs(self.replication.bootstrap_pin_secs);
+        config
+            .validate()
+            .map_err(|e| CliError::config_error(e.message))?;
+        Ok(Some(config))
+    }
+
+    /// Build the synchronous replication configuration.
+    ///
+    /// Checked whatever the role; only an enabled primary waits for
+    /// replicas.
+    pub fn sync_replication_config(&self) -> CliResult<SyncReplicationConfig> {
+        let r = &self.replication;
+        let config = SyncReplicationConfig {
+            mode: SyncMode::parse(&r.sync_mode).map_err(CliError::config_error)?,
+            min_sync_replicas: r.min_sync_replicas,
+            timeout: Duration::from_millis(r.sync_timeout_ms),
+            on_timeout: SyncTimeoutPolicy::parse(&r.sync_on_timeout)
+                .map_err(CliError::config_error)?,
+        };
+        config
+            .validate()
+            .map_err(|e| CliError::config_error(e.message))?;
+        Ok(config)
+    }
+
     /// Initialize ReplicationState based on config.
     ///
     /// Per PHASE5_IMPLEMENTATION_ORDER.md §Stage 1:
@@ -242,28 +709,126 @@ impl Config {
     }
 }
 
+/// Check that a replication address has the form `host:port`.
+///
+/// IPv6 hosts must be bracketed (`[::1]:7000`). The port must be a non-zero
+/// u16. No name resolution is performed.
+fn validate_host_port(addr: &str) -> CliResult<()> {
+    let malformed = |reason: &str| {
+        CliError::config_error(format!(
+            "Invalid replication.primary_address '{}': {}. Expected host:port",
+            addr, reason
+        ))
+    };
+
+    let (host, port) = addr
+        .rsplit_once(':')
+        .ok_or_else(|| malformed("missing port"))?;
+
+    let bare_host = host
+        .strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host);
+    if bare_host.is_empty() {
+        return Err(malformed("missing host"));
+    }
+    if bare_host.chars().any(char::is_whitespace) {
+        return Err(malformed("host contains whitespace"));
+    }
+    if bare_host == host && host.contains(':') {
+        return Err(malformed("IPv6 hosts must be enclosed in brackets"));
+    }
+
+    match port.parse::<u16>() {
+        Ok(0) | Err(_) => Err(malformed("port must be a number between 1 and 65535")),
+        Ok(_) => Ok(()),
+    }
+}
+
 /// Main CLI entry point
 ///
 /// Parses arguments and dispatches to the appropriate command.
 /// This is the only function that main.rs should call.
 pub fn run() -> CliResult<()> {
     let cli = super::args::Cli::parse_args();
-    run_command(cli.command)
+    run_command(cli.command, cli.output)
 }
 
 /// Run the appropriate command based on CLI args
-pub fn run_command(cmd: Command) -> CliResult<()> {
+pub fn run_command(cmd: Command, format: OutputFormat) -> CliResult<()> {
     match cmd {
-        Command::Init { config } => init(&config),
-        Command::Start { config } => start(&config),
-        Command::Query { config } => query(&config),
-        Command::Explain { config } => explain(&config),
+        Command::Init {
+            config,
+            force,
+            confirm_destroy,
+        } => init(&config, force, confirm_destroy.as_deref(), format),
+        Command::Start { config } => start(&config, format),
+        Command::Query { config } => query(&config, format),
+        Command::Explain { config } => explain(&config, format),
         Command::Serve { config, port } => serve(&config, port),
-        Command::Control { config, action } => control(&config, action),
-        Command::Migrate { config, action } => migrate(&config, action),
-        Command::Schema { config, action } => schema(&config, action),
-        Command::Deploy { config, action } => deploy(&config, action),
-        Command::Logs { config, lines, level, follow } => logs(&config, lines, level, follow),
+        Command::Control { config, action } => control(&config, action, format),
+        Command::Migrate { config, action } => migrate(&config, action, format),
+        Command::Schema { config, action } => schema(&config, action, format),
+        Command::Deploy { config, action } => deploy(&config, action, format),
+        Command::Logs { config, lines, level, follow } => {
+            logs(&config, lines, level, follow, format)
+        }
+        Command::Backup { config, action } => backup(&config, action, format),
+        Command::Wal { config, action } => wal(&config, action),
+        Command::Dx { config, action } => dx(&config, action, format),
+        Command::Doctor { config, json } => doctor(&config, json, format),
+        Command::Export {
+            config,
+            collection,
+            schema_version,
+            format: file_format,
+            out,
+            filter,
+        } => {
+            let options = ExportOptions::new(collection, schema_version, file_format);
+            export(&config, options, &out, filter.as_deref(), format)
+        }
+        Command::Import {
+            config,
+            collection,
+            schema_version,
+            file,
+            format: file_format,
+            mode,
+            batch_size,
+            rejects,
+            abort_on_error,
+            column_map,
+        } => {
+            let file_format = file_format.unwrap_or_else(|| TransferFormat::from_path(&file));
+            let options = ImportOptions::new(collection, schema_version, file_format)
+                .with_mode(mode)
+                .with_batch_size(batch_size)
+                .with_abort_on_error(abort_on_error);
+            import(&config, options, &column_map, &file, rejects.as_deref(), format)
+        }
+        Command::ConfigValidate { config } => config_validate(&config, format),
+        Command::Restore {
+            config,
+            backup_id,
+            to_time,
+            to_offset,
+            to_segment,
+            target_dir,
+            confirm,
+        } => restore(
+            &config,
+            &backup_id,
+            to_time.as_deref(),
+            to_offset.map(|offset| WalOffset {
+                segment: to_segment,
+                offset,
+            }),
+            target_dir.as_deref(),
+            confirm.as_deref(),
+            format,
+        ),
+        Command::Upgrade { config } => upgrade(&config, format),
     }
 }
 
@@ -271,15 +836,27 @@ pub fn run_command(cmd: Command) -> CliResult<()> {
 ///
 /// Per LIFECYCLE.md §2:
 /// - Creates directory structure
+/// - Writes the version and initialization markers
 /// - Does NOT start server
 /// - Writes no WAL records
 /// - Does not create clean_shutdown marker
-pub fn init(config_path: &Path) -> CliResult<()> {
-    let config = Config::load(config_path)?;
+///
+/// With `force`, an existing data directory (initialized or partial) is
+/// deleted first. `confirm_destroy` must name the configured `data_dir`.
+pub fn init(
+    config_path: &Path,
+    force: bool,
+    confirm_destroy: Option<&str>,
+    format: OutputFormat,
+) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
     let data_dir = config.data_path();
 
-    // Check if already initialized
-    if is_initialized(data_dir) {
+    let reinitialized = force && data_dir.exists();
+    if reinitialized {
+        destroy_data_dir(data_dir, confirm_destroy)?;
+    } else if is_initialized(data_dir) {
+        // Check if already initialized
         return Err(CliError::already_initialized());
     }
 
@@ -296,11 +873,59 @@ pub fn init(config_path: &Path) -> CliResult<()> {
         })?;
     }
 
-    write_response(json!({"initialized": true}))?;
+    VersionChecker::new(data_dir)
+        .mark_initialized()
+        .map_err(|e| CliError::config_error(e.to_string()))?;
+
+    write_response(format, json!({
+        "initialized": true,
+        "reinitialized": reinitialized
+    }))?;
 
     Ok(())
 }
 
+/// Delete the data directory for `init --force`
+///
+/// Refused unless `confirm_destroy` names the data directory, or while a
+/// live process holds it.
+fn destroy_data_dir(data_dir: &Path, confirm_destroy: Option<&str>) -> CliResult<()> {
+    if confirm_destroy.map(Path::new) != Some(data_dir) {
+        return Err(CliError::confirmation_required(format!(
+            "init --force deletes {} and all data in it. \
+             Pass --confirm-destroy {} to proceed.",
+            data_dir.display(),
+            data_dir.display()
+        )));
+    }
+
+    // An unreadable marker is expected after a failed init: ignore it
+    if let Ok(Some(marker)) = VersionMarker::load(data_dir) {
+        if let Some(lock) = marker.process_lock.filter(ProcessLock::held_by_other) {
+            return Err(CliError::config_error(
+                VersionError::AlreadyRunning {
+                    pid: lock.pid,
+                    acquired_at: lock.acquired_at,
+                }
+                .to_string(),
+            ));
+        }
+    }
+
+    fs::remove_dir_all(data_dir).map_err(|e| {
+        CliError::config_error(format!("Failed to delete {}: {}", data_dir.display(), e))
+    })
+}
+
+/// Input of the `start` serving loop
+enum Input {
+    Request(CliResult<Value>),
+    /// stdin reached end of file
+    Closed,
+    /// SIGTERM or SIGINT
+    Shutdown,
+}
+
 /// Start the AeroDB server
 ///
 /// Per BOOT.md §3, startup sequence:
@@ -311,9 +936,10 @@ pub fn init(config_path: &Path) -> CliResult<()> {
 /// 5. Verification
 /// 6. API Activation
 ///
-/// Then enters SERVING loop reading JSON from stdin.
-pub fn start(config_path: &Path) -> CliResult<()> {
-    let config = Config::load(config_path)?;
+/// Then enters SERVING loop reading JSON from stdin, until stdin closes or
+/// SIGTERM/SIGINT arrives.
+pub fn start(config_path: &Path, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
     let data_dir = config.data_path();
 
     // Check if initialized
@@ -322,54 +948,173 @@ pub fn start(config_path: &Path) -> CliResult<()> {
     }
 
     // Boot the system
-    let (mut wal_writer, mut storage_writer, mut storage_reader, schema_loader, mut index_manager, rm, bpm, ac) =
+    let (mut wal_writer, mut storage_writer, mut storage_reader, mut schema_loader, mut index_manager, rm, bpm, ac) =
         boot_system(&config)?;
+    let mut statistics = open_statistics(&config)?;
+
+    // In group commit mode responses wait for the WAL sync covering them
+    let sync_config = config.wal_sync_config()?;
+    let mut batch = None;
+    if sync_config.mode == WalSyncMode::GroupCommit {
+        wal_writer = wal_writer.with_deferred_sync();
+        storage_writer = storage_writer.with_deferred_sync();
+        batch = Some(GroupCommitBatch::new(sync_config));
+    }
 
-    // Initialize API handler
-    let handler = ApiHandler::new("default");
+    // From here on a panic leaves a crash report behind
+    let rm = Arc::new(rm);
+    crash_reporter(&config, rm.clone()).install();
+
+    // Initialize API handler (read-only on a replica)
+    let operation_log = Arc::new(OperationLog::new(config.observability.operation_log.clone()));
+    let handler = ApiHandler::new("default")
+        .with_replica_gate(open_replica_gate(&config, &wal_writer)?)
+        .with_operation_log(operation_log)
+        .with_confirmation_ttl(Duration::from_secs(config.control_plane.confirmation_ttl_secs))
+        .with_audit_log(open_audit_log(&config)?);
+    let sweep_interval = Duration::from_millis(config.ttl.sweep_interval_ms);
+    let mut last_sweep = Instant::now();
+    let mut checkpoints = CheckpointScheduler::new(
+        data_dir,
+        storage_writer.path(),
+        &data_dir.join("metadata").join("schemas"),
+        config.checkpoint_policy()?,
+    )
+    .map_err(|e| CliError::boot_failed(format!("Checkpoint scheduler failed: {}", e)))?;
+
+    // This loop is the only executor of the booted subsystems
+    let lock = GlobalExecutionLock::new();
 
     // Enter SERVING loop
-    // Read JSON from stdin line-by-line, write response to stdout
-    for request_result in read_requests() {
+    // Read JSON from stdin line-by-line, write response to stdout, until
+    // stdin closes or SIGTERM/SIGINT arrives
+    let (sender, inputs) = mpsc::channel();
+    shutdown::notify_on_signal(sender.clone(), Input::Shutdown)?;
+    thread::spawn(move || {
+        for request in read_requests() {
+            if sender.send(Input::Request(request)).is_err() {
+                return;
+            }
+        }
+        let _ = sender.send(Input::Closed);
+    });
+
+    // A signal is only seen between requests, so the running one finishes
+    loop {
+        // An open batch is released when its window closes
+        let input = match batch.as_ref().and_then(GroupCommitBatch::deadline) {
+            Some(deadline) => {
+                match inputs.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(input) => input,
+                    Err(RecvTimeoutError::Timeout) => {
+                        release_batch(&mut batch, &mut wal_writer, &mut storage_writer, format)?;
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            None => match inputs.recv() {
+                Ok(input) => input,
+                Err(_) => break,
+            },
+        };
+        let Input::Request(request_result) = input else {
+            break;
+        };
         match request_result {
             Ok(request) => {
                 let request_str = request.to_string();
+                let request_id =
+                    resolve_request_id(request.get("request_id").and_then(Value::as_str));
+
+                // Checkpoint between requests once one is due (a failure
+                // leaves the WAL intact and is reported by `diag wal`)
+                let _ = checkpoints.maybe_checkpoint(&mut wal_writer, &lock);
 
                 let mut subsystems = Subsystems {
-                    schema_loader: &schema_loader,
+                    schema_loader: &mut schema_loader,
                     wal_writer: &mut wal_writer,
                     storage_writer: &mut storage_writer,
                     storage_reader: &mut storage_reader,
                     index_manager: &mut index_manager,
+                    statistics: &mut statistics,
                     resource_manager: &rm,
                     backpressure_manager: &bpm,
                     admission_controller: &ac,
                     query_limits: &config.query_limits,
                 };
 
-                let response = handler.handle(&request_str, &mut subsystems);
-                write_json(&response.to_json())?;
+                // Sweep expired documents between requests once the
+                // interval has passed (advisory: queries already hide them)
+                if last_sweep.elapsed() >= sweep_interval {
+                    let _ = handler.sweep_expired(config.ttl.sweep_batch_size, &mut subsystems);
+                    last_sweep = Instant::now();
+                }
+
+                let response =
+                    handler.handle_with_request_id(request_id, &request_str, &mut subsystems);
+                match &mut batch {
+                    Some(open) if open.must_hold(&wal_writer) => {
+                        open.hold(response.to_json());
+                        if open.is_due(&wal_writer) {
+                            release_batch(
+                                &mut batch,
+                                &mut wal_writer,
+                                &mut storage_writer,
+                                format,
+                            )?;
+                        }
+                    }
+                    _ => write_json(format, &response.to_json())?,
+                }
             }
             Err(e) => {
                 // I/O error reading - this is fatal
-                write_error(e.code_str(), e.message())?;
+                release_batch(&mut batch, &mut wal_writer, &mut storage_writer, format)?;
+                write_error(format, e.code_str(), e.message())?;
                 break;
             }
         }
     }
 
-    // Clean shutdown - write marker
-    let shutdown_marker = data_dir.join("clean_shutdown");
-    let _ = fs::write(&shutdown_marker, "");
+    // Clean shutdown - answer the open batch, fsync the WAL, save
+    // statistics, write marker
+    release_batch(&mut batch, &mut wal_writer, &mut storage_writer, format)?;
+    shutdown::finish(data_dir, &wal_writer, &mut statistics)?;
+
+    Ok(())
+}
 
+/// Sync the WAL, then storage, and write the responses of the open group
+/// commit batch
+///
+/// A failed sync is FATAL: none of the held responses is sent.
+fn release_batch(
+    batch: &mut Option<GroupCommitBatch<String>>,
+    wal_writer: &mut WalWriter,
+    storage_writer: &mut StorageWriter,
+    format: OutputFormat,
+) -> CliResult<()> {
+    let Some(batch) = batch else {
+        return Ok(());
+    };
+    let responses = batch.release(wal_writer).map_err(|e| {
+        CliError::io_error(format!("Group commit sync failed (FATAL): {}", e))
+    })?;
+    storage_writer.sync_deferred().map_err(|e| {
+        CliError::io_error(format!("Group commit sync failed (FATAL): {}", e))
+    })?;
+    for response in responses {
+        write_json(format, &response)?;
+    }
     Ok(())
 }
 
 /// Execute a single query and exit
 ///
 /// Per CLI spec: Full boot → Execute single query → Print result → Exit
-pub fn query(config_path: &Path) -> CliResult<()> {
-    let config = Config::load(config_path)?;
+pub fn query(config_path: &Path, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
     let data_dir = config.data_path();
 
     // Check if initialized
@@ -378,8 +1123,9 @@ pub fn query(config_path: &Path) -> CliResult<()> {
     }
 
     // Boot the system
-    let (mut wal_writer, mut storage_writer, mut storage_reader, schema_loader, mut index_manager, rm, bpm, ac) =
+    let (mut wal_writer, mut storage_writer, mut storage_reader, mut schema_loader, mut index_manager, rm, bpm, ac) =
         boot_system(&config)?;
+    let mut statistics = open_statistics(&config)?;
 
     // Read single request from stdin
     let request = read_request()?;
@@ -398,15 +1144,17 @@ pub fn query(config_path: &Path) -> CliResult<()> {
 
     let request_str = request_obj.to_string();
 
-    // Initialize API handler
-    let handler = ApiHandler::new("default");
+    // Initialize API handler (read-only on a replica)
+    let handler =
+        ApiHandler::new("default").with_replica_gate(open_replica_gate(&config, &wal_writer)?);
 
     let mut subsystems = Subsystems {
-        schema_loader: &schema_loader,
+        schema_loader: &mut schema_loader,
         wal_writer: &mut wal_writer,
         storage_writer: &mut storage_writer,
         storage_reader: &mut storage_reader,
         index_manager: &mut index_manager,
+        statistics: &mut statistics,
         resource_manager: &rm,
         backpressure_manager: &bpm,
         admission_controller: &ac,
@@ -414,7 +1162,10 @@ pub fn query(config_path: &Path) -> CliResult<()> {
     };
 
     let response = handler.handle(&request_str, &mut subsystems);
-    write_json(&response.to_json())?;
+    write_json(format, &response.to_json())?;
+
+    // Persist incremental statistics (advisory: failure is not fatal)
+    let _ = statistics.save();
 
     Ok(())
 }
@@ -422,8 +1173,8 @@ pub fn query(config_path: &Path) -> CliResult<()> {
 /// Execute explain on a query and exit
 ///
 /// Same as query, but forces "op":"explain"
-pub fn explain(config_path: &Path) -> CliResult<()> {
-    let config = Config::load(config_path)?;
+pub fn explain(config_path: &Path, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
     let data_dir = config.data_path();
 
     // Check if initialized
@@ -432,8 +1183,9 @@ pub fn explain(config_path: &Path) -> CliResult<()> {
     }
 
     // Boot the system
-    let (mut wal_writer, mut storage_writer, mut storage_reader, schema_loader, mut index_manager, rm, bpm, ac) =
+    let (mut wal_writer, mut storage_writer, mut storage_reader, mut schema_loader, mut index_manager, rm, bpm, ac) =
         boot_system(&config)?;
+    let mut statistics = open_statistics(&config)?;
 
     // Read single request from stdin
     let request = read_request()?;
@@ -446,15 +1198,17 @@ pub fn explain(config_path: &Path) -> CliResult<()> {
 
     let request_str = request_obj.to_string();
 
-    // Initialize API handler
-    let handler = ApiHandler::new("default");
+    // Initialize API handler (read-only on a replica)
+    let handler =
+        ApiHandler::new("default").with_replica_gate(open_replica_gate(&config, &wal_writer)?);
 
     let mut subsystems = Subsystems {
-        schema_loader: &schema_loader,
+        schema_loader: &mut schema_loader,
         wal_writer: &mut wal_writer,
         storage_writer: &mut storage_writer,
         storage_reader: &mut storage_reader,
         index_manager: &mut index_manager,
+        statistics: &mut statistics,
         resource_manager: &rm,
         backpressure_manager: &bpm,
         admission_controller: &ac,
@@ -462,7 +1216,7 @@ pub fn explain(config_path: &Path) -> CliResult<()> {
     };
 
     let response = handler.handle(&request_str, &mut subsystems);
-    write_json(&response.to_json())?;
+    write_json(format, &response.to_json())?;
 
     Ok(())
 }
@@ -476,112 +1230,683 @@ pub fn explain(config_path: &Path) -> CliResult<()> {
 /// 1. Boot database (same as start command)
 /// 2. Initialize HTTP server with all subsystems
 /// 3. Start Axum server on specified port
+///
+/// Runtime-tunable settings are reloaded from `config_path` when the file
+/// changes, on SIGHUP, or on `POST /admin/v1/config/reload`.
 pub fn serve(config_path: &Path, port: u16) -> CliResult<()> {
-    let config = Config::load(config_path)?;
+    let started_at = Instant::now();
+    let config = AeroConfig::load(config_path)?;
     let data_dir = config.data_path();
 
+    // An empty replica starts from a snapshot of the primary
+    bootstrap_replica(&config)?;
+
     // Check if initialized
     if !is_initialized(data_dir) {
         return Err(CliError::not_initialized());
     }
 
     // Boot the system (same as start command)
-    let (_wal_writer, _storage_writer, _storage_reader, _schema_loader, _index_manager, _rm, _bpm, _ac) =
+    let (wal_writer, storage_writer, storage_reader, schema_loader, index_manager, rm, bpm, ac) =
         boot_system(&config)?;
+    let mut handler = ApiHandler::new("default")
+        .with_replica_gate(open_replica_gate(&config, &wal_writer)?)
+        .with_confirmation_ttl(Duration::from_secs(config.control_plane.confirmation_ttl_secs))
+        .with_audit_log(open_audit_log(&config)?);
+
+    // A primary listens for replicas; in quorum mode a write waits for
+    // their acknowledgments
+    let streamer = bind_streamer(&config)?;
+    if let Some(streamer) = &streamer {
+        let sync = config.sync_replication_config()?;
+        if sync.mode == SyncMode::Quorum {
+            let sync = SyncReplication::new(data_dir, sync, streamer.tracker());
+            handler = handler.with_sync_replication(Arc::new(sync));
+        }
+    }
 
-    // Create HTTP server with configured port
-    use crate::http_server::{HttpServer, HttpServerConfig};
+    // Follow the primary
+    let writers = follow_primary(&config, wal_writer, storage_writer)?;
+
+    // Components whose settings follow the config file while serving
+    let observability = &config.observability;
+    let operation_log = Arc::new(OperationLog::new(observability.operation_log.clone()));
+    let rm = Arc::new(rm);
+    let bpm = Arc::new(bpm);
+    let ac = Arc::new(ac);
+    let settings = RuntimeSettings::new()
+        .with_slow_query_tracker(Arc::new(SlowQueryTracker::new(
+            observability.slow_query.clone(),
+        )))
+        .with_operation_log(operation_log.clone())
+        .with_backpressure(bpm.clone())
+        .with_admission_controller(ac.clone())
+        .with_resource_manager(rm.clone())
+        .with_log_level(JsonLogger::set_min_severity);
+
+    // From here on a panic leaves a crash report behind
+    crash_reporter(&config, rm.clone())
+        .with_operation_log(operation_log.clone())
+        .install();
+    let reloader = Arc::new(ConfigReloader::new(config_path, &config, settings));
 
-    let http_config = HttpServerConfig::with_port(port);
-    let server = HttpServer::with_config(http_config);
+    // Create HTTP server with configured port
+    use crate::http_server::HttpServerConfig;
+
+    let http_config = HttpServerConfig::with_port(port)
+        .with_backup_dir(config.backup.backup_dir.clone())
+        .with_data_dir(config.server.data_dir.clone())
+        .with_realtime(config.realtime.clone())
+        .with_storage(config.storage.clone())
+        .with_confirmation_ttl_secs(config.control_plane.confirmation_ttl_secs)
+        .with_password_hasher(config.password_hasher()?)
+        .with_jwt_config(config.jwt_config()?);
+    let mut server = HttpServer::with_config(http_config)
+        .with_config_reload(reloader.clone())
+        .with_resource_manager(rm.clone())
+        .with_operation_log(operation_log);
+
+    // Bulk import and export and statistics for the dashboard; a
+    // replica's writers apply the primary's WAL, so it serves none of them
+    let mut engine = None;
+    if let Some((mut wal_writer, mut storage_writer)) = writers {
+        // In group commit mode an import syncs its writes once, at the end
+        if config.wal_sync_config()?.mode == WalSyncMode::GroupCommit {
+            wal_writer = wal_writer.with_deferred_sync();
+            storage_writer = storage_writer.with_deferred_sync();
+        }
+        let booted = Engine {
+            wal_writer,
+            storage_writer,
+            storage_reader,
+            schema_loader,
+            index_manager,
+            statistics: open_statistics(&config)?,
+            resource_manager: rm,
+            backpressure_manager: bpm,
+            admission_controller: ac,
+            query_limits: config.query_limits.clone(),
+        };
+        let transfer = Arc::new(LocalTransfer::new(handler, booted));
+        engine = Some(transfer.engine());
+
+        // Ship the WAL to replicas, bootstrapping new ones from snapshots
+        // taken on demand
+        if let Some(streamer) = streamer {
+            let snapshots = LocalSnapshots::new(transfer.engine(), data_dir);
+            stream_to_replicas(streamer, Arc::new(snapshots))?;
+        }
+        let stats = LocalStats::new(
+            transfer.engine(),
+            data_dir,
+            Path::new(&config.backup.backup_dir),
+            replication_role(&config),
+            started_at,
+        );
+        server = server
+            .with_data_transfer(transfer)
+            .with_database_stats(Arc::new(stats));
+    }
+    let scheduler = Arc::new(open_scheduler(&config, &server)?);
 
     // Start the async runtime and run the server
     let rt = tokio::runtime::Runtime::new()
         .map_err(|e| CliError::boot_failed(format!("Failed to create tokio runtime: {}", e)))?;
 
+    // Serve until SIGTERM/SIGINT, then let in-flight requests finish
+    let grace = Duration::from_millis(config.server.shutdown_grace_ms);
     rt.block_on(async {
-        server
-            .start()
-            .await
-            .map_err(|e| CliError::boot_failed(format!("HTTP server failed: {}", e)))
+        let signal = ShutdownSignal::install()
+            .map_err(|e| CliError::boot_failed(format!("Signal handler failed: {}", e)))?;
+        tokio::spawn(reloader.watch(DEFAULT_WATCH_INTERVAL));
+        if config.scheduler.enabled {
+            scheduler.spawn(DEFAULT_TICK_INTERVAL);
+        }
+
+        let stopping = Arc::new(tokio::sync::Notify::new());
+        let serving = server.start_with_shutdown({
+            let stopping = stopping.clone();
+            async move { stopping.notified().await }
+        });
+        tokio::pin!(serving);
+        let served = tokio::select! {
+            served = &mut serving => served,
+            () = signal.recv() => {
+                stopping.notify_one();
+                tokio::time::timeout(grace, serving).await.map_err(|_| {
+                    CliError::io_error(format!(
+                        "In-flight requests did not finish within {} ms; shutdown is not clean",
+                        grace.as_millis()
+                    ))
+                })?
+            }
+        };
+        served.map_err(|e| CliError::boot_failed(format!("HTTP server failed: {}", e)))
     })?;
 
+    // Requests have drained; a replica's follower owns its WAL, so only a
+    // standalone or primary server marks its shutdown as clean
+    if let Some(engine) = engine {
+        let mut engine = engine.lock().unwrap();
+        let engine = &mut *engine;
+        shutdown::finish(data_dir, &engine.wal_writer, &mut engine.statistics)?;
+    }
+
     Ok(())
 }
 
-/// Execute a Phase 7 control plane command.
-///
-/// Per PHASE7_COMMAND_MODEL.md:
-/// - CLI is a thin client with no authority
-/// - No retries, no defaults
-/// - Safety enforced server-side
-pub fn control(config_path: &Path, action: ControlAction) -> CliResult<()> {
-    let _config = Config::load(config_path)?;
-
-    // Create in-memory audit log for this session
-    let audit_log = MemoryAuditLog::new();
-
-    // Create control plane handler
-    let mut handler = ControlPlaneHandler::new();
+/// Scheduler for `serve`: jobs from `[scheduler]` and `_system.schedules`,
+/// which may invoke the server's edge functions, plus the built-in jobs of
+/// the services the server runs
+fn open_scheduler(config: &AeroConfig, server: &HttpServer) -> CliResult<Scheduler> {
+    let scheduler = Scheduler::open(config.data_path(), &config.scheduler)
+        .map_err(|e| CliError::boot_failed(format!("Scheduler failed: {}", e)))?
+        .with_registry(server.function_registry().clone());
+
+    let mut builtins: Vec<(&str, &str, Arc<dyn JobFunction>)> = vec![(
+        builtin_jobs::SESSION_EXPIRY,
+        builtin_jobs::SESSION_EXPIRY_CRON,
+        Arc::new(builtin_jobs::session_expiry(server.auth_service().clone())),
+    )];
+    match BackupManager::new(config.backup.clone()) {
+        Ok(manager) => builtins.push((
+            builtin_jobs::BACKUP_RETENTION,
+            builtin_jobs::BACKUP_RETENTION_CRON,
+            Arc::new(builtin_jobs::backup_retention(Arc::new(manager))),
+        )),
+        Err(e) => eprintln!("Warning: Backup retention job not scheduled: {}", e),
+    }
+    for (name, cron, function) in builtins {
+        scheduler
+            .register_builtin(name, cron, function)
+            .map_err(|e| CliError::boot_failed(format!("Scheduler failed: {}", e)))?;
+    }
 
-    // Convert CLI action to control plane command
-    let (command, authority) = build_command(action)?;
+    Ok(scheduler)
+}
 
-    // Log command request
-    let request_audit = AuditRecord::new(AuditAction::CommandRequested, AuditOutcome::Pending)
-        .with_command(command.command_name())
-        .with_authority(&authority.level.to_string());
-    audit_log.append(&request_audit).ok();
+/// Crash reporter for a server booted from `config`
+fn crash_reporter(config: &AeroConfig, resource_manager: Arc<ResourceManager>) -> CrashReporter {
+    CrashReporter::new(config.data_path())
+        .with_max_reports(config.server.max_crash_reports)
+        .with_resource_manager(resource_manager)
+        .with_replication_role(replication_role(config))
+}
 
-    // Create request
-    let request = CommandRequest::new(command.clone(), authority);
+/// `standalone`, or the configured replication role
+fn replication_role(config: &AeroConfig) -> &str {
+    if config.replication.enabled {
+        config.replication.role.as_str()
+    } else {
+        "standalone"
+    }
+}
+
+/// Bootstrap a replica whose data directory is empty, or whose last
+/// bootstrap did not finish, from a snapshot of the primary.
+fn bootstrap_replica(config: &AeroConfig) -> CliResult<()> {
+    let Some(stream_config) = config.replication_stream_config()? else {
+        return Ok(());
+    };
+    let replication = config.to_replication_config()?;
+    if replication.is_primary() {
+        return Ok(());
+    }
+    let data_dir = config.data_path();
+    let needed = ReplicaBootstrap::is_needed(data_dir)
+        .map_err(|e| CliError::boot_failed(format!("Replica bootstrap failed: {}", e.message)))?;
+    if !needed {
+        return Ok(());
+    }
+
+    let replica_id = replication
+        .get_replica_id()
+        .expect("Replica must have replica_id after validation");
+    let primary_address = replication
+        .primary_address
+        .expect("Replica must have primary_address after validation");
+    ReplicaBootstrap::new(data_dir, replica_id, primary_address, stream_config)
+        .run()
+        .map_err(|e| CliError::boot_failed(format!("Replica bootstrap failed: {}", e.message)))?;
+    Ok(())
+}
+
+/// Follow the primary on a background thread.
+///
+/// A replica hands its WAL writer and storage writer to a `WalFollower`,
+/// which connects to the primary and keeps following it until replication
+/// halts.
+///
+/// Returns the writers unless a follower took them.
+fn follow_primary(
+    config: &AeroConfig,
+    wal_writer: WalWriter,
+    storage_writer: StorageWriter,
+) -> CliResult<Option<(WalWriter, StorageWriter)>> {
+    let Some(stream_config) = config.replication_stream_config()? else {
+        return Ok(Some((wal_writer, storage_writer)));
+    };
+    let replication = config.to_replication_config()?;
+    if replication.is_primary() {
+        return Ok(Some((wal_writer, storage_writer)));
+    }
+
+    let replica_id = replication
+        .get_replica_id()
+        .expect("Replica must have replica_id after validation");
+    let primary_address = replication
+        .primary_address
+        .expect("Replica must have primary_address after validation");
+    let mut follower = WalFollower::open(
+        config.data_path(),
+        replica_id,
+        primary_address,
+        stream_config,
+        wal_writer,
+        storage_writer,
+    )
+    .map_err(|e| CliError::boot_failed(format!("Replication failed: {}", e.message)))?;
+    std::thread::Builder::new()
+        .name("replication-replica".to_string())
+        .spawn(move || {
+            if let Err(e) = follower.run(&AtomicBool::new(false)) {
+                eprintln!("Replication halted: {}", e);
+            }
+        })
+        .map_err(|e| {
+            CliError::boot_failed(format!("Failed to start replication thread: {}", e))
+        })?;
+    Ok(None)
+}
+
+/// Listen for replicas on `replication.port`, unless this is not a primary.
+fn bind_streamer(config: &AeroConfig) -> CliResult<Option<WalStreamer>> {
+    let Some(stream_config) = config.replication_stream_config()? else {
+        return Ok(None);
+    };
+    if !config.to_replication_config()?.is_primary() {
+        return Ok(None);
+    }
+    WalStreamer::bind(config.data_path(), stream_config)
+        .map(Some)
+        .map_err(|e| CliError::boot_failed(format!("Replication listener failed: {}", e.message)))
+}
+
+/// Ship the WAL to replicas on a background thread, bootstrapping empty
+/// ones from the snapshots `snapshots` names.
+fn stream_to_replicas(
+    streamer: WalStreamer,
+    snapshots: Arc<dyn BaseSnapshotSource>,
+) -> CliResult<()> {
+    let streamer = streamer.with_snapshot_source(snapshots);
+    std::thread::Builder::new()
+        .name("replication-primary".to_string())
+        .spawn(move || {
+            if let Err(e) = streamer.run(&AtomicBool::new(false)) {
+                eprintln!("Replication listener stopped: {}", e);
+            }
+        })
+        .map_err(|e| {
+            CliError::boot_failed(format!("Failed to start replication thread: {}", e))
+        })?;
+    Ok(())
+}
+
+/// Execute a Phase 7 control plane command.
+///
+/// Per PHASE7_COMMAND_MODEL.md:
+/// - CLI is a thin client with no authority
+/// - No retries, no defaults
+/// - Safety enforced server-side
+pub fn control(config_path: &Path, action: ControlAction, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
+
+    // Audit to data_dir/system/audit.log once the data directory exists;
+    // before that, to an in-memory log for this session
+    let audit_log: Box<dyn AuditLog> = if config.data_path().is_dir() {
+        let log = FileAuditLog::open_in(config.data_path())
+            .map_err(|e| CliError::io_error(format!("Failed to open audit log: {}", e)))?;
+        Box::new(log)
+    } else {
+        Box::new(MemoryAuditLog::new())
+    };
+
+    // Convert CLI action to control plane command
+    let (command, authority, confirm) = build_command(action)?;
+
+    // Create control plane handler (statistics are loaded only when inspected)
+    let handler = match command {
+        ControlPlaneCommand::Inspection(InspectionCommand::InspectStatistics) => {
+            let kernel = DefaultKernelAdapter::default().with_statistics(open_statistics(&config)?);
+            ControlPlaneHandler::with_kernel(Arc::new(kernel))
+        }
+        ControlPlaneCommand::Inspection(InspectionCommand::InspectReplicationStatus) => {
+            let state = config.init_replication_state()?;
+            let lag = if state.is_primary() { open_replica_lag(&config)? } else { Vec::new() };
+            let staleness_ms = open_replica_freshness(&config)?.staleness_ms();
+            let sync = open_sync_replication(&config)?;
+            let kernel = DefaultKernelAdapter::new(state, PromotionState::Steady)
+                .with_replica_lag(lag)
+                .with_replica_staleness(staleness_ms)
+                .with_sync_replication(sync);
+            ControlPlaneHandler::with_kernel(Arc::new(kernel))
+        }
+        ControlPlaneCommand::Inspection(InspectionCommand::InspectTenantUsage { .. }) => {
+            let kernel =
+                DefaultKernelAdapter::default().with_tenant_quotas(open_tenant_quotas(&config)?);
+            ControlPlaneHandler::with_kernel(Arc::new(kernel))
+        }
+        ControlPlaneCommand::Inspection(InspectionCommand::InspectScheduledJobs) => {
+            let kernel =
+                DefaultKernelAdapter::default().with_scheduled_jobs(open_scheduled_jobs(&config)?);
+            ControlPlaneHandler::with_kernel(Arc::new(kernel))
+        }
+        ControlPlaneCommand::Diagnostic(DiagnosticCommand::InspectWal) => {
+            let status = CheckpointStatus::load(config.data_path()).map_err(|e| {
+                CliError::config_error(format!("Checkpoint status load failed: {}", e))
+            })?;
+            let kernel = DefaultKernelAdapter::default().with_checkpoint_status(status);
+            ControlPlaneHandler::with_kernel(Arc::new(kernel))
+        }
+        ControlPlaneCommand::Diagnostic(DiagnosticCommand::InspectSnapshots) => {
+            let kernel =
+                DefaultKernelAdapter::default().with_snapshot_integrity(verify_snapshots(&config)?);
+            ControlPlaneHandler::with_kernel(Arc::new(kernel))
+        }
+        ControlPlaneCommand::Diagnostic(DiagnosticCommand::InspectCrashReports { .. }) => {
+            let reports = list_crash_reports(config.data_path()).map_err(|e| {
+                CliError::config_error(format!("Crash report listing failed: {}", e))
+            })?;
+            let kernel = DefaultKernelAdapter::default().with_crash_reports(reports);
+            ControlPlaneHandler::with_kernel(Arc::new(kernel))
+        }
+        ControlPlaneCommand::Diagnostic(DiagnosticCommand::InspectAuditLog { .. }) => {
+            let path = config.data_path().join(AUDIT_LOG_PATH);
+            let chain = verify_chain(&path)
+                .map_err(|e| CliError::io_error(format!("Failed to read audit log: {}", e)))?;
+            let kernel = DefaultKernelAdapter::default().with_audit_chain(chain);
+            ControlPlaneHandler::with_kernel(Arc::new(kernel))
+        }
+        _ => ControlPlaneHandler::new(),
+    };
+    let mut handler =
+        handler.with_confirmation_flow(load_confirmations(&config, config.data_path()));
+
+    // Create request; its audit records all carry its id
+    let mut request = CommandRequest::new(command.clone(), authority.clone());
+    let request_id = request.request_id;
+
+    // Log command request; a command that cannot be audited does not run
+    let request_audit = AuditRecord::new(AuditAction::CommandRequested, AuditOutcome::Pending)
+        .with_command(command.command_name())
+        .with_request_id(request_id)
+        .with_authority(&authority.level.to_string());
+    audit_log
+        .append(&request_audit)
+        .map_err(|e| CliError::io_error(format!("Failed to write audit log: {}", e)))?;
+
+    if let Some(token) = confirm {
+        let confirm_audit =
+            AuditRecord::new(AuditAction::ConfirmationProvided, AuditOutcome::Pending)
+                .with_command(command.command_name())
+                .with_request_id(request_id)
+                .with_confirmation_token(token);
+        audit_log
+            .append(&confirm_audit)
+            .map_err(|e| CliError::io_error(format!("Failed to write audit log: {}", e)))?;
+        request = request.with_confirmation(token);
+    }
 
     // Handle command
-    match handler.handle_command(request) {
+    let result = handler.handle_command(request);
+    save_confirmations(config.data_path(), handler.confirmation_flow())?;
+    match result {
         Ok(response) => {
-            // Log success
-            let outcome_audit =
-                AuditRecord::new(AuditAction::CommandExecuted, AuditOutcome::Success)
-                    .with_command(response.command_name.clone());
+            // Log success, or the token the operator must confirm with
+            let outcome_audit = match response.confirmation_token {
+                Some(token) if response.outcome == CommandOutcome::ConfirmationRequired => {
+                    AuditRecord::new(AuditAction::ConfirmationRequested, AuditOutcome::Pending)
+                        .with_command(response.command_name.clone())
+                        .with_request_id(request_id)
+                        .with_confirmation_token(token)
+                }
+                _ => AuditRecord::new(AuditAction::CommandExecuted, AuditOutcome::Success)
+                    .with_command(response.command_name.clone())
+                    .with_request_id(request_id),
+            };
             audit_log.append(&outcome_audit).ok();
 
             // Output response
-            write_response(json!({
+            let mut output = json!({
                 "request_id": response.request_id.to_string(),
                 "command": response.command_name,
                 "outcome": format!("{:?}", response.outcome),
                 "confirmation_token": response.confirmation_token.map(|t| t.to_string()),
-            }))?;
+            });
+            if let Some(CommandResponseData::PlannerStatistics(view)) = &response.data {
+                output["data"] = planner_statistics_json(view);
+            }
+            if let Some(CommandResponseData::ReplicationStatus(status)) = &response.data {
+                output["data"] = replication_status_json(status);
+            }
+            if let Some(CommandResponseData::TenantUsage(view)) = &response.data {
+                output["data"] = tenant_usage_json(view);
+            }
+            if let Some(CommandResponseData::ScheduledJobs(view)) = &response.data {
+                output["data"] = scheduled_jobs_json(view);
+            }
+            if let Some(CommandResponseData::WalInfo(info)) = &response.data {
+                output["data"] = wal_info_json(info);
+            }
+            if let Some(CommandResponseData::SnapshtoInfo(info)) = &response.data {
+                output["data"] = snapshot_info_json(info);
+            }
+            if let Some(CommandResponseData::CrashReports(info)) = &response.data {
+                output["data"] = crash_reports_json(info);
+            }
+            if let Some(CommandResponseData::AuditLog(info)) = &response.data {
+                output["data"] = audit_log_json(info);
+            }
+            write_response(format, output)?;
         }
         Err(e) => {
             // Log rejection
             let outcome_audit =
                 AuditRecord::new(AuditAction::CommandRejected, AuditOutcome::Rejected)
                     .with_command(command.command_name())
+                    .with_request_id(request_id)
                     .with_error(e.message());
             audit_log.append(&outcome_audit).ok();
 
             // Output error
-            write_error(e.code(), e.message())?;
+            write_error(format, e.code(), e.message())?;
         }
     }
 
     Ok(())
 }
 
+/// JSON form of the planner statistics inspection result.
+fn planner_statistics_json(view: &PlannerStatisticsView) -> Value {
+    let collections: Vec<Value> = view
+        .collections
+        .iter()
+        .map(|c| {
+            json!({
+                "collection": c.collection,
+                "document_count": c.document_count,
+                "average_document_size": c.average_document_size,
+                "distinct_values": c.distinct_values,
+                "analyzed_at": c.analyzed_at.map(|t| DateTime::<Utc>::from(t).to_rfc3339()),
+                "mutations_since_analyze": c.mutations_since_analyze,
+                "stale": c.stale,
+            })
+        })
+        .collect();
+    json!({ "collections": collections })
+}
+
+/// JSON form of the replication status inspection result.
+fn replication_status_json(status: &ReplicationStatus) -> Value {
+    let replicas: Vec<Value> = status
+        .replicas
+        .iter()
+        .map(|r| {
+            json!({
+                "replica_id": r.replica_id.to_string(),
+                "health": format!("{:?}", r.health),
+                "lag_records": r.lag_records,
+                "lag_bytes": r.lag_bytes,
+                "staleness_ms": r.staleness_ms,
+            })
+        })
+        .collect();
+    let sync = status.sync.as_ref().map(|sync| {
+        json!({
+            "mode": sync.mode,
+            "min_sync_replicas": sync.min_sync_replicas,
+            "connected_replicas": sync.connected_replicas,
+            "quorum_healthy": sync.quorum_healthy,
+            "degraded": sync.degraded,
+        })
+    });
+    json!({
+        "primary_id": status.primary_id.map(|id| id.to_string()),
+        "replicas": replicas,
+        "sync": sync,
+    })
+}
+
+/// JSON form of the tenant usage inspection result.
+fn tenant_usage_json(view: &TenantUsageView) -> Value {
+    json!({
+        "tenant_id": view.tenant_id.to_string(),
+        "usage": view.usage,
+        "quotas": view.quotas,
+    })
+}
+
+/// JSON form of the scheduled jobs inspection result.
+fn scheduled_jobs_json(view: &ScheduledJobsView) -> Value {
+    let jobs: Vec<Value> = view
+        .jobs
+        .iter()
+        .map(|job| {
+            json!({
+                "name": job.name,
+                "function": job.function,
+                "cron": job.cron,
+                "enabled": job.enabled,
+                "last_run": job.last_run.map(|t| DateTime::<Utc>::from(t).to_rfc3339()),
+                "next_run": job.next_run.map(|t| DateTime::<Utc>::from(t).to_rfc3339()),
+                "last_outcome": job.last_outcome,
+                "last_duration_ms": job.last_duration_ms,
+            })
+        })
+        .collect();
+    json!({ "jobs": jobs })
+}
+
+/// JSON form of the snapshot inspection result.
+fn snapshot_info_json(info: &SnapshotInfo) -> Value {
+    let snapshots: Vec<Value> = info
+        .integrity
+        .iter()
+        .map(|snapshot| match &snapshot.result {
+            Ok(report) => json!({
+                "snapshot_id": snapshot.snapshot_id,
+                "ok": report.ok,
+                "files_checked": report.files_checked,
+                "mismatches": report.mismatches,
+                "missing": report.missing,
+            }),
+            Err(error) => json!({
+                "snapshot_id": snapshot.snapshot_id,
+                "ok": false,
+                "error": error,
+            }),
+        })
+        .collect();
+    json!({ "snapshots": snapshots })
+}
+
+/// JSON form of the WAL inspection result.
+///
+/// `checkpoint` reports the running checkpoint (`in_progress`) and the
+/// last completed one, including its LSN (`wal_sequence`) and time.
+fn wal_info_json(info: &WalInfo) -> Value {
+    json!({
+        "current_position": info.current_position,
+        "oldest_position": info.oldest_position,
+        "size_bytes": info.size_bytes,
+        "checkpoint": info.checkpoint,
+    })
+}
+
+/// JSON form of the crash reports inspection result.
+///
+/// Each report is summarized by its time and panic message; the requested
+/// report, if any, is included whole as `report`.
+fn crash_reports_json(info: &CrashReportInfo) -> Value {
+    let summary = |stored: &StoredCrashReport| match &stored.report {
+        Ok(report) => json!({
+            "id": stored.id,
+            "size_bytes": stored.size_bytes,
+            "timestamp": report["timestamp"],
+            "message": report["message"],
+        }),
+        Err(error) => json!({
+            "id": stored.id,
+            "size_bytes": stored.size_bytes,
+            "error": error,
+        }),
+    };
+    let mut data = json!({ "reports": info.reports.iter().map(summary).collect::<Vec<_>>() });
+    if let Some(dump) = &info.dump {
+        data["report"] = match &dump.report {
+            Ok(report) => report.clone(),
+            Err(error) => json!({ "id": dump.id, "error": error }),
+        };
+    }
+    data
+}
+
+/// JSON form of the audit log inspection result.
+///
+/// The chain walk (`records`, `head`, `intact`, `broken_link`) is included
+/// only when verification was requested.
+fn audit_log_json(info: &AuditLogInfo) -> Value {
+    let chain = &info.chain;
+    let files: Vec<String> = chain.files.iter().map(|f| f.display().to_string()).collect();
+    let mut data = json!({ "files": files });
+    if info.verified {
+        data["records"] = json!(chain.records);
+        data["head"] = json!(chain.head);
+        data["intact"] = json!(chain.is_intact());
+        data["broken_link"] = match &chain.broken {
+            Some(link) => json!({
+                "file": link.file.display().to_string(),
+                "line": link.line,
+                "reason": link.reason,
+            }),
+            None => Value::Null,
+        };
+    }
+    data
+}
+
 /// Execute a migration command (Phase 14).
 ///
 /// MANIFESTO ALIGNMENT: Deterministic, checksummed, reversible migrations.
 /// All operations are explicit with clear success/failure feedback.
-pub fn migrate(config_path: &Path, action: MigrateAction) -> CliResult<()> {
+pub fn migrate(config_path: &Path, action: MigrateAction, format: OutputFormat) -> CliResult<()> {
     use crate::migrations::{
         generator::MigrationGenerator,
         operations::InMemoryExecutor,
         runner::MigrationRunner,
     };
-    use std::sync::Arc;
 
-    let config = Config::load(config_path)?;
+    let config = AeroConfig::load(config_path)?;
     let data_dir = config.data_path();
 
     // Migrations directory defaults to data_dir/migrations
@@ -613,7 +1938,7 @@ pub fn migrate(config_path: &Path, action: MigrateAction) -> CliResult<()> {
                 .unwrap_or(("0", filename));
             let version: u64 = version_str.parse().unwrap_or(0);
 
-            write_response(json!({
+            write_response(format, json!({
                 "created": true,
                 "version": version,
                 "name": migration_name,
@@ -651,6 +1976,7 @@ pub fn migrate(config_path: &Path, action: MigrateAction) -> CliResult<()> {
 
             if let Some(failed) = report.failed {
                 write_error(
+                    format,
                     "MIGRATION_FAILED",
                     &format!(
                         "Migration {} (v{}) failed: {}",
@@ -670,7 +1996,7 @@ pub fn migrate(config_path: &Path, action: MigrateAction) -> CliResult<()> {
                     })
                     .collect();
 
-                write_response(json!({
+                write_response(format, json!({
                     "success": true,
                     "applied_count": applied.len(),
                     "applied": applied,
@@ -708,7 +2034,7 @@ pub fn migrate(config_path: &Path, action: MigrateAction) -> CliResult<()> {
 
             match result {
                 Some(rolled_back) => {
-                    write_response(json!({
+                    write_response(format, json!({
                         "success": true,
                         "rolled_back": {
                             "version": rolled_back.version,
@@ -718,7 +2044,7 @@ pub fn migrate(config_path: &Path, action: MigrateAction) -> CliResult<()> {
                     }))?;
                 }
                 None => {
-                    write_response(json!({
+                    write_response(format, json!({
                         "success": true,
                         "message": "No migrations to rollback"
                     }))?;
@@ -734,7 +2060,7 @@ pub fn migrate(config_path: &Path, action: MigrateAction) -> CliResult<()> {
 
             // Check if migrations directory exists
             if !migrations_dir.exists() {
-                write_response(json!({
+                write_response(format, json!({
                     "current_version": 0,
                     "total_migrations": 0,
                     "applied_count": 0,
@@ -770,7 +2096,7 @@ pub fn migrate(config_path: &Path, action: MigrateAction) -> CliResult<()> {
                 })
                 .collect();
 
-            write_response(json!({
+            write_response(format, json!({
                 "current_version": status.current_version,
                 "total_migrations": status.total_migrations,
                 "applied_count": status.applied_count,
@@ -786,8 +2112,8 @@ pub fn migrate(config_path: &Path, action: MigrateAction) -> CliResult<()> {
 /// Execute a schema management command.
 ///
 /// MANIFESTO ALIGNMENT: Explicit schema management with full introspection.
-pub fn schema(config_path: &Path, action: SchemaAction) -> CliResult<()> {
-    let config = Config::load(config_path)?;
+pub fn schema(config_path: &Path, action: SchemaAction, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
     let data_dir = config.data_path();
 
     let schema_dir = data_dir.join("metadata").join("schemas");
@@ -795,7 +2121,7 @@ pub fn schema(config_path: &Path, action: SchemaAction) -> CliResult<()> {
     match action {
         SchemaAction::List => {
             if !schema_dir.exists() {
-                write_response(json!({
+                write_response(format, json!({
                     "schemas": [],
                     "count": 0
                 }))?;
@@ -821,7 +2147,7 @@ pub fn schema(config_path: &Path, action: SchemaAction) -> CliResult<()> {
                 }
             }
 
-            write_response(json!({
+            write_response(format, json!({
                 "schemas": schemas,
                 "count": schemas.len()
             }))?;
@@ -844,7 +2170,7 @@ pub fn schema(config_path: &Path, action: SchemaAction) -> CliResult<()> {
                 CliError::config_error(format!("Invalid schema JSON: {}", e))
             })?;
 
-            write_response(json!({
+            write_response(format, json!({
                 "name": name,
                 "schema": schema
             }))?;
@@ -878,14 +2204,14 @@ pub fn schema(config_path: &Path, action: SchemaAction) -> CliResult<()> {
                 CliError::config_error(format!("Failed to write schema: {}", e))
             })?;
 
-            write_response(json!({
+            write_response(format, json!({
                 "created": true,
                 "name": name,
                 "file": target.to_string_lossy().to_string()
             }))?;
         }
 
-        SchemaAction::Types { output } => {
+        SchemaAction::Types { out_dir: output, .. } => {
             // Ensure output directory exists
             if !output.exists() {
                 fs::create_dir_all(&output).map_err(|e| {
@@ -894,7 +2220,7 @@ pub fn schema(config_path: &Path, action: SchemaAction) -> CliResult<()> {
             }
 
             if !schema_dir.exists() {
-                write_response(json!({
+                write_response(format, json!({
                     "generated": [],
                     "count": 0
                 }))?;
@@ -937,7 +2263,7 @@ pub fn schema(config_path: &Path, action: SchemaAction) -> CliResult<()> {
                 }
             }
 
-            write_response(json!({
+            write_response(format, json!({
                 "generated": generated,
                 "count": generated.len()
             }))?;
@@ -963,12 +2289,12 @@ fn to_pascal_case(s: &str) -> String {
 /// Execute a deployment command.
 ///
 /// MANIFESTO ALIGNMENT: Explicit deployment configuration generation.
-pub fn deploy(config_path: &Path, action: DeployAction) -> CliResult<()> {
-    let config = Config::load(config_path)?;
+pub fn deploy(config_path: &Path, action: DeployAction, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
     let _data_dir = config.data_path();
 
     match action {
-        DeployAction::Docker { output } => {
+        DeployAction::Docker { out_file: output, .. } => {
             let docker_compose = r#"# AeroDB Docker Compose Configuration
 # Auto-generated - customize as needed
 
@@ -981,9 +2307,9 @@ services:
       - "54321:54321"
     volumes:
       - aerodb_data:/var/lib/aerodb
-      - ./aerodb.json:/etc/aerodb/config.json:ro
+      - ./aerodb.toml:/etc/aerodb/aerodb.toml:ro
     environment:
-      - AERODB_CONFIG=/etc/aerodb/config.json
+      - AERODB_CONFIG=/etc/aerodb/aerodb.toml
     restart: unless-stopped
     healthcheck:
       test: ["CMD", "aerodb", "control", "diag", "diagnostics"]
@@ -999,14 +2325,14 @@ volumes:
                 CliError::config_error(format!("Failed to write Docker Compose file: {}", e))
             })?;
 
-            write_response(json!({
+            write_response(format, json!({
                 "generated": true,
                 "file": output.to_string_lossy().to_string(),
                 "type": "docker-compose"
             }))?;
         }
 
-        DeployAction::K8s { output } => {
+        DeployAction::K8s { out_dir: output, .. } => {
             // Ensure output directory exists
             if !output.exists() {
                 fs::create_dir_all(&output).map_err(|e| {
@@ -1037,6 +2363,18 @@ spec:
         image: aerodb/aerodb:latest
         ports:
         - containerPort: 54321
+        livenessProbe:
+          httpGet:
+            path: /healthz
+            port: 54321
+          periodSeconds: 10
+          failureThreshold: 3
+        readinessProbe:
+          httpGet:
+            path: /readyz
+            port: 54321
+          periodSeconds: 5
+          failureThreshold: 1
         volumeMounts:
         - name: data
           mountPath: /var/lib/aerodb
@@ -1044,7 +2382,7 @@ spec:
           mountPath: /etc/aerodb
         env:
         - name: AERODB_CONFIG
-          value: /etc/aerodb/config.json
+          value: /etc/aerodb/aerodb.toml
       volumes:
       - name: data
         persistentVolumeClaim:
@@ -1098,7 +2436,7 @@ spec:
                 generated.push(file_path.to_string_lossy().to_string());
             }
 
-            write_response(json!({
+            write_response(format, json!({
                 "generated": true,
                 "files": generated,
                 "type": "kubernetes"
@@ -1107,7 +2445,7 @@ spec:
 
         DeployAction::Status => {
             // For now, just check if Docker is available
-            write_response(json!({
+            write_response(format, json!({
                 "status": "ready",
                 "docker": true,
                 "kubernetes": true,
@@ -1115,7 +2453,7 @@ spec:
             }))?;
         }
 
-        DeployAction::Env { output } => {
+        DeployAction::Env { out_file: output, .. } => {
             let env_template = r#"# AeroDB Environment Configuration
 # Copy this file to .env and customize
 
@@ -1146,7 +2484,7 @@ AERODB_LOG_LEVEL=info
                 CliError::config_error(format!("Failed to write env file: {}", e))
             })?;
 
-            write_response(json!({
+            write_response(format, json!({
                 "generated": true,
                 "file": output.to_string_lossy().to_string(),
                 "type": "env"
@@ -1160,19 +2498,23 @@ AERODB_LOG_LEVEL=info
 /// Execute a logs command.
 ///
 /// MANIFESTO ALIGNMENT: Explicit log viewing with filtering.
+///
+/// With `follow`, `lines` is ignored: lines appended after the command
+/// starts are streamed verbatim to stdout until the process is interrupted.
 pub fn logs(
     config_path: &Path,
     lines: usize,
     level: Option<String>,
     follow: bool,
+    format: OutputFormat,
 ) -> CliResult<()> {
-    let config = Config::load(config_path)?;
+    let config = AeroConfig::load(config_path)?;
     let data_dir = config.data_path();
 
     let log_file = data_dir.join("logs").join("aerodb.log");
 
     if !log_file.exists() {
-        write_response(json!({
+        write_response(format, json!({
             "logs": [],
             "count": 0,
             "message": "No log file found"
@@ -1181,13 +2523,17 @@ pub fn logs(
     }
 
     if follow {
-        // For follow mode, we'd need async streaming
-        // For now, just return current logs with a message
-        write_response(json!({
-            "error": "Follow mode not yet implemented",
-            "hint": "Use 'tail -f' on the log file directly"
-        }))?;
-        return Ok(());
+        let follower = LogFollower::from_end(&log_file).map_err(|e| {
+            CliError::config_error(format!("Failed to open log file: {}", e))
+        })?;
+        let stop = AtomicBool::new(false);
+        return follow_log(
+            follower,
+            level.as_deref(),
+            &mut std::io::stdout(),
+            DEFAULT_POLL_INTERVAL,
+            &stop,
+        );
     }
 
     // Read log file
@@ -1199,15 +2545,12 @@ pub fn logs(
     let total_count = all_lines.len();
     
     // Filter by level if specified
-    let filtered: Vec<&str> = if let Some(ref level_filter) = level {
-        let level_upper = level_filter.to_uppercase();
-        all_lines.iter()
-            .filter(|line| line.to_uppercase().contains(&level_upper))
-            .copied()
-            .collect()
-    } else {
-        all_lines
-    };
+    let level_upper = level.as_ref().map(|l| l.to_uppercase());
+    let filtered: Vec<&str> = all_lines
+        .iter()
+        .filter(|line| line_matches_level(line, level_upper.as_deref()))
+        .copied()
+        .collect();
 
     // Take last N lines
     let result: Vec<&str> = filtered.iter()
@@ -1217,7 +2560,7 @@ pub fn logs(
         .copied()
         .collect();
 
-    write_response(json!({
+    write_response(format, json!({
         "logs": result,
         "count": result.len(),
         "total": total_count,
@@ -1227,136 +2570,955 @@ pub fn logs(
     Ok(())
 }
 
-/// Build a control plane command from CLI action.
-fn build_command(action: ControlAction) -> CliResult<(ControlPlaneCommand, AuthorityContext)> {
-    let authority = AuthorityContext::operator();
+/// Execute a backup management command.
+///
+/// Operates on the archives in `[backup] backup_dir`. `create` boots the data
+/// directory like `start` does, so AeroDB must not be running.
+pub fn backup(config_path: &Path, action: BackupAction, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
+    let data_dir = config.data_path();
+    let manager = BackupManager::new(config.backup.clone())
+        .map_err(|e| CliError::backup_failed(e.to_string()))?;
 
-    let command = match action {
-        ControlAction::Inspect { target } => {
-            let inspection = match target {
-                InspectTarget::Cluster => InspectionCommand::InspectClusterState,
-                InspectTarget::Node { node_id } => {
-                    let uuid = parse_uuid(&node_id)?;
-                    InspectionCommand::InspectNode { node_id: uuid }
-                }
-                InspectTarget::Replication => InspectionCommand::InspectReplicationStatus,
-                InspectTarget::Promotion => InspectionCommand::InspectPromotionState,
-            };
-            ControlPlaneCommand::Inspection(inspection)
-        }
-        ControlAction::Diag { target } => {
-            let diagnostic = match target {
-                DiagTarget::Diagnostics { .. } => DiagnosticCommand::RunDiagnostics,
-                DiagTarget::Wal => DiagnosticCommand::InspectWal,
-                DiagTarget::Snapshots => DiagnosticCommand::InspectSnapshots,
-            };
-            ControlPlaneCommand::Diagnostic(diagnostic)
+    match action {
+        BackupAction::Create { description } => {
+            if !is_initialized(data_dir) {
+                return Err(CliError::not_initialized());
+            }
+
+            // Recovery brings storage up to date with the WAL before the snapshot
+            let (wal_writer, storage_writer, _storage_reader, _schema_loader, _index_manager, _rm, _bpm, _ac) =
+                boot_system(&config)?;
+
+            // This process is the only executor of the booted subsystems
+            let lock = GlobalExecutionLock::new();
+            let schema_dir = data_dir.join("metadata").join("schemas");
+            let metadata = manager
+                .create_backup(
+                    data_dir,
+                    storage_writer.path(),
+                    &schema_dir,
+                    &wal_writer,
+                    description,
+                    &lock,
+                )
+                .map_err(|e| CliError::backup_failed(e.to_string()))?;
+
+            // Clean shutdown - write marker
+            let _ = fs::write(data_dir.join("clean_shutdown"), "");
+
+            write_response(format, json!({
+                "created": true,
+                "backup": metadata
+            }))?;
         }
-        ControlAction::Promote {
-            replica_id, reason, ..
-        } => {
-            let uuid = parse_uuid(&replica_id)?;
-            ControlPlaneCommand::Control(ControlCommand::RequestPromotion {
-                replica_id: uuid,
-                reason,
-            })
+
+        BackupAction::List => {
+            let backups: Vec<_> = manager
+                .list_backups()
+                .map_err(|e| CliError::backup_failed(e.to_string()))?
+                .into_iter()
+                .map(|b| {
+                    json!({
+                        "id": b.id,
+                        "created_at": b.created_at,
+                        "size_bytes": b.size_bytes,
+                        "description": b.description,
+                        "verified": manager.is_verified(&b.id)
+                    })
+                })
+                .collect();
+
+            write_response(format, json!({
+                "count": backups.len(),
+                "backups": backups
+            }))?;
         }
-        ControlAction::Demote {
-            node_id, reason, ..
-        } => {
-            let uuid = parse_uuid(&node_id)?;
-            ControlPlaneCommand::Control(ControlCommand::RequestDemotion {
-                node_id: uuid,
-                reason,
-            })
+
+        BackupAction::Delete { id } => {
+            manager
+                .delete_backup(&id)
+                .map_err(|e| CliError::backup_failed(e.to_string()))?;
+
+            write_response(format, json!({
+                "deleted": true,
+                "backup_id": id
+            }))?;
         }
-        ControlAction::ForcePromote {
-            replica_id,
-            reason,
-            acknowledge_risks,
-            ..
-        } => {
-            let uuid = parse_uuid(&replica_id)?;
-            let risks: Vec<String> = acknowledge_risks
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .collect();
-            ControlPlaneCommand::Control(ControlCommand::ForcePromotion {
-                replica_id: uuid,
-                reason,
-                acknowledged_risks: risks,
-            })
+
+        BackupAction::Verify { id } => {
+            let report = manager
+                .verify_backup(&id)
+                .map_err(|e| CliError::backup_failed(e.to_string()))?;
+
+            write_response(format, json!({
+                "backup_id": id,
+                "ok": report.ok,
+                "files_checked": report.files_checked,
+                "errors": report.errors
+            }))?;
         }
-    };
+    }
 
-    Ok((command, authority))
+    Ok(())
 }
 
-/// Parse a UUID from a string.
-fn parse_uuid(s: &str) -> CliResult<Uuid> {
-    Uuid::parse_str(s).map_err(|e| CliError::config_error(format!("Invalid UUID '{}': {}", s, e)))
-}
+/// Validate a configuration file without booting
+///
+/// Parses the file, runs every check in `config_validator` and prints all
+/// errors and warnings. Fails (non-zero exit) if there is at least one
+/// error; warnings alone do not fail.
+pub fn config_validate(config_path: &Path, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::read(config_path)?;
+    let report = config.validation_report();
+
+    write_response(format, json!({
+        "config": config_path.display().to_string(),
+        "valid": !report.has_errors(),
+        "errors": report.errors,
+        "warnings": report.warnings
+    }))?;
 
-/// Check if a data directory is initialized
-fn is_initialized(data_dir: &Path) -> bool {
-    data_dir.join("wal").exists()
-        && data_dir.join("data").exists()
-        && data_dir.join("metadata").join("schemas").exists()
+    if report.has_errors() {
+        return Err(CliError::config_error(format!(
+            "{} configuration error(s) in {}",
+            report.errors.len(),
+            config_path.display()
+        )));
+    }
+
+    Ok(())
 }
 
-/// Boot the system per BOOT.md with mandatory recovery
+/// Restore the data directory from a backup
 ///
-/// Steps (strict order, all mandatory):
-/// 1. Load schemas
-/// 2. Open WAL reader for replay
-/// 3. Open recovery storage (combined writer + scanner)
-/// 4. Execute RecoveryManager::recover() which:
-///    - Replays WAL from offset 0
-///    - Applies all records to storage
-///    - Rebuilds indexes from storage
-///    - Verifies consistency
-///    - Removes clean_shutdown marker
-/// 5. Return initialized subsystems
+/// AeroDB must not be running. Without `to_time` or `to_offset` the backup
+/// is restored as-is; with one, WAL from the backup and `wal.archive_dir`
+/// is replayed up to that time or offset. Either way the restore report is
+/// printed. `target_dir`, if
+/// given, is restored instead of the configured data directory and is
+/// created if missing.
 ///
-/// FATAL: Any failure at any step halts startup immediately.
-/// No partial startup. No serving without complete recovery.
-fn boot_system(
-    config: &Config,
-) -> CliResult<(
-    WalWriter,
-    StorageWriter,
-    StorageReader,
-    SchemaLoader,
-    IndexManager,
-    ResourceManager,
-    BackpressureManager,
-    AdmissionController,
-)> {
-    use crate::recovery::RecoveryStorage;
+/// A directory that already holds data is only replaced with `confirm`,
+/// a token that an earlier run issued for this exact restore; see
+/// `confirm_restore`.
+pub fn restore(
+    config_path: &Path,
+    backup_id: &str,
+    to_time: Option<&str>,
+    to_offset: Option<WalOffset>,
+    target_dir: Option<&Path>,
+    confirm: Option<&str>,
+    format: OutputFormat,
+) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
+    let backup_dir = Path::new(&config.backup.backup_dir);
+
+    let data_dir = match target_dir {
+        Some(dir) => {
+            fs::create_dir_all(dir).map_err(|e| {
+                CliError::restore_failed(format!(
+                    "Failed to create target directory {}: {}",
+                    dir.display(),
+                    e
+                ))
+            })?;
+            dir
+        }
+        None => config.data_path(),
+    };
 
-    let data_dir = Path::new(&config.data_dir);
+    let mut request_id = None;
+    if holds_data(data_dir) {
+        let payload = json!({
+            "command": RESTORE_COMMAND,
+            "backup_id": backup_id,
+            "to_time": to_time,
+            "to_offset": to_offset.map(|offset| offset.offset),
+            "to_segment": to_offset.and_then(|offset| offset.segment),
+            "target_dir": data_dir,
+        });
+        let confirm = confirm.map(parse_uuid).transpose()?;
+        match confirm_restore(&config, data_dir, &payload.to_string(), confirm)? {
+            Ok(id) => request_id = Some(id),
+            Err(token) => {
+                return write_response(format, json!({
+                    "restored": false,
+                    "outcome": format!("{:?}", CommandOutcome::ConfirmationRequired),
+                    "confirmation_token": token.to_string(),
+                    "message": format!(
+                        "Restore replaces all data in {}. Run again with --confirm {} to proceed.",
+                        data_dir.display(),
+                        token
+                    ),
+                }));
+            }
+        }
+    }
 
-    // Step 1: Load schemas (required for schema validation during recovery)
-    let mut schema_loader = SchemaLoader::new(data_dir);
-    schema_loader
-        .load_all()
-        .map_err(|e| CliError::boot_failed(format!("Schema load failed: {}", e)))?;
+    let wal_archive_dir = config.wal.archive_dir.as_deref().map(Path::new);
+    let result = if let Some(to_offset) = to_offset {
+        RestoreManager::restore_to_offset(
+            data_dir,
+            backup_dir,
+            backup_id,
+            to_offset,
+            wal_archive_dir,
+        )
+    } else if let Some(to_time) = to_time {
+        let target_time = parse_target_time(to_time)?;
+        RestoreManager::restore_to_timestamp(
+            data_dir,
+            backup_dir,
+            backup_id,
+            target_time,
+            wal_archive_dir,
+        )
+    } else {
+        let backup_path = backup_dir.join(format!("{}.tar", backup_id));
+        RestoreManager::restore_from_backup(data_dir, &backup_path)
+    };
 
-    // Step 2: Open WAL reader for replay
-    let wal_path = data_dir.join("wal").join("wal.log");
-    let wal_exists = wal_path.exists();
+    // A confirmed restore's outcome is audited in the directory as it is
+    // now: restored, or left as it was
+    if let Some(request_id) = request_id {
+        let outcome = match &result {
+            Ok(_) => AuditRecord::new(AuditAction::CommandExecuted, AuditOutcome::Success),
+            Err(e) => AuditRecord::new(AuditAction::CommandFailed, AuditOutcome::Failed)
+                .with_error(e.to_string()),
+        };
+        append_audit(data_dir, &outcome.with_command(RESTORE_COMMAND).with_request_id(request_id))?;
+    }
+    let report = result.map_err(|e| CliError::restore_failed(e.to_string()))?;
 
-    // Step 3: Create index manager
-    let indexed_fields: HashSet<String> = HashSet::new();
-    let mut index_manager = IndexManager::new(indexed_fields);
+    write_response(format, json!({
+        "restored": true,
+        "backup_id": backup_id,
+        "report": report
+    }))?;
+
+    Ok(())
+}
+
+/// Name of `aerodb restore` in confirmation tokens and the audit log
+const RESTORE_COMMAND: &str = "restore";
+
+/// Whether a restore target holds anything a restore would replace
+fn holds_data(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some())
+}
+
+/// Confirm a restore over `data_dir`, as `aerodb control` confirms a
+/// control command
+///
+/// Without `confirm`, issues a token for `payload` and returns it as the
+/// error. With one, consumes it, returning the id of the confirmed
+/// request, or fails if the token is unknown, expired, used or was issued
+/// for another restore. Tokens are kept in, and each step audited to,
+/// `data_dir`'s `system/`.
+fn confirm_restore(
+    config: &AeroConfig,
+    data_dir: &Path,
+    payload: &str,
+    confirm: Option<Uuid>,
+) -> CliResult<Result<Uuid, Uuid>> {
+    let request_id = Uuid::new_v4();
+    let audit = |record: AuditRecord| {
+        append_audit(data_dir, &record.with_command(RESTORE_COMMAND).with_request_id(request_id))
+    };
+    audit(
+        AuditRecord::new(AuditAction::CommandRequested, AuditOutcome::Pending)
+            .with_authority(AuthorityContext::operator().level.to_string()),
+    )?;
+
+    let mut flow = load_confirmations(config, data_dir);
+    let Some(token_id) = confirm else {
+        let token = flow.request_confirmation(RESTORE_COMMAND, None, payload);
+        save_confirmations(data_dir, &flow)?;
+        audit(
+            AuditRecord::new(AuditAction::ConfirmationRequested, AuditOutcome::Pending)
+                .with_confirmation_token(token.id()),
+        )?;
+        return Ok(Err(token.id()));
+    };
+
+    audit(
+        AuditRecord::new(AuditAction::ConfirmationProvided, AuditOutcome::Pending)
+            .with_confirmation_token(token_id),
+    )?;
+    let result = flow.confirm(token_id, RESTORE_COMMAND, None, payload);
+    save_confirmations(data_dir, &flow)?;
+    match result {
+        ConfirmationResult::Proceed { .. } => Ok(Ok(request_id)),
+        ConfirmationResult::Abort { status, .. } => {
+            let e = ControlPlaneError::from_confirmation_status(status, RESTORE_COMMAND);
+            audit(
+                AuditRecord::new(AuditAction::CommandRejected, AuditOutcome::Rejected)
+                    .with_error(e.message()),
+            )?;
+            Err(CliError::confirmation_required(e.message()))
+        }
+    }
+}
+
+/// Open the audit log of the served data directory, creating it if needed
+fn open_audit_log(config: &AeroConfig) -> CliResult<Arc<dyn AuditLog>> {
+    FileAuditLog::open_in(config.data_path())
+        .map(|log| Arc::new(log) as Arc<dyn AuditLog>)
+        .map_err(|e| CliError::boot_failed(format!("Failed to open audit log: {}", e)))
+}
+
+/// Append to the audit log of `data_dir`, creating it if needed
+fn append_audit(data_dir: &Path, record: &AuditRecord) -> CliResult<()> {
+    FileAuditLog::open_in(data_dir)
+        .and_then(|log| log.append(record))
+        .map_err(|e| CliError::io_error(format!("Failed to write audit log: {}", e)))
+}
+
+/// Upgrade the data directory's WAL format to `WAL_FORMAT_VERSION`
+///
+/// AeroDB must not be running. Reports the format versions before and after
+/// and the number of transforms applied (0 if already current).
+pub fn upgrade(config_path: &Path, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
+    let data_dir = config.data_path();
+
+    let marker = VersionMarker::load(data_dir)
+        .map_err(|e| CliError::upgrade_failed(e.to_string()))?
+        .ok_or_else(|| {
+            CliError::upgrade_failed(format!(
+                "No version marker in {}; nothing to upgrade",
+                data_dir.display()
+            ))
+        })?;
+
+    let from = marker.wal_format_version;
+    let steps = upgrade_wal_format(data_dir, from, WAL_FORMAT_VERSION)
+        .map_err(|e| CliError::upgrade_failed(e.to_string()))?;
+
+    write_response(format, json!({
+        "upgraded": steps > 0,
+        "from_wal_format": from,
+        "to_wal_format": WAL_FORMAT_VERSION,
+        "steps": steps
+    }))?;
+
+    Ok(())
+}
+
+/// Run the `doctor` checks and print their report
+///
+/// The report is text unless `json` is set. Warnings and failures are
+/// returned as errors whose exit codes (1 and 2) tell them apart.
+pub fn doctor(config_path: &Path, json: bool, format: OutputFormat) -> CliResult<()> {
+    let report = super::doctor::run_checks(config_path);
+
+    if json {
+        write_response(format, report.to_json())?;
+    } else {
+        print!("{}", report.render_text());
+    }
+
+    match report.worst() {
+        Severity::Pass => Ok(()),
+        Severity::Warn => Err(CliError::doctor_warnings("aerodb doctor reported warnings")),
+        Severity::Fail => Err(CliError::doctor_failed("aerodb doctor reported failures")),
+    }
+}
+
+/// Execute a developer tooling command.
+///
+/// `seed` reports progress on stderr and prints the per-collection counts
+/// when done.
+pub fn dx(config_path: &Path, action: DxAction, format: OutputFormat) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
+
+    match action {
+        DxAction::Seed { spec, seed, truncate } => {
+            if !is_initialized(config.data_path()) {
+                return Err(CliError::not_initialized());
+            }
+            let spec = SeedSpec::from_file(&spec)
+                .map_err(|e| CliError::seed_failed(e.to_string()))?;
+
+            let (mut wal_writer, mut storage_writer, mut storage_reader, mut schema_loader, mut index_manager, rm, bpm, ac) =
+                boot_system(&config)?;
+            let mut statistics = open_statistics(&config)?;
+            let handler = ApiHandler::new("default");
+            let mut subsystems = Subsystems {
+                schema_loader: &mut schema_loader,
+                wal_writer: &mut wal_writer,
+                storage_writer: &mut storage_writer,
+                storage_reader: &mut storage_reader,
+                index_manager: &mut index_manager,
+                statistics: &mut statistics,
+                resource_manager: &rm,
+                backpressure_manager: &bpm,
+                admission_controller: &ac,
+                query_limits: &config.query_limits,
+            };
+
+            let report = seed::seed(
+                &spec,
+                seed,
+                truncate.as_deref(),
+                &handler,
+                &mut subsystems,
+                |p| eprintln!("{}: {}/{}", p.collection, p.inserted, p.total),
+            )
+            .map_err(|e| CliError::seed_failed(e.to_string()))?;
+
+            // Persist incremental statistics (advisory: failure is not fatal)
+            let _ = statistics.save();
+
+            write_response(format, json!({
+                "seed": seed,
+                "truncated": report.truncated,
+                "inserted": report.inserted,
+            }))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Export a collection to `out`.
+///
+/// `filter` is a query filter as JSON. Prints the number of documents
+/// exported.
+pub fn export(
+    config_path: &Path,
+    options: ExportOptions,
+    out: &Path,
+    filter: Option<&str>,
+    format: OutputFormat,
+) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
+    let options = match filter {
+        Some(filter) => {
+            let filter: Value = serde_json::from_str(filter)
+                .map_err(|e| CliError::transfer_failed(format!("Invalid --filter: {}", e)))?;
+            options.with_filter(filter)
+        }
+        None => options,
+    };
+
+    let transfer = open_transfer(&config)?;
+    let file = fs::File::create(out).map_err(|e| {
+        CliError::transfer_failed(format!("Failed to create {}: {}", out.display(), e))
+    })?;
+    let report = transfer
+        .export(&options, &mut std::io::BufWriter::new(file))
+        .map_err(|e| CliError::transfer_failed(e.to_string()))?;
+
+    write_response(format, json!({
+        "collection": options.collection,
+        "out": out.display().to_string(),
+        "exported": report.exported,
+    }))
+}
+
+/// Import `file` into a collection.
+///
+/// `column_map` entries are `HEADER=FIELD`. Rejected rows go to `rejects`
+/// (default `<file>.rejects.jsonl`), which is only created if a row is
+/// rejected. Prints the rows read, inserted, updated and rejected.
+pub fn import(
+    config_path: &Path,
+    mut options: ImportOptions,
+    column_map: &[String],
+    file: &Path,
+    rejects: Option<&Path>,
+    format: OutputFormat,
+) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
+    for entry in column_map {
+        let (header, field) = entry.split_once('=').ok_or_else(|| {
+            CliError::transfer_failed(format!("Invalid --map '{}': expected HEADER=FIELD", entry))
+        })?;
+        options = options.with_column(header, field);
+    }
+    let rejects_path = match rejects {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let mut name = file.as_os_str().to_owned();
+            name.push(".rejects.jsonl");
+            name.into()
+        }
+    };
+
+    let transfer = open_transfer(&config)?;
+    let input = fs::File::open(file).map_err(|e| {
+        CliError::transfer_failed(format!("Failed to open {}: {}", file.display(), e))
+    })?;
+    let mut rejects = RejectsFile::new(&rejects_path);
+    let report = transfer
+        .import(&options, &mut std::io::BufReader::new(input), &mut rejects)
+        .map_err(|e| CliError::transfer_failed(e.to_string()))?;
+
+    write_response(format, json!({
+        "collection": options.collection,
+        "read": report.read,
+        "inserted": report.inserted,
+        "updated": report.updated,
+        "rejected": report.rejected,
+        "rejects": rejects.written().map(|path| path.display().to_string()),
+    }))
+}
+
+/// Boot the database for a one-shot import or export
+fn open_transfer(config: &AeroConfig) -> CliResult<LocalTransfer> {
+    if !is_initialized(config.data_path()) {
+        return Err(CliError::not_initialized());
+    }
+    let (wal_writer, storage_writer, storage_reader, schema_loader, index_manager, rm, bpm, ac) =
+        boot_system(config)?;
+    let handler =
+        ApiHandler::new("default").with_replica_gate(open_replica_gate(config, &wal_writer)?);
+    let engine = Engine {
+        wal_writer,
+        storage_writer,
+        storage_reader,
+        schema_loader,
+        index_manager,
+        statistics: open_statistics(config)?,
+        resource_manager: Arc::new(rm),
+        backpressure_manager: Arc::new(bpm),
+        admission_controller: Arc::new(ac),
+        query_limits: config.query_limits.clone(),
+    };
+    Ok(LocalTransfer::new(handler, engine))
+}
+
+/// Execute a WAL diagnostic command.
+///
+/// `inspect` writes one compact JSON object per record to stdout,
+/// regardless of `--output`, so the listing can be piped to line-based
+/// tools.
+pub fn wal(config_path: &Path, action: WalAction) -> CliResult<()> {
+    let config = AeroConfig::load(config_path)?;
+
+    match action {
+        WalAction::Inspect {
+            segment,
+            from_offset,
+            limit,
+        } => {
+            let wal_dir = config.data_path().join("wal");
+            if let Some(mut reader) = open_wal_for_inspection(&wal_dir, segment)? {
+                inspect_wal(&mut reader, from_offset, limit, &mut std::io::stdout().lock())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Open the WAL in `wal_dir`, starting at `segment` if given.
+///
+/// Returns `None` if the directory holds no WAL yet.
+pub(super) fn open_wal_for_inspection(
+    wal_dir: &Path,
+    segment: Option<u64>,
+) -> CliResult<Option<WalReader>> {
+    let failed = |e: crate::wal::WalError| CliError::wal_inspect_failed(e.to_string());
+
+    let reader = match (detect_layout(wal_dir).map_err(failed)?, segment) {
+        (WalLayout::Empty, _) => return Ok(None),
+        (WalLayout::Legacy(path), None) => WalReader::open(&path).map_err(failed)?,
+        (WalLayout::Legacy(_), Some(_)) => {
+            return Err(CliError::wal_inspect_failed(
+                "--segment given but the WAL is a single wal.log file",
+            ))
+        }
+        (WalLayout::Segmented(_), None) => WalReader::open_segments(wal_dir).map_err(failed)?,
+        (WalLayout::Segmented(segments), Some(index)) => {
+            let files: Vec<_> = segments
+                .into_iter()
+                .skip_while(|s| s.index < index)
+                .collect();
+            if files.first().map(|s| s.index) != Some(index) {
+                return Err(CliError::wal_inspect_failed(format!(
+                    "WAL segment {} not found",
+                    index
+                )));
+            }
+            WalReader::open_history(files.into_iter().map(|s| s.path).collect()).map_err(failed)?
+        }
+    };
+    Ok(Some(reader))
+}
+
+/// Write one JSON line per record, skipping records before `from_offset`
+/// in the first file read. Returns the number of records written.
+///
+/// The first unreadable record ends the listing with an error, after the
+/// records before it have been written.
+fn inspect_wal(
+    reader: &mut WalReader,
+    from_offset: u64,
+    limit: Option<usize>,
+    out: &mut impl std::io::Write,
+) -> CliResult<usize> {
+    let mut written = 0;
+    let mut first_segment = None;
+
+    for entry in reader.iter_records() {
+        if limit.is_some_and(|limit| written >= limit) {
+            break;
+        }
+        let entry = entry.map_err(|e| CliError::wal_inspect_failed(e.to_string()))?;
+        if *first_segment.get_or_insert(entry.segment) == entry.segment
+            && entry.offset < from_offset
+        {
+            continue;
+        }
+        writeln!(out, "{}", wal_record_json(&entry))?;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+/// JSON form of a WAL record for `wal inspect`.
+fn wal_record_json(entry: &PositionedRecord) -> Value {
+    let record = &entry.record;
+    json!({
+        "segment": entry.segment,
+        "offset": entry.offset,
+        "sequence": record.sequence_number,
+        "type": record.record_type.name(),
+        "collection": record.payload.collection_id,
+        "key": record.payload.document_id,
+        "schema": record.payload.schema_id,
+        "schema_version": record.payload.schema_version,
+        "body_bytes": record.payload.document_body.len(),
+        "commit_timestamp_ms": record.commit_timestamp_ms,
+        "epoch": record.epoch,
+    })
+}
+
+/// Parse a `--to-time` value (RFC 3339).
+fn parse_target_time(value: &str) -> CliResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| {
+            CliError::restore_failed(format!(
+                "Invalid --to-time '{}': expected RFC 3339 (e.g. 2026-02-07T13:45:00Z): {}",
+                value, e
+            ))
+        })
+}
+
+/// Build a control plane command from CLI action, with its `--confirm` token.
+fn build_command(
+    action: ControlAction,
+) -> CliResult<(ControlPlaneCommand, AuthorityContext, Option<Uuid>)> {
+    let authority = AuthorityContext::operator();
+    let mut confirm = None;
+
+    let command = match action {
+        ControlAction::Inspect { target } => {
+            let inspection = match target {
+                InspectTarget::Cluster => InspectionCommand::InspectClusterState,
+                InspectTarget::Node { node_id } => {
+                    let uuid = parse_uuid(&node_id)?;
+                    InspectionCommand::InspectNode { node_id: uuid }
+                }
+                InspectTarget::Replication => InspectionCommand::InspectReplicationStatus,
+                InspectTarget::Promotion => InspectionCommand::InspectPromotionState,
+                InspectTarget::Stats => InspectionCommand::InspectStatistics,
+                InspectTarget::Tenant { tenant_id } => {
+                    let uuid = parse_uuid(&tenant_id)?;
+                    InspectionCommand::InspectTenantUsage { tenant_id: uuid }
+                }
+                InspectTarget::Jobs => InspectionCommand::InspectScheduledJobs,
+            };
+            ControlPlaneCommand::Inspection(inspection)
+        }
+        ControlAction::Diag { target } => {
+            let diagnostic = match target {
+                DiagTarget::Diagnostics { confirm: token } => {
+                    confirm = token;
+                    DiagnosticCommand::RunDiagnostics
+                }
+                DiagTarget::Wal => DiagnosticCommand::InspectWal,
+                DiagTarget::Snapshots => DiagnosticCommand::InspectSnapshots,
+                DiagTarget::CrashReports { id } => DiagnosticCommand::InspectCrashReports { id },
+                DiagTarget::Audit { verify } => DiagnosticCommand::InspectAuditLog { verify },
+            };
+            ControlPlaneCommand::Diagnostic(diagnostic)
+        }
+        ControlAction::Promote {
+            replica_id,
+            reason,
+            confirm: token,
+        } => {
+            confirm = token;
+            let uuid = parse_uuid(&replica_id)?;
+            ControlPlaneCommand::Control(ControlCommand::RequestPromotion {
+                replica_id: uuid,
+                reason,
+            })
+        }
+        ControlAction::Demote {
+            node_id,
+            reason,
+            confirm: token,
+        } => {
+            confirm = token;
+            let uuid = parse_uuid(&node_id)?;
+            ControlPlaneCommand::Control(ControlCommand::RequestDemotion {
+                node_id: uuid,
+                reason,
+            })
+        }
+        ControlAction::ForcePromote {
+            replica_id,
+            reason,
+            acknowledge_risks,
+            confirm: token,
+        } => {
+            confirm = token;
+            let uuid = parse_uuid(&replica_id)?;
+            let risks: Vec<String> = acknowledge_risks
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect();
+            ControlPlaneCommand::Control(ControlCommand::ForcePromotion {
+                replica_id: uuid,
+                reason,
+                acknowledged_risks: risks,
+            })
+        }
+    };
+
+    let confirm = confirm.as_deref().map(parse_uuid).transpose()?;
+    Ok((command, authority, confirm))
+}
+
+/// Parse a UUID from a string.
+fn parse_uuid(s: &str) -> CliResult<Uuid> {
+    Uuid::parse_str(s).map_err(|e| CliError::config_error(format!("Invalid UUID '{}': {}", s, e)))
+}
+
+/// Check if a data directory is initialized
+pub(super) fn is_initialized(data_dir: &Path) -> bool {
+    data_dir.join("wal").exists()
+        && data_dir.join("data").exists()
+        && data_dir.join("metadata").join("schemas").exists()
+}
+
+/// Open the planner statistics store under `metadata/stats`
+fn open_statistics(config: &AeroConfig) -> CliResult<Statistics> {
+    Statistics::open(config.data_path(), config.statistics.clone())
+        .map_err(|e| CliError::boot_failed(format!("Statistics load failed: {}", e)))
+}
+
+/// Confirmation tokens handed from the requesting `aerodb control` process
+/// to the confirming one
+const CONFIRMATIONS_PATH: &str = "system/confirmations.json";
+
+/// Confirmation flow resuming the tokens in `data_dir`'s
+/// `system/confirmations.json`.
+///
+/// The file is a hand-off between two CLI invocations, not durable state:
+/// if it is missing or unreadable the operator simply requests a new token.
+fn load_confirmations(config: &AeroConfig, data_dir: &Path) -> ConfirmationFlow {
+    let ttl = Duration::from_secs(config.control_plane.confirmation_ttl_secs);
+    let tokens: Vec<ConfirmationToken> = fs::read(data_dir.join(CONFIRMATIONS_PATH))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    ConfirmationFlow::new()
+        .with_max_token_age(ttl)
+        .with_tokens(tokens)
+}
+
+/// Store issued tokens for the next invocation
+fn save_confirmations(data_dir: &Path, flow: &ConfirmationFlow) -> CliResult<()> {
+    let system_dir = data_dir.join("system");
+    if !system_dir.is_dir() {
+        return Ok(());
+    }
+    let path = data_dir.join(CONFIRMATIONS_PATH);
+    let tmp = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec(flow.tokens())
+        .map_err(|e| CliError::io_error(format!("Failed to serialize confirmations: {}", e)))?;
+    fs::write(&tmp, bytes)
+        .and_then(|()| fs::rename(&tmp, &path))
+        .map_err(|e| CliError::io_error(format!("Failed to save confirmations: {}", e)))
+}
+
+/// Open the tenant quotas and usage stored in `system/tenant_usage.json`
+fn open_tenant_quotas(config: &AeroConfig) -> CliResult<TenantQuotas> {
+    TenantQuotas::open(config.data_path(), Quotas::default(), DEFAULT_PERSIST_INTERVAL)
+        .map_err(|e| CliError::config_error(format!("Tenant usage load failed: {}", e)))
+}
+
+/// Read the jobs in `_system.schedules` with their last run in
+/// `_system.job_runs`
+fn open_scheduled_jobs(config: &AeroConfig) -> CliResult<Vec<ScheduledJobView>> {
+    let data_dir = config.data_path();
+    let load_failed = |e: FunctionError| CliError::config_error(format!("Job load failed: {}", e));
+    let mut jobs = FileJobStore::new(data_dir.join(SCHEDULES_PATH))
+        .load()
+        .map_err(load_failed)?;
+    let runs = FileJobRunStore::new(data_dir.join(JOB_RUNS_PATH))
+        .load()
+        .map_err(load_failed)?;
+    jobs.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(jobs
+        .into_iter()
+        .map(|job| {
+            let last = runs.iter().rev().find(|run| run.job == job.name);
+            ScheduledJobView {
+                last_run: job.last_run.map(SystemTime::from),
+                next_run: job.next_run.map(SystemTime::from),
+                last_outcome: last.map(|run| run.outcome.as_str().to_string()),
+                last_duration_ms: last.map(|run| run.duration_ms),
+                name: job.name,
+                function: job.function_name,
+                cron: job.cron,
+                enabled: job.enabled,
+            }
+        })
+        .collect())
+}
+
+/// Verify every completed snapshot under `snapshots/`
+fn verify_snapshots(config: &AeroConfig) -> CliResult<Vec<SnapshotIntegrity>> {
+    let data_dir = config.data_path();
+    let ids = SnapshotManager::list_snapshots(data_dir)
+        .map_err(|e| CliError::config_error(format!("Snapshot listing failed: {}", e)))?;
+    Ok(ids
+        .into_iter()
+        .map(|snapshot_id| SnapshotIntegrity {
+            result: SnapshotManager::verify(data_dir, &snapshot_id).map_err(|e| e.to_string()),
+            snapshot_id,
+        })
+        .collect())
+}
+
+/// Measure the lag of the Replicas recorded in `system/replicas.json`
+fn open_replica_lag(config: &AeroConfig) -> CliResult<Vec<ReplicaLag>> {
+    let data_dir = config.data_path();
+    let replicas =
+        ReplicaTracker::load(data_dir).map_err(|e| CliError::config_error(e.message))?;
+    if replicas.is_empty() {
+        return Ok(Vec::new());
+    }
+    replica_lag(&data_dir.join("wal"), &replicas)
+        .map_err(|e| CliError::config_error(format!("Replica lag failed: {}", e.message)))
+}
+
+/// Synchronous replication state of this primary, as stored by `serve`.
+fn open_sync_replication(config: &AeroConfig) -> CliResult<SyncReplicationView> {
+    let sync = config.sync_replication_config()?;
+    let data_dir = config.data_path();
+    let connected_replicas = ReplicaTracker::load(data_dir)
+        .map_err(|e| CliError::config_error(e.message))?
+        .iter()
+        .filter(|progress| progress.connected)
+        .count();
+    Ok(SyncReplicationView {
+        mode: sync.mode.as_str().to_string(),
+        min_sync_replicas: sync.min_sync_replicas,
+        connected_replicas,
+        quorum_healthy: connected_replicas >= sync.min_sync_replicas,
+        degraded: sync.mode == SyncMode::Quorum && SyncState::load(data_dir).degraded,
+    })
+}
+
+/// Freshness of this replica as of its stored applied offset.
+fn open_replica_freshness(config: &AeroConfig) -> CliResult<ReplicaFreshness> {
+    let stored =
+        AppliedOffset::load(config.data_path()).map_err(|e| CliError::config_error(e.message))?;
+    Ok(ReplicaFreshness::new(
+        stored.map_or(0, |offset| offset.current_as_of_ms),
+    ))
+}
+
+/// API gate for this node: a replica serves reads and refuses writes.
+///
+/// The stdin API does not follow the primary, so a replica stays current
+/// as of its stored offset or last WAL record and bounded reads are
+/// refused once they exceed their bound.
+fn open_replica_gate(config: &AeroConfig, wal_writer: &WalWriter) -> CliResult<ReplicaGate> {
+    let replication = config.to_replication_config()?;
+    if !replication.is_replica() {
+        return Ok(ReplicaGate::Primary);
+    }
+    let primary_address = replication
+        .primary_address
+        .expect("Replica must have primary_address after validation");
+    let freshness = open_replica_freshness(config)?;
+    freshness.observe(wal_writer.last_commit_timestamp_ms());
+    Ok(ReplicaGate::replica(primary_address, Arc::new(freshness)))
+}
+
+/// Boot the system per BOOT.md with mandatory recovery
+///
+/// Steps (strict order, all mandatory):
+/// 1. Load schemas
+/// 2. Open WAL reader for replay
+/// 3. Open recovery storage (combined writer + scanner)
+/// 4. Execute RecoveryManager::recover() which:
+///    - Replays WAL from offset 0
+///    - Applies all records to storage
+///    - Rebuilds indexes from storage
+///    - Verifies consistency
+///    - Removes clean_shutdown marker
+/// 5. Return initialized subsystems
+///
+/// FATAL: Any failure at any step halts startup immediately.
+/// No partial startup. No serving without complete recovery.
+fn boot_system(
+    config: &AeroConfig,
+) -> CliResult<(
+    WalWriter,
+    StorageWriter,
+    StorageReader,
+    SchemaLoader,
+    IndexManager,
+    ResourceManager,
+    BackpressureManager,
+    AdmissionController,
+)> {
+    use crate::recovery::RecoveryStorage;
+
+    let data_dir = Path::new(&config.server.data_dir);
+    JsonLogger::set_min_severity(config.observability.log_level);
+
+    // Step 1: Load schemas (required for schema validation during recovery)
+    let mut schema_loader = SchemaLoader::new(data_dir);
+    schema_loader
+        .load_all()
+        .map_err(|e| CliError::boot_failed(format!("Schema load failed: {}", e)))?;
+
+    // Step 2: Open WAL reader for replay (legacy wal.log or numbered segments)
+    let wal_layout = detect_layout(&data_dir.join("wal"))
+        .map_err(|e| CliError::boot_failed(format!("WAL layout check failed: {}", e)))?;
+    let wal_exists = wal_layout != WalLayout::Empty;
+
+    // Step 3: Create index manager (the expiry sweeper scans each TTL
+    // schema's expiry field)
+    let indexed_fields: HashSet<String> = schema_loader
+        .all_schemas()
+        .filter_map(|schema| schema.ttl.as_ref())
+        .map(|ttl| ttl.expiry_field().to_string())
+        .collect();
+    let mut index_manager = IndexManager::new(indexed_fields);
 
     // Step 4: Execute RecoveryManager::recover() - MANDATORY
     // This performs: WAL replay -> Index rebuild -> Consistency verification
-    let recovery_manager = RecoveryManager::new(data_dir);
+    let recovery_manager =
+        RecoveryManager::new(data_dir).with_replay_parallelism(config.recovery.parallelism);
 
     let (storage_writer, storage_reader) = if wal_exists {
         // Open WAL reader
-        let mut wal_reader = WalReader::open(&wal_path)
+        let mut wal_reader = WalReader::open_from_data_dir(data_dir)
             .map_err(|e| CliError::boot_failed(format!("WAL reader open failed: {}", e)))?;
 
         // Open recovery storage (implements both StorageApply + StorageScan)
@@ -1401,9 +3563,17 @@ fn boot_system(
         (storage_writer, storage_reader)
     };
 
-    // Step 5: Open WAL writer for new writes
-    let wal_writer = WalWriter::open(data_dir)
-        .map_err(|e| CliError::boot_failed(format!("WAL writer open failed: {}", e)))?;
+    // Step 5: Rebuild indexes from the recovered storage
+    rebuild_indexes(data_dir, &mut index_manager)?;
+
+    // Step 6: Open WAL writer for new writes
+    // A torn record at the tail of the last segment is discarded here
+    let wal_writer = WalWriter::open_segmented(
+        data_dir,
+        config.wal_sync_config()?,
+        config.wal_segment_config()?,
+    )
+    .map_err(|e| CliError::boot_failed(format!("WAL writer open failed: {}", e)))?;
 
     // Recovery complete - system may now enter SERVING state
     // Initialize hardening components
@@ -1423,25 +3593,109 @@ fn boot_system(
     ))
 }
 
+/// Rebuild `index_manager` from the storage file, logging progress.
+///
+/// Large datasets take a while to index; progress is reported every
+/// `REBUILD_PROGRESS_INTERVAL` documents so a slow boot is visibly alive.
+fn rebuild_indexes(data_dir: &Path, index_manager: &mut IndexManager) -> CliResult<()> {
+    if !data_dir.join("data").join("documents.dat").exists() {
+        return Ok(());
+    }
+    let mut reader = StorageReader::open_from_data_dir(data_dir)
+        .map_err(|e| CliError::boot_failed(format!("Storage reader open failed: {}", e)))?;
+
+    index_manager
+        .rebuild_with_progress(&mut reader, |progress| {
+            let total = progress
+                .total
+                .map(|t| format!(" of {}", t))
+                .unwrap_or_default();
+            if progress.complete {
+                eprintln!(
+                    "Index rebuild complete: {} documents in {:.1}s",
+                    progress.documents_processed,
+                    progress.elapsed.as_secs_f64()
+                );
+            } else {
+                eprintln!(
+                    "Rebuilding indexes: {}{} documents ({:.1}s)",
+                    progress.documents_processed,
+                    total,
+                    progress.elapsed.as_secs_f64()
+                );
+            }
+        })
+        .map_err(|e| CliError::boot_failed(format!("Index rebuild failed (FATAL): {}", e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::errors::CliErrorCode;
     use super::*;
+    use crate::functions::JobDefinition;
+    use crate::replication::replication_offset_path;
     use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
     use tempfile::TempDir;
 
     fn create_config(temp_dir: &TempDir) -> std::path::PathBuf {
-        let config_path = temp_dir.path().join("aerodb.json");
-        let data_dir = temp_dir.path().join("data");
-
-        let config = json!({
-            "data_dir": data_dir.to_string_lossy()
-        });
+        write_toml(temp_dir, &temp_dir.path().join("data"), "")
+    }
 
-        fs::write(&config_path, config.to_string()).unwrap();
+    /// Write aerodb.toml with `data_dir` in `[server]`, followed by `rest`
+    fn write_toml(temp_dir: &TempDir, data_dir: &Path, rest: &str) -> std::path::PathBuf {
+        let config_path = temp_dir.path().join("aerodb.toml");
+        let config = format!("[server]\ndata_dir = '{}'\n{}", data_dir.display(), rest);
+        fs::write(&config_path, config).unwrap();
         config_path
     }
 
+    #[test]
+    fn test_cli_definition_is_consistent() {
+        use clap::{CommandFactory, Parser};
+        super::super::args::Cli::command().debug_assert();
+
+        let cli = super::super::args::Cli::try_parse_from([
+            "aerodb", "deploy", "status", "--output", "table",
+        ])
+        .unwrap();
+        assert_eq!(cli.output, OutputFormat::Table);
+
+        // Commands that write files keep `--output` for the path, and take
+        // the format as `--format` or before the command
+        let cases: [(&[&str], OutputFormat); 3] = [
+            (&["deploy", "docker", "--output", "dc.yml", "--format", "table"], OutputFormat::Table),
+            (&["deploy", "docker", "--out-file", "dc.yml"], OutputFormat::Json),
+            (
+                &["--output", "pretty", "deploy", "docker", "--output", "dc.yml"],
+                OutputFormat::Pretty,
+            ),
+        ];
+        for (args, format) in cases {
+            let cli =
+                super::super::args::Cli::try_parse_from(["aerodb"].iter().chain(args)).unwrap();
+            assert_eq!(cli.output, format);
+            assert!(matches!(
+                cli.command,
+                Command::Deploy {
+                    action: DeployAction::Docker { ref out_file, .. },
+                    ..
+                } if out_file == Path::new("dc.yml")
+            ));
+        }
+        let cli = super::super::args::Cli::try_parse_from([
+            "aerodb", "schema", "types", "--output", "./gen",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Schema {
+                action: SchemaAction::Types { ref out_dir, .. },
+                ..
+            } if out_dir == Path::new("./gen")
+        ));
+    }
+
     #[test]
     fn test_init_creates_directories() {
         let temp_dir = TempDir::new().unwrap();
@@ -1449,7 +3703,7 @@ mod tests {
         let data_dir = temp_dir.path().join("data");
 
         // Init should succeed
-        init(&config_path).unwrap();
+        init(&config_path, false, None, OutputFormat::Json).unwrap();
 
         // Check directories exist
         assert!(data_dir.join("wal").exists());
@@ -1463,10 +3717,10 @@ mod tests {
         let config_path = create_config(&temp_dir);
 
         // First init succeeds
-        init(&config_path).unwrap();
+        init(&config_path, false, None, OutputFormat::Json).unwrap();
 
         // Second init fails
-        let result = init(&config_path);
+        let result = init(&config_path, false, None, OutputFormat::Json);
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err().code(),
@@ -1474,91 +3728,824 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_init_force_reinitializes_partial_init() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = create_config(&temp_dir);
+        let data_dir = temp_dir.path().join("data");
+
+        // A failed init: WAL directory and data, no init marker
+        fs::create_dir_all(data_dir.join("wal")).unwrap();
+        fs::write(data_dir.join("wal").join("wal.log"), b"partial").unwrap();
+        assert!(matches!(
+            VersionChecker::new(&data_dir).check(),
+            crate::version::VersionCheck::Incompatible(VersionError::PartialInitialization { .. })
+        ));
+
+        let confirm = data_dir.to_string_lossy().to_string();
+        init(&config_path, true, Some(&confirm), OutputFormat::Json).unwrap();
+
+        assert!(!data_dir.join("wal").join("wal.log").exists());
+        assert!(is_initialized(&data_dir));
+        assert!(matches!(
+            VersionChecker::new(&data_dir).check(),
+            crate::version::VersionCheck::Compatible
+        ));
+    }
+
+    #[test]
+    fn test_init_force_requires_confirmation() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = create_config(&temp_dir);
+        let data_dir = temp_dir.path().join("data");
+        init(&config_path, false, None, OutputFormat::Json).unwrap();
+        fs::write(data_dir.join("data").join("keep"), b"data").unwrap();
+
+        for confirm in [None, Some("/some/other/dir")] {
+            let err = init(&config_path, true, confirm, OutputFormat::Json).unwrap_err();
+            assert_eq!(err.code(), &CliErrorCode::ConfirmationRequired);
+        }
+        assert!(data_dir.join("data").join("keep").exists());
+
+        // Refused while another live process holds the directory
+        let mut server = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let mut marker = VersionMarker::load(&data_dir).unwrap().unwrap();
+        marker.process_lock = Some(ProcessLock {
+            pid: server.id(),
+            ..ProcessLock::current()
+        });
+        marker.save(&data_dir).unwrap();
+        let confirm = data_dir.to_string_lossy().to_string();
+        let result = init(&config_path, true, Some(&confirm), OutputFormat::Json);
+        server.kill().unwrap();
+        server.wait().unwrap();
+        assert!(result.unwrap_err().message().contains("in use by process"));
+        assert!(data_dir.join("data").join("keep").exists());
+
+        // A lock left by this process's PID is stale
+        VersionChecker::new(&data_dir).update_access().unwrap();
+        init(&config_path, true, Some(&confirm), OutputFormat::Json).unwrap();
+        assert!(!data_dir.join("data").join("keep").exists());
+    }
+
     #[test]
     fn test_start_requires_init() {
         let temp_dir = TempDir::new().unwrap();
         let config_path = create_config(&temp_dir);
 
         // Start without init fails
-        let result = start(&config_path);
+        let result = start(&config_path, OutputFormat::Json);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().code(), &CliErrorCode::NotInitialized);
     }
 
     #[test]
-    fn test_config_validates_sync_mode() {
+    fn test_control_confirmation_carries_over_between_invocations() {
         let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("aerodb.json");
+        let config_path = create_config(&temp_dir);
         let data_dir = temp_dir.path().join("data");
+        init(&config_path, false, None, OutputFormat::Json).unwrap();
+        let promote = |confirm: Option<String>| ControlAction::Promote {
+            replica_id: Uuid::nil().to_string(),
+            reason: None,
+            confirm,
+        };
 
-        let config = json!({
-            "data_dir": data_dir.to_string_lossy(),
-            "wal_sync_mode": "none"  // Invalid!
-        });
+        control(&config_path, promote(None), OutputFormat::Json).unwrap();
+        let tokens: Vec<ConfirmationToken> =
+            serde_json::from_slice(&fs::read(data_dir.join(CONFIRMATIONS_PATH)).unwrap()).unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert!(!tokens[0].is_consumed());
+
+        let token = tokens[0].id().to_string();
+        control(&config_path, promote(Some(token)), OutputFormat::Json).unwrap();
+        let tokens: Vec<ConfirmationToken> =
+            serde_json::from_slice(&fs::read(data_dir.join(CONFIRMATIONS_PATH)).unwrap()).unwrap();
+        assert!(tokens[0].is_consumed());
+
+        let audit = fs::read_to_string(data_dir.join(AUDIT_LOG_PATH)).unwrap();
+        for action in ["CONFIRMATION_REQUESTED", "CONFIRMATION_PROVIDED", "COMMAND_EXECUTED"] {
+            assert!(audit.contains(action), "missing {}", action);
+        }
+    }
+
+    #[test]
+    fn test_config_rejects_invalid_job_cron() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let jobs = "[[scheduler.jobs]]\nname = 'report'\ncron = 'hourly'\nfunction = 'report'\n";
+        let config_path = write_toml(&temp_dir, &data_dir, jobs);
+
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert!(err.message().contains("Scheduler config error"));
+    }
+
+    #[test]
+    fn test_scheduled_jobs_are_read_from_system_collections() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = create_config(&temp_dir);
+        let mut config = AeroConfig::load(&config_path).unwrap();
+        config.scheduler.jobs.push(JobDefinition::new("report", "0 2 * * *", "build_report"));
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let scheduler = Scheduler::open(config.data_path(), &config.scheduler).unwrap();
+        scheduler.register_function("build_report", Arc::new(|| Ok("built".to_string())));
+        let job = scheduler.job("report").unwrap();
+        let due = job.next_run.unwrap();
+        for handle in rt.block_on(async { scheduler.run_due_at(due) }) {
+            rt.block_on(handle).unwrap();
+        }
+
+        let views = open_scheduled_jobs(&config).unwrap();
+        assert_eq!(views.len(), 1);
+        assert_eq!(views[0].name, "report");
+        assert_eq!(views[0].last_run, Some(SystemTime::from(due)));
+        assert_eq!(
+            views[0].next_run,
+            Some(SystemTime::from(due + chrono::Duration::days(1)))
+        );
+        assert_eq!(views[0].last_outcome.as_deref(), Some("succeeded"));
+    }
 
-        fs::write(&config_path, config.to_string()).unwrap();
+    #[test]
+    fn test_config_validates_sync_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let config_path = write_toml(&temp_dir, &data_dir, "[wal]\nsync_mode = 'none'\n");
 
-        let result = Config::load(&config_path);
+        let result = AeroConfig::load(&config_path);
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_config_defaults() {
+    fn test_config_accepts_new_sync_modes() {
         let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("aerodb.json");
         let data_dir = temp_dir.path().join("data");
 
-        let config_json = json!({
-            "data_dir": data_dir.to_string_lossy()
-        });
+        for mode in ["fdatasync", "group_commit"] {
+            let wal = format!(
+                "[wal]\nsync_mode = '{}'\ngroup_commit_interval_ms = 5\n\
+                 group_commit_max_records = 16\n",
+                mode
+            );
+            let config_path = write_toml(&temp_dir, &data_dir, &wal);
+
+            let config = AeroConfig::load(&config_path).unwrap();
+            let sync = config.wal_sync_config().unwrap();
+            assert_eq!(sync.mode.as_str(), mode);
+            assert_eq!(sync.group_commit_interval, Duration::from_millis(5));
+            assert_eq!(sync.group_commit_max_records, 16);
+            assert!(config.validation_report().errors.is_empty());
+        }
+    }
 
-        fs::write(&config_path, config_json.to_string()).unwrap();
+    #[test]
+    fn test_config_group_commit_window_us_overrides_interval() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
 
-        let config = Config::load(&config_path).unwrap();
-        assert_eq!(config.max_wal_size_bytes, 1073741824);
-        assert_eq!(config.max_memory_bytes, 536870912);
-        assert_eq!(config.wal_sync_mode, "fsync");
+        let config_path = write_toml(
+            &temp_dir,
+            &data_dir,
+            "[wal]\nsync_mode = 'group_commit'\ngroup_commit_interval_ms = 5\n\
+             group_commit_window_us = 500\n",
+        );
+        let config = AeroConfig::load(&config_path).unwrap();
+        let sync = config.wal_sync_config().unwrap();
+        assert_eq!(sync.group_commit_interval, Duration::from_micros(500));
+
+        let config_path = write_toml(
+            &temp_dir,
+            &data_dir,
+            "[wal]\nsync_mode = 'group_commit'\ngroup_commit_window_us = 0\n",
+        );
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert!(err.message().contains("wal.group_commit_window_us"));
     }
 
     #[test]
-    fn test_config_load_toml() {
+    fn test_config_sync_mode_typo_explains_tradeoffs() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let config_path = write_toml(&temp_dir, &data_dir, "[wal]\nsync_mode = 'group-commit'\n");
+
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert_eq!(err.code(), &CliErrorCode::ConfigError);
+        assert!(err.message().contains("'fdatasync'"));
+        assert!(err.message().contains("'group_commit'"));
+    }
+
+    #[test]
+    fn test_config_rejects_empty_group_commit_window() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let config_path = write_toml(
+            &temp_dir,
+            &data_dir,
+            "[wal]\nsync_mode = 'group_commit'\ngroup_commit_max_records = 0\n",
+        );
+
+        assert!(AeroConfig::load(&config_path).is_err());
+    }
+
+    #[test]
+    fn test_config_wal_segments() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let archive_dir = temp_dir.path().join("archive");
+
+        let wal = format!(
+            "[wal]\nsegment_bytes = 4096\narchive_dir = '{}'\n",
+            archive_dir.display()
+        );
+        let config_path = write_toml(&temp_dir, &data_dir, &wal);
+
+        let segments = AeroConfig::load(&config_path)
+            .unwrap()
+            .wal_segment_config()
+            .unwrap();
+        assert_eq!(segments.segment_bytes, 4096);
+        assert_eq!(segments.archive_dir, Some(archive_dir));
+
+        let config_path = write_toml(
+            &temp_dir,
+            &data_dir,
+            "[wal]\nmax_size_bytes = 1024\nsegment_bytes = 4096\n",
+        );
+
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert!(err.to_string().contains("wal.max_size_bytes"));
+    }
+
+    #[test]
+    fn test_config_password_hash() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+
+        let hash = "[auth.password_hash]\nmemory_kib = 65536\niterations = 3\nparallelism = 2\n";
+        let config_path = write_toml(&temp_dir, &data_dir, hash);
+
+        let params = AeroConfig::load(&config_path)
+            .unwrap()
+            .password_hasher()
+            .unwrap()
+            .params();
+        assert_eq!(params.memory_kib, 65536);
+        assert_eq!(params.iterations, 3);
+        assert_eq!(params.parallelism, 2);
+
+        let config_path = write_toml(
+            &temp_dir,
+            &data_dir,
+            "[auth.password_hash]\nmemory_kib = 8\nparallelism = 4\n",
+        );
+
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert!(err.message().contains("auth.password_hash.memory_kib"));
+
+        let report = AeroConfig::read(&config_path).unwrap().validation_report();
+        let fields: Vec<&str> = report.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["auth.password_hash.memory_kib"]);
+
+        let config_path = write_toml(
+            &temp_dir,
+            &data_dir,
+            "[auth.password_hash]\niterations = 0\n",
+        );
+
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert!(err.message().contains("auth.password_hash.iterations"));
+    }
+
+    #[test]
+    fn test_config_service_role_secret() {
+        use crate::auth::jwt::JwtManager;
+
         let temp_dir = TempDir::new().unwrap();
-        let config_path = temp_dir.path().join("aerodb.toml");
         let data_dir = temp_dir.path().join("data");
 
-        // Manually format TOML string as we don't assume toml serialize available
-        let toml_content = format!(
+        let config = AeroConfig::load(&write_toml(&temp_dir, &data_dir, "")).unwrap();
+        assert!(config.jwt_config().unwrap().service_role_secret.is_none());
+
+        let secret = "[auth]\nservice_role_secret = '0123456789abcdef0123456789abcdef'\n";
+        let config = AeroConfig::load(&write_toml(&temp_dir, &data_dir, secret)).unwrap();
+        let jwt = JwtManager::new(config.jwt_config().unwrap());
+        let token = jwt.generate_service_role_token().unwrap();
+        assert!(jwt.validate_token(&token).is_ok());
+
+        let config_path = write_toml(
+            &temp_dir,
+            &data_dir,
+            "[auth]\nservice_role_secret = 'short'\n",
+        );
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert!(err.message().contains("auth.service_role_secret"));
+        let report = AeroConfig::read(&config_path).unwrap().validation_report();
+        assert_eq!(report.errors[0].field, "auth.service_role_secret");
+        assert_eq!(report.errors[0].value, "<hidden>");
+    }
+
+    #[test]
+    fn test_parse_restore_target_time() {
+        let target = parse_target_time("2026-02-07T14:45:00+01:00").unwrap();
+        assert_eq!(target.to_rfc3339(), "2026-02-07T13:45:00+00:00");
+
+        let err = parse_target_time("yesterday").unwrap_err();
+        assert_eq!(err.code_str(), "AERO_CLI_RESTORE_FAILED");
+        assert!(err.message().contains("RFC 3339"));
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = create_config(&temp_dir);
+
+        let config = AeroConfig::load(&config_path).unwrap();
+        assert_eq!(config.wal.max_size_bytes, 1073741824);
+        assert_eq!(config.server.max_memory_bytes, 536870912);
+        assert_eq!(config.wal.sync_mode, "fsync");
+    }
+
+    #[test]
+    fn test_config_load_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let config_path = write_toml(
+            &temp_dir,
+            &data_dir,
             r#"
-            data_dir = "{}"
-            wal_sync_mode = "fsync"
-            
+            [wal]
+            sync_mode = "fsync"
+
             [resource_limits]
             min_free_disk_bytes = 1048576
-            max_memory_bytes = 1073741824
-            max_file_descriptors = 500
-            max_result_set_docs = 1000
-            warning_threshold_percent = 75
-            critical_threshold_percent = 90
 
             [observability.operation_log]
             enabled = true
-            retention_days = 7
-            
+
             [observability.slow_query]
             enabled = true
             threshold_ms = 100
-            sample_rate = 1.0
             "#,
-            data_dir.to_string_lossy()
         );
 
-        fs::write(&config_path, toml_content).unwrap();
+        let config = AeroConfig::load(&config_path).expect("Failed to load TOML config");
 
-        let config = Config::load(&config_path).expect("Failed to load TOML config");
-        
-        assert_eq!(config.data_dir, data_dir.to_string_lossy());
+        assert_eq!(config.server.data_dir, data_dir.to_string_lossy());
         assert_eq!(config.resource_limits.min_free_disk_bytes, 1048576);
+        assert_eq!(config.resource_limits.max_file_descriptors, 1000);
         assert!(config.observability.operation_log.enabled);
         assert!(config.observability.slow_query.enabled);
     }
+
+    #[test]
+    fn test_config_toml_rejects_unknown_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = write_toml(
+            &temp_dir,
+            &temp_dir.path().join("data"),
+            "[observability.slow_query]\nthresold_ms = 50\n",
+        );
+
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert_eq!(err.code(), &CliErrorCode::ConfigError);
+        assert!(err
+            .message()
+            .contains("observability.slow_query.thresold_ms"));
+        assert!(err.message().contains("threshold_ms"));
+    }
+
+    #[test]
+    fn test_legacy_json_loads_like_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+
+        let toml_path = write_toml(
+            &temp_dir,
+            &data_dir,
+            r#"
+            [wal]
+            sync_mode = "group_commit"
+            group_commit_interval_ms = 5
+            segment_bytes = 1048576
+
+            [backup]
+            backup_dir = "/srv/backups"
+
+            [replication]
+            replica_id = "8c4c1d1e-4f6a-4a38-9d1a-1f3f3a0f6b2e"
+
+            [observability.operation_log]
+            enabled = true
+            slow_threshold_ms = 250
+
+            [observability.slow_query]
+            enabled = true
+            threshold_ms = 50
+            "#,
+        );
+
+        let json_path = temp_dir.path().join("aerodb.json");
+        fs::write(
+            &json_path,
+            serde_json::json!({
+                "data_dir": data_dir.to_string_lossy(),
+                "wal_sync_mode": "group_commit",
+                "group_commit_interval_ms": 5,
+                "wal_segment_bytes": 1048576,
+                "backup_dir": "/srv/backups",
+                "replica_id": "8c4c1d1e-4f6a-4a38-9d1a-1f3f3a0f6b2e",
+                "observability": {
+                    "operation_log": {"enabled": true, "slow_threshold_ms": 250},
+                    "slow_query": {"enabled": true, "threshold_ms": 50}
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let from_toml = AeroConfig::load(&toml_path).unwrap();
+        let from_json = AeroConfig::load(&json_path).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&from_toml).unwrap(),
+            serde_json::to_value(&from_json).unwrap()
+        );
+        assert_eq!(from_json.wal.group_commit_interval_ms, 5);
+        assert_eq!(from_json.backup.backup_dir, "/srv/backups");
+        assert_eq!(from_json.observability.operation_log.slow_threshold_ms, 250);
+    }
+
+    #[test]
+    fn test_config_toml_is_validated() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = write_toml(
+            &temp_dir,
+            &temp_dir.path().join("data"),
+            "[wal]\nmax_size_bytes = 0\n",
+        );
+
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert!(err.message().contains("wal.max_size_bytes"));
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("aerodb.yaml");
+        fs::write(&config_path, "data_dir: /tmp/aerodb\n").unwrap();
+
+        let err = AeroConfig::load(&config_path).unwrap_err();
+        assert!(err.message().contains(".yaml"));
+    }
+
+    fn replication_config(role: &str, primary_address: Option<&str>) -> AeroConfig {
+        let mut config = AeroConfig::new("/tmp/aerodb");
+        config.replication.enabled = true;
+        config.replication.role = role.to_string();
+        config.replication.primary_address = primary_address.map(str::to_string);
+        config
+    }
+
+    #[test]
+    fn test_replica_accepts_host_port_address() {
+        for addr in ["db-primary.internal:7000", "10.0.0.5:7000", "[::1]:7000"] {
+            let repl = replication_config("replica", Some(addr))
+                .to_replication_config()
+                .unwrap();
+            assert_eq!(repl.primary_address.as_deref(), Some(addr));
+        }
+    }
+
+    #[test]
+    fn test_replica_rejects_malformed_address() {
+        for addr in [
+            "db-primary",
+            ":7000",
+            "db-primary:",
+            "db-primary:http",
+            "db-primary:0",
+            "db-primary:70000",
+            "::1:7000",
+            "db primary:7000",
+        ] {
+            let err = replication_config("replica", Some(addr))
+                .to_replication_config()
+                .unwrap_err();
+            assert_eq!(err.code_str(), "AERO_CLI_CONFIG_ERROR", "{}", addr);
+            assert!(err.message().contains("host:port"), "{}", addr);
+        }
+    }
+
+    #[test]
+    fn test_primary_rejects_primary_address() {
+        let err = replication_config("primary", Some("db-primary:7000"))
+            .to_replication_config()
+            .unwrap_err();
+        assert_eq!(err.code_str(), "AERO_CLI_CONFIG_ERROR");
+        assert!(err.message().contains("forbidden"));
+
+        assert!(replication_config("primary", None)
+            .to_replication_config()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_replication_stream_requires_secret_and_port() {
+        let mut config = replication_config("primary", None);
+        let err = config.replication_stream_config().unwrap_err();
+        assert!(err.message().contains("replication.secret is required"));
+
+        config.replication.secret = Some("short".to_string());
+        assert!(config.replication_stream_config().is_err());
+        let report = config.validation_report();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].field, "replication.secret");
+        assert_eq!(report.errors[0].value, "<hidden>");
+
+        config.replication.secret = Some("0123456789abcdef".to_string());
+        let stream = config.replication_stream_config().unwrap().unwrap();
+        assert_eq!(stream.listen_address, "0.0.0.0:7000");
+        assert_eq!(stream.bootstrap_pin, Duration::from_secs(3600));
+
+        config.replication.bootstrap_pin_secs = 0;
+        let report = config.validation_report();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].field, "replication.bootstrap_pin_secs");
+        config.replication.bootstrap_pin_secs = 3600;
+
+        config.replication.port = 0;
+        assert!(config.replication_stream_config().is_err());
+        config.replication.enabled = false;
+        assert!(config.replication_stream_config().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sync_replication_config_validation() {
+        let mut config = replication_config("primary", None);
+        config.replication.secret = Some("0123456789abcdef".to_string());
+        assert_eq!(
+            config.sync_replication_config().unwrap().mode,
+            SyncMode::Async
+        );
+
+        config.replication.sync_mode = "quorum".to_string();
+        config.replication.sync_on_timeout = "degrade".to_string();
+        let sync = config.sync_replication_config().unwrap();
+        assert_eq!(sync.mode, SyncMode::Quorum);
+        assert_eq!(sync.on_timeout, SyncTimeoutPolicy::Degrade);
+        assert!(config.validation_report().errors.is_empty());
+
+        config.replication.min_sync_replicas = 0;
+        let report = config.validation_report();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].field, "replication.min_sync_replicas");
+        config.replication.min_sync_replicas = 1;
+
+        config.replication.sync_mode = "sync".to_string();
+        let report = config.validation_report();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].field, "replication.sync_mode");
+        config.replication.sync_mode = "quorum".to_string();
+
+        config.replication.role = "replica".to_string();
+        config.replication.primary_address = Some("127.0.0.1:7000".to_string());
+        let report = config.validation_report();
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.field == "replication.sync_mode"));
+    }
+
+    #[test]
+    fn test_replica_gate_from_stored_offset() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = replication_config("replica", Some("db-primary.internal:7000"));
+        config.server.data_dir = temp_dir.path().to_string_lossy().to_string();
+        let wal_writer = WalWriter::open(temp_dir.path()).unwrap();
+
+        // Never heard from the primary: bounded reads are refused
+        let gate = open_replica_gate(&config, &wal_writer).unwrap();
+        let err = gate.check_write().unwrap_err();
+        assert!(err.message.contains("db-primary.internal:7000"));
+        assert!(gate.check_read(None).is_ok());
+        assert!(gate.check_read(Some(60_000)).is_err());
+
+        let as_of_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        fs::create_dir_all(temp_dir.path().join("system")).unwrap();
+        fs::write(
+            replication_offset_path(temp_dir.path()),
+            json!({"applied_sequence": 0, "current_as_of_ms": as_of_ms - 1_000}).to_string(),
+        )
+        .unwrap();
+        let gate = open_replica_gate(&config, &wal_writer).unwrap();
+        assert!(gate.check_read(Some(60_000)).is_ok());
+        assert!(gate.check_read(Some(10)).is_err());
+        assert!(gate.staleness_ms().unwrap() >= 1_000);
+
+        config.replication.enabled = false;
+        let gate = open_replica_gate(&config, &wal_writer).unwrap();
+        assert!(gate.check_write().is_ok());
+    }
+
+    #[test]
+    fn test_config_validate_clean_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = create_config(&temp_dir);
+        fs::create_dir(temp_dir.path().join("data")).unwrap();
+
+        let report = AeroConfig::read(&config_path).unwrap().validation_report();
+        assert!(!report.has_errors(), "{:?}", report.errors);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+        assert!(config_validate(&config_path, OutputFormat::Json).is_ok());
+    }
+
+    #[test]
+    fn test_config_validate_reports_every_problem() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_file = temp_dir.path().join("not-a-dir");
+        fs::write(&data_file, "").unwrap();
+
+        let config_path = write_toml(
+            &temp_dir,
+            &data_file,
+            r#"max_memory_bytes = 1024
+
+            [wal]
+            sync_mode = "fsynk"
+
+            [query_limits]
+            max_execution_ms = 600000
+            max_sort_bytes = 0
+
+            [replication]
+            enabled = true
+            role = "replica"
+            replica_id = "not-a-uuid"
+            primary_address = "db-primary:7000"
+            "#,
+        );
+
+        // Boot-time loading stops at the first problem
+        assert!(AeroConfig::load(&config_path).is_err());
+
+        let report = AeroConfig::read(&config_path).unwrap().validation_report();
+        let fields: Vec<&str> = report.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "server.data_dir",
+                "wal.sync_mode",
+                "server.max_memory_bytes",
+                "query_limits.max_execution_ms",
+                "query_limits.max_sort_bytes",
+                "replication"
+            ]
+        );
+
+        let err = config_validate(&config_path, OutputFormat::Json).unwrap_err();
+        assert_eq!(err.code_str(), "AERO_CLI_CONFIG_ERROR");
+        assert!(err.message().starts_with("6 configuration error(s)"));
+    }
+
+    #[test]
+    fn test_config_validate_warnings_do_not_fail() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = write_toml(
+            &temp_dir,
+            &temp_dir.path().join("data"),
+            "[replication]\nprimary_address = 'db-primary:7000'\n",
+        );
+
+        let report = AeroConfig::read(&config_path).unwrap().validation_report();
+        let fields: Vec<&str> = report.warnings.iter().map(|w| w.field.as_str()).collect();
+        assert_eq!(fields, ["server.data_dir", "replication.primary_address"]);
+
+        assert!(config_validate(&config_path, OutputFormat::Json).is_ok());
+    }
+
+    #[test]
+    fn test_upgrade_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = create_config(&temp_dir);
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir(&data_dir).unwrap();
+
+        let err = upgrade(&config_path, OutputFormat::Json).unwrap_err();
+        assert_eq!(err.code_str(), "AERO_CLI_UPGRADE_FAILED");
+
+        let mut marker = VersionMarker::new();
+        marker.wal_format_version = 1;
+        marker.save(&data_dir).unwrap();
+
+        upgrade(&config_path, OutputFormat::Json).unwrap();
+        let marker = VersionMarker::load(&data_dir).unwrap().unwrap();
+        assert_eq!(marker.wal_format_version, WAL_FORMAT_VERSION);
+
+        // Running it again is a no-op
+        upgrade(&config_path, OutputFormat::Json).unwrap();
+    }
+
+    fn inspect_lines(
+        wal_dir: &Path,
+        segment: Option<u64>,
+        from: u64,
+        limit: Option<usize>,
+    ) -> Vec<Value> {
+        let mut reader = open_wal_for_inspection(wal_dir, segment).unwrap().unwrap();
+        let mut out = Vec::new();
+        inspect_wal(&mut reader, from, limit, &mut out).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_wal_inspect_emits_record_fields() {
+        use crate::wal::WalPayload;
+
+        let temp_dir = TempDir::new().unwrap();
+        let wal_dir = temp_dir.path().join("wal");
+        assert!(open_wal_for_inspection(&wal_dir, None).unwrap().is_none());
+
+        let payload =
+            |body: &str| WalPayload::new("users", "u1", "user", "v1", body.as_bytes().to_vec());
+        let mut writer = WalWriter::open(temp_dir.path()).unwrap();
+        writer.append_insert(payload(r#"{"name": "a"}"#)).unwrap();
+        writer.append_update(payload(r#"{"name": "b"}"#)).unwrap();
+        writer.append_delete(payload("")).unwrap();
+
+        let lines = inspect_lines(&wal_dir, None, 0, None);
+        let types: Vec<&str> = lines.iter().map(|l| l["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["insert", "update", "delete"]);
+        assert_eq!(lines[0]["offset"], 0);
+        assert_eq!(lines[0]["segment"], Value::Null);
+        assert_eq!(lines[0]["sequence"], 1);
+        assert_eq!(lines[1]["collection"], "users");
+        assert_eq!(lines[1]["key"], "u1");
+        assert_eq!(lines[2]["body_bytes"], 0);
+        assert!(lines[0]["commit_timestamp_ms"].is_u64());
+
+        // --from-offset skips earlier records; --limit caps the listing
+        let second = lines[1]["offset"].as_u64().unwrap();
+        let tail = inspect_lines(&wal_dir, None, second, None);
+        assert_eq!(tail.len(), 2);
+        assert_eq!(tail[0]["sequence"], 2);
+        assert_eq!(inspect_lines(&wal_dir, None, 0, Some(1)).len(), 1);
+
+        assert_eq!(
+            open_wal_for_inspection(&wal_dir, Some(1))
+                .err()
+                .unwrap()
+                .code_str(),
+            "AERO_CLI_WAL_INSPECT_FAILED"
+        );
+    }
+
+    #[test]
+    fn test_wal_inspect_segments() {
+        use crate::wal::WalPayload;
+
+        let temp_dir = TempDir::new().unwrap();
+        let wal_dir = temp_dir.path().join("wal");
+        let mut writer = WalWriter::open_segmented(
+            temp_dir.path(),
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(1),
+        )
+        .unwrap();
+        for i in 0..3 {
+            let payload =
+                WalPayload::new("users", format!("u{}", i), "user", "v1", b"{}".to_vec());
+            writer.append_insert(payload).unwrap();
+        }
+
+        let lines = inspect_lines(&wal_dir, Some(2), 0, None);
+        let positions: Vec<(u64, u64)> = lines
+            .iter()
+            .map(|l| {
+                (
+                    l["segment"].as_u64().unwrap(),
+                    l["sequence"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(positions, [(2, 2), (3, 3)]);
+
+        // --from-offset applies to the starting segment only
+        assert_eq!(inspect_lines(&wal_dir, Some(2), 1, None).len(), 1);
+        assert!(open_wal_for_inspection(&wal_dir, Some(9)).is_err());
+
+        use clap::Parser;
+        let cli = super::super::args::Cli::try_parse_from([
+            "aerodb", "wal", "inspect", "--segment", "2", "--from-offset", "16", "--limit", "5",
+        ]);
+        assert!(cli.is_ok());
+    }
 }
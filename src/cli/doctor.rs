@@ -0,0 +1,637 @@
+//! `aerodb doctor`: one-shot environment and data directory diagnostics
+//!
+//! Each check reports pass, warn or fail with a remediation hint. The
+//! checks reuse the subsystems that enforce the same conditions at boot
+//! (version checker, WAL reader, schema loader, consistency verifier), so
+//! a data directory the doctor passes is one `aerodb start` accepts.
+//!
+//! Indexes are in memory and rebuilt from storage at every start, so there
+//! are no index files to check; the storage check covers their source.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config_validator::AeroConfig;
+use crate::recovery::{ConsistencyVerifier, RecoveryManager, RecoveryStorage};
+use crate::resource_limits::{DiskSpaceChecker, ResourceError};
+use crate::schema::SchemaLoader;
+use crate::version::{VersionCheck, VersionChecker, VersionError};
+
+use super::commands::{is_initialized, open_wal_for_inspection};
+
+/// File mtimes further in the future than this mean the clock went back
+const CLOCK_SKEW_TOLERANCE: Duration = Duration::from_secs(300);
+
+/// File descriptors needed besides one per connection (WAL segments,
+/// storage, schemas, logs)
+const FD_HEADROOM: u64 = 64;
+
+/// Outcome of one check, ordered by severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Severity {
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Pass => "PASS",
+            Severity::Warn => "WARN",
+            Severity::Fail => "FAIL",
+        }
+    }
+}
+
+/// Result of one check
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// What to do about a warning or failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity: Severity::Pass,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity: Severity::Warn,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity: Severity::Fail,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Results of every check run
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl DoctorReport {
+    /// The most severe result (pass if nothing ran)
+    pub fn worst(&self) -> Severity {
+        self.checks
+            .iter()
+            .map(|c| c.severity)
+            .max()
+            .unwrap_or(Severity::Pass)
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.checks
+            .iter()
+            .filter(|c| c.severity == severity)
+            .count()
+    }
+
+    #[cfg(test)]
+    fn check(&self, name: &str) -> Option<&CheckResult> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "status": self.worst(),
+            "checks": self.checks,
+        })
+    }
+
+    /// One line per check, hints indented below, then a summary
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        for check in &self.checks {
+            out.push_str(&format!(
+                "{}  {:<12} {}\n",
+                check.severity.label(),
+                check.name,
+                check.message
+            ));
+            if let Some(hint) = &check.hint {
+                out.push_str(&format!("      {:<12} hint: {}\n", "", hint));
+            }
+        }
+        out.push_str(&format!(
+            "\n{} passed, {} warning(s), {} failed\n",
+            self.count(Severity::Pass),
+            self.count(Severity::Warn),
+            self.count(Severity::Fail)
+        ));
+        out
+    }
+}
+
+/// Run every check against the config at `config_path` and its data
+/// directory
+///
+/// A config that cannot be read ends the run: the other checks need it.
+/// Checks of the data directory's contents are skipped if it is not
+/// initialized.
+pub fn run_checks(config_path: &Path) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    let config = match AeroConfig::read(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            report.checks.push(CheckResult::fail(
+                "config",
+                e.message().to_string(),
+                format!(
+                    "Fix {} and run `aerodb doctor` again",
+                    config_path.display()
+                ),
+            ));
+            return report;
+        }
+    };
+    report.checks.push(check_config(&config, config_path));
+
+    let data_dir = config.data_path();
+    let data_dir_check = check_data_dir(data_dir);
+    let usable = data_dir_check.severity != Severity::Fail;
+    report.checks.push(data_dir_check);
+    if !usable {
+        return report;
+    }
+
+    report.checks.push(check_disk_space(&config));
+    report.checks.push(check_version(data_dir));
+    report.checks.push(check_wal(data_dir));
+    let (schema_check, schemas) = check_schemas(data_dir);
+    report.checks.push(schema_check);
+    if let Some(schemas) = schemas {
+        report.checks.push(check_storage(data_dir, &schemas));
+    }
+    report.checks.push(check_clock(data_dir));
+    report.checks.push(check_file_descriptors(&config));
+    report.checks.push(check_shutdown(data_dir));
+
+    report
+}
+
+fn check_config(config: &AeroConfig, config_path: &Path) -> CheckResult {
+    let validation = config.validation_report();
+    let path = config_path.display();
+    if validation.has_errors() {
+        CheckResult::fail(
+            "config",
+            format!(
+                "{} error(s) in {}: {}",
+                validation.errors.len(),
+                path,
+                join(&validation.errors)
+            ),
+            "Run `aerodb config-validate` for details",
+        )
+    } else if !validation.warnings.is_empty() {
+        CheckResult::warn(
+            "config",
+            format!("{}: {}", path, join(&validation.warnings)),
+            "Run `aerodb config-validate` for details",
+        )
+    } else {
+        CheckResult::pass("config", format!("{} is valid", path))
+    }
+}
+
+fn join(problems: &[impl ToString]) -> String {
+    problems
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn check_data_dir(data_dir: &Path) -> CheckResult {
+    if !data_dir.is_dir() {
+        return CheckResult::fail(
+            "data_dir",
+            format!("{} does not exist", data_dir.display()),
+            "Run `aerodb init`, or point server.data_dir at the data directory",
+        );
+    }
+    if !is_initialized(data_dir) {
+        return CheckResult::fail(
+            "data_dir",
+            format!("{} is not initialized", data_dir.display()),
+            "Run `aerodb init`",
+        );
+    }
+
+    let probe = data_dir.join(".doctor_probe");
+    match fs::write(&probe, b"").and_then(|_| fs::remove_file(&probe)) {
+        Ok(()) => CheckResult::pass("data_dir", format!("{} is writable", data_dir.display())),
+        Err(e) => CheckResult::fail(
+            "data_dir",
+            format!("{} is not writable: {}", data_dir.display(), e),
+            "Give the user running AeroDB write access to the data directory",
+        ),
+    }
+}
+
+fn check_disk_space(config: &AeroConfig) -> CheckResult {
+    let min_free = config.resource_limits.min_free_disk_bytes;
+    let checker = DiskSpaceChecker::new(config.data_path(), min_free);
+    match checker.check_space(0) {
+        Ok(()) => {
+            let free = checker.get_free_space().unwrap_or(0);
+            CheckResult::pass("disk_space", format!("{} bytes free", free))
+        }
+        Err(ResourceError::DiskFull {
+            available,
+            required,
+        }) => CheckResult::fail(
+            "disk_space",
+            format!("{} bytes free, {} required", available, required),
+            "Free disk space or lower resource_limits.min_free_disk_bytes; writes are refused",
+        ),
+        Err(e) => CheckResult::warn(
+            "disk_space",
+            format!("Free space unknown: {}", e),
+            "Check free space on the data directory's file system by hand",
+        ),
+    }
+}
+
+fn check_version(data_dir: &Path) -> CheckResult {
+    match VersionChecker::new(data_dir).check() {
+        VersionCheck::Compatible => CheckResult::pass("version", "Data format matches this binary"),
+        VersionCheck::NewInstallation => CheckResult::warn(
+            "version",
+            "No version marker",
+            "The marker is written at the next start",
+        ),
+        VersionCheck::UpgradeCompatible { from, to } => CheckResult::warn(
+            "version",
+            format!("Data written by {}, this binary is {}", from, to),
+            "Compatible; the marker is updated at the next start",
+        ),
+        VersionCheck::Incompatible(e @ VersionError::AlreadyRunning { .. }) => CheckResult::warn(
+            "version",
+            e.to_string(),
+            "Results may change while AeroDB is running; stop it for a consistent report",
+        ),
+        VersionCheck::Incompatible(e) => CheckResult::fail(
+            "version",
+            e.to_string(),
+            "AeroDB refuses to start until this is resolved",
+        ),
+    }
+}
+
+fn check_wal(data_dir: &Path) -> CheckResult {
+    let mut reader = match open_wal_for_inspection(&data_dir.join("wal"), None) {
+        Ok(Some(reader)) => reader,
+        Ok(None) => return CheckResult::pass("wal", "WAL is empty"),
+        Err(e) => {
+            return CheckResult::fail(
+                "wal",
+                e.message().to_string(),
+                "Inspect the wal directory; AeroDB cannot replay it",
+            )
+        }
+    };
+
+    let mut records = 0u64;
+    let mut error = None;
+    for entry in reader.iter_records() {
+        match entry {
+            Ok(_) => records += 1,
+            Err(e) => {
+                error = Some(e);
+                break;
+            }
+        }
+    }
+
+    match (error, reader.corrupt_tail(), reader.torn_tail()) {
+        (Some(e), Some(offset), _) => CheckResult::fail(
+            "wal",
+            format!(
+                "Damaged record at the end of the WAL (offset {}): {}",
+                offset, e
+            ),
+            "Recovery refuses to start; restore from a backup, or discard the damaged tail \
+             after confirming it was never acknowledged",
+        ),
+        (Some(e), None, _) => CheckResult::fail(
+            "wal",
+            format!("WAL corrupt after {} record(s): {}", records, e),
+            "Recovery refuses to start; restore from a backup",
+        ),
+        (None, _, Some(offset)) => CheckResult::warn(
+            "wal",
+            format!(
+                "{} record(s); torn write at offset {} of the last segment",
+                records, offset
+            ),
+            "The unacknowledged record is truncated at the next start",
+        ),
+        (None, _, None) => CheckResult::pass("wal", format!("{} record(s), all intact", records)),
+    }
+}
+
+fn check_schemas(data_dir: &Path) -> (CheckResult, Option<SchemaLoader>) {
+    let mut loader = SchemaLoader::new(data_dir);
+    match loader.load_all() {
+        Ok(()) => (
+            CheckResult::pass(
+                "schemas",
+                format!("{} schema version(s) parsed", loader.schema_count()),
+            ),
+            Some(loader),
+        ),
+        Err(e) => (
+            CheckResult::fail(
+                "schemas",
+                e.to_string(),
+                format!(
+                    "Fix or restore the schema files in {}",
+                    loader.schema_dir().display()
+                ),
+            ),
+            None,
+        ),
+    }
+}
+
+fn check_storage(data_dir: &Path, schemas: &SchemaLoader) -> CheckResult {
+    let verified = RecoveryStorage::open(data_dir)
+        .and_then(|mut storage| ConsistencyVerifier::verify(&mut storage, schemas));
+    match verified {
+        Ok(stats) => CheckResult::pass(
+            "storage",
+            format!(
+                "{} record(s), {} live document(s), every schema present",
+                stats.records_verified, stats.live_documents
+            ),
+        ),
+        Err(e) => CheckResult::fail(
+            "storage",
+            e.to_string(),
+            "Restore missing schema files, or restore the data directory from a backup",
+        ),
+    }
+}
+
+fn check_clock(data_dir: &Path) -> CheckResult {
+    let now = SystemTime::now();
+    let mut newest: Option<(SystemTime, std::path::PathBuf)> = None;
+    let mut pending = vec![data_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                pending.push(entry.path());
+            } else if let Ok(modified) = meta.modified() {
+                if newest.as_ref().is_none_or(|(at, _)| modified > *at) {
+                    newest = Some((modified, entry.path()));
+                }
+            }
+        }
+    }
+
+    match newest {
+        Some((modified, path)) if modified > now + CLOCK_SKEW_TOLERANCE => {
+            let ahead = modified.duration_since(now).unwrap_or_default().as_secs();
+            CheckResult::warn(
+                "clock",
+                format!("{} was modified {}s in the future", path.display(), ahead),
+                "The system clock went back; fix NTP. TTL expiry and point-in-time \
+                 restore use wall-clock time",
+            )
+        }
+        _ => CheckResult::pass("clock", "No data file is newer than the system clock"),
+    }
+}
+
+fn check_file_descriptors(config: &AeroConfig) -> CheckResult {
+    let needed = config.backpressure.max_connections as u64 + FD_HEADROOM;
+    match open_file_limit() {
+        Some(limit) if limit < needed => CheckResult::warn(
+            "file_limit",
+            format!(
+                "Open file limit is {}, backpressure.max_connections needs about {}",
+                limit, needed
+            ),
+            format!(
+                "Raise the limit (`ulimit -n {}`, or LimitNOFILE in the service unit), \
+                 or lower backpressure.max_connections",
+                needed
+            ),
+        ),
+        Some(limit) => CheckResult::pass(
+            "file_limit",
+            format!("Open file limit is {} (about {} needed)", limit, needed),
+        ),
+        None => CheckResult::warn(
+            "file_limit",
+            "Open file limit unknown",
+            format!("Check that at least {} files may be open", needed),
+        ),
+    }
+}
+
+/// Soft limit on open file descriptors
+#[cfg(unix)]
+fn open_file_limit() -> Option<u64> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: getrlimit only writes the rlimit it is given
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)] // rlim_t is not u64 on every target
+    Some(limit.rlim_cur as u64)
+}
+
+#[cfg(not(unix))]
+fn open_file_limit() -> Option<u64> {
+    None
+}
+
+fn check_shutdown(data_dir: &Path) -> CheckResult {
+    if RecoveryManager::new(data_dir).was_clean_shutdown() {
+        CheckResult::pass("shutdown", "Last shutdown was clean")
+    } else {
+        CheckResult::warn(
+            "shutdown",
+            "No clean_shutdown marker: AeroDB is running or did not shut down cleanly",
+            "Nothing to do if it is running; otherwise the WAL is replayed at the next start",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::commands::init;
+    use super::super::io::OutputFormat;
+    use super::*;
+    use crate::wal::{WalPayload, WalWriter};
+    use std::io::{Seek, SeekFrom, Write};
+    use tempfile::TempDir;
+
+    /// An initialized data directory and its config
+    fn setup() -> (TempDir, std::path::PathBuf, std::path::PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let config_path = temp_dir.path().join("aerodb.toml");
+        fs::write(
+            &config_path,
+            format!(
+                "[server]\ndata_dir = '{}'\n[resource_limits]\nmin_free_disk_bytes = 0\n",
+                data_dir.display()
+            ),
+        )
+        .unwrap();
+        init(&config_path, false, None, OutputFormat::Json).unwrap();
+        (temp_dir, config_path, data_dir)
+    }
+
+    fn append(data_dir: &Path, count: usize) {
+        let mut writer = WalWriter::open(data_dir).unwrap();
+        for i in 0..count {
+            let payload =
+                WalPayload::new("users", format!("u{}", i), "users", "v1", b"{}".to_vec());
+            writer.append_insert(payload).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_healthy_data_dir() {
+        let (_temp, config_path, data_dir) = setup();
+        append(&data_dir, 2);
+        fs::write(data_dir.join("clean_shutdown"), "").unwrap();
+
+        let report = run_checks(&config_path);
+        for name in [
+            "config", "data_dir", "version", "schemas", "storage", "clock", "shutdown",
+        ] {
+            assert_eq!(
+                report.check(name).unwrap().severity,
+                Severity::Pass,
+                "{:?}",
+                report.check(name)
+            );
+        }
+        assert_eq!(
+            report.check("wal").unwrap().message,
+            "2 record(s), all intact"
+        );
+        assert_eq!(report.to_json()["checks"][0]["name"], "config");
+        assert!(report.render_text().starts_with("PASS  config"));
+    }
+
+    #[test]
+    fn test_detects_corrupt_wal_tail() {
+        let (_temp, config_path, data_dir) = setup();
+        append(&data_dir, 2);
+
+        // Flip a byte inside the last record
+        let wal = data_dir.join("wal").join("wal.log");
+        let len = fs::metadata(&wal).unwrap().len();
+        let mut file = fs::OpenOptions::new().write(true).open(&wal).unwrap();
+        file.seek(SeekFrom::Start(len - 6)).unwrap();
+        file.write_all(&[0xFF]).unwrap();
+
+        let report = run_checks(&config_path);
+        let wal_check = report.check("wal").unwrap();
+        assert_eq!(wal_check.severity, Severity::Fail);
+        assert!(wal_check
+            .message
+            .starts_with("Damaged record at the end of the WAL"));
+        assert_eq!(report.worst(), Severity::Fail);
+    }
+
+    #[test]
+    fn test_detects_missing_schema() {
+        use crate::storage::{StoragePayload, StorageWriter};
+
+        let (_temp, config_path, data_dir) = setup();
+        let mut storage = StorageWriter::open(&data_dir).unwrap();
+        storage
+            .write(&StoragePayload::new(
+                "users",
+                "u1",
+                "users",
+                "v1",
+                b"{}".to_vec(),
+            ))
+            .unwrap();
+
+        let report = run_checks(&config_path);
+        assert_eq!(report.check("schemas").unwrap().severity, Severity::Pass);
+        let storage_check = report.check("storage").unwrap();
+        assert_eq!(storage_check.severity, Severity::Fail);
+        assert!(storage_check.message.contains("users"));
+
+        // An unparseable schema file is reported by the schema check
+        fs::write(data_dir.join("metadata/schemas/users_v1.json"), "{").unwrap();
+        let report = run_checks(&config_path);
+        assert_eq!(report.check("schemas").unwrap().severity, Severity::Fail);
+        assert!(report.check("storage").is_none());
+    }
+
+    #[test]
+    fn test_uninitialized_and_unreadable_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let report = run_checks(&temp_dir.path().join("missing.toml"));
+        assert_eq!(report.checks.len(), 1);
+        assert_eq!(report.check("config").unwrap().severity, Severity::Fail);
+
+        let config_path = temp_dir.path().join("aerodb.toml");
+        fs::write(
+            &config_path,
+            format!(
+                "[server]\ndata_dir = '{}'\n",
+                temp_dir.path().join("nowhere").display()
+            ),
+        )
+        .unwrap();
+        let report = run_checks(&config_path);
+        assert_eq!(report.check("data_dir").unwrap().severity, Severity::Fail);
+        assert!(report.check("wal").is_none());
+    }
+
+    #[test]
+    fn test_missing_shutdown_marker_warns() {
+        let (_temp, config_path, _data_dir) = setup();
+        let report = run_checks(&config_path);
+        assert_eq!(report.check("shutdown").unwrap().severity, Severity::Warn);
+        assert!(report.worst() >= Severity::Warn);
+    }
+}
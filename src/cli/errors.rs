@@ -18,6 +18,24 @@ pub enum CliErrorCode {
     NotInitialized,
     /// Boot failed
     BootFailed,
+    /// Restore failed
+    RestoreFailed,
+    /// Backup operation failed
+    BackupFailed,
+    /// Data format upgrade failed
+    UpgradeFailed,
+    /// Destructive operation not confirmed
+    ConfirmationRequired,
+    /// WAL could not be read for inspection
+    WalInspectFailed,
+    /// Test data seeding failed
+    SeedFailed,
+    /// Import or export failed
+    TransferFailed,
+    /// `doctor` found problems worth a look
+    DoctorWarnings,
+    /// `doctor` found problems that stop AeroDB from working
+    DoctorFailed,
 }
 
 impl CliErrorCode {
@@ -29,6 +47,15 @@ impl CliErrorCode {
             Self::AlreadyInitialized => "AERO_CLI_ALREADY_INITIALIZED",
             Self::NotInitialized => "AERO_CLI_NOT_INITIALIZED",
             Self::BootFailed => "AERO_CLI_BOOT_FAILED",
+            Self::RestoreFailed => "AERO_CLI_RESTORE_FAILED",
+            Self::BackupFailed => "AERO_CLI_BACKUP_FAILED",
+            Self::UpgradeFailed => "AERO_CLI_UPGRADE_FAILED",
+            Self::ConfirmationRequired => "AERO_CLI_CONFIRMATION_REQUIRED",
+            Self::WalInspectFailed => "AERO_CLI_WAL_INSPECT_FAILED",
+            Self::SeedFailed => "AERO_CLI_SEED_FAILED",
+            Self::TransferFailed => "AERO_CLI_TRANSFER_FAILED",
+            Self::DoctorWarnings => "AERO_CLI_DOCTOR_WARNINGS",
+            Self::DoctorFailed => "AERO_CLI_DOCTOR_FAILED",
         }
     }
 }
@@ -80,6 +107,59 @@ impl CliError {
         Self::new(CliErrorCode::BootFailed, msg)
     }
 
+    /// Restore failed
+    pub fn restore_failed(msg: impl Into<String>) -> Self {
+        Self::new(CliErrorCode::RestoreFailed, msg)
+    }
+
+    /// Backup operation failed
+    pub fn backup_failed(msg: impl Into<String>) -> Self {
+        Self::new(CliErrorCode::BackupFailed, msg)
+    }
+
+    /// Data format upgrade failed
+    pub fn upgrade_failed(msg: impl Into<String>) -> Self {
+        Self::new(CliErrorCode::UpgradeFailed, msg)
+    }
+
+    /// Destructive operation not confirmed
+    pub fn confirmation_required(msg: impl Into<String>) -> Self {
+        Self::new(CliErrorCode::ConfirmationRequired, msg)
+    }
+
+    /// Create a WAL inspection error
+    pub fn wal_inspect_failed(msg: impl Into<String>) -> Self {
+        Self::new(CliErrorCode::WalInspectFailed, msg)
+    }
+
+    /// Create a seeding error
+    pub fn seed_failed(msg: impl Into<String>) -> Self {
+        Self::new(CliErrorCode::SeedFailed, msg)
+    }
+
+    /// Create an import or export error
+    pub fn transfer_failed(msg: impl Into<String>) -> Self {
+        Self::new(CliErrorCode::TransferFailed, msg)
+    }
+
+    /// Create a doctor warnings error
+    pub fn doctor_warnings(msg: impl Into<String>) -> Self {
+        Self::new(CliErrorCode::DoctorWarnings, msg)
+    }
+
+    /// Create a doctor failures error
+    pub fn doctor_failed(msg: impl Into<String>) -> Self {
+        Self::new(CliErrorCode::DoctorFailed, msg)
+    }
+
+    /// Process exit code: 2 for `doctor` failures, 1 otherwise
+    pub fn exit_code(&self) -> i32 {
+        match self.code {
+            CliErrorCode::DoctorFailed => 2,
+            _ => 1,
+        }
+    }
+
     /// Get the error code
     pub fn code(&self) -> &CliErrorCode {
         &self.code
@@ -0,0 +1,277 @@
+//! Log tailing for `aerodb logs --follow`
+//!
+//! Polls the log file for appended bytes and emits complete lines only.
+//! Rotation is detected when the file at the path is replaced (different
+//! inode) or shrinks below the read position; the follower then drains
+//! the old handle and continues from the start of the new file.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use super::errors::CliResult;
+
+/// Default interval between polls of the log file.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Returns whether a log line passes an (upper-cased) level filter.
+///
+/// Shared by `logs` and `logs --follow` so both filter identically.
+pub fn line_matches_level(line: &str, level_upper: Option<&str>) -> bool {
+    match level_upper {
+        Some(level) => line.to_uppercase().contains(level),
+        None => true,
+    }
+}
+
+/// Identity of the file behind a path, used to detect replacement.
+#[cfg(unix)]
+fn file_identity(meta: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_meta: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Incremental reader of a growing log file.
+pub struct LogFollower {
+    path: PathBuf,
+    file: File,
+    position: u64,
+    identity: Option<(u64, u64)>,
+    partial: Vec<u8>,
+}
+
+impl LogFollower {
+    /// Opens the log file positioned at its current end.
+    ///
+    /// Only lines appended after this call are returned by `poll`.
+    pub fn from_end(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let identity = file_identity(&file.metadata()?);
+        let position = file.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            position,
+            identity,
+            partial: Vec::new(),
+        })
+    }
+
+    /// Returns the complete lines appended since the last poll.
+    ///
+    /// A trailing line without a newline is held back until it is
+    /// completed, unless the file is rotated, in which case it is
+    /// returned as-is.
+    pub fn poll(&mut self) -> io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        self.read_available(&mut lines)?;
+
+        let meta = match fs::metadata(&self.path) {
+            Ok(meta) => meta,
+            // Rotated away and not yet recreated
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(lines),
+            Err(e) => return Err(e),
+        };
+
+        let replaced = file_identity(&meta) != self.identity;
+        let truncated = meta.len() < self.position;
+        if replaced || truncated {
+            if !self.partial.is_empty() {
+                lines.push(String::from_utf8_lossy(&self.partial).into_owned());
+                self.partial.clear();
+            }
+            self.file = File::open(&self.path)?;
+            self.identity = file_identity(&self.file.metadata()?);
+            self.position = 0;
+            self.read_available(&mut lines)?;
+        }
+
+        Ok(lines)
+    }
+
+    /// Reads everything past the current position and splits it into lines.
+    fn read_available(&mut self, lines: &mut Vec<String>) -> io::Result<()> {
+        let mut buf = Vec::new();
+        let read = self.file.read_to_end(&mut buf)?;
+        self.position += read as u64;
+        self.partial.extend_from_slice(&buf);
+
+        while let Some(newline) = self.partial.iter().position(|&b| b == b'\n') {
+            let mut line: Vec<u8> = self.partial.drain(..=newline).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            lines.push(String::from_utf8_lossy(&line).into_owned());
+        }
+
+        Ok(())
+    }
+}
+
+/// Streams lines appended to the log until `stop` is set.
+///
+/// Each line matching `level` is written to `out` verbatim, followed by a
+/// newline, and `out` is flushed after every poll.
+pub fn follow<W: Write>(
+    mut follower: LogFollower,
+    level: Option<&str>,
+    out: &mut W,
+    poll_interval: Duration,
+    stop: &AtomicBool,
+) -> CliResult<()> {
+    let level_upper = level.map(str::to_uppercase);
+
+    loop {
+        for line in follower.poll()? {
+            if line_matches_level(&line, level_upper.as_deref()) {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        out.flush()?;
+
+        if stop.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        thread::sleep(poll_interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    /// `Write` handle onto a buffer shared with the test thread.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuf {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_follow_emits_appended_lines_in_order() {
+        let temp = TempDir::new().unwrap();
+        let log = temp.path().join("aerodb.log");
+        append(&log, "INFO old line\n");
+
+        let follower = LogFollower::from_end(&log).unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+        let out = SharedBuf::default();
+
+        let handle = {
+            let stop = Arc::clone(&stop);
+            let mut out = out.clone();
+            thread::spawn(move || {
+                follow(
+                    follower,
+                    Some("info"),
+                    &mut out,
+                    Duration::from_millis(5),
+                    &stop,
+                )
+            })
+        };
+
+        append(&log, "INFO first\nDEBUG skipped\n");
+        append(&log, "INFO sec");
+        append(&log, "ond\nINFO third\n");
+
+        let expected = "INFO first\nINFO second\nINFO third\n";
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while out.text() != expected && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        stop.store(true, Ordering::SeqCst);
+        handle.join().unwrap().unwrap();
+
+        assert_eq!(out.text(), expected);
+    }
+
+    #[test]
+    fn test_poll_holds_back_partial_line() {
+        let temp = TempDir::new().unwrap();
+        let log = temp.path().join("aerodb.log");
+        append(&log, "");
+
+        let mut follower = LogFollower::from_end(&log).unwrap();
+        append(&log, "half");
+        assert!(follower.poll().unwrap().is_empty());
+        append(&log, " line\r\n");
+        assert_eq!(follower.poll().unwrap(), vec!["half line"]);
+    }
+
+    #[test]
+    fn test_poll_reopens_after_rotation() {
+        let temp = TempDir::new().unwrap();
+        let log = temp.path().join("aerodb.log");
+        append(&log, "before start\n");
+
+        let mut follower = LogFollower::from_end(&log).unwrap();
+        append(&log, "a\n");
+        fs::rename(&log, temp.path().join("aerodb.log.1")).unwrap();
+        assert_eq!(follower.poll().unwrap(), vec!["a"]);
+
+        append(&log, "b\n");
+        assert_eq!(follower.poll().unwrap(), vec!["b"]);
+        append(&log, "c\n");
+        assert_eq!(follower.poll().unwrap(), vec!["c"]);
+    }
+
+    #[test]
+    fn test_poll_restarts_after_truncation() {
+        let temp = TempDir::new().unwrap();
+        let log = temp.path().join("aerodb.log");
+        append(&log, "a long line that will be truncated away\n");
+
+        let mut follower = LogFollower::from_end(&log).unwrap();
+        OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&log)
+            .unwrap();
+        append(&log, "new\n");
+
+        assert_eq!(follower.poll().unwrap(), vec!["new"]);
+    }
+
+    #[test]
+    fn test_line_matches_level() {
+        assert!(line_matches_level("anything", None));
+        assert!(line_matches_level("[warn] disk", Some("WARN")));
+        assert!(!line_matches_level("[info] ok", Some("WARN")));
+    }
+}
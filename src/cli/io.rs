@@ -4,10 +4,15 @@
 //! - Input: single JSON object via stdin
 //! - Output: single JSON object via stdout
 //! - UTF-8 only
+//!
+//! The global `--output` flag selects how responses are rendered:
+//! `json` (compact, one object per line, the default), `pretty`
+//! (indented JSON) or `table` (aligned columns for humans).
 
 use std::io::{self, BufRead, Write};
 
-use serde_json::Value;
+use clap::ValueEnum;
+use serde_json::{Map, Value};
 
 use super::errors::{CliError, CliResult};
 
@@ -38,42 +43,260 @@ pub fn read_requests() -> impl Iterator<Item = CliResult<Value>> {
     })
 }
 
-/// Write a success response to stdout
-pub fn write_response(data: Value) -> CliResult<()> {
-    let response = serde_json::json!({
-        "status": "ok",
-        "data": data
-    });
-
-    let mut stdout = io::stdout();
-    serde_json::to_writer(&mut stdout, &response)?;
-    writeln!(stdout)?;
-    stdout.flush()?;
+/// Rendering of command responses on stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Compact JSON, one object per line (stable, for scripts)
+    #[default]
+    Json,
+    /// Indented JSON
+    Pretty,
+    /// Aligned key/value rows and columns
+    Table,
+}
 
-    Ok(())
+/// Render a success response
+///
+/// `json` and `pretty` emit the `{"status": "ok", "data": ...}` envelope;
+/// `table` renders `data` alone.
+pub fn format_response(format: OutputFormat, data: &Value) -> CliResult<String> {
+    match format {
+        OutputFormat::Table => Ok(render_table(data)),
+        _ => format_json(
+            format,
+            &serde_json::json!({
+                "status": "ok",
+                "data": data
+            }),
+        ),
+    }
 }
 
-/// Write an error response to stdout
-pub fn write_error(code: &str, message: &str) -> CliResult<()> {
+/// Render an error response
+pub fn format_error(format: OutputFormat, code: &str, message: &str) -> CliResult<String> {
     let response = serde_json::json!({
         "status": "error",
         "code": code,
         "message": message
     });
 
-    let mut stdout = io::stdout();
-    serde_json::to_writer(&mut stdout, &response)?;
-    writeln!(stdout)?;
-    stdout.flush()?;
+    match format {
+        OutputFormat::Table => Ok(render_table(&response)),
+        _ => format_json(format, &response),
+    }
+}
 
-    Ok(())
+fn format_json(format: OutputFormat, value: &Value) -> CliResult<String> {
+    let text = match format {
+        OutputFormat::Pretty => serde_json::to_string_pretty(value)?,
+        _ => serde_json::to_string(value)?,
+    };
+    Ok(text)
+}
+
+/// Write a success response to stdout
+pub fn write_response(format: OutputFormat, data: Value) -> CliResult<()> {
+    write_text(&format_response(format, &data)?)
+}
+
+/// Write an error response to stdout
+pub fn write_error(format: OutputFormat, code: &str, message: &str) -> CliResult<()> {
+    write_text(&format_error(format, code, message)?)
 }
 
 /// Write a raw JSON string to stdout
-pub fn write_json(json_str: &str) -> CliResult<()> {
+///
+/// The string is passed through untouched for `json`; other formats
+/// re-render it, falling back to the raw text if it does not parse.
+pub fn write_json(format: OutputFormat, json_str: &str) -> CliResult<()> {
+    let rendered = match (format, serde_json::from_str::<Value>(json_str)) {
+        (OutputFormat::Json, _) | (_, Err(_)) => json_str.to_string(),
+        (OutputFormat::Table, Ok(value)) => render_table(&value),
+        (_, Ok(value)) => format_json(format, &value)?,
+    };
+    write_text(&rendered)
+}
+
+fn write_text(text: &str) -> CliResult<()> {
     let mut stdout = io::stdout();
-    writeln!(stdout, "{}", json_str)?;
+    writeln!(stdout, "{}", text)?;
     stdout.flush()?;
 
     Ok(())
 }
+
+/// Render a value for `--output table`
+///
+/// Scalar fields of an object become aligned `key  value` rows. Fields
+/// holding a list of objects (migration status, backup lists) follow as
+/// a titled section with one column per key.
+fn render_table(value: &Value) -> String {
+    match value {
+        Value::Object(map) => render_object(map),
+        Value::Array(items) if is_record_list(items) => render_columns(items),
+        other => cell(other),
+    }
+}
+
+fn render_object(map: &Map<String, Value>) -> String {
+    let mut rows = Vec::new();
+    let mut sections = Vec::new();
+
+    for (key, value) in map {
+        match value {
+            Value::Array(items) if is_record_list(items) => {
+                sections.push(format!("{}:\n{}", key, render_columns(items)));
+            }
+            _ => rows.push(vec![key.clone(), cell(value)]),
+        }
+    }
+
+    let mut blocks = Vec::new();
+    if !rows.is_empty() {
+        blocks.push(align(&rows));
+    }
+    blocks.extend(sections);
+    blocks.join("\n\n")
+}
+
+/// A non-empty array whose elements are all objects
+fn is_record_list(items: &[Value]) -> bool {
+    !items.is_empty() && items.iter().all(Value::is_object)
+}
+
+fn render_columns(items: &[Value]) -> String {
+    let mut columns: Vec<&String> = Vec::new();
+    for item in items.iter().filter_map(Value::as_object) {
+        for key in item.keys() {
+            if !columns.contains(&key) {
+                columns.push(key);
+            }
+        }
+    }
+
+    let mut rows = vec![columns.iter().map(|c| c.to_uppercase()).collect()];
+    for item in items.iter().filter_map(Value::as_object) {
+        rows.push(
+            columns
+                .iter()
+                .map(|c| item.get(c.as_str()).map_or_else(|| "-".to_string(), cell))
+                .collect(),
+        );
+    }
+
+    align(&rows)
+}
+
+/// Pad every column but the last to its widest cell
+fn align(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|c| c.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            let mut line = String::new();
+            for (i, c) in row.iter().enumerate() {
+                if i + 1 < row.len() {
+                    line.push_str(&format!("{:<width$}  ", c, width = widths[i]));
+                } else {
+                    line.push_str(c);
+                }
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.is_empty() => "-".to_string(),
+        Value::Array(items) if !is_record_list(items) => {
+            items.iter().map(cell).collect::<Vec<_>>().join(", ")
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn migration_status() -> Value {
+        json!({
+            "current_version": 2,
+            "pending_count": 2,
+            "pending": [
+                {"version": 3, "name": "add_users_index"},
+                {"version": 10, "name": "drop_legacy"}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_json_output_is_compact_envelope() {
+        let out = format_response(OutputFormat::Json, &migration_status()).unwrap();
+        assert!(!out.contains('\n'));
+
+        let parsed: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed["status"], "ok");
+        assert_eq!(parsed["data"], migration_status());
+
+        let err = format_error(OutputFormat::Json, "AERO_X", "boom").unwrap();
+        assert_eq!(
+            err,
+            r#"{"code":"AERO_X","message":"boom","status":"error"}"#
+        );
+    }
+
+    #[test]
+    fn test_pretty_output_is_indented_envelope() {
+        let out = format_response(OutputFormat::Pretty, &migration_status()).unwrap();
+        assert!(out.contains("\n  \"data\": {"));
+
+        let parsed: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed["data"], migration_status());
+    }
+
+    #[test]
+    fn test_table_output_aligns_columns() {
+        let out = format_response(OutputFormat::Table, &migration_status()).unwrap();
+        let expected = "\
+current_version  2
+pending_count    2
+
+pending:
+NAME             VERSION
+add_users_index  3
+drop_legacy      10";
+        assert_eq!(out, expected);
+
+        let err = format_error(OutputFormat::Table, "AERO_X", "boom").unwrap();
+        assert_eq!(err, "code     AERO_X\nmessage  boom\nstatus   error");
+    }
+
+    #[test]
+    fn test_table_output_of_list_and_missing_fields() {
+        let backups = json!([
+            {"id": "b1", "size": 10},
+            {"id": "backup-2", "verified": true}
+        ]);
+        let expected = "\
+ID        SIZE  VERIFIED
+b1        10    -
+backup-2  -     true";
+        assert_eq!(render_table(&backups), expected);
+        assert_eq!(render_table(&json!({"pending": []})), "pending  -");
+    }
+}
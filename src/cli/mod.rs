@@ -8,10 +8,17 @@
 
 mod args;
 mod commands;
+mod doctor;
 mod errors;
+mod follow;
 mod io;
+mod reload;
+mod shutdown;
+mod snapshots;
+mod stats;
+mod transfer;
 
 pub use args::{Cli, Command};
 pub use commands::{explain, init, query, run, run_command, start};
 pub use errors::{CliError, CliResult};
-pub use io::{read_request, write_error, write_response};
+pub use io::{read_request, write_error, write_response, OutputFormat};
@@ -0,0 +1,212 @@
+//! Config file reload for `aerodb serve`
+//!
+//! The serving process reloads its config file when the file changes on
+//! disk, when it receives SIGHUP, or on `POST /admin/v1/config/reload`.
+//! The file goes through the same parsing and `config_validator` checks as
+//! at boot; only runtime-tunable fields are then applied (see
+//! `config_reload`).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use serde_json::Value;
+
+use crate::config_reload::{ConfigReload, ReloadError, ReloadReport, RuntimeSettings};
+use crate::config_validator::AeroConfig;
+use crate::observability::Event;
+
+/// How often `watch` checks the config file for changes
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Reloads one config file into a running server
+pub struct ConfigReloader {
+    path: PathBuf,
+    settings: RuntimeSettings,
+    /// Configuration in effect, as JSON
+    running: Mutex<Value>,
+    /// Modification time and length of the file at the last reload
+    stamp: Mutex<Option<(SystemTime, u64)>>,
+}
+
+impl ConfigReloader {
+    /// `config` is the configuration the server booted with from `path`
+    pub(super) fn new(path: &Path, config: &AeroConfig, settings: RuntimeSettings) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            settings,
+            running: Mutex::new(serde_json::to_value(config).expect("config serializes")),
+            stamp: Mutex::new(file_stamp(path)),
+        }
+    }
+
+    /// True if the file was modified since the last check or reload
+    fn changed_on_disk(&self) -> bool {
+        let stamp = file_stamp(&self.path);
+        let mut last = self.stamp.lock().unwrap();
+        if stamp.is_none() || stamp == *last {
+            return false;
+        }
+        *last = stamp;
+        true
+    }
+
+    /// Reload on SIGHUP and whenever the file changes, until the runtime stops
+    pub async fn watch(self: Arc<Self>, interval: Duration) {
+        #[cfg(unix)]
+        let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        {
+            Ok(signal) => Some(signal),
+            Err(e) => {
+                self.settings.logger().warn(
+                    Event::ConfigReloadSighupUnavailable.as_str(),
+                    &[("error", &e.to_string())],
+                );
+                None
+            }
+        };
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            #[cfg(unix)]
+            let triggered = tokio::select! {
+                Some(()) = async { hangup.as_mut()?.recv().await } => true,
+                _ = ticker.tick() => self.changed_on_disk(),
+            };
+            #[cfg(not(unix))]
+            let triggered = {
+                ticker.tick().await;
+                self.changed_on_disk()
+            };
+
+            if triggered {
+                let reloader = Arc::clone(&self);
+                let error = match tokio::task::spawn_blocking(move || reloader.reload()).await {
+                    Ok(Err(e)) => e.to_string(),
+                    Err(e) => e.to_string(),
+                    Ok(Ok(_)) => continue,
+                };
+                self.settings.logger().error(
+                    Event::ConfigReloadFailed.as_str(),
+                    &[
+                        ("path", &self.path.display().to_string()),
+                        ("error", &error),
+                    ],
+                );
+            }
+        }
+    }
+}
+
+impl ConfigReload for ConfigReloader {
+    fn reload(&self) -> Result<ReloadReport, ReloadError> {
+        *self.stamp.lock().unwrap() = file_stamp(&self.path);
+
+        let config = AeroConfig::read(&self.path).map_err(|e| ReloadError::new(e.message()))?;
+        let validation = config.validation_report();
+        if validation.has_errors() {
+            return Err(ReloadError {
+                message: format!("{} failed validation", self.path.display()),
+                errors: validation.errors,
+            });
+        }
+        let loaded = serde_json::to_value(&config)
+            .map_err(|e| ReloadError::new(format!("Failed to serialize config: {}", e)))?;
+
+        let mut running = self.running.lock().unwrap();
+        let (report, next) = self.settings.apply(&running, &loaded)?;
+        *running = next;
+        Ok(report)
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::slow_query::SlowQueryTracker;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn write_config(path: &Path, data_dir: &Path, threshold_ms: u64) {
+        let config = format!(
+            "[server]\ndata_dir = '{}'\n\n\
+             [observability.slow_query]\nenabled = true\nthreshold_ms = {}\n",
+            data_dir.display(),
+            threshold_ms
+        );
+        fs::write(path, config).unwrap();
+    }
+
+    fn reloader(dir: &TempDir) -> (ConfigReloader, Arc<SlowQueryTracker>) {
+        let path = dir.path().join("aerodb.toml");
+        write_config(&path, &dir.path().join("data"), 100);
+        let config = AeroConfig::load(&path).unwrap();
+        let tracker = Arc::new(SlowQueryTracker::new(
+            config.observability.slow_query.clone(),
+        ));
+        let settings = RuntimeSettings::new().with_slow_query_tracker(tracker.clone());
+        (ConfigReloader::new(&path, &config, settings), tracker)
+    }
+
+    #[test]
+    fn test_reload_applies_threshold_and_refuses_data_dir() {
+        let dir = TempDir::new().unwrap();
+        let (reloader, tracker) = reloader(&dir);
+        assert!(tracker.is_slow(150));
+
+        let path = dir.path().join("aerodb.toml");
+        write_config(&path, &dir.path().join("elsewhere"), 200);
+        assert!(reloader.changed_on_disk());
+        let report = reloader.reload().unwrap();
+
+        assert_eq!(tracker.threshold_ms(), 200);
+        assert!(!tracker.is_slow(150));
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(
+            report.applied[0].field,
+            "observability.slow_query.threshold_ms"
+        );
+        assert_eq!(report.applied[0].old, json!(100));
+        assert_eq!(report.applied[0].new, json!(200));
+        assert_eq!(report.refused.len(), 1);
+        assert_eq!(report.refused[0].change.field, "server.data_dir");
+
+        // The refused change is still pending; the applied one is not
+        let again = reloader.reload().unwrap();
+        assert!(again.applied.is_empty());
+        assert_eq!(again.refused[0].change.field, "server.data_dir");
+    }
+
+    #[test]
+    fn test_reload_rejects_invalid_file() {
+        let dir = TempDir::new().unwrap();
+        let (reloader, tracker) = reloader(&dir);
+
+        let path = dir.path().join("aerodb.toml");
+        write_config(&path, &dir.path().join("data"), 500);
+        let mut config = fs::read_to_string(&path).unwrap();
+        config.push_str("\n[wal]\nmax_size_bytes = 0\n");
+        fs::write(&path, config).unwrap();
+
+        let err = reloader.reload().unwrap_err();
+        assert_eq!(err.errors[0].field, "wal.max_size_bytes");
+        assert_eq!(tracker.threshold_ms(), 100);
+
+        fs::write(&path, "[server\n").unwrap();
+        assert!(reloader.reload().is_err());
+        assert_eq!(tracker.threshold_ms(), 100);
+    }
+
+    #[test]
+    fn test_unchanged_file_is_not_reported_changed() {
+        let dir = TempDir::new().unwrap();
+        let (reloader, _) = reloader(&dir);
+        assert!(!reloader.changed_on_disk());
+    }
+}
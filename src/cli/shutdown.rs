@@ -0,0 +1,93 @@
+//! Graceful shutdown of `aerodb start` and `aerodb serve`
+//!
+//! On SIGTERM or SIGINT the process stops taking new requests and lets the
+//! running ones finish. The WAL is then fsynced, incremental statistics are
+//! saved and the `clean_shutdown` marker is written before the process
+//! exits 0 (CORE_LIFECYCLE.md §7).
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use super::errors::{CliError, CliResult};
+use crate::planner::Statistics;
+use crate::wal::WalWriter;
+
+/// SIGTERM and SIGINT, caught from the moment the listener is installed
+pub(super) struct ShutdownSignal {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+}
+
+impl ShutdownSignal {
+    /// Replace the default handlers; must be called within a tokio runtime
+    pub(super) fn install() -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            Ok(Self {
+                terminate: signal(SignalKind::terminate())?,
+                interrupt: signal(SignalKind::interrupt())?,
+            })
+        }
+        #[cfg(not(unix))]
+        Ok(Self {})
+    }
+
+    /// Resolve on the first signal
+    pub(super) async fn recv(mut self) {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = self.terminate.recv() => {}
+            _ = self.interrupt.recv() => {}
+        }
+        #[cfg(not(unix))]
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Send `event` on the first SIGTERM or SIGINT
+///
+/// The handlers are installed before this returns; the signal is awaited
+/// on a background thread.
+pub(super) fn notify_on_signal<T: Send + 'static>(sender: Sender<T>, event: T) -> CliResult<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| CliError::boot_failed(format!("Failed to create tokio runtime: {}", e)))?;
+    let signal = {
+        let _context = runtime.enter();
+        ShutdownSignal::install()
+            .map_err(|e| CliError::boot_failed(format!("Signal handler failed: {}", e)))?
+    };
+    thread::Builder::new()
+        .name("aerodb-shutdown".to_string())
+        .spawn(move || {
+            runtime.block_on(signal.recv());
+            let _ = sender.send(event);
+        })?;
+    Ok(())
+}
+
+/// Make every write durable and mark the shutdown as clean
+///
+/// Statistics are advisory and a failure to save them does not prevent the
+/// marker; a failed WAL fsync does, so the next start treats it as a crash.
+pub(super) fn finish(
+    data_dir: &Path,
+    wal_writer: &WalWriter,
+    statistics: &mut Statistics,
+) -> CliResult<()> {
+    wal_writer
+        .fsync()
+        .map_err(|e| CliError::io_error(format!("WAL fsync at shutdown failed: {}", e)))?;
+    let _ = statistics.save();
+    fs::write(data_dir.join("clean_shutdown"), "")?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! Base snapshots for bootstrapping replicas
+//!
+//! While `aerodb serve` runs as a primary, a replica with an empty data
+//! directory is sent a snapshot taken on demand. The snapshot is fenced
+//! under the server's `Engine` lock, so it matches a WAL position exactly;
+//! the storage file is copied after the lock is released.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::transfer::Engine;
+use crate::replication::{BaseSnapshotSource, LatestSnapshot, ReplicationError, ReplicationResult};
+use crate::snapshot::{
+    generate_snapshot_id, GlobalExecutionLock, SnapshotConfig, SnapshotId, SnapshotManager,
+};
+
+/// Snapshots of the database `aerodb serve` runs, taken when a replica asks
+pub struct LocalSnapshots {
+    engine: Arc<Mutex<Engine>>,
+    data_dir: PathBuf,
+    /// One snapshot at a time: ids have one-second granularity
+    taking: Mutex<()>,
+}
+
+impl LocalSnapshots {
+    pub(super) fn new(engine: Arc<Mutex<Engine>>, data_dir: &Path) -> Self {
+        Self {
+            engine,
+            data_dir: data_dir.to_path_buf(),
+            taking: Mutex::new(()),
+        }
+    }
+}
+
+impl fmt::Debug for LocalSnapshots {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalSnapshots")
+            .field("data_dir", &self.data_dir)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BaseSnapshotSource for LocalSnapshots {
+    fn base_snapshot(&self) -> ReplicationResult<SnapshotId> {
+        let _taking = self.taking.lock().unwrap();
+
+        // A snapshot from this second is as fresh as a new one, and would
+        // share its id
+        if let Ok(latest) = LatestSnapshot::new(&self.data_dir).base_snapshot() {
+            if latest == generate_snapshot_id() {
+                return Ok(latest);
+            }
+        }
+
+        let pending = {
+            let engine = self.engine.lock().unwrap();
+            SnapshotManager::begin_snapshot(
+                &self.data_dir,
+                engine.storage_writer.path(),
+                &self.data_dir.join("metadata").join("schemas"),
+                &engine.wal_writer,
+                &GlobalExecutionLock::new(),
+            )
+        }
+        .map_err(snapshot_failed)?;
+        pending
+            .complete(&SnapshotConfig::default())
+            .map_err(snapshot_failed)
+    }
+}
+
+fn snapshot_failed(e: crate::snapshot::SnapshotError) -> ReplicationError {
+    ReplicationError::bootstrap_failed(format!("Base snapshot failed: {}", e))
+}
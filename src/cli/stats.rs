@@ -0,0 +1,285 @@
+//! Database statistics over a booted database
+//!
+//! Backs the admin statistics endpoints while `aerodb serve` runs. Shares
+//! the subsystems of the server's `LocalTransfer`, so a request waits for
+//! a running import or export. Every answer comes from maintained
+//! counters, directory listings and small marker files; nothing is scanned.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+
+use chrono::{DateTime, Utc};
+
+use super::transfer::Engine;
+use crate::checkpoint::{marker_path, CheckpointMarker};
+use crate::observability::{
+    describe_collection, summarize_collections, CollectionDetail, CollectionSummary, DatabaseStats,
+    ReplicaLagSummary, ReplicationSummary, SystemSummary,
+};
+use crate::replication::{replica_lag_at, ReplicaTracker};
+
+/// Statistics of the database `aerodb serve` runs
+pub struct LocalStats {
+    engine: Arc<Mutex<Engine>>,
+    data_dir: PathBuf,
+    backup_dir: PathBuf,
+    /// `standalone` or `primary`; a replica serves no statistics
+    role: String,
+    started_at: Instant,
+}
+
+impl LocalStats {
+    pub(super) fn new(
+        engine: Arc<Mutex<Engine>>,
+        data_dir: &Path,
+        backup_dir: &Path,
+        role: &str,
+        started_at: Instant,
+    ) -> Self {
+        Self {
+            engine,
+            data_dir: data_dir.to_path_buf(),
+            backup_dir: backup_dir.to_path_buf(),
+            role: role.to_string(),
+            started_at,
+        }
+    }
+
+    fn replicas(&self, last_sequence: u64) -> Vec<ReplicaLagSummary> {
+        if self.role != "primary" {
+            return Vec::new();
+        }
+        // Advisory: a missing or unreadable progress file reports no replicas
+        let Ok(progress) = ReplicaTracker::load(&self.data_dir) else {
+            return Vec::new();
+        };
+        replica_lag_at(&self.data_dir.join("wal"), &progress, last_sequence)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|lag| ReplicaLagSummary {
+                replica_id: lag.replica_id.to_string(),
+                connected: lag.connected,
+                lag_records: lag.lag_records,
+                lag_bytes: lag.lag_bytes,
+            })
+            .collect()
+    }
+}
+
+impl DatabaseStats for LocalStats {
+    fn collections(&self) -> Vec<CollectionSummary> {
+        let engine = self.engine.lock().unwrap();
+        summarize_collections(
+            &engine.schema_loader,
+            &engine.statistics,
+            &engine.storage_writer,
+            &engine.index_manager,
+        )
+    }
+
+    fn collection(&self, name: &str) -> Option<CollectionDetail> {
+        let engine = self.engine.lock().unwrap();
+        describe_collection(
+            name,
+            &engine.schema_loader,
+            &engine.statistics,
+            &engine.storage_writer,
+            &engine.index_manager,
+        )
+    }
+
+    fn system(&self) -> SystemSummary {
+        let (resources, wal_sequence) = {
+            let engine = self.engine.lock().unwrap();
+            (
+                engine.resource_manager.get_status().ok(),
+                engine.wal_writer.last_sequence_number(),
+            )
+        };
+        SystemSummary {
+            resources,
+            wal_bytes: directory_bytes(&self.data_dir.join("wal")),
+            wal_sequence,
+            last_checkpoint: CheckpointMarker::read_from_file(&marker_path(&self.data_dir))
+                .ok()
+                .map(|marker| marker.created_at),
+            last_backup: newest_backup(&self.backup_dir),
+            replication: ReplicationSummary {
+                role: self.role.clone(),
+                replicas: self.replicas(wal_sequence),
+            },
+            uptime_secs: self.started_at.elapsed().as_secs(),
+        }
+    }
+}
+
+/// Total size of the files directly in `dir`, 0 if it cannot be listed
+fn directory_bytes(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Modification time of the newest `.tar` archive in `dir`
+fn newest_backup(dir: &Path) -> Option<DateTime<Utc>> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            if entry.path().extension()? != "tar" {
+                return None;
+            }
+            entry.metadata().ok()?.modified().ok()
+        })
+        .max()
+        .map(|modified: SystemTime| modified.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admission_control::{AdmissionControlConfig, AdmissionController};
+    use crate::api::ApiHandler;
+    use crate::backpressure::{BackpressureConfig, BackpressureManager};
+    use crate::index::IndexManager;
+    use crate::planner::{Statistics, StatisticsConfig};
+    use crate::query_limits::QueryLimitsConfig;
+    use crate::resource_limits::{ResourceLimitsConfig, ResourceManager};
+    use crate::schema::{FieldDef, Schema, SchemaLoader};
+    use crate::storage::{StorageReader, StorageWriter};
+    use crate::transfer::{DataTransfer, ImportMode, ImportOptions, TransferFormat};
+    use crate::wal::WalWriter;
+    use serde_json::json;
+    use std::collections::{HashMap, HashSet};
+    use std::io;
+    use tempfile::TempDir;
+
+    use super::super::transfer::LocalTransfer;
+
+    /// A database with a `users` collection indexed on `age`
+    fn database(data_dir: &Path) -> LocalTransfer {
+        let mut schema_loader = SchemaLoader::new(data_dir);
+        let mut fields = HashMap::new();
+        fields.insert("_id".to_string(), FieldDef::required_string());
+        fields.insert("name".to_string(), FieldDef::required_string());
+        fields.insert("age".to_string(), FieldDef::optional_int());
+        schema_loader
+            .register(Schema::new("users", "v1", fields))
+            .unwrap();
+
+        let resource_config = ResourceLimitsConfig {
+            min_free_disk_bytes: 0,
+            ..Default::default()
+        };
+        let engine = Engine {
+            wal_writer: WalWriter::open(data_dir).unwrap(),
+            storage_writer: StorageWriter::open(data_dir).unwrap(),
+            storage_reader: StorageReader::open_from_data_dir(data_dir).unwrap(),
+            schema_loader,
+            index_manager: IndexManager::new(HashSet::from(["age".to_string()])),
+            statistics: Statistics::in_memory(StatisticsConfig::default()),
+            resource_manager: Arc::new(ResourceManager::new(resource_config, data_dir)),
+            backpressure_manager: Arc::new(BackpressureManager::new(BackpressureConfig::default())),
+            admission_controller: Arc::new(AdmissionController::new(
+                AdmissionControlConfig::default(),
+            )),
+            query_limits: QueryLimitsConfig::default(),
+        };
+        LocalTransfer::new(ApiHandler::new("default"), engine)
+    }
+
+    fn import(transfer: &LocalTransfer, mode: ImportMode, rows: &str) {
+        let options = ImportOptions {
+            mode,
+            ..ImportOptions::new("users", "v1", TransferFormat::Jsonl)
+        };
+        let report = transfer
+            .import(&options, &mut rows.as_bytes(), &mut io::sink())
+            .unwrap();
+        assert_eq!(report.rejected, 0);
+    }
+
+    #[test]
+    fn test_stats_after_workload() {
+        let temp = TempDir::new().unwrap();
+        let backup_dir = temp.path().join("backups");
+        fs::create_dir_all(&backup_dir).unwrap();
+        fs::write(backup_dir.join("backup_1.tar"), b"archive").unwrap();
+
+        // Three inserts, then one of them rewritten
+        let transfer = database(temp.path());
+        import(
+            &transfer,
+            ImportMode::Insert,
+            "{\"_id\":\"a\",\"name\":\"Ann\",\"age\":30}\n\
+             {\"_id\":\"b\",\"name\":\"Bob\",\"age\":41}\n\
+             {\"_id\":\"c\",\"name\":\"Cy\"}\n",
+        );
+        import(
+            &transfer,
+            ImportMode::Upsert,
+            "{\"_id\":\"b\",\"name\":\"Bob\",\"age\":42}\n",
+        );
+        let stats = LocalStats::new(
+            transfer.engine(),
+            temp.path(),
+            &backup_dir,
+            "standalone",
+            Instant::now(),
+        );
+
+        let collections = stats.collections();
+        assert_eq!(collections.len(), 1);
+        let users = &collections[0];
+        assert_eq!(users.name, "users");
+        assert_eq!(users.schema_version, "v1");
+        assert_eq!(users.document_count, 3);
+        assert_eq!(users.storage_records, 4);
+        let storage_len = fs::metadata(temp.path().join("data").join("documents.dat"))
+            .unwrap()
+            .len();
+        assert_eq!(users.storage_bytes, storage_len);
+        assert!(users.last_write.is_some());
+        let indexes: Vec<(&str, Option<u64>)> = users
+            .indexes
+            .iter()
+            .map(|index| (index.name.as_str(), index.entries))
+            .collect();
+        assert_eq!(indexes, [("_id", Some(3)), ("age", Some(2))]);
+
+        let detail = json!(stats.collection("users").unwrap());
+        assert_eq!(detail["document_count"], 3);
+        let field = |name: &str| {
+            detail["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|field| field["name"] == name)
+                .unwrap()
+                .clone()
+        };
+        let age = field("age");
+        assert_eq!(age["type"], "int");
+        assert_eq!(age["indexed"], true);
+        assert!((age["null_ratio"].as_f64().unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert!(age["distinct_estimate"].as_u64().unwrap() >= 2);
+        assert!(field("name")["null_ratio"].is_null());
+        assert!(stats.collection("missing").is_none());
+
+        let system = stats.system();
+        assert_eq!(system.wal_sequence, 4);
+        assert!(system.wal_bytes > 0);
+        assert!(system.resources.is_some());
+        assert!(system.last_backup.is_some());
+        assert!(system.last_checkpoint.is_none());
+        assert_eq!(system.replication.role, "standalone");
+        assert!(system.replication.replicas.is_empty());
+    }
+}
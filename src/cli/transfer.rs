@@ -0,0 +1,147 @@
+//! Bulk import and export over a booted database
+//!
+//! Backs `aerodb export`, `aerodb import` and, while `aerodb serve` runs,
+//! the admin HTTP import and export endpoints. Requests are served one at
+//! a time: the subsystems sit behind a single lock.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::admission_control::AdmissionController;
+use crate::api::{ApiHandler, Subsystems};
+use crate::backpressure::BackpressureManager;
+use crate::index::IndexManager;
+use crate::planner::Statistics;
+use crate::query_limits::QueryLimitsConfig;
+use crate::resource_limits::ResourceManager;
+use crate::schema::SchemaLoader;
+use crate::storage::{StorageReader, StorageWriter};
+use crate::transfer::{
+    self, DataTransfer, ExportOptions, ExportReport, ImportOptions, ImportReport, TransferError,
+    TransferResult,
+};
+use crate::wal::WalWriter;
+
+/// The subsystems a transfer writes through
+pub(super) struct Engine {
+    pub(super) wal_writer: WalWriter,
+    pub(super) storage_writer: StorageWriter,
+    pub(super) storage_reader: StorageReader,
+    pub(super) schema_loader: SchemaLoader,
+    pub(super) index_manager: IndexManager,
+    pub(super) statistics: Statistics,
+    pub(super) resource_manager: Arc<ResourceManager>,
+    pub(super) backpressure_manager: Arc<BackpressureManager>,
+    pub(super) admission_controller: Arc<AdmissionController>,
+    pub(super) query_limits: QueryLimitsConfig,
+}
+
+/// Imports and exports through the ordinary API of one database
+pub struct LocalTransfer {
+    handler: ApiHandler,
+    engine: Arc<Mutex<Engine>>,
+}
+
+impl LocalTransfer {
+    pub(super) fn new(handler: ApiHandler, engine: Engine) -> Self {
+        Self {
+            handler,
+            engine: Arc::new(Mutex::new(engine)),
+        }
+    }
+
+    /// The subsystems, to share with other users of the same database
+    pub(super) fn engine(&self) -> Arc<Mutex<Engine>> {
+        Arc::clone(&self.engine)
+    }
+
+    fn with_subsystems<T>(&self, f: impl FnOnce(&ApiHandler, &mut Subsystems<'_>) -> T) -> T {
+        let mut engine = self.engine.lock().unwrap();
+        let engine = &mut *engine;
+        let mut subsystems = Subsystems {
+            schema_loader: &mut engine.schema_loader,
+            wal_writer: &mut engine.wal_writer,
+            storage_writer: &mut engine.storage_writer,
+            storage_reader: &mut engine.storage_reader,
+            index_manager: &mut engine.index_manager,
+            statistics: &mut engine.statistics,
+            resource_manager: &engine.resource_manager,
+            backpressure_manager: &engine.backpressure_manager,
+            admission_controller: &engine.admission_controller,
+            query_limits: &engine.query_limits,
+        };
+        f(&self.handler, &mut subsystems)
+    }
+}
+
+impl DataTransfer for LocalTransfer {
+    fn export(&self, options: &ExportOptions, out: &mut dyn Write) -> TransferResult<ExportReport> {
+        self.with_subsystems(|handler, sys| transfer::export(options, handler, sys, out))
+    }
+
+    fn import(
+        &self,
+        options: &ImportOptions,
+        input: &mut dyn Read,
+        rejects: &mut dyn Write,
+    ) -> TransferResult<ImportReport> {
+        self.with_subsystems(|handler, sys| {
+            let report = transfer::import(options, handler, sys, input, rejects);
+            // Persist incremental statistics (advisory: failure is not fatal)
+            let _ = sys.statistics.save();
+            // In group commit mode the import's writes share one sync,
+            // before the report acknowledges them
+            sync_deferred(sys)?;
+            report
+        })
+    }
+}
+
+/// Sync the writes whose syncs were deferred (group commit), the WAL first
+fn sync_deferred(sys: &mut Subsystems<'_>) -> TransferResult<()> {
+    let failed = |e: &dyn std::fmt::Display| TransferError::Io {
+        message: format!("Group commit sync failed (FATAL): {}", e),
+    };
+    sys.wal_writer.sync_deferred().map_err(|e| failed(&e))?;
+    sys.storage_writer.sync_deferred().map_err(|e| failed(&e))
+}
+
+/// Rejects file, created when the first rejected row is written so a clean
+/// import leaves nothing behind
+pub(super) struct RejectsFile {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl RejectsFile {
+    pub(super) fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            file: None,
+        }
+    }
+
+    /// The path, if anything was written
+    pub(super) fn written(&self) -> Option<&Path> {
+        self.file.as_ref().map(|_| self.path.as_path())
+    }
+}
+
+impl Write for RejectsFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(File::create(&self.path)?),
+        };
+        file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
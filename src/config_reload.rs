@@ -0,0 +1,613 @@
+//! Configuration Hot-Reload
+//!
+//! Applies a re-read configuration file to a running server. Only the
+//! fields in [`RUNTIME_TUNABLE`] take effect; every other change
+//! (server.data_dir, wal.sync_mode, replication.role, ...) needs a restart
+//! and is refused.
+//!
+//! Reload is field-by-field: the running and reloaded configurations are
+//! compared as JSON, and each differing leaf is either applied or refused.
+//! A tunable field is applied only once the running component it belongs
+//! to has taken the new value. Refused fields keep their running value, so
+//! the next reload reports them again until the file and the server agree.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::admission_control::AdmissionController;
+use crate::backpressure::{BackpressureConfig, BackpressureManager};
+use crate::config_validator::ConfigValidationError;
+use crate::observability::slow_query::{SlowQueryConfig, SlowQueryTracker};
+use crate::observability::{Event, JsonLogger, Logger, OperationLog, Severity};
+use crate::resource_limits::ResourceManager;
+
+/// Config fields that can change without a restart
+///
+/// An entry covers the field itself and everything nested under it.
+pub const RUNTIME_TUNABLE: &[&str] = &[
+    "observability.slow_query",
+    "observability.operation_log.enabled",
+    "backpressure",
+    "admission_control.max_writes_per_second",
+    "resource_limits.warning_threshold_percent",
+    "resource_limits.critical_threshold_percent",
+    "observability.log_level",
+];
+
+/// Value shown in place of secrets in reports and logs
+const HIDDEN: &str = "<hidden>";
+
+/// A config field whose value differs between two configurations
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    /// Dotted path, e.g. `observability.slow_query.threshold_ms`
+    pub field: String,
+    /// Running value (`null` if the field was absent)
+    pub old: Value,
+    /// Value in the reloaded file (`null` if the field was removed)
+    pub new: Value,
+}
+
+/// A change that was not applied
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RefusedChange {
+    #[serde(flatten)]
+    pub change: FieldChange,
+    /// Why the change was refused
+    pub reason: String,
+}
+
+/// Outcome of one reload
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReloadReport {
+    /// Changes now in effect
+    pub applied: Vec<FieldChange>,
+    /// Changes that need a restart and were ignored
+    pub refused: Vec<RefusedChange>,
+}
+
+impl ReloadReport {
+    /// True if nothing in the file differed from the running configuration
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.refused.is_empty()
+    }
+}
+
+/// Reload failed before any field was compared
+///
+/// Nothing was applied: the file could not be read or parsed, or it
+/// failed validation.
+#[derive(Debug, Serialize)]
+pub struct ReloadError {
+    pub message: String,
+    /// Validation errors, when the file parsed but was invalid
+    pub errors: Vec<ConfigValidationError>,
+}
+
+impl ReloadError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        for error in &self.errors {
+            write!(f, "; {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ReloadError {}
+
+/// Something that can re-read its configuration on request
+///
+/// Implemented by the CLI's config reloader; the admin HTTP endpoint only
+/// needs this trait.
+pub trait ConfigReload: Send + Sync {
+    fn reload(&self) -> Result<ReloadReport, ReloadError>;
+}
+
+/// Live components whose settings a reload may change
+///
+/// A change to a field whose component is not registered is refused: no
+/// running component would take it.
+pub struct RuntimeSettings {
+    slow_query: Option<Arc<SlowQueryTracker>>,
+    operation_log: Option<Arc<OperationLog>>,
+    backpressure: Option<Arc<BackpressureManager>>,
+    admission: Option<Arc<AdmissionController>>,
+    resources: Option<Arc<ResourceManager>>,
+    log_level: Option<fn(Severity)>,
+    logger: Arc<dyn Logger>,
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        Self {
+            slow_query: None,
+            operation_log: None,
+            backpressure: None,
+            admission: None,
+            resources: None,
+            log_level: None,
+            logger: JsonLogger::shared(),
+        }
+    }
+}
+
+impl RuntimeSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log applied and refused changes to `logger`
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    pub fn with_slow_query_tracker(mut self, tracker: Arc<SlowQueryTracker>) -> Self {
+        self.slow_query = Some(tracker);
+        self
+    }
+
+    pub fn with_operation_log(mut self, log: Arc<OperationLog>) -> Self {
+        self.operation_log = Some(log);
+        self
+    }
+
+    pub fn with_backpressure(mut self, manager: Arc<BackpressureManager>) -> Self {
+        self.backpressure = Some(manager);
+        self
+    }
+
+    pub fn with_admission_controller(mut self, controller: Arc<AdmissionController>) -> Self {
+        self.admission = Some(controller);
+        self
+    }
+
+    pub fn with_resource_manager(mut self, manager: Arc<ResourceManager>) -> Self {
+        self.resources = Some(manager);
+        self
+    }
+
+    /// Apply `observability.log_level` by calling `set`, e.g.
+    /// [`JsonLogger::set_min_severity`]
+    pub fn with_log_level(mut self, set: fn(Severity)) -> Self {
+        self.log_level = Some(set);
+        self
+    }
+
+    /// Apply the tunable differences between `running` and `loaded`.
+    ///
+    /// Both are full configurations serialized to JSON. Returns the report
+    /// and the new running configuration: `running` with every applied
+    /// change written in. Each applied and refused change is logged.
+    pub fn apply(
+        &self,
+        running: &Value,
+        loaded: &Value,
+    ) -> Result<(ReloadReport, Value), ReloadError> {
+        let changes = diff(running, loaded);
+        let mut candidate = running.clone();
+        for change in changes.iter().filter(|c| is_tunable(&c.field)) {
+            set_path(&mut candidate, &change.field, change.new.clone());
+        }
+        let accepted = if candidate == *running {
+            Vec::new()
+        } else {
+            self.reconfigure(&changes, &candidate)?
+        };
+
+        let mut report = ReloadReport::default();
+        let mut next = running.clone();
+        for change in changes {
+            let reason = if !is_tunable(&change.field) {
+                "Requires a restart to take effect"
+            } else if !accepted.iter().any(|prefix| covers(prefix, &change.field)) {
+                "No running component uses this setting"
+            } else {
+                set_path(&mut next, &change.field, change.new.clone());
+                report.applied.push(redacted(change));
+                continue;
+            };
+            report.refused.push(RefusedChange {
+                change: redacted(change),
+                reason: reason.to_string(),
+            });
+        }
+        self.log_report(&report);
+        Ok((report, next))
+    }
+
+    /// Hand the tunable sections of `candidate` touched by `changes` to
+    /// their components; returns the [`RUNTIME_TUNABLE`] entries taken.
+    fn reconfigure(
+        &self,
+        changes: &[FieldChange],
+        candidate: &Value,
+    ) -> Result<Vec<&'static str>, ReloadError> {
+        // Check every section before touching any component, so a bad
+        // value leaves the server as it was
+        let section = |path: &str| lookup(candidate, path).cloned().unwrap_or(Value::Null);
+        let slow_query: SlowQueryConfig = parse(&section("observability.slow_query"))?;
+        let operation_log_enabled: bool = parse(&section("observability.operation_log.enabled"))?;
+        let backpressure: BackpressureConfig = parse(&section("backpressure"))?;
+        let max_writes: u32 = parse(&section("admission_control.max_writes_per_second"))?;
+        let warning: u8 = parse(&section("resource_limits.warning_threshold_percent"))?;
+        let critical: u8 = parse(&section("resource_limits.critical_threshold_percent"))?;
+        let log_level: Severity = parse(&section("observability.log_level"))?;
+        if warning > critical {
+            return Err(ReloadError::new(format!(
+                "resource_limits: warning threshold {}% exceeds critical threshold {}%",
+                warning, critical
+            )));
+        }
+
+        let touched = |prefix: &str| changes.iter().any(|c| covers(prefix, &c.field));
+        let mut accepted = Vec::new();
+        if let Some(tracker) = &self.slow_query {
+            if touched("observability.slow_query") {
+                tracker.reconfigure(slow_query);
+                accepted.push("observability.slow_query");
+            }
+        }
+        if let Some(log) = &self.operation_log {
+            if touched("observability.operation_log.enabled") {
+                log.set_enabled(operation_log_enabled);
+                accepted.push("observability.operation_log.enabled");
+            }
+        }
+        if let Some(manager) = &self.backpressure {
+            if touched("backpressure") {
+                manager.reconfigure(backpressure);
+                accepted.push("backpressure");
+            }
+        }
+        if let Some(controller) = &self.admission {
+            if touched("admission_control.max_writes_per_second") {
+                controller.set_max_writes_per_second(max_writes);
+                accepted.push("admission_control.max_writes_per_second");
+            }
+        }
+        if let Some(manager) = &self.resources {
+            if touched("resource_limits") {
+                manager.set_thresholds(warning, critical);
+                accepted.push("resource_limits.warning_threshold_percent");
+                accepted.push("resource_limits.critical_threshold_percent");
+            }
+        }
+        if let Some(set_log_level) = self.log_level {
+            if touched("observability.log_level") {
+                set_log_level(log_level);
+                accepted.push("observability.log_level");
+            }
+        }
+        Ok(accepted)
+    }
+
+    /// Where applied and refused changes are logged
+    pub fn logger(&self) -> &dyn Logger {
+        self.logger.as_ref()
+    }
+
+    fn log_report(&self, report: &ReloadReport) {
+        for change in &report.applied {
+            self.logger.info(
+                Event::ConfigReloadApplied.as_str(),
+                &[
+                    ("field", &change.field),
+                    ("old", &change.old.to_string()),
+                    ("new", &change.new.to_string()),
+                ],
+            );
+        }
+        for refused in &report.refused {
+            self.logger.warn(
+                Event::ConfigReloadRefused.as_str(),
+                &[
+                    ("field", &refused.change.field),
+                    ("old", &refused.change.old.to_string()),
+                    ("new", &refused.change.new.to_string()),
+                    ("reason", &refused.reason),
+                ],
+            );
+        }
+        if report.is_empty() {
+            self.logger.info(Event::ConfigReloadUnchanged.as_str(), &[]);
+        }
+    }
+}
+
+/// Every leaf that differs between `old` and `new`, in field order
+pub fn diff(old: &Value, new: &Value) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_into(String::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_into(path: String, old: &Value, new: &Value, out: &mut Vec<FieldChange>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            let empty = Map::new();
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                let old = a.get(key).unwrap_or(&Value::Null);
+                let new = b.get(key).unwrap_or(&Value::Null);
+                match (old, new) {
+                    (Value::Object(_), Value::Null) => {
+                        diff_into(child, old, &Value::Object(empty.clone()), out)
+                    }
+                    (Value::Null, Value::Object(_)) => {
+                        diff_into(child, &Value::Object(empty.clone()), new, out)
+                    }
+                    _ => diff_into(child, old, new, out),
+                }
+            }
+        }
+        _ if old != new => out.push(FieldChange {
+            field: path,
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Whether a whitelist entry `prefix` covers `field`
+fn covers(prefix: &str, field: &str) -> bool {
+    field == prefix
+        || field
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+fn is_tunable(field: &str) -> bool {
+    RUNTIME_TUNABLE.iter().any(|prefix| covers(prefix, field))
+}
+
+fn is_secret(field: &str) -> bool {
+    let name = field.rsplit('.').next().unwrap_or(field);
+    name.contains("secret") || name.contains("password")
+}
+
+fn redacted(mut change: FieldChange) -> FieldChange {
+    if is_secret(&change.field) {
+        change.old = Value::String(HIDDEN.to_string());
+        change.new = Value::String(HIDDEN.to_string());
+    }
+    change
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |v, key| v.get(key))
+}
+
+fn set_path(value: &mut Value, path: &str, new: Value) {
+    let mut keys = path.split('.').peekable();
+    let mut current = value;
+    while let Some(key) = keys.next() {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        let map = current.as_object_mut().expect("just made an object");
+        if keys.peek().is_none() {
+            if new.is_null() {
+                map.remove(key);
+            } else {
+                map.insert(key.to_string(), new);
+            }
+            return;
+        }
+        current = map.entry(key.to_string()).or_insert(Value::Null);
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(value: &Value) -> Result<T, ReloadError> {
+    serde_json::from_value(value.clone())
+        .map_err(|e| ReloadError::new(format!("Invalid runtime setting: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admission_control::AdmissionControlConfig;
+    use crate::observability::VecLogger;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    fn running() -> Value {
+        json!({
+            "server": { "data_dir": "/var/lib/aerodb" },
+            "wal": { "sync_mode": "fsync" },
+            "replication": { "secret": "s3cret" },
+            "observability": {
+                "log_level": "trace",
+                "operation_log": { "enabled": false, "slow_threshold_ms": 100, "max_entries": 10 },
+                "slow_query": { "enabled": true, "threshold_ms": 100, "emit_log": true,
+                                "webhook_url": null, "webhook_timeout_ms": 5000 }
+            },
+            "backpressure": { "max_connections": 10, "max_queue_depth": 10,
+                              "max_ops_per_connection": 10, "queue_timeout_ms": 1000 },
+            "admission_control": { "max_writes_per_second": 0, "max_concurrent_queries": 100 },
+            "resource_limits": { "warning_threshold_percent": 75, "critical_threshold_percent": 90 }
+        })
+    }
+
+    #[test]
+    fn test_diff_reports_changed_leaves() {
+        let old = json!({ "a": { "b": 1, "c": 2 }, "d": "x" });
+        let new = json!({ "a": { "b": 1, "c": 3 }, "d": "y", "e": true });
+
+        let fields: Vec<_> = diff(&old, &new).into_iter().map(|c| c.field).collect();
+        assert_eq!(fields, vec!["a.c", "d", "e"]);
+    }
+
+    #[test]
+    fn test_apply_splits_tunable_and_boot_only_fields() {
+        let tracker = Arc::new(SlowQueryTracker::new(SlowQueryConfig::with_threshold_ms(
+            100,
+        )));
+        let log = Arc::new(OperationLog::disabled());
+        let admission = Arc::new(AdmissionController::new(AdmissionControlConfig::default()));
+        let settings = RuntimeSettings::new()
+            .with_slow_query_tracker(tracker.clone())
+            .with_operation_log(log.clone())
+            .with_admission_controller(admission.clone());
+
+        let mut loaded = running();
+        loaded["observability"]["slow_query"]["threshold_ms"] = json!(250);
+        loaded["observability"]["operation_log"]["enabled"] = json!(true);
+        loaded["admission_control"]["max_writes_per_second"] = json!(1);
+        loaded["server"]["data_dir"] = json!("/mnt/other");
+        loaded["replication"]["secret"] = json!("changed");
+
+        let (report, next) = settings.apply(&running(), &loaded).unwrap();
+
+        let applied: Vec<_> = report.applied.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            applied,
+            vec![
+                "admission_control.max_writes_per_second",
+                "observability.operation_log.enabled",
+                "observability.slow_query.threshold_ms",
+            ]
+        );
+        assert_eq!(report.applied[2].old, json!(100));
+        assert_eq!(report.applied[2].new, json!(250));
+
+        let refused: Vec<_> = report
+            .refused
+            .iter()
+            .map(|r| r.change.field.as_str())
+            .collect();
+        assert_eq!(refused, vec!["replication.secret", "server.data_dir"]);
+        assert_eq!(report.refused[0].change.new, json!(HIDDEN));
+
+        assert_eq!(tracker.threshold_ms(), 250);
+        assert!(log.is_enabled());
+        assert!(admission.try_acquire_write());
+        assert!(!admission.try_acquire_write());
+        assert_eq!(next["server"]["data_dir"], json!("/var/lib/aerodb"));
+        assert_eq!(
+            next["observability"]["slow_query"]["threshold_ms"],
+            json!(250)
+        );
+    }
+
+    #[test]
+    fn test_apply_rejects_invalid_tunable_without_side_effects() {
+        let tracker = Arc::new(SlowQueryTracker::new(SlowQueryConfig::with_threshold_ms(
+            100,
+        )));
+        let settings = RuntimeSettings::new().with_slow_query_tracker(tracker.clone());
+
+        let mut loaded = running();
+        loaded["observability"]["slow_query"]["threshold_ms"] = json!(250);
+        loaded["resource_limits"]["warning_threshold_percent"] = json!(95);
+
+        assert!(settings.apply(&running(), &loaded).is_err());
+        assert_eq!(tracker.threshold_ms(), 100);
+    }
+
+    #[test]
+    fn test_apply_refuses_fields_without_a_registered_component() {
+        let logger = Arc::new(VecLogger::new());
+        let tracker = Arc::new(SlowQueryTracker::new(SlowQueryConfig::with_threshold_ms(
+            100,
+        )));
+        let settings = RuntimeSettings::new()
+            .with_slow_query_tracker(tracker.clone())
+            .with_logger(logger.clone());
+
+        let mut loaded = running();
+        loaded["observability"]["slow_query"]["threshold_ms"] = json!(250);
+        loaded["backpressure"]["max_connections"] = json!(20);
+
+        let (report, next) = settings.apply(&running(), &loaded).unwrap();
+
+        let applied: Vec<_> = report.applied.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(applied, vec!["observability.slow_query.threshold_ms"]);
+        assert_eq!(report.refused.len(), 1);
+        assert_eq!(
+            report.refused[0].change.field,
+            "backpressure.max_connections"
+        );
+        assert_eq!(
+            report.refused[0].reason,
+            "No running component uses this setting"
+        );
+        assert_eq!(next["backpressure"]["max_connections"], json!(10));
+
+        let records = logger.records();
+        assert_eq!(
+            logger.events(),
+            vec!["CONFIG_RELOAD_APPLIED", "CONFIG_RELOAD_REFUSED"]
+        );
+        assert_eq!(records[0].severity, Severity::Info);
+        assert_eq!(
+            records[0].field("field"),
+            Some("observability.slow_query.threshold_ms")
+        );
+        assert_eq!(records[0].field("old"), Some("100"));
+        assert_eq!(records[0].field("new"), Some("250"));
+        assert_eq!(records[1].severity, Severity::Warn);
+        assert_eq!(
+            records[1].field("field"),
+            Some("backpressure.max_connections")
+        );
+
+        // Still pending on the next reload
+        let (again, _) = settings.apply(&next, &loaded).unwrap();
+        assert!(again.applied.is_empty());
+        assert_eq!(again.refused.len(), 1);
+    }
+
+    #[test]
+    fn test_apply_reloads_log_level() {
+        static LEVEL: AtomicU8 = AtomicU8::new(Severity::Trace as u8);
+        fn set_level(severity: Severity) {
+            LEVEL.store(severity as u8, Ordering::SeqCst);
+        }
+        let settings = RuntimeSettings::new()
+            .with_log_level(set_level)
+            .with_logger(Arc::new(VecLogger::new()));
+
+        let mut loaded = running();
+        loaded["observability"]["log_level"] = json!("warn");
+        let (report, next) = settings.apply(&running(), &loaded).unwrap();
+
+        assert_eq!(report.applied[0].field, "observability.log_level");
+        assert_eq!(LEVEL.load(Ordering::SeqCst), Severity::Warn as u8);
+        assert_eq!(next["observability"]["log_level"], json!("warn"));
+
+        loaded["observability"]["log_level"] = json!("verbose");
+        assert!(settings.apply(&next, &loaded).is_err());
+        assert_eq!(LEVEL.load(Ordering::SeqCst), Severity::Warn as u8);
+    }
+
+    #[test]
+    fn test_unchanged_reload_is_logged() {
+        let logger = Arc::new(VecLogger::new());
+        let settings = RuntimeSettings::new().with_logger(logger.clone());
+
+        let (report, _) = settings.apply(&running(), &running()).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(logger.events(), vec!["CONFIG_RELOAD_UNCHANGED"]);
+    }
+}
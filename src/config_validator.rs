@@ -2,11 +2,35 @@
 //!
 //! HARDENING: Validates all configuration at startup.
 //! Rejects invalid values with explicit error messages.
+//! Settings that are legal but likely mistaken can be reported as warnings;
+//! warnings never cause validation to fail.
+//!
+//! [`AeroConfig`] is the unified `aerodb.toml` file: one section per
+//! subsystem, each deserializing into that subsystem's config struct.
 
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
+
+use crate::admission_control::AdmissionControlConfig;
+use crate::auth::security::SecurityConfig;
+use crate::backpressure::BackpressureConfig;
+use crate::backup::BackupConfig;
+use crate::dx::api::control_plane::DEFAULT_CONFIRMATION_TTL;
+use crate::file_storage::StorageConfig;
+use crate::functions::SchedulerConfig;
+use crate::http_server::RealtimeConfig;
+use crate::observability::ObservabilityConfig;
+use crate::panic_handler::DEFAULT_MAX_CRASH_REPORTS;
+use crate::planner::StatisticsConfig;
+use crate::query_limits::QueryLimitsConfig;
+use crate::recovery::default_replay_parallelism;
+use crate::replication::{SyncMode, SyncTimeoutPolicy};
+use crate::resource_limits::ResourceLimitsConfig;
+use crate::wal::DEFAULT_SEGMENT_BYTES;
+
 /// Configuration validation errors
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ConfigValidationError {
     pub field: String,
     pub value: String,
@@ -28,14 +52,32 @@ impl std::error::Error for ConfigValidationError {}
 /// Result of config validation
 pub type ConfigResult<T> = Result<T, Vec<ConfigValidationError>>;
 
+/// Full validation outcome: every error and warning found
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationReport {
+    pub errors: Vec<ConfigValidationError>,
+    pub warnings: Vec<ConfigValidationError>,
+}
+
+impl ValidationReport {
+    /// Check if any errors occurred
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
+
 /// Configuration validator
 pub struct ConfigValidator {
     errors: Vec<ConfigValidationError>,
+    warnings: Vec<ConfigValidationError>,
 }
 
 impl ConfigValidator {
     pub fn new() -> Self {
-        Self { errors: Vec::new() }
+        Self {
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
     }
 
     /// Add an error
@@ -47,6 +89,22 @@ impl ConfigValidator {
         });
     }
 
+    /// Record an error found by a check outside this validator
+    pub fn reject(&mut self, field: &str, value: impl std::fmt::Display, message: &str) -> &mut Self {
+        self.error(field, value, message);
+        self
+    }
+
+    /// Record a warning: the value is accepted but probably not intended
+    pub fn warn(&mut self, field: &str, value: impl std::fmt::Display, message: &str) -> &mut Self {
+        self.warnings.push(ConfigValidationError {
+            field: field.to_string(),
+            value: value.to_string(),
+            message: message.to_string(),
+        });
+        self
+    }
+
     /// Validate port number (1-65535)
     pub fn validate_port(&mut self, field: &str, port: u16) -> &mut Self {
         if port == 0 {
@@ -174,6 +232,19 @@ impl ConfigValidator {
     pub fn errors(&self) -> &[ConfigValidationError] {
         &self.errors
     }
+
+    /// Get current warnings
+    pub fn warnings(&self) -> &[ConfigValidationError] {
+        &self.warnings
+    }
+
+    /// Finish validation, keeping warnings alongside errors
+    pub fn report(self) -> ValidationReport {
+        ValidationReport {
+            errors: self.errors,
+            warnings: self.warnings,
+        }
+    }
 }
 
 impl Default for ConfigValidator {
@@ -208,6 +279,549 @@ pub fn format_validation_errors(errors: &[ConfigValidationError]) -> String {
         .join("\n")
 }
 
+/// Unified configuration file (`aerodb.toml`)
+///
+/// Only `[server]` is required, and within it only `data_dir`; every other
+/// section and key has a default. [`AeroConfig::from_toml`] rejects unknown
+/// sections and keys, so a typo fails loudly instead of silently keeping a
+/// default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AeroConfig {
+    /// `[server]`: data directory and memory budget
+    pub server: ServerSection,
+
+    /// `[wal]`: WAL size, segmenting, archiving and sync mode
+    #[serde(default)]
+    pub wal: WalSection,
+
+    /// `[resource_limits]`
+    #[serde(default)]
+    pub resource_limits: ResourceLimitsConfig,
+
+    /// `[backup]`
+    #[serde(default)]
+    pub backup: BackupConfig,
+
+    /// `[observability.operation_log]` and `[observability.slow_query]`
+    #[serde(default)]
+    pub observability: ObservabilityConfig,
+
+    /// `[realtime]` and `[realtime.backpressure]`
+    #[serde(default)]
+    pub realtime: RealtimeConfig,
+
+    /// `[storage]`: upload and bucket size limits, signed URLs
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    /// `[replication]` (disabled by default per P5-I16)
+    #[serde(default)]
+    pub replication: ReplicationSection,
+
+    /// `[auth]`
+    #[serde(default)]
+    pub auth: SecurityConfig,
+
+    /// `[backpressure]`: request queueing in front of the executor
+    #[serde(default)]
+    pub backpressure: BackpressureConfig,
+
+    /// `[admission_control]`: write rate and concurrency limits
+    #[serde(default)]
+    pub admission_control: AdmissionControlConfig,
+
+    /// `[query_limits]`
+    #[serde(default)]
+    pub query_limits: QueryLimitsConfig,
+
+    /// `[statistics]`: planner statistics
+    #[serde(default)]
+    pub statistics: StatisticsConfig,
+
+    /// `[control_plane]`: operator command confirmation
+    #[serde(default)]
+    pub control_plane: ControlPlaneSection,
+
+    /// `[scheduler]` and `[[scheduler.jobs]]`: cron jobs run while serving
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+
+    /// `[ttl]`: the sweeper removing expired documents
+    #[serde(default)]
+    pub ttl: TtlSection,
+
+    /// `[recovery]`: WAL replay at startup
+    #[serde(default)]
+    pub recovery: RecoverySection,
+}
+
+/// `[server]` section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerSection {
+    /// Data directory (required)
+    pub data_dir: String,
+
+    /// Max memory in bytes (default 512MB)
+    #[serde(default = "default_max_memory_bytes")]
+    pub max_memory_bytes: u64,
+
+    /// Crash reports kept in `<data_dir>/crash_reports` (default 10)
+    #[serde(default = "default_max_crash_reports")]
+    pub max_crash_reports: usize,
+
+    /// Milliseconds a SIGTERM or SIGINT waits for in-flight requests
+    /// before shutdown gives up (default 10000)
+    #[serde(default = "default_shutdown_grace_ms")]
+    pub shutdown_grace_ms: u64,
+}
+
+/// `[wal]` section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalSection {
+    /// Max WAL size in bytes (default 1GB); a checkpoint runs once the
+    /// WAL written since the last one reaches it
+    pub max_size_bytes: u64,
+
+    /// Segment size in bytes (default 64MB, at most max_size_bytes)
+    pub segment_bytes: u64,
+
+    /// Directory that receives segments before checkpoint deletes them
+    pub archive_dir: Option<String>,
+
+    /// "fsync" (default), "fdatasync" or "group_commit"
+    pub sync_mode: String,
+
+    /// Group commit window in milliseconds (only used with group_commit)
+    pub group_commit_interval_ms: u64,
+
+    /// Group commit window in microseconds; overrides group_commit_interval_ms
+    pub group_commit_window_us: Option<u64>,
+
+    /// Max records per group commit batch (only used with group_commit)
+    pub group_commit_max_records: usize,
+
+    /// Also checkpoint after this many WAL records (default: unset)
+    pub checkpoint_max_records: Option<u64>,
+
+    /// Also checkpoint after this many seconds (default: unset)
+    pub checkpoint_interval_secs: Option<u64>,
+}
+
+impl Default for WalSection {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: default_max_wal_size_bytes(),
+            segment_bytes: DEFAULT_SEGMENT_BYTES,
+            archive_dir: None,
+            sync_mode: default_wal_sync_mode(),
+            group_commit_interval_ms: default_group_commit_interval_ms(),
+            group_commit_window_us: None,
+            group_commit_max_records: default_group_commit_max_records(),
+            checkpoint_max_records: None,
+            checkpoint_interval_secs: None,
+        }
+    }
+}
+
+/// `[replication]` section (Phase 5)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplicationSection {
+    /// Whether replication is enabled (default: false per P5-I16)
+    pub enabled: bool,
+
+    /// "primary" (default) or "replica"
+    pub role: String,
+
+    /// Replica ID (UUID, auto-generated if not provided for replicas)
+    pub replica_id: Option<String>,
+
+    /// Primary node address (required for replicas, forbidden for primaries)
+    pub primary_address: Option<String>,
+
+    /// Port the primary listens on for replicas (default: 7000)
+    pub port: u16,
+
+    /// Secret shared by the primary and its replicas (required when
+    /// replication is enabled)
+    pub secret: Option<String>,
+
+    /// Seconds the primary keeps WAL for a replica it sent a base snapshot
+    /// to, until the replica starts streaming (default: 3600)
+    pub bootstrap_pin_secs: u64,
+
+    /// "async" (default) or "quorum": whether a write on the primary waits
+    /// for replica acknowledgments
+    pub sync_mode: String,
+
+    /// Replicas that must acknowledge a write in quorum mode (default: 1)
+    pub min_sync_replicas: usize,
+
+    /// Milliseconds a write waits for the quorum (default: 5000)
+    pub sync_timeout_ms: u64,
+
+    /// "fail" (default) or "degrade": what a write does when the quorum
+    /// does not acknowledge it in time
+    pub sync_on_timeout: String,
+}
+
+impl Default for ReplicationSection {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            role: default_replication_role(),
+            replica_id: None,
+            primary_address: None,
+            port: default_replication_port(),
+            secret: None,
+            bootstrap_pin_secs: default_bootstrap_pin_secs(),
+            sync_mode: default_replication_sync_mode(),
+            min_sync_replicas: default_min_sync_replicas(),
+            sync_timeout_ms: default_sync_timeout_ms(),
+            sync_on_timeout: default_sync_on_timeout(),
+        }
+    }
+}
+
+/// `[control_plane]` section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlPlaneSection {
+    /// Seconds a confirmation token stays valid (default 300)
+    pub confirmation_ttl_secs: u64,
+}
+
+impl Default for ControlPlaneSection {
+    fn default() -> Self {
+        Self {
+            confirmation_ttl_secs: DEFAULT_CONFIRMATION_TTL.as_secs(),
+        }
+    }
+}
+
+/// `[ttl]` section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TtlSection {
+    /// Milliseconds between expiry sweeps (default 60000)
+    pub sweep_interval_ms: u64,
+
+    /// Max expired documents one sweep removes (default 1000)
+    pub sweep_batch_size: usize,
+}
+
+impl Default for TtlSection {
+    fn default() -> Self {
+        Self {
+            sweep_interval_ms: 60_000,
+            sweep_batch_size: 1000,
+        }
+    }
+}
+
+/// `[recovery]` section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecoverySection {
+    /// Threads replaying the WAL, partitioned by collection (default: the
+    /// available cores, at most 8; 1 replays sequentially)
+    pub parallelism: usize,
+}
+
+impl Default for RecoverySection {
+    fn default() -> Self {
+        Self {
+            parallelism: default_replay_parallelism(),
+        }
+    }
+}
+
+fn default_max_memory_bytes() -> u64 {
+    512 * 1024 * 1024
+}
+fn default_max_crash_reports() -> usize {
+    DEFAULT_MAX_CRASH_REPORTS
+}
+fn default_shutdown_grace_ms() -> u64 {
+    10_000
+}
+fn default_max_wal_size_bytes() -> u64 {
+    1024 * 1024 * 1024
+}
+fn default_wal_sync_mode() -> String {
+    "fsync".to_string()
+}
+fn default_group_commit_interval_ms() -> u64 {
+    2
+}
+fn default_group_commit_max_records() -> usize {
+    64
+}
+fn default_replication_role() -> String {
+    "primary".to_string()
+}
+fn default_replication_port() -> u16 {
+    7000
+}
+fn default_bootstrap_pin_secs() -> u64 {
+    3600
+}
+fn default_replication_sync_mode() -> String {
+    SyncMode::default().as_str().to_string()
+}
+fn default_min_sync_replicas() -> usize {
+    1
+}
+fn default_sync_timeout_ms() -> u64 {
+    5000
+}
+fn default_sync_on_timeout() -> String {
+    SyncTimeoutPolicy::default().as_str().to_string()
+}
+fn default_backup_dir() -> String {
+    BackupConfig::new().backup_dir
+}
+
+/// Keys accepted under another name, as (section, alias)
+const KEY_ALIASES: &[(&str, &str)] = &[("query_limits", "query_timeout_ms")];
+
+impl AeroConfig {
+    /// Configuration with every default and the given data directory
+    pub fn new(data_dir: impl Into<String>) -> Self {
+        Self {
+            server: ServerSection {
+                data_dir: data_dir.into(),
+                max_memory_bytes: default_max_memory_bytes(),
+                max_crash_reports: default_max_crash_reports(),
+                shutdown_grace_ms: default_shutdown_grace_ms(),
+            },
+            wal: WalSection::default(),
+            resource_limits: ResourceLimitsConfig::default(),
+            backup: BackupConfig::default(),
+            observability: ObservabilityConfig::default(),
+            realtime: RealtimeConfig::default(),
+            storage: StorageConfig::default(),
+            replication: ReplicationSection::default(),
+            auth: SecurityConfig::default(),
+            backpressure: BackpressureConfig::default(),
+            admission_control: AdmissionControlConfig::default(),
+            query_limits: QueryLimitsConfig::default(),
+            statistics: StatisticsConfig::default(),
+            control_plane: ControlPlaneSection::default(),
+            scheduler: SchedulerConfig::default(),
+            ttl: TtlSection::default(),
+            recovery: RecoverySection::default(),
+        }
+    }
+
+    /// Parse an `aerodb.toml` document.
+    ///
+    /// Every unknown section or key is reported, each naming its full path
+    /// (e.g. `observability.slow_query.thresold_ms`). Values of the wrong
+    /// type are reported with their path and line.
+    pub fn from_toml(content: &str) -> ConfigResult<Self> {
+        let table: toml::Table =
+            toml::from_str(content).map_err(|e| vec![toml_error(content, ".", &e)])?;
+
+        let known = serde_json::to_value(Self::new("")).expect("config serializes");
+        let mut errors = Vec::new();
+        check_keys(&table, &known, "", &mut errors);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        serde_path_to_error::deserialize(toml::Deserializer::new(content))
+            .map_err(|e| vec![toml_error(content, &e.path().to_string(), e.inner())])
+    }
+
+    /// Parse the flat JSON layout that preceded `aerodb.toml`.
+    ///
+    /// Deprecated: kept for one release so existing deployments can
+    /// migrate. Unknown keys are ignored, as they always were.
+    pub fn from_legacy_json(content: &str) -> serde_json::Result<Self> {
+        serde_json::from_str::<LegacyConfig>(content).map(Self::from)
+    }
+}
+
+/// Report every key in `given` that has no counterpart in `known`
+fn check_keys(
+    given: &toml::Table,
+    known: &serde_json::Value,
+    section: &str,
+    errors: &mut Vec<ConfigValidationError>,
+) {
+    for (key, value) in given {
+        let path = if section.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", section, key)
+        };
+        match (known.get(key), value) {
+            (Some(serde_json::Value::Object(_)), toml::Value::Table(table)) => {
+                check_keys(table, &known[key.as_str()], &path, errors);
+            }
+            (Some(_), _) => {}
+            (None, _) if KEY_ALIASES.contains(&(section, key.as_str())) => {}
+            (None, value) => {
+                let expected = known
+                    .as_object()
+                    .map(|keys| keys.keys().cloned().collect::<Vec<_>>().join(", "))
+                    .unwrap_or_default();
+                let message = if section.is_empty() {
+                    format!("Unknown section; expected one of: {}", expected)
+                } else {
+                    format!(
+                        "Unknown key in [{}]; expected one of: {}",
+                        section, expected
+                    )
+                };
+                let value = match value {
+                    toml::Value::Table(_) => "[table]".to_string(),
+                    other => other.to_string(),
+                };
+                errors.push(ConfigValidationError {
+                    field: path,
+                    value,
+                    message,
+                });
+            }
+        }
+    }
+}
+
+/// Describe a TOML error at `path`, with its line number when known
+fn toml_error(content: &str, path: &str, error: &toml::de::Error) -> ConfigValidationError {
+    let message = match error.span() {
+        Some(span) => {
+            let line = content[..span.start].matches('\n').count() + 1;
+            format!("{} (line {})", error.message(), line)
+        }
+        None => error.message().to_string(),
+    };
+    ConfigValidationError {
+        field: path.to_string(),
+        value: String::new(),
+        message,
+    }
+}
+
+/// Flat JSON configuration read before `aerodb.toml` existed
+#[derive(Deserialize)]
+struct LegacyConfig {
+    data_dir: String,
+    #[serde(default = "default_max_wal_size_bytes")]
+    max_wal_size_bytes: u64,
+    #[serde(default = "default_wal_segment_bytes")]
+    wal_segment_bytes: u64,
+    #[serde(default)]
+    wal_archive_dir: Option<String>,
+    #[serde(default = "default_backup_dir")]
+    backup_dir: String,
+    #[serde(default = "default_max_memory_bytes")]
+    max_memory_bytes: u64,
+    #[serde(default = "default_wal_sync_mode")]
+    wal_sync_mode: String,
+    #[serde(default = "default_group_commit_interval_ms")]
+    group_commit_interval_ms: u64,
+    #[serde(default)]
+    group_commit_window_us: Option<u64>,
+    #[serde(default = "default_group_commit_max_records")]
+    group_commit_max_records: usize,
+    #[serde(default)]
+    resource_limits: ResourceLimitsConfig,
+    #[serde(default)]
+    observability: ObservabilityConfig,
+    #[serde(default)]
+    backpressure: BackpressureConfig,
+    #[serde(default)]
+    admission_control: AdmissionControlConfig,
+    #[serde(default)]
+    query_limits: QueryLimitsConfig,
+    #[serde(default)]
+    security: SecurityConfig,
+    #[serde(default)]
+    statistics: StatisticsConfig,
+    #[serde(default)]
+    replication_enabled: bool,
+    #[serde(default = "default_replication_role")]
+    replication_role: String,
+    #[serde(default)]
+    replica_id: Option<String>,
+    #[serde(default)]
+    primary_address: Option<String>,
+    #[serde(default = "default_replication_port")]
+    replication_port: u16,
+    #[serde(default)]
+    replication_secret: Option<String>,
+    #[serde(default = "default_replication_sync_mode")]
+    replication_sync_mode: String,
+    #[serde(default = "default_min_sync_replicas")]
+    min_sync_replicas: usize,
+    #[serde(default = "default_sync_timeout_ms")]
+    sync_timeout_ms: u64,
+    #[serde(default = "default_sync_on_timeout")]
+    sync_on_timeout: String,
+}
+
+fn default_wal_segment_bytes() -> u64 {
+    DEFAULT_SEGMENT_BYTES
+}
+
+impl From<LegacyConfig> for AeroConfig {
+    fn from(legacy: LegacyConfig) -> Self {
+        Self {
+            server: ServerSection {
+                data_dir: legacy.data_dir,
+                max_memory_bytes: legacy.max_memory_bytes,
+                max_crash_reports: default_max_crash_reports(),
+                shutdown_grace_ms: default_shutdown_grace_ms(),
+            },
+            wal: WalSection {
+                max_size_bytes: legacy.max_wal_size_bytes,
+                segment_bytes: legacy.wal_segment_bytes,
+                archive_dir: legacy.wal_archive_dir,
+                sync_mode: legacy.wal_sync_mode,
+                group_commit_interval_ms: legacy.group_commit_interval_ms,
+                group_commit_window_us: legacy.group_commit_window_us,
+                group_commit_max_records: legacy.group_commit_max_records,
+                checkpoint_max_records: None,
+                checkpoint_interval_secs: None,
+            },
+            resource_limits: legacy.resource_limits,
+            backup: BackupConfig {
+                backup_dir: legacy.backup_dir,
+                ..BackupConfig::new()
+            },
+            observability: legacy.observability,
+            realtime: RealtimeConfig::default(),
+            storage: StorageConfig::default(),
+            replication: ReplicationSection {
+                enabled: legacy.replication_enabled,
+                role: legacy.replication_role,
+                replica_id: legacy.replica_id,
+                primary_address: legacy.primary_address,
+                port: legacy.replication_port,
+                secret: legacy.replication_secret,
+                bootstrap_pin_secs: default_bootstrap_pin_secs(),
+                sync_mode: legacy.replication_sync_mode,
+                min_sync_replicas: legacy.min_sync_replicas,
+                sync_timeout_ms: legacy.sync_timeout_ms,
+                sync_on_timeout: legacy.sync_on_timeout,
+            },
+            auth: legacy.security,
+            backpressure: legacy.backpressure,
+            admission_control: legacy.admission_control,
+            query_limits: legacy.query_limits,
+            statistics: legacy.statistics,
+            control_plane: ControlPlaneSection::default(),
+            scheduler: SchedulerConfig::default(),
+            ttl: TtlSection::default(),
+            recovery: RecoverySection::default(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,6 +861,204 @@ mod tests {
         assert_eq!(errors.len(), 3);
     }
 
+    #[test]
+    fn test_warnings_do_not_fail_validation() {
+        let mut v = ConfigValidator::new();
+        v.warn("data_dir", "/tmp/missing", "Path does not exist")
+            .validate_port("port", 8080);
+        assert_eq!(v.warnings().len(), 1);
+
+        let report = v.report();
+        assert!(!report.has_errors());
+        assert_eq!(report.warnings[0].field, "data_dir");
+
+        let mut v = ConfigValidator::new();
+        v.reject("replication", "replica", "primary_address is required");
+        assert!(v.report().has_errors());
+    }
+
+    const FULL_EXAMPLE: &str = r#"
+        [server]
+        data_dir = "/var/lib/aerodb"
+        max_memory_bytes = 1073741824
+
+        [wal]
+        max_size_bytes = 2147483648
+        segment_bytes = 67108864
+        archive_dir = "/var/lib/aerodb/wal-archive"
+        sync_mode = "group_commit"
+        group_commit_interval_ms = 5
+        group_commit_max_records = 128
+
+        [resource_limits]
+        min_free_disk_bytes = 2147483648
+        max_memory_bytes = 4294967296
+        max_file_descriptors = 4096
+        max_result_set_docs = 5000
+        warning_threshold_percent = 70
+        critical_threshold_percent = 85
+
+        [backup]
+        enabled = true
+        interval_hours = 12
+        max_backups = 14
+        backup_dir = "/var/backups/aerodb"
+        keep_verified = true
+
+        [backup.snapshot]
+        io_throttle_mbps = 100
+
+        [observability.operation_log]
+        enabled = true
+        slow_threshold_ms = 250
+
+        [observability.slow_query]
+        enabled = true
+        threshold_ms = 50
+        webhook_url = "https://alerts.example.com/aerodb"
+
+        [realtime]
+        heartbeat_interval_ms = 15000
+
+        [realtime.backpressure]
+        max_pending_messages = 500
+
+        [storage]
+        max_upload_bytes = 10485760
+        max_signed_url_expiry_secs = 3600
+
+        [replication]
+        enabled = true
+        role = "primary"
+        port = 7100
+        secret = "0123456789abcdef"
+
+        [auth]
+        fail_closed_mode = true
+        audit_auth_failures = false
+
+        [backpressure]
+        max_queue_depth = 2000
+
+        [admission_control]
+        max_writes_per_second = 500
+
+        [query_limits]
+        query_timeout_ms = 10000
+
+        [statistics]
+        stale_mutation_percent = 20
+
+        [scheduler]
+        job_timeout_ms = 30000
+
+        [[scheduler.jobs]]
+        name = "nightly_report"
+        cron = "0 2 * * *"
+        function = "build_report"
+
+        [ttl]
+        sweep_batch_size = 500
+
+        [recovery]
+        parallelism = 4
+    "#;
+
+    #[test]
+    fn test_full_example_config_parses() {
+        let config = AeroConfig::from_toml(FULL_EXAMPLE).unwrap();
+
+        assert_eq!(config.server.data_dir, "/var/lib/aerodb");
+        assert_eq!(config.wal.sync_mode, "group_commit");
+        assert_eq!(config.wal.group_commit_window_us, None);
+        assert_eq!(config.resource_limits.warning_threshold_percent, 70);
+        assert_eq!(config.backup.backup_dir, "/var/backups/aerodb");
+        assert_eq!(config.backup.snapshot.io_throttle_mbps, 100);
+        assert_eq!(config.observability.slow_query.threshold_ms, 50);
+        assert_eq!(config.realtime.backpressure.max_pending_messages, 500);
+        assert_eq!(config.storage.max_upload_bytes, 10 * 1024 * 1024);
+        assert_eq!(config.storage.max_total_storage_bytes, 0);
+        assert_eq!(config.storage.max_signed_url_expiry_secs, 3600);
+        assert_eq!(config.realtime.max_missed_pongs, 2);
+        assert_eq!(config.replication.port, 7100);
+        assert!(!config.auth.audit_auth_failures);
+        assert_eq!(config.backpressure.max_queue_depth, 2000);
+        assert_eq!(config.admission_control.max_writes_per_second, 500);
+        assert_eq!(config.query_limits.max_execution_ms, 10000);
+        assert_eq!(config.statistics.stale_mutation_percent, 20);
+        assert_eq!(config.scheduler.job_timeout_ms, 30000);
+        assert_eq!(config.scheduler.jobs[0].function, "build_report");
+        assert!(config.scheduler.jobs[0].enabled);
+        assert_eq!(config.ttl.sweep_batch_size, 500);
+        assert_eq!(config.ttl.sweep_interval_ms, 60_000);
+        assert_eq!(config.recovery.parallelism, 4);
+    }
+
+    #[test]
+    fn test_unknown_keys_name_their_path() {
+        let content = FULL_EXAMPLE
+            .replace("threshold_ms = 50", "thresold_ms = 50")
+            .replace("[statistics]", "[statistics]\nrefresh = true");
+
+        let errors = AeroConfig::from_toml(&content).unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            ["observability.slow_query.thresold_ms", "statistics.refresh"]
+        );
+        assert!(errors[0].message.contains("[observability.slow_query]"));
+        assert!(errors[0].message.contains("threshold_ms"));
+
+        let errors = AeroConfig::from_toml("[server]\ndata_dir = \"/d\"\n[sever]\n").unwrap_err();
+        assert_eq!(errors[0].field, "sever");
+        assert!(errors[0].message.contains("Unknown section"));
+    }
+
+    #[test]
+    fn test_invalid_value_names_its_path_and_line() {
+        let errors = AeroConfig::from_toml(
+            "[server]\ndata_dir = \"/d\"\n\n[wal]\nsegment_bytes = \"big\"\n",
+        )
+        .unwrap_err();
+        assert_eq!(errors[0].field, "wal.segment_bytes");
+        assert!(
+            errors[0].message.contains("line 5"),
+            "{}",
+            errors[0].message
+        );
+
+        let errors = AeroConfig::from_toml("[wal]\nsync_mode = \"fsync\"\n").unwrap_err();
+        assert!(
+            errors[0].message.contains("server"),
+            "{}",
+            errors[0].message
+        );
+    }
+
+    #[test]
+    fn test_defaults_round_trip_through_toml() {
+        let content = toml::to_string(&AeroConfig::new("/var/lib/aerodb")).unwrap();
+        let config = AeroConfig::from_toml(&content).unwrap();
+        assert_eq!(config.server.data_dir, "/var/lib/aerodb");
+        assert_eq!(config.wal.max_size_bytes, 1024 * 1024 * 1024);
+        assert_eq!(config.replication.role, "primary");
+    }
+
+    #[test]
+    fn test_legacy_json_maps_to_sections() {
+        let config = AeroConfig::from_legacy_json(
+            r#"{"data_dir": "/d", "wal_sync_mode": "fdatasync", "replication_port": 7100,
+                "security": {"fail_closed_mode": false}, "unknown": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(config.server.data_dir, "/d");
+        assert_eq!(config.wal.sync_mode, "fdatasync");
+        assert_eq!(config.replication.port, 7100);
+        assert!(!config.auth.fail_closed_mode);
+        assert!(config.auth.audit_auth_failures);
+        assert_eq!(config.backup.backup_dir, "/var/lib/aerodb/backups");
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format_bytes(500), "500B");
@@ -1,13 +1,26 @@
 //! # Billing Service
 //!
 //! Invoice generation and cost calculation.
+//!
+//! `BillingEngine` turns a tenant's daily metering records for a month into
+//! an invoice priced from a `PriceBook`. Each day is billed at the plan in
+//! effect at the end of that day, so a mid-month plan change splits the
+//! invoice into prorated line items per plan. Invoices are stored in the
+//! tenant registry, one per (tenant, period); regenerating a period updates
+//! its invoice in place.
+
+use std::sync::Arc;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::errors::{ControlPlaneError, ControlPlaneResult};
 use super::metering::UsageMetrics;
-use super::tenant::Plan;
+use super::registry::TenantRegistry;
+use super::tenant::{Plan, Tenant};
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
 
 /// Pricing per unit (in USD)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +80,93 @@ pub struct FreeTierLimits {
     pub function_invocations: u64,
 }
 
+/// Usage rates of one plan (in USD)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanRates {
+    /// Flat fee per month
+    pub base_monthly: f64,
+    /// Price per GB storage per month
+    pub storage_per_gb_month: f64,
+    /// Price per 1M read operations
+    pub reads_per_million: f64,
+    /// Price per 1M write operations
+    pub writes_per_million: f64,
+    /// Price per GB egress
+    pub egress_per_gb: f64,
+}
+
+/// Rates of every plan, used by `BillingEngine`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceBook {
+    /// Free plan rates
+    pub free: PlanRates,
+    /// Pro plan rates
+    pub pro: PlanRates,
+    /// Enterprise plan rates
+    pub enterprise: PlanRates,
+    /// Tax applied to the subtotal (0.1 = 10%)
+    pub tax_rate: f64,
+    /// Currency
+    pub currency: String,
+}
+
+impl PriceBook {
+    /// Rates of a plan
+    pub fn rates(&self, plan: Plan) -> &PlanRates {
+        match plan {
+            Plan::Free => &self.free,
+            Plan::Pro => &self.pro,
+            Plan::Enterprise => &self.enterprise,
+        }
+    }
+
+    /// Replace the rates of a plan
+    pub fn with_rates(mut self, plan: Plan, rates: PlanRates) -> Self {
+        match plan {
+            Plan::Free => self.free = rates,
+            Plan::Pro => self.pro = rates,
+            Plan::Enterprise => self.enterprise = rates,
+        }
+        self
+    }
+
+    /// Set the tax rate
+    pub fn with_tax_rate(mut self, tax_rate: f64) -> Self {
+        self.tax_rate = tax_rate;
+        self
+    }
+}
+
+impl Default for PriceBook {
+    fn default() -> Self {
+        Self {
+            free: PlanRates {
+                base_monthly: 0.0,
+                storage_per_gb_month: 0.0,
+                reads_per_million: 0.0,
+                writes_per_million: 0.0,
+                egress_per_gb: 0.0,
+            },
+            pro: PlanRates {
+                base_monthly: 25.0,
+                storage_per_gb_month: 0.25,
+                reads_per_million: 0.50,
+                writes_per_million: 1.50,
+                egress_per_gb: 0.09,
+            },
+            enterprise: PlanRates {
+                base_monthly: 599.0,
+                storage_per_gb_month: 0.125,
+                reads_per_million: 0.25,
+                writes_per_million: 0.75,
+                egress_per_gb: 0.05,
+            },
+            tax_rate: 0.0,
+            currency: "USD".to_string(),
+        }
+    }
+}
+
 /// Line item on an invoice
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LineItem {
@@ -89,29 +189,64 @@ pub struct Invoice {
     pub invoice_id: Uuid,
     /// Tenant ID
     pub tenant_id: Uuid,
-    /// Billing month (YYYY-MM)
-    pub month: String,
-    /// Plan
+    /// Billing period (YYYY-MM)
+    pub period: String,
+    /// Plan at the end of the period
     pub plan: Plan,
-    /// Base price for plan
-    pub base_price: f64,
-    /// Usage-based charges
-    pub usage_charges: Vec<LineItem>,
-    /// Total usage charges
-    pub usage_total: f64,
-    /// Credits applied
-    pub credits: f64,
+    /// Charges, grouped by plan when the plan changed during the period
+    pub line_items: Vec<LineItem>,
+    /// Sum of line item totals
+    pub subtotal: f64,
+    /// Tax on the subtotal
+    pub tax: f64,
     /// Total amount due
     pub total: f64,
     /// Currency
     pub currency: String,
-    /// Generated at
+    /// First generated at
     pub generated_at: DateTime<Utc>,
+    /// Last regenerated at
+    pub updated_at: DateTime<Utc>,
     /// Paid at (if paid)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub paid_at: Option<DateTime<Utc>>,
 }
 
+impl Invoice {
+    /// Export as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("invoice serializes to JSON")
+    }
+
+    /// Export as CSV: one row per line item, then subtotal, tax and total rows
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("description,quantity,unit,unit_price,total\n");
+        for item in &self.line_items {
+            csv.push_str(&format!(
+                "{},{},{},{},{:.2}\n",
+                csv_field(&item.description),
+                item.quantity,
+                csv_field(&item.unit),
+                item.unit_price,
+                item.total
+            ));
+        }
+        let totals = [("subtotal", self.subtotal), ("tax", self.tax), ("total", self.total)];
+        for (label, amount) in totals {
+            csv.push_str(&format!("{},,,,{:.2}\n", label, amount));
+        }
+        csv
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 /// Billing calculator
 #[derive(Debug, Clone)]
 pub struct BillingCalculator {
@@ -236,28 +371,36 @@ impl BillingCalculator {
         usage: &UsageMetrics,
     ) -> Invoice {
         let base_price = self.base_price(plan);
-        let usage_charges = self.calculate_usage_charges(usage, plan);
-        let usage_total: f64 = usage_charges.iter().map(|i| i.total).sum();
+        let mut line_items = Vec::new();
+        if base_price > 0.0 {
+            line_items.push(LineItem {
+                description: format!("Base fee ({} plan)", plan),
+                quantity: 1.0,
+                unit: "months".to_string(),
+                unit_price: base_price,
+                total: base_price,
+            });
+        }
 
         // Enterprise plan includes all usage
-        let effective_usage_total = if *plan == Plan::Enterprise {
-            0.0
-        } else {
-            usage_total
-        };
+        if *plan != Plan::Enterprise {
+            line_items.extend(self.calculate_usage_charges(usage, plan));
+        }
+        let subtotal: f64 = line_items.iter().map(|i| i.total).sum();
 
+        let now = Utc::now();
         Invoice {
             invoice_id: Uuid::new_v4(),
             tenant_id,
-            month: month.to_string(),
+            period: month.to_string(),
             plan: *plan,
-            base_price,
-            usage_charges,
-            usage_total: effective_usage_total,
-            credits: 0.0,
-            total: base_price + effective_usage_total,
+            line_items,
+            subtotal,
+            tax: 0.0,
+            total: subtotal,
             currency: "USD".to_string(),
-            generated_at: Utc::now(),
+            generated_at: now,
+            updated_at: now,
             paid_at: None,
         }
     }
@@ -269,9 +412,224 @@ impl Default for BillingCalculator {
     }
 }
 
+/// Usage attributed to one day of a billing period
+#[derive(Debug, Clone, Copy, Default)]
+struct DayUsage {
+    storage_gb: f64,
+    read_ops: f64,
+    write_ops: f64,
+    egress_gb: f64,
+}
+
+/// Invoice generation from metered usage
+#[derive(Debug, Clone)]
+pub struct BillingEngine {
+    registry: Arc<TenantRegistry>,
+    price_book: PriceBook,
+}
+
+impl BillingEngine {
+    /// Create an engine billing tenants of `registry` at the default prices
+    pub fn new(registry: Arc<TenantRegistry>) -> Self {
+        Self {
+            registry,
+            price_book: PriceBook::default(),
+        }
+    }
+
+    /// Use a custom price book
+    pub fn with_price_book(mut self, price_book: PriceBook) -> Self {
+        self.price_book = price_book;
+        self
+    }
+
+    /// Get the price book
+    pub fn price_book(&self) -> &PriceBook {
+        &self.price_book
+    }
+
+    /// Generate a tenant's invoice for a billing period (YYYY-MM)
+    ///
+    /// Regenerating a period replaces the stored invoice, keeping its ID.
+    pub fn generate_invoice(&self, tenant_id: Uuid, period: &str) -> ControlPlaneResult<Invoice> {
+        let (start, days) = parse_period(period)?;
+        let tenant = self.registry.get(tenant_id)?;
+        let usage = self.daily_usage(tenant_id, period, days);
+
+        // Bill each run of consecutive days on the same plan separately
+        let mut line_items = Vec::new();
+        let mut first = 0;
+        while first < days {
+            let plan = plan_on_day(&tenant, start, first);
+            let mut end = first + 1;
+            while end < days && plan_on_day(&tenant, start, end) == plan {
+                end += 1;
+            }
+            line_items.extend(self.plan_items(plan, first, &usage[first..end], days));
+            first = end;
+        }
+
+        let subtotal = round_cents(line_items.iter().map(|i| i.total).sum());
+        let tax = round_cents(subtotal * self.price_book.tax_rate);
+        let existing = self.registry.get_invoice(tenant_id, period).ok();
+        let now = Utc::now();
+        let invoice = Invoice {
+            invoice_id: existing.as_ref().map_or_else(Uuid::new_v4, |i| i.invoice_id),
+            tenant_id,
+            period: period.to_string(),
+            plan: plan_on_day(&tenant, start, days - 1),
+            line_items,
+            subtotal,
+            tax,
+            total: round_cents(subtotal + tax),
+            currency: self.price_book.currency.clone(),
+            generated_at: existing.as_ref().map_or(now, |i| i.generated_at),
+            updated_at: now,
+            paid_at: existing.and_then(|i| i.paid_at),
+        };
+        self.registry.store_invoice(invoice.clone())?;
+        Ok(invoice)
+    }
+
+    /// A tenant's invoices, oldest period first
+    pub fn list_invoices(&self, tenant_id: Uuid) -> ControlPlaneResult<Vec<Invoice>> {
+        self.registry.get(tenant_id)?;
+        Ok(self.registry.list_invoices(tenant_id))
+    }
+
+    /// A tenant's invoice for a billing period (YYYY-MM)
+    pub fn get_invoice(&self, tenant_id: Uuid, period: &str) -> ControlPlaneResult<Invoice> {
+        self.registry.get_invoice(tenant_id, period)
+    }
+
+    /// Usage of each day of the period
+    ///
+    /// Days without a snapshot carry the previous day's storage forward and
+    /// have no operations or egress. When only the monthly total was metered
+    /// it is spread evenly over the period.
+    fn daily_usage(&self, tenant_id: Uuid, period: &str, days: usize) -> Vec<DayUsage> {
+        let tracker = self.registry.usage_tracker();
+        let snapshots = tracker.daily_usage(tenant_id, period);
+        let mut usage = vec![DayUsage::default(); days];
+
+        if snapshots.is_empty() {
+            if let Some(month) = tracker.get_usage(tenant_id, period) {
+                let days = days as f64;
+                usage.fill(DayUsage {
+                    storage_gb: month.storage_bytes as f64 / GB,
+                    read_ops: month.read_ops as f64 / days,
+                    write_ops: month.write_ops as f64 / days,
+                    egress_gb: month.egress_bytes as f64 / GB / days,
+                });
+            }
+            return usage;
+        }
+
+        let mut recorded = vec![None; days];
+        for snapshot in snapshots {
+            let day = snapshot.date.get(8..10).and_then(|d| d.parse::<usize>().ok());
+            if let Some(entry) = day.and_then(|d| recorded.get_mut(d.wrapping_sub(1))) {
+                *entry = Some(snapshot);
+            }
+        }
+        let mut storage_gb = 0.0;
+        for (day, snapshot) in usage.iter_mut().zip(recorded) {
+            if let Some(snapshot) = snapshot {
+                storage_gb = snapshot.storage_bytes as f64 / GB;
+                day.read_ops = snapshot.read_ops as f64;
+                day.write_ops = snapshot.write_ops as f64;
+                day.egress_gb = snapshot.egress_bytes as f64 / GB;
+            }
+            day.storage_gb = storage_gb;
+        }
+        usage
+    }
+
+    /// Line items for the days `first..first + usage.len()` billed on `plan`
+    fn plan_items(
+        &self,
+        plan: Plan,
+        first: usize,
+        usage: &[DayUsage],
+        days: usize,
+    ) -> Vec<LineItem> {
+        let rates = self.price_book.rates(plan);
+        let last = first + usage.len();
+        let span = if usage.len() == 1 {
+            format!("day {}", last)
+        } else {
+            format!("days {}-{}", first + 1, last)
+        };
+        let sum = |f: fn(&DayUsage) -> f64| usage.iter().map(f).sum::<f64>();
+
+        let charges = [
+            ("Base fee", usage.len() as f64 / days as f64, "months", rates.base_monthly),
+            (
+                "Storage",
+                sum(|d| d.storage_gb) / days as f64,
+                "GB-months",
+                rates.storage_per_gb_month,
+            ),
+            (
+                "Read operations",
+                sum(|d| d.read_ops) / 1_000_000.0,
+                "million ops",
+                rates.reads_per_million,
+            ),
+            (
+                "Write operations",
+                sum(|d| d.write_ops) / 1_000_000.0,
+                "million ops",
+                rates.writes_per_million,
+            ),
+            ("Egress", sum(|d| d.egress_gb), "GB", rates.egress_per_gb),
+        ];
+        charges
+            .into_iter()
+            .filter(|(_, quantity, _, _)| *quantity > 0.0)
+            .map(|(description, quantity, unit, unit_price)| LineItem {
+                description: format!("{} ({} plan, {})", description, plan, span),
+                quantity: (quantity * 1e6).round() / 1e6,
+                unit: unit.to_string(),
+                unit_price,
+                total: round_cents(quantity * unit_price),
+            })
+            .collect()
+    }
+}
+
+/// First day and length in days of a billing period (YYYY-MM)
+fn parse_period(period: &str) -> ControlPlaneResult<(NaiveDate, usize)> {
+    let start = NaiveDate::parse_from_str(&format!("{}-01", period), "%Y-%m-%d")
+        .ok()
+        .filter(|_| period.len() == 7)
+        .ok_or_else(|| ControlPlaneError::ConfigError {
+            message: format!("Invalid billing period '{}': expected YYYY-MM", period),
+        })?;
+    let next = match start.month() {
+        12 => NaiveDate::from_ymd_opt(start.year() + 1, 1, 1),
+        month => NaiveDate::from_ymd_opt(start.year(), month + 1, 1),
+    }
+    .expect("first of next month is a valid date");
+    Ok((start, (next - start).num_days() as usize))
+}
+
+/// Plan a day is billed at: the plan in effect at the end of the day
+fn plan_on_day(tenant: &Tenant, start: NaiveDate, day: usize) -> Plan {
+    let next_day = (start + Duration::days(day as i64 + 1))
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time");
+    tenant.plan_at(Utc.from_utc_datetime(&next_day) - Duration::nanoseconds(1))
+}
+
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::control_plane::metering::DailySnapshot;
 
     #[test]
     fn test_base_pricing() {
@@ -337,8 +695,131 @@ mod tests {
 
         let invoice = calc.generate_invoice(tenant_id, "2024-08", &Plan::Pro, &usage);
         assert_eq!(invoice.tenant_id, tenant_id);
-        assert_eq!(invoice.month, "2024-08");
-        assert_eq!(invoice.base_price, 25.0);
-        assert!(invoice.total >= invoice.base_price);
+        assert_eq!(invoice.period, "2024-08");
+        assert_eq!(invoice.line_items[0].total, 25.0);
+        assert!(invoice.total >= 25.0);
+    }
+
+    /// Pro until a switch to Enterprise on day 15 of June 2026, with
+    /// 10 GB stored, 1M reads, 200k writes and 1 GB egress every day
+    fn june_with_plan_change() -> (BillingEngine, Uuid) {
+        let registry = Arc::new(TenantRegistry::new());
+        let tenant = Tenant::new(
+            "acme".to_string(),
+            Plan::Pro,
+            "local".to_string(),
+            crate::control_plane::tenant::IsolationModel::Schema,
+        );
+        let tenant_id = tenant.tenant_id;
+        registry.insert(tenant).unwrap();
+        let day_15 = Utc.with_ymd_and_hms(2026, 6, 15, 0, 0, 0).unwrap();
+        registry.change_plan(tenant_id, Plan::Enterprise, day_15).unwrap();
+
+        let tracker = registry.usage_tracker();
+        for day in 1..=30 {
+            tracker.record_daily_snapshot(DailySnapshot {
+                date: format!("2026-06-{:02}", day),
+                tenant_id,
+                api_requests: 0,
+                storage_bytes: 10 * GB as u64,
+                file_storage_bytes: 0,
+                egress_bytes: GB as u64,
+                realtime_connections_peak: 0,
+                read_ops: 1_000_000,
+                write_ops: 200_000,
+            });
+        }
+
+        let engine = BillingEngine::new(registry)
+            .with_price_book(PriceBook::default().with_tax_rate(0.10));
+        (engine, tenant_id)
+    }
+
+    #[test]
+    fn test_invoice_prorates_plan_change() {
+        let (engine, tenant_id) = june_with_plan_change();
+        let invoice = engine.generate_invoice(tenant_id, "2026-06").unwrap();
+
+        let items: Vec<(&str, f64)> = invoice
+            .line_items
+            .iter()
+            .map(|i| (i.description.as_str(), i.total))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("Base fee (pro plan, days 1-14)", 11.67),
+                ("Storage (pro plan, days 1-14)", 1.17),
+                ("Read operations (pro plan, days 1-14)", 7.00),
+                ("Write operations (pro plan, days 1-14)", 4.20),
+                ("Egress (pro plan, days 1-14)", 1.26),
+                ("Base fee (enterprise plan, days 15-30)", 319.47),
+                ("Storage (enterprise plan, days 15-30)", 0.67),
+                ("Read operations (enterprise plan, days 15-30)", 4.00),
+                ("Write operations (enterprise plan, days 15-30)", 2.40),
+                ("Egress (enterprise plan, days 15-30)", 0.80),
+            ]
+        );
+        assert_eq!(invoice.subtotal, 352.64);
+        assert_eq!(invoice.tax, 35.26);
+        assert_eq!(invoice.total, 387.90);
+        assert_eq!(invoice.plan, Plan::Enterprise);
+        assert_eq!(invoice.line_items[1].quantity, 4.666667);
+    }
+
+    #[test]
+    fn test_regenerating_invoice_updates_in_place() {
+        let (engine, tenant_id) = june_with_plan_change();
+        let first = engine.generate_invoice(tenant_id, "2026-06").unwrap();
+
+        // Late metering for day 30 arrives after the first run
+        engine.registry.usage_tracker().record_daily_snapshot(DailySnapshot {
+            date: "2026-06-30".to_string(),
+            tenant_id,
+            api_requests: 0,
+            storage_bytes: 10 * GB as u64,
+            file_storage_bytes: 0,
+            egress_bytes: GB as u64,
+            realtime_connections_peak: 0,
+            read_ops: 5_000_000,
+            write_ops: 200_000,
+        });
+        let second = engine.generate_invoice(tenant_id, "2026-06").unwrap();
+
+        assert_eq!(second.invoice_id, first.invoice_id);
+        assert_eq!(second.generated_at, first.generated_at);
+        assert_eq!(second.subtotal, 353.64);
+        let invoices = engine.list_invoices(tenant_id).unwrap();
+        assert_eq!(invoices.len(), 1);
+        assert_eq!(invoices[0].total, second.total);
+        assert_eq!(engine.get_invoice(tenant_id, "2026-06").unwrap().total, second.total);
+        assert!(matches!(
+            engine.get_invoice(tenant_id, "2026-05"),
+            Err(ControlPlaneError::InvoiceNotFound { .. })
+        ));
+        assert!(matches!(
+            engine.generate_invoice(tenant_id, "2026-13"),
+            Err(ControlPlaneError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invoice_export() {
+        let (engine, tenant_id) = june_with_plan_change();
+        let invoice = engine.generate_invoice(tenant_id, "2026-06").unwrap();
+
+        let parsed: Invoice = serde_json::from_str(&invoice.to_json()).unwrap();
+        assert_eq!(parsed.invoice_id, invoice.invoice_id);
+        assert_eq!(parsed.line_items.len(), 10);
+        assert_eq!(parsed.total, 387.90);
+
+        let csv = invoice.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 14);
+        assert_eq!(lines[0], "description,quantity,unit,unit_price,total");
+        assert_eq!(lines[1], "\"Base fee (pro plan, days 1-14)\",0.466667,months,25,11.67");
+        assert_eq!(lines[11], "subtotal,,,,352.64");
+        assert_eq!(lines[12], "tax,,,,35.26");
+        assert_eq!(lines[13], "total,,,,387.90");
     }
 }
@@ -1,19 +1,238 @@
 //! # Database-per-Tenant Provisioner
 //!
 //! Separate database processes for complete isolation.
+//!
+//! Each tenant gets a directory under the base data directory holding its
+//! config file (`aerodb.toml`), its data directory (`data/`) and the server
+//! log (`aerodb.log`). Provisioning runs `aerodb init` and then spawns
+//! `aerodb serve` on the tenant's port; the process ID and port are recorded
+//! in the tenant's `TenantConfig`.
+//!
+//! ## Supervision
+//!
+//! `supervise` runs a task that probes every child's `/health` endpoint once
+//! per `health_interval`. A child that exited, or is still unhealthy after its
+//! startup timeout, is restarted after an exponential backoff. When
+//! `max_restarts` consecutive restarts fail, the tenant is marked `Degraded`
+//! and left stopped. A healthy probe resets the count. Restarts, failed
+//! restarts and degradations are logged as `TENANT_DATABASE_*` events.
+//!
+//! Children outlive the control plane. After a control plane restart,
+//! `recover` adopts them again from the process IDs in the registry.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+use crate::observability::{JsonLogger, Logger};
+
 use super::errors::{ControlPlaneError, ControlPlaneResult};
-use super::tenant::{Tenant, TenantConfig};
+use super::registry::TenantRegistry;
+use super::tenant::{IsolationModel, Tenant, TenantConfig, TenantStatus};
+use crate::config_validator::AeroConfig;
+use crate::version::process_alive;
 
 /// Port range for tenant databases
 const PORT_RANGE_START: u16 = 50000;
 const PORT_RANGE_END: u16 = 60000;
 
+/// Config file in each tenant directory
+pub const TENANT_CONFIG_FILE: &str = "aerodb.toml";
+
+/// Server log in each tenant directory
+pub const TENANT_LOG_FILE: &str = "aerodb.log";
+
+/// How tenant processes are probed, restarted and stopped
+#[derive(Debug, Clone)]
+pub struct SupervisorPolicy {
+    /// Time between supervision passes
+    pub health_interval: Duration,
+    /// Timeout of one health probe
+    pub probe_timeout: Duration,
+    /// Time a new process has to become healthy
+    pub startup_timeout: Duration,
+    /// Delay before the first restart, doubled for each further one
+    pub initial_backoff: Duration,
+    /// Upper bound of the restart delay
+    pub max_backoff: Duration,
+    /// Consecutive restarts before the tenant is marked degraded
+    pub max_restarts: u32,
+    /// Time between SIGTERM and SIGKILL when stopping a process
+    pub stop_timeout: Duration,
+}
+
+impl SupervisorPolicy {
+    /// Delay before restart number `attempt` (0 = first restart)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+}
+
+impl Default for SupervisorPolicy {
+    fn default() -> Self {
+        Self {
+            health_interval: Duration::from_secs(5),
+            probe_timeout: Duration::from_secs(2),
+            startup_timeout: Duration::from_secs(30),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_restarts: 5,
+            stop_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Action taken by a supervision pass
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupervisionEvent {
+    /// A crashed or unhealthy process was replaced
+    Restarted {
+        tenant_id: Uuid,
+        pid: u32,
+        attempt: u32,
+    },
+    /// A replacement process could not be spawned
+    RestartFailed { tenant_id: Uuid, reason: String },
+    /// Restarts were exhausted and the tenant was marked degraded
+    Degraded { tenant_id: Uuid, restarts: u32 },
+}
+
+impl fmt::Display for SupervisionEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Restarted {
+                tenant_id,
+                pid,
+                attempt,
+            } => write!(
+                f,
+                "Restarted database of tenant {} as pid {} (attempt {})",
+                tenant_id, pid, attempt
+            ),
+            Self::RestartFailed { tenant_id, reason } => {
+                write!(
+                    f,
+                    "Failed to restart database of tenant {}: {}",
+                    tenant_id, reason
+                )
+            }
+            Self::Degraded {
+                tenant_id,
+                restarts,
+            } => write!(
+                f,
+                "Tenant {} degraded: database still down after {} restarts",
+                tenant_id, restarts
+            ),
+        }
+    }
+}
+
+impl SupervisionEvent {
+    /// Log the event: a restart as a warning, a failed restart or a
+    /// degraded tenant as an error
+    pub fn log(&self, logger: &dyn Logger) {
+        match self {
+            Self::Restarted {
+                tenant_id,
+                pid,
+                attempt,
+            } => logger.warn(
+                "TENANT_DATABASE_RESTARTED",
+                &[
+                    ("tenant_id", &tenant_id.to_string()),
+                    ("pid", &pid.to_string()),
+                    ("attempt", &attempt.to_string()),
+                ],
+            ),
+            Self::RestartFailed { tenant_id, reason } => logger.error(
+                "TENANT_DATABASE_RESTART_FAILED",
+                &[("tenant_id", &tenant_id.to_string()), ("reason", reason)],
+            ),
+            Self::Degraded {
+                tenant_id,
+                restarts,
+            } => logger.error(
+                "TENANT_DATABASE_DEGRADED",
+                &[
+                    ("tenant_id", &tenant_id.to_string()),
+                    ("restarts", &restarts.to_string()),
+                ],
+            ),
+        }
+    }
+}
+
+/// Server process of a tenant
+#[derive(Debug)]
+enum Process {
+    /// Spawned by this control plane
+    Owned(Child),
+    /// Spawned before a control plane restart, known by PID only
+    Adopted(u32),
+    /// Not running
+    Exited,
+}
+
+impl Process {
+    fn pid(&self) -> Option<u32> {
+        match self {
+            Process::Owned(child) => child.id(),
+            Process::Adopted(pid) => Some(*pid),
+            Process::Exited => None,
+        }
+    }
+
+    fn is_running(&mut self) -> bool {
+        match self {
+            Process::Owned(child) => matches!(child.try_wait(), Ok(None)),
+            Process::Adopted(pid) => process_alive(*pid),
+            Process::Exited => false,
+        }
+    }
+}
+
+/// A tenant process under supervision
+#[derive(Debug)]
+struct Supervised {
+    port: u16,
+    process: Process,
+    started_at: Instant,
+    /// Consecutive restarts since the process was last healthy
+    restarts: u32,
+    /// When the pending restart is due
+    restart_at: Option<Instant>,
+    degraded: bool,
+}
+
+impl Supervised {
+    fn new(port: u16, process: Process) -> Self {
+        Self {
+            port,
+            process,
+            started_at: Instant::now(),
+            restarts: 0,
+            restart_at: None,
+            degraded: false,
+        }
+    }
+}
+
 /// Database-per-tenant provisioner
 #[derive(Debug)]
 pub struct DatabaseProvisioner {
@@ -23,19 +242,26 @@ pub struct DatabaseProvisioner {
     allocated_ports: Arc<RwLock<HashMap<Uuid, u16>>>,
     /// Next available port
     next_port: Arc<RwLock<u16>>,
+    /// End of the port range (exclusive)
+    port_range_end: u16,
     /// Whether to actually start processes
     start_processes: bool,
+    /// Server binary
+    binary: PathBuf,
+    /// Where deprovisioned tenant directories are archived (None: deleted)
+    archive_dir: Option<PathBuf>,
+    /// Supervision policy
+    policy: SupervisorPolicy,
+    /// Supervised processes by tenant
+    children: Arc<Mutex<HashMap<Uuid, Supervised>>>,
+    /// Where supervision events are logged
+    logger: Arc<dyn Logger>,
 }
 
 impl DatabaseProvisioner {
     /// Create a new database provisioner
     pub fn new() -> Self {
-        Self {
-            base_data_dir: PathBuf::from("/data/tenants"),
-            allocated_ports: Arc::new(RwLock::new(HashMap::new())),
-            next_port: Arc::new(RwLock::new(PORT_RANGE_START)),
-            start_processes: false, // Default to false for safety
-        }
+        Self::with_data_dir(PathBuf::from("/data/tenants"))
     }
 
     /// Create with custom data directory
@@ -44,7 +270,13 @@ impl DatabaseProvisioner {
             base_data_dir: data_dir,
             allocated_ports: Arc::new(RwLock::new(HashMap::new())),
             next_port: Arc::new(RwLock::new(PORT_RANGE_START)),
-            start_processes: false,
+            port_range_end: PORT_RANGE_END,
+            start_processes: false, // Default to false for safety
+            binary: PathBuf::from("aerodb"),
+            archive_dir: None,
+            policy: SupervisorPolicy::default(),
+            children: Arc::new(Mutex::new(HashMap::new())),
+            logger: JsonLogger::shared(),
         }
     }
 
@@ -54,6 +286,37 @@ impl DatabaseProvisioner {
         self
     }
 
+    /// Use a different server binary (default: `aerodb` from `PATH`)
+    pub fn with_binary(mut self, binary: PathBuf) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    /// Archive tenant directories here on deprovisioning instead of deleting them
+    pub fn with_archive_dir(mut self, archive_dir: PathBuf) -> Self {
+        self.archive_dir = Some(archive_dir);
+        self
+    }
+
+    /// Use a custom supervision policy
+    pub fn with_policy(mut self, policy: SupervisorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Log supervision events to `logger`
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Allocate ports from `start..end`
+    pub fn with_port_range(mut self, start: u16, end: u16) -> Self {
+        self.next_port = Arc::new(RwLock::new(start));
+        self.port_range_end = end;
+        self
+    }
+
     /// Allocate a port for a tenant
     fn allocate_port(&self, tenant_id: Uuid) -> ControlPlaneResult<u16> {
         let mut next = self.next_port.write().unwrap();
@@ -66,7 +329,7 @@ impl DatabaseProvisioner {
 
         // Find next available port
         let port = *next;
-        if port >= PORT_RANGE_END {
+        if port >= self.port_range_end {
             return Err(ControlPlaneError::ProvisioningFailed {
                 tenant_id: tenant_id.to_string(),
                 reason: "No available ports".to_string(),
@@ -79,15 +342,36 @@ impl DatabaseProvisioner {
         Ok(port)
     }
 
+    /// Mark a port recorded in the registry as allocated
+    fn reserve_port(&self, tenant_id: Uuid, port: u16) {
+        let mut next = self.next_port.write().unwrap();
+        let mut allocated = self.allocated_ports.write().unwrap();
+
+        allocated.insert(tenant_id, port);
+        if *next <= port {
+            *next = port.saturating_add(1);
+        }
+    }
+
     /// Release a port
     fn release_port(&self, tenant_id: Uuid) {
         let mut allocated = self.allocated_ports.write().unwrap();
         allocated.remove(&tenant_id);
     }
 
+    /// Get the directory holding a tenant's config, data and log
+    pub fn tenant_dir(&self, tenant_id: Uuid) -> PathBuf {
+        self.base_data_dir.join(tenant_id.to_string())
+    }
+
     /// Get data directory for a tenant
     pub fn tenant_data_dir(&self, tenant_id: Uuid) -> PathBuf {
-        self.base_data_dir.join(tenant_id.to_string())
+        self.tenant_dir(tenant_id).join("data")
+    }
+
+    /// Get the config file for a tenant
+    pub fn tenant_config_path(&self, tenant_id: Uuid) -> PathBuf {
+        self.tenant_dir(tenant_id).join(TENANT_CONFIG_FILE)
     }
 
     /// Provision database-per-tenant resources
@@ -95,51 +379,17 @@ impl DatabaseProvisioner {
         let port = self.allocate_port(tenant.tenant_id)?;
         let data_dir = self.tenant_data_dir(tenant.tenant_id);
 
-        if self.start_processes {
-            // Create data directory
-            tokio::fs::create_dir_all(&data_dir).await.map_err(|e| {
-                ControlPlaneError::ProvisioningFailed {
-                    tenant_id: tenant.tenant_id.to_string(),
-                    reason: format!("Failed to create data directory: {}", e),
+        let process_id = if self.start_processes {
+            match self.start_tenant(tenant.tenant_id, port).await {
+                Ok(pid) => Some(pid),
+                Err(e) => {
+                    self.release_port(tenant.tenant_id);
+                    return Err(e);
                 }
-            })?;
-
-            // Initialize database
-            let init_output = tokio::process::Command::new("aerodb")
-                .args(&["init", "--data-dir", data_dir.to_str().unwrap()])
-                .output()
-                .await
-                .map_err(|e| ControlPlaneError::ProcessError {
-                    message: format!("Failed to initialize database: {}", e),
-                })?;
-
-            if !init_output.status.success() {
-                return Err(ControlPlaneError::ProvisioningFailed {
-                    tenant_id: tenant.tenant_id.to_string(),
-                    reason: format!(
-                        "Database init failed: {}",
-                        String::from_utf8_lossy(&init_output.stderr)
-                    ),
-                });
             }
-
-            // Start database process
-            let _child = tokio::process::Command::new("aerodb")
-                .args(&[
-                    "serve",
-                    "--port",
-                    &port.to_string(),
-                    "--data-dir",
-                    data_dir.to_str().unwrap(),
-                ])
-                .spawn()
-                .map_err(|e| ControlPlaneError::ProcessError {
-                    message: format!("Failed to start database: {}", e),
-                })?;
-
-            // Wait for health check
-            self.wait_for_health(port, 30).await?;
-        }
+        } else {
+            None
+        };
 
         let database_url = format!("http://localhost:{}", port);
 
@@ -147,51 +397,153 @@ impl DatabaseProvisioner {
             database_url,
             port: Some(port),
             data_dir: Some(data_dir.to_string_lossy().to_string()),
-            process_id: None, // Would be set from child.id() in production
+            process_id,
         })
     }
 
-    /// Wait for database to be ready
-    async fn wait_for_health(&self, port: u16, timeout_secs: u64) -> ControlPlaneResult<()> {
-        use tokio::net::TcpStream;
-        
-        let addr = format!("127.0.0.1:{}", port);
-        let start = std::time::Instant::now();
-        
+    /// Write the tenant's config, initialize its data directory and start
+    /// its server, returning the server's PID once it is healthy
+    async fn start_tenant(&self, tenant_id: Uuid, port: u16) -> ControlPlaneResult<u32> {
+        let failed = |reason: String| ControlPlaneError::ProvisioningFailed {
+            tenant_id: tenant_id.to_string(),
+            reason,
+        };
+
+        tokio::fs::create_dir_all(self.tenant_dir(tenant_id))
+            .await
+            .map_err(|e| failed(format!("Failed to create tenant directory: {}", e)))?;
+
+        let config_path = self.tenant_config_path(tenant_id);
+        let data_dir = self.tenant_data_dir(tenant_id);
+        let config = AeroConfig::new(data_dir.to_string_lossy());
+        let config = toml::to_string(&config).expect("tenant config serializes");
+        tokio::fs::write(&config_path, config)
+            .await
+            .map_err(|e| failed(format!("Failed to write {}: {}", config_path.display(), e)))?;
+
+        // Initialize database
+        let init_output = Command::new(&self.binary)
+            .arg("init")
+            .arg("--config")
+            .arg(&config_path)
+            .output()
+            .await
+            .map_err(|e| ControlPlaneError::ProcessError {
+                message: format!("Failed to initialize database: {}", e),
+            })?;
+
+        if !init_output.status.success() {
+            return Err(failed(format!(
+                "Database init failed: {}",
+                String::from_utf8_lossy(&init_output.stderr)
+            )));
+        }
+
+        // Start database process
+        let mut child = self.spawn_server(tenant_id, port)?;
+        let pid = child.id().unwrap_or_default();
+
+        // Wait for health check
+        if let Err(reason) = self.wait_for_health(&mut child, port).await {
+            stop_process(&mut Process::Owned(child), self.policy.stop_timeout).await;
+            return Err(failed(reason));
+        }
+
+        let mut children = self.children.lock().await;
+        children.insert(tenant_id, Supervised::new(port, Process::Owned(child)));
+        Ok(pid)
+    }
+
+    /// Spawn `aerodb serve` for a tenant, logging to its directory
+    fn spawn_server(&self, tenant_id: Uuid, port: u16) -> ControlPlaneResult<Child> {
+        let log_path = self.tenant_dir(tenant_id).join(TENANT_LOG_FILE);
+        let open_log = || -> std::io::Result<(File, File)> {
+            let log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&log_path)?;
+            let err = log.try_clone()?;
+            Ok((log, err))
+        };
+        let (stdout, stderr) = open_log().map_err(|e| ControlPlaneError::ProcessError {
+            message: format!("Failed to open {}: {}", log_path.display(), e),
+        })?;
+
+        let mut command = Command::new(&self.binary);
+        command
+            .arg("serve")
+            .arg("--config")
+            .arg(self.tenant_config_path(tenant_id))
+            .arg("--port")
+            .arg(port.to_string())
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr);
+        // Own process group: a signal to the control plane's group (e.g.
+        // Ctrl-C) must not take the tenant databases down with it
+        #[cfg(unix)]
+        command.process_group(0);
+
+        command
+            .spawn()
+            .map_err(|e| ControlPlaneError::ProcessError {
+                message: format!("Failed to start database: {}", e),
+            })
+    }
+
+    /// Wait for a new server to answer its health check
+    async fn wait_for_health(&self, child: &mut Child, port: u16) -> Result<(), String> {
+        let deadline = Instant::now() + self.policy.startup_timeout;
+
         loop {
-            match TcpStream::connect(&addr).await {
-                Ok(_) => return Ok(()),
-                Err(_) => {
-                    if start.elapsed().as_secs() > timeout_secs {
-                        return Err(ControlPlaneError::ProvisioningFailed {
-                            tenant_id: "unknown".to_string(),
-                            reason: "Health check timeout".to_string(),
-                        });
-                    }
-                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                }
+            if let Ok(Some(status)) = child.try_wait() {
+                return Err(format!("Database exited during startup: {}", status));
             }
+            if probe_health(port, self.policy.probe_timeout).await {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err("Health check timeout".to_string());
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
         }
     }
 
     /// Deprovision database-per-tenant resources
+    ///
+    /// Stops the tenant's server (SIGTERM, then SIGKILL after the stop
+    /// timeout) before archiving or removing its directory.
     pub async fn deprovision(&self, tenant: &Tenant) -> ControlPlaneResult<()> {
-        if self.start_processes {
-            // Stop database process (would use stored PID in production)
-            // For now, we just clean up
+        let supervised = self.children.lock().await.remove(&tenant.tenant_id);
+        match supervised {
+            Some(mut supervised) => {
+                stop_process(&mut supervised.process, self.policy.stop_timeout).await;
+            }
+            None if self.start_processes => {
+                // Never adopted (e.g. recover was not run): trust the
+                // recorded PID only if it still runs this tenant's config
+                let pid = tenant.config.as_ref().and_then(|c| c.process_id);
+                let config_path = self.tenant_config_path(tenant.tenant_id);
+                if let Some(pid) = pid.filter(|&pid| is_tenant_process(pid, &config_path)) {
+                    stop_process(&mut Process::Adopted(pid), self.policy.stop_timeout).await;
+                }
+            }
+            None => {}
         }
 
         // Release port
         self.release_port(tenant.tenant_id);
 
         if self.start_processes {
-            // Remove data directory
-            let data_dir = self.tenant_data_dir(tenant.tenant_id);
-            if data_dir.exists() {
-                tokio::fs::remove_dir_all(&data_dir).await.map_err(|e| {
+            let tenant_dir = self.tenant_dir(tenant.tenant_id);
+            if tenant_dir.exists() {
+                if let Some(archive_dir) = &self.archive_dir {
+                    archive_tenant_dir(tenant.tenant_id, &tenant_dir, archive_dir)?;
+                }
+                tokio::fs::remove_dir_all(&tenant_dir).await.map_err(|e| {
                     ControlPlaneError::DeprovisioningFailed {
                         tenant_id: tenant.tenant_id.to_string(),
-                        reason: format!("Failed to remove data directory: {}", e),
+                        reason: format!("Failed to remove tenant directory: {}", e),
                     }
                 })?;
             }
@@ -200,6 +552,125 @@ impl DatabaseProvisioner {
         Ok(())
     }
 
+    /// Take the processes of database-per-tenant tenants recorded in the
+    /// registry under supervision, returning how many tenants were recovered
+    ///
+    /// A recorded PID is adopted only while it still runs the tenant's
+    /// config; tenants whose process died while no control plane was
+    /// running are restarted by the next supervision pass.
+    pub async fn recover(&self, registry: &TenantRegistry) -> usize {
+        if !self.start_processes {
+            return 0;
+        }
+
+        let mut children = self.children.lock().await;
+        let mut recovered = 0;
+        for tenant in registry.tenants() {
+            if tenant.isolation != IsolationModel::Database {
+                continue;
+            }
+            let Some(port) = tenant.config.as_ref().and_then(|c| c.port) else {
+                continue;
+            };
+            self.reserve_port(tenant.tenant_id, port);
+
+            let config_path = self.tenant_config_path(tenant.tenant_id);
+            let process = match tenant.config.as_ref().and_then(|c| c.process_id) {
+                Some(pid) if is_tenant_process(pid, &config_path) => Process::Adopted(pid),
+                _ => Process::Exited,
+            };
+            let mut supervised = Supervised::new(port, process);
+            supervised.degraded = tenant.status == TenantStatus::Degraded;
+            children.insert(tenant.tenant_id, supervised);
+            recovered += 1;
+        }
+        recovered
+    }
+
+    /// Run supervision passes every `health_interval` until the task is aborted
+    pub fn supervise(&self, registry: Arc<TenantRegistry>) -> JoinHandle<()> {
+        let provisioner = self.clone();
+        tokio::spawn(async move {
+            loop {
+                for event in provisioner.supervise_once(&registry).await {
+                    event.log(provisioner.logger.as_ref());
+                }
+                tokio::time::sleep(provisioner.policy.health_interval).await;
+            }
+        })
+    }
+
+    /// Health-check every supervised process once, restarting or degrading
+    /// the tenants whose process is down
+    pub async fn supervise_once(&self, registry: &TenantRegistry) -> Vec<SupervisionEvent> {
+        let mut events = Vec::new();
+        let mut children = self.children.lock().await;
+
+        for (&tenant_id, supervised) in children.iter_mut() {
+            if supervised.degraded {
+                continue;
+            }
+
+            let running = supervised.process.is_running();
+            if running && probe_health(supervised.port, self.policy.probe_timeout).await {
+                supervised.restarts = 0;
+                supervised.restart_at = None;
+                continue;
+            }
+            if running && supervised.started_at.elapsed() < self.policy.startup_timeout {
+                // Still starting up
+                continue;
+            }
+
+            if supervised.restarts >= self.policy.max_restarts {
+                stop_process(&mut supervised.process, self.policy.stop_timeout).await;
+                supervised.degraded = true;
+                registry.mark_degraded(tenant_id).ok();
+                events.push(SupervisionEvent::Degraded {
+                    tenant_id,
+                    restarts: supervised.restarts,
+                });
+                continue;
+            }
+
+            let now = Instant::now();
+            let backoff = self.policy.backoff(supervised.restarts);
+            if now < *supervised.restart_at.get_or_insert(now + backoff) {
+                continue;
+            }
+
+            // An unhealthy process that is still running is replaced too
+            stop_process(&mut supervised.process, self.policy.stop_timeout).await;
+            supervised.restarts += 1;
+            supervised.restart_at = None;
+            supervised.started_at = Instant::now();
+            match self.spawn_server(tenant_id, supervised.port) {
+                Ok(child) => {
+                    let pid = child.id().unwrap_or_default();
+                    supervised.process = Process::Owned(child);
+                    registry.set_process_id(tenant_id, Some(pid)).ok();
+                    events.push(SupervisionEvent::Restarted {
+                        tenant_id,
+                        pid,
+                        attempt: supervised.restarts,
+                    });
+                }
+                Err(e) => events.push(SupervisionEvent::RestartFailed {
+                    tenant_id,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+
+        events
+    }
+
+    /// Get the PID of a tenant's supervised process
+    pub async fn process_id(&self, tenant_id: Uuid) -> Option<u32> {
+        let children = self.children.lock().await;
+        children.get(&tenant_id).and_then(|s| s.process.pid())
+    }
+
     /// Get allocated port for a tenant
     pub fn get_port(&self, tenant_id: Uuid) -> Option<u16> {
         let allocated = self.allocated_ports.read().unwrap();
@@ -225,11 +696,143 @@ impl Clone for DatabaseProvisioner {
             base_data_dir: self.base_data_dir.clone(),
             allocated_ports: self.allocated_ports.clone(),
             next_port: self.next_port.clone(),
+            port_range_end: self.port_range_end,
             start_processes: self.start_processes,
+            binary: self.binary.clone(),
+            archive_dir: self.archive_dir.clone(),
+            policy: self.policy.clone(),
+            children: self.children.clone(),
+            logger: self.logger.clone(),
+        }
+    }
+}
+
+/// Whether `GET /health` on a local port answers 200
+async fn probe_health(port: u16, timeout: Duration) -> bool {
+    let probe = async {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.ok()?;
+        stream
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .ok()?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await.ok()?;
+        let status = response.split(|&b| b == b' ').nth(1)?;
+        Some(status == b"200")
+    };
+    matches!(tokio::time::timeout(timeout, probe).await, Ok(Some(true)))
+}
+
+/// Stop a process: SIGTERM, then SIGKILL if it outlives `timeout`
+async fn stop_process(process: &mut Process, timeout: Duration) {
+    match process {
+        Process::Owned(child) => {
+            if let Some(pid) = child.id() {
+                send_signal(pid, Signal::Term);
+                if tokio::time::timeout(timeout, child.wait()).await.is_err() {
+                    // kill() sends SIGKILL and reaps the child
+                    child.kill().await.ok();
+                }
+            }
+        }
+        Process::Adopted(pid) => {
+            let pid = *pid;
+            send_signal(pid, Signal::Term);
+            if !wait_for_exit(pid, timeout).await {
+                send_signal(pid, Signal::Kill);
+                wait_for_exit(pid, timeout).await;
+            }
+        }
+        Process::Exited => {}
+    }
+    *process = Process::Exited;
+}
+
+/// Wait up to `timeout` for a process that is not our child to exit
+async fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while process_alive(pid) {
+        if Instant::now() >= deadline {
+            return false;
         }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    true
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Signal {
+    Term,
+    Kill,
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: Signal) {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return;
+    };
+    if pid <= 0 {
+        return;
+    }
+    let signal = match signal {
+        Signal::Term => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+    };
+    // SAFETY: kill has no memory effects; pid addresses a single process
+    unsafe {
+        libc::kill(pid, signal);
+    }
+}
+
+/// Signals are unavailable: owned children are still killed via `Child::kill`
+#[cfg(not(unix))]
+fn send_signal(_pid: u32, _signal: Signal) {}
+
+/// Whether `pid` is a live server process started with `config_path`
+///
+/// Guards against PID reuse when adopting or stopping processes from a
+/// previous control plane. Where the command line cannot be read (no
+/// /proc), a live PID is trusted.
+fn is_tenant_process(pid: u32, config_path: &Path) -> bool {
+    if !process_alive(pid) {
+        return false;
+    }
+    match fs::read(format!("/proc/{}/cmdline", pid)) {
+        Ok(cmdline) => cmdline
+            .split(|&b| b == 0)
+            .any(|arg| Path::new(&*String::from_utf8_lossy(arg)) == config_path),
+        Err(_) => true,
     }
 }
 
+/// Archive a tenant directory as `<archive_dir>/<tenant_id>-<timestamp>.tar`
+fn archive_tenant_dir(
+    tenant_id: Uuid,
+    tenant_dir: &Path,
+    archive_dir: &Path,
+) -> ControlPlaneResult<PathBuf> {
+    let failed = |e: std::io::Error| ControlPlaneError::DeprovisioningFailed {
+        tenant_id: tenant_id.to_string(),
+        reason: format!("Failed to archive tenant directory: {}", e),
+    };
+
+    fs::create_dir_all(archive_dir).map_err(failed)?;
+    let archive_path = archive_dir.join(format!(
+        "{}-{}.tar",
+        tenant_id,
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    let mut builder = tar::Builder::new(File::create(&archive_path).map_err(failed)?);
+    builder
+        .append_dir_all(tenant_id.to_string(), tenant_dir)
+        .map_err(failed)?;
+    builder
+        .into_inner()
+        .and_then(|file| file.sync_all())
+        .map_err(failed)?;
+    Ok(archive_path)
+}
+
 /// Database instance information
 #[derive(Debug, Clone)]
 pub struct TenantDatabase {
@@ -246,7 +849,10 @@ pub struct TenantDatabase {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::control_plane::tenant::{IsolationModel, Plan};
+    use crate::control_plane::schema_provisioner::SchemaProvisioner;
+    use crate::control_plane::tenant::{CreateTenantRequest, IsolationModel, Plan};
+    use crate::control_plane::ProvisioningService;
+    use crate::observability::{Severity, VecLogger};
 
     #[test]
     fn test_port_allocation() {
@@ -276,6 +882,19 @@ mod tests {
         assert!(dir.to_str().unwrap().contains(&tenant_id.to_string()));
     }
 
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = SupervisorPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(8));
+        assert_eq!(policy.backoff(4), Duration::from_secs(10));
+        assert_eq!(policy.backoff(40), Duration::from_secs(10));
+    }
+
     #[tokio::test]
     async fn test_provision() {
         let provisioner = DatabaseProvisioner::new();
@@ -310,4 +929,217 @@ mod tests {
         provisioner.deprovision(&tenant).await.unwrap();
         assert!(provisioner.get_port(tenant.tenant_id).is_none());
     }
+
+    /// Stub `aerodb`: `init` checks the config exists, `serve` sleeps (or
+    /// exits at once while a `crash` file exists). Health is answered by the
+    /// test itself on the tenant's port.
+    #[cfg(unix)]
+    fn stub_binary(dir: &Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("aerodb-stub");
+        let script = format!(
+            concat!(
+                "#!/bin/sh\n",
+                "case \"$1\" in\n",
+                "  init) test -f \"$3\" ;;\n",
+                "  serve) [ -e \"{}\" ] && exit 1; sleep 60 ;;\n",
+                "esac\n",
+            ),
+            dir.join("crash").display()
+        );
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    /// Serve `/health` on a free local port
+    async fn health_server() -> (u16, JoinHandle<()>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let routes = crate::http_server::observability_routes::health_routes();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, routes).await.unwrap();
+        });
+        (port, server)
+    }
+
+    #[cfg(unix)]
+    fn supervised_provisioner(dir: &Path, port: u16) -> DatabaseProvisioner {
+        DatabaseProvisioner::with_data_dir(dir.join("tenants"))
+            .with_process_spawning()
+            .with_binary(stub_binary(dir))
+            .with_archive_dir(dir.join("archive"))
+            .with_port_range(port, port + 1)
+            .with_policy(SupervisorPolicy {
+                startup_timeout: Duration::from_secs(5),
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(20),
+                max_restarts: 2,
+                stop_timeout: Duration::from_secs(2),
+                ..Default::default()
+            })
+    }
+
+    #[cfg(unix)]
+    async fn create_db_tenant(
+        provisioner: &DatabaseProvisioner,
+    ) -> (ProvisioningService, Uuid, u32) {
+        let registry = Arc::new(TenantRegistry::new());
+        let service = ProvisioningService::with_provisioners(
+            registry,
+            Arc::new(SchemaProvisioner::new()),
+            Arc::new(provisioner.clone()),
+        );
+        let response = service
+            .create_tenant(CreateTenantRequest {
+                name: "isolated".to_string(),
+                plan: Plan::Pro,
+                region: "local".to_string(),
+                isolation: IsolationModel::Database,
+            })
+            .await
+            .unwrap();
+        let tenant = service.get_tenant(response.tenant_id).unwrap();
+        let pid = tenant.config.unwrap().process_id.unwrap();
+        (service, response.tenant_id, pid)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_provision_spawns_and_deprovision_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let (port, _health) = health_server().await;
+        let provisioner = supervised_provisioner(dir.path(), port);
+
+        let (service, tenant_id, pid) = create_db_tenant(&provisioner).await;
+        assert!(process_alive(pid));
+        assert!(provisioner.tenant_config_path(tenant_id).is_file());
+        assert_eq!(
+            service.get_tenant(tenant_id).unwrap().config.unwrap().port,
+            Some(port)
+        );
+
+        service.delete_tenant(tenant_id).await.unwrap();
+        assert!(!process_alive(pid));
+        assert!(!provisioner.tenant_dir(tenant_id).exists());
+        let archives: Vec<_> = fs::read_dir(dir.path().join("archive")).unwrap().collect();
+        assert_eq!(archives.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_supervisor_restarts_crashed_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let (port, _health) = health_server().await;
+        let provisioner = supervised_provisioner(dir.path(), port);
+        let (service, tenant_id, pid) = create_db_tenant(&provisioner).await;
+        let registry = service.registry();
+
+        send_signal(pid, Signal::Kill);
+        let mut events = Vec::new();
+        for _ in 0..50 {
+            events.extend(provisioner.supervise_once(&registry).await);
+            if !events.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let Some(SupervisionEvent::Restarted {
+            pid: new_pid,
+            attempt: 1,
+            ..
+        }) = events.first().cloned()
+        else {
+            panic!("Expected a restart, got {:?}", events);
+        };
+        assert_ne!(new_pid, pid);
+        assert!(process_alive(new_pid));
+        let tenant = registry.get(tenant_id).unwrap();
+        assert_eq!(tenant.config.as_ref().unwrap().process_id, Some(new_pid));
+        assert!(tenant.is_active());
+
+        service.delete_tenant(tenant_id).await.unwrap();
+        assert!(!process_alive(new_pid));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_supervisor_degrades_after_exhausted_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let (port, health) = health_server().await;
+        let provisioner = supervised_provisioner(dir.path(), port);
+        let (service, tenant_id, pid) = create_db_tenant(&provisioner).await;
+        let registry = service.registry();
+
+        // Every restart now exits at once and health never answers
+        health.abort();
+        fs::write(dir.path().join("crash"), b"").unwrap();
+        send_signal(pid, Signal::Kill);
+
+        let mut events = Vec::new();
+        for _ in 0..200 {
+            events.extend(provisioner.supervise_once(&registry).await);
+            if matches!(events.last(), Some(SupervisionEvent::Degraded { .. })) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let restarts = events
+            .iter()
+            .filter(|e| matches!(e, SupervisionEvent::Restarted { .. }))
+            .count();
+        assert_eq!(restarts, 2);
+        assert_eq!(
+            events.last(),
+            Some(&SupervisionEvent::Degraded {
+                tenant_id,
+                restarts: 2
+            })
+        );
+        assert_eq!(
+            registry.get(tenant_id).unwrap().status,
+            TenantStatus::Degraded
+        );
+
+        let logger = VecLogger::new();
+        events.iter().for_each(|event| event.log(&logger));
+        let records = logger.records();
+        assert_eq!(
+            logger.events(),
+            [
+                "TENANT_DATABASE_RESTARTED",
+                "TENANT_DATABASE_RESTARTED",
+                "TENANT_DATABASE_DEGRADED"
+            ]
+        );
+        assert_eq!(records[2].severity, Severity::Error);
+        assert_eq!(records[2].field("restarts"), Some("2"));
+
+        // Degraded tenants are left alone
+        assert!(provisioner.supervise_once(&registry).await.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_recover_adopts_running_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (port, _health) = health_server().await;
+        let provisioner = supervised_provisioner(dir.path(), port);
+        let (service, tenant_id, pid) = create_db_tenant(&provisioner).await;
+        let registry = service.registry();
+
+        // A restarted control plane knows the children only from the registry
+        let restarted = supervised_provisioner(dir.path(), port);
+        assert_eq!(restarted.recover(&registry).await, 1);
+        assert_eq!(restarted.get_port(tenant_id), Some(port));
+        assert_eq!(restarted.process_id(tenant_id).await, Some(pid));
+        assert!(restarted.supervise_once(&registry).await.is_empty());
+
+        let tenant = registry.get(tenant_id).unwrap();
+        restarted.deprovision(&tenant).await.unwrap();
+        assert!(!process_alive(pid));
+    }
 }
@@ -37,11 +37,12 @@ pub enum ControlPlaneError {
         reason: String,
     },
 
-    /// Quota exceeded
+    /// Quota exceeded: `used` plus `requested` would pass `limit`
     QuotaExceeded {
         tenant_id: String,
         resource: String,
         used: u64,
+        requested: u64,
         limit: u64,
     },
 
@@ -50,6 +51,12 @@ pub enum ControlPlaneError {
         tenant_id: String,
     },
 
+    /// Resume asked of a tenant that is not suspended
+    TenantNotSuspended {
+        tenant_id: String,
+        status: String,
+    },
+
     /// Tenant is deleted
     TenantDeleted {
         tenant_id: String,
@@ -71,6 +78,12 @@ pub enum ControlPlaneError {
         message: String,
     },
 
+    /// No invoice for the tenant and billing period
+    InvoiceNotFound {
+        tenant_id: String,
+        period: String,
+    },
+
     /// Configuration error
     ConfigError {
         message: String,
@@ -80,6 +93,19 @@ pub enum ControlPlaneError {
     Internal {
         message: String,
     },
+
+    /// The operation needs a confirmation token; repeat it with this one
+    ConfirmationRequired {
+        operation: String,
+        confirmation_token: String,
+    },
+
+    /// The confirmation token given is unknown, expired, used, or was
+    /// issued for another operation
+    ConfirmationRejected {
+        operation: String,
+        reason: String,
+    },
 }
 
 impl fmt::Display for ControlPlaneError {
@@ -104,17 +130,21 @@ impl fmt::Display for ControlPlaneError {
                 tenant_id,
                 resource,
                 used,
+                requested,
                 limit,
             } => {
                 write!(
                     f,
-                    "Quota exceeded for {} on {}: {} / {}",
-                    tenant_id, resource, used, limit
+                    "Quota exceeded for {} on {}: {} used + {} requested > {} limit",
+                    tenant_id, resource, used, requested, limit
                 )
             }
             Self::TenantSuspended { tenant_id } => {
                 write!(f, "Tenant is suspended: {}", tenant_id)
             }
+            Self::TenantNotSuspended { tenant_id, status } => {
+                write!(f, "Tenant is not suspended: {} is {}", tenant_id, status)
+            }
             Self::TenantDeleted { tenant_id } => {
                 write!(f, "Tenant is deleted: {}", tenant_id)
             }
@@ -127,12 +157,28 @@ impl fmt::Display for ControlPlaneError {
             Self::ProcessError { message } => {
                 write!(f, "Process error: {}", message)
             }
+            Self::InvoiceNotFound { tenant_id, period } => {
+                write!(f, "No invoice for {} in {}", tenant_id, period)
+            }
             Self::ConfigError { message } => {
                 write!(f, "Configuration error: {}", message)
             }
             Self::Internal { message } => {
                 write!(f, "Internal error: {}", message)
             }
+            Self::ConfirmationRequired {
+                operation,
+                confirmation_token,
+            } => {
+                write!(
+                    f,
+                    "{} requires confirmation: repeat it with confirm={}",
+                    operation, confirmation_token
+                )
+            }
+            Self::ConfirmationRejected { operation, reason } => {
+                write!(f, "{} not confirmed: {}", operation, reason)
+            }
         }
     }
 }
@@ -153,12 +199,16 @@ impl ControlPlaneError {
             Self::DeprovisioningFailed { .. } => 500,
             Self::QuotaExceeded { .. } => 429,
             Self::TenantSuspended { .. } => 403,
+            Self::TenantNotSuspended { .. } => 409,
             Self::TenantDeleted { .. } => 410,
             Self::InvalidIsolationModel { .. } => 400,
             Self::DatabaseError { .. } => 500,
             Self::ProcessError { .. } => 500,
+            Self::InvoiceNotFound { .. } => 404,
             Self::ConfigError { .. } => 400,
             Self::Internal { .. } => 500,
+            Self::ConfirmationRequired { .. } => 428,
+            Self::ConfirmationRejected { .. } => 409,
         }
     }
 
@@ -172,12 +222,16 @@ impl ControlPlaneError {
             Self::DeprovisioningFailed { .. } => "DEPROVISIONING_FAILED",
             Self::QuotaExceeded { .. } => "QUOTA_EXCEEDED",
             Self::TenantSuspended { .. } => "TENANT_SUSPENDED",
+            Self::TenantNotSuspended { .. } => "TENANT_NOT_SUSPENDED",
             Self::TenantDeleted { .. } => "TENANT_DELETED",
             Self::InvalidIsolationModel { .. } => "INVALID_ISOLATION_MODEL",
             Self::DatabaseError { .. } => "DATABASE_ERROR",
             Self::ProcessError { .. } => "PROCESS_ERROR",
+            Self::InvoiceNotFound { .. } => "INVOICE_NOT_FOUND",
             Self::ConfigError { .. } => "CONFIG_ERROR",
             Self::Internal { .. } => "INTERNAL_ERROR",
+            Self::ConfirmationRequired { .. } => "CONFIRMATION_REQUIRED",
+            Self::ConfirmationRejected { .. } => "CONFIRMATION_REJECTED",
         }
     }
 }
@@ -218,7 +272,8 @@ mod tests {
         let err = ControlPlaneError::QuotaExceeded {
             tenant_id: "test".to_string(),
             resource: "storage".to_string(),
-            used: 600,
+            used: 450,
+            requested: 150,
             limit: 500,
         };
         assert_eq!(err.status_code(), 429);
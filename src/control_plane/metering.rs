@@ -1,10 +1,13 @@
 //! # Usage Metering
 //!
 //! Track resource usage per tenant for billing and quota enforcement.
+//!
+//! [`UsageTracker::export`] hands a billing period's usage to external
+//! billing as JSON or CSV, one row per tenant ordered by tenant ID.
 
 use chrono::{DateTime, Datelike, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
@@ -31,6 +34,21 @@ pub struct UsageMetrics {
     pub function_invocations: u64,
     /// Total function execution time (ms)
     pub function_execution_ms: u64,
+    /// Write operations that succeeded
+    #[serde(default)]
+    pub write_ops: u64,
+    /// Read operations that succeeded
+    #[serde(default)]
+    pub read_ops: u64,
+    /// Live documents
+    #[serde(default)]
+    pub documents: u64,
+    /// Operations admitted over a soft quota, by dimension
+    #[serde(default)]
+    pub soft_quota_events: BTreeMap<String, u64>,
+    /// Operations rejected at the tenant's concurrency limit
+    #[serde(default)]
+    pub admission_rejections: u64,
 }
 
 impl UsageMetrics {
@@ -53,6 +71,55 @@ impl UsageMetrics {
     }
 }
 
+/// Format of a usage export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Pretty-printed JSON array of rows
+    Json,
+    /// CSV with a header row
+    Csv,
+}
+
+/// One tenant's usage in a billing period, as exported for billing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageExportRow {
+    /// Tenant ID
+    pub tenant_id: Uuid,
+    /// Billing period (YYYY-MM)
+    pub period: String,
+    /// Storage used at the end of the period (bytes)
+    pub storage_bytes: u64,
+    /// File storage used at the end of the period (bytes)
+    pub file_storage_bytes: u64,
+    /// API requests
+    pub api_requests: u64,
+    /// Read operations that succeeded
+    pub read_ops: u64,
+    /// Write operations that succeeded
+    pub write_ops: u64,
+    /// Egress bandwidth (bytes)
+    pub egress_bytes: u64,
+}
+
+impl From<&UsageMetrics> for UsageExportRow {
+    fn from(usage: &UsageMetrics) -> Self {
+        Self {
+            tenant_id: usage.tenant_id,
+            period: usage.month.clone(),
+            storage_bytes: usage.storage_bytes,
+            file_storage_bytes: usage.file_storage_bytes,
+            api_requests: usage.api_requests,
+            read_ops: usage.read_ops,
+            write_ops: usage.write_ops,
+            egress_bytes: usage.egress_bytes,
+        }
+    }
+}
+
+const EXPORT_CSV_HEADER: &str =
+    "tenant_id,period,storage_bytes,file_storage_bytes,api_requests,read_ops,write_ops,egress_bytes\n";
+
 /// Get current month in YYYY-MM format
 pub fn current_month() -> String {
     let now = Utc::now();
@@ -67,6 +134,8 @@ pub struct UsageTracker {
     metrics: Arc<RwLock<HashMap<(Uuid, String), UsageMetrics>>>,
     /// Current realtime connections by tenant
     realtime_connections: Arc<RwLock<HashMap<Uuid, u64>>>,
+    /// Daily snapshots by tenant and date
+    daily: Arc<RwLock<BTreeMap<(Uuid, String), DailySnapshot>>>,
 }
 
 impl UsageTracker {
@@ -77,8 +146,7 @@ impl UsageTracker {
 
     /// Get or create metrics for a tenant in the current month
     fn get_or_create(&self, tenant_id: Uuid) -> UsageMetrics {
-        let month = current_month();
-        let key = (tenant_id, month.clone());
+        let key = (tenant_id, current_month());
 
         let read = self.metrics.read().unwrap();
         if let Some(metrics) = read.get(&key) {
@@ -87,10 +155,7 @@ impl UsageTracker {
         drop(read);
 
         let mut write = self.metrics.write().unwrap();
-        write
-            .entry(key)
-            .or_insert_with(|| UsageMetrics::for_month(tenant_id, month))
-            .clone()
+        month_entry(&mut write, tenant_id).clone()
     }
 
     /// Record an API request
@@ -203,6 +268,129 @@ impl UsageTracker {
             metrics.function_execution_ms.saturating_add(execution_ms);
     }
 
+    /// Record a successful write with its storage and document deltas
+    ///
+    /// `soft_quotas` names the dimensions the write was admitted over a
+    /// soft quota on; all counters change under one lock.
+    pub fn record_write(
+        &self,
+        tenant_id: Uuid,
+        storage_delta: i64,
+        documents_delta: i64,
+        soft_quotas: &[&str],
+    ) {
+        let mut write = self.metrics.write().unwrap();
+        let metrics = month_entry(&mut write, tenant_id);
+        metrics.write_ops += 1;
+        metrics.storage_bytes = apply_delta(metrics.storage_bytes, storage_delta);
+        metrics.documents = apply_delta(metrics.documents, documents_delta);
+        for dimension in soft_quotas {
+            *metrics.soft_quota_events.entry(dimension.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// Record a successful read
+    pub fn record_read(&self, tenant_id: Uuid, soft_quotas: &[&str]) {
+        let mut write = self.metrics.write().unwrap();
+        let metrics = month_entry(&mut write, tenant_id);
+        metrics.read_ops += 1;
+        for dimension in soft_quotas {
+            *metrics.soft_quota_events.entry(dimension.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// Record an operation rejected at the tenant's concurrency limit
+    pub fn record_admission_rejection(&self, tenant_id: Uuid) {
+        let mut write = self.metrics.write().unwrap();
+        month_entry(&mut write, tenant_id).admission_rejections += 1;
+    }
+
+    /// Current month's usage without creating an entry for it
+    pub fn peek_current_usage(&self, tenant_id: Uuid) -> UsageMetrics {
+        let read = self.metrics.read().unwrap();
+        match read.get(&(tenant_id, current_month())) {
+            Some(metrics) => metrics.clone(),
+            None => new_month(&read, tenant_id),
+        }
+    }
+
+    /// Usage of every tenant and month, ordered by tenant then month
+    pub fn all_usage(&self) -> Vec<UsageMetrics> {
+        let read = self.metrics.read().unwrap();
+        let mut usage: Vec<UsageMetrics> = read.values().cloned().collect();
+        usage.sort_by(|a, b| (a.tenant_id, &a.month).cmp(&(b.tenant_id, &b.month)));
+        usage
+    }
+
+    /// Every tenant's usage in a billing period (YYYY-MM), ordered by
+    /// tenant ID
+    pub fn export_rows(&self, period: &str) -> Vec<UsageExportRow> {
+        self.all_usage()
+            .iter()
+            .filter(|usage| usage.month == period)
+            .map(UsageExportRow::from)
+            .collect()
+    }
+
+    /// Export a billing period's usage (YYYY-MM) for billing
+    ///
+    /// A period nobody used exports as an empty JSON array, or a CSV with
+    /// only its header.
+    pub fn export(&self, period: &str, format: ExportFormat) -> String {
+        let rows = self.export_rows(period);
+        match format {
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(&rows).expect("usage serializes to JSON")
+            }
+            ExportFormat::Csv => {
+                let mut csv = String::from(EXPORT_CSV_HEADER);
+                for row in rows {
+                    csv.push_str(&format!(
+                        "{},{},{},{},{},{},{},{}\n",
+                        row.tenant_id,
+                        row.period,
+                        row.storage_bytes,
+                        row.file_storage_bytes,
+                        row.api_requests,
+                        row.read_ops,
+                        row.write_ops,
+                        row.egress_bytes
+                    ));
+                }
+                csv
+            }
+        }
+    }
+
+    /// Tracker holding previously recorded usage
+    pub fn from_usage(usage: Vec<UsageMetrics>) -> Self {
+        let metrics = usage
+            .into_iter()
+            .map(|m| ((m.tenant_id, m.month.clone()), m))
+            .collect();
+        Self {
+            metrics: Arc::new(RwLock::new(metrics)),
+            realtime_connections: Arc::default(),
+            daily: Arc::default(),
+        }
+    }
+
+    /// Record (or replace) a tenant's snapshot for one day
+    pub fn record_daily_snapshot(&self, snapshot: DailySnapshot) {
+        let key = (snapshot.tenant_id, snapshot.date.clone());
+        self.daily.write().unwrap().insert(key, snapshot);
+    }
+
+    /// Daily snapshots of a tenant within a month (YYYY-MM), ordered by date
+    pub fn daily_usage(&self, tenant_id: Uuid, month: &str) -> Vec<DailySnapshot> {
+        let daily = self.daily.read().unwrap();
+        daily
+            .range((tenant_id, month.to_string())..)
+            .take_while(|((id, date), _)| *id == tenant_id && date.starts_with(month))
+            .map(|(_, snapshot)| snapshot.clone())
+            .collect()
+    }
+
     /// Get usage for current month
     pub fn get_current_usage(&self, tenant_id: Uuid) -> UsageMetrics {
         self.get_or_create(tenant_id)
@@ -244,6 +432,45 @@ impl UsageTracker {
     }
 }
 
+/// Current month's metrics of a tenant, created on first use
+///
+/// Storage and document counts are levels rather than monthly totals, so a
+/// new month starts from the tenant's latest earlier month.
+fn month_entry(
+    metrics: &mut HashMap<(Uuid, String), UsageMetrics>,
+    tenant_id: Uuid,
+) -> &mut UsageMetrics {
+    let key = (tenant_id, current_month());
+    if !metrics.contains_key(&key) {
+        let fresh = new_month(metrics, tenant_id);
+        metrics.insert(key.clone(), fresh);
+    }
+    metrics.get_mut(&key).expect("inserted above")
+}
+
+/// Metrics starting the current month, carrying levels forward
+fn new_month(metrics: &HashMap<(Uuid, String), UsageMetrics>, tenant_id: Uuid) -> UsageMetrics {
+    let mut fresh = UsageMetrics::new(tenant_id);
+    if let Some(previous) = metrics
+        .values()
+        .filter(|m| m.tenant_id == tenant_id)
+        .max_by(|a, b| a.month.cmp(&b.month))
+    {
+        fresh.storage_bytes = previous.storage_bytes;
+        fresh.file_storage_bytes = previous.file_storage_bytes;
+        fresh.documents = previous.documents;
+    }
+    fresh
+}
+
+fn apply_delta(value: u64, delta: i64) -> u64 {
+    if delta >= 0 {
+        value.saturating_add(delta as u64)
+    } else {
+        value.saturating_sub(delta.unsigned_abs())
+    }
+}
+
 /// Daily usage snapshot for historical tracking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DailySnapshot {
@@ -261,6 +488,12 @@ pub struct DailySnapshot {
     pub egress_bytes: u64,
     /// Peak realtime connections
     pub realtime_connections_peak: u64,
+    /// Read operations on this day
+    #[serde(default)]
+    pub read_ops: u64,
+    /// Write operations on this day
+    #[serde(default)]
+    pub write_ops: u64,
 }
 
 #[cfg(test)]
@@ -305,6 +538,82 @@ mod tests {
         assert_eq!(usage.realtime_connections_peak, 2);
     }
 
+    #[test]
+    fn test_write_and_read_metering() {
+        let tracker = UsageTracker::new();
+        let tenant_id = Uuid::new_v4();
+
+        tracker.record_write(tenant_id, 100, 1, &[]);
+        tracker.record_write(tenant_id, 40, -1, &["documents"]);
+        tracker.record_read(tenant_id, &["read_ops"]);
+        tracker.record_admission_rejection(tenant_id);
+
+        let usage = tracker.get_current_usage(tenant_id);
+        assert_eq!(usage.write_ops, 2);
+        assert_eq!(usage.read_ops, 1);
+        assert_eq!(usage.documents, 0);
+        assert_eq!(usage.storage_bytes, 140);
+        assert_eq!(usage.soft_quota_events.get("documents"), Some(&1));
+        assert_eq!(usage.admission_rejections, 1);
+
+        let restored = UsageTracker::from_usage(tracker.all_usage());
+        assert_eq!(restored.get_current_usage(tenant_id).write_ops, 2);
+    }
+
+    /// Usage of two tenants over two months, recorded out of tenant order
+    fn export_fixture() -> UsageTracker {
+        let first = Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
+        let second = Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap();
+        let usage = |tenant_id, month: &str, storage_bytes, read_ops, write_ops| UsageMetrics {
+            storage_bytes,
+            read_ops,
+            write_ops,
+            api_requests: read_ops + write_ops,
+            egress_bytes: read_ops * 100,
+            ..UsageMetrics::for_month(tenant_id, month.to_string())
+        };
+        UsageTracker::from_usage(vec![
+            usage(second, "2026-03", 2048, 10, 5),
+            usage(first, "2026-03", 1024, 7, 3),
+            usage(first, "2026-02", 512, 1, 1),
+        ])
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let csv = export_fixture().export("2026-03", ExportFormat::Csv);
+        assert_eq!(
+            csv,
+            "tenant_id,period,storage_bytes,file_storage_bytes,api_requests,read_ops,write_ops,egress_bytes\n\
+             00000000-0000-0000-0000-000000000001,2026-03,1024,0,10,7,3,700\n\
+             00000000-0000-0000-0000-000000000002,2026-03,2048,0,15,10,5,1000\n"
+        );
+
+        let empty = export_fixture().export("2026-04", ExportFormat::Csv);
+        assert_eq!(empty, EXPORT_CSV_HEADER);
+    }
+
+    #[test]
+    fn test_export_json() {
+        let json = export_fixture().export("2026-02", ExportFormat::Json);
+        let expected = serde_json::json!([{
+            "tenant_id": "00000000-0000-0000-0000-000000000001",
+            "period": "2026-02",
+            "storage_bytes": 512,
+            "file_storage_bytes": 0,
+            "api_requests": 2,
+            "read_ops": 1,
+            "write_ops": 1,
+            "egress_bytes": 100
+        }]);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&json).unwrap(), expected);
+
+        let rows: Vec<UsageExportRow> =
+            serde_json::from_str(&export_fixture().export("2026-03", ExportFormat::Json)).unwrap();
+        assert_eq!(rows, export_fixture().export_rows("2026-03"));
+        assert_eq!(rows[0].tenant_id.to_string(), "00000000-0000-0000-0000-000000000001");
+    }
+
     #[test]
     fn test_current_month() {
         let month = current_month();
@@ -11,6 +11,7 @@
 //! - `database_provisioner`: Database-per-tenant (separate processes)
 //! - `quota`: Quota definitions and enforcement
 //! - `metering`: Usage tracking
+//! - `tenant_quotas`: Quota enforcement and metering on the API paths
 //! - `billing`: Invoice generation
 //! - `errors`: Control plane errors
 //!
@@ -29,6 +30,7 @@ pub mod quota;
 pub mod registry;
 pub mod schema_provisioner;
 pub mod tenant;
+pub mod tenant_quotas;
 
 pub use billing::*;
 pub use errors::*;
@@ -37,3 +39,4 @@ pub use provisioning::*;
 pub use quota::*;
 pub use registry::*;
 pub use tenant::*;
+pub use tenant_quotas::*;
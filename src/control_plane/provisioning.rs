@@ -8,7 +8,7 @@ use uuid::Uuid;
 use super::database_provisioner::DatabaseProvisioner;
 use super::errors::{ControlPlaneError, ControlPlaneResult};
 use super::registry::TenantRegistry;
-use super::schema_provisioner::SchemaProvisioner;
+use super::schema_provisioner::{NamespaceReport, SchemaProvisioner};
 use super::tenant::{CreateTenantRequest, CreateTenantResponse, IsolationModel, Tenant, TenantConfig};
 
 /// Provisioning orchestrator
@@ -69,10 +69,27 @@ impl ProvisioningService {
         // Provision based on isolation model
         let config = match request.isolation {
             IsolationModel::Schema => {
+                // All or nothing: a failed namespace leaves no tenant behind
+                let report = match self.schema_provisioner.provision_namespace(&tenant) {
+                    Ok(report) => report,
+                    Err(e) => {
+                        self.registry.remove(tenant.tenant_id)?;
+                        return Err(e);
+                    }
+                };
+                self.registry
+                    .set_namespace(tenant.tenant_id, Some(report.namespace.clone()))?;
+                tenant.set_namespace(Some(report.namespace));
                 self.schema_provisioner.provision(&tenant).await?
             }
             IsolationModel::Database => {
-                self.database_provisioner.provision(&tenant).await?
+                match self.database_provisioner.provision(&tenant).await {
+                    Ok(config) => config,
+                    Err(e) => {
+                        self.registry.remove(tenant.tenant_id)?;
+                        return Err(e);
+                    }
+                }
             }
             IsolationModel::Cluster => {
                 // Cluster provisioning not yet implemented
@@ -107,6 +124,9 @@ impl ProvisioningService {
         match tenant.isolation {
             IsolationModel::Schema => {
                 self.schema_provisioner.deprovision(&tenant).await?;
+                if tenant.namespace.is_some() {
+                    self.registry.set_namespace(tenant_id, None)?;
+                }
             }
             IsolationModel::Database => {
                 self.database_provisioner.deprovision(&tenant).await?;
@@ -122,6 +142,46 @@ impl ProvisioningService {
         Ok(())
     }
 
+    /// Supervise the processes of database-per-tenant tenants
+    ///
+    /// Call `recover` first after a control plane restart so processes
+    /// started by the previous control plane are supervised too.
+    pub fn supervise(&self) -> tokio::task::JoinHandle<()> {
+        self.database_provisioner.supervise(self.registry.clone())
+    }
+
+    /// Adopt the database-per-tenant processes recorded in the registry
+    pub async fn recover(&self) -> usize {
+        self.database_provisioner.recover(&self.registry).await
+    }
+
+    /// Provision a schema-per-tenant tenant's namespace
+    ///
+    /// Re-running for a tenant that is already provisioned changes nothing
+    /// and reports `already_provisioned`.
+    pub fn provision_namespace(&self, tenant_id: Uuid) -> ControlPlaneResult<NamespaceReport> {
+        let tenant = self.registry.get(tenant_id)?;
+
+        if tenant.is_deleted() {
+            return Err(ControlPlaneError::TenantDeleted {
+                tenant_id: tenant_id.to_string(),
+            });
+        }
+        if tenant.isolation != IsolationModel::Schema {
+            return Err(ControlPlaneError::InvalidIsolationModel {
+                model: tenant.isolation.to_string(),
+                reason: "Namespaces are only provisioned for schema isolation".to_string(),
+            });
+        }
+
+        let report = self.schema_provisioner.provision_namespace(&tenant)?;
+        if !report.already_provisioned {
+            self.registry
+                .set_namespace(tenant_id, Some(report.namespace.clone()))?;
+        }
+        Ok(report)
+    }
+
     /// Get tenant details
     pub fn get_tenant(&self, tenant_id: Uuid) -> ControlPlaneResult<Tenant> {
         self.registry.get(tenant_id)
@@ -161,14 +221,17 @@ impl ProvisioningService {
         Ok(tenant)
     }
 
-    /// Suspend a tenant (e.g., for quota exceeded)
-    pub fn suspend_tenant(&self, tenant_id: Uuid) -> ControlPlaneResult<()> {
-        self.registry.suspend(tenant_id)
+    /// Suspend a tenant (e.g., for non-payment) without touching its data
+    ///
+    /// API handlers sharing the registry refuse the tenant's writes with
+    /// `TENANT_SUSPENDED`, and its reads too if `fence_reads`.
+    pub fn suspend(&self, tenant_id: Uuid, fence_reads: bool) -> ControlPlaneResult<()> {
+        self.registry.suspend(tenant_id, fence_reads)
     }
 
-    /// Reactivate a suspended tenant
-    pub fn activate_tenant(&self, tenant_id: Uuid) -> ControlPlaneResult<()> {
-        self.registry.activate(tenant_id)
+    /// Lift a tenant's suspension, restoring its access
+    pub fn resume(&self, tenant_id: Uuid) -> ControlPlaneResult<()> {
+        self.registry.resume(tenant_id)
     }
 }
 
@@ -190,7 +253,11 @@ pub trait Provisioner: Send + Sync {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::auth::rls::RlsContext;
     use crate::control_plane::tenant::Plan;
+    use crate::rest_api::generator::{EndpointRegistry, FieldDef, FieldType, SchemaDef};
+    use crate::rest_api::{generate_routes, DatabaseFacade, QueryParams, RestError, RestHandler};
+    use serde_json::json;
 
     #[tokio::test]
     async fn test_create_schema_tenant() {
@@ -213,6 +280,52 @@ mod tests {
         assert!(tenant.is_active());
     }
 
+    #[tokio::test]
+    async fn test_suspend_fences_writes_until_resumed() {
+        let registry = Arc::new(TenantRegistry::new());
+        let service = ProvisioningService::new(registry.clone());
+
+        let request = CreateTenantRequest {
+            name: "late-payer".to_string(),
+            plan: Plan::Free,
+            region: "local".to_string(),
+            isolation: IsolationModel::Schema,
+        };
+        let tenant_id = service.create_tenant(request).await.unwrap().tenant_id;
+
+        // Writes fenced, reads still served
+        service.suspend(tenant_id, false).unwrap();
+        assert!(matches!(
+            registry.check_access(tenant_id, true),
+            Err(ControlPlaneError::TenantSuspended { .. })
+        ));
+        assert!(registry.check_access(tenant_id, false).is_ok());
+
+        // Suspending again with the read fence refuses reads too
+        service.suspend(tenant_id, true).unwrap();
+        assert!(registry.check_access(tenant_id, false).is_err());
+
+        service.resume(tenant_id).unwrap();
+        assert!(service.get_tenant(tenant_id).unwrap().is_active());
+        assert!(registry.check_access(tenant_id, true).is_ok());
+        assert!(registry.check_access(tenant_id, false).is_ok());
+
+        // Only a suspended tenant resumes, and a deleted one cannot be suspended
+        assert!(matches!(
+            service.resume(tenant_id),
+            Err(ControlPlaneError::TenantNotSuspended { .. })
+        ));
+        service.delete_tenant(tenant_id).await.unwrap();
+        assert!(matches!(
+            service.suspend(tenant_id, false),
+            Err(ControlPlaneError::TenantDeleted { .. })
+        ));
+        assert!(matches!(
+            registry.check_access(tenant_id, false),
+            Err(ControlPlaneError::TenantDeleted { .. })
+        ));
+    }
+
     #[tokio::test]
     async fn test_delete_tenant() {
         let registry = Arc::new(TenantRegistry::new());
@@ -233,6 +346,143 @@ mod tests {
         assert!(tenant.is_deleted());
     }
 
+    fn namespaced_service() -> (ProvisioningService, Arc<DatabaseFacade>, Arc<EndpointRegistry>) {
+        let database = Arc::new(DatabaseFacade::new(Default::default()));
+        let endpoints = Arc::new(EndpointRegistry::new());
+        let template = SchemaDef {
+            name: "notes".to_string(),
+            fields: vec![FieldDef {
+                name: "body".to_string(),
+                field_type: FieldType::String,
+                required: true,
+                primary: false,
+                default: None,
+            }],
+            rls_policy: None,
+            anon_read: false,
+            relations: Vec::new(),
+        };
+        let schema_provisioner = SchemaProvisioner::new()
+            .with_collection(template)
+            .with_backend(Arc::clone(&database), Arc::clone(&endpoints));
+        let service = ProvisioningService::with_provisioners(
+            Arc::new(TenantRegistry::new()),
+            Arc::new(schema_provisioner),
+            Arc::new(DatabaseProvisioner::new()),
+        );
+        (service, database, endpoints)
+    }
+
+    async fn create(service: &ProvisioningService, name: &str) -> Uuid {
+        let request = CreateTenantRequest {
+            name: name.to_string(),
+            plan: Plan::Free,
+            region: "local".to_string(),
+            isolation: IsolationModel::Schema,
+        };
+        service.create_tenant(request).await.unwrap().tenant_id
+    }
+
+    fn tenant_ctx(tenant_id: Uuid) -> RlsContext {
+        RlsContext::authenticated(Uuid::new_v4())
+            .with_claim("tenant_id", json!(tenant_id.to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_schema_tenant_namespace_is_isolated_and_dropped() {
+        let (service, database, endpoints) = namespaced_service();
+        let acme = create(&service, "acme").await;
+        let globex = create(&service, "globex").await;
+
+        let acme_notes = SchemaProvisioner::namespaced_collection(acme, "notes");
+        let globex_notes = SchemaProvisioner::namespaced_collection(globex, "notes");
+        let namespace = service.get_tenant(acme).unwrap().namespace.unwrap();
+        assert_eq!(namespace.collections, vec![acme_notes.clone()]);
+        let paths: Vec<String> = generate_routes(&endpoints)
+            .into_iter()
+            .map(|r| r.path)
+            .collect();
+        assert!(paths.contains(&format!("/rest/v1/{}", acme_notes)));
+        assert!(paths.contains(&format!("/rest/v1/{}", globex_notes)));
+
+        // Re-running is a no-op
+        let report = service.provision_namespace(acme).unwrap();
+        assert!(report.already_provisioned);
+        assert!(report.created.is_empty());
+        assert_eq!(report.namespace, namespace);
+
+        // Inserts are stamped with the caller's tenant
+        let inserted = database
+            .insert(&acme_notes, json!({"body": "secret"}), &tenant_ctx(acme))
+            .unwrap();
+        let id = inserted.data[0]["_id"].as_str().unwrap().to_string();
+        assert_eq!(inserted.data[0]["tenant_id"], acme.to_string());
+
+        // Another tenant's token sees nothing and cannot write in
+        let intruder = tenant_ctx(globex);
+        let listed = database
+            .list(&acme_notes, QueryParams::default(), &intruder)
+            .unwrap();
+        assert_eq!(listed.count, 0);
+        assert!(matches!(
+            database.get(&acme_notes, &id, &intruder),
+            Err(RestError::NotFound)
+        ));
+        assert!(database
+            .insert(
+                &acme_notes,
+                json!({"body": "x", "tenant_id": acme.to_string()}),
+                &intruder
+            )
+            .is_err());
+        // A token without a tenant claim is refused
+        let unscoped = RlsContext::authenticated(Uuid::new_v4());
+        assert!(database
+            .list(&acme_notes, QueryParams::default(), &unscoped)
+            .is_err());
+
+        let own = database
+            .list(&acme_notes, QueryParams::default(), &tenant_ctx(acme))
+            .unwrap();
+        assert_eq!(own.count, 1);
+
+        service.delete_tenant(acme).await.unwrap();
+        assert!(!database.has_collection(&acme_notes));
+        assert!(database.rls().policy(&acme_notes).is_none());
+        assert!(endpoints.get(&acme_notes).is_none());
+        assert!(service.get_tenant(acme).unwrap().namespace.is_none());
+
+        // The other tenant is untouched
+        assert!(database.has_collection(&globex_notes));
+        assert!(endpoints.get(&globex_notes).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_failed_namespace_leaves_no_tenant() {
+        let schema_provisioner = SchemaProvisioner::new().with_collection(SchemaDef {
+            name: "notes".to_string(),
+            fields: Vec::new(),
+            rls_policy: None,
+            anon_read: false,
+            relations: Vec::new(),
+        });
+        let service = ProvisioningService::with_provisioners(
+            Arc::new(TenantRegistry::new()),
+            Arc::new(schema_provisioner),
+            Arc::new(DatabaseProvisioner::new()),
+        );
+
+        let request = CreateTenantRequest {
+            name: "acme".to_string(),
+            plan: Plan::Free,
+            region: "local".to_string(),
+            isolation: IsolationModel::Schema,
+        };
+        assert!(service.create_tenant(request).await.is_err());
+        assert_eq!(service.registry().count(), 0);
+        assert!(service.registry().is_name_available("acme"));
+    }
+
     #[tokio::test]
     async fn test_cluster_not_implemented() {
         let registry = Arc::new(TenantRegistry::new());
@@ -1,6 +1,12 @@
 //! # Quota Management
 //!
 //! Resource quota definitions and enforcement for multi-tenancy.
+//!
+//! Each dimension has a hard limit, which refuses the operation with
+//! `QUOTA_EXCEEDED`, and a soft limit at `soft_limit_percent` of it, which
+//! only records a metering event and logs a warning.
+
+use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
@@ -22,6 +28,45 @@ pub struct Quotas {
     pub max_collections: u32,
     /// Maximum document size in bytes
     pub max_document_size: u64,
+    /// Maximum number of live documents
+    #[serde(default = "default_max_documents")]
+    pub max_documents: u64,
+    /// Maximum write operations per month
+    #[serde(default = "default_write_ops_month")]
+    pub write_ops_month: u64,
+    /// Maximum read operations per month
+    #[serde(default = "default_read_ops_month")]
+    pub read_ops_month: u64,
+    /// Largest result set a query may ask for
+    #[serde(default)]
+    pub max_result_size: ResultSizeClass,
+    /// Percentage of a limit at which usage is metered as a soft overage
+    #[serde(default = "default_soft_limit_percent")]
+    pub soft_limit_percent: u8,
+    /// Maximum operations executing at once; further ones queue, then are
+    /// rejected by admission control
+    #[serde(default = "default_max_concurrent_operations")]
+    pub max_concurrent_operations: u32,
+}
+
+fn default_max_documents() -> u64 {
+    Quotas::free().max_documents
+}
+
+fn default_write_ops_month() -> u64 {
+    Quotas::free().write_ops_month
+}
+
+fn default_read_ops_month() -> u64 {
+    Quotas::free().read_ops_month
+}
+
+fn default_soft_limit_percent() -> u8 {
+    80
+}
+
+fn default_max_concurrent_operations() -> u32 {
+    Quotas::free().max_concurrent_operations
 }
 
 impl Quotas {
@@ -43,6 +88,12 @@ impl Quotas {
             file_storage_bytes: 1 * 1024 * 1024 * 1024, // 1 GB
             max_collections: 20,
             max_document_size: 1 * 1024 * 1024,         // 1 MB
+            max_documents: 100_000,
+            write_ops_month: 100_000,
+            read_ops_month: 1_000_000,
+            max_result_size: ResultSizeClass::Medium,
+            soft_limit_percent: 80,
+            max_concurrent_operations: 10,
         }
     }
 
@@ -55,6 +106,12 @@ impl Quotas {
             file_storage_bytes: 100 * 1024 * 1024 * 1024, // 100 GB
             max_collections: 500,
             max_document_size: 16 * 1024 * 1024,          // 16 MB
+            max_documents: 10_000_000,
+            write_ops_month: 10_000_000,
+            read_ops_month: 100_000_000,
+            max_result_size: ResultSizeClass::Large,
+            soft_limit_percent: 80,
+            max_concurrent_operations: 100,
         }
     }
 
@@ -67,6 +124,12 @@ impl Quotas {
             file_storage_bytes: u64::MAX,
             max_collections: u32::MAX,
             max_document_size: u64::MAX,
+            max_documents: u64::MAX,
+            write_ops_month: u64::MAX,
+            read_ops_month: u64::MAX,
+            max_result_size: ResultSizeClass::Unbounded,
+            soft_limit_percent: 100,
+            max_concurrent_operations: u32::MAX,
         }
     }
 
@@ -74,6 +137,99 @@ impl Quotas {
     pub fn unlimited() -> Self {
         Self::enterprise()
     }
+
+    /// Hard limit of a counted dimension
+    pub fn limit(&self, dimension: QuotaDimension) -> u64 {
+        match dimension {
+            QuotaDimension::Storage => self.storage_bytes,
+            QuotaDimension::Documents => self.max_documents,
+            QuotaDimension::WriteOps => self.write_ops_month,
+            QuotaDimension::ReadOps => self.read_ops_month,
+            QuotaDimension::ResultSize => self.max_result_size.max_rows(),
+        }
+    }
+
+    /// Usage at which a dimension is metered as a soft overage
+    pub fn soft_limit(&self, dimension: QuotaDimension) -> u64 {
+        let limit = self.limit(dimension);
+        let percent = u64::from(self.soft_limit_percent.min(100));
+        if limit == u64::MAX || percent == 100 {
+            return limit;
+        }
+        ((u128::from(limit) * u128::from(percent)) / 100) as u64
+    }
+}
+
+/// Quota dimension checked on the write and query paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaDimension {
+    /// Stored bytes
+    Storage,
+    /// Live documents
+    Documents,
+    /// Write operations this month
+    WriteOps,
+    /// Read operations this month
+    ReadOps,
+    /// Result-set size class of a query
+    ResultSize,
+}
+
+impl QuotaDimension {
+    /// Name used in errors and metering events
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuotaDimension::Storage => "storage_bytes",
+            QuotaDimension::Documents => "documents",
+            QuotaDimension::WriteOps => "write_ops",
+            QuotaDimension::ReadOps => "read_ops",
+            QuotaDimension::ResultSize => "result_size",
+        }
+    }
+}
+
+impl fmt::Display for QuotaDimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Size class of a query's result set, by its requested `limit`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultSizeClass {
+    /// Up to 100 rows
+    Small,
+    /// Up to 1,000 rows
+    #[default]
+    Medium,
+    /// Up to 10,000 rows
+    Large,
+    /// No limit
+    Unbounded,
+}
+
+impl ResultSizeClass {
+    /// Smallest class that holds `rows` rows
+    pub fn for_rows(rows: u64) -> Self {
+        match rows {
+            0..=100 => ResultSizeClass::Small,
+            101..=1_000 => ResultSizeClass::Medium,
+            1_001..=10_000 => ResultSizeClass::Large,
+            _ => ResultSizeClass::Unbounded,
+        }
+    }
+
+    /// Most rows a result of this class may hold
+    pub fn max_rows(&self) -> u64 {
+        match self {
+            ResultSizeClass::Small => 100,
+            ResultSizeClass::Medium => 1_000,
+            ResultSizeClass::Large => 10_000,
+            ResultSizeClass::Unbounded => u64::MAX,
+        }
+    }
 }
 
 impl Default for Quotas {
@@ -157,6 +313,7 @@ impl QuotaEnforcer {
                 tenant_id: self.tenant_id.clone(),
                 resource: "storage".to_string(),
                 used: check.used,
+                requested: additional_bytes,
                 limit: check.limit,
             })
         }
@@ -181,6 +338,7 @@ impl QuotaEnforcer {
                 tenant_id: self.tenant_id.clone(),
                 resource: "api_requests".to_string(),
                 used: check.used,
+                requested: 1,
                 limit: check.limit,
             })
         }
@@ -205,6 +363,7 @@ impl QuotaEnforcer {
                 tenant_id: self.tenant_id.clone(),
                 resource: "realtime_connections".to_string(),
                 used: check.used,
+                requested: 1,
                 limit: check.limit,
             })
         }
@@ -234,6 +393,7 @@ impl QuotaEnforcer {
                 tenant_id: self.tenant_id.clone(),
                 resource: "file_storage".to_string(),
                 used: check.used,
+                requested: additional_bytes,
                 limit: check.limit,
             })
         }
@@ -248,6 +408,59 @@ impl QuotaEnforcer {
         }
     }
 
+    /// Check a counted dimension: `used` plus `additional` must stay
+    /// within the hard limit
+    pub fn check_dimension(
+        &self,
+        dimension: QuotaDimension,
+        used: u64,
+        additional: u64,
+    ) -> QuotaCheck {
+        let limit = self.quotas.limit(dimension);
+        if used.saturating_add(additional) <= limit {
+            QuotaCheck::allowed(used, limit)
+        } else {
+            QuotaCheck::denied(used, limit)
+        }
+    }
+
+    /// Enforce a counted dimension
+    ///
+    /// Returns whether the operation crosses the soft limit, which the
+    /// caller meters instead of refusing.
+    pub fn enforce_dimension(
+        &self,
+        dimension: QuotaDimension,
+        used: u64,
+        additional: u64,
+    ) -> ControlPlaneResult<bool> {
+        let check = self.check_dimension(dimension, used, additional);
+        if !check.allowed {
+            return Err(ControlPlaneError::QuotaExceeded {
+                tenant_id: self.tenant_id.clone(),
+                resource: dimension.name().to_string(),
+                used: check.used,
+                requested: additional,
+                limit: check.limit,
+            });
+        }
+        Ok(used.saturating_add(additional) > self.quotas.soft_limit(dimension))
+    }
+
+    /// Enforce the result-set size class of a query
+    pub fn enforce_result_size(&self, class: ResultSizeClass) -> ControlPlaneResult<()> {
+        if class <= self.quotas.max_result_size {
+            return Ok(());
+        }
+        Err(ControlPlaneError::QuotaExceeded {
+            tenant_id: self.tenant_id.clone(),
+            resource: QuotaDimension::ResultSize.name().to_string(),
+            used: 0,
+            requested: class.max_rows(),
+            limit: self.quotas.max_result_size.max_rows(),
+        })
+    }
+
     /// Get the quotas
     pub fn quotas(&self) -> &Quotas {
         &self.quotas
@@ -292,4 +505,32 @@ mod tests {
         let check = enforcer.check_api_requests(10_000);
         assert!(!check.allowed);
     }
+
+    #[test]
+    fn test_soft_and_hard_dimension_limits() {
+        let quotas = Quotas {
+            max_documents: 10,
+            ..Quotas::free()
+        };
+        let enforcer = QuotaEnforcer::new("test", quotas);
+
+        assert!(!enforcer.enforce_dimension(QuotaDimension::Documents, 7, 1).unwrap());
+        assert!(enforcer.enforce_dimension(QuotaDimension::Documents, 8, 1).unwrap());
+        assert!(enforcer.enforce_dimension(QuotaDimension::Documents, 9, 1).unwrap());
+
+        let err = enforcer.enforce_dimension(QuotaDimension::Documents, 10, 1).unwrap_err();
+        assert_eq!(err.error_code(), "QUOTA_EXCEEDED");
+        assert!(err.to_string().contains("documents"));
+    }
+
+    #[test]
+    fn test_result_size_class() {
+        assert_eq!(ResultSizeClass::for_rows(100), ResultSizeClass::Small);
+        assert_eq!(ResultSizeClass::for_rows(101), ResultSizeClass::Medium);
+        assert_eq!(ResultSizeClass::for_rows(50_000), ResultSizeClass::Unbounded);
+
+        let enforcer = QuotaEnforcer::new("test", Quotas::free());
+        assert!(enforcer.enforce_result_size(ResultSizeClass::Medium).is_ok());
+        assert!(enforcer.enforce_result_size(ResultSizeClass::Large).is_err());
+    }
 }
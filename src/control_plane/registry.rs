@@ -6,8 +6,11 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
+use chrono::{DateTime, Utc};
+
+use super::billing::Invoice;
 use super::errors::{ControlPlaneError, ControlPlaneResult};
-use super::tenant::{Tenant, TenantListItem, TenantStatus, UpdateTenantRequest};
+use super::tenant::{Plan, Tenant, TenantListItem, TenantStatus, UpdateTenantRequest};
 use super::metering::UsageTracker;
 
 /// In-memory tenant registry
@@ -22,6 +25,8 @@ pub struct TenantRegistry {
     api_keys: Arc<RwLock<HashMap<String, Uuid>>>,
     /// Usage tracker
     usage_tracker: Arc<UsageTracker>,
+    /// Invoices by tenant ID and billing period
+    invoices: Arc<RwLock<HashMap<(Uuid, String), Invoice>>>,
 }
 
 impl TenantRegistry {
@@ -32,6 +37,7 @@ impl TenantRegistry {
             names: Arc::new(RwLock::new(HashMap::new())),
             api_keys: Arc::new(RwLock::new(HashMap::new())),
             usage_tracker: Arc::new(UsageTracker::new()),
+            invoices: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -42,6 +48,7 @@ impl TenantRegistry {
             names: Arc::new(RwLock::new(HashMap::new())),
             api_keys: Arc::new(RwLock::new(HashMap::new())),
             usage_tracker,
+            invoices: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -181,6 +188,12 @@ impl TenantRegistry {
             .collect()
     }
 
+    /// All tenants that are not deleted
+    pub fn tenants(&self) -> Vec<Tenant> {
+        let tenants = self.tenants.read().unwrap();
+        tenants.values().filter(|t| !t.is_deleted()).cloned().collect()
+    }
+
     /// Update tenant
     pub fn update(&self, tenant_id: Uuid, update: UpdateTenantRequest) -> ControlPlaneResult<Tenant> {
         let mut tenants = self.tenants.write().unwrap();
@@ -191,16 +204,36 @@ impl TenantRegistry {
             })?;
 
         if let Some(plan) = update.plan {
-            tenant.plan = plan;
+            tenant.change_plan(plan, Utc::now());
         }
         if let Some(status) = update.status {
+            // A suspension set this way fences writes only
             tenant.status = status;
+            tenant.reads_suspended = false;
         }
         tenant.updated_at = chrono::Utc::now();
 
         Ok(tenant.clone())
     }
 
+    /// Change a tenant's plan, billed from `effective_at`
+    pub fn change_plan(
+        &self,
+        tenant_id: Uuid,
+        plan: Plan,
+        effective_at: DateTime<Utc>,
+    ) -> ControlPlaneResult<Tenant> {
+        let mut tenants = self.tenants.write().unwrap();
+        let tenant = tenants
+            .get_mut(&tenant_id)
+            .ok_or_else(|| ControlPlaneError::TenantNotFound {
+                tenant_id: tenant_id.to_string(),
+            })?;
+
+        tenant.change_plan(plan, effective_at);
+        Ok(tenant.clone())
+    }
+
     /// Delete tenant (soft delete)
     pub fn delete(&self, tenant_id: Uuid) -> ControlPlaneResult<()> {
         let mut tenants = self.tenants.write().unwrap();
@@ -236,8 +269,86 @@ impl TenantRegistry {
         Ok(())
     }
 
-    /// Suspend tenant
-    pub fn suspend(&self, tenant_id: Uuid) -> ControlPlaneResult<()> {
+    /// Suspend tenant: its writes are refused, and its reads too if
+    /// `fence_reads`; suspending again replaces the read fence
+    pub fn suspend(&self, tenant_id: Uuid, fence_reads: bool) -> ControlPlaneResult<()> {
+        let mut tenants = self.tenants.write().unwrap();
+        let tenant = tenants
+            .get_mut(&tenant_id)
+            .ok_or_else(|| ControlPlaneError::TenantNotFound {
+                tenant_id: tenant_id.to_string(),
+            })?;
+
+        if tenant.is_deleted() {
+            return Err(ControlPlaneError::TenantDeleted {
+                tenant_id: tenant_id.to_string(),
+            });
+        }
+        tenant.suspend(fence_reads);
+        Ok(())
+    }
+
+    /// Resume a suspended tenant
+    pub fn resume(&self, tenant_id: Uuid) -> ControlPlaneResult<()> {
+        let mut tenants = self.tenants.write().unwrap();
+        let tenant = tenants
+            .get_mut(&tenant_id)
+            .ok_or_else(|| ControlPlaneError::TenantNotFound {
+                tenant_id: tenant_id.to_string(),
+            })?;
+
+        if !tenant.is_suspended() {
+            return Err(ControlPlaneError::TenantNotSuspended {
+                tenant_id: tenant_id.to_string(),
+                status: tenant.status.to_string(),
+            });
+        }
+        tenant.resume();
+        Ok(())
+    }
+
+    /// Refuse a write, or a read, that the tenant's status fences
+    ///
+    /// Suspended tenants get `TENANT_SUSPENDED`; tenants being deleted or
+    /// deleted get `TENANT_DELETED`.
+    pub fn check_access(&self, tenant_id: Uuid, write: bool) -> ControlPlaneResult<()> {
+        let tenants = self.tenants.read().unwrap();
+        let tenant = tenants
+            .get(&tenant_id)
+            .ok_or_else(|| ControlPlaneError::TenantNotFound {
+                tenant_id: tenant_id.to_string(),
+            })?;
+
+        if !tenant.is_fenced(write) {
+            return Ok(());
+        }
+        let tenant_id = tenant_id.to_string();
+        Err(if tenant.is_suspended() {
+            ControlPlaneError::TenantSuspended { tenant_id }
+        } else {
+            ControlPlaneError::TenantDeleted { tenant_id }
+        })
+    }
+
+    /// Mark tenant as degraded
+    pub fn mark_degraded(&self, tenant_id: Uuid) -> ControlPlaneResult<()> {
+        let mut tenants = self.tenants.write().unwrap();
+        let tenant = tenants
+            .get_mut(&tenant_id)
+            .ok_or_else(|| ControlPlaneError::TenantNotFound {
+                tenant_id: tenant_id.to_string(),
+            })?;
+
+        tenant.degrade();
+        Ok(())
+    }
+
+    /// Record the process serving a database-per-tenant tenant
+    pub fn set_process_id(
+        &self,
+        tenant_id: Uuid,
+        process_id: Option<u32>,
+    ) -> ControlPlaneResult<()> {
         let mut tenants = self.tenants.write().unwrap();
         let tenant = tenants
             .get_mut(&tenant_id)
@@ -245,7 +356,11 @@ impl TenantRegistry {
                 tenant_id: tenant_id.to_string(),
             })?;
 
-        tenant.suspend();
+        let mut config = tenant.config.clone().ok_or_else(|| ControlPlaneError::ConfigError {
+            message: format!("Tenant {} has no configuration", tenant_id),
+        })?;
+        config.process_id = process_id;
+        tenant.set_config(config);
         Ok(())
     }
 
@@ -262,6 +377,72 @@ impl TenantRegistry {
         Ok(())
     }
 
+    /// Record (or clear) a tenant's provisioned namespace
+    pub fn set_namespace(
+        &self,
+        tenant_id: Uuid,
+        namespace: Option<super::tenant::TenantNamespace>,
+    ) -> ControlPlaneResult<()> {
+        let mut tenants = self.tenants.write().unwrap();
+        let tenant = tenants
+            .get_mut(&tenant_id)
+            .ok_or_else(|| ControlPlaneError::TenantNotFound {
+                tenant_id: tenant_id.to_string(),
+            })?;
+
+        tenant.set_namespace(namespace);
+        Ok(())
+    }
+
+    /// Remove a tenant entirely (rollback of a failed provisioning)
+    pub fn remove(&self, tenant_id: Uuid) -> ControlPlaneResult<Tenant> {
+        let mut tenants = self.tenants.write().unwrap();
+        let tenant = tenants
+            .remove(&tenant_id)
+            .ok_or_else(|| ControlPlaneError::TenantNotFound {
+                tenant_id: tenant_id.to_string(),
+            })?;
+
+        let mut names = self.names.write().unwrap();
+        names.remove(&tenant.name);
+        let mut api_keys = self.api_keys.write().unwrap();
+        api_keys.remove(&tenant.api_key);
+
+        Ok(tenant)
+    }
+
+    /// Store an invoice, replacing any earlier one for the same period
+    pub fn store_invoice(&self, invoice: Invoice) -> ControlPlaneResult<()> {
+        self.get(invoice.tenant_id)?;
+        let mut invoices = self.invoices.write().unwrap();
+        invoices.insert((invoice.tenant_id, invoice.period.clone()), invoice);
+        Ok(())
+    }
+
+    /// Get a tenant's invoice for a billing period (YYYY-MM)
+    pub fn get_invoice(&self, tenant_id: Uuid, period: &str) -> ControlPlaneResult<Invoice> {
+        let invoices = self.invoices.read().unwrap();
+        invoices
+            .get(&(tenant_id, period.to_string()))
+            .cloned()
+            .ok_or_else(|| ControlPlaneError::InvoiceNotFound {
+                tenant_id: tenant_id.to_string(),
+                period: period.to_string(),
+            })
+    }
+
+    /// A tenant's invoices, oldest period first
+    pub fn list_invoices(&self, tenant_id: Uuid) -> Vec<Invoice> {
+        let invoices = self.invoices.read().unwrap();
+        let mut list: Vec<Invoice> = invoices
+            .values()
+            .filter(|i| i.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        list.sort_by(|a, b| a.period.cmp(&b.period));
+        list
+    }
+
     /// Count total tenants (including deleted)
     pub fn count(&self) -> usize {
         let tenants = self.tenants.read().unwrap();
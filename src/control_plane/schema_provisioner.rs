@@ -1,11 +1,61 @@
 //! # Schema-per-Tenant Provisioner
 //!
 //! RLS-based isolation with all tenants in one database.
+//!
+//! Provisioning a tenant creates its namespace: one collection per template,
+//! named `tenant_<id>.<collection>`, each registered as a `/rest/v1/` endpoint
+//! and guarded by an RLS policy binding the `tenant_id` field to the caller's
+//! JWT `tenant_id` claim. Every other collection of the backend is then
+//! isolated the same way (see `isolate_shared_collections`). A failure
+//! partway removes everything created so far.
+
+use std::fmt;
+use std::sync::Arc;
 
+use serde::Serialize;
 use uuid::Uuid;
 
 use super::errors::{ControlPlaneError, ControlPlaneResult};
-use super::tenant::{Tenant, TenantConfig};
+use super::tenant::{Tenant, TenantConfig, TenantNamespace};
+use crate::auth::rls::RlsPolicy;
+use crate::rest_api::generator::{
+    EndpointRegistry, FieldDef, FieldType, SchemaDef, SchemaEndpoint,
+};
+use crate::rest_api::DatabaseFacade;
+
+/// Field every namespaced collection binds to the tenant
+pub const TENANT_FIELD: &str = "tenant_id";
+
+/// JWT claim the tenant field must equal
+pub const TENANT_CLAIM: &str = "tenant_id";
+
+/// Where namespaced collections, endpoints and policies are installed
+#[derive(Clone)]
+pub struct NamespaceBackend {
+    /// Collections and their RLS enforcer
+    pub database: Arc<DatabaseFacade>,
+    /// REST endpoint registry
+    pub endpoints: Arc<EndpointRegistry>,
+}
+
+impl fmt::Debug for NamespaceBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamespaceBackend")
+            .field("endpoints", &self.endpoints)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Outcome of provisioning a tenant namespace
+#[derive(Debug, Clone, Serialize)]
+pub struct NamespaceReport {
+    /// The tenant's namespace
+    pub namespace: TenantNamespace,
+    /// Collections created by this run
+    pub created: Vec<String>,
+    /// The namespace existed already; nothing was changed
+    pub already_provisioned: bool,
+}
 
 /// Schema-per-tenant provisioner
 /// Uses RLS policies for data isolation
@@ -13,30 +63,43 @@ use super::tenant::{Tenant, TenantConfig};
 pub struct SchemaProvisioner {
     /// Base database URL
     base_url: String,
-    /// Whether to create actual schema (false for testing)
-    create_schema: bool,
+    /// Collections every tenant namespace gets
+    templates: Vec<SchemaDef>,
+    /// Where namespaces are installed (None: nothing to install into)
+    backend: Option<NamespaceBackend>,
 }
 
 impl SchemaProvisioner {
     /// Create a new schema provisioner
     pub fn new() -> Self {
-        Self {
-            base_url: "http://localhost:54321".to_string(),
-            create_schema: false, // Default to false for safety
-        }
+        Self::with_base_url("http://localhost:54321".to_string())
     }
 
     /// Create with custom base URL
     pub fn with_base_url(base_url: String) -> Self {
         Self {
             base_url,
-            create_schema: false,
+            templates: Vec::new(),
+            backend: None,
         }
     }
 
-    /// Enable actual schema creation
-    pub fn with_schema_creation(mut self) -> Self {
-        self.create_schema = true;
+    /// Add a collection to every tenant namespace
+    pub fn with_collection(mut self, template: SchemaDef) -> Self {
+        self.templates.push(template);
+        self
+    }
+
+    /// Install namespaces into `database` and `endpoints`
+    pub fn with_backend(
+        mut self,
+        database: Arc<DatabaseFacade>,
+        endpoints: Arc<EndpointRegistry>,
+    ) -> Self {
+        self.backend = Some(NamespaceBackend {
+            database,
+            endpoints,
+        });
         self
     }
 
@@ -45,6 +108,220 @@ impl SchemaProvisioner {
         format!("tenant_{}", tenant_id.to_string().replace("-", "_"))
     }
 
+    /// Name of a template collection inside a tenant's namespace
+    pub fn namespaced_collection(tenant_id: Uuid, collection: &str) -> String {
+        format!("{}.{}", Self::schema_name(tenant_id), collection)
+    }
+
+    /// The namespace a tenant is provisioned into
+    pub fn namespace(&self, tenant_id: Uuid) -> TenantNamespace {
+        TenantNamespace {
+            name: Self::schema_name(tenant_id),
+            collections: self
+                .templates
+                .iter()
+                .map(|t| Self::namespaced_collection(tenant_id, &t.name))
+                .collect(),
+            tenant_field: TENANT_FIELD.to_string(),
+        }
+    }
+
+    /// RLS policy installed on every namespaced collection
+    pub fn tenant_policy() -> RlsPolicy {
+        RlsPolicy::Claim {
+            field: TENANT_FIELD.to_string(),
+            claim: TENANT_CLAIM.to_string(),
+        }
+    }
+
+    /// Create the tenant's namespaced collections, endpoints and policies,
+    /// then isolate the backend's shared collections by tenant
+    ///
+    /// Idempotent: a tenant that already has a namespace is reported as
+    /// provisioned and left alone. On failure everything this run created is
+    /// removed before the error is returned.
+    pub fn provision_namespace(&self, tenant: &Tenant) -> ControlPlaneResult<NamespaceReport> {
+        if let Some(namespace) = &tenant.namespace {
+            return Ok(NamespaceReport {
+                namespace: namespace.clone(),
+                created: Vec::new(),
+                already_provisioned: true,
+            });
+        }
+
+        let namespace = self.namespace(tenant.tenant_id);
+        let mut created = Vec::new();
+        let Some(backend) = &self.backend else {
+            if self.templates.is_empty() {
+                return Ok(NamespaceReport {
+                    namespace,
+                    created,
+                    already_provisioned: false,
+                });
+            }
+            return Err(ControlPlaneError::ProvisioningFailed {
+                tenant_id: tenant.tenant_id.to_string(),
+                reason: "no namespace backend configured".to_string(),
+            });
+        };
+
+        for (template, name) in self.templates.iter().zip(&namespace.collections) {
+            if let Err(reason) = Self::install_collection(backend, name, template) {
+                for done in created.iter().rev() {
+                    Self::remove_collection(backend, done);
+                }
+                return Err(ControlPlaneError::ProvisioningFailed {
+                    tenant_id: tenant.tenant_id.to_string(),
+                    reason,
+                });
+            }
+            created.push(name.clone());
+        }
+
+        // The tenant's rows may land in shared collections too: isolate them
+        if let Err(e) = self.isolate_shared_collections() {
+            for done in created.iter().rev() {
+                Self::remove_collection(backend, done);
+            }
+            return Err(ControlPlaneError::ProvisioningFailed {
+                tenant_id: tenant.tenant_id.to_string(),
+                reason: e.to_string(),
+            });
+        }
+
+        Ok(NamespaceReport {
+            namespace,
+            created,
+            already_provisioned: false,
+        })
+    }
+
+    /// Drop a tenant's namespaced collections, endpoints and policies
+    pub fn deprovision_namespace(
+        &self,
+        tenant_id: Uuid,
+        namespace: &TenantNamespace,
+    ) -> ControlPlaneResult<()> {
+        if namespace.collections.is_empty() {
+            return Ok(());
+        }
+        let backend = self
+            .backend
+            .as_ref()
+            .ok_or_else(|| ControlPlaneError::DeprovisioningFailed {
+                tenant_id: tenant_id.to_string(),
+                reason: "no namespace backend configured".to_string(),
+            })?;
+
+        for name in &namespace.collections {
+            Self::remove_collection(backend, name);
+        }
+        Ok(())
+    }
+
+    /// Install one namespaced collection; on error nothing is left behind
+    fn install_collection(
+        backend: &NamespaceBackend,
+        name: &str,
+        template: &SchemaDef,
+    ) -> Result<(), String> {
+        // Never take over a collection this run did not create
+        if backend.database.has_collection(name) || backend.endpoints.get(name).is_some() {
+            return Err(format!("collection {} already exists", name));
+        }
+
+        let mut schema = template.clone();
+        schema.name = name.to_string();
+        schema.rls_policy = None;
+        schema.anon_read = false;
+        Self::add_tenant_field(&mut schema);
+
+        // Policy first, so the collection is never reachable unguarded
+        backend.database.rls().install_policy(name, Self::tenant_policy());
+        backend.database.create_collection(name);
+        let registered = backend.endpoints.register(SchemaEndpoint {
+            collection: name.to_string(),
+            schema,
+            rls_policy: Some(Self::tenant_policy()),
+            anon_read: false,
+        });
+        if let Err(reason) = registered {
+            Self::remove_collection(backend, name);
+            return Err(reason);
+        }
+        Ok(())
+    }
+
+    /// Declare the tenant field on a schema that lacks it
+    fn add_tenant_field(schema: &mut SchemaDef) {
+        if !schema.fields.iter().any(|f| f.name == TENANT_FIELD) {
+            schema.fields.push(FieldDef {
+                name: TENANT_FIELD.to_string(),
+                field_type: FieldType::Uuid,
+                required: false,
+                primary: false,
+                default: None,
+            });
+        }
+    }
+
+    /// Isolate every collection of the backend by tenant
+    ///
+    /// Installs [`tenant_policy`](Self::tenant_policy) on each existing
+    /// collection and registered endpoint, revoking anonymous reads, and makes
+    /// it the default policy so collections created later are isolated too.
+    /// Reads then see only rows whose `tenant_id` equals the caller's JWT
+    /// `tenant_id` claim; a caller without the claim is refused, and a filter
+    /// naming another tenant matches nothing. Inserts are stamped with the
+    /// caller's tenant. The service role still bypasses RLS.
+    ///
+    /// Returns the collections isolated, sorted.
+    pub fn isolate_shared_collections(&self) -> ControlPlaneResult<Vec<String>> {
+        let backend = self
+            .backend
+            .as_ref()
+            .ok_or_else(|| ControlPlaneError::InvalidIsolationModel {
+                model: "schema".to_string(),
+                reason: "no namespace backend configured".to_string(),
+            })?;
+        let rls = backend.database.rls();
+
+        // Default first, so a collection created meanwhile is never unguarded
+        rls.set_default_policy(Self::tenant_policy());
+
+        let mut isolated = backend.database.collections();
+        for name in backend.endpoints.collections() {
+            if !isolated.contains(&name) {
+                isolated.push(name);
+            }
+        }
+        isolated.sort();
+
+        for name in &isolated {
+            rls.install_policy(name, Self::tenant_policy());
+            rls.set_anonymous_read(name, false);
+            if let Some(mut endpoint) = backend.endpoints.get(name) {
+                Self::add_tenant_field(&mut endpoint.schema);
+                endpoint.rls_policy = Some(Self::tenant_policy());
+                endpoint.anon_read = false;
+                backend
+                    .endpoints
+                    .register(endpoint)
+                    .map_err(|message| ControlPlaneError::DatabaseError { message })?;
+            }
+        }
+        Ok(isolated)
+    }
+
+    /// Remove a namespaced collection; parts already gone are skipped
+    fn remove_collection(backend: &NamespaceBackend, name: &str) {
+        if let Err(reason) = backend.endpoints.unregister(name) {
+            eprintln!("failed to unregister endpoint {}: {}", name, reason);
+        }
+        backend.database.drop_collection(name);
+        backend.database.rls().remove_policy(name);
+    }
+
     /// Generate RLS policy for a table
     pub fn generate_rls_policy(table: &str, tenant_id: Uuid) -> String {
         format!(
@@ -79,18 +356,12 @@ WITH CHECK (tenant_id = '{tenant_id}'::uuid);
     }
 
     /// Provision schema-per-tenant resources
+    ///
+    /// Returns the connection config; the namespace itself is created by
+    /// [`provision_namespace`](Self::provision_namespace).
     pub async fn provision(&self, tenant: &Tenant) -> ControlPlaneResult<TenantConfig> {
         let schema_name = Self::schema_name(tenant.tenant_id);
 
-        if self.create_schema {
-            // In production, execute SQL to create schema
-            // For now, we just log the intent
-            let sql_statements = Self::generate_schema_sql(tenant.tenant_id);
-            for sql in &sql_statements {
-                // Would execute: {sql} in production
-            }
-        }
-
         // Generate database URL with tenant schema
         let database_url = format!(
             "{}?schema={}&tenant_id={}",
@@ -109,15 +380,10 @@ WITH CHECK (tenant_id = '{tenant_id}'::uuid);
 
     /// Deprovision schema-per-tenant resources
     pub async fn deprovision(&self, tenant: &Tenant) -> ControlPlaneResult<()> {
-        let schema_name = Self::schema_name(tenant.tenant_id);
-
-        if self.create_schema {
-            // In production, execute SQL to drop schema
-            let _sql = format!("DROP SCHEMA IF EXISTS {} CASCADE;", schema_name);
-            // Would execute: {_sql} in production
+        match &tenant.namespace {
+            Some(namespace) => self.deprovision_namespace(tenant.tenant_id, namespace),
+            None => Ok(()),
         }
-
-        Ok(())
     }
 }
 
@@ -200,6 +466,187 @@ mod tests {
         assert!(policy.contains("tenant_id"));
     }
 
+    fn notes_template() -> SchemaDef {
+        SchemaDef {
+            name: "notes".to_string(),
+            fields: vec![FieldDef {
+                name: "body".to_string(),
+                field_type: FieldType::String,
+                required: true,
+                primary: false,
+                default: None,
+            }],
+            rls_policy: None,
+            anon_read: false,
+            relations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_failed_namespace_leaves_nothing_behind() {
+        let database = Arc::new(DatabaseFacade::new(Default::default()));
+        let endpoints = Arc::new(EndpointRegistry::new());
+        let mut tasks = notes_template();
+        tasks.name = "tasks".to_string();
+        let provisioner = SchemaProvisioner::new()
+            .with_collection(notes_template())
+            .with_collection(tasks)
+            .with_backend(Arc::clone(&database), Arc::clone(&endpoints));
+        let tenant = Tenant::new(
+            "acme".to_string(),
+            Plan::Free,
+            "local".to_string(),
+            IsolationModel::Schema,
+        );
+
+        // Someone else owns the second collection: provisioning must fail
+        let notes = SchemaProvisioner::namespaced_collection(tenant.tenant_id, "notes");
+        let tasks = SchemaProvisioner::namespaced_collection(tenant.tenant_id, "tasks");
+        database.create_collection(&tasks);
+
+        let err = provisioner.provision_namespace(&tenant).unwrap_err();
+        assert!(matches!(err, ControlPlaneError::ProvisioningFailed { .. }));
+        assert!(!database.has_collection(&notes));
+        assert!(database.rls().policy(&notes).is_none());
+        assert!(endpoints.collections().is_empty());
+        // The foreign collection is untouched
+        assert!(database.has_collection(&tasks));
+
+        // Once the conflict is gone the namespace provisions in full
+        database.drop_collection(&tasks);
+        let report = provisioner.provision_namespace(&tenant).unwrap();
+        assert_eq!(report.created, vec![notes.clone(), tasks.clone()]);
+        assert!(database.has_collection(&notes) && database.has_collection(&tasks));
+        assert_eq!(endpoints.collections().len(), 2);
+    }
+
+    #[test]
+    fn test_provisioning_isolates_shared_collections() {
+        let database: Arc<DatabaseFacade> = Arc::new(DatabaseFacade::new(Default::default()));
+        let endpoints = Arc::new(EndpointRegistry::new());
+        database.create_collection("notes");
+        database.rls().set_anonymous_read("notes", true);
+        let provisioner = SchemaProvisioner::new()
+            .with_collection(notes_template())
+            .with_backend(Arc::clone(&database), Arc::clone(&endpoints));
+        let tenant = Tenant::new(
+            "acme".to_string(),
+            Plan::Free,
+            "local".to_string(),
+            IsolationModel::Schema,
+        );
+
+        provisioner.provision_namespace(&tenant).unwrap();
+        assert!(matches!(
+            database.rls().policy("notes"),
+            Some(RlsPolicy::Claim { field, .. }) if field == TENANT_FIELD
+        ));
+        assert!(!database.rls().allows_anonymous_read("notes"));
+    }
+
+    #[test]
+    fn test_shared_collections_are_isolated_by_tenant() {
+        use crate::auth::crypto::PasswordPolicy;
+        use crate::auth::jwt::{JwtConfig, JwtManager};
+        use crate::auth::rls::RlsContext;
+        use crate::auth::user::User;
+        use crate::auth::AuthError;
+        use crate::rest_api::{QueryParams, RestError, RestHandler};
+        use std::collections::HashMap;
+
+        let database = Arc::new(DatabaseFacade::new(Default::default()));
+        let endpoints = Arc::new(EndpointRegistry::new());
+        let mut notes = SchemaEndpoint::from_schema(notes_template());
+        notes.anon_read = true;
+        notes.install_rls(database.rls());
+        endpoints.register(notes).unwrap();
+        database.create_collection("notes");
+        let provisioner =
+            SchemaProvisioner::new().with_backend(Arc::clone(&database), Arc::clone(&endpoints));
+
+        assert_eq!(provisioner.isolate_shared_collections().unwrap(), vec!["notes"]);
+        let endpoint = endpoints.get("notes").unwrap();
+        assert!(endpoint.schema.fields.iter().any(|f| f.name == TENANT_FIELD));
+        assert!(!endpoint.anon_read);
+
+        // Contexts come from each request's token, as the REST server builds them
+        let jwt = JwtManager::new(JwtConfig::default());
+        let context = |token: String| {
+            RlsContext::from_claims(&jwt.validate_token(&token).unwrap()).unwrap()
+        };
+        let user = |email: &str| {
+            User::new(email.to_string(), "password123", &PasswordPolicy::default()).unwrap()
+        };
+        let (acme, globex) = (Uuid::new_v4(), Uuid::new_v4());
+        let (alice, bob) = (user("alice@acme.test"), user("bob@globex.test"));
+        let alice_ctx = context(jwt.generate_tenant_token(&alice, acme).unwrap());
+        let bob_ctx = context(jwt.generate_tenant_token(&bob, globex).unwrap());
+
+        // Inserts are stamped with the caller's tenant and cannot claim another
+        for (ctx, body) in [(&alice_ctx, "acme"), (&bob_ctx, "globex")] {
+            database
+                .insert("notes", serde_json::json!({"body": body}), ctx)
+                .unwrap();
+        }
+        let forged = serde_json::json!({"body": "forged", "tenant_id": acme.to_string()});
+        assert!(database.insert("notes", forged, &bob_ctx).is_err());
+
+        let list = |query: &[(&str, String)], ctx: &RlsContext| {
+            let params: HashMap<String, String> =
+                query.iter().map(|(k, v)| (k.to_string(), v.clone())).collect();
+            database.list("notes", QueryParams::parse(&params).unwrap(), ctx)
+        };
+        let rows = list(&[], &alice_ctx).unwrap();
+        assert_eq!(rows.data.len(), 1);
+        assert_eq!(rows.data[0]["tenant_id"], acme.to_string());
+
+        // A crafted filter on the tenant field only narrows the caller's rows
+        for query in [
+            vec![("tenant_id", format!("eq.{}", globex))],
+            vec![("tenant_id", format!("neq.{}", acme))],
+            vec![("body", "eq.globex".to_string())],
+        ] {
+            assert!(list(&query, &alice_ctx).unwrap().data.is_empty());
+        }
+        let globex_row = list(&[], &bob_ctx).unwrap().data[0]["_id"].clone();
+        let err = database
+            .get("notes", globex_row.as_str().unwrap(), &alice_ctx)
+            .unwrap_err();
+        assert!(matches!(err, RestError::NotFound));
+
+        // Without a tenant context the query is refused, not left unfiltered
+        let untenanted = context(jwt.generate_access_token(&alice).unwrap());
+        let err = list(&[], &untenanted).unwrap_err();
+        assert!(matches!(err, RestError::Auth(AuthError::Unauthorized)));
+        let err = list(&[], &RlsContext::anonymous()).unwrap_err();
+        assert!(matches!(err, RestError::Auth(AuthError::AuthenticationRequired)));
+
+        // Collections created later fall under the tenant policy too
+        database.create_collection("tasks");
+        database
+            .insert("tasks", serde_json::json!({"title": "ship"}), &alice_ctx)
+            .unwrap();
+        let params = QueryParams::parse(&HashMap::new()).unwrap();
+        assert!(database.list("tasks", params, &bob_ctx).unwrap().data.is_empty());
+
+        // The service role still sees every tenant
+        let all = list(&[], &RlsContext::service_role()).unwrap();
+        assert_eq!(all.data.len(), 2);
+    }
+
+    #[test]
+    fn test_templates_need_a_backend() {
+        let provisioner = SchemaProvisioner::new().with_collection(notes_template());
+        let tenant = Tenant::new(
+            "acme".to_string(),
+            Plan::Free,
+            "local".to_string(),
+            IsolationModel::Schema,
+        );
+
+        assert!(provisioner.provision_namespace(&tenant).is_err());
+    }
+
     #[tokio::test]
     async fn test_provision() {
         let provisioner = SchemaProvisioner::new();
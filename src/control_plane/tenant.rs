@@ -72,6 +72,8 @@ pub enum TenantStatus {
     Active,
     /// Tenant is suspended (quota exceeded or billing issue)
     Suspended,
+    /// Tenant's database process kept crashing and is no longer restarted
+    Degraded,
     /// Tenant is being deleted
     Deleting,
     /// Tenant has been deleted (soft delete)
@@ -84,6 +86,19 @@ impl Default for TenantStatus {
     }
 }
 
+impl std::fmt::Display for TenantStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Provisioning => write!(f, "provisioning"),
+            Self::Active => write!(f, "active"),
+            Self::Suspended => write!(f, "suspended"),
+            Self::Degraded => write!(f, "degraded"),
+            Self::Deleting => write!(f, "deleting"),
+            Self::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
 /// Tenant configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TenantConfig {
@@ -97,6 +112,28 @@ pub struct TenantConfig {
     pub process_id: Option<u32>,
 }
 
+/// Collections and RLS policies provisioned for a schema-per-tenant tenant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantNamespace {
+    /// Namespace name, prefixed to every collection (e.g. `tenant_<id>`)
+    pub name: String,
+    /// Namespaced collection names
+    pub collections: Vec<String>,
+    /// Field bound to the JWT `tenant_id` claim in every collection
+    pub tenant_field: String,
+}
+
+/// A change of pricing plan, billed from `effective_at`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanChange {
+    /// Plan before the change
+    pub from: Plan,
+    /// Plan after the change
+    pub to: Plan,
+    /// When the new plan took effect
+    pub effective_at: DateTime<Utc>,
+}
+
 /// Core tenant model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tenant {
@@ -112,6 +149,9 @@ pub struct Tenant {
     pub isolation: IsolationModel,
     /// Current status
     pub status: TenantStatus,
+    /// Whether the current suspension refuses reads as well as writes
+    #[serde(default)]
+    pub reads_suspended: bool,
     /// Database connection URL
     pub database_url: String,
     /// API key for authentication
@@ -119,6 +159,12 @@ pub struct Tenant {
     /// Tenant configuration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<TenantConfig>,
+    /// Provisioned namespace (schema-per-tenant only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<TenantNamespace>,
+    /// Plan changes, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plan_history: Vec<PlanChange>,
     /// Creation timestamp
     pub created_at: DateTime<Utc>,
     /// Last update timestamp
@@ -142,9 +188,12 @@ impl Tenant {
             region: region.clone(),
             isolation,
             status: TenantStatus::Provisioning,
+            reads_suspended: false,
             database_url: format!("https://{}.aerodb.com", name),
             api_key,
             config: None,
+            namespace: None,
+            plan_history: Vec::new(),
             created_at: now,
             updated_at: now,
             deleted_at: None,
@@ -167,9 +216,37 @@ impl Tenant {
         self.updated_at = Utc::now();
     }
 
-    /// Mark tenant as suspended
-    pub fn suspend(&mut self) {
+    /// Check if tenant is suspended
+    pub fn is_suspended(&self) -> bool {
+        self.status == TenantStatus::Suspended
+    }
+
+    /// Mark tenant as suspended; writes are refused, and reads too if
+    /// `fence_reads`
+    pub fn suspend(&mut self, fence_reads: bool) {
         self.status = TenantStatus::Suspended;
+        self.reads_suspended = fence_reads;
+        self.updated_at = Utc::now();
+    }
+
+    /// Lift a suspension, making the tenant active again
+    pub fn resume(&mut self) {
+        self.reads_suspended = false;
+        self.activate();
+    }
+
+    /// Whether the tenant's status refuses a write, or a read
+    pub fn is_fenced(&self, write: bool) -> bool {
+        match self.status {
+            TenantStatus::Suspended => write || self.reads_suspended,
+            TenantStatus::Deleting | TenantStatus::Deleted => true,
+            _ => self.deleted_at.is_some(),
+        }
+    }
+
+    /// Mark tenant as degraded
+    pub fn degrade(&mut self) {
+        self.status = TenantStatus::Degraded;
         self.updated_at = Utc::now();
     }
 
@@ -186,6 +263,39 @@ impl Tenant {
         self.config = Some(config);
         self.updated_at = Utc::now();
     }
+
+    /// Record (or clear) the provisioned namespace
+    pub fn set_namespace(&mut self, namespace: Option<TenantNamespace>) {
+        self.namespace = namespace;
+        self.updated_at = Utc::now();
+    }
+
+    /// Switch to `plan`, recording the change in the plan history
+    pub fn change_plan(&mut self, plan: Plan, effective_at: DateTime<Utc>) {
+        if plan == self.plan {
+            return;
+        }
+        self.plan_history.push(PlanChange {
+            from: self.plan,
+            to: plan,
+            effective_at,
+        });
+        self.plan = plan;
+        self.updated_at = Utc::now();
+    }
+
+    /// Plan in effect at `at`
+    pub fn plan_at(&self, at: DateTime<Utc>) -> Plan {
+        match self
+            .plan_history
+            .iter()
+            .rev()
+            .find(|c| c.effective_at <= at)
+        {
+            Some(change) => change.to,
+            None => self.plan_history.first().map_or(self.plan, |c| c.from),
+        }
+    }
 }
 
 /// Request to create a new tenant
@@ -325,14 +435,44 @@ mod tests {
         tenant.activate();
         assert!(tenant.is_active());
 
-        tenant.suspend();
+        tenant.suspend(false);
         assert!(!tenant.is_active());
         assert_eq!(tenant.status, TenantStatus::Suspended);
+        assert!(tenant.is_fenced(true));
+        assert!(!tenant.is_fenced(false));
+
+        tenant.suspend(true);
+        assert!(tenant.is_fenced(false));
+
+        tenant.resume();
+        assert!(tenant.is_active());
+        assert!(!tenant.reads_suspended);
+        assert!(!tenant.is_fenced(true));
 
         tenant.mark_deleted();
         assert!(tenant.is_deleted());
     }
 
+    #[test]
+    fn test_plan_history() {
+        let mut tenant = Tenant::new(
+            "test".to_string(),
+            Plan::Free,
+            "local".to_string(),
+            IsolationModel::Schema,
+        );
+        let upgrade = Utc::now();
+        tenant.change_plan(Plan::Pro, upgrade);
+        tenant.change_plan(Plan::Pro, upgrade + chrono::Duration::days(1));
+
+        assert_eq!(tenant.plan_history.len(), 1);
+        assert_eq!(
+            tenant.plan_at(upgrade - chrono::Duration::seconds(1)),
+            Plan::Free
+        );
+        assert_eq!(tenant.plan_at(upgrade), Plan::Pro);
+    }
+
     #[test]
     fn test_isolation_model_serialization() {
         let model = IsolationModel::Database;
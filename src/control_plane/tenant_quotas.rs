@@ -0,0 +1,485 @@
+//! # Tenant Quotas
+//!
+//! Quota enforcement and metering for the API write and query paths.
+//!
+//! The API handler checks an operation against the resolved tenant's
+//! quotas before executing it and records its usage only once it has
+//! succeeded, so refused and failed operations are never counted. An
+//! operation crossing a soft quota is allowed and logged as a
+//! `QUOTA_SOFT_LIMIT` warning. Usage is
+//! persisted to `data_dir/system/tenant_usage.json` at most once per
+//! persist interval and on [`TenantQuotas::flush`], so a restart resumes
+//! from the last persisted usage.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::observability::{JsonLogger, Logger};
+
+use super::errors::{ControlPlaneError, ControlPlaneResult};
+use super::metering::{UsageMetrics, UsageTracker};
+use super::quota::{QuotaDimension, QuotaEnforcer, Quotas, ResultSizeClass};
+
+/// Default interval between usage persists
+pub const DEFAULT_PERSIST_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Path of the stored tenant quotas and usage
+pub fn tenant_usage_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("system").join("tenant_usage.json")
+}
+
+/// Contents of the tenant usage file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoredTenantUsage {
+    /// Quotas of tenants that do not use the defaults
+    #[serde(default)]
+    pub quotas: BTreeMap<Uuid, Quotas>,
+
+    /// Usage by tenant and month
+    #[serde(default)]
+    pub usage: Vec<UsageMetrics>,
+}
+
+/// Kind of operation admitted against a tenant's quotas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaOperation {
+    /// Insert, update or delete changing the live documents by `documents`
+    Write { documents: i64 },
+    /// Query or aggregate
+    Read,
+}
+
+/// An operation admitted against a tenant's quotas, recorded once it
+/// succeeds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaAdmission {
+    /// Tenant the operation runs as
+    pub tenant_id: Uuid,
+
+    /// What was admitted
+    pub operation: QuotaOperation,
+
+    /// Dimensions the operation crosses a soft quota on
+    pub soft_exceeded: Vec<QuotaDimension>,
+}
+
+/// Per-tenant quotas and usage
+#[derive(Debug)]
+pub struct TenantQuotas {
+    usage: Arc<UsageTracker>,
+    quotas: RwLock<HashMap<Uuid, Quotas>>,
+    default_quotas: Quotas,
+    path: Option<PathBuf>,
+    persist_interval: Duration,
+    last_persisted: Mutex<Instant>,
+    logger: Arc<dyn Logger>,
+}
+
+impl TenantQuotas {
+    /// In-memory quotas: usage is lost on restart.
+    pub fn new(default_quotas: Quotas) -> Self {
+        Self {
+            usage: Arc::new(UsageTracker::new()),
+            quotas: RwLock::new(HashMap::new()),
+            default_quotas,
+            path: None,
+            persist_interval: DEFAULT_PERSIST_INTERVAL,
+            last_persisted: Mutex::new(Instant::now()),
+            logger: JsonLogger::shared(),
+        }
+    }
+
+    /// Open the quotas and usage persisted in a data directory.
+    pub fn open(
+        data_dir: &Path,
+        default_quotas: Quotas,
+        persist_interval: Duration,
+    ) -> ControlPlaneResult<Self> {
+        let stored = Self::load(data_dir)?;
+        Ok(Self {
+            usage: Arc::new(UsageTracker::from_usage(stored.usage)),
+            quotas: RwLock::new(stored.quotas.into_iter().collect()),
+            default_quotas,
+            path: Some(tenant_usage_path(data_dir)),
+            persist_interval,
+            last_persisted: Mutex::new(Instant::now()),
+            logger: JsonLogger::shared(),
+        })
+    }
+
+    /// Send soft quota warnings and persistence failures to `logger`
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Read the stored quotas and usage of a data directory.
+    pub fn load(data_dir: &Path) -> ControlPlaneResult<StoredTenantUsage> {
+        let path = tenant_usage_path(data_dir);
+        match fs::read(&path) {
+            Ok(contents) => {
+                serde_json::from_slice(&contents).map_err(|e| ControlPlaneError::Internal {
+                    message: format!("Invalid tenant usage in {}: {}", path.display(), e),
+                })
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(StoredTenantUsage::default()),
+            Err(e) => Err(ControlPlaneError::Internal {
+                message: format!("Failed to read {}: {}", path.display(), e),
+            }),
+        }
+    }
+
+    /// Override the default quotas of a tenant.
+    pub fn set_quotas(&self, tenant_id: Uuid, quotas: Quotas) {
+        self.quotas.write().unwrap().insert(tenant_id, quotas);
+    }
+
+    /// Quotas that apply to a tenant.
+    pub fn quotas(&self, tenant_id: Uuid) -> Quotas {
+        self.quotas
+            .read()
+            .unwrap()
+            .get(&tenant_id)
+            .cloned()
+            .unwrap_or_else(|| self.default_quotas.clone())
+    }
+
+    /// Current month's usage of a tenant.
+    pub fn usage(&self, tenant_id: Uuid) -> UsageMetrics {
+        self.usage.peek_current_usage(tenant_id)
+    }
+
+    /// The underlying usage tracker
+    pub fn usage_tracker(&self) -> Arc<UsageTracker> {
+        self.usage.clone()
+    }
+
+    /// Check a write of `bytes` bytes changing the live documents by
+    /// `documents`.
+    ///
+    /// A write crossing a soft quota is allowed and logged.
+    ///
+    /// # Errors
+    ///
+    /// `QUOTA_EXCEEDED` naming the dimension, its usage, the requested
+    /// amount and the limit if the write would exceed a hard quota on write
+    /// operations, storage or documents.
+    pub fn check_write(
+        &self,
+        tenant_id: Uuid,
+        bytes: u64,
+        documents: i64,
+    ) -> ControlPlaneResult<QuotaAdmission> {
+        let enforcer = QuotaEnforcer::new(tenant_id.to_string(), self.quotas(tenant_id));
+        let usage = self.usage(tenant_id);
+
+        let mut checks = vec![
+            (QuotaDimension::WriteOps, usage.write_ops, 1),
+            (QuotaDimension::Storage, usage.storage_bytes, bytes),
+        ];
+        if documents > 0 {
+            checks.push((QuotaDimension::Documents, usage.documents, documents as u64));
+        }
+
+        let mut soft_exceeded = Vec::new();
+        for (dimension, used, additional) in checks {
+            if enforcer.enforce_dimension(dimension, used, additional)? {
+                self.warn_soft_limit(&enforcer, tenant_id, dimension, used, additional);
+                soft_exceeded.push(dimension);
+            }
+        }
+
+        Ok(QuotaAdmission {
+            tenant_id,
+            operation: QuotaOperation::Write { documents },
+            soft_exceeded,
+        })
+    }
+
+    /// Check a read returning at most `result_size` rows (`None` if the
+    /// result is not row-limited).
+    ///
+    /// # Errors
+    ///
+    /// `QUOTA_EXCEEDED` naming the dimension if the read would exceed the
+    /// hard quota on read operations or asks for a larger result set than
+    /// the tenant may.
+    pub fn check_read(
+        &self,
+        tenant_id: Uuid,
+        result_size: Option<ResultSizeClass>,
+    ) -> ControlPlaneResult<QuotaAdmission> {
+        let enforcer = QuotaEnforcer::new(tenant_id.to_string(), self.quotas(tenant_id));
+        let usage = self.usage(tenant_id);
+
+        if let Some(class) = result_size {
+            enforcer.enforce_result_size(class)?;
+        }
+
+        let mut soft_exceeded = Vec::new();
+        if enforcer.enforce_dimension(QuotaDimension::ReadOps, usage.read_ops, 1)? {
+            self.warn_soft_limit(
+                &enforcer,
+                tenant_id,
+                QuotaDimension::ReadOps,
+                usage.read_ops,
+                1,
+            );
+            soft_exceeded.push(QuotaDimension::ReadOps);
+        }
+
+        Ok(QuotaAdmission {
+            tenant_id,
+            operation: QuotaOperation::Read,
+            soft_exceeded,
+        })
+    }
+
+    /// Log an operation allowed past a dimension's soft quota
+    fn warn_soft_limit(
+        &self,
+        enforcer: &QuotaEnforcer,
+        tenant_id: Uuid,
+        dimension: QuotaDimension,
+        used: u64,
+        requested: u64,
+    ) {
+        let quotas = enforcer.quotas();
+        self.logger.warn(
+            "QUOTA_SOFT_LIMIT",
+            &[
+                ("tenant_id", &tenant_id.to_string()),
+                ("resource", dimension.name()),
+                ("used", &used.to_string()),
+                ("requested", &requested.to_string()),
+                ("soft_limit", &quotas.soft_limit(dimension).to_string()),
+                ("limit", &quotas.limit(dimension).to_string()),
+            ],
+        );
+    }
+
+    /// Record an admitted operation that succeeded.
+    ///
+    /// `storage_delta` is the change in stored bytes the operation caused.
+    /// Usage is persisted if the persist interval has elapsed; persisting
+    /// is advisory and a failure only delays it.
+    pub fn record(&self, admission: &QuotaAdmission, storage_delta: i64) {
+        let soft: Vec<&str> = admission.soft_exceeded.iter().map(|d| d.name()).collect();
+        match admission.operation {
+            QuotaOperation::Write { documents } => {
+                self.usage
+                    .record_write(admission.tenant_id, storage_delta, documents, &soft)
+            }
+            QuotaOperation::Read => self.usage.record_read(admission.tenant_id, &soft),
+        }
+        self.persist_if_due();
+    }
+
+    /// Record an operation rejected at the tenant's concurrency limit.
+    pub fn record_rejection(&self, tenant_id: Uuid) {
+        self.usage.record_admission_rejection(tenant_id);
+        self.persist_if_due();
+    }
+
+    /// Persist usage if the persist interval has elapsed
+    fn persist_if_due(&self) {
+        let mut last_persisted = self.last_persisted.lock().unwrap();
+        if last_persisted.elapsed() >= self.persist_interval {
+            if let Err(e) = self.store() {
+                self.logger
+                    .error("TENANT_USAGE_PERSIST_FAILED", &[("error", &e.to_string())]);
+            }
+            *last_persisted = Instant::now();
+        }
+    }
+
+    /// Persist quotas and usage now.
+    pub fn flush(&self) -> ControlPlaneResult<()> {
+        let mut last_persisted = self.last_persisted.lock().unwrap();
+        self.store()?;
+        *last_persisted = Instant::now();
+        Ok(())
+    }
+
+    /// Replace the stored quotas and usage (no-op when in memory).
+    fn store(&self) -> ControlPlaneResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let stored = StoredTenantUsage {
+            quotas: self
+                .quotas
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(id, quotas)| (*id, quotas.clone()))
+                .collect(),
+            usage: self.usage.all_usage(),
+        };
+        let temp_path = path.with_extension("tmp");
+        serde_json::to_vec_pretty(&stored)
+            .map_err(io::Error::other)
+            .and_then(|json| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                fs::write(&temp_path, json)
+            })
+            .and_then(|()| fs::rename(&temp_path, path))
+            .map_err(|e| ControlPlaneError::Internal {
+                message: format!("Failed to write {}: {}", path.display(), e),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::{Severity, VecLogger};
+    use tempfile::TempDir;
+
+    fn small_quotas() -> Quotas {
+        Quotas {
+            max_documents: 2,
+            ..Quotas::free()
+        }
+    }
+
+    #[test]
+    fn test_refused_writes_are_not_metered() {
+        let quotas = TenantQuotas::new(small_quotas());
+        let tenant_id = Uuid::new_v4();
+
+        for _ in 0..2 {
+            let admission = quotas.check_write(tenant_id, 10, 1).unwrap();
+            quotas.record(&admission, 10);
+        }
+
+        let err = quotas.check_write(tenant_id, 10, 1).unwrap_err();
+        assert_eq!(err.error_code(), "QUOTA_EXCEEDED");
+        assert!(matches!(
+            err,
+            ControlPlaneError::QuotaExceeded { ref resource, used: 2, limit: 2, .. }
+                if resource == "documents"
+        ));
+
+        let usage = quotas.usage(tenant_id);
+        assert_eq!(usage.write_ops, 2);
+        assert_eq!(usage.documents, 2);
+        assert_eq!(usage.storage_bytes, 20);
+        assert_eq!(usage.soft_quota_events.get("documents"), Some(&1));
+
+        // A delete is admitted at the document quota
+        let admission = quotas.check_write(tenant_id, 0, -1).unwrap();
+        quotas.record(&admission, 0);
+        assert_eq!(quotas.usage(tenant_id).documents, 1);
+    }
+
+    #[test]
+    fn test_storage_quota_warns_then_refuses() {
+        let logger = Arc::new(VecLogger::new());
+        let quotas = TenantQuotas::new(Quotas {
+            storage_bytes: 1000,
+            soft_limit_percent: 80,
+            ..Quotas::free()
+        })
+        .with_logger(logger.clone());
+        let tenant_id = Uuid::new_v4();
+
+        // Under the soft limit: allowed, nothing logged
+        let admission = quotas.check_write(tenant_id, 700, 0).unwrap();
+        assert!(admission.soft_exceeded.is_empty());
+        quotas.record(&admission, 700);
+        assert!(logger.records().is_empty());
+
+        // Past the soft limit: allowed and logged
+        let admission = quotas.check_write(tenant_id, 200, 0).unwrap();
+        assert_eq!(admission.soft_exceeded, vec![QuotaDimension::Storage]);
+        quotas.record(&admission, 200);
+        let records = logger.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].event, "QUOTA_SOFT_LIMIT");
+        assert_eq!(records[0].severity, Severity::Warn);
+        assert_eq!(records[0].field("resource"), Some("storage_bytes"));
+        assert_eq!(records[0].field("used"), Some("700"));
+        assert_eq!(records[0].field("requested"), Some("200"));
+        assert_eq!(records[0].field("soft_limit"), Some("800"));
+
+        // Past the hard limit: refused with usage, request and limit
+        let err = quotas.check_write(tenant_id, 101, 0).unwrap_err();
+        assert!(matches!(
+            err,
+            ControlPlaneError::QuotaExceeded {
+                ref resource,
+                used: 900,
+                requested: 101,
+                limit: 1000,
+                ..
+            } if resource == "storage_bytes"
+        ));
+        assert!(err
+            .to_string()
+            .contains("900 used + 101 requested > 1000 limit"));
+        assert_eq!(quotas.usage(tenant_id).storage_bytes, 900);
+    }
+
+    #[test]
+    fn test_result_size_quota() {
+        let quotas = TenantQuotas::new(Quotas::free());
+        let tenant_id = Uuid::new_v4();
+
+        assert!(quotas
+            .check_read(tenant_id, Some(ResultSizeClass::Medium))
+            .is_ok());
+        let err = quotas
+            .check_read(tenant_id, Some(ResultSizeClass::Unbounded))
+            .unwrap_err();
+        assert!(err.to_string().contains("result_size"));
+    }
+
+    #[test]
+    fn test_usage_survives_reopen() {
+        let dir = TempDir::new().unwrap();
+        let tenant_id = Uuid::new_v4();
+
+        let quotas = TenantQuotas::open(dir.path(), Quotas::free(), Duration::ZERO).unwrap();
+        quotas.set_quotas(tenant_id, small_quotas());
+        let admission = quotas.check_write(tenant_id, 64, 1).unwrap();
+        quotas.record(&admission, 64);
+        let admission = quotas.check_read(tenant_id, None).unwrap();
+        quotas.record(&admission, 0);
+        drop(quotas);
+
+        let reopened = TenantQuotas::open(dir.path(), Quotas::free(), Duration::ZERO).unwrap();
+        let usage = reopened.usage(tenant_id);
+        assert_eq!(usage.write_ops, 1);
+        assert_eq!(usage.read_ops, 1);
+        assert_eq!(usage.documents, 1);
+        assert_eq!(reopened.quotas(tenant_id).max_documents, 2);
+    }
+
+    #[test]
+    fn test_persist_failure_is_logged() {
+        let dir = TempDir::new().unwrap();
+        let logger = Arc::new(VecLogger::new());
+        let quotas = TenantQuotas::open(dir.path(), Quotas::free(), Duration::ZERO)
+            .unwrap()
+            .with_logger(logger.clone());
+
+        // A directory in the way of the temporary file fails the write
+        fs::create_dir_all(tenant_usage_path(dir.path()).with_extension("tmp")).unwrap();
+        quotas.record_rejection(Uuid::new_v4());
+
+        let records = logger.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].event, "TENANT_USAGE_PERSIST_FAILED");
+        assert_eq!(records[0].severity, Severity::Error);
+    }
+}
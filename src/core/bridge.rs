@@ -12,6 +12,7 @@ use crate::core::error::CoreError;
 use crate::core::middleware::auth::AuthMiddleware;
 use crate::core::middleware::observe::{AuditLogger, MetricsRecorder, ObserveMiddleware};
 use crate::core::middleware::rls::{OwnershipPolicy, RlsMiddleware};
+use crate::core::middleware::tenant::TenantScopeMiddleware;
 use crate::core::operation::{DeleteOp, Operation, QueryOp, ReadOp, UpdateOp, WriteOp};
 use crate::core::pipeline::Pipeline;
 use crate::core::StorageBackend;
@@ -29,6 +30,8 @@ pub struct BridgeConfig {
     pub enable_observe: bool,
     /// Allow anonymous reads
     pub allow_anonymous_reads: bool,
+    /// Confine every operation to the caller's `tenant_id` claim
+    pub tenant_isolation: bool,
 }
 
 impl Default for BridgeConfig {
@@ -38,6 +41,7 @@ impl Default for BridgeConfig {
             enable_auth: true,
             enable_observe: true,
             allow_anonymous_reads: false,
+            tenant_isolation: false,
         }
     }
 }
@@ -68,6 +72,10 @@ impl PipelineBridge {
             pipeline = pipeline.with_middleware(auth);
         }
 
+        if config.tenant_isolation {
+            pipeline = pipeline.with_middleware(TenantScopeMiddleware::new());
+        }
+
         if config.enable_rls {
             pipeline = pipeline.with_middleware(RlsMiddleware::ownership());
         }
@@ -9,6 +9,8 @@ use std::time::Instant;
 use serde_json::Value;
 use uuid::Uuid;
 
+use crate::observability::current_request_id;
+
 /// Context carried through the execution pipeline
 #[derive(Debug, Clone)]
 pub struct RequestContext {
@@ -29,10 +31,11 @@ pub struct RequestContext {
 }
 
 impl RequestContext {
-    /// Create a new request context
+    /// Create a new request context, with the ID of the request being
+    /// handled if there is one
     pub fn new(auth: AuthContext) -> Self {
         Self {
-            request_id: Uuid::new_v4(),
+            request_id: current_request_id().unwrap_or_else(Uuid::new_v4),
             auth,
             rls_filters: Vec::new(),
             metadata: HashMap::new(),
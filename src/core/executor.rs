@@ -10,7 +10,7 @@ use std::sync::Arc;
 
 use serde_json::{json, Value};
 
-use crate::core::context::RequestContext;
+use crate::core::context::{FilterOperator, RequestContext};
 use crate::core::error::CoreError;
 use crate::core::operation::{DeleteOp, Operation, QueryOp, ReadOp, UpdateOp, WriteOp};
 use crate::core::pipeline::{OperationExecutor, OperationResult};
@@ -69,7 +69,7 @@ impl OperationExecutor for UnifiedExecutor {
         Box::pin(async move {
             match op_clone {
                 Operation::Read(read) => execute_read(&storage, &read, &rls_filters),
-                Operation::Write(write) => execute_write(&storage, &write),
+                Operation::Write(write) => execute_write(&storage, &write, &rls_filters),
                 Operation::Update(update) => execute_update(&storage, &update, &rls_filters),
                 Operation::Delete(delete) => execute_delete(&storage, &delete, &rls_filters),
                 Operation::Query(query) => execute_query(&storage, &query, &rls_filters),
@@ -129,9 +129,21 @@ fn execute_read(
     }
 }
 
-fn execute_write(storage: &Arc<dyn StorageBackend>, op: &WriteOp) -> OperationResult {
+fn execute_write(
+    storage: &Arc<dyn StorageBackend>,
+    op: &WriteOp,
+    rls_filters: &[crate::core::context::RlsFilter],
+) -> OperationResult {
+    let mut document = op.document.clone();
+    stamp_rls_fields(&mut document, rls_filters).map_err(|field| {
+        CoreError::access_denied(format!(
+            "Cannot write {} of another scope in {}",
+            field, op.collection
+        ))
+    })?;
+
     let id = storage
-        .write(&op.collection, op.document.clone())
+        .write(&op.collection, document)
         .map_err(CoreError::execution)?;
 
     Ok(json!({
@@ -163,6 +175,18 @@ fn execute_update(
                 }
             }
 
+            // The update must not move the document out of scope
+            for filter in rls_filters {
+                if let Some(value) = op.updates.get(&filter.field) {
+                    if filter.operator == FilterOperator::Eq && value != &filter.value {
+                        return Err(CoreError::access_denied(format!(
+                            "Cannot move document {} in {} to another {}",
+                            op.id, op.collection, filter.field
+                        )));
+                    }
+                }
+            }
+
             // Perform update
             let updated = storage
                 .update(&op.collection, &op.id, op.updates.clone())
@@ -244,10 +268,32 @@ fn execute_query(
     }))
 }
 
+/// Set each equality-filtered field a new document lacks to the filter's
+/// value, failing with the field if the document holds another value
+fn stamp_rls_fields(
+    document: &mut Value,
+    rls_filters: &[crate::core::context::RlsFilter],
+) -> Result<(), String> {
+    let Some(fields) = document.as_object_mut() else {
+        return Ok(());
+    };
+    for filter in rls_filters {
+        if filter.operator != FilterOperator::Eq {
+            continue;
+        }
+        match fields.get(&filter.field) {
+            Some(value) if value != &filter.value => return Err(filter.field.clone()),
+            Some(_) => {}
+            None => {
+                fields.insert(filter.field.clone(), filter.value.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Check if a document passes an RLS filter
 fn check_rls_filter(doc: &Value, filter: &crate::core::context::RlsFilter) -> bool {
-    use crate::core::context::FilterOperator;
-
     let field_value = doc.get(&filter.field);
 
     match (&filter.operator, field_value) {
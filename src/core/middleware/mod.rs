@@ -24,3 +24,4 @@ pub trait Middleware: Send + Sync {
 pub mod auth;
 pub mod observe;
 pub mod rls;
+pub mod tenant;
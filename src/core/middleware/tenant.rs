@@ -0,0 +1,116 @@
+//! Tenant Scope Middleware
+//!
+//! Confines every data operation to the caller's tenant.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use serde_json::Value;
+
+use crate::control_plane::schema_provisioner::{TENANT_CLAIM, TENANT_FIELD};
+use crate::core::context::{RequestContext, RlsFilter};
+use crate::core::error::CoreError;
+use crate::core::operation::Operation;
+use crate::core::pipeline::{Next, OperationResult};
+
+use super::Middleware;
+
+/// Tenant scope middleware
+///
+/// Injects a filter binding the tenant field to the caller's tenant claim,
+/// so reads, updates and deletes only reach the caller's rows and writes
+/// are stamped with its tenant. A data operation without a tenant claim is
+/// refused. The service role is not scoped.
+pub struct TenantScopeMiddleware {
+    /// Document field holding the tenant
+    field: String,
+    /// JWT claim naming the caller's tenant
+    claim: String,
+}
+
+impl TenantScopeMiddleware {
+    /// Scope by the `tenant_id` field and claim
+    pub fn new() -> Self {
+        Self {
+            field: TENANT_FIELD.to_string(),
+            claim: TENANT_CLAIM.to_string(),
+        }
+    }
+
+    /// The caller's tenant, if its context names one
+    fn tenant<'c>(&self, ctx: &'c RequestContext) -> Option<&'c Value> {
+        ctx.auth.claims.get(&self.claim).filter(|v| !v.is_null())
+    }
+}
+
+impl Default for TenantScopeMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Middleware for TenantScopeMiddleware {
+    fn process<'a>(
+        &'a self,
+        op: &'a Operation,
+        ctx: &'a mut RequestContext,
+        next: Next<'a>,
+    ) -> Pin<Box<dyn Future<Output = OperationResult> + Send + 'a>> {
+        Box::pin(async move {
+            if ctx.bypass_rls() || op.collection().is_none() {
+                return next.run(op, ctx).await;
+            }
+
+            let tenant = self
+                .tenant(ctx)
+                .cloned()
+                .ok_or_else(|| CoreError::access_denied("Tenant context required"))?;
+            ctx.rls_filters
+                .push(RlsFilter::eq(self.field.clone(), tenant));
+
+            next.run(op, ctx).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::AuthContext;
+    use crate::core::operation::ReadOp;
+    use crate::core::pipeline::{NoOpExecutor, Pipeline};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn read() -> Operation {
+        Operation::Read(ReadOp {
+            collection: "notes".to_string(),
+            id: "note_1".to_string(),
+            select: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_tenant_context_required() {
+        let pipeline = Pipeline::new(NoOpExecutor).with_middleware(TenantScopeMiddleware::new());
+
+        let ctx = RequestContext::new(AuthContext::authenticated(Uuid::new_v4()));
+        let result = pipeline.execute(read(), ctx).await;
+        assert!(matches!(result, Err(CoreError::AccessDenied(_))));
+
+        let claims = HashMap::from([(TENANT_CLAIM.to_string(), Value::String("acme".into()))]);
+        let ctx =
+            RequestContext::new(AuthContext::authenticated(Uuid::new_v4()).with_claims(claims));
+        assert!(pipeline.execute(read(), ctx).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_service_role_is_not_scoped() {
+        let pipeline = Pipeline::new(NoOpExecutor).with_middleware(TenantScopeMiddleware::new());
+
+        let result = pipeline
+            .execute(read(), RequestContext::service_role())
+            .await;
+        assert!(result.is_ok());
+    }
+}
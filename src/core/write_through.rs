@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 use std::path::Path;
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Mutex, MutexGuard, RwLock};
 
 use serde_json::Value;
 
@@ -25,9 +25,10 @@ type DocumentCache = HashMap<String, HashMap<String, Value>>;
 /// - WAL append before storage write (crash safety)
 /// - Storage write with fsync (durability)
 /// - In-memory cache (performance)
-/// - Optional index updates
+/// - Optional index updates, with unique indexes checked before the WAL
 ///
-/// Thread-safety is provided via `Mutex` on writers.
+/// Thread-safety is provided via `Mutex` on writers. When an index is
+/// attached, its lock is held for the whole write.
 pub struct WriteThroughBackend {
     /// Collection name
     collection: String,
@@ -87,6 +88,126 @@ impl WriteThroughBackend {
         self
     }
 
+    /// Lock the index manager, if one is attached.
+    ///
+    /// Writers take this lock first and hold it until the index is updated,
+    /// so a unique check and the write it admits cannot interleave with
+    /// another writer claiming the same key.
+    fn lock_index(&self) -> Result<Option<MutexGuard<'_, IndexManager>>, String> {
+        match self.index_manager {
+            Some(ref index_mutex) => index_mutex.lock().map(Some).map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+
+    /// Write several documents as one unit.
+    ///
+    /// Every document is serialized and the whole batch is checked against
+    /// the unique indexes before the first WAL record is appended, so a
+    /// rejected batch leaves WAL, storage, index and cache untouched. The
+    /// index is updated only after every storage write has succeeded.
+    pub fn write_many(
+        &self,
+        collection: &str,
+        documents: Vec<Value>,
+    ) -> Result<Vec<String>, String> {
+        let mut index = self.lock_index()?;
+
+        // Generate or extract document IDs and serialize up front
+        let mut batch = Vec::with_capacity(documents.len());
+        for mut document in documents {
+            let doc_id = document
+                .get("_id")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+            // Ensure _id is set
+            if let Some(obj) = document.as_object_mut() {
+                obj.insert("_id".to_string(), Value::String(doc_id.clone()));
+            }
+
+            let body_bytes = serde_json::to_vec(&document)
+                .map_err(|e| format!("Failed to serialize document: {}", e))?;
+
+            batch.push((doc_id, document, body_bytes));
+        }
+
+        // 0. Unique check (nothing is durable yet)
+        if let Some(ref index) = index {
+            let writes: Vec<(&str, &Value)> = batch
+                .iter()
+                .map(|(doc_id, document, _)| (doc_id.as_str(), document))
+                .collect();
+            index
+                .check_unique_batch(&writes)
+                .map_err(|e| e.to_string())?;
+        }
+
+        let mut offsets = Vec::with_capacity(batch.len());
+        for (doc_id, _, body_bytes) in &batch {
+            // 1. WAL append (durability first)
+            let wal_payload = WalPayload::new(
+                collection,
+                doc_id,
+                &self.default_schema_id,
+                &self.default_schema_version,
+                body_bytes.clone(),
+            );
+
+            {
+                let mut wal = self.wal_writer.lock().map_err(|e| e.to_string())?;
+                wal.append(RecordType::Insert, wal_payload)
+                    .map_err(|e| format!("WAL append failed: {}", e))?;
+            }
+
+            // 2. Storage write (persistence)
+            let storage_payload = StoragePayload::new(
+                collection,
+                doc_id,
+                &self.default_schema_id,
+                &self.default_schema_version,
+                body_bytes.clone(),
+            );
+
+            let offset = {
+                let mut storage = self.storage_writer.lock().map_err(|e| e.to_string())?;
+                storage
+                    .write(&storage_payload)
+                    .map_err(|e| format!("Storage write failed: {}", e))?
+            };
+            offsets.push(offset);
+        }
+
+        // 3. Index update (optional)
+        if let Some(ref mut index) = index {
+            for ((doc_id, document, _), offset) in batch.iter().zip(offsets) {
+                let doc_info = DocumentInfo {
+                    document_id: doc_id.clone(),
+                    schema_id: self.default_schema_id.clone(),
+                    schema_version: self.default_schema_version.clone(),
+                    is_tombstone: false,
+                    body: document.clone(),
+                    offset,
+                };
+                index.apply_write(&doc_info);
+            }
+        }
+
+        // 4. Cache update (performance)
+        let mut ids = Vec::with_capacity(batch.len());
+        {
+            let mut cache = self.cache.write().map_err(|e| e.to_string())?;
+            let collection_cache = cache.entry(collection.to_string()).or_default();
+            for (doc_id, document, _) in batch {
+                collection_cache.insert(doc_id.clone(), document);
+                ids.push(doc_id);
+            }
+        }
+
+        Ok(ids)
+    }
+
     /// Load existing documents from storage into cache
     fn load_from_storage(&mut self, data_dir: &Path) -> Result<usize, String> {
         let storage_path = data_dir.join("data").join("documents.dat");
@@ -129,81 +250,14 @@ impl StorageBackend for WriteThroughBackend {
         Ok(cache.get(collection).and_then(|c| c.get(id)).cloned())
     }
 
-    fn write(&self, collection: &str, mut document: Value) -> Result<String, String> {
-        // Generate or extract document ID
-        let doc_id = document
-            .get("_id")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
-
-        // Ensure _id is set
-        if let Some(obj) = document.as_object_mut() {
-            obj.insert("_id".to_string(), Value::String(doc_id.clone()));
-        }
-
-        // Serialize document
-        let body_bytes = serde_json::to_vec(&document)
-            .map_err(|e| format!("Failed to serialize document: {}", e))?;
-
-        // 1. WAL append (durability first)
-        let wal_payload = WalPayload::new(
-            collection,
-            &doc_id,
-            &self.default_schema_id,
-            &self.default_schema_version,
-            body_bytes.clone(),
-        );
-
-        {
-            let mut wal = self.wal_writer.lock().map_err(|e| e.to_string())?;
-            wal.append(RecordType::Insert, wal_payload)
-                .map_err(|e| format!("WAL append failed: {}", e))?;
-        }
-
-        // 2. Storage write (persistence)
-        let storage_payload = StoragePayload::new(
-            collection,
-            &doc_id,
-            &self.default_schema_id,
-            &self.default_schema_version,
-            body_bytes,
-        );
-
-        let offset = {
-            let mut storage = self.storage_writer.lock().map_err(|e| e.to_string())?;
-            storage
-                .write(&storage_payload)
-                .map_err(|e| format!("Storage write failed: {}", e))?
-        };
-
-        // 3. Index update (optional)
-        if let Some(ref index_mutex) = self.index_manager {
-            let doc_info = DocumentInfo {
-                document_id: doc_id.clone(),
-                schema_id: self.default_schema_id.clone(),
-                schema_version: self.default_schema_version.clone(),
-                is_tombstone: false,
-                body: document.clone(),
-                offset,
-            };
-            let mut index = index_mutex.lock().map_err(|e| e.to_string())?;
-            index.apply_write(&doc_info);
-        }
-
-        // 4. Cache update (performance)
-        {
-            let mut cache = self.cache.write().map_err(|e| e.to_string())?;
-            cache
-                .entry(collection.to_string())
-                .or_default()
-                .insert(doc_id.clone(), document);
-        }
-
-        Ok(doc_id)
+    fn write(&self, collection: &str, document: Value) -> Result<String, String> {
+        let mut ids = self.write_many(collection, vec![document])?;
+        Ok(ids.remove(0))
     }
 
     fn update(&self, collection: &str, id: &str, updates: Value) -> Result<Value, String> {
+        let mut index = self.lock_index()?;
+
         // Read current document
         let current = self
             .read(collection, id)?
@@ -221,6 +275,13 @@ impl StorageBackend for WriteThroughBackend {
         let body_bytes = serde_json::to_vec(&updated)
             .map_err(|e| format!("Failed to serialize document: {}", e))?;
 
+        // 0. Unique check (nothing is durable yet)
+        if let Some(ref index) = index {
+            index
+                .check_unique(id, &updated)
+                .map_err(|e| e.to_string())?;
+        }
+
         // 1. WAL append
         let wal_payload = WalPayload::new(
             collection,
@@ -253,7 +314,7 @@ impl StorageBackend for WriteThroughBackend {
         };
 
         // 3. Index update
-        if let Some(ref index_mutex) = self.index_manager {
+        if let Some(ref mut index) = index {
             let doc_info = DocumentInfo {
                 document_id: id.to_string(),
                 schema_id: self.default_schema_id.clone(),
@@ -262,7 +323,6 @@ impl StorageBackend for WriteThroughBackend {
                 body: updated.clone(),
                 offset,
             };
-            let mut index = index_mutex.lock().map_err(|e| e.to_string())?;
             index.apply_write(&doc_info);
         }
 
@@ -279,6 +339,8 @@ impl StorageBackend for WriteThroughBackend {
     }
 
     fn delete(&self, collection: &str, id: &str) -> Result<bool, String> {
+        let mut index = self.lock_index()?;
+
         // Check if exists
         let exists = self.read(collection, id)?.is_some();
         if !exists {
@@ -313,7 +375,7 @@ impl StorageBackend for WriteThroughBackend {
         };
 
         // 3. Index update
-        if let Some(ref index_mutex) = self.index_manager {
+        if let Some(ref mut index) = index {
             let doc_info = DocumentInfo {
                 document_id: id.to_string(),
                 schema_id: self.default_schema_id.clone(),
@@ -322,7 +384,6 @@ impl StorageBackend for WriteThroughBackend {
                 body: serde_json::json!({}),
                 offset,
             };
-            let mut index = index_mutex.lock().map_err(|e| e.to_string())?;
             index.apply_write(&doc_info);
         }
 
@@ -359,6 +420,7 @@ impl StorageBackend for WriteThroughBackend {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::index::{IndexResult, StorageScan};
     use tempfile::TempDir;
 
     fn setup_backend(temp_dir: &TempDir) -> WriteThroughBackend {
@@ -454,4 +516,141 @@ mod tests {
         let result = backend2.read("users", &doc_id).unwrap();
         assert!(result.is_none());
     }
+
+    struct EmptyScan;
+
+    impl StorageScan for EmptyScan {
+        fn scan_next(&mut self) -> IndexResult<Option<DocumentInfo>> {
+            Ok(None)
+        }
+
+        fn reset(&mut self) -> IndexResult<()> {
+            Ok(())
+        }
+
+        fn current_offset(&self) -> u64 {
+            0
+        }
+    }
+
+    fn setup_unique_backend(temp_dir: &TempDir) -> WriteThroughBackend {
+        let mut index = IndexManager::pk_only();
+        index
+            .create_unique_index("users_email", "email", &mut EmptyScan)
+            .unwrap();
+        setup_backend(temp_dir).with_index(index)
+    }
+
+    fn dir_len(path: &Path) -> u64 {
+        std::fs::read_dir(path)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let meta = entry.metadata().unwrap();
+                if meta.is_dir() {
+                    dir_len(&entry.path())
+                } else {
+                    meta.len()
+                }
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_write_rejects_unique_violation_before_wal() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = setup_unique_backend(&temp_dir);
+
+        backend
+            .write("users", serde_json::json!({"_id": "u1", "email": "a@x.io"}))
+            .unwrap();
+        let before = dir_len(temp_dir.path());
+
+        let err = backend
+            .write("users", serde_json::json!({"_id": "u2", "email": "a@x.io"}))
+            .unwrap_err();
+        assert!(err.contains("AERO_INDEX_UNIQUE_VIOLATION"));
+        assert!(err.contains("users_email"));
+        assert_eq!(dir_len(temp_dir.path()), before);
+        assert!(backend.read("users", "u2").unwrap().is_none());
+
+        // Updating into another document's value is rejected too
+        backend
+            .write("users", serde_json::json!({"_id": "u3", "email": "c@x.io"}))
+            .unwrap();
+        let err = backend
+            .update("users", "u3", serde_json::json!({"email": "a@x.io"}))
+            .unwrap_err();
+        assert!(err.contains("AERO_INDEX_UNIQUE_VIOLATION"));
+        assert_eq!(
+            backend.read("users", "u3").unwrap().unwrap()["email"],
+            "c@x.io"
+        );
+    }
+
+    #[test]
+    fn test_write_many_failing_batch_changes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = setup_unique_backend(&temp_dir);
+
+        backend
+            .write("users", serde_json::json!({"_id": "u1", "email": "a@x.io"}))
+            .unwrap();
+        let before = dir_len(temp_dir.path());
+
+        // The last document conflicts; the first two must not land either
+        let batch = vec![
+            serde_json::json!({"_id": "u2", "email": "b@x.io"}),
+            serde_json::json!({"_id": "u3", "email": "c@x.io"}),
+            serde_json::json!({"_id": "u4", "email": "a@x.io"}),
+        ];
+        let err = backend.write_many("users", batch).unwrap_err();
+        assert!(err.contains("AERO_INDEX_UNIQUE_VIOLATION"));
+
+        assert_eq!(dir_len(temp_dir.path()), before);
+        for id in ["u2", "u3", "u4"] {
+            assert!(backend.read("users", id).unwrap().is_none());
+        }
+
+        // Index was not touched: the batch's values are still free
+        let ids = backend
+            .write_many(
+                "users",
+                vec![
+                    serde_json::json!({"_id": "u2", "email": "b@x.io"}),
+                    serde_json::json!({"_id": "u3", "email": "c@x.io"}),
+                ],
+            )
+            .unwrap();
+        assert_eq!(ids, vec!["u2", "u3"]);
+    }
+
+    #[test]
+    fn test_concurrent_inserts_of_same_unique_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = setup_unique_backend(&temp_dir);
+
+        let results: Vec<Result<String, String>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    scope
+                        .spawn(|| backend.write("users", serde_json::json!({"email": "same@x.io"})))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let winners: Vec<&String> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+        assert_eq!(winners.len(), 1);
+        for err in results.iter().filter_map(|r| r.as_ref().err()) {
+            assert!(err.contains("AERO_INDEX_UNIQUE_VIOLATION"));
+        }
+
+        // Only the winner reached storage
+        drop(backend);
+        let reopened = WriteThroughBackend::open(temp_dir.path(), "users").unwrap();
+        let docs = reopened.query("users", None, 100, 0).unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0]["_id"], winners[0].as_str());
+    }
 }
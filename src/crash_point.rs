@@ -68,6 +68,7 @@ pub mod points {
     // WAL crash points
     pub const WAL_BEFORE_APPEND: &str = "wal_before_append";
     pub const WAL_AFTER_APPEND: &str = "wal_after_append";
+    pub const WAL_AFTER_APPEND_BEFORE_FSYNC: &str = "wal_after_append_before_fsync";
     pub const WAL_BEFORE_FSYNC: &str = "wal_before_fsync";
     pub const WAL_AFTER_FSYNC: &str = "wal_after_fsync";
     pub const WAL_BEFORE_TRUNCATE: &str = "wal_before_truncate";
@@ -76,12 +77,14 @@ pub mod points {
     // Storage crash points
     pub const STORAGE_BEFORE_WRITE: &str = "storage_before_write";
     pub const STORAGE_AFTER_WRITE: &str = "storage_after_write";
+    pub const STORAGE_AFTER_APPLY: &str = "storage_after_apply";
     pub const STORAGE_BEFORE_CHECKSUM: &str = "storage_before_checksum";
     pub const STORAGE_AFTER_CHECKSUM: &str = "storage_after_checksum";
 
     // Snapshot crash points
     pub const SNAPSHOT_START: &str = "snapshot_start";
     pub const SNAPSHOT_AFTER_STORAGE_COPY: &str = "snapshot_after_storage_copy";
+    pub const SNAPSHOT_AFTER_COPY_BEFORE_MANIFEST: &str = "snapshot_after_copy_before_manifest";
     pub const SNAPSHOT_BEFORE_MANIFEST: &str = "snapshot_before_manifest";
     pub const SNAPSHOT_AFTER_MANIFEST: &str = "snapshot_after_manifest";
 
@@ -96,6 +99,7 @@ pub mod points {
     pub const BACKUP_AFTER_SNAPSHOT_COPY: &str = "backup_after_snapshot_copy";
     pub const BACKUP_AFTER_WAL_COPY: &str = "backup_after_wal_copy";
     pub const BACKUP_BEFORE_ARCHIVE: &str = "backup_before_archive";
+    pub const BACKUP_BEFORE_ARCHIVE_FSYNC: &str = "backup_before_archive_fsync";
 
     // Restore crash points
     pub const RESTORE_START: &str = "restore_start";
@@ -108,6 +112,13 @@ pub mod points {
     pub const RECOVERY_AFTER_WAL_REPLAY: &str = "recovery_after_wal_replay";
     pub const RECOVERY_AFTER_INDEX_REBUILD: &str = "recovery_after_index_rebuild";
 
+    // Migration crash points
+    pub const MIGRATION_AFTER_RECORD_START: &str = "migration_after_record_start";
+
+    // Collection rename crash points
+    pub const COLLECTION_RENAME_AFTER_SCHEMA_COPY: &str = "collection_rename_after_schema_copy";
+    pub const COLLECTION_RENAME_MID_APPLY: &str = "collection_rename_mid_apply";
+
     // MVCC crash points per MVCC_FAILURE_MATRIX.md
     pub const MVCC_BEFORE_COMMIT_RECORD: &str = "mvcc_before_commit_record";
     pub const MVCC_AFTER_COMMIT_RECORD: &str = "mvcc_after_commit_record";
@@ -123,16 +134,19 @@ pub mod points {
         &[
             WAL_BEFORE_APPEND,
             WAL_AFTER_APPEND,
+            WAL_AFTER_APPEND_BEFORE_FSYNC,
             WAL_BEFORE_FSYNC,
             WAL_AFTER_FSYNC,
             WAL_BEFORE_TRUNCATE,
             WAL_AFTER_TRUNCATE,
             STORAGE_BEFORE_WRITE,
             STORAGE_AFTER_WRITE,
+            STORAGE_AFTER_APPLY,
             STORAGE_BEFORE_CHECKSUM,
             STORAGE_AFTER_CHECKSUM,
             SNAPSHOT_START,
             SNAPSHOT_AFTER_STORAGE_COPY,
+            SNAPSHOT_AFTER_COPY_BEFORE_MANIFEST,
             SNAPSHOT_BEFORE_MANIFEST,
             SNAPSHOT_AFTER_MANIFEST,
             CHECKPOINT_START,
@@ -143,6 +157,7 @@ pub mod points {
             BACKUP_AFTER_SNAPSHOT_COPY,
             BACKUP_AFTER_WAL_COPY,
             BACKUP_BEFORE_ARCHIVE,
+            BACKUP_BEFORE_ARCHIVE_FSYNC,
             RESTORE_START,
             RESTORE_AFTER_EXTRACT,
             RESTORE_BEFORE_REPLACE,
@@ -150,6 +165,9 @@ pub mod points {
             RECOVERY_START,
             RECOVERY_AFTER_WAL_REPLAY,
             RECOVERY_AFTER_INDEX_REBUILD,
+            MIGRATION_AFTER_RECORD_START,
+            COLLECTION_RENAME_AFTER_SCHEMA_COPY,
+            COLLECTION_RENAME_MID_APPLY,
             MVCC_BEFORE_COMMIT_RECORD,
             MVCC_AFTER_COMMIT_RECORD,
             MVCC_AFTER_COMMIT_FSYNC,
@@ -173,11 +191,12 @@ mod tests {
     #[test]
     fn test_all_crash_points_defined() {
         let all = points::all();
-        assert_eq!(all.len(), 35);
+        assert_eq!(all.len(), 42);
 
         // Verify WAL points
         assert!(all.contains(&"wal_before_append"));
         assert!(all.contains(&"wal_after_fsync"));
+        assert!(all.contains(&"wal_after_append_before_fsync"));
 
         // Verify snapshot points
         assert!(all.contains(&"snapshot_start"));
@@ -193,6 +212,12 @@ mod tests {
 
         // Verify recovery points
         assert!(all.contains(&"recovery_start"));
+
+        // Verify migration points
+        assert!(all.contains(&"migration_after_record_start"));
+
+        // Verify collection rename points
+        assert!(all.contains(&"collection_rename_mid_apply"));
     }
 
     #[test]
@@ -17,6 +17,10 @@ pub enum DangerousOperation {
     TruncateCollection,
     /// Drop a collection entirely
     DropCollection,
+    /// Rename a collection, moving its documents and schema
+    RenameCollection,
+    /// Copy a collection's documents and schema to a new name
+    CopyCollection,
     /// Delete a schema
     DeleteSchema,
     /// Force failover
@@ -37,6 +41,8 @@ impl DangerousOperation {
         match self {
             DangerousOperation::TruncateCollection => "Delete all documents in the collection",
             DangerousOperation::DropCollection => "Permanently delete the collection and all its data",
+            DangerousOperation::RenameCollection => "Rename the collection and move its documents",
+            DangerousOperation::CopyCollection => "Copy the collection and its documents",
             DangerousOperation::DeleteSchema => "Delete the schema definition",
             DangerousOperation::ForceFailover => "Force a failover to a replica",
             DangerousOperation::ResetWal => "Reset the write-ahead log (may cause data loss)",
@@ -53,6 +59,10 @@ impl DangerousOperation {
                 "WARNING: This will permanently delete ALL documents in this collection. This action cannot be undone.",
             DangerousOperation::DropCollection => 
                 "WARNING: This will permanently delete the collection and all its data. This action cannot be undone.",
+            DangerousOperation::RenameCollection => 
+                "WARNING: Clients still using the old collection name will fail once it is renamed.",
+            DangerousOperation::CopyCollection => 
+                "WARNING: Copying writes every document of the collection again.",
             DangerousOperation::DeleteSchema => 
                 "WARNING: Deleting the schema will also delete all collections using this schema.",
             DangerousOperation::ForceFailover => 
@@ -75,6 +85,8 @@ impl DangerousOperation {
             DangerousOperation::FactoryReset
                 | DangerousOperation::ResetWal
                 | DangerousOperation::DropCollection
+                | DangerousOperation::RenameCollection
+                | DangerousOperation::CopyCollection
         )
     }
 
@@ -84,6 +96,8 @@ impl DangerousOperation {
             DangerousOperation::FactoryReset => "delete everything".to_string(),
             DangerousOperation::ResetWal => "reset wal".to_string(),
             DangerousOperation::DropCollection => format!("drop {}", resource_name),
+            DangerousOperation::RenameCollection => format!("rename {}", resource_name),
+            DangerousOperation::CopyCollection => format!("copy {}", resource_name),
             _ => "confirm".to_string(),
         }
     }
@@ -93,6 +107,7 @@ impl DangerousOperation {
         match self {
             DangerousOperation::FactoryReset | DangerousOperation::ResetWal => "CRITICAL",
             DangerousOperation::DropCollection 
+            | DangerousOperation::RenameCollection
             | DangerousOperation::TruncateCollection 
             | DangerousOperation::RestoreBackup => "WARNING",
             _ => "INFO",
@@ -11,13 +11,16 @@
 //! 3. Control Commands (mutating, high-risk)
 
 use std::fmt;
+
+use serde::Serialize;
 use uuid::Uuid;
 
 /// All Phase 7 control plane commands.
 ///
 /// Per PHASE7_COMMAND_MODEL.md §3:
 /// If a command is not defined in this enum, it must not exist.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
 pub enum ControlPlaneCommand {
     /// Read-only inspection command.
     Inspection(InspectionCommand),
@@ -47,6 +50,16 @@ impl ControlPlaneCommand {
         matches!(self, ControlPlaneCommand::Control(_))
     }
 
+    /// The full command, name and arguments, as a confirmation token binds
+    /// it: two commands have the same payload only if they are equal.
+    ///
+    /// Canonical JSON, `{"command": <command_name>, <argument>: ...}` with
+    /// arguments in declaration order, so the payload does not change with
+    /// the `Debug` output of the types it contains.
+    pub fn payload(&self) -> String {
+        serde_json::to_string(self).expect("command payload serializes")
+    }
+
     /// Returns the command name for audit logging.
     pub fn command_name(&self) -> &'static str {
         match self {
@@ -61,7 +74,8 @@ impl ControlPlaneCommand {
 ///
 /// Per PHASE7_COMMAND_MODEL.md §4:
 /// Inspection commands MUST NOT mutate any kernel state.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
 pub enum InspectionCommand {
     /// Retrieve current cluster topology and roles.
     InspectClusterState,
@@ -74,6 +88,21 @@ pub enum InspectionCommand {
 
     /// View current promotion/demotion state machine status.
     InspectPromotionState,
+
+    /// View planner statistics for each collection.
+    InspectStatistics,
+
+    /// View a tenant's quotas and metered usage.
+    InspectTenantUsage { tenant_id: Uuid },
+
+    /// List a tenant's invoices.
+    ListInvoices { tenant_id: Uuid },
+
+    /// View a tenant's invoice for one billing period (YYYY-MM).
+    GetInvoice { tenant_id: Uuid, period: String },
+
+    /// View each scheduled job's last and next run.
+    InspectScheduledJobs,
 }
 
 impl InspectionCommand {
@@ -84,6 +113,11 @@ impl InspectionCommand {
             InspectionCommand::InspectNode { .. } => "inspect_node",
             InspectionCommand::InspectReplicationStatus => "inspect_replication_status",
             InspectionCommand::InspectPromotionState => "inspect_promotion_state",
+            InspectionCommand::InspectStatistics => "inspect_statistics",
+            InspectionCommand::InspectTenantUsage { .. } => "inspect_tenant_usage",
+            InspectionCommand::ListInvoices { .. } => "list_invoices",
+            InspectionCommand::GetInvoice { .. } => "get_invoice",
+            InspectionCommand::InspectScheduledJobs => "inspect_scheduled_jobs",
         }
     }
 }
@@ -92,7 +126,8 @@ impl InspectionCommand {
 ///
 /// Per PHASE7_COMMAND_MODEL.md §5:
 /// Diagnostics are read-only but may be disruptive or expensive.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
 pub enum DiagnosticCommand {
     /// Collect kernel diagnostic information.
     /// Requires confirmation due to potential cost.
@@ -103,6 +138,12 @@ pub enum DiagnosticCommand {
 
     /// Inspect available snapshots and checkpoints.
     InspectSnapshots,
+
+    /// List crash reports, dumping the one named `id` if given.
+    InspectCrashReports { id: Option<String> },
+
+    /// Inspect the audit log, walking its hash chain if `verify`.
+    InspectAuditLog { verify: bool },
 }
 
 impl DiagnosticCommand {
@@ -112,6 +153,8 @@ impl DiagnosticCommand {
             DiagnosticCommand::RunDiagnostics => "run_diagnostics",
             DiagnosticCommand::InspectWal => "inspect_wal",
             DiagnosticCommand::InspectSnapshots => "inspect_snapshots",
+            DiagnosticCommand::InspectCrashReports { .. } => "inspect_crash_reports",
+            DiagnosticCommand::InspectAuditLog { .. } => "inspect_audit_log",
         }
     }
 
@@ -128,7 +171,8 @@ impl DiagnosticCommand {
 ///
 /// Per PHASE7_COMMAND_MODEL.md §6:
 /// Control commands mutate kernel state and are strictly regulated.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
 pub enum ControlCommand {
     /// Request promotion of a replica to primary.
     /// Confirmation required: Yes (mandatory).
@@ -251,4 +295,29 @@ mod tests {
             "request_promotion"
         );
     }
+
+    #[test]
+    fn test_payload_is_canonical_json() {
+        let cmd = ControlPlaneCommand::Control(ControlCommand::RequestPromotion {
+            replica_id: Uuid::nil(),
+            reason: Some("maintenance".to_string()),
+        });
+        assert_eq!(
+            cmd.payload(),
+            "{\"command\":\"request_promotion\",\
+             \"replica_id\":\"00000000-0000-0000-0000-000000000000\",\
+             \"reason\":\"maintenance\"}"
+        );
+        assert_eq!(
+            ControlPlaneCommand::Inspection(InspectionCommand::InspectClusterState).payload(),
+            "{\"command\":\"inspect_cluster_state\"}"
+        );
+
+        // Every argument is part of the payload
+        let other = ControlPlaneCommand::Control(ControlCommand::RequestPromotion {
+            replica_id: Uuid::nil(),
+            reason: None,
+        });
+        assert_ne!(cmd.payload(), other.payload());
+    }
 }
@@ -6,17 +6,28 @@
 //! - Override commands require enhanced confirmation
 //!
 //! Confirmation is a SAFETY BOUNDARY, not a usability feature.
+//!
+//! A token is bound to the exact command payload it was issued for: the
+//! command name, its target and every argument. Confirming with a token
+//! that is unknown, expired, already used, or issued for a different
+//! payload aborts with a distinct `ConfirmationStatus`.
 
 use std::fmt;
 use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+/// How long a confirmation token stays valid by default (5 minutes).
+pub const DEFAULT_CONFIRMATION_TTL: Duration = Duration::from_secs(300);
+
 /// Confirmation token — ephemeral, non-reusable proof of intent.
 ///
 /// Per PHASE7_CONFIRMATION_MODEL.md §4:
 /// - Confirmation must be contemporaneous
 /// - Confirmation cannot be reused
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ConfirmationToken {
     /// Unique token ID.
     id: Uuid,
@@ -27,6 +38,9 @@ pub struct ConfirmationToken {
     /// Target ID (node/replica) for the command.
     target_id: Option<Uuid>,
 
+    /// Hex SHA-256 of the command payload this token confirms.
+    payload_digest: String,
+
     /// When this token was created.
     created_at: SystemTime,
 
@@ -37,10 +51,20 @@ pub struct ConfirmationToken {
 impl ConfirmationToken {
     /// Create a new confirmation token for a command.
     pub fn new(command_name: impl Into<String>, target_id: Option<Uuid>) -> Self {
+        Self::for_payload(command_name, target_id, "")
+    }
+
+    /// Create a token confirming exactly `payload`.
+    pub fn for_payload(
+        command_name: impl Into<String>,
+        target_id: Option<Uuid>,
+        payload: &str,
+    ) -> Self {
         Self {
             id: Uuid::new_v4(),
             command_name: command_name.into(),
             target_id,
+            payload_digest: payload_digest(payload),
             created_at: SystemTime::now(),
             consumed: false,
         }
@@ -64,6 +88,11 @@ impl ConfirmationToken {
         !self.consumed && self.command_name == command_name && self.target_id == target_id
     }
 
+    /// Check if this token was issued for exactly `payload`.
+    pub fn matches_payload(&self, payload: &str) -> bool {
+        self.payload_digest == payload_digest(payload)
+    }
+
     /// Check if this token has expired.
     ///
     /// Tokens are ephemeral and expire after a short period.
@@ -93,6 +122,10 @@ impl ConfirmationToken {
     }
 }
 
+fn payload_digest(payload: &str) -> String {
+    hex::encode(Sha256::digest(payload.as_bytes()))
+}
+
 /// Enhanced confirmation for override commands.
 ///
 /// Per PHASE7_CONFIRMATION_MODEL.md §8:
@@ -168,6 +201,12 @@ pub enum ConfirmationStatus {
 
     /// Token was already consumed.
     AlreadyConsumed,
+
+    /// No such token was issued.
+    NotFound,
+
+    /// Token was issued for a different command or payload.
+    Mismatch,
 }
 
 impl fmt::Display for ConfirmationStatus {
@@ -178,6 +217,8 @@ impl fmt::Display for ConfirmationStatus {
             ConfirmationStatus::Rejected => write!(f, "REJECTED"),
             ConfirmationStatus::Expired => write!(f, "EXPIRED"),
             ConfirmationStatus::AlreadyConsumed => write!(f, "ALREADY_CONSUMED"),
+            ConfirmationStatus::NotFound => write!(f, "NOT_FOUND"),
+            ConfirmationStatus::Mismatch => write!(f, "MISMATCH"),
         }
     }
 }
@@ -189,31 +230,63 @@ pub enum ConfirmationResult {
     Proceed { token_id: Uuid },
 
     /// Confirmation rejected or failed.
-    Abort { reason: String },
+    Abort {
+        status: ConfirmationStatus,
+        reason: String,
+    },
 }
 
 /// Confirmation flow manager.
 ///
 /// Per PHASE7_STATE_MODEL.md §6:
 /// Confirmation state is ephemeral and must not survive restarts.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct ConfirmationFlow {
-    /// Pending confirmation tokens (ephemeral, in-memory only).
+    /// Issued tokens (ephemeral, in-memory only). Consumed tokens are kept
+    /// until they expire so that reuse is reported as such.
     pending_tokens: Vec<ConfirmationToken>,
 
     /// Maximum age for confirmation tokens.
     max_token_age: Duration,
 }
 
+impl Default for ConfirmationFlow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ConfirmationFlow {
     /// Create a new confirmation flow manager.
     pub fn new() -> Self {
         Self {
             pending_tokens: Vec::new(),
-            max_token_age: Duration::from_secs(300), // 5 minutes
+            max_token_age: DEFAULT_CONFIRMATION_TTL,
         }
     }
 
+    /// Expire tokens `max_token_age` after they are issued.
+    pub fn with_max_token_age(mut self, max_token_age: Duration) -> Self {
+        self.max_token_age = max_token_age;
+        self
+    }
+
+    /// Resume with tokens issued earlier.
+    ///
+    /// Lets a control plane that runs one process per command (the CLI)
+    /// hand tokens from the requesting process to the confirming one.
+    /// Expired tokens are kept so that confirming with one reports
+    /// `Expired`; the next `request_confirmation` sweeps them.
+    pub fn with_tokens(mut self, tokens: Vec<ConfirmationToken>) -> Self {
+        self.pending_tokens = tokens;
+        self
+    }
+
+    /// Issued tokens not yet swept, consumed and expired ones included.
+    pub fn tokens(&self) -> &[ConfirmationToken] {
+        &self.pending_tokens
+    }
+
     /// Create a confirmation token for a command.
     ///
     /// Per PHASE7_CONFIRMATION_MODEL.md §5:
@@ -224,8 +297,10 @@ impl ConfirmationFlow {
         &mut self,
         command_name: impl Into<String>,
         target_id: Option<Uuid>,
+        payload: &str,
     ) -> ConfirmationToken {
-        let token = ConfirmationToken::new(command_name, target_id);
+        self.cleanup_expired();
+        let token = ConfirmationToken::for_payload(command_name, target_id, payload);
         self.pending_tokens.push(token.clone());
         token
     }
@@ -239,45 +314,43 @@ impl ConfirmationFlow {
         token_id: Uuid,
         command_name: &str,
         target_id: Option<Uuid>,
+        payload: &str,
     ) -> ConfirmationResult {
+        let abort = |status, reason: &str| ConfirmationResult::Abort {
+            status,
+            reason: reason.to_string(),
+        };
+
         // Find and validate the token
-        let token_idx = self.pending_tokens.iter().position(|t| t.id() == token_id);
-
-        match token_idx {
-            None => ConfirmationResult::Abort {
-                reason: "Confirmation token not found".to_string(),
-            },
-            Some(idx) => {
-                let token = &mut self.pending_tokens[idx];
-
-                // Check expiry
-                if token.is_expired(self.max_token_age) {
-                    self.pending_tokens.remove(idx);
-                    return ConfirmationResult::Abort {
-                        reason: "Confirmation token expired".to_string(),
-                    };
-                }
-
-                // Check validity for this command
-                if !token.is_valid_for(command_name, target_id) {
-                    return ConfirmationResult::Abort {
-                        reason: "Confirmation token does not match command".to_string(),
-                    };
-                }
-
-                // Consume the token
-                if !token.consume() {
-                    return ConfirmationResult::Abort {
-                        reason: "Confirmation token already consumed".to_string(),
-                    };
-                }
-
-                let id = token.id();
-                self.pending_tokens.remove(idx);
-
-                ConfirmationResult::Proceed { token_id: id }
-            }
+        let Some(idx) = self.pending_tokens.iter().position(|t| t.id() == token_id) else {
+            return abort(ConfirmationStatus::NotFound, "Confirmation token not found");
+        };
+        let token = &mut self.pending_tokens[idx];
+
+        // Check expiry
+        if token.is_expired(self.max_token_age) {
+            self.pending_tokens.remove(idx);
+            return abort(ConfirmationStatus::Expired, "Confirmation token expired");
+        }
+
+        if token.is_consumed() {
+            return abort(
+                ConfirmationStatus::AlreadyConsumed,
+                "Confirmation token already consumed",
+            );
+        }
+
+        // Check validity for this exact command
+        if !token.is_valid_for(command_name, target_id) || !token.matches_payload(payload) {
+            return abort(
+                ConfirmationStatus::Mismatch,
+                "Confirmation token does not match command",
+            );
         }
+
+        // Consume the token
+        token.consume();
+        ConfirmationResult::Proceed { token_id }
     }
 
     /// Reject/cancel a pending confirmation.
@@ -349,12 +422,12 @@ mod tests {
         let mut flow = ConfirmationFlow::new();
         let target = Uuid::new_v4();
 
-        let token = flow.request_confirmation("request_promotion", Some(target));
+        let token = flow.request_confirmation("request_promotion", Some(target), "promote");
         let token_id = token.id();
 
-        match flow.confirm(token_id, "request_promotion", Some(target)) {
+        match flow.confirm(token_id, "request_promotion", Some(target), "promote") {
             ConfirmationResult::Proceed { .. } => {}
-            ConfirmationResult::Abort { reason } => {
+            ConfirmationResult::Abort { reason, .. } => {
                 panic!("Expected proceed, got abort: {}", reason);
             }
         }
@@ -365,19 +438,96 @@ mod tests {
         let mut flow = ConfirmationFlow::new();
         let target = Uuid::new_v4();
 
-        let token = flow.request_confirmation("request_promotion", Some(target));
+        let token = flow.request_confirmation("request_promotion", Some(target), "promote");
         let token_id = token.id();
 
         // First confirm succeeds
         assert!(matches!(
-            flow.confirm(token_id, "request_promotion", Some(target)),
+            flow.confirm(token_id, "request_promotion", Some(target), "promote"),
+            ConfirmationResult::Proceed { .. }
+        ));
+
+        // Second confirm fails (token consumed)
+        assert!(matches!(
+            flow.confirm(token_id, "request_promotion", Some(target), "promote"),
+            ConfirmationResult::Abort {
+                status: ConfirmationStatus::AlreadyConsumed,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_confirmation_bound_to_payload() {
+        let mut flow = ConfirmationFlow::new();
+        let target = Uuid::new_v4();
+        let token_id = flow
+            .request_confirmation("force_promotion", Some(target), "risks=a")
+            .id();
+
+        // A tampered payload is refused and leaves the token usable
+        assert!(matches!(
+            flow.confirm(token_id, "force_promotion", Some(target), "risks=a,b"),
+            ConfirmationResult::Abort {
+                status: ConfirmationStatus::Mismatch,
+                ..
+            }
+        ));
+        assert!(matches!(
+            flow.confirm(token_id, "force_promotion", Some(target), "risks=a"),
             ConfirmationResult::Proceed { .. }
         ));
+        assert!(matches!(
+            flow.confirm(Uuid::new_v4(), "force_promotion", Some(target), "risks=a"),
+            ConfirmationResult::Abort {
+                status: ConfirmationStatus::NotFound,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_confirmation_expires_after_ttl() {
+        let mut flow = ConfirmationFlow::new().with_max_token_age(Duration::ZERO);
+        let token_id = flow.request_confirmation("request_demotion", None, "").id();
+        std::thread::sleep(Duration::from_millis(2));
 
-        // Second confirm fails (token consumed/removed)
         assert!(matches!(
-            flow.confirm(token_id, "request_promotion", Some(target)),
-            ConfirmationResult::Abort { .. }
+            flow.confirm(token_id, "request_demotion", None, ""),
+            ConfirmationResult::Abort {
+                status: ConfirmationStatus::Expired,
+                ..
+            }
+        ));
+        assert!(flow.tokens().is_empty());
+    }
+
+    #[test]
+    fn test_tokens_carry_over_between_flows() {
+        let mut first = ConfirmationFlow::new();
+        let token_id = first
+            .request_confirmation("request_demotion", None, "")
+            .id();
+        let tokens: Vec<ConfirmationToken> =
+            serde_json::from_str(&serde_json::to_string(first.tokens()).unwrap()).unwrap();
+
+        let mut second = ConfirmationFlow::new().with_tokens(tokens.clone());
+        assert!(matches!(
+            second.confirm(token_id, "request_demotion", None, ""),
+            ConfirmationResult::Proceed { .. }
+        ));
+
+        // A token that expired in between is reported as expired
+        let mut late = ConfirmationFlow::new()
+            .with_max_token_age(Duration::ZERO)
+            .with_tokens(tokens);
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(matches!(
+            late.confirm(token_id, "request_demotion", None, ""),
+            ConfirmationResult::Abort {
+                status: ConfirmationStatus::Expired,
+                ..
+            }
         ));
     }
 }
@@ -13,6 +13,8 @@
 
 use std::fmt;
 
+use super::confirmation::ConfirmationStatus;
+
 /// Phase 7 error domain classification.
 ///
 /// Per PHASE7_ERROR_MODEL.md §3:
@@ -188,6 +190,43 @@ impl ControlPlaneError {
         }
     }
 
+    /// Create an error for a confirmation token that was never issued.
+    pub fn confirmation_not_found() -> Self {
+        Self {
+            domain: ControlPlaneErrorDomain::ValidationError,
+            code: "PHASE7_CONFIRMATION_NOT_FOUND".to_string(),
+            message: "Confirmation token not found; request a new confirmation".to_string(),
+            invariant: Some("P7-A2".to_string()),
+            outcome: ExecutionOutcome::NotExecuted,
+        }
+    }
+
+    /// Create an error for a token issued for a different command payload.
+    ///
+    /// Per PHASE7_CONFIRMATION_MODEL.md §4.3:
+    /// Confirmation must apply to exactly one command.
+    pub fn confirmation_mismatch() -> Self {
+        Self {
+            domain: ControlPlaneErrorDomain::ValidationError,
+            code: "PHASE7_CONFIRMATION_MISMATCH".to_string(),
+            message: "Confirmation token was issued for a different command or arguments"
+                .to_string(),
+            invariant: Some("PHASE7_CONFIRMATION_MODEL.md §4.3".to_string()),
+            outcome: ExecutionOutcome::NotExecuted,
+        }
+    }
+
+    /// Create the error for a confirmation that aborted with `status`.
+    pub fn from_confirmation_status(status: ConfirmationStatus, command: &str) -> Self {
+        match status {
+            ConfirmationStatus::Expired => Self::confirmation_expired(),
+            ConfirmationStatus::AlreadyConsumed => Self::confirmation_reused(),
+            ConfirmationStatus::Mismatch => Self::confirmation_mismatch(),
+            ConfirmationStatus::NotFound => Self::confirmation_not_found(),
+            _ => Self::missing_confirmation(command),
+        }
+    }
+
     /// Create an error for incomplete enhanced confirmation.
     pub fn incomplete_enhanced_confirmation() -> Self {
         Self {
@@ -17,13 +17,20 @@ use super::commands::{ControlCommand, ControlPlaneCommand, DiagnosticCommand, In
 use super::confirmation::{ConfirmationFlow, ConfirmationResult, ConfirmationToken};
 use super::errors::{ControlPlaneError, ControlPlaneResult};
 use super::types::{
-    ClusterState, CommandOutcome, CommandRequest, CommandResponse, CommandResponseData,
-    DiagnosticResult, DiagnosticSection, NodeHealth, NodeRole, NodeState, PromotionResultData,
-    PromotionStateView, ReplicaState, ReplicationStatus, SnapshotInfo, WalInfo,
+    AuditLogInfo, ClusterState, CollectionStatisticsView, CommandRequest, CommandResponse,
+    CommandResponseData, CrashReportInfo, DiagnosticResult, DiagnosticSection, InvoiceListView,
+    NodeHealth, NodeRole, NodeState, PlannerStatisticsView, PromotionResultData,
+    PromotionStateView, ReplicaState, ReplicationStatus, ScheduledJobView, ScheduledJobsView,
+    SnapshotInfo, SnapshotIntegrity, SyncReplicationView, TenantUsageView, WalInfo,
 };
 
+use crate::checkpoint::CheckpointStatus;
+use crate::control_plane::{Quotas, TenantQuotas, TenantRegistry, UsageMetrics};
+use crate::observability::audit::ChainReport;
+use crate::panic_handler::StoredCrashReport;
+use crate::planner::Statistics;
 use crate::promotion::{PromotionController, PromotionState};
-use crate::replication::ReplicationState;
+use crate::replication::{ReplicaLag, ReplicationState};
 
 /// Kernel Adapter trait for accessing kernel subsystems.
 ///
@@ -46,12 +53,45 @@ pub trait KernelAdapter: Send + Sync {
     /// Get WAL size in bytes
     fn get_wal_size_bytes(&self) -> u64;
 
+    /// Get progress of the running and last checkpoint (None if not loaded)
+    fn get_checkpoint_status(&self) -> Option<CheckpointStatus>;
+
     /// Get list of active snapshots
     fn get_snapshots(&self) -> Vec<(u64, SystemTime)>;
 
     /// Get list of checkpoints
     fn get_checkpoints(&self) -> Vec<(u64, SystemTime)>;
 
+    /// Get the verified integrity of snapshots on disk
+    fn get_snapshot_integrity(&self) -> Vec<SnapshotIntegrity>;
+
+    /// Get crash reports on disk, oldest first
+    fn get_crash_reports(&self) -> Vec<StoredCrashReport>;
+
+    /// Get the walked hash chain of the audit log (None if not loaded)
+    fn get_audit_chain(&self) -> Option<ChainReport>;
+
+    /// Get planner statistics (None if not loaded)
+    fn get_planner_statistics(&self) -> Option<&Statistics>;
+
+    /// Get lag of each Replica streaming from this Primary
+    fn get_replica_lag(&self) -> Vec<ReplicaLag>;
+
+    /// Get how stale this Replica's applied state is (None if unknown)
+    fn get_replica_staleness_ms(&self) -> Option<u64>;
+
+    /// Get the synchronous replication state of this Primary (None if unknown)
+    fn get_sync_replication(&self) -> Option<SyncReplicationView>;
+
+    /// Get tenant quotas and usage (None if not loaded)
+    fn get_tenant_quotas(&self) -> Option<&TenantQuotas>;
+
+    /// Get the tenant registry holding invoices (None if not loaded)
+    fn get_tenant_registry(&self) -> Option<&TenantRegistry>;
+
+    /// Get scheduled jobs and their most recent runs
+    fn get_scheduled_jobs(&self) -> Vec<ScheduledJobView>;
+
     /// Request promotion for a replica
     fn request_promotion(&self, replica_id: Uuid, reason: &str) -> Result<String, String>;
 
@@ -59,13 +99,32 @@ pub trait KernelAdapter: Send + Sync {
     fn request_demotion(&self, node_id: Uuid, reason: &str) -> Result<String, String>;
 
     /// Force promotion (with risk acknowledgment)
-    fn force_promotion(&self, replica_id: Uuid, reason: &str) -> Result<String, String>;
+    ///
+    /// Promoting a replica that is missing WAL additionally requires
+    /// `acknowledged_risks` to include `promotion::ACCEPT_DATA_LOSS`.
+    fn force_promotion(
+        &self,
+        replica_id: Uuid,
+        reason: &str,
+        acknowledged_risks: &[String],
+    ) -> Result<String, String>;
 }
 
 /// Default kernel adapter using actual kernel modules
 pub struct DefaultKernelAdapter {
     replication_state: ReplicationState,
     promotion_state: PromotionState,
+    statistics: Option<Statistics>,
+    replica_lag: Vec<ReplicaLag>,
+    replica_staleness_ms: Option<u64>,
+    sync_replication: Option<SyncReplicationView>,
+    tenant_quotas: Option<TenantQuotas>,
+    tenant_registry: Option<TenantRegistry>,
+    snapshot_integrity: Vec<SnapshotIntegrity>,
+    crash_reports: Vec<StoredCrashReport>,
+    audit_chain: Option<ChainReport>,
+    scheduled_jobs: Vec<ScheduledJobView>,
+    checkpoint_status: Option<CheckpointStatus>,
 }
 
 impl Default for DefaultKernelAdapter {
@@ -73,6 +132,17 @@ impl Default for DefaultKernelAdapter {
         Self {
             replication_state: ReplicationState::default(),
             promotion_state: PromotionState::Steady,
+            statistics: None,
+            replica_lag: Vec::new(),
+            replica_staleness_ms: None,
+            sync_replication: None,
+            tenant_quotas: None,
+            tenant_registry: None,
+            snapshot_integrity: Vec::new(),
+            crash_reports: Vec::new(),
+            audit_chain: None,
+            scheduled_jobs: Vec::new(),
+            checkpoint_status: None,
         }
     }
 }
@@ -82,8 +152,85 @@ impl DefaultKernelAdapter {
         Self {
             replication_state,
             promotion_state,
+            statistics: None,
+            replica_lag: Vec::new(),
+            replica_staleness_ms: None,
+            sync_replication: None,
+            tenant_quotas: None,
+            tenant_registry: None,
+            snapshot_integrity: Vec::new(),
+            crash_reports: Vec::new(),
+            audit_chain: None,
+            scheduled_jobs: Vec::new(),
+            checkpoint_status: None,
         }
     }
+
+    /// Attach the planner statistics store
+    pub fn with_statistics(mut self, statistics: Statistics) -> Self {
+        self.statistics = Some(statistics);
+        self
+    }
+
+    /// Attach the measured lag of this Primary's Replicas
+    pub fn with_replica_lag(mut self, replica_lag: Vec<ReplicaLag>) -> Self {
+        self.replica_lag = replica_lag;
+        self
+    }
+
+    /// Attach the measured staleness of this Replica
+    pub fn with_replica_staleness(mut self, staleness_ms: Option<u64>) -> Self {
+        self.replica_staleness_ms = staleness_ms;
+        self
+    }
+
+    /// Attach the synchronous replication state of this Primary
+    pub fn with_sync_replication(mut self, sync_replication: SyncReplicationView) -> Self {
+        self.sync_replication = Some(sync_replication);
+        self
+    }
+
+    /// Attach tenant quotas and persisted usage
+    pub fn with_tenant_quotas(mut self, tenant_quotas: TenantQuotas) -> Self {
+        self.tenant_quotas = Some(tenant_quotas);
+        self
+    }
+
+    /// Attach the tenant registry holding invoices
+    pub fn with_tenant_registry(mut self, tenant_registry: TenantRegistry) -> Self {
+        self.tenant_registry = Some(tenant_registry);
+        self
+    }
+
+    /// Attach the verification of snapshots on disk
+    pub fn with_snapshot_integrity(mut self, snapshot_integrity: Vec<SnapshotIntegrity>) -> Self {
+        self.snapshot_integrity = snapshot_integrity;
+        self
+    }
+
+    /// Attach the crash reports on disk
+    pub fn with_crash_reports(mut self, crash_reports: Vec<StoredCrashReport>) -> Self {
+        self.crash_reports = crash_reports;
+        self
+    }
+
+    /// Attach the walked hash chain of the audit log
+    pub fn with_audit_chain(mut self, audit_chain: ChainReport) -> Self {
+        self.audit_chain = Some(audit_chain);
+        self
+    }
+
+    /// Attach the scheduled jobs and their most recent runs
+    pub fn with_scheduled_jobs(mut self, scheduled_jobs: Vec<ScheduledJobView>) -> Self {
+        self.scheduled_jobs = scheduled_jobs;
+        self
+    }
+
+    /// Attach the progress of the running and last checkpoint
+    pub fn with_checkpoint_status(mut self, checkpoint_status: CheckpointStatus) -> Self {
+        self.checkpoint_status = Some(checkpoint_status);
+        self
+    }
 }
 
 impl KernelAdapter for DefaultKernelAdapter {
@@ -108,6 +255,10 @@ impl KernelAdapter for DefaultKernelAdapter {
         0
     }
 
+    fn get_checkpoint_status(&self) -> Option<CheckpointStatus> {
+        self.checkpoint_status.clone()
+    }
+
     fn get_snapshots(&self) -> Vec<(u64, SystemTime)> {
         Vec::new()
     }
@@ -116,6 +267,46 @@ impl KernelAdapter for DefaultKernelAdapter {
         Vec::new()
     }
 
+    fn get_snapshot_integrity(&self) -> Vec<SnapshotIntegrity> {
+        self.snapshot_integrity.clone()
+    }
+
+    fn get_crash_reports(&self) -> Vec<StoredCrashReport> {
+        self.crash_reports.clone()
+    }
+
+    fn get_audit_chain(&self) -> Option<ChainReport> {
+        self.audit_chain.clone()
+    }
+
+    fn get_planner_statistics(&self) -> Option<&Statistics> {
+        self.statistics.as_ref()
+    }
+
+    fn get_replica_lag(&self) -> Vec<ReplicaLag> {
+        self.replica_lag.clone()
+    }
+
+    fn get_replica_staleness_ms(&self) -> Option<u64> {
+        self.replica_staleness_ms
+    }
+
+    fn get_sync_replication(&self) -> Option<SyncReplicationView> {
+        self.sync_replication.clone()
+    }
+
+    fn get_tenant_quotas(&self) -> Option<&TenantQuotas> {
+        self.tenant_quotas.as_ref()
+    }
+
+    fn get_tenant_registry(&self) -> Option<&TenantRegistry> {
+        self.tenant_registry.as_ref()
+    }
+
+    fn get_scheduled_jobs(&self) -> Vec<ScheduledJobView> {
+        self.scheduled_jobs.clone()
+    }
+
     fn request_promotion(&self, _replica_id: Uuid, _reason: &str) -> Result<String, String> {
         Err("Promotion controller not connected".to_string())
     }
@@ -124,7 +315,12 @@ impl KernelAdapter for DefaultKernelAdapter {
         Err("Demotion controller not connected".to_string())
     }
 
-    fn force_promotion(&self, _replica_id: Uuid, _reason: &str) -> Result<String, String> {
+    fn force_promotion(
+        &self,
+        _replica_id: Uuid,
+        _reason: &str,
+        _acknowledged_risks: &[String],
+    ) -> Result<String, String> {
         Err("Promotion controller not connected".to_string())
     }
 }
@@ -173,6 +369,17 @@ impl ControlPlaneHandler {
         }
     }
 
+    /// Issue and check confirmation tokens with `confirmation`.
+    pub fn with_confirmation_flow(mut self, confirmation: ConfirmationFlow) -> Self {
+        self.confirmation = confirmation;
+        self
+    }
+
+    /// Confirmation tokens issued so far.
+    pub fn confirmation_flow(&self) -> &ConfirmationFlow {
+        &self.confirmation
+    }
+
     /// Handle a command request.
     ///
     /// Per PHASE7_CONTROL_PLANE_ARCHITECTURE.md §6:
@@ -217,15 +424,16 @@ impl ControlPlaneHandler {
     ) -> ControlPlaneResult<CommandResponse> {
         let command_name = request.command.command_name();
         let target_id = self.extract_target_id(&request.command);
+        let payload = request.command.payload();
 
         // Check if confirmation token is provided
         match request.confirmation_token {
             None => {
                 // Request confirmation
-                let token = self
-                    .confirmation
-                    .request_confirmation(command_name, target_id);
-                Ok(CommandResponse::awaiting_confirmation(
+                let token =
+                    self.confirmation
+                        .request_confirmation(command_name, target_id, &payload);
+                Ok(CommandResponse::confirmation_required(
                     request.request_id,
                     command_name,
                     token.id(),
@@ -233,14 +441,17 @@ impl ControlPlaneHandler {
             }
             Some(token_id) => {
                 // Validate and consume confirmation
-                match self.confirmation.confirm(token_id, command_name, target_id) {
+                match self
+                    .confirmation
+                    .confirm(token_id, command_name, target_id, &payload)
+                {
                     ConfirmationResult::Proceed { .. } => {
                         // Execute the command
                         self.execute_command(&request)
                     }
-                    ConfirmationResult::Abort { reason } => {
-                        Err(ControlPlaneError::missing_confirmation(&reason))
-                    }
+                    ConfirmationResult::Abort { status, .. } => Err(
+                        ControlPlaneError::from_confirmation_status(status, command_name),
+                    ),
                 }
             }
         }
@@ -252,6 +463,11 @@ impl ControlPlaneHandler {
             ControlPlaneCommand::Inspection(InspectionCommand::InspectNode { node_id }) => {
                 Some(*node_id)
             }
+            ControlPlaneCommand::Inspection(
+                InspectionCommand::InspectTenantUsage { tenant_id }
+                | InspectionCommand::ListInvoices { tenant_id }
+                | InspectionCommand::GetInvoice { tenant_id, .. },
+            ) => Some(*tenant_id),
             ControlPlaneCommand::Control(cmd) => Some(cmd.target_id()),
             _ => None,
         }
@@ -342,15 +558,38 @@ impl ControlPlaneHandler {
                     } else {
                         None
                     },
-                    replicas: if let Some(replica_id) = repl_state.replica_id() {
+                    replicas: if repl_state.is_primary() {
+                        self.kernel
+                            .get_replica_lag()
+                            .into_iter()
+                            .map(|lag| ReplicaState {
+                                replica_id: lag.replica_id,
+                                lag_bytes: lag.lag_bytes,
+                                lag_records: lag.lag_records,
+                                staleness_ms: None,
+                                health: if lag.connected {
+                                    NodeHealth::Healthy
+                                } else {
+                                    NodeHealth::Unavailable
+                                },
+                            })
+                            .collect()
+                    } else if let Some(replica_id) = repl_state.replica_id() {
                         vec![ReplicaState {
                             replica_id,
                             lag_bytes: 0,
+                            lag_records: 0,
+                            staleness_ms: self.kernel.get_replica_staleness_ms(),
                             health: NodeHealth::Healthy,
                         }]
                     } else {
                         Vec::new()
                     },
+                    sync: if repl_state.is_primary() {
+                        self.kernel.get_sync_replication()
+                    } else {
+                        None
+                    },
                     snapshot_time: SystemTime::now(),
                 };
                 Ok(CommandResponse::success(
@@ -373,6 +612,99 @@ impl ControlPlaneHandler {
                     CommandResponseData::PromotionState(state),
                 ))
             }
+            InspectionCommand::InspectStatistics => {
+                let collections = match self.kernel.get_planner_statistics() {
+                    Some(statistics) => statistics
+                        .collections()
+                        .map(|(name, stats)| CollectionStatisticsView {
+                            collection: name.to_string(),
+                            document_count: stats.document_count,
+                            average_document_size: stats.average_document_size(),
+                            distinct_values: stats
+                                .fields
+                                .iter()
+                                .map(|(field, sketch)| (field.clone(), sketch.distinct_estimate()))
+                                .collect(),
+                            analyzed_at: stats.analyzed_at.map(SystemTime::from),
+                            mutations_since_analyze: stats.mutations_since_analyze,
+                            stale: statistics.is_stale(stats),
+                        })
+                        .collect(),
+                    None => Vec::new(),
+                };
+                let view = PlannerStatisticsView {
+                    collections,
+                    snapshot_time: SystemTime::now(),
+                };
+                Ok(CommandResponse::success(
+                    request_id,
+                    cmd.command_name(),
+                    CommandResponseData::PlannerStatistics(view),
+                ))
+            }
+            InspectionCommand::InspectTenantUsage { tenant_id } => {
+                let (quotas, usage) = match self.kernel.get_tenant_quotas() {
+                    Some(tenant_quotas) => (
+                        tenant_quotas.quotas(*tenant_id),
+                        tenant_quotas.usage(*tenant_id),
+                    ),
+                    None => (Quotas::default(), UsageMetrics::new(*tenant_id)),
+                };
+                let view = TenantUsageView {
+                    tenant_id: *tenant_id,
+                    quotas,
+                    usage,
+                    snapshot_time: SystemTime::now(),
+                };
+                Ok(CommandResponse::success(
+                    request_id,
+                    cmd.command_name(),
+                    CommandResponseData::TenantUsage(view),
+                ))
+            }
+            InspectionCommand::ListInvoices { tenant_id } => {
+                let invoices = match self.kernel.get_tenant_registry() {
+                    Some(registry) => registry.list_invoices(*tenant_id),
+                    None => Vec::new(),
+                };
+                let view = InvoiceListView {
+                    tenant_id: *tenant_id,
+                    invoices,
+                    snapshot_time: SystemTime::now(),
+                };
+                Ok(CommandResponse::success(
+                    request_id,
+                    cmd.command_name(),
+                    CommandResponseData::Invoices(view),
+                ))
+            }
+            InspectionCommand::GetInvoice { tenant_id, period } => {
+                let registry = self.kernel.get_tenant_registry().ok_or_else(|| {
+                    ControlPlaneError::from_kernel_rejection(
+                        "TENANT_REGISTRY_UNAVAILABLE",
+                        "Tenant registry not loaded",
+                    )
+                })?;
+                let invoice = registry.get_invoice(*tenant_id, period).map_err(|e| {
+                    ControlPlaneError::from_kernel_rejection(e.error_code(), &e.to_string())
+                })?;
+                Ok(CommandResponse::success(
+                    request_id,
+                    cmd.command_name(),
+                    CommandResponseData::Invoice(invoice),
+                ))
+            }
+            InspectionCommand::InspectScheduledJobs => {
+                let view = ScheduledJobsView {
+                    jobs: self.kernel.get_scheduled_jobs(),
+                    snapshot_time: SystemTime::now(),
+                };
+                Ok(CommandResponse::success(
+                    request_id,
+                    cmd.command_name(),
+                    CommandResponseData::ScheduledJobs(view),
+                ))
+            }
         }
     }
 
@@ -450,6 +782,7 @@ impl ControlPlaneHandler {
                     current_position: self.kernel.get_wal_position(),
                     oldest_position: self.kernel.get_wal_oldest_position(),
                     size_bytes: self.kernel.get_wal_size_bytes(),
+                    checkpoint: self.kernel.get_checkpoint_status(),
                     snapshot_time: SystemTime::now(),
                 };
                 Ok(CommandResponse::success(
@@ -479,6 +812,7 @@ impl ControlPlaneHandler {
                             wal_position,
                         })
                         .collect(),
+                    integrity: self.kernel.get_snapshot_integrity(),
                     snapshot_time: SystemTime::now(),
                 };
                 Ok(CommandResponse::success(
@@ -487,6 +821,52 @@ impl ControlPlaneHandler {
                     CommandResponseData::SnapshtoInfo(info),
                 ))
             }
+            DiagnosticCommand::InspectCrashReports { id } => {
+                let reports = self.kernel.get_crash_reports();
+                let dump = match id {
+                    Some(id) => Some(
+                        reports
+                            .iter()
+                            .find(|report| &report.id == id)
+                            .cloned()
+                            .ok_or_else(|| {
+                                ControlPlaneError::from_kernel_rejection(
+                                    "CRASH_REPORT_NOT_FOUND",
+                                    &format!("No crash report with ID {}", id),
+                                )
+                            })?,
+                    ),
+                    None => None,
+                };
+                let info = CrashReportInfo {
+                    reports,
+                    dump,
+                    snapshot_time: SystemTime::now(),
+                };
+                Ok(CommandResponse::success(
+                    request_id,
+                    cmd.command_name(),
+                    CommandResponseData::CrashReports(info),
+                ))
+            }
+            DiagnosticCommand::InspectAuditLog { verify } => {
+                let chain = self.kernel.get_audit_chain().ok_or_else(|| {
+                    ControlPlaneError::from_kernel_rejection(
+                        "AUDIT_LOG_UNAVAILABLE",
+                        "Audit log not loaded",
+                    )
+                })?;
+                let info = AuditLogInfo {
+                    chain,
+                    verified: *verify,
+                    snapshot_time: SystemTime::now(),
+                };
+                Ok(CommandResponse::success(
+                    request_id,
+                    cmd.command_name(),
+                    CommandResponseData::AuditLog(info),
+                ))
+            }
         }
     }
 
@@ -552,9 +932,11 @@ impl ControlPlaneHandler {
             ControlCommand::ForcePromotion {
                 replica_id,
                 reason,
-                acknowledged_risks: _,
+                acknowledged_risks,
             } => {
-                let result_msg = self.kernel.force_promotion(*replica_id, reason.as_str());
+                let result_msg =
+                    self.kernel
+                        .force_promotion(*replica_id, reason.as_str(), acknowledged_risks);
                 let (success, explanation) = match result_msg {
                     Ok(msg) => (true, msg),
                     Err(msg) => (false, msg),
@@ -583,7 +965,7 @@ impl ControlPlaneHandler {
         let command_name = command.command_name();
         let target_id = self.extract_target_id(command);
         self.confirmation
-            .request_confirmation(command_name, target_id)
+            .request_confirmation(command_name, target_id, &command.payload())
     }
 
     /// Reject a pending confirmation.
@@ -595,7 +977,10 @@ impl ControlPlaneHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::control_plane::{BillingEngine, IsolationModel, Plan, Tenant};
     use crate::dx::api::control_plane::authority::AuthorityContext;
+    use crate::dx::api::control_plane::types::CommandOutcome;
+    use crate::snapshot::{ChecksumMismatch, VerifyReport};
 
     #[test]
     fn test_inspection_no_confirmation() {
@@ -607,6 +992,323 @@ mod tests {
         assert_eq!(response.outcome, CommandOutcome::Success);
     }
 
+    #[test]
+    fn test_inspect_scheduled_jobs() {
+        let job = ScheduledJobView {
+            name: "session_expiry".to_string(),
+            function: "session_expiry".to_string(),
+            cron: "0 * * * *".to_string(),
+            enabled: true,
+            last_run: Some(SystemTime::UNIX_EPOCH),
+            next_run: Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(3600)),
+            last_outcome: Some("succeeded".to_string()),
+            last_duration_ms: Some(3),
+        };
+        let kernel = DefaultKernelAdapter::default().with_scheduled_jobs(vec![job]);
+        let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+        let cmd = ControlPlaneCommand::Inspection(InspectionCommand::InspectScheduledJobs);
+        let request = CommandRequest::new(cmd, AuthorityContext::observer());
+
+        let response = handler.handle_command(request).unwrap();
+        assert_eq!(response.command_name, "inspect_scheduled_jobs");
+        let Some(CommandResponseData::ScheduledJobs(view)) = response.data else {
+            panic!("Expected scheduled jobs");
+        };
+        assert_eq!(view.jobs.len(), 1);
+        assert_eq!(view.jobs[0].last_outcome.as_deref(), Some("succeeded"));
+    }
+
+    #[test]
+    fn test_inspect_statistics() {
+        let mut statistics = Statistics::in_memory(Default::default());
+        let fields = vec!["status".to_string()];
+        let docs: Vec<_> = (0..10)
+            .map(|i| serde_json::json!({"status": i % 2}))
+            .collect();
+        statistics
+            .analyze("orders", docs.iter().map(|doc| (doc, 20)), &fields)
+            .unwrap();
+        statistics.record_insert("orders", &docs[0], 40, &fields);
+
+        let kernel = DefaultKernelAdapter::default().with_statistics(statistics);
+        let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+        let cmd = ControlPlaneCommand::Inspection(InspectionCommand::InspectStatistics);
+        let request = CommandRequest::new(cmd, AuthorityContext::observer());
+
+        let response = handler.handle_command(request).unwrap();
+        let Some(CommandResponseData::PlannerStatistics(view)) = response.data else {
+            panic!("Expected planner statistics");
+        };
+        assert_eq!(view.collections.len(), 1);
+        let orders = &view.collections[0];
+        assert_eq!(orders.collection, "orders");
+        assert_eq!(orders.document_count, 11);
+        assert_eq!(orders.average_document_size, 21);
+        assert_eq!(orders.distinct_values["status"], 2);
+        assert!(orders.analyzed_at.is_some());
+        assert_eq!(orders.mutations_since_analyze, 1);
+        assert!(!orders.stale);
+    }
+
+    #[test]
+    fn test_inspect_tenant_usage() {
+        let tenant_id = Uuid::new_v4();
+        let tenant_quotas = TenantQuotas::new(Quotas::free());
+        let admission = tenant_quotas.check_write(tenant_id, 100, 1).unwrap();
+        tenant_quotas.record(&admission, 120);
+
+        let kernel = DefaultKernelAdapter::default().with_tenant_quotas(tenant_quotas);
+        let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+        let cmd =
+            ControlPlaneCommand::Inspection(InspectionCommand::InspectTenantUsage { tenant_id });
+        let request = CommandRequest::new(cmd, AuthorityContext::observer());
+
+        let response = handler.handle_command(request).unwrap();
+        let Some(CommandResponseData::TenantUsage(view)) = response.data else {
+            panic!("Expected tenant usage");
+        };
+        assert_eq!(view.tenant_id, tenant_id);
+        assert_eq!(view.usage.documents, 1);
+        assert_eq!(view.usage.write_ops, 1);
+        assert_eq!(view.usage.storage_bytes, 120);
+        assert_eq!(view.quotas.max_documents, Quotas::free().max_documents);
+    }
+
+    #[test]
+    fn test_list_and_get_invoices() {
+        let registry = TenantRegistry::new();
+        let tenant = Tenant::new(
+            "acme".to_string(),
+            Plan::Pro,
+            "local".to_string(),
+            IsolationModel::Schema,
+        );
+        let tenant_id = tenant.tenant_id;
+        registry.insert(tenant).unwrap();
+        let engine = BillingEngine::new(Arc::new(registry.clone()));
+        engine.generate_invoice(tenant_id, "2026-05").unwrap();
+        engine.generate_invoice(tenant_id, "2026-06").unwrap();
+
+        let kernel = DefaultKernelAdapter::default().with_tenant_registry(registry);
+        let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+
+        let cmd = ControlPlaneCommand::Inspection(InspectionCommand::ListInvoices { tenant_id });
+        let response = handler
+            .handle_command(CommandRequest::new(cmd, AuthorityContext::observer()))
+            .unwrap();
+        let Some(CommandResponseData::Invoices(view)) = response.data else {
+            panic!("Expected invoice list");
+        };
+        let periods: Vec<&str> = view.invoices.iter().map(|i| i.period.as_str()).collect();
+        assert_eq!(periods, vec!["2026-05", "2026-06"]);
+
+        let cmd = ControlPlaneCommand::Inspection(InspectionCommand::GetInvoice {
+            tenant_id,
+            period: "2026-06".to_string(),
+        });
+        let response = handler
+            .handle_command(CommandRequest::new(cmd, AuthorityContext::observer()))
+            .unwrap();
+        let Some(CommandResponseData::Invoice(invoice)) = response.data else {
+            panic!("Expected invoice");
+        };
+        assert_eq!(invoice.total, 25.0);
+
+        let cmd = ControlPlaneCommand::Inspection(InspectionCommand::GetInvoice {
+            tenant_id,
+            period: "2026-07".to_string(),
+        });
+        let err = handler
+            .handle_command(CommandRequest::new(cmd, AuthorityContext::observer()))
+            .unwrap_err();
+        assert_eq!(err.code(), "INVOICE_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_inspect_snapshots_reports_integrity() {
+        let report = VerifyReport {
+            snapshot_id: "20260101T000000Z".to_string(),
+            ok: false,
+            files_checked: 2,
+            mismatches: vec![ChecksumMismatch {
+                file: "storage.dat".to_string(),
+                expected: "crc32:00000001".to_string(),
+                actual: "crc32:00000002".to_string(),
+            }],
+            missing: Vec::new(),
+        };
+        let integrity = vec![
+            SnapshotIntegrity {
+                snapshot_id: report.snapshot_id.clone(),
+                result: Ok(report.clone()),
+            },
+            SnapshotIntegrity {
+                snapshot_id: "20260102T000000Z".to_string(),
+                result: Err("manifest missing".to_string()),
+            },
+        ];
+        let kernel = DefaultKernelAdapter::default().with_snapshot_integrity(integrity);
+        let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+        let cmd = ControlPlaneCommand::Diagnostic(DiagnosticCommand::InspectSnapshots);
+        let request = CommandRequest::new(cmd, AuthorityContext::observer());
+
+        let response = handler.handle_command(request).unwrap();
+        let Some(CommandResponseData::SnapshtoInfo(info)) = response.data else {
+            panic!("Expected snapshot info");
+        };
+        assert_eq!(info.integrity.len(), 2);
+        assert_eq!(info.integrity[0].result, Ok(report));
+        assert!(info.integrity[1].result.is_err());
+    }
+
+    #[test]
+    fn test_inspect_crash_reports_dumps_requested_report() {
+        let stored = |id: &str| StoredCrashReport {
+            id: id.to_string(),
+            size_bytes: 2,
+            report: Ok(serde_json::json!({})),
+        };
+        let kernel =
+            DefaultKernelAdapter::default().with_crash_reports(vec![stored("a"), stored("b")]);
+        let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+
+        let cmd = ControlPlaneCommand::Diagnostic(DiagnosticCommand::InspectCrashReports {
+            id: Some("b".to_string()),
+        });
+        let response = handler
+            .handle_command(CommandRequest::new(cmd, AuthorityContext::observer()))
+            .unwrap();
+        let Some(CommandResponseData::CrashReports(info)) = response.data else {
+            panic!("Expected crash reports");
+        };
+        assert_eq!(info.reports.len(), 2);
+        assert_eq!(info.dump.unwrap().id, "b");
+
+        let cmd = ControlPlaneCommand::Diagnostic(DiagnosticCommand::InspectCrashReports {
+            id: Some("missing".to_string()),
+        });
+        let err = handler
+            .handle_command(CommandRequest::new(cmd, AuthorityContext::observer()))
+            .unwrap_err();
+        assert!(err.message().contains("missing"));
+    }
+
+    #[test]
+    fn test_inspect_audit_log_requires_loaded_chain() {
+        let cmd =
+            ControlPlaneCommand::Diagnostic(DiagnosticCommand::InspectAuditLog { verify: true });
+        let mut handler = ControlPlaneHandler::new();
+        let err = handler
+            .handle_command(CommandRequest::new(
+                cmd.clone(),
+                AuthorityContext::observer(),
+            ))
+            .unwrap_err();
+        assert!(err.message().contains("Audit log"));
+
+        let chain = crate::observability::verify_chain("/nonexistent/audit.log").unwrap();
+        let kernel = DefaultKernelAdapter::default().with_audit_chain(chain);
+        let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+        let response = handler
+            .handle_command(CommandRequest::new(cmd, AuthorityContext::observer()))
+            .unwrap();
+        let Some(CommandResponseData::AuditLog(info)) = response.data else {
+            panic!("Expected audit log");
+        };
+        assert!(info.verified);
+        assert!(info.chain.is_intact());
+        assert_eq!(info.chain.records, 0);
+    }
+
+    #[test]
+    fn test_inspect_replication_status_reports_replica_lag() {
+        let lag = |connected, lag_records, lag_bytes| ReplicaLag {
+            replica_id: Uuid::new_v4(),
+            address: "127.0.0.1:50000".to_string(),
+            connected,
+            acked_sequence: 10 - lag_records,
+            lag_records,
+            lag_bytes,
+        };
+        let kernel =
+            DefaultKernelAdapter::new(ReplicationState::PrimaryActive, PromotionState::Steady)
+                .with_replica_lag(vec![lag(true, 0, 0), lag(false, 4, 512)]);
+        let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+        let cmd = ControlPlaneCommand::Inspection(InspectionCommand::InspectReplicationStatus);
+        let request = CommandRequest::new(cmd, AuthorityContext::observer());
+
+        let response = handler.handle_command(request).unwrap();
+        let Some(CommandResponseData::ReplicationStatus(status)) = response.data else {
+            panic!("Expected replication status");
+        };
+        let replicas: Vec<_> = status
+            .replicas
+            .iter()
+            .map(|r| (r.lag_records, r.lag_bytes, r.health))
+            .collect();
+        assert_eq!(
+            replicas,
+            [
+                (0, 0, NodeHealth::Healthy),
+                (4, 512, NodeHealth::Unavailable)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inspect_replication_status_reports_staleness() {
+        let replica_id = Uuid::new_v4();
+        let kernel = DefaultKernelAdapter::new(
+            ReplicationState::ReplicaActive { replica_id },
+            PromotionState::Steady,
+        )
+        .with_replica_staleness(Some(1_500));
+        let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+        let cmd = ControlPlaneCommand::Inspection(InspectionCommand::InspectReplicationStatus);
+        let request = CommandRequest::new(cmd, AuthorityContext::observer());
+
+        let response = handler.handle_command(request).unwrap();
+        let Some(CommandResponseData::ReplicationStatus(status)) = response.data else {
+            panic!("Expected replication status");
+        };
+        assert_eq!(status.replicas.len(), 1);
+        assert_eq!(status.replicas[0].replica_id, replica_id);
+        assert_eq!(status.replicas[0].staleness_ms, Some(1_500));
+    }
+
+    #[test]
+    fn test_inspect_replication_status_reports_sync_replication() {
+        let view = SyncReplicationView {
+            mode: "quorum".to_string(),
+            min_sync_replicas: 2,
+            connected_replicas: 1,
+            quorum_healthy: false,
+            degraded: true,
+        };
+        let replica = DefaultKernelAdapter::new(
+            ReplicationState::ReplicaActive {
+                replica_id: Uuid::new_v4(),
+            },
+            PromotionState::Steady,
+        )
+        .with_sync_replication(view.clone());
+        let primary =
+            DefaultKernelAdapter::new(ReplicationState::PrimaryActive, PromotionState::Steady)
+                .with_sync_replication(view.clone());
+
+        let status = |kernel: DefaultKernelAdapter| {
+            let mut handler = ControlPlaneHandler::with_kernel(Arc::new(kernel));
+            let cmd = ControlPlaneCommand::Inspection(InspectionCommand::InspectReplicationStatus);
+            let request = CommandRequest::new(cmd, AuthorityContext::observer());
+            match handler.handle_command(request).unwrap().data {
+                Some(CommandResponseData::ReplicationStatus(status)) => status,
+                _ => panic!("Expected replication status"),
+            }
+        };
+        assert_eq!(status(primary).sync, Some(view));
+        assert_eq!(status(replica).sync, None);
+    }
+
     #[test]
     fn test_control_requires_confirmation() {
         let mut handler = ControlPlaneHandler::new();
@@ -617,7 +1319,7 @@ mod tests {
         let request = CommandRequest::new(cmd, AuthorityContext::operator());
 
         let response = handler.handle_command(request).unwrap();
-        assert_eq!(response.outcome, CommandOutcome::AwaitingConfirmation);
+        assert_eq!(response.outcome, CommandOutcome::ConfirmationRequired);
         assert!(response.confirmation_token.is_some());
     }
 
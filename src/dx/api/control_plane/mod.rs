@@ -21,11 +21,14 @@ pub use authority::{AuthorityContext, AuthorityLevel};
 pub use commands::{ControlCommand, ControlPlaneCommand, DiagnosticCommand, InspectionCommand};
 pub use confirmation::{
     ConfirmationFlow, ConfirmationResult, ConfirmationStatus, ConfirmationToken,
-    EnhancedConfirmation,
+    EnhancedConfirmation, DEFAULT_CONFIRMATION_TTL,
 };
 pub use errors::{ControlPlaneError, ControlPlaneErrorDomain, ControlPlaneResult};
-pub use handlers::ControlPlaneHandler;
+pub use handlers::{ControlPlaneHandler, DefaultKernelAdapter, KernelAdapter};
 pub use types::{
-    ClusterState, CommandOutcome, CommandRequest, CommandResponse, NodeState, PromotionStateView,
-    ReplicationStatus,
+    AuditLogInfo, ClusterState, CollectionStatisticsView, CommandOutcome, CommandRequest,
+    CommandResponse, CommandResponseData, CrashReportInfo, InvoiceListView, NodeState,
+    PlannerStatisticsView, PromotionStateView, ReplicationStatus, ScheduledJobView,
+    ScheduledJobsView, SnapshotInfo, SnapshotIntegrity, SyncReplicationView, TenantUsageView,
+    WalInfo,
 };
@@ -7,11 +7,18 @@
 //! Derived presentation state is computed for human consumption.
 //! No derived view is authoritative.
 
+use std::collections::BTreeMap;
 use std::time::SystemTime;
 use uuid::Uuid;
 
 use super::authority::AuthorityContext;
 use super::commands::ControlPlaneCommand;
+use crate::checkpoint::CheckpointStatus;
+use crate::control_plane::{Invoice, Quotas, UsageMetrics};
+use crate::observability::audit::ChainReport;
+use crate::observability::current_request_id;
+use crate::panic_handler::StoredCrashReport;
+use crate::snapshot::VerifyReport;
 
 /// Command request — operator-initiated action.
 ///
@@ -37,9 +44,11 @@ pub struct CommandRequest {
 
 impl CommandRequest {
     /// Create a new command request.
+    ///
+    /// Takes the ID of the request being handled, if any.
     pub fn new(command: ControlPlaneCommand, authority: AuthorityContext) -> Self {
         Self {
-            request_id: Uuid::new_v4(),
+            request_id: current_request_id().unwrap_or_else(Uuid::new_v4),
             command,
             authority,
             confirmation_token: None,
@@ -66,8 +75,9 @@ pub enum CommandOutcome {
     /// Command failed during execution.
     Failed,
 
-    /// Awaiting confirmation.
-    AwaitingConfirmation,
+    /// Not executed: resubmit the identical command with the returned
+    /// confirmation token.
+    ConfirmationRequired,
 }
 
 /// Command response.
@@ -123,11 +133,11 @@ impl CommandResponse {
     }
 
     /// Create an awaiting confirmation response.
-    pub fn awaiting_confirmation(request_id: Uuid, command_name: &str, token_id: Uuid) -> Self {
+    pub fn confirmation_required(request_id: Uuid, command_name: &str, token_id: Uuid) -> Self {
         Self {
             request_id,
             command_name: command_name.to_string(),
-            outcome: CommandOutcome::AwaitingConfirmation,
+            outcome: CommandOutcome::ConfirmationRequired,
             timestamp: SystemTime::now(),
             confirmation_token: Some(token_id),
             data: None,
@@ -151,6 +161,21 @@ pub enum CommandResponseData {
     /// Promotion state inspection result.
     PromotionState(PromotionStateView),
 
+    /// Planner statistics inspection result.
+    PlannerStatistics(PlannerStatisticsView),
+
+    /// Tenant usage inspection result.
+    TenantUsage(TenantUsageView),
+
+    /// Tenant invoice list result.
+    Invoices(InvoiceListView),
+
+    /// Single invoice result.
+    Invoice(Invoice),
+
+    /// Scheduled jobs inspection result.
+    ScheduledJobs(ScheduledJobsView),
+
     /// Diagnostic results.
     Diagnostics(DiagnosticResult),
 
@@ -160,6 +185,12 @@ pub enum CommandResponseData {
     /// Snapshots inspection result.
     SnapshtoInfo(SnapshotInfo),
 
+    /// Crash reports inspection result.
+    CrashReports(CrashReportInfo),
+
+    /// Audit log inspection result.
+    AuditLog(AuditLogInfo),
+
     /// Promotion request result.
     PromotionResult(PromotionResultData),
 }
@@ -232,10 +263,32 @@ pub struct ReplicationStatus {
     /// Replica states.
     pub replicas: Vec<ReplicaState>,
 
+    /// Synchronous replication (`None` unless a primary).
+    pub sync: Option<SyncReplicationView>,
+
     /// Snapshot timestamp.
     pub snapshot_time: SystemTime,
 }
 
+/// Synchronous replication view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncReplicationView {
+    /// `async` or `quorum`.
+    pub mode: String,
+
+    /// Replicas that must acknowledge a write in quorum mode.
+    pub min_sync_replicas: usize,
+
+    /// Replicas currently streaming.
+    pub connected_replicas: usize,
+
+    /// Whether enough replicas are streaming to form the quorum.
+    pub quorum_healthy: bool,
+
+    /// Whether writes stopped waiting for replicas after a timeout.
+    pub degraded: bool,
+}
+
 /// Individual replica state.
 #[derive(Debug, Clone)]
 pub struct ReplicaState {
@@ -245,6 +298,13 @@ pub struct ReplicaState {
     /// WAL position lag (bytes behind primary).
     pub lag_bytes: u64,
 
+    /// WAL records not yet acknowledged.
+    pub lag_records: u64,
+
+    /// Milliseconds since the replica was last current with the primary,
+    /// as seen by the replica itself (`None` if unknown).
+    pub staleness_ms: Option<u64>,
+
     /// Health status.
     pub health: NodeHealth,
 }
@@ -265,6 +325,109 @@ pub struct PromotionStateView {
     pub snapshot_time: SystemTime,
 }
 
+/// Planner statistics view.
+#[derive(Debug, Clone)]
+pub struct PlannerStatisticsView {
+    /// Per-collection statistics, in collection name order.
+    pub collections: Vec<CollectionStatisticsView>,
+
+    /// Snapshot timestamp.
+    pub snapshot_time: SystemTime,
+}
+
+/// Statistics for one collection.
+#[derive(Debug, Clone)]
+pub struct CollectionStatisticsView {
+    /// Collection name.
+    pub collection: String,
+
+    /// Live documents.
+    pub document_count: u64,
+
+    /// Average document size in bytes.
+    pub average_document_size: u64,
+
+    /// Estimated distinct values per indexed field.
+    pub distinct_values: BTreeMap<String, u64>,
+
+    /// Last analyze (if any).
+    pub analyzed_at: Option<SystemTime>,
+
+    /// Inserts, updates and deletes since the last analyze.
+    pub mutations_since_analyze: u64,
+
+    /// Whether the planner ignores the distinct-value estimates.
+    pub stale: bool,
+}
+
+/// Tenant quotas and metered usage view.
+#[derive(Debug, Clone)]
+pub struct TenantUsageView {
+    /// Tenant identifier.
+    pub tenant_id: Uuid,
+
+    /// Quotas that apply to the tenant.
+    pub quotas: Quotas,
+
+    /// Usage in the current month, as last persisted.
+    pub usage: UsageMetrics,
+
+    /// Snapshot timestamp.
+    pub snapshot_time: SystemTime,
+}
+
+/// Invoices of a tenant.
+#[derive(Debug, Clone)]
+pub struct InvoiceListView {
+    /// Tenant identifier.
+    pub tenant_id: Uuid,
+
+    /// Invoices, oldest billing period first.
+    pub invoices: Vec<Invoice>,
+
+    /// Snapshot timestamp.
+    pub snapshot_time: SystemTime,
+}
+
+/// Scheduled jobs view.
+#[derive(Debug, Clone)]
+pub struct ScheduledJobsView {
+    /// Jobs, in name order.
+    pub jobs: Vec<ScheduledJobView>,
+
+    /// Snapshot timestamp.
+    pub snapshot_time: SystemTime,
+}
+
+/// One scheduled job and its most recent run.
+#[derive(Debug, Clone)]
+pub struct ScheduledJobView {
+    /// Job name.
+    pub name: String,
+
+    /// Function the job invokes.
+    pub function: String,
+
+    /// Cron expression.
+    pub cron: String,
+
+    /// Whether the job runs.
+    pub enabled: bool,
+
+    /// Last run (if any).
+    pub last_run: Option<SystemTime>,
+
+    /// Next run (None if the cron expression never fires again).
+    pub next_run: Option<SystemTime>,
+
+    /// Outcome of the last recorded run: succeeded, failed, timed_out or
+    /// skipped.
+    pub last_outcome: Option<String>,
+
+    /// Duration of the last recorded run.
+    pub last_duration_ms: Option<u64>,
+}
+
 // ============================================================================
 // DIAGNOSTIC RESULTS
 // ============================================================================
@@ -301,6 +464,9 @@ pub struct WalInfo {
     /// WAL size in bytes.
     pub size_bytes: u64,
 
+    /// Running and last automatic checkpoint (None if not loaded).
+    pub checkpoint: Option<CheckpointStatus>,
+
     /// Snapshot timestamp.
     pub snapshot_time: SystemTime,
 }
@@ -314,10 +480,49 @@ pub struct SnapshotInfo {
     /// Available checkpoints.
     pub checkpoints: Vec<CheckpointMeta>,
 
+    /// Integrity of each snapshot on disk, oldest first.
+    pub integrity: Vec<SnapshotIntegrity>,
+
     /// Snapshot timestamp.
     pub snapshot_time: SystemTime,
 }
 
+/// Crash reports on disk.
+#[derive(Debug, Clone)]
+pub struct CrashReportInfo {
+    /// Every report, oldest first.
+    pub reports: Vec<StoredCrashReport>,
+
+    /// The report requested by ID, if any.
+    pub dump: Option<StoredCrashReport>,
+
+    /// Snapshot timestamp.
+    pub snapshot_time: SystemTime,
+}
+
+/// Audit log on disk.
+#[derive(Debug, Clone)]
+pub struct AuditLogInfo {
+    /// The log's hash chain, walked to its end or first broken link.
+    pub chain: ChainReport,
+
+    /// Whether the chain walk was requested (`--verify`).
+    pub verified: bool,
+
+    /// Snapshot timestamp.
+    pub snapshot_time: SystemTime,
+}
+
+/// Integrity of one snapshot on disk.
+#[derive(Debug, Clone)]
+pub struct SnapshotIntegrity {
+    /// Snapshot ID.
+    pub snapshot_id: String,
+
+    /// Verification report, or why the snapshot could not be verified.
+    pub result: Result<VerifyReport, String>,
+}
+
 /// Snapshot metadata.
 #[derive(Debug, Clone)]
 pub struct SnapshotMeta {
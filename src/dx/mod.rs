@@ -12,9 +12,14 @@
 //! Per DX_INVARIANTS.md §P4-16:
 //! - Phase 4 MUST be fully disableable
 //! - Disabling requires no migration, data changes, or behavior changes
+//!
+//! The exception is `seed`, run only from the CLI (`aerodb dx seed`): it
+//! writes test data as an ordinary API client, with no authority any
+//! other client lacks.
 
 pub mod api;
 pub mod config;
 pub mod explain;
+pub mod seed;
 
 pub use config::DxConfig;
@@ -0,0 +1,733 @@
+//! Deterministic test data seeding
+//!
+//! A seed spec lists collections, how many documents each gets, and a
+//! generator per field. The same spec and RNG seed always produce the same
+//! documents, so demos and load tests are reproducible.
+//!
+//! Unlike the rest of the DX module, seeding writes to the database. It
+//! does so only as a client of the ordinary API: documents go through
+//! `insert_many` (schema validation, unique indexes, WAL) like any other
+//! write.
+//!
+//! ```yaml
+//! batch_size: 500
+//! collections:
+//!   - name: users
+//!     schema_version: v1
+//!     count: 100
+//!     fields:
+//!       name: { type: name }
+//!       email: { type: email }
+//!       age: { type: int, min: 18, max: 90 }
+//!   - name: posts
+//!     schema_version: v1
+//!     count: 1000
+//!     fields:
+//!       author_id: { type: reference, collection: users }
+//!       status: { type: choice, values: [draft, published] }
+//!       created_at: { type: datetime, start: "2024-01-01T00:00:00Z", end: "2025-01-01T00:00:00Z" }
+//! ```
+//!
+//! Documents without an `_id` generator get a UUID `_id`. A reference
+//! picks the `_id` of a document seeded earlier in the same run, so the
+//! referenced collection must be listed before the referencing one.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::api::{ApiHandler, Response, Subsystems};
+use crate::schema::SchemaValidator;
+use crate::storage::{StorageError, StorageReader};
+
+/// Result type for seeding
+pub type SeedResult<T> = Result<T, SeedError>;
+
+/// Seeding errors
+#[derive(Debug)]
+pub enum SeedError {
+    /// The spec file could not be read or parsed
+    SpecRead { path: String, message: String },
+
+    /// The spec is structurally invalid
+    InvalidSpec { reason: String },
+
+    /// A generated document does not match its collection's schema
+    SchemaViolation { collection: String, message: String },
+
+    /// `--truncate` named a collection the spec does not seed
+    TruncateNotInSpec { collection: String },
+
+    /// A write was rejected by the API
+    WriteFailed {
+        collection: String,
+        code: String,
+        message: String,
+    },
+}
+
+impl SeedError {
+    fn invalid(reason: impl Into<String>) -> Self {
+        Self::InvalidSpec {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for SeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SpecRead { path, message } => {
+                write!(f, "Failed to read seed spec {}: {}", path, message)
+            }
+            Self::InvalidSpec { reason } => write!(f, "Invalid seed spec: {}", reason),
+            Self::SchemaViolation {
+                collection,
+                message,
+            } => write!(
+                f,
+                "Seed spec for {} does not match its schema: {}",
+                collection, message
+            ),
+            Self::TruncateNotInSpec { collection } => write!(
+                f,
+                "Refusing to truncate {}: it is not seeded by the spec",
+                collection
+            ),
+            Self::WriteFailed {
+                collection,
+                code,
+                message,
+            } => write!(f, "Seeding {} failed: {}: {}", collection, code, message),
+        }
+    }
+}
+
+impl std::error::Error for SeedError {}
+
+fn default_batch_size() -> usize {
+    500
+}
+
+/// A seed spec, read from YAML or JSON
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeedSpec {
+    /// Documents per `insert_many` request
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Collections to seed, in order
+    pub collections: Vec<CollectionSeed>,
+}
+
+/// How to seed one collection
+#[derive(Debug, Clone, Deserialize)]
+pub struct CollectionSeed {
+    /// Collection (schema id)
+    pub name: String,
+    /// Schema version the documents are written with
+    pub schema_version: String,
+    /// Number of documents
+    pub count: usize,
+    /// Generator per field
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldGenerator>,
+}
+
+/// Generator for one field's values
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FieldGenerator {
+    /// Random UUID, as a string
+    Uuid,
+    /// Full name
+    Name,
+    /// Email address, unique within the collection
+    Email,
+    /// Integer in `min..=max`
+    Int { min: i64, max: i64 },
+    /// RFC 3339 timestamp in `start..=end`
+    Datetime {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    /// One of the listed values
+    Choice { values: Vec<Value> },
+    /// `_id` of a document of a collection seeded earlier
+    Reference { collection: String },
+}
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada",
+    "Alan",
+    "Barbara",
+    "Claude",
+    "Edsger",
+    "Frances",
+    "Grace",
+    "John",
+    "Katherine",
+    "Ken",
+    "Leslie",
+    "Linus",
+    "Margaret",
+    "Niklaus",
+    "Radia",
+    "Tony",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Allen",
+    "Dijkstra",
+    "Hamilton",
+    "Hoare",
+    "Hopper",
+    "Johnson",
+    "Kernighan",
+    "Lamport",
+    "Liskov",
+    "Lovelace",
+    "McCarthy",
+    "Perlman",
+    "Ritchie",
+    "Thompson",
+    "Turing",
+    "Wirth",
+];
+
+impl SeedSpec {
+    /// Read a spec; `.json` files are parsed as JSON, anything else as YAML
+    pub fn from_file(path: &Path) -> SeedResult<Self> {
+        let read_failed = |message: String| SeedError::SpecRead {
+            path: path.display().to_string(),
+            message,
+        };
+        let text = std::fs::read_to_string(path).map_err(|e| read_failed(e.to_string()))?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&text).map_err(|e| read_failed(e.to_string()))
+        } else {
+            serde_yaml::from_str(&text).map_err(|e| read_failed(e.to_string()))
+        }
+    }
+
+    /// Check the spec's structure (schemas are checked by `seed`)
+    pub fn validate(&self) -> SeedResult<()> {
+        if self.batch_size == 0 {
+            return Err(SeedError::invalid("batch_size must be at least 1"));
+        }
+        let mut seen = HashSet::new();
+        for collection in &self.collections {
+            for (field, generator) in &collection.fields {
+                let at = || format!("{}.{}", collection.name, field);
+                match generator {
+                    FieldGenerator::Int { min, max } if min > max => {
+                        return Err(SeedError::invalid(format!("{}: min > max", at())));
+                    }
+                    FieldGenerator::Datetime { start, end } if start > end => {
+                        return Err(SeedError::invalid(format!("{}: start > end", at())));
+                    }
+                    FieldGenerator::Choice { values } if values.is_empty() => {
+                        return Err(SeedError::invalid(format!("{}: no values", at())));
+                    }
+                    FieldGenerator::Reference { collection: parent }
+                        if !seen.contains(parent.as_str()) =>
+                    {
+                        return Err(SeedError::invalid(format!(
+                            "{}: {} must be listed before the collections referencing it",
+                            at(),
+                            parent
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+            if !seen.insert(collection.name.as_str()) {
+                return Err(SeedError::invalid(format!(
+                    "{} is listed twice",
+                    collection.name
+                )));
+            }
+        }
+        for collection in &self.collections {
+            let references_empty = collection.fields.values().any(|generator| {
+                matches!(generator, FieldGenerator::Reference { collection: parent }
+                    if self.collection(parent).is_some_and(|p| p.count == 0))
+            });
+            if references_empty && collection.count > 0 {
+                return Err(SeedError::invalid(format!(
+                    "{} references a collection seeded with no documents",
+                    collection.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn collection(&self, name: &str) -> Option<&CollectionSeed> {
+        self.collections.iter().find(|c| c.name == name)
+    }
+}
+
+/// Documents generated for one collection
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeededCollection {
+    pub name: String,
+    pub schema_version: String,
+    pub documents: Vec<Value>,
+}
+
+/// Generate every collection's documents from `seed`
+///
+/// Deterministic: the same spec and seed always give the same documents.
+pub fn generate(spec: &SeedSpec, seed: u64) -> SeedResult<Vec<SeededCollection>> {
+    spec.validate()?;
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut ids: HashMap<&str, Vec<Value>> = HashMap::new();
+    let mut seeded = Vec::with_capacity(spec.collections.len());
+
+    for collection in &spec.collections {
+        let mut documents = Vec::with_capacity(collection.count);
+        for row in 0..collection.count {
+            let mut document = Map::new();
+            if !collection.fields.contains_key("_id") {
+                document.insert("_id".to_string(), json!(random_uuid(&mut rng)));
+            }
+            for (field, generator) in &collection.fields {
+                let value = generate_value(generator, row, &ids, &mut rng);
+                document.insert(field.clone(), value);
+            }
+            documents.push(Value::Object(document));
+        }
+        ids.insert(
+            &collection.name,
+            documents.iter().map(|d| d["_id"].clone()).collect(),
+        );
+        seeded.push(SeededCollection {
+            name: collection.name.clone(),
+            schema_version: collection.schema_version.clone(),
+            documents,
+        });
+    }
+
+    Ok(seeded)
+}
+
+fn random_uuid(rng: &mut StdRng) -> String {
+    uuid::Builder::from_random_bytes(rng.gen())
+        .into_uuid()
+        .to_string()
+}
+
+fn generate_value(
+    generator: &FieldGenerator,
+    row: usize,
+    ids: &HashMap<&str, Vec<Value>>,
+    rng: &mut StdRng,
+) -> Value {
+    let name = |rng: &mut StdRng| {
+        let first = FIRST_NAMES[rng.gen_range(0..FIRST_NAMES.len())];
+        let last = LAST_NAMES[rng.gen_range(0..LAST_NAMES.len())];
+        (first, last)
+    };
+
+    match generator {
+        FieldGenerator::Uuid => json!(random_uuid(rng)),
+        FieldGenerator::Name => {
+            let (first, last) = name(rng);
+            json!(format!("{} {}", first, last))
+        }
+        // The row number keeps emails unique, for unique indexes
+        FieldGenerator::Email => {
+            let (first, last) = name(rng);
+            json!(format!("{}.{}{}@example.com", first, last, row).to_lowercase())
+        }
+        FieldGenerator::Int { min, max } => json!(rng.gen_range(*min..=*max)),
+        FieldGenerator::Datetime { start, end } => {
+            let secs = rng.gen_range(start.timestamp()..=end.timestamp());
+            let at = Utc.timestamp_opt(secs, 0).single().unwrap_or(*start);
+            json!(at.to_rfc3339())
+        }
+        FieldGenerator::Choice { values } => values[rng.gen_range(0..values.len())].clone(),
+        FieldGenerator::Reference { collection } => {
+            let parents = &ids[collection.as_str()];
+            parents[rng.gen_range(0..parents.len())].clone()
+        }
+    }
+}
+
+/// Progress after each batch written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeedProgress<'a> {
+    pub collection: &'a str,
+    pub inserted: usize,
+    pub total: usize,
+}
+
+/// Outcome of a seeding run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    /// Documents removed by truncation, per collection
+    pub truncated: BTreeMap<String, usize>,
+    /// Documents inserted, per collection
+    pub inserted: BTreeMap<String, usize>,
+}
+
+/// Seed the database through `handler`
+///
+/// `truncate` lists the collections to empty first (purging their
+/// documents); an empty list means every collection in the spec. Naming a
+/// collection the spec does not seed is refused. Every generated document
+/// is checked against its schema before anything is written, so a spec
+/// that violates a schema changes nothing.
+pub fn seed(
+    spec: &SeedSpec,
+    seed: u64,
+    truncate: Option<&[String]>,
+    handler: &ApiHandler,
+    sys: &mut Subsystems<'_>,
+    mut progress: impl FnMut(SeedProgress<'_>),
+) -> SeedResult<SeedReport> {
+    let truncate: Vec<&str> = match truncate {
+        None => Vec::new(),
+        Some([]) => spec.collections.iter().map(|c| c.name.as_str()).collect(),
+        Some(names) => names
+            .iter()
+            .map(|name| match spec.collection(name) {
+                Some(collection) => Ok(collection.name.as_str()),
+                None => Err(SeedError::TruncateNotInSpec {
+                    collection: name.clone(),
+                }),
+            })
+            .collect::<SeedResult<_>>()?,
+    };
+
+    let seeded = generate(spec, seed)?;
+
+    // 1. Check every document before writing any
+    let validator = SchemaValidator::new(sys.schema_loader);
+    for collection in &seeded {
+        for document in &collection.documents {
+            validator
+                .validate_document(&collection.name, &collection.schema_version, document)
+                .map_err(|e| SeedError::SchemaViolation {
+                    collection: collection.name.clone(),
+                    message: e.to_string(),
+                })?;
+        }
+    }
+
+    let mut report = SeedReport::default();
+
+    // 2. Truncate
+    for name in truncate {
+        let purged = purge_collection(name, handler, sys)?;
+        report.truncated.insert(name.to_string(), purged);
+    }
+
+    // 3. Insert in batches
+    for collection in &seeded {
+        let total = collection.documents.len();
+        let mut inserted = 0;
+        for batch in collection.documents.chunks(spec.batch_size) {
+            let request = json!({
+                "op": "insert_many",
+                "schema_id": collection.name,
+                "schema_version": collection.schema_version,
+                "documents": batch,
+            });
+            execute(&collection.name, &request, handler, sys)?;
+            inserted += batch.len();
+            progress(SeedProgress {
+                collection: &collection.name,
+                inserted,
+                total,
+            });
+        }
+        report.inserted.insert(collection.name.clone(), inserted);
+    }
+
+    Ok(report)
+}
+
+/// Purge every live document of `collection`, returning how many
+fn purge_collection(
+    collection: &str,
+    handler: &ApiHandler,
+    sys: &mut Subsystems<'_>,
+) -> SeedResult<usize> {
+    let read_failed = |e: StorageError| SeedError::WriteFailed {
+        collection: collection.to_string(),
+        code: e.code().code().to_string(),
+        message: e.to_string(),
+    };
+    let mut reader = StorageReader::open(sys.storage_writer.path()).map_err(read_failed)?;
+    let mut ids: Vec<String> = reader
+        .build_document_map()
+        .map_err(read_failed)?
+        .into_values()
+        .filter(|record| record.schema_id == collection && !record.is_tombstone)
+        .filter_map(|record| {
+            let body: Value = serde_json::from_slice(&record.document_body).ok()?;
+            body.get("_id")?.as_str().map(str::to_string)
+        })
+        .collect();
+    ids.sort();
+
+    for id in &ids {
+        let request = json!({
+            "op": "purge",
+            "schema_id": collection,
+            "document_id": id,
+        });
+        execute(collection, &request, handler, sys)?;
+    }
+    Ok(ids.len())
+}
+
+fn execute(
+    collection: &str,
+    request: &Value,
+    handler: &ApiHandler,
+    sys: &mut Subsystems<'_>,
+) -> SeedResult<()> {
+    match handler.handle(&request.to_string(), sys) {
+        Response::Success(_) => Ok(()),
+        Response::Error(e) => Err(SeedError::WriteFailed {
+            collection: collection.to_string(),
+            code: e.code,
+            message: e.message,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admission_control::{AdmissionControlConfig, AdmissionController};
+    use crate::backpressure::{BackpressureConfig, BackpressureManager};
+    use crate::index::IndexManager;
+    use crate::planner::{Statistics, StatisticsConfig};
+    use crate::query_limits::QueryLimitsConfig;
+    use crate::resource_limits::{ResourceLimitsConfig, ResourceManager};
+    use crate::schema::{FieldDef, Schema, SchemaLoader};
+    use crate::storage::StorageWriter;
+    use crate::wal::WalWriter;
+    use tempfile::TempDir;
+
+    const SPEC: &str = r#"
+batch_size: 4
+collections:
+  - name: users
+    schema_version: v1
+    count: 10
+    fields:
+      name: { type: name }
+      email: { type: email }
+      age: { type: int, min: 18, max: 90 }
+  - name: posts
+    schema_version: v1
+    count: 25
+    fields:
+      author_id: { type: reference, collection: users }
+      status: { type: choice, values: [draft, published] }
+      created_at: { type: datetime, start: "2024-01-01T00:00:00Z", end: "2025-01-01T00:00:00Z" }
+"#;
+
+    struct Env {
+        temp: TempDir,
+        loader: SchemaLoader,
+        wal: WalWriter,
+        storage_w: StorageWriter,
+        storage_r: StorageReader,
+        index: IndexManager,
+        statistics: Statistics,
+        rm: ResourceManager,
+        bpm: BackpressureManager,
+        ac: AdmissionController,
+        ql: QueryLimitsConfig,
+    }
+
+    impl Env {
+        fn new() -> Self {
+            let temp = TempDir::new().unwrap();
+            let data_dir = temp.path();
+
+            let mut loader = SchemaLoader::new(data_dir);
+            let mut users = std::collections::HashMap::new();
+            users.insert("_id".to_string(), FieldDef::required_string());
+            users.insert("name".to_string(), FieldDef::required_string());
+            users.insert("email".to_string(), FieldDef::required_string());
+            users.insert("age".to_string(), FieldDef::optional_int());
+            loader.register(Schema::new("users", "v1", users)).unwrap();
+            let mut posts = std::collections::HashMap::new();
+            posts.insert("_id".to_string(), FieldDef::required_string());
+            posts.insert("author_id".to_string(), FieldDef::required_string());
+            posts.insert("status".to_string(), FieldDef::required_string());
+            posts.insert("created_at".to_string(), FieldDef::required_string());
+            loader.register(Schema::new("posts", "v1", posts)).unwrap();
+
+            let resource_config = ResourceLimitsConfig {
+                min_free_disk_bytes: 0,
+                ..Default::default()
+            };
+            Self {
+                wal: WalWriter::open(data_dir).unwrap(),
+                storage_w: StorageWriter::open(data_dir).unwrap(),
+                storage_r: StorageReader::open_from_data_dir(data_dir).unwrap(),
+                index: IndexManager::new(HashSet::new()),
+                statistics: Statistics::in_memory(StatisticsConfig::default()),
+                rm: ResourceManager::new(resource_config, data_dir),
+                bpm: BackpressureManager::new(BackpressureConfig::default()),
+                ac: AdmissionController::new(AdmissionControlConfig::default()),
+                ql: QueryLimitsConfig::default(),
+                loader,
+                temp,
+            }
+        }
+
+        fn seed(
+            &mut self,
+            spec: &SeedSpec,
+            rng_seed: u64,
+            truncate: Option<&[String]>,
+        ) -> SeedResult<SeedReport> {
+            // Each CLI run boots with a fresh reader
+            self.storage_r = StorageReader::open_from_data_dir(self.temp.path()).unwrap();
+            let handler = ApiHandler::new("default");
+            let mut sys = Subsystems {
+                schema_loader: &mut self.loader,
+                wal_writer: &mut self.wal,
+                storage_writer: &mut self.storage_w,
+                storage_reader: &mut self.storage_r,
+                index_manager: &mut self.index,
+                statistics: &mut self.statistics,
+                resource_manager: &self.rm,
+                backpressure_manager: &self.bpm,
+                admission_controller: &self.ac,
+                query_limits: &self.ql,
+            };
+            seed(spec, rng_seed, truncate, &handler, &mut sys, |_| {})
+        }
+
+        /// Live documents of `collection`, by `_id`
+        fn stored(&self, collection: &str) -> Vec<Value> {
+            let mut reader = StorageReader::open_from_data_dir(self.temp.path()).unwrap();
+            let mut documents: Vec<Value> = reader
+                .build_document_map()
+                .unwrap()
+                .into_values()
+                .filter(|r| r.schema_id == collection && !r.is_tombstone)
+                .map(|r| serde_json::from_slice(&r.document_body).unwrap())
+                .collect();
+            documents.sort_by(|a, b| a["_id"].as_str().cmp(&b["_id"].as_str()));
+            documents
+        }
+    }
+
+    fn spec() -> SeedSpec {
+        serde_yaml::from_str(SPEC).unwrap()
+    }
+
+    #[test]
+    fn test_same_seed_same_documents() {
+        let spec = spec();
+        let first = generate(&spec, 42).unwrap();
+        assert_eq!(first, generate(&spec, 42).unwrap());
+        assert_ne!(first, generate(&spec, 43).unwrap());
+
+        // Through the insert path too
+        let (mut a, mut b) = (Env::new(), Env::new());
+        a.seed(&spec, 42, None).unwrap();
+        b.seed(&spec, 42, None).unwrap();
+        for collection in ["users", "posts"] {
+            assert_eq!(a.stored(collection), b.stored(collection));
+        }
+        assert_eq!(a.stored("users").len(), 10);
+        assert_eq!(a.stored("posts").len(), 25);
+    }
+
+    #[test]
+    fn test_references_resolve_to_parents() {
+        let mut env = Env::new();
+        let mut batches = Vec::new();
+        let handler = ApiHandler::new("default");
+        let mut sys = Subsystems {
+            schema_loader: &mut env.loader,
+            wal_writer: &mut env.wal,
+            storage_writer: &mut env.storage_w,
+            storage_reader: &mut env.storage_r,
+            index_manager: &mut env.index,
+            statistics: &mut env.statistics,
+            resource_manager: &env.rm,
+            backpressure_manager: &env.bpm,
+            admission_controller: &env.ac,
+            query_limits: &env.ql,
+        };
+        seed(&spec(), 7, None, &handler, &mut sys, |p| {
+            batches.push((p.collection.to_string(), p.inserted, p.total))
+        })
+        .unwrap();
+        assert_eq!(batches.len(), 3 + 7);
+        assert_eq!(batches[2], ("users".to_string(), 10, 10));
+
+        let users: HashSet<_> = env
+            .stored("users")
+            .into_iter()
+            .map(|u| u["_id"].clone())
+            .collect();
+        let posts = env.stored("posts");
+        assert_eq!(posts.len(), 25);
+        assert!(posts.iter().all(|p| users.contains(&p["author_id"])));
+    }
+
+    #[test]
+    fn test_schema_violation_fails_before_any_insert() {
+        let mut env = Env::new();
+        let mut spec = spec();
+        // `status` is a string, so every post violates the schema
+        spec.collections[1]
+            .fields
+            .insert("status".to_string(), FieldGenerator::Int { min: 0, max: 1 });
+
+        let err = env.seed(&spec, 42, None).unwrap_err();
+        assert!(
+            matches!(err, SeedError::SchemaViolation { ref collection, .. } if collection == "posts")
+        );
+        assert!(env.stored("users").is_empty());
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut env = Env::new();
+        let spec = spec();
+        env.seed(&spec, 1, None).unwrap();
+
+        let err = env
+            .seed(&spec, 2, Some(&["comments".to_string()]))
+            .unwrap_err();
+        assert!(matches!(err, SeedError::TruncateNotInSpec { .. }));
+        assert_eq!(env.stored("users").len(), 10);
+
+        let report = env.seed(&spec, 2, Some(&[])).unwrap();
+        assert_eq!(report.truncated["users"], 10);
+        assert_eq!(report.truncated["posts"], 25);
+        assert_eq!(env.stored("users").len(), 10);
+        assert_eq!(env.stored("posts").len(), 25);
+    }
+
+    #[test]
+    fn test_invalid_spec() {
+        let mut spec = spec();
+        spec.collections.swap(0, 1);
+        assert!(matches!(
+            generate(&spec, 0),
+            Err(SeedError::InvalidSpec { .. })
+        ));
+    }
+}
@@ -0,0 +1,584 @@
+//! Hash aggregation for the aggregate operation
+//!
+//! Documents stream through a `HashAggregator`, which keeps one state per
+//! distinct group key. Every group is charged to a `MemoryTracker`; there
+//! is no spilling, so aggregation fails with `AERO_EXECUTION_LIMIT` once
+//! the group table would exceed the memory budget or the group limit.
+//!
+//! # Semantics
+//!
+//! - A missing group field groups as `null`
+//! - `count("*")` counts documents; `count(field)` counts non-null values
+//! - `sum` and `avg` use numeric values only; `min` and `max` compare
+//!   scalar values with the sort ordering (null < bool < number < string)
+//! - An aggregate with no applicable values is `null` (`count` is 0)
+//! - Groups are returned ordered by group key
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use super::errors::{ExecutorError, ExecutorResult};
+use super::sorter::ResultSorter;
+use crate::resource_limits::MemoryTracker;
+
+/// Fixed bytes charged per group (map entry and bookkeeping)
+const GROUP_OVERHEAD_BYTES: u64 = 64;
+
+/// Bytes charged per aggregate state in a group
+const STATE_BYTES: u64 = std::mem::size_of::<AggregateState>() as u64;
+
+/// Aggregate function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    /// Every aggregate function, in documentation order
+    pub const ALL: [AggregateFunction; 5] = [
+        AggregateFunction::Count,
+        AggregateFunction::Sum,
+        AggregateFunction::Avg,
+        AggregateFunction::Min,
+        AggregateFunction::Max,
+    ];
+
+    /// Parse a function name (`count`, `sum`, `avg`, `min`, `max`)
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.as_str() == name)
+    }
+
+    /// Function name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AggregateFunction::Count => "count",
+            AggregateFunction::Sum => "sum",
+            AggregateFunction::Avg => "avg",
+            AggregateFunction::Min => "min",
+            AggregateFunction::Max => "max",
+        }
+    }
+}
+
+/// One aggregate: a function over a field (`None` for `count("*")`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    pub field: Option<String>,
+}
+
+impl Aggregate {
+    /// Aggregate `function` over `field`; `"*"` is accepted for `count` only
+    pub fn new(function: AggregateFunction, field: &str) -> Result<Self, String> {
+        let field = match field {
+            "*" if function == AggregateFunction::Count => None,
+            "*" => return Err(format!("{}(*) is not supported", function.as_str())),
+            "" => return Err(format!("{} requires a field", function.as_str())),
+            field => Some(field.to_string()),
+        };
+        Ok(Self { function, field })
+    }
+}
+
+/// Validated aggregation: group fields and named aggregates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateSpec {
+    group_by: Vec<String>,
+    aggregates: Vec<(String, Aggregate)>,
+}
+
+impl AggregateSpec {
+    /// Validate an aggregation
+    ///
+    /// Requires at least one group field and one aggregate. Group fields
+    /// and aggregate names must be unique and must not overlap, since both
+    /// become keys of each result object.
+    pub fn new(
+        group_by: Vec<String>,
+        aggregates: Vec<(String, Aggregate)>,
+    ) -> Result<Self, String> {
+        if group_by.is_empty() {
+            return Err("group_by requires at least one field".to_string());
+        }
+        if aggregates.is_empty() {
+            return Err("aggregates requires at least one aggregate".to_string());
+        }
+
+        let mut names: Vec<&str> = Vec::new();
+        for name in group_by
+            .iter()
+            .chain(aggregates.iter().map(|(name, _)| name))
+        {
+            if name.is_empty() {
+                return Err("group_by fields and aggregate names must not be empty".to_string());
+            }
+            if names.contains(&name.as_str()) {
+                return Err(format!(
+                    "'{}' appears more than once in group_by and aggregates",
+                    name
+                ));
+            }
+            names.push(name);
+        }
+
+        Ok(Self {
+            group_by,
+            aggregates,
+        })
+    }
+
+    /// Parse aggregates given as `{"<name>": {"<function>": "<field>"}}`
+    pub fn from_json(group_by: Vec<String>, aggregates: &Value) -> Result<Self, String> {
+        let obj = aggregates
+            .as_object()
+            .ok_or_else(|| "aggregates must be an object".to_string())?;
+
+        let mut parsed = Vec::with_capacity(obj.len());
+        for (name, definition) in obj {
+            let (function, field) = match definition.as_object() {
+                Some(def) if def.len() == 1 => def.iter().next().unwrap(),
+                _ => {
+                    return Err(format!(
+                        "aggregate '{}' must be an object with one function, e.g. {{\"sum\": \"amount\"}}",
+                        name
+                    ))
+                }
+            };
+            let function = AggregateFunction::parse(function)
+                .ok_or_else(|| format!("Unknown aggregate function: {}", function))?;
+            let field = field
+                .as_str()
+                .ok_or_else(|| format!("aggregate '{}' field must be a string", name))?;
+            parsed.push((name.clone(), Aggregate::new(function, field)?));
+        }
+
+        Self::new(group_by, parsed)
+    }
+
+    /// Group fields
+    pub fn group_by(&self) -> &[String] {
+        &self.group_by
+    }
+
+    /// Named aggregates
+    pub fn aggregates(&self) -> &[(String, Aggregate)] {
+        &self.aggregates
+    }
+}
+
+/// Running state of one aggregate in one group
+#[derive(Debug, Clone)]
+enum AggregateState {
+    Count(u64),
+    /// Integer sum until a float or an overflow, then float
+    Sum {
+        int: i64,
+        float: f64,
+        is_float: bool,
+        seen: bool,
+    },
+    Avg {
+        sum: f64,
+        count: u64,
+    },
+    Min(Option<Value>),
+    Max(Option<Value>),
+}
+
+impl AggregateState {
+    fn new(function: AggregateFunction) -> Self {
+        match function {
+            AggregateFunction::Count => AggregateState::Count(0),
+            AggregateFunction::Sum => AggregateState::Sum {
+                int: 0,
+                float: 0.0,
+                is_float: false,
+                seen: false,
+            },
+            AggregateFunction::Avg => AggregateState::Avg { sum: 0.0, count: 0 },
+            AggregateFunction::Min => AggregateState::Min(None),
+            AggregateFunction::Max => AggregateState::Max(None),
+        }
+    }
+
+    /// Fold in a document's value (`None` if absent). Returns the bytes the
+    /// state grew by.
+    fn update(&mut self, value: Option<&Value>, count_all: bool) -> u64 {
+        let value = value.filter(|v| !v.is_null());
+        match self {
+            AggregateState::Count(n) => {
+                if count_all || value.is_some() {
+                    *n += 1;
+                }
+                0
+            }
+            AggregateState::Sum {
+                int,
+                float,
+                is_float,
+                seen,
+            } => {
+                if let Some(Value::Number(n)) = value {
+                    *seen = true;
+                    match (n.as_i64(), *is_float) {
+                        (Some(i), false) => match int.checked_add(i) {
+                            Some(total) => *int = total,
+                            None => {
+                                *float = *int as f64 + i as f64;
+                                *is_float = true;
+                            }
+                        },
+                        (_, false) => {
+                            *float = *int as f64 + n.as_f64().unwrap_or(0.0);
+                            *is_float = true;
+                        }
+                        (_, true) => *float += n.as_f64().unwrap_or(0.0),
+                    }
+                }
+                0
+            }
+            AggregateState::Avg { sum, count } => {
+                if let Some(Value::Number(n)) = value {
+                    *sum += n.as_f64().unwrap_or(0.0);
+                    *count += 1;
+                }
+                0
+            }
+            AggregateState::Min(current) => Self::replace_if(current, value, Ordering::Less),
+            AggregateState::Max(current) => Self::replace_if(current, value, Ordering::Greater),
+        }
+    }
+
+    /// Replace `current` with a scalar `value` ordering `wanted` against it
+    fn replace_if(current: &mut Option<Value>, value: Option<&Value>, wanted: Ordering) -> u64 {
+        let Some(value) = value.filter(|v| !v.is_array() && !v.is_object()) else {
+            return 0;
+        };
+        let replace = match current {
+            None => true,
+            Some(held) => ResultSorter::compare_values(Some(value), Some(held)) == wanted,
+        };
+        if !replace {
+            return 0;
+        }
+        let grown = value_bytes(value).saturating_sub(current.as_ref().map_or(0, value_bytes));
+        *current = Some(value.clone());
+        grown
+    }
+
+    fn result(&self) -> Value {
+        match self {
+            AggregateState::Count(n) => Value::from(*n),
+            AggregateState::Sum { seen: false, .. } => Value::Null,
+            AggregateState::Sum {
+                int,
+                float,
+                is_float,
+                ..
+            } => {
+                if *is_float {
+                    Value::from(*float)
+                } else {
+                    Value::from(*int)
+                }
+            }
+            AggregateState::Avg { count: 0, .. } => Value::Null,
+            AggregateState::Avg { sum, count } => Value::from(*sum / *count as f64),
+            AggregateState::Min(v) | AggregateState::Max(v) => v.clone().unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// Approximate heap size of a JSON value
+fn value_bytes(value: &Value) -> u64 {
+    match value {
+        Value::String(s) => s.len() as u64,
+        _ => 8,
+    }
+}
+
+/// One group: its key values and aggregate states
+#[derive(Debug)]
+struct Group {
+    key: Vec<Value>,
+    states: Vec<AggregateState>,
+}
+
+/// Streaming hash-grouping operator
+///
+/// Memory charged to the tracker is released when the aggregator is
+/// dropped.
+pub struct HashAggregator<'a> {
+    spec: AggregateSpec,
+    memory: &'a MemoryTracker,
+    max_groups: usize,
+    /// Groups by serialized key
+    groups: HashMap<String, Group>,
+    charged: u64,
+}
+
+impl<'a> HashAggregator<'a> {
+    /// Aggregator charging `memory` and allowing at most `max_groups` groups
+    pub fn new(spec: AggregateSpec, memory: &'a MemoryTracker, max_groups: usize) -> Self {
+        Self {
+            spec,
+            memory,
+            max_groups,
+            groups: HashMap::new(),
+            charged: 0,
+        }
+    }
+
+    /// Number of groups so far
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Add a document to its group, creating the group if needed
+    pub fn push(&mut self, document: &Value) -> ExecutorResult<()> {
+        let key: Vec<Value> = self
+            .spec
+            .group_by
+            .iter()
+            .map(|field| document.get(field).cloned().unwrap_or(Value::Null))
+            .collect();
+        let serialized = serde_json::to_string(&key).expect("JSON values serialize");
+
+        if !self.groups.contains_key(&serialized) {
+            self.add_group(serialized.clone(), key)?;
+        }
+
+        let group = self
+            .groups
+            .get_mut(&serialized)
+            .expect("group just ensured");
+        let mut grown = 0;
+        for ((_, aggregate), state) in self.spec.aggregates.iter().zip(group.states.iter_mut()) {
+            let value = aggregate
+                .field
+                .as_ref()
+                .and_then(|field| document.get(field));
+            grown += state.update(value, aggregate.field.is_none());
+        }
+        self.charge(grown)
+    }
+
+    /// Result objects (group fields and aggregates), ordered by group key
+    pub fn finish(mut self) -> Vec<Value> {
+        let mut groups: Vec<(String, Group)> =
+            std::mem::take(&mut self.groups).into_iter().collect();
+        groups.sort_by(|(a_serialized, a), (b_serialized, b)| {
+            a.key
+                .iter()
+                .zip(&b.key)
+                .map(|(x, y)| ResultSorter::compare_values(Some(x), Some(y)))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a_serialized.cmp(b_serialized))
+        });
+
+        groups
+            .into_iter()
+            .map(|(_, group)| {
+                let mut row = Map::new();
+                for (field, value) in self.spec.group_by.iter().zip(group.key) {
+                    row.insert(field.clone(), value);
+                }
+                for ((name, _), state) in self.spec.aggregates.iter().zip(&group.states) {
+                    row.insert(name.clone(), state.result());
+                }
+                Value::Object(row)
+            })
+            .collect()
+    }
+
+    fn add_group(&mut self, serialized: String, key: Vec<Value>) -> ExecutorResult<()> {
+        if self.groups.len() >= self.max_groups {
+            return Err(ExecutorError::execution_limit(format!(
+                "Aggregation exceeds {} groups (query_limits.max_result_set_docs)",
+                self.max_groups
+            )));
+        }
+
+        let bytes = GROUP_OVERHEAD_BYTES
+            + serialized.len() as u64 * 2
+            + self.spec.aggregates.len() as u64 * STATE_BYTES;
+        self.charge(bytes)?;
+
+        let states = self
+            .spec
+            .aggregates
+            .iter()
+            .map(|(_, aggregate)| AggregateState::new(aggregate.function))
+            .collect();
+        self.groups.insert(serialized, Group { key, states });
+        Ok(())
+    }
+
+    fn charge(&mut self, bytes: u64) -> ExecutorResult<()> {
+        if bytes == 0 {
+            return Ok(());
+        }
+        self.memory.try_allocate(bytes).map_err(|e| {
+            ExecutorError::execution_limit(format!(
+                "Aggregation group table exceeds the memory budget after {} groups \
+                 (spilling is not supported): {}",
+                self.groups.len(),
+                e
+            ))
+        })?;
+        self.charged += bytes;
+        Ok(())
+    }
+}
+
+impl Drop for HashAggregator<'_> {
+    fn drop(&mut self) {
+        self.memory.release(self.charged);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spec(group_by: &[&str], aggregates: Value) -> AggregateSpec {
+        AggregateSpec::from_json(
+            group_by.iter().map(|f| f.to_string()).collect(),
+            &aggregates,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_multi_field_grouping() {
+        let memory = MemoryTracker::new(1 << 20);
+        let spec = spec(
+            &["region", "status"],
+            json!({
+                "n": {"count": "*"},
+                "total": {"sum": "amount"},
+                "mean": {"avg": "amount"},
+                "low": {"min": "amount"},
+                "high": {"max": "amount"}
+            }),
+        );
+        let mut aggregator = HashAggregator::new(spec, &memory, 100);
+
+        for doc in [
+            json!({"region": "eu", "status": "paid", "amount": 10}),
+            json!({"region": "us", "status": "paid", "amount": 5}),
+            json!({"region": "eu", "status": "open", "amount": 7}),
+            json!({"region": "eu", "status": "paid", "amount": 2.5}),
+            json!({"region": "eu", "status": "paid"}),
+        ] {
+            aggregator.push(&doc).unwrap();
+        }
+        assert_eq!(aggregator.group_count(), 3);
+        assert!(memory.current() > 0);
+
+        let rows = aggregator.finish();
+        assert_eq!(memory.current(), 0);
+        assert_eq!(
+            rows,
+            vec![
+                json!({"region": "eu", "status": "open", "n": 1, "total": 7, "mean": 7.0, "low": 7, "high": 7}),
+                json!({"region": "eu", "status": "paid", "n": 3, "total": 12.5, "mean": 6.25, "low": 2.5, "high": 10}),
+                json!({"region": "us", "status": "paid", "n": 1, "total": 5, "mean": 5.0, "low": 5, "high": 5}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_null_group_keys() {
+        let memory = MemoryTracker::new(1 << 20);
+        let spec = spec(
+            &["team"],
+            json!({"n": {"count": "*"}, "scored": {"count": "score"}}),
+        );
+        let mut aggregator = HashAggregator::new(spec, &memory, 100);
+
+        for doc in [
+            json!({"team": "b", "score": 1}),
+            json!({"team": null, "score": 2}),
+            json!({"score": null}),
+            json!({"team": "a"}),
+        ] {
+            aggregator.push(&doc).unwrap();
+        }
+
+        // Missing and null share the null group, which sorts first
+        assert_eq!(
+            aggregator.finish(),
+            vec![
+                json!({"team": null, "n": 2, "scored": 1}),
+                json!({"team": "a", "n": 1, "scored": 0}),
+                json!({"team": "b", "n": 1, "scored": 1}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_aggregates_are_null() {
+        let memory = MemoryTracker::new(1 << 20);
+        let spec = spec(
+            &["k"],
+            json!({"s": {"sum": "v"}, "a": {"avg": "v"}, "m": {"min": "v"}}),
+        );
+        let mut aggregator = HashAggregator::new(spec, &memory, 100);
+        aggregator.push(&json!({"k": 1, "v": "text"})).unwrap();
+
+        // Strings are not summed, but min compares them
+        assert_eq!(
+            aggregator.finish(),
+            vec![json!({"k": 1, "s": null, "a": null, "m": "text"})]
+        );
+    }
+
+    #[test]
+    fn test_group_and_memory_limits() {
+        let memory = MemoryTracker::new(1 << 20);
+        let mut aggregator =
+            HashAggregator::new(spec(&["k"], json!({"n": {"count": "*"}})), &memory, 2);
+        aggregator.push(&json!({"k": 1})).unwrap();
+        aggregator.push(&json!({"k": 2})).unwrap();
+        aggregator.push(&json!({"k": 2})).unwrap();
+        let err = aggregator.push(&json!({"k": 3})).unwrap_err();
+        assert_eq!(err.code().code(), "AERO_EXECUTION_LIMIT");
+        assert!(err.message().contains("2 groups"));
+        drop(aggregator);
+        assert_eq!(memory.current(), 0);
+
+        let small = MemoryTracker::new(200);
+        let mut aggregator =
+            HashAggregator::new(spec(&["k"], json!({"n": {"count": "*"}})), &small, 100);
+        let err = (0..100)
+            .find_map(|i| aggregator.push(&json!({"k": i})).err())
+            .expect("memory budget exceeded");
+        assert_eq!(err.code().code(), "AERO_EXECUTION_LIMIT");
+        assert!(err.message().contains("memory budget"));
+        drop(aggregator);
+        assert_eq!(small.current(), 0);
+    }
+
+    #[test]
+    fn test_spec_validation() {
+        let group = || vec!["k".to_string()];
+        assert!(AggregateSpec::from_json(vec![], &json!({"n": {"count": "*"}})).is_err());
+        assert!(AggregateSpec::from_json(group(), &json!({})).is_err());
+        assert!(AggregateSpec::from_json(group(), &json!({"n": {"sum": "*"}})).is_err());
+        assert!(AggregateSpec::from_json(group(), &json!({"n": {"median": "v"}})).is_err());
+        assert!(
+            AggregateSpec::from_json(group(), &json!({"n": {"sum": "v", "max": "v"}})).is_err()
+        );
+        assert!(AggregateSpec::from_json(group(), &json!({"k": {"count": "*"}})).is_err());
+        assert!(AggregateSpec::from_json(group(), &json!([])).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Request deadlines for cooperative cancellation
+//!
+//! Execution checks its deadline at batch boundaries (every
+//! `DEADLINE_CHECK_INTERVAL` documents) and between phases, and aborts
+//! with `AERO_QUERY_TIMEOUT` once it has passed. Work between two checks
+//! is never interrupted.
+
+use std::time::{Duration, Instant};
+
+use super::errors::{ExecutorError, ExecutorResult};
+
+/// Documents examined between deadline checks
+pub const DEADLINE_CHECK_INTERVAL: usize = 64;
+
+/// Point in time after which a request is abandoned
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    started: Instant,
+    timeout: Duration,
+}
+
+impl Deadline {
+    /// Deadline `timeout_ms` from now
+    pub fn after_ms(timeout_ms: u64) -> Self {
+        Self {
+            started: Instant::now(),
+            timeout: Duration::from_millis(timeout_ms),
+        }
+    }
+
+    /// The timeout in milliseconds
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout.as_millis() as u64
+    }
+
+    /// Whether the deadline has passed
+    pub fn is_expired(&self) -> bool {
+        self.started.elapsed() >= self.timeout
+    }
+
+    /// Fail with a timeout if the deadline has passed
+    pub fn check(&self, documents_examined: usize) -> ExecutorResult<()> {
+        if self.is_expired() {
+            return Err(ExecutorError::query_timeout(
+                self.timeout_ms(),
+                documents_examined,
+            ));
+        }
+        Ok(())
+    }
+
+    /// `check` at batch boundaries only (every `DEADLINE_CHECK_INTERVAL`
+    /// documents, including before the first)
+    pub fn check_batch(&self, documents_examined: usize) -> ExecutorResult<()> {
+        if documents_examined.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+            self.check(documents_examined)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expired_deadline_reports_examined_documents() {
+        let deadline = Deadline::after_ms(0);
+        let err = deadline.check(42).unwrap_err();
+        assert_eq!(err.code().code(), "AERO_QUERY_TIMEOUT");
+        assert_eq!(err.documents_examined(), Some(42));
+    }
+
+    #[test]
+    fn test_batch_checks_only_at_boundaries() {
+        let deadline = Deadline::after_ms(0);
+        assert!(deadline.check_batch(1).is_ok());
+        assert!(deadline.check_batch(DEADLINE_CHECK_INTERVAL - 1).is_ok());
+        assert!(deadline.check_batch(DEADLINE_CHECK_INTERVAL).is_err());
+
+        let generous = Deadline::after_ms(60_000);
+        assert!(generous.check_batch(0).is_ok());
+    }
+}
@@ -4,6 +4,8 @@
 //! - AERO_EXECUTION_FAILED (ERROR)
 //! - AERO_DATA_CORRUPTION (FATAL)
 //! - AERO_EXECUTION_LIMIT (ERROR)
+//! - AERO_QUERY_TIMEOUT (ERROR)
+//! - AERO_SORT_MEMORY_EXCEEDED (ERROR)
 
 use std::fmt;
 
@@ -34,6 +36,10 @@ pub enum ExecutorErrorCode {
     AeroDataCorruption,
     /// Limit exceeded during execution
     AeroExecutionLimit,
+    /// Request deadline passed during execution
+    AeroQueryTimeout,
+    /// In-memory sort would exceed its memory budget
+    AeroSortMemoryExceeded,
 }
 
 impl ExecutorErrorCode {
@@ -43,6 +49,8 @@ impl ExecutorErrorCode {
             ExecutorErrorCode::AeroExecutionFailed => "AERO_EXECUTION_FAILED",
             ExecutorErrorCode::AeroDataCorruption => "AERO_DATA_CORRUPTION",
             ExecutorErrorCode::AeroExecutionLimit => "AERO_EXECUTION_LIMIT",
+            ExecutorErrorCode::AeroQueryTimeout => "AERO_QUERY_TIMEOUT",
+            ExecutorErrorCode::AeroSortMemoryExceeded => "AERO_SORT_MEMORY_EXCEEDED",
         }
     }
 
@@ -60,6 +68,8 @@ impl ExecutorErrorCode {
             ExecutorErrorCode::AeroExecutionFailed => "T2",
             ExecutorErrorCode::AeroDataCorruption => "D2",
             ExecutorErrorCode::AeroExecutionLimit => "Q1",
+            ExecutorErrorCode::AeroQueryTimeout => "Q1",
+            ExecutorErrorCode::AeroSortMemoryExceeded => "Q1",
         }
     }
 }
@@ -79,6 +89,8 @@ pub struct ExecutorError {
     message: String,
     /// File offset if applicable
     offset: Option<u64>,
+    /// Documents examined before a timeout
+    documents_examined: Option<usize>,
 }
 
 impl ExecutorError {
@@ -88,6 +100,7 @@ impl ExecutorError {
             code: ExecutorErrorCode::AeroExecutionFailed,
             message: reason.into(),
             offset: None,
+            documents_examined: None,
         }
     }
 
@@ -97,6 +110,7 @@ impl ExecutorError {
             code: ExecutorErrorCode::AeroDataCorruption,
             message: format!("Data corruption at offset {}: {}", offset, reason.into()),
             offset: Some(offset),
+            documents_examined: None,
         }
     }
 
@@ -106,6 +120,34 @@ impl ExecutorError {
             code: ExecutorErrorCode::AeroExecutionLimit,
             message: reason.into(),
             offset: None,
+            documents_examined: None,
+        }
+    }
+
+    /// Create a timeout error after `documents_examined` documents
+    pub fn query_timeout(timeout_ms: u64, documents_examined: usize) -> Self {
+        Self {
+            code: ExecutorErrorCode::AeroQueryTimeout,
+            message: format!(
+                "Query exceeded its {} ms timeout after examining {} documents",
+                timeout_ms, documents_examined
+            ),
+            offset: None,
+            documents_examined: Some(documents_examined),
+        }
+    }
+
+    /// Create a sort memory error: holding the sorted documents would pass
+    /// `max_sort_bytes`
+    pub fn sort_memory_exceeded(max_sort_bytes: u64) -> Self {
+        Self {
+            code: ExecutorErrorCode::AeroSortMemoryExceeded,
+            message: format!(
+                "In-memory sort exceeds max_sort_bytes ({} bytes); narrow the filter, lower limit + offset, or sort on the scanned index",
+                max_sort_bytes
+            ),
+            offset: None,
+            documents_examined: None,
         }
     }
 
@@ -134,6 +176,11 @@ impl ExecutorError {
         self.offset
     }
 
+    /// Returns the documents examined before a timeout
+    pub fn documents_examined(&self) -> Option<usize> {
+        self.documents_examined
+    }
+
     /// Returns whether this is a fatal error
     pub fn is_fatal(&self) -> bool {
         self.severity() == Severity::Fatal
@@ -177,6 +224,14 @@ mod tests {
             ExecutorErrorCode::AeroExecutionLimit.code(),
             "AERO_EXECUTION_LIMIT"
         );
+        assert_eq!(
+            ExecutorErrorCode::AeroQueryTimeout.code(),
+            "AERO_QUERY_TIMEOUT"
+        );
+        assert_eq!(
+            ExecutorErrorCode::AeroSortMemoryExceeded.code(),
+            "AERO_SORT_MEMORY_EXCEEDED"
+        );
     }
 
     #[test]
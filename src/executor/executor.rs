@@ -9,18 +9,40 @@
 //! 4. Filter documents strictly according to predicates
 //! 5. Apply schema version filtering
 //! 6. Apply sort (if specified)
-//! 7. Apply limit
+//! 7. Apply offset and limit
 //! 8. Return ordered results
+//!
+//! When the plan's index scan provides the sort order, offsets are read in
+//! key order; without a sort they are read in scan order. Either way
+//! reading stops once one document past limit + offset matches, and
+//! `scanned_count` reports the documents actually read. An in-memory sort
+//! goes through a `SortBuffer` bounded by `max_sort_bytes` and reads every
+//! candidate.
+//!
+//! A plan's projection is applied to each matching document after its
+//! sort key is taken.
+//!
+//! With an `MvccSnapshot`, step 2 reads the version visible at the
+//! snapshot's read timestamp instead of the record at the offset.
+
+use std::collections::BTreeSet;
+use std::ops::Bound;
 
 use serde_json::Value;
 
-use crate::planner::{FilterOp, QueryPlan, ScanType};
+use crate::planner::{
+    range_bounds, FilterOp, Predicate, QueryPlan, ScanType, SortDirection, SortStrategy,
+};
+use crate::query_limits::QueryLimitsConfig;
 use crate::storage::DocumentRecord;
 
+use super::deadline::Deadline;
 use super::errors::{ExecutorError, ExecutorResult};
 use super::filters::PredicateFilter;
+use super::projection::project;
 use super::result::{ExecutionResult, ResultDocument};
-use super::sorter::ResultSorter;
+use super::snapshot::MvccSnapshot;
+use super::sorter::SortBuffer;
 
 /// Trait for looking up document offsets by index
 pub trait IndexLookup {
@@ -30,11 +52,43 @@ pub trait IndexLookup {
     /// Get all document offsets for an indexed field equality
     fn lookup_eq(&self, field: &str, value: &Value) -> Vec<u64>;
 
-    /// Get all document offsets for an indexed field range
-    fn lookup_range(&self, field: &str, min: Option<&Value>, max: Option<&Value>) -> Vec<u64>;
+    /// Get all document offsets for an indexed field range between
+    /// inclusive or exclusive bounds
+    fn lookup_range(&self, field: &str, lower: Bound<&Value>, upper: Bound<&Value>) -> Vec<u64>;
+
+    /// Get all document offsets for a compound index prefix: equality on the
+    /// leading fields, then a range on the next field
+    fn lookup_compound(
+        &self,
+        index: &str,
+        prefix: &[&Value],
+        lower: Bound<&Value>,
+        upper: Bound<&Value>,
+    ) -> Vec<u64>;
 
     /// Get all document offsets in primary key order
     fn all_offsets_pk_order(&self) -> Vec<u64>;
+
+    /// Get document offsets for an indexed field range in key order,
+    /// reversed for `Desc`
+    fn scan_ordered(
+        &self,
+        field: &str,
+        lower: Bound<&Value>,
+        upper: Bound<&Value>,
+        direction: SortDirection,
+    ) -> Vec<u64>;
+
+    /// Get document offsets for a compound index prefix in key order,
+    /// reversed for `Desc`
+    fn scan_compound_ordered(
+        &self,
+        index: &str,
+        prefix: &[&Value],
+        lower: Bound<&Value>,
+        upper: Bound<&Value>,
+        direction: SortDirection,
+    ) -> Vec<u64>;
 }
 
 /// Trait for reading documents from storage
@@ -43,18 +97,69 @@ pub trait StorageRead {
     /// Returns None if offset is invalid
     /// Returns Err if checksum fails (corruption)
     fn read_at(&mut self, offset: u64) -> ExecutorResult<Option<DocumentRecord>>;
+
+    /// Read the version of the document at `offset` that `snapshot` sees:
+    /// its newest version committed at or before the read timestamp.
+    /// Returns None if the document did not exist as of the snapshot.
+    ///
+    /// The default is for storage without version history, whose only
+    /// version of a document is the record at `offset`.
+    fn read_as_of(
+        &mut self,
+        offset: u64,
+        snapshot: &MvccSnapshot,
+    ) -> ExecutorResult<Option<DocumentRecord>> {
+        let _ = snapshot;
+        self.read_at(offset)
+    }
 }
 
 /// Query executor that processes plans against storage
 pub struct QueryExecutor<'a, I: IndexLookup, S: StorageRead> {
     index: &'a I,
     storage: &'a mut S,
+    deadline: Option<Deadline>,
+    snapshot: Option<MvccSnapshot>,
+    max_sort_bytes: u64,
 }
 
 impl<'a, I: IndexLookup, S: StorageRead> QueryExecutor<'a, I, S> {
     /// Creates a new executor
     pub fn new(index: &'a I, storage: &'a mut S) -> Self {
-        Self { index, storage }
+        Self {
+            index,
+            storage,
+            deadline: None,
+            snapshot: None,
+            max_sort_bytes: QueryLimitsConfig::default().max_sort_bytes,
+        }
+    }
+
+    /// Memory budget for in-memory sorts; past it execution fails with
+    /// `AERO_SORT_MEMORY_EXCEEDED`
+    pub fn with_max_sort_bytes(mut self, max_sort_bytes: u64) -> Self {
+        self.max_sort_bytes = max_sort_bytes;
+        self
+    }
+
+    /// Abort execution with `AERO_QUERY_TIMEOUT` once `deadline` passes
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Read every document as of `snapshot`'s read timestamp
+    pub fn with_snapshot(mut self, snapshot: MvccSnapshot) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Check the deadline (if any) at a phase boundary
+    fn check_deadline(&self, documents_examined: usize) -> ExecutorResult<()> {
+        match &self.deadline {
+            Some(deadline) => deadline.check(documents_examined),
+            None => Ok(()),
+        }
     }
 
     /// Executes a query plan and returns results.
@@ -64,15 +169,37 @@ impl<'a, I: IndexLookup, S: StorageRead> QueryExecutor<'a, I, S> {
         // Step 1: Use chosen_index to obtain candidate document offsets
         let offsets = self.get_candidate_offsets(plan);
 
+        // An in-memory sort buffers every match. Otherwise documents arrive
+        // in result order, from an index-ordered or unsorted scan, and the
+        // scan stops one match past the window, which is enough to tell
+        // whether the limit applied.
+        let mut sort_buffer = SortBuffer::for_plan(plan, self.max_sort_bytes);
+        let streaming = sort_buffer.is_none();
+        let window = plan.limit.saturating_add(plan.offset);
+
         // Steps 2-5: Read, validate, filter, and check schema
         let mut candidates = Vec::new();
+        let mut matched: u64 = 0;
         let mut scanned_count = 0;
 
         for offset in offsets {
+            if streaming && matched > window {
+                break;
+            }
+
+            // Cooperative cancellation at batch boundaries; the first check
+            // covers the index lookup
+            if let Some(deadline) = &self.deadline {
+                deadline.check_batch(scanned_count)?;
+            }
             scanned_count += 1;
 
             // Step 2-3: Read document with checksum validation
-            let record = match self.storage.read_at(offset)? {
+            let record = match &self.snapshot {
+                Some(snapshot) => self.storage.read_as_of(offset, snapshot)?,
+                None => self.storage.read_at(offset)?,
+            };
+            let record = match record {
                 Some(r) => r,
                 None => continue, // Invalid offset, skip
             };
@@ -94,8 +221,8 @@ impl<'a, I: IndexLookup, S: StorageRead> QueryExecutor<'a, I, S> {
                 Err(_) => continue, // Invalid JSON, skip
             };
 
-            // Step 4: Filter according to predicates
-            if !PredicateFilter::matches(&body, &plan.predicates) {
+            // Step 4: Filter according to predicates and expressions
+            if !PredicateFilter::matches_plan(&body, plan) {
                 continue;
             }
 
@@ -106,24 +233,43 @@ impl<'a, I: IndexLookup, S: StorageRead> QueryExecutor<'a, I, S> {
                 .last()
                 .unwrap_or(&record.document_id);
 
-            candidates.push(ResultDocument::new(
+            matched += 1;
+            // Take the sort key before projection may drop its field
+            let sort_key = plan.sort.as_ref().map(|s| body.get(&s.field).cloned());
+            let body = match &plan.projection {
+                Some(projection) => project(&body, projection),
+                None => body,
+            };
+            let document = ResultDocument::new(
                 doc_id,
                 &record.schema_id,
                 &record.schema_version,
                 body,
                 offset,
-            ));
+            );
+
+            match (&mut sort_buffer, sort_key) {
+                (Some(buffer), Some(key)) => {
+                    buffer.push(document, key, record.document_body.len() as u64)?;
+                }
+                _ => candidates.push(document),
+            }
         }
 
-        // Step 6: Apply sort (if specified)
-        if let Some(sort_spec) = &plan.sort {
-            ResultSorter::sort(&mut candidates, sort_spec);
+        // Step 6: Finish an in-memory sort
+        if let Some(buffer) = sort_buffer {
+            self.check_deadline(scanned_count)?;
+            candidates = buffer.finish();
+            self.check_deadline(scanned_count)?;
         }
 
-        // Step 7: Apply limit
-        let limit = plan.limit as usize;
-        let limit_applied = candidates.len() > limit;
-        candidates.truncate(limit);
+        // Step 7: Apply offset and limit
+        let limit_applied = matched > window;
+        let candidates: Vec<ResultDocument> = candidates
+            .into_iter()
+            .skip(plan.offset as usize)
+            .take(plan.limit as usize)
+            .collect();
 
         // Step 8: Return ordered results
         Ok(ExecutionResult {
@@ -135,11 +281,58 @@ impl<'a, I: IndexLookup, S: StorageRead> QueryExecutor<'a, I, S> {
     }
 
     /// Gets candidate document offsets based on plan's chosen index and scan type.
+    ///
+    /// When the index provides the sort order, offsets come in key order.
+    /// An index union yields the distinct offsets of its branch scans in
+    /// storage order.
     fn get_candidate_offsets(&self, plan: &QueryPlan) -> Vec<u64> {
+        let order = match plan.sort_strategy {
+            Some(SortStrategy::Index(direction)) => Some(direction),
+            _ => None,
+        };
+
         match plan.scan_type {
+            ScanType::CompoundPrefix => match (plan.compound_bounds(), order) {
+                (Some((prefix, lower, upper)), Some(direction)) => self
+                    .index
+                    .scan_compound_ordered(&plan.chosen_index, &prefix, lower, upper, direction),
+                (Some((prefix, lower, upper)), None) => {
+                    self.index
+                        .lookup_compound(&plan.chosen_index, &prefix, lower, upper)
+                }
+                (None, _) => Vec::new(),
+            },
+            ScanType::IndexUnion => {
+                let mut offsets = BTreeSet::new();
+                for branch in plan.union.iter().flat_map(|u| &u.branches) {
+                    offsets.extend(self.single_field_offsets(
+                        branch.scan_type,
+                        &branch.index,
+                        &branch.predicates,
+                        None,
+                    ));
+                }
+                offsets.into_iter().collect()
+            }
+            scan_type => {
+                self.single_field_offsets(scan_type, &plan.chosen_index, &plan.predicates, order)
+            }
+        }
+    }
+
+    /// Offsets from a primary key, equality or range scan of `field`'s
+    /// index, bounded by `predicates`
+    fn single_field_offsets(
+        &self,
+        scan_type: ScanType,
+        field: &str,
+        predicates: &[Predicate],
+        order: Option<SortDirection>,
+    ) -> Vec<u64> {
+        match scan_type {
             ScanType::PrimaryKey => {
                 // Find the _id predicate value
-                for pred in &plan.predicates {
+                for pred in predicates {
                     if pred.field == "_id" {
                         if let FilterOp::Eq(ref val) = pred.op {
                             if let Some(pk) = val.as_str() {
@@ -152,32 +345,33 @@ impl<'a, I: IndexLookup, S: StorageRead> QueryExecutor<'a, I, S> {
             }
             ScanType::IndexedEquality => {
                 // Find the equality predicate for chosen index
-                for pred in &plan.predicates {
-                    if pred.field == plan.chosen_index {
+                for pred in predicates {
+                    if pred.field == field {
                         if let FilterOp::Eq(ref val) = pred.op {
-                            return self.index.lookup_eq(&plan.chosen_index, val);
+                            return match order {
+                                Some(direction) => self.index.scan_ordered(
+                                    field,
+                                    Bound::Included(val),
+                                    Bound::Included(val),
+                                    direction,
+                                ),
+                                None => self.index.lookup_eq(field, val),
+                            };
                         }
                     }
                 }
                 Vec::new()
             }
             ScanType::IndexedRange => {
-                // Find range bounds for chosen index
-                let mut min = None;
-                let mut max = None;
-
-                for pred in &plan.predicates {
-                    if pred.field == plan.chosen_index {
-                        match &pred.op {
-                            FilterOp::Gte(v) | FilterOp::Gt(v) => min = Some(v),
-                            FilterOp::Lte(v) | FilterOp::Lt(v) => max = Some(v),
-                            _ => {}
-                        }
-                    }
-                }
+                // Traverse only the index range the predicates allow
+                let (lower, upper) = range_bounds(predicates, field);
 
-                self.index.lookup_range(&plan.chosen_index, min, max)
+                match order {
+                    Some(direction) => self.index.scan_ordered(field, lower, upper, direction),
+                    None => self.index.lookup_range(field, lower, upper),
+                }
             }
+            ScanType::CompoundPrefix | ScanType::IndexUnion => Vec::new(),
         }
     }
 }
@@ -185,16 +379,22 @@ impl<'a, I: IndexLookup, S: StorageRead> QueryExecutor<'a, I, S> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::planner::{BoundednessProof, Predicate, SortSpec};
+    use crate::executor::ExecutorErrorCode;
+    use crate::mvcc::{CommitAuthority, CommitId, Version, VersionChain, VersionPayload};
+    use crate::planner::{
+        BoundednessProof, FilterExpr, IndexUnion, Projection, SortSpec, UnionBranch,
+    };
     use crate::storage::DocumentRecord;
     use serde_json::json;
     use std::collections::HashMap;
+    use std::sync::{mpsc, Arc, Mutex};
 
     /// Mock index for testing
     struct MockIndex {
         pk_index: HashMap<String, Vec<u64>>,
         field_indexes: HashMap<String, HashMap<String, Vec<u64>>>,
         all_offsets: Vec<u64>,
+        key_order: HashMap<String, Vec<u64>>,
     }
 
     impl MockIndex {
@@ -203,9 +403,23 @@ mod tests {
                 pk_index: HashMap::new(),
                 field_indexes: HashMap::new(),
                 all_offsets: Vec::new(),
+                key_order: HashMap::new(),
             }
         }
 
+        /// Offsets of a field's index in ascending key order
+        fn set_key_order(&mut self, field: &str, offsets: Vec<u64>) {
+            self.key_order.insert(field.to_string(), offsets);
+        }
+
+        fn ordered(&self, field: &str, direction: SortDirection) -> Vec<u64> {
+            let mut offsets = self.key_order.get(field).cloned().unwrap_or_default();
+            if direction == SortDirection::Desc {
+                offsets.reverse();
+            }
+            offsets
+        }
+
         fn add_pk(&mut self, pk: &str, offset: u64) {
             self.pk_index
                 .entry(pk.to_string())
@@ -248,8 +462,19 @@ mod tests {
         fn lookup_range(
             &self,
             _field: &str,
-            _min: Option<&Value>,
-            _max: Option<&Value>,
+            _lower: Bound<&Value>,
+            _upper: Bound<&Value>,
+        ) -> Vec<u64> {
+            // For testing, return all offsets
+            self.all_offsets.clone()
+        }
+
+        fn lookup_compound(
+            &self,
+            _index: &str,
+            _prefix: &[&Value],
+            _lower: Bound<&Value>,
+            _upper: Bound<&Value>,
         ) -> Vec<u64> {
             // For testing, return all offsets
             self.all_offsets.clone()
@@ -260,6 +485,27 @@ mod tests {
             offsets.sort();
             offsets
         }
+
+        fn scan_ordered(
+            &self,
+            field: &str,
+            _lower: Bound<&Value>,
+            _upper: Bound<&Value>,
+            direction: SortDirection,
+        ) -> Vec<u64> {
+            self.ordered(field, direction)
+        }
+
+        fn scan_compound_ordered(
+            &self,
+            index: &str,
+            _prefix: &[&Value],
+            _lower: Bound<&Value>,
+            _upper: Bound<&Value>,
+            direction: SortDirection,
+        ) -> Vec<u64> {
+            self.ordered(index, direction)
+        }
     }
 
     /// Mock storage for testing
@@ -318,9 +564,15 @@ mod tests {
             schema_version: version.to_string(),
             chosen_index: index.to_string(),
             scan_type,
+            compound: None,
+            union: None,
             predicates,
+            expressions: Vec::new(),
             sort: None,
+            sort_strategy: None,
             limit,
+            offset: 0,
+            projection: None,
             bounds_proof: BoundednessProof::pk_lookup(),
         }
     }
@@ -441,6 +693,255 @@ mod tests {
         assert!(result.limit_applied);
     }
 
+    /// Users with an email and a status, both indexed
+    fn status_fixture() -> (MockIndex, MockStorage) {
+        let mut index = MockIndex::new();
+        let mut storage = MockStorage::new();
+        for (offset, id, email, status) in [
+            (100, "user_1", "alice@example.com", "active"),
+            (200, "user_2", "bob@example.com", "banned"),
+            (300, "user_3", "carol@example.com", "active"),
+            (400, "user_4", "dave@example.com", "banned"),
+        ] {
+            index.add_pk(id, offset);
+            index.add_field_index("email", email, offset);
+            index.add_field_index("status", status, offset);
+            storage.add_record(
+                offset,
+                make_record(
+                    id,
+                    "users",
+                    "v1",
+                    json!({"_id": id, "email": email, "status": status}),
+                ),
+            );
+        }
+        (index, storage)
+    }
+
+    #[test]
+    fn test_index_union_execution() {
+        let (index, mut storage) = status_fixture();
+
+        // email = carol OR status = banned; the union reads each offset once
+        let mut plan = make_plan(
+            "users",
+            "v1",
+            "email|status",
+            ScanType::IndexUnion,
+            vec![],
+            10,
+        );
+        let branch = |field: &str, value: &str| UnionBranch {
+            index: field.to_string(),
+            scan_type: ScanType::IndexedEquality,
+            predicates: vec![Predicate::eq(field, json!(value))],
+        };
+        plan.union = Some(IndexUnion {
+            expression: 0,
+            branches: vec![
+                branch("email", "carol@example.com"),
+                branch("status", "banned"),
+                branch("email", "bob@example.com"),
+            ],
+        });
+        plan.expressions = vec![FilterExpr::or([
+            Predicate::eq("email", json!("carol@example.com")).into(),
+            Predicate::eq("status", json!("banned")).into(),
+            Predicate::eq("email", json!("bob@example.com")).into(),
+        ])];
+
+        let mut executor = QueryExecutor::new(&index, &mut storage);
+        let result = executor.execute(&plan).unwrap();
+
+        let ids: Vec<&str> = result.documents.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["user_2", "user_3", "user_4"]);
+        assert_eq!(result.scanned_count, 3);
+    }
+
+    /// `IndexLookup` over a real `IndexManager`, which traverses only the
+    /// requested range of an index
+    struct ManagerIndex(crate::index::IndexManager);
+
+    impl IndexLookup for ManagerIndex {
+        fn lookup_pk(&self, pk: &str) -> Vec<u64> {
+            self.0.lookup_pk(pk)
+        }
+
+        fn lookup_eq(&self, field: &str, value: &Value) -> Vec<u64> {
+            self.0.lookup_eq(field, value)
+        }
+
+        fn lookup_range(
+            &self,
+            field: &str,
+            lower: Bound<&Value>,
+            upper: Bound<&Value>,
+        ) -> Vec<u64> {
+            self.0.lookup_range(field, lower, upper, None)
+        }
+
+        fn lookup_compound(
+            &self,
+            index: &str,
+            prefix: &[&Value],
+            lower: Bound<&Value>,
+            upper: Bound<&Value>,
+        ) -> Vec<u64> {
+            self.0.lookup_compound(index, prefix, lower, upper, None)
+        }
+
+        fn all_offsets_pk_order(&self) -> Vec<u64> {
+            self.0.all_offsets_pk_order()
+        }
+
+        fn scan_ordered(
+            &self,
+            field: &str,
+            lower: Bound<&Value>,
+            upper: Bound<&Value>,
+            direction: SortDirection,
+        ) -> Vec<u64> {
+            self.0
+                .scan_ordered(field, lower, upper, direction == SortDirection::Desc)
+        }
+
+        fn scan_compound_ordered(
+            &self,
+            index: &str,
+            prefix: &[&Value],
+            lower: Bound<&Value>,
+            upper: Bound<&Value>,
+            direction: SortDirection,
+        ) -> Vec<u64> {
+            self.0.scan_compound_ordered(
+                index,
+                prefix,
+                lower,
+                upper,
+                direction == SortDirection::Desc,
+            )
+        }
+    }
+
+    #[test]
+    fn test_indexed_range_scans_only_matching_range() {
+        let mut manager = crate::index::IndexManager::new(["age".to_string()].into());
+        let mut storage = MockStorage::new();
+        for age in 0..100u64 {
+            let id = format!("user_{}", age);
+            let body = json!({"_id": id, "age": age});
+            manager.apply_write(&crate::index::DocumentInfo {
+                document_id: id.clone(),
+                schema_id: "users".to_string(),
+                schema_version: "v1".to_string(),
+                is_tombstone: false,
+                body: body.clone(),
+                offset: age * 100,
+            });
+            storage.add_record(age * 100, make_record(&id, "users", "v1", body));
+        }
+        let index = ManagerIndex(manager);
+
+        for (predicates, expected) in [
+            // age > 18 AND age < 65
+            (
+                vec![
+                    Predicate::gt("age", json!(18)),
+                    Predicate::lt("age", json!(65)),
+                ],
+                46,
+            ),
+            (
+                vec![
+                    Predicate::gte("age", json!(18)),
+                    Predicate::lte("age", json!(65)),
+                ],
+                48,
+            ),
+            (
+                vec![
+                    Predicate::gte("age", json!(18)),
+                    Predicate::lt("age", json!(65)),
+                ],
+                47,
+            ),
+            (vec![Predicate::gt("age", json!(90))], 9),
+            (vec![Predicate::lte("age", json!(9))], 10),
+            // The tighter of two bounds on one side wins
+            (
+                vec![
+                    Predicate::gt("age", json!(10)),
+                    Predicate::gte("age", json!(50)),
+                ],
+                50,
+            ),
+        ] {
+            let plan = make_plan(
+                "users",
+                "v1",
+                "age",
+                ScanType::IndexedRange,
+                predicates,
+                1000,
+            );
+
+            // Full scan: every record, filtered
+            let mut full: Vec<String> = storage
+                .records
+                .values()
+                .filter_map(|r| {
+                    let body: Value = serde_json::from_slice(&r.document_body).unwrap();
+                    PredicateFilter::matches_plan(&body, &plan)
+                        .then(|| body["_id"].as_str().unwrap().to_string())
+                })
+                .collect();
+            full.sort();
+
+            let mut executor = QueryExecutor::new(&index, &mut storage);
+            let result = executor.execute(&plan).unwrap();
+            let mut ids: Vec<String> = result.documents.iter().map(|d| d.id.clone()).collect();
+            ids.sort();
+
+            assert_eq!(ids, full);
+            assert_eq!(result.len(), expected);
+            // The range scan reads only the documents it returns
+            assert_eq!(result.scanned_count, expected);
+            assert!(result.scanned_count < storage.records.len());
+        }
+    }
+
+    #[test]
+    fn test_not_negation_execution() {
+        let (mut index, mut storage) = status_fixture();
+        index.add_pk("user_5", 500);
+        storage.add_record(
+            500,
+            make_record("user_5", "users", "v1", json!({"_id": "user_5"})),
+        );
+
+        // email >= bob AND NOT (status = banned AND email < dave)
+        let mut plan = make_plan(
+            "users",
+            "v1",
+            "email",
+            ScanType::IndexedRange,
+            vec![Predicate::gte("email", json!("bob@example.com"))],
+            10,
+        );
+        plan.expressions = vec![FilterExpr::negate(FilterExpr::and([
+            Predicate::eq("status", json!("banned")).into(),
+            Predicate::lt("email", json!("dave@example.com")).into(),
+        ]))];
+
+        let mut executor = QueryExecutor::new(&index, &mut storage);
+        let result = executor.execute(&plan).unwrap();
+
+        // user_1 fails the range; user_2 is negated; user_5 has no email
+        let ids: Vec<&str> = result.documents.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["user_3", "user_4"]);
+    }
+
     #[test]
     fn test_schema_mismatch_excluded() {
         let mut index = MockIndex::new();
@@ -487,6 +988,7 @@ mod tests {
             },
             field_indexes: HashMap::new(),
             all_offsets: vec![200],
+            key_order: HashMap::new(),
         };
 
         let mut executor = QueryExecutor::new(&index2, &mut storage);
@@ -611,6 +1113,404 @@ mod tests {
         assert!(result.limit_applied);
     }
 
+    /// Six users at offsets 100..=600 whose ages are out of offset order,
+    /// with the age index's key order
+    fn sorted_fixture() -> (MockIndex, MockStorage) {
+        let ages = [30, 10, 60, 20, 50, 40];
+        let mut index = MockIndex::new();
+        let mut storage = MockStorage::new();
+        for (i, age) in ages.iter().enumerate() {
+            let id = format!("user_{}", i + 1);
+            let offset = (i as u64 + 1) * 100;
+            index.add_pk(&id, offset);
+            storage.add_record(
+                offset,
+                make_record(&id, "users", "v1", json!({"_id": id, "age": age})),
+            );
+        }
+        index.set_key_order("age", vec![200, 400, 100, 600, 500, 300]);
+        (index, storage)
+    }
+
+    fn sorted_plan(sort: SortSpec, strategy: SortStrategy, limit: u64, offset: u64) -> QueryPlan {
+        let mut plan = make_plan(
+            "users",
+            "v1",
+            "age",
+            ScanType::IndexedRange,
+            vec![Predicate::gte("age", json!(0))],
+            limit,
+        );
+        plan.sort = Some(sort);
+        plan.sort_strategy = Some(strategy);
+        plan.offset = offset;
+        plan
+    }
+
+    fn ages(result: &ExecutionResult) -> Vec<i64> {
+        result
+            .documents
+            .iter()
+            .map(|d| d.body["age"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_index_provided_desc_order_stops_past_window() {
+        let (index, mut storage) = sorted_fixture();
+        let plan = sorted_plan(
+            SortSpec::desc("age"),
+            SortStrategy::Index(SortDirection::Desc),
+            2,
+            1,
+        );
+
+        let mut executor = QueryExecutor::new(&index, &mut storage);
+        let result = executor.execute(&plan).unwrap();
+
+        assert_eq!(ages(&result), vec![50, 40]);
+        assert!(result.limit_applied);
+        // Window of 3, plus one read to see the limit applied
+        assert_eq!(result.scanned_count, 4);
+    }
+
+    #[test]
+    fn test_limit_stops_scan_of_large_collection() {
+        const COUNT: u64 = 10_000;
+        let mut index = MockIndex::new();
+        let mut storage = MockStorage::new();
+        for i in 0..COUNT {
+            let id = format!("user_{}", i);
+            index.add_pk(&id, i * 100);
+            storage.add_record(
+                i * 100,
+                make_record(&id, "users", "v1", json!({"_id": id, "age": COUNT - i})),
+            );
+        }
+        // Ascending age is descending storage order
+        index.set_key_order("age", (0..COUNT).rev().map(|i| i * 100).collect());
+
+        // Ordering index: the first 10 in key order, plus one read past them
+        let plan = sorted_plan(
+            SortSpec::asc("age"),
+            SortStrategy::Index(SortDirection::Asc),
+            10,
+            0,
+        );
+        let mut executor = QueryExecutor::new(&index, &mut storage);
+        let result = executor.execute(&plan).unwrap();
+        assert_eq!(ages(&result), (1..=10).collect::<Vec<_>>());
+        assert!(result.limit_applied);
+        assert_eq!(result.scanned_count, 11);
+
+        // Unsorted scan: the first 10 matches past the offset in scan order
+        let mut plan = sorted_plan(SortSpec::asc("age"), SortStrategy::Full, 10, 5);
+        plan.sort = None;
+        plan.sort_strategy = None;
+        let mut executor = QueryExecutor::new(&index, &mut storage);
+        let result = executor.execute(&plan).unwrap();
+        assert_eq!(result.len(), 10);
+        assert!(result.limit_applied);
+        assert_eq!(result.scanned_count, 16);
+
+        // An in-memory sort has to see every candidate
+        let plan = sorted_plan(SortSpec::asc("age"), SortStrategy::TopN(10), 10, 0);
+        let mut executor = QueryExecutor::new(&index, &mut storage);
+        let result = executor.execute(&plan).unwrap();
+        assert_eq!(ages(&result), (1..=10).collect::<Vec<_>>());
+        assert_eq!(result.scanned_count, COUNT as usize);
+    }
+
+    #[test]
+    fn test_projection_keeps_sort_order_of_dropped_field() {
+        let (index, mut storage) = sorted_fixture();
+        let mut plan = sorted_plan(SortSpec::desc("age"), SortStrategy::TopN(3), 3, 0);
+        plan.projection = Projection::parse(&["_id"]);
+
+        let mut executor = QueryExecutor::new(&index, &mut storage);
+        let result = executor.execute(&plan).unwrap();
+
+        let bodies: Vec<&Value> = result.iter().map(|d| d.body()).collect();
+        assert_eq!(
+            bodies,
+            vec![
+                &json!({"_id": "user_3"}),
+                &json!({"_id": "user_5"}),
+                &json!({"_id": "user_6"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_n_with_limit_and_offset() {
+        let (index, mut storage) = sorted_fixture();
+
+        for (sort, expected) in [
+            (SortSpec::asc("age"), vec![30, 40]),
+            (SortSpec::desc("age"), vec![40, 30]),
+        ] {
+            let plan = sorted_plan(sort.clone(), SortStrategy::TopN(4), 2, 2);
+            let mut executor = QueryExecutor::new(&index, &mut storage);
+            let top = executor.execute(&plan).unwrap();
+
+            let plan = sorted_plan(sort, SortStrategy::Full, 2, 2);
+            let mut executor = QueryExecutor::new(&index, &mut storage);
+            let full = executor.execute(&plan).unwrap();
+
+            assert_eq!(ages(&top), expected);
+            assert_eq!(ages(&full), expected);
+            assert!(top.limit_applied);
+            assert_eq!(top.scanned_count, 6);
+        }
+    }
+
+    #[test]
+    fn test_in_memory_sort_refused_past_max_sort_bytes() {
+        let (index, mut storage) = sorted_fixture();
+        let plan = sorted_plan(SortSpec::asc("age"), SortStrategy::Full, 2, 0);
+
+        let mut executor = QueryExecutor::new(&index, &mut storage).with_max_sort_bytes(200);
+        let err = executor.execute(&plan).unwrap_err();
+        assert_eq!(err.code(), ExecutorErrorCode::AeroSortMemoryExceeded);
+
+        // A top-N heap of 2 fits the same budget
+        let plan = sorted_plan(SortSpec::asc("age"), SortStrategy::TopN(2), 2, 0);
+        let mut executor = QueryExecutor::new(&index, &mut storage).with_max_sort_bytes(200);
+        assert_eq!(ages(&executor.execute(&plan).unwrap()), vec![10, 20]);
+    }
+
+    /// Storage that takes 1ms per read
+    struct SlowStorage(MockStorage);
+
+    impl StorageRead for SlowStorage {
+        fn read_at(&mut self, offset: u64) -> ExecutorResult<Option<DocumentRecord>> {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            self.0.read_at(offset)
+        }
+    }
+
+    fn slow_scan_fixture(count: u64) -> (MockIndex, SlowStorage, QueryPlan) {
+        let mut index = MockIndex::new();
+        let mut storage = MockStorage::new();
+        for i in 1..=count {
+            let id = format!("user_{}", i);
+            index.add_pk(&id, i * 100);
+            storage.add_record(
+                i * 100,
+                make_record(&id, "users", "v1", json!({"_id": id, "age": i})),
+            );
+        }
+
+        let plan = make_plan(
+            "users",
+            "v1",
+            "age",
+            ScanType::IndexedRange,
+            vec![Predicate::gte("age", json!(0))],
+            count,
+        );
+        (index, SlowStorage(storage), plan)
+    }
+
+    #[test]
+    fn test_slow_scan_aborts_at_deadline() {
+        let (index, mut storage, plan) = slow_scan_fixture(200);
+
+        let mut executor =
+            QueryExecutor::new(&index, &mut storage).with_deadline(Deadline::after_ms(10));
+        let err = executor.execute(&plan).unwrap_err();
+
+        assert_eq!(err.code().code(), "AERO_QUERY_TIMEOUT");
+        assert!(!err.is_fatal());
+        // Aborted at a batch boundary, before the scan finished
+        let examined = err.documents_examined().unwrap();
+        assert!(examined > 0 && examined < 200);
+        assert_eq!(examined % crate::executor::DEADLINE_CHECK_INTERVAL, 0);
+        assert!(err
+            .message()
+            .contains(&format!("after examining {} documents", examined)));
+    }
+
+    #[test]
+    fn test_slow_scan_completes_within_generous_deadline() {
+        let (index, mut storage, plan) = slow_scan_fixture(200);
+
+        let mut executor =
+            QueryExecutor::new(&index, &mut storage).with_deadline(Deadline::after_ms(60_000));
+        let result = executor.execute(&plan).unwrap();
+
+        assert_eq!(result.len(), 200);
+        assert_eq!(result.scanned_count, 200);
+    }
+
+    /// Version chains by document ID, shared by storage and its writer
+    type SharedChains = Arc<Mutex<HashMap<String, VersionChain>>>;
+
+    /// Storage keeping every committed version of each document, with a
+    /// writer sharing its version chains. Offsets name documents; `read_at`
+    /// returns the latest version.
+    struct VersionedStorage {
+        keys: HashMap<u64, String>,
+        chains: SharedChains,
+        /// Signals the first read, then waits before returning it
+        pause: Option<(mpsc::Sender<()>, mpsc::Receiver<()>)>,
+    }
+
+    impl VersionedStorage {
+        fn read_chain(
+            &mut self,
+            offset: u64,
+            visible: impl Fn(&VersionChain) -> Option<Version>,
+        ) -> ExecutorResult<Option<DocumentRecord>> {
+            if let Some((started, resume)) = self.pause.take() {
+                started.send(()).unwrap();
+                resume.recv().unwrap();
+            }
+            let Some(key) = self.keys.get(&offset) else {
+                return Ok(None);
+            };
+            let chains = self.chains.lock().unwrap();
+            let Some(version) = chains.get(key).and_then(visible) else {
+                return Ok(None);
+            };
+            Ok(Some(DocumentRecord {
+                document_id: format!("users:{}", key),
+                schema_id: "users".to_string(),
+                schema_version: "v1".to_string(),
+                is_tombstone: version.is_tombstone(),
+                document_body: match version.payload() {
+                    VersionPayload::Document(body) => body.clone(),
+                    VersionPayload::Tombstone => Vec::new(),
+                },
+            }))
+        }
+    }
+
+    impl StorageRead for VersionedStorage {
+        fn read_at(&mut self, offset: u64) -> ExecutorResult<Option<DocumentRecord>> {
+            self.read_chain(offset, |chain| chain.versions().last().cloned())
+        }
+
+        fn read_as_of(
+            &mut self,
+            offset: u64,
+            snapshot: &MvccSnapshot,
+        ) -> ExecutorResult<Option<DocumentRecord>> {
+            self.read_chain(offset, |chain| {
+                chain
+                    .visible_version(snapshot.read_view())
+                    .version()
+                    .cloned()
+            })
+        }
+    }
+
+    fn commit_user(
+        chains: &Mutex<HashMap<String, VersionChain>>,
+        authority: &mut CommitAuthority,
+        id: &str,
+        age: i64,
+    ) {
+        let commit_id = authority.next_commit_id();
+        let body = serde_json::to_vec(&json!({"_id": id, "age": age})).unwrap();
+        chains
+            .lock()
+            .unwrap()
+            .entry(id.to_string())
+            .or_insert_with(|| VersionChain::new(id.to_string()))
+            .push(Version::with_document(id.to_string(), body, commit_id));
+        authority.mark_committed(commit_id).unwrap();
+    }
+
+    /// Two users committed at 1 and 2, indexed at offsets 100 and 200
+    fn versioned_fixture() -> (MockIndex, VersionedStorage, SharedChains, CommitAuthority) {
+        let chains = Arc::new(Mutex::new(HashMap::new()));
+        let mut authority = CommitAuthority::new();
+        let mut index = MockIndex::new();
+        let mut keys = HashMap::new();
+        for (i, age) in [10, 20].into_iter().enumerate() {
+            let id = format!("user_{}", i + 1);
+            let offset = (i as u64 + 1) * 100;
+            commit_user(&chains, &mut authority, &id, age);
+            index.add_pk(&id, offset);
+            keys.insert(offset, id);
+        }
+        let storage = VersionedStorage {
+            keys,
+            chains: Arc::clone(&chains),
+            pause: None,
+        };
+        (index, storage, chains, authority)
+    }
+
+    fn age_plan() -> QueryPlan {
+        make_plan(
+            "users",
+            "v1",
+            "age",
+            ScanType::IndexedRange,
+            vec![Predicate::gte("age", json!(0))],
+            10,
+        )
+    }
+
+    /// Runs a query that pauses after its first read while another thread
+    /// commits a new version of user_2
+    fn read_during_concurrent_update(snapshot: bool) -> Vec<i64> {
+        let (index, mut storage, chains, mut authority) = versioned_fixture();
+        let read_start = MvccSnapshot::current(&authority);
+
+        let (started_tx, started_rx) = mpsc::channel();
+        let (resume_tx, resume_rx) = mpsc::channel();
+        storage.pause = Some((started_tx, resume_rx));
+        let writer = std::thread::spawn(move || {
+            started_rx.recv().unwrap();
+            commit_user(&chains, &mut authority, "user_2", 99);
+            resume_tx.send(()).unwrap();
+            authority
+        });
+
+        let mut executor = QueryExecutor::new(&index, &mut storage);
+        if snapshot {
+            executor = executor.with_snapshot(read_start);
+        }
+        let result = executor.execute(&age_plan()).unwrap();
+
+        let authority = writer.join().unwrap();
+        assert_eq!(authority.highest_commit_id(), Some(CommitId::new(3)));
+        ages(&result)
+    }
+
+    #[test]
+    fn test_snapshot_read_ignores_concurrent_commit() {
+        assert_eq!(read_during_concurrent_update(true), vec![10, 20]);
+    }
+
+    #[test]
+    fn test_read_without_snapshot_sees_concurrent_commit() {
+        assert_eq!(read_during_concurrent_update(false), vec![10, 99]);
+    }
+
+    #[test]
+    fn test_snapshot_skips_documents_committed_after_read_timestamp() {
+        let (mut index, mut storage, chains, mut authority) = versioned_fixture();
+        let snapshot = MvccSnapshot::current(&authority);
+
+        // Committed and indexed before the query looks up its candidates
+        commit_user(&chains, &mut authority, "user_3", 30);
+        index.add_pk("user_3", 300);
+        storage.keys.insert(300, "user_3".to_string());
+
+        let mut executor = QueryExecutor::new(&index, &mut storage).with_snapshot(snapshot);
+        assert_eq!(ages(&executor.execute(&age_plan()).unwrap()), vec![10, 20]);
+
+        let mut executor = QueryExecutor::new(&index, &mut storage);
+        assert_eq!(
+            ages(&executor.execute(&age_plan()).unwrap()),
+            vec![10, 20, 30]
+        );
+    }
+
     #[test]
     fn test_replay_stability() {
         // Same setup as deterministic_ordering
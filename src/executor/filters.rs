@@ -2,10 +2,13 @@
 //!
 //! Filters documents strictly according to predicates.
 //! No type coercion, no expressions, exact match only.
+//!
+//! Boolean filters combine predicates with AND, OR and NOT. A predicate on
+//! a missing or null field never matches, so its negation does.
 
 use serde_json::Value;
 
-use crate::planner::{FilterOp, Predicate};
+use crate::planner::{FilterExpr, FilterOp, Predicate, QueryPlan};
 
 /// Evaluates predicates against documents
 pub struct PredicateFilter;
@@ -19,6 +22,25 @@ impl PredicateFilter {
             .all(|pred| Self::matches_predicate(document, pred))
     }
 
+    /// Checks if a document matches a boolean filter expression
+    pub fn matches_expr(document: &Value, expr: &FilterExpr) -> bool {
+        match expr {
+            FilterExpr::Predicate(pred) => Self::matches_predicate(document, pred),
+            FilterExpr::And(children) => children.iter().all(|c| Self::matches_expr(document, c)),
+            FilterExpr::Or(children) => children.iter().any(|c| Self::matches_expr(document, c)),
+            FilterExpr::Not(child) => !Self::matches_expr(document, child),
+        }
+    }
+
+    /// Checks if a document matches a plan's predicates and expressions
+    pub fn matches_plan(document: &Value, plan: &QueryPlan) -> bool {
+        Self::matches(document, &plan.predicates)
+            && plan
+                .expressions
+                .iter()
+                .all(|expr| Self::matches_expr(document, expr))
+    }
+
     /// Checks if a document matches a single predicate
     fn matches_predicate(document: &Value, predicate: &Predicate) -> bool {
         let field_value = match document.get(&predicate.field) {
@@ -177,6 +199,43 @@ mod tests {
         assert!(!PredicateFilter::matches(&doc, &preds));
     }
 
+    #[test]
+    fn test_nested_boolean_expressions() {
+        let doc = json!({"age": 25, "status": "active"});
+
+        // age < 18 OR (status = active AND NOT age > 30)
+        let expr = FilterExpr::or([
+            Predicate::lt("age", json!(18)).into(),
+            FilterExpr::and([
+                Predicate::eq("status", json!("active")).into(),
+                FilterExpr::negate(Predicate::gt("age", json!(30)).into()),
+            ]),
+        ]);
+        assert!(PredicateFilter::matches_expr(&doc, &expr));
+
+        let doc = json!({"age": 35, "status": "active"});
+        assert!(!PredicateFilter::matches_expr(&doc, &expr));
+    }
+
+    #[test]
+    fn test_not_matches_missing_and_null_fields() {
+        let expr = FilterExpr::negate(Predicate::eq("status", json!("banned")).into());
+
+        assert!(!PredicateFilter::matches_expr(
+            &json!({"status": "banned"}),
+            &expr
+        ));
+        assert!(PredicateFilter::matches_expr(
+            &json!({"status": "active"}),
+            &expr
+        ));
+        assert!(PredicateFilter::matches_expr(
+            &json!({"status": null}),
+            &expr
+        ));
+        assert!(PredicateFilter::matches_expr(&json!({}), &expr));
+    }
+
     #[test]
     fn test_missing_field_no_match() {
         let doc = json!({"name": "Alice"});
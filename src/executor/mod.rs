@@ -13,20 +13,54 @@
 //! 7. Apply limit
 //! 8. Return ordered results
 //!
+//! # Timeouts
+//!
+//! An executor with a `Deadline` checks it after the index lookup, every
+//! `DEADLINE_CHECK_INTERVAL` documents, and around the sort, failing with
+//! `AERO_QUERY_TIMEOUT` (including the documents examined so far).
+//!
+//! # Aggregation
+//!
+//! `HashAggregator` groups documents by one or more fields and computes
+//! count, sum, avg, min and max per group. The group table is charged to a
+//! `MemoryTracker` and bounded by a group limit; exceeding either fails
+//! with `AERO_EXECUTION_LIMIT`.
+//!
+//! # Projection
+//!
+//! A plan with a `Projection` returns only the selected fields of each
+//! document (dotted paths reach into nested objects), plus `_id` unless
+//! excluded. Documents are projected as they match, after their sort key
+//! is taken, so a sort may use a field the projection drops.
+//!
+//! # Snapshot reads
+//!
+//! An executor with an `MvccSnapshot` reads every document as of the
+//! snapshot's read timestamp through `StorageRead::read_as_of`, ignoring
+//! versions committed after the query started.
+//!
 //! # Invariants
 //!
 //! - T2: Deterministic execution
 //! - D2: Checksum validation on every read
 //! - F1: Fail loudly on corruption
 
+mod aggregate;
+mod deadline;
 mod errors;
 mod executor;
 mod filters;
+mod projection;
 mod result;
+mod snapshot;
 mod sorter;
 
+pub use aggregate::{Aggregate, AggregateFunction, AggregateSpec, HashAggregator};
+pub use deadline::{Deadline, DEADLINE_CHECK_INTERVAL};
 pub use errors::{ExecutorError, ExecutorErrorCode, ExecutorResult};
-pub use executor::{IndexLookup, QueryExecutor};
+pub use executor::{IndexLookup, QueryExecutor, StorageRead};
 pub use filters::PredicateFilter;
+pub use projection::project;
 pub use result::{ExecutionResult, ResultDocument};
-pub use sorter::ResultSorter;
+pub use snapshot::MvccSnapshot;
+pub use sorter::{ResultSorter, SortBuffer, SORT_ENTRY_OVERHEAD_BYTES};
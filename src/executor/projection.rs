@@ -0,0 +1,141 @@
+//! Field projection for query results
+//!
+//! Builds the returned document from only the fields a `Projection`
+//! selects, so unselected fields are never copied or serialized. A dotted
+//! path selects a field of a nested object and keeps the objects around
+//! it; paths sharing a prefix merge into one nested object. Paths do not
+//! descend into arrays, and selected fields missing from the document are
+//! left out.
+
+use serde_json::{Map, Value};
+
+use crate::planner::Projection;
+
+/// Primary key field, returned unless the projection excludes it
+const PRIMARY_KEY: &str = "_id";
+
+/// Projects `document` onto the fields `projection` selects
+pub fn project(document: &Value, projection: &Projection) -> Value {
+    let Value::Object(source) = document else {
+        return document.clone();
+    };
+
+    // Only excludes `_id`: everything else
+    if projection.fields.is_empty() {
+        let mut projected = source.clone();
+        projected.remove(PRIMARY_KEY);
+        return Value::Object(projected);
+    }
+
+    let mut projected = Map::new();
+    if projection.include_id {
+        if let Some(id) = source.get(PRIMARY_KEY) {
+            projected.insert(PRIMARY_KEY.to_string(), id.clone());
+        }
+    }
+    for field in &projection.fields {
+        let path: Vec<&str> = field.split('.').collect();
+        if let Some(value) = lookup(source, &path) {
+            insert(&mut projected, &path, value.clone());
+        }
+    }
+    Value::Object(projected)
+}
+
+/// The value at `path` in `object`, through nested objects
+fn lookup<'a>(object: &'a Map<String, Value>, path: &[&str]) -> Option<&'a Value> {
+    let (first, rest) = path.split_first()?;
+    let value = object.get(*first)?;
+    if rest.is_empty() {
+        return Some(value);
+    }
+    match value {
+        Value::Object(nested) => lookup(nested, rest),
+        _ => None,
+    }
+}
+
+/// Insert `value` at `path` in `object`, creating the objects around it
+/// and merging into those already there
+fn insert(object: &mut Map<String, Value>, path: &[&str], value: Value) {
+    let Some((first, rest)) = path.split_first() else {
+        return;
+    };
+    if rest.is_empty() {
+        match (object.get_mut(*first), value) {
+            (Some(Value::Object(existing)), Value::Object(value)) => {
+                for (key, nested) in value {
+                    insert(existing, &[key.as_str()], nested);
+                }
+            }
+            (_, value) => {
+                object.insert(first.to_string(), value);
+            }
+        }
+        return;
+    }
+    let nested = object
+        .entry(first.to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+    if let Value::Object(nested) = nested {
+        insert(nested, rest, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn user() -> Value {
+        json!({
+            "_id": "u1",
+            "name": "Alice",
+            "email": "alice@example.com",
+            "address": {"city": "Paris", "zip": "75001", "geo": {"lat": 48.8}}
+        })
+    }
+
+    #[test]
+    fn test_top_level_fields_keep_primary_key() {
+        let projection = Projection::parse(&["name"]).unwrap();
+        assert_eq!(
+            project(&user(), &projection),
+            json!({"_id": "u1", "name": "Alice"})
+        );
+    }
+
+    #[test]
+    fn test_nested_paths() {
+        let projection =
+            Projection::parse(&["address.city", "address.geo.lat", "missing.x"]).unwrap();
+        assert_eq!(
+            project(&user(), &projection),
+            json!({"_id": "u1", "address": {"city": "Paris", "geo": {"lat": 48.8}}})
+        );
+
+        // A whole object and one of its fields in either order
+        for select in [["address", "address.city"], ["address.city", "address"]] {
+            let projection = Projection::parse(&select).unwrap();
+            assert_eq!(project(&user(), &projection)["address"], user()["address"]);
+        }
+    }
+
+    #[test]
+    fn test_primary_key_excluded() {
+        let projection = Projection::parse(&["-_id", "name"]).unwrap();
+        assert_eq!(project(&user(), &projection), json!({"name": "Alice"}));
+
+        let projection = Projection::parse(&["-_id"]).unwrap();
+        let projected = project(&user(), &projection);
+        assert!(projected.get("_id").is_none());
+        assert_eq!(projected["email"], "alice@example.com");
+    }
+
+    #[test]
+    fn test_select_all() {
+        assert!(Projection::parse(&["*"]).is_none());
+        assert!(Projection::parse(&["_id"]).is_some());
+        assert!(Projection::parse::<&str>(&[]).is_none());
+    }
+}
@@ -0,0 +1,74 @@
+//! MVCC read snapshots for query execution
+//!
+//! A query given an `MvccSnapshot` reads as of its read timestamp: the
+//! highest commit identity when the query started. For each candidate it
+//! reads the document's newest version committed at or before that
+//! timestamp (per `mvcc::Visibility`), so writes that commit while the query
+//! runs are not seen and documents created after the read timestamp are
+//! skipped.
+//!
+//! Candidate offsets still come from the index, looked up once when
+//! execution starts.
+
+use crate::mvcc::{CommitAuthority, CommitId, ReadView};
+
+/// The read timestamp a query executes at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MvccSnapshot {
+    view: ReadView,
+}
+
+impl MvccSnapshot {
+    /// Read as of `read_timestamp`
+    pub fn new(read_timestamp: CommitId) -> Self {
+        Self {
+            view: ReadView::new(read_timestamp),
+        }
+    }
+
+    /// Read as of the latest commit `authority` has made durable
+    pub fn current(authority: &CommitAuthority) -> Self {
+        Self {
+            view: authority.current_snapshot(),
+        }
+    }
+
+    /// Highest commit identity visible to the query
+    pub fn read_timestamp(&self) -> CommitId {
+        self.view.upper_bound()
+    }
+
+    /// The read view version chains are resolved against
+    pub fn read_view(&self) -> ReadView {
+        self.view
+    }
+
+    /// True if a version committed at `commit_id` is visible
+    pub fn is_visible(&self, commit_id: CommitId) -> bool {
+        commit_id <= self.read_timestamp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_sees_commits_up_to_read_timestamp() {
+        let snapshot = MvccSnapshot::new(CommitId::new(5));
+        assert!(snapshot.is_visible(CommitId::new(1)));
+        assert!(snapshot.is_visible(CommitId::new(5)));
+        assert!(!snapshot.is_visible(CommitId::new(6)));
+    }
+
+    #[test]
+    fn test_current_snapshot_is_fixed_at_creation() {
+        let mut authority = CommitAuthority::new();
+        authority.mark_committed(CommitId::new(1)).unwrap();
+        let snapshot = MvccSnapshot::current(&authority);
+
+        authority.mark_committed(CommitId::new(2)).unwrap();
+        assert_eq!(snapshot.read_timestamp(), CommitId::new(1));
+        assert!(!snapshot.is_visible(CommitId::new(2)));
+    }
+}
@@ -1,9 +1,23 @@
 //! Result sorting for query execution
 //!
-//! Sorts results by indexed fields only, deterministically.
+//! Sorts results by indexed fields only, deterministically. Values are
+//! compared after mapping through the sort's collation.
+//!
+//! `SortBuffer` performs a plan's in-memory sort (`TOP_N` or `FULL`) within
+//! a memory budget.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use serde_json::Value;
 
+use super::errors::{ExecutorError, ExecutorResult};
 use super::result::ResultDocument;
-use crate::planner::{SortDirection, SortSpec};
+use crate::index::Collation;
+use crate::planner::{QueryPlan, SortDirection, SortSpec, SortStrategy};
+
+/// Bytes charged per buffered document on top of its encoded size
+pub const SORT_ENTRY_OVERHEAD_BYTES: u64 = 64;
 
 /// Sorts result documents
 pub struct ResultSorter;
@@ -13,11 +27,12 @@ impl ResultSorter {
     ///
     /// Sort is stable and deterministic.
     pub fn sort(documents: &mut [ResultDocument], sort_spec: &SortSpec) {
+        let collation = sort_spec.collation.unwrap_or_default();
         documents.sort_by(|a, b| {
-            let a_val = a.body.get(&sort_spec.field);
-            let b_val = b.body.get(&sort_spec.field);
+            let a_val = a.body.get(&sort_spec.field).map(|v| collation.normalize(v));
+            let b_val = b.body.get(&sort_spec.field).map(|v| collation.normalize(v));
 
-            let ordering = Self::compare_values(a_val, b_val);
+            let ordering = Self::compare_values(a_val.as_deref(), b_val.as_deref());
 
             match sort_spec.direction {
                 SortDirection::Asc => ordering,
@@ -31,7 +46,7 @@ impl ResultSorter {
     /// Ordering rules:
     /// - null < bool < number < string
     /// - For same types, natural ordering
-    fn compare_values(
+    pub(crate) fn compare_values(
         a: Option<&serde_json::Value>,
         b: Option<&serde_json::Value>,
     ) -> std::cmp::Ordering {
@@ -79,6 +94,149 @@ impl ResultSorter {
     }
 }
 
+/// Collects documents for an in-memory sort within a memory budget.
+///
+/// With a window (top-N), only the first `window` documents in sort order
+/// are kept: a max-heap whose worst entry is evicted when a better one
+/// arrives. Without one, every document is kept. Each kept document is
+/// charged its encoded size plus `SORT_ENTRY_OVERHEAD_BYTES`; a push that
+/// would exceed `max_bytes` fails with `AERO_SORT_MEMORY_EXCEEDED`.
+///
+/// Ties keep arrival order, as `ResultSorter::sort` does.
+pub struct SortBuffer<T> {
+    descending: bool,
+    collation: Collation,
+    window: Option<usize>,
+    max_bytes: u64,
+    used_bytes: u64,
+    next_seq: u64,
+    heap: BinaryHeap<SortEntry<T>>,
+}
+
+impl<T> SortBuffer<T> {
+    /// A buffer keeping the first `window` documents, or all when None
+    pub fn new(direction: SortDirection, window: Option<usize>, max_bytes: u64) -> Self {
+        Self {
+            descending: direction == SortDirection::Desc,
+            collation: Collation::Binary,
+            window,
+            max_bytes,
+            used_bytes: 0,
+            next_seq: 0,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    /// Compare keys after mapping them through `collation`
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = collation;
+        self
+    }
+
+    /// The buffer for a plan's in-memory sort; None when the plan is unsorted
+    /// or its index scan provides the order
+    pub fn for_plan(plan: &QueryPlan, max_bytes: u64) -> Option<Self> {
+        let sort = plan.sort.as_ref()?;
+        let window = match plan.sort_strategy {
+            Some(SortStrategy::Index(_)) => return None,
+            Some(SortStrategy::TopN(window)) => Some(usize::try_from(window).unwrap_or(usize::MAX)),
+            Some(SortStrategy::Full) | None => None,
+        };
+        Some(
+            Self::new(sort.direction, window, max_bytes)
+                .with_collation(sort.collation.unwrap_or_default()),
+        )
+    }
+
+    /// Offer a document with its sort key and encoded size
+    pub fn push(&mut self, item: T, key: Option<Value>, size_bytes: u64) -> ExecutorResult<()> {
+        let key = match self.collation {
+            Collation::Binary => key,
+            collation => key.map(|k| collation.normalize(&k).into_owned()),
+        };
+        let entry = SortEntry {
+            key,
+            seq: self.next_seq,
+            bytes: size_bytes.saturating_add(SORT_ENTRY_OVERHEAD_BYTES),
+            descending: self.descending,
+            item,
+        };
+        self.next_seq += 1;
+
+        if let Some(window) = self.window {
+            if self.heap.len() >= window {
+                // Keep the entry only if it sorts before the current worst
+                match self.heap.peek() {
+                    Some(worst) if entry < *worst => {
+                        if let Some(evicted) = self.heap.pop() {
+                            self.used_bytes -= evicted.bytes;
+                        }
+                    }
+                    _ => return Ok(()),
+                }
+            }
+        }
+
+        let used = self.used_bytes.saturating_add(entry.bytes);
+        if used > self.max_bytes {
+            return Err(ExecutorError::sort_memory_exceeded(self.max_bytes));
+        }
+        self.used_bytes = used;
+        self.heap.push(entry);
+        Ok(())
+    }
+
+    /// Bytes currently charged
+    pub fn used_bytes(&self) -> u64 {
+        self.used_bytes
+    }
+
+    /// Kept documents, in sort order
+    pub fn finish(self) -> Vec<T> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|entry| entry.item)
+            .collect()
+    }
+}
+
+/// A buffered document, ordered by sort key then arrival
+struct SortEntry<T> {
+    /// Sort key, already mapped through the collation
+    key: Option<Value>,
+    seq: u64,
+    bytes: u64,
+    descending: bool,
+    item: T,
+}
+
+impl<T> Ord for SortEntry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ordering = ResultSorter::compare_values(self.key.as_ref(), other.key.as_ref());
+        let ordering = if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        ordering.then(self.seq.cmp(&other.seq))
+    }
+}
+
+impl<T> PartialOrd for SortEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for SortEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for SortEntry<T> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,4 +299,106 @@ mod tests {
         assert_eq!(docs[1].id, "3"); // bob
         assert_eq!(docs[2].id, "1"); // charlie
     }
+
+    #[test]
+    fn test_sort_with_collation() {
+        fn make_doc_with(id: &str, field: &str, value: &str) -> ResultDocument {
+            ResultDocument::new(id, "items", "v1", json!({"_id": id, field: value}), 0)
+        }
+        let ids = |docs: &[ResultDocument]| docs.iter().map(|d| d.id.clone()).collect::<Vec<_>>();
+
+        let mut docs = vec![
+            make_doc_with("1", "price", "10"),
+            make_doc_with("2", "price", "9"),
+            make_doc_with("3", "price", "100"),
+        ];
+        ResultSorter::sort(&mut docs, &SortSpec::asc("price"));
+        assert_eq!(ids(&docs), ["1", "3", "2"]);
+        let numeric = SortSpec::asc("price").with_collation(Collation::Numeric);
+        ResultSorter::sort(&mut docs, &numeric);
+        assert_eq!(ids(&docs), ["2", "1", "3"]);
+        let numeric = SortSpec::desc("price").with_collation(Collation::Numeric);
+        ResultSorter::sort(&mut docs, &numeric);
+        assert_eq!(ids(&docs), ["3", "1", "2"]);
+
+        let mut docs = vec![
+            make_doc_with("1", "name", "bob"),
+            make_doc_with("2", "name", "Carol"),
+            make_doc_with("3", "name", "alice"),
+        ];
+        ResultSorter::sort(&mut docs, &SortSpec::asc("name"));
+        assert_eq!(ids(&docs), ["2", "3", "1"]);
+        let folded = SortSpec::asc("name").with_collation(Collation::CaseInsensitive);
+        ResultSorter::sort(&mut docs, &folded);
+        assert_eq!(ids(&docs), ["3", "1", "2"]);
+        let folded = SortSpec::desc("name").with_collation(Collation::CaseInsensitive);
+        ResultSorter::sort(&mut docs, &folded);
+        assert_eq!(ids(&docs), ["2", "1", "3"]);
+    }
+
+    #[test]
+    fn test_sort_buffer_applies_collation() {
+        let prices = ["10", "9", "100", "2.5"];
+        for (direction, expected) in [
+            (SortDirection::Asc, ["2.5", "9", "10", "100"]),
+            (SortDirection::Desc, ["100", "10", "9", "2.5"]),
+        ] {
+            let mut buffer =
+                SortBuffer::new(direction, None, u64::MAX).with_collation(Collation::Numeric);
+            for price in prices {
+                buffer.push(price, Some(json!(price)), 10).unwrap();
+            }
+            assert_eq!(buffer.finish(), expected);
+        }
+    }
+
+    #[test]
+    fn test_sort_buffer_top_n_matches_full_sort() {
+        let ages = [40, 10, 30, 20, 30, 50, 10];
+        let docs: Vec<ResultDocument> = ages
+            .iter()
+            .enumerate()
+            .map(|(i, age)| make_doc(&i.to_string(), *age))
+            .collect();
+
+        for spec in [SortSpec::asc("age"), SortSpec::desc("age")] {
+            let mut expected = docs.clone();
+            ResultSorter::sort(&mut expected, &spec);
+            let expected: Vec<String> = expected.into_iter().map(|d| d.id).collect();
+
+            // limit 2 offset 3: a window of 5
+            let mut top = SortBuffer::new(spec.direction, Some(5), u64::MAX);
+            let mut full = SortBuffer::new(spec.direction, None, u64::MAX);
+            for doc in &docs {
+                top.push(doc.id.clone(), doc.body.get("age").cloned(), 10)
+                    .unwrap();
+                full.push(doc.id.clone(), doc.body.get("age").cloned(), 10)
+                    .unwrap();
+            }
+            assert_eq!(top.used_bytes(), 5 * (10 + SORT_ENTRY_OVERHEAD_BYTES));
+
+            let page: Vec<String> = top.finish().into_iter().skip(3).take(2).collect();
+            assert_eq!(page, expected[3..5]);
+            assert_eq!(full.finish(), expected);
+        }
+    }
+
+    #[test]
+    fn test_sort_buffer_refuses_past_memory_budget() {
+        let entry = 100 + SORT_ENTRY_OVERHEAD_BYTES;
+
+        let mut full = SortBuffer::new(SortDirection::Asc, None, 3 * entry);
+        for age in 0..3 {
+            full.push(age, Some(json!(age)), 100).unwrap();
+        }
+        let err = full.push(3, Some(json!(3)), 100).unwrap_err();
+        assert_eq!(err.code().code(), "AERO_SORT_MEMORY_EXCEEDED");
+
+        // A top-N heap only holds its window, so the same budget suffices
+        let mut top = SortBuffer::new(SortDirection::Asc, Some(3), 3 * entry);
+        for age in (0..100).rev() {
+            top.push(age, Some(json!(age)), 100).unwrap();
+        }
+        assert_eq!(top.finish(), vec![0, 1, 2]);
+    }
 }
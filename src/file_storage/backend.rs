@@ -7,6 +7,10 @@ pub trait StorageBackend: Send + Sync + std::fmt::Debug {
     /// Write data to path
     fn write(&self, path: &str, data: &[u8]) -> StorageResult<()>;
 
+    /// Start a chunked write to path. Nothing is visible at path until the
+    /// writer commits; dropping it uncommitted discards what was written.
+    fn begin_write(&self, path: &str) -> StorageResult<Box<dyn ObjectWriter>>;
+
     /// Read data from path
     fn read(&self, path: &str) -> StorageResult<Vec<u8>>;
 
@@ -19,3 +23,13 @@ pub trait StorageBackend: Send + Sync + std::fmt::Debug {
     /// List files with prefix
     fn list(&self, prefix: &str) -> StorageResult<Vec<String>>;
 }
+
+/// A write in progress, started by [`StorageBackend::begin_write`]
+pub trait ObjectWriter: Send {
+    /// Append a chunk
+    fn write_chunk(&mut self, chunk: &[u8]) -> StorageResult<()>;
+
+    /// Make everything written durable and visible at the target path,
+    /// replacing any object there
+    fn commit(self: Box<Self>) -> StorageResult<()>;
+}
@@ -63,6 +63,9 @@ pub struct Bucket {
     pub name: String,
     pub owner_id: Option<Uuid>,
     pub config: BucketConfig,
+    /// Bumped to revoke every signed URL issued for the bucket
+    #[serde(default)]
+    pub signing_generation: u64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -76,6 +79,7 @@ impl Bucket {
             name,
             owner_id,
             config,
+            signing_generation: 0,
             created_at: now,
             updated_at: now,
         }
@@ -170,6 +174,22 @@ impl BucketRegistry {
         Ok(())
     }
 
+    /// Revoke every signed URL issued for a bucket by bumping its signing
+    /// generation
+    pub fn revoke_signed_urls(&self, name: &str) -> StorageResult<Bucket> {
+        let mut buckets = self
+            .buckets
+            .write()
+            .map_err(|_| StorageError::Internal("Lock poisoned".into()))?;
+
+        let bucket = buckets
+            .get_mut(name)
+            .ok_or_else(|| StorageError::BucketNotFound(name.to_string()))?;
+        bucket.signing_generation += 1;
+        bucket.updated_at = Utc::now();
+        Ok(bucket.clone())
+    }
+
     /// List all buckets
     pub fn list(&self) -> Vec<Bucket> {
         self.buckets
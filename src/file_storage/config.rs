@@ -0,0 +1,34 @@
+//! # File Storage Configuration
+
+use serde::{Deserialize, Serialize};
+
+/// Storage limits and URL signing (`[storage]` section)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Maximum bytes in one upload (default 100MB, 0 = unlimited). A
+    /// bucket's own `max_file_size` applies as well.
+    pub max_upload_bytes: u64,
+
+    /// Maximum bytes stored in one bucket (default 0 = unlimited)
+    pub max_total_storage_bytes: u64,
+
+    /// Secret signed URLs are keyed by (default: none, a random secret is
+    /// drawn at startup and signed URLs do not survive a restart)
+    pub signing_secret: Option<String>,
+
+    /// Longest lifetime of a signed URL in seconds; longer requests are
+    /// capped (default 604800, one week)
+    pub max_signed_url_expiry_secs: u64,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            max_upload_bytes: 100 * 1024 * 1024, // 100MB
+            max_total_storage_bytes: 0,
+            signing_secret: None,
+            max_signed_url_expiry_secs: 7 * 24 * 60 * 60,
+        }
+    }
+}
@@ -29,6 +29,9 @@ pub enum StorageError {
     #[error("File too large: {0} bytes (max: {1})")]
     FileTooLarge(u64, u64),
 
+    #[error("Bucket storage quota exceeded: {0} bytes (max: {1})")]
+    StorageQuotaExceeded(u64, u64),
+
     #[error("Invalid MIME type: {0}")]
     InvalidMimeType(String),
 
@@ -74,6 +77,7 @@ impl StorageError {
             StorageError::ObjectNotFound(_) => 404,
             StorageError::ObjectAlreadyExists(_) => 409,
             StorageError::FileTooLarge(_, _) => 413,
+            StorageError::StorageQuotaExceeded(_, _) => 413,
             StorageError::InvalidMimeType(_) => 415,
             StorageError::InvalidPath(_) => 400,
             StorageError::Unauthorized => 401,
@@ -1,6 +1,7 @@
 //! # File Operations
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sha2::{Digest, Sha256};
@@ -8,10 +9,13 @@ use std::collections::HashMap;
 use std::sync::RwLock;
 use uuid::Uuid;
 
-use super::backend::StorageBackend;
+use super::backend::{ObjectWriter, StorageBackend};
 use super::bucket::{Bucket, BucketRegistry};
+use super::config::StorageConfig;
 use super::errors::{StorageError, StorageResult};
 use super::permissions::StoragePermissions;
+use super::signed_url::{SignedMethod, SignedUrl, SignedUrlGenerator};
+use super::upload::Upload;
 use crate::auth::rls::RlsContext;
 
 /// A storage object (file metadata)
@@ -68,6 +72,8 @@ pub struct FileService<B: StorageBackend> {
     buckets: BucketRegistry,
     objects: RwLock<HashMap<String, StorageObject>>, // key: bucket_id/path
     permissions: StoragePermissions,
+    config: StorageConfig,
+    signer: SignedUrlGenerator,
 }
 
 impl<B: StorageBackend> FileService<B> {
@@ -78,9 +84,32 @@ impl<B: StorageBackend> FileService<B> {
             buckets: BucketRegistry::new(),
             objects: RwLock::new(HashMap::new()),
             permissions: StoragePermissions::new(),
+            config: StorageConfig::default(),
+            signer: Self::signer(&StorageConfig::default()),
         }
     }
 
+    /// Enforce the limits in `config` on uploads and sign URLs with its
+    /// secret
+    pub fn with_config(mut self, config: StorageConfig) -> Self {
+        self.signer = Self::signer(&config);
+        self.config = config;
+        self
+    }
+
+    fn signer(config: &StorageConfig) -> SignedUrlGenerator {
+        let secret = match &config.signing_secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => {
+                let mut secret = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut secret);
+                secret
+            }
+        };
+        let max_expiry = Duration::seconds(config.max_signed_url_expiry_secs as i64);
+        SignedUrlGenerator::new(&secret).with_max_expiry(max_expiry)
+    }
+
     /// Get bucket registry
     pub fn buckets(&self) -> &BucketRegistry {
         &self.buckets
@@ -99,37 +128,119 @@ impl<B: StorageBackend> FileService<B> {
         content_type: &str,
         context: &RlsContext,
     ) -> StorageResult<StorageObject> {
+        let mut upload = self.begin_upload(bucket_name, path, content_type, context)?;
+        upload.write_chunk(data)?;
+        upload.finish()
+    }
+
+    /// Start a chunked upload; see [`Upload`]
+    pub fn begin_upload(
+        &self,
+        bucket_name: &str,
+        path: &str,
+        content_type: &str,
+        context: &RlsContext,
+    ) -> StorageResult<Upload<'_, B>> {
         let bucket = self.buckets.get(bucket_name)?;
 
         // Check permissions
         self.permissions.check_write(&bucket, context)?;
 
+        self.open_upload(bucket, path, content_type, context.user_id)
+    }
+
+    fn open_upload(
+        &self,
+        bucket: Bucket,
+        path: &str,
+        content_type: &str,
+        owner_id: Option<Uuid>,
+    ) -> StorageResult<Upload<'_, B>> {
         // Validate
-        bucket.check_size(data.len() as u64)?;
         if !bucket.is_mime_allowed(content_type) {
             return Err(StorageError::InvalidMimeType(content_type.to_string()));
         }
 
-        // Write to backend
         let storage_path = format!("{}/{}", bucket.id, path);
-        self.backend.write(&storage_path, data)?;
+        let writer = self.backend.begin_write(&storage_path)?;
 
-        // Create metadata
-        let mut object = StorageObject::new(
-            bucket.id,
+        Ok(Upload::new(
+            self,
+            bucket,
             path.to_string(),
-            data.len() as u64,
             content_type.to_string(),
-            context.user_id,
-        );
-        object.checksum = StorageObject::calculate_checksum(data);
+            owner_id,
+            writer,
+        ))
+    }
 
-        // Store metadata
-        let key = Self::object_key(&bucket.id, path);
-        if let Ok(mut objects) = self.objects.write() {
-            objects.insert(key, object.clone());
+    /// Check that `size` bytes uploaded to `path` stay within the bucket's
+    /// file size limit, the upload limit and the bucket quota
+    pub(super) fn check_upload_size(
+        &self,
+        bucket: &Bucket,
+        path: &str,
+        size: u64,
+    ) -> StorageResult<()> {
+        bucket.check_size(size)?;
+
+        let max = self.config.max_upload_bytes;
+        if max > 0 && size > max {
+            return Err(StorageError::FileTooLarge(size, max));
+        }
+
+        let objects = self
+            .objects
+            .read()
+            .map_err(|_| StorageError::Internal("Lock poisoned".into()))?;
+        self.check_quota(&objects, bucket, path, size)
+    }
+
+    /// Check that storing `size` bytes at `path` keeps the bucket within
+    /// `max_total_storage_bytes`. An object already at `path` is replaced,
+    /// so its bytes do not count.
+    fn check_quota(
+        &self,
+        objects: &HashMap<String, StorageObject>,
+        bucket: &Bucket,
+        path: &str,
+        size: u64,
+    ) -> StorageResult<()> {
+        let max = self.config.max_total_storage_bytes;
+        if max == 0 {
+            return Ok(());
         }
 
+        let stored: u64 = objects
+            .values()
+            .filter(|obj| obj.bucket_id == bucket.id && obj.path != path)
+            .map(|obj| obj.size)
+            .sum();
+        if stored + size > max {
+            return Err(StorageError::StorageQuotaExceeded(stored + size, max));
+        }
+        Ok(())
+    }
+
+    /// Commit a finished upload and store its metadata. The quota is
+    /// checked again under the metadata lock, as concurrent uploads to the
+    /// bucket may have committed since the last chunk.
+    pub(super) fn commit_upload(
+        &self,
+        bucket: &Bucket,
+        writer: Box<dyn ObjectWriter>,
+        object: StorageObject,
+    ) -> StorageResult<StorageObject> {
+        let mut objects = self
+            .objects
+            .write()
+            .map_err(|_| StorageError::Internal("Lock poisoned".into()))?;
+        self.check_quota(&objects, bucket, &object.path, object.size)?;
+
+        writer.commit()?;
+
+        let key = Self::object_key(&bucket.id, &object.path);
+        objects.insert(key, object.clone());
         Ok(object)
     }
 
@@ -145,6 +256,10 @@ impl<B: StorageBackend> FileService<B> {
         // Check permissions
         self.permissions.check_read(&bucket, context)?;
 
+        self.read_object(&bucket, path)
+    }
+
+    fn read_object(&self, bucket: &Bucket, path: &str) -> StorageResult<(StorageObject, Vec<u8>)> {
         // Get metadata
         let key = Self::object_key(&bucket.id, path);
         let object = {
@@ -165,6 +280,70 @@ impl<B: StorageBackend> FileService<B> {
         Ok((object, data))
     }
 
+    /// Sign a URL granting `method` on `path` without a JWT. The caller
+    /// must hold the matching permission: read for GET, write for PUT.
+    pub fn create_signed_url(
+        &self,
+        bucket_name: &str,
+        path: &str,
+        method: SignedMethod,
+        expires_in: Option<Duration>,
+        max_bytes: Option<u64>,
+        context: &RlsContext,
+    ) -> StorageResult<SignedUrl> {
+        let bucket = self.buckets.get(bucket_name)?;
+        match method {
+            SignedMethod::Get => self.permissions.check_read(&bucket, context)?,
+            SignedMethod::Put => self.permissions.check_write(&bucket, context)?,
+        }
+        Ok(self
+            .signer
+            .generate(&bucket, path, method, expires_in, max_bytes))
+    }
+
+    /// Verify a signed URL and return the bucket it grants access to
+    pub fn verify_signed_url(&self, url: &SignedUrl) -> StorageResult<Bucket> {
+        let bucket = self.buckets.get(&url.bucket)?;
+        self.signer.verify(url, &bucket)?;
+        Ok(bucket)
+    }
+
+    /// Download the object a signed GET URL names
+    pub fn download_signed(&self, url: &SignedUrl) -> StorageResult<(StorageObject, Vec<u8>)> {
+        if url.method != SignedMethod::Get {
+            return Err(StorageError::InvalidSignature);
+        }
+        let bucket = self.verify_signed_url(url)?;
+        self.read_object(&bucket, &url.path)
+    }
+
+    /// Start an upload to the object a signed PUT URL names, limited to
+    /// the URL's `max_bytes`
+    pub fn begin_signed_upload(
+        &self,
+        url: &SignedUrl,
+        content_type: &str,
+    ) -> StorageResult<Upload<'_, B>> {
+        if url.method != SignedMethod::Put {
+            return Err(StorageError::InvalidSignature);
+        }
+        let bucket = self.verify_signed_url(url)?;
+        let upload = self.open_upload(bucket, &url.path, content_type, None)?;
+        Ok(match url.max_bytes {
+            Some(max) => upload.with_max_bytes(max),
+            None => upload,
+        })
+    }
+
+    /// Revoke every signed URL issued for a bucket. The caller must hold
+    /// write permission on it.
+    pub fn revoke_signed_urls(&self, bucket_name: &str, context: &RlsContext) -> StorageResult<()> {
+        let bucket = self.buckets.get(bucket_name)?;
+        self.permissions.check_write(&bucket, context)?;
+        self.buckets.revoke_signed_urls(bucket_name)?;
+        Ok(())
+    }
+
     /// Delete a file
     pub fn delete(&self, bucket_name: &str, path: &str, context: &RlsContext) -> StorageResult<()> {
         let bucket = self.buckets.get(bucket_name)?;
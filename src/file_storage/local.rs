@@ -1,11 +1,17 @@
 //! # Local Filesystem Backend
 
-use std::fs;
+use std::fs::{self, File};
+use std::io::Write;
 use std::path::PathBuf;
 
-use super::backend::StorageBackend;
+use uuid::Uuid;
+
+use super::backend::{ObjectWriter, StorageBackend};
 use super::errors::{StorageError, StorageResult};
 
+/// Directory under the root holding writes that have not committed
+const STAGING_DIR: &str = ".staging";
+
 /// Local filesystem storage backend
 #[derive(Debug)]
 pub struct LocalBackend {
@@ -23,6 +29,52 @@ impl LocalBackend {
     }
 }
 
+fn io_error(e: std::io::Error) -> StorageError {
+    StorageError::IoError(e.to_string())
+}
+
+/// Chunked write to a staging file, renamed over the target on commit
+#[derive(Debug)]
+struct LocalObjectWriter {
+    file: File,
+    staging_path: PathBuf,
+    target_path: PathBuf,
+    committed: bool,
+}
+
+impl ObjectWriter for LocalObjectWriter {
+    fn write_chunk(&mut self, chunk: &[u8]) -> StorageResult<()> {
+        self.file.write_all(chunk).map_err(io_error)
+    }
+
+    fn commit(mut self: Box<Self>) -> StorageResult<()> {
+        self.file.sync_all().map_err(io_error)?;
+
+        let parent = self.target_path.parent().map(PathBuf::from);
+        if let Some(parent) = &parent {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::rename(&self.staging_path, &self.target_path).map_err(io_error)?;
+        self.committed = true;
+
+        // Persist the rename itself
+        if let Some(parent) = parent {
+            File::open(parent)
+                .and_then(|dir| dir.sync_all())
+                .map_err(io_error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LocalObjectWriter {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.staging_path);
+        }
+    }
+}
+
 impl StorageBackend for LocalBackend {
     fn write(&self, path: &str, data: &[u8]) -> StorageResult<()> {
         let full_path = self.full_path(path);
@@ -35,6 +87,21 @@ impl StorageBackend for LocalBackend {
         fs::write(&full_path, data).map_err(|e| StorageError::IoError(e.to_string()))
     }
 
+    fn begin_write(&self, path: &str) -> StorageResult<Box<dyn ObjectWriter>> {
+        let staging_dir = self.root.join(STAGING_DIR);
+        fs::create_dir_all(&staging_dir).map_err(io_error)?;
+
+        let staging_path = staging_dir.join(format!("{}.part", Uuid::new_v4()));
+        let file = File::create(&staging_path).map_err(io_error)?;
+
+        Ok(Box::new(LocalObjectWriter {
+            file,
+            staging_path,
+            target_path: self.full_path(path),
+            committed: false,
+        }))
+    }
+
     fn read(&self, path: &str) -> StorageResult<Vec<u8>> {
         let full_path = self.full_path(path);
 
@@ -120,6 +187,41 @@ mod tests {
         assert!(!backend.exists("delete-me.txt").unwrap());
     }
 
+    #[test]
+    fn test_chunked_write_visible_after_commit() {
+        let temp = TempDir::new().unwrap();
+        let backend = LocalBackend::new(temp.path().to_path_buf());
+
+        let mut writer = backend.begin_write("a/file.txt").unwrap();
+        writer.write_chunk(b"hello, ").unwrap();
+        writer.write_chunk(b"world").unwrap();
+        assert!(!backend.exists("a/file.txt").unwrap());
+
+        writer.commit().unwrap();
+        assert_eq!(backend.read("a/file.txt").unwrap(), b"hello, world");
+        assert_eq!(
+            fs::read_dir(temp.path().join(STAGING_DIR)).unwrap().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_dropped_write_leaves_nothing() {
+        let temp = TempDir::new().unwrap();
+        let backend = LocalBackend::new(temp.path().to_path_buf());
+        backend.write("file.txt", b"old").unwrap();
+
+        let mut writer = backend.begin_write("file.txt").unwrap();
+        writer.write_chunk(b"partial").unwrap();
+        drop(writer);
+
+        assert_eq!(backend.read("file.txt").unwrap(), b"old");
+        assert_eq!(
+            fs::read_dir(temp.path().join(STAGING_DIR)).unwrap().count(),
+            0
+        );
+    }
+
     #[test]
     fn test_not_found() {
         let temp = TempDir::new().unwrap();
@@ -6,18 +6,22 @@
 
 pub mod backend;
 pub mod bucket;
+pub mod config;
 pub mod errors;
 pub mod file;
 pub mod local;
 pub mod metadata;
 pub mod permissions;
 pub mod signed_url;
+pub mod upload;
 
 pub use backend::StorageBackend;
 pub use bucket::{Bucket, BucketConfig};
+pub use config::StorageConfig;
 pub use errors::{StorageError, StorageResult};
 pub use file::{FileService, StorageObject};
 pub use local::LocalBackend;
 pub use metadata::{InMemoryMetadataStore, MetadataStore};
 pub use permissions::StoragePermissions;
-pub use signed_url::SignedUrlGenerator;
+pub use signed_url::{SignedMethod, SignedUrl, SignedUrlGenerator};
+pub use upload::Upload;
@@ -1,16 +1,54 @@
 //! # Signed URL Generation
+//!
+//! A signed URL grants one HTTP method on one object until it expires,
+//! without a JWT. Its signature is an HMAC-SHA256, keyed by the server's
+//! signing secret, over the method, bucket, path, expiry and optional byte
+//! limit, and over the bucket's ID and signing generation, which the URL
+//! does not carry: recreating a bucket or bumping its generation revokes
+//! every URL signed for it.
 
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
-use chrono::{DateTime, Duration, Utc};
-use sha2::{Digest, Sha256};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 
+use super::bucket::Bucket;
 use super::errors::{StorageError, StorageResult};
 
+/// HTTP method a signed URL grants
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignedMethod {
+    /// Download the object
+    Get,
+    /// Upload the object
+    Put,
+}
+
+impl SignedMethod {
+    /// Parse an HTTP method name, case-insensitively
+    pub fn parse(method: &str) -> Option<Self> {
+        match method.to_ascii_uppercase().as_str() {
+            "GET" => Some(Self::Get),
+            "PUT" => Some(Self::Put),
+            _ => None,
+        }
+    }
+
+    /// The HTTP method name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Put => "PUT",
+        }
+    }
+}
+
 /// Signed URL generator
 #[derive(Debug)]
 pub struct SignedUrlGenerator {
     secret: Vec<u8>,
     default_expiry: Duration,
+    max_expiry: Duration,
 }
 
 impl SignedUrlGenerator {
@@ -19,55 +57,90 @@ impl SignedUrlGenerator {
         Self {
             secret: secret.to_vec(),
             default_expiry: Duration::hours(1),
+            max_expiry: Duration::weeks(1),
         }
     }
 
-    /// Generate a signed URL
+    /// Cap the lifetime of generated URLs at `max_expiry`
+    pub fn with_max_expiry(mut self, max_expiry: Duration) -> Self {
+        self.max_expiry = max_expiry;
+        self
+    }
+
+    /// Generate a signed URL for `method` on `path` in `bucket`, valid for
+    /// `expires_in` (default one hour) capped at the maximum expiry
     pub fn generate(
         &self,
-        bucket: &str,
+        bucket: &Bucket,
         path: &str,
-        expires_at: Option<DateTime<Utc>>,
+        method: SignedMethod,
+        expires_in: Option<Duration>,
+        max_bytes: Option<u64>,
     ) -> SignedUrl {
-        let expires = expires_at.unwrap_or_else(|| Utc::now() + self.default_expiry);
-        let expires_ts = expires.timestamp();
-
-        let message = format!("{}/{}/{}", bucket, path, expires_ts);
-        let signature = self.sign(&message);
+        let expires_in = expires_in
+            .unwrap_or(self.default_expiry)
+            .min(self.max_expiry);
+        // URLs carry whole seconds
+        let expires_at = Utc
+            .timestamp_opt((Utc::now() + expires_in).timestamp(), 0)
+            .single()
+            .unwrap_or_else(Utc::now);
 
-        SignedUrl {
-            bucket: bucket.to_string(),
+        let mut url = SignedUrl {
+            bucket: bucket.name.clone(),
             path: path.to_string(),
-            expires_at: expires,
-            signature,
-        }
+            method,
+            expires_at,
+            max_bytes,
+            signature: String::new(),
+        };
+        url.signature = URL_SAFE_NO_PAD.encode(self.mac(&url, bucket).finalize().into_bytes());
+        url
     }
 
-    /// Verify a signed URL
-    pub fn verify(&self, url: &SignedUrl) -> StorageResult<()> {
+    /// Verify a signed URL against the bucket it names.
+    ///
+    /// The signature is compared in constant time.
+    pub fn verify(&self, url: &SignedUrl, bucket: &Bucket) -> StorageResult<()> {
         // Check expiry
         if Utc::now() > url.expires_at {
             return Err(StorageError::UrlExpired);
         }
 
         // Verify signature
-        let message = format!("{}/{}/{}", url.bucket, url.path, url.expires_at.timestamp());
-        let expected = self.sign(&message);
-
-        if url.signature != expected {
+        let signature = URL_SAFE_NO_PAD
+            .decode(&url.signature)
+            .map_err(|_| StorageError::InvalidSignature)?;
+        if url.bucket != bucket.name || self.mac(url, bucket).verify_slice(&signature).is_err() {
             return Err(StorageError::InvalidSignature);
         }
 
         Ok(())
     }
 
-    fn sign(&self, message: &str) -> String {
-        // Simple SHA-256 based signature (secret + message)
-        let mut hasher = Sha256::new();
-        hasher.update(&self.secret);
-        hasher.update(message.as_bytes());
-        let result = hasher.finalize();
-        URL_SAFE_NO_PAD.encode(result)
+    fn mac(&self, url: &SignedUrl, bucket: &Bucket) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC can accept any key size");
+        // Length-prefix the variable fields so no two URLs share a message
+        for field in [
+            url.method.as_str().as_bytes(),
+            url.bucket.as_bytes(),
+            url.path.as_bytes(),
+        ] {
+            mac.update(&(field.len() as u64).to_le_bytes());
+            mac.update(field);
+        }
+        mac.update(&url.expires_at.timestamp().to_le_bytes());
+        match url.max_bytes {
+            Some(max) => {
+                mac.update(&[1]);
+                mac.update(&max.to_le_bytes());
+            }
+            None => mac.update(&[0]),
+        }
+        mac.update(bucket.id.as_bytes());
+        mac.update(&bucket.signing_generation.to_le_bytes());
+        mac
     }
 }
 
@@ -76,52 +149,85 @@ impl SignedUrlGenerator {
 pub struct SignedUrl {
     pub bucket: String,
     pub path: String,
+    pub method: SignedMethod,
     pub expires_at: DateTime<Utc>,
+    pub max_bytes: Option<u64>,
     pub signature: String,
 }
 
 impl SignedUrl {
-    /// Generate the URL string
+    /// Rebuild a signed URL from a request for `method` on `path`, with the
+    /// expiry, byte limit and signature from its query string
+    pub fn from_request(
+        bucket: &str,
+        path: &str,
+        method: SignedMethod,
+        expires: i64,
+        max_bytes: Option<u64>,
+        signature: &str,
+    ) -> StorageResult<Self> {
+        let expires_at = Utc
+            .timestamp_opt(expires, 0)
+            .single()
+            .ok_or(StorageError::InvalidSignature)?;
+        Ok(Self {
+            bucket: bucket.to_string(),
+            path: path.to_string(),
+            method,
+            expires_at,
+            max_bytes,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Generate the URL string for the object route under `base_url`
     pub fn to_url(&self, base_url: &str) -> String {
-        format!(
-            "{}/storage/v1/object/sign/{}/{}?token={}&expires={}",
+        let mut url = format!(
+            "{}/buckets/{}/files/{}?expires={}",
             base_url,
             self.bucket,
             self.path,
-            self.signature,
             self.expires_at.timestamp()
-        )
+        );
+        if let Some(max) = self.max_bytes {
+            url.push_str(&format!("&max_bytes={}", max));
+        }
+        url.push_str(&format!("&signature={}", self.signature));
+        url
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::file_storage::bucket::BucketConfig;
+
+    fn bucket() -> Bucket {
+        Bucket::new("avatars".to_string(), None, BucketConfig::default())
+    }
 
     #[test]
     fn test_generate_and_verify() {
         let generator = SignedUrlGenerator::new(b"test-secret");
+        let bucket = bucket();
 
-        let signed = generator.generate("avatars", "user/123.png", None);
+        let signed = generator.generate(&bucket, "user/123.png", SignedMethod::Get, None, None);
         assert!(!signed.signature.is_empty());
 
         // Should verify successfully
-        assert!(generator.verify(&signed).is_ok());
+        assert!(generator.verify(&signed, &bucket).is_ok());
     }
 
     #[test]
     fn test_expired_url() {
         let generator = SignedUrlGenerator::new(b"test-secret");
+        let bucket = bucket();
 
-        let expired = SignedUrl {
-            bucket: "test".to_string(),
-            path: "file.txt".to_string(),
-            expires_at: Utc::now() - Duration::hours(1),
-            signature: "fake".to_string(),
-        };
+        let mut expired = generator.generate(&bucket, "file.txt", SignedMethod::Get, None, None);
+        expired.expires_at = Utc::now() - Duration::hours(1);
 
         assert!(matches!(
-            generator.verify(&expired),
+            generator.verify(&expired, &bucket),
             Err(StorageError::UrlExpired)
         ));
     }
@@ -129,28 +235,74 @@ mod tests {
     #[test]
     fn test_invalid_signature() {
         let generator = SignedUrlGenerator::new(b"test-secret");
+        let bucket = bucket();
 
         let invalid = SignedUrl {
-            bucket: "test".to_string(),
+            bucket: "avatars".to_string(),
             path: "file.txt".to_string(),
+            method: SignedMethod::Get,
             expires_at: Utc::now() + Duration::hours(1),
+            max_bytes: None,
             signature: "bad-signature".to_string(),
         };
 
         assert!(matches!(
-            generator.verify(&invalid),
+            generator.verify(&invalid, &bucket),
             Err(StorageError::InvalidSignature)
         ));
     }
 
+    #[test]
+    fn test_signed_parameters_cannot_change() {
+        let generator = SignedUrlGenerator::new(b"test-secret");
+        let mut bucket = bucket();
+        let signed = generator.generate(&bucket, "a.txt", SignedMethod::Put, None, Some(100));
+
+        let mut tampered = vec![signed.clone(); 4];
+        tampered[0].path = "b.txt".to_string();
+        tampered[1].method = SignedMethod::Get;
+        tampered[2].max_bytes = Some(1000);
+        tampered[3].expires_at += Duration::hours(1);
+        for url in &tampered {
+            assert!(matches!(
+                generator.verify(url, &bucket),
+                Err(StorageError::InvalidSignature)
+            ));
+        }
+
+        // Another secret or a bumped generation rejects the URL too
+        let other = SignedUrlGenerator::new(b"other-secret");
+        assert!(other.verify(&signed, &bucket).is_err());
+        bucket.signing_generation += 1;
+        assert!(generator.verify(&signed, &bucket).is_err());
+    }
+
+    #[test]
+    fn test_expiry_capped() {
+        let generator = SignedUrlGenerator::new(b"secret").with_max_expiry(Duration::minutes(5));
+        let signed = generator.generate(
+            &bucket(),
+            "file.txt",
+            SignedMethod::Get,
+            Some(Duration::days(30)),
+            None,
+        );
+        assert!(signed.expires_at <= Utc::now() + Duration::minutes(5));
+    }
+
     #[test]
     fn test_to_url() {
         let generator = SignedUrlGenerator::new(b"secret");
-        let signed = generator.generate("bucket", "path/file.txt", None);
+        let signed = generator.generate(
+            &bucket(),
+            "path/file.txt",
+            SignedMethod::Put,
+            None,
+            Some(10),
+        );
 
-        let url = signed.to_url("https://api.example.com");
-        assert!(url.contains("/storage/v1/object/sign/"));
-        assert!(url.contains("bucket"));
-        assert!(url.contains("token="));
+        let url = signed.to_url("/storage");
+        assert!(url.starts_with("/storage/buckets/avatars/files/path/file.txt?expires="));
+        assert!(url.contains("&max_bytes=10&signature="));
     }
 }
@@ -0,0 +1,227 @@
+//! # Streaming Uploads
+//!
+//! An upload arrives in chunks. Each chunk is written to the backend's
+//! staging area as it comes, hashed and counted, so only the chunk in hand
+//! is ever held in memory. The chunk that takes an upload past a limit
+//! fails it at once; dropping the failed upload discards what was staged,
+//! so no partial object is ever visible.
+
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::backend::{ObjectWriter, StorageBackend};
+use super::bucket::Bucket;
+use super::errors::{StorageError, StorageResult};
+use super::file::{FileService, StorageObject};
+
+/// An upload in progress, started by [`FileService::begin_upload`].
+///
+/// After an error the upload must be dropped, which discards it.
+pub struct Upload<'a, B: StorageBackend> {
+    service: &'a FileService<B>,
+    bucket: Bucket,
+    path: String,
+    content_type: String,
+    owner_id: Option<Uuid>,
+    writer: Box<dyn ObjectWriter>,
+    hasher: Sha256,
+    size: u64,
+    max_bytes: Option<u64>,
+}
+
+impl<'a, B: StorageBackend> Upload<'a, B> {
+    pub(super) fn new(
+        service: &'a FileService<B>,
+        bucket: Bucket,
+        path: String,
+        content_type: String,
+        owner_id: Option<Uuid>,
+        writer: Box<dyn ObjectWriter>,
+    ) -> Self {
+        Self {
+            service,
+            bucket,
+            path,
+            content_type,
+            owner_id,
+            writer,
+            hasher: Sha256::new(),
+            size: 0,
+            max_bytes: None,
+        }
+    }
+
+    /// Limit this upload to `max_bytes`, on top of the configured limits
+    pub(super) fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Append a chunk, failing with `FileTooLarge` or
+    /// `StorageQuotaExceeded` before writing it if it crosses a limit
+    pub fn write_chunk(&mut self, chunk: &[u8]) -> StorageResult<()> {
+        let size = self.size + chunk.len() as u64;
+        if let Some(max) = self.max_bytes.filter(|&max| size > max) {
+            return Err(StorageError::FileTooLarge(size, max));
+        }
+        self.service
+            .check_upload_size(&self.bucket, &self.path, size)?;
+
+        self.writer.write_chunk(chunk)?;
+        self.hasher.update(chunk);
+        self.size = size;
+        Ok(())
+    }
+
+    /// Bytes written so far
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Commit the upload: the object becomes durable and visible, with the
+    /// SHA-256 of its content as checksum
+    pub fn finish(self) -> StorageResult<StorageObject> {
+        let mut object = StorageObject::new(
+            self.bucket.id,
+            self.path,
+            self.size,
+            self.content_type,
+            self.owner_id,
+        );
+        object.checksum = format!("{:x}", self.hasher.finalize());
+
+        self.service
+            .commit_upload(&self.bucket, self.writer, object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::rls::RlsContext;
+    use crate::file_storage::bucket::{BucketConfig, BucketPolicy};
+    use crate::file_storage::config::StorageConfig;
+    use crate::file_storage::local::LocalBackend;
+    use tempfile::TempDir;
+
+    fn create_test_service(config: StorageConfig) -> (FileService<LocalBackend>, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = LocalBackend::new(temp_dir.path().to_path_buf());
+        let service = FileService::new(backend).with_config(config);
+
+        let config = BucketConfig {
+            policy: BucketPolicy::Public,
+            ..BucketConfig::default()
+        };
+        service
+            .buckets()
+            .create("test".to_string(), None, config)
+            .unwrap();
+        (service, temp_dir)
+    }
+
+    /// Every file under `root`, at any depth
+    fn files_under(root: &std::path::Path) -> Vec<std::path::PathBuf> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(root).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files.extend(files_under(&path));
+            } else {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn test_chunked_upload_hashes_content() {
+        let (service, _temp) = create_test_service(StorageConfig::default());
+        let context = RlsContext::authenticated(Uuid::new_v4());
+
+        let mut upload = service
+            .begin_upload("test", "hello.txt", "text/plain", &context)
+            .unwrap();
+        upload.write_chunk(b"Hello, ").unwrap();
+        upload.write_chunk(b"World!").unwrap();
+        assert_eq!(upload.size(), 13);
+        let obj = upload.finish().unwrap();
+
+        assert_eq!(obj.size, 13);
+        assert_eq!(
+            obj.checksum,
+            StorageObject::calculate_checksum(b"Hello, World!")
+        );
+        let (stored, data) = service.download("test", "hello.txt", &context).unwrap();
+        assert_eq!(data, b"Hello, World!");
+        assert_eq!(stored.checksum, obj.checksum);
+    }
+
+    #[test]
+    fn test_over_limit_upload_leaves_no_partial_object() {
+        let config = StorageConfig {
+            max_upload_bytes: 10,
+            ..StorageConfig::default()
+        };
+        let (service, temp) = create_test_service(config);
+        let context = RlsContext::authenticated(Uuid::new_v4());
+
+        let mut upload = service
+            .begin_upload("test", "big.bin", "application/octet-stream", &context)
+            .unwrap();
+        upload.write_chunk(&[0; 8]).unwrap();
+        let err = upload.write_chunk(&[0; 8]).unwrap_err();
+        assert!(matches!(err, StorageError::FileTooLarge(16, 10)));
+        assert_eq!(err.status_code(), 413);
+        drop(upload);
+
+        assert!(service.list("test", "", &context).unwrap().is_empty());
+        assert!(service.download("test", "big.bin", &context).is_err());
+        assert!(files_under(temp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_bucket_quota_counts_stored_objects() {
+        let config = StorageConfig {
+            max_total_storage_bytes: 20,
+            ..StorageConfig::default()
+        };
+        let (service, _temp) = create_test_service(config);
+        let context = RlsContext::authenticated(Uuid::new_v4());
+
+        service
+            .upload(
+                "test",
+                "a.bin",
+                &[0; 12],
+                "application/octet-stream",
+                &context,
+            )
+            .unwrap();
+
+        // 12 stored + 9 new crosses the quota
+        let err = service
+            .upload(
+                "test",
+                "b.bin",
+                &[0; 9],
+                "application/octet-stream",
+                &context,
+            )
+            .unwrap_err();
+        assert!(matches!(err, StorageError::StorageQuotaExceeded(21, 20)));
+        assert_eq!(err.status_code(), 413);
+
+        // Replacing an object does not count its old size
+        service
+            .upload(
+                "test",
+                "a.bin",
+                &[0; 20],
+                "application/octet-stream",
+                &context,
+            )
+            .unwrap();
+        assert_eq!(service.list("test", "", &context).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,135 @@
+//! # Built-in Jobs
+//!
+//! Housekeeping jobs the server schedules for itself with
+//! [`Scheduler::register_builtin`](super::scheduler::Scheduler::register_builtin).
+//! Each constructor wraps the service it cleans up; the server registers the
+//! jobs of the services it runs. Like any job, a built-in one can be
+//! rescheduled or disabled by defining a job of the same name.
+
+use std::sync::Arc;
+
+use super::errors::{FunctionError, FunctionResult};
+use super::scheduler::JobFunction;
+use crate::auth::api::AuthService;
+use crate::auth::magic_link::MagicLinkService;
+use crate::auth::oauth::{OAuthRepository, OAuthService};
+use crate::auth::session::SessionRepository;
+use crate::auth::user::UserRepository;
+use crate::backup::BackupManager;
+
+/// Deletes expired magic-link tokens
+pub const MAGIC_LINK_CLEANUP: &str = "magic_link_cleanup";
+
+/// Every five minutes
+pub const MAGIC_LINK_CLEANUP_CRON: &str = "*/5 * * * *";
+
+/// Deletes OAuth states older than their maximum age
+pub const OAUTH_STATE_GC: &str = "oauth_state_gc";
+
+/// Every five minutes
+pub const OAUTH_STATE_GC_CRON: &str = "*/5 * * * *";
+
+/// Deletes expired sessions and password reset tokens
+pub const SESSION_EXPIRY: &str = "session_expiry";
+
+/// Hourly
+pub const SESSION_EXPIRY_CRON: &str = "0 * * * *";
+
+/// Deletes backups beyond `backup.max_backups`
+pub const BACKUP_RETENTION: &str = "backup_retention";
+
+/// Daily at 03:30 UTC
+pub const BACKUP_RETENTION_CRON: &str = "30 3 * * *";
+
+/// The magic-link cleanup job of `service`
+pub fn magic_link_cleanup<U>(service: Arc<MagicLinkService<U>>) -> impl JobFunction
+where
+    U: UserRepository + 'static,
+{
+    move || {
+        let removed = service.cleanup_expired();
+        Ok(format!("Removed {} expired magic links", removed))
+    }
+}
+
+/// The OAuth state GC job of `service`
+pub fn oauth_state_gc<U, O>(service: Arc<OAuthService<U, O>>) -> impl JobFunction
+where
+    U: UserRepository + 'static,
+    O: OAuthRepository + 'static,
+{
+    move || {
+        let removed = service.gc_expired_states();
+        Ok(format!("Removed {} expired OAuth states", removed))
+    }
+}
+
+/// The session expiry job of `service`
+pub fn session_expiry<U, S>(service: Arc<AuthService<U, S>>) -> impl JobFunction
+where
+    U: UserRepository + 'static,
+    S: SessionRepository + 'static,
+{
+    move || -> FunctionResult<String> {
+        let removed = service
+            .expire_sessions()
+            .map_err(|e| FunctionError::RuntimeError(e.to_string()))?;
+        Ok(format!("Removed {} expired sessions", removed))
+    }
+}
+
+/// The backup retention job of `manager`
+pub fn backup_retention(manager: Arc<BackupManager>) -> impl JobFunction {
+    move || -> FunctionResult<String> {
+        let deleted = manager
+            .enforce_retention()
+            .map_err(|e| FunctionError::RuntimeError(e.to_string()))?;
+        Ok(format!("Deleted {} old backups", deleted))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::crypto::PasswordPolicy;
+    use crate::auth::jwt::JwtConfig;
+    use crate::auth::session::{InMemorySessionRepository, SessionConfig};
+    use crate::auth::user::{InMemoryUserRepository, SignupRequest};
+    use crate::functions::scheduler::JobDefinition;
+
+    #[test]
+    fn test_builtin_crons_are_valid() {
+        for (name, cron) in [
+            (MAGIC_LINK_CLEANUP, MAGIC_LINK_CLEANUP_CRON),
+            (OAUTH_STATE_GC, OAUTH_STATE_GC_CRON),
+            (SESSION_EXPIRY, SESSION_EXPIRY_CRON),
+            (BACKUP_RETENTION, BACKUP_RETENTION_CRON),
+        ] {
+            assert!(JobDefinition::new(name, cron, name).validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_session_expiry_job() {
+        let service = Arc::new(AuthService::new(
+            InMemoryUserRepository::new(),
+            InMemorySessionRepository::new(),
+            JwtConfig::default(),
+            SessionConfig {
+                refresh_token_ttl: chrono::Duration::seconds(-1),
+            },
+            PasswordPolicy::default(),
+        ));
+        service
+            .signup(SignupRequest {
+                email: "test@example.com".to_string(),
+                password: "password123".to_string(),
+                metadata: None,
+            })
+            .unwrap();
+
+        let job = session_expiry(service);
+        assert_eq!(job.run().unwrap(), "Removed 1 expired sessions");
+        assert_eq!(job.run().unwrap(), "Removed 0 expired sessions");
+    }
+}
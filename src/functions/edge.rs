@@ -0,0 +1,506 @@
+//! # Edge Functions
+//!
+//! An edge function answers HTTP requests at `/functions/v1/{name}`. It is
+//! native Rust code behind the [`EdgeFunction`] trait, registered with a
+//! [`FunctionManifest`] declaring the methods it accepts and the time and
+//! memory one invocation may take.
+//!
+//! Each invocation runs on the blocking thread pool. It takes one of its
+//! function's in-flight slots and reserves its memory limit from the edge
+//! function budget, apart from the memory queries draw from, before it
+//! starts, and gives both back when it finishes; a panic or error becomes
+//! `Crashed` (502) and an invocation past its timeout becomes `Timeout`
+//! (504). Neither affects the server or other invocations. A timed-out
+//! function cannot be stopped: its thread runs on, holding its slot and
+//! reservation, until it returns. A hung function therefore exhausts its
+//! own slots and the edge budget, never the server's memory.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::errors::{FunctionError, FunctionResult};
+use super::trigger::HttpMethod;
+use crate::auth::jwt::JwtClaims;
+use crate::observability::{JsonLogger, Logger};
+use crate::panic_handler::catch_contained;
+use crate::resource_limits::MemoryTracker;
+
+/// Default time one invocation may take
+pub const DEFAULT_EDGE_TIMEOUT_MS: u64 = 10_000;
+
+/// Default memory reserved for one invocation
+pub const DEFAULT_EDGE_MEMORY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Default invocations of one function running at once
+pub const DEFAULT_EDGE_MAX_CONCURRENCY: usize = 8;
+
+/// What an edge function accepts and may use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionManifest {
+    /// Name, the last segment of the function's URL
+    pub name: String,
+
+    /// HTTP methods the function answers; others get 405
+    pub methods: Vec<HttpMethod>,
+
+    /// Time one invocation may take
+    pub timeout_ms: u64,
+
+    /// Memory reserved from the edge function budget for one invocation
+    pub memory_limit_bytes: u64,
+
+    /// Invocations running at once, timed-out ones included; more get 503
+    pub max_concurrency: usize,
+
+    /// Require a valid access token (default); otherwise callers without
+    /// one are invoked as anonymous
+    pub verify_jwt: bool,
+}
+
+impl FunctionManifest {
+    /// A manifest for `name` answering POST with the default limits
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            methods: vec![HttpMethod::Post],
+            timeout_ms: DEFAULT_EDGE_TIMEOUT_MS,
+            memory_limit_bytes: DEFAULT_EDGE_MEMORY_BYTES,
+            max_concurrency: DEFAULT_EDGE_MAX_CONCURRENCY,
+            verify_jwt: true,
+        }
+    }
+
+    /// Answer `methods` instead
+    pub fn with_methods(mut self, methods: impl IntoIterator<Item = HttpMethod>) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Set the time one invocation may take
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = timeout.as_millis() as u64;
+        self
+    }
+
+    /// Set the memory reserved for one invocation
+    pub fn with_memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_limit_bytes = bytes;
+        self
+    }
+
+    /// Set how many invocations may run at once
+    pub fn with_max_concurrency(mut self, invocations: usize) -> Self {
+        self.max_concurrency = invocations;
+        self
+    }
+
+    /// Invoke callers without an access token as anonymous
+    pub fn allow_anonymous(mut self) -> Self {
+        self.verify_jwt = false;
+        self
+    }
+
+    /// Whether the function answers `method`
+    pub fn allows(&self, method: HttpMethod) -> bool {
+        self.methods.contains(&method)
+    }
+}
+
+/// The request an edge function is invoked with
+#[derive(Debug, Clone)]
+pub struct EdgeRequest {
+    /// Invocation ID, unique per call
+    pub invocation_id: Uuid,
+
+    /// HTTP method
+    pub method: HttpMethod,
+
+    /// Request headers, names lowercased; the access token is removed
+    pub headers: BTreeMap<String, String>,
+
+    /// Raw request body
+    pub body: Vec<u8>,
+
+    /// Claims of the caller's access token, if one was given
+    pub claims: Option<JwtClaims>,
+}
+
+impl EdgeRequest {
+    /// A request for `method` with an empty body
+    pub fn new(method: HttpMethod) -> Self {
+        Self {
+            invocation_id: Uuid::new_v4(),
+            method,
+            headers: BTreeMap::new(),
+            body: Vec::new(),
+            claims: None,
+        }
+    }
+
+    /// Parse the body as JSON
+    pub fn json(&self) -> FunctionResult<Value> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| FunctionError::RuntimeError(format!("Invalid JSON body: {}", e)))
+    }
+}
+
+/// The response an edge function returns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeResponse {
+    /// HTTP status code
+    pub status: u16,
+
+    /// Response headers
+    pub headers: Vec<(String, String)>,
+
+    /// Response body
+    pub body: Vec<u8>,
+}
+
+impl EdgeResponse {
+    /// A response with `status` and `body` and no headers
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// A JSON response
+    pub fn json(status: u16, value: &Value) -> Self {
+        Self::new(status, value.to_string()).with_header("content-type", "application/json")
+    }
+
+    /// Add a response header
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// A native edge function
+///
+/// Called on a blocking thread, so it may block; it should still return
+/// within its manifest's timeout. Any closure taking an [`EdgeRequest`]
+/// and returning a [`FunctionResult<EdgeResponse>`] is an edge function.
+pub trait EdgeFunction: Send + Sync {
+    /// Answer one request
+    fn call(&self, request: EdgeRequest) -> FunctionResult<EdgeResponse>;
+}
+
+impl<F> EdgeFunction for F
+where
+    F: Fn(EdgeRequest) -> FunctionResult<EdgeResponse> + Send + Sync,
+{
+    fn call(&self, request: EdgeRequest) -> FunctionResult<EdgeResponse> {
+        self(request)
+    }
+}
+
+/// A registered edge function: its manifest and its code
+///
+/// Clones share the count of invocations in flight.
+#[derive(Clone)]
+pub struct DeployedEdgeFunction {
+    pub manifest: FunctionManifest,
+    pub handler: Arc<dyn EdgeFunction>,
+    /// Invocations whose function has not returned yet
+    in_flight: Arc<AtomicUsize>,
+    /// Where contained panics are logged
+    logger: Arc<dyn Logger>,
+}
+
+impl fmt::Debug for DeployedEdgeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeployedEdgeFunction")
+            .field("manifest", &self.manifest)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DeployedEdgeFunction {
+    /// Deploy `handler` under `manifest`
+    pub fn new(manifest: FunctionManifest, handler: Arc<dyn EdgeFunction>) -> Self {
+        Self {
+            manifest,
+            handler,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            logger: JsonLogger::shared(),
+        }
+    }
+
+    /// Log contained panics to `logger`
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Invocations whose function has not returned yet
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Run the function on `request` within its manifest's limits, charging
+    /// its memory limit to the edge function `budget` if given
+    pub async fn invoke(
+        &self,
+        request: EdgeRequest,
+        budget: Option<&Arc<MemoryTracker>>,
+    ) -> FunctionResult<EdgeResponse> {
+        if !self.manifest.allows(request.method) {
+            return Err(FunctionError::MethodNotAllowed(
+                request.method.as_str().to_string(),
+            ));
+        }
+
+        let slot = InFlightSlot::new(&self.in_flight, &self.manifest)?;
+        let reservation = match budget {
+            Some(budget) => Some(MemoryReservation::new(
+                budget.clone(),
+                self.manifest.memory_limit_bytes,
+            )?),
+            None => None,
+        };
+        let handler = Arc::clone(&self.handler);
+        let logger = Arc::clone(&self.logger);
+        let name = self.manifest.name.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            // Held until the function returns, even after a timeout
+            let _reservation = reservation;
+            let _slot = slot;
+            let invocation_id = request.invocation_id.to_string();
+            let result = catch_contained(|| handler.call(request));
+            // The panic hook ignores contained panics; this is their only trace
+            if let Err(panic) = &result {
+                logger.error(
+                    "EDGE_FUNCTION_PANICKED",
+                    &[
+                        ("function", &name),
+                        ("invocation_id", &invocation_id),
+                        ("panic", &panic_message(&**panic)),
+                    ],
+                );
+            }
+            result
+        });
+
+        let timeout_ms = self.manifest.timeout_ms;
+        match tokio::time::timeout(Duration::from_millis(timeout_ms), task).await {
+            Err(_) => Err(FunctionError::Timeout(timeout_ms)),
+            Ok(Err(e)) => Err(FunctionError::Crashed(e.to_string())),
+            Ok(Ok(Err(panic))) => Err(FunctionError::Crashed(panic_message(&*panic))),
+            Ok(Ok(Ok(Err(e)))) => Err(FunctionError::Crashed(e.to_string())),
+            Ok(Ok(Ok(Ok(response)))) => Ok(response),
+        }
+    }
+}
+
+/// One of a function's in-flight invocations, given back on drop
+struct InFlightSlot {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl InFlightSlot {
+    fn new(in_flight: &Arc<AtomicUsize>, manifest: &FunctionManifest) -> FunctionResult<Self> {
+        in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < manifest.max_concurrency).then_some(n + 1)
+            })
+            .map_err(|n| {
+                FunctionError::ResourcesExhausted(format!(
+                    "{} invocations of {} in flight (max {})",
+                    n, manifest.name, manifest.max_concurrency
+                ))
+            })?;
+        Ok(Self {
+            in_flight: Arc::clone(in_flight),
+        })
+    }
+}
+
+impl Drop for InFlightSlot {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Memory charged to a budget, released on drop
+struct MemoryReservation {
+    budget: Arc<MemoryTracker>,
+    bytes: u64,
+}
+
+impl MemoryReservation {
+    fn new(budget: Arc<MemoryTracker>, bytes: u64) -> FunctionResult<Self> {
+        budget
+            .try_allocate(bytes)
+            .map_err(|e| FunctionError::ResourcesExhausted(e.to_string()))?;
+        Ok(Self { budget, bytes })
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.budget.release(self.bytes);
+    }
+}
+
+pub(super) fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        format!("panicked: {}", s)
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        format!("panicked: {}", s)
+    } else {
+        "panicked".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::VecLogger;
+    use crate::resource_limits::{ResourceLimitsConfig, ResourceManager};
+
+    fn deploy(
+        manifest: FunctionManifest,
+        handler: impl EdgeFunction + 'static,
+    ) -> DeployedEdgeFunction {
+        DeployedEdgeFunction::new(manifest, Arc::new(handler))
+    }
+
+    fn resources(max_edge_function_memory_bytes: u64) -> Arc<ResourceManager> {
+        let config = ResourceLimitsConfig {
+            max_memory_bytes: 1024,
+            max_edge_function_memory_bytes,
+            ..ResourceLimitsConfig::default()
+        };
+        Arc::new(ResourceManager::new(config, std::env::temp_dir()))
+    }
+
+    fn echo(request: EdgeRequest) -> FunctionResult<EdgeResponse> {
+        Ok(EdgeResponse::new(200, request.body).with_header("x-echo", "1"))
+    }
+
+    #[tokio::test]
+    async fn test_invoke_returns_response() {
+        let function = deploy(FunctionManifest::new("echo"), echo);
+        let mut request = EdgeRequest::new(HttpMethod::Post);
+        request.body = b"hello".to_vec();
+
+        let response = function.invoke(request, None).await.unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"hello");
+        assert_eq!(response.headers, vec![("x-echo".into(), "1".into())]);
+
+        let err = function
+            .invoke(EdgeRequest::new(HttpMethod::Get), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), 405);
+    }
+
+    #[tokio::test]
+    async fn test_panic_and_error_are_crashes() {
+        let logger = Arc::new(VecLogger::new());
+        let panics = deploy(
+            FunctionManifest::new("panics"),
+            |_: EdgeRequest| -> FunctionResult<EdgeResponse> { panic!("boom") },
+        )
+        .with_logger(logger.clone());
+        let request = EdgeRequest::new(HttpMethod::Post);
+        let invocation_id = request.invocation_id.to_string();
+        let err = panics.invoke(request, None).await.unwrap_err();
+        assert!(matches!(&err, FunctionError::Crashed(m) if m.contains("boom")));
+        assert_eq!(err.status_code(), 502);
+
+        // The contained panic is logged with the function and invocation
+        let records = logger.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].event, "EDGE_FUNCTION_PANICKED");
+        assert_eq!(records[0].field("function"), Some("panics"));
+        assert_eq!(
+            records[0].field("invocation_id"),
+            Some(invocation_id.as_str())
+        );
+        assert_eq!(records[0].field("panic"), Some("panicked: boom"));
+
+        let fails = deploy(FunctionManifest::new("fails"), |_: EdgeRequest| {
+            Err(FunctionError::RuntimeError("bad input".into()))
+        });
+        let err = fails
+            .invoke(EdgeRequest::new(HttpMethod::Post), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), 502);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_invocations_stay_within_the_function() {
+        let resources = resources(1024);
+        let budget = resources.edge_function_memory();
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let wait = std::sync::Mutex::new(wait);
+        let slow = deploy(
+            FunctionManifest::new("slow")
+                .with_timeout(Duration::from_millis(50))
+                .with_memory_limit(1000)
+                .with_max_concurrency(1),
+            move |_: EdgeRequest| {
+                let _ = wait.lock().unwrap().recv();
+                Ok(EdgeResponse::new(200, "late"))
+            },
+        );
+
+        let err = slow
+            .invoke(EdgeRequest::new(HttpMethod::Post), Some(budget))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FunctionError::Timeout(50)));
+        assert_eq!(err.status_code(), 504);
+
+        // The abandoned invocation keeps its slot and edge memory, but the
+        // server's memory stays free for queries
+        assert_eq!(slow.in_flight(), 1);
+        assert_eq!(budget.current(), 1000);
+        assert_eq!(resources.memory_tracker().current(), 0);
+        assert!(resources.try_allocate_memory(1024).is_ok());
+        resources.release_memory(1024);
+
+        // Further invocations of the function are refused
+        let err = slow
+            .invoke(EdgeRequest::new(HttpMethod::Post), Some(budget))
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, FunctionError::ResourcesExhausted(m) if m.contains("in flight")));
+        assert_eq!(err.status_code(), 503);
+
+        // Other functions only compete for what is left of the edge budget
+        let echo = deploy(FunctionManifest::new("echo").with_memory_limit(1000), echo);
+        let err = echo
+            .invoke(EdgeRequest::new(HttpMethod::Post), Some(budget))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code(), 503);
+
+        release.send(()).unwrap();
+        for _ in 0..100 {
+            if budget.current() == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(budget.current(), 0);
+        assert_eq!(slow.in_flight(), 0);
+        assert!(echo
+            .invoke(EdgeRequest::new(HttpMethod::Post), Some(budget))
+            .await
+            .is_ok());
+    }
+}
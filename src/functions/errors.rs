@@ -28,6 +28,17 @@ pub enum FunctionError {
     #[error("Runtime error: {0}")]
     RuntimeError(String),
 
+    /// The function panicked or returned an error instead of a response
+    #[error("Function crashed: {0}")]
+    Crashed(String),
+
+    #[error("Method {0} not allowed")]
+    MethodNotAllowed(String),
+
+    /// No invocation slot or edge function memory left to run the function
+    #[error("Resources exhausted: {0}")]
+    ResourcesExhausted(String),
+
     #[error("Invalid trigger: {0}")]
     InvalidTrigger(String),
 
@@ -62,6 +73,9 @@ impl FunctionError {
             FunctionError::Timeout(_) => 504,
             FunctionError::MemoryExceeded(_) => 500,
             FunctionError::RuntimeError(_) => 500,
+            FunctionError::Crashed(_) => 502,
+            FunctionError::MethodNotAllowed(_) => 405,
+            FunctionError::ResourcesExhausted(_) => 503,
             FunctionError::InvalidTrigger(_) => 400,
             FunctionError::InvalidCron(_) => 400,
             FunctionError::Internal(_) => 500,
@@ -81,5 +95,6 @@ mod tests {
     fn test_status_codes() {
         assert_eq!(FunctionError::NotFound("test".into()).status_code(), 404);
         assert_eq!(FunctionError::Timeout(1000).status_code(), 504);
+        assert_eq!(FunctionError::Crashed("boom".into()).status_code(), 502);
     }
 }
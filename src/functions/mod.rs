@@ -3,8 +3,11 @@
 //! Phase 12: Serverless Functions
 //!
 //! WebAssembly-based serverless functions with HTTP, database,
-//! and scheduled triggers.
+//! and scheduled triggers, native edge functions served at
+//! `/functions/v1/{name}`, and cron jobs run by the [`Scheduler`].
 
+pub mod builtin_jobs;
+pub mod edge;
 pub mod errors;
 pub mod function;
 pub mod invoker;
@@ -14,10 +17,11 @@ pub mod scheduler;
 pub mod store;
 pub mod trigger;
 
+pub use edge::{DeployedEdgeFunction, EdgeFunction, EdgeRequest, EdgeResponse, FunctionManifest};
 pub use errors::{FunctionError, FunctionResult};
 pub use function::{Function, FunctionConfig};
 pub use invoker::{InvocationContext, InvocationResult, Invoker};
 pub use registry::FunctionRegistry;
 pub use runtime::{ExecutionContext, ExecutionResult, RuntimeConfig, WasmRuntime, WasmtimeRuntime};
-pub use scheduler::Scheduler;
-pub use trigger::TriggerType;
+pub use scheduler::{JobDefinition, JobFunction, JobOutcome, JobRun, Scheduler, SchedulerConfig};
+pub use trigger::{HttpMethod, TriggerType};
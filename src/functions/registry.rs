@@ -1,8 +1,9 @@
 //! # Function Registry
 
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
+use super::edge::{DeployedEdgeFunction, EdgeFunction, FunctionManifest};
 use super::errors::{FunctionError, FunctionResult};
 use super::function::Function;
 use super::trigger::TriggerType;
@@ -18,6 +19,9 @@ pub struct FunctionRegistry {
 
     /// Function IDs by trigger identifier
     by_trigger: RwLock<HashMap<String, Vec<String>>>,
+
+    /// Edge functions by name
+    edge: RwLock<HashMap<String, DeployedEdgeFunction>>,
 }
 
 impl FunctionRegistry {
@@ -164,6 +168,48 @@ impl FunctionRegistry {
             .unwrap_or_default()
     }
 
+    /// Register a native edge function under its manifest's name
+    pub fn register_edge(
+        &self,
+        manifest: FunctionManifest,
+        handler: Arc<dyn EdgeFunction>,
+    ) -> FunctionResult<()> {
+        let mut edge = self
+            .edge
+            .write()
+            .map_err(|_| FunctionError::Internal("Lock poisoned".into()))?;
+        if edge.contains_key(&manifest.name) {
+            return Err(FunctionError::AlreadyExists(manifest.name));
+        }
+        edge.insert(
+            manifest.name.clone(),
+            DeployedEdgeFunction::new(manifest, handler),
+        );
+        Ok(())
+    }
+
+    /// Get edge function by name
+    pub fn get_edge(&self, name: &str) -> FunctionResult<DeployedEdgeFunction> {
+        let edge = self
+            .edge
+            .read()
+            .map_err(|_| FunctionError::Internal("Lock poisoned".into()))?;
+        edge.get(name)
+            .cloned()
+            .ok_or_else(|| FunctionError::NotFound(name.to_string()))
+    }
+
+    /// Unregister an edge function
+    pub fn unregister_edge(&self, name: &str) -> FunctionResult<()> {
+        let mut edge = self
+            .edge
+            .write()
+            .map_err(|_| FunctionError::Internal("Lock poisoned".into()))?;
+        edge.remove(name)
+            .map(|_| ())
+            .ok_or_else(|| FunctionError::NotFound(name.to_string()))
+    }
+
     /// Get function count
     pub fn len(&self) -> usize {
         self.by_id.read().map(|m| m.len()).unwrap_or(0)
@@ -232,6 +278,29 @@ mod tests {
         assert_eq!(registry.len(), 0);
     }
 
+    #[test]
+    fn test_register_edge() {
+        use super::super::edge::{EdgeRequest, EdgeResponse};
+
+        let registry = FunctionRegistry::new();
+        let handler = |_: EdgeRequest| Ok(EdgeResponse::new(200, "hi"));
+
+        registry
+            .register_edge(FunctionManifest::new("hi"), Arc::new(handler))
+            .unwrap();
+        assert!(matches!(
+            registry.register_edge(FunctionManifest::new("hi"), Arc::new(handler)),
+            Err(FunctionError::AlreadyExists(_))
+        ));
+        assert_eq!(registry.get_edge("hi").unwrap().manifest.name, "hi");
+
+        registry.unregister_edge("hi").unwrap();
+        assert!(matches!(
+            registry.get_edge("hi"),
+            Err(FunctionError::NotFound(_))
+        ));
+    }
+
     #[test]
     fn test_get_by_trigger() {
         let registry = FunctionRegistry::new();
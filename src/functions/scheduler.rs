@@ -1,15 +1,188 @@
 //! # Function Scheduler
+//!
+//! Runs functions on cron schedules. Jobs are defined in `[[scheduler.jobs]]`
+//! of the config or in `_system.schedules` (`<data_dir>/system/schedules.json`);
+//! a job defined in both takes its settings from the config. A job invokes
+//! the function registered with the scheduler under its function name, or
+//! else the edge function of that name.
+//!
+//! A job's next run is the first cron occurrence after its last run, so the
+//! schedule depends only on the cron expression and the clock; runs missed
+//! while the server was down are not caught up. Each run happens on the
+//! blocking thread pool under the job's timeout. While a run is still going,
+//! later occurrences of its job are skipped with a warning rather than run
+//! alongside it. Every run and skip is recorded in `_system.job_runs`
+//! (`<data_dir>/system/job_runs.json`), which keeps the newest runs of each
+//! job.
 
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
 use croner::Cron;
 use serde::{Deserialize, Serialize};
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+use super::edge::{panic_message, DeployedEdgeFunction, EdgeRequest};
 use super::errors::{FunctionError, FunctionResult};
-use super::store::{JobStore, MemJobStore};
+use super::registry::FunctionRegistry;
+use super::store::{
+    FileJobRunStore, FileJobStore, JobRunStore, JobStore, MemJobRunStore, MemJobStore,
+};
+use super::trigger::HttpMethod;
+use crate::observability::{JsonLogger, Logger};
+use crate::panic_handler::catch_contained;
+
+/// `_system.schedules`, relative to the data directory
+pub const SCHEDULES_PATH: &str = "system/schedules.json";
+
+/// `_system.job_runs`, relative to the data directory
+pub const JOB_RUNS_PATH: &str = "system/job_runs.json";
+
+/// Default time one run of a job may take
+pub const DEFAULT_JOB_TIMEOUT_MS: u64 = 60_000;
+
+/// Default runs of each job kept in `_system.job_runs`
+pub const DEFAULT_JOB_HISTORY_RUNS: usize = 100;
+
+/// How often the server checks for due jobs
+pub const DEFAULT_TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// First occurrence of `cron` after `after`
+fn next_occurrence(cron: &str, after: &DateTime<Utc>) -> FunctionResult<DateTime<Utc>> {
+    let cron_parser = Cron::new(cron).parse().map_err(|e| {
+        FunctionError::InvalidCron(format!("Invalid cron expression '{}': {}", cron, e))
+    })?;
+
+    cron_parser
+        .find_next_occurrence(after, false)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| FunctionError::InvalidCron(format!("Error calculating next run: {}", e)))
+}
+
+/// A job as defined in the config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobDefinition {
+    /// Job name, unique per scheduler
+    pub name: String,
+
+    /// Cron expression
+    pub cron: String,
+
+    /// Function to invoke
+    pub function: String,
+
+    /// Whether the job runs (default true)
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Time one run may take (default `scheduler.job_timeout_ms`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl JobDefinition {
+    /// An enabled job `name` invoking `function` on `cron`
+    pub fn new(
+        name: impl Into<String>,
+        cron: impl Into<String>,
+        function: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            cron: cron.into(),
+            function: function.into(),
+            enabled: true,
+            timeout_ms: None,
+        }
+    }
+
+    /// Check the name, function, cron expression and timeout
+    pub fn validate(&self) -> FunctionResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(FunctionError::InvalidTrigger("Job name is empty".into()));
+        }
+        if self.function.trim().is_empty() {
+            return Err(FunctionError::InvalidTrigger(format!(
+                "Job '{}' names no function",
+                self.name
+            )));
+        }
+        if self.timeout_ms == Some(0) {
+            return Err(FunctionError::InvalidTrigger(format!(
+                "Job '{}' timeout_ms must be > 0",
+                self.name
+            )));
+        }
+        next_occurrence(&self.cron, &Utc::now())?;
+        Ok(())
+    }
+}
+
+/// `[scheduler]` section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// Run scheduled jobs while serving (default true)
+    pub enabled: bool,
+
+    /// Time one run of a job may take unless the job sets its own
+    pub job_timeout_ms: u64,
+
+    /// Runs of each job kept in `_system.job_runs`
+    pub history_runs: usize,
+
+    /// `[[scheduler.jobs]]`
+    pub jobs: Vec<JobDefinition>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            job_timeout_ms: DEFAULT_JOB_TIMEOUT_MS,
+            history_runs: DEFAULT_JOB_HISTORY_RUNS,
+            jobs: Vec::new(),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// Check the limits and every job, and that job names are unique
+    pub fn validate(&self) -> FunctionResult<()> {
+        if self.job_timeout_ms == 0 {
+            return Err(FunctionError::InvalidTrigger(
+                "scheduler.job_timeout_ms must be > 0".into(),
+            ));
+        }
+        if self.history_runs == 0 {
+            return Err(FunctionError::InvalidTrigger(
+                "scheduler.history_runs must be > 0".into(),
+            ));
+        }
+
+        let mut names = HashSet::new();
+        for job in &self.jobs {
+            job.validate()?;
+            if !names.insert(job.name.as_str()) {
+                return Err(FunctionError::InvalidTrigger(format!(
+                    "Job '{}' is defined twice",
+                    job.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
 
 /// A scheduled job
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +190,10 @@ pub struct ScheduledJob {
     /// Job ID
     pub id: Uuid,
 
+    /// Job name (the function name for jobs scheduled without one)
+    #[serde(default)]
+    pub name: String,
+
     /// Function name
     pub function_name: String,
 
@@ -31,36 +208,61 @@ pub struct ScheduledJob {
 
     /// Whether the job is enabled
     pub enabled: bool,
+
+    /// Time one run may take (None: the scheduler's default)
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
 impl ScheduledJob {
     /// Create a new scheduled job
     pub fn new(function_name: String, cron: String) -> FunctionResult<Self> {
-        // Validate cron expression
-        let cron_parser = Cron::new(&cron).parse().map_err(|e| {
-            FunctionError::InvalidCron(format!("Invalid cron expression '{}': {}", cron, e))
-        })?;
+        let definition = JobDefinition::new(function_name.clone(), cron, function_name);
+        Self::from_definition(&definition, Utc::now())
+    }
 
-        let next_run = cron_parser
-            .find_next_occurrence(&Utc::now(), false)
-            .map(|dt| dt.with_timezone(&Utc))
-            .map_err(|e| {
-                FunctionError::InvalidCron(format!("Error calculating next run: {}", e))
-            })?;
+    /// Create a job from its definition, first due after `now`
+    pub fn from_definition(definition: &JobDefinition, now: DateTime<Utc>) -> FunctionResult<Self> {
+        let next_run = next_occurrence(&definition.cron, &now)?;
 
         Ok(Self {
             id: Uuid::new_v4(),
-            function_name,
-            cron,
+            name: definition.name.clone(),
+            function_name: definition.function.clone(),
+            cron: definition.cron.clone(),
             last_run: None,
             next_run: Some(next_run),
-            enabled: true,
+            enabled: definition.enabled,
+            timeout_ms: definition.timeout_ms,
         })
     }
 
+    /// Take the settings of `definition`, rescheduling from `now` if its
+    /// cron expression differs
+    fn apply(&mut self, definition: &JobDefinition, now: DateTime<Utc>) -> FunctionResult<()> {
+        if self.cron != definition.cron {
+            self.next_run = Some(next_occurrence(&definition.cron, &now)?);
+            self.cron = definition.cron.clone();
+        }
+        self.function_name = definition.function.clone();
+        self.enabled = definition.enabled;
+        self.timeout_ms = definition.timeout_ms;
+        Ok(())
+    }
+
+    /// Whether the job is enabled and its next run is at or before `now`
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.enabled && self.next_run.map(|t| t <= now).unwrap_or(false)
+    }
+
     /// Mark as run
     pub fn mark_run(&mut self) -> FunctionResult<()> {
-        self.last_run = Some(Utc::now());
+        self.mark_run_at(Utc::now())
+    }
+
+    /// Mark as run at `now`; the next run is the first occurrence after it
+    pub fn mark_run_at(&mut self, now: DateTime<Utc>) -> FunctionResult<()> {
+        self.last_run = Some(now);
 
         // Calculate next run
         let cron_parser = Cron::new(&self.cron).parse().map_err(|e| {
@@ -68,7 +270,7 @@ impl ScheduledJob {
         })?;
 
         self.next_run = cron_parser
-            .find_next_occurrence(&Utc::now(), false)
+            .find_next_occurrence(&now, false)
             .map(|dt| dt.with_timezone(&Utc))
             .ok();
 
@@ -76,17 +278,202 @@ impl ScheduledJob {
     }
 }
 
+/// How a run of a job ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobOutcome {
+    /// The function returned; `output` is what it reported
+    Succeeded { output: String },
+
+    /// The function was missing, panicked or returned an error
+    Failed { error: String },
+
+    /// The function did not return within the job's timeout
+    TimedOut { timeout_ms: u64 },
+
+    /// Not run: the job's previous run was still active
+    Skipped,
+}
+
+impl JobOutcome {
+    /// The outcome's status name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Succeeded { .. } => "succeeded",
+            Self::Failed { .. } => "failed",
+            Self::TimedOut { .. } => "timed_out",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+/// One run of a job, as recorded in `_system.job_runs`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobRun {
+    /// Run ID
+    pub id: Uuid,
+
+    /// Job name
+    pub job: String,
+
+    /// Function invoked
+    pub function: String,
+
+    /// When the run was due to start
+    pub started_at: DateTime<Utc>,
+
+    /// Time the run took
+    pub duration_ms: u64,
+
+    /// How the run ended
+    pub outcome: JobOutcome,
+}
+
+impl JobRun {
+    fn new(
+        job: &ScheduledJob,
+        started_at: DateTime<Utc>,
+        duration: Duration,
+        outcome: JobOutcome,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            job: job.name.clone(),
+            function: job.function_name.clone(),
+            started_at,
+            duration_ms: duration.as_millis() as u64,
+            outcome,
+        }
+    }
+}
+
+/// A function a job can invoke
+///
+/// Called on a blocking thread, so it may block; it should still return
+/// within the job's timeout. What it returns is recorded as the run's
+/// output. Any closure taking nothing and returning a
+/// [`FunctionResult<String>`] is a job function.
+pub trait JobFunction: Send + Sync {
+    /// Run once
+    fn run(&self) -> FunctionResult<String>;
+}
+
+impl<F> JobFunction for F
+where
+    F: Fn() -> FunctionResult<String> + Send + Sync,
+{
+    fn run(&self) -> FunctionResult<String> {
+        self()
+    }
+}
+
+/// An edge function run as a job: POSTed `{"job": <name>}` without an
+/// access token
+struct EdgeJob {
+    function: DeployedEdgeFunction,
+    job: String,
+    runtime: Handle,
+}
+
+impl JobFunction for EdgeJob {
+    fn run(&self) -> FunctionResult<String> {
+        let mut request = EdgeRequest::new(HttpMethod::Post);
+        request.body = serde_json::json!({ "job": self.job })
+            .to_string()
+            .into_bytes();
+
+        let response = self.runtime.block_on(self.function.invoke(request, None))?;
+        if response.status >= 400 {
+            return Err(FunctionError::RuntimeError(format!(
+                "Responded {}",
+                response.status
+            )));
+        }
+        Ok(format!("Responded {}", response.status))
+    }
+}
+
+/// Names of jobs with a run in progress
+type ActiveJobs = Arc<Mutex<HashSet<String>>>;
+
+/// A job's claim to be running, released on drop
+struct ActiveRun {
+    active: ActiveJobs,
+    job: String,
+}
+
+impl ActiveRun {
+    /// Claim `job`, unless a run of it is already active
+    fn claim(active: &ActiveJobs, job: &str) -> Option<Self> {
+        if !active.lock().unwrap().insert(job.to_string()) {
+            return None;
+        }
+        Some(Self {
+            active: Arc::clone(active),
+            job: job.to_string(),
+        })
+    }
+}
+
+impl Drop for ActiveRun {
+    fn drop(&mut self) {
+        self.active.lock().unwrap().remove(&self.job);
+    }
+}
+
+/// Record `run`, warning instead of failing if it cannot be written
+fn record_run(runs: &dyn JobRunStore, run: &JobRun, keep: usize, logger: &dyn Logger) {
+    if let Err(e) = runs.record(run, keep) {
+        logger.warn(
+            "JOB_RUN_RECORD_FAILED",
+            &[("job", &run.job), ("error", &e.to_string())],
+        );
+    }
+}
+
 /// Job scheduler
-#[derive(Debug)]
 pub struct Scheduler {
     /// Jobs by ID
     jobs: RwLock<HashMap<Uuid, ScheduledJob>>,
 
-    /// Jobs by function name
-    by_function: RwLock<HashMap<String, Uuid>>,
+    /// Jobs by name
+    by_name: RwLock<HashMap<String, Uuid>>,
 
     /// Persistent store
     store: Arc<dyn JobStore>,
+
+    /// Run history
+    runs: Arc<dyn JobRunStore>,
+
+    /// Functions jobs can invoke, by name
+    functions: RwLock<HashMap<String, Arc<dyn JobFunction>>>,
+
+    /// Edge functions jobs can invoke when no function is registered here
+    registry: Option<Arc<FunctionRegistry>>,
+
+    /// Jobs with a run in progress
+    active: ActiveJobs,
+
+    /// Time one run may take unless its job sets its own
+    job_timeout_ms: u64,
+
+    /// Runs of each job kept in the run history
+    history_runs: usize,
+
+    /// Where failures to reschedule, skip or record runs are logged
+    logger: Arc<dyn Logger>,
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("jobs", &self.jobs)
+            .field("store", &self.store)
+            .field("runs", &self.runs)
+            .field("job_timeout_ms", &self.job_timeout_ms)
+            .field("history_runs", &self.history_runs)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Default for Scheduler {
@@ -104,24 +491,74 @@ impl Scheduler {
         });
 
         let mut jobs_map = HashMap::new();
-        let mut by_function = HashMap::new();
+        let mut by_name = HashMap::new();
 
-        for job in jobs {
-            by_function.insert(job.function_name.clone(), job.id);
+        for mut job in jobs {
+            if job.name.is_empty() {
+                job.name = job.function_name.clone();
+            }
+            by_name.insert(job.name.clone(), job.id);
             jobs_map.insert(job.id, job);
         }
 
         Self {
             jobs: RwLock::new(jobs_map),
-            by_function: RwLock::new(by_function),
+            by_name: RwLock::new(by_name),
             store,
+            runs: Arc::new(MemJobRunStore::default()),
+            functions: RwLock::new(HashMap::new()),
+            registry: None,
+            active: Arc::new(Mutex::new(HashSet::new())),
+            job_timeout_ms: DEFAULT_JOB_TIMEOUT_MS,
+            history_runs: DEFAULT_JOB_HISTORY_RUNS,
+            logger: JsonLogger::shared(),
         }
     }
 
+    /// Open the scheduler of the server in `data_dir`: jobs in
+    /// `_system.schedules` plus those defined in `config`, with their runs
+    /// recorded in `_system.job_runs`
+    pub fn open(data_dir: &Path, config: &SchedulerConfig) -> FunctionResult<Self> {
+        let scheduler = Self::new(Arc::new(FileJobStore::new(data_dir.join(SCHEDULES_PATH))))
+            .with_run_store(Arc::new(FileJobRunStore::new(data_dir.join(JOB_RUNS_PATH))))
+            .with_config(config);
+
+        let now = Utc::now();
+        for definition in &config.jobs {
+            scheduler.define(definition, now)?;
+        }
+        Ok(scheduler)
+    }
+
+    /// Record runs in `runs`
+    pub fn with_run_store(mut self, runs: Arc<dyn JobRunStore>) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// Take the default timeout and history length of `config`
+    pub fn with_config(mut self, config: &SchedulerConfig) -> Self {
+        self.job_timeout_ms = config.job_timeout_ms;
+        self.history_runs = config.history_runs;
+        self
+    }
+
+    /// Log skipped runs and scheduling failures to `logger`
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Let jobs invoke the edge functions of `registry`
+    pub fn with_registry(mut self, registry: Arc<FunctionRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
     /// Add a scheduled job
     pub fn schedule(&self, job: ScheduledJob) -> FunctionResult<Uuid> {
         let id = job.id;
-        let function_name = job.function_name.clone();
+        let name = job.name.clone();
 
         // Persist first
         self.store.save(&job)?;
@@ -135,57 +572,138 @@ impl Scheduler {
         }
 
         {
-            let mut by_function = self
-                .by_function
+            let mut by_name = self
+                .by_name
                 .write()
                 .map_err(|_| FunctionError::Internal("Lock poisoned".into()))?;
-            by_function.insert(function_name, id);
+            by_name.insert(name, id);
         }
 
         Ok(id)
     }
 
+    /// Schedule the job `definition` describes, or update the job of that
+    /// name to match it
+    pub fn define(&self, definition: &JobDefinition, now: DateTime<Utc>) -> FunctionResult<Uuid> {
+        definition.validate()?;
+
+        let existing = self
+            .by_name
+            .read()
+            .map_err(|_| FunctionError::Internal("Lock poisoned".into()))?
+            .get(&definition.name)
+            .copied();
+        let Some(id) = existing else {
+            return self.schedule(ScheduledJob::from_definition(definition, now)?);
+        };
+
+        let job_copy = {
+            let mut jobs = self
+                .jobs
+                .write()
+                .map_err(|_| FunctionError::Internal("Lock poisoned".into()))?;
+            let job = jobs
+                .get_mut(&id)
+                .ok_or_else(|| FunctionError::NotFound(definition.name.clone()))?;
+            job.apply(definition, now)?;
+            job.clone()
+        };
+        self.store.save(&job_copy)?;
+
+        Ok(id)
+    }
+
+    /// Let jobs invoke `function` as `name`
+    pub fn register_function(&self, name: impl Into<String>, function: Arc<dyn JobFunction>) {
+        self.functions
+            .write()
+            .unwrap()
+            .insert(name.into(), function);
+    }
+
+    /// Register a built-in job: `function` is registered as `name`, and a
+    /// job `name` invoking it on `cron` is scheduled unless one exists
+    /// already, so the config or `_system.schedules` may reschedule or
+    /// disable it
+    pub fn register_builtin(
+        &self,
+        name: &str,
+        cron: &str,
+        function: Arc<dyn JobFunction>,
+    ) -> FunctionResult<()> {
+        self.register_function(name, function);
+        if self.job(name).is_none() {
+            self.define(&JobDefinition::new(name, cron, name), Utc::now())?;
+        }
+        Ok(())
+    }
+
     /// Cancel a job
     pub fn cancel(&self, job_id: Uuid) -> FunctionResult<()> {
         // Persist removal
         self.store.delete(&job_id)?;
 
-        let function_name = {
+        let name = {
             let mut jobs = self
                 .jobs
                 .write()
                 .map_err(|_| FunctionError::Internal("Lock poisoned".into()))?;
-            jobs.remove(&job_id).map(|j| j.function_name)
+            jobs.remove(&job_id).map(|j| j.name)
         };
 
-        if let Some(name) = function_name {
-            let mut by_function = self
-                .by_function
+        if let Some(name) = name {
+            let mut by_name = self
+                .by_name
                 .write()
                 .map_err(|_| FunctionError::Internal("Lock poisoned".into()))?;
-            by_function.remove(&name);
+            by_name.remove(&name);
         }
 
         Ok(())
     }
 
+    /// Get the job named `name`
+    pub fn job(&self, name: &str) -> Option<ScheduledJob> {
+        let id = *self.by_name.read().ok()?.get(name)?;
+        self.jobs.read().ok()?.get(&id).cloned()
+    }
+
+    /// Get every job, in name order
+    pub fn jobs(&self) -> Vec<ScheduledJob> {
+        let mut jobs: Vec<ScheduledJob> = self
+            .jobs
+            .read()
+            .map(|jobs| jobs.values().cloned().collect())
+            .unwrap_or_default();
+        jobs.sort_by(|a, b| a.name.cmp(&b.name));
+        jobs
+    }
+
+    /// Get the recorded runs, oldest first
+    pub fn runs(&self) -> FunctionResult<Vec<JobRun>> {
+        self.runs.load()
+    }
+
     /// Get jobs that are due to run
     pub fn get_due_jobs(&self) -> Vec<ScheduledJob> {
-        let now = Utc::now();
+        self.get_due_jobs_at(Utc::now())
+    }
 
+    /// Get jobs that are due to run at `now`
+    pub fn get_due_jobs_at(&self, now: DateTime<Utc>) -> Vec<ScheduledJob> {
         self.jobs
             .read()
-            .map(|jobs| {
-                jobs.values()
-                    .filter(|j| j.enabled && j.next_run.map(|t| t <= now).unwrap_or(false))
-                    .cloned()
-                    .collect()
-            })
+            .map(|jobs| jobs.values().filter(|j| j.is_due(now)).cloned().collect())
             .unwrap_or_default()
     }
 
     /// Mark a job as run
     pub fn mark_run(&self, job_id: Uuid) -> FunctionResult<()> {
+        self.mark_run_at(job_id, Utc::now())
+    }
+
+    /// Mark a job as run at `now`
+    pub fn mark_run_at(&self, job_id: Uuid, now: DateTime<Utc>) -> FunctionResult<()> {
         let job_copy = {
             let mut jobs = self
                 .jobs
@@ -193,7 +711,7 @@ impl Scheduler {
                 .map_err(|_| FunctionError::Internal("Lock poisoned".into()))?;
 
             if let Some(job) = jobs.get_mut(&job_id) {
-                job.mark_run()?;
+                job.mark_run_at(now)?;
                 Some(job.clone())
             } else {
                 None
@@ -207,6 +725,103 @@ impl Scheduler {
         Ok(())
     }
 
+    /// Start every job due at `now` on the current tokio runtime
+    ///
+    /// Each due job is rescheduled from `now`. A job whose previous run is
+    /// still active is skipped; the others start, and their handles resolve
+    /// to the recorded run once it ends.
+    pub fn run_due_at(&self, now: DateTime<Utc>) -> Vec<JoinHandle<JobRun>> {
+        let mut started = Vec::new();
+        for job in self.get_due_jobs_at(now) {
+            if let Err(e) = self.mark_run_at(job.id, now) {
+                self.logger.warn(
+                    "JOB_RESCHEDULE_FAILED",
+                    &[("job", &job.name), ("error", &e.to_string())],
+                );
+            }
+            if let Some(handle) = self.start(job, now) {
+                started.push(handle);
+            }
+        }
+        started
+    }
+
+    /// Check for due jobs every `interval` for as long as the runtime runs
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                ticks.tick().await;
+                self.run_due_at(Utc::now());
+            }
+        })
+    }
+
+    /// Start one run of `job`, or record it skipped if one is active
+    fn start(&self, job: ScheduledJob, now: DateTime<Utc>) -> Option<JoinHandle<JobRun>> {
+        let Some(active) = ActiveRun::claim(&self.active, &job.name) else {
+            self.logger.warn(
+                "JOB_RUN_SKIPPED",
+                &[("job", &job.name), ("reason", "previous run still active")],
+            );
+            let run = JobRun::new(&job, now, Duration::ZERO, JobOutcome::Skipped);
+            record_run(&*self.runs, &run, self.history_runs, &*self.logger);
+            return None;
+        };
+
+        let function = self.resolve(&job);
+        let timeout_ms = job.timeout_ms.unwrap_or(self.job_timeout_ms);
+        let runs = Arc::clone(&self.runs);
+        let keep = self.history_runs;
+        let logger = Arc::clone(&self.logger);
+
+        Some(tokio::spawn(async move {
+            let started = Instant::now();
+            let outcome = match function {
+                None => JobOutcome::Failed {
+                    error: FunctionError::NotFound(job.function_name.clone()).to_string(),
+                },
+                Some(function) => {
+                    let task = tokio::task::spawn_blocking(move || {
+                        // Held until the function returns, even after a timeout
+                        let _active = active;
+                        catch_contained(|| function.run())
+                    });
+                    match tokio::time::timeout(Duration::from_millis(timeout_ms), task).await {
+                        Err(_) => JobOutcome::TimedOut { timeout_ms },
+                        Ok(Err(e)) => JobOutcome::Failed {
+                            error: e.to_string(),
+                        },
+                        Ok(Ok(Err(panic))) => JobOutcome::Failed {
+                            error: panic_message(&*panic),
+                        },
+                        Ok(Ok(Ok(Err(e)))) => JobOutcome::Failed {
+                            error: e.to_string(),
+                        },
+                        Ok(Ok(Ok(Ok(output)))) => JobOutcome::Succeeded { output },
+                    }
+                }
+            };
+
+            let run = JobRun::new(&job, now, started.elapsed(), outcome);
+            record_run(&*runs, &run, keep, &*logger);
+            run
+        }))
+    }
+
+    /// The function `job` invokes: one registered here, else an edge function
+    fn resolve(&self, job: &ScheduledJob) -> Option<Arc<dyn JobFunction>> {
+        if let Some(function) = self.functions.read().ok()?.get(&job.function_name) {
+            return Some(Arc::clone(function));
+        }
+        let function = self.registry.as_ref()?.get_edge(&job.function_name).ok()?;
+        Some(Arc::new(EdgeJob {
+            function,
+            job: job.name.clone(),
+            runtime: Handle::current(),
+        }))
+    }
+
     /// Get job count
     pub fn len(&self) -> usize {
         self.jobs.read().map(|j| j.len()).unwrap_or(0)
@@ -221,6 +836,22 @@ impl Scheduler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::functions::edge::{EdgeResponse, FunctionManifest};
+    use crate::observability::VecLogger;
+    use chrono::TimeZone;
+
+    /// A fixed point on the fake clock
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, hour, minute, second)
+            .unwrap()
+    }
+
+    fn scheduler() -> (Scheduler, Arc<MemJobRunStore>) {
+        let runs = Arc::new(MemJobRunStore::new());
+        let scheduler =
+            Scheduler::new(Arc::new(MemJobStore::default())).with_run_store(runs.clone());
+        (scheduler, runs)
+    }
 
     #[test]
     fn test_scheduled_job_creation() {
@@ -263,4 +894,232 @@ mod tests {
         let due = scheduler.get_due_jobs();
         assert_eq!(due.len(), 1);
     }
+
+    #[test]
+    fn test_schedule_follows_fake_clock() {
+        let (scheduler, _) = scheduler();
+        let definition = JobDefinition::new("reports", "*/15 * * * *", "build_reports");
+        let id = scheduler.define(&definition, at(9, 7, 30)).unwrap();
+
+        let job = scheduler.job("reports").unwrap();
+        assert_eq!(job.next_run, Some(at(9, 15, 0)));
+        assert!(scheduler.get_due_jobs_at(at(9, 14, 59)).is_empty());
+        assert_eq!(scheduler.get_due_jobs_at(at(9, 15, 0)).len(), 1);
+
+        // Run late: the occurrences missed meanwhile are not caught up
+        scheduler.mark_run_at(id, at(9, 47, 2)).unwrap();
+        let job = scheduler.job("reports").unwrap();
+        assert_eq!(job.last_run, Some(at(9, 47, 2)));
+        assert_eq!(job.next_run, Some(at(10, 0, 0)));
+
+        // Redefining with the same cron keeps the schedule; a new cron
+        // reschedules from the given time
+        let mut disabled = definition.clone();
+        disabled.enabled = false;
+        scheduler.define(&disabled, at(9, 50, 0)).unwrap();
+        assert_eq!(
+            scheduler.job("reports").unwrap().next_run,
+            Some(at(10, 0, 0))
+        );
+        assert!(scheduler.get_due_jobs_at(at(10, 0, 0)).is_empty());
+
+        let hourly = JobDefinition::new("reports", "30 * * * *", "build_reports");
+        scheduler.define(&hourly, at(9, 50, 0)).unwrap();
+        assert_eq!(scheduler.len(), 1);
+        assert_eq!(
+            scheduler.job("reports").unwrap().next_run,
+            Some(at(10, 30, 0))
+        );
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = SchedulerConfig::default();
+        config.jobs.push(JobDefinition::new("a", "0 * * * *", "f"));
+        assert!(config.validate().is_ok());
+
+        config.jobs.push(JobDefinition::new("a", "5 * * * *", "g"));
+        assert!(config.validate().is_err());
+
+        config.jobs[1] = JobDefinition::new("b", "not cron", "g");
+        assert!(matches!(
+            config.validate(),
+            Err(FunctionError::InvalidCron(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_run_records_outcome() {
+        let (scheduler, runs) = scheduler();
+        scheduler.register_function("ok", Arc::new(|| Ok("cleaned 3".to_string())));
+        scheduler.register_function(
+            "boom",
+            Arc::new(|| -> FunctionResult<String> { panic!("boom") }),
+        );
+        for (name, function) in [("ok", "ok"), ("boom", "boom"), ("missing", "missing")] {
+            let definition = JobDefinition::new(name, "0 * * * *", function);
+            scheduler.define(&definition, at(9, 0, 0)).unwrap();
+        }
+
+        let handles = scheduler.run_due_at(at(10, 0, 0));
+        assert_eq!(handles.len(), 3);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let mut recorded = runs.load().unwrap();
+        recorded.sort_by(|a, b| a.job.cmp(&b.job));
+        assert_eq!(recorded.len(), 3);
+        assert!(
+            matches!(&recorded[0].outcome, JobOutcome::Failed { error } if error.contains("boom"))
+        );
+        assert!(
+            matches!(&recorded[1].outcome, JobOutcome::Failed { error } if error.contains("missing"))
+        );
+        assert_eq!(
+            recorded[2].outcome,
+            JobOutcome::Succeeded {
+                output: "cleaned 3".into()
+            }
+        );
+        assert!(recorded.iter().all(|r| r.started_at == at(10, 0, 0)));
+        assert_eq!(scheduler.job("ok").unwrap().next_run, Some(at(11, 0, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_run_is_skipped() {
+        let logger = Arc::new(VecLogger::new());
+        let (scheduler, runs) = scheduler();
+        let scheduler = scheduler.with_logger(logger.clone());
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let wait = Mutex::new(wait);
+        scheduler.register_function(
+            "slow",
+            Arc::new(move || {
+                let _ = wait.lock().unwrap().recv();
+                Ok("done".to_string())
+            }),
+        );
+        let mut definition = JobDefinition::new("slow", "* * * * *", "slow");
+        definition.timeout_ms = Some(50);
+        scheduler.define(&definition, at(9, 0, 0)).unwrap();
+
+        // The first run times out but its function keeps running
+        let first = scheduler.run_due_at(at(9, 1, 0));
+        assert_eq!(first.len(), 1);
+        for handle in first {
+            let run = handle.await.unwrap();
+            assert_eq!(run.outcome, JobOutcome::TimedOut { timeout_ms: 50 });
+        }
+
+        // So the next occurrence is skipped, and still advances the schedule
+        assert!(scheduler.run_due_at(at(9, 2, 0)).is_empty());
+        let skipped = runs.load().unwrap();
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[1].outcome, JobOutcome::Skipped);
+        assert_eq!(skipped[1].started_at, at(9, 2, 0));
+        assert_eq!(logger.events(), ["JOB_RUN_SKIPPED"]);
+        assert_eq!(logger.records()[0].field("job"), Some("slow"));
+        assert_eq!(scheduler.job("slow").unwrap().next_run, Some(at(9, 3, 0)));
+
+        // Once the function returns the job runs again
+        release.send(()).unwrap();
+        drop(release);
+        for _ in 0..100 {
+            if scheduler.active.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let third = scheduler.run_due_at(at(9, 3, 0));
+        assert_eq!(third.len(), 1);
+        for handle in third {
+            let run = handle.await.unwrap();
+            assert_eq!(
+                run.outcome,
+                JobOutcome::Succeeded {
+                    output: "done".into()
+                }
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_keeps_newest_runs() {
+        let (scheduler, runs) = scheduler();
+        let scheduler = scheduler.with_config(&SchedulerConfig {
+            history_runs: 2,
+            ..SchedulerConfig::default()
+        });
+        scheduler.register_function("tick", Arc::new(|| Ok(String::new())));
+        scheduler
+            .define(
+                &JobDefinition::new("tick", "* * * * *", "tick"),
+                at(9, 0, 0),
+            )
+            .unwrap();
+
+        for minute in 1..=3 {
+            for handle in scheduler.run_due_at(at(9, minute, 0)) {
+                handle.await.unwrap();
+            }
+        }
+
+        let kept: Vec<_> = runs.load().unwrap().iter().map(|r| r.started_at).collect();
+        assert_eq!(kept, vec![at(9, 2, 0), at(9, 3, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_job_invokes_edge_function() {
+        let registry = Arc::new(FunctionRegistry::new());
+        registry
+            .register_edge(
+                FunctionManifest::new("nightly"),
+                Arc::new(|request: EdgeRequest| {
+                    let body = request.json()?;
+                    assert_eq!(body["job"], "nightly_job");
+                    Ok(EdgeResponse::new(204, Vec::new()))
+                }),
+            )
+            .unwrap();
+        let (scheduler, _) = scheduler();
+        let scheduler = scheduler.with_registry(registry);
+        scheduler
+            .define(
+                &JobDefinition::new("nightly_job", "0 0 * * *", "nightly"),
+                at(9, 0, 0),
+            )
+            .unwrap();
+
+        let handles = scheduler.run_due_at(at(0, 0, 0) + chrono::Duration::days(1));
+        assert_eq!(handles.len(), 1);
+        for handle in handles {
+            let run = handle.await.unwrap();
+            assert_eq!(
+                run.outcome,
+                JobOutcome::Succeeded {
+                    output: "Responded 204".into()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_builtin_keeps_existing_schedule() {
+        let (scheduler, _) = scheduler();
+        let mut disabled = JobDefinition::new("session_expiry", "*/5 * * * *", "session_expiry");
+        disabled.enabled = false;
+        scheduler.define(&disabled, at(9, 0, 0)).unwrap();
+
+        scheduler
+            .register_builtin(
+                "session_expiry",
+                "0 * * * *",
+                Arc::new(|| Ok(String::new())),
+            )
+            .unwrap();
+        let job = scheduler.job("session_expiry").unwrap();
+        assert!(!job.enabled);
+        assert_eq!(job.cron, "*/5 * * * *");
+    }
 }
@@ -1,13 +1,13 @@
 //! # Job Store
 //!
-//! Durable storage for scheduled jobs.
+//! Durable storage for scheduled jobs and the history of their runs.
 
 use std::fs;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 use super::errors::{FunctionError, FunctionResult};
-use super::scheduler::ScheduledJob;
+use super::scheduler::{JobRun, ScheduledJob};
 
 /// Trait for durable job storage
 pub trait JobStore: Send + Sync + std::fmt::Debug {
@@ -131,3 +131,102 @@ impl JobStore for MemJobStore {
         Ok(())
     }
 }
+
+/// Trait for durable job run history
+pub trait JobRunStore: Send + Sync + std::fmt::Debug {
+    /// Load all recorded runs, oldest first
+    fn load(&self) -> FunctionResult<Vec<JobRun>>;
+
+    /// Record a run, keeping only the newest `keep` runs of its job
+    fn record(&self, run: &JobRun, keep: usize) -> FunctionResult<()>;
+}
+
+/// Append `run` to `runs` and drop the oldest runs of its job beyond `keep`
+fn append_run(runs: &mut Vec<JobRun>, run: &JobRun, keep: usize) {
+    runs.push(run.clone());
+    let mut excess = runs
+        .iter()
+        .filter(|r| r.job == run.job)
+        .count()
+        .saturating_sub(keep);
+    runs.retain(|r| {
+        if excess > 0 && r.job == run.job {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// JSON file-based job run history
+#[derive(Debug)]
+pub struct FileJobRunStore {
+    path: PathBuf,
+}
+
+impl FileJobRunStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl JobRunStore for FileJobRunStore {
+    fn load(&self) -> FunctionResult<Vec<JobRun>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| FunctionError::Internal(format!("Failed to read job runs: {}", e)))?;
+
+        if content.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        serde_json::from_str(&content)
+            .map_err(|e| FunctionError::Internal(format!("Failed to parse job runs: {}", e)))
+    }
+
+    fn record(&self, run: &JobRun, keep: usize) -> FunctionResult<()> {
+        let mut runs = self.load()?;
+        append_run(&mut runs, run, keep);
+
+        let content = serde_json::to_string_pretty(&runs)
+            .map_err(|e| FunctionError::Internal(format!("Failed to serialize job runs: {}", e)))?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                FunctionError::Internal(format!("Failed to create job run directory: {}", e))
+            })?;
+        }
+
+        fs::write(&self.path, content)
+            .map_err(|e| FunctionError::Internal(format!("Failed to write job runs: {}", e)))
+    }
+}
+
+/// In-memory job run history for testing
+#[derive(Debug, Default)]
+pub struct MemJobRunStore {
+    runs: std::sync::RwLock<Vec<JobRun>>,
+}
+
+impl MemJobRunStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl JobRunStore for MemJobRunStore {
+    fn load(&self) -> FunctionResult<Vec<JobRun>> {
+        Ok(self.runs.read().unwrap().clone())
+    }
+
+    fn record(&self, run: &JobRun, keep: usize) -> FunctionResult<()> {
+        append_run(&mut self.runs.write().unwrap(), run, keep);
+        Ok(())
+    }
+}
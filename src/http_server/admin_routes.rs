@@ -0,0 +1,689 @@
+//! Admin HTTP Routes
+//!
+//! Operator endpoints under `/admin/v1`:
+//! - `POST /admin/v1/config/reload` - re-read the config file and apply
+//!   its runtime-tunable fields
+//! - `POST /admin/v1/collections/{name}/export` - the collection as JSON
+//!   Lines or CSV; the JSON body holds `schema_version`, `format` and an
+//!   optional query `filter`
+//! - `POST /admin/v1/collections/{name}/import` - import the request body;
+//!   `schema_version`, `format`, `mode`, `batch_size`, `abort_on_error`
+//!   and `map` (`HEADER=FIELD,...`) are query parameters
+//! - `GET /admin/v1/collections` - per-collection document counts, bytes,
+//!   indexes, last write time and schema version
+//! - `GET /admin/v1/collections/{name}` - one collection with its field
+//!   statistics
+//! - `GET /admin/v1/system` - resources, WAL size, last checkpoint and
+//!   backup, replication role and lag, uptime
+//!
+//! The reload endpoint answers 200 when every change was applied, 409 when
+//! some changes need a restart (the others are still applied), 400 when
+//! the file is unreadable or invalid, and 503 when the server was started
+//! without a config file to reload.
+//!
+//! The import endpoint answers 200 with the row counts and the rejected
+//! rows, 422 (with the rejected rows) when `abort_on_error` stopped it,
+//! and 400 for unusable options. Both transfer endpoints answer 503 when
+//! the server takes no writes (a replica).
+//!
+//! The statistics endpoints need a bearer JWT with the `admin` or
+//! `service_role` role (401 without a valid token, 403 for another role),
+//! answer 404 for an unknown collection, and 503 when no statistics are
+//! attached (a replica). They read maintained counters and never scan.
+
+use std::io::Cursor;
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::auth_routes::AuthState;
+use crate::auth::errors::AuthError;
+use crate::config_reload::ConfigReload;
+use crate::observability::DatabaseStats;
+use crate::transfer::{
+    DataTransfer, ExportOptions, ImportMode, ImportOptions, RejectedRow, TransferError,
+    TransferFormat,
+};
+
+/// Admin state shared across handlers
+#[derive(Default)]
+pub struct AdminState {
+    reload: OnceLock<Arc<dyn ConfigReload>>,
+    transfer: OnceLock<Arc<dyn DataTransfer>>,
+    stats: OnceLock<Arc<dyn DatabaseStats>>,
+    auth: OnceLock<Arc<AuthState>>,
+}
+
+impl AdminState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve config reloads from `reload`; only the first call has effect
+    pub fn set_config_reload(&self, reload: Arc<dyn ConfigReload>) {
+        let _ = self.reload.set(reload);
+    }
+
+    /// Serve imports and exports from `transfer`; only the first call has
+    /// effect
+    pub fn set_data_transfer(&self, transfer: Arc<dyn DataTransfer>) {
+        let _ = self.transfer.set(transfer);
+    }
+
+    /// Serve the statistics endpoints from `stats`; only the first call
+    /// has effect
+    pub fn set_database_stats(&self, stats: Arc<dyn DatabaseStats>) {
+        let _ = self.stats.set(stats);
+    }
+
+    /// Validate the statistics endpoints' tokens with `auth`; only the
+    /// first call has effect
+    pub fn set_auth(&self, auth: Arc<AuthState>) {
+        let _ = self.auth.set(auth);
+    }
+
+    /// The statistics source, once the bearer token in `headers` is shown
+    /// to carry the admin or service_role role
+    fn authorized_stats(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<Arc<dyn DatabaseStats>, (StatusCode, String)> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.strip_prefix("Bearer "))
+            .ok_or((
+                StatusCode::UNAUTHORIZED,
+                "Missing authorization header".to_string(),
+            ))?;
+        let auth = self.auth.get().ok_or((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Authentication is not available".to_string(),
+        ))?;
+        auth.service
+            .validate_operator_token(token)
+            .map_err(|e| match e {
+                AuthError::Unauthorized => (
+                    StatusCode::FORBIDDEN,
+                    "Requires the admin or service_role role".to_string(),
+                ),
+                e => (StatusCode::UNAUTHORIZED, e.to_string()),
+            })?;
+        self.stats.get().cloned().ok_or((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Statistics are not available".to_string(),
+        ))
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(json!({ "error": message, "code": status.as_u16() })),
+    )
+        .into_response()
+}
+
+/// POST /admin/v1/config/reload - Apply the config file without restarting
+async fn reload_config(State(state): State<Arc<AdminState>>) -> (StatusCode, Json<Value>) {
+    let Some(reload) = state.reload.get().cloned() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "Config reload is not available", "code": 503 })),
+        );
+    };
+
+    match tokio::task::spawn_blocking(move || reload.reload()).await {
+        Ok(Ok(report)) => {
+            let status = if report.refused.is_empty() {
+                StatusCode::OK
+            } else {
+                StatusCode::CONFLICT
+            };
+            (status, Json(json!(report)))
+        }
+        Ok(Err(e)) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": e.message, "errors": e.errors, "code": 400 })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("Config reload failed: {}", e), "code": 500 })),
+        ),
+    }
+}
+
+/// Export request body
+#[derive(Debug, Deserialize)]
+pub struct ExportBody {
+    pub schema_version: String,
+    pub format: TransferFormat,
+    #[serde(default)]
+    pub filter: Option<Value>,
+}
+
+/// Import query parameters
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    pub schema_version: String,
+    pub format: TransferFormat,
+    #[serde(default)]
+    pub mode: ImportMode,
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+    #[serde(default)]
+    pub abort_on_error: bool,
+    /// `HEADER=FIELD` pairs, comma-separated
+    #[serde(default)]
+    pub map: Option<String>,
+}
+
+fn transfer_unavailable() -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(json!({ "error": "Import and export are not available", "code": 503 })),
+    )
+        .into_response()
+}
+
+fn transfer_failed(e: &TransferError, rejects: &[RejectedRow]) -> Response {
+    let status = match e {
+        TransferError::InvalidOptions { .. } | TransferError::UnknownColumn { .. } => {
+            StatusCode::BAD_REQUEST
+        }
+        TransferError::RowRejected { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        TransferError::Io { .. } | TransferError::Api { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = json!({
+        "error": e.to_string(),
+        "code": status.as_u16(),
+        "rejects": rejects,
+    });
+    (status, Json(body)).into_response()
+}
+
+/// POST /admin/v1/collections/{name}/export - Download a collection
+async fn export_collection(
+    State(state): State<Arc<AdminState>>,
+    Path(name): Path<String>,
+    Json(body): Json<ExportBody>,
+) -> Response {
+    let Some(transfer) = state.transfer.get().cloned() else {
+        return transfer_unavailable();
+    };
+    let mut options = ExportOptions::new(name, body.schema_version, body.format);
+    options.filter = body.filter;
+
+    let exported = tokio::task::spawn_blocking(move || {
+        let mut out = Vec::new();
+        transfer
+            .export(&options, &mut out)
+            .map(|_| (options.format, out))
+    })
+    .await;
+    match exported {
+        Ok(Ok((format, out))) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, format.content_type())],
+            out,
+        )
+            .into_response(),
+        Ok(Err(e)) => transfer_failed(&e, &[]),
+        Err(e) => transfer_failed(
+            &TransferError::Io {
+                message: e.to_string(),
+            },
+            &[],
+        ),
+    }
+}
+
+/// POST /admin/v1/collections/{name}/import - Import the request body
+async fn import_collection(
+    State(state): State<Arc<AdminState>>,
+    Path(name): Path<String>,
+    Query(query): Query<ImportQuery>,
+    body: Bytes,
+) -> Response {
+    let Some(transfer) = state.transfer.get().cloned() else {
+        return transfer_unavailable();
+    };
+    let mut options = ImportOptions::new(name, query.schema_version, query.format)
+        .with_mode(query.mode)
+        .with_abort_on_error(query.abort_on_error);
+    if let Some(batch_size) = query.batch_size {
+        options = options.with_batch_size(batch_size);
+    }
+    for entry in query.map.iter().flat_map(|map| map.split(',')) {
+        let Some((header, field)) = entry.split_once('=') else {
+            let reason = format!("Invalid map entry '{}': expected HEADER=FIELD", entry);
+            return transfer_failed(&TransferError::InvalidOptions { reason }, &[]);
+        };
+        options = options.with_column(header, field);
+    }
+
+    let imported = tokio::task::spawn_blocking(move || {
+        let mut rejects = Vec::new();
+        let report = transfer.import(&options, &mut Cursor::new(body), &mut rejects);
+        let rejects: Vec<RejectedRow> = rejects
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| serde_json::from_slice(line).ok())
+            .collect();
+        (report, rejects)
+    })
+    .await;
+    match imported {
+        Ok((Ok(report), rejects)) => {
+            let mut body = json!(report);
+            body["rejects"] = json!(rejects);
+            (StatusCode::OK, Json(body)).into_response()
+        }
+        Ok((Err(e), rejects)) => transfer_failed(&e, &rejects),
+        Err(e) => transfer_failed(
+            &TransferError::Io {
+                message: e.to_string(),
+            },
+            &[],
+        ),
+    }
+}
+
+/// GET /admin/v1/collections - Overview of every collection
+async fn list_collections(State(state): State<Arc<AdminState>>, headers: HeaderMap) -> Response {
+    match state.authorized_stats(&headers) {
+        Ok(stats) => Json(json!({ "collections": stats.collections() })).into_response(),
+        Err((status, message)) => error_response(status, &message),
+    }
+}
+
+/// GET /admin/v1/collections/{name} - One collection with field statistics
+async fn get_collection(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Response {
+    let stats = match state.authorized_stats(&headers) {
+        Ok(stats) => stats,
+        Err((status, message)) => return error_response(status, &message),
+    };
+    match stats.collection(&name) {
+        Some(detail) => Json(json!(detail)).into_response(),
+        None => error_response(
+            StatusCode::NOT_FOUND,
+            &format!("Unknown collection '{}'", name),
+        ),
+    }
+}
+
+/// GET /admin/v1/system - Overview of the node
+async fn get_system(State(state): State<Arc<AdminState>>, headers: HeaderMap) -> Response {
+    match state.authorized_stats(&headers) {
+        Ok(stats) => Json(json!(stats.system())).into_response(),
+        Err((status, message)) => error_response(status, &message),
+    }
+}
+
+/// Create admin routes
+pub fn admin_routes(state: Arc<AdminState>) -> Router {
+    Router::new()
+        .route("/v1/config/reload", post(reload_config))
+        .route("/v1/collections", get(list_collections))
+        .route("/v1/collections/{name}", get(get_collection))
+        .route("/v1/collections/{name}/export", post(export_collection))
+        .route("/v1/collections/{name}/import", post(import_collection))
+        .route("/v1/system", get(get_system))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::api::AuthService;
+    use crate::auth::api_key::{ApiKeyService, InMemoryApiKeyRepository};
+    use crate::auth::crypto::PasswordPolicy;
+    use crate::auth::jwt::{JwtConfig, JwtManager};
+    use crate::auth::session::{InMemorySessionRepository, SessionConfig};
+    use crate::auth::user::{InMemoryUserRepository, User};
+    use crate::config_reload::{FieldChange, RefusedChange, ReloadError, ReloadReport};
+    use crate::observability::{
+        CollectionDetail, CollectionSummary, ReplicationSummary, SystemSummary,
+    };
+    use crate::transfer::{ExportReport, ImportReport, TransferResult};
+    use std::io::{Read, Write};
+
+    struct FixedReload(ReloadReport);
+
+    impl ConfigReload for FixedReload {
+        fn reload(&self) -> Result<ReloadReport, ReloadError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Exports a fixed dump; imports by counting lines and rejecting the
+    /// ones that mention "bad"
+    struct FakeTransfer;
+
+    impl DataTransfer for FakeTransfer {
+        fn export(
+            &self,
+            options: &ExportOptions,
+            out: &mut dyn Write,
+        ) -> TransferResult<ExportReport> {
+            writeln!(out, "{{\"collection\":\"{}\"}}", options.collection)?;
+            Ok(ExportReport { exported: 1 })
+        }
+
+        fn import(
+            &self,
+            options: &ImportOptions,
+            input: &mut dyn Read,
+            rejects: &mut dyn Write,
+        ) -> TransferResult<ImportReport> {
+            let mut text = String::new();
+            input.read_to_string(&mut text)?;
+            let mut report = ImportReport::default();
+            for (i, line) in text.lines().enumerate() {
+                report.read += 1;
+                if !line.contains("bad") {
+                    report.inserted += 1;
+                    continue;
+                }
+                let reject = RejectedRow {
+                    line: i as u64 + 1,
+                    code: "AERO_SCHEMA_VIOLATION".to_string(),
+                    message: format!("mapped {:?}", options.column_map),
+                    row: json!(line),
+                };
+                serde_json::to_writer(&mut *rejects, &reject)?;
+                rejects.write_all(b"\n")?;
+                report.rejected += 1;
+            }
+            Ok(report)
+        }
+    }
+
+    /// One empty collection, `users`
+    struct FixedStats;
+
+    impl DatabaseStats for FixedStats {
+        fn collections(&self) -> Vec<CollectionSummary> {
+            vec![CollectionSummary {
+                name: "users".to_string(),
+                schema_version: "v1".to_string(),
+                schema_versions: vec!["v1".to_string()],
+                document_count: 0,
+                storage_bytes: 0,
+                storage_records: 0,
+                last_write: None,
+                indexes: Vec::new(),
+            }]
+        }
+
+        fn collection(&self, name: &str) -> Option<CollectionDetail> {
+            let summary = self.collections().into_iter().find(|c| c.name == name)?;
+            Some(CollectionDetail {
+                summary,
+                analyzed_at: None,
+                stale: true,
+                fields: Vec::new(),
+            })
+        }
+
+        fn system(&self) -> SystemSummary {
+            SystemSummary {
+                resources: None,
+                wal_bytes: 0,
+                wal_sequence: 0,
+                last_checkpoint: None,
+                last_backup: None,
+                replication: ReplicationSummary {
+                    role: "standalone".to_string(),
+                    replicas: Vec::new(),
+                },
+                uptime_secs: 7,
+            }
+        }
+    }
+
+    fn jwt_config() -> JwtConfig {
+        JwtConfig {
+            service_role_secret: Some("service_secret_for_testing".to_string()),
+            ..JwtConfig::default()
+        }
+    }
+
+    /// Admin state whose tokens are checked against `jwt_config()`
+    fn stats_state(stats: bool) -> Arc<AdminState> {
+        let state = Arc::new(AdminState::new());
+        state.set_auth(Arc::new(AuthState {
+            service: Arc::new(AuthService::new(
+                InMemoryUserRepository::new(),
+                InMemorySessionRepository::new(),
+                jwt_config(),
+                SessionConfig::default(),
+                PasswordPolicy::default(),
+            )),
+            api_keys: ApiKeyService::new(Box::new(InMemoryApiKeyRepository::new())),
+        }));
+        if stats {
+            state.set_database_stats(Arc::new(FixedStats));
+        }
+        state
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
+    }
+
+    fn user_token(admin: bool) -> String {
+        let jwt = JwtManager::new(jwt_config());
+        let user = User::new(
+            "ops@example.com".to_string(),
+            "password123",
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        if admin {
+            jwt.generate_admin_token(&user).unwrap()
+        } else {
+            jwt.generate_access_token(&user).unwrap()
+        }
+    }
+
+    fn transfer_state() -> Arc<AdminState> {
+        let state = Arc::new(AdminState::new());
+        state.set_data_transfer(Arc::new(FakeTransfer));
+        state
+    }
+
+    fn import_query(map: Option<&str>) -> ImportQuery {
+        ImportQuery {
+            schema_version: "v1".to_string(),
+            format: TransferFormat::Jsonl,
+            mode: ImportMode::Insert,
+            batch_size: None,
+            abort_on_error: false,
+            map: map.map(str::to_string),
+        }
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reload_unavailable_without_config() {
+        let (status, _) = reload_config(State(Arc::new(AdminState::new()))).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_reload_reports_refused_fields() {
+        let change = FieldChange {
+            field: "server.data_dir".to_string(),
+            old: json!("/a"),
+            new: json!("/b"),
+        };
+        let report = ReloadReport {
+            applied: Vec::new(),
+            refused: vec![RefusedChange {
+                change,
+                reason: "Requires a restart to take effect".to_string(),
+            }],
+        };
+        let state = Arc::new(AdminState::new());
+        state.set_config_reload(Arc::new(FixedReload(report)));
+
+        let (status, Json(body)) = reload_config(State(state)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["refused"][0]["field"], "server.data_dir");
+        assert_eq!(body["refused"][0]["new"], "/b");
+    }
+
+    #[tokio::test]
+    async fn test_transfer_unavailable_on_replica() {
+        let state = Arc::new(AdminState::new());
+        let body = ExportBody {
+            schema_version: "v1".to_string(),
+            format: TransferFormat::Jsonl,
+            filter: None,
+        };
+        let response =
+            export_collection(State(state.clone()), Path("users".to_string()), Json(body)).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let query = Query(import_query(None));
+        let response =
+            import_collection(State(state), Path("users".to_string()), query, Bytes::new()).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_export_sets_content_type() {
+        let body = ExportBody {
+            schema_version: "v1".to_string(),
+            format: TransferFormat::Jsonl,
+            filter: Some(json!({"age": {"$gt": 40}})),
+        };
+        let response = export_collection(
+            State(transfer_state()),
+            Path("users".to_string()),
+            Json(body),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/x-ndjson"
+        );
+        assert_eq!(body_json(response).await, json!({"collection": "users"}));
+    }
+
+    #[tokio::test]
+    async fn test_import_reports_rejected_rows() {
+        let input = Bytes::from("{\"_id\":\"a\"}\n{\"bad\":1}\n");
+        let query = Query(import_query(Some("ID=_id,Name=name")));
+        let response = import_collection(
+            State(transfer_state()),
+            Path("users".to_string()),
+            query,
+            input,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["read"], 2);
+        assert_eq!(body["inserted"], 1);
+        assert_eq!(body["rejects"][0]["line"], 2);
+        assert!(body["rejects"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("\"ID\": \"_id\""));
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_malformed_map() {
+        let query = Query(import_query(Some("ID")));
+        let response = import_collection(
+            State(transfer_state()),
+            Path("users".to_string()),
+            query,
+            Bytes::new(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_stats_require_operator_token() {
+        let state = stats_state(true);
+        let service = JwtManager::new(jwt_config())
+            .generate_service_role_token()
+            .unwrap();
+
+        let cases = [
+            (HeaderMap::new(), StatusCode::UNAUTHORIZED),
+            (bearer("not-a-jwt"), StatusCode::UNAUTHORIZED),
+            (bearer(&user_token(false)), StatusCode::FORBIDDEN),
+            (bearer(&user_token(true)), StatusCode::OK),
+            (bearer(&service), StatusCode::OK),
+        ];
+        for (headers, expected) in cases {
+            let response = list_collections(State(state.clone()), headers.clone()).await;
+            assert_eq!(response.status(), expected);
+            let response = get_system(State(state.clone()), headers).await;
+            assert_eq!(response.status(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stats_bodies() {
+        let state = stats_state(true);
+        let admin = bearer(&user_token(true));
+
+        let response = list_collections(State(state.clone()), admin.clone()).await;
+        assert_eq!(body_json(response).await["collections"][0]["name"], "users");
+
+        let response = get_system(State(state.clone()), admin.clone()).await;
+        let body = body_json(response).await;
+        assert_eq!(body["uptime_secs"], 7);
+        assert_eq!(body["replication"]["role"], "standalone");
+
+        let response = get_collection(
+            State(state.clone()),
+            admin.clone(),
+            Path("users".to_string()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["stale"], true);
+
+        let response = get_collection(State(state), admin, Path("missing".to_string())).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_stats_unavailable_on_replica() {
+        let response = get_system(State(stats_state(false)), bearer(&user_token(true))).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,108 @@
+//! # API Key Middleware
+//!
+//! Authenticates machine clients that send an `apikey` header.
+//!
+//! # How It Works
+//!
+//! 1. Requests without the header pass through untouched (JWT or anonymous)
+//! 2. A valid key's `RlsContext` is added to the request extensions, where
+//!    handlers read it with `Extension<RlsContext>`
+//! 3. An unknown, revoked or expired key is rejected with 401 before the
+//!    request reaches any handler
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+    Json,
+};
+
+use crate::auth::api_key::API_KEY_HEADER;
+use crate::auth::errors::AuthError;
+use crate::auth::rls::RlsContext;
+
+use super::auth_routes::{AuthState, ErrorResponse};
+
+/// Resolve the `apikey` header, if any, to its RLS context
+fn resolve_api_key(
+    state: &AuthState,
+    headers: &HeaderMap,
+) -> Result<Option<RlsContext>, (StatusCode, Json<ErrorResponse>)> {
+    let Some(value) = headers.get(API_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| AuthError::InvalidCredentials)
+        .and_then(|key| state.api_keys.authenticate(key))
+        .map_err(|e| {
+            (
+                StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::UNAUTHORIZED),
+                Json(ErrorResponse::from(e)),
+            )
+        })?;
+    Ok(Some(key))
+}
+
+/// API key middleware
+///
+/// # Behavior
+///
+/// - No `apikey` header: request proceeds normally
+/// - Valid key: request proceeds with the key's `RlsContext` attached
+/// - Invalid, revoked or expired key: 401 with the auth error
+pub async fn api_key_auth(
+    State(state): State<Arc<AuthState>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    if let Some(ctx) = resolve_api_key(&state, request.headers())? {
+        request.extensions_mut().insert(ctx);
+    }
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(key: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(API_KEY_HEADER, key.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_requests_without_key_pass_through() {
+        let state = AuthState::new();
+        assert!(resolve_api_key(&state, &HeaderMap::new())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_valid_key_resolves_and_revoked_key_is_rejected() {
+        let state = AuthState::new();
+        let (key, plaintext) = state
+            .api_keys
+            .create_key("reporting", Some("tenant-a".to_string()), None)
+            .unwrap();
+
+        let ctx = resolve_api_key(&state, &headers(&plaintext))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ctx.claims["tenant_id"], "tenant-a");
+
+        state.api_keys.revoke_key(key.id).unwrap();
+        let (status, Json(body)) = resolve_api_key(&state, &headers(&plaintext)).unwrap_err();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body.error, "API key has been revoked");
+
+        let (status, _) = resolve_api_key(&state, &headers("service_test")).unwrap_err();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
@@ -11,6 +11,7 @@ use axum::{
     routing::{delete, get, patch, post},
     Router,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -18,6 +19,7 @@ use crate::auth::api::{
     AuthService, ChangePasswordRequest, ErrorResponse, ForgotPasswordRequest, ResetPasswordRequest,
     UpdateUserRequest, UserResponse,
 };
+use crate::auth::api_key::ApiKey;
 use crate::auth::crypto::PasswordPolicy;
 use crate::auth::errors::AuthError;
 use crate::auth::rls::{DefaultRlsEnforcer, RlsPolicy};
@@ -102,6 +104,57 @@ pub struct MessageResponse {
     pub message: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub role: String,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// An API key as shown to admins: identified by prefix, never by hash
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: String,
+    pub prefix: String,
+    pub role: String,
+    pub tenant_id: Option<String>,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub revoked: bool,
+}
+
+impl From<&ApiKey> for ApiKeyResponse {
+    fn from(key: &ApiKey) -> Self {
+        Self {
+            id: key.id.to_string(),
+            prefix: key.prefix.clone(),
+            role: key.role.clone(),
+            tenant_id: key.tenant_id.clone(),
+            created_at: key.created_at.to_rfc3339(),
+            expires_at: key.expires_at.map(|t| t.to_rfc3339()),
+            last_used_at: key.last_used_at.map(|t| t.to_rfc3339()),
+            revoked: key.revoked,
+        }
+    }
+}
+
+/// A new API key, with the plaintext key shown this one time
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKeyResponse {
+    #[serde(flatten)]
+    pub key: ApiKeyResponse,
+    pub api_key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeysListResponse {
+    pub keys: Vec<ApiKeyResponse>,
+    pub total: usize,
+}
+
 // ==================
 // Auth Management Routes
 // ==================
@@ -123,6 +176,10 @@ pub fn auth_management_routes(state: Arc<AuthState>) -> Router {
         .route("/reset-password", post(reset_password_handler))
         .route("/change-password", post(change_password_handler))
         .route("/password-policy", get(get_password_policy_handler))
+        // API key management (admin JWT required)
+        .route("/api-keys", get(list_api_keys_handler))
+        .route("/api-keys", post(create_api_key_handler))
+        .route("/api-keys/{id}", delete(revoke_api_key_handler))
         // RLS management
         .route("/rls/{table}", get(get_rls_policy_handler))
         .route("/rls/{table}", post(create_rls_policy_handler))
@@ -181,6 +238,34 @@ fn validate_admin_access(
     })
 }
 
+/// Validate that the bearer token carries the admin role claim
+fn validate_admin_role(
+    state: &AuthState,
+    headers: &HeaderMap,
+) -> Result<Uuid, (StatusCode, Json<ErrorResponse>)> {
+    let token = extract_bearer_token(headers).ok_or_else(|| {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: "Missing authorization header".to_string(),
+                code: 401,
+            }),
+        )
+    })?;
+
+    state
+        .service
+        .validate_admin_token(token)
+        .map_err(auth_error)
+}
+
+fn auth_error(e: AuthError) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        Json(ErrorResponse::from(e)),
+    )
+}
+
 // ==================
 // User Management Handlers
 // ==================
@@ -318,6 +403,67 @@ async fn revoke_session_handler(
     ))
 }
 
+// ==================
+// API Key Management Handlers
+// ==================
+
+/// List API keys (admin only)
+async fn list_api_keys_handler(
+    State(state): State<Arc<AuthState>>,
+    headers: HeaderMap,
+) -> Result<Json<ApiKeysListResponse>, (StatusCode, Json<ErrorResponse>)> {
+    validate_admin_role(&state, &headers)?;
+
+    let keys: Vec<ApiKeyResponse> = state
+        .api_keys
+        .list_keys()
+        .map_err(auth_error)?
+        .iter()
+        .map(ApiKeyResponse::from)
+        .collect();
+
+    Ok(Json(ApiKeysListResponse {
+        total: keys.len(),
+        keys,
+    }))
+}
+
+/// Create an API key (admin only)
+///
+/// The response is the only place the plaintext key ever appears.
+async fn create_api_key_handler(
+    State(state): State<Arc<AuthState>>,
+    headers: HeaderMap,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<CreatedApiKeyResponse>), (StatusCode, Json<ErrorResponse>)> {
+    validate_admin_role(&state, &headers)?;
+
+    let (key, api_key) = state
+        .api_keys
+        .create_key(&request.role, request.tenant_id, request.expires_at)
+        .map_err(auth_error)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiKeyResponse {
+            key: ApiKeyResponse::from(&key),
+            api_key,
+        }),
+    ))
+}
+
+/// Revoke an API key (admin only)
+async fn revoke_api_key_handler(
+    State(state): State<Arc<AuthState>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiKeyResponse>, (StatusCode, Json<ErrorResponse>)> {
+    validate_admin_role(&state, &headers)?;
+
+    let key = state.api_keys.revoke_key(id).map_err(auth_error)?;
+    Ok(Json(ApiKeyResponse::from(&key)))
+}
+
 // ==================
 // Password Management Handlers
 // ==================
@@ -414,6 +560,7 @@ async fn create_rls_policy_handler(
     Json(request): Json<CreateRlsPolicyRequest>,
 ) -> Result<(StatusCode, Json<RlsPolicyResponse>), (StatusCode, Json<ErrorResponse>)> {
     // RLS policy creation would need to be wired
+    request.policy.validate().map_err(auth_error)?;
     Ok((
         StatusCode::CREATED,
         Json(RlsPolicyResponse {
@@ -464,10 +611,82 @@ async fn resend_verification_handler(
 mod tests {
     use super::*;
 
+    use crate::auth::jwt::{JwtConfig, JwtManager};
+    use crate::auth::user::User;
+
     #[test]
     fn test_password_policy_response() {
         let policy = PasswordPolicy::default();
         let response = PasswordPolicyResponse::from(&policy);
         assert_eq!(response.min_length, policy.min_length);
     }
+
+    fn bearer(admin: bool) -> HeaderMap {
+        let jwt = JwtManager::new(JwtConfig::default());
+        let user = User::new(
+            "ops@example.com".to_string(),
+            "password123",
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        let token = if admin {
+            jwt.generate_admin_token(&user).unwrap()
+        } else {
+            jwt.generate_access_token(&user).unwrap()
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_api_key_management_requires_admin_jwt() {
+        let state = Arc::new(AuthState::new());
+
+        let (status, _) = list_api_keys_handler(State(state.clone()), HeaderMap::new())
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = list_api_keys_handler(State(state), bearer(false))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_create_list_revoke() {
+        let state = Arc::new(AuthState::new());
+        let request = CreateApiKeyRequest {
+            role: "reporting".to_string(),
+            tenant_id: None,
+            expires_at: None,
+        };
+
+        let (status, Json(created)) =
+            create_api_key_handler(State(state.clone()), bearer(true), Json(request))
+                .await
+                .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(created.api_key.starts_with(&created.key.prefix));
+        assert!(state.api_keys.authenticate(&created.api_key).is_ok());
+
+        let Json(list) = list_api_keys_handler(State(state.clone()), bearer(true))
+            .await
+            .unwrap();
+        assert_eq!(list.total, 1);
+        let listed = serde_json::to_string(&list).unwrap();
+        assert!(!listed.contains(&created.api_key));
+        assert!(!listed.contains("hash"));
+
+        let id = Uuid::parse_str(&created.key.id).unwrap();
+        let Json(revoked) = revoke_api_key_handler(State(state.clone()), bearer(true), Path(id))
+            .await
+            .unwrap();
+        assert!(revoked.revoked);
+        assert!(state.api_keys.authenticate(&created.api_key).is_err());
+    }
 }
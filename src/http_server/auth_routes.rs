@@ -14,7 +14,8 @@ use axum::{
 use serde::{Deserialize, Serialize};
 
 use crate::auth::api::AuthService;
-use crate::auth::crypto::PasswordPolicy;
+use crate::auth::api_key::{ApiKeyRepository, ApiKeyService, InMemoryApiKeyRepository};
+use crate::auth::crypto::{PasswordHasher, PasswordPolicy};
 use crate::auth::errors::AuthError;
 use crate::auth::jwt::{JwtConfig, JwtManager, TokenResponse};
 use crate::auth::session::{InMemorySessionRepository, SessionConfig};
@@ -22,20 +23,39 @@ use crate::auth::user::{InMemoryUserRepository, LoginRequest, SignupRequest, Use
 
 /// Shared auth state
 pub struct AuthState {
-    pub service: AuthService<InMemoryUserRepository, InMemorySessionRepository>,
+    pub service: Arc<AuthService<InMemoryUserRepository, InMemorySessionRepository>>,
+    pub api_keys: ApiKeyService<Box<dyn ApiKeyRepository>>,
 }
 
 impl AuthState {
     /// Create new auth state with default config
     pub fn new() -> Self {
+        Self::with_api_key_repository(
+            Box::new(InMemoryApiKeyRepository::new()),
+            PasswordHasher::default(),
+            JwtConfig::default(),
+        )
+    }
+
+    /// Create auth state whose API keys live in `api_keys`, whose
+    /// passwords are hashed by `password_hasher` and whose tokens are
+    /// signed as `jwt_config` says
+    pub fn with_api_key_repository(
+        api_keys: Box<dyn ApiKeyRepository>,
+        password_hasher: PasswordHasher,
+        jwt_config: JwtConfig,
+    ) -> Self {
+        let service = AuthService::new(
+            InMemoryUserRepository::new(),
+            InMemorySessionRepository::new(),
+            jwt_config,
+            SessionConfig::default(),
+            PasswordPolicy::default(),
+        )
+        .with_password_hasher(password_hasher);
         Self {
-            service: AuthService::new(
-                InMemoryUserRepository::new(),
-                InMemorySessionRepository::new(),
-                JwtConfig::default(),
-                SessionConfig::default(),
-                PasswordPolicy::default(),
-            ),
+            service: Arc::new(service),
+            api_keys: ApiKeyService::new(api_keys),
         }
     }
 }
@@ -14,8 +14,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
 
-// Backup module types would be imported when available
-// For now, using placeholder types since backup module has different structure
+use crate::backup::{BackupConfig, BackupErrorCode, BackupManager, VerificationReport};
+
+// Most handlers still return placeholder data; verification reads the
+// archives in the configured backup directory
 
 // ==================
 // Shared State
@@ -23,13 +25,30 @@ use uuid::Uuid;
 
 /// Backup state shared across handlers
 pub struct BackupState {
-    // In a real system, would hold BackupManager
-    // For now, we use a simple struct
+    /// Manager for the configured backup directory, if any
+    manager: Option<BackupManager>,
 }
 
 impl BackupState {
     pub fn new() -> Self {
-        Self {}
+        Self { manager: None }
+    }
+
+    /// State backed by the archives in `backup_dir`
+    pub fn with_backup_dir(backup_dir: &str) -> Self {
+        let config = BackupConfig {
+            backup_dir: backup_dir.to_string(),
+            ..BackupConfig::new()
+        };
+        match BackupManager::new(config) {
+            Ok(manager) => Self {
+                manager: Some(manager),
+            },
+            Err(e) => {
+                eprintln!("Warning: backup directory unavailable: {}", e);
+                Self::new()
+            }
+        }
     }
 }
 
@@ -155,6 +174,7 @@ pub fn backup_routes(state: Arc<BackupState>) -> Router {
         .route("/{id}", get(get_backup_handler))
         .route("/{id}", delete(delete_backup_handler))
         .route("/{id}/download", get(download_backup_handler))
+        .route("/{id}/verify", post(verify_backup_handler))
         // Restore operations
         .route("/{id}/restore", post(restore_backup_handler))
         .route(
@@ -243,6 +263,53 @@ async fn download_backup_handler(
     Ok((StatusCode::OK, headers, vec![]))
 }
 
+/// Verify a backup archive without restoring it.
+///
+/// Returns 200 with the report whether or not the backup is intact.
+async fn verify_backup_handler(
+    State(state): State<Arc<BackupState>>,
+    Path(id): Path<String>,
+) -> Result<Json<VerificationReport>, (StatusCode, Json<ErrorResponse>)> {
+    if state.manager.is_none() {
+        return Err(error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "No backup directory configured",
+        ));
+    }
+
+    // Verification reads the whole archive, keep it off the async workers
+    let result = tokio::task::spawn_blocking(move || {
+        state
+            .manager
+            .as_ref()
+            .expect("checked above")
+            .verify_backup(&id)
+    })
+    .await
+    .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()))?;
+
+    match result {
+        Ok(report) => Ok(Json(report)),
+        Err(e) if e.code() == BackupErrorCode::AeroBackupNotFound => {
+            Err(error_response(StatusCode::NOT_FOUND, e.message()))
+        }
+        Err(e) => Err(error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &e.to_string(),
+        )),
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message.to_string(),
+            code: status.as_u16(),
+        }),
+    )
+}
+
 // ==================
 // Restore Handlers
 // ==================
@@ -336,4 +403,25 @@ mod tests {
         let state = BackupState::new();
         // State should be created successfully
     }
+
+    #[tokio::test]
+    async fn test_verify_requires_backup_dir() {
+        let state = Arc::new(BackupState::new());
+
+        let err = verify_backup_handler(State(state), Path("b1".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.0, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_verify_unknown_backup_is_not_found() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let state = Arc::new(BackupState::with_backup_dir(&temp.path().to_string_lossy()));
+
+        let err = verify_backup_handler(State(state), Path("missing".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.0, StatusCode::NOT_FOUND);
+    }
 }
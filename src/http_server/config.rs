@@ -2,8 +2,16 @@
 //!
 //! Configuration for the HTTP server including host, port, and CORS settings.
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use crate::auth::crypto::PasswordHasher;
+use crate::auth::jwt::JwtConfig;
+use crate::dx::api::control_plane::DEFAULT_CONFIRMATION_TTL;
+use crate::file_storage::StorageConfig;
+use crate::realtime::{BackpressureConfig, ChangeStreamConfig, HubConfig};
+
 /// HTTP server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HttpServerConfig {
@@ -18,6 +26,126 @@ pub struct HttpServerConfig {
     /// CORS allowed origins (default: ["http://localhost:5173"])
     #[serde(default = "default_cors_origins")]
     pub cors_origins: Vec<String>,
+
+    /// Directory holding backup archives (default: none, backup
+    /// admin endpoints that read archives are unavailable)
+    #[serde(default)]
+    pub backup_dir: Option<String>,
+
+    /// Data directory whose `system/api_keys.json` holds API keys
+    /// (default: none, keys are kept in memory and lost on restart)
+    #[serde(default)]
+    pub data_dir: Option<String>,
+
+    /// Realtime WebSocket endpoint (`/realtime/v1`) settings
+    #[serde(default)]
+    pub realtime: RealtimeConfig,
+
+    /// Upload limits and URL signing of the storage endpoints
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    /// Seconds a tenant deletion confirmation token stays valid
+    /// (default: 300)
+    #[serde(default = "default_confirmation_ttl_secs")]
+    pub confirmation_ttl_secs: u64,
+
+    /// Argon2id hasher for new passwords, built from `[auth.password_hash]`
+    #[serde(skip)]
+    pub password_hasher: PasswordHasher,
+
+    /// Access token signing, with `[auth] service_role_secret`
+    #[serde(skip)]
+    pub jwt: JwtConfig,
+}
+
+/// Realtime WebSocket endpoint configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeConfig {
+    /// Interval between server pings in milliseconds (default: 30000)
+    #[serde(default = "default_heartbeat_interval_ms")]
+    pub heartbeat_interval_ms: u64,
+
+    /// Consecutive unanswered pings before the connection is dropped
+    /// (default: 2)
+    #[serde(default = "default_max_missed_pongs")]
+    pub max_missed_pongs: u32,
+
+    /// Events kept per subscription for resume (default: 256)
+    #[serde(default = "default_resume_buffer_size")]
+    pub resume_buffer_size: usize,
+
+    /// Subscriptions kept for resume after their connection closes
+    /// (default: 1024)
+    #[serde(default = "default_max_detached_subscriptions")]
+    pub max_detached_subscriptions: usize,
+
+    /// Outbound buffer of each connection
+    #[serde(default)]
+    pub backpressure: BackpressureConfig,
+
+    /// Data directory whose WAL is tailed for change events (default:
+    /// none, no database events are published)
+    #[serde(default)]
+    pub cdc_data_dir: Option<String>,
+
+    /// Wait between WAL polls once the change stream has caught up, in
+    /// milliseconds (default: 50)
+    #[serde(default = "default_cdc_poll_interval_ms")]
+    pub cdc_poll_interval_ms: u64,
+}
+
+fn default_heartbeat_interval_ms() -> u64 {
+    30_000
+}
+
+fn default_max_missed_pongs() -> u32 {
+    2
+}
+
+fn default_resume_buffer_size() -> usize {
+    256
+}
+
+fn default_max_detached_subscriptions() -> usize {
+    1024
+}
+
+fn default_cdc_poll_interval_ms() -> u64 {
+    50
+}
+
+impl Default for RealtimeConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval_ms: default_heartbeat_interval_ms(),
+            max_missed_pongs: default_max_missed_pongs(),
+            resume_buffer_size: default_resume_buffer_size(),
+            max_detached_subscriptions: default_max_detached_subscriptions(),
+            backpressure: BackpressureConfig::default(),
+            cdc_data_dir: None,
+            cdc_poll_interval_ms: default_cdc_poll_interval_ms(),
+        }
+    }
+}
+
+impl RealtimeConfig {
+    /// Hub settings derived from this configuration
+    pub fn hub_config(&self) -> HubConfig {
+        HubConfig {
+            resume_buffer_size: self.resume_buffer_size,
+            max_detached_subscriptions: self.max_detached_subscriptions,
+            backpressure: self.backpressure.clone(),
+        }
+    }
+
+    /// Change stream settings derived from this configuration
+    pub fn change_stream_config(&self) -> ChangeStreamConfig {
+        ChangeStreamConfig {
+            poll_interval: Duration::from_millis(self.cdc_poll_interval_ms),
+            ..ChangeStreamConfig::default()
+        }
+    }
 }
 
 fn default_host() -> String {
@@ -28,6 +156,10 @@ fn default_port() -> u16 {
     54321
 }
 
+fn default_confirmation_ttl_secs() -> u64 {
+    DEFAULT_CONFIRMATION_TTL.as_secs()
+}
+
 fn default_cors_origins() -> Vec<String> {
     vec![
         "http://localhost:5173".to_string(), // Vite dev server
@@ -42,6 +174,13 @@ impl Default for HttpServerConfig {
             host: default_host(),
             port: default_port(),
             cors_origins: default_cors_origins(),
+            backup_dir: None,
+            data_dir: None,
+            realtime: RealtimeConfig::default(),
+            storage: StorageConfig::default(),
+            confirmation_ttl_secs: default_confirmation_ttl_secs(),
+            password_hasher: PasswordHasher::default(),
+            jwt: JwtConfig::default(),
         }
     }
 }
@@ -55,6 +194,48 @@ impl HttpServerConfig {
         }
     }
 
+    /// Set the directory holding backup archives
+    pub fn with_backup_dir(mut self, backup_dir: impl Into<String>) -> Self {
+        self.backup_dir = Some(backup_dir.into());
+        self
+    }
+
+    /// Set the data directory API keys are stored in
+    pub fn with_data_dir(mut self, data_dir: impl Into<String>) -> Self {
+        self.data_dir = Some(data_dir.into());
+        self
+    }
+
+    /// Set the realtime endpoint configuration
+    pub fn with_realtime(mut self, realtime: RealtimeConfig) -> Self {
+        self.realtime = realtime;
+        self
+    }
+
+    /// Set the storage endpoint limits and URL signing
+    pub fn with_storage(mut self, storage: StorageConfig) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Set how long tenant deletion confirmation tokens stay valid
+    pub fn with_confirmation_ttl_secs(mut self, confirmation_ttl_secs: u64) -> Self {
+        self.confirmation_ttl_secs = confirmation_ttl_secs;
+        self
+    }
+
+    /// Set the hasher new passwords are hashed with
+    pub fn with_password_hasher(mut self, password_hasher: PasswordHasher) -> Self {
+        self.password_hasher = password_hasher;
+        self
+    }
+
+    /// Set how access tokens, service-role ones included, are signed
+    pub fn with_jwt_config(mut self, jwt: JwtConfig) -> Self {
+        self.jwt = jwt;
+        self
+    }
+
     /// Get the socket address string
     pub fn socket_addr(&self) -> String {
         format!("{}:{}", self.host, self.port)
@@ -71,6 +252,8 @@ mod tests {
         assert_eq!(config.host, "0.0.0.0");
         assert_eq!(config.port, 54321);
         assert!(!config.cors_origins.is_empty());
+        assert_eq!(config.realtime.heartbeat_interval_ms, 30_000);
+        assert_eq!(config.realtime.max_missed_pongs, 2);
     }
 
     #[test]
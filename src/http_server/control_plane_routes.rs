@@ -2,7 +2,8 @@
 //!
 //! REST API endpoints for multi-tenant management.
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use axum::{
     extract::{Path, Query, State},
@@ -15,7 +16,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::control_plane::{
-    billing::BillingCalculator,
+    billing::BillingEngine,
     errors::{ControlPlaneError, ErrorResponse},
     metering::UsageTracker,
     provisioning::ProvisioningService,
@@ -26,6 +27,7 @@ use crate::control_plane::{
         TenantListItem, TenantStatus, UpdateTenantRequest,
     },
 };
+use crate::dx::api::control_plane::{ConfirmationFlow, ConfirmationResult};
 
 /// Control plane state
 #[derive(Clone)]
@@ -34,8 +36,10 @@ pub struct ControlPlaneState {
     provisioning: Arc<ProvisioningService>,
     /// Usage tracker
     usage_tracker: Arc<UsageTracker>,
-    /// Billing calculator
-    billing: Arc<BillingCalculator>,
+    /// Billing engine
+    billing: Arc<BillingEngine>,
+    /// Confirmation tokens for tenant deletion
+    confirmation: Arc<Mutex<ConfirmationFlow>>,
 }
 
 impl ControlPlaneState {
@@ -43,12 +47,13 @@ impl ControlPlaneState {
     pub fn new() -> Self {
         let usage_tracker = Arc::new(UsageTracker::new());
         let registry = Arc::new(TenantRegistry::with_usage_tracker(usage_tracker.clone()));
-        let provisioning = Arc::new(ProvisioningService::new(registry));
+        let provisioning = Arc::new(ProvisioningService::new(registry.clone()));
 
         Self {
             provisioning,
             usage_tracker,
-            billing: Arc::new(BillingCalculator::new()),
+            billing: Arc::new(BillingEngine::new(registry)),
+            confirmation: Arc::new(Mutex::new(ConfirmationFlow::new())),
         }
     }
 
@@ -56,8 +61,17 @@ impl ControlPlaneState {
     pub fn with_provisioning(provisioning: Arc<ProvisioningService>) -> Self {
         Self {
             usage_tracker: provisioning.registry().usage_tracker(),
+            billing: Arc::new(BillingEngine::new(provisioning.registry())),
             provisioning,
-            billing: Arc::new(BillingCalculator::new()),
+            confirmation: Arc::new(Mutex::new(ConfirmationFlow::new())),
+        }
+    }
+
+    /// Expire tenant deletion confirmation tokens `ttl` after they are issued
+    pub fn with_confirmation_ttl(self, ttl: Duration) -> Self {
+        Self {
+            confirmation: Arc::new(Mutex::new(ConfirmationFlow::new().with_max_token_age(ttl))),
+            ..self
         }
     }
 }
@@ -77,6 +91,9 @@ pub fn control_plane_routes(state: Arc<ControlPlaneState>) -> Router {
         .route("/v1/tenants/{id}", get(get_tenant))
         .route("/v1/tenants/{id}", patch(update_tenant))
         .route("/v1/tenants/{id}", delete(delete_tenant))
+        // Lifecycle
+        .route("/v1/tenants/{id}/suspend", post(suspend_tenant))
+        .route("/v1/tenants/{id}/resume", post(resume_tenant))
         // Usage & Billing
         .route("/v1/tenants/{id}/usage", get(get_usage))
         .route("/v1/tenants/{id}/invoice", get(get_invoice))
@@ -178,17 +195,100 @@ async fn update_tenant(
     }
 }
 
-/// Delete tenant
+/// Delete tenant query params
+#[derive(Deserialize, Default)]
+pub struct DeleteTenantQuery {
+    /// Confirmation token issued by an earlier request for this deletion
+    confirm: Option<Uuid>,
+}
+
+/// Delete tenant, deprovisioning its data
+///
+/// Without `confirm`, nothing is deleted: the response is 428 with a
+/// confirmation token bound to this tenant. Repeating the request with
+/// `?confirm=<token>` deletes it.
 async fn delete_tenant(
     State(state): State<Arc<ControlPlaneState>>,
     Path(id): Path<Uuid>,
+    Query(params): Query<DeleteTenantQuery>,
 ) -> impl IntoResponse {
+    match state.provisioning.get_tenant(id) {
+        Ok(tenant) if tenant.is_deleted() => {
+            return error_response(ControlPlaneError::TenantDeleted {
+                tenant_id: id.to_string(),
+            })
+        }
+        Ok(_) => {}
+        Err(e) => return error_response(e),
+    }
+    if let Err(e) = confirm_delete_tenant(&state, id, params.confirm) {
+        return error_response(e);
+    }
     match state.provisioning.delete_tenant(id).await {
         Ok(()) => StatusCode::NO_CONTENT.into_response(),
         Err(e) => error_response(e),
     }
 }
 
+/// Name of tenant deletion in confirmation tokens
+const DELETE_TENANT: &str = "delete_tenant";
+
+/// Issue a token for deleting tenant `id`, or consume `confirm`
+fn confirm_delete_tenant(
+    state: &ControlPlaneState,
+    id: Uuid,
+    confirm: Option<Uuid>,
+) -> Result<(), ControlPlaneError> {
+    let payload = serde_json::json!({"command": DELETE_TENANT, "tenant_id": id}).to_string();
+    let mut flow = state.confirmation.lock().unwrap();
+    let Some(token_id) = confirm else {
+        let token = flow.request_confirmation(DELETE_TENANT, Some(id), &payload);
+        return Err(ControlPlaneError::ConfirmationRequired {
+            operation: DELETE_TENANT.to_string(),
+            confirmation_token: token.id().to_string(),
+        });
+    };
+    match flow.confirm(token_id, DELETE_TENANT, Some(id), &payload) {
+        ConfirmationResult::Proceed { .. } => Ok(()),
+        ConfirmationResult::Abort { reason, .. } => Err(ControlPlaneError::ConfirmationRejected {
+            operation: DELETE_TENANT.to_string(),
+            reason,
+        }),
+    }
+}
+
+/// Suspend request body
+#[derive(Deserialize, Default)]
+pub struct SuspendRequest {
+    /// Refuse reads as well as writes
+    #[serde(default)]
+    fence_reads: bool,
+}
+
+/// Suspend tenant; its data is kept
+async fn suspend_tenant(
+    State(state): State<Arc<ControlPlaneState>>,
+    Path(id): Path<Uuid>,
+    request: Option<Json<SuspendRequest>>,
+) -> impl IntoResponse {
+    let Json(request) = request.unwrap_or_default();
+    match state.provisioning.suspend(id, request.fence_reads) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// Resume a suspended tenant
+async fn resume_tenant(
+    State(state): State<Arc<ControlPlaneState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match state.provisioning.resume(id) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
 /// Get usage query params
 #[derive(Deserialize)]
 pub struct UsageQuery {
@@ -225,30 +325,16 @@ pub struct InvoiceQuery {
     month: String,
 }
 
-/// Get invoice
+/// Get invoice (generated, or regenerated, from the month's metering)
 async fn get_invoice(
     State(state): State<Arc<ControlPlaneState>>,
     Path(id): Path<Uuid>,
     Query(params): Query<InvoiceQuery>,
 ) -> impl IntoResponse {
-    // Get tenant
-    let tenant = match state.provisioning.get_tenant(id) {
-        Ok(t) => t,
-        Err(e) => return error_response(e),
-    };
-
-    // Get usage for the month
-    let usage = state
-        .usage_tracker
-        .get_usage(id, &params.month)
-        .unwrap_or_else(|| state.usage_tracker.get_current_usage(id));
-
-    // Generate invoice
-    let invoice = state
-        .billing
-        .generate_invoice(id, &params.month, &tenant.plan, &usage);
-
-    (StatusCode::OK, Json(invoice)).into_response()
+    match state.billing.generate_invoice(id, &params.month) {
+        Ok(invoice) => (StatusCode::OK, Json(invoice)).into_response(),
+        Err(e) => error_response(e),
+    }
 }
 
 /// Get quota information
@@ -295,6 +381,7 @@ fn error_response(err: ControlPlaneError) -> axum::response::Response {
         404 => StatusCode::NOT_FOUND,
         409 => StatusCode::CONFLICT,
         410 => StatusCode::GONE,
+        428 => StatusCode::PRECONDITION_REQUIRED,
         429 => StatusCode::TOO_MANY_REQUESTS,
         _ => StatusCode::INTERNAL_SERVER_ERROR,
     };
@@ -404,6 +491,54 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_delete_tenant_requires_confirmation_token() {
+        let state = Arc::new(ControlPlaneState::new());
+        let request = CreateTenantRequest {
+            name: "doomed".to_string(),
+            plan: Plan::Free,
+            region: "local".to_string(),
+            isolation: IsolationModel::Schema,
+        };
+        let id = state.provisioning.create_tenant(request).await.unwrap().tenant_id;
+        let delete = |confirm: Option<Uuid>, id: Uuid| {
+            delete_tenant(State(state.clone()), Path(id), Query(DeleteTenantQuery { confirm }))
+        };
+
+        // The first request only issues a token
+        let response = delete(None, id).await.into_response();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_REQUIRED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "CONFIRMATION_REQUIRED");
+        let token: Uuid = body["details"]["details"]["confirmation_token"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(!state.provisioning.get_tenant(id).unwrap().is_deleted());
+
+        // A token for one tenant does not delete another
+        let other = CreateTenantRequest {
+            name: "spared".to_string(),
+            plan: Plan::Free,
+            region: "local".to_string(),
+            isolation: IsolationModel::Schema,
+        };
+        let other = state.provisioning.create_tenant(other).await.unwrap().tenant_id;
+        let response = delete(Some(token), other).await.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert!(!state.provisioning.get_tenant(other).unwrap().is_deleted());
+
+        let response = delete(Some(token), id).await.into_response();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(state.provisioning.get_tenant(id).unwrap().is_deleted());
+
+        // Tokens are single-use
+        let response = delete(Some(token), other).await.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
     #[test]
     fn test_control_plane_routes_builder() {
         // Just verify routes build without panic
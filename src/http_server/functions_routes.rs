@@ -1,23 +1,39 @@
 //! Functions HTTP Routes
 //!
 //! Endpoints for serverless function management and invocation.
+//!
+//! Edge functions are invoked at `/functions/v1/{name}` with any method
+//! their manifest allows. The caller's access token is validated and its
+//! claims passed to the function with the request headers and body; the
+//! function's status, headers and body are returned as they are. A crashed
+//! function answers 502 and a timed-out one 504. Every invocation is
+//! recorded in the operation log once one is attached.
 
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 
 use axum::{
+    body::{Body, Bytes},
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
-    routing::{delete, get, patch, post},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::Response,
+    routing::{any, delete, get, patch, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
 
+use crate::auth::jwt::{JwtClaims, JwtConfig, JwtManager};
+use crate::auth::rls::RlsContext;
+use crate::functions::edge::{EdgeRequest, EdgeResponse};
+use crate::functions::errors::{FunctionError, FunctionResult};
 use crate::functions::function::Function;
 use crate::functions::invoker::{InvocationContext, InvocationResult, Invoker};
 use crate::functions::registry::FunctionRegistry;
-use crate::functions::trigger::TriggerType;
+use crate::functions::trigger::{HttpMethod, TriggerType};
+use crate::observability::{OperationLog, OperationLogEntry, OperationType};
+use crate::resource_limits::ResourceManager;
 
 // ==================
 // Shared State
@@ -27,6 +43,12 @@ use crate::functions::trigger::TriggerType;
 pub struct FunctionsState {
     pub registry: Arc<FunctionRegistry>,
     pub invoker: Invoker,
+    jwt: JwtManager,
+    /// Resources whose edge function budget memory limits are reserved
+    /// from, once attached
+    resources: OnceLock<Arc<ResourceManager>>,
+    /// Log edge function invocations are recorded in, once attached
+    operation_log: OnceLock<Arc<OperationLog>>,
 }
 
 impl FunctionsState {
@@ -34,7 +56,76 @@ impl FunctionsState {
         Self {
             registry: Arc::new(FunctionRegistry::new()),
             invoker: Invoker::new(),
+            jwt: JwtManager::new(JwtConfig::default()),
+            resources: OnceLock::new(),
+            operation_log: OnceLock::new(),
+        }
+    }
+
+    /// Validate bearer tokens with `jwt`, e.g. the auth service's manager,
+    /// whose key rotations and revocations it then follows
+    pub fn with_jwt_manager(mut self, jwt: JwtManager) -> Self {
+        self.jwt = jwt;
+        self
+    }
+
+    /// Reserve each edge function invocation's memory limit from
+    /// `resources`' edge function budget. Only the first manager set is
+    /// used.
+    pub fn set_resource_manager(&self, resources: Arc<ResourceManager>) {
+        let _ = self.resources.set(resources);
+    }
+
+    /// Record edge function invocations in `log`. Only the first log set
+    /// is used.
+    pub fn set_operation_log(&self, log: Arc<OperationLog>) {
+        let _ = self.operation_log.set(log);
+    }
+
+    /// Validate the bearer token in `headers`, if any
+    fn authenticate(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<Option<JwtClaims>, (StatusCode, Json<ErrorResponse>)> {
+        let Some(auth) = headers.get("authorization") else {
+            return Ok(None);
+        };
+        let token = auth
+            .to_str()
+            .ok()
+            .and_then(|s| s.strip_prefix("Bearer "))
+            .ok_or_else(|| unauthorized("Expected a Bearer token".to_string()))?;
+        self.jwt
+            .validate_token(token)
+            .map(Some)
+            .map_err(|e| unauthorized(e.to_string()))
+    }
+
+    /// Record one invocation of edge function `name`
+    fn log_invocation(
+        &self,
+        name: &str,
+        ctx: &RlsContext,
+        started: Instant,
+        result: &FunctionResult<EdgeResponse>,
+    ) {
+        let Some(log) = self.operation_log.get() else {
+            return;
+        };
+        let mut entry = OperationLogEntry::builder(OperationType::Function)
+            .collection(name)
+            .role(ctx.role())
+            .duration(started.elapsed())
+            .slow_threshold_ms(log.slow_threshold_ms());
+        if let Some(user_id) = ctx.user_id {
+            entry = entry.user_id(user_id);
+        }
+        match result {
+            Ok(_) => {}
+            Err(FunctionError::Timeout(timeout_ms)) => entry = entry.timed_out(*timeout_ms, 0),
+            Err(err) => entry = entry.error(err.status_code().to_string(), err.to_string()),
         }
+        log.log(entry.build());
     }
 }
 
@@ -250,6 +341,8 @@ pub fn functions_routes(state: Arc<FunctionsState>) -> Router {
         .route("/{id}/versions", get(list_versions_handler))
         // Templates
         .route("/templates", get(list_templates_handler))
+        // Edge functions
+        .route("/v1/{name}", any(invoke_edge_function_handler))
         .with_state(state)
 }
 
@@ -424,6 +517,80 @@ async fn invoke_function_handler(
     Ok(Json(InvokeResponse::from(result)))
 }
 
+// ==================
+// Edge Function Handler
+// ==================
+
+async fn invoke_edge_function_handler(
+    State(state): State<Arc<FunctionsState>>,
+    Path(name): Path<String>,
+    method: Method,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let started = Instant::now();
+    let function = state.registry.get_edge(&name).map_err(function_error)?;
+
+    let (claims, ctx) = match state.authenticate(&headers)? {
+        Some(claims) => {
+            let ctx = RlsContext::from_claims(&claims).map_err(|e| unauthorized(e.to_string()))?;
+            (Some(claims), ctx)
+        }
+        None if function.manifest.verify_jwt => {
+            return Err(unauthorized("Missing access token".to_string()));
+        }
+        None => (None, RlsContext::anonymous()),
+    };
+    let method = HttpMethod::parse(method.as_str())
+        .ok_or_else(|| function_error(FunctionError::MethodNotAllowed(method.to_string())))?;
+
+    let mut request = EdgeRequest::new(method);
+    request.claims = claims;
+    request.body = body.to_vec();
+    request.headers = headers
+        .iter()
+        .filter(|(name, _)| *name != "authorization")
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+
+    let budget = state.resources.get().map(|r| r.edge_function_memory());
+    let result = function.invoke(request, budget).await;
+    state.log_invocation(&name, &ctx, started, &result);
+    edge_response(result.map_err(function_error)?)
+}
+
+/// The HTTP response for what an edge function returned
+fn edge_response(response: EdgeResponse) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let invalid = |what: &str| function_error(FunctionError::Crashed(format!("invalid {}", what)));
+    let mut http = Response::builder()
+        .status(StatusCode::from_u16(response.status).map_err(|_| invalid("status code"))?);
+    for (name, value) in &response.headers {
+        let name = HeaderName::try_from(name.as_str()).map_err(|_| invalid("header name"))?;
+        let value = HeaderValue::try_from(value.as_str()).map_err(|_| invalid("header value"))?;
+        http = http.header(name, value);
+    }
+    http.body(Body::from(response.body))
+        .map_err(|_| invalid("response"))
+}
+
+fn function_error(e: FunctionError) -> (StatusCode, Json<ErrorResponse>) {
+    let code = e.status_code();
+    (
+        StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        Json(ErrorResponse {
+            error: e.to_string(),
+            code,
+        }),
+    )
+}
+
+fn unauthorized(error: String) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ErrorResponse { error, code: 401 }),
+    )
+}
+
 // ==================
 // Logs and History Handlers
 // ==================
@@ -509,6 +676,227 @@ async fn list_templates_handler(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
+
+    use axum::response::IntoResponse;
+
+    use crate::auth::crypto::PasswordPolicy;
+    use crate::auth::user::{SignupRequest, User};
+    use crate::functions::edge::FunctionManifest;
+    use crate::http_server::auth_routes::AuthState;
+    use crate::observability::{OperationLogConfig, OperationResult};
+
+    fn edge_state() -> (Arc<FunctionsState>, Arc<OperationLog>) {
+        let state = Arc::new(FunctionsState::new());
+        let log = Arc::new(OperationLog::new(OperationLogConfig {
+            enabled: true,
+            ..OperationLogConfig::default()
+        }));
+        state.set_operation_log(log.clone());
+        (state, log)
+    }
+
+    fn bearer() -> (HeaderMap, User) {
+        let user = User::new(
+            "edge@example.com".to_string(),
+            "password123",
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        let token = JwtManager::new(JwtConfig::default())
+            .generate_access_token(&user)
+            .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        (headers, user)
+    }
+
+    async fn invoke(
+        state: &Arc<FunctionsState>,
+        name: &str,
+        method: Method,
+        headers: HeaderMap,
+        body: &'static str,
+    ) -> (StatusCode, HeaderMap, Value) {
+        let response = invoke_edge_function_handler(
+            State(state.clone()),
+            Path(name.to_string()),
+            method,
+            headers,
+            Bytes::from_static(body.as_bytes()),
+        )
+        .await
+        .into_response();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            headers,
+            serde_json::from_slice(&body).unwrap_or(Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_edge_function_gets_claims_headers_and_body() {
+        let (state, log) = edge_state();
+        state
+            .registry
+            .register_edge(
+                FunctionManifest::new("whoami").with_methods([HttpMethod::Post]),
+                Arc::new(|request: EdgeRequest| {
+                    let body = request.json()?;
+                    let response = serde_json::json!({
+                        "sub": request.claims.map(|c| c.sub),
+                        "trace": request.headers.get("x-trace"),
+                        "token_passed": request.headers.contains_key("authorization"),
+                        "body": body,
+                    });
+                    Ok(EdgeResponse::json(201, &response).with_header("x-function", "whoami"))
+                }),
+            )
+            .unwrap();
+
+        let (mut headers, user) = bearer();
+        headers.insert("x-trace", "abc".parse().unwrap());
+        let (status, response_headers, body) =
+            invoke(&state, "whoami", Method::POST, headers, r#"{"n":1}"#).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(response_headers["x-function"], "whoami");
+        assert_eq!(body["sub"], user.id.to_string());
+        assert_eq!(body["trace"], "abc");
+        assert_eq!(body["token_passed"], false);
+        assert_eq!(body["body"]["n"], 1);
+
+        let entries = log.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].operation, OperationType::Function);
+        assert_eq!(entries[0].collection.as_deref(), Some("whoami"));
+        assert_eq!(entries[0].user_id, Some(user.id));
+        assert_eq!(entries[0].result_status, OperationResult::Success);
+
+        // Methods outside the manifest are refused
+        let (headers, _) = bearer();
+        let (status, _, _) = invoke(&state, "whoami", Method::GET, headers, "").await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn test_edge_function_authentication() {
+        let (state, _log) = edge_state();
+        let hello = |_: EdgeRequest| Ok(EdgeResponse::new(200, "{}"));
+        state
+            .registry
+            .register_edge(FunctionManifest::new("private"), Arc::new(hello))
+            .unwrap();
+        state
+            .registry
+            .register_edge(
+                FunctionManifest::new("public").allow_anonymous(),
+                Arc::new(hello),
+            )
+            .unwrap();
+
+        let (status, _, _) = invoke(&state, "private", Method::POST, HeaderMap::new(), "").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let mut forged = HeaderMap::new();
+        forged.insert("authorization", "Bearer not-a-jwt".parse().unwrap());
+        let (status, _, _) = invoke(&state, "public", Method::POST, forged, "").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _, _) = invoke(&state, "public", Method::POST, HeaderMap::new(), "").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _, _) = invoke(&state, "missing", Method::POST, HeaderMap::new(), "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_revoked_token_is_rejected() {
+        let auth = AuthState::new().service;
+        let state = Arc::new(FunctionsState::new().with_jwt_manager(auth.jwt_manager().clone()));
+        let hello = |_: EdgeRequest| Ok(EdgeResponse::new(200, "{}"));
+        state
+            .registry
+            .register_edge(FunctionManifest::new("private"), Arc::new(hello))
+            .unwrap();
+        let signup = |email: &str| {
+            let request = SignupRequest {
+                email: email.to_string(),
+                password: "password123".to_string(),
+                metadata: None,
+            };
+            let (_, tokens) = auth.signup(request).unwrap();
+            let mut headers = HeaderMap::new();
+            let bearer = format!("Bearer {}", tokens.access_token);
+            headers.insert("authorization", bearer.parse().unwrap());
+            (headers, tokens.refresh_token)
+        };
+
+        let (headers, refresh_token) = signup("revoked@example.com");
+        let (status, _, _) = invoke(&state, "private", Method::POST, headers.clone(), "").await;
+        assert_eq!(status, StatusCode::OK);
+        auth.logout(&refresh_token).unwrap();
+        let (status, _, body) = invoke(&state, "private", Method::POST, headers, "").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", body);
+
+        // Keys rotated through the auth service are honored here too
+        auth.jwt_manager().rotate("rotated_secret_for_testing");
+        let (headers, _) = signup("rotated@example.com");
+        let (status, _, _) = invoke(&state, "private", Method::POST, headers, "").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_crashed_and_timed_out_functions_are_isolated() {
+        let (state, log) = edge_state();
+        let registry = &state.registry;
+        registry
+            .register_edge(
+                FunctionManifest::new("crash").allow_anonymous(),
+                Arc::new(|_: EdgeRequest| -> FunctionResult<EdgeResponse> { panic!("boom") }),
+            )
+            .unwrap();
+        registry
+            .register_edge(
+                FunctionManifest::new("slow")
+                    .allow_anonymous()
+                    .with_timeout(Duration::from_millis(20)),
+                Arc::new(|_: EdgeRequest| {
+                    std::thread::sleep(Duration::from_millis(200));
+                    Ok(EdgeResponse::new(200, "{}"))
+                }),
+            )
+            .unwrap();
+        registry
+            .register_edge(
+                FunctionManifest::new("ok").allow_anonymous(),
+                Arc::new(|_: EdgeRequest| Ok(EdgeResponse::new(200, "{}"))),
+            )
+            .unwrap();
+
+        let (status, _, body) = invoke(&state, "crash", Method::POST, HeaderMap::new(), "").await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert!(body["error"].as_str().unwrap().contains("boom"));
+        let (status, _, _) = invoke(&state, "slow", Method::POST, HeaderMap::new(), "").await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+
+        // The server keeps serving other functions
+        let (status, _, _) = invoke(&state, "ok", Method::POST, HeaderMap::new(), "").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let results: Vec<_> = log.entries().into_iter().map(|e| e.result_status).collect();
+        assert!(matches!(&results[0], OperationResult::Error { code, .. } if code == "502"));
+        assert!(matches!(
+            results[1],
+            OperationResult::TimedOut { timeout_ms: 20, .. }
+        ));
+        assert_eq!(results[2], OperationResult::Success);
+    }
 
     #[test]
     fn test_functions_state_creation() {
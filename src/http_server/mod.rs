@@ -8,6 +8,7 @@
 //! # Endpoints
 //!
 //! - `/health` - Health check
+//! - `/healthz`, `/readyz` - Liveness and readiness probes
 //! - `/setup/*` - First-run setup wizard (locked after complete)
 //! - `/rest/v1/*` - REST API for database operations
 //! - `/auth/*` - Authentication endpoints
@@ -17,7 +18,12 @@
 //! - `/realtime/*` - Real-time subscriptions and WebSocket
 //! - `/backup/*` - Backup and restore endpoints
 //! - `/cluster/*` - Cluster management endpoints
+//! - `/admin/v1/*` - Operator endpoints (config reload)
+//!
+//! Every response carries the request's id in `x-request-id`.
 
+pub mod admin_routes;
+pub mod api_key_guard;
 pub mod auth_management_routes;
 pub mod auth_routes;
 pub mod backup_routes;
@@ -28,11 +34,12 @@ pub mod database_routes;
 pub mod functions_routes;
 pub mod observability_routes;
 pub mod realtime_routes;
+pub mod request_id;
 pub mod server;
 pub mod setup_guard;
 pub mod setup_routes;
 pub mod settings_routes;
 pub mod storage_routes;
 
-pub use config::HttpServerConfig;
+pub use config::{HttpServerConfig, RealtimeConfig};
 pub use server::HttpServer;
@@ -1,12 +1,20 @@
 //! Observability HTTP Routes
 //!
 //! HTTP endpoints for system observability including health checks and metrics.
+//!
+//! Kubernetes-style probes are served at the root: `/healthz` answers while
+//! the process is up, `/readyz` only while it can serve requests.
+
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
 
-use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
 use serde::Serialize;
 use serde_json::Value;
 
 use crate::observability::MetricsRegistry;
+use crate::recovery::RecoveryProgress;
+use crate::resource_limits::{HealthStatus, ResourceManager, ResourceStatus};
 
 /// Health check response
 #[derive(Debug, Serialize)]
@@ -15,6 +23,63 @@ pub struct HealthResponse {
     pub version: String,
 }
 
+/// Readiness response
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    /// `ready` or `recovering`; `/readyz` also reports `read_only` and
+    /// `critical`
+    pub status: String,
+    /// Human-readable state, e.g. `recovering, 63%`
+    pub message: String,
+    /// Latest recovery progress, while recovering
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<RecoveryProgress>,
+    /// Resource status, on `/readyz` once a resource manager is attached
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourceStatus>,
+}
+
+impl ReadinessResponse {
+    fn ready() -> Self {
+        Self {
+            status: "ready".to_string(),
+            message: "ready".to_string(),
+            recovery: None,
+            resources: None,
+        }
+    }
+}
+
+/// State behind the readiness routes
+pub struct ReadinessState {
+    /// Where recovery records its progress
+    data_dir: Option<PathBuf>,
+    /// Resources whose exhaustion makes the server unready
+    resources: OnceLock<Arc<ResourceManager>>,
+}
+
+impl ReadinessState {
+    pub fn new(data_dir: Option<PathBuf>) -> Self {
+        Self {
+            data_dir,
+            resources: OnceLock::new(),
+        }
+    }
+
+    /// Report `/readyz` unready while `resources` is read-only or critical
+    pub fn set_resource_manager(&self, resources: Arc<ResourceManager>) {
+        let _ = self.resources.set(resources);
+    }
+
+    /// Recovery progress, while recovery runs
+    fn recovering(&self) -> Option<RecoveryProgress> {
+        self.data_dir
+            .as_deref()
+            .and_then(RecoveryProgress::load)
+            .filter(RecoveryProgress::is_recovering)
+    }
+}
+
 /// Create observability routes
 pub fn observability_routes() -> Router {
     Router::new()
@@ -22,9 +87,96 @@ pub fn observability_routes() -> Router {
         .route("/metrics", get(metrics_handler))
 }
 
-/// Health check route (also available at root /health)
+/// Health check routes: `/health`, and `/healthz` for liveness probes
+/// (also available at root /health)
 pub fn health_routes() -> Router {
-    Router::new().route("/health", get(health_handler))
+    Router::new()
+        .route("/health", get(health_handler))
+        .route("/healthz", get(health_handler))
+}
+
+/// Readiness routes
+///
+/// `/health/ready` reports recovery progress from
+/// `data_dir/recovery_progress.json`, 503 until recovery completes.
+/// `/readyz` is also 503 while the resource manager is read-only or a
+/// resource is at its critical threshold.
+pub fn readiness_routes(state: Arc<ReadinessState>) -> Router {
+    Router::new()
+        .route("/health/ready", get(ready_handler))
+        .route("/readyz", get(readyz_handler))
+        .with_state(state)
+}
+
+/// 503 while recovering
+fn recovering_response(progress: RecoveryProgress) -> (StatusCode, Json<ReadinessResponse>) {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ReadinessResponse {
+            status: "recovering".to_string(),
+            message: format!(
+                "recovering, {}% ({})",
+                progress.percent.floor(),
+                progress.phase.as_str()
+            ),
+            recovery: Some(progress),
+            resources: None,
+        }),
+    )
+}
+
+/// Readiness handler
+async fn ready_handler(State(state): State<Arc<ReadinessState>>) -> impl IntoResponse {
+    match state.recovering() {
+        Some(progress) => recovering_response(progress),
+        None => (StatusCode::OK, Json(ReadinessResponse::ready())),
+    }
+}
+
+/// Readiness probe handler
+async fn readyz_handler(State(state): State<Arc<ReadinessState>>) -> impl IntoResponse {
+    if let Some(progress) = state.recovering() {
+        return recovering_response(progress);
+    }
+    let Some(resources) = state.resources.get() else {
+        return (StatusCode::OK, Json(ReadinessResponse::ready()));
+    };
+    let status = match resources.get_status() {
+        Ok(status) => status,
+        Err(e) => {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ReadinessResponse {
+                    status: "unknown".to_string(),
+                    message: format!("resource status unavailable: {}", e),
+                    ..ReadinessResponse::ready()
+                }),
+            )
+        }
+    };
+    let unready = match status.health_status {
+        HealthStatus::ReadOnly => Some(("read_only", "read-only mode (resource exhaustion)")),
+        HealthStatus::Critical => Some(("critical", "a resource is at its critical threshold")),
+        HealthStatus::Normal | HealthStatus::Warning => None,
+    };
+    let (code, response) = match unready {
+        Some((status, message)) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            ReadinessResponse {
+                status: status.to_string(),
+                message: message.to_string(),
+                ..ReadinessResponse::ready()
+            },
+        ),
+        None => (StatusCode::OK, ReadinessResponse::ready()),
+    };
+    (
+        code,
+        Json(ReadinessResponse {
+            resources: Some(status),
+            ..response
+        }),
+    )
 }
 
 /// Health check handler
@@ -52,6 +204,67 @@ async fn metrics_handler() -> impl IntoResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::resource_limits::ResourceLimitsConfig;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tempfile::TempDir;
+    use tower::Service;
+
+    /// Status and JSON body of `GET uri`
+    async fn get_json(mut router: Router, uri: &str) -> (StatusCode, Value) {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = router.call(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_healthz_is_live() {
+        let (status, body) = get_json(health_routes(), "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_readyz_follows_resource_manager() {
+        let temp = TempDir::new().unwrap();
+        let resources = Arc::new(ResourceManager::new(
+            ResourceLimitsConfig::default(),
+            temp.path(),
+        ));
+        // Only a full disk reaches the critical threshold
+        resources.set_thresholds(99, 100);
+        let state = Arc::new(ReadinessState::new(Some(temp.path().to_path_buf())));
+        state.set_resource_manager(resources.clone());
+
+        let (status, body) = get_json(readiness_routes(state.clone()), "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ready");
+        assert_eq!(body["resources"]["read_only_mode"], false);
+
+        resources.enter_read_only_mode();
+        let (status, body) = get_json(readiness_routes(state.clone()), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "read_only");
+        assert_eq!(body["resources"]["health_status"], "read_only");
+
+        // /health/ready reports recovery only
+        let (status, _) = get_json(readiness_routes(state.clone()), "/health/ready").await;
+        assert_eq!(status, StatusCode::OK);
+
+        resources.exit_read_only_mode();
+        let (status, _) = get_json(readiness_routes(state), "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_without_resource_manager() {
+        let state = Arc::new(ReadinessState::new(None));
+        let (status, body) = get_json(readiness_routes(state), "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.get("resources").is_none());
+    }
 
     #[test]
     fn test_health_response_serialization() {
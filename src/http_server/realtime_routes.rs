@@ -3,23 +3,31 @@
 //! Endpoints for subscriptions, broadcasting, and WebSocket connections.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
-        Path, State,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
     },
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{delete, get, post},
     Json, Router,
 };
+use futures_util::stream::SplitSink;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use super::config::RealtimeConfig;
+use crate::auth::jwt::{JwtConfig, JwtManager};
+use crate::auth::rls::RlsContext;
+use crate::realtime::websocket::build_subscription;
+use crate::realtime::{RealtimeError, RealtimeHub, RealtimeResult, SequencedEvent};
+
 // ==================
 // Shared State
 // ==================
@@ -28,6 +36,10 @@ use uuid::Uuid;
 pub struct RealtimeState {
     pub active_connections: Arc<RwLock<usize>>,
     pub subscriptions: Arc<RwLock<Vec<SubscriptionInfo>>>,
+    /// Sequenced, resumable delivery for `/realtime/v1`
+    pub hub: Arc<RealtimeHub>,
+    config: RealtimeConfig,
+    jwt: JwtManager,
 }
 
 #[derive(Debug, Clone)]
@@ -40,11 +52,37 @@ pub struct SubscriptionInfo {
 
 impl RealtimeState {
     pub fn new() -> Self {
+        Self::with_config(RealtimeConfig::default())
+    }
+
+    /// Create state for the given `/realtime/v1` configuration
+    pub fn with_config(config: RealtimeConfig) -> Self {
         Self {
             active_connections: Arc::new(RwLock::new(0)),
             subscriptions: Arc::new(RwLock::new(Vec::new())),
+            hub: Arc::new(RealtimeHub::new(config.hub_config())),
+            config,
+            jwt: JwtManager::new(JwtConfig::default()),
         }
     }
+
+    /// Validate access tokens with `jwt`, e.g. the auth service's manager,
+    /// whose key rotations and revocations it then follows
+    pub fn with_jwt_manager(mut self, jwt: JwtManager) -> Self {
+        self.jwt = jwt;
+        self
+    }
+
+    /// Validate an access token and derive the connection's RLS context
+    fn authenticate(&self, token: &str) -> RealtimeResult<RlsContext> {
+        let claims = self
+            .jwt
+            .validate_token(token)
+            .map_err(|e| RealtimeError::AuthError(e.to_string()))?;
+        let user_id = JwtManager::get_user_id(&claims)
+            .map_err(|e| RealtimeError::AuthError(e.to_string()))?;
+        Ok(RlsContext::authenticated(user_id))
+    }
 }
 
 impl Default for RealtimeState {
@@ -155,6 +193,79 @@ impl WebSocketMessage {
     }
 }
 
+/// Query parameters of a `/realtime/v1` connection
+#[derive(Debug, Deserialize)]
+pub struct ConnectParams {
+    /// JWT access token
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Frame sent by a `/realtime/v1` client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientFrame {
+    /// Subscribe to changes of a collection
+    Subscribe {
+        collection: String,
+        #[serde(default)]
+        event_types: Option<Vec<String>>,
+        #[serde(default)]
+        filter: Option<Value>,
+    },
+
+    /// Re-attach a subscription after reconnecting, replaying every
+    /// buffered event after `last_seq`
+    Resume {
+        subscription_id: String,
+        last_seq: u64,
+    },
+
+    /// Remove a subscription
+    Unsubscribe { subscription_id: String },
+}
+
+/// Frame sent to a `/realtime/v1` client
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerFrame {
+    /// Connection accepted
+    Connected { connection_id: String },
+
+    /// Subscription created
+    Subscribed {
+        subscription_id: String,
+        collection: String,
+    },
+
+    /// Subscription re-attached; `replayed` buffered events follow
+    Resumed {
+        subscription_id: String,
+        replayed: usize,
+    },
+
+    /// Subscription removed
+    Unsubscribed { subscription_id: String },
+
+    /// Change event
+    Event(SequencedEvent),
+
+    /// Events were lost to backpressure since the last frame
+    MessagesDropped { count: u64 },
+
+    /// Request failed; `code` is the realtime close code
+    Error { code: u16, message: String },
+}
+
+impl ServerFrame {
+    fn error(error: &RealtimeError) -> Self {
+        ServerFrame::Error {
+            code: error.close_code(),
+            message: error.to_string(),
+        }
+    }
+}
+
 // ==================
 // Realtime Routes
 // ==================
@@ -174,9 +285,199 @@ pub fn realtime_routes(state: Arc<RealtimeState>) -> Router {
         .route("/stats", get(get_stats_handler))
         // WebSocket endpoint
         .route("/ws", get(websocket_handler))
+        // Sequenced, resumable WebSocket endpoint
+        .route("/v1", get(realtime_v1_handler))
         .with_state(state)
 }
 
+// ==================
+// /realtime/v1 Handler
+// ==================
+
+/// Authenticate and upgrade a `/realtime/v1` connection
+async fn realtime_v1_handler(
+    ws: WebSocketUpgrade,
+    Query(params): Query<ConnectParams>,
+    State(state): State<Arc<RealtimeState>>,
+) -> Response {
+    let authenticated = match params.token.as_deref() {
+        Some(token) => state.authenticate(token),
+        None => Err(RealtimeError::AuthenticationRequired),
+    };
+
+    match authenticated {
+        Ok(rls_context) => ws
+            .on_upgrade(move |socket| handle_realtime_v1(socket, state, rls_context))
+            .into_response(),
+        Err(e) => (
+            StatusCode::UNAUTHORIZED,
+            Json(ErrorResponse {
+                error: e.to_string(),
+                code: 401,
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Serve one `/realtime/v1` connection until it closes or stops answering pings
+async fn handle_realtime_v1(socket: WebSocket, state: Arc<RealtimeState>, rls_context: RlsContext) {
+    {
+        let mut count = state.active_connections.write().await;
+        *count += 1;
+    }
+
+    let connection_id = Uuid::new_v4().to_string();
+    let outbox = state.hub.connect(&connection_id);
+    let (mut sender, mut receiver) = socket.split();
+
+    let mut heartbeat = tokio::time::interval(Duration::from_millis(
+        state.config.heartbeat_interval_ms.max(1),
+    ));
+    // The first tick completes immediately
+    heartbeat.tick().await;
+    let max_missed_pongs = state.config.max_missed_pongs.max(1);
+    let mut awaiting_pong = false;
+    let mut missed_pongs = 0;
+
+    let connected = ServerFrame::Connected {
+        connection_id: connection_id.clone(),
+    };
+    if send_frame(&mut sender, &connected).await.is_ok() {
+        'connection: loop {
+            tokio::select! {
+                incoming = receiver.next() => match incoming {
+                    Some(Ok(Message::Text(text))) => {
+                        for frame in handle_v1_frame(&state, &connection_id, &rls_context, &text) {
+                            if send_frame(&mut sender, &frame).await.is_err() {
+                                break 'connection;
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Pong(_))) => {
+                        awaiting_pong = false;
+                        missed_pongs = 0;
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+
+                event = outbox.next() => {
+                    let lost = outbox.take_losses();
+                    if lost > 0
+                        && send_frame(&mut sender, &ServerFrame::MessagesDropped { count: lost })
+                            .await
+                            .is_err()
+                    {
+                        break;
+                    }
+                    if send_frame(&mut sender, &ServerFrame::Event(event)).await.is_err() {
+                        break;
+                    }
+                }
+
+                _ = heartbeat.tick() => {
+                    if awaiting_pong {
+                        missed_pongs += 1;
+                        if missed_pongs >= max_missed_pongs {
+                            let timeout = RealtimeError::ConnectionTimeout;
+                            let _ = sender
+                                .send(Message::Close(Some(CloseFrame {
+                                    code: timeout.close_code(),
+                                    reason: "heartbeat timeout".into(),
+                                })))
+                                .await;
+                            break;
+                        }
+                    }
+                    awaiting_pong = true;
+                    if sender.send(Message::Ping(Vec::new())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // Subscriptions stay in the hub, detached, for a later resume
+    state.hub.disconnect(&connection_id);
+    {
+        let mut count = state.active_connections.write().await;
+        *count = count.saturating_sub(1);
+    }
+}
+
+/// Apply one client frame, returning the frames to send back in order
+fn handle_v1_frame(
+    state: &RealtimeState,
+    connection_id: &str,
+    rls_context: &RlsContext,
+    text: &str,
+) -> Vec<ServerFrame> {
+    let frame = match serde_json::from_str::<ClientFrame>(text) {
+        Ok(frame) => frame,
+        Err(e) => {
+            return vec![ServerFrame::error(&RealtimeError::InvalidMessage(
+                e.to_string(),
+            ))]
+        }
+    };
+
+    let result = match frame {
+        ClientFrame::Subscribe {
+            collection,
+            event_types,
+            filter,
+        } => build_subscription(
+            connection_id,
+            &collection,
+            event_types.as_deref(),
+            filter.as_ref(),
+            rls_context,
+        )
+        .and_then(|subscription| state.hub.subscribe(subscription))
+        .map(|subscription_id| {
+            vec![ServerFrame::Subscribed {
+                subscription_id,
+                collection,
+            }]
+        }),
+
+        // The backlog goes out ahead of anything queued on the outbox, so
+        // the client sees the replay and then live events in sequence
+        ClientFrame::Resume {
+            subscription_id,
+            last_seq,
+        } => state
+            .hub
+            .resume(connection_id, &subscription_id, last_seq, rls_context)
+            .map(|backlog| {
+                let mut frames = vec![ServerFrame::Resumed {
+                    subscription_id,
+                    replayed: backlog.len(),
+                }];
+                frames.extend(backlog.into_iter().map(ServerFrame::Event));
+                frames
+            }),
+
+        ClientFrame::Unsubscribe { subscription_id } => state
+            .hub
+            .unsubscribe(connection_id, &subscription_id)
+            .map(|()| vec![ServerFrame::Unsubscribed { subscription_id }]),
+    };
+
+    result.unwrap_or_else(|e| vec![ServerFrame::error(&e)])
+}
+
+/// Serialize and send one frame
+async fn send_frame(
+    sender: &mut SplitSink<WebSocket, Message>,
+    frame: &ServerFrame,
+) -> Result<(), axum::Error> {
+    let json = serde_json::to_string(frame).map_err(axum::Error::new)?;
+    sender.send(Message::Text(json)).await
+}
+
 // ==================
 // WebSocket Handler
 // ==================
@@ -409,6 +710,37 @@ mod tests {
         assert_eq!(msg.channel, Some("test-channel".to_string()));
     }
 
+    #[test]
+    fn test_v1_frame_wire_format() {
+        let frame: ClientFrame =
+            serde_json::from_str(r#"{"type": "resume", "subscription_id": "s1", "last_seq": 7}"#)
+                .unwrap();
+        assert!(matches!(frame, ClientFrame::Resume { last_seq: 7, .. }));
+
+        let event = crate::realtime::DatabaseEvent::insert(
+            1,
+            "messages".into(),
+            "m1".into(),
+            serde_json::json!({}),
+            None,
+        );
+        let json = serde_json::to_value(ServerFrame::Event(SequencedEvent {
+            subscription_id: "s1".into(),
+            seq: 3,
+            event,
+        }))
+        .unwrap();
+        assert_eq!(json["type"], "event");
+        assert_eq!(json["seq"], 3);
+        assert_eq!(json["event"]["record_id"], "m1");
+
+        let json = serde_json::to_value(ServerFrame::MessagesDropped { count: 2 }).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "messages_dropped", "count": 2})
+        );
+    }
+
     #[test]
     fn test_realtime_state_creation() {
         let state = RealtimeState::new();
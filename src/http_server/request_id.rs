@@ -0,0 +1,94 @@
+//! # Request ID Middleware
+//!
+//! Gives every HTTP request an id so its log lines, operation log entries
+//! and audit records can be correlated.
+//!
+//! # How It Works
+//!
+//! 1. A UUID in the `x-request-id` header is kept; otherwise a new one is
+//!    generated
+//! 2. The request is handled with the id as its current request id
+//! 3. The response carries the id back in `x-request-id`
+
+use axum::{
+    body::Body,
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::observability::{in_request, resolve_request_id, REQUEST_ID_HEADER};
+
+/// Request ID middleware
+pub async fn request_id(request: Request<Body>, next: Next) -> Response {
+    let supplied = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let id = resolve_request_id(supplied);
+
+    let mut response = in_request(id, next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&id.to_string()) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::to_bytes, routing::get, Router};
+    use tower::Service;
+    use uuid::Uuid;
+
+    use crate::observability::current_request_id;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/id",
+                get(|| async {
+                    current_request_id()
+                        .map(|id| id.to_string())
+                        .unwrap_or_default()
+                }),
+            )
+            .layer(axum::middleware::from_fn(request_id))
+    }
+
+    async fn call(header: Option<&str>) -> (String, String) {
+        let mut request = Request::get("/id");
+        if let Some(header) = header {
+            request = request.header(REQUEST_ID_HEADER, header);
+        }
+        // A router is always ready, so it can be called directly
+        let response = app()
+            .call(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let returned = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (returned, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_supplied_id_is_honored() {
+        let id = Uuid::new_v4().to_string();
+        let (returned, seen) = call(Some(&id)).await;
+        assert_eq!(returned, id);
+        assert_eq!(seen, id);
+    }
+
+    #[tokio::test]
+    async fn test_missing_id_is_generated() {
+        let (returned, seen) = call(None).await;
+        assert_eq!(returned, seen);
+        assert!(Uuid::parse_str(&returned).is_ok());
+
+        let (returned, _) = call(Some("not a uuid")).await;
+        assert!(Uuid::parse_str(&returned).is_ok());
+    }
+}
@@ -4,13 +4,21 @@
 //!
 //! This is the unified entry point for the AeroDB dashboard API.
 
+use std::future::Future;
+use std::io;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::Duration;
 
+use axum::http::HeaderName;
 use axum::Router;
 use tokio::net::TcpListener;
 use tower_http::cors::{Any, CorsLayer};
 
+use super::admin_routes::{admin_routes, AdminState};
+use super::api_key_guard::api_key_auth;
 use super::auth_management_routes::auth_management_routes;
 use super::auth_routes::{auth_routes, AuthState};
 use super::backup_routes::{backup_routes, BackupState};
@@ -19,12 +27,25 @@ use super::config::HttpServerConfig;
 use super::control_plane_routes::{control_plane_routes, ControlPlaneState};
 use super::database_routes::{database_routes, DatabaseState};
 use super::functions_routes::{functions_routes, FunctionsState};
-use super::observability_routes::{health_routes, observability_routes};
+use super::observability_routes::{
+    health_routes, observability_routes, readiness_routes, ReadinessState,
+};
 use super::realtime_routes::{realtime_routes, RealtimeState};
+use super::request_id::request_id;
 use super::setup_guard::setup_guard;
 use super::setup_routes::{setup_routes, SetupState};
 use super::settings_routes::{settings_routes, SettingsState};
 use super::storage_routes::{storage_routes, StorageState};
+use crate::auth::api::AuthService;
+use crate::auth::api_key::{ApiKeyRepository, FileApiKeyRepository, InMemoryApiKeyRepository};
+use crate::auth::session::InMemorySessionRepository;
+use crate::auth::user::InMemoryUserRepository;
+use crate::config_reload::ConfigReload;
+use crate::functions::FunctionRegistry;
+use crate::observability::{DatabaseStats, OperationLog, REQUEST_ID_HEADER};
+use crate::realtime::{ChangeStream, RealtimeHub};
+use crate::resource_limits::ResourceManager;
+use crate::transfer::DataTransfer;
 
 /// HTTP Server for AeroDB Dashboard
 ///
@@ -33,6 +54,22 @@ use super::storage_routes::{storage_routes, StorageState};
 pub struct HttpServer {
     config: HttpServerConfig,
     router: Router,
+    /// Hub fed by the WAL change stream once the server starts
+    realtime_hub: Arc<RealtimeHub>,
+    /// Backs `/admin/v1`; holds the config reloader and the data transfer
+    /// once they are attached
+    admin_state: Arc<AdminState>,
+    /// Backs `/storage`; charges upload chunks to the resource manager
+    /// once one is attached
+    storage_state: Arc<StorageState>,
+    /// Backs `/functions`; reserves edge function memory from the resource
+    /// manager and logs invocations once they are attached
+    functions_state: Arc<FunctionsState>,
+    /// Backs `/auth`; its sessions are expired by a scheduled job
+    auth_state: Arc<AuthState>,
+    /// Backs `/health/ready` and `/readyz`; `/readyz` consults the resource
+    /// manager once one is attached
+    readiness_state: Arc<ReadinessState>,
 }
 
 impl HttpServer {
@@ -43,26 +80,118 @@ impl HttpServer {
 
     /// Create a new HTTP server with custom configuration
     pub fn with_config(config: HttpServerConfig) -> Self {
-        let router = Self::build_router(&config);
-        Self { config, router }
+        // Every route validates tokens against the auth service's keyring
+        // and revocation list
+        let auth_state = Arc::new(Self::auth_state(&config));
+        let jwt = auth_state.service.jwt_manager();
+        let realtime_state = Arc::new(
+            RealtimeState::with_config(config.realtime.clone()).with_jwt_manager(jwt.clone()),
+        );
+        let realtime_hub = Arc::clone(&realtime_state.hub);
+        let admin_state = Arc::new(AdminState::new());
+        let storage_state =
+            Arc::new(StorageState::with_default_path().with_config(config.storage.clone()));
+        let functions_state = Arc::new(FunctionsState::new().with_jwt_manager(jwt.clone()));
+        admin_state.set_auth(auth_state.clone());
+        let readiness_state = Arc::new(ReadinessState::new(
+            config.data_dir.as_ref().map(PathBuf::from),
+        ));
+        let router = Self::build_router(
+            &config,
+            realtime_state,
+            admin_state.clone(),
+            storage_state.clone(),
+            functions_state.clone(),
+            auth_state.clone(),
+            readiness_state.clone(),
+        );
+        Self {
+            config,
+            router,
+            realtime_hub,
+            admin_state,
+            storage_state,
+            functions_state,
+            auth_state,
+            readiness_state,
+        }
+    }
+
+    /// Serve `POST /admin/v1/config/reload` from `reload`
+    pub fn with_config_reload(self, reload: Arc<dyn ConfigReload>) -> Self {
+        self.admin_state.set_config_reload(reload);
+        self
+    }
+
+    /// Serve `/admin/v1/collections/{name}/import` and `.../export` from
+    /// `transfer`
+    pub fn with_data_transfer(self, transfer: Arc<dyn DataTransfer>) -> Self {
+        self.admin_state.set_data_transfer(transfer);
+        self
+    }
+
+    /// Serve `GET /admin/v1/collections`, `.../collections/{name}` and
+    /// `/admin/v1/system` from `stats`
+    pub fn with_database_stats(self, stats: Arc<dyn DatabaseStats>) -> Self {
+        self.admin_state.set_database_stats(stats);
+        self
+    }
+
+    /// Charge buffered upload chunks to `resources`' memory budget and
+    /// edge function invocations to its edge function budget, check its
+    /// free disk space before writing upload chunks, and report `/readyz`
+    /// unready while it is read-only or critical
+    pub fn with_resource_manager(self, resources: Arc<ResourceManager>) -> Self {
+        self.storage_state.set_resource_manager(resources.clone());
+        self.functions_state.set_resource_manager(resources.clone());
+        self.readiness_state.set_resource_manager(resources);
+        self
+    }
+
+    /// Record edge function invocations in `log`
+    pub fn with_operation_log(self, log: Arc<OperationLog>) -> Self {
+        self.functions_state.set_operation_log(log);
+        self
+    }
+
+    /// The registry behind `/functions`, where edge functions are registered
+    pub fn function_registry(&self) -> &Arc<FunctionRegistry> {
+        &self.functions_state.registry
+    }
+
+    /// The service behind `/auth`, whose sessions are stored in memory
+    pub fn auth_service(
+        &self,
+    ) -> &Arc<AuthService<InMemoryUserRepository, InMemorySessionRepository>> {
+        &self.auth_state.service
     }
 
     /// Build the combined router with all endpoints
     ///
     /// MANIFESTO ALIGNMENT: Route structure enforces setup discipline.
-    /// - /health and /setup/* are ALWAYS accessible
+    /// - /health, /healthz, /readyz and /setup/* are ALWAYS accessible
     /// - All other routes require setup completion (503 if not ready)
-    fn build_router(config: &HttpServerConfig) -> Router {
+    fn build_router(
+        config: &HttpServerConfig,
+        realtime_state: Arc<RealtimeState>,
+        admin_state: Arc<AdminState>,
+        storage_state: Arc<StorageState>,
+        functions_state: Arc<FunctionsState>,
+        auth_state: Arc<AuthState>,
+        readiness_state: Arc<ReadinessState>,
+    ) -> Router {
         // Create shared states for each module
         let setup_state = Arc::new(SetupState::new());
-        let auth_state = Arc::new(AuthState::new());
-        let storage_state = Arc::new(StorageState::with_default_path());
         let database_state = Arc::new(DatabaseState::new());
-        let functions_state = Arc::new(FunctionsState::new());
-        let realtime_state = Arc::new(RealtimeState::new());
-        let backup_state = Arc::new(BackupState::new());
+        let backup_state = Arc::new(match &config.backup_dir {
+            Some(dir) => BackupState::with_backup_dir(dir),
+            None => BackupState::new(),
+        });
         let cluster_state = Arc::new(ClusterState::new());
-        let control_plane_state = Arc::new(ControlPlaneState::new());
+        let control_plane_state = Arc::new(
+            ControlPlaneState::new()
+                .with_confirmation_ttl(Duration::from_secs(config.confirmation_ttl_secs)),
+        );
         let settings_state = Arc::new(SettingsState::new());
 
         // Configure CORS from config
@@ -72,6 +201,7 @@ impl HttpServer {
                 .allow_origin(Any)
                 .allow_methods(Any)
                 .allow_headers(Any)
+                .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)])
         } else {
             // Use configured origins for production
             use tower_http::cors::AllowOrigin;
@@ -85,6 +215,7 @@ impl HttpServer {
                 .allow_origin(AllowOrigin::list(origins))
                 .allow_methods(Any)
                 .allow_headers(Any)
+                .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)])
         };
 
         // MANIFESTO ALIGNMENT: Protected routes that require setup completion
@@ -95,7 +226,7 @@ impl HttpServer {
             // Auth routes under /auth
             .nest("/auth", auth_routes(auth_state.clone()))
             // Auth management routes (extends /auth with user management, sessions, RLS, etc.)
-            .nest("/auth", auth_management_routes(auth_state))
+            .nest("/auth", auth_management_routes(auth_state.clone()))
             // Observability routes under /observability
             .nest("/observability", observability_routes())
             // Storage routes under /storage
@@ -112,8 +243,12 @@ impl HttpServer {
             .nest("/cluster", cluster_routes(cluster_state))
             // Settings routes under /settings
             .nest("/settings", settings_routes(settings_state))
+            // Admin routes under /admin (config reload)
+            .nest("/admin", admin_routes(admin_state))
             // Control plane routes (multi-tenant management)
             .merge(control_plane_routes(control_plane_state))
+            // Resolve `apikey` headers for every protected route
+            .layer(axum::middleware::from_fn_with_state(auth_state, api_key_auth))
             // MANIFESTO ALIGNMENT: Apply setup guard to ALL protected routes
             .layer(axum::middleware::from_fn_with_state(
                 setup_state.clone(),
@@ -124,12 +259,37 @@ impl HttpServer {
         Router::new()
             // Health check at root level - ALWAYS accessible (no setup required)
             .merge(health_routes())
+            .merge(readiness_routes(readiness_state))
             // Setup routes under /setup - ALWAYS accessible (how else would you complete setup?)
             .nest("/setup", setup_routes(setup_state))
             // Protected routes - require setup completion
             .merge(protected_routes)
             // Apply CORS middleware
             .layer(cors)
+            // Name every request, outermost so everything inside sees the id
+            .layer(axum::middleware::from_fn(request_id))
+    }
+
+    /// Auth state whose API keys are stored under `config.data_dir`
+    ///
+    /// If the key file cannot be read the server still starts, with an
+    /// empty in-memory store: no existing key authenticates.
+    fn auth_state(config: &HttpServerConfig) -> AuthState {
+        let api_keys: Box<dyn ApiKeyRepository> = match &config.data_dir {
+            None => Box::new(InMemoryApiKeyRepository::new()),
+            Some(data_dir) => match FileApiKeyRepository::open(Path::new(data_dir)) {
+                Ok(repository) => Box::new(repository),
+                Err(e) => {
+                    eprintln!("API keys unavailable, starting with none: {}", e);
+                    Box::new(InMemoryApiKeyRepository::new())
+                }
+            },
+        };
+        AuthState::with_api_key_repository(
+            api_keys,
+            config.password_hasher.clone(),
+            config.jwt.clone(),
+        )
     }
 
     /// Get the socket address
@@ -144,6 +304,17 @@ impl HttpServer {
 
     /// Start the HTTP server (async)
     pub async fn start(self) -> Result<(), std::io::Error> {
+        self.start_with_shutdown(std::future::pending()).await
+    }
+
+    /// Start the HTTP server, stopping once `signal` resolves
+    ///
+    /// After `signal` the listener closes and the returned future resolves
+    /// when every in-flight request has completed.
+    pub async fn start_with_shutdown(
+        self,
+        signal: impl Future<Output = ()> + Send + 'static,
+    ) -> Result<(), std::io::Error> {
         let addr: SocketAddr = self
             .config
             .socket_addr()
@@ -153,20 +324,45 @@ impl HttpServer {
         println!("Starting AeroDB HTTP server on {}", addr);
         println!("Dashboard API available at http://{}", addr);
         println!("Health check: http://{}/health", addr);
+        println!("Probes: http://{}/healthz, http://{}/readyz", addr, addr);
         println!("API endpoints:");
         println!("  - /auth/* - Authentication & user management");
         println!("  - /api/* - Database operations");
         println!("  - /storage/* - File storage");
         println!("  - /functions/* - Serverless functions");
-        println!("  - /realtime/* - Subscriptions & WebSocket");
+        println!("  - /realtime/* - Subscriptions & WebSocket (/realtime/v1)");
         println!("  - /backup/* - Backup & restore");
         println!("  - /cluster/* - Cluster management");
         println!("  - /observability/* - Metrics & monitoring");
         println!("  - /v1/tenants/* - Control Plane (multi-tenant)");
+        println!("  - /admin/v1/* - Config reload, collection import/export, statistics");
+
+        if let Some(data_dir) = &self.config.realtime.cdc_data_dir {
+            self.start_change_stream(data_dir)?;
+            println!("Realtime change stream following {}", data_dir);
+        }
 
         let listener = TcpListener::bind(addr).await?;
-        axum::serve(listener, self.router).await?;
+        axum::serve(listener, self.router)
+            .with_graceful_shutdown(signal)
+            .await?;
+
+        Ok(())
+    }
 
+    /// Tail the WAL of `data_dir` on a background thread, publishing its
+    /// changes to `/realtime/v1` subscribers
+    fn start_change_stream(&self, data_dir: &str) -> Result<(), std::io::Error> {
+        let stream = ChangeStream::open(data_dir, self.config.realtime.change_stream_config())
+            .map_err(io::Error::other)?;
+        let hub = Arc::clone(&self.realtime_hub);
+        std::thread::Builder::new()
+            .name("realtime-cdc".to_string())
+            .spawn(move || {
+                if let Err(e) = stream.run(&*hub, &AtomicBool::new(false)) {
+                    eprintln!("Realtime change stream stopped: {}", e);
+                }
+            })?;
         Ok(())
     }
 }
@@ -200,4 +396,25 @@ mod tests {
         let _router = server.router();
         // If we get here, router construction succeeded
     }
+
+    #[test]
+    fn test_service_role_secret_reaches_auth_service() {
+        use crate::auth::jwt::JwtConfig;
+
+        let server = HttpServer::new();
+        assert!(server
+            .auth_service()
+            .jwt_manager()
+            .generate_service_role_token()
+            .is_err());
+
+        let jwt = JwtConfig {
+            service_role_secret: Some("0123456789abcdef0123456789abcdef".to_string()),
+            ..JwtConfig::default()
+        };
+        let server = HttpServer::with_config(HttpServerConfig::default().with_jwt_config(jwt));
+        let manager = server.auth_service().jwt_manager();
+        let token = manager.generate_service_role_token().unwrap();
+        assert!(manager.validate_token(&token).is_ok());
+    }
 }
@@ -2,22 +2,28 @@
 //!
 //! Endpoints for bucket and file management.
 
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use axum::{
-    body::Bytes,
-    extract::{Multipart, Path, Query, State},
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
     http::{HeaderMap, StatusCode},
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
     Json, Router,
 };
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::auth::rls::RlsContext;
 use crate::file_storage::bucket::{Bucket, BucketConfig, BucketPolicy, BucketRegistry};
+use crate::file_storage::config::StorageConfig;
+use crate::file_storage::errors::StorageError;
 use crate::file_storage::file::{FileService, StorageObject};
 use crate::file_storage::local::LocalBackend;
+use crate::file_storage::signed_url::{SignedMethod, SignedUrl};
+use crate::file_storage::upload::Upload;
+use crate::resource_limits::{ResourceError, ResourceManager};
 
 // ==================
 // Shared State
@@ -26,6 +32,7 @@ use crate::file_storage::local::LocalBackend;
 /// Storage state shared across handlers
 pub struct StorageState {
     pub file_service: FileService<LocalBackend>,
+    resources: OnceLock<Arc<ResourceManager>>,
 }
 
 impl StorageState {
@@ -33,6 +40,7 @@ impl StorageState {
         let backend = LocalBackend::new(storage_path.to_path_buf());
         Self {
             file_service: FileService::new(backend),
+            resources: OnceLock::new(),
         }
     }
 
@@ -40,6 +48,37 @@ impl StorageState {
         let storage_path = std::env::temp_dir().join("aerodb_storage");
         Self::new(&storage_path)
     }
+
+    /// Apply the upload limits and URL signing settings of `config`
+    pub fn with_config(mut self, config: StorageConfig) -> Self {
+        self.file_service = self.file_service.with_config(config);
+        self
+    }
+
+    /// Charge upload chunks to `resources`; only the first call has effect
+    pub fn set_resource_manager(&self, resources: Arc<ResourceManager>) {
+        let _ = self.resources.set(resources);
+    }
+
+    /// Write one received chunk of `upload`. Only the chunk, never the
+    /// whole file, is charged to the memory budget and checked against
+    /// free disk space.
+    fn write_chunk(
+        &self,
+        upload: &mut Upload<'_, LocalBackend>,
+        chunk: &[u8],
+    ) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+        let Some(resources) = self.resources.get() else {
+            return upload.write_chunk(chunk).map_err(storage_error);
+        };
+
+        let len = chunk.len() as u64;
+        resources.check_disk_space(len).map_err(resource_error)?;
+        resources.try_allocate_memory(len).map_err(resource_error)?;
+        let result = upload.write_chunk(chunk);
+        resources.release_memory(len);
+        result.map_err(storage_error)
+    }
 }
 
 // ==================
@@ -104,6 +143,7 @@ pub struct FileResponse {
     pub path: String,
     pub size: u64,
     pub content_type: String,
+    pub checksum: String,
     pub created_at: String,
 }
 
@@ -116,6 +156,7 @@ impl FileResponse {
             path: obj.path.clone(),
             size: obj.size,
             content_type: obj.content_type.clone(),
+            checksum: obj.checksum.clone(),
             created_at: obj.created_at.to_rfc3339(),
         }
     }
@@ -141,7 +182,51 @@ pub struct MoveFileRequest {
 
 #[derive(Debug, Deserialize)]
 pub struct CreateSignedUrlRequest {
+    /// Lifetime in seconds, capped by `max_signed_url_expiry_secs`
     pub expires_in: Option<u64>,
+    /// `GET` (default) to download, `PUT` to upload
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Largest upload a `PUT` URL accepts
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+/// Query string of a signed URL
+#[derive(Debug, Default, Deserialize)]
+pub struct SignedUrlQuery {
+    #[serde(default)]
+    pub expires: Option<i64>,
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+impl SignedUrlQuery {
+    /// The signed URL this request carries for `method`, if any
+    fn signed_url(
+        &self,
+        bucket: &str,
+        path: &str,
+        method: SignedMethod,
+    ) -> Option<Result<SignedUrl, StorageError>> {
+        let signature = self.signature.as_deref()?;
+        Some(
+            self.expires
+                .ok_or(StorageError::InvalidSignature)
+                .and_then(|expires| {
+                    SignedUrl::from_request(
+                        bucket,
+                        path,
+                        method,
+                        expires,
+                        self.max_bytes,
+                        signature,
+                    )
+                }),
+        )
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -156,6 +241,7 @@ pub struct UploadResponse {
     pub path: String,
     pub size: u64,
     pub content_type: String,
+    pub checksum: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -192,17 +278,29 @@ pub fn storage_routes(state: Arc<StorageState>) -> Router {
         .route("/buckets/{name}/stats", get(get_bucket_stats_handler))
         // File operations (non-wildcard routes first)
         .route("/buckets/{name}/files", get(list_files_handler))
-        .route("/buckets/{name}/files", post(upload_file_handler))
+        // Uploads are size-limited chunk by chunk, not by buffering the body
+        .route(
+            "/buckets/{name}/files",
+            post(upload_file_handler).layer(DefaultBodyLimit::disable()),
+        )
         .route("/buckets/{name}/files/move", post(move_file_handler))
         // Signed URLs - use separate path prefix to avoid wildcard conflict
         .route(
             "/buckets/{name}/sign/*path",
             post(create_signed_url_handler),
         )
+        .route(
+            "/buckets/{name}/revoke-signed-urls",
+            post(revoke_signed_urls_handler),
+        )
         // Folders
         .route("/buckets/{name}/folders", post(create_folder_handler))
         // Wildcard file routes (must come last)
         .route("/buckets/{name}/files/*path", get(download_file_handler))
+        .route(
+            "/buckets/{name}/files/*path",
+            put(put_file_handler).layer(DefaultBodyLimit::disable()),
+        )
         .route("/buckets/{name}/files/*path", delete(delete_file_handler))
         .with_state(state)
 }
@@ -227,6 +325,30 @@ fn get_rls_context_from_headers(headers: &HeaderMap) -> RlsContext {
     RlsContext::anonymous()
 }
 
+/// Error response carrying the status code of `e`
+fn storage_error(e: StorageError) -> (StatusCode, Json<ErrorResponse>) {
+    let code = e.status_code();
+    (
+        StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        Json(ErrorResponse {
+            error: e.to_string(),
+            code,
+        }),
+    )
+}
+
+/// Error response carrying the status code of `e`
+fn resource_error(e: ResourceError) -> (StatusCode, Json<ErrorResponse>) {
+    let code = e.http_status_code();
+    (
+        StatusCode::from_u16(code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        Json(ErrorResponse {
+            error: e.to_string(),
+            code,
+        }),
+    )
+}
+
 fn parse_bucket_policy(policy_str: &str) -> BucketPolicy {
     match policy_str.to_lowercase().as_str() {
         "public" => BucketPolicy::Public,
@@ -392,9 +514,7 @@ async fn upload_file_handler(
     mut multipart: Multipart,
 ) -> Result<(StatusCode, Json<UploadResponse>), (StatusCode, Json<ErrorResponse>)> {
     let ctx = get_rls_context_from_headers(&headers);
-
-    // Extract file from multipart
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
+    let bad_request = |e: axum::extract::multipart::MultipartError| {
         (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -402,34 +522,26 @@ async fn upload_file_handler(
                 code: 400,
             }),
         )
-    })? {
+    };
+
+    // Extract file from multipart
+    if let Some(mut field) = multipart.next_field().await.map_err(bad_request)? {
         let file_name = field.file_name().unwrap_or("unnamed").to_string();
         let content_type = field
             .content_type()
             .unwrap_or("application/octet-stream")
             .to_string();
-        let data = field.bytes().await.map_err(|e| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                    code: 400,
-                }),
-            )
-        })?;
 
-        let obj = state
+        // Stream the file to staging as it arrives; an error drops the
+        // upload, which discards everything staged so far
+        let mut upload = state
             .file_service
-            .upload(&bucket_name, &file_name, &data, &content_type, &ctx)
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: e.to_string(),
-                        code: 500,
-                    }),
-                )
-            })?;
+            .begin_upload(&bucket_name, &file_name, &content_type, &ctx)
+            .map_err(storage_error)?;
+        while let Some(chunk) = field.chunk().await.map_err(bad_request)? {
+            state.write_chunk(&mut upload, &chunk)?;
+        }
+        let obj = upload.finish().map_err(storage_error)?;
 
         return Ok((
             StatusCode::CREATED,
@@ -438,6 +550,7 @@ async fn upload_file_handler(
                 path: obj.path,
                 size: obj.size,
                 content_type: obj.content_type,
+                checksum: obj.checksum,
             }),
         ));
     }
@@ -455,21 +568,29 @@ async fn download_file_handler(
     State(state): State<Arc<StorageState>>,
     headers: HeaderMap,
     Path((bucket_name, path)): Path<(String, String)>,
+    Query(query): Query<SignedUrlQuery>,
 ) -> Result<(StatusCode, HeaderMap, Bytes), (StatusCode, Json<ErrorResponse>)> {
-    let ctx = get_rls_context_from_headers(&headers);
-
-    let (obj, data) = state
-        .file_service
-        .download(&bucket_name, &path, &ctx)
-        .map_err(|e| {
-            (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: e.to_string(),
-                    code: 404,
-                }),
-            )
-        })?;
+    // A signed URL stands in for the JWT
+    let (obj, data) = match query.signed_url(&bucket_name, &path, SignedMethod::Get) {
+        Some(url) => url
+            .and_then(|url| state.file_service.download_signed(&url))
+            .map_err(storage_error)?,
+        None => {
+            let ctx = get_rls_context_from_headers(&headers);
+            state
+                .file_service
+                .download(&bucket_name, &path, &ctx)
+                .map_err(|e| {
+                    (
+                        StatusCode::NOT_FOUND,
+                        Json(ErrorResponse {
+                            error: e.to_string(),
+                            code: 404,
+                        }),
+                    )
+                })?
+        }
+    };
 
     let mut response_headers = HeaderMap::new();
     response_headers.insert(
@@ -479,10 +600,64 @@ async fn download_file_handler(
             .unwrap_or_else(|_| "application/octet-stream".parse().unwrap()),
     );
     response_headers.insert("content-length", obj.size.to_string().parse().unwrap());
+    if let Ok(etag) = format!("\"{}\"", obj.checksum).parse() {
+        response_headers.insert("etag", etag);
+    }
 
     Ok((StatusCode::OK, response_headers, Bytes::from(data)))
 }
 
+/// Upload the raw request body to `path`, with a JWT or a signed PUT URL
+async fn put_file_handler(
+    State(state): State<Arc<StorageState>>,
+    headers: HeaderMap,
+    Path((bucket_name, path)): Path<(String, String)>,
+    Query(query): Query<SignedUrlQuery>,
+    body: Body,
+) -> Result<(StatusCode, Json<UploadResponse>), (StatusCode, Json<ErrorResponse>)> {
+    let content_type = headers
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream");
+
+    let mut upload = match query.signed_url(&bucket_name, &path, SignedMethod::Put) {
+        Some(url) => url.and_then(|url| state.file_service.begin_signed_upload(&url, content_type)),
+        None => {
+            let ctx = get_rls_context_from_headers(&headers);
+            state
+                .file_service
+                .begin_upload(&bucket_name, &path, content_type, &ctx)
+        }
+    }
+    .map_err(storage_error)?;
+
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: e.to_string(),
+                    code: 400,
+                }),
+            )
+        })?;
+        state.write_chunk(&mut upload, &chunk)?;
+    }
+    let obj = upload.finish().map_err(storage_error)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(UploadResponse {
+            id: obj.id.to_string(),
+            path: obj.path,
+            size: obj.size,
+            content_type: obj.content_type,
+            checksum: obj.checksum,
+        }),
+    ))
+}
+
 async fn delete_file_handler(
     State(state): State<Arc<StorageState>>,
     headers: HeaderMap,
@@ -522,23 +697,57 @@ async fn move_file_handler(
 }
 
 async fn create_signed_url_handler(
-    State(_state): State<Arc<StorageState>>,
+    State(state): State<Arc<StorageState>>,
+    headers: HeaderMap,
     Path((bucket_name, path)): Path<(String, String)>,
     Json(request): Json<CreateSignedUrlRequest>,
 ) -> Result<Json<SignedUrlResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Would use SignedUrlGenerator from file_storage module
-    let expires_in = request.expires_in.unwrap_or(3600);
-    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(expires_in as i64);
+    let ctx = get_rls_context_from_headers(&headers);
+    let method_name = request.method.as_deref().unwrap_or("GET");
+    let method = SignedMethod::parse(method_name).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Cannot sign method {}", method_name),
+                code: 400,
+            }),
+        )
+    })?;
+    let expires_in = request
+        .expires_in
+        .map(|secs| chrono::Duration::seconds(secs.min(i64::MAX as u64) as i64));
+
+    let signed = state
+        .file_service
+        .create_signed_url(
+            &bucket_name,
+            &path,
+            method,
+            expires_in,
+            request.max_bytes,
+            &ctx,
+        )
+        .map_err(storage_error)?;
 
     Ok(Json(SignedUrlResponse {
-        url: format!(
-            "/storage/v1/{}/{}?token=signed_placeholder",
-            bucket_name, path
-        ),
-        expires_at: expires_at.to_rfc3339(),
+        url: signed.to_url("/storage"),
+        expires_at: signed.expires_at.to_rfc3339(),
     }))
 }
 
+async fn revoke_signed_urls_handler(
+    State(state): State<Arc<StorageState>>,
+    headers: HeaderMap,
+    Path(bucket_name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let ctx = get_rls_context_from_headers(&headers);
+    state
+        .file_service
+        .revoke_signed_urls(&bucket_name, &ctx)
+        .map_err(storage_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 async fn create_folder_handler(
     State(_state): State<Arc<StorageState>>,
     Path(bucket_name): Path<String>,
@@ -555,6 +764,321 @@ async fn create_folder_handler(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::body::Body;
+    use axum::extract::FromRequest;
+    use axum::http::Request;
+    use axum::http::Uri;
+    use futures_util::stream;
+
+    use crate::file_storage::bucket::BucketConfig;
+    use crate::resource_limits::ResourceLimitsConfig;
+
+    const BOUNDARY: &str = "aerodb-boundary";
+
+    fn test_state(root: &std::path::Path, config: StorageConfig) -> Arc<StorageState> {
+        let state = StorageState::new(root).with_config(config);
+        let config = BucketConfig {
+            policy: BucketPolicy::Public,
+            ..BucketConfig::default()
+        };
+        state
+            .file_service
+            .buckets()
+            .create("test".to_string(), None, config)
+            .unwrap();
+        Arc::new(state)
+    }
+
+    fn service_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer service".parse().unwrap());
+        headers
+    }
+
+    /// Multipart request whose single file part is streamed as `body`.
+    /// Each chunk arrives separately, as from a socket: the parser cannot
+    /// read ahead past what has arrived.
+    async fn multipart<S>(body: S) -> Multipart
+    where
+        S: futures_util::Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
+    {
+        let body = body.then(|chunk| async move {
+            tokio::task::yield_now().await;
+            chunk
+        });
+        let head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"big.bin\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n",
+            BOUNDARY
+        );
+        let body = stream::once(async move { Ok(Bytes::from(head)) }).chain(body);
+        let request = Request::builder()
+            .method("POST")
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .body(Body::from_stream(body))
+            .unwrap();
+        Multipart::from_request(request, &()).await.unwrap()
+    }
+
+    /// `data` followed by the closing boundary
+    fn complete_part(data: &'static [u8]) -> Vec<Result<Bytes, std::io::Error>> {
+        vec![
+            Ok(Bytes::from_static(data)),
+            Ok(Bytes::from(format!("\r\n--{}--\r\n", BOUNDARY))),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_upload_streams_and_reports_checksum() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let state = test_state(temp.path(), StorageConfig::default());
+
+        let body = multipart(stream::iter(complete_part(b"Hello, World!"))).await;
+        let (status, Json(response)) = upload_file_handler(
+            State(state.clone()),
+            service_headers(),
+            Path("test".to_string()),
+            body,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(response.size, 13);
+        assert_eq!(
+            response.checksum,
+            StorageObject::calculate_checksum(b"Hello, World!")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_over_limit_upload_rejected_mid_stream() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let config = StorageConfig {
+            max_upload_bytes: 64 * 1024,
+            ..StorageConfig::default()
+        };
+        let state = test_state(temp.path(), config);
+
+        // The body never ends: only an abort mid-stream answers at all
+        let endless = stream::repeat_with(|| Ok(Bytes::from(vec![0u8; 4096])));
+        let err = upload_file_handler(
+            State(state.clone()),
+            service_headers(),
+            Path("test".to_string()),
+            multipart(endless).await,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.0, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let ctx = RlsContext::service_role();
+        assert!(state
+            .file_service
+            .list("test", "", &ctx)
+            .unwrap()
+            .is_empty());
+        let leftover: Vec<_> = walk(temp.path());
+        assert!(leftover.is_empty(), "partial files left: {:?}", leftover);
+    }
+
+    #[tokio::test]
+    async fn test_upload_charges_only_chunks_in_flight() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let state = test_state(temp.path(), StorageConfig::default());
+        let config = ResourceLimitsConfig {
+            min_free_disk_bytes: 0,
+            max_memory_bytes: 1024,
+            ..ResourceLimitsConfig::default()
+        };
+        let resources = Arc::new(ResourceManager::new(config, temp.path()));
+        state.set_resource_manager(resources.clone());
+
+        // 4KB in 512-byte chunks fits a 1KB memory budget
+        let chunks = (0..8).map(|_| Ok(Bytes::from(vec![7u8; 512])));
+        let tail = stream::iter(complete_part(b""));
+        let body = multipart(stream::iter(chunks).chain(tail)).await;
+        let (status, Json(response)) = upload_file_handler(
+            State(state),
+            service_headers(),
+            Path("test".to_string()),
+            body,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(response.size, 4096);
+        assert_eq!(resources.memory_tracker().current(), 0);
+    }
+
+    /// Sign `method` on `path` in the test bucket, as the service role
+    async fn sign(
+        state: &Arc<StorageState>,
+        path: &str,
+        method: &str,
+        max_bytes: Option<u64>,
+    ) -> Query<SignedUrlQuery> {
+        let Json(response) = create_signed_url_handler(
+            State(state.clone()),
+            service_headers(),
+            Path(("test".to_string(), path.to_string())),
+            Json(CreateSignedUrlRequest {
+                expires_in: Some(60),
+                method: Some(method.to_string()),
+                max_bytes,
+            }),
+        )
+        .await
+        .unwrap();
+        let uri: Uri = response.url.parse().unwrap();
+        assert_eq!(uri.path(), format!("/storage/buckets/test/files/{}", path));
+        Query::try_from_uri(&uri).unwrap()
+    }
+
+    async fn signed_get(
+        state: &Arc<StorageState>,
+        path: &str,
+        query: Query<SignedUrlQuery>,
+    ) -> Result<Bytes, StatusCode> {
+        download_file_handler(
+            State(state.clone()),
+            HeaderMap::new(),
+            Path(("test".to_string(), path.to_string())),
+            query,
+        )
+        .await
+        .map(|(_, _, data)| data)
+        .map_err(|(status, _)| status)
+    }
+
+    #[tokio::test]
+    async fn test_signed_get_without_jwt() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let state = test_state(temp.path(), StorageConfig::default());
+        let ctx = RlsContext::service_role();
+        state
+            .file_service
+            .upload("test", "a.txt", b"secret a", "text/plain", &ctx)
+            .unwrap();
+        state
+            .file_service
+            .upload("test", "b.txt", b"secret b", "text/plain", &ctx)
+            .unwrap();
+
+        let Query(query) = sign(&state, "a.txt", "GET", None).await;
+        let data = signed_get(&state, "a.txt", Query(query)).await.unwrap();
+        assert_eq!(&data[..], b"secret a");
+
+        // The signature does not carry over to another path
+        let Query(query) = sign(&state, "a.txt", "GET", None).await;
+        let status = signed_get(&state, "b.txt", Query(query)).await.unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // Nor past its expiry
+        let Query(mut query) = sign(&state, "a.txt", "GET", None).await;
+        query.expires = Some(chrono::Utc::now().timestamp() - 1);
+        let status = signed_get(&state, "a.txt", Query(query)).await.unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // Nor to an upload
+        let Query(query) = sign(&state, "a.txt", "GET", None).await;
+        let err = put_file_handler(
+            State(state.clone()),
+            HeaderMap::new(),
+            Path(("test".to_string(), "a.txt".to_string())),
+            Query(query),
+            Body::from("overwritten"),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.0, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_signed_put_enforces_max_bytes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let state = test_state(temp.path(), StorageConfig::default());
+
+        let put = |query, body: &'static [u8]| {
+            put_file_handler(
+                State(state.clone()),
+                HeaderMap::new(),
+                Path(("test".to_string(), "up.bin".to_string())),
+                query,
+                Body::from(body),
+            )
+        };
+
+        let (status, Json(response)) =
+            put(sign(&state, "up.bin", "PUT", Some(8)).await, b"12345678")
+                .await
+                .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(response.size, 8);
+
+        let err = put(sign(&state, "up.bin", "PUT", Some(8)).await, b"123456789")
+            .await
+            .unwrap_err();
+        assert_eq!(err.0, StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Raising the signed limit invalidates the signature
+        let Query(mut query) = sign(&state, "up.bin", "PUT", Some(8)).await;
+        query.max_bytes = Some(1024);
+        let err = put(Query(query), b"123456789").await.unwrap_err();
+        assert_eq!(err.0, StatusCode::FORBIDDEN);
+
+        let ctx = RlsContext::service_role();
+        let (obj, _) = state.file_service.download("test", "up.bin", &ctx).unwrap();
+        assert_eq!(obj.size, 8);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_invalidates_signed_urls() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let state = test_state(temp.path(), StorageConfig::default());
+        let ctx = RlsContext::service_role();
+        state
+            .file_service
+            .upload("test", "a.txt", b"data", "text/plain", &ctx)
+            .unwrap();
+
+        let Query(issued) = sign(&state, "a.txt", "GET", None).await;
+        let status = revoke_signed_urls_handler(
+            State(state.clone()),
+            service_headers(),
+            Path("test".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let status = signed_get(&state, "a.txt", Query(issued))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // URLs signed after the revocation work
+        let query = sign(&state, "a.txt", "GET", None).await;
+        assert!(signed_get(&state, "a.txt", query).await.is_ok());
+    }
+
+    /// Every file under `root`, at any depth
+    fn walk(root: &std::path::Path) -> Vec<std::path::PathBuf> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(root).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files.extend(walk(&path));
+            } else {
+                files.push(path);
+            }
+        }
+        files
+    }
 
     #[test]
     fn test_parse_bucket_policy() {
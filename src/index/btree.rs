@@ -3,13 +3,17 @@
 //! Indexes use BTreeMap<IndexKey, Vec<StorageOffset>> for deterministic ordering.
 //! Offsets are always sorted ascending.
 
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::ops::Bound;
 
 /// Index key representing a serialized field value.
 ///
 /// Supports String, Int (i64), Float (f64 bits for ordering), Bool.
-/// Ordering is deterministic: Bool < Int < Float < String.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Ordering is deterministic: Bool < numbers < String. Int and Float compare
+/// numerically with each other; integral floats from JSON are stored as Int
+/// so that `1` and `1.0` are the same key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum IndexKey {
     /// Boolean value (false < true)
     Bool(bool),
@@ -61,7 +65,12 @@ impl IndexKey {
                 if let Some(i) = n.as_i64() {
                     Some(IndexKey::from_int(i))
                 } else if let Some(f) = n.as_f64() {
-                    Some(IndexKey::from_float(f))
+                    // Integral floats share a key with the equal integer
+                    if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 {
+                        Some(IndexKey::from_int(f as i64))
+                    } else {
+                        Some(IndexKey::from_float(f))
+                    }
                 } else {
                     None
                 }
@@ -70,6 +79,49 @@ impl IndexKey {
             _ => None, // Arrays and objects not indexed
         }
     }
+
+    /// Decode the f64 stored in a Float key
+    fn float_value(bits: u64) -> f64 {
+        let raw = if (bits >> 63) == 1 {
+            bits ^ (1 << 63) // Was positive: restore sign bit
+        } else {
+            !bits // Was negative: flip all bits back
+        };
+        f64::from_bits(raw)
+    }
+
+    /// Type rank used to order keys of different kinds
+    fn rank(&self) -> u8 {
+        match self {
+            IndexKey::Bool(_) => 0,
+            IndexKey::Int(_) | IndexKey::Float(_) => 1,
+            IndexKey::String(_) => 2,
+        }
+    }
+}
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (IndexKey::Bool(a), IndexKey::Bool(b)) => a.cmp(b),
+            (IndexKey::Int(a), IndexKey::Int(b)) => a.cmp(b),
+            (IndexKey::Float(a), IndexKey::Float(b)) => a.cmp(b),
+            (IndexKey::String(a), IndexKey::String(b)) => a.cmp(b),
+            (IndexKey::Int(i), IndexKey::Float(f)) => {
+                let f = Self::float_value(*f);
+                // Ties (only possible through precision loss) put Int first
+                (*i as f64).total_cmp(&f).then(Ordering::Less)
+            }
+            (IndexKey::Float(_), IndexKey::Int(_)) => other.cmp(self).reverse(),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 /// Storage offset type
@@ -80,6 +132,8 @@ pub type StorageOffset = u64;
 pub struct IndexTree {
     /// Maps key values to sorted lists of offsets
     tree: BTreeMap<IndexKey, Vec<StorageOffset>>,
+    /// Key order of the index: largest key first
+    descending: bool,
 }
 
 impl IndexTree {
@@ -87,6 +141,15 @@ impl IndexTree {
     pub fn new() -> Self {
         Self {
             tree: BTreeMap::new(),
+            descending: false,
+        }
+    }
+
+    /// Creates a new empty index tree whose key order is largest first
+    pub fn new_descending() -> Self {
+        Self {
+            tree: BTreeMap::new(),
+            descending: true,
         }
     }
 
@@ -124,37 +187,68 @@ impl IndexTree {
         self.tree.get(key).cloned().unwrap_or_default()
     }
 
-    /// Lookup offsets in a range [min, max] (inclusive).
+    /// Lookup offsets with keys between `lower` and `upper`, each bound
+    /// inclusive, exclusive or unbounded.
     ///
     /// Returns offsets sorted ascending.
-    /// If min is None, starts from the beginning.
-    /// If max is None, goes to the end.
     pub fn lookup_range(
         &self,
-        min: Option<&IndexKey>,
-        max: Option<&IndexKey>,
+        lower: Bound<&IndexKey>,
+        upper: Bound<&IndexKey>,
     ) -> Vec<StorageOffset> {
-        use std::ops::Bound;
+        let mut result = self.scan_ordered(lower, upper, false);
 
-        let min_bound: Bound<&IndexKey> = match min {
-            Some(k) => Bound::Included(k),
-            None => Bound::Unbounded,
-        };
-        let max_bound: Bound<&IndexKey> = match max {
-            Some(k) => Bound::Included(k),
-            None => Bound::Unbounded,
-        };
+        // Sort to ensure deterministic order even when combining multiple keys
+        result.sort();
+        result
+    }
 
+    /// Lookup offsets with keys between `lower` and `upper` in key order,
+    /// largest key first if `descending`. Only keys in the range are
+    /// visited.
+    ///
+    /// Scanning in the tree's own key order keeps offsets under one key
+    /// ascending; scanning against it reverses the whole sequence.
+    pub fn scan_ordered(
+        &self,
+        lower: Bound<&IndexKey>,
+        upper: Bound<&IndexKey>,
+        descending: bool,
+    ) -> Vec<StorageOffset> {
+        // BTreeMap::range panics on an empty or inverted range
+        if Self::is_empty_range(lower, upper) {
+            return Vec::new();
+        }
+
+        let range = self.tree.range((lower, upper));
         let mut result = Vec::new();
-        for (_, offsets) in self.tree.range((min_bound, max_bound)) {
-            result.extend(offsets);
+        if self.descending {
+            for (_, offsets) in range.rev() {
+                result.extend(offsets);
+            }
+        } else {
+            for (_, offsets) in range {
+                result.extend(offsets);
+            }
         }
 
-        // Sort to ensure deterministic order even when combining multiple keys
-        result.sort();
+        if descending != self.descending {
+            result.reverse();
+        }
         result
     }
 
+    /// Whether no key lies between `lower` and `upper`
+    fn is_empty_range(lower: Bound<&IndexKey>, upper: Bound<&IndexKey>) -> bool {
+        match (lower, upper) {
+            (Bound::Included(l), Bound::Included(u)) => l > u,
+            (Bound::Included(l), Bound::Excluded(u))
+            | (Bound::Excluded(l), Bound::Included(u))
+            | (Bound::Excluded(l), Bound::Excluded(u)) => l >= u,
+            _ => false,
+        }
+    }
+
     /// Clear all entries
     pub fn clear(&mut self) {
         self.tree.clear();
@@ -174,6 +268,7 @@ impl IndexTree {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::ops::Bound::{Excluded, Included, Unbounded};
 
     #[test]
     fn test_key_ordering() {
@@ -193,6 +288,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_mixed_number_ordering() {
+        let keys = vec![
+            IndexKey::from_float(-2.5),
+            IndexKey::from_int(-2),
+            IndexKey::from_float(-0.5),
+            IndexKey::from_int(0),
+            IndexKey::from_float(0.5),
+            IndexKey::from_int(1),
+            IndexKey::from_float(1.5),
+            IndexKey::from_int(100),
+            IndexKey::from_float(1e300),
+        ];
+
+        for i in 1..keys.len() {
+            assert!(keys[i - 1] < keys[i], "{:?} < {:?}", keys[i - 1], keys[i]);
+            assert!(keys[i] > keys[i - 1]);
+        }
+
+        // Integral floats are the same key as the integer
+        assert_eq!(
+            IndexKey::from_json(&serde_json::json!(3.0)),
+            IndexKey::from_json(&serde_json::json!(3))
+        );
+    }
+
     #[test]
     fn test_insert_and_lookup() {
         let mut tree = IndexTree::new();
@@ -248,8 +369,71 @@ mod tests {
         tree.insert(IndexKey::from_int(4), 400);
         tree.insert(IndexKey::from_int(5), 500);
 
-        let offsets = tree.lookup_range(Some(&IndexKey::from_int(2)), Some(&IndexKey::from_int(4)));
+        let (two, four) = (IndexKey::from_int(2), IndexKey::from_int(4));
+        let offsets = tree.lookup_range(Included(&two), Included(&four));
         assert_eq!(offsets, vec![200, 300, 400]);
+
+        let offsets = tree.lookup_range(Excluded(&two), Excluded(&four));
+        assert_eq!(offsets, vec![300]);
+        let offsets = tree.lookup_range(Excluded(&two), Unbounded);
+        assert_eq!(offsets, vec![300, 400, 500]);
+
+        // Empty and inverted ranges hold nothing
+        assert!(tree.lookup_range(Excluded(&two), Excluded(&two)).is_empty());
+        assert!(tree
+            .lookup_range(Included(&four), Included(&two))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_scan_ordered() {
+        let mut tree = IndexTree::new();
+        tree.insert(IndexKey::from_int(30), 1);
+        tree.insert(IndexKey::from_int(10), 5);
+        tree.insert(IndexKey::from_int(20), 3);
+        tree.insert(IndexKey::from_int(20), 2);
+
+        assert_eq!(
+            tree.scan_ordered(Unbounded, Unbounded, false),
+            vec![5, 2, 3, 1]
+        );
+        assert_eq!(
+            tree.scan_ordered(Unbounded, Unbounded, true),
+            vec![1, 3, 2, 5]
+        );
+
+        let min = IndexKey::from_int(15);
+        assert_eq!(
+            tree.scan_ordered(Included(&min), Unbounded, true),
+            vec![1, 3, 2]
+        );
+        assert_eq!(tree.lookup_range(Included(&min), Unbounded), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_scan_ordered_descending_tree() {
+        let mut tree = IndexTree::new_descending();
+        tree.insert(IndexKey::from_int(30), 1);
+        tree.insert(IndexKey::from_int(10), 5);
+        tree.insert(IndexKey::from_int(20), 3);
+        tree.insert(IndexKey::from_int(20), 2);
+
+        // Offsets under one key stay ascending in the tree's own order
+        assert_eq!(
+            tree.scan_ordered(Unbounded, Unbounded, true),
+            vec![1, 2, 3, 5]
+        );
+        assert_eq!(
+            tree.scan_ordered(Unbounded, Unbounded, false),
+            vec![5, 3, 2, 1]
+        );
+
+        let max = IndexKey::from_int(25);
+        assert_eq!(
+            tree.scan_ordered(Unbounded, Included(&max), true),
+            vec![2, 3, 5]
+        );
+        assert_eq!(tree.lookup_range(Unbounded, Included(&max)), vec![2, 3, 5]);
     }
 
     #[test]
@@ -0,0 +1,188 @@
+//! Compound (multi-field) indexes
+//!
+//! A compound index over fields `(f1, .., fn)` orders documents by the tuple
+//! of their values for those fields, so it can serve equality on any leading
+//! run of fields followed by a range on the next one.
+//!
+//! # Key layout
+//!
+//! Each entry key is one component per field. A document is only indexed if
+//! its leading field has an indexable value; later fields that are absent,
+//! `null`, arrays or objects are stored as a missing component, which sorts
+//! before every value and never matches an equality or range on that field.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+
+use serde_json::Value;
+
+use super::btree::{IndexKey, StorageOffset};
+
+/// One entry key: a component per indexed field
+type CompoundKey = Vec<Option<IndexKey>>;
+
+/// Ordered tuple index over several fields
+#[derive(Debug)]
+pub(crate) struct CompoundIndex {
+    /// Indexed fields, in key order
+    fields: Vec<String>,
+    /// Tuple key -> sorted offsets
+    tree: BTreeMap<CompoundKey, Vec<StorageOffset>>,
+    /// Document ID -> entry it currently occupies
+    entries: HashMap<String, (CompoundKey, StorageOffset)>,
+}
+
+impl CompoundIndex {
+    /// Create an empty compound index
+    pub(crate) fn new(fields: Vec<String>) -> Self {
+        Self {
+            fields,
+            tree: BTreeMap::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Indexed fields, in key order
+    pub(crate) fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Number of indexed documents
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Build the tuple key for a document body
+    fn key_of(&self, body: &Value) -> Option<CompoundKey> {
+        let key: CompoundKey = self
+            .fields
+            .iter()
+            .map(|field| body.get(field).and_then(IndexKey::from_json))
+            .collect();
+        key[0].is_some().then_some(key)
+    }
+
+    /// Record the current version of a document, replacing any earlier one
+    pub(crate) fn insert(&mut self, doc_id: &str, body: &Value, offset: StorageOffset) {
+        self.remove(doc_id);
+        let Some(key) = self.key_of(body) else {
+            return;
+        };
+
+        let offsets = self.tree.entry(key.clone()).or_default();
+        if let Err(pos) = offsets.binary_search(&offset) {
+            offsets.insert(pos, offset);
+        }
+        self.entries.insert(doc_id.to_string(), (key, offset));
+    }
+
+    /// Remove whatever entry a document occupies
+    pub(crate) fn remove(&mut self, doc_id: &str) {
+        let Some((key, offset)) = self.entries.remove(doc_id) else {
+            return;
+        };
+
+        if let Some(offsets) = self.tree.get_mut(&key) {
+            if let Ok(pos) = offsets.binary_search(&offset) {
+                offsets.remove(pos);
+            }
+            if offsets.is_empty() {
+                self.tree.remove(&key);
+            }
+        }
+    }
+
+    /// Drop all entries
+    pub(crate) fn clear(&mut self) {
+        self.tree.clear();
+        self.entries.clear();
+    }
+
+    /// Look up documents whose leading fields equal `prefix` and whose next
+    /// field falls within `lower`..`upper`.
+    ///
+    /// With both bounds unbounded this is a pure equality prefix lookup.
+    /// Returns offsets sorted ascending.
+    pub(crate) fn lookup_prefix(
+        &self,
+        prefix: &[IndexKey],
+        lower: Bound<&IndexKey>,
+        upper: Bound<&IndexKey>,
+    ) -> Vec<StorageOffset> {
+        let mut offsets = self.scan_prefix(prefix, lower, upper, false);
+        offsets.sort_unstable();
+        offsets
+    }
+
+    /// Same entries as `lookup_prefix`, in key order: by the field after the
+    /// prefix, then the remaining fields. With `descending` the sequence is
+    /// reversed.
+    pub(crate) fn scan_prefix(
+        &self,
+        prefix: &[IndexKey],
+        lower: Bound<&IndexKey>,
+        upper: Bound<&IndexKey>,
+        descending: bool,
+    ) -> Vec<StorageOffset> {
+        let depth = prefix.len();
+        let ranged = !matches!((lower, upper), (Bound::Unbounded, Bound::Unbounded));
+        if depth > self.fields.len() || (ranged && depth == self.fields.len()) {
+            return Vec::new();
+        }
+
+        // Every key with this prefix sorts at or after the bare prefix, and
+        // every key meeting the lower bound at or after prefix + [min]
+        let mut start: CompoundKey = prefix.iter().cloned().map(Some).collect();
+        if let Bound::Included(min) | Bound::Excluded(min) = lower {
+            start.push(Some(min.clone()));
+        }
+
+        let mut offsets: Vec<StorageOffset> = Vec::new();
+        for (key, entry_offsets) in self.tree.range(start..) {
+            let matches_prefix = key[..depth]
+                .iter()
+                .zip(prefix)
+                .all(|(component, want)| component.as_ref() == Some(want));
+            if !matches_prefix {
+                break;
+            }
+
+            if ranged {
+                let Some(value) = key[depth].as_ref() else {
+                    continue; // Missing component never satisfies a range
+                };
+                if !above(value, lower) {
+                    continue;
+                }
+                if !below(value, upper) {
+                    break;
+                }
+            }
+
+            offsets.extend(entry_offsets);
+        }
+
+        if descending {
+            offsets.reverse();
+        }
+        offsets
+    }
+}
+
+/// Whether `value` satisfies a lower bound
+fn above(value: &IndexKey, lower: Bound<&IndexKey>) -> bool {
+    match lower {
+        Bound::Included(min) => value >= min,
+        Bound::Excluded(min) => value > min,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Whether `value` satisfies an upper bound
+fn below(value: &IndexKey, upper: Bound<&IndexKey>) -> bool {
+    match upper {
+        Bound::Included(max) => value <= max,
+        Bound::Excluded(max) => value < max,
+        Bound::Unbounded => true,
+    }
+}
@@ -3,12 +3,15 @@
 //! Error codes:
 //! - AERO_INDEX_BUILD_FAILED (FATAL)
 //! - AERO_DATA_CORRUPTION (FATAL)
+//! - AERO_INDEX_UNIQUE_VIOLATION (REJECT)
 
 use std::fmt;
 
 /// Severity levels for index errors
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Severity {
+    /// Client request rejected
+    Reject,
     /// System must halt immediately
     Fatal,
 }
@@ -16,6 +19,7 @@ pub enum Severity {
 impl fmt::Display for Severity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            Severity::Reject => write!(f, "REJECT"),
             Severity::Fatal => write!(f, "FATAL"),
         }
     }
@@ -28,6 +32,8 @@ pub enum IndexErrorCode {
     AeroIndexBuildFailed,
     /// Data corruption detected during rebuild
     AeroDataCorruption,
+    /// Write or index build would give two documents the same unique key
+    AeroIndexUniqueViolation,
 }
 
 impl IndexErrorCode {
@@ -36,12 +42,18 @@ impl IndexErrorCode {
         match self {
             IndexErrorCode::AeroIndexBuildFailed => "AERO_INDEX_BUILD_FAILED",
             IndexErrorCode::AeroDataCorruption => "AERO_DATA_CORRUPTION",
+            IndexErrorCode::AeroIndexUniqueViolation => "AERO_INDEX_UNIQUE_VIOLATION",
         }
     }
 
     /// Returns the severity level for this error
     pub fn severity(&self) -> Severity {
-        Severity::Fatal // All index errors are FATAL
+        match self {
+            IndexErrorCode::AeroIndexUniqueViolation => Severity::Reject,
+            IndexErrorCode::AeroIndexBuildFailed | IndexErrorCode::AeroDataCorruption => {
+                Severity::Fatal
+            }
+        }
     }
 
     /// Returns the invariant violated by this error
@@ -49,6 +61,7 @@ impl IndexErrorCode {
         match self {
             IndexErrorCode::AeroIndexBuildFailed => "R1",
             IndexErrorCode::AeroDataCorruption => "K2",
+            IndexErrorCode::AeroIndexUniqueViolation => "S2",
         }
     }
 }
@@ -68,6 +81,8 @@ pub struct IndexError {
     message: String,
     /// Offset if applicable
     offset: Option<u64>,
+    /// Document already holding the value, for a unique violation
+    conflicting_document: Option<String>,
 }
 
 impl IndexError {
@@ -77,6 +92,7 @@ impl IndexError {
             code: IndexErrorCode::AeroIndexBuildFailed,
             message: reason.into(),
             offset: None,
+            conflicting_document: None,
         }
     }
 
@@ -86,6 +102,39 @@ impl IndexError {
             code: IndexErrorCode::AeroDataCorruption,
             message: format!("Data corruption at offset {}: {}", offset, reason.into()),
             offset: Some(offset),
+            conflicting_document: None,
+        }
+    }
+
+    /// Create a unique violation error for a write whose value is held by
+    /// `conflicting_document`
+    pub fn unique_violation(
+        index: &str,
+        value: &serde_json::Value,
+        conflicting_document: &str,
+    ) -> Self {
+        Self {
+            code: IndexErrorCode::AeroIndexUniqueViolation,
+            message: format!(
+                "Unique index '{}' already contains value {} (document '{}')",
+                index, value, conflicting_document
+            ),
+            offset: None,
+            conflicting_document: Some(conflicting_document.to_string()),
+        }
+    }
+
+    /// Create a unique violation error for an index build over existing data
+    pub fn unique_build_violation(index: &str, duplicate_keys: &[String]) -> Self {
+        Self {
+            code: IndexErrorCode::AeroIndexUniqueViolation,
+            message: format!(
+                "Cannot build unique index '{}': duplicate values {}",
+                index,
+                duplicate_keys.join(", ")
+            ),
+            offset: None,
+            conflicting_document: None,
         }
     }
 
@@ -114,9 +163,15 @@ impl IndexError {
         self.offset
     }
 
+    /// Returns the document already holding the value, for a unique
+    /// violation raised by a write
+    pub fn conflicting_document(&self) -> Option<&str> {
+        self.conflicting_document.as_deref()
+    }
+
     /// Returns whether this is a fatal error
     pub fn is_fatal(&self) -> bool {
-        true // All index errors are FATAL
+        self.severity() == Severity::Fatal
     }
 }
 
@@ -156,7 +211,18 @@ mod tests {
     }
 
     #[test]
-    fn test_all_errors_are_fatal() {
+    fn test_unique_violation_is_rejected_not_fatal() {
+        let err = IndexError::unique_violation("users_email", &serde_json::json!("a@x.io"), "u1");
+        assert_eq!(err.code().code(), "AERO_INDEX_UNIQUE_VIOLATION");
+        assert_eq!(err.severity(), Severity::Reject);
+        assert!(!err.is_fatal());
+        assert!(err.message().contains("users_email"));
+        assert!(err.message().contains("\"a@x.io\""));
+        assert_eq!(err.conflicting_document(), Some("u1"));
+    }
+
+    #[test]
+    fn test_build_and_corruption_errors_are_fatal() {
         let codes = [
             IndexErrorCode::AeroIndexBuildFailed,
             IndexErrorCode::AeroDataCorruption,
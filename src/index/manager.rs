@@ -5,17 +5,31 @@
 //! # API
 //!
 //! - `rebuild_from_storage(reader)` - Rebuild all indexes
+//! - `rebuild_with_progress(reader, callback)` - Rebuild, reporting progress
 //! - `apply_write(doc, offset)` - Update index after storage write
 //! - `apply_delete(doc_id)` - Update index after delete
+//! - `create_unique_index(name, field, reader)` - Build a unique index
+//! - `check_unique(doc_id, body)` - Reject a write before it reaches the WAL
+//! - `unique_owner(field, value)` - Document holding a unique value
+//! - `create_compound_index(name, fields, reader)` - Build a compound index
+//! - `lookup_compound(name, prefix, lower, upper, limit)` - Prefix lookup
 //! - `lookup_eq(field, value)` - Exact match lookup
-//! - `lookup_range(field, min, max, limit)` - Range lookup
+//! - `lookup_range(field, lower, upper, limit)` - Range lookup between
+//!   inclusive or exclusive bounds
+//! - `with_index_options(field, options)` - Index a field with a key order
+//!   other than ascending binary
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
+use std::time::{Duration, Instant};
 
 use serde_json::Value;
 
 use super::btree::{IndexKey, IndexTree, StorageOffset};
+use super::compound::CompoundIndex;
 use super::errors::{IndexError, IndexResult};
+use super::options::IndexOptions;
+use super::unique::UniqueIndex;
 
 /// Document info extracted from storage for indexing
 #[derive(Debug, Clone)]
@@ -46,6 +60,28 @@ pub trait StorageScan {
 
     /// Get current offset
     fn current_offset(&self) -> u64;
+
+    /// Number of records a full scan will return, if known in advance
+    fn total_records(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Records scanned between two `rebuild_with_progress` callbacks
+pub const REBUILD_PROGRESS_INTERVAL: u64 = 10_000;
+
+/// Progress of an index rebuild, passed to the `rebuild_with_progress`
+/// callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RebuildProgress {
+    /// Storage records scanned so far, tombstones included
+    pub documents_processed: u64,
+    /// Records the scan will return, if the storage knows
+    pub total: Option<u64>,
+    /// Time since the rebuild started
+    pub elapsed: Duration,
+    /// True for the final report, sent once the scan reached the end
+    pub complete: bool,
 }
 
 /// Index Manager that maintains in-memory indexes
@@ -59,8 +95,17 @@ pub struct IndexManager {
     /// Indexed field names
     indexed_fields: HashSet<String>,
 
+    /// Ordering options of secondary indexes created with them
+    index_options: HashMap<String, IndexOptions>,
+
     /// Document ID to offset mapping (for delete)
     doc_offsets: HashMap<String, StorageOffset>,
+
+    /// Unique indexes, checked before writes
+    unique_indexes: Vec<UniqueIndex>,
+
+    /// Compound indexes (name -> index)
+    compound_indexes: BTreeMap<String, CompoundIndex>,
 }
 
 impl IndexManager {
@@ -75,7 +120,10 @@ impl IndexManager {
             pk_index: IndexTree::new(),
             field_indexes,
             indexed_fields,
+            index_options: HashMap::new(),
             doc_offsets: HashMap::new(),
+            unique_indexes: Vec::new(),
+            compound_indexes: BTreeMap::new(),
         }
     }
 
@@ -84,26 +132,82 @@ impl IndexManager {
         Self::new(HashSet::new())
     }
 
+    /// Index `field` with the given ordering options, replacing any index
+    /// on it. Call before `rebuild_from_storage`.
+    ///
+    /// Keys are the field values mapped through the collation, so lookups
+    /// on a collated index match every value with the same mapping.
+    pub fn with_index_options(mut self, field: impl Into<String>, options: IndexOptions) -> Self {
+        let field = field.into();
+        let tree = if options.descending {
+            IndexTree::new_descending()
+        } else {
+            IndexTree::new()
+        };
+        self.field_indexes.insert(field.clone(), tree);
+        self.indexed_fields.insert(field.clone());
+        self.index_options.insert(field, options);
+        self
+    }
+
     /// Rebuild all indexes from storage.
     ///
     /// Behavior:
     /// - Sequentially scan storage
-    /// - Ignore tombstones
+    /// - A later version of a document replaces the earlier one
+    /// - A tombstone removes the document from every index
     /// - For each live document: extract indexed fields, insert offset
     /// - Deterministic traversal order
     ///
     /// On checksum failure: returns AERO_DATA_CORRUPTION (FATAL)
     pub fn rebuild_from_storage<S: StorageScan>(&mut self, storage: &mut S) -> IndexResult<()> {
+        self.rebuild_with_progress(storage, |_| {})
+    }
+
+    /// Rebuild all indexes from storage, as `rebuild_from_storage`, calling
+    /// `callback` every `REBUILD_PROGRESS_INTERVAL` records and once more
+    /// when the scan completes.
+    ///
+    /// The final report's `documents_processed` is the number of records
+    /// scanned. No final report is sent if the rebuild fails.
+    pub fn rebuild_with_progress<S, F>(
+        &mut self,
+        storage: &mut S,
+        mut callback: F,
+    ) -> IndexResult<()>
+    where
+        S: StorageScan,
+        F: FnMut(RebuildProgress),
+    {
+        let started = Instant::now();
+        let total = storage.total_records();
+        let mut processed: u64 = 0;
+        let mut report = |processed: u64, complete: bool| {
+            callback(RebuildProgress {
+                documents_processed: processed,
+                total,
+                elapsed: started.elapsed(),
+                complete,
+            })
+        };
+
         // Clear existing indexes
         self.pk_index.clear();
         for tree in self.field_indexes.values_mut() {
             tree.clear();
         }
         self.doc_offsets.clear();
+        for unique in &mut self.unique_indexes {
+            unique.clear();
+        }
+        for compound in self.compound_indexes.values_mut() {
+            compound.clear();
+        }
 
         // Reset storage to beginning
         storage.reset()?;
 
+        let mut bodies: HashMap<String, Value> = HashMap::new();
         loop {
             let doc = match storage.scan_next() {
                 Ok(Some(d)) => d,
@@ -116,19 +220,199 @@ impl IndexManager {
                     ));
                 }
             };
+            processed += 1;
+            if processed.is_multiple_of(REBUILD_PROGRESS_INTERVAL) {
+                report(processed, false);
+            }
 
-            // Skip tombstones
+            // A later record supersedes the earlier version, as on the
+            // write path
+            if let Some(offset) = self.doc_offsets.get(&doc.document_id).copied() {
+                let body = bodies.remove(&doc.document_id).unwrap_or(Value::Null);
+                self.unindex_document(&doc.document_id, offset, &body);
+            }
+
+            // Tombstones are not indexed
             if doc.is_tombstone {
+                for unique in &mut self.unique_indexes {
+                    unique.remove(&doc.document_id);
+                }
+                for compound in self.compound_indexes.values_mut() {
+                    compound.remove(&doc.document_id);
+                }
                 continue;
             }
 
             // Index this document
+            for unique in &mut self.unique_indexes {
+                unique.insert(&doc.document_id, &doc.body);
+            }
+            for compound in self.compound_indexes.values_mut() {
+                compound.insert(&doc.document_id, &doc.body, doc.offset);
+            }
             self.index_document(&doc);
+            if !self.indexed_fields.is_empty() {
+                // Kept to unindex secondary fields if the document changes
+                bodies.insert(doc.document_id.clone(), doc.body);
+            }
+        }
+
+        report(processed, true);
+        Ok(())
+    }
+
+    /// Build a unique index on `field` from the current contents of storage.
+    ///
+    /// The latest version of each document is indexed; deleted documents are
+    /// not. Absent, null, array and object values are not indexed and never
+    /// conflict.
+    ///
+    /// If existing documents already share a value, returns
+    /// AERO_INDEX_UNIQUE_VIOLATION listing every duplicated value and leaves
+    /// the manager unchanged.
+    pub fn create_unique_index<S: StorageScan>(
+        &mut self,
+        name: &str,
+        field: &str,
+        storage: &mut S,
+    ) -> IndexResult<()> {
+        if self.unique_indexes.iter().any(|u| u.name() == name) {
+            return Err(IndexError::build_failed(format!(
+                "Unique index '{}' already exists",
+                name
+            )));
+        }
+
+        let mut latest: BTreeMap<String, Option<Value>> = BTreeMap::new();
+        Self::scan_storage(storage, |doc| {
+            let body = (!doc.is_tombstone).then_some(doc.body);
+            latest.insert(doc.document_id, body);
+        })?;
+
+        let live = latest
+            .iter()
+            .filter_map(|(id, body)| body.as_ref().map(|b| (id.as_str(), b)));
+        let unique = UniqueIndex::build(name, field, live)?;
+        self.unique_indexes.push(unique);
+
+        Ok(())
+    }
+
+    /// Build a compound index over `fields` from the current contents of
+    /// storage.
+    ///
+    /// The index is registered only if the scan succeeds, and is maintained
+    /// by every later write and rebuilt by `rebuild_from_storage`.
+    pub fn create_compound_index<S: StorageScan>(
+        &mut self,
+        name: &str,
+        fields: Vec<String>,
+        storage: &mut S,
+    ) -> IndexResult<()> {
+        if self.compound_indexes.contains_key(name) {
+            return Err(IndexError::build_failed(format!(
+                "Compound index '{}' already exists",
+                name
+            )));
         }
+        if fields.len() < 2 {
+            return Err(IndexError::build_failed(format!(
+                "Compound index '{}' needs at least two fields",
+                name
+            )));
+        }
+
+        let mut compound = CompoundIndex::new(fields);
+        Self::scan_storage(storage, |doc| {
+            if doc.is_tombstone {
+                compound.remove(&doc.document_id);
+            } else {
+                compound.insert(&doc.document_id, &doc.body, doc.offset);
+            }
+        })?;
+        self.compound_indexes.insert(name.to_string(), compound);
 
         Ok(())
     }
 
+    /// Scan storage from the beginning, mapping scan failures to
+    /// AERO_DATA_CORRUPTION (FATAL)
+    fn scan_storage<S: StorageScan>(
+        storage: &mut S,
+        mut visit: impl FnMut(DocumentInfo),
+    ) -> IndexResult<()> {
+        storage.reset()?;
+
+        loop {
+            match storage.scan_next() {
+                Ok(Some(doc)) => visit(doc),
+                Ok(None) => return Ok(()),
+                Err(e) => {
+                    return Err(IndexError::data_corruption(
+                        storage.current_offset(),
+                        e.message(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Check a single write against every unique index.
+    ///
+    /// Must be called BEFORE the WAL append: a violation means nothing has
+    /// been written and the request is rejected.
+    pub fn check_unique(&self, doc_id: &str, body: &Value) -> IndexResult<()> {
+        self.check_unique_batch(&[(doc_id, body)])
+    }
+
+    /// Check a set of writes that will be applied together.
+    ///
+    /// Conflicts within the set are detected as well as conflicts with
+    /// documents already indexed, so a multi-document write can be rejected
+    /// as a whole before any of it reaches the WAL.
+    pub fn check_unique_batch(&self, writes: &[(&str, &Value)]) -> IndexResult<()> {
+        for unique in &self.unique_indexes {
+            unique.check(writes)?;
+        }
+        Ok(())
+    }
+
+    /// Check writes in order, each against the indexed documents and the
+    /// earlier writes that passed, for batches that accept some writes and
+    /// reject others.
+    ///
+    /// Returns one result per write. A rejected write claims nothing, so a
+    /// later write may still take its values.
+    pub fn check_unique_each(&self, writes: &[(&str, &Value)]) -> Vec<IndexResult<()>> {
+        let mut claimed = vec![BTreeMap::new(); self.unique_indexes.len()];
+        let mut results = Vec::with_capacity(writes.len());
+        for (doc_id, body) in writes {
+            let result = self
+                .unique_indexes
+                .iter()
+                .zip(&claimed)
+                .try_for_each(|(unique, claimed)| unique.check_claim(doc_id, body, claimed));
+            if result.is_ok() {
+                for (unique, claimed) in self.unique_indexes.iter().zip(&mut claimed) {
+                    unique.claim(doc_id, body, claimed);
+                }
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    /// The document holding `value` in a unique index on `field`, if any.
+    ///
+    /// A write may take the value only if this is None or the writing
+    /// document itself.
+    pub fn unique_owner(&self, field: &str, value: &Value) -> Option<&str> {
+        self.unique_indexes
+            .iter()
+            .filter(|unique| unique.field() == field)
+            .find_map(|unique| unique.owner(value))
+    }
+
     /// Index a single document
     fn index_document(&mut self, doc: &DocumentInfo) {
         // Primary key index
@@ -141,7 +425,7 @@ impl IndexManager {
         // Secondary indexes
         for field in &self.indexed_fields {
             if let Some(value) = doc.body.get(field) {
-                if let Some(key) = IndexKey::from_json(value) {
+                if let Some(key) = self.field_key(field, value) {
                     if let Some(tree) = self.field_indexes.get_mut(field) {
                         tree.insert(key, doc.offset);
                     }
@@ -162,7 +446,7 @@ impl IndexManager {
         // Remove from secondary indexes
         for field in &self.indexed_fields {
             if let Some(value) = body.get(field) {
-                if let Some(key) = IndexKey::from_json(value) {
+                if let Some(key) = self.field_key(field, value) {
                     if let Some(tree) = self.field_indexes.get_mut(field) {
                         tree.remove(&key, offset);
                     }
@@ -176,6 +460,21 @@ impl IndexManager {
     /// Called AFTER storage write.
     /// Updates in-memory index only.
     pub fn apply_write(&mut self, doc: &DocumentInfo) {
+        for unique in &mut self.unique_indexes {
+            if doc.is_tombstone {
+                unique.remove(&doc.document_id);
+            } else {
+                unique.insert(&doc.document_id, &doc.body);
+            }
+        }
+        for compound in self.compound_indexes.values_mut() {
+            if doc.is_tombstone {
+                compound.remove(&doc.document_id);
+            } else {
+                compound.insert(&doc.document_id, &doc.body, doc.offset);
+            }
+        }
+
         // If document already exists, remove old index entry
         if let Some(&old_offset) = self.doc_offsets.get(&doc.document_id) {
             // Note: For proper update, we'd need the old body.
@@ -193,6 +492,13 @@ impl IndexManager {
     /// Called AFTER storage write (tombstone).
     /// Removes document from all indexes.
     pub fn apply_delete(&mut self, doc_id: &str, body: &Value) {
+        for unique in &mut self.unique_indexes {
+            unique.remove(doc_id);
+        }
+        for compound in self.compound_indexes.values_mut() {
+            compound.remove(doc_id);
+        }
+
         if let Some(offset) = self.doc_offsets.get(doc_id).copied() {
             self.unindex_document(doc_id, offset, body);
         }
@@ -221,32 +527,106 @@ impl IndexManager {
             return Vec::new();
         };
 
-        let Some(key) = IndexKey::from_json(value) else {
+        let Some(key) = self.field_key(field, value) else {
             return Vec::new();
         };
 
         tree.lookup_eq(&key)
     }
 
-    /// Lookup offsets in a range.
+    /// Lookup offsets with keys between `lower` and `upper`, traversing
+    /// only that part of the index.
     ///
     /// Returns offsets sorted ascending.
     /// Limit is applied after collecting offsets.
     pub fn lookup_range(
         &self,
         field: &str,
-        min: Option<&Value>,
-        max: Option<&Value>,
+        lower: Bound<&Value>,
+        upper: Bound<&Value>,
         limit: Option<usize>,
     ) -> Vec<StorageOffset> {
         let Some(tree) = self.field_indexes.get(field) else {
             return Vec::new();
         };
 
-        let min_key = min.and_then(IndexKey::from_json);
-        let max_key = max.and_then(IndexKey::from_json);
+        let (Some(lower_key), Some(upper_key)) = (
+            self.field_bound(field, lower),
+            self.field_bound(field, upper),
+        ) else {
+            return Vec::new();
+        };
+
+        let mut offsets = tree.lookup_range(lower_key.as_ref(), upper_key.as_ref());
+
+        if let Some(lim) = limit {
+            offsets.truncate(lim);
+        }
+
+        offsets
+    }
+
+    /// Lookup offsets in a range in key order, for scans that provide a
+    /// sort order. `_id` scans the primary key index.
+    ///
+    /// Offsets under one key stay ascending when `descending` matches the
+    /// index's own order; otherwise the whole sequence is reversed.
+    pub fn scan_ordered(
+        &self,
+        field: &str,
+        lower: Bound<&Value>,
+        upper: Bound<&Value>,
+        descending: bool,
+    ) -> Vec<StorageOffset> {
+        let tree = if field == "_id" {
+            &self.pk_index
+        } else {
+            match self.field_indexes.get(field) {
+                Some(tree) => tree,
+                None => return Vec::new(),
+            }
+        };
+
+        let (Some(lower_key), Some(upper_key)) = (
+            self.field_bound(field, lower),
+            self.field_bound(field, upper),
+        ) else {
+            return Vec::new();
+        };
+
+        tree.scan_ordered(lower_key.as_ref(), upper_key.as_ref(), descending)
+    }
+
+    /// Lookup offsets through a compound index: equality on the leading
+    /// `prefix.len()` fields, then an optional range on the next field.
+    ///
+    /// Returns offsets sorted ascending.
+    /// Limit is applied after collecting offsets.
+    pub fn lookup_compound(
+        &self,
+        name: &str,
+        prefix: &[&Value],
+        lower: Bound<&Value>,
+        upper: Bound<&Value>,
+        limit: Option<usize>,
+    ) -> Vec<StorageOffset> {
+        let Some(compound) = self.compound_indexes.get(name) else {
+            return Vec::new();
+        };
+
+        let Some(prefix_keys) = prefix
+            .iter()
+            .map(|v| IndexKey::from_json(v))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Vec::new();
+        };
+        let (Some(lower_key), Some(upper_key)) = (bound_key(lower), bound_key(upper)) else {
+            return Vec::new();
+        };
 
-        let mut offsets = tree.lookup_range(min_key.as_ref(), max_key.as_ref());
+        let mut offsets =
+            compound.lookup_prefix(&prefix_keys, lower_key.as_ref(), upper_key.as_ref());
 
         if let Some(lim) = limit {
             offsets.truncate(lim);
@@ -255,22 +635,112 @@ impl IndexManager {
         offsets
     }
 
+    /// Compound index lookup in key order (by the field after `prefix`,
+    /// then the remaining fields), for scans that provide a sort order.
+    /// `descending` reverses the sequence.
+    pub fn scan_compound_ordered(
+        &self,
+        name: &str,
+        prefix: &[&Value],
+        lower: Bound<&Value>,
+        upper: Bound<&Value>,
+        descending: bool,
+    ) -> Vec<StorageOffset> {
+        let Some(compound) = self.compound_indexes.get(name) else {
+            return Vec::new();
+        };
+
+        let Some(prefix_keys) = prefix
+            .iter()
+            .map(|v| IndexKey::from_json(v))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Vec::new();
+        };
+        let (Some(lower_key), Some(upper_key)) = (bound_key(lower), bound_key(upper)) else {
+            return Vec::new();
+        };
+
+        compound.scan_prefix(
+            &prefix_keys,
+            lower_key.as_ref(),
+            upper_key.as_ref(),
+            descending,
+        )
+    }
+
+    /// Returns compound index definitions (name, fields) in name order
+    pub fn compound_indexes(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.compound_indexes
+            .iter()
+            .map(|(name, compound)| (name.as_str(), compound.fields()))
+    }
+
     /// Get all offsets in primary key order.
     ///
     /// Returns offsets sorted ascending.
     pub fn all_offsets_pk_order(&self) -> Vec<StorageOffset> {
-        self.pk_index.lookup_range(None, None)
+        self.pk_index
+            .lookup_range(Bound::Unbounded, Bound::Unbounded)
     }
 
     /// Returns the set of indexed fields
     pub fn indexed_fields(&self) -> &HashSet<String> {
         &self.indexed_fields
     }
+
+    /// Returns the ordering options of a field's index
+    pub fn index_options(&self, field: &str) -> IndexOptions {
+        self.index_options.get(field).copied().unwrap_or_default()
+    }
+
+    /// Key of `value` in a field's index, mapped through its collation
+    fn field_key(&self, field: &str, value: &Value) -> Option<IndexKey> {
+        let collation = self.index_options(field).collation;
+        IndexKey::from_json(&collation.normalize(value))
+    }
+
+    /// Key bound of `bound` in a field's index; None if its value is not
+    /// indexable, so no key can satisfy it
+    fn field_bound(&self, field: &str, bound: Bound<&Value>) -> Option<Bound<IndexKey>> {
+        match bound {
+            Bound::Included(v) => self.field_key(field, v).map(Bound::Included),
+            Bound::Excluded(v) => self.field_key(field, v).map(Bound::Excluded),
+            Bound::Unbounded => Some(Bound::Unbounded),
+        }
+    }
+
+    /// Number of live documents
+    pub fn document_count(&self) -> usize {
+        self.doc_offsets.len()
+    }
+
+    /// Returns (entries, distinct keys) for a single-field index
+    pub fn field_cardinality(&self, field: &str) -> Option<(usize, usize)> {
+        self.field_indexes
+            .get(field)
+            .map(|index| (index.offset_count(), index.key_count()))
+    }
+
+    /// Returns the number of documents in a compound index
+    pub fn compound_entry_count(&self, name: &str) -> Option<usize> {
+        self.compound_indexes.get(name).map(CompoundIndex::len)
+    }
+}
+
+/// Convert a JSON bound to a key bound; None if the value is not indexable
+fn bound_key(bound: Bound<&Value>) -> Option<Bound<IndexKey>> {
+    match bound {
+        Bound::Included(v) => IndexKey::from_json(v).map(Bound::Included),
+        Bound::Excluded(v) => IndexKey::from_json(v).map(Bound::Excluded),
+        Bound::Unbounded => Some(Bound::Unbounded),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::index::Collation;
     use serde_json::json;
 
     struct MockStorage {
@@ -324,6 +794,10 @@ mod tests {
                 0
             }
         }
+
+        fn total_records(&self) -> Option<u64> {
+            Some(self.documents.len() as u64)
+        }
     }
 
     fn make_doc(id: &str, age: i64, offset: u64) -> DocumentInfo {
@@ -372,6 +846,31 @@ mod tests {
         assert_eq!(age_25, vec![100, 300]);
     }
 
+    #[test]
+    fn test_statistics_counts() {
+        let docs = vec![
+            make_doc("user_1", 25, 100),
+            make_doc("user_2", 30, 200),
+            make_doc("user_3", 25, 300),
+        ];
+        let mut indexed = HashSet::new();
+        indexed.insert("age".to_string());
+
+        let mut manager = IndexManager::new(indexed);
+        manager
+            .rebuild_from_storage(&mut MockStorage::new(docs))
+            .unwrap();
+
+        assert_eq!(manager.document_count(), 3);
+        assert_eq!(manager.field_cardinality("age"), Some((3, 2)));
+        assert_eq!(manager.field_cardinality("name"), None);
+        assert_eq!(manager.compound_entry_count("a_b"), None);
+
+        manager.apply_delete("user_2", &json!({"_id": "user_2", "age": 30}));
+        assert_eq!(manager.document_count(), 2);
+        assert_eq!(manager.field_cardinality("age"), Some((2, 1)));
+    }
+
     #[test]
     fn test_delete_removes_index_entry() {
         let mut manager = IndexManager::pk_only();
@@ -440,14 +939,100 @@ mod tests {
         let mut manager = IndexManager::new(indexed);
         manager.rebuild_from_storage(&mut storage).unwrap();
 
-        let result = manager.lookup_range("age", Some(&json!(25)), Some(&json!(35)), None);
+        let (min, max) = (json!(25), json!(35));
+        let result =
+            manager.lookup_range("age", Bound::Included(&min), Bound::Included(&max), None);
         assert_eq!(result, vec![200, 300, 400]);
 
         // With limit
-        let result_limited = manager.lookup_range("age", Some(&json!(25)), None, Some(2));
+        let result_limited =
+            manager.lookup_range("age", Bound::Included(&min), Bound::Unbounded, Some(2));
         assert_eq!(result_limited, vec![200, 300]);
     }
 
+    #[test]
+    fn test_lookup_range_exclusive_bounds() {
+        let docs = (1..=5)
+            .map(|i| make_doc(&format!("user_{}", i), i * 10, i as u64 * 100))
+            .collect();
+
+        let mut storage = MockStorage::new(docs);
+        let mut manager = IndexManager::new(HashSet::from(["age".to_string()]));
+        manager.rebuild_from_storage(&mut storage).unwrap();
+
+        let (twenty, forty) = (json!(20), json!(40));
+        let range = |lower, upper| manager.lookup_range("age", lower, upper, None);
+        assert_eq!(
+            range(Bound::Included(&twenty), Bound::Included(&forty)),
+            vec![200, 300, 400]
+        );
+        assert_eq!(
+            range(Bound::Excluded(&twenty), Bound::Included(&forty)),
+            vec![300, 400]
+        );
+        assert_eq!(
+            range(Bound::Included(&twenty), Bound::Excluded(&forty)),
+            vec![200, 300]
+        );
+        assert_eq!(
+            range(Bound::Excluded(&twenty), Bound::Excluded(&forty)),
+            vec![300]
+        );
+        assert_eq!(range(Bound::Excluded(&forty), Bound::Unbounded), vec![500]);
+
+        // Empty, inverted and unindexable ranges match nothing
+        assert!(range(Bound::Excluded(&twenty), Bound::Excluded(&twenty)).is_empty());
+        assert!(range(Bound::Included(&forty), Bound::Included(&twenty)).is_empty());
+        assert!(range(Bound::Included(&json!(null)), Bound::Unbounded).is_empty());
+
+        let ordered = manager.scan_ordered("age", Bound::Excluded(&twenty), Bound::Unbounded, true);
+        assert_eq!(ordered, vec![500, 400, 300]);
+    }
+
+    #[test]
+    fn test_rebuild_with_progress_reports_counts() {
+        let count = 2 * REBUILD_PROGRESS_INTERVAL + 7;
+        let mut docs: Vec<_> = (0..count)
+            .map(|i| make_doc(&format!("u{}", i), 20, i * 100))
+            .collect();
+        docs.push(make_tombstone("u0", count * 100));
+        let mut storage = MockStorage::new(docs);
+
+        let mut reports = Vec::new();
+        let mut manager = IndexManager::pk_only();
+        manager
+            .rebuild_with_progress(&mut storage, |p| reports.push(p))
+            .unwrap();
+
+        let counts: Vec<_> = reports.iter().map(|p| p.documents_processed).collect();
+        assert_eq!(
+            counts,
+            vec![
+                REBUILD_PROGRESS_INTERVAL,
+                2 * REBUILD_PROGRESS_INTERVAL,
+                count + 1
+            ]
+        );
+        assert!(reports.windows(2).all(|w| w[0].elapsed <= w[1].elapsed));
+        assert!(reports.iter().all(|p| p.total == Some(count + 1)));
+        let last = reports.last().unwrap();
+        assert!(last.complete);
+        assert!(reports[..2].iter().all(|p| !p.complete));
+        assert_eq!(manager.document_count(), count as usize - 1);
+    }
+
+    #[test]
+    fn test_rebuild_with_progress_skips_final_report_on_corruption() {
+        let docs = vec![make_doc("a", 1, 0), make_doc("b", 2, 100)];
+        let mut storage = MockStorage::new(docs).with_corruption_at(1);
+
+        let mut reports = Vec::new();
+        let result =
+            IndexManager::pk_only().rebuild_with_progress(&mut storage, |p| reports.push(p));
+        assert!(result.is_err());
+        assert!(reports.is_empty());
+    }
+
     #[test]
     fn test_corruption_during_rebuild_halts() {
         let docs = vec![make_doc("user_1", 25, 100), make_doc("user_2", 30, 200)];
@@ -478,4 +1063,468 @@ mod tests {
         assert_eq!(manager.lookup_pk("user_1"), vec![100]);
         assert_eq!(manager.lookup_pk("user_3"), vec![300]);
     }
+
+    #[test]
+    fn test_rebuild_keeps_latest_version() {
+        let docs = vec![
+            make_doc("user_1", 25, 100),
+            make_doc("user_2", 40, 200),
+            make_doc("user_1", 26, 300),
+            make_tombstone("user_2", 400),
+        ];
+
+        let mut manager = IndexManager::new(HashSet::from(["age".to_string()]));
+        manager
+            .rebuild_from_storage(&mut MockStorage::new(docs))
+            .unwrap();
+
+        assert_eq!(manager.lookup_pk("user_1"), vec![300]);
+        assert!(manager.lookup_pk("user_2").is_empty());
+        assert!(manager.lookup_eq("age", &json!(25)).is_empty());
+        assert!(manager.lookup_eq("age", &json!(40)).is_empty());
+        assert_eq!(manager.lookup_eq("age", &json!(26)), vec![300]);
+    }
+
+    fn make_user(id: &str, email: Value, offset: u64) -> DocumentInfo {
+        DocumentInfo {
+            document_id: id.to_string(),
+            schema_id: "users".to_string(),
+            schema_version: "v1".to_string(),
+            is_tombstone: false,
+            body: json!({"_id": id, "email": email}),
+            offset,
+        }
+    }
+
+    fn unique_email_manager(docs: Vec<DocumentInfo>) -> IndexManager {
+        let mut storage = MockStorage::new(docs);
+        let mut manager = IndexManager::pk_only();
+        manager
+            .create_unique_index("users_email", "email", &mut storage)
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_unique_build_fails_listing_duplicates() {
+        let docs = vec![
+            make_user("user_1", json!("a@x.io"), 100),
+            make_user("user_2", json!("a@x.io"), 200),
+            make_user("user_3", json!("b@x.io"), 300),
+            make_user("user_4", json!(7), 400),
+            make_user("user_5", json!(7), 500),
+        ];
+
+        let mut storage = MockStorage::new(docs);
+        let mut manager = IndexManager::pk_only();
+        let err = manager
+            .create_unique_index("users_email", "email", &mut storage)
+            .unwrap_err();
+
+        assert_eq!(err.code().code(), "AERO_INDEX_UNIQUE_VIOLATION");
+        assert!(err.message().contains("\"a@x.io\" (user_1, user_2)"));
+        assert!(err.message().contains("7 (user_4, user_5)"));
+        assert!(!err.message().contains("b@x.io"));
+
+        // Failed build leaves no index behind
+        let dup = json!({"email": "a@x.io"});
+        assert!(manager.check_unique("user_9", &dup).is_ok());
+    }
+
+    #[test]
+    fn test_unique_build_uses_latest_version_and_skips_deleted() {
+        let docs = vec![
+            make_user("user_1", json!("a@x.io"), 100),
+            make_user("user_1", json!("c@x.io"), 200),
+            make_user("user_2", json!("a@x.io"), 300),
+            make_user("user_3", json!("b@x.io"), 400),
+            make_tombstone("user_3", 500),
+            make_user("user_4", json!("b@x.io"), 600),
+        ];
+
+        let manager = unique_email_manager(docs);
+
+        let taken = json!({"email": "c@x.io"});
+        let err = manager.check_unique("user_9", &taken).unwrap_err();
+        assert!(err.message().contains("users_email"));
+        assert!(err.message().contains("\"c@x.io\""));
+        assert_eq!(err.conflicting_document(), Some("user_1"));
+        assert_eq!(
+            manager.unique_owner("email", &json!("a@x.io")),
+            Some("user_2")
+        );
+        assert_eq!(
+            manager.unique_owner("email", &json!("b@x.io")),
+            Some("user_4")
+        );
+    }
+
+    #[test]
+    fn test_unique_rejects_other_owner_but_not_self() {
+        let mut manager = unique_email_manager(vec![]);
+        manager.apply_write(&make_user("user_1", json!("a@x.io"), 100));
+
+        let body = json!({"email": "a@x.io"});
+        assert!(manager.check_unique("user_1", &body).is_ok());
+        let err = manager.check_unique("user_2", &body).unwrap_err();
+        assert_eq!(err.conflicting_document(), Some("user_1"));
+        assert_eq!(
+            manager.unique_owner("email", &json!("a@x.io")),
+            Some("user_1")
+        );
+        assert_eq!(manager.unique_owner("age", &json!("a@x.io")), None);
+
+        // Moving user_1 to a new value releases the old one
+        manager.apply_write(&make_user("user_1", json!("b@x.io"), 200));
+        assert!(manager.check_unique("user_2", &body).is_ok());
+
+        // Deleting releases the value too
+        manager.apply_delete("user_1", &json!({"email": "b@x.io"}));
+        assert!(manager
+            .check_unique("user_2", &json!({"email": "b@x.io"}))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_unique_nulls_and_absent_fields_do_not_conflict() {
+        let mut manager = unique_email_manager(vec![
+            make_user("user_1", json!(null), 100),
+            make_user("user_2", json!(null), 200),
+        ]);
+        manager.apply_write(&make_doc("user_3", 30, 300));
+
+        assert!(manager
+            .check_unique("user_4", &json!({"email": null}))
+            .is_ok());
+        assert!(manager.check_unique("user_5", &json!({"age": 1})).is_ok());
+        assert!(manager
+            .check_unique("user_6", &json!({"email": ["a@x.io"]}))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_unique_batch_detects_conflicts_within_batch() {
+        let mut manager = unique_email_manager(vec![]);
+        manager.apply_write(&make_user("user_1", json!("a@x.io"), 100));
+
+        let b = json!({"email": "b@x.io"});
+        let a = json!({"email": "a@x.io"});
+        let err = manager
+            .check_unique_batch(&[("user_2", &b), ("user_3", &b)])
+            .unwrap_err();
+        assert_eq!(err.code().code(), "AERO_INDEX_UNIQUE_VIOLATION");
+        assert_eq!(err.conflicting_document(), Some("user_2"));
+
+        // A key released by its owner within the same batch may be taken
+        assert!(manager
+            .check_unique_batch(&[("user_1", &b), ("user_2", &a)])
+            .is_ok());
+    }
+
+    #[test]
+    fn test_unique_each_reports_every_write() {
+        let mut manager = unique_email_manager(vec![]);
+        manager.apply_write(&make_user("user_1", json!("a@x.io"), 100));
+
+        let a = json!({"email": "a@x.io"});
+        let b = json!({"email": "b@x.io"});
+        let results = manager.check_unique_each(&[
+            ("user_2", &a),
+            ("user_3", &b),
+            ("user_4", &b),
+            ("user_1", &a),
+        ]);
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().conflicting_document(),
+            Some("user_1")
+        );
+        assert!(results[1].is_ok());
+        assert_eq!(
+            results[2].as_ref().unwrap_err().conflicting_document(),
+            Some("user_3")
+        );
+        assert!(results[3].is_ok());
+    }
+
+    #[test]
+    fn test_unique_index_survives_rebuild() {
+        let docs = vec![
+            make_user("user_1", json!("a@x.io"), 100),
+            make_user("user_2", json!("b@x.io"), 200),
+            make_tombstone("user_2", 300),
+        ];
+        let mut manager = unique_email_manager(docs.clone());
+        manager
+            .rebuild_from_storage(&mut MockStorage::new(docs))
+            .unwrap();
+
+        assert!(manager
+            .check_unique("user_9", &json!({"email": "a@x.io"}))
+            .is_err());
+        assert!(manager
+            .check_unique("user_9", &json!({"email": "b@x.io"}))
+            .is_ok());
+    }
+
+    fn make_ab(id: &str, a: Value, b: Value, offset: u64) -> DocumentInfo {
+        DocumentInfo {
+            document_id: id.to_string(),
+            schema_id: "users".to_string(),
+            schema_version: "v1".to_string(),
+            is_tombstone: false,
+            body: json!({"_id": id, "a": a, "b": b}),
+            offset,
+        }
+    }
+
+    fn ab_manager(docs: Vec<DocumentInfo>) -> IndexManager {
+        let mut manager = IndexManager::pk_only();
+        manager
+            .create_compound_index(
+                "a_b",
+                vec!["a".to_string(), "b".to_string()],
+                &mut MockStorage::new(docs),
+            )
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_compound_prefix_lookups() {
+        let manager = ab_manager(vec![
+            make_ab("d1", json!("x"), json!(1), 100),
+            make_ab("d2", json!("x"), json!(2.5), 200),
+            make_ab("d3", json!("x"), json!(3), 300),
+            make_ab("d4", json!("y"), json!(2), 400),
+            make_ab("d5", json!("x"), json!(null), 500),
+        ]);
+
+        let x = json!("x");
+        let two = json!(2);
+
+        // Equality on the leading field alone (includes the missing `b`)
+        let all_x = manager.lookup_compound("a_b", &[&x], Bound::Unbounded, Bound::Unbounded, None);
+        assert_eq!(all_x, vec![100, 200, 300, 500]);
+
+        // a = x AND b > 2, across int and float values
+        let gt =
+            manager.lookup_compound("a_b", &[&x], Bound::Excluded(&two), Bound::Unbounded, None);
+        assert_eq!(gt, vec![200, 300]);
+
+        // a = x AND b <= 2.5
+        let le = manager.lookup_compound(
+            "a_b",
+            &[&x],
+            Bound::Unbounded,
+            Bound::Included(&json!(2.5)),
+            None,
+        );
+        assert_eq!(le, vec![100, 200]);
+
+        // Full equality
+        let exact = manager.lookup_compound(
+            "a_b",
+            &[&x, &json!(3)],
+            Bound::Unbounded,
+            Bound::Unbounded,
+            None,
+        );
+        assert_eq!(exact, vec![300]);
+
+        // Range on the leading field
+        let range_a =
+            manager.lookup_compound("a_b", &[], Bound::Excluded(&x), Bound::Unbounded, None);
+        assert_eq!(range_a, vec![400]);
+    }
+
+    #[test]
+    fn test_ordered_scans_follow_key_order() {
+        let docs = vec![
+            make_ab("d1", json!("x"), json!(3), 100),
+            make_ab("d2", json!("x"), json!(1), 200),
+            make_ab("d3", json!("x"), json!(2), 300),
+            make_ab("d4", json!("x"), json!(null), 400),
+            make_ab("d5", json!("y"), json!(0), 500),
+        ];
+        let mut manager = ab_manager(docs.clone());
+        manager
+            .rebuild_from_storage(&mut MockStorage::new(docs))
+            .unwrap();
+        let x = json!("x");
+
+        // Missing `b` sorts first
+        let asc =
+            manager.scan_compound_ordered("a_b", &[&x], Bound::Unbounded, Bound::Unbounded, false);
+        assert_eq!(asc, vec![400, 200, 300, 100]);
+        let desc =
+            manager.scan_compound_ordered("a_b", &[&x], Bound::Unbounded, Bound::Unbounded, true);
+        assert_eq!(desc, vec![100, 300, 200, 400]);
+        let ranged = manager.scan_compound_ordered(
+            "a_b",
+            &[&x],
+            Bound::Included(&json!(2)),
+            Bound::Unbounded,
+            true,
+        );
+        assert_eq!(ranged, vec![100, 300]);
+
+        // `_id` scans the primary key index
+        assert_eq!(
+            manager.scan_ordered("_id", Bound::Unbounded, Bound::Unbounded, true),
+            vec![500, 400, 300, 200, 100]
+        );
+        assert!(manager
+            .scan_ordered("b", Bound::Unbounded, Bound::Unbounded, false)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_compound_maintained_on_write_and_delete() {
+        let mut manager = ab_manager(vec![]);
+        let x = json!("x");
+        let lookup = |m: &IndexManager, b: i64| {
+            m.lookup_compound(
+                "a_b",
+                &[&x, &json!(b)],
+                Bound::Unbounded,
+                Bound::Unbounded,
+                None,
+            )
+        };
+
+        manager.apply_write(&make_ab("d1", json!("x"), json!(1), 100));
+        assert_eq!(lookup(&manager, 1), vec![100]);
+
+        // Changing a constituent field moves the entry
+        manager.apply_write(&make_ab("d1", json!("x"), json!(2), 200));
+        assert!(lookup(&manager, 1).is_empty());
+        assert_eq!(lookup(&manager, 2), vec![200]);
+
+        // Changing the leading field moves it out of the prefix
+        manager.apply_write(&make_ab("d1", json!("y"), json!(2), 300));
+        assert!(lookup(&manager, 2).is_empty());
+
+        manager.apply_write(&make_ab("d2", json!("x"), json!(2), 400));
+        manager.apply_delete("d2", &json!({"a": "x", "b": 2}));
+        assert!(lookup(&manager, 2).is_empty());
+    }
+
+    #[test]
+    fn test_compound_rebuilt_from_storage() {
+        let docs = vec![
+            make_ab("d1", json!("x"), json!(1), 100),
+            make_ab("d1", json!("x"), json!(5), 200),
+            make_ab("d2", json!("x"), json!(7), 300),
+            make_tombstone("d2", 400),
+            make_ab("d3", json!("x"), json!(9), 500),
+        ];
+        let mut manager = ab_manager(vec![]);
+        manager
+            .rebuild_from_storage(&mut MockStorage::new(docs))
+            .unwrap();
+
+        let x = json!("x");
+        let offsets = manager.lookup_compound(
+            "a_b",
+            &[&x],
+            Bound::Included(&json!(1)),
+            Bound::Unbounded,
+            None,
+        );
+        assert_eq!(offsets, vec![200, 500]);
+        assert_eq!(
+            manager.compound_indexes().collect::<Vec<_>>(),
+            vec![("a_b", &["a".to_string(), "b".to_string()][..])]
+        );
+    }
+
+    #[test]
+    fn test_compound_index_requires_two_fields() {
+        let mut manager = IndexManager::pk_only();
+        let err = manager
+            .create_compound_index("a", vec!["a".to_string()], &mut MockStorage::new(vec![]))
+            .unwrap_err();
+        assert_eq!(err.code().code(), "AERO_INDEX_BUILD_FAILED");
+        assert_eq!(manager.compound_indexes().count(), 0);
+    }
+
+    fn make_field(id: &str, field: &str, value: Value, offset: u64) -> DocumentInfo {
+        DocumentInfo {
+            document_id: id.to_string(),
+            schema_id: "items".to_string(),
+            schema_version: "v1".to_string(),
+            is_tombstone: false,
+            body: json!({"_id": id, field: value}),
+            offset,
+        }
+    }
+
+    #[test]
+    fn test_numeric_collation_orders_numeric_strings() {
+        let docs = vec![
+            make_field("a", "price", json!("10"), 100),
+            make_field("b", "price", json!("9"), 200),
+            make_field("c", "price", json!("100"), 300),
+            make_field("d", "price", json!("2.5"), 400),
+        ];
+        let options = IndexOptions::new().with_collation(Collation::Numeric);
+        let mut manager = IndexManager::pk_only().with_index_options("price", options);
+        manager
+            .rebuild_from_storage(&mut MockStorage::new(docs))
+            .unwrap();
+
+        assert_eq!(
+            manager.scan_ordered("price", Bound::Unbounded, Bound::Unbounded, false),
+            vec![400, 200, 100, 300]
+        );
+        assert_eq!(
+            manager.scan_ordered("price", Bound::Unbounded, Bound::Unbounded, true),
+            vec![300, 100, 200, 400]
+        );
+        // Bounds go through the collation too
+        assert_eq!(
+            manager.scan_ordered(
+                "price",
+                Bound::Included(&json!("5")),
+                Bound::Included(&json!(50)),
+                false
+            ),
+            vec![200, 100]
+        );
+        assert_eq!(manager.lookup_eq("price", &json!("9.0")), vec![200]);
+    }
+
+    #[test]
+    fn test_case_insensitive_descending_index() {
+        let docs = vec![
+            make_field("a", "name", json!("bob"), 100),
+            make_field("b", "name", json!("Alice"), 200),
+            make_field("c", "name", json!("carol"), 300),
+            make_field("d", "name", json!("alice"), 400),
+        ];
+        let options = IndexOptions::new()
+            .descending()
+            .with_collation(Collation::CaseInsensitive);
+        let mut manager = IndexManager::pk_only().with_index_options("name", options);
+        manager
+            .rebuild_from_storage(&mut MockStorage::new(docs))
+            .unwrap();
+        assert_eq!(manager.index_options("name"), options);
+        assert!(manager.indexed_fields().contains("name"));
+
+        // The index's own order keeps equal keys in offset order
+        assert_eq!(
+            manager.scan_ordered("name", Bound::Unbounded, Bound::Unbounded, true),
+            vec![300, 100, 200, 400]
+        );
+        assert_eq!(
+            manager.scan_ordered("name", Bound::Unbounded, Bound::Unbounded, false),
+            vec![400, 200, 100, 300]
+        );
+        assert_eq!(manager.lookup_eq("name", &json!("ALICE")), vec![200, 400]);
+
+        manager.apply_delete("b", &json!({"_id": "b", "name": "Alice"}));
+        assert_eq!(manager.lookup_eq("name", &json!("alice")), vec![400]);
+    }
 }
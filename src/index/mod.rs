@@ -13,6 +13,9 @@
 //! - Indexes rebuilt on startup from storage
 //! - Updates occur AFTER storage writes
 //! - Lookup returns sorted offsets ascending
+//! - Index options (descending, collation) change key order, never the
+//!   set of documents a key holds
+//! - Unique indexes are checked before the WAL append, never after
 //!
 //! # Phase 3 Optimizations
 //!
@@ -20,8 +23,11 @@
 
 mod acceleration;
 mod btree;
+mod compound;
 mod errors;
 mod manager;
+mod options;
+mod unique;
 
 pub use acceleration::{
     AcceleratorStats, AttributeIndex, CompositeIndex, IndexAccelConfig, IndexAccelerator,
@@ -29,4 +35,7 @@ pub use acceleration::{
 };
 pub use btree::{IndexKey, IndexTree};
 pub use errors::{IndexError, IndexErrorCode, IndexResult};
-pub use manager::{DocumentInfo, IndexManager};
+pub use manager::{
+    DocumentInfo, IndexManager, RebuildProgress, StorageScan, REBUILD_PROGRESS_INTERVAL,
+};
+pub use options::{Collation, IndexOptions};
@@ -0,0 +1,150 @@
+//! Per-index ordering options
+//!
+//! A single-field index orders its keys by a `Collation`, ascending unless
+//! created `descending`. The collation maps a field value to the value that
+//! is ordered:
+//!
+//! - `Binary`: the value itself (strings compare byte-wise)
+//! - `Numeric`: strings holding a finite number order as that number, so
+//!   `"9"` sorts before `"10"`; other strings are left as they are
+//! - `CaseInsensitive`: strings order by their lowercase form
+//!
+//! Sorts apply the same mapping, so an index serves a sort only when their
+//! collations agree.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+/// How string values are ordered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Collation {
+    /// Byte-wise string order
+    #[default]
+    Binary,
+    /// Numeric strings order as numbers
+    Numeric,
+    /// Strings order by their lowercase form
+    CaseInsensitive,
+}
+
+impl Collation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Collation::Binary => "binary",
+            Collation::Numeric => "numeric",
+            Collation::CaseInsensitive => "case_insensitive",
+        }
+    }
+
+    /// The value ordered in place of `value`; non-strings are unchanged
+    pub fn normalize<'a>(&self, value: &'a Value) -> Cow<'a, Value> {
+        let Value::String(s) = value else {
+            return Cow::Borrowed(value);
+        };
+        match self {
+            Collation::Binary => Cow::Borrowed(value),
+            Collation::Numeric => match parse_number(s) {
+                Some(n) => Cow::Owned(Value::Number(n)),
+                None => Cow::Borrowed(value),
+            },
+            Collation::CaseInsensitive => Cow::Owned(Value::String(s.to_lowercase())),
+        }
+    }
+}
+
+impl fmt::Display for Collation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Collation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "binary" => Ok(Collation::Binary),
+            "numeric" => Ok(Collation::Numeric),
+            "case_insensitive" => Ok(Collation::CaseInsensitive),
+            other => Err(format!("Unknown collation: {}", other)),
+        }
+    }
+}
+
+/// Parse a string holding a finite number, as an integer when it is one
+fn parse_number(s: &str) -> Option<Number> {
+    if let Ok(i) = s.parse::<i64>() {
+        return Some(Number::from(i));
+    }
+    s.parse::<f64>().ok().and_then(Number::from_f64)
+}
+
+/// Ordering options of a single-field index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexOptions {
+    /// Keys are stored largest first
+    pub descending: bool,
+    /// How keys are ordered
+    pub collation: Collation,
+}
+
+impl IndexOptions {
+    /// Ascending, binary collation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store keys largest first
+    pub fn descending(mut self) -> Self {
+        self.descending = true;
+        self
+    }
+
+    /// Order keys by `collation`
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = collation;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_numeric_collation_parses_numeric_strings() {
+        let numeric = Collation::Numeric;
+        assert_eq!(*numeric.normalize(&json!("10")), json!(10));
+        assert_eq!(*numeric.normalize(&json!("-2.5")), json!(-2.5));
+        assert_eq!(*numeric.normalize(&json!("ten")), json!("ten"));
+        assert_eq!(*numeric.normalize(&json!("NaN")), json!("NaN"));
+        assert_eq!(*numeric.normalize(&json!(true)), json!(true));
+    }
+
+    #[test]
+    fn test_case_insensitive_collation_lowercases_strings() {
+        let folded = Collation::CaseInsensitive;
+        assert_eq!(*folded.normalize(&json!("Bob")), json!("bob"));
+        assert_eq!(*folded.normalize(&json!(3)), json!(3));
+        assert_eq!(*Collation::Binary.normalize(&json!("Bob")), json!("Bob"));
+    }
+
+    #[test]
+    fn test_collation_names_round_trip() {
+        for collation in [
+            Collation::Binary,
+            Collation::Numeric,
+            Collation::CaseInsensitive,
+        ] {
+            assert_eq!(collation.as_str().parse::<Collation>(), Ok(collation));
+        }
+        assert!("natural".parse::<Collation>().is_err());
+    }
+}
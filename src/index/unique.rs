@@ -0,0 +1,205 @@
+//! Unique index enforcement
+//!
+//! A unique index maps each key of one field to the single document that
+//! owns it. It is derived state like every other index, but unlike a
+//! secondary index it is consulted BEFORE a write reaches the WAL so that a
+//! conflicting write is rejected without leaving any durable trace. The
+//! rejection names the document that already holds the value.
+//!
+//! Check and insert are not atomic on their own: callers hold exclusive
+//! access to the `IndexManager` from the check until the write is applied,
+//! so no concurrent write can take the value in between.
+//!
+//! # Null handling
+//!
+//! Only values that produce an `IndexKey` are indexed. A field that is
+//! absent, `null`, an array, or an object is not indexed and therefore never
+//! conflicts: any number of documents may omit a unique field.
+//!
+//! # Soft deletes
+//!
+//! A soft-deleted document (one with `_deleted_at` set) holds no key, so a
+//! new document may reuse its values. Purging it later releases nothing.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+use super::btree::IndexKey;
+use super::errors::{IndexError, IndexResult};
+use crate::schema::is_soft_deleted;
+
+/// Key -> owning document for a single unique field
+#[derive(Debug)]
+pub(crate) struct UniqueIndex {
+    /// Index name reported in violations
+    name: String,
+    /// Indexed field
+    field: String,
+    /// Key -> owning document ID
+    owners: BTreeMap<IndexKey, String>,
+    /// Document ID -> key it currently owns
+    keys: HashMap<String, IndexKey>,
+}
+
+impl UniqueIndex {
+    /// Build a unique index from the live documents of a collection.
+    ///
+    /// Fails with AERO_INDEX_UNIQUE_VIOLATION listing every duplicated value
+    /// (and the documents holding it) if the data is not already unique.
+    pub(crate) fn build<'a>(
+        name: &str,
+        field: &str,
+        live: impl IntoIterator<Item = (&'a str, &'a Value)>,
+    ) -> IndexResult<Self> {
+        let mut index = Self {
+            name: name.to_string(),
+            field: field.to_string(),
+            owners: BTreeMap::new(),
+            keys: HashMap::new(),
+        };
+
+        let mut holders: BTreeMap<IndexKey, (&Value, Vec<&str>)> = BTreeMap::new();
+        for (doc_id, body) in live {
+            if let Some((key, value)) = index.key_of(body) {
+                holders
+                    .entry(key)
+                    .or_insert((value, Vec::new()))
+                    .1
+                    .push(doc_id);
+            }
+        }
+
+        let duplicates: Vec<String> = holders
+            .values()
+            .filter(|(_, ids)| ids.len() > 1)
+            .map(|(value, ids)| format!("{} ({})", value, ids.join(", ")))
+            .collect();
+        if !duplicates.is_empty() {
+            return Err(IndexError::unique_build_violation(name, &duplicates));
+        }
+
+        for (key, (_, ids)) in holders {
+            index.keys.insert(ids[0].to_string(), key.clone());
+            index.owners.insert(key, ids[0].to_string());
+        }
+
+        Ok(index)
+    }
+
+    /// Index name
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Indexed field
+    pub(crate) fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Document holding `value`, if any
+    pub(crate) fn owner(&self, value: &Value) -> Option<&str> {
+        let key = IndexKey::from_json(value)?;
+        self.owners.get(&key).map(String::as_str)
+    }
+
+    /// Extract the indexed key from a document body, if it has one
+    fn key_of<'v>(&self, body: &'v Value) -> Option<(IndexKey, &'v Value)> {
+        if is_soft_deleted(body) {
+            return None;
+        }
+        let value = body.get(&self.field)?;
+        IndexKey::from_json(value).map(|key| (key, value))
+    }
+
+    /// Check that a set of writes can be applied together.
+    ///
+    /// A write conflicts if another write in the same set claims the same
+    /// key, or if the key is owned by a document outside the set. A document
+    /// rewriting its own key is not a conflict; neither is taking a key whose
+    /// owner is rewritten within the same set (the check against the other
+    /// write covers the case where the owner keeps it).
+    pub(crate) fn check(&self, writes: &[(&str, &Value)]) -> IndexResult<()> {
+        let mut claimed: BTreeMap<IndexKey, &str> = BTreeMap::new();
+
+        for (doc_id, body) in writes {
+            let Some((key, value)) = self.key_of(body) else {
+                continue;
+            };
+
+            if let Some(other) = claimed.insert(key.clone(), doc_id) {
+                if other != *doc_id {
+                    return Err(IndexError::unique_violation(&self.name, value, other));
+                }
+            }
+
+            if let Some(owner) = self.owners.get(&key) {
+                let owner_rewritten = writes.iter().any(|(id, _)| id == owner);
+                if owner != doc_id && !owner_rewritten {
+                    return Err(IndexError::unique_violation(&self.name, value, owner));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check one write of a sequence against the index and `claimed`, the
+    /// keys taken by the sequence's earlier writes. Unlike `check`, a key
+    /// stays with its owner even if the owner is rewritten in the sequence.
+    pub(crate) fn check_claim(
+        &self,
+        doc_id: &str,
+        body: &Value,
+        claimed: &BTreeMap<IndexKey, String>,
+    ) -> IndexResult<()> {
+        let Some((key, value)) = self.key_of(body) else {
+            return Ok(());
+        };
+        for owner in [claimed.get(&key), self.owners.get(&key)]
+            .into_iter()
+            .flatten()
+        {
+            if owner != doc_id {
+                return Err(IndexError::unique_violation(&self.name, value, owner));
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the key of a write that passed `check_claim` to `claimed`
+    pub(crate) fn claim(
+        &self,
+        doc_id: &str,
+        body: &Value,
+        claimed: &mut BTreeMap<IndexKey, String>,
+    ) {
+        if let Some((key, _)) = self.key_of(body) {
+            claimed.insert(key, doc_id.to_string());
+        }
+    }
+
+    /// Record the current body of a document, releasing its previous key
+    pub(crate) fn insert(&mut self, doc_id: &str, body: &Value) {
+        self.remove(doc_id);
+        if let Some((key, _)) = self.key_of(body) {
+            self.owners.insert(key.clone(), doc_id.to_string());
+            self.keys.insert(doc_id.to_string(), key);
+        }
+    }
+
+    /// Release whatever key a document owns
+    pub(crate) fn remove(&mut self, doc_id: &str) {
+        if let Some(key) = self.keys.remove(doc_id) {
+            if self.owners.get(&key).map(String::as_str) == Some(doc_id) {
+                self.owners.remove(&key);
+            }
+        }
+    }
+
+    /// Drop all entries
+    pub(crate) fn clear(&mut self) {
+        self.owners.clear();
+        self.keys.clear();
+    }
+}
@@ -9,6 +9,7 @@ pub mod backup;
 pub mod backpressure;
 pub mod checkpoint;
 pub mod cli;
+pub mod config_reload;
 pub mod config_validator;
 pub mod control_plane;
 pub mod core;
@@ -37,5 +38,7 @@ pub mod restore;
 pub mod schema;
 pub mod snapshot;
 pub mod storage;
+pub mod storage_io;
+pub mod transfer;
 pub mod version;
 pub mod wal;
@@ -4,7 +4,7 @@
 //! 1. Parses CLI arguments (via cli::run)
 //! 2. Dispatches to CLI commands (via cli::run)
 //! 3. Prints errors to stderr
-//! 4. Exits with non-zero on failure
+//! 4. Exits with non-zero on failure (2 for `doctor` failures)
 //!
 //! Per BOOT.md, main.rs must NOT:
 //! - Load configuration
@@ -19,6 +19,6 @@ use aerodb::cli;
 fn main() {
     if let Err(e) = cli::run() {
         eprintln!("{}", e);
-        std::process::exit(1);
+        std::process::exit(e.exit_code());
     }
 }
@@ -131,9 +131,13 @@ pub enum MigrationOperation {
     /// Drop an index
     DropIndex { collection: String, name: String },
 
-    /// Rename a collection
+    /// Rename a collection, moving its indexes
     RenameCollection { from: String, to: String },
 
+    /// Copy a collection's schema and indexes to a new collection, leaving
+    /// the source untouched
+    CopyCollection { from: String, to: String },
+
     /// Execute raw operation (escape hatch - use sparingly)
     ///
     /// MANIFESTO ALIGNMENT: This is an explicit escape hatch.
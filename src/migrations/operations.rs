@@ -35,6 +35,38 @@ impl InMemoryExecutor {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Create `to` with the indexes of `from`; `to` must not exist
+    fn copy_collection(&self, operation: &str, from: &str, to: &str) -> MigrationResult<()> {
+        let mut collections = self.collections.write().unwrap();
+        if !collections.contains(from) {
+            return Err(MigrationError::ExecutionFailed {
+                version: 0,
+                operation: operation.to_string(),
+                reason: format!("Collection '{}' does not exist", from),
+            });
+        }
+        if collections.contains(to) {
+            return Err(MigrationError::ExecutionFailed {
+                version: 0,
+                operation: operation.to_string(),
+                reason: format!("Collection '{}' already exists", to),
+            });
+        }
+        collections.insert(to.to_string());
+
+        let mut indexes = self.indexes.write().unwrap();
+        let prefix = format!("{}.", from);
+        let copies: Vec<(String, Vec<String>)> = indexes
+            .iter()
+            .filter_map(|(key, fields)| {
+                let name = key.strip_prefix(&prefix)?;
+                Some((format!("{}.{}", to, name), fields.clone()))
+            })
+            .collect();
+        indexes.extend(copies);
+        Ok(())
+    }
 }
 
 impl OperationExecutor for InMemoryExecutor {
@@ -142,25 +174,18 @@ impl OperationExecutor for InMemoryExecutor {
                 Ok(())
             }
             MigrationOperation::RenameCollection { from, to } => {
-                let mut collections = self.collections.write().unwrap();
-                if !collections.contains(from) {
-                    return Err(MigrationError::ExecutionFailed {
-                        version: 0,
-                        operation: "rename_collection".to_string(),
-                        reason: format!("Collection '{}' does not exist", from),
-                    });
-                }
-                if collections.contains(to) {
-                    return Err(MigrationError::ExecutionFailed {
-                        version: 0,
-                        operation: "rename_collection".to_string(),
-                        reason: format!("Collection '{}' already exists", to),
-                    });
-                }
-                collections.remove(from);
-                collections.insert(to.clone());
+                self.copy_collection("rename_collection", from, to)?;
+                self.collections.write().unwrap().remove(from);
+                let prefix = format!("{}.", from);
+                self.indexes
+                    .write()
+                    .unwrap()
+                    .retain(|key, _| !key.starts_with(&prefix));
                 Ok(())
             }
+            MigrationOperation::CopyCollection { from, to } => {
+                self.copy_collection("copy_collection", from, to)
+            }
             MigrationOperation::Raw { operation: _ } => {
                 // Raw operations are pass-through
                 // Real implementation would execute the raw operation
@@ -257,4 +282,48 @@ mod tests {
 
         assert!(executor.index_exists("users", "idx_email").unwrap());
     }
+
+    #[test]
+    fn test_rename_and_copy_collection_move_indexes() {
+        let executor = InMemoryExecutor::new();
+        for name in ["users", "orders"] {
+            executor
+                .execute(&MigrationOperation::CreateCollection {
+                    name: name.to_string(),
+                    schema: serde_json::json!({}),
+                })
+                .unwrap();
+        }
+        executor
+            .execute(&MigrationOperation::CreateIndex {
+                collection: "users".to_string(),
+                fields: vec!["email".to_string()],
+                unique: true,
+                name: Some("idx_email".to_string()),
+            })
+            .unwrap();
+        let rename = |to: &str| MigrationOperation::RenameCollection {
+            from: "users".to_string(),
+            to: to.to_string(),
+        };
+
+        // A taken name is refused
+        let err = executor.execute(&rename("orders")).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        executor
+            .execute(&MigrationOperation::CopyCollection {
+                from: "users".to_string(),
+                to: "archive".to_string(),
+            })
+            .unwrap();
+        assert!(executor.collection_exists("users").unwrap());
+        assert!(executor.index_exists("archive", "idx_email").unwrap());
+
+        executor.execute(&rename("members")).unwrap();
+        assert!(!executor.collection_exists("users").unwrap());
+        assert!(!executor.index_exists("users", "idx_email").unwrap());
+        assert!(executor.index_exists("members", "idx_email").unwrap());
+        assert!(executor.index_exists("archive", "idx_email").unwrap());
+    }
 }
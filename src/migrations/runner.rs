@@ -15,6 +15,7 @@ use super::errors::{MigrationError, MigrationResult};
 use super::operations::OperationExecutor;
 use super::state::MigrationState;
 use super::{Migration, MigrationOperation, MigrationVersion};
+use crate::crash_point::{maybe_crash, points};
 use chrono::Utc;
 use std::collections::BTreeMap;
 use std::fs;
@@ -46,6 +47,9 @@ impl MigrationRunner {
     ) -> MigrationResult<Self> {
         let state = Arc::new(MigrationState::new(data_dir));
         state.load()?;
+        for version in state.fail_interrupted()? {
+            eprintln!("Migration {} was interrupted; marked failed", version);
+        }
 
         Ok(Self {
             migrations_dir,
@@ -222,6 +226,7 @@ impl MigrationRunner {
             migration.name.clone(),
             migration.checksum.clone(),
         )?;
+        maybe_crash(points::MIGRATION_AFTER_RECORD_START);
 
         // Execute operations
         for (i, op) in migration.up.iter().enumerate() {
@@ -226,6 +226,28 @@ impl MigrationState {
         self.save()
     }
 
+    /// Mark migrations left `Running` by an interrupted process as failed
+    ///
+    /// A migration is only `Running` between `record_start` and its outcome
+    /// being recorded, so one found at load time never completed. Returns
+    /// the versions that were marked.
+    pub fn fail_interrupted(&self) -> MigrationResult<Vec<MigrationVersion>> {
+        let mut records = self.records.write().unwrap();
+        let mut interrupted = Vec::new();
+        for record in records.values_mut() {
+            if record.status == MigrationStatus::Running {
+                record.status = MigrationStatus::Failed;
+                record.error = Some("Interrupted before completion".to_string());
+                interrupted.push(record.version);
+            }
+        }
+        drop(records);
+        if !interrupted.is_empty() {
+            self.save()?;
+        }
+        Ok(interrupted)
+    }
+
     /// Record rollback
     pub fn record_rollback(&self, version: MigrationVersion) -> MigrationResult<()> {
         let mut records = self.records.write().unwrap();
@@ -305,4 +327,30 @@ mod tests {
         state.release_lock();
         state.acquire_lock("process-2".to_string()).unwrap();
     }
+    #[test]
+    fn test_interrupted_migration_is_marked_failed() {
+        let temp_dir = TempDir::new().unwrap();
+        let state = MigrationState::new(temp_dir.path().to_path_buf());
+        state
+            .record_start(1, "create_users".to_string(), "crc32:ABC".to_string())
+            .unwrap();
+        state.record_success(1, 10).unwrap();
+        state
+            .record_start(2, "create_posts".to_string(), "crc32:DEF".to_string())
+            .unwrap();
+
+        // A new process finds version 2 still running
+        let reloaded = MigrationState::new(temp_dir.path().to_path_buf());
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.fail_interrupted().unwrap(), vec![2]);
+        assert!(reloaded.is_applied(1));
+        assert!(!reloaded.is_applied(2));
+
+        let on_disk = MigrationState::new(temp_dir.path().to_path_buf());
+        on_disk.load().unwrap();
+        let records = on_disk.get_all();
+        assert_eq!(records[1].status, MigrationStatus::Failed);
+        assert!(records[1].error.is_some());
+        assert!(on_disk.fail_interrupted().unwrap().is_empty());
+    }
 }
@@ -9,15 +9,30 @@
 //! Per PHASE7_INVARIANTS.md §P7-O3:
 //! - States and action history are written to persistent append-only audit logs.
 //! - No background purging or retention policies (those are external concerns).
+//!
+//! # Hash chain
+//!
+//! `FileAuditLog` writes each record as one JSON line carrying `prev`, the
+//! hex SHA-256 of the line before it (`GENESIS_HASH` for the first record).
+//! Editing, removing or reordering a record breaks the link after it, which
+//! `verify_chain` reports. When the file reaches its size limit it is renamed
+//! to `audit.log.<n>` and the new file's first record links to the last
+//! record of the old one, so the chain runs across rotated files. Removing
+//! records from the end of the newest file cannot be detected from the log
+//! alone.
 
 use std::fmt;
-use std::fs::{File, OpenOptions};
-use std::io::{self, BufWriter, Write};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use super::request_id::current_request_id;
+
 /// Audit action type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AuditAction {
@@ -151,13 +166,15 @@ pub struct AuditRecord {
 
 impl AuditRecord {
     /// Create a new audit record.
+    ///
+    /// The record belongs to the request being handled, if any.
     pub fn new(action: AuditAction, outcome: AuditOutcome) -> Self {
         Self {
             id: Uuid::new_v4(),
             timestamp: SystemTime::now(),
             action,
             command_name: None,
-            request_id: None,
+            request_id: current_request_id(),
             target_id: None,
             authority_level: None,
             operator_id: None,
@@ -284,49 +301,287 @@ pub trait AuditLog: Send + Sync {
     fn sync(&self) -> io::Result<()>;
 }
 
+/// Audit log file under the data directory.
+pub const AUDIT_LOG_PATH: &str = "system/audit.log";
+
+/// Size at which `FileAuditLog::open_in` rotates the audit log (64 MB).
+pub const DEFAULT_AUDIT_LOG_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// `prev` of the first record in a chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
 /// File-based audit log implementation.
 ///
 /// Per PHASE7_AUDITABILITY.md §5:
 /// - Append-only file format
 /// - fsync after each write for durability
-/// - One JSON record per line
+/// - One JSON record per line, hash-chained to the line before it
 pub struct FileAuditLog {
     path: PathBuf,
-    writer: Arc<Mutex<BufWriter<File>>>,
+    max_bytes: u64,
+    file: Mutex<ChainedFile>,
+}
+
+/// The file being appended to and the chain head.
+struct ChainedFile {
+    file: File,
+    len: u64,
+    /// Hash of the last line written, `prev` of the next record
+    head: String,
 }
 
 impl FileAuditLog {
     /// Open or create an audit log file.
+    ///
+    /// The chain continues from the last record in the file, or in the
+    /// newest rotated file if this one is empty.
     pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
         let path = path.as_ref().to_path_buf();
-        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+        let len = file.metadata()?.len();
+
+        let head = match last_line(&path)? {
+            Some((line, terminated)) => {
+                // A record torn by a crash is left as it is; the next one
+                // starts on a new line and links to the torn bytes
+                if !terminated {
+                    file.write_all(b"\n")?;
+                    file.sync_all()?;
+                }
+                hash_line(&line)
+            }
+            None => match rotated_files(&path)?.last() {
+                Some(rotated) => last_line(rotated)?
+                    .map(|(line, _)| hash_line(&line))
+                    .unwrap_or_else(|| GENESIS_HASH.to_string()),
+                None => GENESIS_HASH.to_string(),
+            },
+        };
 
         Ok(Self {
             path,
-            writer: Arc::new(Mutex::new(BufWriter::new(file))),
+            max_bytes: u64::MAX,
+            file: Mutex::new(ChainedFile { file, len, head }),
         })
     }
 
+    /// Open the audit log of a data directory, creating `system/` if needed.
+    pub fn open_in(data_dir: impl AsRef<Path>) -> io::Result<Self> {
+        let path = data_dir.as_ref().join(AUDIT_LOG_PATH);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(Self::open(path)?.with_max_bytes(DEFAULT_AUDIT_LOG_MAX_BYTES))
+    }
+
+    /// Rotate the file once it holds `max_bytes` or more.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
     /// Get the audit log path.
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// Hash of the last record written.
+    pub fn head(&self) -> String {
+        self.file.lock().unwrap().head.clone()
+    }
+
+    /// Rename the file to `<path>.<n>` and continue the chain in a new one.
+    pub fn rotate(&self) -> io::Result<()> {
+        let mut chained = self.file.lock().unwrap();
+        self.rotate_locked(&mut chained)
+    }
+
+    fn rotate_locked(&self, chained: &mut ChainedFile) -> io::Result<()> {
+        let next = rotated_files(&self.path)?
+            .last()
+            .and_then(|p| rotation_number(&self.path, p))
+            .map_or(1, |n| n + 1);
+        fs::rename(&self.path, rotated_path(&self.path, next))?;
+        chained.file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+        chained.len = 0;
+        if let Some(dir) = self.path.parent() {
+            File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
 }
 
 impl AuditLog for FileAuditLog {
     fn append(&self, record: &AuditRecord) -> io::Result<()> {
+        let mut chained = self.file.lock().unwrap();
+        if chained.len > 0 && chained.len >= self.max_bytes {
+            self.rotate_locked(&mut chained)?;
+        }
+
         let json = record.to_json();
-        let mut writer = self.writer.lock().unwrap();
-        writeln!(writer, "{}", json)?;
-        writer.flush()?;
+        let line = format!(r#"{},"prev":"{}"}}"#, &json[..json.len() - 1], chained.head);
+        chained.file.write_all(format!("{}\n", line).as_bytes())?;
         // Sync to disk for durability
-        writer.get_ref().sync_all()
+        chained.file.sync_all()?;
+        chained.len += line.len() as u64 + 1;
+        chained.head = hash_line(&line);
+        Ok(())
     }
 
     fn sync(&self) -> io::Result<()> {
-        let writer = self.writer.lock().unwrap();
-        writer.get_ref().sync_all()
+        self.file.lock().unwrap().file.sync_all()
+    }
+}
+
+/// Result of walking an audit log's hash chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainReport {
+    /// Files walked, oldest first.
+    pub files: Vec<PathBuf>,
+
+    /// Records whose link was checked, up to the first broken one.
+    pub records: usize,
+
+    /// Hash of the last record checked.
+    pub head: String,
+
+    /// The first broken link, if any.
+    pub broken: Option<BrokenLink>,
+}
+
+impl ChainReport {
+    /// True if every link holds.
+    pub fn is_intact(&self) -> bool {
+        self.broken.is_none()
+    }
+}
+
+/// A record whose `prev` does not match the record before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    /// File holding the record.
+    pub file: PathBuf,
+
+    /// Line number of the record in that file (1-based).
+    pub line: usize,
+
+    /// Why the link is broken.
+    pub reason: String,
+}
+
+/// Walk the hash chain of the audit log at `path`, rotated files first.
+///
+/// Stops at the first broken link. A missing log is an empty, intact chain.
+pub fn verify_chain(path: impl AsRef<Path>) -> io::Result<ChainReport> {
+    let path = path.as_ref();
+    let mut files = rotated_files(path)?;
+    if path.exists() {
+        files.push(path.to_path_buf());
+    }
+
+    let mut report = ChainReport {
+        files: files.clone(),
+        records: 0,
+        head: GENESIS_HASH.to_string(),
+        broken: None,
+    };
+    for file in files {
+        let reader = BufReader::new(File::open(&file)?);
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let prev = serde_json::from_str::<serde_json::Value>(&line)
+                .ok()
+                .and_then(|record| record["prev"].as_str().map(str::to_string));
+            let reason = match prev {
+                None => Some("record is not valid JSON with a prev hash".to_string()),
+                Some(prev) if prev != report.head => Some(format!(
+                    "prev is {} but the record before hashes to {}",
+                    prev, report.head
+                )),
+                Some(_) => None,
+            };
+            if let Some(reason) = reason {
+                report.broken = Some(BrokenLink {
+                    file,
+                    line: index + 1,
+                    reason,
+                });
+                return Ok(report);
+            }
+            report.records += 1;
+            report.head = hash_line(&line);
+        }
+    }
+    Ok(report)
+}
+
+/// Hex SHA-256 of one line, without its newline.
+fn hash_line(line: &str) -> String {
+    hex::encode(Sha256::digest(line.as_bytes()))
+}
+
+/// The last line of `path` and whether it ends with a newline.
+fn last_line(path: &Path) -> io::Result<Option<(String, bool)>> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let terminated = contents.ends_with(b"\n");
+    let body = contents.strip_suffix(b"\n").unwrap_or(&contents);
+    if body.is_empty() {
+        return Ok(None);
+    }
+    let start = body.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    let line = String::from_utf8_lossy(&body[start..]).into_owned();
+    Ok(Some((line, terminated)))
+}
+
+fn rotated_path(path: &Path, n: u64) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", n));
+    path.with_file_name(name)
+}
+
+fn rotation_number(path: &Path, rotated: &Path) -> Option<u64> {
+    let name = path.file_name()?.to_str()?;
+    rotated
+        .file_name()?
+        .to_str()?
+        .strip_prefix(name)?
+        .strip_prefix('.')?
+        .parse()
+        .ok()
+}
+
+/// Rotated files of the log at `path`, oldest first.
+fn rotated_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut rotated = Vec::new();
+    for entry in entries {
+        let candidate = entry?.path();
+        if let Some(n) = rotation_number(path, &candidate) {
+            rotated.push((n, candidate));
+        }
     }
+    rotated.sort();
+    Ok(rotated.into_iter().map(|(_, p)| p).collect())
 }
 
 /// In-memory audit log for testing.
@@ -431,6 +686,74 @@ mod tests {
         assert!(contents.contains("inspect_cluster_state"));
     }
 
+    #[test]
+    fn test_file_audit_log_chain_detects_corruption() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+
+        let log = FileAuditLog::open(&path).unwrap();
+        for name in ["a", "b", "c"] {
+            let record = AuditRecord::new(AuditAction::CommandExecuted, AuditOutcome::Success)
+                .with_command(name);
+            log.append(&record).unwrap();
+        }
+        let report = verify_chain(&path).unwrap();
+        assert!(report.is_intact());
+        assert_eq!(report.records, 3);
+        assert_eq!(report.head, log.head());
+
+        // Reopening continues the chain
+        drop(log);
+        let log = FileAuditLog::open(&path).unwrap();
+        log.append(&AuditRecord::new(
+            AuditAction::CommandRequested,
+            AuditOutcome::Pending,
+        ))
+        .unwrap();
+        assert_eq!(verify_chain(&path).unwrap().records, 4);
+
+        // Editing the second record breaks the link from the third
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::write(&path, contents.replacen(r#""cmd":"b""#, r#""cmd":"x""#, 1)).unwrap();
+        let report = verify_chain(&path).unwrap();
+        assert_eq!(report.records, 2);
+        let broken = report.broken.unwrap();
+        assert_eq!(broken.file, path);
+        assert_eq!(broken.line, 3);
+    }
+
+    #[test]
+    fn test_file_audit_log_rotation_carries_chain() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+
+        let log = FileAuditLog::open(&path).unwrap().with_max_bytes(1);
+        for _ in 0..3 {
+            log.append(&AuditRecord::new(
+                AuditAction::AuthorityCheck,
+                AuditOutcome::Success,
+            ))
+            .unwrap();
+        }
+        let report = verify_chain(&path).unwrap();
+        assert!(report.is_intact());
+        assert_eq!(report.records, 3);
+        assert_eq!(
+            report.files,
+            vec![
+                dir.path().join("audit.log.1"),
+                dir.path().join("audit.log.2"),
+                path.clone()
+            ]
+        );
+
+        // Removing a rotated file breaks the link into the next one
+        fs::remove_file(dir.path().join("audit.log.2")).unwrap();
+        let broken = verify_chain(&path).unwrap().broken.unwrap();
+        assert_eq!(broken.file, path);
+        assert_eq!(broken.line, 1);
+    }
+
     #[test]
     fn test_escape_json() {
         assert_eq!(escape_json("hello"), "hello");
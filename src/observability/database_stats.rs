@@ -0,0 +1,255 @@
+//! Database statistics for the admin dashboard
+//!
+//! Summaries of the collections and of the whole system, served read-only
+//! by the `/admin/v1` endpoints. Everything is taken from counters the
+//! engine already maintains, never from a scan:
+//!
+//! - document counts, bytes, records and last write time from the storage
+//!   writer's per-collection usage
+//! - indexed-field entries and distinct estimates from the planner
+//!   `Statistics` kept under the collection's name
+//! - schema versions from the schema loader
+//!
+//! Field statistics exist only for indexed fields, and only once writes
+//! or an `analyze` recorded them; other fields report their schema type
+//! alone.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::index::IndexManager;
+use crate::planner::Statistics;
+use crate::resource_limits::ResourceStatus;
+use crate::schema::{Schema, SchemaLoader};
+use crate::storage::StorageWriter;
+
+/// One index of a collection
+///
+/// Indexes are shared by every collection of the database; the entry
+/// count is the collection's share.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexSummary {
+    /// Index name: the field, `_id` for the primary key, or the compound
+    /// index's name
+    pub name: String,
+    /// Indexed fields, in key order
+    pub fields: Vec<String>,
+    /// Whether this is the primary key index
+    pub primary: bool,
+    /// Entries the index holds for the collection, where known
+    pub entries: Option<u64>,
+}
+
+/// Overview of one collection
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CollectionSummary {
+    pub name: String,
+    /// Newest schema version
+    pub schema_version: String,
+    /// Every registered schema version, oldest first
+    pub schema_versions: Vec<String>,
+    /// Live documents
+    pub document_count: u64,
+    /// Bytes the collection's records take in the storage file,
+    /// superseded versions and tombstones included
+    pub storage_bytes: u64,
+    /// Records of the collection in the storage file
+    pub storage_records: u64,
+    /// When a document was last written, if since the server started
+    pub last_write: Option<DateTime<Utc>>,
+    pub indexes: Vec<IndexSummary>,
+}
+
+/// Statistics of one field of a collection
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldSummary {
+    pub name: String,
+    /// Schema type: `string`, `int`, `bool`, `float`, `object` or `array`
+    #[serde(rename = "type")]
+    pub field_type: &'static str,
+    pub required: bool,
+    pub indexed: bool,
+    /// Share of documents without an indexable value, where stats exist
+    pub null_ratio: Option<f64>,
+    /// Estimated distinct values, where stats exist
+    pub distinct_estimate: Option<u64>,
+}
+
+/// A collection with its field statistics
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CollectionDetail {
+    #[serde(flatten)]
+    pub summary: CollectionSummary,
+    /// When the statistics were last recomputed by `analyze`
+    pub analyzed_at: Option<DateTime<Utc>>,
+    /// Whether the distinct estimates are too old to trust
+    pub stale: bool,
+    /// Fields of the newest schema version, by name
+    pub fields: Vec<FieldSummary>,
+}
+
+/// Replication state of this node
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplicationSummary {
+    /// `standalone`, `primary` or `replica`
+    pub role: String,
+    /// Per replica lag, on a primary
+    pub replicas: Vec<ReplicaLagSummary>,
+}
+
+/// Lag of one replica behind this primary
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplicaLagSummary {
+    pub replica_id: String,
+    pub connected: bool,
+    pub lag_records: u64,
+    pub lag_bytes: u64,
+}
+
+/// Overview of the whole node
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemSummary {
+    /// Resource usage and health, if it could be measured
+    pub resources: Option<ResourceStatus>,
+    /// Bytes in the WAL directory
+    pub wal_bytes: u64,
+    /// Last WAL sequence written
+    pub wal_sequence: u64,
+    /// Time of the last checkpoint, if any
+    pub last_checkpoint: Option<String>,
+    /// Time of the newest backup archive, if any
+    pub last_backup: Option<DateTime<Utc>>,
+    pub replication: ReplicationSummary,
+    pub uptime_secs: u64,
+}
+
+/// Source of the statistics the admin endpoints serve
+///
+/// Implemented by the CLI over a booted database; the admin HTTP endpoints
+/// only need this trait. Every call must be cheap.
+pub trait DatabaseStats: Send + Sync {
+    /// Every collection, in name order
+    fn collections(&self) -> Vec<CollectionSummary>;
+
+    /// One collection with its field statistics, if it exists
+    fn collection(&self, name: &str) -> Option<CollectionDetail>;
+
+    /// The node as a whole
+    fn system(&self) -> SystemSummary;
+}
+
+/// Summaries of every collection with a registered schema
+pub fn summarize_collections(
+    schemas: &SchemaLoader,
+    statistics: &Statistics,
+    storage: &StorageWriter,
+    indexes: &IndexManager,
+) -> Vec<CollectionSummary> {
+    let mut versions: BTreeMap<&str, Vec<&Schema>> = BTreeMap::new();
+    for schema in schemas.all_schemas() {
+        versions.entry(&schema.schema_id).or_default().push(schema);
+    }
+    versions
+        .into_values()
+        .map(|versions| summarize(versions, statistics, storage, indexes))
+        .collect()
+}
+
+/// One collection with its field statistics, if it has a schema
+pub fn describe_collection(
+    name: &str,
+    schemas: &SchemaLoader,
+    statistics: &Statistics,
+    storage: &StorageWriter,
+    indexes: &IndexManager,
+) -> Option<CollectionDetail> {
+    let versions: Vec<&Schema> = schemas
+        .all_schemas()
+        .filter(|schema| schema.schema_id == name)
+        .collect();
+    if versions.is_empty() {
+        return None;
+    }
+    let summary = summarize(versions, statistics, storage, indexes);
+    let newest = schemas.get(name, &summary.schema_version)?;
+    let stats = statistics.collection(name);
+
+    let mut fields: Vec<FieldSummary> = newest
+        .fields
+        .iter()
+        .map(|(field, def)| {
+            let sketch = stats.and_then(|s| s.fields.get(field));
+            let documents = stats.map_or(0, |s| s.document_count);
+            FieldSummary {
+                name: field.clone(),
+                field_type: def.field_type.type_name(),
+                required: def.required,
+                indexed: indexes.indexed_fields().contains(field),
+                null_ratio: sketch.filter(|_| documents > 0).map(|sketch| {
+                    documents.saturating_sub(sketch.entries) as f64 / documents as f64
+                }),
+                distinct_estimate: sketch.map(|sketch| sketch.distinct_estimate()),
+            }
+        })
+        .collect();
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Some(CollectionDetail {
+        summary,
+        analyzed_at: stats.and_then(|s| s.analyzed_at),
+        stale: stats.is_none_or(|s| statistics.is_stale(s)),
+        fields,
+    })
+}
+
+/// Summary of the collection whose schema versions are `versions`
+fn summarize(
+    mut versions: Vec<&Schema>,
+    statistics: &Statistics,
+    storage: &StorageWriter,
+    indexes: &IndexManager,
+) -> CollectionSummary {
+    versions.sort_by(|a, b| a.schema_version.cmp(&b.schema_version));
+    let name = versions[0].schema_id.clone();
+    let stats = statistics.collection(&name);
+    let usage = storage.collection_usage(&name);
+    let document_count = usage.map_or(0, |u| u.documents);
+
+    let mut indexed: Vec<&String> = indexes.indexed_fields().iter().collect();
+    indexed.sort();
+    let mut index_list = vec![IndexSummary {
+        name: "_id".to_string(),
+        fields: vec!["_id".to_string()],
+        primary: true,
+        entries: Some(document_count),
+    }];
+    index_list.extend(indexed.into_iter().map(|field| IndexSummary {
+        name: field.clone(),
+        fields: vec![field.clone()],
+        primary: false,
+        entries: stats.map(|s| s.fields.get(field).map_or(0, |sketch| sketch.entries)),
+    }));
+    index_list.extend(
+        indexes
+            .compound_indexes()
+            .map(|(index, fields)| IndexSummary {
+                name: index.to_string(),
+                fields: fields.to_vec(),
+                primary: false,
+                entries: None,
+            }),
+    );
+
+    CollectionSummary {
+        schema_versions: versions.iter().map(|s| s.schema_version.clone()).collect(),
+        schema_version: versions[versions.len() - 1].schema_version.clone(),
+        name,
+        document_count,
+        storage_bytes: usage.map_or(0, |u| u.bytes),
+        storage_records: usage.map_or(0, |u| u.records),
+        last_write: usage.and_then(|u| u.last_write),
+        indexes: index_list,
+    }
+}
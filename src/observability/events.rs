@@ -15,6 +15,7 @@ use std::fmt;
 /// - Snapshot / Checkpoint
 /// - Backup / Restore
 /// - Recovery
+/// - Replica bootstrap and WAL streaming
 /// - Query processing
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Event {
@@ -33,6 +34,16 @@ pub enum Event {
     ConfigLoaded,
     /// Schemas loaded
     SchemasLoaded,
+    /// A reloaded config field took effect
+    ConfigReloadApplied,
+    /// A reloaded config field was refused and keeps its running value
+    ConfigReloadRefused,
+    /// A reloaded config file matched the running configuration
+    ConfigReloadUnchanged,
+    /// A config file could not be reloaded; nothing changed
+    ConfigReloadFailed,
+    /// SIGHUP does not trigger a config reload in this process
+    ConfigReloadSighupUnavailable,
 
     // WAL operations
     /// WAL record appended
@@ -89,9 +100,33 @@ pub enum Event {
     RecoveryVerifyBegin,
     /// Verification complete
     RecoveryVerifyComplete,
+    /// Progress within a recovery phase
+    RecoveryProgress,
     /// Recovery failed (FATAL)
     RecoveryFailed,
 
+    // Replica bootstrap
+    /// Replica bootstrap from a Primary snapshot started
+    ReplicaBootstrapBegin,
+    /// Progress of a base snapshot transfer
+    ReplicaBootstrapProgress,
+    /// Base snapshot installed; the Replica streams the WAL after it
+    ReplicaBootstrapComplete,
+    /// Replica bootstrap failed; the data directory stays marked incomplete
+    ReplicaBootstrapFailed,
+
+    // Synchronous replication
+    /// Quorum not reached in time; writes no longer wait for Replicas
+    ReplicationSyncDegraded,
+    /// Quorum reached again; writes wait for Replicas
+    ReplicationSyncRestored,
+
+    // WAL streaming
+    /// A Replica lost its connection to the Primary and will reconnect
+    ReplicationStreamInterrupted,
+    /// The Primary stopped streaming to a Replica
+    ReplicationStreamEnded,
+
     // Query operations
     /// Query received
     QueryReceived,
@@ -132,6 +167,11 @@ impl Event {
             // Configuration
             Event::ConfigLoaded => "CONFIG_LOADED",
             Event::SchemasLoaded => "SCHEMAS_LOADED",
+            Event::ConfigReloadApplied => "CONFIG_RELOAD_APPLIED",
+            Event::ConfigReloadRefused => "CONFIG_RELOAD_REFUSED",
+            Event::ConfigReloadUnchanged => "CONFIG_RELOAD_UNCHANGED",
+            Event::ConfigReloadFailed => "CONFIG_RELOAD_FAILED",
+            Event::ConfigReloadSighupUnavailable => "CONFIG_RELOAD_SIGHUP_UNAVAILABLE",
 
             // WAL
             Event::WalAppend => "WAL_APPEND",
@@ -166,8 +206,23 @@ impl Event {
             Event::RecoveryIndexRebuildComplete => "INDEX_REBUILD_COMPLETE",
             Event::RecoveryVerifyBegin => "VERIFICATION_BEGIN",
             Event::RecoveryVerifyComplete => "VERIFICATION_COMPLETE",
+            Event::RecoveryProgress => "RECOVERY_PROGRESS",
             Event::RecoveryFailed => "RECOVERY_FAILED",
 
+            // Replica bootstrap
+            Event::ReplicaBootstrapBegin => "REPLICA_BOOTSTRAP_BEGIN",
+            Event::ReplicaBootstrapProgress => "REPLICA_BOOTSTRAP_PROGRESS",
+            Event::ReplicaBootstrapComplete => "REPLICA_BOOTSTRAP_COMPLETE",
+            Event::ReplicaBootstrapFailed => "REPLICA_BOOTSTRAP_FAILED",
+
+            // Synchronous replication
+            Event::ReplicationSyncDegraded => "REPLICATION_SYNC_DEGRADED",
+            Event::ReplicationSyncRestored => "REPLICATION_SYNC_RESTORED",
+
+            // WAL streaming
+            Event::ReplicationStreamInterrupted => "REPLICATION_STREAM_INTERRUPTED",
+            Event::ReplicationStreamEnded => "REPLICATION_STREAM_ENDED",
+
             // Query
             Event::QueryReceived => "QUERY_BEGIN",
             Event::QueryPlanned => "QUERY_PLANNED",
@@ -212,6 +267,11 @@ mod tests {
             Event::ShutdownComplete,
             Event::ConfigLoaded,
             Event::SchemasLoaded,
+            Event::ConfigReloadApplied,
+            Event::ConfigReloadRefused,
+            Event::ConfigReloadUnchanged,
+            Event::ConfigReloadFailed,
+            Event::ConfigReloadSighupUnavailable,
             Event::WalAppend,
             Event::WalFsync,
             Event::WalTruncate,
@@ -234,7 +294,16 @@ mod tests {
             Event::RecoveryIndexRebuildComplete,
             Event::RecoveryVerifyBegin,
             Event::RecoveryVerifyComplete,
+            Event::RecoveryProgress,
             Event::RecoveryFailed,
+            Event::ReplicaBootstrapBegin,
+            Event::ReplicaBootstrapProgress,
+            Event::ReplicaBootstrapComplete,
+            Event::ReplicaBootstrapFailed,
+            Event::ReplicationSyncDegraded,
+            Event::ReplicationSyncRestored,
+            Event::ReplicationStreamInterrupted,
+            Event::ReplicationStreamEnded,
             Event::QueryReceived,
             Event::QueryPlanned,
             Event::QueryExecuted,
@@ -9,9 +9,18 @@
 
 use std::fmt;
 use std::io::{self, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use super::request_id::current_request_id;
 
 /// Log severity levels per OBSERVABILITY.md
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// Named in config files in lower case (`observability.log_level = "warn"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Severity {
     /// Debug-level detail
     Trace = 0,
@@ -36,6 +45,16 @@ impl Severity {
             Severity::Fatal => "FATAL",
         }
     }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Severity::Trace,
+            1 => Severity::Info,
+            2 => Severity::Warn,
+            3 => Severity::Error,
+            _ => Severity::Fatal,
+        }
+    }
 }
 
 impl fmt::Display for Severity {
@@ -44,25 +63,69 @@ impl fmt::Display for Severity {
     }
 }
 
+/// A sink for structured log events
+///
+/// Components that log hold an injected `Arc<dyn Logger>` so output can be
+/// redirected or captured. Each event is a name plus key/value fields.
+pub trait Logger: Send + Sync + fmt::Debug {
+    /// Log an event with the given severity and fields
+    fn log(&self, severity: Severity, event: &str, fields: &[(&str, &str)]);
+
+    /// Log at TRACE level
+    fn trace(&self, event: &str, fields: &[(&str, &str)]) {
+        self.log(Severity::Trace, event, fields);
+    }
+
+    /// Log at INFO level
+    fn info(&self, event: &str, fields: &[(&str, &str)]) {
+        self.log(Severity::Info, event, fields);
+    }
+
+    /// Log at WARN level
+    fn warn(&self, event: &str, fields: &[(&str, &str)]) {
+        self.log(Severity::Warn, event, fields);
+    }
+
+    /// Log at ERROR level
+    fn error(&self, event: &str, fields: &[(&str, &str)]) {
+        self.log(Severity::Error, event, fields);
+    }
+
+    /// Log at FATAL level
+    fn fatal(&self, event: &str, fields: &[(&str, &str)]) {
+        self.log(Severity::Fatal, event, fields);
+    }
+}
+
 /// A structured logger that outputs JSON logs
 ///
 /// Per OBSERVABILITY.md:
 /// - Logs are synchronous
 /// - No buffering
 /// - Deterministic key ordering
-pub struct Logger;
+///
+/// TRACE, INFO and WARN go to stdout; ERROR and FATAL go to stderr.
+/// Events below [`JsonLogger::min_severity`] are dropped.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonLogger;
+
+/// Lowest severity every [`JsonLogger`] writes
+static MIN_SEVERITY: AtomicU8 = AtomicU8::new(Severity::Trace as u8);
+
+impl JsonLogger {
+    /// Shared handle for injecting into components
+    pub fn shared() -> Arc<dyn Logger> {
+        Arc::new(JsonLogger)
+    }
 
-impl Logger {
-    /// Log an event with the given severity and fields
-    ///
-    /// Fields are output in deterministic order (alphabetical by key)
-    pub fn log(severity: Severity, event: &str, fields: &[(&str, &str)]) {
-        Self::log_to_writer(severity, event, fields, &mut io::stdout());
+    /// Lowest severity written; TRACE until set
+    pub fn min_severity() -> Severity {
+        Severity::from_u8(MIN_SEVERITY.load(Ordering::Relaxed))
     }
 
-    /// Log to stderr (for errors and fatal messages)
-    pub fn log_stderr(severity: Severity, event: &str, fields: &[(&str, &str)]) {
-        Self::log_to_writer(severity, event, fields, &mut io::stderr());
+    /// Drop events below `severity` from now on, in every handle
+    pub fn set_min_severity(severity: Severity) {
+        MIN_SEVERITY.store(severity as u8, Ordering::Relaxed);
     }
 
     /// Internal log implementation that writes to a given writer
@@ -123,30 +186,104 @@ impl Logger {
             }
         }
     }
+}
 
-    /// Log at TRACE level
-    pub fn trace(event: &str, fields: &[(&str, &str)]) {
-        Self::log(Severity::Trace, event, fields);
+impl Logger for JsonLogger {
+    /// Fields are output in deterministic order (alphabetical by key)
+    fn log(&self, severity: Severity, event: &str, fields: &[(&str, &str)]) {
+        if severity < Self::min_severity() {
+            return;
+        }
+        with_request_id(fields, |fields| {
+            if severity >= Severity::Error {
+                Self::log_to_writer(severity, event, fields, &mut io::stderr());
+            } else {
+                Self::log_to_writer(severity, event, fields, &mut io::stdout());
+            }
+        });
     }
+}
 
-    /// Log at INFO level
-    pub fn info(event: &str, fields: &[(&str, &str)]) {
-        Self::log(Severity::Info, event, fields);
+/// `fields` plus the current request's id, unless absent or already given
+fn with_request_id(fields: &[(&str, &str)], log: impl FnOnce(&[(&str, &str)])) {
+    let request_id = current_request_id()
+        .filter(|_| !fields.iter().any(|(key, _)| *key == "request_id"))
+        .map(|id| id.to_string());
+    match request_id {
+        Some(request_id) => {
+            let mut tagged = fields.to_vec();
+            tagged.push(("request_id", &request_id));
+            log(&tagged);
+        }
+        None => log(fields),
     }
+}
 
-    /// Log at WARN level
-    pub fn warn(event: &str, fields: &[(&str, &str)]) {
-        Self::log(Severity::Warn, event, fields);
+/// A logger that discards every event
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullLogger;
+
+impl Logger for NullLogger {
+    fn log(&self, _severity: Severity, _event: &str, _fields: &[(&str, &str)]) {}
+}
+
+/// An event captured by [`VecLogger`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub severity: Severity,
+    pub event: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogRecord {
+    /// Value of the named field, if present
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
     }
+}
 
-    /// Log at ERROR level
-    pub fn error(event: &str, fields: &[(&str, &str)]) {
-        Self::log_stderr(Severity::Error, event, fields);
+/// A logger that keeps events in memory, for inspecting log output
+#[derive(Debug, Default)]
+pub struct VecLogger {
+    records: Mutex<Vec<LogRecord>>,
+}
+
+impl VecLogger {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Log at FATAL level
-    pub fn fatal(event: &str, fields: &[(&str, &str)]) {
-        Self::log_stderr(Severity::Fatal, event, fields);
+    /// Events captured so far, in emission order
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.records
+            .lock()
+            .map(|records| records.clone())
+            .unwrap_or_default()
+    }
+
+    /// Names of the events captured so far
+    pub fn events(&self) -> Vec<String> {
+        self.records().into_iter().map(|r| r.event).collect()
+    }
+}
+
+impl Logger for VecLogger {
+    fn log(&self, severity: Severity, event: &str, fields: &[(&str, &str)]) {
+        with_request_id(fields, |fields| {
+            if let Ok(mut records) = self.records.lock() {
+                records.push(LogRecord {
+                    severity,
+                    event: event.to_string(),
+                    fields: fields
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                });
+            }
+        });
     }
 }
 
@@ -154,7 +291,7 @@ impl Logger {
 #[cfg(test)]
 pub fn capture_log(severity: Severity, event: &str, fields: &[(&str, &str)]) -> String {
     let mut buffer = Vec::new();
-    Logger::log_to_writer(severity, event, fields, &mut buffer);
+    JsonLogger::log_to_writer(severity, event, fields, &mut buffer);
     String::from_utf8(buffer).unwrap()
 }
 
@@ -179,6 +316,25 @@ mod tests {
         assert_eq!(Severity::Fatal.as_str(), "FATAL");
     }
 
+    #[test]
+    fn test_severity_config_names() {
+        let severity: Severity = serde_json::from_str("\"warn\"").unwrap();
+        assert_eq!(severity, Severity::Warn);
+        assert_eq!(
+            serde_json::to_string(&Severity::Trace).unwrap(),
+            "\"trace\""
+        );
+        assert!(serde_json::from_str::<Severity>("\"WARN\"").is_err());
+        for severity in [
+            Severity::Trace,
+            Severity::Info,
+            Severity::Error,
+            Severity::Fatal,
+        ] {
+            assert_eq!(Severity::from_u8(severity as u8), severity);
+        }
+    }
+
     #[test]
     fn test_log_json_format() {
         let output = capture_log(Severity::Info, "TEST_EVENT", &[]);
@@ -264,4 +420,28 @@ mod tests {
 
         assert!(event_pos < severity_pos);
     }
+
+    #[test]
+    fn test_vec_logger_captures_events() {
+        let logger = VecLogger::new();
+        logger.warn("FIRST", &[("k", "v")]);
+        logger.error("SECOND", &[]);
+
+        let records = logger.records();
+        assert_eq!(logger.events(), vec!["FIRST", "SECOND"]);
+        assert_eq!(records[0].severity, Severity::Warn);
+        assert_eq!(records[0].field("k"), Some("v"));
+        assert_eq!(records[1].severity, Severity::Error);
+        assert_eq!(records[1].field("k"), None);
+    }
+
+    #[test]
+    fn test_loggers_as_trait_objects() {
+        let capture = Arc::new(VecLogger::new());
+        let loggers: Vec<Arc<dyn Logger>> = vec![Arc::new(NullLogger), capture.clone()];
+        for logger in &loggers {
+            logger.info("EVENT", &[("n", "1")]);
+        }
+        assert_eq!(capture.events(), vec!["EVENT"]);
+    }
 }
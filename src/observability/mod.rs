@@ -16,10 +16,10 @@
 //! # Usage
 //!
 //! ```ignore
-//! use aerodb::observability::{Logger, Event, MetricsRegistry, ObservationScope};
+//! use aerodb::observability::{JsonLogger, Logger, Event, MetricsRegistry, ObservationScope};
 //!
 //! // Log an event
-//! Logger::info("QUERY_COMPLETE", &[("rows", "42")]);
+//! JsonLogger.info("QUERY_COMPLETE", &[("rows", "42")]);
 //!
 //! // Track metrics
 //! let metrics = MetricsRegistry::new();
@@ -32,21 +32,34 @@
 //! ```
 
 pub mod audit;
+pub mod database_stats;
 mod events;
 mod logger;
 mod metrics;
 pub mod operation_log;
+pub mod request_id;
 mod scope;
 pub mod slow_query;
 
-pub use audit::{AuditAction, AuditLog, AuditOutcome, AuditRecord, FileAuditLog, MemoryAuditLog};
+pub use audit::{
+    verify_chain, AuditAction, AuditLog, AuditOutcome, AuditRecord, BrokenLink, ChainReport,
+    FileAuditLog, MemoryAuditLog,
+};
+pub use database_stats::{
+    describe_collection, summarize_collections, CollectionDetail, CollectionSummary,
+    DatabaseStats, FieldSummary, IndexSummary, ReplicaLagSummary, ReplicationSummary,
+    SystemSummary,
+};
 pub use events::Event;
-pub use logger::{Logger, Severity};
+pub use logger::{JsonLogger, LogRecord, Logger, NullLogger, Severity, VecLogger};
 pub use metrics::{MetricsRegistry, MetricsSnapshot};
 pub use operation_log::{
     OperationLog, OperationLogConfig, OperationLogEntry, OperationResult, OperationType,
     SharedOperationLog,
 };
+pub use request_id::{
+    current_request_id, in_request, in_request_sync, resolve_request_id, REQUEST_ID_HEADER,
+};
 pub use scope::{ObservationScope, Timer};
 
 use std::fmt;
@@ -61,6 +74,14 @@ pub struct ObservabilityConfig {
     
     #[serde(default)]
     pub slow_query: slow_query::SlowQueryConfig,
+
+    /// Lowest severity the structured logger writes
+    #[serde(default = "default_log_level")]
+    pub log_level: Severity,
+}
+
+fn default_log_level() -> Severity {
+    Severity::Trace
 }
 
 impl Default for ObservabilityConfig {
@@ -68,6 +89,7 @@ impl Default for ObservabilityConfig {
         Self {
             operation_log: OperationLogConfig::default(),
             slow_query: slow_query::SlowQueryConfig::default(),
+            log_level: default_log_level(),
         }
     }
 }
@@ -175,7 +197,7 @@ pub fn log_event(event: Event) {
     } else {
         Severity::Info
     };
-    Logger::log(severity, event.as_str(), &[]);
+    JsonLogger.log(severity, event.as_str(), &[]);
 }
 
 /// Log a lifecycle event with fields
@@ -185,7 +207,7 @@ pub fn log_event_with_fields(event: Event, fields: &[(&str, &str)]) {
     } else {
         Severity::Info
     };
-    Logger::log(severity, event.as_str(), fields);
+    JsonLogger.log(severity, event.as_str(), fields);
 }
 
 #[cfg(test)]
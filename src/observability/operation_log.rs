@@ -19,12 +19,15 @@
 //! - **No hidden aggregation**: Raw entries only
 
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime};
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::request_id::current_request_id;
+
 /// Operation type for logging
 ///
 /// MANIFESTO ALIGNMENT: All operation types are explicit.
@@ -49,6 +52,10 @@ pub enum OperationType {
     Subscribe,
     /// File storage operation
     Storage,
+    /// Expiry sweep removing documents past their TTL
+    Expire,
+    /// Batch of writes applied atomically
+    Transaction,
 }
 
 impl OperationType {
@@ -64,6 +71,8 @@ impl OperationType {
             OperationType::Function => "function",
             OperationType::Subscribe => "subscribe",
             OperationType::Storage => "storage",
+            OperationType::Expire => "expire",
+            OperationType::Transaction => "transaction",
         }
     }
 }
@@ -84,6 +93,13 @@ pub enum OperationResult {
         /// Human-readable error message
         message: String,
     },
+    /// Operation abandoned at its deadline
+    TimedOut {
+        /// Timeout that applied, in milliseconds
+        timeout_ms: u64,
+        /// Documents examined before cancellation
+        documents_examined: usize,
+    },
 }
 
 /// A single operation log entry
@@ -95,6 +111,10 @@ pub struct OperationLogEntry {
     /// Unique operation ID
     pub id: Uuid,
 
+    /// ID of the request that performed the operation, if known
+    #[serde(default)]
+    pub request_id: Option<Uuid>,
+
     /// Timestamp of operation
     pub timestamp: SystemTime,
 
@@ -107,6 +127,11 @@ pub struct OperationLogEntry {
     /// User ID (if authenticated)
     pub user_id: Option<Uuid>,
 
+    /// Caller's access level (`anon`, `authenticated` or `service_role`),
+    /// so RLS bypasses stand out
+    #[serde(default)]
+    pub role: Option<String>,
+
     /// Execution duration in milliseconds
     ///
     /// MANIFESTO ALIGNMENT: Duration is explicit, not hidden.
@@ -141,11 +166,15 @@ pub struct OperationLogEntry {
 
 impl OperationLogEntry {
     /// Create a new operation log entry builder
+    ///
+    /// The entry belongs to the request being handled, if any.
     pub fn builder(operation: OperationType) -> OperationLogEntryBuilder {
         OperationLogEntryBuilder {
             operation,
+            request_id: current_request_id(),
             collection: None,
             user_id: None,
+            role: None,
             duration_ms: 0,
             documents_scanned: None,
             documents_affected: None,
@@ -160,8 +189,10 @@ impl OperationLogEntry {
 /// Builder for operation log entries
 pub struct OperationLogEntryBuilder {
     operation: OperationType,
+    request_id: Option<Uuid>,
     collection: Option<String>,
     user_id: Option<Uuid>,
+    role: Option<String>,
     duration_ms: u64,
     documents_scanned: Option<usize>,
     documents_affected: Option<usize>,
@@ -178,12 +209,24 @@ impl OperationLogEntryBuilder {
         self
     }
 
+    /// Set the ID of the request that performed the operation
+    pub fn request_id(mut self, request_id: Uuid) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
     /// Set user ID
     pub fn user_id(mut self, user_id: Uuid) -> Self {
         self.user_id = Some(user_id);
         self
     }
 
+    /// Set the caller's access level
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+
     /// Set execution duration
     pub fn duration(mut self, duration: Duration) -> Self {
         self.duration_ms = duration.as_millis() as u64;
@@ -239,6 +282,16 @@ impl OperationLogEntryBuilder {
         self
     }
 
+    /// Set result as timed out, recording the documents examined
+    pub fn timed_out(mut self, timeout_ms: u64, documents_examined: usize) -> Self {
+        self.result_status = OperationResult::TimedOut {
+            timeout_ms,
+            documents_examined,
+        };
+        self.documents_scanned = Some(documents_examined);
+        self
+    }
+
     /// Set explain plan reference
     ///
     /// CERTIFICATION REQUIREMENT: Correlation with query plan.
@@ -251,10 +304,12 @@ impl OperationLogEntryBuilder {
     pub fn build(self) -> OperationLogEntry {
         OperationLogEntry {
             id: Uuid::new_v4(),
+            request_id: self.request_id,
             timestamp: SystemTime::now(),
             collection: self.collection,
             operation: self.operation,
             user_id: self.user_id,
+            role: self.role,
             duration_ms: self.duration_ms,
             documents_scanned: self.documents_scanned,
             documents_affected: self.documents_affected,
@@ -269,7 +324,9 @@ impl OperationLogEntryBuilder {
 /// Operation log configuration
 ///
 /// MANIFESTO ALIGNMENT: Configuration is explicit, no hidden defaults.
+/// Fields missing from a config file take the documented defaults below.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OperationLogConfig {
     /// Whether operation logging is enabled
     ///
@@ -320,6 +377,8 @@ impl Default for OperationLogConfig {
 #[derive(Debug)]
 pub struct OperationLog {
     config: OperationLogConfig,
+    /// Starts as `config.enabled`; toggled by config reload
+    enabled: AtomicBool,
     entries: RwLock<VecDeque<OperationLogEntry>>,
 }
 
@@ -327,6 +386,7 @@ impl OperationLog {
     /// Create a new operation log with the given configuration
     pub fn new(config: OperationLogConfig) -> Self {
         Self {
+            enabled: AtomicBool::new(config.enabled),
             config,
             entries: RwLock::new(VecDeque::new()),
         }
@@ -342,7 +402,14 @@ impl OperationLog {
 
     /// Check if operation logging is enabled
     pub fn is_enabled(&self) -> bool {
-        self.config.enabled
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Turn logging on or off without restarting
+    ///
+    /// Entries already recorded are kept either way.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
     }
 
     /// Get the slow query threshold
@@ -355,7 +422,7 @@ impl OperationLog {
     /// MANIFESTO ALIGNMENT: If logging is enabled, ALL operations are logged.
     /// No sampling, no hidden filtering.
     pub fn log(&self, entry: OperationLogEntry) {
-        if !self.config.enabled {
+        if !self.is_enabled() {
             return;
         }
 
@@ -376,6 +443,17 @@ impl OperationLog {
             .unwrap_or_default()
     }
 
+    /// The newest `n` entries, oldest first
+    ///
+    /// Returns nothing while the log is being written, so a panic hook can
+    /// call it without risking deadlock.
+    pub fn recent(&self, n: usize) -> Vec<OperationLogEntry> {
+        self.entries
+            .try_read()
+            .map(|e| e.iter().skip(e.len().saturating_sub(n)).cloned().collect())
+            .unwrap_or_default()
+    }
+
     /// Get slow queries only
     ///
     /// MANIFESTO ALIGNMENT: Slow query detection uses configured threshold.
@@ -444,6 +522,28 @@ mod tests {
         assert_eq!(entry.index_used, Some("email_idx".to_string()));
     }
 
+    #[test]
+    fn test_operation_log_records_timeout() {
+        let entry = OperationLogEntry::builder(OperationType::Find)
+            .collection("users")
+            .duration_ms(12)
+            .timed_out(10, 128)
+            .build();
+
+        assert_eq!(
+            entry.result_status,
+            OperationResult::TimedOut {
+                timeout_ms: 10,
+                documents_examined: 128
+            }
+        );
+        assert_eq!(entry.documents_scanned, Some(128));
+
+        let json = serde_json::to_value(&entry.result_status).unwrap();
+        assert_eq!(json["status"], "timed_out");
+        assert_eq!(json["documents_examined"], 128);
+    }
+
     #[test]
     fn test_operation_log_append_only() {
         let config = OperationLogConfig {
@@ -515,6 +615,18 @@ mod tests {
         assert_eq!(log.count(), 0);
     }
 
+    #[test]
+    fn test_operation_log_set_enabled() {
+        let log = OperationLog::disabled();
+        log.set_enabled(true);
+        log.log(OperationLogEntry::builder(OperationType::Find).build());
+        assert_eq!(log.count(), 1);
+
+        log.set_enabled(false);
+        log.log(OperationLogEntry::builder(OperationType::Find).build());
+        assert_eq!(log.count(), 1);
+    }
+
     #[test]
     fn test_operation_log_max_entries() {
         let config = OperationLogConfig {
@@ -542,4 +654,25 @@ mod tests {
         assert_eq!(entries[1].duration_ms, 30); // Entry 3
         assert_eq!(entries[2].duration_ms, 40); // Entry 4
     }
+
+    #[test]
+    fn test_operation_log_recent() {
+        let log = OperationLog::new(OperationLogConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        for i in 0..5 {
+            log.log(
+                OperationLogEntry::builder(OperationType::Find)
+                    .duration_ms(i)
+                    .build(),
+            );
+        }
+
+        let recent = log.recent(2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].duration_ms, 3);
+        assert_eq!(recent[1].duration_ms, 4);
+        assert_eq!(log.recent(10).len(), 5);
+    }
 }
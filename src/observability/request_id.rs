@@ -0,0 +1,69 @@
+//! Request ids
+//!
+//! Every HTTP request and every stdin API request is given an id, or keeps
+//! the one its caller supplied. While the request is handled the id is held
+//! in a task-local, so the operation log entries, slow query events, audit
+//! records and structured log lines it produces all carry it, and it is
+//! returned to the caller in the `x-request-id` header or the error
+//! envelope.
+
+use std::future::Future;
+
+use uuid::Uuid;
+
+/// Header carrying the request id, in both directions
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: Uuid;
+}
+
+/// The caller's id if it is a UUID, otherwise a new one
+pub fn resolve_request_id(supplied: Option<&str>) -> Uuid {
+    supplied
+        .and_then(|id| Uuid::parse_str(id.trim()).ok())
+        .unwrap_or_else(Uuid::new_v4)
+}
+
+/// Id of the request being handled, if any
+pub fn current_request_id() -> Option<Uuid> {
+    REQUEST_ID.try_with(|id| *id).ok()
+}
+
+/// Run `future` as the handling of request `id`
+pub async fn in_request<F: Future>(id: Uuid, future: F) -> F::Output {
+    REQUEST_ID.scope(id, future).await
+}
+
+/// Run `f` on this thread as the handling of request `id`
+pub fn in_request_sync<R>(id: Uuid, f: impl FnOnce() -> R) -> R {
+    REQUEST_ID.sync_scope(id, f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_honors_supplied_uuid() {
+        let id = Uuid::new_v4();
+        assert_eq!(resolve_request_id(Some(&id.to_string())), id);
+        assert_ne!(resolve_request_id(Some("not-a-uuid")), id);
+        assert!(!resolve_request_id(None).is_nil());
+    }
+
+    #[test]
+    fn test_current_request_id_is_scoped() {
+        let id = Uuid::new_v4();
+        assert_eq!(current_request_id(), None);
+        assert_eq!(in_request_sync(id, current_request_id), Some(id));
+        assert_eq!(current_request_id(), None);
+    }
+
+    #[tokio::test]
+    async fn test_current_request_id_in_task() {
+        let id = Uuid::new_v4();
+        let seen = in_request(id, async { current_request_id() }).await;
+        assert_eq!(seen, Some(id));
+    }
+}
@@ -7,7 +7,7 @@
 
 use std::cell::Cell;
 
-use super::logger::{Logger, Severity};
+use super::logger::{JsonLogger, Logger};
 
 /// A scope that automatically logs start and complete events
 ///
@@ -37,7 +37,7 @@ impl<'a> ObservationScope<'a> {
     /// Logs `{name}_BEGIN` immediately.
     pub fn new(name: &'a str) -> Self {
         let event = format!("{}_BEGIN", name);
-        Logger::info(&event, &[]);
+        JsonLogger.info(&event, &[]);
 
         Self {
             name,
@@ -50,7 +50,7 @@ impl<'a> ObservationScope<'a> {
     pub fn with_fields(name: &'a str, fields: &[(&'a str, &str)]) -> Self {
         let event = format!("{}_BEGIN", name);
         let field_refs: Vec<(&str, &str)> = fields.iter().map(|(k, v)| (*k, *v)).collect();
-        Logger::info(&event, &field_refs);
+        JsonLogger.info(&event, &field_refs);
 
         Self {
             name,
@@ -67,7 +67,7 @@ impl<'a> ObservationScope<'a> {
         let event = format!("{}_COMPLETE", self.name);
         let field_refs: Vec<(&str, &str)> =
             self.fields.iter().map(|(k, v)| (*k, v.as_str())).collect();
-        Logger::info(&event, &field_refs);
+        JsonLogger.info(&event, &field_refs);
     }
 
     /// Mark the scope as successfully completed with additional fields
@@ -79,7 +79,7 @@ impl<'a> ObservationScope<'a> {
             self.fields.iter().map(|(k, v)| (*k, v.as_str())).collect();
         all_fields.extend(extra_fields.iter().copied());
 
-        Logger::info(&event, &all_fields);
+        JsonLogger.info(&event, &all_fields);
     }
 
     /// Mark the scope as failed with a reason
@@ -88,7 +88,7 @@ impl<'a> ObservationScope<'a> {
     pub fn fail(self, reason: &str) {
         self.completed.set(true);
         let event = format!("{}_FAILED", self.name);
-        Logger::error(&event, &[("reason", reason)]);
+        JsonLogger.error(&event, &[("reason", reason)]);
     }
 
     /// Mark the scope as failed with FATAL severity
@@ -97,7 +97,7 @@ impl<'a> ObservationScope<'a> {
     pub fn fail_fatal(self, reason: &str) {
         self.completed.set(true);
         let event = format!("{}_FAILED", self.name);
-        Logger::fatal(&event, &[("reason", reason)]);
+        JsonLogger.fatal(&event, &[("reason", reason)]);
     }
 
     /// Check if the scope has been completed
@@ -111,7 +111,7 @@ impl Drop for ObservationScope<'_> {
         // Only log error if not already completed
         if !self.completed.get() {
             let event = format!("{}_INCOMPLETE", self.name);
-            Logger::warn(&event, &[("reason", "scope dropped without completion")]);
+            JsonLogger.warn(&event, &[("reason", "scope dropped without completion")]);
         }
     }
 }
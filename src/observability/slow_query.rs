@@ -19,8 +19,11 @@
 //! - **No hidden aggregation**: Raw slow query events only
 
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+use super::logger::{JsonLogger, Logger};
+
 /// Slow query configuration
 ///
 /// MANIFESTO ALIGNMENT: Configuration is explicit, no hidden defaults.
@@ -116,6 +119,9 @@ impl SlowQueryConfig {
 pub struct SlowQueryEvent {
     /// Unique operation ID
     pub operation_id: uuid::Uuid,
+    /// ID of the request that ran the query (if known)
+    #[serde(default)]
+    pub request_id: Option<uuid::Uuid>,
     /// Collection name (if applicable)
     pub collection: Option<String>,
     /// Operation type (e.g., "find", "insert")
@@ -138,38 +144,60 @@ pub struct SlowQueryEvent {
 ///
 /// MANIFESTO ALIGNMENT: Deterministic slow query detection.
 /// Per certification: Must track queries exceeding configured threshold.
+/// The configuration can be replaced while the tracker is in use (config
+/// reload); each query is judged against the configuration current at the
+/// time it is checked.
 pub struct SlowQueryTracker {
-    config: SlowQueryConfig,
+    config: RwLock<SlowQueryConfig>,
+    logger: Arc<dyn Logger>,
 }
 
 impl SlowQueryTracker {
     /// Create a new slow query tracker with the given configuration
     pub fn new(config: SlowQueryConfig) -> Self {
-        Self { config }
+        Self {
+            config: RwLock::new(config),
+            logger: JsonLogger::shared(),
+        }
+    }
+
+    /// Send slow query and webhook failure events to `logger`
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
     }
 
     /// Create a disabled tracker
     pub fn disabled() -> Self {
-        Self {
-            config: SlowQueryConfig::disabled(),
-        }
+        Self::new(SlowQueryConfig::disabled())
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> SlowQueryConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Replace the configuration without restarting
+    pub fn reconfigure(&self, config: SlowQueryConfig) {
+        *self.config.write().unwrap() = config;
     }
 
     /// Check if slow query tracking is enabled
     pub fn is_enabled(&self) -> bool {
-        self.config.enabled
+        self.config.read().unwrap().enabled
     }
 
     /// Get the configured threshold in milliseconds
     pub fn threshold_ms(&self) -> u64 {
-        self.config.threshold_ms
+        self.config.read().unwrap().threshold_ms
     }
 
     /// Check if a duration exceeds the slow query threshold
     ///
     /// MANIFESTO ALIGNMENT: Deterministic comparison.
     pub fn is_slow(&self, duration_ms: u64) -> bool {
-        self.config.enabled && duration_ms > self.config.threshold_ms
+        let config = self.config.read().unwrap();
+        config.enabled && duration_ms > config.threshold_ms
     }
 
     /// Track a slow query event
@@ -179,18 +207,20 @@ impl SlowQueryTracker {
     /// - If webhook_url is configured, sends a POST request
     /// - Webhook failures are logged but never crash the database
     pub fn track(&self, event: SlowQueryEvent) {
-        if !self.config.enabled {
+        let config = self.config();
+        if !config.enabled {
             return;
         }
 
         // Emit structured log if configured
-        if self.config.emit_log {
+        if config.emit_log {
             self.emit_log(&event);
         }
 
         // Send webhook if configured (fire-and-forget)
-        if let Some(ref url) = self.config.webhook_url {
-            self.send_webhook(url, &event);
+        if let Some(ref url) = config.webhook_url {
+            let timeout = Duration::from_millis(config.webhook_timeout_ms);
+            self.send_webhook(url, &event, timeout);
         }
     }
 
@@ -198,27 +228,45 @@ impl SlowQueryTracker {
     ///
     /// MANIFESTO ALIGNMENT: JSON structured logging.
     fn emit_log(&self, event: &SlowQueryEvent) {
-        // Using eprintln for structured logging to stderr
-        // In production, this would integrate with the Logger subsystem
-        if let Ok(json) = serde_json::to_string(event) {
-            eprintln!(
-                "{{\"level\":\"WARN\",\"event\":\"SLOW_QUERY\",\"details\":{}}}",
-                json
-            );
-        }
+        let operation_id = event.operation_id.to_string();
+        let duration_ms = event.duration_ms.to_string();
+        let threshold_ms = event.threshold_ms.to_string();
+        let request_id = event.request_id.map(|id| id.to_string());
+        let user_id = event.user_id.map(|id| id.to_string());
+        let documents_scanned = event.documents_scanned.map(|n| n.to_string());
+
+        let mut fields = vec![
+            ("operation_id", operation_id.as_str()),
+            ("operation_type", event.operation_type.as_str()),
+            ("duration_ms", duration_ms.as_str()),
+            ("threshold_ms", threshold_ms.as_str()),
+            ("timestamp", event.timestamp.as_str()),
+        ];
+        let optional = [
+            ("collection", event.collection.as_deref()),
+            ("request_id", request_id.as_deref()),
+            ("user_id", user_id.as_deref()),
+            ("index_used", event.index_used.as_deref()),
+            ("documents_scanned", documents_scanned.as_deref()),
+        ];
+        fields.extend(
+            optional
+                .into_iter()
+                .filter_map(|(key, value)| value.map(|v| (key, v))),
+        );
+
+        self.logger.warn("SLOW_QUERY", &fields);
     }
 
     /// Send webhook notification for slow query
     ///
     /// MANIFESTO ALIGNMENT: Fire-and-forget, timeout-bounded.
     /// Failures are logged but never crash the database.
-    fn send_webhook(&self, url: &str, event: &SlowQueryEvent) {
+    fn send_webhook(&self, url: &str, event: &SlowQueryEvent, timeout: Duration) {
         // NOTE: This is a synchronous, blocking call with timeout.
         // In production, consider using a bounded async queue.
         // Per manifesto: We do NOT retry, we do NOT buffer.
 
-        let timeout = Duration::from_millis(self.config.webhook_timeout_ms);
-
         // Attempt to send webhook - failure is non-fatal
         match self.try_send_webhook(url, event, timeout) {
             Ok(()) => {
@@ -226,10 +274,8 @@ impl SlowQueryTracker {
             }
             Err(e) => {
                 // Log failure but do not crash
-                eprintln!(
-                    "{{\"level\":\"ERROR\",\"event\":\"SLOW_QUERY_WEBHOOK_FAILED\",\"url\":\"{}\",\"error\":\"{}\"}}",
-                    url, e
-                );
+                self.logger
+                    .error("SLOW_QUERY_WEBHOOK_FAILED", &[("url", url), ("error", &e)]);
             }
         }
     }
@@ -308,11 +354,13 @@ impl SlowQueryTracker {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::observability::{Severity, VecLogger};
     use uuid::Uuid;
 
     fn create_test_event(duration_ms: u64) -> SlowQueryEvent {
         SlowQueryEvent {
             operation_id: Uuid::new_v4(),
+            request_id: None,
             collection: Some("test_collection".to_string()),
             operation_type: "find".to_string(),
             duration_ms,
@@ -374,6 +422,16 @@ mod tests {
         assert!(tracker.is_slow(1000));
     }
 
+    #[test]
+    fn test_reconfigure_changes_threshold() {
+        let tracker = SlowQueryTracker::new(SlowQueryConfig::with_threshold_ms(100));
+        assert!(tracker.is_slow(150));
+
+        tracker.reconfigure(SlowQueryConfig::with_threshold_ms(200));
+        assert_eq!(tracker.threshold_ms(), 200);
+        assert!(!tracker.is_slow(150));
+    }
+
     #[test]
     fn test_tracker_tracks_without_panic() {
         // Verify tracking doesn't panic even with no webhook configured
@@ -400,10 +458,72 @@ mod tests {
             webhook_url: Some("http://invalid-host-that-does-not-exist:9999/webhook".to_string()),
             webhook_timeout_ms: 100, // Very short timeout
         };
-        let tracker = SlowQueryTracker::new(config);
+        let logger = Arc::new(VecLogger::new());
+        let tracker = SlowQueryTracker::new(config).with_logger(logger.clone());
         let event = create_test_event(200);
 
         // This should NOT panic even though webhook will fail
         tracker.track(event);
+
+        let records = logger.records();
+        assert_eq!(logger.events(), vec!["SLOW_QUERY_WEBHOOK_FAILED"]);
+        assert_eq!(records[0].severity, Severity::Error);
+        assert!(records[0].field("error").is_some());
+    }
+
+    #[test]
+    fn test_slow_query_logged_with_fields() {
+        let logger = Arc::new(VecLogger::new());
+        let tracker =
+            SlowQueryTracker::new(SlowQueryConfig::enabled()).with_logger(logger.clone());
+        let mut event = create_test_event(250);
+        event.user_id = None;
+        tracker.track(event.clone());
+
+        let records = logger.records();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.event, "SLOW_QUERY");
+        assert_eq!(record.severity, Severity::Warn);
+        assert_eq!(record.field("duration_ms"), Some("250"));
+        assert_eq!(record.field("collection"), Some("test_collection"));
+        assert_eq!(record.field("documents_scanned"), Some("1000"));
+        assert_eq!(
+            record.field("operation_id"),
+            Some(event.operation_id.to_string().as_str())
+        );
+        assert_eq!(record.field("user_id"), None);
+        assert_eq!(record.field("request_id"), None);
+
+        // Disabled trackers and emit_log = false stay silent
+        let silent = Arc::new(VecLogger::new());
+        SlowQueryTracker::disabled()
+            .with_logger(silent.clone())
+            .track(create_test_event(250));
+        let config = SlowQueryConfig {
+            emit_log: false,
+            ..SlowQueryConfig::enabled()
+        };
+        SlowQueryTracker::new(config)
+            .with_logger(silent.clone())
+            .track(create_test_event(250));
+        assert!(silent.records().is_empty());
+    }
+
+    #[test]
+    fn test_slow_query_logged_with_request_id() {
+        let logger = Arc::new(VecLogger::new());
+        let tracker =
+            SlowQueryTracker::new(SlowQueryConfig::enabled()).with_logger(logger.clone());
+        let request_id = Uuid::new_v4();
+        crate::observability::in_request_sync(request_id, || {
+            tracker.track(create_test_event(250));
+        });
+
+        let records = logger.records();
+        assert_eq!(
+            records[0].field("request_id"),
+            Some(request_id.to_string().as_str())
+        );
     }
 }
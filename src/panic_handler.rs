@@ -5,11 +5,80 @@
 //! - Captures panic info
 //! - Writes to crash log before terminating
 //! - Never silently swallows panics
+//!
+//! # Crash reports
+//!
+//! A server installs a `CrashReporter` once it has booted. On panic the
+//! reporter writes `<data_dir>/crash_reports/<timestamp>.json` holding the
+//! panic message and location, the backtrace, the binary version, the most
+//! recent operation log entries (when the log is enabled), the current
+//! `ResourceStatus` and the replication role. The report is fsynced, one
+//! structured line naming it goes to stderr, and the process aborts.
+//!
+//! Writing the report is best-effort: a failure is reported on stderr and
+//! never prevents the abort. A report is at most `MAX_CRASH_REPORT_BYTES`;
+//! operation log entries, then the backtrace, are trimmed to fit. Only the
+//! newest `max_reports` reports are kept.
+//!
+//! # Crash marker
+//!
+//! Before the report, the hook writes `<data_dir>/crash_marker` with the
+//! panic message, location, backtrace and timestamp, then fsyncs the WAL
+//! files so records already written reach disk. The next boot's recovery
+//! finds the marker, logs that it follows an unclean shutdown, and removes
+//! the marker once recovery completes. Both steps are best-effort too.
+
+use std::cell::Cell;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::observability::OperationLog;
+use crate::resource_limits::{HealthStatus, ResourceManager};
+use crate::version::BINARY_VERSION;
+
+/// Directory under the data directory that holds crash reports
+pub const CRASH_REPORTS_DIR: &str = "crash_reports";
+
+/// Crash reports kept by default
+pub const DEFAULT_MAX_CRASH_REPORTS: usize = 10;
+
+/// Largest crash report written, in bytes
+pub const MAX_CRASH_REPORT_BYTES: usize = 256 * 1024;
+
+/// Operation log entries included in a crash report
+pub const CRASH_REPORT_OPERATIONS: usize = 100;
+
+/// File under the data directory recording the panic that stopped the
+/// server, until the next recovery completes
+pub const CRASH_MARKER: &str = "crash_marker";
+
+thread_local! {
+    /// Set while the thread runs code under `catch_contained`
+    static CONTAINED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f`, returning its panic as an error instead of crashing
+///
+/// The installed hooks ignore a panic inside `f`: nothing is logged or
+/// reported and the process keeps running. For code the server runs but
+/// does not own, such as edge functions.
+pub fn catch_contained<R>(f: impl FnOnce() -> R) -> std::thread::Result<R> {
+    let outer = CONTAINED.with(|contained| contained.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CONTAINED.with(|contained| contained.set(outer));
+    result
+}
 
-use std::fs::{self, OpenOptions};
-use std::io::Write;
-use std::panic::{self, PanicInfo};
-use std::path::PathBuf;
+/// Whether the panicking thread is inside `catch_contained`
+fn panic_is_contained() -> bool {
+    CONTAINED.with(Cell::get)
+}
 
 /// Initialize the production panic handler
 ///
@@ -19,11 +88,14 @@ pub fn init_panic_handler(data_dir: Option<PathBuf>) {
     let crash_log_path = data_dir.map(|d| d.join("crash.log"));
     
     panic::set_hook(Box::new(move |info| {
+        if panic_is_contained() {
+            return;
+        }
         handle_panic(info, crash_log_path.as_ref());
     }));
 }
 
-fn handle_panic(info: &PanicInfo<'_>, crash_log_path: Option<&PathBuf>) {
+fn handle_panic(info: &PanicHookInfo<'_>, crash_log_path: Option<&PathBuf>) {
     // Build panic message
     let message = format_panic_info(info);
     
@@ -46,7 +118,7 @@ fn handle_panic(info: &PanicInfo<'_>, crash_log_path: Option<&PathBuf>) {
     eprintln!("\nThis is a bug. Please report it with the above information.");
 }
 
-fn format_panic_info(info: &PanicInfo<'_>) -> String {
+fn format_panic_info(info: &PanicHookInfo<'_>) -> String {
     let mut msg = String::new();
     
     // Timestamp
@@ -105,6 +177,374 @@ fn write_crash_log(path: &PathBuf, message: &str) -> std::io::Result<()> {
     Ok(())
 }
 
+/// The panic that stopped the server, as recorded in the crash marker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashMarker {
+    /// When the panic happened (RFC 3339)
+    pub timestamp: String,
+
+    /// Panic message
+    pub message: String,
+
+    /// Source location of the panic, if known
+    pub location: Option<String>,
+
+    /// Backtrace of the panicking thread
+    pub backtrace: String,
+}
+
+impl CrashMarker {
+    /// Path of the marker in `data_dir`
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(CRASH_MARKER)
+    }
+
+    /// Read the marker left in `data_dir`, if any
+    ///
+    /// A marker that exists but cannot be parsed is an error: the server
+    /// still stopped uncleanly.
+    pub fn load(data_dir: &Path) -> io::Result<Option<Self>> {
+        let bytes = match fs::read(Self::path(data_dir)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the marker into `data_dir` and fsync it
+    pub fn store(&self, data_dir: &Path) -> io::Result<()> {
+        let path = Self::path(data_dir);
+        let tmp = data_dir.join(format!("{}.tmp", CRASH_MARKER));
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(&serde_json::to_vec_pretty(self)?)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, &path)?;
+        fsync_dir(data_dir)
+    }
+
+    /// Remove the marker from `data_dir`; a missing marker is fine
+    pub fn remove(data_dir: &Path) -> io::Result<()> {
+        match fs::remove_file(Self::path(data_dir)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Writes a crash report when the process panics, then aborts
+pub struct CrashReporter {
+    data_dir: PathBuf,
+    reports_dir: PathBuf,
+    max_reports: usize,
+    operation_log: Option<Arc<OperationLog>>,
+    resource_manager: Option<Arc<ResourceManager>>,
+    replication_role: Option<String>,
+}
+
+impl CrashReporter {
+    /// Report into `<data_dir>/crash_reports`
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            reports_dir: data_dir.join(CRASH_REPORTS_DIR),
+            max_reports: DEFAULT_MAX_CRASH_REPORTS,
+            operation_log: None,
+            resource_manager: None,
+            replication_role: None,
+        }
+    }
+
+    /// Keep only the newest `max_reports` reports (at least one)
+    pub fn with_max_reports(mut self, max_reports: usize) -> Self {
+        self.max_reports = max_reports.max(1);
+        self
+    }
+
+    /// Include the most recent entries of `operation_log`
+    pub fn with_operation_log(mut self, operation_log: Arc<OperationLog>) -> Self {
+        self.operation_log = Some(operation_log);
+        self
+    }
+
+    /// Include the resource status of `resource_manager`
+    pub fn with_resource_manager(mut self, resource_manager: Arc<ResourceManager>) -> Self {
+        self.resource_manager = Some(resource_manager);
+        self
+    }
+
+    /// Include the replication role the process runs as
+    pub fn with_replication_role(mut self, role: impl Into<String>) -> Self {
+        self.replication_role = Some(role.into());
+        self
+    }
+
+    /// Replace the panic hook; every later panic writes the crash marker,
+    /// fsyncs the WAL, writes a report and aborts
+    pub fn install(self) {
+        panic::set_hook(Box::new(move |info| {
+            if panic_is_contained() {
+                return;
+            }
+            let marker = CrashMarker {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                message: panic_message(info),
+                location: info
+                    .location()
+                    .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+                backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            };
+
+            let written = panic::catch_unwind(AssertUnwindSafe(|| self.record_panic(&marker)));
+            let (report, error) = match written {
+                Ok(Ok(path)) => (Some(path.display().to_string()), None),
+                Ok(Err(e)) => (None, Some(e.to_string())),
+                Err(_) => (None, Some("panicked while writing report".to_string())),
+            };
+
+            eprintln!(
+                "{}",
+                json!({
+                    "level": "FATAL",
+                    "event": "PANIC",
+                    "message": marker.message,
+                    "location": marker.location,
+                    "crash_marker": CrashMarker::path(&self.data_dir).display().to_string(),
+                    "crash_report": report,
+                    "crash_report_error": error,
+                })
+            );
+            std::process::abort();
+        }));
+    }
+
+    /// Write the crash marker, fsync the WAL, then write the report
+    ///
+    /// A failed marker or fsync does not stop the report; the first error
+    /// is returned.
+    fn record_panic(&self, marker: &CrashMarker) -> io::Result<PathBuf> {
+        let marked = marker.store(&self.data_dir);
+        let synced = sync_wal(&self.data_dir);
+        let report = self.write_report(marker)?;
+        marked.and(synced).map(|()| report)
+    }
+
+    /// Write the report for a panic, then prune old reports
+    fn write_report(&self, marker: &CrashMarker) -> io::Result<PathBuf> {
+        let now = chrono::Utc::now();
+        let mut report = json!({
+            "timestamp": now.to_rfc3339(),
+            "message": marker.message,
+            "location": marker.location,
+            "backtrace": marker.backtrace,
+            "version": BINARY_VERSION,
+            "replication_role": self.replication_role,
+            "resource_status": self.resource_status(),
+            "recent_operations": self.recent_operations(),
+        });
+        let bytes = encode_bounded(&mut report, MAX_CRASH_REPORT_BYTES);
+
+        fs::create_dir_all(&self.reports_dir)?;
+        let id = now.format("%Y%m%dT%H%M%S%.6fZ").to_string();
+        let path = self.reports_dir.join(format!("{}.json", id));
+        let tmp = self.reports_dir.join(format!("{}.json.tmp", id));
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(&bytes)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, &path)?;
+        fsync_dir(&self.reports_dir)?;
+
+        prune_crash_reports(&self.reports_dir, self.max_reports)?;
+        Ok(path)
+    }
+
+    fn resource_status(&self) -> Value {
+        let Some(status) = self
+            .resource_manager
+            .as_ref()
+            .and_then(|rm| rm.get_status().ok())
+        else {
+            return Value::Null;
+        };
+        let health = match status.health_status {
+            HealthStatus::Normal => "normal",
+            HealthStatus::Warning => "warning",
+            HealthStatus::Critical => "critical",
+            HealthStatus::ReadOnly => "read_only",
+        };
+        json!({
+            "disk_usage_bytes": status.disk_usage_bytes,
+            "disk_total_bytes": status.disk_total_bytes,
+            "disk_free_bytes": status.disk_free_bytes,
+            "memory_usage_bytes": status.memory_usage_bytes,
+            "memory_limit_bytes": status.memory_limit_bytes,
+            "open_file_descriptors": status.open_file_descriptors,
+            "fd_limit": status.fd_limit,
+            "health_status": health,
+            "read_only_mode": status.read_only_mode,
+        })
+    }
+
+    fn recent_operations(&self) -> Value {
+        match &self.operation_log {
+            Some(log) if log.is_enabled() => {
+                serde_json::to_value(log.recent(CRASH_REPORT_OPERATIONS)).unwrap_or(Value::Null)
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<unknown panic payload>".to_string()
+    }
+}
+
+/// Serialize `report` in at most `max_bytes`
+///
+/// The oldest operation log entries are dropped first, then the backtrace
+/// and message are cut short. `truncated` is set when anything was removed.
+fn encode_bounded(report: &mut Value, max_bytes: usize) -> Vec<u8> {
+    loop {
+        let bytes = serde_json::to_vec_pretty(report).unwrap_or_default();
+        if bytes.len() <= max_bytes {
+            return bytes;
+        }
+        let excess = bytes.len() - max_bytes;
+        report["truncated"] = Value::Bool(true);
+
+        if let Some(operations) = report["recent_operations"]
+            .as_array_mut()
+            .filter(|ops| !ops.is_empty())
+        {
+            let dropped = operations.len().div_ceil(2);
+            operations.drain(..dropped);
+            continue;
+        }
+        let shortened = ["backtrace", "message"]
+            .iter()
+            .any(|field| truncate_string(&mut report[*field], excess));
+        if !shortened {
+            return bytes;
+        }
+    }
+}
+
+/// Drop at least `excess` bytes from the end of a string value
+fn truncate_string(value: &mut Value, excess: usize) -> bool {
+    const MARKER: &str = "...";
+    let Value::String(s) = value else {
+        return false;
+    };
+    if s.len() <= MARKER.len() {
+        return false;
+    }
+    let mut end = s.len().saturating_sub(excess + MARKER.len());
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s.truncate(end);
+    s.push_str(MARKER);
+    true
+}
+
+/// A crash report on disk
+#[derive(Debug, Clone)]
+pub struct StoredCrashReport {
+    /// File stem: the UTC time the report was written
+    pub id: String,
+
+    /// Size of the file in bytes
+    pub size_bytes: u64,
+
+    /// Parsed report, or why it could not be read
+    pub report: Result<Value, String>,
+}
+
+/// Crash reports in `<data_dir>/crash_reports`, oldest first
+pub fn list_crash_reports(data_dir: &Path) -> io::Result<Vec<StoredCrashReport>> {
+    let reports_dir = data_dir.join(CRASH_REPORTS_DIR);
+    if !reports_dir.exists() {
+        return Ok(Vec::new());
+    }
+    report_paths(&reports_dir)?
+        .into_iter()
+        .map(|path| {
+            let id = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let size_bytes = fs::metadata(&path)?.len();
+            let report = fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()));
+            Ok(StoredCrashReport {
+                id,
+                size_bytes,
+                report,
+            })
+        })
+        .collect()
+}
+
+/// Paths of the reports in `reports_dir`, oldest first
+fn report_paths(reports_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(reports_dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    // Ids are fixed-width UTC times, so name order is age order
+    paths.sort();
+    Ok(paths)
+}
+
+/// Remove all but the newest `max_reports` reports
+fn prune_crash_reports(reports_dir: &Path, max_reports: usize) -> io::Result<()> {
+    let paths = report_paths(reports_dir)?;
+    let excess = paths.len().saturating_sub(max_reports);
+    for path in &paths[..excess] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn fsync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+/// Best-effort fsync of every WAL file in `<data_dir>/wal`
+///
+/// The writer may be mid-append on another thread, so the files are
+/// reopened rather than borrowed: fsync flushes a file's dirty pages
+/// whichever descriptor asks.
+fn sync_wal(data_dir: &Path) -> io::Result<()> {
+    let wal_dir = data_dir.join("wal");
+    if !wal_dir.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(&wal_dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            File::open(&path)?.sync_all()?;
+        }
+    }
+    fsync_dir(&wal_dir)
+}
+
 /// Wrapper for unwrap that provides better panic messages
 ///
 /// HARDENING: Use this instead of bare unwrap() for better debugging.
@@ -145,10 +585,19 @@ macro_rules! safe_unwrap_result {
 mod tests {
     use super::*;
     use tempfile::TempDir;
+
+    fn marker(message: &str) -> CrashMarker {
+        CrashMarker {
+            timestamp: "2026-01-01T00:00:00+00:00".to_string(),
+            message: message.to_string(),
+            location: Some("src/lib.rs:1:1".to_string()),
+            backtrace: "frame".to_string(),
+        }
+    }
     
     #[test]
     fn test_format_panic_info_captures_location() {
-        // We can't easily test PanicInfo directly, but we can test the format function
+        // We can't easily test PanicHookInfo directly, but we can test the format function
         // by checking that the formatted string contains expected elements
     }
     
@@ -176,6 +625,75 @@ mod tests {
         assert!(content.contains("Second crash"));
     }
     
+    #[test]
+    fn test_write_report_is_readable_and_pruned() {
+        let temp = TempDir::new().unwrap();
+        let reporter = CrashReporter::new(temp.path())
+            .with_max_reports(2)
+            .with_replication_role("primary");
+
+        for i in 0..3 {
+            reporter.write_report(&marker(&format!("crash {}", i))).unwrap();
+        }
+
+        let reports = list_crash_reports(temp.path()).unwrap();
+        assert_eq!(reports.len(), 2);
+        let newest = reports[1].report.as_ref().unwrap();
+        assert_eq!(newest["message"], "crash 2");
+        assert_eq!(newest["version"], BINARY_VERSION);
+        assert_eq!(newest["replication_role"], "primary");
+        assert!(newest["backtrace"].is_string());
+    }
+
+    #[test]
+    fn test_record_panic_writes_marker_and_syncs_wal() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("wal")).unwrap();
+        fs::write(temp.path().join("wal").join("wal.log"), b"records").unwrap();
+        let reporter = CrashReporter::new(temp.path());
+
+        let report = reporter.record_panic(&marker("boom")).unwrap();
+        assert!(report.exists());
+
+        let stored = CrashMarker::load(temp.path()).unwrap().unwrap();
+        assert_eq!(stored, marker("boom"));
+        assert!(!temp.path().join("crash_marker.tmp").exists());
+
+        CrashMarker::remove(temp.path()).unwrap();
+        assert!(CrashMarker::load(temp.path()).unwrap().is_none());
+        CrashMarker::remove(temp.path()).unwrap();
+    }
+
+    #[test]
+    fn test_encode_bounded_trims_operations_then_backtrace() {
+        let mut report = json!({
+            "message": "boom",
+            "backtrace": "frame\n".repeat(1000),
+            "recent_operations": vec!["op"; 100],
+        });
+        let bytes = encode_bounded(&mut report, 1024);
+
+        assert!(bytes.len() <= 1024);
+        let parsed: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(parsed["truncated"], true);
+        assert_eq!(parsed["message"], "boom");
+        assert!(parsed["recent_operations"].as_array().unwrap().is_empty());
+        assert!(parsed["backtrace"].as_str().unwrap().ends_with("..."));
+    }
+
+    #[test]
+    fn test_catch_contained() {
+        assert_eq!(catch_contained(|| 7).unwrap(), 7);
+
+        let outer = catch_contained(|| {
+            assert!(catch_contained(|| panic!("inner")).is_err());
+            // Still contained after the inner scope ends
+            assert!(panic_is_contained());
+        });
+        assert!(outer.is_ok());
+        assert!(!panic_is_contained());
+    }
+
     #[test]
     fn test_safe_unwrap_some() {
         let val: Option<i32> = Some(42);
@@ -4,6 +4,8 @@
 
 use std::collections::HashMap;
 
+use crate::index::Collation;
+
 /// Filter operation types
 #[derive(Debug, Clone, PartialEq)]
 pub enum FilterOp {
@@ -111,6 +113,87 @@ impl Predicate {
     }
 }
 
+/// Boolean combination of predicates (`$and`, `$or`, `$not`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    /// A single predicate
+    Predicate(Predicate),
+    /// Every child matches
+    And(Vec<FilterExpr>),
+    /// At least one child matches
+    Or(Vec<FilterExpr>),
+    /// The child does not match
+    Not(Box<FilterExpr>),
+}
+
+impl FilterExpr {
+    /// Create a conjunction
+    pub fn and(children: impl IntoIterator<Item = FilterExpr>) -> Self {
+        FilterExpr::And(children.into_iter().collect())
+    }
+
+    /// Create a disjunction
+    pub fn or(children: impl IntoIterator<Item = FilterExpr>) -> Self {
+        FilterExpr::Or(children.into_iter().collect())
+    }
+
+    /// Create a negation
+    pub fn negate(child: FilterExpr) -> Self {
+        FilterExpr::Not(Box::new(child))
+    }
+
+    /// Fields referenced anywhere in the expression
+    pub fn fields(&self) -> Vec<&str> {
+        let mut fields = Vec::new();
+        self.collect_fields(&mut fields);
+        fields
+    }
+
+    fn collect_fields<'a>(&'a self, fields: &mut Vec<&'a str>) {
+        match self {
+            FilterExpr::Predicate(p) => fields.push(&p.field),
+            FilterExpr::And(children) | FilterExpr::Or(children) => {
+                children.iter().for_each(|c| c.collect_fields(fields))
+            }
+            FilterExpr::Not(child) => child.collect_fields(fields),
+        }
+    }
+
+    /// The predicates every match satisfies: the expression itself if it
+    /// is a predicate, or those joined by AND (nested ANDs included). ORs
+    /// and NOTs contribute none.
+    pub fn conjuncts(&self) -> Vec<Predicate> {
+        match self {
+            FilterExpr::Predicate(p) => vec![p.clone()],
+            FilterExpr::And(children) => children.iter().flat_map(Self::conjuncts).collect(),
+            FilterExpr::Or(_) | FilterExpr::Not(_) => Vec::new(),
+        }
+    }
+
+    /// Short description for explain output, e.g. `or(a eq, not(b lt))`
+    pub fn describe(&self) -> String {
+        let list = |children: &[FilterExpr]| {
+            children
+                .iter()
+                .map(FilterExpr::describe)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            FilterExpr::Predicate(p) => format!("{} {}", p.field, p.op.op_name()),
+            FilterExpr::And(children) => format!("and({})", list(children)),
+            FilterExpr::Or(children) => format!("or({})", list(children)),
+            FilterExpr::Not(child) => format!("not({})", child.describe()),
+        }
+    }
+}
+
+impl From<Predicate> for FilterExpr {
+    fn from(predicate: Predicate) -> Self {
+        FilterExpr::Predicate(predicate)
+    }
+}
+
 /// Sort direction
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SortDirection {
@@ -134,6 +217,9 @@ pub struct SortSpec {
     pub field: String,
     /// Sort direction
     pub direction: SortDirection,
+    /// How values are ordered; None uses the collation of the field's
+    /// index, which the planner fills in
+    pub collation: Option<Collation>,
 }
 
 impl SortSpec {
@@ -141,6 +227,7 @@ impl SortSpec {
         Self {
             field: field.into(),
             direction: SortDirection::Asc,
+            collation: None,
         }
     }
 
@@ -148,8 +235,52 @@ impl SortSpec {
         Self {
             field: field.into(),
             direction: SortDirection::Desc,
+            collation: None,
         }
     }
+
+    /// Order values by `collation` regardless of the field's index
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = Some(collation);
+        self
+    }
+}
+
+/// Fields a query returns, from a `select` list
+///
+/// Fields are dotted paths into nested objects (`address.city`). The
+/// primary key `_id` is returned unless the list excludes it as `-_id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Projection {
+    /// Paths of the returned fields
+    pub fields: Vec<String>,
+    /// Whether `_id` is returned
+    pub include_id: bool,
+}
+
+impl Projection {
+    /// Parse a `select` list. `*` selects the whole document, as does a
+    /// list that only excludes `_id` apart from that field.
+    ///
+    /// Returns None if every field is selected.
+    pub fn parse<S: AsRef<str>>(select: &[S]) -> Option<Self> {
+        let mut projection = Self {
+            fields: Vec::new(),
+            include_id: true,
+        };
+        for field in select.iter().map(|f| f.as_ref().trim()) {
+            match field {
+                "*" => return None,
+                "-_id" => projection.include_id = false,
+                "" => {}
+                field => projection.fields.push(field.to_string()),
+            }
+        }
+        if projection.fields.is_empty() && projection.include_id {
+            return None;
+        }
+        Some(projection)
+    }
 }
 
 /// Parsed query AST per QUERY.md §53-80
@@ -163,10 +294,17 @@ pub struct Query {
     pub schema_version: Option<String>,
     /// Filter predicates (all combined with AND)
     pub predicates: Vec<Predicate>,
+    /// Boolean expressions (ORs and NOTs), combined with the predicates
+    /// and each other by AND
+    pub expressions: Vec<FilterExpr>,
     /// Sort specification (optional, single field only in Phase 0)
     pub sort: Option<SortSpec>,
     /// Limit (mandatory)
     pub limit: Option<u64>,
+    /// Documents to skip before the limit applies
+    pub offset: Option<u64>,
+    /// Fields to return (None returns whole documents)
+    pub projection: Option<Projection>,
 }
 
 impl Query {
@@ -177,8 +315,11 @@ impl Query {
             schema_id: schema_id.into(),
             schema_version: None,
             predicates: Vec::new(),
+            expressions: Vec::new(),
             sort: None,
             limit: None,
+            offset: None,
+            projection: None,
         }
     }
 
@@ -194,6 +335,19 @@ impl Query {
         self
     }
 
+    /// Adds a filter expression. Predicates and ANDs of them join the flat
+    /// predicates; ORs and NOTs are kept as expressions.
+    pub fn with_filter(mut self, filter: FilterExpr) -> Self {
+        match filter {
+            FilterExpr::Predicate(predicate) => self.with_predicate(predicate),
+            FilterExpr::And(children) => children.into_iter().fold(self, Self::with_filter),
+            other => {
+                self.expressions.push(other);
+                self
+            }
+        }
+    }
+
     /// Adds an equality filter
     pub fn filter_eq(self, field: impl Into<String>, value: serde_json::Value) -> Self {
         self.with_predicate(Predicate::eq(field, value))
@@ -211,11 +365,31 @@ impl Query {
         self
     }
 
+    /// Sets the offset
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sets the fields to return
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = Some(projection);
+        self
+    }
+
     /// Returns true if query has a primary key equality filter
     pub fn has_pk_filter(&self) -> bool {
         self.predicates.iter().any(|p| p.is_primary_key())
     }
 
+    /// Every field the query filters on, in predicates and expressions
+    pub fn filter_fields(&self) -> impl Iterator<Item = &str> {
+        self.predicates
+            .iter()
+            .map(|p| p.field.as_str())
+            .chain(self.expressions.iter().flat_map(FilterExpr::fields))
+    }
+
     /// Returns predicates grouped by field
     pub fn predicates_by_field(&self) -> HashMap<&str, Vec<&Predicate>> {
         let mut map: HashMap<&str, Vec<&Predicate>> = HashMap::new();
@@ -267,6 +441,26 @@ mod tests {
         assert!(!range_id.is_primary_key());
     }
 
+    #[test]
+    fn test_with_filter_flattens_conjunctions() {
+        let query = Query::new("users", "users").with_filter(FilterExpr::and([
+            Predicate::eq("a", json!(1)).into(),
+            FilterExpr::and([Predicate::gt("b", json!(2)).into()]),
+            FilterExpr::or([
+                Predicate::eq("c", json!(3)).into(),
+                FilterExpr::negate(Predicate::eq("d", json!(4)).into()),
+            ]),
+        ]));
+
+        assert_eq!(query.predicates.len(), 2);
+        assert_eq!(query.expressions.len(), 1);
+        assert_eq!(query.expressions[0].describe(), "or(c eq, not(d eq))");
+        assert_eq!(
+            query.filter_fields().collect::<Vec<_>>(),
+            vec!["a", "b", "c", "d"]
+        );
+    }
+
     #[test]
     fn test_sort_spec() {
         let asc = SortSpec::asc("created_at");
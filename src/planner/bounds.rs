@@ -1,11 +1,11 @@
 //! Boundedness analysis for queries per QUERY.md §205-218
 //!
 //! A query is bounded if:
-//! - Every filter predicate references indexed fields ONLY
+//! - Every filter predicate references indexed fields ONLY, including
+//!   predicates inside `$or` and `$not`
 //! - Range predicates have explicit limit
 //! - Limit is mandatory and > 0
 //! - Sort field is indexed
-//! - No OR conditions
 //! - No functions or expressions
 
 use std::collections::HashSet;
@@ -66,9 +66,9 @@ impl<'a> BoundednessAnalyzer<'a> {
         }
 
         // 2. Check all filter predicates use indexed fields
-        for pred in &query.predicates {
-            if !self.is_indexed(&pred.field) {
-                return Err(PlannerError::unindexed_field(&pred.field));
+        for field in query.filter_fields() {
+            if !self.is_indexed(field) {
+                return Err(PlannerError::unindexed_field(field));
             }
         }
 
@@ -91,10 +91,11 @@ impl<'a> BoundednessAnalyzer<'a> {
 
         // 5. For range queries, limit is already checked above
         // Collect indexed fields used in predicates
-        let indexed_fields: Vec<String> =
-            query.predicates.iter().map(|p| p.field.clone()).collect();
+        let indexed_fields: Vec<String> = query.filter_fields().map(String::from).collect();
 
-        Ok(BoundednessProof::indexed_scan(limit, indexed_fields))
+        // Skipped documents are read too
+        let window = limit.saturating_add(query.offset.unwrap_or(0));
+        Ok(BoundednessProof::indexed_scan(window, indexed_fields))
     }
 
     /// Checks if a field is indexed (_id is always indexed)
@@ -106,7 +107,7 @@ impl<'a> BoundednessAnalyzer<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::planner::ast::Predicate;
+    use crate::planner::ast::{FilterExpr, Predicate};
     use serde_json::json;
 
     fn make_indexes(fields: &[&str]) -> HashSet<String> {
@@ -183,6 +184,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unindexed_field_inside_or_rejected() {
+        let indexes = make_indexes(&["email"]);
+        let analyzer = BoundednessAnalyzer::new(&indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_filter(FilterExpr::or([
+                Predicate::eq("email", json!("a@example.com")).into(),
+                FilterExpr::negate(Predicate::eq("name", json!("Alice")).into()),
+            ]))
+            .with_limit(10);
+
+        let result = analyzer.analyze(&query);
+        assert_eq!(
+            result.unwrap_err().code().code(),
+            "AERO_QUERY_UNINDEXED_FIELD"
+        );
+    }
+
     #[test]
     fn test_indexed_range_bounded() {
         let indexes = make_indexes(&["age"]);
@@ -0,0 +1,265 @@
+//! Cost estimates for explain output
+//!
+//! Index selection follows the strict priority order in `planner.rs`.
+//! Estimates only order single-field indexes at the same priority, so the
+//! same query, index set and statistics always produce the same plan (T1).
+//!
+//! # Model
+//!
+//! - `estimated_rows`: documents the access path yields, before residual
+//!   predicates and the limit are applied
+//! - `estimated_cost`: documents read to answer the query. Index paths stop
+//!   at the limit unless the plan sorts in memory, which reads every
+//!   candidate; a collection scan reads every document.
+//!
+//! Selectivity of a predicate on an indexed field:
+//! - Equality: 1 / distinct keys, or 1/10 without statistics
+//! - Range: 1/3
+//!
+//! A compound index multiplies the selectivities of its matched fields,
+//! assuming they are independent.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::planner::CompoundMatch;
+
+/// Scan type code reported for a collection scan, which is never chosen
+pub const COLLECTION_SCAN: &str = "COLLECTION_SCAN";
+
+/// Equality selectivity divisor for a field without statistics (1/10)
+const DEFAULT_EQ_DIVISOR: u64 = 10;
+
+/// Range selectivity divisor (1/3)
+const RANGE_DIVISOR: u64 = 3;
+
+/// Statistics for one single-field index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldStatistics {
+    /// Indexed entries (documents with an indexable value)
+    pub entries: u64,
+    /// Distinct keys
+    pub distinct_keys: u64,
+}
+
+/// Collection and index statistics used for estimates
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexStatistics {
+    /// Live documents in the collection
+    pub documents: u64,
+    /// Single-field index statistics (field -> statistics)
+    pub fields: BTreeMap<String, FieldStatistics>,
+    /// Compound index entries (index name -> entries)
+    pub compound_entries: BTreeMap<String, u64>,
+}
+
+impl IndexStatistics {
+    /// Estimated rows for a primary key lookup
+    pub fn pk_rows(&self) -> u64 {
+        self.documents.min(1)
+    }
+
+    /// Estimated rows for an equality or range lookup on one field
+    pub fn field_rows(&self, field: &str, equality: bool) -> u64 {
+        let entries = self
+            .fields
+            .get(field)
+            .map_or(self.documents, |stats| stats.entries);
+        scale(entries, self.divisor(field, equality))
+    }
+
+    /// Estimated rows for an unbounded scan of one field's index
+    pub fn scan_rows(&self, field: &str) -> u64 {
+        if field == "_id" {
+            return self.documents;
+        }
+        self.fields
+            .get(field)
+            .map_or(self.documents, |stats| stats.entries)
+    }
+
+    /// Estimated rows for a compound index lookup
+    pub fn compound_rows(&self, name: &str, matched: &CompoundMatch) -> u64 {
+        let entries = self
+            .compound_entries
+            .get(name)
+            .copied()
+            .unwrap_or(self.documents);
+
+        let fields = matched.matched_fields();
+        let divisor = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let equality = !(matched.ends_in_range && i + 1 == fields.len());
+                self.divisor(field, equality)
+            })
+            .fold(1u64, u64::saturating_mul);
+        scale(entries, divisor)
+    }
+
+    /// Estimated rows left after residual predicates filter `rows`.
+    ///
+    /// Each `(field, equality)` pair applies its selectivity once.
+    pub fn residual_rows(&self, rows: u64, residual: &[(&str, bool)]) -> u64 {
+        let divisor = residual
+            .iter()
+            .map(|(field, equality)| self.divisor(field, *equality))
+            .fold(1u64, u64::saturating_mul);
+        scale(rows, divisor)
+    }
+
+    /// Selectivity of a predicate on `field`, as the divisor `d` in `1/d`
+    fn divisor(&self, field: &str, equality: bool) -> u64 {
+        if !equality {
+            return RANGE_DIVISOR;
+        }
+        match self.fields.get(field) {
+            Some(stats) if stats.distinct_keys > 0 => stats.distinct_keys,
+            _ => DEFAULT_EQ_DIVISOR,
+        }
+    }
+}
+
+/// Apply a selectivity, rounding up so a non-empty index never estimates 0
+fn scale(entries: u64, divisor: u64) -> u64 {
+    entries.div_ceil(divisor)
+}
+
+/// Estimate for one access path considered by the planner
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathEstimate {
+    /// Scan type code (`ScanType::as_str`, or `COLLECTION_SCAN`)
+    pub scan_type: String,
+    /// Index used: field name or compound index name (None for a
+    /// collection scan)
+    pub index: Option<String>,
+    /// Leading compound index fields the query constrains
+    pub matched_prefix: Option<usize>,
+    /// Documents the path yields
+    pub estimated_rows: u64,
+    /// Documents read to answer the query
+    pub estimated_cost: u64,
+    /// Why the path was not chosen (None for the chosen path)
+    pub reason: Option<String>,
+}
+
+impl PathEstimate {
+    /// Estimate for an index path, whose reads stop at the limit
+    pub(crate) fn index_path(
+        scan_type: &str,
+        index: &str,
+        matched_prefix: Option<usize>,
+        rows: u64,
+        limit: Option<u64>,
+    ) -> Self {
+        Self {
+            scan_type: scan_type.to_string(),
+            index: Some(index.to_string()),
+            matched_prefix,
+            estimated_rows: rows,
+            estimated_cost: limit.map_or(rows, |limit| rows.min(limit)),
+            reason: None,
+        }
+    }
+
+    /// Estimate for a collection scan, which reads every document
+    pub(crate) fn collection_scan(stats: &IndexStatistics) -> Self {
+        Self {
+            scan_type: COLLECTION_SCAN.to_string(),
+            index: None,
+            matched_prefix: None,
+            estimated_rows: stats.documents,
+            estimated_cost: stats.documents,
+            reason: None,
+        }
+    }
+
+    /// Short description, e.g. `INDEX_EQ on email`
+    pub fn describe(&self) -> String {
+        match &self.index {
+            Some(index) => format!("{} on {}", self.scan_type, index),
+            None => self.scan_type.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> IndexStatistics {
+        let mut stats = IndexStatistics {
+            documents: 1000,
+            ..Default::default()
+        };
+        stats.fields.insert(
+            "email".into(),
+            FieldStatistics {
+                entries: 1000,
+                distinct_keys: 1000,
+            },
+        );
+        stats.fields.insert(
+            "status".into(),
+            FieldStatistics {
+                entries: 900,
+                distinct_keys: 3,
+            },
+        );
+        stats.compound_entries.insert("status_age".into(), 900);
+        stats
+    }
+
+    #[test]
+    fn test_field_estimates() {
+        let stats = stats();
+        assert_eq!(stats.pk_rows(), 1);
+        assert_eq!(stats.field_rows("email", true), 1);
+        assert_eq!(stats.field_rows("status", true), 300);
+        assert_eq!(stats.field_rows("status", false), 300);
+        // No statistics: fraction of the collection
+        assert_eq!(stats.field_rows("age", true), 100);
+    }
+
+    #[test]
+    fn test_compound_estimate_multiplies_selectivities() {
+        let stats = stats();
+        let matched = CompoundMatch {
+            fields: vec!["status".into(), "age".into()],
+            prefix_len: 2,
+            ends_in_range: true,
+        };
+        // 900 * 1/3 (status eq) * 1/3 (age range)
+        assert_eq!(stats.compound_rows("status_age", &matched), 100);
+    }
+
+    #[test]
+    fn test_cost_is_capped_by_limit_for_index_paths_only() {
+        let stats = stats();
+        let path = PathEstimate::index_path("INDEX_EQ", "status", None, 300, Some(10));
+        assert_eq!((path.estimated_rows, path.estimated_cost), (300, 10));
+
+        let scan = PathEstimate::collection_scan(&stats);
+        assert_eq!((scan.estimated_rows, scan.estimated_cost), (1000, 1000));
+    }
+
+    #[test]
+    fn test_residual_rows_apply_each_selectivity() {
+        let stats = stats();
+        assert_eq!(stats.residual_rows(900, &[]), 900);
+        // 900 * 1/3 (status eq) * 1/3 (age range)
+        assert_eq!(
+            stats.residual_rows(900, &[("status", true), ("age", false)]),
+            100
+        );
+    }
+
+    #[test]
+    fn test_empty_statistics_estimate_zero() {
+        let stats = IndexStatistics::default();
+        assert_eq!(stats.pk_rows(), 0);
+        assert_eq!(stats.field_rows("email", true), 0);
+    }
+}
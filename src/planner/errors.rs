@@ -132,6 +132,20 @@ impl PlannerError {
         }
     }
 
+    /// Create an unindexed field error for a filter on a field whose only
+    /// index is collated: its keys order sorts but cannot answer predicates
+    pub fn collated_field(field: impl Into<String>) -> Self {
+        let f = field.into();
+        Self {
+            code: PlannerErrorCode::AeroQueryUnindexedField,
+            message: format!(
+                "Field '{}' has only a collated index, which cannot serve filters",
+                f
+            ),
+            field: Some(f),
+        }
+    }
+
     /// Create a limit required error
     pub fn limit_required() -> Self {
         Self {
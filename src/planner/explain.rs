@@ -1,11 +1,83 @@
 //! Explain plan output per QUERY.md §292-304
 //!
-//! Produces deterministic, human-readable explain output.
+//! Produces deterministic, human-readable explain output, and a structured
+//! JSON form (`to_json`) whose shape is stable for tools to parse.
 
 use std::fmt;
 
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::ast::Predicate;
+use super::cost::PathEstimate;
 use super::errors::PlannerError;
-use super::planner::QueryPlan;
+use super::planner::{QueryPlan, ScanType, SortStrategy};
+use crate::index::Collation;
+
+/// Which predicates the chosen index serves, as `<field> <op>`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FilterPushdown {
+    /// Predicates that bound the index scan
+    pub index: Vec<String>,
+    /// Predicates applied to each fetched document
+    pub residual: Vec<String>,
+}
+
+impl FilterPushdown {
+    /// Splits a plan's predicates between its index scan and the residual
+    /// filter.
+    ///
+    /// An index union lists the predicates its branch scans serve as
+    /// `or(..)`; the `$or` is residual too unless those are all it holds.
+    /// Other expressions are always residual.
+    pub fn from_plan(plan: &QueryPlan) -> Self {
+        let mut pushdown = Self::default();
+        for p in &plan.predicates {
+            let desc = format!("{} {}", p.field, p.op.op_name());
+            if Self::is_pushed(plan, p) {
+                pushdown.index.push(desc);
+            } else {
+                pushdown.residual.push(desc);
+            }
+        }
+        for (i, expr) in plan.expressions.iter().enumerate() {
+            match plan.union.as_ref().filter(|u| u.expression == i) {
+                Some(union) => {
+                    let served: Vec<String> = union
+                        .branches
+                        .iter()
+                        .flat_map(|b| b.predicates.iter().filter(|p| b.serves(p)))
+                        .map(|p| format!("{} {}", p.field, p.op.op_name()))
+                        .collect();
+                    if served.len() < expr.fields().len() {
+                        pushdown.residual.push(expr.describe());
+                    }
+                    pushdown.index.push(format!("or({})", served.join(", ")));
+                }
+                None => pushdown.residual.push(expr.describe()),
+            }
+        }
+        pushdown
+    }
+
+    /// Whether the chosen index serves predicate `p`
+    pub(crate) fn is_pushed(plan: &QueryPlan, p: &Predicate) -> bool {
+        match plan.scan_type {
+            ScanType::PrimaryKey => p.is_primary_key(),
+            ScanType::IndexedEquality => p.field == plan.chosen_index && p.is_equality(),
+            ScanType::IndexedRange => p.field == plan.chosen_index && p.is_range(),
+            ScanType::CompoundPrefix => plan.compound.as_ref().is_some_and(|c| {
+                let eq_len = c.equality_len();
+                match c.matched_fields().iter().position(|f| *f == p.field) {
+                    Some(i) if i < eq_len => p.is_equality(),
+                    Some(_) => p.is_range(),
+                    None => false,
+                }
+            }),
+            ScanType::IndexUnion => false,
+        }
+    }
+}
 
 /// Explain plan output
 #[derive(Debug, Clone)]
@@ -16,24 +88,45 @@ pub struct ExplainPlan {
     pub selected_index: Option<String>,
     /// Scan type description
     pub scan_type: Option<String>,
+    /// Fields of the selected compound index, in key order
+    pub index_fields: Vec<String>,
+    /// Number of leading compound index fields the query constrains
+    pub matched_prefix: Option<usize>,
     /// List of predicates
     pub predicates: Vec<String>,
     /// Sort description
     pub sort: Option<String>,
+    /// How the sort order is produced: `INDEX`, `TOP_N` or `FULL`
+    pub sort_strategy: Option<String>,
+    /// Documents kept by a top-N sort (limit + offset)
+    pub sort_window: Option<u64>,
     /// Limit
     pub limit: Option<u64>,
+    /// Offset
+    pub offset: Option<u64>,
     /// Proven bounds
     pub max_scan: Option<u64>,
     /// Rejection reason (if rejected)
     pub rejection_reason: Option<String>,
     /// Rejection error code (if rejected)
     pub rejection_code: Option<String>,
+    /// Estimate for the chosen access path (set by `QueryPlanner::explain`)
+    pub estimate: Option<PathEstimate>,
+    /// Access paths not chosen, in selection order, each with its reason
+    pub alternatives: Vec<PathEstimate>,
+    /// Documents the chosen path reads (set by `QueryPlanner::explain`)
+    pub estimated_rows_scanned: Option<u64>,
+    /// Documents returned after residual predicates, offset and limit (set
+    /// by `QueryPlanner::explain`)
+    pub estimated_rows_returned: Option<u64>,
+    /// Predicates served by the index versus applied after fetch
+    pub filter_pushdown: Option<FilterPushdown>,
 }
 
 impl ExplainPlan {
     /// Creates an explain plan from a successful query plan
     pub fn from_plan(plan: &QueryPlan) -> Self {
-        let predicates: Vec<String> = plan
+        let mut predicates: Vec<String> = plan
             .predicates
             .iter()
             .map(|p| {
@@ -51,22 +144,42 @@ impl ExplainPlan {
                 )
             })
             .collect();
+        predicates.extend(plan.expressions.iter().map(|e| e.describe()));
 
-        let sort = plan
-            .sort
-            .as_ref()
-            .map(|s| format!("{} {}", s.field, s.direction.as_str()));
+        let sort = plan.sort.as_ref().map(|s| match s.collation {
+            Some(collation) if collation != Collation::Binary => {
+                format!("{} {} ({})", s.field, s.direction.as_str(), collation)
+            }
+            _ => format!("{} {}", s.field, s.direction.as_str()),
+        });
 
         Self {
             accepted: true,
             selected_index: Some(plan.chosen_index.clone()),
             scan_type: Some(plan.scan_type.as_str().to_string()),
+            index_fields: plan
+                .compound
+                .as_ref()
+                .map(|c| c.fields.clone())
+                .unwrap_or_default(),
+            matched_prefix: plan.compound.as_ref().map(|c| c.prefix_len),
             predicates,
             sort,
+            sort_strategy: plan.sort_strategy.map(|s| s.as_str().to_string()),
+            sort_window: match plan.sort_strategy {
+                Some(SortStrategy::TopN(window)) => Some(window),
+                _ => None,
+            },
             limit: Some(plan.limit),
+            offset: Some(plan.offset),
             max_scan: Some(plan.bounds_proof.max_scan),
             rejection_reason: None,
             rejection_code: None,
+            estimate: None,
+            alternatives: Vec::new(),
+            estimated_rows_scanned: None,
+            estimated_rows_returned: None,
+            filter_pushdown: Some(FilterPushdown::from_plan(plan)),
         }
     }
 
@@ -76,14 +189,51 @@ impl ExplainPlan {
             accepted: false,
             selected_index: None,
             scan_type: None,
+            index_fields: Vec::new(),
+            matched_prefix: None,
             predicates: Vec::new(),
             sort: None,
+            sort_strategy: None,
+            sort_window: None,
             limit: None,
+            offset: None,
             max_scan: None,
             rejection_reason: Some(err.message().to_string()),
             rejection_code: Some(err.code().code().to_string()),
+            estimate: None,
+            alternatives: Vec::new(),
+            estimated_rows_scanned: None,
+            estimated_rows_returned: None,
+            filter_pushdown: None,
         }
     }
+
+    /// Structured explain output, documented in CORE_QUERY.md.
+    ///
+    /// Every key is always present; absent values are `null`.
+    pub fn to_json(&self) -> Value {
+        let rejection = match (&self.rejection_code, &self.rejection_reason) {
+            (Some(code), Some(reason)) => json!({"code": code, "reason": reason}),
+            _ => Value::Null,
+        };
+
+        json!({
+            "accepted": self.accepted,
+            "chosen_index": self.selected_index,
+            "chosen": self.estimate,
+            "estimated_rows_scanned": self.estimated_rows_scanned,
+            "estimated_rows_returned": self.estimated_rows_returned,
+            "filter_pushdown": self.filter_pushdown,
+            "alternatives": self.alternatives,
+            "sort": self.sort,
+            "sort_strategy": self.sort_strategy,
+            "sort_window": self.sort_window,
+            "limit": self.limit,
+            "offset": self.offset,
+            "max_scan": self.max_scan,
+            "rejection": rejection
+        })
+    }
 }
 
 impl fmt::Display for ExplainPlan {
@@ -98,21 +248,59 @@ impl fmt::Display for ExplainPlan {
             if let Some(scan) = &self.scan_type {
                 writeln!(f, "Scan Type: {}", scan)?;
             }
+            if let Some(prefix) = self.matched_prefix {
+                writeln!(
+                    f,
+                    "Matched Prefix: {} of {} ({})",
+                    prefix,
+                    self.index_fields.len(),
+                    self.index_fields[..prefix].join(", ")
+                )?;
+            }
             if !self.predicates.is_empty() {
                 writeln!(f, "Predicates:")?;
                 for pred in &self.predicates {
                     writeln!(f, "  - {}", pred)?;
                 }
             }
+            if let Some(pushdown) = &self.filter_pushdown {
+                if !pushdown.index.is_empty() {
+                    writeln!(f, "Index Filter: {}", pushdown.index.join(", "))?;
+                }
+                if !pushdown.residual.is_empty() {
+                    writeln!(f, "Residual Filter: {}", pushdown.residual.join(", "))?;
+                }
+            }
             if let Some(sort) = &self.sort {
                 writeln!(f, "Sort: {}", sort)?;
             }
+            if let Some(strategy) = &self.sort_strategy {
+                match self.sort_window {
+                    Some(window) => {
+                        writeln!(f, "Sort Strategy: {} ({} documents)", strategy, window)?
+                    }
+                    None => writeln!(f, "Sort Strategy: {}", strategy)?,
+                }
+            }
             if let Some(limit) = self.limit {
                 writeln!(f, "Limit: {}", limit)?;
             }
+            if let Some(offset) = self.offset.filter(|offset| *offset > 0) {
+                writeln!(f, "Offset: {}", offset)?;
+            }
             if let Some(max_scan) = self.max_scan {
                 writeln!(f, "Max Scan: {} documents", max_scan)?;
             }
+            if let Some(estimate) = &self.estimate {
+                writeln!(
+                    f,
+                    "Estimated Cost: {} documents ({} rows)",
+                    estimate.estimated_cost, estimate.estimated_rows
+                )?;
+            }
+            if let Some(returned) = self.estimated_rows_returned {
+                writeln!(f, "Estimated Returned: {} documents", returned)?;
+            }
         } else {
             writeln!(f, "Status: REJECTED")?;
             if let Some(code) = &self.rejection_code {
@@ -123,6 +311,20 @@ impl fmt::Display for ExplainPlan {
             }
         }
 
+        if !self.alternatives.is_empty() {
+            writeln!(f, "Alternatives:")?;
+            for alt in &self.alternatives {
+                writeln!(
+                    f,
+                    "  - {}: cost {} ({} rows), {}",
+                    alt.describe(),
+                    alt.estimated_cost,
+                    alt.estimated_rows,
+                    alt.reason.as_deref().unwrap_or("not chosen")
+                )?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -130,7 +332,7 @@ impl fmt::Display for ExplainPlan {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::planner::ast::{Predicate, Query};
+    use crate::planner::ast::{Predicate, Query, SortSpec};
     use crate::planner::planner::{IndexMetadata, QueryPlanner, SchemaRegistry};
     use serde_json::json;
     use std::collections::HashSet;
@@ -170,6 +372,86 @@ mod tests {
         assert!(output.contains("email"));
     }
 
+    #[test]
+    fn test_explain_reports_compound_prefix() {
+        let registry = TestSchemaRegistry;
+        let indexes = IndexMetadata::new().with_compound_index("a_b_c", ["a", "b", "c"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("a", json!(1)))
+            .with_predicate(Predicate::gt("b", json!(5)))
+            .with_limit(10);
+
+        let plan = planner.plan(&query).unwrap();
+        let explain = ExplainPlan::from_plan(&plan);
+
+        assert_eq!(explain.selected_index, Some("a_b_c".into()));
+        assert_eq!(explain.scan_type, Some("INDEX_COMPOUND".into()));
+        assert_eq!(explain.matched_prefix, Some(2));
+
+        let output = format!("{}", explain);
+        assert!(output.contains("Matched Prefix: 2 of 3 (a, b)"));
+    }
+
+    #[test]
+    fn test_explain_splits_index_and_residual_filters() {
+        let registry = TestSchemaRegistry;
+        let indexes = IndexMetadata::with_indexes(["c"]).with_compound_index("a_b", ["a", "b"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("a", json!(1)))
+            .with_predicate(Predicate::gt("b", json!(5)))
+            .with_predicate(Predicate::lt("b", json!(9)))
+            .with_predicate(Predicate::eq("c", json!(2)))
+            .with_limit(10);
+        let explain = ExplainPlan::from_plan(&planner.plan(&query).unwrap());
+
+        let pushdown = explain.filter_pushdown.as_ref().unwrap();
+        assert_eq!(pushdown.index, vec!["a eq", "b gt", "b lt"]);
+        assert_eq!(pushdown.residual, vec!["c eq"]);
+
+        let output = format!("{}", explain);
+        assert!(output.contains("Index Filter: a eq, b gt, b lt\n"));
+        assert!(output.contains("Residual Filter: c eq\n"));
+    }
+
+    #[test]
+    fn test_explain_reports_sort_strategy() {
+        let registry = TestSchemaRegistry;
+        let indexes = IndexMetadata::with_indexes(["age", "name"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_sort(SortSpec::desc("age"))
+            .with_limit(10);
+        let explain = ExplainPlan::from_plan(&planner.plan(&query).unwrap());
+        assert_eq!(explain.sort_strategy, Some("INDEX".into()));
+        assert_eq!(explain.sort_window, None);
+        assert!(format!("{}", explain).contains("Sort Strategy: INDEX\n"));
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_sort(SortSpec::asc("name"))
+            .with_limit(10)
+            .with_offset(5);
+        let explain = ExplainPlan::from_plan(&planner.plan(&query).unwrap());
+        let json = explain.to_json();
+        assert_eq!(json["sort_strategy"], "TOP_N");
+        assert_eq!(json["sort_window"], 15);
+        assert_eq!(json["offset"], 5);
+
+        let output = format!("{}", explain);
+        assert!(output.contains("Sort Strategy: TOP_N (15 documents)"));
+        assert!(output.contains("Offset: 5"));
+    }
+
     #[test]
     fn test_explain_rejected_plan() {
         let err = PlannerError::unindexed_field("name");
@@ -12,19 +12,58 @@
 //! # Index Selection Priority (strict order)
 //!
 //! 1. Primary key equality (_id)
-//! 2. Indexed equality predicate
-//! 3. Indexed range predicate with limit
+//! 2. Compound index matching a prefix of two or more fields
+//! 3. Indexed equality predicate
+//! 4. Compound index with equality on its leading field
+//! 5. Indexed range predicate with limit
+//! 6. Compound index with a range on its leading field
+//! 7. No flat predicates: union of index scans over an `$or`'s branches
+//! 8. No flat predicates: ordered scan of the sort field's index
 //!
-//! Ties broken lexicographically by field name.
+//! Ties broken by fewest estimated rows, then lexicographically by field
+//! name.
+//!
+//! # Boolean Filters
+//!
+//! `FilterExpr` nests `$and`, `$or` and `$not` over predicates. ANDs of
+//! predicates flatten into the query's predicates; ORs and NOTs are
+//! evaluated against each fetched document, and an `$or` whose branches are
+//! all indexed can drive an `INDEX_UNION` scan.
+//!
+//! # Sorting
+//!
+//! Each sorted plan carries a `SortStrategy`: `INDEX` when the scan yields
+//! documents in sort order (reversed for DESC), otherwise an in-memory
+//! `TOP_N` heap over limit + offset documents, or a `FULL` sort for windows
+//! above `MAX_TOP_N_WINDOW`.
+//!
+//! # Explain
+//!
+//! `QueryPlanner::explain` reports estimated cost for the chosen access path
+//! and for each rejected alternative, with the reason it was not chosen.
+//! Estimates come from `IndexStatistics` and never affect selection.
+//!
+//! # Statistics
+//!
+//! `Statistics` (stats.rs) keeps per-collection document counts, sizes and
+//! distinct-value sketches, maintained on writes and refreshed by the
+//! `analyze` operation. Missing or stale statistics fall back to defaults.
 
 mod ast;
 mod bounds;
+mod cost;
 mod errors;
 mod explain;
 mod planner;
+mod stats;
 
-pub use ast::{FilterOp, Predicate, Query, SortDirection, SortSpec};
+pub use ast::{FilterExpr, FilterOp, Predicate, Projection, Query, SortDirection, SortSpec};
 pub use bounds::BoundednessProof;
+pub use cost::{FieldStatistics, IndexStatistics, PathEstimate, COLLECTION_SCAN};
 pub use errors::{PlannerError, PlannerErrorCode, PlannerResult};
-pub use explain::ExplainPlan;
-pub use planner::{IndexMetadata, QueryPlan, QueryPlanner, ScanType, SchemaRegistry};
+pub use explain::{ExplainPlan, FilterPushdown};
+pub use planner::{
+    range_bounds, CompoundMatch, IndexMetadata, IndexUnion, QueryPlan, QueryPlanner, ScanType,
+    SchemaRegistry, SortStrategy, UnionBranch, MAX_TOP_N_WINDOW,
+};
+pub use stats::{CollectionStatistics, FieldSketch, Statistics, StatisticsConfig};
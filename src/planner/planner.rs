@@ -4,22 +4,79 @@
 //!
 //! Index selection priority (strict order):
 //! 1. Primary key equality (_id)
-//! 2. Indexed equality predicate
-//! 3. Indexed range predicate with limit
+//! 2. Compound index matching a prefix of two or more fields
+//! 3. Indexed equality predicate
+//! 4. Compound index with equality on its leading field
+//! 5. Indexed range predicate with limit
+//! 6. Compound index with a range on its leading field
+//! 7. No flat predicates: union of index scans over an `$or`'s branches
+//! 8. No flat predicates: ordered scan of the sort field's index
 //!
-//! Ties broken lexicographically by field name.
+//! Between single-field indexes at the same priority, the fewest estimated
+//! rows wins, then the lexicographically smallest field name. Only fresh
+//! statistics (see `stats.rs`) give fields different estimates, so without
+//! them the choice is lexicographic. Compound indexes: longest prefix first,
+//! then index name.
+//!
+//! A compound index over `(f1, .., fn)` matches the longest run of leading
+//! fields with equality predicates, plus the next field if it has a range
+//! predicate. Predicates on fields beyond the matched prefix do not use it.
+//!
+//! # Boolean filters
+//!
+//! `$and` of predicates flattens into the query's predicates. `$or` and
+//! `$not` stay expressions, evaluated against every fetched document; all
+//! their fields must be indexed like any other filter field. A query whose
+//! only filters are expressions can use an index union: the first `$or`
+//! whose every branch ANDs at least one indexed predicate. Each branch
+//! scans its own single-field index (primary key, then equality, then
+//! range) and the executor unions the offsets.
+//!
+//! `explain` additionally estimates the cost of the chosen path and of every
+//! alternative (see `cost.rs`). Beyond the tie-break above, estimates never
+//! influence selection.
+//!
+//! # Sorting
+//!
+//! A sorted plan records a `SortStrategy`. The chosen scan provides the
+//! order when its key order leads with the sort field: a single-field scan
+//! of that field, or a compound scan whose field after the equality prefix
+//! is the sort field. DESC traverses the index in reverse. Otherwise the
+//! executor sorts in memory: a top-N heap over limit + offset documents, or
+//! a full sort when that window exceeds `MAX_TOP_N_WINDOW`.
+//!
+//! A single-field index may be descending and collated (see
+//! `IndexOptions`). A sort without a collation takes the collation of its
+//! field's index. The index provides the order only when its collation is
+//! the sort's; either direction is served, traversing a descending index
+//! forwards for DESC. Compound indexes are binary and ascending. A
+//! collated index cannot serve filters: its keys equate values the filter
+//! tells apart.
 
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
 
-use super::ast::{Predicate, Query, SortSpec};
+use serde_json::Value;
+
+use super::ast::{FilterExpr, FilterOp, Predicate, Projection, Query, SortDirection, SortSpec};
 use super::bounds::{BoundednessAnalyzer, BoundednessProof};
+use super::cost::{IndexStatistics, PathEstimate};
 use super::errors::{PlannerError, PlannerResult};
+use super::explain::{ExplainPlan, FilterPushdown};
+use crate::index::{Collation, IndexKey, IndexOptions};
 
 /// Index metadata provided to the planner
 #[derive(Debug, Clone)]
 pub struct IndexMetadata {
     /// Set of indexed field names (excluding _id which is always indexed)
     pub indexed_fields: HashSet<String>,
+    /// Compound indexes (name -> fields in key order)
+    pub compound_indexes: BTreeMap<String, Vec<String>>,
+    /// Ordering options of single-field indexes that have them
+    pub index_options: HashMap<String, IndexOptions>,
+    /// Statistics for explain estimates (never used for selection)
+    pub statistics: IndexStatistics,
 }
 
 impl IndexMetadata {
@@ -27,6 +84,9 @@ impl IndexMetadata {
     pub fn new() -> Self {
         Self {
             indexed_fields: HashSet::new(),
+            compound_indexes: BTreeMap::new(),
+            index_options: HashMap::new(),
+            statistics: IndexStatistics::default(),
         }
     }
 
@@ -34,13 +94,51 @@ impl IndexMetadata {
     pub fn with_indexes(fields: impl IntoIterator<Item = impl Into<String>>) -> Self {
         Self {
             indexed_fields: fields.into_iter().map(Into::into).collect(),
+            compound_indexes: BTreeMap::new(),
+            index_options: HashMap::new(),
+            statistics: IndexStatistics::default(),
         }
     }
 
+    /// Adds a compound index over `fields` (in key order)
+    pub fn with_compound_index(
+        mut self,
+        name: impl Into<String>,
+        fields: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.compound_indexes
+            .insert(name.into(), fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Adds a single-field index on `field` with the given ordering options
+    pub fn with_index_options(mut self, field: impl Into<String>, options: IndexOptions) -> Self {
+        let field = field.into();
+        self.indexed_fields.insert(field.clone());
+        self.index_options.insert(field, options);
+        self
+    }
+
+    /// Sets the statistics used for explain estimates
+    pub fn with_statistics(mut self, statistics: IndexStatistics) -> Self {
+        self.statistics = statistics;
+        self
+    }
+
     /// Checks if a field is indexed
     pub fn is_indexed(&self, field: &str) -> bool {
         field == "_id" || self.indexed_fields.contains(field)
     }
+
+    /// Returns the ordering options of a field's index
+    pub fn index_options(&self, field: &str) -> IndexOptions {
+        self.index_options.get(field).copied().unwrap_or_default()
+    }
+
+    /// Checks if a field's index can serve filters: it is not collated
+    pub fn is_filter_indexed(&self, field: &str) -> bool {
+        self.is_indexed(field) && self.index_options(field).collation == Collation::Binary
+    }
 }
 
 impl Default for IndexMetadata {
@@ -58,6 +156,11 @@ pub enum ScanType {
     IndexedEquality,
     /// Indexed range scan with limit
     IndexedRange,
+    /// Compound index scan: equality on leading fields, optional range on
+    /// the next
+    CompoundPrefix,
+    /// Union of single-field index scans, one per branch of an `$or`
+    IndexUnion,
 }
 
 impl ScanType {
@@ -66,10 +169,137 @@ impl ScanType {
             ScanType::PrimaryKey => "PK_LOOKUP",
             ScanType::IndexedEquality => "INDEX_EQ",
             ScanType::IndexedRange => "INDEX_RANGE",
+            ScanType::CompoundPrefix => "INDEX_COMPOUND",
+            ScanType::IndexUnion => "INDEX_UNION",
+        }
+    }
+}
+
+/// Largest window (limit + offset) the executor keeps in a top-N heap.
+///
+/// Past this the heap holds most of a typical candidate set anyway, so the
+/// plan sorts every candidate instead. Both are charged against
+/// `max_sort_bytes`.
+pub const MAX_TOP_N_WINDOW: u64 = 10_000;
+
+/// How a plan produces its sort order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortStrategy {
+    /// The index scan yields documents in sort order, traversed in the
+    /// given direction; reading stops once the window is filled
+    Index(SortDirection),
+    /// In-memory heap keeping the first `window` (limit + offset) documents
+    TopN(u64),
+    /// In-memory sort of every matching document
+    Full,
+}
+
+impl SortStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortStrategy::Index(_) => "INDEX",
+            SortStrategy::TopN(_) => "TOP_N",
+            SortStrategy::Full => "FULL",
+        }
+    }
+
+    /// Whether the executor sorts in memory
+    pub fn is_in_memory(&self) -> bool {
+        !matches!(self, SortStrategy::Index(_))
+    }
+}
+
+/// How a query's predicates use a compound index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompoundMatch {
+    /// Fields of the compound index, in key order
+    pub fields: Vec<String>,
+    /// Number of leading fields constrained by the query
+    pub prefix_len: usize,
+    /// Whether the last matched field is constrained by a range
+    pub ends_in_range: bool,
+}
+
+impl CompoundMatch {
+    /// Matches a compound index against a query's predicates
+    fn new(fields: &[String], query: &Query) -> Self {
+        let mut prefix_len = 0;
+        let mut ends_in_range = false;
+
+        for field in fields {
+            let on_field = || query.predicates.iter().filter(|p| &p.field == field);
+            if on_field().any(|p| p.is_equality()) {
+                prefix_len += 1;
+                continue;
+            }
+            if on_field().any(|p| p.is_range()) {
+                prefix_len += 1;
+                ends_in_range = true;
+            }
+            break;
+        }
+
+        Self {
+            fields: fields.to_vec(),
+            prefix_len,
+            ends_in_range,
+        }
+    }
+
+    /// Fields constrained by the query, in key order
+    pub fn matched_fields(&self) -> &[String] {
+        &self.fields[..self.prefix_len]
+    }
+
+    /// Number of leading fields constrained by equality
+    pub fn equality_len(&self) -> usize {
+        self.prefix_len - usize::from(self.ends_in_range)
+    }
+}
+
+/// One branch of an index union: a single-field index scan
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnionBranch {
+    /// Field whose index the branch scans
+    pub index: String,
+    /// `PrimaryKey`, `IndexedEquality` or `IndexedRange`
+    pub scan_type: ScanType,
+    /// The branch's predicates, all combined with AND
+    pub predicates: Vec<Predicate>,
+}
+
+impl UnionBranch {
+    /// Whether the branch's index scan serves predicate `p`
+    pub fn serves(&self, p: &Predicate) -> bool {
+        match self.scan_type {
+            ScanType::PrimaryKey => p.is_primary_key(),
+            ScanType::IndexedEquality => p.field == self.index && p.is_equality(),
+            ScanType::IndexedRange => p.field == self.index && p.is_range(),
+            ScanType::CompoundPrefix | ScanType::IndexUnion => false,
         }
     }
 }
 
+/// Index scans whose offsets are unioned to serve an `$or`
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexUnion {
+    /// Position of the `$or` in the query's expressions
+    pub expression: usize,
+    /// One scan per branch, in branch order
+    pub branches: Vec<UnionBranch>,
+}
+
+impl IndexUnion {
+    /// Name reported as the chosen index, e.g. `email|status`
+    pub fn name(&self) -> String {
+        self.branches
+            .iter()
+            .map(|b| b.index.as_str())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+}
+
 /// Immutable query plan (no runtime state)
 #[derive(Debug, Clone)]
 pub struct QueryPlan {
@@ -79,20 +309,120 @@ pub struct QueryPlan {
     pub schema_id: String,
     /// Schema version
     pub schema_version: String,
-    /// Chosen index (field name)
+    /// Chosen index (field name, or index name for a compound index)
     pub chosen_index: String,
     /// Scan type
     pub scan_type: ScanType,
+    /// Compound index match (set when scan_type is CompoundPrefix)
+    pub compound: Option<CompoundMatch>,
+    /// Branch scans (set when scan_type is IndexUnion)
+    pub union: Option<IndexUnion>,
     /// Filter predicates to apply
     pub predicates: Vec<Predicate>,
+    /// Boolean expressions to apply, combined with the predicates by AND
+    pub expressions: Vec<FilterExpr>,
     /// Sort specification (if any)
     pub sort: Option<SortSpec>,
+    /// How the sort order is produced (set when sort is)
+    pub sort_strategy: Option<SortStrategy>,
     /// Limit
     pub limit: u64,
+    /// Documents skipped before the limit applies
+    pub offset: u64,
+    /// Fields to return (None returns whole documents)
+    pub projection: Option<Projection>,
     /// Boundedness proof
     pub bounds_proof: BoundednessProof,
 }
 
+impl QueryPlan {
+    /// Scan bounds for a compound plan: equality values for the leading
+    /// fields, then the lower and upper bound on the next field.
+    ///
+    /// Returns None if the plan does not use a compound index.
+    pub fn compound_bounds(&self) -> Option<(Vec<&Value>, Bound<&Value>, Bound<&Value>)> {
+        let compound = self.compound.as_ref()?;
+        let eq_len = compound.equality_len();
+
+        let mut prefix = Vec::with_capacity(eq_len);
+        for field in &compound.fields[..eq_len] {
+            let value = self.predicates.iter().find_map(|p| match &p.op {
+                FilterOp::Eq(v) if &p.field == field => Some(v),
+                _ => None,
+            })?;
+            prefix.push(value);
+        }
+
+        let (lower, upper) = match compound.ends_in_range {
+            true => self.range_bounds(&compound.fields[eq_len]),
+            false => (Bound::Unbounded, Bound::Unbounded),
+        };
+
+        Some((prefix, lower, upper))
+    }
+
+    /// Tightest lower and upper bounds the plan's range predicates put on
+    /// `field`.
+    ///
+    /// Of two bounds on the same side the narrower wins; at equal values
+    /// the exclusive one. `age > 18 AND age >= 20 AND age < 65` gives
+    /// `[20, 65)`.
+    pub fn range_bounds(&self, field: &str) -> (Bound<&Value>, Bound<&Value>) {
+        range_bounds(&self.predicates, field)
+    }
+}
+
+/// Tightest bounds the range predicates in `predicates` put on `field`
+pub fn range_bounds<'p>(
+    predicates: &'p [Predicate],
+    field: &str,
+) -> (Bound<&'p Value>, Bound<&'p Value>) {
+    let mut lower = Bound::Unbounded;
+    let mut upper = Bound::Unbounded;
+    for pred in predicates.iter().filter(|p| p.field == field) {
+        match &pred.op {
+            FilterOp::Gte(v) => lower = narrower(lower, Bound::Included(v), Ordering::Greater),
+            FilterOp::Gt(v) => lower = narrower(lower, Bound::Excluded(v), Ordering::Greater),
+            FilterOp::Lte(v) => upper = narrower(upper, Bound::Included(v), Ordering::Less),
+            FilterOp::Lt(v) => upper = narrower(upper, Bound::Excluded(v), Ordering::Less),
+            FilterOp::Eq(_) => {}
+        }
+    }
+    (lower, upper)
+}
+
+/// The narrower of two bounds on one side of a range. `inward` is how a
+/// value compares to another when it narrows the range: `Greater` for
+/// lower bounds. Values compare as index keys; incomparable values keep
+/// the current bound, and the residual filter applies both.
+fn narrower<'p>(
+    current: Bound<&'p Value>,
+    candidate: Bound<&'p Value>,
+    inward: Ordering,
+) -> Bound<&'p Value> {
+    let (current_value, current_excluded) = match current {
+        Bound::Included(v) => (v, false),
+        Bound::Excluded(v) => (v, true),
+        Bound::Unbounded => return candidate,
+    };
+    let (value, excluded) = match candidate {
+        Bound::Included(v) => (v, false),
+        Bound::Excluded(v) => (v, true),
+        Bound::Unbounded => return current,
+    };
+    let (Some(key), Some(current_key)) = (
+        IndexKey::from_json(value),
+        IndexKey::from_json(current_value),
+    ) else {
+        return current;
+    };
+    match key.cmp(&current_key) {
+        Ordering::Equal if excluded && !current_excluded => candidate,
+        ordering if ordering == inward => candidate,
+        _ => current,
+    }
+}
+
 /// Schema registry trait for planner (read-only)
 pub trait SchemaRegistry {
     /// Check if schema exists
@@ -142,73 +472,497 @@ impl<'a, S: SchemaRegistry> QueryPlanner<'a, S> {
             ));
         }
 
-        // 4. Prove boundedness BEFORE plan generation
-        let analyzer = BoundednessAnalyzer::new(&self.index_metadata.indexed_fields);
+        // 4. Select index using strict priority order. Errors are reported
+        // after the boundedness check, which gives the more specific reason.
+        let compound = self.best_compound(query);
+        let union = self.index_union(query);
+        let selection = self.select_index(query, compound.as_ref(), union.as_ref());
+        let compound = match selection {
+            Ok((_, ScanType::CompoundPrefix)) => compound.map(|(_, m)| m),
+            _ => None,
+        };
+        let union = match selection {
+            Ok((_, ScanType::IndexUnion)) => union,
+            _ => None,
+        };
+
+        // 5. Prove boundedness BEFORE plan generation. Fields in the matched
+        // prefix of a chosen compound index count as indexed.
+        let mut indexed_fields = self.index_metadata.indexed_fields.clone();
+        if let Some(m) = &compound {
+            indexed_fields.extend(m.matched_fields().iter().cloned());
+        }
+        let analyzer = BoundednessAnalyzer::new(&indexed_fields);
         let bounds_proof = analyzer.analyze(query)?;
+        if let Some(field) = query.filter_fields().find(|field| {
+            !self.index_metadata.is_filter_indexed(field)
+                && !compound
+                    .as_ref()
+                    .is_some_and(|m| m.matched_fields().iter().any(|f| f == field))
+        }) {
+            return Err(PlannerError::collated_field(field));
+        }
+        let (chosen_index, scan_type) = selection?;
 
-        // 5. Select index using strict priority order
-        let (chosen_index, scan_type) = self.select_index(query)?;
+        // 6. Decide how the sort order is produced. A sort without a
+        // collation takes its field's index collation.
+        let limit = query.limit.unwrap(); // Already validated in bounds
+        let offset = query.offset.unwrap_or(0);
+        let sort = query.sort.clone().map(|mut sort| {
+            sort.collation
+                .get_or_insert(self.index_metadata.index_options(&sort.field).collation);
+            sort
+        });
+        let sort_strategy = sort.as_ref().map(|sort| {
+            Self::sort_strategy(
+                sort,
+                &chosen_index,
+                self.index_metadata.index_options(&chosen_index),
+                compound.as_ref(),
+                limit.saturating_add(offset),
+            )
+        });
 
-        // 6. Build immutable plan
+        // 7. Build immutable plan
         Ok(QueryPlan {
             collection: query.collection.clone(),
             schema_id: query.schema_id.clone(),
             schema_version: schema_version.clone(),
             chosen_index,
             scan_type,
+            compound,
+            union,
             predicates: query.predicates.clone(),
-            sort: query.sort.clone(),
-            limit: query.limit.unwrap(), // Already validated in bounds
+            expressions: query.expressions.clone(),
+            sort,
+            sort_strategy,
+            limit,
+            offset,
+            projection: query.projection.clone(),
             bounds_proof,
         })
     }
 
+    /// Chooses how a plan produces its sort order.
+    ///
+    /// The scan provides it when its key order leads with the sort field
+    /// under the sort's collation; otherwise the executor sorts in memory,
+    /// keeping a top-N heap unless the window is larger than
+    /// `MAX_TOP_N_WINDOW`.
+    fn sort_strategy(
+        sort: &SortSpec,
+        chosen_index: &str,
+        index_options: IndexOptions,
+        compound: Option<&CompoundMatch>,
+        window: u64,
+    ) -> SortStrategy {
+        let collation = sort.collation.unwrap_or_default();
+        let index_ordered = match compound {
+            Some(m) => {
+                m.fields.get(m.equality_len()) == Some(&sort.field)
+                    && collation == Collation::Binary
+            }
+            None => chosen_index == sort.field && collation == index_options.collation,
+        };
+
+        if index_ordered {
+            SortStrategy::Index(sort.direction)
+        } else if window <= MAX_TOP_N_WINDOW {
+            SortStrategy::TopN(window)
+        } else {
+            SortStrategy::Full
+        }
+    }
+
+    /// Plans a query and explains the result.
+    ///
+    /// Reports the estimated cost of the chosen access path and lists every
+    /// other path the query's predicates could use, each with its estimate
+    /// and the reason it was not chosen. A rejected query lists all paths as
+    /// alternatives.
+    pub fn explain(&self, query: &Query) -> ExplainPlan {
+        let plan = self.plan(query);
+        let mut paths = self.access_paths(query);
+
+        let mut explain = match &plan {
+            Ok(plan) => ExplainPlan::from_plan(plan),
+            Err(err) => ExplainPlan::from_error(err),
+        };
+
+        let chosen = plan.as_ref().ok().and_then(|plan| {
+            let pos = paths.iter().position(|(_, path)| {
+                path.scan_type == plan.scan_type.as_str()
+                    && path.index.as_deref() == Some(plan.chosen_index.as_str())
+            })?;
+            let (tier, mut path) = paths.remove(pos);
+            // Sorting in memory reads every candidate before the limit
+            if plan.sort_strategy.is_some_and(|s| s.is_in_memory()) {
+                path.estimated_cost = path.estimated_rows;
+            }
+            Some((tier, path))
+        });
+
+        for (tier, path) in &mut paths {
+            if path.reason.is_some() {
+                continue;
+            }
+            let reason = match (&chosen, &plan) {
+                (Some((chosen_tier, chosen)), _) if tier == chosen_tier => {
+                    format!("ranked after {} at equal priority", chosen.describe())
+                }
+                (Some((_, chosen)), _) => {
+                    format!("lower priority than {}", chosen.describe())
+                }
+                (None, Err(err)) => format!("query rejected: {}", err.message()),
+                (None, Ok(_)) => "not chosen".to_string(),
+            };
+            path.reason = Some(match self.partial_coverage(path) {
+                Some(coverage) => format!("{}; {}", coverage, reason),
+                None => reason,
+            });
+        }
+
+        if let (Ok(plan), Some((_, path))) = (&plan, &chosen) {
+            explain.estimated_rows_scanned = Some(path.estimated_cost);
+            explain.estimated_rows_returned = Some(self.estimate_returned(plan, path));
+        }
+        explain.estimate = chosen.map(|(_, path)| path);
+        explain.alternatives = paths.into_iter().map(|(_, path)| path).collect();
+        explain
+    }
+
+    /// Rows the chosen path yields, filtered by the residual predicates,
+    /// then reduced by the offset and capped at the limit
+    fn estimate_returned(&self, plan: &QueryPlan, path: &PathEstimate) -> u64 {
+        let mut residual: Vec<(&str, bool)> = plan
+            .predicates
+            .iter()
+            .filter(|p| !FilterPushdown::is_pushed(plan, p))
+            .map(|p| (p.field.as_str(), p.is_equality()))
+            .collect();
+        // A field with both equality and range predicates counts once, as
+        // equality
+        residual.sort_by_key(|(field, equality)| (*field, !equality));
+        residual.dedup_by_key(|(field, _)| *field);
+
+        let rows = self
+            .index_metadata
+            .statistics
+            .residual_rows(path.estimated_rows, &residual);
+        rows.saturating_sub(plan.offset).min(plan.limit)
+    }
+
+    /// Describes a compound index path that uses only some of its fields,
+    /// e.g. `index covers 1 of 2 fields`
+    fn partial_coverage(&self, path: &PathEstimate) -> Option<String> {
+        let prefix = path.matched_prefix?;
+        let total = self
+            .index_metadata
+            .compound_indexes
+            .get(path.index.as_ref()?)?
+            .len();
+        (prefix < total).then(|| format!("index covers {} of {} fields", prefix, total))
+    }
+
+    /// Every access path the query's predicates could use, with estimates,
+    /// in selection order. Each is paired with its selection priority;
+    /// paths that cannot serve the query already carry their reason.
+    fn access_paths(&self, query: &Query) -> Vec<(u8, PathEstimate)> {
+        const UNUSABLE: u8 = u8::MAX;
+        let stats = &self.index_metadata.statistics;
+        let mut paths = Vec::new();
+
+        // Single-field indexes, including _id
+        let mut fields: Vec<&str> = query.predicates.iter().map(|p| p.field.as_str()).collect();
+        fields.sort();
+        fields.dedup();
+        for field in fields {
+            if !self.index_metadata.is_filter_indexed(field) {
+                continue;
+            }
+            let on_field = || query.predicates.iter().filter(|p| p.field == field);
+            let (tier, scan_type, rows) = if on_field().any(|p| p.is_primary_key()) {
+                (1, ScanType::PrimaryKey, stats.pk_rows())
+            } else if on_field().any(|p| p.is_equality()) {
+                (3, ScanType::IndexedEquality, stats.field_rows(field, true))
+            } else {
+                (5, ScanType::IndexedRange, stats.field_rows(field, false))
+            };
+            let path = PathEstimate::index_path(scan_type.as_str(), field, None, rows, query.limit);
+            paths.push((tier, 0, path));
+        }
+
+        // Without flat predicates, a union of index scans over an `$or`
+        if let Some(union) = self.index_union(query) {
+            let rows = union
+                .branches
+                .iter()
+                .map(|b| self.branch_rows(b))
+                .fold(0, u64::saturating_add);
+            let path = PathEstimate::index_path(
+                ScanType::IndexUnion.as_str(),
+                &union.name(),
+                None,
+                rows,
+                None,
+            );
+            paths.push((7, 0, path));
+        }
+
+        // Without predicates, an ordered scan of the sort field's index
+        if let Some(sort) = query.sort.as_ref().filter(|_| query.predicates.is_empty()) {
+            if self.index_metadata.is_indexed(&sort.field) {
+                let path = PathEstimate::index_path(
+                    ScanType::IndexedRange.as_str(),
+                    &sort.field,
+                    None,
+                    stats.scan_rows(&sort.field),
+                    query
+                        .limit
+                        .map(|limit| limit.saturating_add(query.offset.unwrap_or(0))),
+                );
+                paths.push((8, 0, path));
+            }
+        }
+
+        // Compound indexes with a predicate on any of their fields
+        for (name, index_fields) in &self.index_metadata.compound_indexes {
+            if !index_fields
+                .iter()
+                .any(|f| query.predicates.iter().any(|p| &p.field == f))
+            {
+                continue;
+            }
+
+            let matched = CompoundMatch::new(index_fields, query);
+            let rows = stats.compound_rows(name, &matched);
+            let mut path = PathEstimate::index_path(
+                ScanType::CompoundPrefix.as_str(),
+                name,
+                Some(matched.prefix_len),
+                rows,
+                query.limit,
+            );
+            let tier = match (matched.prefix_len, matched.ends_in_range) {
+                (0, _) => {
+                    path.reason = Some(format!(
+                        "predicate not sargable: leading field '{}' has no predicate",
+                        index_fields[0]
+                    ));
+                    UNUSABLE
+                }
+                (1, false) => 4,
+                (1, true) => 6,
+                _ => 2,
+            };
+            paths.push((tier, matched.prefix_len, path));
+        }
+
+        // Selection order: priority, then longest compound prefix, then
+        // fewest rows for single-field indexes, then name
+        let single_rows = |path: &PathEstimate| match path.matched_prefix {
+            Some(_) => 0,
+            None => path.estimated_rows,
+        };
+        paths.sort_by(|(tier_a, len_a, a), (tier_b, len_b, b)| {
+            (tier_a, std::cmp::Reverse(len_a), single_rows(a), &a.index).cmp(&(
+                tier_b,
+                std::cmp::Reverse(len_b),
+                single_rows(b),
+                &b.index,
+            ))
+        });
+
+        // A collection scan is never permitted, but its cost is reported last
+        let in_any_index = |field: &str| {
+            self.index_metadata.is_filter_indexed(field)
+                || self
+                    .index_metadata
+                    .compound_indexes
+                    .values()
+                    .any(|fields| fields.iter().any(|f| f == field))
+        };
+        let mut scan = PathEstimate::collection_scan(stats);
+        scan.reason = Some(match query.predicates.iter().find(|p| !in_any_index(&p.field)) {
+            Some(p) => format!(
+                "filter on unindexed field '{}' needs a collection scan, which is not permitted (Q2)",
+                p.field
+            ),
+            None => "collection scans are not permitted (Q2)".to_string(),
+        });
+        paths.push((UNUSABLE, 0, scan));
+
+        paths
+            .into_iter()
+            .map(|(tier, _, path)| (tier, path))
+            .collect()
+    }
+
+    /// Estimated rows one branch of an index union yields
+    fn branch_rows(&self, branch: &UnionBranch) -> u64 {
+        let stats = &self.index_metadata.statistics;
+        match branch.scan_type {
+            ScanType::PrimaryKey => stats.pk_rows(),
+            ScanType::IndexedEquality => stats.field_rows(&branch.index, true),
+            _ => stats.field_rows(&branch.index, false),
+        }
+    }
+
+    /// The index union serving a query without flat predicates: the first
+    /// `$or` whose every branch can scan a single-field index.
+    fn index_union(&self, query: &Query) -> Option<IndexUnion> {
+        if !query.predicates.is_empty() {
+            return None;
+        }
+        query
+            .expressions
+            .iter()
+            .enumerate()
+            .find_map(|(expression, expr)| {
+                let FilterExpr::Or(children) = expr else {
+                    return None;
+                };
+                let branches = children
+                    .iter()
+                    .map(|child| self.union_branch(child))
+                    .collect::<Option<Vec<_>>>()?;
+                (!branches.is_empty()).then_some(IndexUnion {
+                    expression,
+                    branches,
+                })
+            })
+    }
+
+    /// The index scan for one `$or` branch over the predicates it ANDs:
+    /// primary key, then the most selective indexed equality, then the
+    /// most selective indexed range.
+    ///
+    /// None if none of those predicates is indexed.
+    fn union_branch(&self, branch: &FilterExpr) -> Option<UnionBranch> {
+        let predicates = branch.conjuncts();
+        let (index, scan_type) = if predicates.iter().any(|p| p.is_primary_key()) {
+            ("_id".to_string(), ScanType::PrimaryKey)
+        } else if let Some(field) = self.most_selective(&predicates, true) {
+            (field.to_string(), ScanType::IndexedEquality)
+        } else {
+            let field = self.most_selective(&predicates, false)?;
+            (field.to_string(), ScanType::IndexedRange)
+        };
+        Some(UnionBranch {
+            index,
+            scan_type,
+            predicates,
+        })
+    }
+
+    /// Finds the compound index with the longest matched prefix.
+    ///
+    /// Ties broken lexicographically by index name.
+    fn best_compound(&self, query: &Query) -> Option<(String, CompoundMatch)> {
+        let mut best: Option<(String, CompoundMatch)> = None;
+        for (name, fields) in &self.index_metadata.compound_indexes {
+            let candidate = CompoundMatch::new(fields, query);
+            let longer = best
+                .as_ref()
+                .is_none_or(|(_, b)| candidate.prefix_len > b.prefix_len);
+            if candidate.prefix_len > 0 && longer {
+                best = Some((name.clone(), candidate));
+            }
+        }
+        best
+    }
+
     /// Selects index using strict priority order per QUERY.md §230-237.
     ///
     /// Priority:
     /// 1. Primary key equality (_id)
-    /// 2. Indexed equality predicate
-    /// 3. Indexed range predicate with limit
+    /// 2. Compound index matching two or more fields
+    /// 3. Indexed equality predicate
+    /// 4. Compound index with equality on its leading field
+    /// 5. Indexed range predicate with limit
+    /// 6. Compound index with a range on its leading field
+    /// 7. No flat predicates: union of index scans over an `$or`'s branches
+    /// 8. No flat predicates: ordered scan of the sort field's index
     ///
-    /// Ties broken lexicographically.
-    fn select_index(&self, query: &Query) -> PlannerResult<(String, ScanType)> {
+    /// Ties broken by fewest estimated rows, then lexicographically.
+    fn select_index(
+        &self,
+        query: &Query,
+        compound: Option<&(String, CompoundMatch)>,
+        union: Option<&IndexUnion>,
+    ) -> PlannerResult<(String, ScanType)> {
         // Priority 1: Primary key equality
         if query.has_pk_filter() {
             return Ok(("_id".to_string(), ScanType::PrimaryKey));
         }
 
-        // Collect equality predicates on indexed fields
-        let mut eq_candidates: Vec<&str> = query
-            .predicates
-            .iter()
-            .filter(|p| p.is_equality() && self.index_metadata.is_indexed(&p.field))
-            .map(|p| p.field.as_str())
-            .collect();
+        let compound_scan = |name: &String| Ok((name.clone(), ScanType::CompoundPrefix));
 
-        // Priority 2: Indexed equality (lexicographically smallest)
-        if !eq_candidates.is_empty() {
-            eq_candidates.sort();
-            return Ok((eq_candidates[0].to_string(), ScanType::IndexedEquality));
+        // Priority 2: Compound index matching two or more fields
+        if let Some((name, m)) = compound {
+            if m.prefix_len >= 2 {
+                return compound_scan(name);
+            }
         }
 
-        // Collect range predicates on indexed fields
-        let mut range_candidates: Vec<&str> = query
-            .predicates
-            .iter()
-            .filter(|p| p.is_range() && self.index_metadata.is_indexed(&p.field))
-            .map(|p| p.field.as_str())
-            .collect();
+        // Priority 3: Indexed equality (most selective)
+        if let Some(field) = self.most_selective(&query.predicates, true) {
+            return Ok((field.to_string(), ScanType::IndexedEquality));
+        }
+
+        // Priority 4: Compound index with equality on its leading field
+        if let Some((name, m)) = compound {
+            if !m.ends_in_range {
+                return compound_scan(name);
+            }
+        }
+
+        // Priority 5: Indexed range (most selective)
+        if let Some(field) = self.most_selective(&query.predicates, false) {
+            return Ok((field.to_string(), ScanType::IndexedRange));
+        }
+
+        // Priority 6: Compound index with a range on its leading field
+        if let Some((name, _)) = compound {
+            return compound_scan(name);
+        }
 
-        // Priority 3: Indexed range (lexicographically smallest)
-        if !range_candidates.is_empty() {
-            range_candidates.sort();
-            return Ok((range_candidates[0].to_string(), ScanType::IndexedRange));
+        // Priority 7: No flat predicates; an index scan per `$or` branch
+        if let Some(union) = union {
+            return Ok((union.name(), ScanType::IndexUnion));
+        }
+
+        // Priority 8: No flat predicates; the sort field's index, read in
+        // order, stops at the limit
+        if let Some(sort) = query.sort.as_ref().filter(|_| query.predicates.is_empty()) {
+            if self.index_metadata.is_indexed(&sort.field) {
+                return Ok((sort.field.clone(), ScanType::IndexedRange));
+            }
         }
 
         // No usable index found - should have been caught by bounds check
         // This path indicates a bug (empty query with no filters)
         Err(PlannerError::unbounded("No usable index found"))
     }
+
+    /// The indexed field with an equality (or range) predicate among
+    /// `predicates` and the fewest estimated rows; ties broken
+    /// lexicographically.
+    fn most_selective<'q>(&self, predicates: &'q [Predicate], equality: bool) -> Option<&'q str> {
+        let stats = &self.index_metadata.statistics;
+        predicates
+            .iter()
+            .filter(|p| {
+                let usable = if equality {
+                    p.is_equality()
+                } else {
+                    p.is_range()
+                };
+                usable && self.index_metadata.is_filter_indexed(&p.field)
+            })
+            .map(|p| p.field.as_str())
+            .min_by_key(|field| (stats.field_rows(field, equality), *field))
+    }
 }
 
 #[cfg(test)]
@@ -287,6 +1041,40 @@ mod tests {
         let plan = planner.plan(&query).unwrap();
         assert_eq!(plan.scan_type, ScanType::IndexedRange);
         assert_eq!(plan.chosen_index, "age");
+        assert_eq!(
+            plan.range_bounds("age"),
+            (Bound::Included(&json!(18)), Bound::Included(&json!(30)))
+        );
+    }
+
+    #[test]
+    fn test_range_bounds_keep_tightest() {
+        let (v18, v20, v65) = (json!(18), json!(20), json!(65));
+        let predicates = vec![
+            Predicate::gt("age", v18.clone()),
+            Predicate::gte("age", v20.clone()),
+            Predicate::lte("age", v65.clone()),
+            Predicate::lt("age", v65.clone()),
+            Predicate::gt("name", json!("a")),
+        ];
+        assert_eq!(
+            range_bounds(&predicates, "age"),
+            (Bound::Included(&v20), Bound::Excluded(&v65))
+        );
+
+        // At equal values the exclusive bound wins, in either order
+        let predicates = vec![
+            Predicate::gt("age", v18.clone()),
+            Predicate::gte("age", v18.clone()),
+        ];
+        assert_eq!(
+            range_bounds(&predicates, "age"),
+            (Bound::Excluded(&v18), Bound::Unbounded)
+        );
+        assert_eq!(
+            range_bounds(&[], "age"),
+            (Bound::Unbounded, Bound::Unbounded)
+        );
     }
 
     #[test]
@@ -423,4 +1211,567 @@ mod tests {
         // Should pick "alpha" (lexicographically smallest)
         assert_eq!(plan.chosen_index, "alpha");
     }
+
+    #[test]
+    fn test_fresh_statistics_break_ties_by_selectivity() {
+        use crate::planner::cost::FieldStatistics;
+
+        let registry = TestSchemaRegistry::new();
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("country", json!("NZ")))
+            .with_predicate(Predicate::eq("email", json!("a@example.com")))
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_predicate(Predicate::gte("score", json!(90)))
+            .with_limit(10);
+
+        // Without statistics: lexicographic
+        let indexes = IndexMetadata::with_indexes(["age", "country", "email", "score"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+        assert_eq!(planner.plan(&query).unwrap().chosen_index, "country");
+
+        let mut stats = IndexStatistics {
+            documents: 1000,
+            ..Default::default()
+        };
+        for (field, entries, distinct_keys) in [
+            ("age", 1000, 80),
+            ("country", 1000, 20),
+            ("email", 1000, 1000),
+            ("score", 90, 90),
+        ] {
+            let field_stats = FieldStatistics {
+                entries,
+                distinct_keys,
+            };
+            stats.fields.insert(field.to_string(), field_stats);
+        }
+        let indexes = indexes.with_statistics(stats);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        // Equality still outranks range; the more selective equality wins
+        assert_eq!(planner.plan(&query).unwrap().chosen_index, "email");
+        let explain = planner.explain(&query);
+        assert_eq!(
+            explain.alternatives[0].reason.as_deref(),
+            Some("ranked after INDEX_EQ on email at equal priority")
+        );
+
+        // Among ranges, the index with fewer entries wins
+        let ranges = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_predicate(Predicate::gte("score", json!(90)))
+            .with_limit(10);
+        assert_eq!(planner.plan(&ranges).unwrap().chosen_index, "score");
+    }
+
+    #[test]
+    fn test_compound_index_serves_eq_then_range() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::new().with_compound_index("a_b", ["a", "b"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("a", json!("x")))
+            .with_predicate(Predicate::gt("b", json!(10)))
+            .with_limit(10);
+
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.scan_type, ScanType::CompoundPrefix);
+        assert_eq!(plan.chosen_index, "a_b");
+
+        let compound = plan.compound.as_ref().unwrap();
+        assert_eq!(compound.prefix_len, 2);
+        assert!(compound.ends_in_range);
+
+        let (prefix, lower, upper) = plan.compound_bounds().unwrap();
+        assert_eq!(prefix, vec![&json!("x")]);
+        assert_eq!(lower, Bound::Excluded(&json!(10)));
+        assert_eq!(upper, Bound::Unbounded);
+    }
+
+    #[test]
+    fn test_compound_range_on_leading_field_matches_one_field() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::new().with_compound_index("a_b", ["a", "b"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        // A range on `a` stops the prefix: `b` is not matched
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gt("a", json!(5)))
+            .with_limit(10);
+
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.scan_type, ScanType::CompoundPrefix);
+        let compound = plan.compound.as_ref().unwrap();
+        assert_eq!(compound.prefix_len, 1);
+        assert_eq!(compound.matched_fields(), ["a".to_string()]);
+
+        let (prefix, lower, upper) = plan.compound_bounds().unwrap();
+        assert!(prefix.is_empty());
+        assert_eq!(lower, Bound::Excluded(&json!(5)));
+        assert_eq!(upper, Bound::Unbounded);
+
+        // ...so a predicate on `b` after a range on `a` is unindexed
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gt("a", json!(5)))
+            .with_predicate(Predicate::eq("b", json!(1)))
+            .with_limit(10);
+        let err = planner.plan(&query).unwrap_err();
+        assert_eq!(err.code().code(), "AERO_QUERY_UNINDEXED_FIELD");
+    }
+
+    #[test]
+    fn test_compound_non_leading_field_alone_is_unindexed() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::new().with_compound_index("a_b", ["a", "b"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gt("b", json!(5)))
+            .with_limit(10);
+
+        let err = planner.plan(&query).unwrap_err();
+        assert_eq!(err.code().code(), "AERO_QUERY_UNINDEXED_FIELD");
+    }
+
+    #[test]
+    fn test_compound_priority_against_single_field_indexes() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::with_indexes(["a"])
+            .with_compound_index("a_b", ["a", "b"])
+            .with_compound_index("a_b_c", ["a", "b", "c"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        // Single-field equality beats a one-field compound match
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("a", json!(1)))
+            .with_limit(10);
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.scan_type, ScanType::IndexedEquality);
+        assert!(plan.compound.is_none());
+
+        // A longer compound prefix beats single-field equality, and the
+        // longest prefix wins between compound indexes
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("a", json!(1)))
+            .with_predicate(Predicate::eq("b", json!(2)))
+            .with_predicate(Predicate::lte("c", json!(3)))
+            .with_limit(10);
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.scan_type, ScanType::CompoundPrefix);
+        assert_eq!(plan.chosen_index, "a_b_c");
+        assert_eq!(plan.compound.unwrap().prefix_len, 3);
+    }
+
+    #[test]
+    fn test_sort_strategy_selection() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::with_indexes(["age", "name"])
+            .with_compound_index("city_age", ["city", "age"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        // Sorting on the scanned index walks it in reverse for DESC
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_sort(SortSpec::desc("age"))
+            .with_limit(10);
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(
+            plan.sort_strategy,
+            Some(SortStrategy::Index(SortDirection::Desc))
+        );
+
+        // The field after a compound equality prefix is in index order
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("city", json!("Oslo")))
+            .with_sort(SortSpec::asc("age"))
+            .with_limit(10);
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.chosen_index, "city_age");
+        assert_eq!(
+            plan.sort_strategy,
+            Some(SortStrategy::Index(SortDirection::Asc))
+        );
+
+        // Any other sort keeps limit + offset documents in a heap...
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_sort(SortSpec::asc("name"))
+            .with_limit(10)
+            .with_offset(20);
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.sort_strategy, Some(SortStrategy::TopN(30)));
+        assert_eq!(plan.offset, 20);
+
+        // ...unless the window is too large for one
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_sort(SortSpec::asc("name"))
+            .with_limit(10)
+            .with_offset(MAX_TOP_N_WINDOW);
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.sort_strategy, Some(SortStrategy::Full));
+
+        // No sort, no strategy
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_limit(10);
+        assert_eq!(planner.plan(&query).unwrap().sort_strategy, None);
+    }
+
+    #[test]
+    fn test_sort_only_query_scans_sort_index() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::with_indexes(["age", "name"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_sort(SortSpec::desc("name"))
+            .with_limit(10);
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.scan_type, ScanType::IndexedRange);
+        assert_eq!(plan.chosen_index, "name");
+        assert_eq!(
+            plan.sort_strategy,
+            Some(SortStrategy::Index(SortDirection::Desc))
+        );
+    }
+
+    #[test]
+    fn test_sort_matches_index_collation() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::with_indexes(["age"])
+            .with_index_options(
+                "price",
+                IndexOptions::new().with_collation(Collation::Numeric),
+            )
+            .with_index_options(
+                "name",
+                IndexOptions::new()
+                    .descending()
+                    .with_collation(Collation::CaseInsensitive),
+            );
+        let planner = QueryPlanner::new(&registry, &indexes);
+        let sorted = |sort: SortSpec| {
+            Query::new("users", "users")
+                .with_schema_version("v1")
+                .with_sort(sort)
+                .with_limit(10)
+        };
+
+        // A sort takes its field's collation and is served either way
+        for sort in [SortSpec::asc("price"), SortSpec::desc("price")] {
+            let plan = planner.plan(&sorted(sort.clone())).unwrap();
+            assert_eq!(plan.chosen_index, "price");
+            assert_eq!(
+                plan.sort_strategy,
+                Some(SortStrategy::Index(sort.direction))
+            );
+            assert_eq!(plan.sort.unwrap().collation, Some(Collation::Numeric));
+        }
+        let plan = planner.plan(&sorted(SortSpec::desc("name"))).unwrap();
+        assert_eq!(
+            plan.sort_strategy,
+            Some(SortStrategy::Index(SortDirection::Desc))
+        );
+
+        // A different collation sorts in memory
+        let sort = SortSpec::asc("price").with_collation(Collation::Binary);
+        let plan = planner.plan(&sorted(sort)).unwrap();
+        assert_eq!(plan.sort_strategy, Some(SortStrategy::TopN(10)));
+        assert_eq!(plan.sort.unwrap().collation, Some(Collation::Binary));
+
+        // So does a collated sort on a binary index
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_sort(SortSpec::desc("age").with_collation(Collation::Numeric))
+            .with_limit(10);
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.sort_strategy, Some(SortStrategy::TopN(10)));
+    }
+
+    #[test]
+    fn test_collated_index_cannot_serve_filters() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::with_indexes(["age"]).with_index_options(
+            "name",
+            IndexOptions::new().with_collation(Collation::CaseInsensitive),
+        );
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("name", json!("Alice")))
+            .with_limit(10);
+        let err = planner.plan(&query).unwrap_err();
+        assert_eq!(err.code().code(), "AERO_QUERY_UNINDEXED_FIELD");
+        assert_eq!(err.field(), Some("name"));
+
+        // Sorting on it after a filter on another index is fine
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_sort(SortSpec::asc("name"))
+            .with_limit(10);
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.chosen_index, "age");
+        assert_eq!(plan.sort_strategy, Some(SortStrategy::TopN(10)));
+    }
+
+    #[test]
+    fn test_or_across_indexed_fields_uses_index_union() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::with_indexes(["email", "status"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_filter(FilterExpr::or([
+                Predicate::eq("email", json!("a@example.com")).into(),
+                FilterExpr::and([
+                    Predicate::eq("status", json!("banned")).into(),
+                    FilterExpr::negate(Predicate::eq("email", json!("b@example.com")).into()),
+                ]),
+            ]))
+            .with_limit(10);
+
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.scan_type, ScanType::IndexUnion);
+        assert_eq!(plan.chosen_index, "email|status");
+        assert!(plan.predicates.is_empty());
+        assert_eq!(plan.expressions.len(), 1);
+
+        // Each branch scans its own index; the NOT is left to the filter
+        let union = plan.union.unwrap();
+        let scans: Vec<_> = union
+            .branches
+            .iter()
+            .map(|b| (b.index.as_str(), b.scan_type))
+            .collect();
+        assert_eq!(
+            scans,
+            vec![
+                ("email", ScanType::IndexedEquality),
+                ("status", ScanType::IndexedEquality)
+            ]
+        );
+
+        let explain = planner.explain(&query);
+        let pushdown = explain.filter_pushdown.as_ref().unwrap();
+        assert_eq!(pushdown.index, vec!["or(email eq, status eq)"]);
+        assert_eq!(
+            pushdown.residual,
+            vec!["or(email eq, and(status eq, not(email eq)))"]
+        );
+        assert_eq!(
+            explain.estimate.unwrap().describe(),
+            "INDEX_UNION on email|status"
+        );
+    }
+
+    #[test]
+    fn test_or_branch_without_index_scan_rejected() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::with_indexes(["email", "status"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        // A branch that is only a negation has no index to scan
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_filter(FilterExpr::or([
+                Predicate::eq("email", json!("a@example.com")).into(),
+                FilterExpr::negate(Predicate::eq("status", json!("active")).into()),
+            ]))
+            .with_limit(10);
+        assert_eq!(
+            planner.plan(&query).unwrap_err().code().code(),
+            "AERO_QUERY_UNBOUNDED"
+        );
+
+        // With an indexed flat predicate the OR is a residual filter
+        let query = query.filter_eq("status", json!("banned"));
+        let plan = planner.plan(&query).unwrap();
+        assert_eq!(plan.scan_type, ScanType::IndexedEquality);
+        assert_eq!(plan.chosen_index, "status");
+        assert!(plan.union.is_none());
+    }
+
+    #[test]
+    fn test_explain_lists_alternatives_with_reasons() {
+        use crate::planner::cost::FieldStatistics;
+
+        let registry = TestSchemaRegistry::new();
+        let mut stats = IndexStatistics {
+            documents: 100,
+            ..Default::default()
+        };
+        for (field, distinct_keys) in [("email", 100), ("status", 4)] {
+            let field_stats = FieldStatistics {
+                entries: 100,
+                distinct_keys,
+            };
+            stats.fields.insert(field.to_string(), field_stats);
+        }
+        let indexes = IndexMetadata::with_indexes(["email", "status"])
+            .with_compound_index("age_status", ["age", "status"])
+            .with_compound_index("status_age", ["status", "age"])
+            .with_statistics(stats);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("email", json!("a@example.com")))
+            .with_predicate(Predicate::eq("status", json!("active")))
+            .with_limit(10);
+        let explain = planner.explain(&query);
+
+        let chosen = explain.estimate.unwrap();
+        assert_eq!(chosen.describe(), "INDEX_EQ on email");
+        assert_eq!((chosen.estimated_rows, chosen.estimated_cost), (1, 1));
+        assert_eq!(chosen.reason, None);
+
+        let alternatives: Vec<(String, u64, &str)> = explain
+            .alternatives
+            .iter()
+            .map(|a| (a.describe(), a.estimated_cost, a.reason.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            alternatives,
+            vec![
+                (
+                    "INDEX_EQ on status".to_string(),
+                    10,
+                    "ranked after INDEX_EQ on email at equal priority"
+                ),
+                (
+                    "INDEX_COMPOUND on status_age".to_string(),
+                    10,
+                    "index covers 1 of 2 fields; lower priority than INDEX_EQ on email"
+                ),
+                (
+                    "INDEX_COMPOUND on age_status".to_string(),
+                    10,
+                    "predicate not sargable: leading field 'age' has no predicate"
+                ),
+                (
+                    "COLLECTION_SCAN".to_string(),
+                    100,
+                    "collection scans are not permitted (Q2)"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_explain_reports_scan_and_return_estimates() {
+        use crate::planner::cost::FieldStatistics;
+
+        let registry = TestSchemaRegistry::new();
+        let mut stats = IndexStatistics {
+            documents: 1000,
+            ..Default::default()
+        };
+        for (field, distinct_keys) in [("email", 1000), ("status", 4)] {
+            let field_stats = FieldStatistics {
+                entries: 1000,
+                distinct_keys,
+            };
+            stats.fields.insert(field.to_string(), field_stats);
+        }
+        let indexes =
+            IndexMetadata::with_indexes(["age", "email", "status"]).with_statistics(stats);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("email", json!("a@example.com")))
+            .with_limit(10);
+        let json = planner.explain(&query).to_json();
+        assert_eq!(json["chosen_index"], "email");
+        assert_eq!(json["estimated_rows_scanned"], 1);
+        assert_eq!(json["estimated_rows_returned"], 1);
+        assert_eq!(
+            json["filter_pushdown"],
+            json!({"index": ["email eq"], "residual": []})
+        );
+
+        // Residual predicates reduce the returned estimate, not the scan
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("status", json!("active")))
+            .with_predicate(Predicate::gte("age", json!(18)))
+            .with_limit(1000);
+        let explain = planner.explain(&query);
+        assert_eq!(explain.selected_index.as_deref(), Some("status"));
+        assert_eq!(explain.estimated_rows_scanned, Some(250));
+        assert_eq!(explain.estimated_rows_returned, Some(84));
+        let pushdown = explain.filter_pushdown.unwrap();
+        assert_eq!(pushdown.index, vec!["status eq"]);
+        assert_eq!(pushdown.residual, vec!["age gte"]);
+    }
+
+    #[test]
+    fn test_explain_unindexed_field_reports_full_scan() {
+        let registry = TestSchemaRegistry::new();
+        let stats = IndexStatistics {
+            documents: 1000,
+            ..Default::default()
+        };
+        let indexes = IndexMetadata::with_indexes(["email"]).with_statistics(stats);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("name", json!("Alice")))
+            .with_limit(10);
+        let json = planner.explain(&query).to_json();
+        assert_eq!(json["accepted"], false);
+        assert_eq!(json["chosen_index"], Value::Null);
+        assert_eq!(json["estimated_rows_scanned"], Value::Null);
+        assert_eq!(json["filter_pushdown"], Value::Null);
+
+        let scan = &json["alternatives"][0];
+        assert_eq!(scan["scan_type"], "COLLECTION_SCAN");
+        assert_eq!(scan["estimated_cost"], 1000);
+    }
+
+    #[test]
+    fn test_explain_rejected_query_lists_every_path() {
+        let registry = TestSchemaRegistry::new();
+        let indexes = IndexMetadata::with_indexes(["email"]);
+        let planner = QueryPlanner::new(&registry, &indexes);
+
+        let query = Query::new("users", "users")
+            .with_schema_version("v1")
+            .with_predicate(Predicate::eq("email", json!("a@example.com")))
+            .with_predicate(Predicate::eq("name", json!("Alice")))
+            .with_limit(10);
+        let explain = planner.explain(&query);
+
+        assert!(!explain.accepted);
+        assert!(explain.estimate.is_none());
+        let reasons: Vec<&str> = explain
+            .alternatives
+            .iter()
+            .map(|a| a.reason.as_deref().unwrap())
+            .collect();
+        assert_eq!(reasons.len(), 2);
+        assert!(reasons[0].starts_with("query rejected: "));
+        assert!(reasons[1].starts_with("filter on unindexed field 'name'"));
+    }
 }
@@ -0,0 +1,619 @@
+//! Collection statistics for cost estimates
+//!
+//! Per collection the store keeps the document count, total document size
+//! and, for each indexed field, the number of documents with an indexable
+//! value plus a HyperLogLog sketch of its distinct values.
+//!
+//! # Maintenance
+//!
+//! - Inserts and deletes update counts and sizes incrementally
+//! - Inserts and updates add values to the sketches. A sketch cannot forget
+//!   values, so deletes and updates only count towards staleness.
+//! - `analyze` recomputes everything from a full scan of the collection
+//!
+//! # Staleness
+//!
+//! Statistics are stale once the mutations since the last analyze exceed
+//! `stale_mutation_percent` of the documents counted at that analyze, or if
+//! the collection has never been analyzed. Stale statistics still supply
+//! the (incrementally maintained) document count, but not distinct-value
+//! estimates, so the planner falls back to its default selectivities.
+//!
+//! # Persistence
+//!
+//! Each collection is stored as `metadata/stats/<collection>.json`, written
+//! after every analyze and by `save`. Statistics are advisory: increments
+//! lost in a crash are corrected by the next analyze.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::cost::{FieldStatistics, IndexStatistics};
+use crate::index::IndexKey;
+
+/// Sketch precision: 2^10 registers, about 3% standard error
+const SKETCH_PRECISION: u32 = 10;
+
+/// Number of sketch registers
+const SKETCH_REGISTERS: usize = 1 << SKETCH_PRECISION;
+
+/// Statistics configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatisticsConfig {
+    /// Mutations since the last analyze, as a percentage of the documents
+    /// counted then, after which statistics are stale (default 20)
+    #[serde(default = "default_stale_mutation_percent")]
+    pub stale_mutation_percent: u64,
+}
+
+fn default_stale_mutation_percent() -> u64 {
+    20
+}
+
+impl Default for StatisticsConfig {
+    fn default() -> Self {
+        Self {
+            stale_mutation_percent: default_stale_mutation_percent(),
+        }
+    }
+}
+
+/// HyperLogLog sketch of distinct values
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct DistinctSketch {
+    registers: Vec<u8>,
+}
+
+impl DistinctSketch {
+    fn new() -> Self {
+        Self {
+            registers: vec![0; SKETCH_REGISTERS],
+        }
+    }
+
+    fn insert(&mut self, key: &IndexKey) {
+        let hash = hash_key(key);
+        let register = (hash >> (64 - SKETCH_PRECISION)) as usize;
+        let rank = ((hash << SKETCH_PRECISION).leading_zeros() + 1).min(64 - SKETCH_PRECISION + 1);
+        self.registers[register] = self.registers[register].max(rank as u8);
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = SKETCH_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&rank| 2f64.powi(-i32::from(rank)))
+            .sum();
+        let raw = alpha * m * m / sum;
+
+        // Linear counting is more accurate for small cardinalities
+        let empty = self.registers.iter().filter(|&&rank| rank == 0).count();
+        let estimate = if raw <= 2.5 * m && empty > 0 {
+            m * (m / empty as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+/// Deterministic 64-bit hash of an index key (FNV-1a, then a finalizer so
+/// the high bits used for register selection are well mixed)
+fn hash_key(key: &IndexKey) -> u64 {
+    let (tag, bytes): (u8, Vec<u8>) = match key {
+        IndexKey::Bool(b) => (0, vec![u8::from(*b)]),
+        IndexKey::Int(i) => (1, i.to_le_bytes().to_vec()),
+        IndexKey::Float(bits) => (2, bits.to_le_bytes().to_vec()),
+        IndexKey::String(s) => (3, s.as_bytes().to_vec()),
+    };
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in std::iter::once(tag).chain(bytes) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+/// Statistics for one indexed field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSketch {
+    /// Documents with an indexable value for the field
+    pub entries: u64,
+    /// Distinct values seen
+    sketch: DistinctSketch,
+}
+
+impl FieldSketch {
+    fn new() -> Self {
+        Self {
+            entries: 0,
+            sketch: DistinctSketch::new(),
+        }
+    }
+
+    /// Estimated number of distinct values (never more than `entries`)
+    pub fn distinct_estimate(&self) -> u64 {
+        self.sketch.estimate().min(self.entries)
+    }
+}
+
+/// Statistics for one collection
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionStatistics {
+    /// Live documents
+    pub document_count: u64,
+    /// Total size of live documents in bytes
+    pub total_document_bytes: u64,
+    /// Indexed field -> statistics
+    pub fields: BTreeMap<String, FieldSketch>,
+    /// When the collection was last analyzed
+    pub analyzed_at: Option<DateTime<Utc>>,
+    /// Documents counted by the last analyze
+    pub documents_at_analyze: u64,
+    /// Inserts, updates and deletes since the last analyze
+    pub mutations_since_analyze: u64,
+}
+
+impl CollectionStatistics {
+    /// Average document size in bytes
+    pub fn average_document_size(&self) -> u64 {
+        self.total_document_bytes
+            .checked_div(self.document_count)
+            .unwrap_or(0)
+    }
+
+    /// Add a document's indexed values to the field statistics
+    fn add_values(&mut self, body: &Value, fields: &[String], count_entries: bool) {
+        for field in fields {
+            let Some(key) = body.get(field).and_then(IndexKey::from_json) else {
+                continue;
+            };
+            let stats = self
+                .fields
+                .entry(field.clone())
+                .or_insert_with(FieldSketch::new);
+            if count_entries {
+                stats.entries += 1;
+            }
+            stats.sketch.insert(&key);
+        }
+    }
+}
+
+/// Statistics store for all collections
+#[derive(Debug)]
+pub struct Statistics {
+    /// `metadata/stats` directory (None for an in-memory store)
+    dir: Option<PathBuf>,
+    config: StatisticsConfig,
+    collections: BTreeMap<String, CollectionStatistics>,
+}
+
+impl Statistics {
+    /// Create a store that is never persisted
+    pub fn in_memory(config: StatisticsConfig) -> Self {
+        Self {
+            dir: None,
+            config,
+            collections: BTreeMap::new(),
+        }
+    }
+
+    /// Open the store under `data_dir/metadata/stats`, loading any
+    /// persisted statistics. A missing directory is an empty store.
+    pub fn open(data_dir: &Path, config: StatisticsConfig) -> io::Result<Self> {
+        let dir = data_dir.join("metadata").join("stats");
+        let mut collections = BTreeMap::new();
+
+        if dir.exists() {
+            let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<io::Result<_>>()?;
+            paths.sort();
+
+            for path in paths {
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                let stats = load_collection(&path)?;
+                collections.insert(name.to_string(), stats);
+            }
+        }
+
+        Ok(Self {
+            dir: Some(dir),
+            config,
+            collections,
+        })
+    }
+
+    /// Statistics configuration
+    pub fn config(&self) -> &StatisticsConfig {
+        &self.config
+    }
+
+    /// Statistics for a collection, if any were recorded
+    pub fn collection(&self, collection: &str) -> Option<&CollectionStatistics> {
+        self.collections.get(collection)
+    }
+
+    /// All collections with statistics, in name order
+    pub fn collections(&self) -> impl Iterator<Item = (&str, &CollectionStatistics)> {
+        self.collections
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+    }
+
+    /// Whether a collection's statistics are too old to trust for
+    /// distinct-value estimates
+    pub fn is_stale(&self, stats: &CollectionStatistics) -> bool {
+        if stats.analyzed_at.is_none() {
+            return true;
+        }
+        let allowed =
+            stats.documents_at_analyze as u128 * self.config.stale_mutation_percent as u128;
+        stats.mutations_since_analyze as u128 * 100 > allowed
+    }
+
+    /// Record an inserted document of `size` bytes
+    pub fn record_insert(&mut self, collection: &str, body: &Value, size: u64, fields: &[String]) {
+        let stats = self.collections.entry(collection.to_string()).or_default();
+        stats.document_count += 1;
+        stats.total_document_bytes += size;
+        stats.mutations_since_analyze += 1;
+        stats.add_values(body, fields, true);
+    }
+
+    /// Record an updated document. Counts are unchanged; the new values are
+    /// added to the sketches.
+    pub fn record_update(&mut self, collection: &str, body: &Value, fields: &[String]) {
+        let stats = self.collections.entry(collection.to_string()).or_default();
+        stats.mutations_since_analyze += 1;
+        stats.add_values(body, fields, false);
+    }
+
+    /// Record a deleted document of `size` bytes
+    pub fn record_delete(&mut self, collection: &str, body: &Value, size: u64, fields: &[String]) {
+        let stats = self.collections.entry(collection.to_string()).or_default();
+        stats.document_count = stats.document_count.saturating_sub(1);
+        stats.total_document_bytes = stats.total_document_bytes.saturating_sub(size);
+        stats.mutations_since_analyze += 1;
+        for field in fields {
+            if body.get(field).and_then(IndexKey::from_json).is_some() {
+                if let Some(field_stats) = stats.fields.get_mut(field) {
+                    field_stats.entries = field_stats.entries.saturating_sub(1);
+                }
+            }
+        }
+    }
+
+    /// Recompute a collection's statistics from all of its live documents
+    /// (body and size in bytes) and persist them.
+    pub fn analyze<'a>(
+        &mut self,
+        collection: &str,
+        documents: impl IntoIterator<Item = (&'a Value, u64)>,
+        fields: &[String],
+    ) -> io::Result<&CollectionStatistics> {
+        let mut stats = CollectionStatistics::default();
+        for (body, size) in documents {
+            stats.document_count += 1;
+            stats.total_document_bytes += size;
+            stats.add_values(body, fields, true);
+        }
+        stats.analyzed_at = Some(Utc::now());
+        stats.documents_at_analyze = stats.document_count;
+
+        self.collections.insert(collection.to_string(), stats);
+        self.save_collection(collection)?;
+        Ok(&self.collections[collection])
+    }
+
+    /// Statistics for the planner, or None if the collection has none.
+    ///
+    /// Stale statistics supply only the document count.
+    pub fn planner_statistics(&self, collection: &str) -> Option<IndexStatistics> {
+        let stats = self.collections.get(collection)?;
+        let mut planner_stats = IndexStatistics {
+            documents: stats.document_count,
+            ..Default::default()
+        };
+
+        if !self.is_stale(stats) {
+            for (field, field_stats) in &stats.fields {
+                let field_stats = FieldStatistics {
+                    entries: field_stats.entries,
+                    distinct_keys: field_stats.distinct_estimate(),
+                };
+                planner_stats.fields.insert(field.clone(), field_stats);
+            }
+        }
+
+        Some(planner_stats)
+    }
+
+    /// Move a collection's statistics to a new name, replacing any
+    /// recorded under it, and delete the persisted old ones
+    pub fn rename(&mut self, from: &str, to: &str) -> io::Result<()> {
+        let Some(stats) = self.collections.remove(from) else {
+            return Ok(());
+        };
+        self.collections.insert(to.to_string(), stats);
+        self.save_collection(to)?;
+        self.delete_collection(from)
+    }
+
+    /// Forget a collection's statistics and delete the persisted ones
+    pub fn remove(&mut self, collection: &str) -> io::Result<()> {
+        if self.collections.remove(collection).is_none() {
+            return Ok(());
+        }
+        self.delete_collection(collection)
+    }
+
+    /// Persist every collection's statistics
+    pub fn save(&self) -> io::Result<()> {
+        for collection in self.collections.keys() {
+            self.save_collection(collection)?;
+        }
+        Ok(())
+    }
+
+    /// Delete one collection's persisted statistics
+    fn delete_collection(&self, collection: &str) -> io::Result<()> {
+        match &self.dir {
+            Some(dir) => match fs::remove_file(dir.join(format!("{}.json", collection))) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Persist one collection's statistics (write to a temp file, rename)
+    fn save_collection(&self, collection: &str) -> io::Result<()> {
+        let (Some(dir), Some(stats)) = (&self.dir, self.collections.get(collection)) else {
+            return Ok(());
+        };
+        fs::create_dir_all(dir)?;
+
+        let json =
+            serde_json::to_vec(stats).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let temp_path = dir.join(format!("{}.json.tmp", collection));
+        let final_path = dir.join(format!("{}.json", collection));
+        fs::write(&temp_path, json)?;
+        fs::rename(&temp_path, &final_path)
+    }
+}
+
+/// Load and check one persisted collection
+fn load_collection(path: &Path) -> io::Result<CollectionStatistics> {
+    let invalid = |reason: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid statistics file {}: {}", path.display(), reason),
+        )
+    };
+
+    let content = fs::read(path)?;
+    let stats: CollectionStatistics =
+        serde_json::from_slice(&content).map_err(|e| invalid(e.to_string()))?;
+    for (field, field_stats) in &stats.fields {
+        if field_stats.sketch.registers.len() != SKETCH_REGISTERS {
+            return Err(invalid(format!(
+                "sketch for '{}' has the wrong size",
+                field
+            )));
+        }
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn fields() -> Vec<String> {
+        vec!["status".to_string(), "user".to_string()]
+    }
+
+    /// `n` documents with `n / 4` distinct users and 5 statuses
+    fn documents(n: u64) -> Vec<(Value, u64)> {
+        (0..n)
+            .map(|i| {
+                let body = json!({"_id": format!("d{}", i), "user": format!("u{}", i % (n / 4)), "status": i % 5});
+                let size = serde_json::to_vec(&body).unwrap().len() as u64;
+                (body, size)
+            })
+            .collect()
+    }
+
+    fn analyzed(n: u64) -> Statistics {
+        let docs = documents(n);
+        let mut stats = Statistics::in_memory(StatisticsConfig::default());
+        stats
+            .analyze("events", docs.iter().map(|(b, s)| (b, *s)), &fields())
+            .unwrap();
+        stats
+    }
+
+    fn within(estimate: u64, actual: u64, percent: u64) -> bool {
+        estimate.abs_diff(actual) * 100 <= actual * percent
+    }
+
+    #[test]
+    fn test_analyze_estimates_track_reality() {
+        for n in [40, 2_000, 20_000] {
+            let docs = documents(n);
+            let total: u64 = docs.iter().map(|(_, size)| size).sum();
+            let stats = analyzed(n);
+            let collection = stats.collection("events").unwrap();
+
+            assert_eq!(collection.document_count, n);
+            assert_eq!(collection.average_document_size(), total / n);
+            assert_eq!(collection.fields["status"].entries, n);
+            assert_eq!(collection.fields["status"].distinct_estimate(), 5);
+
+            let users = collection.fields["user"].distinct_estimate();
+            assert!(
+                within(users, n / 4, 5),
+                "n={}: estimated {} users",
+                n,
+                users
+            );
+        }
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let mut stats = analyzed(40);
+        let body = json!({"_id": "new", "user": "someone-new", "status": 9});
+
+        stats.record_insert("events", &body, 100, &fields());
+        let collection = stats.collection("events").unwrap();
+        assert_eq!(collection.document_count, 41);
+        assert_eq!(collection.fields["status"].entries, 41);
+        assert_eq!(collection.fields["status"].distinct_estimate(), 6);
+        assert_eq!(collection.mutations_since_analyze, 1);
+
+        stats.record_delete("events", &body, 100, &fields());
+        let collection = stats.collection("events").unwrap();
+        assert_eq!(collection.document_count, 40);
+        assert_eq!(collection.fields["status"].entries, 40);
+        assert_eq!(collection.mutations_since_analyze, 2);
+
+        // Values without an index key are not counted
+        stats.record_insert(
+            "events",
+            &json!({"_id": "x", "status": null}),
+            10,
+            &fields(),
+        );
+        assert_eq!(
+            stats.collection("events").unwrap().fields["status"].entries,
+            40
+        );
+    }
+
+    #[test]
+    fn test_inserts_grow_distinct_estimates() {
+        let mut stats = Statistics::in_memory(StatisticsConfig::default());
+        for (i, (body, size)) in documents(8_000).iter().enumerate() {
+            stats.record_insert("events", body, *size, &fields());
+
+            let seen = i as u64 + 1;
+            if seen.is_multiple_of(500) {
+                let collection = stats.collection("events").unwrap();
+                let users = collection.fields["user"].distinct_estimate();
+                // Users cycle every 2000 documents
+                assert!(
+                    within(users, seen.min(2_000), 5),
+                    "{} inserts: estimated {} users",
+                    seen,
+                    users
+                );
+                assert_eq!(collection.document_count, seen);
+            }
+        }
+    }
+
+    #[test]
+    fn test_staleness_threshold() {
+        let config = StatisticsConfig {
+            stale_mutation_percent: 10,
+        };
+        let docs = documents(40);
+        let mut stats = Statistics::in_memory(config);
+        stats
+            .analyze("events", docs.iter().map(|(b, s)| (b, *s)), &fields())
+            .unwrap();
+        let fresh = stats.planner_statistics("events").unwrap();
+        assert_eq!(fresh.fields["status"].distinct_keys, 5);
+
+        // 4 mutations = 10% of 40: still fresh
+        for i in 0..4 {
+            stats.record_update("events", &json!({"_id": format!("d{}", i)}), &fields());
+        }
+        assert!(!stats.is_stale(stats.collection("events").unwrap()));
+
+        // One more: stale, so only the document count is used
+        stats.record_update("events", &json!({"_id": "d4"}), &fields());
+        assert!(stats.is_stale(stats.collection("events").unwrap()));
+        let stale = stats.planner_statistics("events").unwrap();
+        assert_eq!(stale.documents, 40);
+        assert!(stale.fields.is_empty());
+
+        // Never analyzed: stale; no statistics at all: none
+        let mut fresh_store = Statistics::in_memory(StatisticsConfig::default());
+        fresh_store.record_insert("other", &json!({"status": 1}), 10, &fields());
+        assert!(fresh_store.is_stale(fresh_store.collection("other").unwrap()));
+        assert!(fresh_store.planner_statistics("missing").is_none());
+    }
+
+    #[test]
+    fn test_persisted_and_reloaded() {
+        let temp_dir = TempDir::new().unwrap();
+        let docs = documents(40);
+
+        let mut stats = Statistics::open(temp_dir.path(), StatisticsConfig::default()).unwrap();
+        assert!(stats.collection("events").is_none());
+        stats
+            .analyze("events", docs.iter().map(|(b, s)| (b, *s)), &fields())
+            .unwrap();
+        assert!(temp_dir.path().join("metadata/stats/events.json").exists());
+
+        // Increments are persisted by save
+        stats.record_insert("events", &json!({"status": 7}), 50, &fields());
+        stats.save().unwrap();
+
+        let reloaded = Statistics::open(temp_dir.path(), StatisticsConfig::default()).unwrap();
+        assert_eq!(reloaded.collection("events"), stats.collection("events"));
+    }
+
+    #[test]
+    fn test_rename_moves_persisted_statistics() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut stats = Statistics::open(temp_dir.path(), StatisticsConfig::default()).unwrap();
+        stats.record_insert("users", &json!({"status": 1}), 10, &fields());
+        stats.save().unwrap();
+
+        stats.rename("users", "members").unwrap();
+        assert!(stats.collection("users").is_none());
+        assert_eq!(stats.collection("members").unwrap().document_count, 1);
+
+        let reopened = Statistics::open(temp_dir.path(), StatisticsConfig::default()).unwrap();
+        assert!(reopened.collection("users").is_none());
+        assert_eq!(reopened.collection("members").unwrap().document_count, 1);
+    }
+
+    #[test]
+    fn test_corrupt_statistics_file_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("metadata").join("stats");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("events.json"), "not json").unwrap();
+
+        let err = Statistics::open(temp_dir.path(), StatisticsConfig::default()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("events.json"));
+    }
+}
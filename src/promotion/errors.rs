@@ -39,6 +39,9 @@ pub enum PromotionErrorKind {
 
     /// Promotion denied by validator
     PromotionDenied,
+
+    /// Replica has not applied everything the primary acknowledged
+    ReplicaNotCaughtUp,
 }
 
 impl PromotionError {
@@ -71,6 +74,11 @@ impl PromotionError {
         )
     }
 
+    /// Create a replica not caught up error.
+    pub fn replica_not_caught_up(detail: impl Into<String>) -> Self {
+        Self::new(PromotionErrorKind::ReplicaNotCaughtUp, detail)
+    }
+
     /// Create a promotion already in progress error.
     pub fn promotion_already_in_progress() -> Self {
         Self::new(
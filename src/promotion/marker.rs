@@ -39,6 +39,11 @@ pub struct AuthorityMarker {
 
     /// Previous authority (for audit trail)
     pub previous_state: String,
+
+    /// WAL epoch the new primary writes in (0 for markers written before
+    /// epoch fencing existed)
+    #[serde(default)]
+    pub epoch: u64,
 }
 
 impl AuthorityMarker {
@@ -51,9 +56,16 @@ impl AuthorityMarker {
                 .map(|d| d.as_secs() as i64)
                 .unwrap_or(0),
             previous_state: previous_state.to_string(),
+            epoch: 0,
         }
     }
 
+    /// Record the WAL epoch the new primary writes in.
+    pub fn with_epoch(mut self, epoch: u64) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
     /// Get the primary ID as UUID.
     pub fn get_primary_id(&self) -> Option<Uuid> {
         Uuid::parse_str(&self.new_primary_id).ok()
@@ -260,6 +272,20 @@ mod tests {
         assert_eq!(read_marker.unwrap().get_primary_id(), Some(id));
     }
 
+    #[test]
+    fn test_marker_epoch_defaults_for_old_markers() {
+        let tmp = TempDir::new().unwrap();
+        let dm = DurableMarker::new(tmp.path());
+
+        let marker = AuthorityMarker::new(test_uuid(), "ReplicaActive").with_epoch(4);
+        dm.write_atomic(&marker).unwrap();
+        assert_eq!(dm.read().unwrap().unwrap().epoch, 4);
+
+        let old = r#"{"new_primary_id":"x","timestamp_secs":0,"previous_state":"ReplicaActive"}"#;
+        let parsed: AuthorityMarker = serde_json::from_str(old).unwrap();
+        assert_eq!(parsed.epoch, 0);
+    }
+
     #[test]
     fn test_marker_absent_returns_none() {
         let tmp = TempDir::new().unwrap();
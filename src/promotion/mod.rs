@@ -26,6 +26,7 @@ mod errors;
 mod integration;
 mod marker;
 mod observability;
+mod preconditions;
 mod request;
 mod state;
 mod transition;
@@ -38,7 +39,12 @@ pub use marker::{AuthorityMarker, DurableMarker};
 pub use observability::{
     InvariantCheck, PromotionEvent, PromotionExplanation, PromotionObserver, PromotionOutcome,
 };
+pub use preconditions::{
+    CatchUpReference, MissingRange, PromotionPreconditions, ACCEPT_DATA_LOSS,
+};
 pub use request::{PromotionRequest, PromotionRequestResult};
 pub use state::{DenialReason, PromotionState};
-pub use transition::{AuthorityTransitionManager, TransitionFailureReason, TransitionResult};
+pub use transition::{
+    AuthorityTransitionManager, FencedPromotion, TransitionFailureReason, TransitionResult,
+};
 pub use validator::{PromotionValidator, ValidationContext, ValidationResult};
@@ -0,0 +1,285 @@
+//! Promotion Preconditions
+//!
+//! Per PHASE6_INVARIANTS.md §P6-S1:
+//! Promotion MUST NOT lose writes the primary acknowledged.
+//!
+//! Before authority moves, the replica's applied WAL position is compared
+//! against a catch-up reference:
+//! - The last position the primary acknowledged, when the primary answers
+//! - Otherwise, the highest position any reachable replica reports
+//!
+//! Any gap refuses normal promotion with the missing byte ranges listed.
+//! Only a forced promotion whose acknowledged risks include
+//! [`ACCEPT_DATA_LOSS`] may proceed past a gap.
+
+use super::errors::{PromotionError, PromotionResult};
+use crate::replication::WalPosition;
+use std::fmt;
+use uuid::Uuid;
+
+/// Risk token a forced promotion must acknowledge to discard missing WAL.
+pub const ACCEPT_DATA_LOSS: &str = "accept_data_loss";
+
+/// The position a promoted replica must have applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatchUpReference {
+    /// Last WAL position the primary acknowledged.
+    PrimaryAcknowledged(WalPosition),
+
+    /// Primary is unreachable: positions reported by reachable replicas.
+    ReachableReplicas(Vec<(Uuid, WalPosition)>),
+}
+
+/// A span of WAL the replica has not applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingRange {
+    /// First missing byte (the replica's applied offset)
+    pub from_offset: u64,
+
+    /// End of the missing bytes, exclusive
+    pub to_offset: u64,
+
+    /// First missing record sequence
+    pub first_sequence: u64,
+
+    /// Last missing record sequence
+    pub last_sequence: u64,
+
+    /// Node that has the missing records
+    pub source: String,
+}
+
+impl MissingRange {
+    /// Number of missing bytes.
+    pub fn len(&self) -> u64 {
+        self.to_offset - self.from_offset
+    }
+
+    /// Whether the range covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for MissingRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bytes {}..{} ({} bytes, sequences {}..={}) held by {}",
+            self.from_offset,
+            self.to_offset,
+            self.len(),
+            self.first_sequence,
+            self.last_sequence,
+            self.source
+        )
+    }
+}
+
+/// Catch-up check for a promotion candidate.
+///
+/// Like the validator, the check is deterministic and side-effect free.
+#[derive(Debug, Clone)]
+pub struct PromotionPreconditions {
+    /// Replica being promoted
+    pub replica_id: Uuid,
+
+    /// Last WAL position the replica applied
+    pub applied: WalPosition,
+
+    /// Position the replica must have reached
+    pub reference: CatchUpReference,
+}
+
+impl PromotionPreconditions {
+    /// Create a precondition check for `replica_id`.
+    pub fn new(replica_id: Uuid, applied: WalPosition, reference: CatchUpReference) -> Self {
+        Self {
+            replica_id,
+            applied,
+            reference,
+        }
+    }
+
+    /// WAL the replica is missing, one range per node that is ahead of it.
+    pub fn missing_ranges(&self) -> Vec<MissingRange> {
+        let ahead = |source: String, position: &WalPosition| {
+            (position.offset > self.applied.offset).then(|| MissingRange {
+                from_offset: self.applied.offset,
+                to_offset: position.offset,
+                first_sequence: self.applied.sequence + 1,
+                last_sequence: position.sequence.max(self.applied.sequence + 1),
+                source,
+            })
+        };
+
+        match &self.reference {
+            CatchUpReference::PrimaryAcknowledged(position) => {
+                ahead("primary".to_string(), position).into_iter().collect()
+            }
+            CatchUpReference::ReachableReplicas(replicas) => replicas
+                .iter()
+                .filter(|(id, _)| *id != self.replica_id)
+                .filter_map(|(id, position)| ahead(format!("replica {}", id), position))
+                .collect(),
+        }
+    }
+
+    /// Check whether promotion may proceed.
+    ///
+    /// `acknowledged_risks` is `None` for a normal promotion and the
+    /// operator's acknowledged risk tokens for a forced one. Returns the
+    /// ranges a forced promotion discards (empty when caught up).
+    ///
+    /// # Errors
+    ///
+    /// `ReplicaNotCaughtUp` if the replica is missing WAL, or if the
+    /// primary is unreachable and no other replica reports a position,
+    /// unless the risks include [`ACCEPT_DATA_LOSS`].
+    pub fn check(
+        &self,
+        acknowledged_risks: Option<&[String]>,
+    ) -> PromotionResult<Vec<MissingRange>> {
+        let accepts_loss = acknowledged_risks
+            .is_some_and(|risks| risks.iter().any(|risk| risk.trim() == ACCEPT_DATA_LOSS));
+        let ranges = self.missing_ranges();
+
+        if accepts_loss {
+            return Ok(ranges);
+        }
+
+        if self.has_no_witness() {
+            return Err(PromotionError::replica_not_caught_up(format!(
+                "replica {} applied through sequence {} (offset {}), but the primary is \
+                 unreachable and no other replica reported a position; cannot prove it is \
+                 caught up. Force promotion acknowledging '{}' to proceed",
+                self.replica_id, self.applied.sequence, self.applied.offset, ACCEPT_DATA_LOSS
+            )));
+        }
+
+        if ranges.is_empty() {
+            return Ok(ranges);
+        }
+
+        let listed: Vec<String> = ranges.iter().map(|r| format!("  - {}", r)).collect();
+        let hint = if acknowledged_risks.is_some() {
+            format!(
+                "forced promotion must acknowledge '{}' to discard them",
+                ACCEPT_DATA_LOSS
+            )
+        } else {
+            format!(
+                "wait for the replica to catch up, or force promotion acknowledging '{}'",
+                ACCEPT_DATA_LOSS
+            )
+        };
+        Err(PromotionError::replica_not_caught_up(format!(
+            "replica {} applied through sequence {} (offset {}) and is missing WAL:\n{}\n{}",
+            self.replica_id,
+            self.applied.sequence,
+            self.applied.offset,
+            listed.join("\n"),
+            hint
+        )))
+    }
+
+    /// Whether nothing but the candidate itself reports a position.
+    fn has_no_witness(&self) -> bool {
+        match &self.reference {
+            CatchUpReference::PrimaryAcknowledged(_) => false,
+            CatchUpReference::ReachableReplicas(replicas) => {
+                replicas.iter().all(|(id, _)| *id == self.replica_id)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::promotion::PromotionErrorKind;
+
+    fn risks(tokens: &[&str]) -> Vec<String> {
+        tokens.iter().map(|t| t.to_string()).collect()
+    }
+
+    fn lagging_replica() -> PromotionPreconditions {
+        PromotionPreconditions::new(
+            Uuid::new_v4(),
+            WalPosition::new(10, 4096),
+            CatchUpReference::PrimaryAcknowledged(WalPosition::new(14, 5120)),
+        )
+    }
+
+    #[test]
+    fn test_caught_up_replica_passes() {
+        let check = PromotionPreconditions::new(
+            Uuid::new_v4(),
+            WalPosition::new(14, 5120),
+            CatchUpReference::PrimaryAcknowledged(WalPosition::new(14, 5120)),
+        );
+        assert!(check.check(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lagging_replica_is_refused_with_ranges() {
+        let err = lagging_replica().check(None).unwrap_err();
+        assert_eq!(err.kind, PromotionErrorKind::ReplicaNotCaughtUp);
+        assert!(err
+            .message
+            .contains("bytes 4096..5120 (1024 bytes, sequences 11..=14)"));
+        assert!(err.message.contains("held by primary"));
+        assert!(err.message.contains(ACCEPT_DATA_LOSS));
+    }
+
+    #[test]
+    fn test_force_requires_accept_data_loss() {
+        let check = lagging_replica();
+
+        let err = check.check(Some(&risks(&["split_brain"]))).unwrap_err();
+        assert_eq!(err.kind, PromotionErrorKind::ReplicaNotCaughtUp);
+        assert!(err.message.contains("forced promotion must acknowledge"));
+
+        let lost = check
+            .check(Some(&risks(&["split_brain", ACCEPT_DATA_LOSS])))
+            .unwrap();
+        assert_eq!(lost.len(), 1);
+        assert_eq!((lost[0].from_offset, lost[0].to_offset), (4096, 5120));
+    }
+
+    #[test]
+    fn test_unreachable_primary_uses_replica_reports() {
+        let candidate = Uuid::new_v4();
+        let ahead = Uuid::new_v4();
+        let check = PromotionPreconditions::new(
+            candidate,
+            WalPosition::new(10, 4096),
+            CatchUpReference::ReachableReplicas(vec![
+                (candidate, WalPosition::new(10, 4096)),
+                (Uuid::new_v4(), WalPosition::new(9, 3900)),
+                (ahead, WalPosition::new(12, 4600)),
+            ]),
+        );
+
+        let ranges = check.missing_ranges();
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].source, format!("replica {}", ahead));
+
+        let err = check.check(None).unwrap_err();
+        assert!(err.message.contains("bytes 4096..4600"));
+    }
+
+    #[test]
+    fn test_no_witness_is_refused_unless_forced() {
+        let candidate = Uuid::new_v4();
+        let check = PromotionPreconditions::new(
+            candidate,
+            WalPosition::new(10, 4096),
+            CatchUpReference::ReachableReplicas(vec![(candidate, WalPosition::new(10, 4096))]),
+        );
+
+        let err = check.check(None).unwrap_err();
+        assert!(err.message.contains("no other replica reported a position"));
+        assert!(check.check(Some(&risks(&[ACCEPT_DATA_LOSS]))).is_ok());
+    }
+}
@@ -16,10 +16,16 @@
 //!
 //! Per PHASE6_ARCHITECTURE.md §4.2 (amended):
 //! Uses fsynced marker file for durable authority transition.
+//!
+//! A fenced transition also starts a new WAL epoch on the promoted node.
+//! Replicas reject records from a lower epoch, so a former primary that
+//! is still running cannot get its writes applied.
 
 use super::errors::{PromotionError, PromotionErrorKind, PromotionResult};
 use super::marker::{AuthorityMarker, DurableMarker};
+use super::preconditions::{MissingRange, PromotionPreconditions};
 use crate::replication::ReplicationState;
+use crate::wal::WalWriter;
 use std::path::Path;
 use uuid::Uuid;
 
@@ -61,6 +67,19 @@ pub enum TransitionFailureReason {
     MarkerWriteFailed,
 }
 
+/// Outcome of [`AuthorityTransitionManager::promote`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FencedPromotion {
+    /// ID of the new primary
+    pub new_primary_id: Uuid,
+
+    /// WAL epoch the new primary writes in
+    pub epoch: u64,
+
+    /// WAL discarded by a forced promotion (empty when caught up)
+    pub discarded: Vec<MissingRange>,
+}
+
 /// Authority Transition Manager
 ///
 /// Per PHASE6_ARCHITECTURE.md §3.3:
@@ -165,6 +184,71 @@ impl AuthorityTransitionManager {
     ///
     /// Returns the new ReplicationState for the promoted replica.
     pub fn apply_transition(&mut self) -> PromotionResult<ReplicationState> {
+        self.commit_marker(AuthorityMarker::new)?;
+        Ok(ReplicationState::PrimaryActive)
+    }
+
+    /// Apply the authority transition and fence the previous primary.
+    ///
+    /// The marker records the next WAL epoch before the WAL starts it, so
+    /// a crash in between is finished by [`Self::recover_fence`].
+    pub fn apply_fenced_transition(
+        &mut self,
+        wal: &mut WalWriter,
+    ) -> PromotionResult<ReplicationState> {
+        let epoch = wal.epoch() + 1;
+        self.commit_marker(|id, previous| AuthorityMarker::new(id, previous).with_epoch(epoch))?;
+        start_epoch(wal, epoch)?;
+        Ok(ReplicationState::PrimaryActive)
+    }
+
+    /// Promote a replica after checking it is caught up.
+    ///
+    /// Runs the full lifecycle: precondition check, begin, fenced apply
+    /// and complete. `acknowledged_risks` is `None` for a normal promotion
+    /// and the operator's risk tokens for a forced one. Nothing changes
+    /// if the check refuses.
+    pub fn promote(
+        &mut self,
+        current_state: &ReplicationState,
+        preconditions: &PromotionPreconditions,
+        acknowledged_risks: Option<&[String]>,
+        wal: &mut WalWriter,
+    ) -> PromotionResult<FencedPromotion> {
+        let discarded = preconditions.check(acknowledged_risks)?;
+
+        self.begin_transition(preconditions.replica_id, current_state)?;
+        if let Err(e) = self.apply_fenced_transition(wal) {
+            // Before the marker is written the attempt can be abandoned;
+            // after it, recovery finishes the fence.
+            let _ = self.abort_transition();
+            return Err(e);
+        }
+        let new_primary_id = self.complete_transition()?;
+
+        Ok(FencedPromotion {
+            new_primary_id,
+            epoch: wal.epoch(),
+            discarded,
+        })
+    }
+
+    /// Finish fencing after a crash during a fenced transition.
+    ///
+    /// Starts the epoch recorded in a committed marker if the WAL has not
+    /// reached it yet. Returns the WAL's epoch.
+    pub fn recover_fence(&self, wal: &mut WalWriter) -> PromotionResult<u64> {
+        if let Some(marker) = self.durable_marker.read()? {
+            start_epoch(wal, marker.epoch)?;
+        }
+        Ok(wal.epoch())
+    }
+
+    /// Write the durable marker for the transition in progress.
+    fn commit_marker(
+        &mut self,
+        make_marker: impl FnOnce(Uuid, &str) -> AuthorityMarker,
+    ) -> PromotionResult<()> {
         if !self.transition_in_progress {
             return Err(PromotionError::new(
                 PromotionErrorKind::AuthorityTransitionFailed,
@@ -181,17 +265,13 @@ impl AuthorityTransitionManager {
 
         // Write durable marker - CRITICAL per P6-F2
         // This is the point of no return
-        let marker = AuthorityMarker::new(replica_id, "ReplicaActive");
+        let marker = make_marker(replica_id, "ReplicaActive");
         self.durable_marker.write_atomic(&marker)?;
 
-        // Authority rebinding complete
-        // The replica is now PrimaryActive
-        let new_state = ReplicationState::PrimaryActive;
-
-        // Clear in-memory transition state
+        // Authority rebinding complete; clear in-memory transition state
         self.transition_in_progress = false;
 
-        Ok(new_state)
+        Ok(())
     }
 
     /// Complete the transition and return the new primary ID.
@@ -263,6 +343,16 @@ impl AuthorityTransitionManager {
     }
 }
 
+/// Start `epoch` in the WAL, mapping failure to a transition error.
+fn start_epoch(wal: &mut WalWriter, epoch: u64) -> PromotionResult<()> {
+    wal.begin_epoch(epoch).map_err(|e| {
+        PromotionError::new(
+            PromotionErrorKind::AuthorityTransitionFailed,
+            format!("failed to start WAL epoch {}: {}", epoch, e),
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -446,4 +536,74 @@ mod tests {
         assert!(result1.0); // Both should see committed
         assert_eq!(result1.1, Some(replica_id));
     }
+
+    fn lagging_replica(replica_id: Uuid) -> PromotionPreconditions {
+        use super::super::preconditions::CatchUpReference;
+        use crate::replication::WalPosition;
+
+        PromotionPreconditions::new(
+            replica_id,
+            WalPosition::new(3, 300),
+            CatchUpReference::PrimaryAcknowledged(WalPosition::new(5, 500)),
+        )
+    }
+
+    #[test]
+    fn test_promote_refuses_lagging_replica() {
+        let (tmp, mut manager) = make_manager();
+        let replica_id = test_uuid();
+        let state = ReplicationState::ReplicaActive { replica_id };
+        let mut wal = WalWriter::open(tmp.path()).unwrap();
+
+        let err = manager
+            .promote(&state, &lagging_replica(replica_id), None, &mut wal)
+            .unwrap_err();
+        assert_eq!(err.kind, PromotionErrorKind::ReplicaNotCaughtUp);
+
+        // Nothing changed: no transition, no marker, old epoch
+        assert!(!manager.is_transition_in_progress());
+        assert!(!manager.has_durable_marker());
+        assert_eq!(wal.epoch(), 0);
+    }
+
+    #[test]
+    fn test_forced_promote_fences_with_new_epoch() {
+        use super::super::preconditions::ACCEPT_DATA_LOSS;
+
+        let (tmp, mut manager) = make_manager();
+        let replica_id = test_uuid();
+        let state = ReplicationState::ReplicaActive { replica_id };
+        let mut wal = WalWriter::open(tmp.path()).unwrap();
+        let risks = vec![ACCEPT_DATA_LOSS.to_string()];
+
+        let promoted = manager
+            .promote(&state, &lagging_replica(replica_id), Some(&risks), &mut wal)
+            .unwrap();
+        assert_eq!(promoted.new_primary_id, replica_id);
+        assert_eq!(promoted.epoch, 1);
+        assert_eq!(promoted.discarded.len(), 1);
+        assert!(!manager.has_durable_marker());
+
+        drop(wal);
+        assert_eq!(WalWriter::open(tmp.path()).unwrap().epoch(), 1);
+    }
+
+    #[test]
+    fn test_recover_fence_after_crash() {
+        let (tmp, mut manager) = make_manager();
+        let replica_id = test_uuid();
+        let state = ReplicationState::ReplicaActive { replica_id };
+
+        // Crash after the marker committed but before the WAL epoch began
+        manager.begin_transition(replica_id, &state).unwrap();
+        manager
+            .commit_marker(|id, previous| AuthorityMarker::new(id, previous).with_epoch(1))
+            .unwrap();
+
+        let mut wal = WalWriter::open(tmp.path()).unwrap();
+        assert_eq!(wal.epoch(), 0);
+        let recovered = AuthorityTransitionManager::new(tmp.path());
+        assert_eq!(recovered.recover_fence(&mut wal).unwrap(), 1);
+        assert_eq!(recovered.recover_fence(&mut wal).unwrap(), 1);
+    }
 }
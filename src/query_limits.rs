@@ -3,24 +3,77 @@
 //! HARDENING: Enforce limits on individual query execution.
 //!
 //! - Max result set size
-//! - Query timeout
+//! - Memory for in-memory sorts (`max_sort_bytes`)
+//! - Query timeout: `max_execution_ms` by default, overridable per request
+//!   with `timeout_ms` up to `max_timeout_ms`
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct QueryLimitsConfig {
     /// Max documents in result set
     pub max_result_set_docs: usize,
-    
-    /// Default query timeout in ms
-    pub query_timeout_ms: u64,
+
+    /// Default request timeout in ms
+    #[serde(alias = "query_timeout_ms")]
+    pub max_execution_ms: u64,
+
+    /// Largest timeout a request may ask for, in ms
+    pub max_timeout_ms: u64,
+
+    /// Memory an in-memory sort may hold, in bytes
+    pub max_sort_bytes: u64,
 }
 
 impl Default for QueryLimitsConfig {
     fn default() -> Self {
         Self {
             max_result_set_docs: 10000,
-            query_timeout_ms: 30000, // 30s
+            max_execution_ms: 30000, // 30s
+            max_timeout_ms: 300000,  // 5min
+            max_sort_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+impl QueryLimitsConfig {
+    /// Timeout for a request: its own `timeout_ms` if given, otherwise the
+    /// configured default.
+    ///
+    /// Rejects a requested timeout of 0 or above `max_timeout_ms`.
+    pub fn resolve_timeout_ms(&self, requested: Option<u64>) -> Result<u64, String> {
+        match requested {
+            None => Ok(self.max_execution_ms),
+            Some(0) => Err("timeout_ms must be greater than 0".to_string()),
+            Some(ms) if ms > self.max_timeout_ms => Err(format!(
+                "timeout_ms {} exceeds maximum {}",
+                ms, self.max_timeout_ms
+            )),
+            Some(ms) => Ok(ms),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_timeout() {
+        let limits = QueryLimitsConfig::default();
+        assert_eq!(limits.resolve_timeout_ms(None), Ok(30000));
+        assert_eq!(limits.resolve_timeout_ms(Some(10)), Ok(10));
+        assert_eq!(limits.resolve_timeout_ms(Some(300000)), Ok(300000));
+        assert!(limits.resolve_timeout_ms(Some(300001)).is_err());
+        assert!(limits.resolve_timeout_ms(Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_legacy_timeout_key() {
+        let limits: QueryLimitsConfig =
+            serde_json::from_str(r#"{"query_timeout_ms": 5000}"#).unwrap();
+        assert_eq!(limits.max_execution_ms, 5000);
+        assert_eq!(limits.max_result_set_docs, 10000);
+    }
+}
@@ -27,6 +27,8 @@ use std::collections::VecDeque;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
+use crate::observability::{JsonLogger, Logger};
+
 /// Backpressure configuration
 ///
 /// MANIFESTO ALIGNMENT: Configuration is explicit, no hidden defaults.
@@ -176,6 +178,7 @@ pub struct BackpressureChannel<T> {
     config: BackpressureConfig,
     buffer: Arc<RwLock<VecDeque<T>>>,
     counters: Arc<BackpressureCounters>,
+    logger: Arc<dyn Logger>,
 }
 
 impl<T> BackpressureChannel<T> {
@@ -187,9 +190,16 @@ impl<T> BackpressureChannel<T> {
             ))),
             config,
             counters: Arc::new(BackpressureCounters::default()),
+            logger: JsonLogger::shared(),
         }
     }
 
+    /// Send drop and reject events to `logger`
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
     /// Get reference to the counters
     pub fn counters(&self) -> &BackpressureCounters {
         &self.counters
@@ -286,18 +296,25 @@ impl<T> BackpressureChannel<T> {
     /// Log drop event
     fn log_drop_event(&self, policy: &str) {
         let snapshot = self.counters.snapshot();
-        eprintln!(
-            "{{\"level\":\"WARN\",\"event\":\"BACKPRESSURE_DROP\",\"policy\":\"{}\",\"dropped\":{},\"buffer_size\":{}}}",
-            policy, snapshot.dropped, self.config.max_pending_messages
+        self.logger.warn(
+            "BACKPRESSURE_DROP",
+            &[
+                ("policy", policy),
+                ("dropped", &snapshot.dropped.to_string()),
+                ("buffer_size", &self.config.max_pending_messages.to_string()),
+            ],
         );
     }
 
     /// Log reject event
     fn log_reject_event(&self) {
         let snapshot = self.counters.snapshot();
-        eprintln!(
-            "{{\"level\":\"WARN\",\"event\":\"BACKPRESSURE_REJECT\",\"rejected\":{},\"buffer_size\":{}}}",
-            snapshot.rejected, self.config.max_pending_messages
+        self.logger.warn(
+            "BACKPRESSURE_REJECT",
+            &[
+                ("rejected", &snapshot.rejected.to_string()),
+                ("buffer_size", &self.config.max_pending_messages.to_string()),
+            ],
         );
     }
 }
@@ -308,6 +325,7 @@ impl<T: Clone> Clone for BackpressureChannel<T> {
             config: self.config.clone(),
             buffer: Arc::clone(&self.buffer),
             counters: Arc::clone(&self.counters),
+            logger: Arc::clone(&self.logger),
         }
     }
 }
@@ -315,6 +333,7 @@ impl<T: Clone> Clone for BackpressureChannel<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::observability::{Severity, VecLogger};
 
     #[test]
     fn test_config_defaults() {
@@ -443,6 +462,41 @@ mod tests {
         assert_eq!(snapshot.rejected, 1);
     }
 
+    #[test]
+    fn test_channel_logs_drops_and_rejects() {
+        let logger = Arc::new(VecLogger::new());
+        let config = BackpressureConfig {
+            max_pending_messages: 1,
+            drop_policy: DropPolicy::NewestFirst,
+        };
+        let channel: BackpressureChannel<i32> =
+            BackpressureChannel::new(config).with_logger(logger.clone());
+        channel.send(1).unwrap();
+        channel.send(2).unwrap();
+        channel.send(3).unwrap();
+
+        let records = logger.records();
+        assert_eq!(logger.events(), vec!["BACKPRESSURE_DROP"; 2]);
+        assert_eq!(records[1].severity, Severity::Warn);
+        assert_eq!(records[1].field("policy"), Some("newest_first"));
+        assert_eq!(records[1].field("dropped"), Some("2"));
+        assert_eq!(records[1].field("buffer_size"), Some("1"));
+
+        let logger = Arc::new(VecLogger::new());
+        let config = BackpressureConfig {
+            max_pending_messages: 1,
+            drop_policy: DropPolicy::Reject,
+        };
+        let channel: BackpressureChannel<i32> =
+            BackpressureChannel::new(config).with_logger(logger.clone());
+        channel.send(1).unwrap();
+        assert!(channel.clone().send(2).is_err());
+
+        let records = logger.records();
+        assert_eq!(logger.events(), vec!["BACKPRESSURE_REJECT"]);
+        assert_eq!(records[0].field("rejected"), Some("1"));
+    }
+
     #[test]
     fn test_channel_recv() {
         let config = BackpressureConfig::default();
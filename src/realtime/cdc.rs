@@ -0,0 +1,609 @@
+//! # WAL Change Data Capture
+//!
+//! Realtime events derived by tailing the WAL instead of being emitted by
+//! the write path, so every committed change is observed: by replicas and
+//! external consumers reading the same WAL, and across a crash between
+//! storage apply and event delivery.
+//!
+//! ## Invariant: RT-C1
+//! The stream position is stored durably in `data_dir/system/cdc_offset`
+//! after each delivered batch, and a restarted stream resumes from it. No
+//! record after the stored position is skipped and no record before it is
+//! delivered again. Events are numbered from the stored position, so a
+//! batch redelivered after a crash carries the same sequence numbers.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::dispatcher::Dispatcher;
+use super::errors::{RealtimeError, RealtimeResult};
+use super::event::DatabaseEvent;
+use super::event_log::EventLog;
+use super::hub::RealtimeHub;
+use crate::schema::is_soft_deleted;
+use crate::wal::{
+    detect_layout, parse_segment_file_name, RecordType, WalError, WalLayout, WalReader, WalRecord,
+};
+
+/// Receives events decoded from the WAL, in WAL order
+pub trait ChangeSink {
+    /// Deliver one event
+    fn deliver(&self, event: &DatabaseEvent);
+}
+
+impl ChangeSink for Dispatcher {
+    fn deliver(&self, event: &DatabaseEvent) {
+        self.dispatch(event);
+    }
+}
+
+impl ChangeSink for RealtimeHub {
+    fn deliver(&self, event: &DatabaseEvent) {
+        self.publish(event);
+    }
+}
+
+impl ChangeSink for EventLog {
+    fn deliver(&self, event: &DatabaseEvent) {
+        self.record(event.clone());
+    }
+}
+
+/// Change stream configuration
+#[derive(Debug, Clone)]
+pub struct ChangeStreamConfig {
+    /// Records read per poll; the position is stored after each poll
+    pub batch_size: usize,
+
+    /// Wait between polls once the stream has caught up with the WAL
+    pub poll_interval: Duration,
+}
+
+impl Default for ChangeStreamConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 1024,
+            poll_interval: Duration::from_millis(50),
+        }
+    }
+}
+
+/// Position of a change stream in the WAL
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeOffset {
+    /// Segment holding the last record read (0 for a legacy `wal.log`)
+    pub segment: u64,
+
+    /// Byte offset just past the last record read
+    pub offset: u64,
+
+    /// Sequence number of the last record read (0 before the first)
+    pub wal_sequence: u64,
+
+    /// Sequence number of the last event delivered (0 before the first)
+    pub event_sequence: u64,
+}
+
+/// Path of the stored change stream position
+pub fn cdc_offset_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("system").join("cdc_offset")
+}
+
+/// Tails the WAL of a data directory and feeds decoded changes to a sink
+#[derive(Debug)]
+pub struct ChangeStream {
+    /// WAL directory being followed
+    wal_dir: PathBuf,
+
+    /// Where the position is stored
+    offset_path: PathBuf,
+
+    /// Position after the last delivered batch
+    position: ChangeOffset,
+
+    /// Configuration
+    config: ChangeStreamConfig,
+}
+
+impl ChangeStream {
+    /// Open the change stream of a data directory, resuming from the stored
+    /// position if there is one
+    pub fn open(data_dir: impl AsRef<Path>, config: ChangeStreamConfig) -> RealtimeResult<Self> {
+        let data_dir = data_dir.as_ref();
+        let offset_path = cdc_offset_path(data_dir);
+        let position = match fs::read_to_string(&offset_path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|e| {
+                RealtimeError::ChangeStream(format!(
+                    "Invalid position in {}: {}",
+                    offset_path.display(),
+                    e
+                ))
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ChangeOffset::default(),
+            Err(e) => {
+                return Err(RealtimeError::ChangeStream(format!(
+                    "Failed to read {}: {}",
+                    offset_path.display(),
+                    e
+                )))
+            }
+        };
+
+        Ok(Self {
+            wal_dir: data_dir.join("wal"),
+            offset_path,
+            position,
+            config,
+        })
+    }
+
+    /// Position after the last delivered batch
+    pub fn position(&self) -> ChangeOffset {
+        self.position
+    }
+
+    /// Deliver up to one batch of new changes, then store the position.
+    ///
+    /// Returns the number of events delivered. If reading the WAL fails
+    /// part-way, the position of the events already delivered is stored
+    /// before the error is returned.
+    pub fn poll<S: ChangeSink + ?Sized>(&mut self, sink: &S) -> RealtimeResult<usize> {
+        let Some(mut reader) = self.open_reader()? else {
+            return Ok(0);
+        };
+
+        let mut position = self.position;
+        let mut delivered = 0;
+        let mut result = Ok(());
+        for _ in 0..self.config.batch_size {
+            let record = match reader.read_next() {
+                Ok(Some(record)) => record,
+                Ok(None) => break,
+                Err(e) => {
+                    result = Err(wal_error(e));
+                    break;
+                }
+            };
+            position.segment = segment_of(reader.path());
+            position.offset = reader.current_offset();
+            position.wal_sequence = record.sequence_number;
+
+            if let Some(event) = change_event(&record, position.event_sequence + 1) {
+                sink.deliver(&event);
+                position.event_sequence = event.sequence;
+                delivered += 1;
+            }
+        }
+
+        if position != self.position {
+            self.store_position(&position)?;
+            self.position = position;
+        }
+        result.map(|()| delivered)
+    }
+
+    /// Poll until `stop` is set, waiting `poll_interval` whenever the
+    /// stream has caught up. Blocks the calling thread.
+    pub fn run<S: ChangeSink + ?Sized>(
+        mut self,
+        sink: &S,
+        stop: &AtomicBool,
+    ) -> RealtimeResult<()> {
+        while !stop.load(Ordering::Acquire) {
+            if self.poll(sink)? == 0 {
+                std::thread::sleep(self.config.poll_interval);
+            }
+        }
+        Ok(())
+    }
+
+    /// Open a reader positioned just after the last record read.
+    ///
+    /// If a checkpoint removed the segment holding the position, reading
+    /// starts at the next segment, whose first record must continue the
+    /// stored sequence (or restart it at 1 after a WAL reset).
+    fn open_reader(&self) -> RealtimeResult<Option<WalReader>> {
+        let (path, files) = match detect_layout(&self.wal_dir).map_err(wal_error)? {
+            WalLayout::Empty => return Ok(None),
+            WalLayout::Legacy(path) => {
+                if self.position.segment != 0 {
+                    return Err(RealtimeError::ChangeStream(format!(
+                        "Stored position is in segment {} but the WAL is a single file",
+                        self.position.segment
+                    )));
+                }
+                (path.clone(), vec![path])
+            }
+            // A legacy `wal.log` is renamed to the first segment, so
+            // segment 0 continues at the same offset there
+            WalLayout::Segmented(segments) => {
+                let files: Vec<PathBuf> = segments
+                    .into_iter()
+                    .filter(|s| s.index >= self.position.segment)
+                    .map(|s| s.path)
+                    .collect();
+                let Some(first) = files.first().cloned() else {
+                    return Err(RealtimeError::ChangeStream(format!(
+                        "WAL segments at or after {} are missing",
+                        self.position.segment
+                    )));
+                };
+                (first, files)
+            }
+        };
+
+        let at_position = self.position.segment == 0 || segment_of(&path) == self.position.segment;
+        let offset = if !at_position {
+            0
+        } else if file_len(&path)? < self.position.offset {
+            // A checkpoint reset the legacy WAL file
+            0
+        } else {
+            self.position.offset
+        };
+
+        let mut reader = WalReader::open_history(files).map_err(wal_error)?;
+        reader
+            .seek_to(offset, self.position.wal_sequence)
+            .map_err(wal_error)?;
+        Ok(Some(reader))
+    }
+
+    /// Durably replace the stored position
+    fn store_position(&self, position: &ChangeOffset) -> RealtimeResult<()> {
+        let dir = self
+            .offset_path
+            .parent()
+            .expect("offset path has a parent directory");
+        let temp_path = self.offset_path.with_extension("tmp");
+        let json =
+            serde_json::to_vec(position).map_err(|e| RealtimeError::ChangeStream(e.to_string()))?;
+
+        fs::create_dir_all(dir)
+            .and_then(|()| {
+                let mut file = File::create(&temp_path)?;
+                file.write_all(&json)?;
+                file.sync_all()
+            })
+            .and_then(|()| fs::rename(&temp_path, &self.offset_path))
+            .and_then(|()| File::open(dir)?.sync_all())
+            .map_err(|e| {
+                RealtimeError::ChangeStream(format!(
+                    "Failed to store position in {}: {}",
+                    self.offset_path.display(),
+                    e
+                ))
+            })
+    }
+}
+
+/// Decode a WAL record into a realtime event numbered `sequence`.
+///
+/// Returns `None` for MVCC records; a collection rename or copy is decoded
+/// as SCHEMA_CHANGE, its `record_id` the source collection. The WAL holds
+/// post-images only, so `old_data` is never set and a DELETE carries no row,
+/// except for the two records that carry the removed document: an update
+/// setting `_deleted_at` (a soft delete, decoded as DELETE) and a purge's
+/// tombstone (decoded as PURGE).
+pub fn change_event(record: &WalRecord, sequence: u64) -> Option<DatabaseEvent> {
+    let payload = &record.payload;
+    let collection = payload.collection_id.clone();
+    let record_id = payload.document_id.clone();
+    let body = || serde_json::from_slice(&payload.document_body).unwrap_or(Value::Null);
+
+    let mut event = match record.record_type {
+        RecordType::Insert => DatabaseEvent::insert(sequence, collection, record_id, body(), None),
+        RecordType::Update => {
+            let body = body();
+            if is_soft_deleted(&body) {
+                DatabaseEvent::delete(sequence, collection, record_id, body, None)
+            } else {
+                let mut event =
+                    DatabaseEvent::update(sequence, collection, record_id, Value::Null, body, None);
+                event.old_data = None;
+                event
+            }
+        }
+        RecordType::Delete if payload.is_purge() => {
+            DatabaseEvent::purge(sequence, collection, record_id, body(), None)
+        }
+        RecordType::Delete => {
+            let mut event =
+                DatabaseEvent::delete(sequence, collection, record_id, Value::Null, None);
+            event.old_data = None;
+            event
+        }
+        RecordType::CollectionRename | RecordType::CollectionCopy => {
+            let (from, to) = record.collection_change()?;
+            let change = if record.record_type == RecordType::CollectionRename {
+                "rename"
+            } else {
+                "copy"
+            };
+            let change = serde_json::json!({"change": change, "from": from, "to": to});
+            DatabaseEvent::schema_change(sequence, collection, from.to_string(), change)
+        }
+        RecordType::MvccCommit | RecordType::MvccVersion | RecordType::MvccGc => return None,
+    };
+    if let Some(timestamp) = record
+        .commit_timestamp_ms
+        .and_then(|ms| DateTime::from_timestamp_millis(ms as i64))
+    {
+        event.timestamp = timestamp;
+    }
+    Some(event)
+}
+
+/// Segment index of a WAL file, 0 for a legacy `wal.log`
+fn segment_of(path: &Path) -> u64 {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(parse_segment_file_name)
+        .unwrap_or(0)
+}
+
+fn file_len(path: &Path) -> RealtimeResult<u64> {
+    fs::metadata(path).map(|m| m.len()).map_err(|e| {
+        RealtimeError::ChangeStream(format!("Failed to read {}: {}", path.display(), e))
+    })
+}
+
+fn wal_error(e: WalError) -> RealtimeError {
+    RealtimeError::ChangeStream(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::remove_covered_segments;
+    use crate::realtime::EventType;
+    use crate::wal::{WalPayload, WalSegmentConfig, WalSyncConfig, WalWriter};
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    #[derive(Default)]
+    struct Collect(Mutex<Vec<DatabaseEvent>>);
+
+    impl ChangeSink for Collect {
+        fn deliver(&self, event: &DatabaseEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    impl Collect {
+        fn take(&self) -> Vec<(u64, String)> {
+            self.0
+                .lock()
+                .unwrap()
+                .drain(..)
+                .map(|e| (e.sequence, e.record_id))
+                .collect()
+        }
+    }
+
+    fn payload(doc_id: &str, body: &str) -> WalPayload {
+        WalPayload::new(
+            "messages",
+            doc_id,
+            "messages",
+            "v1",
+            body.as_bytes().to_vec(),
+        )
+    }
+
+    fn insert(wal: &mut WalWriter, doc_id: &str) {
+        wal.append_insert(payload(doc_id, r#"{"room": "a"}"#))
+            .unwrap();
+    }
+
+    fn batch(size: usize) -> ChangeStreamConfig {
+        ChangeStreamConfig {
+            batch_size: size,
+            ..ChangeStreamConfig::default()
+        }
+    }
+
+    fn ids(range: std::ops::RangeInclusive<u64>) -> Vec<(u64, String)> {
+        range.map(|i| (i, format!("m{}", i))).collect()
+    }
+
+    #[test]
+    fn test_decodes_typed_change_events() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open(temp.path()).unwrap();
+        wal.append_insert(payload("m1", r#"{"room": "a"}"#))
+            .unwrap();
+        wal.append_update(payload("m1", r#"{"room": "b"}"#))
+            .unwrap();
+        wal.append_delete(payload("m1", "")).unwrap();
+
+        let sink = Collect::default();
+        let mut stream = ChangeStream::open(temp.path(), batch(10)).unwrap();
+        assert_eq!(stream.poll(&sink).unwrap(), 3);
+
+        let events = sink.0.lock().unwrap();
+        let types: Vec<EventType> = events.iter().map(|e| e.event_type).collect();
+        assert_eq!(
+            types,
+            vec![EventType::Insert, EventType::Update, EventType::Delete]
+        );
+        assert!(events.iter().all(|e| e.collection == "messages"));
+        assert!(events.iter().all(|e| e.record_id == "m1"));
+        assert_eq!(events[1].new_data, Some(serde_json::json!({"room": "b"})));
+        assert_eq!(events[1].old_data, None);
+        assert_eq!(events[2].row(), None);
+    }
+
+    #[test]
+    fn test_decodes_soft_delete_and_purge() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open(temp.path()).unwrap();
+        let deleted = r#"{"room": "a", "_deleted_at": "2026-01-01T00:00:00Z"}"#;
+        wal.append_update(payload("m1", deleted)).unwrap();
+        wal.append_delete(WalPayload::purge(
+            "messages",
+            "m1",
+            "messages",
+            deleted.as_bytes().to_vec(),
+        ))
+        .unwrap();
+
+        let sink = Collect::default();
+        let mut stream = ChangeStream::open(temp.path(), batch(10)).unwrap();
+        assert_eq!(stream.poll(&sink).unwrap(), 2);
+
+        let events = sink.0.lock().unwrap();
+        assert_eq!(events[0].event_type, EventType::Delete);
+        assert_eq!(events[0].new_data, None);
+        assert_eq!(events[0].row().unwrap()["room"], "a");
+        assert_eq!(events[1].event_type, EventType::Purge);
+        assert_eq!(events[1].row().unwrap()["room"], "a");
+    }
+
+    #[test]
+    fn test_decodes_collection_change_as_schema_change() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open(temp.path()).unwrap();
+        insert(&mut wal, "m1");
+        wal.append(
+            RecordType::CollectionRename,
+            WalPayload::collection_change("messages", "messages", "chats"),
+        )
+        .unwrap();
+        wal.append(
+            RecordType::CollectionCopy,
+            WalPayload::collection_change("messages", "chats", "archive"),
+        )
+        .unwrap();
+
+        let sink = Collect::default();
+        let mut stream = ChangeStream::open(temp.path(), batch(10)).unwrap();
+        assert_eq!(stream.poll(&sink).unwrap(), 3);
+
+        let events = sink.0.lock().unwrap();
+        assert_eq!(events[1].event_type, EventType::SchemaChange);
+        assert_eq!(events[1].record_id, "messages");
+        assert_eq!(
+            events[1].new_data,
+            Some(serde_json::json!({"change": "rename", "from": "messages", "to": "chats"}))
+        );
+        assert_eq!(events[2].event_type, EventType::SchemaChange);
+        assert_eq!(events[2].new_data.as_ref().unwrap()["change"], "copy");
+        assert_eq!(events[2].row(), None);
+    }
+
+    #[test]
+    fn test_restart_mid_flow_delivers_exactly_once() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open(temp.path()).unwrap();
+        for i in 1..=5 {
+            insert(&mut wal, &format!("m{}", i));
+        }
+
+        let sink = Collect::default();
+        let mut stream = ChangeStream::open(temp.path(), batch(3)).unwrap();
+        assert_eq!(stream.poll(&sink).unwrap(), 3);
+        let stored = stream.position();
+        drop(stream);
+
+        // Killed after storing the first batch; writes continue meanwhile
+        for i in 6..=7 {
+            insert(&mut wal, &format!("m{}", i));
+        }
+
+        let mut stream = ChangeStream::open(temp.path(), batch(3)).unwrap();
+        assert_eq!(stream.position(), stored);
+        while stream.poll(&sink).unwrap() > 0 {}
+        assert_eq!(sink.take(), ids(1..=7));
+        assert_eq!(stream.position().wal_sequence, 7);
+    }
+
+    #[test]
+    fn test_crash_before_storing_redelivers_same_sequences() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open(temp.path()).unwrap();
+        for i in 1..=4 {
+            insert(&mut wal, &format!("m{}", i));
+        }
+        let offset_path = cdc_offset_path(temp.path());
+
+        let sink = Collect::default();
+        let mut stream = ChangeStream::open(temp.path(), batch(2)).unwrap();
+        stream.poll(&sink).unwrap();
+        let stored = fs::read(&offset_path).unwrap();
+        stream.poll(&sink).unwrap();
+        assert_eq!(sink.take(), ids(1..=4));
+
+        // Crash after delivering the second batch but before storing it
+        fs::write(&offset_path, stored).unwrap();
+        let mut stream = ChangeStream::open(temp.path(), batch(10)).unwrap();
+        stream.poll(&sink).unwrap();
+        assert_eq!(sink.take(), ids(3..=4));
+    }
+
+    #[test]
+    fn test_follows_segments_across_checkpoint() {
+        let temp = TempDir::new().unwrap();
+        let open = || {
+            WalWriter::open_segmented(
+                temp.path(),
+                WalSyncConfig::default(),
+                WalSegmentConfig::new(1),
+            )
+            .unwrap()
+        };
+        let mut wal = open();
+        for i in 1..=3 {
+            insert(&mut wal, &format!("m{}", i));
+        }
+
+        let sink = Collect::default();
+        let mut stream = ChangeStream::open(temp.path(), batch(10)).unwrap();
+        stream.poll(&sink).unwrap();
+        assert_eq!(stream.position().segment, 3);
+
+        // A full checkpoint removes every segment and restarts at 1
+        remove_covered_segments(&mut wal).unwrap();
+        wal.truncate().unwrap();
+        insert(&mut wal, "m4");
+        insert(&mut wal, "m5");
+
+        let mut stream = ChangeStream::open(temp.path(), batch(10)).unwrap();
+        stream.poll(&sink).unwrap();
+        assert_eq!(sink.take(), ids(1..=5));
+        assert_eq!(stream.position().wal_sequence, 2);
+    }
+
+    #[test]
+    fn test_missing_records_are_not_skipped() {
+        let temp = TempDir::new().unwrap();
+        let mut wal = WalWriter::open_segmented(
+            temp.path(),
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(1),
+        )
+        .unwrap();
+        insert(&mut wal, "m1");
+
+        let sink = Collect::default();
+        let mut stream = ChangeStream::open(temp.path(), batch(10)).unwrap();
+        stream.poll(&sink).unwrap();
+
+        // Segment 2 is lost before the stream read it
+        insert(&mut wal, "m2");
+        insert(&mut wal, "m3");
+        drop(wal);
+        fs::remove_file(temp.path().join("wal").join("0000001.log")).unwrap();
+        fs::remove_file(temp.path().join("wal").join("0000002.log")).unwrap();
+
+        assert!(stream.poll(&sink).is_err());
+        assert_eq!(stream.position().wal_sequence, 1);
+    }
+}
@@ -209,43 +209,58 @@ impl Dispatcher {
         policy: &Option<RlsPolicy>,
         event: &DatabaseEvent,
     ) -> bool {
-        // Service role bypasses RLS
-        if context.can_bypass_rls() {
-            return true;
-        }
+        rls_allows(context, policy.as_ref(), event)
+    }
 
-        let Some(policy) = policy else {
-            return true; // No policy = allow
-        };
+    /// Get connection count
+    pub fn connection_count(&self) -> usize {
+        self.connections.read().map(|c| c.len()).unwrap_or(0)
+    }
+}
+
+/// Check if an event passes a collection's RLS policy for a given context
+pub(crate) fn rls_allows(
+    context: &RlsContext,
+    policy: Option<&RlsPolicy>,
+    event: &DatabaseEvent,
+) -> bool {
+    // Service role bypasses RLS
+    if context.can_bypass_rls() {
+        return true;
+    }
 
-        match policy {
-            RlsPolicy::None => true,
-            RlsPolicy::Ownership { owner_field } => {
-                // Check if user owns the record
-                let data = event.new_data.as_ref().or(event.old_data.as_ref());
-                if let Some(data) = data {
-                    if let Some(owner_id) = data.get(owner_field).and_then(|v| v.as_str()) {
-                        if let Some(user_id) = &context.user_id {
-                            return owner_id == user_id.to_string();
-                        }
+    let Some(policy) = policy else {
+        return true; // No policy = allow
+    };
+
+    match policy {
+        RlsPolicy::None => true,
+        RlsPolicy::Ownership { owner_field } => {
+            // Check if user owns the record
+            if let Some(data) = event.row() {
+                if let Some(owner_id) = data.get(owner_field).and_then(|v| v.as_str()) {
+                    if let Some(user_id) = &context.user_id {
+                        return owner_id == user_id.to_string();
                     }
                 }
-                false
             }
-            RlsPolicy::PublicRead { .. } => {
-                // Allow read for everyone
-                true
-            }
-            RlsPolicy::Custom { .. } => {
-                // Custom policies not yet supported
-                false
+            false
+        }
+        RlsPolicy::PublicRead { .. } => {
+            // Allow read for everyone
+            true
+        }
+        RlsPolicy::Claim { field, claim } => {
+            // Row field must equal the subscriber's claim
+            match (event.row(), context.claims.get(claim)) {
+                (Some(data), Some(expected)) => data.get(field) == Some(expected),
+                _ => false,
             }
         }
-    }
-
-    /// Get connection count
-    pub fn connection_count(&self) -> usize {
-        self.connections.read().map(|c| c.len()).unwrap_or(0)
+        RlsPolicy::Custom { .. } => {
+            // Custom policies not yet supported
+            false
+        }
     }
 }
 
@@ -282,6 +297,7 @@ pub struct DispatchResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::realtime::subscription::SubscriptionFilter;
     use serde_json::json;
     use uuid::Uuid;
 
@@ -333,6 +349,94 @@ mod tests {
         assert_eq!(received.collection, "posts");
     }
 
+    #[tokio::test]
+    async fn test_predicates_partition_events() {
+        let registry = Arc::new(SubscriptionRegistry::new());
+        let dispatcher = Dispatcher::new(Arc::clone(&registry));
+
+        let mut receivers = Vec::new();
+        for (conn, room) in [("conn-1", "abc"), ("conn-2", "xyz")] {
+            receivers.push(dispatcher.connect(conn.to_string(), RlsContext::anonymous()));
+            let predicate = json!({"room_id": {"eq": room}});
+            let sub = Subscription::new(
+                conn.to_string(),
+                "messages".to_string(),
+                RlsContext::anonymous(),
+            )
+            .with_filters(SubscriptionFilter::compile(&predicate).unwrap());
+            registry.subscribe(sub).unwrap();
+        }
+
+        for (seq, room) in [(1, "abc"), (2, "xyz"), (3, "abc"), (4, "other")] {
+            let event = DatabaseEvent::insert(
+                seq,
+                "messages".to_string(),
+                seq.to_string(),
+                json!({"room_id": room}),
+                None,
+            );
+            dispatcher.dispatch(&event);
+        }
+
+        let mut received = Vec::new();
+        for rx in &mut receivers {
+            let mut sequences = Vec::new();
+            while let Ok(event) = rx.try_recv() {
+                sequences.push(event.sequence);
+            }
+            received.push(sequences);
+        }
+        assert_eq!(received, vec![vec![1, 3], vec![2]]);
+    }
+
+    #[tokio::test]
+    async fn test_rls_restricted_subscriber_sees_only_own_rows() {
+        let registry = Arc::new(SubscriptionRegistry::new());
+        let dispatcher = Dispatcher::new(Arc::clone(&registry));
+        dispatcher.register_rls_policy(
+            "messages",
+            RlsPolicy::Ownership {
+                owner_field: "owner_id".to_string(),
+            },
+        );
+
+        let user_id = Uuid::new_v4();
+        let other_user = Uuid::new_v4();
+        let context = RlsContext::authenticated(user_id);
+        let mut rx = dispatcher.connect("conn-1".to_string(), context.clone());
+
+        // The predicate matches every row; RLS still hides other users' rows
+        let predicate = json!({"room_id": {"eq": "abc"}});
+        let sub = Subscription::new("conn-1".to_string(), "messages".to_string(), context)
+            .with_filters(SubscriptionFilter::compile(&predicate).unwrap());
+        registry.subscribe(sub).unwrap();
+
+        let row = |owner: Uuid| json!({"room_id": "abc", "owner_id": owner.to_string()});
+        let events = [
+            DatabaseEvent::insert(1, "messages".into(), "1".into(), row(other_user), None),
+            DatabaseEvent::update(
+                2,
+                "messages".into(),
+                "1".into(),
+                row(other_user),
+                row(other_user),
+                None,
+            ),
+            DatabaseEvent::delete(3, "messages".into(), "1".into(), row(other_user), None),
+        ];
+        for event in &events {
+            let result = dispatcher.dispatch(event);
+            assert_eq!(result.matched, 1);
+            assert_eq!(result.filtered, 1);
+            assert_eq!(result.delivered, 0);
+        }
+        assert!(rx.try_recv().is_err());
+
+        let own = DatabaseEvent::insert(4, "messages".into(), "2".into(), row(user_id), None);
+        assert_eq!(dispatcher.dispatch(&own).delivered, 1);
+        assert_eq!(rx.try_recv().unwrap().sequence, 4);
+    }
+
     #[tokio::test]
     async fn test_rls_filtering() {
         let registry = Arc::new(SubscriptionRegistry::new());
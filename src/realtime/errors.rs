@@ -40,6 +40,14 @@ pub enum RealtimeError {
     #[error("Too many subscriptions (max: {0})")]
     TooManySubscriptions(usize),
 
+    /// Invalid subscription filter or event type
+    #[error("Invalid subscription filter: {0}")]
+    InvalidFilter(String),
+
+    /// Subscription cannot be resumed from the requested sequence
+    #[error("Cannot resume subscription: {0}")]
+    ResumeUnavailable(String),
+
     // ==================
     // Authorization Errors
     // ==================
@@ -91,6 +99,10 @@ pub enum RealtimeError {
     /// Authentication error
     #[error("Authentication error: {0}")]
     AuthError(String),
+
+    /// WAL change stream cannot continue without skipping changes
+    #[error("Change stream error: {0}")]
+    ChangeStream(String),
 }
 
 impl RealtimeError {
@@ -103,6 +115,8 @@ impl RealtimeError {
             RealtimeError::InvalidTopic(_) => 4000,
             RealtimeError::SubscriptionNotFound(_) => 4001,
             RealtimeError::TooManySubscriptions(_) => 4002,
+            RealtimeError::InvalidFilter(_) => 4005,
+            RealtimeError::ResumeUnavailable(_) => 4006,
             RealtimeError::Unauthorized => 4003,
             RealtimeError::AuthenticationRequired => 4004,
             RealtimeError::ChannelNotFound(_) => 4010,
@@ -113,6 +127,7 @@ impl RealtimeError {
             RealtimeError::ConfigError(_) => 4501,
             RealtimeError::ConnectionError(_) => 4502,
             RealtimeError::AuthError(_) => 4003,
+            RealtimeError::ChangeStream(_) => 4503,
         }
     }
 }
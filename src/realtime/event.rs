@@ -8,15 +8,35 @@ use serde_json::Value;
 use uuid::Uuid;
 
 /// Type of database event
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum EventType {
     /// New record inserted
     Insert,
     /// Existing record updated
     Update,
-    /// Record deleted
+    /// Record deleted (soft-deleted, in a `soft_delete` collection)
     Delete,
+    /// Record removed for good by a purge
+    Purge,
+    /// Collection renamed or copied
+    #[serde(rename = "SCHEMA_CHANGE")]
+    SchemaChange,
+}
+
+impl EventType {
+    /// Parse an event type name (`insert`, `update`, `delete`, `purge` or
+    /// `schema_change`, any case)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "insert" => Some(EventType::Insert),
+            "update" => Some(EventType::Update),
+            "delete" => Some(EventType::Delete),
+            "purge" => Some(EventType::Purge),
+            "schema_change" => Some(EventType::SchemaChange),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for EventType {
@@ -25,6 +45,8 @@ impl std::fmt::Display for EventType {
             EventType::Insert => write!(f, "INSERT"),
             EventType::Update => write!(f, "UPDATE"),
             EventType::Delete => write!(f, "DELETE"),
+            EventType::Purge => write!(f, "PURGE"),
+            EventType::SchemaChange => write!(f, "SCHEMA_CHANGE"),
         }
     }
 }
@@ -133,11 +155,51 @@ impl DatabaseEvent {
         }
     }
 
+    /// Create a PURGE event
+    pub fn purge(
+        sequence: u64,
+        collection: String,
+        record_id: String,
+        data: Value,
+        user_id: Option<Uuid>,
+    ) -> Self {
+        Self {
+            event_type: EventType::Purge,
+            ..Self::delete(sequence, collection, record_id, data, user_id)
+        }
+    }
+
+    /// Create a SCHEMA_CHANGE event; `change` describes the rename or copy
+    /// and `record_id` is the source collection
+    pub fn schema_change(sequence: u64, collection: String, from: String, change: Value) -> Self {
+        Self {
+            sequence,
+            event_type: EventType::SchemaChange,
+            collection,
+            schema: default_schema(),
+            record_id: from,
+            new_data: Some(change),
+            old_data: None,
+            timestamp: Utc::now(),
+            user_id: None,
+        }
+    }
+
     /// Get the topic string for this event
     pub fn topic(&self) -> String {
         format!("realtime:{}:{}", self.schema, self.collection)
     }
 
+    /// The record filters and RLS are evaluated against: new values for
+    /// INSERT/UPDATE, old values for DELETE/PURGE, none for SCHEMA_CHANGE
+    pub fn row(&self) -> Option<&Value> {
+        match self.event_type {
+            EventType::Insert | EventType::Update => self.new_data.as_ref(),
+            EventType::Delete | EventType::Purge => self.old_data.as_ref(),
+            EventType::SchemaChange => None,
+        }
+    }
+
     /// Serialize to Supabase-compatible format
     pub fn to_wire_format(&self) -> Value {
         serde_json::json!({
@@ -286,6 +348,30 @@ mod tests {
         assert!(event.old_data.is_some());
     }
 
+    #[test]
+    fn test_schema_change_event() {
+        let change = serde_json::json!({"change": "rename", "from": "posts", "to": "articles"});
+        let event = DatabaseEvent::schema_change(
+            4,
+            "default".to_string(),
+            "posts".to_string(),
+            change.clone(),
+        );
+
+        assert_eq!(event.event_type, EventType::SchemaChange);
+        assert_eq!(event.new_data, Some(change));
+        assert!(event.row().is_none());
+        assert_eq!(
+            EventType::parse("schema_change"),
+            Some(EventType::SchemaChange)
+        );
+        assert_eq!(
+            serde_json::to_value(EventType::SchemaChange).unwrap(),
+            "SCHEMA_CHANGE"
+        );
+        assert_eq!(event.to_wire_format()["payload"]["event"], "SCHEMA_CHANGE");
+    }
+
     #[test]
     fn test_event_topic() {
         let event = DatabaseEvent::insert(
@@ -1,6 +1,6 @@
 //! # Event Log
 //!
-//! Deterministic transformation from WAL to events.
+//! Ring buffer of recent events, fed by the WAL change stream (`cdc`).
 //!
 //! ## Invariant: RT-E1
 //! Same WAL → Same events. Event generation is reproducible.
@@ -9,10 +9,7 @@ use std::collections::VecDeque;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 
-use serde_json::Value;
-use uuid::Uuid;
-
-use super::event::{DatabaseEvent, EventType};
+use super::event::DatabaseEvent;
 
 /// Configuration for the event log
 #[derive(Debug, Clone)]
@@ -27,7 +24,7 @@ impl Default for EventLogConfig {
     }
 }
 
-/// Event log that transforms WAL entries into events
+/// Event log of events decoded from the WAL
 ///
 /// This is the deterministic layer - same WAL produces same events.
 #[derive(Debug)]
@@ -35,7 +32,7 @@ pub struct EventLog {
     /// Configuration
     config: EventLogConfig,
 
-    /// Next sequence number (monotonically increasing)
+    /// Sequence number expected for the next event (monotonically increasing)
     next_sequence: AtomicU64,
 
     /// Ring buffer of recent events
@@ -64,60 +61,27 @@ impl EventLog {
         self.next_sequence.load(Ordering::Acquire)
     }
 
-    /// Record an INSERT event from WAL
-    pub fn record_insert(
-        &self,
-        collection: String,
-        record_id: String,
-        data: Value,
-        user_id: Option<Uuid>,
-    ) -> DatabaseEvent {
-        let sequence = self.next_sequence.fetch_add(1, Ordering::SeqCst);
-        let event = DatabaseEvent::insert(sequence, collection, record_id, data, user_id);
-        self.append(event.clone());
-        event
-    }
-
-    /// Record an UPDATE event from WAL
-    pub fn record_update(
-        &self,
-        collection: String,
-        record_id: String,
-        old_data: Value,
-        new_data: Value,
-        user_id: Option<Uuid>,
-    ) -> DatabaseEvent {
-        let sequence = self.next_sequence.fetch_add(1, Ordering::SeqCst);
-        let event =
-            DatabaseEvent::update(sequence, collection, record_id, old_data, new_data, user_id);
-        self.append(event.clone());
-        event
-    }
-
-    /// Record a DELETE event from WAL
-    pub fn record_delete(
-        &self,
-        collection: String,
-        record_id: String,
-        data: Value,
-        user_id: Option<Uuid>,
-    ) -> DatabaseEvent {
-        let sequence = self.next_sequence.fetch_add(1, Ordering::SeqCst);
-        let event = DatabaseEvent::delete(sequence, collection, record_id, data, user_id);
-        self.append(event.clone());
-        event
-    }
-
-    /// Append event to the ring buffer
-    fn append(&self, event: DatabaseEvent) {
-        if let Ok(mut events) = self.events.write() {
-            events.push_back(event);
+    /// Record an event decoded from the WAL by the change stream.
+    ///
+    /// Events carry the stream's sequence numbers. An event at or below the
+    /// last recorded sequence was redelivered after a stream restart and is
+    /// ignored. Returns whether the event was recorded.
+    pub fn record(&self, event: DatabaseEvent) -> bool {
+        let Ok(mut events) = self.events.write() else {
+            return false;
+        };
+        if event.sequence < self.next_sequence.load(Ordering::Acquire) {
+            return false;
+        }
+        self.next_sequence
+            .store(event.sequence + 1, Ordering::Release);
+        events.push_back(event);
 
-            // Trim if over capacity
-            while events.len() > self.config.max_events {
-                events.pop_front();
-            }
+        // Trim if over capacity
+        while events.len() > self.config.max_events {
+            events.pop_front();
         }
+        true
     }
 
     /// Get events since a given sequence number
@@ -173,29 +137,37 @@ impl EventLog {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::realtime::EventType;
+    use serde_json::Value;
+
+    fn insert(log: &EventLog, collection: &str, id: &str, data: Value) -> DatabaseEvent {
+        let sequence = log.next_sequence();
+        let event = DatabaseEvent::insert(sequence, collection.into(), id.into(), data, None);
+        log.record(event.clone());
+        event
+    }
+
+    fn update(log: &EventLog, collection: &str, id: &str, old: Value, new: Value) -> DatabaseEvent {
+        let sequence = log.next_sequence();
+        let event = DatabaseEvent::update(sequence, collection.into(), id.into(), old, new, None);
+        log.record(event.clone());
+        event
+    }
+
+    fn delete(log: &EventLog, collection: &str, id: &str, data: Value) -> DatabaseEvent {
+        let sequence = log.next_sequence();
+        let event = DatabaseEvent::delete(sequence, collection.into(), id.into(), data, None);
+        log.record(event.clone());
+        event
+    }
 
     #[test]
     fn test_sequence_numbers_increment() {
         let log = EventLog::default();
 
-        let e1 = log.record_insert(
-            "posts".to_string(),
-            "1".to_string(),
-            serde_json::json!({}),
-            None,
-        );
-        let e2 = log.record_insert(
-            "posts".to_string(),
-            "2".to_string(),
-            serde_json::json!({}),
-            None,
-        );
-        let e3 = log.record_insert(
-            "posts".to_string(),
-            "3".to_string(),
-            serde_json::json!({}),
-            None,
-        );
+        let e1 = insert(&log, "posts", "1", serde_json::json!({}));
+        let e2 = insert(&log, "posts", "2", serde_json::json!({}));
+        let e3 = insert(&log, "posts", "3", serde_json::json!({}));
 
         assert_eq!(e1.sequence, 1);
         assert_eq!(e2.sequence, 2);
@@ -207,24 +179,9 @@ mod tests {
     fn test_events_since() {
         let log = EventLog::default();
 
-        log.record_insert(
-            "posts".to_string(),
-            "1".to_string(),
-            serde_json::json!({}),
-            None,
-        );
-        log.record_insert(
-            "posts".to_string(),
-            "2".to_string(),
-            serde_json::json!({}),
-            None,
-        );
-        log.record_insert(
-            "posts".to_string(),
-            "3".to_string(),
-            serde_json::json!({}),
-            None,
-        );
+        insert(&log, "posts", "1", serde_json::json!({}));
+        insert(&log, "posts", "2", serde_json::json!({}));
+        insert(&log, "posts", "3", serde_json::json!({}));
 
         let events = log.events_since(1);
         assert_eq!(events.len(), 2);
@@ -236,24 +193,9 @@ mod tests {
     fn test_events_for_collection() {
         let log = EventLog::default();
 
-        log.record_insert(
-            "posts".to_string(),
-            "1".to_string(),
-            serde_json::json!({}),
-            None,
-        );
-        log.record_insert(
-            "comments".to_string(),
-            "1".to_string(),
-            serde_json::json!({}),
-            None,
-        );
-        log.record_insert(
-            "posts".to_string(),
-            "2".to_string(),
-            serde_json::json!({}),
-            None,
-        );
+        insert(&log, "posts", "1", serde_json::json!({}));
+        insert(&log, "comments", "1", serde_json::json!({}));
+        insert(&log, "posts", "2", serde_json::json!({}));
 
         let events = log.events_for_collection("posts", 0);
         assert_eq!(events.len(), 2);
@@ -265,12 +207,7 @@ mod tests {
         let log = EventLog::new(EventLogConfig { max_events: 5 });
 
         for i in 0..10 {
-            log.record_insert(
-                "posts".to_string(),
-                i.to_string(),
-                serde_json::json!({}),
-                None,
-            );
+            insert(&log, "posts", &i.to_string(), serde_json::json!({}));
         }
 
         assert_eq!(log.len(), 5);
@@ -285,25 +222,15 @@ mod tests {
     fn test_event_types() {
         let log = EventLog::default();
 
-        let insert = log.record_insert(
-            "posts".to_string(),
-            "1".to_string(),
-            serde_json::json!({"a": 1}),
-            None,
-        );
-        let update = log.record_update(
-            "posts".to_string(),
-            "1".to_string(),
+        let insert = insert(&log, "posts", "1", serde_json::json!({"a": 1}));
+        let update = update(
+            &log,
+            "posts",
+            "1",
             serde_json::json!({"a": 1}),
             serde_json::json!({"a": 2}),
-            None,
-        );
-        let delete = log.record_delete(
-            "posts".to_string(),
-            "1".to_string(),
-            serde_json::json!({"a": 2}),
-            None,
         );
+        let delete = delete(&log, "posts", "1", serde_json::json!({"a": 2}));
 
         assert_eq!(insert.event_type, EventType::Insert);
         assert_eq!(update.event_type, EventType::Update);
@@ -318,32 +245,22 @@ mod tests {
         let log2 = EventLog::default();
 
         // Same operations on both logs
-        log1.record_insert(
-            "posts".to_string(),
-            "1".to_string(),
-            serde_json::json!({"title": "Hello"}),
-            None,
-        );
-        log1.record_update(
-            "posts".to_string(),
-            "1".to_string(),
+        insert(&log1, "posts", "1", serde_json::json!({"title": "Hello"}));
+        update(
+            &log1,
+            "posts",
+            "1",
             serde_json::json!({"title": "Hello"}),
             serde_json::json!({"title": "World"}),
-            None,
         );
 
-        log2.record_insert(
-            "posts".to_string(),
-            "1".to_string(),
-            serde_json::json!({"title": "Hello"}),
-            None,
-        );
-        log2.record_update(
-            "posts".to_string(),
-            "1".to_string(),
+        insert(&log2, "posts", "1", serde_json::json!({"title": "Hello"}));
+        update(
+            &log2,
+            "posts",
+            "1",
             serde_json::json!({"title": "Hello"}),
             serde_json::json!({"title": "World"}),
-            None,
         );
 
         let events1 = log1.events_since(0);
@@ -359,4 +276,15 @@ mod tests {
             assert_eq!(e1.old_data, e2.old_data);
         }
     }
+
+    #[test]
+    fn test_redelivered_events_are_ignored() {
+        let log = EventLog::default();
+        let first = insert(&log, "posts", "1", serde_json::json!({}));
+        insert(&log, "posts", "2", serde_json::json!({}));
+
+        assert!(!log.record(first));
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.next_sequence(), 3);
+    }
 }
@@ -0,0 +1,544 @@
+//! # Resumable Subscription Hub
+//!
+//! Fan-out of database events to connections, with a bounded outbound
+//! buffer per connection and a bounded replay ring per subscription.
+//!
+//! ## Invariant: RT-R1
+//! Events on a subscription carry a sequence number one greater than the
+//! previous event on that subscription. A client that resumes with the last
+//! sequence it saw gets the missed events from the ring and then live events,
+//! with no gap and no duplicate, or an explicit error when the ring no longer
+//! holds them.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use super::backpressure::{BackpressureChannel, BackpressureConfig};
+use super::dispatcher::rls_allows;
+use super::errors::{RealtimeError, RealtimeResult};
+use super::event::DatabaseEvent;
+use super::subscription::Subscription;
+use crate::auth::rls::{RlsContext, RlsPolicy};
+
+/// Hub configuration
+#[derive(Debug, Clone)]
+pub struct HubConfig {
+    /// Events retained per subscription for resume
+    pub resume_buffer_size: usize,
+
+    /// Subscriptions retained for resume after their connection closes;
+    /// the longest-detached are dropped first
+    pub max_detached_subscriptions: usize,
+
+    /// Outbound buffer of each connection
+    pub backpressure: BackpressureConfig,
+}
+
+impl Default for HubConfig {
+    fn default() -> Self {
+        Self {
+            resume_buffer_size: 256,
+            max_detached_subscriptions: 1024,
+            backpressure: BackpressureConfig::default(),
+        }
+    }
+}
+
+/// An event as delivered on one subscription
+#[derive(Debug, Clone, Serialize)]
+pub struct SequencedEvent {
+    /// Subscription the event was delivered on
+    pub subscription_id: String,
+
+    /// Per-subscription sequence, starting at 1
+    pub seq: u64,
+
+    /// The change itself
+    pub event: DatabaseEvent,
+}
+
+/// Outbound queue of one connection
+pub struct Outbox {
+    channel: BackpressureChannel<SequencedEvent>,
+    notify: Notify,
+    reported_losses: AtomicU64,
+}
+
+impl Outbox {
+    fn new(config: BackpressureConfig) -> Self {
+        Self {
+            channel: BackpressureChannel::new(config),
+            notify: Notify::new(),
+            reported_losses: AtomicU64::new(0),
+        }
+    }
+
+    fn push(&self, event: SequencedEvent) {
+        // A rejected event is counted by the channel and reported through
+        // `take_losses`, like a dropped one
+        let _ = self.channel.send(event);
+        self.notify.notify_one();
+    }
+
+    /// Wait for the next queued event
+    ///
+    /// Cancel-safe: an event is only removed from the queue when returned.
+    pub async fn next(&self) -> SequencedEvent {
+        loop {
+            if let Some(event) = self.channel.recv() {
+                return event;
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Number of events lost to backpressure since the last call
+    pub fn take_losses(&self) -> u64 {
+        let snapshot = self.channel.counters().snapshot();
+        let total = snapshot.dropped + snapshot.rejected;
+        total - self.reported_losses.swap(total, Ordering::Relaxed)
+    }
+}
+
+/// A subscription and the state needed to resume it
+struct Stream {
+    subscription: Subscription,
+    next_seq: u64,
+    ring: VecDeque<SequencedEvent>,
+    /// Attached connection, `None` once detached
+    connection_id: Option<String>,
+    detached_at: Option<Instant>,
+}
+
+/// Subscription hub with per-subscription sequencing and resume
+pub struct RealtimeHub {
+    config: HubConfig,
+    streams: RwLock<HashMap<String, Stream>>,
+    outboxes: RwLock<HashMap<String, Arc<Outbox>>>,
+    rls_policies: RwLock<HashMap<String, RlsPolicy>>,
+}
+
+impl Default for RealtimeHub {
+    fn default() -> Self {
+        Self::new(HubConfig::default())
+    }
+}
+
+impl RealtimeHub {
+    /// Create a new hub
+    pub fn new(config: HubConfig) -> Self {
+        Self {
+            config,
+            streams: RwLock::new(HashMap::new()),
+            outboxes: RwLock::new(HashMap::new()),
+            rls_policies: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Register an RLS policy for a collection
+    pub fn register_rls_policy(&self, collection: &str, policy: RlsPolicy) {
+        if let Ok(mut policies) = self.rls_policies.write() {
+            policies.insert(collection.to_string(), policy);
+        }
+    }
+
+    /// Add a connection and return its outbound queue
+    pub fn connect(&self, connection_id: &str) -> Arc<Outbox> {
+        let outbox = Arc::new(Outbox::new(self.config.backpressure.clone()));
+        if let Ok(mut outboxes) = self.outboxes.write() {
+            outboxes.insert(connection_id.to_string(), Arc::clone(&outbox));
+        }
+        outbox
+    }
+
+    /// Remove a connection
+    ///
+    /// Its subscriptions keep sequencing events into their rings so a later
+    /// connection can resume them.
+    pub fn disconnect(&self, connection_id: &str) {
+        if let Ok(mut outboxes) = self.outboxes.write() {
+            outboxes.remove(connection_id);
+        }
+
+        let Ok(mut streams) = self.streams.write() else {
+            return;
+        };
+        let now = Instant::now();
+        for stream in streams.values_mut() {
+            if stream.connection_id.as_deref() == Some(connection_id) {
+                stream.connection_id = None;
+                stream.detached_at = Some(now);
+            }
+        }
+
+        let mut detached: Vec<(Instant, String)> = streams
+            .iter()
+            .filter_map(|(id, stream)| stream.detached_at.map(|at| (at, id.clone())))
+            .collect();
+        if detached.len() > self.config.max_detached_subscriptions {
+            detached.sort();
+            let excess = detached.len() - self.config.max_detached_subscriptions;
+            for (_, id) in detached.into_iter().take(excess) {
+                streams.remove(&id);
+            }
+        }
+    }
+
+    /// Add a subscription, attached to its connection
+    pub fn subscribe(&self, subscription: Subscription) -> RealtimeResult<String> {
+        let mut streams = self
+            .streams
+            .write()
+            .map_err(|_| RealtimeError::Internal("Lock poisoned".into()))?;
+
+        let id = subscription.id.clone();
+        let connection_id = subscription.connection_id.clone();
+        streams.insert(
+            id.clone(),
+            Stream {
+                subscription,
+                next_seq: 1,
+                ring: VecDeque::new(),
+                connection_id: Some(connection_id),
+                detached_at: None,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Attach an existing subscription to `connection_id`
+    ///
+    /// Returns the buffered events after `last_seq`, which the caller must
+    /// deliver before anything queued on the connection's outbox. Fails if
+    /// the subscription belongs to another user or the ring no longer holds
+    /// every event after `last_seq`; in the latter case the subscription is
+    /// removed and the client has to subscribe afresh.
+    pub fn resume(
+        &self,
+        connection_id: &str,
+        subscription_id: &str,
+        last_seq: u64,
+        rls_context: &RlsContext,
+    ) -> RealtimeResult<Vec<SequencedEvent>> {
+        let mut streams = self
+            .streams
+            .write()
+            .map_err(|_| RealtimeError::Internal("Lock poisoned".into()))?;
+
+        let stream = streams
+            .get_mut(subscription_id)
+            .ok_or_else(|| RealtimeError::SubscriptionNotFound(subscription_id.to_string()))?;
+
+        if stream.subscription.rls_context.user_id != rls_context.user_id {
+            return Err(RealtimeError::Unauthorized);
+        }
+
+        if last_seq >= stream.next_seq {
+            return Err(RealtimeError::ResumeUnavailable(format!(
+                "last_seq {} is ahead of the subscription (last sent {})",
+                last_seq,
+                stream.next_seq - 1
+            )));
+        }
+
+        let oldest = stream
+            .ring
+            .front()
+            .map(|buffered| buffered.seq)
+            .unwrap_or(stream.next_seq);
+        if last_seq + 1 < oldest {
+            streams.remove(subscription_id);
+            return Err(RealtimeError::ResumeUnavailable(format!(
+                "events after {} are no longer buffered (oldest is {})",
+                last_seq, oldest
+            )));
+        }
+
+        let backlog = stream
+            .ring
+            .iter()
+            .filter(|buffered| buffered.seq > last_seq)
+            .cloned()
+            .collect();
+
+        stream.subscription.connection_id = connection_id.to_string();
+        stream.connection_id = Some(connection_id.to_string());
+        stream.detached_at = None;
+
+        Ok(backlog)
+    }
+
+    /// Remove a subscription attached to `connection_id`
+    pub fn unsubscribe(&self, connection_id: &str, subscription_id: &str) -> RealtimeResult<()> {
+        let mut streams = self
+            .streams
+            .write()
+            .map_err(|_| RealtimeError::Internal("Lock poisoned".into()))?;
+
+        match streams.get(subscription_id) {
+            Some(stream) if stream.connection_id.as_deref() == Some(connection_id) => {
+                streams.remove(subscription_id);
+                Ok(())
+            }
+            _ => Err(RealtimeError::SubscriptionNotFound(
+                subscription_id.to_string(),
+            )),
+        }
+    }
+
+    /// Sequence an event on every matching subscription
+    ///
+    /// The event is buffered for resume on every subscription it passes the
+    /// filter and RLS for, attached or not. Returns the number of
+    /// subscriptions it was queued to a live connection for.
+    pub fn publish(&self, event: &DatabaseEvent) -> usize {
+        let policy = self
+            .rls_policies
+            .read()
+            .ok()
+            .and_then(|policies| policies.get(&event.collection).cloned());
+
+        let Ok(mut streams) = self.streams.write() else {
+            return 0;
+        };
+        let Ok(outboxes) = self.outboxes.read() else {
+            return 0;
+        };
+
+        let mut queued = 0;
+        for (id, stream) in streams.iter_mut() {
+            if !stream.subscription.matches(event)
+                || !rls_allows(&stream.subscription.rls_context, policy.as_ref(), event)
+            {
+                continue;
+            }
+
+            let sequenced = SequencedEvent {
+                subscription_id: id.clone(),
+                seq: stream.next_seq,
+                event: event.clone(),
+            };
+            stream.next_seq += 1;
+
+            if self.config.resume_buffer_size > 0 {
+                if stream.ring.len() == self.config.resume_buffer_size {
+                    stream.ring.pop_front();
+                }
+                stream.ring.push_back(sequenced.clone());
+            }
+
+            let outbox = stream
+                .connection_id
+                .as_ref()
+                .and_then(|connection_id| outboxes.get(connection_id));
+            if let Some(outbox) = outbox {
+                outbox.push(sequenced);
+                queued += 1;
+            }
+        }
+        queued
+    }
+
+    /// Number of subscriptions, attached or retained for resume
+    pub fn subscription_count(&self) -> usize {
+        self.streams.read().map(|s| s.len()).unwrap_or(0)
+    }
+
+    /// Number of connected clients
+    pub fn connection_count(&self) -> usize {
+        self.outboxes.read().map(|o| o.len()).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::realtime::backpressure::DropPolicy;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn insert(record_id: &str, data: serde_json::Value) -> DatabaseEvent {
+        DatabaseEvent::insert(1, "messages".into(), record_id.into(), data, None)
+    }
+
+    fn subscribe(hub: &RealtimeHub, connection_id: &str, rls: RlsContext) -> String {
+        hub.subscribe(Subscription::new(
+            connection_id.to_string(),
+            "messages".to_string(),
+            rls,
+        ))
+        .unwrap()
+    }
+
+    fn drain(outbox: &Outbox) -> Vec<u64> {
+        std::iter::from_fn(|| outbox.channel.recv())
+            .map(|event| event.seq)
+            .collect()
+    }
+
+    #[test]
+    fn test_publish_sequences_per_subscription() {
+        let hub = RealtimeHub::default();
+        let outbox = hub.connect("conn-1");
+        let first = subscribe(&hub, "conn-1", RlsContext::anonymous());
+        hub.publish(&insert("m1", json!({})));
+        let second = subscribe(&hub, "conn-1", RlsContext::anonymous());
+        assert_eq!(hub.publish(&insert("m2", json!({}))), 2);
+
+        let delivered: Vec<(String, u64)> = std::iter::from_fn(|| outbox.channel.recv())
+            .map(|event| (event.subscription_id, event.seq))
+            .collect();
+        assert_eq!(delivered.len(), 3);
+        assert!(delivered.contains(&(first.clone(), 1)));
+        assert!(delivered.contains(&(first, 2)));
+        assert!(delivered.contains(&(second, 1)));
+    }
+
+    #[test]
+    fn test_resume_replays_ring_then_goes_live() {
+        let hub = RealtimeHub::new(HubConfig {
+            resume_buffer_size: 3,
+            ..HubConfig::default()
+        });
+        let rls = RlsContext::authenticated(Uuid::new_v4());
+        hub.connect("conn-1");
+        let id = subscribe(&hub, "conn-1", rls.clone());
+        hub.publish(&insert("m1", json!({})));
+        hub.disconnect("conn-1");
+
+        for i in 2..=4 {
+            assert_eq!(hub.publish(&insert(&format!("m{}", i), json!({}))), 0);
+        }
+
+        let outbox = hub.connect("conn-2");
+        let backlog = hub.resume("conn-2", &id, 2, &rls).unwrap();
+        let seqs: Vec<u64> = backlog.iter().map(|event| event.seq).collect();
+        assert_eq!(seqs, vec![3, 4]);
+
+        hub.publish(&insert("m5", json!({})));
+        assert_eq!(drain(&outbox), vec![5]);
+    }
+
+    #[test]
+    fn test_resume_past_ring_removes_subscription() {
+        let hub = RealtimeHub::new(HubConfig {
+            resume_buffer_size: 2,
+            ..HubConfig::default()
+        });
+        let rls = RlsContext::anonymous();
+        hub.connect("conn-1");
+        let id = subscribe(&hub, "conn-1", rls.clone());
+        for i in 1..=4 {
+            hub.publish(&insert(&format!("m{}", i), json!({})));
+        }
+        hub.disconnect("conn-1");
+
+        hub.connect("conn-2");
+        let err = hub.resume("conn-2", &id, 1, &rls).unwrap_err();
+        assert!(matches!(err, RealtimeError::ResumeUnavailable(_)));
+        assert_eq!(err.close_code(), 4006);
+        assert!(matches!(
+            hub.resume("conn-2", &id, 3, &rls),
+            Err(RealtimeError::SubscriptionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_resume_requires_same_user_and_known_seq() {
+        let hub = RealtimeHub::default();
+        let owner = RlsContext::authenticated(Uuid::new_v4());
+        hub.connect("conn-1");
+        let id = subscribe(&hub, "conn-1", owner.clone());
+        hub.publish(&insert("m1", json!({})));
+
+        let stranger = RlsContext::authenticated(Uuid::new_v4());
+        assert!(matches!(
+            hub.resume("conn-2", &id, 0, &stranger),
+            Err(RealtimeError::Unauthorized)
+        ));
+        assert!(matches!(
+            hub.resume("conn-2", &id, 5, &owner),
+            Err(RealtimeError::ResumeUnavailable(_))
+        ));
+        assert_eq!(hub.resume("conn-2", &id, 0, &owner).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rls_applies_before_sequencing() {
+        let hub = RealtimeHub::default();
+        hub.register_rls_policy(
+            "messages",
+            RlsPolicy::Ownership {
+                owner_field: "owner_id".to_string(),
+            },
+        );
+        let alice = Uuid::new_v4();
+        let outbox = hub.connect("conn-1");
+        subscribe(&hub, "conn-1", RlsContext::authenticated(alice));
+
+        hub.publish(&insert(
+            "m1",
+            json!({"owner_id": Uuid::new_v4().to_string()}),
+        ));
+        hub.publish(&insert("m2", json!({"owner_id": alice.to_string()})));
+
+        // The hidden row consumes no sequence number, so there is no gap
+        assert_eq!(drain(&outbox), vec![1]);
+    }
+
+    #[test]
+    fn test_outbox_reports_losses_once() {
+        let hub = RealtimeHub::new(HubConfig {
+            backpressure: BackpressureConfig {
+                max_pending_messages: 2,
+                drop_policy: DropPolicy::OldestFirst,
+            },
+            ..HubConfig::default()
+        });
+        let outbox = hub.connect("conn-1");
+        subscribe(&hub, "conn-1", RlsContext::anonymous());
+        for i in 1..=5 {
+            hub.publish(&insert(&format!("m{}", i), json!({})));
+        }
+
+        assert_eq!(outbox.take_losses(), 3);
+        assert_eq!(outbox.take_losses(), 0);
+        assert_eq!(drain(&outbox), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_detached_subscriptions_are_bounded() {
+        let hub = RealtimeHub::new(HubConfig {
+            max_detached_subscriptions: 1,
+            ..HubConfig::default()
+        });
+        hub.connect("conn-1");
+        let old = subscribe(&hub, "conn-1", RlsContext::anonymous());
+        hub.disconnect("conn-1");
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        hub.connect("conn-2");
+        let new = subscribe(&hub, "conn-2", RlsContext::anonymous());
+        hub.disconnect("conn-2");
+
+        assert_eq!(hub.subscription_count(), 1);
+        let rls = RlsContext::anonymous();
+        assert!(hub.resume("conn-3", &old, 0, &rls).is_err());
+        assert!(hub.resume("conn-3", &new, 0, &rls).is_ok());
+    }
+
+    #[test]
+    fn test_unsubscribe_only_from_attached_connection() {
+        let hub = RealtimeHub::default();
+        hub.connect("conn-1");
+        let id = subscribe(&hub, "conn-1", RlsContext::anonymous());
+
+        assert!(hub.unsubscribe("conn-2", &id).is_err());
+        hub.unsubscribe("conn-1", &id).unwrap();
+        assert_eq!(hub.subscription_count(), 0);
+    }
+}
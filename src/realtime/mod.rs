@@ -7,19 +7,23 @@
 //!
 //! ## Architecture
 //!
-//! - **Event Log** (deterministic): WAL → Event transformation
+//! - **Change Stream** (deterministic): WAL → Event transformation, resumable
+//! - **Event Log**: Ring buffer of recent events
 //! - **Dispatcher** (non-deterministic): WebSocket event delivery
 //! - **Subscriptions**: Client subscription management
+//! - **Hub**: Sequenced, resumable delivery for `/realtime/v1`
 //! - **Broadcast**: Pub/sub channels
 //! - **Presence**: User presence tracking
 //! - **WebSocket**: Network layer for connections
 
 pub mod backpressure;
 pub mod broadcast;
+pub mod cdc;
 pub mod dispatcher;
 pub mod errors;
 pub mod event;
 pub mod event_log;
+pub mod hub;
 pub mod presence;
 pub mod subscription;
 pub mod websocket;
@@ -30,10 +34,12 @@ pub use backpressure::{
 };
 
 pub use broadcast::BroadcastChannel;
+pub use cdc::{change_event, ChangeOffset, ChangeSink, ChangeStream, ChangeStreamConfig};
 pub use dispatcher::Dispatcher;
 pub use errors::{RealtimeError, RealtimeResult};
 pub use event::{BroadcastEvent, DatabaseEvent, EventType};
 pub use event_log::EventLog;
+pub use hub::{HubConfig, Outbox, RealtimeHub, SequencedEvent};
 pub use presence::PresenceTracker;
 pub use subscription::{Subscription, SubscriptionFilter, SubscriptionRegistry};
 pub use websocket::{WebSocketConfig, WebSocketServer};
@@ -1,6 +1,10 @@
 //! # Subscription Management
 //!
 //! Client subscription registry and filtering.
+//!
+//! A subscription's predicate is compiled into `SubscriptionFilter`s once,
+//! at subscribe time, and evaluated against each event's row (new values
+//! for INSERT/UPDATE, old values for DELETE).
 
 use std::collections::{HashMap, HashSet};
 use std::sync::RwLock;
@@ -9,7 +13,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::errors::{RealtimeError, RealtimeResult};
-use super::event::DatabaseEvent;
+use super::event::{DatabaseEvent, EventType};
 use crate::auth::rls::RlsContext;
 
 /// Filter operator for subscription predicates
@@ -36,13 +40,59 @@ pub struct SubscriptionFilter {
     pub value: serde_json::Value,
 }
 
+impl FilterOp {
+    /// Parse an operator name, with or without the query API's `$` prefix
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.strip_prefix('$').unwrap_or(name) {
+            "eq" => Some(FilterOp::Eq),
+            "neq" => Some(FilterOp::Neq),
+            "gt" => Some(FilterOp::Gt),
+            "gte" => Some(FilterOp::Gte),
+            "lt" => Some(FilterOp::Lt),
+            "lte" => Some(FilterOp::Lte),
+            "in" => Some(FilterOp::In),
+            _ => None,
+        }
+    }
+}
+
 impl SubscriptionFilter {
+    /// Compile a `{"<field>": {"<op>": <value>}}` predicate into filters
+    ///
+    /// Every condition must hold. `in` takes an array of values.
+    pub fn compile(predicate: &serde_json::Value) -> RealtimeResult<Vec<Self>> {
+        let fields = predicate
+            .as_object()
+            .ok_or_else(|| RealtimeError::InvalidFilter("predicate must be an object".into()))?;
+
+        let mut filters = Vec::new();
+        for (field, conditions) in fields {
+            let conditions = conditions.as_object().ok_or_else(|| {
+                RealtimeError::InvalidFilter(format!("conditions on '{}' must be an object", field))
+            })?;
+            for (op_name, value) in conditions {
+                let op = FilterOp::parse(op_name).ok_or_else(|| {
+                    RealtimeError::InvalidFilter(format!("unknown operator '{}'", op_name))
+                })?;
+                if op == FilterOp::In && !value.is_array() {
+                    return Err(RealtimeError::InvalidFilter(format!(
+                        "'in' on '{}' needs an array",
+                        field
+                    )));
+                }
+                filters.push(SubscriptionFilter {
+                    field: field.clone(),
+                    op,
+                    value: value.clone(),
+                });
+            }
+        }
+        Ok(filters)
+    }
+
     /// Check if an event matches this filter
     pub fn matches(&self, event: &DatabaseEvent) -> bool {
-        // Get the value from new_data or old_data
-        let data = event.new_data.as_ref().or(event.old_data.as_ref());
-
-        let Some(data) = data else {
+        let Some(data) = event.row() else {
             return false;
         };
 
@@ -108,7 +158,7 @@ pub struct Subscription {
     pub collection: String,
 
     /// Event types to subscribe to (None = all)
-    pub event_types: Option<HashSet<String>>,
+    pub event_types: Option<HashSet<EventType>>,
 
     /// Filters to apply
     pub filters: Vec<SubscriptionFilter>,
@@ -140,8 +190,14 @@ impl Subscription {
         self
     }
 
+    /// Add every filter of a compiled predicate
+    pub fn with_filters(mut self, filters: Vec<SubscriptionFilter>) -> Self {
+        self.filters.extend(filters);
+        self
+    }
+
     /// Set event types
-    pub fn with_events(mut self, events: HashSet<String>) -> Self {
+    pub fn with_events(mut self, events: HashSet<EventType>) -> Self {
         self.event_types = Some(events);
         self
     }
@@ -155,7 +211,7 @@ impl Subscription {
 
         // Check event type
         if let Some(ref types) = self.event_types {
-            if !types.contains(&event.event_type.to_string()) {
+            if !types.contains(&event.event_type) {
                 return false;
             }
         }
@@ -432,6 +488,70 @@ mod tests {
         assert!(filter.matches(&event));
     }
 
+    #[test]
+    fn test_compile_predicate() {
+        let filters = SubscriptionFilter::compile(&json!({
+            "room_id": {"eq": "abc"},
+            "score": {"$gte": 10, "lt": 20},
+            "kind": {"in": ["a", "b"]}
+        }))
+        .unwrap();
+        assert_eq!(filters.len(), 4);
+        assert!(filters
+            .iter()
+            .any(|f| f.field == "score" && f.op == FilterOp::Gte));
+
+        for bad in [
+            json!("room_id=eq.abc"),
+            json!({"room_id": "abc"}),
+            json!({"room_id": {"like": "a%"}}),
+            json!({"kind": {"in": "a"}}),
+        ] {
+            assert!(matches!(
+                SubscriptionFilter::compile(&bad),
+                Err(RealtimeError::InvalidFilter(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_filters_use_old_values_for_delete() {
+        let sub = Subscription::new("conn-1".to_string(), "posts".to_string(), create_test_rls())
+            .with_filters(SubscriptionFilter::compile(&json!({"status": {"eq": "draft"}})).unwrap())
+            .with_events(HashSet::from([EventType::Update, EventType::Delete]));
+
+        // UPDATE: the new values decide
+        let published = DatabaseEvent::update(
+            1,
+            "posts".to_string(),
+            "1".to_string(),
+            json!({"status": "draft"}),
+            json!({"status": "published"}),
+            None,
+        );
+        assert!(!sub.matches(&published));
+
+        // DELETE: the old values decide
+        let deleted = DatabaseEvent::delete(
+            2,
+            "posts".to_string(),
+            "1".to_string(),
+            json!({"status": "draft"}),
+            None,
+        );
+        assert!(sub.matches(&deleted));
+
+        // INSERT is not subscribed
+        let inserted = DatabaseEvent::insert(
+            3,
+            "posts".to_string(),
+            "2".to_string(),
+            json!({"status": "draft"}),
+            None,
+        );
+        assert!(!sub.matches(&inserted));
+    }
+
     #[test]
     fn test_subscription_matching() {
         let sub = Subscription::new("conn-1".to_string(), "posts".to_string(), create_test_rls());
@@ -3,7 +3,7 @@
 //! Provides WebSocket connectivity for real-time event delivery.
 //! This is the network layer on top of the Dispatcher.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
 
@@ -19,7 +19,7 @@ use uuid::Uuid;
 
 use super::dispatcher::{Dispatcher, EventReceiver};
 use super::errors::{RealtimeError, RealtimeResult};
-use super::event::DatabaseEvent;
+use super::event::{DatabaseEvent, EventType};
 use super::subscription::{Subscription, SubscriptionFilter};
 use crate::auth::rls::RlsContext;
 
@@ -67,11 +67,16 @@ impl Default for WebSocketConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ClientMessage {
-    /// Subscribe to a channel
+    /// Subscribe to a channel (collection)
+    ///
+    /// `event_types` limits delivery to `insert`, `update` and/or `delete`;
+    /// `filter` is a `{"<field>": {"<op>": <value>}}` predicate.
     Subscribe {
         channel: String,
         #[serde(default)]
-        filter: Option<SubscriptionFilter>,
+        event_types: Option<Vec<String>>,
+        #[serde(default)]
+        filter: Option<serde_json::Value>,
     },
 
     /// Unsubscribe from a channel
@@ -374,20 +379,26 @@ impl WebSocketServer {
         msg_tx: &mpsc::Sender<ServerMessage>,
     ) -> RealtimeResult<()> {
         match message {
-            ClientMessage::Subscribe { channel, filter: _ } => {
+            ClientMessage::Subscribe {
+                channel,
+                event_types,
+                filter,
+            } => {
+                // Compile before connecting, so a bad filter changes nothing
+                let subscription = build_subscription(
+                    connection_id,
+                    &channel,
+                    event_types.as_deref(),
+                    filter.as_ref(),
+                    rls_context,
+                )?;
+
                 // Connect to dispatcher if not already
                 if event_receiver.is_none() {
                     let rx = dispatcher.connect(connection_id.to_string(), rls_context.clone());
                     *event_receiver = Some(rx);
                 }
 
-                // Subscribe to channel
-                let subscription = Subscription::new(
-                    connection_id.to_string(),
-                    channel.clone(),
-                    rls_context.clone(),
-                );
-
                 dispatcher.subscriptions.register(subscription.clone())?;
                 subscribed_channels.push(channel.clone());
 
@@ -452,6 +463,39 @@ impl WebSocketServer {
     }
 }
 
+/// Build a subscription, compiling its event types and predicate
+pub(crate) fn build_subscription(
+    connection_id: &str,
+    channel: &str,
+    event_types: Option<&[String]>,
+    filter: Option<&serde_json::Value>,
+    rls_context: &RlsContext,
+) -> RealtimeResult<Subscription> {
+    let mut subscription = Subscription::new(
+        connection_id.to_string(),
+        channel.to_string(),
+        rls_context.clone(),
+    );
+
+    if let Some(names) = event_types {
+        let types = names
+            .iter()
+            .map(|name| {
+                EventType::parse(name).ok_or_else(|| {
+                    RealtimeError::InvalidFilter(format!("unknown event type '{}'", name))
+                })
+            })
+            .collect::<RealtimeResult<HashSet<_>>>()?;
+        subscription = subscription.with_events(types);
+    }
+
+    if let Some(predicate) = filter {
+        subscription = subscription.with_filters(SubscriptionFilter::compile(predicate)?);
+    }
+
+    Ok(subscription)
+}
+
 /// Validate JWT token and return RLS context
 fn validate_token(token: &str) -> RealtimeResult<RlsContext> {
     use crate::auth::jwt::{JwtConfig, JwtManager};
@@ -507,6 +551,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_subscribe_compiles_event_types_and_filter() {
+        let json = r#"{
+            "type": "subscribe",
+            "channel": "messages",
+            "event_types": ["insert", "DELETE"],
+            "filter": {"room_id": {"eq": "abc"}}
+        }"#;
+        let ClientMessage::Subscribe {
+            channel,
+            event_types,
+            filter,
+        } = serde_json::from_str(json).unwrap()
+        else {
+            panic!("Wrong message type");
+        };
+
+        let rls = RlsContext::anonymous();
+        let sub = build_subscription(
+            "conn-1",
+            &channel,
+            event_types.as_deref(),
+            filter.as_ref(),
+            &rls,
+        )
+        .unwrap();
+        assert_eq!(
+            sub.event_types,
+            Some(HashSet::from([EventType::Insert, EventType::Delete]))
+        );
+        assert_eq!(sub.filters.len(), 1);
+
+        let bad_type = ["upsert".to_string()];
+        let err = build_subscription("conn-1", "messages", Some(&bad_type), None, &rls);
+        assert!(matches!(err, Err(RealtimeError::InvalidFilter(_))));
+
+        let bad_filter = serde_json::json!({"room_id": {"like": "a%"}});
+        let err = build_subscription("conn-1", "messages", None, Some(&bad_filter), &rls);
+        assert!(matches!(err, Err(RealtimeError::InvalidFilter(_))));
+    }
+
     #[test]
     fn test_server_message_serialize() {
         let msg = ServerMessage::Heartbeat {
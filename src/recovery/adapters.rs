@@ -3,15 +3,17 @@
 //! This module provides implementations of the recovery traits for the
 //! actual WAL, Storage, Index, and Schema types used in the system.
 
+use std::collections::{HashMap, VecDeque};
 use std::path::Path;
+use std::sync::Mutex;
 
-use crate::index::IndexManager;
+use crate::index::{DocumentInfo, IndexError, IndexManager, IndexResult};
 use crate::schema::SchemaLoader;
-use crate::storage::{StorageReader, StorageWriter};
-use crate::wal::{WalReader, WalRecord};
+use crate::storage::{PreparedRecord, StorageReader, StorageWriter};
+use crate::wal::{DiscardedTail, WalReader, WalRecord};
 
 use super::errors::{RecoveryError, RecoveryResult};
-use super::replay::{StorageApply, WalRead};
+use super::replay::{ConcurrentApply, StorageApply, WalRead};
 use super::startup::IndexRebuild;
 use super::verifier::{SchemaCheck, StorageRecordInfo, StorageScan};
 
@@ -34,6 +36,20 @@ impl WalRead for WalReader {
             RecoveryError::recovery_failed(format!("Failed to reset WAL reader: {}", e))
         })
     }
+
+    fn total_bytes(&self) -> Option<u64> {
+        Some(WalReader::total_bytes(self))
+    }
+
+    fn bytes_read(&self) -> u64 {
+        WalReader::bytes_read(self)
+    }
+
+    fn discard_corrupt_tail(&mut self) -> RecoveryResult<Option<DiscardedTail>> {
+        WalReader::discard_corrupt_tail(self).map_err(|e| {
+            RecoveryError::recovery_failed(format!("Failed to discard corrupt WAL tail: {}", e))
+        })
+    }
 }
 
 // ============================================================================
@@ -42,11 +58,47 @@ impl WalRead for WalReader {
 
 /// Combined storage adapter that implements both StorageApply and StorageScan.
 /// Required by RecoveryManager::recover() which needs both traits on the same type.
+///
+/// It replays in parallel too: workers encode records concurrently, and
+/// each record is appended once every record scheduled before it has been,
+/// so `documents.dat` ends up byte-identical to a sequential replay.
 pub struct RecoveryStorage {
-    writer: StorageWriter,
+    writer: Mutex<OrderedWriter>,
     reader: StorageReader,
 }
 
+/// Storage writer appending records encoded on several threads in WAL order
+struct OrderedWriter {
+    writer: StorageWriter,
+    /// Sequences scheduled for replay and not yet written, in WAL order
+    scheduled: VecDeque<u64>,
+    /// Encoded records waiting for the records scheduled before them
+    encoded: HashMap<u64, PreparedRecord>,
+    /// Why the first failed write failed; nothing is written after it
+    failure: Option<String>,
+}
+
+impl OrderedWriter {
+    /// Appends the encoded records at the head of the schedule
+    fn flush(&mut self) -> RecoveryResult<()> {
+        if let Some(failure) = &self.failure {
+            return Err(RecoveryError::recovery_failed(failure.clone()));
+        }
+        while let Some(sequence) = self.scheduled.front().copied() {
+            let Some(prepared) = self.encoded.remove(&sequence) else {
+                break;
+            };
+            if let Err(e) = self.writer.apply_prepared(&prepared) {
+                let failure = format!("Failed to apply WAL record {}: {}", sequence, e);
+                self.failure = Some(failure.clone());
+                return Err(RecoveryError::recovery_failed(failure));
+            }
+            self.scheduled.pop_front();
+        }
+        Ok(())
+    }
+}
+
 impl RecoveryStorage {
     /// Create a new recovery storage adapter from a data directory
     pub fn open(data_dir: &Path) -> RecoveryResult<Self> {
@@ -56,18 +108,60 @@ impl RecoveryStorage {
         let reader = StorageReader::open_from_data_dir(data_dir).map_err(|e| {
             RecoveryError::recovery_failed(format!("Failed to open storage reader: {}", e))
         })?;
+        let writer = Mutex::new(OrderedWriter {
+            writer,
+            scheduled: VecDeque::new(),
+            encoded: HashMap::new(),
+            failure: None,
+        });
         Ok(Self { writer, reader })
     }
 
     /// Consume the adapter and return the underlying writer and reader
     pub fn into_parts(self) -> (StorageWriter, StorageReader) {
-        (self.writer, self.reader)
+        (self.writer.into_inner().unwrap().writer, self.reader)
     }
 }
 
 impl StorageApply for RecoveryStorage {
     fn apply_wal_record(&mut self, record: &WalRecord) -> RecoveryResult<()> {
-        self.writer.apply_wal_record(record).map_err(|e| {
+        StorageApply::apply_wal_record(&mut self.writer.get_mut().unwrap().writer, record)
+    }
+
+    fn concurrent(&self) -> Option<&dyn ConcurrentApply> {
+        Some(self)
+    }
+}
+
+impl ConcurrentApply for RecoveryStorage {
+    fn schedule(&self, record: &WalRecord) {
+        let mut ordered = self.writer.lock().unwrap();
+        ordered.scheduled.push_back(record.sequence_number);
+    }
+
+    fn apply_concurrent(&self, record: &WalRecord) -> RecoveryResult<()> {
+        if record.record_type.is_collection_change() {
+            // A barrier: every record scheduled before it has been written
+            let mut ordered = self.writer.lock().unwrap();
+            ordered.flush()?;
+            ordered
+                .scheduled
+                .retain(|&sequence| sequence != record.sequence_number);
+            return StorageApply::apply_wal_record(&mut ordered.writer, record);
+        }
+
+        // Encoding is the work done in parallel; appending stays in WAL order
+        let prepared = PreparedRecord::from_wal_record(record);
+        let mut ordered = self.writer.lock().unwrap();
+        ordered.encoded.insert(record.sequence_number, prepared);
+        ordered.flush()
+    }
+}
+
+/// Applies records as they arrive after recovery, e.g. on a replica
+impl StorageApply for StorageWriter {
+    fn apply_wal_record(&mut self, record: &WalRecord) -> RecoveryResult<()> {
+        StorageWriter::apply_wal_record(self, record).map_err(|e| {
             RecoveryError::recovery_failed(format!("Failed to apply WAL record: {}", e))
         })?;
         Ok(())
@@ -99,6 +193,53 @@ impl StorageScan for RecoveryStorage {
     }
 }
 
+// ============================================================================
+// Index StorageScan implementation for StorageReader
+// ============================================================================
+
+/// Feeds a boot-time `IndexManager::rebuild_with_progress`
+///
+/// Documents are indexed under the id they were written with, without the
+/// `collection:` prefix storage keys carry, matching the write path.
+impl crate::index::StorageScan for StorageReader {
+    fn scan_next(&mut self) -> IndexResult<Option<DocumentInfo>> {
+        let offset = self.current_offset();
+        let record = match self.read_next() {
+            Ok(Some(record)) => record,
+            Ok(None) => return Ok(None),
+            Err(e) => return Err(IndexError::data_corruption(offset, e.to_string())),
+        };
+        let body = if record.is_tombstone {
+            serde_json::Value::Null
+        } else {
+            serde_json::from_slice(&record.document_body).map_err(|e| {
+                IndexError::data_corruption(offset, format!("Invalid document JSON: {}", e))
+            })?
+        };
+        let document_id = match record.document_id.split_once(':') {
+            Some((_, id)) => id.to_string(),
+            None => record.document_id,
+        };
+
+        Ok(Some(DocumentInfo {
+            document_id,
+            schema_id: record.schema_id,
+            schema_version: record.schema_version,
+            is_tombstone: record.is_tombstone,
+            body,
+            offset,
+        }))
+    }
+
+    fn reset(&mut self) -> IndexResult<()> {
+        StorageReader::reset(self).map_err(|e| IndexError::build_failed(e.to_string()))
+    }
+
+    fn current_offset(&self) -> u64 {
+        StorageReader::current_offset(self)
+    }
+}
+
 // ============================================================================
 // IndexRebuild implementation for IndexManager
 // ============================================================================
@@ -161,6 +302,40 @@ mod tests {
         assert!(record.is_some());
     }
 
+    #[test]
+    fn test_wal_read_spans_segments() {
+        use crate::wal::{WalSegmentConfig, WalSyncConfig};
+
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let mut writer = WalWriter::open_segmented(
+                temp_dir.path(),
+                WalSyncConfig::default(),
+                WalSegmentConfig::new(256),
+            )
+            .unwrap();
+            for i in 0..20 {
+                let payload = WalPayload::new(
+                    "test_collection",
+                    format!("doc{}", i),
+                    "test_schema",
+                    "v1",
+                    b"{}".to_vec(),
+                );
+                writer.append_insert(payload).unwrap();
+            }
+            assert!(writer.list_segments().unwrap().len() > 1);
+        }
+
+        // Replay must walk every segment in order without gaps
+        let mut reader = WalReader::open_from_data_dir(temp_dir.path()).unwrap();
+        let mut sequences = Vec::new();
+        while let Some(record) = WalRead::read_next(&mut reader).unwrap() {
+            sequences.push(record.sequence_number);
+        }
+        assert_eq!(sequences, (1..=20).collect::<Vec<u64>>());
+    }
+
     #[test]
     fn test_schema_check_implementation() {
         let temp_dir = TempDir::new().unwrap();
@@ -192,4 +367,30 @@ mod tests {
         let (writer, _reader) = storage.into_parts();
         assert_eq!(writer.current_offset(), 0);
     }
+
+    #[test]
+    fn test_index_rebuild_from_storage_reader() {
+        use crate::storage::StoragePayload;
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = StorageWriter::open(temp_dir.path()).unwrap();
+        let mut offsets = Vec::new();
+        for id in ["a", "b"] {
+            let body = format!(r#"{{"_id":"{}"}}"#, id).into_bytes();
+            let payload = StoragePayload::new("users", id, "users", "v1", body);
+            offsets.push(writer.write(&payload).unwrap());
+        }
+        writer.write_tombstone("users", "a", "users", "v1").unwrap();
+
+        let mut reader = StorageReader::open_from_data_dir(temp_dir.path()).unwrap();
+        let mut index = IndexManager::pk_only();
+        let mut last = None;
+        index
+            .rebuild_with_progress(&mut reader, |p| last = Some(p))
+            .unwrap();
+
+        assert_eq!(last.unwrap().documents_processed, 3);
+        assert!(index.lookup_pk("a").is_empty());
+        assert_eq!(index.lookup_pk("b"), vec![offsets[1]]);
+    }
 }
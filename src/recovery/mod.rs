@@ -18,16 +18,37 @@
 //! - R1: WAL is single source of truth for recovery
 //! - R2: Sequential replay from byte 0
 //! - K2: Halt-on-corruption policy
+//!
+//! # Progress
+//!
+//! Recovery logs `RECOVERY_PROGRESS` events per phase and keeps the latest
+//! in `data_dir/recovery_progress.json`, which `/health/ready` reports.
+//!
+//! # Parallel replay
+//!
+//! Storage offering a [`ConcurrentApply`] handle is replayed on several
+//! threads partitioned by collection, with the same result as sequential
+//! replay; other storage is replayed sequentially. [`RecoveryStorage`]
+//! encodes records on the replay threads and appends them to
+//! `documents.dat` in WAL order, so the file is byte-identical.
 
 mod adapters;
 mod errors;
+mod progress;
 mod replay;
 mod startup;
 mod verifier;
 
 pub use adapters::RecoveryStorage;
 pub use errors::{RecoveryError, RecoveryErrorCode, RecoveryResult};
-pub use replay::{ReplayStats, StorageApply, WalRead, WalReplayer};
+pub use progress::{
+    recovery_progress_path, RecoveryPhase, RecoveryProgress, DEFAULT_PROGRESS_INTERVAL,
+    DEFAULT_PROGRESS_RECORDS, RECOVERY_PROGRESS_FILE,
+};
+pub use replay::{
+    default_replay_parallelism, ConcurrentApply, CorruptTailPolicy, ReplayStats, StorageApply,
+    WalRead, WalReplayer, MAX_DEFAULT_REPLAY_PARALLELISM,
+};
 pub use startup::{IndexRebuild, RecoveryManager, RecoveryState};
 pub use verifier::{
     ConsistencyVerifier, SchemaCheck, StorageRecordInfo, StorageScan, VerificationStats,
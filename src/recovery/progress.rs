@@ -0,0 +1,283 @@
+//! Recovery progress reporting and resume markers
+//!
+//! Recovery of a large database takes minutes. While it runs, progress
+//! events (`RECOVERY_PROGRESS`) go to the logger every N records or
+//! seconds, and the latest one is kept in `data_dir/recovery_progress.json`
+//! for the readiness endpoint.
+//!
+//! `data_dir/recovery_resume.json` records how far an unfinished recovery
+//! got: the WAL sequence replayed into storage and the indexes already
+//! rebuilt. A recovery interrupted after replay resumes from there instead
+//! of replaying the WAL and rebuilding those indexes again. The marker is
+//! removed when recovery completes.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::errors::{RecoveryError, RecoveryResult};
+use crate::observability::{Event, Logger};
+
+/// Progress file name, in the data directory
+pub const RECOVERY_PROGRESS_FILE: &str = "recovery_progress.json";
+
+/// Resume marker file name, in the data directory
+const RECOVERY_RESUME_FILE: &str = "recovery_resume.json";
+
+/// Records between progress events, by default
+pub const DEFAULT_PROGRESS_RECORDS: u64 = 10_000;
+
+/// Longest time between progress events, by default
+pub const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Path of the progress file for `data_dir`
+pub fn recovery_progress_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(RECOVERY_PROGRESS_FILE)
+}
+
+/// Phase of recovery, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryPhase {
+    /// Replaying the WAL into storage
+    WalReplay,
+    /// Rebuilding indexes from storage
+    IndexRebuild,
+    /// Checking storage against the schemas
+    Verification,
+    /// Recovery finished; the database may serve
+    Complete,
+}
+
+impl RecoveryPhase {
+    /// Name used in logs and the progress file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecoveryPhase::WalReplay => "wal_replay",
+            RecoveryPhase::IndexRebuild => "index_rebuild",
+            RecoveryPhase::Verification => "verification",
+            RecoveryPhase::Complete => "complete",
+        }
+    }
+}
+
+/// A progress event, as logged and stored in the progress file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryProgress {
+    /// Current phase
+    pub phase: RecoveryPhase,
+    /// Share of the phase done, 0 to 100
+    pub percent: f64,
+    /// Records (or indexes, when rebuilding) processed in the phase
+    pub records: u64,
+    /// Processing rate over the phase so far
+    pub records_per_sec: f64,
+    /// Estimated seconds until the phase completes, once known
+    pub eta_secs: Option<u64>,
+    /// When the event was emitted
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RecoveryProgress {
+    /// The progress last stored for `data_dir`, if any
+    pub fn load(data_dir: &Path) -> Option<Self> {
+        let contents = fs::read(recovery_progress_path(data_dir)).ok()?;
+        serde_json::from_slice(&contents).ok()
+    }
+
+    /// Whether recovery is still running (or died before completing)
+    pub fn is_recovering(&self) -> bool {
+        self.phase != RecoveryPhase::Complete
+    }
+}
+
+/// Emits progress events for one recovery run
+pub(crate) struct ProgressReporter {
+    data_dir: PathBuf,
+    logger: Arc<dyn Logger>,
+    every_records: u64,
+    every: Duration,
+    phase: RecoveryPhase,
+    phase_started: Instant,
+    last_emitted: Instant,
+    last_emitted_records: u64,
+    percent: f64,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(
+        data_dir: &Path,
+        logger: Arc<dyn Logger>,
+        every_records: u64,
+        every: Duration,
+    ) -> Self {
+        let now = Instant::now();
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            logger,
+            every_records: every_records.max(1),
+            every,
+            phase: RecoveryPhase::WalReplay,
+            phase_started: now,
+            last_emitted: now,
+            last_emitted_records: 0,
+            percent: 0.0,
+        }
+    }
+
+    /// Start a phase, reporting it at 0%
+    pub(crate) fn begin(&mut self, phase: RecoveryPhase) {
+        self.phase = phase;
+        self.phase_started = Instant::now();
+        self.last_emitted_records = 0;
+        self.percent = 0.0;
+        self.emit(0, None);
+    }
+
+    /// Note `records` processed and `done` of `total` units (bytes, or
+    /// indexes) complete; reports when due
+    pub(crate) fn advance(&mut self, records: u64, done: u64, total: Option<u64>) {
+        if let Some(total) = total.filter(|&t| t > 0) {
+            // Never goes backwards, nor past 100% before the phase ends
+            let percent = (done as f64 / total as f64 * 100.0).min(100.0);
+            self.percent = self.percent.max(percent);
+        }
+        if records.saturating_sub(self.last_emitted_records) >= self.every_records
+            || self.last_emitted.elapsed() >= self.every
+        {
+            self.emit(records, Some(self.phase_started.elapsed()));
+        }
+    }
+
+    /// End the current phase, reporting it at 100%
+    pub(crate) fn finish(&mut self, records: u64) {
+        self.percent = 100.0;
+        self.emit(records, Some(self.phase_started.elapsed()));
+    }
+
+    /// Report recovery complete
+    pub(crate) fn complete(&mut self) {
+        self.begin(RecoveryPhase::Complete);
+        self.finish(0);
+    }
+
+    fn emit(&mut self, records: u64, elapsed: Option<Duration>) {
+        let secs = elapsed.map(|e| e.as_secs_f64()).unwrap_or(0.0);
+        let records_per_sec = if secs > 0.0 {
+            records as f64 / secs
+        } else {
+            0.0
+        };
+        let eta_secs = (self.percent > 0.0 && secs > 0.0)
+            .then(|| (secs * (100.0 - self.percent) / self.percent).round() as u64);
+        let progress = RecoveryProgress {
+            phase: self.phase,
+            percent: (self.percent * 10.0).round() / 10.0,
+            records,
+            records_per_sec: records_per_sec.round(),
+            eta_secs,
+            updated_at: Utc::now(),
+        };
+
+        let eta = eta_secs.map(|s| s.to_string()).unwrap_or_default();
+        self.logger.info(
+            Event::RecoveryProgress.as_str(),
+            &[
+                ("phase", self.phase.as_str()),
+                ("percent", &progress.percent.to_string()),
+                ("records", &records.to_string()),
+                ("records_per_sec", &progress.records_per_sec.to_string()),
+                ("eta_secs", &eta),
+            ],
+        );
+        self.store(&progress);
+        self.last_emitted = Instant::now();
+        self.last_emitted_records = records;
+    }
+
+    /// Replace the progress file. Advisory: failures are ignored.
+    fn store(&self, progress: &RecoveryProgress) {
+        let path = recovery_progress_path(&self.data_dir);
+        let temp_path = path.with_extension("tmp");
+        let _ = serde_json::to_vec(progress)
+            .map_err(io::Error::other)
+            .and_then(|json| {
+                fs::create_dir_all(&self.data_dir)?;
+                fs::write(&temp_path, json)
+            })
+            .and_then(|()| fs::rename(&temp_path, &path));
+    }
+}
+
+/// How far an unfinished recovery got
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RecoveryResume {
+    /// Last WAL sequence replayed into storage
+    pub(crate) replayed_through: u64,
+    /// Indexes whose rebuild completed, in rebuild order
+    #[serde(default)]
+    pub(crate) indexes_rebuilt: Vec<String>,
+}
+
+impl RecoveryResume {
+    fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(RECOVERY_RESUME_FILE)
+    }
+
+    /// The marker left by an interrupted recovery, if any
+    pub(crate) fn load(data_dir: &Path) -> RecoveryResult<Option<Self>> {
+        let path = Self::path(data_dir);
+        match fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents).map(Some).map_err(|e| {
+                RecoveryError::recovery_failed(format!(
+                    "Invalid recovery resume marker {}: {}",
+                    path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(RecoveryError::recovery_failed(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Durably replace the marker: a later recovery trusts it to skip work
+    pub(crate) fn store(&self, data_dir: &Path) -> RecoveryResult<()> {
+        let path = Self::path(data_dir);
+        let temp_path = path.with_extension("tmp");
+        let write = || -> io::Result<()> {
+            fs::create_dir_all(data_dir)?;
+            let json = serde_json::to_vec(self).map_err(io::Error::other)?;
+            let mut file = File::create(&temp_path)?;
+            file.write_all(&json)?;
+            file.sync_all()?;
+            fs::rename(&temp_path, &path)?;
+            File::open(data_dir)?.sync_all()
+        };
+        write().map_err(|e| {
+            RecoveryError::recovery_failed(format!("Failed to write {}: {}", path.display(), e))
+        })
+    }
+
+    /// Remove the marker once recovery has completed
+    pub(crate) fn clear(data_dir: &Path) -> RecoveryResult<()> {
+        let path = Self::path(data_dir);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(RecoveryError::recovery_failed(format!(
+                "Failed to remove {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+}
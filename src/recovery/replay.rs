@@ -3,19 +3,90 @@
 //! Replays WAL records sequentially from byte 0 to restore state.
 //!
 //! Per WAL.md:
-//! - Must start at byte 0 (no checkpoints in Phase 0)
+//! - Must start at byte 0
 //! - Must read sequentially
 //! - Must validate checksum for every record
 //! - On ANY corruption: FATAL error, abort immediately
+//!
+//! The one opt-in exception is [`CorruptTailPolicy::TruncateAndContinue`]:
+//! a damaged record that ends the WAL is cut off and replay stops after the
+//! last good record. Corruption followed by more WAL is always fatal.
+//!
+//! After a fenced checkpoint, records at or below the checkpoint LSN are
+//! still read and validated but not applied: storage already reflects them.
+//!
+//! # Parallel replay
+//!
+//! Storage that can apply collections independently offers a
+//! [`ConcurrentApply`] handle. Replay then reads records in order on one
+//! thread and hands each to one of N workers by collection, so records of
+//! a collection are applied in WAL order and different collections in
+//! parallel. Records not bound to one collection (MVCC commits, versions
+//! and GC) are barriers: every worker drains before they are applied. The
+//! resulting state is the one sequential replay produces.
+//!
+//! Storage that lays every record out in one sequence, like
+//! `documents.dat`, learns the WAL order through
+//! [`ConcurrentApply::schedule`]: workers encode records in parallel and
+//! the storage appends them in that order.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::Mutex;
+use std::thread;
 
-use crate::wal::{RecordType, WalPayload, WalRecord};
+use crate::wal::{DiscardedTail, RecordType, WalRecord};
 
 use super::errors::{RecoveryError, RecoveryResult};
 
+/// Records queued per replay worker before the reader waits
+const WORKER_QUEUE_RECORDS: usize = 1024;
+
+/// Most replay workers used by default, however many cores there are
+pub const MAX_DEFAULT_REPLAY_PARALLELISM: usize = 8;
+
+/// Replay workers used by default: the available cores, at most
+/// [`MAX_DEFAULT_REPLAY_PARALLELISM`]
+pub fn default_replay_parallelism() -> usize {
+    thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_DEFAULT_REPLAY_PARALLELISM)
+}
+
 /// Trait for applying WAL records to storage
 pub trait StorageApply {
     /// Apply a WAL record to storage
     fn apply_wal_record(&mut self, record: &WalRecord) -> RecoveryResult<()>;
+
+    /// A handle applying records from several threads, if this storage can
+    /// be replayed in parallel.
+    ///
+    /// `None` (the default) keeps replay sequential. Storage must return
+    /// `None` when records of different collections have to be applied in
+    /// WAL order, e.g. when it enforces references between collections, or
+    /// when concurrent application would lay out its state differently.
+    fn concurrent(&self) -> Option<&dyn ConcurrentApply> {
+        None
+    }
+}
+
+/// Storage applying WAL records from several threads at once
+///
+/// Replay calls it concurrently for records of different collections, and
+/// in WAL order, one at a time, for records of the same collection. Barrier
+/// records are applied while no other call is running.
+pub trait ConcurrentApply: Sync {
+    /// Apply a WAL record to storage
+    fn apply_concurrent(&self, record: &WalRecord) -> RecoveryResult<()>;
+
+    /// Called on the reading thread, in WAL order, for every record about
+    /// to be applied, before it is handed to [`Self::apply_concurrent`].
+    ///
+    /// Storage laying records out in one sequence uses it to learn the
+    /// order to write them in; the default does nothing.
+    fn schedule(&self, _record: &WalRecord) {}
 }
 
 /// Trait for reading WAL records
@@ -30,6 +101,34 @@ pub trait WalRead {
 
     /// Reset to beginning of WAL
     fn reset(&mut self) -> RecoveryResult<()>;
+
+    /// Total size of the WAL in bytes, if known
+    fn total_bytes(&self) -> Option<u64> {
+        None
+    }
+
+    /// Bytes of the WAL read so far
+    fn bytes_read(&self) -> u64 {
+        self.current_offset()
+    }
+
+    /// After `read_next` failed, cut the damaged record off the end of the
+    /// WAL if nothing follows it.
+    ///
+    /// Returns `None` if the failure was not at the tail of the WAL.
+    fn discard_corrupt_tail(&mut self) -> RecoveryResult<Option<DiscardedTail>> {
+        Ok(None)
+    }
+}
+
+/// What replay does with a damaged record at the end of the WAL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CorruptTailPolicy {
+    /// Halt recovery, as for any other corruption
+    #[default]
+    Fail,
+    /// Discard the damaged record and continue from the last good one
+    TruncateAndContinue,
 }
 
 /// Statistics from WAL replay
@@ -37,6 +136,8 @@ pub trait WalRead {
 pub struct ReplayStats {
     /// Number of records replayed
     pub records_replayed: u64,
+    /// Number of records skipped as covered by the checkpoint
+    pub records_skipped: u64,
     /// Number of inserts
     pub inserts: u64,
     /// Number of updates
@@ -49,10 +150,14 @@ pub struct ReplayStats {
     pub mvcc_versions: u64,
     /// Number of MVCC garbage collection events
     pub mvcc_gc: u64,
+    /// Number of collection renames and copies
+    pub collection_changes: u64,
     /// Final WAL offset
     pub final_offset: u64,
     /// Final sequence number
     pub final_sequence: u64,
+    /// Damaged tail cut off under `CorruptTailPolicy::TruncateAndContinue`
+    pub discarded_tail: Option<DiscardedTail>,
 }
 
 /// WAL replayer that processes WAL records sequentially
@@ -73,50 +178,271 @@ impl WalReplayer {
         wal: &mut W,
         storage: &mut S,
     ) -> RecoveryResult<ReplayStats> {
+        Self::replay_with_policy(wal, storage, CorruptTailPolicy::Fail)
+    }
+
+    /// Replay all WAL records to storage, handling a damaged record at the
+    /// end of the WAL according to `on_corrupt_tail`.
+    pub fn replay_with_policy<W: WalRead, S: StorageApply>(
+        wal: &mut W,
+        storage: &mut S,
+        on_corrupt_tail: CorruptTailPolicy,
+    ) -> RecoveryResult<ReplayStats> {
+        Self::replay_after(wal, storage, on_corrupt_tail, 0)
+    }
+
+    /// Replay the WAL records after `checkpoint_sequence` to storage.
+    ///
+    /// Records at or below the checkpoint LSN are validated like any other
+    /// but not applied, since storage already reflects them. With a
+    /// checkpoint LSN of 0 this is a full replay.
+    pub fn replay_after<W: WalRead, S: StorageApply>(
+        wal: &mut W,
+        storage: &mut S,
+        on_corrupt_tail: CorruptTailPolicy,
+        checkpoint_sequence: u64,
+    ) -> RecoveryResult<ReplayStats> {
+        Self::replay_observed(
+            wal,
+            storage,
+            on_corrupt_tail,
+            checkpoint_sequence,
+            |_, _| {},
+        )
+    }
+
+    /// Replay as [`replay_after`](Self::replay_after), calling `observe`
+    /// with the statistics so far and the WAL after every record read.
+    pub fn replay_observed<W, S, F>(
+        wal: &mut W,
+        storage: &mut S,
+        on_corrupt_tail: CorruptTailPolicy,
+        checkpoint_sequence: u64,
+        mut observe: F,
+    ) -> RecoveryResult<ReplayStats>
+    where
+        W: WalRead,
+        S: StorageApply,
+        F: FnMut(&ReplayStats, &W),
+    {
         // Reset to beginning of WAL
         wal.reset()?;
 
         let mut stats = ReplayStats::default();
 
-        loop {
-            let offset_before = wal.current_offset();
-
-            // Read next record (checksum validated by reader)
-            let record = match wal.read_next() {
-                Ok(Some(r)) => r,
-                Ok(None) => break, // End of WAL
-                Err(e) => {
-                    // WAL corruption detected - abort immediately
-                    return Err(RecoveryError::wal_corruption(offset_before, e.message()));
-                }
-            };
+        while let Some(record) = Self::read_record(wal, on_corrupt_tail, &mut stats)? {
+            // Covered by the checkpoint: already in storage
+            if record.sequence_number <= checkpoint_sequence {
+                stats.skip(&record);
+                observe(&stats, wal);
+                continue;
+            }
 
             // Apply to storage
             storage.apply_wal_record(&record)?;
-
-            // Update stats based on record type
-            stats.records_replayed += 1;
-            stats.final_sequence = record.sequence_number;
-
-            match record.record_type {
-                RecordType::Insert => stats.inserts += 1,
-                RecordType::Update => stats.updates += 1,
-                RecordType::Delete => stats.deletes += 1,
-                RecordType::MvccCommit => stats.mvcc_commits += 1,
-                RecordType::MvccVersion => stats.mvcc_versions += 1,
-                RecordType::MvccGc => stats.mvcc_gc += 1,
-            }
+            stats.count(&record);
+            observe(&stats, wal);
         }
 
         stats.final_offset = wal.current_offset();
 
         Ok(stats)
     }
+
+    /// Replay as [`replay_observed`](Self::replay_observed), applying
+    /// records on `parallelism` worker threads partitioned by collection.
+    ///
+    /// Records are read, validated and counted in WAL order on the calling
+    /// thread. If applying a record fails, no further records are handed
+    /// out and the failure of the earliest record is returned.
+    pub fn replay_parallel<W, F>(
+        wal: &mut W,
+        storage: &dyn ConcurrentApply,
+        on_corrupt_tail: CorruptTailPolicy,
+        checkpoint_sequence: u64,
+        parallelism: usize,
+        mut observe: F,
+    ) -> RecoveryResult<ReplayStats>
+    where
+        W: WalRead,
+        F: FnMut(&ReplayStats, &W),
+    {
+        wal.reset()?;
+
+        let failed = AtomicBool::new(false);
+        let first_failure: Mutex<Option<(u64, RecoveryError)>> = Mutex::new(None);
+        let fail = |sequence: u64, error: RecoveryError| {
+            let mut first = first_failure.lock().unwrap();
+            if first
+                .as_ref()
+                .is_none_or(|(earliest, _)| sequence < *earliest)
+            {
+                *first = Some((sequence, error));
+            }
+            failed.store(true, Ordering::SeqCst);
+        };
+
+        let result = thread::scope(|scope| {
+            let workers: Vec<SyncSender<ReplayJob>> = (0..parallelism.max(1))
+                .map(|_| {
+                    let (sender, jobs) = mpsc::sync_channel::<ReplayJob>(WORKER_QUEUE_RECORDS);
+                    let (failed, fail) = (&failed, &fail);
+                    scope.spawn(move || {
+                        for job in jobs {
+                            match job {
+                                ReplayJob::Apply(_) if failed.load(Ordering::SeqCst) => {}
+                                ReplayJob::Apply(record) => {
+                                    if let Err(e) = storage.apply_concurrent(&record) {
+                                        fail(record.sequence_number, e);
+                                    }
+                                }
+                                ReplayJob::Drain(done) => {
+                                    let _ = done.send(());
+                                }
+                            }
+                        }
+                    });
+                    sender
+                })
+                .collect();
+
+            let mut stats = ReplayStats::default();
+            while let Some(record) = Self::read_record(wal, on_corrupt_tail, &mut stats)? {
+                if failed.load(Ordering::SeqCst) {
+                    break;
+                }
+                if record.sequence_number <= checkpoint_sequence {
+                    stats.skip(&record);
+                    observe(&stats, wal);
+                    continue;
+                }
+
+                stats.count(&record);
+                storage.schedule(&record);
+                match replay_partition(&record, workers.len()) {
+                    Some(worker) => workers[worker]
+                        .send(ReplayJob::Apply(record))
+                        .map_err(|_| RecoveryError::recovery_failed("Replay worker stopped"))?,
+                    None => {
+                        drain(&workers)?;
+                        if !failed.load(Ordering::SeqCst) {
+                            if let Err(e) = storage.apply_concurrent(&record) {
+                                fail(record.sequence_number, e);
+                            }
+                        }
+                    }
+                }
+                observe(&stats, wal);
+            }
+
+            // Dropping the senders stops the workers once their queues are empty
+            drain(&workers)?;
+            stats.final_offset = wal.current_offset();
+            Ok(stats)
+        });
+
+        // An apply failure precedes anything read after it
+        match first_failure.into_inner().unwrap() {
+            Some((_, error)) => Err(error),
+            None => result,
+        }
+    }
+
+    /// Read the next record, or `None` at the end of the WAL (or after
+    /// discarding a damaged tail, when the policy allows it)
+    fn read_record<W: WalRead>(
+        wal: &mut W,
+        on_corrupt_tail: CorruptTailPolicy,
+        stats: &mut ReplayStats,
+    ) -> RecoveryResult<Option<WalRecord>> {
+        let offset_before = wal.current_offset();
+
+        // Read next record (checksum validated by reader)
+        match wal.read_next() {
+            Ok(record) => Ok(record),
+            Err(e) if on_corrupt_tail == CorruptTailPolicy::TruncateAndContinue => {
+                match wal.discard_corrupt_tail()? {
+                    Some(discarded) => {
+                        stats.discarded_tail = Some(discarded);
+                        Ok(None)
+                    }
+                    None => Err(RecoveryError::wal_corruption(offset_before, e.message())),
+                }
+            }
+            // WAL corruption detected - abort immediately
+            Err(e) => Err(RecoveryError::wal_corruption(offset_before, e.message())),
+        }
+    }
+}
+
+impl ReplayStats {
+    /// Count a record covered by the checkpoint
+    fn skip(&mut self, record: &WalRecord) {
+        self.records_skipped += 1;
+        self.final_sequence = record.sequence_number;
+    }
+
+    /// Count an applied record, by type
+    fn count(&mut self, record: &WalRecord) {
+        self.records_replayed += 1;
+        self.final_sequence = record.sequence_number;
+
+        match record.record_type {
+            RecordType::Insert => self.inserts += 1,
+            RecordType::Update => self.updates += 1,
+            RecordType::Delete => self.deletes += 1,
+            RecordType::MvccCommit => self.mvcc_commits += 1,
+            RecordType::MvccVersion => self.mvcc_versions += 1,
+            RecordType::MvccGc => self.mvcc_gc += 1,
+            RecordType::CollectionRename | RecordType::CollectionCopy => {
+                self.collection_changes += 1
+            }
+        }
+    }
+}
+
+/// Work for a parallel replay worker
+enum ReplayJob {
+    /// Apply a record of one of the worker's collections
+    Apply(WalRecord),
+    /// Signal once every record queued before has been applied
+    Drain(SyncSender<()>),
+}
+
+/// Worker applying `record`, or `None` for a barrier
+///
+/// A collection rename or copy is a barrier: it applies to whatever the
+/// records before it left in storage.
+fn replay_partition(record: &WalRecord, workers: usize) -> Option<usize> {
+    let collection = &record.payload.collection_id;
+    let record_type = record.record_type;
+    if record_type.is_mvcc_record() || record_type.is_collection_change() || collection.is_empty() {
+        return None;
+    }
+    let mut hasher = DefaultHasher::new();
+    collection.hash(&mut hasher);
+    Some((hasher.finish() % workers as u64) as usize)
+}
+
+/// Wait until every worker has applied the records queued to it
+fn drain(workers: &[SyncSender<ReplayJob>]) -> RecoveryResult<()> {
+    let (done, acks) = mpsc::sync_channel(workers.len());
+    for worker in workers {
+        worker
+            .send(ReplayJob::Drain(done.clone()))
+            .map_err(|_| RecoveryError::recovery_failed("Replay worker stopped"))?;
+    }
+    for _ in workers {
+        acks.recv()
+            .map_err(|_| RecoveryError::recovery_failed("Replay worker stopped"))?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wal::WalPayload;
 
     struct MockWal {
         records: Vec<WalRecord>,
@@ -221,6 +547,27 @@ mod tests {
         assert_eq!(storage.applied.len(), 3);
     }
 
+    #[test]
+    fn test_replay_after_checkpoint_skips_covered_records() {
+        let records = vec![
+            make_insert_record(1, "user_1"),
+            make_insert_record(2, "user_2"),
+            make_delete_record(3, "user_1"),
+        ];
+
+        let mut wal = MockWal::new(records);
+        let mut storage = MockStorage::new();
+
+        let stats =
+            WalReplayer::replay_after(&mut wal, &mut storage, CorruptTailPolicy::Fail, 2).unwrap();
+
+        assert_eq!(stats.records_skipped, 2);
+        assert_eq!(stats.records_replayed, 1);
+        assert_eq!(stats.final_sequence, 3);
+        assert_eq!(storage.applied.len(), 1);
+        assert_eq!(storage.applied[0].sequence_number, 3);
+    }
+
     #[test]
     fn test_replay_idempotency() {
         let records = vec![
@@ -275,4 +622,145 @@ mod tests {
         assert_eq!(stats.records_replayed, 0);
         assert_eq!(storage.applied.len(), 0);
     }
+
+    /// Storage keeping an append-only log per collection
+    #[derive(Default)]
+    struct CollectionLogs {
+        logs: Mutex<std::collections::BTreeMap<String, Vec<u8>>>,
+        fail_at: Option<u64>,
+    }
+
+    impl ConcurrentApply for CollectionLogs {
+        fn apply_concurrent(&self, record: &WalRecord) -> RecoveryResult<()> {
+            if self.fail_at == Some(record.sequence_number) {
+                return Err(RecoveryError::recovery_failed("disk full"));
+            }
+            thread::yield_now();
+            let mut logs = self.logs.lock().unwrap();
+            if record.record_type.is_mvcc_record() {
+                // A barrier records what every collection held when it ran
+                let sizes: Vec<u8> = logs
+                    .values()
+                    .flat_map(|log| log.len().to_le_bytes())
+                    .collect();
+                let barriers = logs.entry("_barriers".to_string()).or_default();
+                barriers.extend(sizes);
+                barriers.extend(record.serialize());
+            } else {
+                let log = logs
+                    .entry(record.payload.collection_id.clone())
+                    .or_default();
+                log.extend(record.serialize());
+            }
+            Ok(())
+        }
+    }
+
+    impl StorageApply for CollectionLogs {
+        fn apply_wal_record(&mut self, record: &WalRecord) -> RecoveryResult<()> {
+            self.apply_concurrent(record)
+        }
+
+        fn concurrent(&self) -> Option<&dyn ConcurrentApply> {
+            Some(self)
+        }
+    }
+
+    fn mixed_workload(records: u64) -> Vec<WalRecord> {
+        let collections = ["users", "orders", "items", "events", "carts"];
+        (1..=records)
+            .map(|seq| {
+                let collection = collections[(seq * 7 % 5) as usize];
+                let id = format!("doc_{}", seq % 40);
+                let body = format!("{{\"v\":{}}}", seq).into_bytes();
+                match seq % 10 {
+                    0 => WalRecord::new(
+                        RecordType::MvccCommit,
+                        seq,
+                        WalPayload::new("", "", "", "", seq.to_le_bytes().to_vec()),
+                    ),
+                    3 | 6 => WalRecord::update(
+                        seq,
+                        WalPayload::new(collection, id, collection, "v1", body),
+                    ),
+                    9 => WalRecord::delete(
+                        seq,
+                        WalPayload::tombstone(collection, id, collection, "v1"),
+                    ),
+                    _ => WalRecord::insert(
+                        seq,
+                        WalPayload::new(collection, id, collection, "v1", body),
+                    ),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_replay_matches_sequential() {
+        let records = mixed_workload(3_000);
+
+        let mut sequential = CollectionLogs::default();
+        let seq_stats =
+            WalReplayer::replay(&mut MockWal::new(records.clone()), &mut sequential).unwrap();
+
+        let parallel = CollectionLogs::default();
+        let mut observed = 0;
+        let par_stats = WalReplayer::replay_parallel(
+            &mut MockWal::new(records),
+            &parallel,
+            CorruptTailPolicy::Fail,
+            0,
+            4,
+            |_, _| observed += 1,
+        )
+        .unwrap();
+
+        let sequential = sequential.logs.into_inner().unwrap();
+        assert_eq!(sequential.len(), 6);
+        assert!(sequential == parallel.logs.into_inner().unwrap());
+
+        assert_eq!(observed, 3_000);
+        assert_eq!(par_stats.records_skipped, 0);
+        assert_eq!(par_stats.records_replayed, seq_stats.records_replayed);
+        assert_eq!(par_stats.inserts, seq_stats.inserts);
+        assert_eq!(par_stats.updates, seq_stats.updates);
+        assert_eq!(par_stats.deletes, seq_stats.deletes);
+        assert_eq!(par_stats.mvcc_commits, 300);
+        assert_eq!(par_stats.final_sequence, 3_000);
+        assert_eq!(par_stats.final_offset, seq_stats.final_offset);
+    }
+
+    #[test]
+    fn test_parallel_replay_reports_earliest_failure() {
+        let storage = CollectionLogs {
+            fail_at: Some(42),
+            ..CollectionLogs::default()
+        };
+        let result = WalReplayer::replay_parallel(
+            &mut MockWal::new(mixed_workload(1_000)),
+            &storage,
+            CorruptTailPolicy::Fail,
+            0,
+            4,
+            |_, _| {},
+        );
+
+        assert_eq!(result.unwrap_err().message(), "disk full");
+
+        // Everything before the failure was applied, nothing past the next
+        // barrier (sequence 50) was
+        let mut reference = CollectionLogs::default();
+        let before_barrier = mixed_workload(49)
+            .into_iter()
+            .filter(|r| r.sequence_number != 42)
+            .collect();
+        WalReplayer::replay(&mut MockWal::new(before_barrier), &mut reference).unwrap();
+        let reference = reference.logs.into_inner().unwrap();
+        let logs = storage.logs.into_inner().unwrap();
+        assert_eq!(logs["_barriers"], reference["_barriers"]);
+        for (collection, log) in &logs {
+            assert!(reference[collection].starts_with(log), "{}", collection);
+        }
+    }
 }
@@ -8,25 +8,57 @@
 //! 2. Open WAL reader
 //! 3. Open document storage
 //! 4. Replay WAL from offset 0 sequentially
-//! 5. Apply each WAL record via storage.apply_wal_record
+//! 5. Apply each WAL record after the checkpoint LSN via
+//!    storage.apply_wal_record
 //! 6. After replay completes, call index.rebuild_from_storage
 //! 7. Run consistency verification
 //! 8. Enter serving state
+//!
+//! Each phase reports progress (see the `progress` module). A recovery that
+//! dies after replay resumes at the first index it had not rebuilt.
 
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 use super::errors::{RecoveryError, RecoveryResult};
-use super::replay::{ReplayStats, StorageApply, WalRead, WalReplayer};
+use super::progress::{
+    ProgressReporter, RecoveryPhase, RecoveryResume, DEFAULT_PROGRESS_INTERVAL,
+    DEFAULT_PROGRESS_RECORDS,
+};
+use super::replay::{
+    default_replay_parallelism, CorruptTailPolicy, ReplayStats, StorageApply, WalRead, WalReplayer,
+};
 use super::verifier::{ConsistencyVerifier, SchemaCheck, StorageScan, VerificationStats};
+use crate::checkpoint::checkpoint_sequence;
+use crate::observability::{JsonLogger, Logger};
+use crate::panic_handler::CrashMarker;
 
 /// Clean shutdown marker filename
 const CLEAN_SHUTDOWN_MARKER: &str = "clean_shutdown";
 
 /// Trait for index rebuild
+///
+/// An implementation whose rebuilt indexes survive a restart may list its
+/// collections in `rebuild_collections` and rebuild them one at a time.
+/// Recovery then records each completed collection, so an interrupted
+/// recovery only rebuilds the rest. Otherwise everything is rebuilt with
+/// `rebuild_from_storage` on every recovery.
 pub trait IndexRebuild {
     /// Rebuild indexes from storage
     fn rebuild_from_storage(&mut self) -> RecoveryResult<()>;
+
+    /// Collections whose indexes are rebuilt separately, in rebuild order
+    fn rebuild_collections(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Rebuild one collection's indexes from storage
+    fn rebuild_collection(&mut self, collection: &str) -> RecoveryResult<()> {
+        let _ = collection;
+        self.rebuild_from_storage()
+    }
 }
 
 /// Recovery state after successful startup
@@ -38,11 +70,20 @@ pub struct RecoveryState {
     pub verification_stats: VerificationStats,
     /// Whether clean shutdown marker was present
     pub was_clean_shutdown: bool,
+    /// The panic that stopped the previous run, from its crash marker
+    pub crash_marker: Option<CrashMarker>,
+    /// Collections an interrupted earlier recovery had already rebuilt
+    pub resumed_collections: Vec<String>,
 }
 
 /// Recovery Manager that orchestrates startup
 pub struct RecoveryManager {
     data_dir: PathBuf,
+    on_corrupt_tail: CorruptTailPolicy,
+    logger: Arc<dyn Logger>,
+    progress_records: u64,
+    progress_interval: Duration,
+    replay_parallelism: usize,
 }
 
 impl RecoveryManager {
@@ -50,9 +91,44 @@ impl RecoveryManager {
     pub fn new(data_dir: impl AsRef<Path>) -> Self {
         Self {
             data_dir: data_dir.as_ref().to_path_buf(),
+            on_corrupt_tail: CorruptTailPolicy::Fail,
+            logger: JsonLogger::shared(),
+            progress_records: DEFAULT_PROGRESS_RECORDS,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+            replay_parallelism: default_replay_parallelism(),
         }
     }
 
+    /// Replay on up to `parallelism` threads when the storage supports it
+    ///
+    /// Defaults to [`default_replay_parallelism`]; 1 replays sequentially.
+    pub fn with_replay_parallelism(mut self, parallelism: usize) -> Self {
+        self.replay_parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Send progress events to `logger`
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Report progress every `records` records or `interval`, whichever
+    /// comes first
+    pub fn with_progress_interval(mut self, records: u64, interval: Duration) -> Self {
+        self.progress_records = records;
+        self.progress_interval = interval;
+        self
+    }
+
+    /// Set what replay does with a damaged record at the end of the WAL.
+    ///
+    /// Defaults to `CorruptTailPolicy::Fail`.
+    pub fn on_corrupt_tail(mut self, policy: CorruptTailPolicy) -> Self {
+        self.on_corrupt_tail = policy;
+        self
+    }
+
     /// Returns the path to the clean shutdown marker
     fn marker_path(&self) -> PathBuf {
         self.data_dir.join(CLEAN_SHUTDOWN_MARKER)
@@ -92,14 +168,50 @@ impl RecoveryManager {
         Ok(())
     }
 
+    /// Read the crash marker a panic left, logging that this recovery
+    /// follows an unclean shutdown
+    ///
+    /// An unreadable marker is logged and otherwise ignored.
+    fn detect_crash(&self) -> Option<CrashMarker> {
+        let data_dir = self.data_dir.display().to_string();
+        match CrashMarker::load(&self.data_dir) {
+            Ok(Some(marker)) => {
+                self.logger.warn(
+                    "RECOVERY_AFTER_CRASH",
+                    &[
+                        ("data_dir", &data_dir),
+                        ("panicked_at", &marker.timestamp),
+                        ("panic_message", &marker.message),
+                        ("location", marker.location.as_deref().unwrap_or("unknown")),
+                    ],
+                );
+                Some(marker)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                self.logger.warn(
+                    "RECOVERY_AFTER_CRASH",
+                    &[
+                        ("data_dir", &data_dir),
+                        ("crash_marker_error", &e.to_string()),
+                    ],
+                );
+                None
+            }
+        }
+    }
+
     /// Execute the full recovery sequence.
     ///
     /// Steps (must be exact order):
-    /// 1. Check for clean shutdown marker
-    /// 2. Replay WAL from offset 0
+    /// 1. Check for clean shutdown and crash markers
+    /// 2. Replay WAL after the checkpoint LSN (from offset 0 without one)
     /// 3. Rebuild indexes
     /// 4. Verify consistency
-    /// 5. Remove shutdown marker
+    /// 5. Remove shutdown and crash markers
+    ///
+    /// If an earlier recovery got past step 2, replay skips what it
+    /// replayed and step 3 skips the collections it rebuilt.
     ///
     /// Returns RecoveryState on success, FATAL error on any failure.
     pub fn recover<W, S, I, C>(
@@ -115,25 +227,108 @@ impl RecoveryManager {
         I: IndexRebuild,
         C: SchemaCheck,
     {
-        // Step 1: Check for clean shutdown marker
+        // Step 1: Check for clean shutdown and crash markers
         let was_clean_shutdown = self.was_clean_shutdown();
+        let crash_marker = self.detect_crash();
+        let mut progress = ProgressReporter::new(
+            &self.data_dir,
+            Arc::clone(&self.logger),
+            self.progress_records,
+            self.progress_interval,
+        );
 
-        // Step 2: Replay WAL (always replay in Phase 0, even after clean shutdown)
-        let replay_stats = WalReplayer::replay(wal, storage)?;
+        // Step 2: Replay WAL (always replay, even after clean shutdown),
+        // skipping what the last fenced checkpoint, or an interrupted
+        // recovery, put in storage
+        let checkpoint_sequence = checkpoint_sequence(&self.data_dir).map_err(|e| {
+            RecoveryError::recovery_failed(format!("Failed to read checkpoint marker: {}", e))
+        })?;
+        let resume = RecoveryResume::load(&self.data_dir)?;
+        let replayed_through = resume.as_ref().map_or(0, |r| r.replayed_through);
+        progress.begin(RecoveryPhase::WalReplay);
+        let total_bytes = wal.total_bytes();
+        let skip_through = checkpoint_sequence.unwrap_or(0).max(replayed_through);
+        let observe = |stats: &ReplayStats, wal: &W| {
+            let records = stats.records_replayed + stats.records_skipped;
+            progress.advance(records, wal.bytes_read(), total_bytes);
+        };
+        let concurrent = storage.concurrent().filter(|_| self.replay_parallelism > 1);
+        let replay_stats = match concurrent {
+            Some(shared) => WalReplayer::replay_parallel(
+                wal,
+                shared,
+                self.on_corrupt_tail,
+                skip_through,
+                self.replay_parallelism,
+                observe,
+            )?,
+            None => WalReplayer::replay_observed(
+                wal,
+                storage,
+                self.on_corrupt_tail,
+                skip_through,
+                observe,
+            )?,
+        };
+        progress.finish(replay_stats.records_replayed + replay_stats.records_skipped);
+        if let Some(discarded) = &replay_stats.discarded_tail {
+            self.logger.warn(
+                "RECOVERY_DISCARDED_WAL_TAIL",
+                &[
+                    ("path", &discarded.path.display().to_string()),
+                    ("start", &discarded.range.start.to_string()),
+                    ("end", &discarded.range.end.to_string()),
+                    ("after_sequence", &replay_stats.final_sequence.to_string()),
+                ],
+            );
+        }
+
+        // Storage now holds the whole WAL: a restart need not replay it
+        let mut resume = resume.unwrap_or_default();
+        resume.replayed_through = replay_stats.final_sequence.max(replayed_through);
+        resume.store(&self.data_dir)?;
 
         // Step 3: Rebuild indexes from storage
-        index.rebuild_from_storage()?;
+        progress.begin(RecoveryPhase::IndexRebuild);
+        let collections = index.rebuild_collections();
+        let resumed_collections: Vec<String> = collections
+            .iter()
+            .filter(|c| resume.indexes_rebuilt.contains(c))
+            .cloned()
+            .collect();
+        if collections.is_empty() {
+            index.rebuild_from_storage()?;
+        }
+        for (done, collection) in collections.iter().enumerate() {
+            if !resume.indexes_rebuilt.contains(collection) {
+                index.rebuild_collection(collection)?;
+                resume.indexes_rebuilt.push(collection.clone());
+                resume.store(&self.data_dir)?;
+            }
+            let done = done as u64 + 1;
+            progress.advance(done, done, Some(collections.len() as u64));
+        }
+        progress.finish(collections.len() as u64);
 
         // Step 4: Verify consistency
+        progress.begin(RecoveryPhase::Verification);
         let verification_stats = ConsistencyVerifier::verify(storage, schema_registry)?;
+        progress.finish(verification_stats.records_verified);
 
-        // Step 5: Remove shutdown marker
+        // Step 5: Remove shutdown and crash markers
         self.remove_shutdown_marker()?;
+        CrashMarker::remove(&self.data_dir).map_err(|e| {
+            RecoveryError::recovery_failed(format!("Failed to remove crash marker: {}", e))
+        })?;
+        RecoveryResume::clear(&self.data_dir)?;
+        progress.complete();
 
         Ok(RecoveryState {
             replay_stats,
             verification_stats,
             was_clean_shutdown,
+            crash_marker,
+            resumed_collections,
         })
     }
 }
@@ -141,7 +336,7 @@ impl RecoveryManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::wal::{RecordType, WalPayload, WalRecord};
+    use crate::wal::{RecordType, WalPayload, WalReader, WalRecord, WalWriter};
     use std::collections::HashSet;
     use tempfile::TempDir;
 
@@ -179,6 +374,10 @@ mod tests {
             self.position = 0;
             Ok(())
         }
+
+        fn total_bytes(&self) -> Option<u64> {
+            Some(self.records.len() as u64 * 100)
+        }
     }
 
     struct MockStorage {
@@ -252,6 +451,31 @@ mod tests {
         }
     }
 
+    /// Indexes that survive a restart, rebuilt per collection
+    struct PersistentIndex {
+        collections: Vec<String>,
+        rebuilt: Vec<String>,
+        crash_on: Option<String>,
+    }
+
+    impl IndexRebuild for PersistentIndex {
+        fn rebuild_from_storage(&mut self) -> RecoveryResult<()> {
+            unreachable!("rebuilt per collection")
+        }
+
+        fn rebuild_collections(&self) -> Vec<String> {
+            self.collections.clone()
+        }
+
+        fn rebuild_collection(&mut self, collection: &str) -> RecoveryResult<()> {
+            if self.crash_on.as_deref() == Some(collection) {
+                return Err(RecoveryError::recovery_failed("crashed"));
+            }
+            self.rebuilt.push(collection.to_string());
+            Ok(())
+        }
+    }
+
     struct MockSchemaRegistry {
         schemas: HashSet<(String, String)>,
     }
@@ -306,6 +530,51 @@ mod tests {
         assert_eq!(state.verification_stats.live_documents, 2);
     }
 
+    #[test]
+    fn test_corrupt_tail_policy() {
+        use crate::observability::VecLogger;
+
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let mut writer = WalWriter::open(temp_dir.path()).unwrap();
+            for id in ["user_1", "user_2"] {
+                writer
+                    .append_insert(WalPayload::new("users", id, "users", "v1", b"{}".to_vec()))
+                    .unwrap();
+            }
+        }
+        let wal_path = temp_dir.path().join("wal").join("wal.log");
+        let mut contents = fs::read(&wal_path).unwrap();
+        let last = contents.len() - 1;
+        contents[last] ^= 0xFF;
+        fs::write(&wal_path, contents).unwrap();
+
+        let recover = |manager: &RecoveryManager| {
+            let mut wal = WalReader::open(&wal_path).unwrap();
+            let mut storage = MockStorage::new();
+            let mut index = MockIndex::new();
+            manager.recover(
+                &mut wal,
+                &mut storage,
+                &mut index,
+                &MockSchemaRegistry::new(),
+            )
+        };
+
+        assert!(recover(&RecoveryManager::new(temp_dir.path())).is_err());
+        let logger = Arc::new(VecLogger::new());
+        let manager = RecoveryManager::new(temp_dir.path())
+            .on_corrupt_tail(CorruptTailPolicy::TruncateAndContinue)
+            .with_logger(logger.clone());
+        let state = recover(&manager).unwrap();
+        assert_eq!(state.replay_stats.records_replayed, 1);
+        assert!(state.replay_stats.discarded_tail.is_some());
+        assert!(logger
+            .events()
+            .contains(&"RECOVERY_DISCARDED_WAL_TAIL".to_string()));
+        assert_eq!(state.verification_stats.live_documents, 1);
+    }
+
     #[test]
     fn test_index_rebuilt_after_replay() {
         let temp_dir = TempDir::new().unwrap();
@@ -325,6 +594,125 @@ mod tests {
         assert!(index.rebuild_called);
     }
 
+    #[test]
+    fn test_progress_increases_monotonically() {
+        use crate::observability::VecLogger;
+        use crate::recovery::{RecoveryPhase, RecoveryProgress};
+
+        let temp_dir = TempDir::new().unwrap();
+        let logger = Arc::new(VecLogger::new());
+        let manager = RecoveryManager::new(temp_dir.path())
+            .with_logger(logger.clone())
+            .with_progress_interval(500, Duration::from_secs(3600));
+
+        let records = (1..=5_000)
+            .map(|seq| make_insert_record(seq, &format!("user_{}", seq)))
+            .collect();
+        let mut wal = MockWal::new(records);
+        let mut storage = MockStorage::new();
+        manager
+            .recover(
+                &mut wal,
+                &mut storage,
+                &mut MockIndex::new(),
+                &MockSchemaRegistry::new(),
+            )
+            .unwrap();
+
+        let phases = ["wal_replay", "index_rebuild", "verification", "complete"];
+        let events: Vec<(usize, f64, u64)> = logger
+            .records()
+            .iter()
+            .filter(|r| r.event == "RECOVERY_PROGRESS")
+            .map(|r| {
+                let phase = phases.iter().position(|p| Some(*p) == r.field("phase"));
+                let percent = r.field("percent").unwrap().parse().unwrap();
+                (
+                    phase.unwrap(),
+                    percent,
+                    r.field("records").unwrap().parse().unwrap(),
+                )
+            })
+            .collect();
+
+        // Replay reports every 500 records, and nothing ever goes backwards
+        let replay: Vec<_> = events.iter().filter(|e| e.0 == 0).collect();
+        assert!(replay.len() >= 10);
+        assert!(replay.iter().any(|e| e.1 > 0.0 && e.1 < 100.0));
+        for pair in events.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert!(
+                b.0 > a.0 || (b.0 == a.0 && b.1 >= a.1 && b.2 >= a.2),
+                "{:?}",
+                pair
+            );
+        }
+        assert_eq!(events.last().unwrap().0, 3);
+
+        let progress = RecoveryProgress::load(temp_dir.path()).unwrap();
+        assert_eq!(progress.phase, RecoveryPhase::Complete);
+        assert!(!progress.is_recovering());
+    }
+
+    #[test]
+    fn test_interrupted_index_rebuild_resumes() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = RecoveryManager::new(temp_dir.path());
+        let records = vec![
+            make_insert_record(1, "user_1"),
+            make_insert_record(2, "user_2"),
+        ];
+        let index = |crash_on: Option<&str>| PersistentIndex {
+            collections: vec!["users".to_string(), "orders".to_string()],
+            rebuilt: Vec::new(),
+            crash_on: crash_on.map(str::to_string),
+        };
+
+        // The process dies rebuilding the second collection
+        let mut crashed = index(Some("orders"));
+        let result = manager.recover(
+            &mut MockWal::new(records.clone()),
+            &mut MockStorage::new(),
+            &mut crashed,
+            &MockSchemaRegistry::new(),
+        );
+        assert!(result.is_err());
+        assert_eq!(crashed.rebuilt, vec!["users"]);
+        let progress = crate::recovery::RecoveryProgress::load(temp_dir.path()).unwrap();
+        assert!(progress.is_recovering());
+
+        // The restart neither replays the WAL nor rebuilds `users` again
+        let mut restarted = index(None);
+        let mut storage = MockStorage::new();
+        let state = manager
+            .recover(
+                &mut MockWal::new(records.clone()),
+                &mut storage,
+                &mut restarted,
+                &MockSchemaRegistry::new(),
+            )
+            .unwrap();
+        assert!(storage.applied_records.is_empty());
+        assert_eq!(state.replay_stats.records_skipped, 2);
+        assert_eq!(restarted.rebuilt, vec!["orders"]);
+        assert_eq!(state.resumed_collections, vec!["users"]);
+
+        // A completed recovery leaves nothing to resume
+        let mut storage = MockStorage::new();
+        let mut again = index(None);
+        let state = manager
+            .recover(
+                &mut MockWal::new(records),
+                &mut storage,
+                &mut again,
+                &MockSchemaRegistry::new(),
+            )
+            .unwrap();
+        assert_eq!(state.replay_stats.records_replayed, 2);
+        assert_eq!(again.rebuilt, vec!["users", "orders"]);
+        assert!(state.resumed_collections.is_empty());
+    }
+
     #[test]
     fn test_clean_shutdown_marker() {
         let temp_dir = TempDir::new().unwrap();
@@ -351,6 +739,41 @@ mod tests {
         assert!(!manager.was_clean_shutdown()); // Marker removed
     }
 
+    #[test]
+    fn test_crash_marker_detected_and_removed() {
+        use crate::observability::VecLogger;
+
+        let temp_dir = TempDir::new().unwrap();
+        let marker = CrashMarker {
+            timestamp: "2026-01-01T00:00:00+00:00".to_string(),
+            message: "index out of bounds".to_string(),
+            location: Some("src/api/handler.rs:1:1".to_string()),
+            backtrace: "frame".to_string(),
+        };
+        marker.store(temp_dir.path()).unwrap();
+
+        let logger = Arc::new(VecLogger::new());
+        let manager = RecoveryManager::new(temp_dir.path()).with_logger(logger.clone());
+        let state = manager
+            .recover(
+                &mut MockWal::new(vec![]),
+                &mut MockStorage::new(),
+                &mut MockIndex::new(),
+                &MockSchemaRegistry::new(),
+            )
+            .unwrap();
+
+        assert!(!state.was_clean_shutdown);
+        assert_eq!(state.crash_marker, Some(marker));
+        let logged = logger
+            .records()
+            .into_iter()
+            .find(|r| r.event == "RECOVERY_AFTER_CRASH")
+            .unwrap();
+        assert_eq!(logged.field("panic_message"), Some("index out of bounds"));
+        assert!(CrashMarker::load(temp_dir.path()).unwrap().is_none());
+    }
+
     #[test]
     fn test_replay_restores_documents() {
         let temp_dir = TempDir::new().unwrap();
@@ -6,6 +6,12 @@
 //! - Scan storage sequentially
 //! - Validate checksum on every record
 //! - Ensure no invalid schema references exist
+//!
+//! Only each document's latest record is checked against the schemas: a
+//! record superseded by a later one, e.g. one a collection rename moved,
+//! may name a schema since removed.
+
+use std::collections::HashMap;
 
 use super::errors::{RecoveryError, RecoveryResult};
 
@@ -48,7 +54,7 @@ pub trait StorageScan {
 pub struct VerificationStats {
     /// Number of records verified
     pub records_verified: u64,
-    /// Number of tombstones
+    /// Number of documents whose latest record is a tombstone
     pub tombstones: u64,
     /// Number of live documents
     pub live_documents: u64,
@@ -63,7 +69,7 @@ impl ConsistencyVerifier {
     /// This method:
     /// 1. Scans storage sequentially
     /// 2. Validates checksum on every record
-    /// 3. Ensures no invalid schema references exist
+    /// 3. Ensures no live document references an invalid schema
     ///
     /// Returns FATAL error on any corruption or invalid reference.
     pub fn verify<S: StorageScan, C: SchemaCheck>(
@@ -73,6 +79,7 @@ impl ConsistencyVerifier {
         storage.reset()?;
 
         let mut stats = VerificationStats::default();
+        let mut latest = HashMap::new();
 
         loop {
             // Read next record (checksum validated by scanner)
@@ -89,7 +96,12 @@ impl ConsistencyVerifier {
             };
 
             stats.records_verified += 1;
+            latest.insert(record.document_id.clone(), record);
+        }
 
+        let mut latest: Vec<StorageRecordInfo> = latest.into_values().collect();
+        latest.sort_by_key(|record| record.offset);
+        for record in latest {
             if record.is_tombstone {
                 stats.tombstones += 1;
                 continue;
@@ -264,6 +276,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_superseded_record_of_removed_schema_passes() {
+        // user_1 was moved from `orders`, whose schema is gone
+        let records = vec![
+            make_record("user_1", "orders", "v1", 0),
+            make_record("user_1", "users", "v1", 100),
+            make_record("user_2", "orders", "v1", 200),
+            make_tombstone("user_2", 300),
+        ];
+
+        let mut storage = MockStorage::new(records);
+        let schema = MockSchemaRegistry::new();
+
+        let stats = ConsistencyVerifier::verify(&mut storage, &schema).unwrap();
+
+        assert_eq!(stats.records_verified, 4);
+        assert_eq!(stats.live_documents, 1);
+        assert_eq!(stats.tombstones, 1);
+    }
+
     #[test]
     fn test_empty_storage() {
         let mut storage = MockStorage::new(vec![]);
@@ -0,0 +1,829 @@
+//! Replica Bootstrap
+//!
+//! Seeds an empty Replica from a base snapshot of its Primary, so a new
+//! Replica need not replay the Primary's whole history (nor can it, once a
+//! checkpoint has truncated the WAL).
+//!
+//! Replica side ([`ReplicaBootstrap`]):
+//! 1. Durably marks the data directory incomplete
+//!    (`data_dir/system/bootstrap.json`) and clears whatever an earlier,
+//!    failed attempt left behind
+//! 2. Authenticates like a streaming Replica and asks for a base snapshot
+//! 3. Receives the snapshot files into `data_dir/system/bootstrap/`,
+//!    reporting progress, and verifies each file's checksum and the
+//!    snapshot's manifest
+//! 4. Installs storage and schemas, starts its WAL just after the
+//!    snapshot's sequence and stores that sequence as its applied offset
+//! 5. Removes the marker
+//!
+//! The `WalFollower` then streams the WAL after the snapshot as usual.
+//!
+//! Primary side (served by the `WalStreamer`): a [`BaseSnapshotSource`]
+//! names a completed snapshot whose manifest records a WAL fence, either
+//! taken on demand or the latest one already in the data directory. Before
+//! sending it, the Primary pins the WAL after the fence in its replica
+//! progress until the Replica starts streaming or `bootstrap_pin` elapses,
+//! then checks that WAL is still there.
+//!
+//! A failure at any step leaves the marker in place, so the next attempt
+//! starts again from an empty directory.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::TcpStream;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::errors::{ReplicationError, ReplicationResult};
+use super::transport::{
+    handshake_proof, read_frame, transport_error, write_frame, ControlMessage, Frame,
+    ReplicationStreamConfig, SnapshotFile,
+};
+use super::wal_follower::{replication_offset_path, AppliedOffset};
+use crate::observability::{Event, JsonLogger, Logger};
+use crate::snapshot::{
+    compute_file_checksum, format_checksum, snapshot_path, SnapshotError, SnapshotId,
+    SnapshotManager, SnapshotManifest,
+};
+use crate::wal::{detect_layout, write_base_sequence, write_epoch, WalLayout};
+
+/// Bytes of a snapshot file per chunk frame
+const CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Longest time between progress events, by default
+const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Path of the marker of an unfinished bootstrap
+pub fn bootstrap_marker_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("system").join("bootstrap.json")
+}
+
+/// Directory a Replica receives snapshot files into
+fn staging_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("system").join("bootstrap")
+}
+
+/// Names the snapshot a bootstrapping Replica starts from
+pub trait BaseSnapshotSource: Send + Sync + fmt::Debug {
+    /// Id of a completed snapshot in the Primary's data directory whose
+    /// manifest records a WAL fence.
+    fn base_snapshot(&self) -> ReplicationResult<SnapshotId>;
+}
+
+/// The newest fenced snapshot already in a data directory, e.g. the one a
+/// checkpoint or backup took
+#[derive(Debug, Clone)]
+pub struct LatestSnapshot {
+    data_dir: PathBuf,
+}
+
+impl LatestSnapshot {
+    /// Use the snapshots of `data_dir`.
+    pub fn new(data_dir: impl AsRef<Path>) -> Self {
+        Self {
+            data_dir: data_dir.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl BaseSnapshotSource for LatestSnapshot {
+    fn base_snapshot(&self) -> ReplicationResult<SnapshotId> {
+        let ids = SnapshotManager::list_snapshots(&self.data_dir).map_err(snapshot_error)?;
+        for id in ids.into_iter().rev() {
+            let manifest_path = snapshot_path(&self.data_dir, &id).join("manifest.json");
+            let manifest =
+                SnapshotManifest::read_from_file(&manifest_path).map_err(snapshot_error)?;
+            if manifest.fence.is_some() {
+                return Ok(id);
+            }
+        }
+        Err(ReplicationError::bootstrap_failed(format!(
+            "No snapshot with a WAL fence in {} to bootstrap a replica from",
+            self.data_dir.display()
+        )))
+    }
+}
+
+/// A base snapshot ready to send to a Replica
+#[derive(Debug)]
+pub(crate) struct PreparedSnapshot {
+    /// Snapshot id
+    pub(crate) snapshot_id: SnapshotId,
+
+    /// Last WAL sequence the snapshot reflects
+    pub(crate) wal_sequence: u64,
+
+    /// Files to send, in order
+    pub(crate) files: Vec<SnapshotFile>,
+
+    /// Directory holding the files
+    dir: PathBuf,
+
+    /// Directory an incremental snapshot was materialized in, removed on drop
+    scratch: Option<PathBuf>,
+}
+
+impl PreparedSnapshot {
+    /// Prepare snapshot `snapshot_id` of `data_dir`. An incremental
+    /// snapshot is materialized in `scratch` first.
+    ///
+    /// # Errors
+    ///
+    /// `BootstrapFailed` if the snapshot records no WAL fence, cannot be
+    /// read or does not materialize.
+    pub(crate) fn prepare(
+        data_dir: &Path,
+        snapshot_id: &str,
+        scratch: PathBuf,
+    ) -> ReplicationResult<Self> {
+        let dir = snapshot_path(data_dir, snapshot_id);
+        let manifest =
+            SnapshotManifest::read_from_file(&dir.join("manifest.json")).map_err(snapshot_error)?;
+        let fence = manifest.fence.ok_or_else(|| {
+            ReplicationError::bootstrap_failed(format!(
+                "Snapshot {} records no WAL fence",
+                snapshot_id
+            ))
+        })?;
+
+        let mut prepared = Self {
+            snapshot_id: snapshot_id.to_string(),
+            wal_sequence: fence.wal_sequence,
+            files: Vec::new(),
+            dir,
+            scratch: None,
+        };
+        if manifest.is_incremental() {
+            let _ = fs::remove_dir_all(&scratch);
+            prepared.scratch = Some(scratch.clone());
+            SnapshotManager::materialize(data_dir, snapshot_id, &scratch)
+                .map_err(snapshot_error)?;
+            prepared.dir = scratch;
+        }
+
+        let mut paths = vec![PathBuf::from("manifest.json"), PathBuf::from("storage.dat")];
+        list_files(&prepared.dir, Path::new("schemas"), &mut paths)?;
+        for path in paths {
+            let full = prepared.dir.join(&path);
+            let size = fs::metadata(&full)
+                .map_err(|e| io_error(&format!("Failed to read {}", full.display()), e))?
+                .len();
+            let checksum = compute_file_checksum(&full).map_err(snapshot_error)?;
+            prepared.files.push(SnapshotFile {
+                path: path.to_string_lossy().replace('\\', "/"),
+                size,
+                checksum: format_checksum(checksum),
+            });
+        }
+        Ok(prepared)
+    }
+
+    /// Send every file as chunk frames, in order.
+    pub(crate) fn send(&self, writer: &mut impl Write) -> ReplicationResult<()> {
+        let mut buf = vec![0u8; CHUNK_BYTES];
+        for file in &self.files {
+            let path = self.dir.join(&file.path);
+            let mut source = File::open(&path)
+                .map_err(|e| io_error(&format!("Failed to open {}", path.display()), e))?;
+            let mut remaining = file.size;
+            while remaining > 0 {
+                let len = (remaining as usize).min(CHUNK_BYTES);
+                source
+                    .read_exact(&mut buf[..len])
+                    .map_err(|e| io_error(&format!("Failed to read {}", path.display()), e))?;
+                write_frame(writer, &Frame::Chunk(buf[..len].to_vec()))
+                    .map_err(|e| transport_error("Failed to send snapshot", e))?;
+                remaining -= len as u64;
+            }
+        }
+        writer
+            .flush()
+            .map_err(|e| transport_error("Failed to send snapshot", e))
+    }
+}
+
+impl Drop for PreparedSnapshot {
+    fn drop(&mut self) {
+        if let Some(scratch) = &self.scratch {
+            let _ = fs::remove_dir_all(scratch);
+        }
+    }
+}
+
+/// Append the files under `dir/relative` to `paths`, sorted.
+fn list_files(dir: &Path, relative: &Path, paths: &mut Vec<PathBuf>) -> ReplicationResult<()> {
+    let full = dir.join(relative);
+    if !full.is_dir() {
+        return Ok(());
+    }
+    let mut entries: Vec<_> = fs::read_dir(&full)
+        .and_then(|entries| entries.collect::<io::Result<Vec<_>>>())
+        .map_err(|e| io_error(&format!("Failed to read {}", full.display()), e))?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = relative.join(entry.file_name());
+        if entry.path().is_dir() {
+            list_files(dir, &path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Progress of an unfinished bootstrap, kept in its marker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BootstrapProgress {
+    /// Primary the snapshot comes from
+    pub primary_address: String,
+
+    /// Snapshot being received, once offered
+    pub snapshot_id: Option<String>,
+
+    /// Last WAL sequence the snapshot reflects, once offered
+    pub wal_sequence: Option<u64>,
+
+    /// Snapshot bytes received so far
+    pub bytes_received: u64,
+
+    /// Snapshot bytes in total, once offered
+    pub bytes_total: u64,
+
+    /// When the marker was last written
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BootstrapProgress {
+    /// The marker of an unfinished bootstrap of `data_dir`, if any.
+    pub fn load(data_dir: &Path) -> Option<Self> {
+        let contents = fs::read(bootstrap_marker_path(data_dir)).ok()?;
+        serde_json::from_slice(&contents).ok()
+    }
+}
+
+/// Outcome of a completed bootstrap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapReport {
+    /// Snapshot the Replica was seeded from
+    pub snapshot_id: String,
+
+    /// Last WAL sequence the snapshot reflects; streaming resumes after it
+    pub wal_sequence: u64,
+
+    /// Snapshot bytes received
+    pub bytes: u64,
+}
+
+/// Seeds an empty Replica data directory from its Primary's snapshot
+pub struct ReplicaBootstrap {
+    /// Replica data directory
+    data_dir: PathBuf,
+
+    /// This Replica's identity
+    replica_id: Uuid,
+
+    /// Primary address (`host:port`)
+    primary_address: String,
+
+    /// Stream configuration
+    config: ReplicationStreamConfig,
+
+    /// Where progress events go
+    logger: Arc<dyn Logger>,
+
+    /// Longest time between progress events
+    progress_interval: Duration,
+}
+
+impl ReplicaBootstrap {
+    /// Prepare to bootstrap `data_dir` from `primary_address`.
+    pub fn new(
+        data_dir: impl AsRef<Path>,
+        replica_id: Uuid,
+        primary_address: impl Into<String>,
+        config: ReplicationStreamConfig,
+    ) -> Self {
+        Self {
+            data_dir: data_dir.as_ref().to_path_buf(),
+            replica_id,
+            primary_address: primary_address.into(),
+            config,
+            logger: JsonLogger::shared(),
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+        }
+    }
+
+    /// Send progress events to `logger`.
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Whether `data_dir` must be bootstrapped before it can follow a
+    /// Primary: an earlier bootstrap did not finish, or it holds no data.
+    pub fn is_needed(data_dir: &Path) -> ReplicationResult<bool> {
+        if bootstrap_marker_path(data_dir).exists() {
+            return Ok(true);
+        }
+        if AppliedOffset::load(data_dir)?.is_some() {
+            return Ok(false);
+        }
+
+        let wal_dir = data_dir.join("wal");
+        let wal_files = match detect_layout(&wal_dir).map_err(wal_error)? {
+            WalLayout::Empty => Vec::new(),
+            WalLayout::Legacy(path) => vec![path],
+            WalLayout::Segmented(segments) => segments.into_iter().map(|s| s.path).collect(),
+        };
+        let mut files = wal_files;
+        files.push(data_dir.join("data").join("documents.dat"));
+        for file in files {
+            match fs::metadata(&file) {
+                Ok(metadata) if metadata.len() > 0 => return Ok(false),
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(io_error(&format!("Failed to read {}", file.display()), e)),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Bootstrap the data directory.
+    ///
+    /// # Errors
+    ///
+    /// `BootstrapFailed` if the directory already holds data, the Primary
+    /// has no snapshot to offer, or a file fails verification or cannot be
+    /// installed; `Unauthenticated` or `OffsetUnavailable` if the Primary
+    /// refuses; `Transport` if the connection fails. The directory stays
+    /// marked incomplete.
+    pub fn run(&self) -> ReplicationResult<BootstrapReport> {
+        self.config.validate()?;
+        if !Self::is_needed(&self.data_dir)? {
+            return Err(ReplicationError::bootstrap_failed(format!(
+                "{} already holds data; refusing to replace it with a snapshot of {}",
+                self.data_dir.display(),
+                self.primary_address
+            )));
+        }
+
+        self.logger.info(
+            Event::ReplicaBootstrapBegin.as_str(),
+            &[("primary", &self.primary_address)],
+        );
+        let result = self.bootstrap();
+        match &result {
+            Ok(report) => self.logger.info(
+                Event::ReplicaBootstrapComplete.as_str(),
+                &[
+                    ("snapshot_id", &report.snapshot_id),
+                    ("wal_sequence", &report.wal_sequence.to_string()),
+                    ("bytes", &report.bytes.to_string()),
+                ],
+            ),
+            Err(e) => self.logger.error(
+                Event::ReplicaBootstrapFailed.as_str(),
+                &[("primary", &self.primary_address), ("error", &e.message)],
+            ),
+        }
+        result
+    }
+
+    fn bootstrap(&self) -> ReplicationResult<BootstrapReport> {
+        let mut progress = BootstrapProgress {
+            primary_address: self.primary_address.clone(),
+            snapshot_id: None,
+            wal_sequence: None,
+            bytes_received: 0,
+            bytes_total: 0,
+            updated_at: Utc::now(),
+        };
+        self.store_marker(&progress)?;
+        self.clear()?;
+
+        let stream = TcpStream::connect(&self.primary_address).map_err(|e| {
+            transport_error(&format!("Failed to connect to {}", self.primary_address), e)
+        })?;
+        stream
+            .set_nodelay(true)
+            .and_then(|()| stream.set_read_timeout(Some(self.config.connection_timeout())))
+            .map_err(|e| transport_error("Failed to configure connection", e))?;
+        let mut reader = BufReader::new(
+            stream
+                .try_clone()
+                .map_err(|e| transport_error("Failed to clone connection", e))?,
+        );
+        let mut writer = BufWriter::new(stream);
+
+        let nonce = match self.read(&mut reader)? {
+            Frame::Control(ControlMessage::Challenge { nonce }) => nonce,
+            other => return Err(unexpected("challenge", &other)),
+        };
+        let request = ControlMessage::BootstrapRequest {
+            replica_id: self.replica_id,
+            proof: handshake_proof(&self.config.shared_secret, &nonce, self.replica_id, 0),
+        };
+        write_frame(&mut writer, &Frame::Control(request))
+            .and_then(|()| writer.flush())
+            .map_err(|e| transport_error("Failed to request a snapshot", e))?;
+
+        // The Primary sends keepalives while it prepares the snapshot
+        let (snapshot_id, wal_sequence, epoch, files) = loop {
+            match self.read(&mut reader)? {
+                Frame::Control(ControlMessage::Keepalive) => {}
+                Frame::Control(ControlMessage::SnapshotOffer {
+                    snapshot_id,
+                    wal_sequence,
+                    epoch,
+                    files,
+                }) => break (snapshot_id, wal_sequence, epoch, files),
+                Frame::Control(ControlMessage::Refuse { reason, message }) => {
+                    return Err(reason.to_error(message))
+                }
+                other => return Err(unexpected("snapshot offer", &other)),
+            }
+        };
+        progress.snapshot_id = Some(snapshot_id.clone());
+        progress.wal_sequence = Some(wal_sequence);
+        progress.bytes_total = files.iter().map(|file| file.size).sum();
+        self.report(&mut progress);
+
+        let staging = staging_dir(&self.data_dir);
+        let mut last_reported = Instant::now();
+        for file in &files {
+            self.receive_file(
+                &mut reader,
+                &staging,
+                file,
+                &mut progress,
+                &mut last_reported,
+            )?;
+        }
+        self.report(&mut progress);
+        verify_manifest(&staging, &snapshot_id, wal_sequence, &files)?;
+
+        self.install(&staging, wal_sequence, epoch)?;
+        Ok(BootstrapReport {
+            snapshot_id,
+            wal_sequence,
+            bytes: progress.bytes_received,
+        })
+    }
+
+    /// Receive one file into `staging` and verify its size and checksum.
+    fn receive_file(
+        &self,
+        reader: &mut BufReader<TcpStream>,
+        staging: &Path,
+        file: &SnapshotFile,
+        progress: &mut BootstrapProgress,
+        last_reported: &mut Instant,
+    ) -> ReplicationResult<()> {
+        let relative = Path::new(&file.path);
+        if file.path.is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(ReplicationError::bootstrap_failed(format!(
+                "Snapshot file path '{}' leaves the snapshot directory",
+                file.path
+            )));
+        }
+        let path = staging.join(relative);
+        let mut out = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| File::create(&path))
+            .map_err(|e| io_error(&format!("Failed to create {}", path.display()), e))?;
+
+        let mut remaining = file.size;
+        while remaining > 0 {
+            let bytes = match self.read(reader)? {
+                Frame::Chunk(bytes) => bytes,
+                other => return Err(unexpected("snapshot chunk", &other)),
+            };
+            if bytes.len() as u64 > remaining {
+                return Err(ReplicationError::bootstrap_failed(format!(
+                    "Snapshot file {} is longer than the {} bytes offered",
+                    file.path, file.size
+                )));
+            }
+            out.write_all(&bytes)
+                .map_err(|e| io_error(&format!("Failed to write {}", path.display()), e))?;
+            remaining -= bytes.len() as u64;
+            progress.bytes_received += bytes.len() as u64;
+            if last_reported.elapsed() >= self.progress_interval {
+                self.report(progress);
+                *last_reported = Instant::now();
+            }
+        }
+        out.sync_all()
+            .map_err(|e| io_error(&format!("Failed to fsync {}", path.display()), e))?;
+
+        let checksum = format_checksum(compute_file_checksum(&path).map_err(snapshot_error)?);
+        if checksum != file.checksum {
+            return Err(ReplicationError::bootstrap_failed(format!(
+                "Snapshot file {} has checksum {}, expected {}",
+                file.path, checksum, file.checksum
+            )));
+        }
+        Ok(())
+    }
+
+    /// Move the verified snapshot into place and start the WAL after it.
+    fn install(&self, staging: &Path, wal_sequence: u64, epoch: u64) -> ReplicationResult<()> {
+        let data = self.data_dir.join("data");
+        let schemas = self.data_dir.join("metadata").join("schemas");
+        let wal_dir = self.data_dir.join("wal");
+        for dir in [&data, &schemas, &wal_dir] {
+            fs::create_dir_all(dir)
+                .map_err(|e| io_error(&format!("Failed to create {}", dir.display()), e))?;
+        }
+
+        rename(&staging.join("storage.dat"), &data.join("documents.dat"))?;
+        let staged_schemas = staging.join("schemas");
+        if staged_schemas.is_dir() {
+            for entry in fs::read_dir(&staged_schemas)
+                .map_err(|e| io_error(&format!("Failed to read {}", staged_schemas.display()), e))?
+            {
+                let entry = entry.map_err(|e| {
+                    io_error(&format!("Failed to read {}", staged_schemas.display()), e)
+                })?;
+                rename(&entry.path(), &schemas.join(entry.file_name()))?;
+            }
+        }
+        fsync_dir(&data)?;
+        fsync_dir(&schemas)?;
+
+        if epoch > 0 {
+            write_epoch(&wal_dir, epoch).map_err(wal_error)?;
+        }
+        write_base_sequence(&wal_dir, wal_sequence + 1).map_err(wal_error)?;
+        AppliedOffset {
+            applied_sequence: wal_sequence,
+            current_as_of_ms: 0,
+        }
+        .store(&replication_offset_path(&self.data_dir))?;
+
+        let _ = fs::remove_dir_all(staging);
+        let marker = bootstrap_marker_path(&self.data_dir);
+        fs::remove_file(&marker)
+            .map_err(|e| io_error(&format!("Failed to remove {}", marker.display()), e))?;
+        fsync_dir(marker.parent().expect("marker path has a parent directory"))
+    }
+
+    /// Remove everything an earlier attempt may have left: storage,
+    /// schemas, WAL, applied offset and staged files.
+    fn clear(&self) -> ReplicationResult<()> {
+        for dir in [
+            self.data_dir.join("wal"),
+            self.data_dir.join("data"),
+            self.data_dir.join("metadata").join("schemas"),
+            staging_dir(&self.data_dir),
+        ] {
+            clear_dir(&dir)?;
+        }
+        let offset = replication_offset_path(&self.data_dir);
+        match fs::remove_file(&offset) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(io_error(
+                &format!("Failed to remove {}", offset.display()),
+                e,
+            )),
+        }
+    }
+
+    /// Log progress and update the marker (advisory).
+    fn report(&self, progress: &mut BootstrapProgress) {
+        let percent = if progress.bytes_total > 0 {
+            progress.bytes_received as f64 / progress.bytes_total as f64 * 100.0
+        } else {
+            0.0
+        };
+        self.logger.info(
+            Event::ReplicaBootstrapProgress.as_str(),
+            &[
+                ("snapshot_id", progress.snapshot_id.as_deref().unwrap_or("")),
+                ("bytes_received", &progress.bytes_received.to_string()),
+                ("bytes_total", &progress.bytes_total.to_string()),
+                ("percent", &((percent * 10.0).round() / 10.0).to_string()),
+            ],
+        );
+        let _ = self.store_marker(progress);
+    }
+
+    /// Durably replace the marker.
+    fn store_marker(&self, progress: &BootstrapProgress) -> ReplicationResult<()> {
+        let path = bootstrap_marker_path(&self.data_dir);
+        let dir = path.parent().expect("marker path has a parent directory");
+        let temp_path = path.with_extension("tmp");
+        let progress = BootstrapProgress {
+            updated_at: Utc::now(),
+            ..progress.clone()
+        };
+        let json = serde_json::to_vec(&progress)
+            .map_err(|e| ReplicationError::bootstrap_failed(e.to_string()))?;
+        fs::create_dir_all(dir)
+            .and_then(|()| {
+                let mut file = File::create(&temp_path)?;
+                file.write_all(&json)?;
+                file.sync_all()
+            })
+            .and_then(|()| fs::rename(&temp_path, &path))
+            .and_then(|()| File::open(dir)?.sync_all())
+            .map_err(|e| io_error(&format!("Failed to write {}", path.display()), e))
+    }
+
+    /// Read a frame, treating silence past the connection timeout as a
+    /// lost connection.
+    fn read(&self, reader: &mut BufReader<TcpStream>) -> ReplicationResult<Frame> {
+        read_frame(reader).map_err(|e| {
+            transport_error(&format!("Connection to {} failed", self.primary_address), e)
+        })
+    }
+}
+
+/// Check the received manifest against the offer: it must be the offered
+/// snapshot, fenced at the offered sequence, and list the checksums of the
+/// storage and schema files received.
+fn verify_manifest(
+    staging: &Path,
+    snapshot_id: &str,
+    wal_sequence: u64,
+    files: &[SnapshotFile],
+) -> ReplicationResult<()> {
+    let manifest =
+        SnapshotManifest::read_from_file(&staging.join("manifest.json")).map_err(snapshot_error)?;
+    let received = |path: &str| {
+        files
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| file.checksum.as_str())
+    };
+    let mismatch = |what: String| {
+        Err(ReplicationError::bootstrap_failed(format!(
+            "Snapshot {} does not match its manifest: {}",
+            snapshot_id, what
+        )))
+    };
+
+    if manifest.snapshot_id != snapshot_id {
+        return mismatch(format!("manifest is for snapshot {}", manifest.snapshot_id));
+    }
+    if manifest.fence.map(|fence| fence.wal_sequence) != Some(wal_sequence) {
+        return mismatch(format!(
+            "manifest is not fenced at sequence {}",
+            wal_sequence
+        ));
+    }
+    if received("storage.dat") != Some(manifest.storage_checksum.as_str()) {
+        return mismatch("storage.dat checksum differs".to_string());
+    }
+    for (name, checksum) in &manifest.schema_checksums {
+        if received(&format!("schemas/{}", name)) != Some(checksum.as_str()) {
+            return mismatch(format!("schema {} is missing or differs", name));
+        }
+    }
+    Ok(())
+}
+
+/// Remove every entry of `dir`, keeping `dir` itself.
+fn clear_dir(dir: &Path) -> ReplicationResult<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(io_error(&format!("Failed to read {}", dir.display()), e)),
+    };
+    for entry in entries {
+        let path = entry
+            .map_err(|e| io_error(&format!("Failed to read {}", dir.display()), e))?
+            .path();
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        removed.map_err(|e| io_error(&format!("Failed to remove {}", path.display()), e))?;
+    }
+    fsync_dir(dir)
+}
+
+fn rename(from: &Path, to: &Path) -> ReplicationResult<()> {
+    fs::rename(from, to).map_err(|e| {
+        io_error(
+            &format!("Failed to move {} to {}", from.display(), to.display()),
+            e,
+        )
+    })
+}
+
+fn fsync_dir(dir: &Path) -> ReplicationResult<()> {
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|e| io_error(&format!("Failed to fsync {}", dir.display()), e))
+}
+
+fn unexpected(expected: &str, frame: &Frame) -> ReplicationError {
+    let frame = match frame {
+        Frame::Record(_) => "a WAL record".to_string(),
+        Frame::Chunk(bytes) => format!("a {} byte snapshot chunk", bytes.len()),
+        Frame::Control(message) => format!("{:?}", message),
+    };
+    ReplicationError::transport(format!("Expected {}, received {}", expected, frame))
+}
+
+fn io_error(context: &str, e: io::Error) -> ReplicationError {
+    ReplicationError::bootstrap_failed(format!("{}: {}", context, e))
+}
+
+fn snapshot_error(e: SnapshotError) -> ReplicationError {
+    ReplicationError::bootstrap_failed(e.to_string())
+}
+
+fn wal_error(e: crate::wal::WalError) -> ReplicationError {
+    ReplicationError::bootstrap_failed(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::GlobalExecutionLock;
+    use crate::storage::StorageWriter;
+    use crate::wal::{WalPayload, WalWriter};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_empty_data_dir_needs_bootstrap() {
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path();
+        assert!(ReplicaBootstrap::is_needed(data_dir).unwrap());
+
+        // An opened but unwritten WAL and storage hold no data
+        let mut wal = WalWriter::open(data_dir).unwrap();
+        StorageWriter::open(data_dir).unwrap();
+        assert!(ReplicaBootstrap::is_needed(data_dir).unwrap());
+
+        wal.append_insert(WalPayload::new("users", "u1", "user", "v1", b"{}".to_vec()))
+            .unwrap();
+        assert!(!ReplicaBootstrap::is_needed(data_dir).unwrap());
+
+        // An unfinished bootstrap is redone whatever it left
+        fs::create_dir_all(data_dir.join("system")).unwrap();
+        fs::write(bootstrap_marker_path(data_dir), b"{}").unwrap();
+        assert!(ReplicaBootstrap::is_needed(data_dir).unwrap());
+    }
+
+    #[test]
+    fn test_prepared_snapshot_lists_files_with_checksums() {
+        let temp = TempDir::new().unwrap();
+        let data_dir = temp.path();
+        let mut wal = WalWriter::open(data_dir).unwrap();
+        let storage = StorageWriter::open(data_dir).unwrap();
+        let schema_dir = data_dir.join("metadata").join("schemas");
+        fs::create_dir_all(&schema_dir).unwrap();
+        fs::write(schema_dir.join("user_v1.json"), b"{}").unwrap();
+        wal.append_insert(WalPayload::new("users", "u1", "user", "v1", b"{}".to_vec()))
+            .unwrap();
+
+        // Without a snapshot there is nothing to offer
+        let err = LatestSnapshot::new(data_dir).base_snapshot().unwrap_err();
+        assert_eq!(
+            err.kind,
+            crate::replication::ReplicationErrorKind::BootstrapFailed
+        );
+
+        let id = SnapshotManager::create_snapshot(
+            data_dir,
+            storage.path(),
+            &schema_dir,
+            &wal,
+            &GlobalExecutionLock::new(),
+        )
+        .unwrap();
+        assert_eq!(LatestSnapshot::new(data_dir).base_snapshot().unwrap(), id);
+
+        let prepared = PreparedSnapshot::prepare(data_dir, &id, data_dir.join("scratch")).unwrap();
+        assert_eq!(prepared.wal_sequence, 1);
+        let paths: Vec<&str> = prepared.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["manifest.json", "storage.dat", "schemas/user_v1.json"]
+        );
+
+        let staging = data_dir.join("staging");
+        fs::create_dir_all(staging.join("schemas")).unwrap();
+        for file in &prepared.files {
+            fs::copy(prepared.dir.join(&file.path), staging.join(&file.path)).unwrap();
+        }
+        verify_manifest(&staging, &id, 1, &prepared.files).unwrap();
+        assert!(verify_manifest(&staging, &id, 2, &prepared.files).is_err());
+    }
+}
@@ -48,6 +48,34 @@ pub enum ReplicationErrorKind {
 
     /// Configuration error
     ConfigurationError,
+
+    /// Connection to the peer failed or was lost (retryable)
+    Transport,
+
+    /// Peer failed replication authentication
+    Unauthenticated,
+
+    /// Requested WAL position is no longer (or not yet) on the primary
+    OffsetUnavailable,
+
+    /// Replica failed to append or apply a received record
+    ApplyFailed,
+
+    /// Write sent to a Replica; it must go to the Primary
+    NotPrimary,
+
+    /// Replica is further behind the Primary than the read allows
+    ReplicaTooStale,
+
+    /// Record from a Primary whose authority epoch was superseded by a
+    /// promotion
+    FencedPrimary,
+
+    /// A Replica could not be bootstrapped from the Primary's snapshot
+    BootstrapFailed,
+
+    /// Too few Replicas acknowledged a write within the sync timeout
+    ReplicationTimeout,
 }
 
 impl ReplicationError {
@@ -109,6 +137,51 @@ impl ReplicationError {
         Self::new(ReplicationErrorKind::ConfigurationError, message)
     }
 
+    /// Create a transport error.
+    pub fn transport(message: impl Into<String>) -> Self {
+        Self::new(ReplicationErrorKind::Transport, message)
+    }
+
+    /// Create an authentication error.
+    pub fn unauthenticated(message: impl Into<String>) -> Self {
+        Self::new(ReplicationErrorKind::Unauthenticated, message)
+    }
+
+    /// Create an offset unavailable error.
+    pub fn offset_unavailable(message: impl Into<String>) -> Self {
+        Self::new(ReplicationErrorKind::OffsetUnavailable, message)
+    }
+
+    /// Create an apply failure error.
+    pub fn apply_failed(message: impl Into<String>) -> Self {
+        Self::new(ReplicationErrorKind::ApplyFailed, message)
+    }
+
+    /// Create a not primary error.
+    pub fn not_primary(message: impl Into<String>) -> Self {
+        Self::new(ReplicationErrorKind::NotPrimary, message)
+    }
+
+    /// Create a replica too stale error.
+    pub fn replica_too_stale(message: impl Into<String>) -> Self {
+        Self::new(ReplicationErrorKind::ReplicaTooStale, message)
+    }
+
+    /// Create a fenced primary error.
+    pub fn fenced_primary(message: impl Into<String>) -> Self {
+        Self::new(ReplicationErrorKind::FencedPrimary, message)
+    }
+
+    /// Create a bootstrap failure error.
+    pub fn bootstrap_failed(message: impl Into<String>) -> Self {
+        Self::new(ReplicationErrorKind::BootstrapFailed, message)
+    }
+
+    /// Create a replication timeout error.
+    pub fn replication_timeout(message: impl Into<String>) -> Self {
+        Self::new(ReplicationErrorKind::ReplicationTimeout, message)
+    }
+
     /// Check if this error is fatal (requires operator intervention).
     pub fn is_fatal(&self) -> bool {
         matches!(
@@ -117,6 +190,9 @@ impl ReplicationError {
                 | ReplicationErrorKind::AuthorityAmbiguity
                 | ReplicationErrorKind::HistoryDivergence
                 | ReplicationErrorKind::WalGap
+                | ReplicationErrorKind::OffsetUnavailable
+                | ReplicationErrorKind::ApplyFailed
+                | ReplicationErrorKind::FencedPrimary
         )
     }
 }
@@ -142,11 +218,18 @@ mod tests {
         assert!(ReplicationError::authority_ambiguity("test").is_fatal());
         assert!(ReplicationError::history_divergence("test").is_fatal());
         assert!(ReplicationError::wal_gap("test").is_fatal());
+        assert!(ReplicationError::offset_unavailable("test").is_fatal());
+        assert!(ReplicationError::fenced_primary("test").is_fatal());
     }
 
     #[test]
     fn test_non_fatal_errors() {
         assert!(!ReplicationError::write_rejected("test").is_fatal());
         assert!(!ReplicationError::illegal_transition("test").is_fatal());
+        assert!(!ReplicationError::transport("test").is_fatal());
+        assert!(!ReplicationError::not_primary("test").is_fatal());
+        assert!(!ReplicationError::replica_too_stale("test").is_fatal());
+        assert!(!ReplicationError::bootstrap_failed("test").is_fatal());
+        assert!(!ReplicationError::replication_timeout("test").is_fatal());
     }
 }
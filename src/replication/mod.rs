@@ -20,24 +20,44 @@
 //! # Phase 3 Optimizations
 //!
 //! - Fast Read: Pre-validated snapshot reuse on replicas (optional, disabled by default)
+//!
+//! # WAL Streaming
+//!
+//! - `WalStreamer`: Primary listens for Replicas and ships WAL records over TCP
+//! - `WalFollower`: Replica connects on boot, catches up and applies records
+//! - `ReplicaGate`: Replica serves reads within a staleness bound, refuses writes
+//! - `ReplicaBootstrap`: an empty Replica is seeded from a Primary snapshot
+//!   before it starts streaming
+//! - `SyncReplication`: in quorum mode a write waits for Replica acknowledgments
 
 mod authority;
+mod bootstrap;
 mod compatibility;
 mod config;
 mod errors;
 mod failure_matrix;
 mod fast_read;
+mod read_gate;
 mod recovery;
+mod replica_progress;
 mod replica_reads;
 mod role;
 mod snapshot_transfer;
+mod sync_commit;
+mod transport;
+mod wal_follower;
 mod wal_receiver;
 mod wal_sender;
+mod wal_streamer;
 
 pub use authority::{
     check_commit_authority, check_dual_primary, check_write_admission, AuthorityCheck,
     WriteAdmission,
 };
+pub use bootstrap::{
+    bootstrap_marker_path, BaseSnapshotSource, BootstrapProgress, BootstrapReport,
+    LatestSnapshot, ReplicaBootstrap,
+};
 pub use compatibility::{
     CompatibilityAssertion, CompatibilityCheck, MvccCompatibility, Phase1Compatibility,
 };
@@ -48,12 +68,26 @@ pub use fast_read::{
     FastReadConfig, FastReadManager, FastReadResult, FastReadStats, ReplicaReadPath,
     ReplicaSafetyState, SafetyCheck, SafetyValidator, SafetyViolation,
 };
+pub use read_gate::{ReplicaFreshness, ReplicaGate};
 pub use recovery::{PrimaryRecovery, RecoveryValidation, ReplicaRecovery, ReplicaResumeState};
+pub use replica_progress::{
+    replica_lag, replica_lag_at, replica_progress_path, ReplicaLag, ReplicaProgress, ReplicaTracker,
+};
 pub use replica_reads::{ReadEligibility, ReplicaReadAdmission};
 pub use role::{HaltReason, ReplicationRole, ReplicationState};
 pub use snapshot_transfer::{
     check_snapshot_eligibility, SnapshotEligibility, SnapshotInstallResult, SnapshotMetadata,
     SnapshotReceiver, SnapshotTransferState,
 };
+pub use sync_commit::{
+    sync_state_path, SyncMode, SyncReplication, SyncReplicationConfig, SyncState,
+    SyncTimeoutPolicy,
+};
+pub use transport::{
+    read_frame, write_frame, ControlMessage, Frame, RefuseReason, ReplicationStreamConfig,
+    SnapshotFile, MIN_SECRET_BYTES,
+};
+pub use wal_follower::{replication_offset_path, AppliedOffset, WalFollower};
 pub use wal_receiver::{ReceiveResult, WalReceiver};
 pub use wal_sender::{WalPosition, WalRecordEnvelope, WalSender};
+pub use wal_streamer::WalStreamer;
@@ -0,0 +1,213 @@
+//! Replica Read Gate
+//!
+//! Decides which API operations a node serves.
+//!
+//! A Primary (or a node with replication disabled) serves everything. A
+//! Replica serves reads and refuses writes with `NotPrimary`, naming the
+//! Primary that accepts them.
+//!
+//! A read may bound its staleness with `max_staleness_ms`. A Replica is
+//! current as of the commit timestamp of the last record it applied, or of
+//! the last keepalive it received: the Primary only sends one when it has
+//! no record left to send. Staleness is the time elapsed since then, by
+//! the Replica's clock. A bounded read is refused with `ReplicaTooStale`
+//! when the staleness exceeds the bound or is unknown.
+//!
+//! Per REPLICATION_READ_SEMANTICS.md §11: "A Replica may lag. It may never lie."
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::errors::{ReplicationError, ReplicationResult};
+
+/// How current a Replica's applied state is
+///
+/// Shared between the follower, which advances it, and the API layers,
+/// which read it.
+#[derive(Debug, Default)]
+pub struct ReplicaFreshness {
+    /// Primary time, in ms since the epoch, the Replica is current as of
+    /// (0 if unknown)
+    current_as_of_ms: AtomicU64,
+}
+
+impl ReplicaFreshness {
+    /// Create freshness current as of `current_as_of_ms` (0 if unknown).
+    pub fn new(current_as_of_ms: u64) -> Self {
+        Self {
+            current_as_of_ms: AtomicU64::new(current_as_of_ms),
+        }
+    }
+
+    /// Record that the Replica is current as of `as_of_ms`.
+    ///
+    /// Never moves backwards.
+    pub fn observe(&self, as_of_ms: u64) {
+        self.current_as_of_ms.fetch_max(as_of_ms, Ordering::AcqRel);
+    }
+
+    /// Time the Replica is current as of, if known.
+    pub fn current_as_of_ms(&self) -> Option<u64> {
+        match self.current_as_of_ms.load(Ordering::Acquire) {
+            0 => None,
+            as_of_ms => Some(as_of_ms),
+        }
+    }
+
+    /// Current staleness by this node's clock, if known.
+    pub fn staleness_ms(&self) -> Option<u64> {
+        self.staleness_at(unix_time_ms())
+    }
+
+    fn staleness_at(&self, now_ms: u64) -> Option<u64> {
+        self.current_as_of_ms()
+            .map(|as_of_ms| now_ms.saturating_sub(as_of_ms))
+    }
+}
+
+/// Which operations a node serves
+#[derive(Debug, Clone, Default)]
+pub enum ReplicaGate {
+    /// Primary or replication disabled: reads and writes
+    #[default]
+    Primary,
+
+    /// ReplicaActive: reads only, optionally bounded by staleness
+    Replica {
+        /// Address of the Primary that accepts writes
+        primary_address: String,
+        /// Freshness advanced by the follower
+        freshness: Arc<ReplicaFreshness>,
+    },
+}
+
+impl ReplicaGate {
+    /// Gate for a Replica following `primary_address`.
+    pub fn replica(primary_address: impl Into<String>, freshness: Arc<ReplicaFreshness>) -> Self {
+        Self::Replica {
+            primary_address: primary_address.into(),
+            freshness,
+        }
+    }
+
+    /// Check if this node is a Replica.
+    pub fn is_replica(&self) -> bool {
+        matches!(self, Self::Replica { .. })
+    }
+
+    /// Check that a write may be served.
+    ///
+    /// # Errors
+    ///
+    /// `NotPrimary` on a Replica.
+    pub fn check_write(&self) -> ReplicationResult<()> {
+        match self {
+            Self::Primary => Ok(()),
+            Self::Replica {
+                primary_address, ..
+            } => Err(ReplicationError::not_primary(format!(
+                "This node is a replica; send writes to the primary at {}",
+                primary_address
+            ))),
+        }
+    }
+
+    /// Check that a read bounded by `max_staleness_ms` may be served.
+    ///
+    /// # Errors
+    ///
+    /// `ReplicaTooStale` on a Replica whose staleness exceeds the bound or
+    /// is unknown.
+    pub fn check_read(&self, max_staleness_ms: Option<u64>) -> ReplicationResult<()> {
+        self.check_read_at(max_staleness_ms, unix_time_ms())
+    }
+
+    fn check_read_at(&self, max_staleness_ms: Option<u64>, now_ms: u64) -> ReplicationResult<()> {
+        let (Self::Replica { freshness, .. }, Some(max_staleness_ms)) = (self, max_staleness_ms)
+        else {
+            return Ok(());
+        };
+        match freshness.staleness_at(now_ms) {
+            Some(staleness_ms) if staleness_ms <= max_staleness_ms => Ok(()),
+            Some(staleness_ms) => Err(ReplicationError::replica_too_stale(format!(
+                "Replica is {}ms behind the primary, more than max_staleness_ms {}",
+                staleness_ms, max_staleness_ms
+            ))),
+            None => Err(ReplicationError::replica_too_stale(format!(
+                "Replica has not heard from the primary; cannot serve max_staleness_ms {}",
+                max_staleness_ms
+            ))),
+        }
+    }
+
+    /// Current staleness of a Replica, if known; `None` on a Primary.
+    pub fn staleness_ms(&self) -> Option<u64> {
+        match self {
+            Self::Primary => None,
+            Self::Replica { freshness, .. } => freshness.staleness_ms(),
+        }
+    }
+}
+
+/// Wall-clock time in ms since the epoch.
+pub(super) fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replication::ReplicationErrorKind;
+
+    fn replica(current_as_of_ms: u64) -> ReplicaGate {
+        ReplicaGate::replica(
+            "db-primary:7000",
+            Arc::new(ReplicaFreshness::new(current_as_of_ms)),
+        )
+    }
+
+    #[test]
+    fn test_primary_serves_everything() {
+        let gate = ReplicaGate::default();
+        assert!(gate.check_write().is_ok());
+        assert!(gate.check_read_at(Some(0), 10_000).is_ok());
+        assert_eq!(gate.staleness_ms(), None);
+    }
+
+    #[test]
+    fn test_replica_rejects_writes_naming_primary() {
+        let err = replica(1_000).check_write().unwrap_err();
+        assert_eq!(err.kind, ReplicationErrorKind::NotPrimary);
+        assert!(err.message.contains("db-primary:7000"));
+    }
+
+    #[test]
+    fn test_replica_read_within_staleness_bound() {
+        let gate = replica(1_000);
+        assert!(gate.check_read_at(None, 60_000).is_ok());
+        assert!(gate.check_read_at(Some(500), 1_500).is_ok());
+
+        let err = gate.check_read_at(Some(500), 1_501).unwrap_err();
+        assert_eq!(err.kind, ReplicationErrorKind::ReplicaTooStale);
+    }
+
+    #[test]
+    fn test_unknown_staleness_refuses_bounded_reads() {
+        let gate = replica(0);
+        assert!(gate.check_read_at(None, 1_000).is_ok());
+        let err = gate.check_read_at(Some(60_000), 1_000).unwrap_err();
+        assert_eq!(err.kind, ReplicationErrorKind::ReplicaTooStale);
+    }
+
+    #[test]
+    fn test_freshness_never_moves_backwards() {
+        let freshness = ReplicaFreshness::new(2_000);
+        freshness.observe(1_000);
+        assert_eq!(freshness.current_as_of_ms(), Some(2_000));
+        freshness.observe(3_000);
+        assert_eq!(freshness.staleness_at(3_500), Some(500));
+    }
+}
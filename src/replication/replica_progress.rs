@@ -0,0 +1,487 @@
+//! Replica Progress and Lag
+//!
+//! The Primary records, per Replica, the last sequence the Replica
+//! acknowledged as durable and where that record ends in the Primary's
+//! WAL. Progress is kept in `data_dir/system/replicas.json` so that
+//! inspection from another process (`aerodb control inspect replication`)
+//! can report lag; the file is advisory and not fsynced.
+//!
+//! A Replica being bootstrapped from a base snapshot is recorded with the
+//! snapshot's sequence as acknowledged and a pin deadline: checkpoints
+//! keep the WAL after it until the Replica starts streaming or the pin
+//! expires.
+//!
+//! Lag is measured against the Primary's WAL at inspection time:
+//! - records: sequences after the acknowledged one
+//! - bytes: WAL bytes after the end of the acknowledged record
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::errors::{ReplicationError, ReplicationResult};
+use super::read_gate::unix_time_ms;
+use crate::wal::{list_segments, WalReader, WalSegment};
+
+/// Path of the stored replica progress
+pub fn replica_progress_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("system").join("replicas.json")
+}
+
+/// Last acknowledged position of one Replica
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicaProgress {
+    /// Replica identity from its handshake
+    pub replica_id: Uuid,
+
+    /// Remote address of the current or last connection
+    pub address: String,
+
+    /// Whether the Replica is currently streaming
+    pub connected: bool,
+
+    /// Last sequence the Replica acknowledged (0 before the first)
+    pub acked_sequence: u64,
+
+    /// Segment holding the acknowledged record
+    pub acked_segment: u64,
+
+    /// Byte offset just past the acknowledged record in `acked_segment`
+    pub acked_offset: u64,
+
+    /// For a Replica being bootstrapped, the time (ms since the epoch)
+    /// until which the WAL after `acked_sequence` is kept for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_until_ms: Option<u64>,
+}
+
+impl ReplicaProgress {
+    /// Whether checkpoints must keep the WAL after `acked_sequence`.
+    ///
+    /// False only once a bootstrap pin has expired.
+    pub fn retains_wal(&self) -> bool {
+        self.pinned_until_ms
+            .is_none_or(|until| unix_time_ms() < until)
+    }
+}
+
+/// Lag of one Replica behind the Primary's WAL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicaLag {
+    /// Replica identity
+    pub replica_id: Uuid,
+
+    /// Remote address of the current or last connection
+    pub address: String,
+
+    /// Whether the Replica is currently streaming
+    pub connected: bool,
+
+    /// Last sequence the Replica acknowledged
+    pub acked_sequence: u64,
+
+    /// WAL records not yet acknowledged
+    pub lag_records: u64,
+
+    /// WAL bytes not yet acknowledged
+    pub lag_bytes: u64,
+}
+
+/// Per-Replica progress on a Primary, shared by its stream threads
+#[derive(Debug)]
+pub struct ReplicaTracker {
+    /// Where progress is stored
+    path: PathBuf,
+
+    /// Progress by Replica
+    replicas: Mutex<BTreeMap<Uuid, ReplicaProgress>>,
+
+    /// Signalled on every acknowledgment
+    acks: Condvar,
+}
+
+impl ReplicaTracker {
+    /// Open the tracker of a data directory.
+    ///
+    /// Replicas recorded by a previous run are kept, marked disconnected.
+    pub fn open(data_dir: &Path) -> ReplicationResult<Self> {
+        let replicas = Self::load(data_dir)?
+            .into_iter()
+            .map(|mut progress| {
+                progress.connected = false;
+                (progress.replica_id, progress)
+            })
+            .collect();
+
+        Ok(Self {
+            path: replica_progress_path(data_dir),
+            replicas: Mutex::new(replicas),
+            acks: Condvar::new(),
+        })
+    }
+
+    /// Read the stored progress of a data directory.
+    pub fn load(data_dir: &Path) -> ReplicationResult<Vec<ReplicaProgress>> {
+        let path = replica_progress_path(data_dir);
+        match fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents).map_err(|e| {
+                ReplicationError::configuration_error(format!(
+                    "Invalid replica progress in {}: {}",
+                    path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(ReplicationError::configuration_error(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Current progress of every known Replica.
+    pub fn replicas(&self) -> Vec<ReplicaProgress> {
+        self.lock().values().cloned().collect()
+    }
+
+    /// Number of Replicas currently streaming.
+    pub fn connected_replicas(&self) -> usize {
+        self.lock()
+            .values()
+            .filter(|progress| progress.connected)
+            .count()
+    }
+
+    /// Wait until `count` Replicas have acknowledged `sequence` as durable,
+    /// or `timeout` elapses.
+    ///
+    /// Returns how many have. A Replica being bootstrapped has not
+    /// acknowledged the sequence it is pinned at.
+    pub fn wait_for_acks(&self, sequence: u64, count: usize, timeout: Duration) -> usize {
+        let acked = |replicas: &BTreeMap<Uuid, ReplicaProgress>| {
+            replicas
+                .values()
+                .filter(|p| p.pinned_until_ms.is_none() && p.acked_sequence >= sequence)
+                .count()
+        };
+        let (replicas, _) = self
+            .acks
+            .wait_timeout_while(self.lock(), timeout, |replicas| acked(replicas) < count)
+            .unwrap_or_else(|e| e.into_inner());
+        acked(&replicas)
+    }
+
+    /// Record a Replica starting to stream after `progress.acked_sequence`.
+    pub(crate) fn connected(&self, progress: ReplicaProgress) {
+        let mut replicas = self.lock();
+        replicas.insert(progress.replica_id, progress);
+        self.store(&replicas);
+    }
+
+    /// Pin the WAL after `sequence` for a Replica being bootstrapped from a
+    /// snapshot taken at `sequence`, until `until_ms`.
+    pub(crate) fn pin(&self, replica_id: Uuid, address: String, sequence: u64, until_ms: u64) {
+        let mut replicas = self.lock();
+        replicas.insert(
+            replica_id,
+            ReplicaProgress {
+                replica_id,
+                address,
+                connected: false,
+                acked_sequence: sequence,
+                acked_segment: 0,
+                acked_offset: 0,
+                pinned_until_ms: Some(until_ms),
+            },
+        );
+        self.store(&replicas);
+    }
+
+    /// Release the pin of a bootstrap that failed. A Replica that has
+    /// started streaming since is kept.
+    pub(crate) fn unpin(&self, replica_id: Uuid) {
+        let mut replicas = self.lock();
+        if replicas
+            .get(&replica_id)
+            .is_some_and(|progress| progress.pinned_until_ms.is_some())
+        {
+            replicas.remove(&replica_id);
+            self.store(&replicas);
+        }
+    }
+
+    /// Record an acknowledgment.
+    pub(crate) fn acked(&self, replica_id: Uuid, sequence: u64, segment: u64, offset: u64) {
+        let mut replicas = self.lock();
+        if let Some(progress) = replicas.get_mut(&replica_id) {
+            progress.acked_sequence = sequence;
+            progress.acked_segment = segment;
+            progress.acked_offset = offset;
+            self.store(&replicas);
+        }
+        self.acks.notify_all();
+    }
+
+    /// Record a Replica's stream ending.
+    pub(crate) fn disconnected(&self, replica_id: Uuid) {
+        let mut replicas = self.lock();
+        if let Some(progress) = replicas.get_mut(&replica_id) {
+            progress.connected = false;
+            self.store(&replicas);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<Uuid, ReplicaProgress>> {
+        self.replicas.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace the stored progress. Advisory: failures are ignored.
+    fn store(&self, replicas: &BTreeMap<Uuid, ReplicaProgress>) {
+        let Some(dir) = self.path.parent() else {
+            return;
+        };
+        let temp_path = self.path.with_extension("tmp");
+        let progress: Vec<&ReplicaProgress> = replicas.values().collect();
+        let _ = serde_json::to_vec(&progress)
+            .map_err(io::Error::other)
+            .and_then(|json| {
+                fs::create_dir_all(dir)?;
+                fs::write(&temp_path, json)
+            })
+            .and_then(|()| fs::rename(&temp_path, &self.path));
+    }
+}
+
+/// Measure each Replica's lag against the WAL in `wal_dir`.
+pub fn replica_lag(
+    wal_dir: &Path,
+    replicas: &[ReplicaProgress],
+) -> ReplicationResult<Vec<ReplicaLag>> {
+    let segments = list_wal_segments(wal_dir)?;
+    let last_sequence = last_wal_sequence(&segments)?;
+    lag_behind(&segments, replicas, last_sequence)
+}
+
+/// Measure each Replica's lag against the WAL in `wal_dir`, whose last
+/// record is already known to be `last_sequence`.
+///
+/// Unlike `replica_lag` this reads no records, only segment sizes.
+pub fn replica_lag_at(
+    wal_dir: &Path,
+    replicas: &[ReplicaProgress],
+    last_sequence: u64,
+) -> ReplicationResult<Vec<ReplicaLag>> {
+    lag_behind(&list_wal_segments(wal_dir)?, replicas, last_sequence)
+}
+
+fn list_wal_segments(wal_dir: &Path) -> ReplicationResult<Vec<WalSegment>> {
+    list_segments(wal_dir).map_err(|e| {
+        ReplicationError::configuration_error(format!("Failed to list WAL segments: {}", e))
+    })
+}
+
+fn lag_behind(
+    segments: &[WalSegment],
+    replicas: &[ReplicaProgress],
+    last_sequence: u64,
+) -> ReplicationResult<Vec<ReplicaLag>> {
+    let sizes = segments
+        .iter()
+        .map(|segment| {
+            fs::metadata(&segment.path)
+                .map(|m| (segment.index, m.len()))
+                .map_err(|e| {
+                    ReplicationError::configuration_error(format!(
+                        "Failed to read {}: {}",
+                        segment.path.display(),
+                        e
+                    ))
+                })
+        })
+        .collect::<ReplicationResult<Vec<(u64, u64)>>>()?;
+
+    Ok(replicas
+        .iter()
+        .map(|progress| {
+            let lag_bytes = sizes
+                .iter()
+                .map(|&(index, len)| {
+                    if index > progress.acked_segment {
+                        len
+                    } else if index == progress.acked_segment {
+                        len.saturating_sub(progress.acked_offset)
+                    } else {
+                        0
+                    }
+                })
+                .sum();
+            ReplicaLag {
+                replica_id: progress.replica_id,
+                address: progress.address.clone(),
+                connected: progress.connected,
+                acked_sequence: progress.acked_sequence,
+                lag_records: last_sequence.saturating_sub(progress.acked_sequence),
+                lag_bytes,
+            }
+        })
+        .collect())
+}
+
+/// Sequence of the last record in the WAL, 0 if it holds none.
+///
+/// Reads only the last segment holding a record.
+fn last_wal_sequence(segments: &[WalSegment]) -> ReplicationResult<u64> {
+    for segment in segments.iter().rev() {
+        let mut reader = WalReader::open_history(vec![segment.path.clone()])
+            .map_err(|e| ReplicationError::wal_integrity_failed(e.to_string()))?;
+        let mut last = 0;
+        while let Some(record) = reader
+            .read_next()
+            .map_err(|e| ReplicationError::wal_integrity_failed(e.to_string()))?
+        {
+            last = record.sequence_number;
+        }
+        if last > 0 {
+            return Ok(last);
+        }
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wal::{WalPayload, WalSegmentConfig, WalSyncConfig, WalWriter};
+    use tempfile::TempDir;
+
+    fn progress(acked_sequence: u64, acked_segment: u64, acked_offset: u64) -> ReplicaProgress {
+        ReplicaProgress {
+            replica_id: Uuid::new_v4(),
+            address: "127.0.0.1:50000".to_string(),
+            connected: true,
+            acked_sequence,
+            acked_segment,
+            acked_offset,
+            pinned_until_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_lag_counts_records_and_bytes_after_ack() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut wal = WalWriter::open_segmented(
+            temp_dir.path(),
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(1),
+        )
+        .unwrap();
+        for i in 0..3 {
+            let payload = WalPayload::new("users", format!("u{}", i), "user", "v1", b"{}".to_vec());
+            wal.append_insert(payload).unwrap();
+        }
+        let wal_dir = temp_dir.path().join("wal");
+        let segment_len = fs::metadata(wal_dir.join("0000001.log")).unwrap().len();
+
+        let replicas = [
+            progress(0, 0, 0),
+            progress(1, 1, segment_len),
+            progress(3, 3, segment_len),
+        ];
+        let lag = replica_lag(&wal_dir, &replicas).unwrap();
+        let measured: Vec<(u64, u64)> = lag.iter().map(|l| (l.lag_records, l.lag_bytes)).collect();
+        assert_eq!(
+            measured,
+            [(3, 3 * segment_len), (2, 2 * segment_len), (0, 0)]
+        );
+
+        // The same, given the writer's last sequence
+        let lag = replica_lag_at(&wal_dir, &replicas, wal.last_sequence_number()).unwrap();
+        let at: Vec<(u64, u64)> = lag.iter().map(|l| (l.lag_records, l.lag_bytes)).collect();
+        assert_eq!(at, measured);
+    }
+
+    #[test]
+    fn test_tracker_persists_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = ReplicaTracker::open(temp_dir.path()).unwrap();
+        let replica = progress(4, 1, 100);
+        tracker.connected(replica.clone());
+        tracker.acked(replica.replica_id, 6, 2, 40);
+
+        let reopened = ReplicaTracker::open(temp_dir.path()).unwrap().replicas();
+        assert_eq!(reopened.len(), 1);
+        assert!(!reopened[0].connected);
+        assert_eq!(
+            (
+                reopened[0].acked_sequence,
+                reopened[0].acked_segment,
+                reopened[0].acked_offset
+            ),
+            (6, 2, 40)
+        );
+    }
+
+    #[test]
+    fn test_bootstrap_pin_until_streaming_or_expiry() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = ReplicaTracker::open(temp_dir.path()).unwrap();
+        let replica = progress(0, 0, 0);
+        let id = replica.replica_id;
+
+        tracker.pin(id, replica.address.clone(), 9, unix_time_ms() + 60_000);
+        let pinned = &ReplicaTracker::load(temp_dir.path()).unwrap()[0];
+        assert_eq!(pinned.acked_sequence, 9);
+        assert!(pinned.retains_wal());
+
+        // Streaming replaces the pin
+        tracker.connected(ReplicaProgress {
+            replica_id: id,
+            ..progress(9, 1, 10)
+        });
+        tracker.unpin(id);
+        assert!(tracker.replicas()[0].pinned_until_ms.is_none());
+
+        // An expired pin no longer holds the WAL; a failed one is released
+        tracker.pin(id, replica.address.clone(), 9, unix_time_ms() - 1);
+        assert!(!tracker.replicas()[0].retains_wal());
+        tracker.unpin(id);
+        assert!(ReplicaTracker::load(temp_dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_wait_for_acks_counts_durable_replicas() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = std::sync::Arc::new(ReplicaTracker::open(temp_dir.path()).unwrap());
+        let first = progress(4, 1, 10);
+        let second = progress(4, 1, 10);
+        tracker.connected(first.clone());
+        tracker.connected(second.clone());
+        tracker.pin(
+            Uuid::new_v4(),
+            "127.0.0.1:50001".to_string(),
+            9,
+            unix_time_ms() + 60_000,
+        );
+        assert_eq!(tracker.connected_replicas(), 2);
+        assert_eq!(tracker.wait_for_acks(5, 1, Duration::ZERO), 0);
+
+        let acking = std::thread::spawn({
+            let tracker = std::sync::Arc::clone(&tracker);
+            move || tracker.acked(first.replica_id, 5, 1, 20)
+        });
+        assert_eq!(tracker.wait_for_acks(5, 1, Duration::from_secs(10)), 1);
+        acking.join().unwrap();
+
+        // Times out short of the count
+        assert_eq!(tracker.wait_for_acks(5, 2, Duration::from_millis(20)), 1);
+        tracker.acked(second.replica_id, 7, 1, 40);
+        assert_eq!(tracker.wait_for_acks(5, 2, Duration::ZERO), 2);
+    }
+}
@@ -0,0 +1,380 @@
+//! Synchronous Replication
+//!
+//! In `async` mode (the default) the Primary answers a write once the
+//! record is durable in its own WAL; a Primary lost before its Replicas
+//! received the record loses the write.
+//!
+//! In `quorum` mode the Primary then waits until `min_sync_replicas`
+//! Replicas have acknowledged the record as durable (`ReplicaTracker`,
+//! fed by the Replicas' acks) before answering. When they do not within
+//! `sync_timeout`, the write either:
+//! - fails with `ReplicationTimeout` (`fail`). The record stays in the
+//!   Primary's WAL and still reaches the Replicas: the outcome is unknown
+//!   to the client, never rolled back
+//! - succeeds, and the Primary degrades to async (`degrade`). While too
+//!   few Replicas are connected, writes do not wait; once enough are, the
+//!   next write waits again and, if the quorum acknowledges it, sync
+//!   replication is restored
+//!
+//! The degraded flag is kept in `data_dir/system/replication_sync.json` so
+//! that inspection from another process can report it; the file is
+//! advisory and not fsynced.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::errors::{ReplicationError, ReplicationResult};
+use super::read_gate::unix_time_ms;
+use super::replica_progress::ReplicaTracker;
+use crate::observability::{Event, JsonLogger, Logger};
+
+/// Path of the stored sync replication state
+pub fn sync_state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("system").join("replication_sync.json")
+}
+
+/// When the Primary answers a write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncMode {
+    /// Once the record is durable on the Primary
+    #[default]
+    Async,
+    /// Once `min_sync_replicas` Replicas have acknowledged the record
+    Quorum,
+}
+
+impl SyncMode {
+    /// Parse a mode from its configuration name.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "async" => Ok(Self::Async),
+            "quorum" => Ok(Self::Quorum),
+            other => Err(format!(
+                "Invalid replication sync_mode: '{}'. Allowed values: 'async' (default), 'quorum'",
+                other
+            )),
+        }
+    }
+
+    /// Configuration name of this mode.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Async => "async",
+            Self::Quorum => "quorum",
+        }
+    }
+}
+
+/// What a write does when the quorum does not acknowledge it in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncTimeoutPolicy {
+    /// Fail the write with `ReplicationTimeout`
+    #[default]
+    Fail,
+    /// Succeed, and stop waiting for Replicas until a quorum is back
+    Degrade,
+}
+
+impl SyncTimeoutPolicy {
+    /// Parse a policy from its configuration name.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "fail" => Ok(Self::Fail),
+            "degrade" => Ok(Self::Degrade),
+            other => Err(format!(
+                "Invalid replication sync_on_timeout: '{}'. Allowed values: 'fail' (default), \
+                 'degrade'",
+                other
+            )),
+        }
+    }
+
+    /// Configuration name of this policy.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fail => "fail",
+            Self::Degrade => "degrade",
+        }
+    }
+}
+
+/// Synchronous replication configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncReplicationConfig {
+    /// When the Primary answers a write
+    pub mode: SyncMode,
+
+    /// Replicas that must acknowledge a write in `quorum` mode
+    pub min_sync_replicas: usize,
+
+    /// How long a write waits for the quorum
+    pub timeout: Duration,
+
+    /// What a write does when the quorum is late
+    pub on_timeout: SyncTimeoutPolicy,
+}
+
+impl Default for SyncReplicationConfig {
+    fn default() -> Self {
+        Self {
+            mode: SyncMode::Async,
+            min_sync_replicas: 1,
+            timeout: Duration::from_secs(5),
+            on_timeout: SyncTimeoutPolicy::Fail,
+        }
+    }
+}
+
+impl SyncReplicationConfig {
+    /// Validate the configuration.
+    pub fn validate(&self) -> ReplicationResult<()> {
+        if self.mode == SyncMode::Quorum {
+            if self.min_sync_replicas == 0 {
+                return Err(ReplicationError::configuration_error(
+                    "replication.min_sync_replicas must be > 0 in quorum mode",
+                ));
+            }
+            if self.timeout.is_zero() {
+                return Err(ReplicationError::configuration_error(
+                    "replication.sync_timeout_ms must be > 0 in quorum mode",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stored sync replication state
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncState {
+    /// Whether writes stopped waiting for Replicas after a timeout
+    pub degraded: bool,
+
+    /// When the Primary degraded (ms since the epoch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degraded_since_ms: Option<u64>,
+}
+
+impl SyncState {
+    /// Read the stored state of a data directory (not degraded if absent
+    /// or unreadable).
+    pub fn load(data_dir: &Path) -> Self {
+        fs::read(sync_state_path(data_dir))
+            .ok()
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Waits for a quorum of Replicas to acknowledge each write
+#[derive(Debug)]
+pub struct SyncReplication {
+    config: SyncReplicationConfig,
+
+    /// Acknowledgments recorded by the WAL streamer
+    tracker: Arc<ReplicaTracker>,
+
+    /// Where the degraded flag is stored
+    path: PathBuf,
+
+    /// Whether writes stopped waiting after a timeout
+    degraded: AtomicBool,
+
+    logger: Arc<dyn Logger>,
+}
+
+impl SyncReplication {
+    /// Wait for acknowledgments `tracker` records. Starts not degraded.
+    pub fn new(
+        data_dir: &Path,
+        config: SyncReplicationConfig,
+        tracker: Arc<ReplicaTracker>,
+    ) -> Self {
+        let sync = Self {
+            config,
+            tracker,
+            path: sync_state_path(data_dir),
+            degraded: AtomicBool::new(false),
+            logger: JsonLogger::shared(),
+        };
+        sync.store(&SyncState::default());
+        sync
+    }
+
+    /// Send degradation events to `logger`.
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// The configuration waited by.
+    pub fn config(&self) -> &SyncReplicationConfig {
+        &self.config
+    }
+
+    /// Whether writes stopped waiting for Replicas after a timeout.
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Acquire)
+    }
+
+    /// Wait until the record at `sequence` is acknowledged by the quorum.
+    ///
+    /// Returns at once in `async` mode, and while degraded with too few
+    /// Replicas connected.
+    ///
+    /// # Errors
+    ///
+    /// `ReplicationTimeout` if the quorum did not acknowledge the record
+    /// within the timeout and the policy is `fail`.
+    pub fn wait_for(&self, sequence: u64) -> ReplicationResult<()> {
+        if self.config.mode == SyncMode::Async {
+            return Ok(());
+        }
+        let needed = self.config.min_sync_replicas;
+        if self.is_degraded() && self.tracker.connected_replicas() < needed {
+            return Ok(());
+        }
+
+        let acked = self
+            .tracker
+            .wait_for_acks(sequence, needed, self.config.timeout);
+        if acked >= needed {
+            if self.degraded.swap(false, Ordering::AcqRel) {
+                self.store(&SyncState::default());
+                self.logger.info(
+                    Event::ReplicationSyncRestored.as_str(),
+                    &[("sequence", &sequence.to_string())],
+                );
+            }
+            return Ok(());
+        }
+
+        let message = format!(
+            "Record {} acknowledged by {} of {} required replicas within {} ms",
+            sequence,
+            acked,
+            needed,
+            self.config.timeout.as_millis()
+        );
+        match self.config.on_timeout {
+            SyncTimeoutPolicy::Fail => Err(ReplicationError::replication_timeout(message)),
+            SyncTimeoutPolicy::Degrade => {
+                if !self.degraded.swap(true, Ordering::AcqRel) {
+                    self.store(&SyncState {
+                        degraded: true,
+                        degraded_since_ms: Some(unix_time_ms()),
+                    });
+                    self.logger.warn(
+                        Event::ReplicationSyncDegraded.as_str(),
+                        &[
+                            ("reason", &message),
+                            ("sequence", &sequence.to_string()),
+                            (
+                                "warning",
+                                "writes are acknowledged without replica confirmation",
+                            ),
+                        ],
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Replace the stored state. Advisory: failures are ignored.
+    fn store(&self, state: &SyncState) {
+        let Some(dir) = self.path.parent() else {
+            return;
+        };
+        let temp_path = self.path.with_extension("tmp");
+        let _ = serde_json::to_vec(state)
+            .map_err(io::Error::other)
+            .and_then(|json| {
+                fs::create_dir_all(dir)?;
+                fs::write(&temp_path, json)
+            })
+            .and_then(|()| fs::rename(&temp_path, &self.path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::VecLogger;
+    use tempfile::TempDir;
+
+    fn quorum(on_timeout: SyncTimeoutPolicy) -> SyncReplicationConfig {
+        SyncReplicationConfig {
+            mode: SyncMode::Quorum,
+            min_sync_replicas: 1,
+            timeout: Duration::from_millis(20),
+            on_timeout,
+        }
+    }
+
+    #[test]
+    fn test_parse_names() {
+        assert_eq!(SyncMode::parse("quorum"), Ok(SyncMode::Quorum));
+        assert!(SyncMode::parse("sync").unwrap_err().contains("'async'"));
+        assert_eq!(
+            SyncTimeoutPolicy::parse("degrade"),
+            Ok(SyncTimeoutPolicy::Degrade)
+        );
+        assert!(SyncTimeoutPolicy::parse("strict").is_err());
+    }
+
+    #[test]
+    fn test_validate_quorum() {
+        let mut config = quorum(SyncTimeoutPolicy::Fail);
+        assert!(config.validate().is_ok());
+        config.min_sync_replicas = 0;
+        assert!(config.validate().is_err());
+        config.mode = SyncMode::Async;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_async_never_waits() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = Arc::new(ReplicaTracker::open(temp_dir.path()).unwrap());
+        let sync = SyncReplication::new(temp_dir.path(), SyncReplicationConfig::default(), tracker);
+        assert!(sync.wait_for(1).is_ok());
+    }
+
+    #[test]
+    fn test_strict_timeout_fails_the_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = Arc::new(ReplicaTracker::open(temp_dir.path()).unwrap());
+        let sync = SyncReplication::new(temp_dir.path(), quorum(SyncTimeoutPolicy::Fail), tracker);
+
+        let err = sync.wait_for(3).unwrap_err();
+        assert_eq!(
+            err.kind,
+            crate::replication::ReplicationErrorKind::ReplicationTimeout
+        );
+        assert!(err.message.contains("0 of 1"));
+        assert!(!sync.is_degraded());
+    }
+
+    #[test]
+    fn test_degrade_flags_and_logs_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracker = Arc::new(ReplicaTracker::open(temp_dir.path()).unwrap());
+        let logger = Arc::new(VecLogger::new());
+        let sync =
+            SyncReplication::new(temp_dir.path(), quorum(SyncTimeoutPolicy::Degrade), tracker)
+                .with_logger(logger.clone());
+
+        assert!(sync.wait_for(3).is_ok());
+        assert!(sync.wait_for(4).is_ok());
+        assert!(sync.is_degraded());
+        assert!(SyncState::load(temp_dir.path()).degraded);
+        assert_eq!(logger.events(), vec!["REPLICATION_SYNC_DEGRADED"]);
+    }
+}
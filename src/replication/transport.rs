@@ -0,0 +1,370 @@
+//! Replication Transport
+//!
+//! Framing and authentication for the WAL stream from a Primary to its
+//! Replicas over TCP.
+//!
+//! Every frame is a one-byte kind, a u32 LE payload length and the
+//! payload. Control frames carry a JSON [`ControlMessage`]; record frames
+//! carry one WAL record in its on-disk encoding, so the Replica verifies
+//! the record's own checksum before appending it. Chunk frames carry the
+//! files of a base snapshot to a bootstrapping Replica.
+//!
+//! Per REPLICATION_LOG_FLOW.md §2.1, records are never re-encoded in
+//! transit.
+//!
+//! A Replica proves it holds the shared secret by answering the Primary's
+//! random challenge with an HMAC-SHA256; the secret itself is never sent.
+//! The stream is not encrypted.
+
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use super::errors::{ReplicationError, ReplicationResult};
+
+/// Minimum length of the shared replication secret
+pub const MIN_SECRET_BYTES: usize = 16;
+
+/// Largest frame payload accepted (64MB)
+const MAX_FRAME_BYTES: usize = 64 * 1024 * 1024;
+
+const CONTROL_FRAME: u8 = 1;
+const RECORD_FRAME: u8 = 2;
+const CHUNK_FRAME: u8 = 3;
+
+/// WAL stream configuration, shared by Primary and Replica
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicationStreamConfig {
+    /// Address the Primary listens on for Replicas
+    pub listen_address: String,
+
+    /// Secret shared by the Primary and its Replicas
+    pub shared_secret: String,
+
+    /// Idle time after which the Primary sends a keepalive
+    pub keepalive_interval: Duration,
+
+    /// Wait between WAL polls once a Replica has caught up
+    pub poll_interval: Duration,
+
+    /// Records read from the WAL per poll
+    pub batch_size: usize,
+
+    /// Wait before a Replica reconnects after losing the Primary
+    pub reconnect_interval: Duration,
+
+    /// How long the Primary keeps the WAL after a base snapshot sent to a
+    /// bootstrapping Replica that has not started streaming yet
+    pub bootstrap_pin: Duration,
+}
+
+impl ReplicationStreamConfig {
+    /// Create a stream configuration with default timings.
+    pub fn new(listen_address: impl Into<String>, shared_secret: impl Into<String>) -> Self {
+        Self {
+            listen_address: listen_address.into(),
+            shared_secret: shared_secret.into(),
+            keepalive_interval: Duration::from_secs(1),
+            poll_interval: Duration::from_millis(20),
+            batch_size: 256,
+            reconnect_interval: Duration::from_secs(1),
+            bootstrap_pin: Duration::from_secs(3600),
+        }
+    }
+
+    /// Silence after which either side drops the connection.
+    ///
+    /// Three missed keepalives.
+    pub fn connection_timeout(&self) -> Duration {
+        self.keepalive_interval * 3
+    }
+
+    /// Validate the configuration.
+    pub fn validate(&self) -> ReplicationResult<()> {
+        if self.shared_secret.len() < MIN_SECRET_BYTES {
+            return Err(ReplicationError::configuration_error(format!(
+                "Replication secret must be at least {} bytes",
+                MIN_SECRET_BYTES
+            )));
+        }
+        if self.keepalive_interval.is_zero() {
+            return Err(ReplicationError::configuration_error(
+                "Replication keepalive interval must be > 0",
+            ));
+        }
+        if self.batch_size == 0 {
+            return Err(ReplicationError::configuration_error(
+                "Replication batch size must be > 0",
+            ));
+        }
+        if self.bootstrap_pin.is_zero() {
+            return Err(ReplicationError::configuration_error(
+                "Replication bootstrap pin must be > 0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Control messages exchanged on the stream
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlMessage {
+    /// Primary → Replica on connect
+    Challenge { nonce: String },
+
+    /// Replica → Primary: identity, proof of the secret and resume point
+    Hello {
+        replica_id: Uuid,
+        applied_sequence: u64,
+        proof: String,
+    },
+
+    /// Primary → Replica: records after `applied_sequence` follow
+    Accept,
+
+    /// Primary → Replica: the stream will not start
+    Refuse {
+        reason: RefuseReason,
+        message: String,
+    },
+
+    /// Primary → Replica when no records were sent for a keepalive interval
+    Keepalive,
+
+    /// Replica → Primary: every record up to `applied_sequence` is durable
+    Ack { applied_sequence: u64 },
+
+    /// Replica → Primary instead of Hello: identity and proof of the
+    /// secret, asking for a base snapshot
+    BootstrapRequest { replica_id: Uuid, proof: String },
+
+    /// Primary → Replica: the files of a base snapshot follow, in order,
+    /// as chunk frames
+    SnapshotOffer {
+        snapshot_id: String,
+        /// Last WAL sequence reflected by the snapshot; streaming resumes
+        /// after it
+        wal_sequence: u64,
+        /// Authority epoch of the Primary's WAL
+        epoch: u64,
+        files: Vec<SnapshotFile>,
+    },
+}
+
+/// One file of a base snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// Path relative to the snapshot directory, `/`-separated
+    pub path: String,
+
+    /// Length in bytes
+    pub size: u64,
+
+    /// CRC32 checksum (format: "crc32:XXXXXXXX")
+    pub checksum: String,
+}
+
+/// Why a Primary refused to stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefuseReason {
+    /// The Replica's proof did not match the shared secret
+    Unauthenticated,
+
+    /// The WAL after the Replica's applied sequence is not on the Primary
+    OffsetUnavailable,
+
+    /// The Primary has no base snapshot to bootstrap the Replica from
+    SnapshotUnavailable,
+}
+
+impl RefuseReason {
+    /// Error a Replica reports for this refusal.
+    pub fn to_error(self, message: impl Into<String>) -> ReplicationError {
+        match self {
+            Self::Unauthenticated => ReplicationError::unauthenticated(message),
+            Self::OffsetUnavailable => ReplicationError::offset_unavailable(message),
+            Self::SnapshotUnavailable => ReplicationError::bootstrap_failed(message),
+        }
+    }
+}
+
+/// One frame of the stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// A control message
+    Control(ControlMessage),
+
+    /// A serialized WAL record
+    Record(Vec<u8>),
+
+    /// Part of a base snapshot file
+    Chunk(Vec<u8>),
+}
+
+/// Write one frame. Buffered writers must be flushed by the caller.
+pub fn write_frame(writer: &mut impl Write, frame: &Frame) -> io::Result<()> {
+    let (kind, payload) = match frame {
+        Frame::Control(message) => (CONTROL_FRAME, serde_json::to_vec(message)?),
+        Frame::Record(bytes) => (RECORD_FRAME, bytes.clone()),
+        Frame::Chunk(bytes) => (CHUNK_FRAME, bytes.clone()),
+    };
+    writer.write_all(&[kind])?;
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(&payload)
+}
+
+/// Read one frame.
+///
+/// # Errors
+///
+/// `InvalidData` for an unknown kind, an oversized payload or a control
+/// payload that is not a [`ControlMessage`]; otherwise the read error.
+pub fn read_frame(reader: &mut impl Read) -> io::Result<Frame> {
+    let mut header = [0u8; 5];
+    reader.read_exact(&mut header)?;
+    let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if len > MAX_FRAME_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Frame of {} bytes exceeds {} bytes", len, MAX_FRAME_BYTES),
+        ));
+    }
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+    match header[0] {
+        CONTROL_FRAME => Ok(Frame::Control(serde_json::from_slice(&payload)?)),
+        RECORD_FRAME => Ok(Frame::Record(payload)),
+        CHUNK_FRAME => Ok(Frame::Chunk(payload)),
+        kind => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unknown frame kind {}", kind),
+        )),
+    }
+}
+
+/// Random challenge sent by the Primary, hex encoded.
+pub(crate) fn new_nonce() -> String {
+    let mut nonce = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    hex::encode(nonce)
+}
+
+/// Proof that a Replica holds `secret`, bound to the challenge and to the
+/// Hello fields it authenticates.
+pub(crate) fn handshake_proof(
+    secret: &str,
+    nonce: &str,
+    replica_id: Uuid,
+    applied_sequence: u64,
+) -> String {
+    hex::encode(
+        proof_mac(secret, nonce, replica_id, applied_sequence)
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+/// Check a Replica's proof in constant time.
+pub(crate) fn verify_proof(
+    secret: &str,
+    nonce: &str,
+    replica_id: Uuid,
+    applied_sequence: u64,
+    proof: &str,
+) -> bool {
+    let Ok(proof) = hex::decode(proof) else {
+        return false;
+    };
+    proof_mac(secret, nonce, replica_id, applied_sequence)
+        .verify_slice(&proof)
+        .is_ok()
+}
+
+fn proof_mac(secret: &str, nonce: &str, replica_id: Uuid, applied_sequence: u64) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can accept any key size");
+    mac.update(nonce.as_bytes());
+    mac.update(replica_id.as_bytes());
+    mac.update(&applied_sequence.to_le_bytes());
+    mac
+}
+
+/// Map an I/O error on the stream to a retryable transport error.
+pub(crate) fn transport_error(context: &str, e: io::Error) -> ReplicationError {
+    ReplicationError::transport(format!("{}: {}", context, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_round_trip() {
+        let frames = [
+            Frame::Control(ControlMessage::Hello {
+                replica_id: Uuid::new_v4(),
+                applied_sequence: 42,
+                proof: "00ff".to_string(),
+            }),
+            Frame::Control(ControlMessage::Keepalive),
+            Frame::Record(vec![1, 2, 3]),
+            Frame::Chunk(vec![4, 5]),
+        ];
+
+        let mut buf = Vec::new();
+        for frame in &frames {
+            write_frame(&mut buf, frame).unwrap();
+        }
+        let mut reader = buf.as_slice();
+        for frame in &frames {
+            assert_eq!(&read_frame(&mut reader).unwrap(), frame);
+        }
+        assert!(read_frame(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_rejects_oversized_and_unknown_frames() {
+        let mut oversized = vec![RECORD_FRAME];
+        oversized.extend_from_slice(&u32::MAX.to_le_bytes());
+        let err = read_frame(&mut oversized.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let unknown = [9u8, 0, 0, 0, 0];
+        let err = read_frame(&mut unknown.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_proof_binds_secret_and_hello_fields() {
+        let secret = "0123456789abcdef";
+        let nonce = new_nonce();
+        let id = Uuid::new_v4();
+        let proof = handshake_proof(secret, &nonce, id, 7);
+
+        assert!(verify_proof(secret, &nonce, id, 7, &proof));
+        assert!(!verify_proof("fedcba9876543210", &nonce, id, 7, &proof));
+        assert!(!verify_proof(secret, &new_nonce(), id, 7, &proof));
+        assert!(!verify_proof(secret, &nonce, id, 8, &proof));
+        assert!(!verify_proof(secret, &nonce, id, 7, "not-hex"));
+    }
+
+    #[test]
+    fn test_config_requires_long_secret() {
+        assert!(ReplicationStreamConfig::new("0.0.0.0:7000", "short")
+            .validate()
+            .is_err());
+        assert!(
+            ReplicationStreamConfig::new("0.0.0.0:7000", "0123456789abcdef")
+                .validate()
+                .is_ok()
+        );
+    }
+}
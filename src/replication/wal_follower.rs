@@ -0,0 +1,403 @@
+//! WAL Follower
+//!
+//! Replica side of the replication stream.
+//!
+//! On boot the Replica connects to its Primary, authenticates, and asks for
+//! the records after its applied sequence. Each record received is:
+//! 1. Checked for order by the [`WalReceiver`] (gaps halt replication)
+//! 2. Appended verbatim to the Replica's own WAL and synced
+//! 3. Applied to storage through the recovery apply path
+//!
+//! Once no more records are buffered, the applied sequence is stored in
+//! `data_dir/system/replication_offset` and acknowledged to the Primary.
+//!
+//! Applied records and keepalives advance the Replica's
+//! [`ReplicaFreshness`], which bounds the staleness of reads it serves.
+//!
+//! A record from an earlier authority epoch than the Replica's WAL is
+//! rejected with `FencedPrimary`: its Primary was superseded by a
+//! promotion and must not be followed.
+//!
+//! Per REPLICATION_LOG_FLOW.md §4.2, a record is replicated once it is
+//! durable in the Replica's WAL. A record in the local WAL but not yet in
+//! the stored offset (a crash between the two) is applied by boot-time
+//! WAL replay, so when the Primary resends it, it is not appended again.
+//!
+//! A lost connection is retried after `reconnect_interval`; the stream
+//! resumes after the stored offset. A refusal, gap or local failure stops
+//! the follower.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::errors::{ReplicationError, ReplicationErrorKind, ReplicationResult};
+use super::read_gate::{unix_time_ms, ReplicaFreshness};
+use super::transport::{
+    handshake_proof, read_frame, transport_error, write_frame, ControlMessage, Frame,
+    ReplicationStreamConfig,
+};
+use super::wal_receiver::{ReceiveResult, WalReceiver};
+use super::wal_sender::{WalPosition, WalRecordEnvelope};
+use crate::observability::{Event, JsonLogger, Logger};
+use crate::recovery::StorageApply;
+use crate::wal::{WalRecord, WalWriter};
+
+/// Path of the stored applied offset
+pub fn replication_offset_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("system").join("replication_offset")
+}
+
+/// Stored applied offset of a Replica
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedOffset {
+    /// Last sequence appended to the local WAL and applied
+    pub applied_sequence: u64,
+
+    /// Time, in ms since the epoch, the Replica was current as of (0 if
+    /// unknown)
+    #[serde(default)]
+    pub current_as_of_ms: u64,
+}
+
+impl AppliedOffset {
+    /// Load the stored applied offset of the Replica in `data_dir`, if any.
+    pub fn load(data_dir: &Path) -> ReplicationResult<Option<Self>> {
+        let path = replication_offset_path(data_dir);
+        match fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents).map(Some).map_err(|e| {
+                ReplicationError::configuration_error(format!(
+                    "Invalid applied offset in {}: {}",
+                    path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ReplicationError::configuration_error(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Durably replace the offset stored at `path`.
+    pub(crate) fn store(&self, path: &Path) -> ReplicationResult<()> {
+        let dir = path.parent().expect("offset path has a parent directory");
+        let temp_path = path.with_extension("tmp");
+        let json =
+            serde_json::to_vec(self).map_err(|e| ReplicationError::apply_failed(e.to_string()))?;
+
+        fs::create_dir_all(dir)
+            .and_then(|()| {
+                let mut file = File::create(&temp_path)?;
+                file.write_all(&json)?;
+                file.sync_all()
+            })
+            .and_then(|()| fs::rename(&temp_path, path))
+            .and_then(|()| File::open(dir)?.sync_all())
+            .map_err(|e| {
+                ReplicationError::apply_failed(format!(
+                    "Failed to store applied offset in {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+}
+
+/// Replica-side client that follows a Primary's WAL
+pub struct WalFollower<S: StorageApply> {
+    /// Primary address (`host:port`)
+    primary_address: String,
+
+    /// This Replica's identity
+    replica_id: Uuid,
+
+    /// Stream configuration
+    config: ReplicationStreamConfig,
+
+    /// The Replica's own WAL
+    wal: WalWriter,
+
+    /// Storage records are applied to
+    storage: S,
+
+    /// Where the applied offset is stored
+    offset_path: PathBuf,
+
+    /// Ordering checks for received records
+    receiver: WalReceiver,
+
+    /// How current the applied state is
+    freshness: Arc<ReplicaFreshness>,
+
+    /// Offset last stored
+    stored: AppliedOffset,
+
+    /// Where interrupted connections are logged
+    logger: Arc<dyn Logger>,
+}
+
+impl<S: StorageApply> WalFollower<S> {
+    /// Prepare to follow `primary_address` from the stored applied offset.
+    ///
+    /// Without a stored offset, the Replica has applied everything in its
+    /// WAL (boot-time recovery replayed it), e.g. after a re-seed.
+    ///
+    /// # Errors
+    ///
+    /// `HistoryDivergence` if the stored offset is ahead of the local WAL.
+    pub fn open(
+        data_dir: impl AsRef<Path>,
+        replica_id: Uuid,
+        primary_address: impl Into<String>,
+        config: ReplicationStreamConfig,
+        wal: WalWriter,
+        storage: S,
+    ) -> ReplicationResult<Self> {
+        config.validate()?;
+        let stored = AppliedOffset::load(data_dir.as_ref())?.unwrap_or(AppliedOffset {
+            applied_sequence: wal.last_sequence_number(),
+            current_as_of_ms: 0,
+        });
+        let applied_sequence = stored.applied_sequence;
+        if applied_sequence > wal.last_sequence_number() {
+            return Err(ReplicationError::history_divergence(format!(
+                "Applied offset {} is ahead of the local WAL, which ends at sequence {}",
+                applied_sequence,
+                wal.last_sequence_number()
+            )));
+        }
+
+        let mut receiver = WalReceiver::after_snapshot(applied_sequence, 0);
+        receiver.start();
+        let freshness = ReplicaFreshness::new(stored.current_as_of_ms);
+        freshness.observe(wal.last_commit_timestamp_ms());
+
+        Ok(Self {
+            primary_address: primary_address.into(),
+            replica_id,
+            config,
+            wal,
+            storage,
+            offset_path: replication_offset_path(data_dir.as_ref()),
+            receiver,
+            freshness: Arc::new(freshness),
+            stored,
+            logger: JsonLogger::shared(),
+        })
+    }
+
+    /// Log interrupted connections to `logger`.
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Last sequence appended and applied.
+    pub fn applied_sequence(&self) -> u64 {
+        self.receiver.expected_sequence() - 1
+    }
+
+    /// Freshness of the applied state, advanced while following.
+    pub fn freshness(&self) -> Arc<ReplicaFreshness> {
+        Arc::clone(&self.freshness)
+    }
+
+    /// Follow the Primary until `stop` is set, reconnecting whenever the
+    /// connection is lost. Blocks the calling thread.
+    ///
+    /// # Errors
+    ///
+    /// Any failure other than a lost connection: a refusal from the
+    /// Primary, a WAL gap or a local append/apply failure.
+    pub fn run(&mut self, stop: &AtomicBool) -> ReplicationResult<()> {
+        while !stop.load(Ordering::Acquire) {
+            match self.follow_once(stop) {
+                Ok(()) => {}
+                Err(e) if e.kind == ReplicationErrorKind::Transport => {
+                    self.logger.warn(
+                        Event::ReplicationStreamInterrupted.as_str(),
+                        &[
+                            ("primary", &self.primary_address),
+                            ("error", &e.message),
+                            ("resume_after", &self.applied_sequence().to_string()),
+                        ],
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+            if !stop.load(Ordering::Acquire) {
+                thread::sleep(self.config.reconnect_interval);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run one connection: handshake, then apply records until the
+    /// connection fails or `stop` is set.
+    pub fn follow_once(&mut self, stop: &AtomicBool) -> ReplicationResult<()> {
+        let stream = TcpStream::connect(&self.primary_address).map_err(|e| {
+            transport_error(&format!("Failed to connect to {}", self.primary_address), e)
+        })?;
+        stream
+            .set_nodelay(true)
+            .and_then(|()| stream.set_read_timeout(Some(self.config.connection_timeout())))
+            .map_err(|e| transport_error("Failed to configure connection", e))?;
+        let mut reader = BufReader::new(
+            stream
+                .try_clone()
+                .map_err(|e| transport_error("Failed to clone connection", e))?,
+        );
+        let mut writer = BufWriter::new(stream);
+
+        let nonce = match self.read(&mut reader)? {
+            Frame::Control(ControlMessage::Challenge { nonce }) => nonce,
+            other => return Err(unexpected("challenge", &other)),
+        };
+        let applied_sequence = self.applied_sequence();
+        send(
+            &mut writer,
+            ControlMessage::Hello {
+                replica_id: self.replica_id,
+                applied_sequence,
+                proof: handshake_proof(
+                    &self.config.shared_secret,
+                    &nonce,
+                    self.replica_id,
+                    applied_sequence,
+                ),
+            },
+        )?;
+        match self.read(&mut reader)? {
+            Frame::Control(ControlMessage::Accept) => {}
+            Frame::Control(ControlMessage::Refuse { reason, message }) => {
+                return Err(reason.to_error(message))
+            }
+            other => return Err(unexpected("accept", &other)),
+        }
+
+        let mut unacked = false;
+        while !stop.load(Ordering::Acquire) {
+            match self.read(&mut reader)? {
+                Frame::Record(bytes) => self.apply_record(&bytes)?,
+                // Sent only once the Primary has nothing left to send, so
+                // the Replica is current. Acked so the Primary sees it is alive
+                Frame::Control(ControlMessage::Keepalive) => self.freshness.observe(unix_time_ms()),
+                other => return Err(unexpected("record", &other)),
+            }
+            unacked = true;
+
+            if reader.buffer().is_empty() {
+                self.store_offset()?;
+                send(
+                    &mut writer,
+                    ControlMessage::Ack {
+                        applied_sequence: self.applied_sequence(),
+                    },
+                )?;
+                unacked = false;
+            }
+        }
+        if unacked {
+            self.store_offset()?;
+        }
+        Ok(())
+    }
+
+    /// Append and apply one received record.
+    fn apply_record(&mut self, bytes: &[u8]) -> ReplicationResult<()> {
+        let (record, len) = WalRecord::deserialize(bytes).map_err(|e| {
+            ReplicationError::wal_integrity_failed(format!("Invalid record from primary: {}", e))
+        })?;
+        if len != bytes.len() {
+            return Err(ReplicationError::wal_integrity_failed(format!(
+                "Record frame holds {} bytes after the record",
+                bytes.len() - len
+            )));
+        }
+
+        if record.epoch < self.wal.epoch() {
+            return Err(ReplicationError::fenced_primary(format!(
+                "Record {} from {} is from epoch {}, but this replica has seen epoch {}; \
+                 the primary was superseded by a promotion",
+                record.sequence_number,
+                self.primary_address,
+                record.epoch,
+                self.wal.epoch()
+            )));
+        }
+
+        let sequence = record.sequence_number;
+        let envelope = WalRecordEnvelope::new(WalPosition::new(sequence, 0), record);
+        match self.receiver.receive(&envelope) {
+            ReceiveResult::Accepted => {}
+            ReceiveResult::Duplicate => return Ok(()),
+            result => return result.to_result(),
+        }
+
+        // Otherwise already in the local WAL and applied by boot replay
+        if sequence >= self.wal.next_sequence_number() {
+            self.wal
+                .append_replicated(&envelope.record)
+                .map_err(|e| ReplicationError::apply_failed(e.to_string()))?;
+            self.storage
+                .apply_wal_record(&envelope.record)
+                .map_err(|e| ReplicationError::apply_failed(e.to_string()))?;
+        }
+        self.receiver.apply(&envelope, bytes.len() as u64);
+        if let Some(timestamp_ms) = envelope.record.commit_timestamp_ms {
+            self.freshness.observe(timestamp_ms);
+        }
+        Ok(())
+    }
+
+    /// Read a frame, treating silence past the connection timeout as a
+    /// lost connection.
+    fn read(&self, reader: &mut BufReader<TcpStream>) -> ReplicationResult<Frame> {
+        read_frame(reader).map_err(|e| {
+            let context = match e.kind() {
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => format!(
+                    "No traffic from {} for {:?}",
+                    self.primary_address,
+                    self.config.connection_timeout()
+                ),
+                _ => format!("Connection to {} failed", self.primary_address),
+            };
+            transport_error(&context, e)
+        })
+    }
+
+    /// Durably replace the stored applied offset, if it changed.
+    fn store_offset(&mut self) -> ReplicationResult<()> {
+        let offset = AppliedOffset {
+            applied_sequence: self.applied_sequence(),
+            current_as_of_ms: self.freshness.current_as_of_ms().unwrap_or(0),
+        };
+        if offset == self.stored {
+            return Ok(());
+        }
+        offset.store(&self.offset_path)?;
+        self.stored = offset;
+        Ok(())
+    }
+}
+
+/// Write and flush one control message.
+fn send(writer: &mut BufWriter<TcpStream>, message: ControlMessage) -> ReplicationResult<()> {
+    write_frame(writer, &Frame::Control(message))
+        .and_then(|()| writer.flush())
+        .map_err(|e| transport_error("Failed to send control message", e))
+}
+
+fn unexpected(expected: &str, frame: &Frame) -> ReplicationError {
+    ReplicationError::transport(format!("Expected {}, received {:?}", expected, frame))
+}
@@ -304,6 +304,8 @@ mod tests {
                 schema_version: "v1".to_string(),
                 document_body: vec![],
             },
+            commit_timestamp_ms: None,
+            epoch: 0,
         }
     }
 }
@@ -0,0 +1,627 @@
+//! WAL Streamer
+//!
+//! Primary side of the replication stream.
+//!
+//! The Primary listens for Replicas and, per Replica:
+//! 1. Authenticates it (see `transport`)
+//! 2. Locates the record after the Replica's applied sequence, refusing
+//!    if a checkpoint already truncated it (the Replica must be re-seeded
+//!    from a backup) or if the Replica is ahead of the WAL
+//! 3. Tails the WAL, sending records in order, with a keepalive whenever
+//!    nothing was sent for a keepalive interval
+//! 4. Records the Replica's acknowledgments in the [`ReplicaTracker`]
+//!
+//! A Replica with an empty data directory asks for a base snapshot
+//! instead (see `bootstrap`). The Primary sends keepalives while its
+//! [`BaseSnapshotSource`] prepares one, pins the WAL after it, and sends
+//! its files.
+//!
+//! Per REPLICATION_LOG_FLOW.md §3.1, records are sent strictly in WAL
+//! order; the stream ends rather than skip a sequence number.
+
+use std::collections::VecDeque;
+use std::io::{BufReader, BufWriter, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use super::bootstrap::{BaseSnapshotSource, LatestSnapshot, PreparedSnapshot};
+use super::errors::{ReplicationError, ReplicationErrorKind, ReplicationResult};
+use super::read_gate::unix_time_ms;
+use super::replica_progress::{ReplicaProgress, ReplicaTracker};
+use super::transport::{
+    new_nonce, read_frame, transport_error, verify_proof, write_frame, ControlMessage, Frame,
+    RefuseReason, ReplicationStreamConfig,
+};
+use crate::observability::{Event, JsonLogger, Logger};
+use crate::wal::{
+    detect_layout, list_segments, parse_segment_file_name, read_epoch, WalLayout, WalReader,
+};
+
+/// (sequence, segment, end offset) of a record sent to a Replica
+type SentRecord = (u64, u64, u64);
+
+/// Primary-side listener that streams the WAL to Replicas
+#[derive(Debug)]
+pub struct WalStreamer {
+    /// Listening socket
+    listener: TcpListener,
+
+    /// Data directory being replicated
+    data_dir: PathBuf,
+
+    /// WAL directory being streamed
+    wal_dir: PathBuf,
+
+    /// Stream configuration
+    config: ReplicationStreamConfig,
+
+    /// Per-Replica progress
+    tracker: Arc<ReplicaTracker>,
+
+    /// Snapshots bootstrapping Replicas start from
+    snapshots: Arc<dyn BaseSnapshotSource>,
+
+    /// Where ended streams are logged
+    logger: Arc<dyn Logger>,
+}
+
+impl WalStreamer {
+    /// Listen on `config.listen_address` for Replicas of `data_dir`.
+    pub fn bind(
+        data_dir: impl AsRef<Path>,
+        config: ReplicationStreamConfig,
+    ) -> ReplicationResult<Self> {
+        config.validate()?;
+        let data_dir = data_dir.as_ref();
+        let listener = TcpListener::bind(&config.listen_address).map_err(|e| {
+            transport_error(&format!("Failed to listen on {}", config.listen_address), e)
+        })?;
+
+        Ok(Self {
+            listener,
+            data_dir: data_dir.to_path_buf(),
+            wal_dir: data_dir.join("wal"),
+            config,
+            tracker: Arc::new(ReplicaTracker::open(data_dir)?),
+            snapshots: Arc::new(LatestSnapshot::new(data_dir)),
+            logger: JsonLogger::shared(),
+        })
+    }
+
+    /// Bootstrap Replicas from the snapshots `source` names, instead of the
+    /// latest snapshot in the data directory.
+    pub fn with_snapshot_source(mut self, source: Arc<dyn BaseSnapshotSource>) -> Self {
+        self.snapshots = source;
+        self
+    }
+
+    /// Log streams that end in an error to `logger`.
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Address the listener is bound to.
+    pub fn local_addr(&self) -> ReplicationResult<SocketAddr> {
+        self.listener
+            .local_addr()
+            .map_err(|e| transport_error("Failed to read listen address", e))
+    }
+
+    /// Per-Replica progress.
+    pub fn tracker(&self) -> Arc<ReplicaTracker> {
+        Arc::clone(&self.tracker)
+    }
+
+    /// Accept and serve Replicas until `stop` is set. Blocks the calling
+    /// thread; each Replica is served on its own thread.
+    pub fn run(&self, stop: &AtomicBool) -> ReplicationResult<()> {
+        self.listener
+            .set_nonblocking(true)
+            .map_err(|e| transport_error("Failed to configure listener", e))?;
+
+        thread::scope(|scope| {
+            while !stop.load(Ordering::Acquire) {
+                match self.listener.accept() {
+                    Ok((stream, peer)) => {
+                        scope.spawn(move || {
+                            if let Err(e) = self.serve_replica(stream, peer, stop) {
+                                self.logger.warn(
+                                    Event::ReplicationStreamEnded.as_str(),
+                                    &[("replica", &peer.to_string()), ("error", &e.to_string())],
+                                );
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        thread::sleep(self.config.poll_interval);
+                    }
+                    Err(e) => return Err(transport_error("Failed to accept replica", e)),
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Handshake with one Replica, then stream until it disconnects or
+    /// `stop` is set.
+    fn serve_replica(
+        &self,
+        stream: TcpStream,
+        peer: SocketAddr,
+        stop: &AtomicBool,
+    ) -> ReplicationResult<()> {
+        stream
+            .set_nonblocking(false)
+            .and_then(|()| stream.set_nodelay(true))
+            .and_then(|()| stream.set_read_timeout(Some(self.config.connection_timeout())))
+            .map_err(|e| transport_error("Failed to configure connection", e))?;
+        let mut reader = BufReader::new(
+            stream
+                .try_clone()
+                .map_err(|e| transport_error("Failed to clone connection", e))?,
+        );
+        let mut writer = BufWriter::new(stream);
+
+        let nonce = new_nonce();
+        send(
+            &mut writer,
+            ControlMessage::Challenge {
+                nonce: nonce.clone(),
+            },
+        )?;
+        let (replica_id, applied_sequence, proof) =
+            match read_frame(&mut reader).map_err(|e| transport_error("Handshake failed", e))? {
+                Frame::Control(ControlMessage::Hello {
+                    replica_id,
+                    applied_sequence,
+                    proof,
+                }) => (replica_id, applied_sequence, proof),
+                Frame::Control(ControlMessage::BootstrapRequest { replica_id, proof }) => {
+                    if !verify_proof(&self.config.shared_secret, &nonce, replica_id, 0, &proof) {
+                        let message = format!("Replica {} failed authentication", replica_id);
+                        refuse(&mut writer, RefuseReason::Unauthenticated, &message);
+                        return Err(ReplicationError::unauthenticated(message));
+                    }
+                    return self.serve_bootstrap(&mut writer, replica_id, peer);
+                }
+                other => {
+                    return Err(ReplicationError::transport(format!(
+                        "Expected hello, received {:?}",
+                        other
+                    )))
+                }
+            };
+
+        if !verify_proof(
+            &self.config.shared_secret,
+            &nonce,
+            replica_id,
+            applied_sequence,
+            &proof,
+        ) {
+            let message = format!("Replica {} failed authentication", replica_id);
+            refuse(&mut writer, RefuseReason::Unauthenticated, &message);
+            return Err(ReplicationError::unauthenticated(message));
+        }
+
+        let mut tail = match WalTail::locate(&self.wal_dir, applied_sequence) {
+            Ok(tail) => tail,
+            Err(e) if e.kind == ReplicationErrorKind::OffsetUnavailable => {
+                refuse(&mut writer, RefuseReason::OffsetUnavailable, &e.message);
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
+        send(&mut writer, ControlMessage::Accept)?;
+
+        self.tracker.connected(ReplicaProgress {
+            replica_id,
+            address: peer.to_string(),
+            connected: true,
+            acked_sequence: applied_sequence,
+            acked_segment: tail.segment,
+            acked_offset: tail.offset,
+            pinned_until_ms: None,
+        });
+
+        // Records sent but not yet acked
+        let in_flight = Mutex::new(VecDeque::new());
+        let closed = AtomicBool::new(false);
+        let result = thread::scope(|scope| {
+            scope.spawn(|| {
+                self.read_acks(&mut reader, replica_id, &in_flight);
+                closed.store(true, Ordering::Release);
+            });
+            let result = self.stream_records(&mut writer, &mut tail, &in_flight, &closed, stop);
+            // Unblocks the ack reader
+            let _ = writer.get_ref().shutdown(Shutdown::Both);
+            result
+        });
+
+        self.tracker.disconnected(replica_id);
+        result
+    }
+
+    /// Send a base snapshot to a Replica being bootstrapped.
+    ///
+    /// The WAL after the snapshot stays pinned for the Replica until it
+    /// starts streaming or `bootstrap_pin` elapses; a failed transfer
+    /// releases it.
+    fn serve_bootstrap(
+        &self,
+        writer: &mut BufWriter<TcpStream>,
+        replica_id: uuid::Uuid,
+        peer: SocketAddr,
+    ) -> ReplicationResult<()> {
+        let scratch = self
+            .data_dir
+            .join("system")
+            .join(format!("bootstrap-{}", replica_id));
+        let prepared = self.keep_alive_while(writer, || {
+            let snapshot_id = self.snapshots.base_snapshot()?;
+            PreparedSnapshot::prepare(&self.data_dir, &snapshot_id, scratch)
+        })?;
+        let prepared = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                refuse(writer, RefuseReason::SnapshotUnavailable, &e.message);
+                return Err(e);
+            }
+        };
+
+        // Pinned before it is checked, so a checkpoint cannot truncate it
+        // in between
+        let pinned_until_ms = unix_time_ms() + self.config.bootstrap_pin.as_millis() as u64;
+        self.tracker.pin(
+            replica_id,
+            peer.to_string(),
+            prepared.wal_sequence,
+            pinned_until_ms,
+        );
+        let result = self.send_snapshot(writer, &prepared);
+        if result.is_err() {
+            self.tracker.unpin(replica_id);
+        }
+        result
+    }
+
+    /// Offer a prepared snapshot and send its files.
+    fn send_snapshot(
+        &self,
+        writer: &mut BufWriter<TcpStream>,
+        prepared: &PreparedSnapshot,
+    ) -> ReplicationResult<()> {
+        if let Err(e) = WalTail::locate(&self.wal_dir, prepared.wal_sequence) {
+            let reason = if e.kind == ReplicationErrorKind::OffsetUnavailable {
+                RefuseReason::OffsetUnavailable
+            } else {
+                RefuseReason::SnapshotUnavailable
+            };
+            refuse(writer, reason, &e.message);
+            return Err(e);
+        }
+        send(
+            writer,
+            ControlMessage::SnapshotOffer {
+                snapshot_id: prepared.snapshot_id.clone(),
+                wal_sequence: prepared.wal_sequence,
+                epoch: read_epoch(&self.wal_dir).map_err(wal_error)?,
+                files: prepared.files.clone(),
+            },
+        )?;
+        prepared.send(writer)
+    }
+
+    /// Run `f`, sending keepalives while it runs so the Replica does not
+    /// time out.
+    fn keep_alive_while<T: Send>(
+        &self,
+        writer: &mut BufWriter<TcpStream>,
+        f: impl FnOnce() -> T + Send,
+    ) -> ReplicationResult<T> {
+        thread::scope(|scope| {
+            let running = scope.spawn(f);
+            let mut last_sent = Instant::now();
+            while !running.is_finished() {
+                thread::sleep(self.config.poll_interval);
+                if last_sent.elapsed() >= self.config.keepalive_interval {
+                    send(writer, ControlMessage::Keepalive)?;
+                    last_sent = Instant::now();
+                }
+            }
+            Ok(running
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+        })
+    }
+
+    /// Send WAL records after the tail's position until the connection
+    /// closes or `stop` is set.
+    fn stream_records(
+        &self,
+        writer: &mut BufWriter<TcpStream>,
+        tail: &mut WalTail,
+        in_flight: &Mutex<VecDeque<SentRecord>>,
+        closed: &AtomicBool,
+        stop: &AtomicBool,
+    ) -> ReplicationResult<()> {
+        let mut last_sent = Instant::now();
+        while !stop.load(Ordering::Acquire) && !closed.load(Ordering::Acquire) {
+            let records = tail.poll(self.config.batch_size)?;
+            if records.is_empty() {
+                if last_sent.elapsed() >= self.config.keepalive_interval {
+                    send(writer, ControlMessage::Keepalive)?;
+                    last_sent = Instant::now();
+                }
+                thread::sleep(self.config.poll_interval);
+                continue;
+            }
+
+            for (position, bytes) in records {
+                // Queued before sending so an ack never precedes it
+                in_flight
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push_back(position);
+                write_frame(writer, &Frame::Record(bytes))
+                    .map_err(|e| transport_error("Failed to send record", e))?;
+            }
+            writer
+                .flush()
+                .map_err(|e| transport_error("Failed to send records", e))?;
+            last_sent = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Record acknowledgments until the connection fails or goes silent.
+    fn read_acks(
+        &self,
+        reader: &mut BufReader<TcpStream>,
+        replica_id: uuid::Uuid,
+        in_flight: &Mutex<VecDeque<SentRecord>>,
+    ) {
+        while let Ok(frame) = read_frame(reader) {
+            let Frame::Control(ControlMessage::Ack { applied_sequence }) = frame else {
+                return;
+            };
+            let mut acked = None;
+            {
+                let mut in_flight = in_flight.lock().unwrap_or_else(|e| e.into_inner());
+                while in_flight
+                    .front()
+                    .is_some_and(|&(sequence, _, _)| sequence <= applied_sequence)
+                {
+                    acked = in_flight.pop_front();
+                }
+            }
+            if let Some((sequence, segment, offset)) = acked {
+                self.tracker.acked(replica_id, sequence, segment, offset);
+            }
+        }
+    }
+}
+
+/// Write and flush one control message.
+fn send(writer: &mut BufWriter<TcpStream>, message: ControlMessage) -> ReplicationResult<()> {
+    write_frame(writer, &Frame::Control(message))
+        .and_then(|()| writer.flush())
+        .map_err(|e| transport_error("Failed to send control message", e))
+}
+
+/// Tell a Replica why the stream will not start. Best effort: the
+/// connection is closed either way.
+fn refuse(writer: &mut BufWriter<TcpStream>, reason: RefuseReason, message: &str) {
+    let _ = send(
+        writer,
+        ControlMessage::Refuse {
+            reason,
+            message: message.to_string(),
+        },
+    );
+}
+
+/// Position just after the last record sent to a Replica
+#[derive(Debug)]
+struct WalTail {
+    /// WAL directory being followed
+    wal_dir: PathBuf,
+
+    /// Segment holding the last record sent (0 before the first segment)
+    segment: u64,
+
+    /// Byte offset just past the last record sent
+    offset: u64,
+
+    /// Sequence number of the last record sent
+    sequence: u64,
+}
+
+impl WalTail {
+    /// Position the tail just after record `applied_sequence`.
+    ///
+    /// # Errors
+    ///
+    /// `OffsetUnavailable` if the records after `applied_sequence` were
+    /// truncated or the WAL ends before `applied_sequence`.
+    fn locate(wal_dir: &Path, applied_sequence: u64) -> ReplicationResult<Self> {
+        let mut tail = Self {
+            wal_dir: wal_dir.to_path_buf(),
+            segment: 0,
+            offset: 0,
+            sequence: applied_sequence,
+        };
+        let mut last_sequence = 0;
+
+        match detect_layout(wal_dir).map_err(wal_error)? {
+            WalLayout::Empty => {}
+            WalLayout::Legacy(_) => {
+                return Err(ReplicationError::configuration_error(
+                    "Streaming requires a segmented WAL",
+                ))
+            }
+            WalLayout::Segmented(_) => {
+                let mut reader = WalReader::open_segments(wal_dir).map_err(wal_error)?;
+                while let Some(record) = reader.read_next().map_err(wal_error)? {
+                    let sequence = record.sequence_number;
+                    if last_sequence == 0 && sequence > applied_sequence + 1 {
+                        return Err(ReplicationError::offset_unavailable(format!(
+                            "WAL before sequence {} was truncated by a checkpoint but the \
+                             replica has only applied sequence {}; re-seed the replica \
+                             from a backup",
+                            sequence, applied_sequence
+                        )));
+                    }
+                    last_sequence = sequence;
+                    if sequence == applied_sequence + 1 {
+                        tail.segment = segment_of(reader.path());
+                        tail.offset = reader.last_record_offset();
+                        return Ok(tail);
+                    }
+                }
+                // Caught up: continue after the last record read
+                tail.segment = segment_of(reader.path());
+                tail.offset = reader.current_offset();
+            }
+        }
+
+        if last_sequence < applied_sequence {
+            return Err(ReplicationError::offset_unavailable(format!(
+                "Replica has applied sequence {} but the primary WAL ends at sequence {}; \
+                 re-seed the replica from a backup",
+                applied_sequence, last_sequence
+            )));
+        }
+        Ok(tail)
+    }
+
+    /// Read up to `max` records after the position, returning each with
+    /// its (sequence, segment, end offset) and its serialized bytes.
+    fn poll(&mut self, max: usize) -> ReplicationResult<Vec<(SentRecord, Vec<u8>)>> {
+        let files: Vec<PathBuf> = list_segments(&self.wal_dir)
+            .map_err(wal_error)?
+            .into_iter()
+            .filter(|segment| segment.index >= self.segment)
+            .map(|segment| segment.path)
+            .collect();
+        let Some(first) = files.first() else {
+            return Ok(Vec::new());
+        };
+        let offset = if segment_of(first) == self.segment {
+            self.offset
+        } else {
+            0
+        };
+
+        let mut reader = WalReader::open_history(files).map_err(wal_error)?;
+        reader.seek_to(offset, self.sequence).map_err(wal_error)?;
+
+        let mut records = Vec::new();
+        while records.len() < max {
+            let Some(record) = reader.read_next().map_err(wal_error)? else {
+                break;
+            };
+            if record.sequence_number != self.sequence + 1 {
+                return Err(ReplicationError::offset_unavailable(format!(
+                    "Primary WAL continues at sequence {} after sequence {}; re-seed the \
+                     replica from a backup",
+                    record.sequence_number, self.sequence
+                )));
+            }
+            self.segment = segment_of(reader.path());
+            self.offset = reader.current_offset();
+            self.sequence = record.sequence_number;
+            records.push((
+                (self.sequence, self.segment, self.offset),
+                record.serialize(),
+            ));
+        }
+        Ok(records)
+    }
+}
+
+/// Segment index of a WAL file
+fn segment_of(path: &Path) -> u64 {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(parse_segment_file_name)
+        .unwrap_or(0)
+}
+
+fn wal_error(e: crate::wal::WalError) -> ReplicationError {
+    ReplicationError::wal_integrity_failed(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::truncate_wal_before;
+    use crate::wal::{WalPayload, WalSegmentConfig, WalSyncConfig, WalWriter};
+    use tempfile::TempDir;
+
+    fn segmented_wal(data_dir: &Path, records: usize) -> WalWriter {
+        let mut wal =
+            WalWriter::open_segmented(data_dir, WalSyncConfig::default(), WalSegmentConfig::new(1))
+                .unwrap();
+        for i in 0..records {
+            let payload = WalPayload::new("users", format!("u{}", i), "user", "v1", b"{}".to_vec());
+            wal.append_insert(payload).unwrap();
+        }
+        wal
+    }
+
+    fn sequences(tail: &mut WalTail) -> Vec<u64> {
+        tail.poll(100)
+            .unwrap()
+            .into_iter()
+            .map(|((sequence, _, _), _)| sequence)
+            .collect()
+    }
+
+    #[test]
+    fn test_tail_resumes_after_applied_sequence() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut wal = segmented_wal(temp_dir.path(), 4);
+        let wal_dir = temp_dir.path().join("wal");
+
+        assert_eq!(
+            sequences(&mut WalTail::locate(&wal_dir, 0).unwrap()),
+            [1, 2, 3, 4]
+        );
+        let mut tail = WalTail::locate(&wal_dir, 2).unwrap();
+        assert_eq!(sequences(&mut tail), [3, 4]);
+        assert!(sequences(&mut tail).is_empty());
+
+        wal.append_insert(WalPayload::new("users", "u9", "user", "v1", b"{}".to_vec()))
+            .unwrap();
+        assert_eq!(sequences(&mut tail), [5]);
+        assert_eq!(
+            sequences(&mut WalTail::locate(&wal_dir, 5).unwrap()),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn test_refuses_truncated_or_future_offsets() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut wal = segmented_wal(temp_dir.path(), 4);
+        let wal_dir = temp_dir.path().join("wal");
+        truncate_wal_before(&mut wal, 2).unwrap();
+
+        let err = WalTail::locate(&wal_dir, 1).unwrap_err();
+        assert_eq!(err.kind, ReplicationErrorKind::OffsetUnavailable);
+        assert!(err.message.contains("re-seed"));
+        assert_eq!(
+            sequences(&mut WalTail::locate(&wal_dir, 2).unwrap()),
+            [3, 4]
+        );
+
+        let err = WalTail::locate(&wal_dir, 9).unwrap_err();
+        assert_eq!(err.kind, ReplicationErrorKind::OffsetUnavailable);
+    }
+}
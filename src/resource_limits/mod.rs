@@ -14,20 +14,26 @@
 //! All limits are configurable via aerodb.toml [resource_limits] section.
 
 use std::path::Path;
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
+use crate::observability::{JsonLogger, Logger};
+
 mod errors;
 pub use errors::{ResourceError, ResourceResult, ResourceType};
 
 /// Resource limits configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ResourceLimitsConfig {
     /// Minimum free disk bytes before refusing writes (default: 1GB)
     pub min_free_disk_bytes: u64,
     /// Maximum memory bytes allowed (default: 4GB)
     pub max_memory_bytes: u64,
+    /// Memory edge function invocations reserve from, apart from
+    /// `max_memory_bytes` (default: 512MB)
+    pub max_edge_function_memory_bytes: u64,
     /// Maximum file descriptors (default: 90% of ulimit)
     pub max_file_descriptors: usize,
     /// Maximum documents in a result set
@@ -43,6 +49,7 @@ impl Default for ResourceLimitsConfig {
         Self {
             min_free_disk_bytes: 1024 * 1024 * 1024, // 1GB
             max_memory_bytes: 4 * 1024 * 1024 * 1024, // 4GB
+            max_edge_function_memory_bytes: 512 * 1024 * 1024, // 512MB
             max_file_descriptors: 1000,
             max_result_set_docs: 10000,
             warning_threshold_percent: 75,
@@ -52,7 +59,8 @@ impl Default for ResourceLimitsConfig {
 }
 
 /// System health status
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HealthStatus {
     /// All resources healthy
     Normal,
@@ -65,7 +73,7 @@ pub enum HealthStatus {
 }
 
 /// Current resource status snapshot
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ResourceStatus {
     pub disk_usage_bytes: u64,
     pub disk_total_bytes: u64,
@@ -315,24 +323,47 @@ impl DiskSpaceChecker {
 /// Centralized resource manager
 #[derive(Debug)]
 pub struct ResourceManager {
-    config: ResourceLimitsConfig,
+    /// Health thresholds, adjustable at runtime by config reload
+    warning_threshold_percent: AtomicU8,
+    critical_threshold_percent: AtomicU8,
     memory: Arc<MemoryTracker>,
+    edge_function_memory: Arc<MemoryTracker>,
     file_descriptors: Arc<FileDescriptorTracker>,
     disk: Arc<DiskSpaceChecker>,
     read_only_mode: std::sync::atomic::AtomicBool,
+    logger: Arc<dyn Logger>,
 }
 
 impl ResourceManager {
     pub fn new(config: ResourceLimitsConfig, data_path: impl AsRef<Path>) -> Self {
         Self {
             memory: Arc::new(MemoryTracker::new(config.max_memory_bytes)),
+            edge_function_memory: Arc::new(MemoryTracker::new(
+                config.max_edge_function_memory_bytes,
+            )),
             file_descriptors: Arc::new(FileDescriptorTracker::new(config.max_file_descriptors)),
             disk: Arc::new(DiskSpaceChecker::new(data_path, config.min_free_disk_bytes)),
             read_only_mode: std::sync::atomic::AtomicBool::new(false),
-            config,
+            logger: JsonLogger::shared(),
+            warning_threshold_percent: AtomicU8::new(config.warning_threshold_percent),
+            critical_threshold_percent: AtomicU8::new(config.critical_threshold_percent),
         }
     }
 
+    /// Send read-only mode transitions to `logger`
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Change the Warning and Critical health thresholds without restarting
+    pub fn set_thresholds(&self, warning_percent: u8, critical_percent: u8) {
+        self.warning_threshold_percent
+            .store(warning_percent, Ordering::Relaxed);
+        self.critical_threshold_percent
+            .store(critical_percent, Ordering::Relaxed);
+    }
+
     /// Check if writes are allowed (not in read-only mode)
     pub fn writes_allowed(&self) -> bool {
         !self.read_only_mode.load(Ordering::Acquire)
@@ -341,13 +372,16 @@ impl ResourceManager {
     /// Enter read-only mode
     pub fn enter_read_only_mode(&self) {
         self.read_only_mode.store(true, Ordering::Release);
-        eprintln!("[WARN] System entering READ-ONLY mode due to resource exhaustion");
+        self.logger.warn(
+            "READ_ONLY_MODE_ENTER",
+            &[("reason", "resource exhaustion")],
+        );
     }
 
     /// Exit read-only mode
     pub fn exit_read_only_mode(&self) {
         self.read_only_mode.store(false, Ordering::Release);
-        eprintln!("[INFO] System exiting read-only mode");
+        self.logger.info("READ_ONLY_MODE_EXIT", &[]);
     }
 
     /// Check disk space before write
@@ -365,6 +399,17 @@ impl ResourceManager {
         self.memory.release(size)
     }
 
+    /// The memory tracker, for operators that charge allocations directly
+    pub fn memory_tracker(&self) -> &Arc<MemoryTracker> {
+        &self.memory
+    }
+
+    /// The edge function budget, kept apart from the memory tracker so that
+    /// invocations a timeout abandoned cannot starve other workloads
+    pub fn edge_function_memory(&self) -> &Arc<MemoryTracker> {
+        &self.edge_function_memory
+    }
+
     /// Try to open a file descriptor
     pub fn try_open_fd(&self) -> ResourceResult<()> {
         self.file_descriptors.try_open()
@@ -443,9 +488,9 @@ impl ResourceManager {
 
         let max_percent = disk_percent.max(memory_percent).max(fd_percent);
 
-        if max_percent >= self.config.critical_threshold_percent {
+        if max_percent >= self.critical_threshold_percent.load(Ordering::Relaxed) {
             HealthStatus::Critical
-        } else if max_percent >= self.config.warning_threshold_percent {
+        } else if max_percent >= self.warning_threshold_percent.load(Ordering::Relaxed) {
             HealthStatus::Warning
         } else {
             HealthStatus::Normal
@@ -456,6 +501,7 @@ impl ResourceManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::observability::{Severity, VecLogger};
 
     #[test]
     fn test_memory_tracker_basic() {
@@ -484,4 +530,25 @@ mod tests {
         tracker.try_allocate(75).unwrap();
         assert_eq!(tracker.usage_percent(), 75);
     }
+
+    #[test]
+    fn test_read_only_mode_transitions_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let logger = Arc::new(VecLogger::new());
+        let manager = ResourceManager::new(ResourceLimitsConfig::default(), dir.path())
+            .with_logger(logger.clone());
+
+        manager.enter_read_only_mode();
+        assert!(!manager.writes_allowed());
+        manager.exit_read_only_mode();
+        assert!(manager.writes_allowed());
+
+        let records = logger.records();
+        assert_eq!(
+            logger.events(),
+            vec!["READ_ONLY_MODE_ENTER", "READ_ONLY_MODE_EXIT"]
+        );
+        assert_eq!(records[0].severity, Severity::Warn);
+        assert_eq!(records[1].severity, Severity::Info);
+    }
 }
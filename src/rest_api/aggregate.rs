@@ -0,0 +1,63 @@
+//! # Aggregation
+//!
+//! `GET /rest/v1/{collection}?aggregate=<aggregates>&group_by=<fields>`
+//! groups the records a list request would return (after RLS and filters)
+//! and responds with one record per group, ordered by group key. `limit`
+//! and `offset` page through the groups.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::errors::{RestError, RestResult};
+use crate::executor::{AggregateSpec, ExecutorErrorCode, HashAggregator};
+use crate::query_limits::QueryLimitsConfig;
+use crate::resource_limits::{MemoryTracker, ResourceLimitsConfig};
+
+/// Budget for REST aggregations
+#[derive(Debug, Clone)]
+pub struct AggregateLimits {
+    /// Tracker charged for group tables
+    pub memory: Arc<MemoryTracker>,
+    /// Maximum groups per aggregation
+    pub max_groups: usize,
+}
+
+impl Default for AggregateLimits {
+    fn default() -> Self {
+        Self {
+            memory: Arc::new(MemoryTracker::new(
+                ResourceLimitsConfig::default().max_memory_bytes,
+            )),
+            max_groups: QueryLimitsConfig::default().max_result_set_docs,
+        }
+    }
+}
+
+impl AggregateLimits {
+    /// Limits sharing a memory tracker, with `max_result_set_docs` groups
+    pub fn new(memory: Arc<MemoryTracker>, query_limits: &QueryLimitsConfig) -> Self {
+        Self {
+            memory,
+            max_groups: query_limits.max_result_set_docs,
+        }
+    }
+}
+
+/// Group `records` by `spec`, ordered by group key
+pub fn aggregate_records(
+    records: &[Value],
+    spec: &AggregateSpec,
+    limits: &AggregateLimits,
+) -> RestResult<Vec<Value>> {
+    let mut aggregator = HashAggregator::new(spec.clone(), &limits.memory, limits.max_groups);
+    for record in records {
+        aggregator.push(record).map_err(|e| match e.code() {
+            ExecutorErrorCode::AeroExecutionLimit => {
+                RestError::AggregationLimit(e.message().to_string())
+            }
+            _ => RestError::Internal(e.message().to_string()),
+        })?;
+    }
+    Ok(aggregator.finish())
+}
@@ -0,0 +1,38 @@
+//! Collection bindings of the REST layer
+//!
+//! A collection renamed or copied through the core API keeps its generated
+//! endpoint and its RLS policy: [`RestBindings`] moves or copies both.
+
+use std::sync::Arc;
+
+use crate::api::CollectionBindings;
+use crate::auth::rls::DefaultRlsEnforcer;
+
+use super::generator::EndpointRegistry;
+
+/// Endpoints and RLS policies, kept by collection name
+pub struct RestBindings {
+    rls: DefaultRlsEnforcer,
+    endpoints: Arc<EndpointRegistry>,
+}
+
+impl RestBindings {
+    /// Bindings held by `rls` (or a clone of it) and `endpoints`
+    pub fn new(rls: DefaultRlsEnforcer, endpoints: Arc<EndpointRegistry>) -> Self {
+        Self { rls, endpoints }
+    }
+}
+
+impl CollectionBindings for RestBindings {
+    fn rename(&self, from: &str, to: &str) -> Result<(), String> {
+        self.endpoints.rename(from, to)?;
+        self.rls.rename_collection(from, to);
+        Ok(())
+    }
+
+    fn copy(&self, from: &str, to: &str) -> Result<(), String> {
+        self.endpoints.copy(from, to)?;
+        self.rls.copy_collection(from, to);
+        Ok(())
+    }
+}
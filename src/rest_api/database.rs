@@ -3,20 +3,24 @@
 //! Provides a high-level database interface for the REST API layer.
 //! Acts as a bridge between HTTP handlers and the underlying executor.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
 use serde_json::Value;
 use uuid::Uuid;
 
+use super::aggregate::{aggregate_records, AggregateLimits};
 use super::errors::{RestError, RestResult};
-use super::handler::RestHandler;
-use super::parser::QueryParams;
+use super::generator::{RelationDef, SchemaDef};
+use super::handler::{RestHandler, TransactionOperation};
+use super::parser::{Embed, QueryParams};
 use super::response::{
-    DeleteResponse, InsertResponse, ListResponse, SingleResponse, UpdateResponse,
+    DeleteResponse, InsertResponse, ListResponse, SingleResponse, TransactionResponse,
+    UpdateResponse,
 };
 use crate::auth::rls::{DefaultRlsEnforcer, RlsContext, RlsEnforcer};
 use crate::auth::AuthError;
+use crate::schema::{is_soft_deleted, DELETED_AT_FIELD};
 
 /// Database operations for a collection
 pub struct DatabaseFacade<E: RlsEnforcer = DefaultRlsEnforcer> {
@@ -25,6 +29,9 @@ pub struct DatabaseFacade<E: RlsEnforcer = DefaultRlsEnforcer> {
 
     /// RLS enforcer
     rls: E,
+
+    /// Budget for aggregations
+    aggregate_limits: AggregateLimits,
 }
 
 /// Collection data store
@@ -32,6 +39,15 @@ pub struct DatabaseFacade<E: RlsEnforcer = DefaultRlsEnforcer> {
 struct CollectionData {
     /// Documents by ID
     documents: HashMap<String, Value>,
+
+    /// Deletes set `_deleted_at` instead of removing the document
+    soft_delete: bool,
+
+    /// Declared fields (empty = undeclared)
+    fields: Vec<String>,
+
+    /// Declared references to other collections
+    relations: Vec<RelationDef>,
 }
 
 impl<E: RlsEnforcer> DatabaseFacade<E> {
@@ -40,9 +56,65 @@ impl<E: RlsEnforcer> DatabaseFacade<E> {
         Self {
             collections: Arc::new(RwLock::new(HashMap::new())),
             rls,
+            aggregate_limits: AggregateLimits::default(),
         }
     }
 
+    /// Use `limits` for aggregations
+    pub fn with_aggregate_limits(mut self, limits: AggregateLimits) -> Self {
+        self.aggregate_limits = limits;
+        self
+    }
+
+    /// The RLS enforcer applied to every operation
+    pub fn rls(&self) -> &E {
+        &self.rls
+    }
+
+    /// Whether a collection exists
+    pub fn has_collection(&self, name: &str) -> bool {
+        self.collections.read().unwrap().contains_key(name)
+    }
+
+    /// Names of all collections, sorted
+    pub fn collections(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.collections.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Create an empty collection; false if it already exists
+    pub fn create_collection(&self, name: &str) -> bool {
+        let mut collections = self.collections.write().unwrap();
+        if collections.contains_key(name) {
+            return false;
+        }
+        collections.insert(name.to_string(), CollectionData::default());
+        true
+    }
+
+    /// Drop a collection and all its documents; false if it did not exist
+    pub fn drop_collection(&self, name: &str) -> bool {
+        self.collections.write().unwrap().remove(name).is_some()
+    }
+
+    /// Make deletes in a collection soft (set `_deleted_at`) or hard,
+    /// creating the collection if needed
+    pub fn set_soft_delete(&self, name: &str, enabled: bool) {
+        self.update_collection(name, |coll| coll.soft_delete = enabled);
+    }
+
+    /// Declare a collection's fields and relations, creating it if needed
+    ///
+    /// `select` embeds follow the relations and may only name declared
+    /// fields of the embedded collection.
+    pub fn declare_schema(&self, schema: &SchemaDef) {
+        self.update_collection(&schema.name, |coll| {
+            coll.fields = schema.fields.iter().map(|f| f.name.clone()).collect();
+            coll.relations = schema.relations.clone();
+        });
+    }
+
     /// Get or create a collection
     fn collection(&self, name: &str) -> CollectionData {
         let collections = self.collections.read().unwrap();
@@ -59,6 +131,121 @@ impl<E: RlsEnforcer> DatabaseFacade<E> {
         f(collection);
     }
 
+    /// Insert a document into `collections`, returning it as stored
+    fn insert_into(
+        &self,
+        collections: &mut HashMap<String, CollectionData>,
+        collection: &str,
+        mut data: Value,
+        ctx: &RlsContext,
+    ) -> RestResult<Value> {
+        // Validate RLS for write
+        self.rls
+            .validate_write(collection, &data, ctx)
+            .map_err(RestError::Auth)?;
+
+        // Prepare document (inject owner_id if needed)
+        self.rls
+            .prepare_insert(collection, &mut data, ctx)
+            .map_err(|e| RestError::InvalidBody(e.to_string()))?;
+
+        // Generate ID if not present
+        let id = if let Some(id) = data.get("_id").and_then(|v| v.as_str()) {
+            id.to_string()
+        } else {
+            let id = Uuid::new_v4().to_string();
+            data.as_object_mut()
+                .unwrap()
+                .insert("_id".to_string(), Value::String(id.clone()));
+            id
+        };
+
+        // Insert document
+        let result = data.clone();
+        collections
+            .entry(collection.to_string())
+            .or_default()
+            .documents
+            .insert(id, data);
+
+        Ok(result)
+    }
+
+    /// Merge `updates` into a live document of `collections`, returning it
+    fn update_in(
+        &self,
+        collections: &mut HashMap<String, CollectionData>,
+        collection: &str,
+        id: &str,
+        updates: Value,
+        ctx: &RlsContext,
+    ) -> RestResult<Value> {
+        // Get existing document
+        let existing = collections
+            .get(collection)
+            .ok_or(RestError::NotFound)
+            .and_then(|coll| Self::live(coll, id))?;
+
+        // Check RLS
+        let allowed = self.apply_rls_filter(collection, std::slice::from_ref(existing), ctx)?;
+        if allowed.is_empty() {
+            return Err(RestError::NotFound);
+        }
+
+        // Merge updates
+        let mut updated = existing.clone();
+        if let (Value::Object(base), Value::Object(patches)) = (&mut updated, updates) {
+            for (k, v) in patches {
+                base.insert(k, v);
+            }
+        }
+
+        // Validate updated document
+        self.rls
+            .validate_write(collection, &updated, ctx)
+            .map_err(RestError::Auth)?;
+
+        // Store updated document
+        let result = updated.clone();
+        collections
+            .entry(collection.to_string())
+            .or_default()
+            .documents
+            .insert(id.to_string(), updated);
+
+        Ok(result)
+    }
+
+    /// Delete, or mark deleted, a live document of `collections`
+    fn delete_from(
+        &self,
+        collections: &mut HashMap<String, CollectionData>,
+        collection: &str,
+        id: &str,
+        ctx: &RlsContext,
+    ) -> RestResult<()> {
+        let coll = collections.get_mut(collection).ok_or(RestError::NotFound)?;
+
+        // Check if exists
+        let existing = Self::live(coll, id)?;
+
+        // Check RLS
+        let allowed = self.apply_rls_filter(collection, std::slice::from_ref(existing), ctx)?;
+        if allowed.is_empty() {
+            return Err(RestError::NotFound);
+        }
+
+        // Delete, or mark deleted
+        if !coll.soft_delete {
+            coll.documents.remove(id);
+        } else if let Some(Value::Object(doc)) = coll.documents.get_mut(id) {
+            let deleted_at = Value::String(chrono::Utc::now().to_rfc3339());
+            doc.insert(DELETED_AT_FIELD.to_string(), deleted_at);
+        }
+
+        Ok(())
+    }
+
     /// Apply RLS filter to records
     fn apply_rls_filter(
         &self,
@@ -73,25 +260,32 @@ impl<E: RlsEnforcer> DatabaseFacade<E> {
             .map_err(RestError::Auth)?;
 
         Ok(match filter {
-            Some(f) => {
-                // Apply ownership filter
-                records
-                    .iter()
-                    .filter(|doc| {
-                        if let Some(owner_id) = doc.get(&f.field).and_then(|v| v.as_str()) {
-                            if let Some(user_id) = &ctx.user_id {
-                                return owner_id == user_id.to_string();
-                            }
-                        }
-                        false
-                    })
-                    .cloned()
-                    .collect()
-            }
+            Some(f) => records
+                .iter()
+                .filter(|doc| f.matches(doc))
+                .cloned()
+                .collect(),
             None => records.to_vec(),
         })
     }
 
+    /// Records a request sees: soft-deleted ones only with `include_deleted`
+    fn visible(coll: &CollectionData, params: &QueryParams) -> Vec<Value> {
+        coll.documents
+            .values()
+            .filter(|doc| params.include_deleted || !is_soft_deleted(doc))
+            .cloned()
+            .collect()
+    }
+
+    /// A live (not soft-deleted) document by ID
+    fn live<'a>(coll: &'a CollectionData, id: &str) -> RestResult<&'a Value> {
+        coll.documents
+            .get(id)
+            .filter(|doc| !is_soft_deleted(doc))
+            .ok_or(RestError::NotFound)
+    }
+
     /// Apply query filters to records
     fn apply_filters(records: &[Value], params: &QueryParams) -> Vec<Value> {
         records
@@ -133,11 +327,11 @@ impl<E: RlsEnforcer> DatabaseFacade<E> {
             .collect()
     }
 
-    /// Select fields from records
-    fn select_fields(records: Vec<Value>, params: &QueryParams) -> Vec<Value> {
-        match &params.select {
+    /// Select fields from records (`*` = all)
+    fn select_fields(records: Vec<Value>, select: Option<&[String]>) -> Vec<Value> {
+        match select {
             None => records,
-            Some(fields) if fields.is_empty() => records,
+            Some(fields) if fields.iter().any(|f| f == "*") => records,
             Some(fields) => records
                 .into_iter()
                 .map(|doc| {
@@ -154,6 +348,126 @@ impl<E: RlsEnforcer> DatabaseFacade<E> {
                 .collect(),
         }
     }
+
+    /// The relation `embed` follows from `collection`
+    fn relation(coll: &CollectionData, collection: &str, embed: &Embed) -> RestResult<RelationDef> {
+        let candidates: Vec<&RelationDef> = coll
+            .relations
+            .iter()
+            .filter(|r| r.collection == embed.collection)
+            .filter(|r| embed.field.as_ref().is_none_or(|field| *field == r.field))
+            .collect();
+
+        match candidates.as_slice() {
+            [relation] => Ok((*relation).clone()),
+            [] => Err(embed.no_relation(collection)),
+            _ => Err(RestError::InvalidQueryParam(format!(
+                "select: '{}' has more than one relation to '{}'; name one as {}!<field>",
+                collection, embed.collection, embed.collection
+            ))),
+        }
+    }
+
+    /// Reject selected fields a collection with declared fields lacks
+    fn check_fields(coll: &CollectionData, collection: &str, select: &[String]) -> RestResult<()> {
+        if coll.fields.is_empty() {
+            return Ok(());
+        }
+        match select
+            .iter()
+            .find(|f| *f != "*" && *f != "_id" && !coll.fields.contains(f))
+        {
+            Some(field) => Err(RestError::InvalidQueryParam(format!(
+                "select: '{}' has no field '{}'",
+                collection, field
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Select fields from records and nest the records they reference
+    ///
+    /// Each embed looks up all referenced IDs in one read of the embedded
+    /// collection, under that collection's RLS; references the caller cannot
+    /// read, or that do not resolve, embed as null.
+    fn project(
+        &self,
+        collection: &str,
+        records: Vec<Value>,
+        select: Option<&[String]>,
+        embeds: &[Embed],
+        ctx: &RlsContext,
+    ) -> RestResult<Vec<Value>> {
+        if embeds.is_empty() {
+            return Ok(Self::select_fields(records, select));
+        }
+
+        let coll = self.collection(collection);
+        let mut resolved = Vec::with_capacity(embeds.len());
+        for embed in embeds {
+            let relation = Self::relation(&coll, collection, embed)?;
+            let target = self.collection(&relation.collection);
+            Self::check_fields(&target, &relation.collection, &embed.select)?;
+
+            let ids: HashSet<&str> = records
+                .iter()
+                .filter_map(|doc| doc.get(&relation.field)?.as_str())
+                .collect();
+            let referenced: Vec<Value> = ids
+                .into_iter()
+                .filter_map(|id| Self::live(&target, id).ok())
+                .cloned()
+                .collect();
+            let readable = self.apply_rls_filter(&relation.collection, &referenced, ctx)?;
+
+            let keys: Vec<String> = readable
+                .iter()
+                .map(|doc| {
+                    doc.get("_id")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                })
+                .collect();
+            let projected = self.project(
+                &relation.collection,
+                readable,
+                Some(&embed.select),
+                &embed.embeds,
+                ctx,
+            )?;
+            let by_id: HashMap<String, Value> = keys.into_iter().zip(projected).collect();
+            resolved.push((embed, relation.field, by_id));
+        }
+
+        // References are read before projection may drop their fields
+        let nested: Vec<Vec<Value>> = records
+            .iter()
+            .map(|doc| {
+                resolved
+                    .iter()
+                    .map(|(_, field, by_id)| {
+                        doc.get(field)
+                            .and_then(Value::as_str)
+                            .and_then(|id| by_id.get(id))
+                            .cloned()
+                            .unwrap_or(Value::Null)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut records = Self::select_fields(records, select);
+        for (doc, values) in records.iter_mut().zip(nested) {
+            if let Value::Object(obj) = doc {
+                for ((embed, _, _), value) in resolved.iter().zip(values) {
+                    obj.insert(embed.alias.clone(), value);
+                }
+            }
+        }
+
+        Ok(records)
+    }
 }
 
 impl<E: RlsEnforcer + Send + Sync> RestHandler for DatabaseFacade<E> {
@@ -164,7 +478,7 @@ impl<E: RlsEnforcer + Send + Sync> RestHandler for DatabaseFacade<E> {
         ctx: &RlsContext,
     ) -> RestResult<ListResponse<Value>> {
         let coll = self.collection(collection);
-        let records: Vec<Value> = coll.documents.values().cloned().collect();
+        let records = Self::visible(&coll, &params);
 
         // Apply RLS
         let filtered = self.apply_rls_filter(collection, &records, ctx)?;
@@ -182,8 +496,14 @@ impl<E: RlsEnforcer + Send + Sync> RestHandler for DatabaseFacade<E> {
         // Apply pagination
         let paginated = Self::apply_pagination(sorted, &params);
 
-        // Select fields
-        let selected = Self::select_fields(paginated, &params);
+        // Select fields and embed referenced records
+        let selected = self.project(
+            collection,
+            paginated,
+            params.select.as_deref(),
+            &params.embeds,
+            ctx,
+        )?;
 
         Ok(ListResponse {
             data: selected,
@@ -193,6 +513,39 @@ impl<E: RlsEnforcer + Send + Sync> RestHandler for DatabaseFacade<E> {
         })
     }
 
+    fn aggregate(
+        &self,
+        collection: &str,
+        params: QueryParams,
+        ctx: &RlsContext,
+    ) -> RestResult<ListResponse<Value>> {
+        let spec = params
+            .aggregate
+            .as_ref()
+            .ok_or_else(|| RestError::MissingParam("aggregate".to_string()))?;
+
+        let coll = self.collection(collection);
+        let records = Self::visible(&coll, &params);
+
+        // Apply RLS
+        let filtered = self.apply_rls_filter(collection, &records, ctx)?;
+
+        // Apply query filters
+        let filtered = Self::apply_filters(&filtered, &params);
+
+        // Group; the count is of all groups, before pagination
+        let groups = aggregate_records(&filtered, spec, &self.aggregate_limits)?;
+        let total = groups.len();
+        let paginated = Self::apply_pagination(groups, &params);
+
+        Ok(ListResponse {
+            data: paginated,
+            count: total,
+            limit: params.limit,
+            offset: params.offset,
+        })
+    }
+
     fn get(
         &self,
         collection: &str,
@@ -201,7 +554,7 @@ impl<E: RlsEnforcer + Send + Sync> RestHandler for DatabaseFacade<E> {
     ) -> RestResult<SingleResponse<Value>> {
         let coll = self.collection(collection);
 
-        let doc = coll.documents.get(id).ok_or(RestError::NotFound)?;
+        let doc = Self::live(&coll, id)?;
 
         // Apply RLS
         let allowed = self.apply_rls_filter(collection, &[doc.clone()], ctx)?;
@@ -217,35 +570,11 @@ impl<E: RlsEnforcer + Send + Sync> RestHandler for DatabaseFacade<E> {
     fn insert(
         &self,
         collection: &str,
-        mut data: Value,
+        data: Value,
         ctx: &RlsContext,
     ) -> RestResult<InsertResponse<Value>> {
-        // Validate RLS for write
-        self.rls
-            .validate_write(collection, &data, ctx)
-            .map_err(RestError::Auth)?;
-
-        // Prepare document (inject owner_id if needed)
-        self.rls
-            .prepare_insert(collection, &mut data, ctx)
-            .map_err(|e| RestError::InvalidBody(e.to_string()))?;
-
-        // Generate ID if not present
-        let id = if let Some(id) = data.get("_id").and_then(|v| v.as_str()) {
-            id.to_string()
-        } else {
-            let id = Uuid::new_v4().to_string();
-            data.as_object_mut()
-                .unwrap()
-                .insert("_id".to_string(), Value::String(id.clone()));
-            id
-        };
-
-        // Insert document
-        let result = data.clone();
-        self.update_collection(collection, |coll| {
-            coll.documents.insert(id, data);
-        });
+        let mut collections = self.collections.write().unwrap();
+        let result = self.insert_into(&mut collections, collection, data, ctx)?;
 
         Ok(InsertResponse {
             data: vec![result],
@@ -260,52 +589,62 @@ impl<E: RlsEnforcer + Send + Sync> RestHandler for DatabaseFacade<E> {
         updates: Value,
         ctx: &RlsContext,
     ) -> RestResult<UpdateResponse<Value>> {
-        let coll = self.collection(collection);
-
-        // Get existing document
-        let existing = coll.documents.get(id).ok_or(RestError::NotFound)?;
+        let mut collections = self.collections.write().unwrap();
+        let result = self.update_in(&mut collections, collection, id, updates, ctx)?;
 
-        // Check RLS
-        let allowed = self.apply_rls_filter(collection, &[existing.clone()], ctx)?;
-        if allowed.is_empty() {
-            return Err(RestError::NotFound);
-        }
+        Ok(UpdateResponse { data: result })
+    }
 
-        // Merge updates
-        let mut updated = existing.clone();
-        if let (Value::Object(base), Value::Object(patches)) = (&mut updated, updates) {
-            for (k, v) in patches {
-                base.insert(k, v);
-            }
-        }
+    fn delete(&self, collection: &str, id: &str, ctx: &RlsContext) -> RestResult<DeleteResponse> {
+        let mut collections = self.collections.write().unwrap();
+        self.delete_from(&mut collections, collection, id, ctx)?;
 
-        // Validate updated document
-        self.rls
-            .validate_write(collection, &updated, ctx)
-            .map_err(RestError::Auth)?;
+        Ok(DeleteResponse { deleted: true })
+    }
 
-        // Store updated document
-        let result = updated.clone();
-        self.update_collection(collection, |coll| {
-            coll.documents.insert(id.to_string(), updated);
-        });
+    fn transaction(
+        &self,
+        operations: Vec<TransactionOperation>,
+        ctx: &RlsContext,
+    ) -> RestResult<TransactionResponse<Value>> {
+        // Apply to a copy, published only if every operation succeeds
+        let mut collections = self.collections.write().unwrap();
+        let mut staged = collections.clone();
+        let mut results = Vec::with_capacity(operations.len());
+        for operation in operations {
+            let result = match operation {
+                TransactionOperation::Insert { collection, data } => {
+                    self.insert_into(&mut staged, &collection, data, ctx)?
+                }
+                TransactionOperation::Update {
+                    collection,
+                    id,
+                    data,
+                } => self.update_in(&mut staged, &collection, &id, data, ctx)?,
+                TransactionOperation::Delete { collection, id } => {
+                    self.delete_from(&mut staged, &collection, &id, ctx)?;
+                    serde_json::json!({ "_id": id, "deleted": true })
+                }
+            };
+            results.push(result);
+        }
+        *collections = staged;
 
-        Ok(UpdateResponse { data: result })
+        Ok(TransactionResponse::new(results))
     }
 
-    fn delete(&self, collection: &str, id: &str, ctx: &RlsContext) -> RestResult<DeleteResponse> {
+    fn purge(&self, collection: &str, id: &str, ctx: &RlsContext) -> RestResult<DeleteResponse> {
         let coll = self.collection(collection);
 
-        // Check if exists
+        // Soft-deleted documents can be purged too
         let existing = coll.documents.get(id).ok_or(RestError::NotFound)?;
 
         // Check RLS
-        let allowed = self.apply_rls_filter(collection, &[existing.clone()], ctx)?;
+        let allowed = self.apply_rls_filter(collection, std::slice::from_ref(existing), ctx)?;
         if allowed.is_empty() {
             return Err(RestError::NotFound);
         }
 
-        // Delete
         self.update_collection(collection, |coll| {
             coll.documents.remove(id);
         });
@@ -333,6 +672,7 @@ fn compare_json_values(a: Option<&Value>, b: Option<&Value>) -> std::cmp::Orderi
 mod tests {
     use super::*;
     use crate::auth::rls::{DefaultRlsEnforcer, RlsPolicy};
+    use crate::rest_api::generator::{FieldDef, FieldType};
     use serde_json::json;
 
     fn create_facade() -> DatabaseFacade<DefaultRlsEnforcer> {
@@ -393,6 +733,115 @@ mod tests {
         assert_eq!(list.data[0].get("title").unwrap(), "User1 Post");
     }
 
+    #[test]
+    fn test_expression_policy_limits_rows_to_owner() {
+        let rls = DefaultRlsEnforcer::new().with_policy(
+            "notes",
+            RlsPolicy::Custom {
+                read_predicate: Some("user_id = auth.uid() or auth.role() = 'admin'".to_string()),
+                write_predicate: None,
+            },
+        );
+        let db = DatabaseFacade::new(rls);
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let (alice_ctx, bob_ctx) = (
+            RlsContext::authenticated(alice),
+            RlsContext::authenticated(bob),
+        );
+        for (id, owner, ctx) in [("a", alice, &alice_ctx), ("b", bob, &bob_ctx)] {
+            let doc = json!({"_id": id, "user_id": owner.to_string(), "body": id});
+            db.insert("notes", doc, ctx).unwrap();
+        }
+
+        // Writing a row the policy rejects is blocked
+        let forged = json!({"_id": "c", "user_id": bob.to_string()});
+        let err = db.insert("notes", forged, &alice_ctx).unwrap_err();
+        assert!(matches!(err, RestError::Auth(AuthError::Unauthorized)));
+
+        // Select sees only the caller's rows
+        let list = db
+            .list("notes", QueryParams::default(), &alice_ctx)
+            .unwrap();
+        assert_eq!(list.count, 1);
+        assert_eq!(list.data[0]["body"], "a");
+        assert!(matches!(
+            db.get("notes", "b", &alice_ctx),
+            Err(RestError::NotFound)
+        ));
+
+        // Update reaches only the caller's rows, and cannot give them away
+        let err = db
+            .update("notes", "b", json!({"body": "x"}), &alice_ctx)
+            .unwrap_err();
+        assert!(matches!(err, RestError::NotFound));
+        db.update("notes", "a", json!({"body": "edited"}), &alice_ctx)
+            .unwrap();
+        let err = db
+            .update(
+                "notes",
+                "a",
+                json!({"user_id": bob.to_string()}),
+                &alice_ctx,
+            )
+            .unwrap_err();
+        assert!(matches!(err, RestError::Auth(AuthError::Unauthorized)));
+
+        // Delete likewise
+        assert!(matches!(
+            db.delete("notes", "b", &alice_ctx),
+            Err(RestError::NotFound)
+        ));
+        db.delete("notes", "a", &alice_ctx).unwrap();
+
+        let all = db
+            .list("notes", QueryParams::default(), &RlsContext::service_role())
+            .unwrap();
+        assert_eq!(all.count, 1);
+        assert_eq!(all.data[0]["body"], "b");
+    }
+
+    #[test]
+    fn test_invalid_policy_expression_fails_closed() {
+        let policy = RlsPolicy::Custom {
+            read_predicate: Some("user_id = auth.email()".to_string()),
+            write_predicate: None,
+        };
+        assert!(policy.validate().is_err());
+        let db = DatabaseFacade::new(DefaultRlsEnforcer::new().with_policy("notes", policy));
+
+        let ctx = RlsContext::authenticated(Uuid::new_v4());
+        let err = db.list("notes", QueryParams::default(), &ctx).unwrap_err();
+        assert!(matches!(err, RestError::Auth(AuthError::InvalidPolicy(_))));
+    }
+
+    #[test]
+    fn test_transaction_applies_all_or_nothing() {
+        let db = create_facade();
+        let ctx = RlsContext::service_role();
+        db.insert("items", json!({"_id": "a", "n": 1}), &ctx)
+            .unwrap();
+        let operations = |last: &str| {
+            serde_json::from_value::<Vec<TransactionOperation>>(json!([
+                {"op": "insert", "collection": "items", "data": {"_id": "b", "n": 2}},
+                {"op": "update", "collection": "items", "id": "a", "data": {"n": 10}},
+                {"op": "delete", "collection": "items", "id": last}
+            ]))
+            .unwrap()
+        };
+
+        // The missing document fails the batch; nothing before it stays
+        let err = db.transaction(operations("missing"), &ctx).unwrap_err();
+        assert!(matches!(err, RestError::NotFound));
+        assert_eq!(db.get("items", "a", &ctx).unwrap().data["n"], 1);
+        assert!(db.get("items", "b", &ctx).is_err());
+
+        let result = db.transaction(operations("b"), &ctx).unwrap();
+        assert_eq!(result.count, 3);
+        assert_eq!(result.data[1]["n"], 10);
+        assert_eq!(db.get("items", "a", &ctx).unwrap().data["n"], 10);
+        assert!(db.get("items", "b", &ctx).is_err());
+    }
+
     #[test]
     fn test_get_by_id() {
         let db = create_facade();
@@ -437,6 +886,175 @@ mod tests {
         assert!(db.get("items", "delete-id", &ctx).is_err());
     }
 
+    #[test]
+    fn test_soft_delete_filters_until_purged() {
+        let db = create_facade();
+        let ctx = RlsContext::service_role();
+        db.set_soft_delete("items", true);
+
+        db.insert("items", json!({"_id": "a", "name": "Kept"}), &ctx)
+            .unwrap();
+        db.insert("items", json!({"_id": "b", "name": "Gone"}), &ctx)
+            .unwrap();
+        assert!(db.delete("items", "b", &ctx).unwrap().deleted);
+
+        // Filtered from reads, and deleting again finds nothing
+        assert!(matches!(
+            db.get("items", "b", &ctx),
+            Err(RestError::NotFound)
+        ));
+        assert!(matches!(
+            db.delete("items", "b", &ctx),
+            Err(RestError::NotFound)
+        ));
+        let list = db.list("items", QueryParams::default(), &ctx).unwrap();
+        assert_eq!(list.count, 1);
+
+        // Still there with include_deleted, marked
+        let params = QueryParams {
+            include_deleted: true,
+            ..Default::default()
+        };
+        let list = db.list("items", params, &ctx).unwrap();
+        assert_eq!(list.count, 2);
+        let gone = list.data.iter().find(|doc| doc["_id"] == "b").unwrap();
+        assert!(is_soft_deleted(gone));
+
+        // Purge removes it for good
+        assert!(db.purge("items", "b", &ctx).unwrap().deleted);
+        let params = QueryParams {
+            include_deleted: true,
+            ..Default::default()
+        };
+        assert_eq!(db.list("items", params, &ctx).unwrap().count, 1);
+        assert!(matches!(
+            db.purge("items", "b", &ctx),
+            Err(RestError::NotFound)
+        ));
+    }
+
+    /// `posts` referencing `users` through `author_id`
+    fn blog_facade(rls: DefaultRlsEnforcer) -> DatabaseFacade<DefaultRlsEnforcer> {
+        let field = |name: &str| FieldDef {
+            name: name.to_string(),
+            field_type: FieldType::String,
+            required: false,
+            primary: false,
+            default: None,
+        };
+        let db = DatabaseFacade::new(rls);
+        db.declare_schema(&SchemaDef {
+            name: "users".to_string(),
+            fields: vec![field("name"), field("email"), field("owner_id")],
+            rls_policy: None,
+            anon_read: false,
+            relations: Vec::new(),
+        });
+        db.declare_schema(&SchemaDef {
+            name: "posts".to_string(),
+            fields: vec![field("title"), field("author_id")],
+            rls_policy: None,
+            anon_read: false,
+            relations: vec![RelationDef {
+                field: "author_id".to_string(),
+                collection: "users".to_string(),
+            }],
+        });
+        db
+    }
+
+    fn select(value: &str) -> QueryParams {
+        let mut query = HashMap::new();
+        query.insert("select".to_string(), value.to_string());
+        query.insert("order".to_string(), "title".to_string());
+        QueryParams::parse(&query).unwrap()
+    }
+
+    #[test]
+    fn test_embed_projects_referenced_records() {
+        let db = blog_facade(DefaultRlsEnforcer::new());
+        let ctx = RlsContext::service_role();
+        for (id, name) in [("u1", "Ada"), ("u2", "Grace")] {
+            let user = json!({"_id": id, "name": name, "email": format!("{}@x.io", id)});
+            db.insert("users", user, &ctx).unwrap();
+        }
+        for (title, author) in [("a", "u1"), ("b", "u2"), ("c", "u1"), ("d", "gone")] {
+            let post = json!({"title": title, "author_id": author});
+            db.insert("posts", post, &ctx).unwrap();
+        }
+
+        let list = db
+            .list("posts", select("title,author:users(name)"), &ctx)
+            .unwrap();
+        assert_eq!(
+            list.data,
+            vec![
+                json!({"title": "a", "author": {"name": "Ada"}}),
+                json!({"title": "b", "author": {"name": "Grace"}}),
+                json!({"title": "c", "author": {"name": "Ada"}}),
+                json!({"title": "d", "author": null}),
+            ]
+        );
+
+        // `*` keeps every field, and the alias defaults to the collection
+        let list = db.list("posts", select("*,users(*)"), &ctx).unwrap();
+        assert_eq!(list.data[0]["author_id"], "u1");
+        assert_eq!(list.data[0]["users"]["email"], "u1@x.io");
+    }
+
+    #[test]
+    fn test_embed_hides_records_rls_hides() {
+        let rls = DefaultRlsEnforcer::new()
+            .with_policy(
+                "posts",
+                RlsPolicy::PublicRead {
+                    owner_field: "owner_id".to_string(),
+                },
+            )
+            .with_policy(
+                "users",
+                RlsPolicy::Ownership {
+                    owner_field: "owner_id".to_string(),
+                },
+            );
+        let db = blog_facade(rls);
+        let (me, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let service = RlsContext::service_role();
+        for (id, owner) in [("u1", me), ("u2", other)] {
+            let user = json!({"_id": id, "name": id, "owner_id": owner.to_string()});
+            db.insert("users", user, &service).unwrap();
+        }
+        for (title, author) in [("a", "u1"), ("b", "u2")] {
+            let post = json!({"title": title, "author_id": author});
+            db.insert("posts", post, &service).unwrap();
+        }
+
+        let ctx = RlsContext::authenticated(me);
+        let list = db
+            .list("posts", select("title,author:users(name)"), &ctx)
+            .unwrap();
+        assert_eq!(list.data[0]["author"], json!({"name": "u1"}));
+        assert_eq!(list.data[1]["author"], Value::Null);
+    }
+
+    #[test]
+    fn test_embed_rejects_unknown_relations_and_fields() {
+        let db = blog_facade(DefaultRlsEnforcer::new());
+        let ctx = RlsContext::service_role();
+        db.insert("posts", json!({"title": "a", "author_id": "u1"}), &ctx)
+            .unwrap();
+
+        let message = |value: &str| match db.list("posts", select(value), &ctx) {
+            Err(err @ RestError::InvalidQueryParam(_)) => err.to_string(),
+            other => panic!("{}: expected a bad request, got {:?}", value, other),
+        };
+        assert!(message("title,author:teams(name)").ends_with("'posts' has no relation to 'teams'"));
+        assert!(message("author:users!editor_id(name)")
+            .ends_with("'posts' has no relation to 'users' through 'editor_id'"));
+        assert!(message("author:users(name,age)").ends_with("'users' has no field 'age'"));
+        assert!(message("author:posts(title)").ends_with("'posts' has no relation to 'posts'"));
+    }
+
     #[test]
     fn test_pagination() {
         let db = create_facade();
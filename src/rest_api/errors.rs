@@ -2,13 +2,17 @@
 //!
 //! Error types for the REST API module.
 
-use axum::http::StatusCode;
+use axum::http::{header, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde::Serialize;
 use thiserror::Error;
+use uuid::Uuid;
 
+use crate::admission_control::AdmissionRejection;
 use crate::auth::AuthError;
+use crate::observability::current_request_id;
+use crate::replication::{ReplicationError, ReplicationErrorKind};
 
 /// Result type for REST operations
 pub type RestResult<T> = Result<T, RestError>;
@@ -51,6 +55,10 @@ pub enum RestError {
     #[error("Limit {0} exceeds maximum {1}")]
     LimitExceeded(usize, usize),
 
+    /// Aggregation group table exceeds its group or memory budget
+    #[error("Aggregation refused: {0}")]
+    AggregationLimit(String),
+
     // ==================
     // Auth Errors
     // ==================
@@ -68,6 +76,37 @@ pub enum RestError {
     /// Schema loading error
     #[error("Schema error: {0}")]
     SchemaError(String),
+
+    /// Backend cannot apply a batch of writes atomically
+    #[error("Transactions are not supported by this backend")]
+    TransactionsUnsupported,
+
+    /// Query abandoned at its deadline
+    #[error("Query timed out after {timeout_ms} ms ({documents_examined} documents examined)")]
+    QueryTimeout {
+        timeout_ms: u64,
+        documents_examined: usize,
+    },
+
+    /// Write sent to a replica
+    #[error("NOT_PRIMARY: {0}")]
+    NotPrimary(String),
+
+    /// Replica is behind the read's staleness bound
+    #[error("REPLICA_TOO_STALE: {0}")]
+    ReplicaTooStale(String),
+
+    /// Too few replicas acknowledged the write within the sync timeout;
+    /// it is durable on the primary
+    #[error("REPLICATION_TIMEOUT: {0}")]
+    ReplicationTimeout(String),
+
+    /// Operation class is at its concurrency and queue limits
+    #[error("ADMISSION_REJECTED: {message}")]
+    AdmissionRejected {
+        message: String,
+        retry_after_ms: u64,
+    },
 }
 
 impl RestError {
@@ -82,6 +121,9 @@ impl RestError {
             RestError::UnboundedQuery(_) => StatusCode::BAD_REQUEST,
             RestError::LimitExceeded(_, _) => StatusCode::BAD_REQUEST,
 
+            // 422 Unprocessable Entity
+            RestError::AggregationLimit(_) => StatusCode::UNPROCESSABLE_ENTITY,
+
             // 401/403 from auth
             RestError::Auth(auth_err) => {
                 StatusCode::from_u16(auth_err.status_code()).unwrap_or(StatusCode::UNAUTHORIZED)
@@ -94,6 +136,50 @@ impl RestError {
             // 500 Internal Server Error
             RestError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
             RestError::SchemaError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+
+            // 501 Not Implemented
+            RestError::TransactionsUnsupported => StatusCode::NOT_IMPLEMENTED,
+
+            // 421 Misdirected Request: retry against the primary
+            RestError::NotPrimary(_) => StatusCode::MISDIRECTED_REQUEST,
+
+            // 503 Service Unavailable: retry later or against the primary
+            RestError::ReplicaTooStale(_) => StatusCode::SERVICE_UNAVAILABLE,
+
+            // 429 Too Many Requests: retry after the hint
+            RestError::AdmissionRejected { .. } => StatusCode::TOO_MANY_REQUESTS,
+
+            // 504 Gateway Timeout
+            RestError::QueryTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            RestError::ReplicationTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    /// Suggested wait before retrying, for overload rejections
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        match self {
+            RestError::AdmissionRejected { retry_after_ms, .. } => Some(*retry_after_ms),
+            _ => None,
+        }
+    }
+}
+
+impl From<AdmissionRejection> for RestError {
+    fn from(rejection: AdmissionRejection) -> Self {
+        RestError::AdmissionRejected {
+            message: rejection.to_string(),
+            retry_after_ms: rejection.retry_after_ms,
+        }
+    }
+}
+
+impl From<ReplicationError> for RestError {
+    fn from(err: ReplicationError) -> Self {
+        match err.kind {
+            ReplicationErrorKind::NotPrimary => RestError::NotPrimary(err.message),
+            ReplicationErrorKind::ReplicaTooStale => RestError::ReplicaTooStale(err.message),
+            ReplicationErrorKind::ReplicationTimeout => RestError::ReplicationTimeout(err.message),
+            _ => RestError::Internal(err.to_string()),
         }
     }
 }
@@ -103,13 +189,21 @@ impl RestError {
 pub struct ErrorResponse {
     pub error: String,
     pub code: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    /// Request that failed, for correlation with the logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<Uuid>,
 }
 
 impl From<RestError> for ErrorResponse {
+    /// Names the request being handled, if any
     fn from(err: RestError) -> Self {
         Self {
             code: err.status_code().as_u16(),
             error: err.to_string(),
+            retry_after_ms: err.retry_after_ms(),
+            request_id: current_request_id(),
         }
     }
 }
@@ -117,8 +211,15 @@ impl From<RestError> for ErrorResponse {
 impl IntoResponse for RestError {
     fn into_response(self) -> Response {
         let status = self.status_code();
+        // Retry-After is in whole seconds
+        let retry_after = self
+            .retry_after_ms()
+            .map(|ms| (header::RETRY_AFTER, ms.div_ceil(1_000).to_string()));
         let body = Json(ErrorResponse::from(self));
-        (status, body).into_response()
+        match retry_after {
+            Some(retry_after) => (status, [retry_after], body).into_response(),
+            None => (status, body).into_response(),
+        }
     }
 }
 
@@ -126,6 +227,18 @@ impl IntoResponse for RestError {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_error_body_names_current_request() {
+        let body = ErrorResponse::from(RestError::NotFound);
+        assert_eq!(body.request_id, None);
+
+        let request_id = Uuid::new_v4();
+        let body = crate::observability::in_request_sync(request_id, || {
+            ErrorResponse::from(RestError::NotFound)
+        });
+        assert_eq!(body.request_id, Some(request_id));
+    }
+
     #[test]
     fn test_status_codes() {
         assert_eq!(
@@ -133,16 +246,62 @@ mod tests {
             StatusCode::BAD_REQUEST
         );
         assert_eq!(RestError::NotFound.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            RestError::AggregationLimit("test".to_string()).status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
         assert_eq!(
             RestError::Internal("test".to_string()).status_code(),
             StatusCode::INTERNAL_SERVER_ERROR
         );
     }
 
+    #[test]
+    fn test_query_timeout_status() {
+        let err = RestError::QueryTimeout {
+            timeout_ms: 10,
+            documents_examined: 128,
+        };
+        assert_eq!(err.status_code(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(err.to_string().contains("128 documents examined"));
+    }
+
     #[test]
     fn test_auth_error_propagation() {
         let auth_err = AuthError::InvalidCredentials;
         let rest_err = RestError::from(auth_err);
         assert_eq!(rest_err.status_code(), StatusCode::UNAUTHORIZED);
     }
+
+    #[test]
+    fn test_replica_gate_errors() {
+        let err = RestError::from(ReplicationError::not_primary("primary at db-primary:7000"));
+        assert_eq!(err.status_code(), StatusCode::MISDIRECTED_REQUEST);
+        assert!(err.to_string().starts_with("NOT_PRIMARY"));
+        assert!(err.to_string().contains("db-primary:7000"));
+
+        let err = RestError::from(ReplicationError::replica_too_stale("behind"));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.to_string().starts_with("REPLICA_TOO_STALE"));
+
+        let err = RestError::from(ReplicationError::replication_timeout("1 of 2 acked"));
+        assert_eq!(err.status_code(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(err.to_string().starts_with("REPLICATION_TIMEOUT"));
+    }
+
+    #[test]
+    fn test_admission_rejection() {
+        use crate::admission_control::{OperationClass, RejectionReason};
+
+        let err = RestError::from(AdmissionRejection {
+            class: OperationClass::Read,
+            reason: RejectionReason::QueueFull,
+            retry_after_ms: 1_500,
+        });
+        assert_eq!(err.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(err.to_string().starts_with("ADMISSION_REJECTED"));
+
+        let response = err.into_response();
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+    }
 }
@@ -46,6 +46,39 @@ pub enum FilterOperator {
 }
 
 impl FilterOperator {
+    /// All supported operators, in documentation order
+    pub const ALL: [FilterOperator; 9] = [
+        FilterOperator::Eq,
+        FilterOperator::Neq,
+        FilterOperator::Gt,
+        FilterOperator::Gte,
+        FilterOperator::Lt,
+        FilterOperator::Lte,
+        FilterOperator::Like,
+        FilterOperator::In,
+        FilterOperator::Is,
+    ];
+
+    /// Parse an operator from its query-string prefix (e.g. `gt` in `age=gt.10`)
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|op| op.as_str() == s)
+    }
+
+    /// Human-readable description, used in the generated OpenAPI spec
+    pub fn description(&self) -> &'static str {
+        match self {
+            FilterOperator::Eq => "equals",
+            FilterOperator::Neq => "not equals",
+            FilterOperator::Gt => "greater than",
+            FilterOperator::Gte => "greater than or equal",
+            FilterOperator::Lt => "less than",
+            FilterOperator::Lte => "less than or equal",
+            FilterOperator::Like => "pattern match, % and _ wildcards",
+            FilterOperator::In => "value in list, e.g. in.(a,b,c)",
+            FilterOperator::Is => "is null / not null, e.g. is.null",
+        }
+    }
+
     /// Get the operator string representation
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -273,6 +306,14 @@ mod tests {
         assert!(!filter.matches(&json!({"name": "Smith"})));
     }
 
+    #[test]
+    fn test_operator_parse_roundtrip() {
+        for op in FilterOperator::ALL {
+            assert_eq!(FilterOperator::parse(op.as_str()), Some(op));
+        }
+        assert_eq!(FilterOperator::parse("between"), None);
+    }
+
     #[test]
     fn test_filter_set() {
         let filters = FilterSet::new()
@@ -8,7 +8,7 @@ use std::sync::RwLock;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::auth::rls::RlsPolicy;
+use crate::auth::rls::{DefaultRlsEnforcer, RlsPolicy};
 
 /// Field definition in a schema
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +77,27 @@ pub struct SchemaDef {
     /// RLS policy for this collection
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rls_policy: Option<RlsPolicyDef>,
+
+    /// Whether anonymous callers may read every row (default: false)
+    #[serde(default)]
+    pub anon_read: bool,
+
+    /// References to other collections, embeddable in `select`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relations: Vec<RelationDef>,
+}
+
+/// A reference from one collection to another
+///
+/// `field` holds the `_id` of a record in `collection`; reads embed that
+/// record with `select=<alias>:<collection>(<fields>)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelationDef {
+    /// Field holding the referenced record's ID
+    pub field: String,
+
+    /// Referenced collection
+    pub collection: String,
 }
 
 impl SchemaDef {
@@ -170,6 +191,9 @@ pub struct SchemaEndpoint {
 
     /// RLS policy
     pub rls_policy: Option<RlsPolicy>,
+
+    /// Whether anonymous callers may read the collection
+    pub anon_read: bool,
 }
 
 impl SchemaEndpoint {
@@ -179,10 +203,26 @@ impl SchemaEndpoint {
 
         Self {
             collection: schema.name.clone(),
+            anon_read: schema.anon_read,
             schema,
             rls_policy,
         }
     }
+
+    /// This endpoint for the collection `name`
+    fn renamed(mut self, name: &str) -> Self {
+        self.collection = name.to_string();
+        self.schema.name = name.to_string();
+        self
+    }
+
+    /// Install this collection's policy and anonymous access in `rls`
+    pub fn install_rls(&self, rls: &DefaultRlsEnforcer) {
+        if let Some(policy) = &self.rls_policy {
+            rls.install_policy(&self.collection, policy.clone());
+        }
+        rls.set_anonymous_read(&self.collection, self.anon_read);
+    }
 }
 
 /// Endpoint registry for all generated endpoints
@@ -207,6 +247,15 @@ impl EndpointRegistry {
         Ok(())
     }
 
+    /// Remove an endpoint, returning it if it was registered
+    pub fn unregister(&self, collection: &str) -> Result<Option<SchemaEndpoint>, String> {
+        let mut endpoints = self
+            .endpoints
+            .write()
+            .map_err(|_| "Lock poisoned".to_string())?;
+        Ok(endpoints.remove(collection))
+    }
+
     /// Get an endpoint by collection name
     pub fn get(&self, collection: &str) -> Option<SchemaEndpoint> {
         self.endpoints.read().ok()?.get(collection).cloned()
@@ -220,6 +269,38 @@ impl EndpointRegistry {
             .unwrap_or_default()
     }
 
+    /// Move `from`'s endpoint to `to`, as renaming the collection does;
+    /// nothing happens if `from` has none
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), String> {
+        let mut endpoints = self
+            .endpoints
+            .write()
+            .map_err(|_| "Lock poisoned".to_string())?;
+        if endpoints.contains_key(to) {
+            return Err(format!("Endpoint already registered: {}", to));
+        }
+        if let Some(endpoint) = endpoints.remove(from) {
+            endpoints.insert(to.to_string(), endpoint.renamed(to));
+        }
+        Ok(())
+    }
+
+    /// Register a copy of `from`'s endpoint for `to`, as copying the
+    /// collection does; nothing happens if `from` has none
+    pub fn copy(&self, from: &str, to: &str) -> Result<(), String> {
+        let mut endpoints = self
+            .endpoints
+            .write()
+            .map_err(|_| "Lock poisoned".to_string())?;
+        if endpoints.contains_key(to) {
+            return Err(format!("Endpoint already registered: {}", to));
+        }
+        if let Some(endpoint) = endpoints.get(from).cloned() {
+            endpoints.insert(to.to_string(), endpoint.renamed(to));
+        }
+        Ok(())
+    }
+
     /// Reload endpoints from schema definitions
     pub fn reload(&self, schemas: Vec<SchemaDef>) -> Result<usize, String> {
         let mut endpoints = self
@@ -272,6 +353,8 @@ mod tests {
                 policy_type: RlsPolicyType::Ownership,
                 owner_field: Some("author_id".to_string()),
             }),
+            anon_read: false,
+            relations: Vec::new(),
         }
     }
 
@@ -332,6 +415,59 @@ mod tests {
         assert!(registry.get("posts").is_some());
         assert!(registry.get("nonexistent").is_none());
         assert_eq!(registry.collections(), vec!["posts"]);
+
+        assert!(registry.unregister("posts").unwrap().is_some());
+        assert!(registry.unregister("posts").unwrap().is_none());
+        assert!(registry.collections().is_empty());
+    }
+
+    #[test]
+    fn test_endpoint_registry_rename_and_copy() {
+        let registry = EndpointRegistry::new();
+        registry
+            .register(SchemaEndpoint::from_schema(create_posts_schema()))
+            .unwrap();
+
+        registry.rename("posts", "articles").unwrap();
+        assert!(registry.get("posts").is_none());
+        let endpoint = registry.get("articles").unwrap();
+        assert_eq!(endpoint.schema.name, "articles");
+        assert!(endpoint.rls_policy.is_some());
+
+        registry.copy("articles", "drafts").unwrap();
+        assert!(registry.get("articles").is_some());
+        assert_eq!(registry.get("drafts").unwrap().collection, "drafts");
+
+        // A registered target is refused; an unbound source moves nothing
+        assert!(registry.copy("articles", "drafts").is_err());
+        registry.rename("missing", "other").unwrap();
+        assert!(registry.get("other").is_none());
+    }
+
+    #[test]
+    fn test_anon_read_marking_installs_anonymous_access() {
+        use crate::auth::rls::{RlsContext, RlsEnforcer};
+
+        let rls = DefaultRlsEnforcer::new();
+        let anon = RlsContext::anonymous();
+
+        let mut schema: SchemaDef = serde_json::from_value(serde_json::json!({
+            "name": "posts",
+            "fields": [],
+            "rls_policy": {"type": "ownership", "owner_field": "author_id"}
+        }))
+        .unwrap();
+        assert!(!schema.anon_read);
+        SchemaEndpoint::from_schema(schema.clone()).install_rls(&rls);
+        assert!(rls.get_read_filter("posts", &anon).is_err());
+
+        schema.anon_read = true;
+        SchemaEndpoint::from_schema(schema).install_rls(&rls);
+        assert!(rls.get_read_filter("posts", &anon).unwrap().is_none());
+        assert!(matches!(
+            rls.policy("posts"),
+            Some(RlsPolicy::Ownership { owner_field }) if owner_field == "author_id"
+        ));
     }
 
     #[test]
@@ -5,18 +5,51 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use serde::Deserialize;
 use serde_json::Value;
 use uuid::Uuid;
 
 use crate::auth::rls::{RlsContext, RlsEnforcer};
 
+use super::aggregate::{aggregate_records, AggregateLimits};
 use super::errors::{RestError, RestResult};
-use super::filter::{FilterExpr, FilterSet};
+use super::filter::FilterSet;
 use super::parser::QueryParams;
 use super::response::{
-    DeleteResponse, InsertResponse, ListResponse, SingleResponse, UpdateResponse,
+    DeleteResponse, InsertResponse, ListResponse, SingleResponse, TransactionResponse,
+    UpdateResponse,
 };
 
+/// One write of a transaction batch (`/rest/v1/rpc/transaction`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum TransactionOperation {
+    Insert {
+        collection: String,
+        data: Value,
+    },
+    Update {
+        collection: String,
+        id: String,
+        data: Value,
+    },
+    Delete {
+        collection: String,
+        id: String,
+    },
+}
+
+impl TransactionOperation {
+    /// Collection the operation writes
+    pub fn collection(&self) -> &str {
+        match self {
+            TransactionOperation::Insert { collection, .. }
+            | TransactionOperation::Update { collection, .. }
+            | TransactionOperation::Delete { collection, .. } => collection,
+        }
+    }
+}
+
 /// REST handler trait for collection operations
 pub trait RestHandler: Send + Sync {
     /// List records in a collection
@@ -27,6 +60,15 @@ pub trait RestHandler: Send + Sync {
         ctx: &RlsContext,
     ) -> RestResult<ListResponse<Value>>;
 
+    /// Aggregate the records a list request would return
+    /// (`params.aggregate`), one record per group
+    fn aggregate(
+        &self,
+        collection: &str,
+        params: QueryParams,
+        ctx: &RlsContext,
+    ) -> RestResult<ListResponse<Value>>;
+
     /// Get a single record by ID
     fn get(
         &self,
@@ -54,6 +96,24 @@ pub trait RestHandler: Send + Sync {
 
     /// Delete a record
     fn delete(&self, collection: &str, id: &str, ctx: &RlsContext) -> RestResult<DeleteResponse>;
+
+    /// Remove a record for good, soft-deleted or not; the same as `delete`
+    /// for backends without soft deletes
+    fn purge(&self, collection: &str, id: &str, ctx: &RlsContext) -> RestResult<DeleteResponse> {
+        self.delete(collection, id, ctx)
+    }
+
+    /// Apply `operations` in order, all or none, returning each one's
+    /// record; backends that cannot are refused with
+    /// `TransactionsUnsupported`
+    fn transaction(
+        &self,
+        operations: Vec<TransactionOperation>,
+        ctx: &RlsContext,
+    ) -> RestResult<TransactionResponse<Value>> {
+        let _ = (operations, ctx);
+        Err(RestError::TransactionsUnsupported)
+    }
 }
 
 /// In-memory REST handler for testing
@@ -65,6 +125,9 @@ pub struct InMemoryRestHandler<E: RlsEnforcer> {
 
     /// RLS enforcer
     rls: Arc<E>,
+
+    /// Budget for aggregations
+    aggregate_limits: AggregateLimits,
 }
 
 impl<E: RlsEnforcer> InMemoryRestHandler<E> {
@@ -72,7 +135,101 @@ impl<E: RlsEnforcer> InMemoryRestHandler<E> {
         Self {
             data: std::sync::RwLock::new(HashMap::new()),
             rls: Arc::new(rls),
+            aggregate_limits: AggregateLimits::default(),
+        }
+    }
+
+    /// Use `limits` for aggregations
+    pub fn with_aggregate_limits(mut self, limits: AggregateLimits) -> Self {
+        self.aggregate_limits = limits;
+        self
+    }
+
+    /// Insert a record into `store`, returning it as stored
+    fn insert_into(
+        &self,
+        store: &mut HashMap<String, Vec<Value>>,
+        collection: &str,
+        mut data: Value,
+        ctx: &RlsContext,
+    ) -> RestResult<Value> {
+        // Prepare insert (add owner field)
+        self.rls.prepare_insert(collection, &mut data, ctx)?;
+
+        // Add ID if not present
+        if data.get("id").is_none() {
+            if let Some(obj) = data.as_object_mut() {
+                obj.insert("id".to_string(), Value::String(Uuid::new_v4().to_string()));
+            }
         }
+
+        // Validate write
+        self.rls.validate_write(collection, &data, ctx)?;
+
+        store
+            .entry(collection.to_string())
+            .or_default()
+            .push(data.clone());
+
+        Ok(data)
+    }
+
+    /// Merge `updates` into a record of `store`, returning it
+    fn update_in(
+        &self,
+        store: &mut HashMap<String, Vec<Value>>,
+        collection: &str,
+        id: &str,
+        updates: Value,
+        ctx: &RlsContext,
+    ) -> RestResult<Value> {
+        let records = store
+            .get_mut(collection)
+            .ok_or(RestError::CollectionNotFound(collection.to_string()))?;
+
+        let record = records
+            .iter_mut()
+            .find(|r| r.get("id").and_then(|v| v.as_str()) == Some(id))
+            .ok_or(RestError::NotFound)?;
+
+        // Validate RLS
+        self.rls.validate_write(collection, record, ctx)?;
+
+        // Apply updates
+        if let (Some(record_obj), Some(updates_obj)) = (record.as_object_mut(), updates.as_object())
+        {
+            for (key, value) in updates_obj {
+                record_obj.insert(key.clone(), value.clone());
+            }
+        }
+
+        Ok(record.clone())
+    }
+
+    /// Remove a record from `store`
+    fn delete_from(
+        &self,
+        store: &mut HashMap<String, Vec<Value>>,
+        collection: &str,
+        id: &str,
+        ctx: &RlsContext,
+    ) -> RestResult<()> {
+        let records = store
+            .get_mut(collection)
+            .ok_or(RestError::CollectionNotFound(collection.to_string()))?;
+
+        // Find record and validate RLS
+        let idx = records
+            .iter()
+            .position(|r| r.get("id").and_then(|v| v.as_str()) == Some(id))
+            .ok_or(RestError::NotFound)?;
+
+        let record = &records[idx];
+        self.rls.validate_write(collection, record, ctx)?;
+
+        records.remove(idx);
+
+        Ok(())
     }
 
     /// Apply RLS filter if needed
@@ -85,18 +242,11 @@ impl<E: RlsEnforcer> InMemoryRestHandler<E> {
         let filter = self.rls.get_read_filter(collection, ctx)?;
 
         match filter {
-            Some(rls_filter) => {
-                let filter_expr = FilterExpr::new(
-                    rls_filter.field,
-                    super::filter::FilterOperator::Eq,
-                    rls_filter.value,
-                );
-                Ok(records
-                    .iter()
-                    .filter(|r| filter_expr.matches(r))
-                    .cloned()
-                    .collect())
-            }
+            Some(rls_filter) => Ok(records
+                .iter()
+                .filter(|r| rls_filter.matches(r))
+                .cloned()
+                .collect()),
             None => Ok(records.to_vec()),
         }
     }
@@ -183,6 +333,11 @@ impl<E: RlsEnforcer> RestHandler for InMemoryRestHandler<E> {
         params: QueryParams,
         ctx: &RlsContext,
     ) -> RestResult<ListResponse<Value>> {
+        // No relations to embed through
+        if let Some(embed) = params.embeds.first() {
+            return Err(embed.no_relation(collection));
+        }
+
         let data = self
             .data
             .read()
@@ -209,6 +364,37 @@ impl<E: RlsEnforcer> RestHandler for InMemoryRestHandler<E> {
         Ok(ListResponse::new(records, params.limit, params.offset))
     }
 
+    fn aggregate(
+        &self,
+        collection: &str,
+        params: QueryParams,
+        ctx: &RlsContext,
+    ) -> RestResult<ListResponse<Value>> {
+        let spec = params
+            .aggregate
+            .as_ref()
+            .ok_or_else(|| RestError::MissingParam("aggregate".to_string()))?;
+
+        let data = self
+            .data
+            .read()
+            .map_err(|_| RestError::Internal("Lock poisoned".to_string()))?;
+
+        let records = data.get(collection).cloned().unwrap_or_default();
+
+        // Apply RLS filter
+        let records = self.apply_rls_filter(collection, &records, ctx)?;
+
+        // Apply query filters
+        let records = Self::apply_query_filters(&records, &params);
+
+        // Group, then page through groups
+        let groups = aggregate_records(&records, spec, &self.aggregate_limits)?;
+        let groups = Self::apply_pagination(groups, &params);
+
+        Ok(ListResponse::new(groups, params.limit, params.offset))
+    }
+
     fn get(
         &self,
         collection: &str,
@@ -237,31 +423,15 @@ impl<E: RlsEnforcer> RestHandler for InMemoryRestHandler<E> {
     fn insert(
         &self,
         collection: &str,
-        mut data: Value,
+        data: Value,
         ctx: &RlsContext,
     ) -> RestResult<InsertResponse<Value>> {
-        // Prepare insert (add owner field)
-        self.rls.prepare_insert(collection, &mut data, ctx)?;
-
-        // Add ID if not present
-        if data.get("id").is_none() {
-            if let Some(obj) = data.as_object_mut() {
-                obj.insert("id".to_string(), Value::String(Uuid::new_v4().to_string()));
-            }
-        }
-
-        // Validate write
-        self.rls.validate_write(collection, &data, ctx)?;
-
         let mut store = self
             .data
             .write()
             .map_err(|_| RestError::Internal("Lock poisoned".to_string()))?;
 
-        store
-            .entry(collection.to_string())
-            .or_default()
-            .push(data.clone());
+        let data = self.insert_into(&mut store, collection, data, ctx)?;
 
         Ok(InsertResponse::single(data))
     }
@@ -278,27 +448,9 @@ impl<E: RlsEnforcer> RestHandler for InMemoryRestHandler<E> {
             .write()
             .map_err(|_| RestError::Internal("Lock poisoned".to_string()))?;
 
-        let records = store
-            .get_mut(collection)
-            .ok_or(RestError::CollectionNotFound(collection.to_string()))?;
-
-        let record = records
-            .iter_mut()
-            .find(|r| r.get("id").and_then(|v| v.as_str()) == Some(id))
-            .ok_or(RestError::NotFound)?;
-
-        // Validate RLS
-        self.rls.validate_write(collection, record, ctx)?;
+        let record = self.update_in(&mut store, collection, id, updates, ctx)?;
 
-        // Apply updates
-        if let (Some(record_obj), Some(updates_obj)) = (record.as_object_mut(), updates.as_object())
-        {
-            for (key, value) in updates_obj {
-                record_obj.insert(key.clone(), value.clone());
-            }
-        }
-
-        Ok(UpdateResponse::new(record.clone()))
+        Ok(UpdateResponse::new(record))
     }
 
     fn delete(&self, collection: &str, id: &str, ctx: &RlsContext) -> RestResult<DeleteResponse> {
@@ -307,22 +459,44 @@ impl<E: RlsEnforcer> RestHandler for InMemoryRestHandler<E> {
             .write()
             .map_err(|_| RestError::Internal("Lock poisoned".to_string()))?;
 
-        let records = store
-            .get_mut(collection)
-            .ok_or(RestError::CollectionNotFound(collection.to_string()))?;
+        self.delete_from(&mut store, collection, id, ctx)?;
 
-        // Find record and validate RLS
-        let idx = records
-            .iter()
-            .position(|r| r.get("id").and_then(|v| v.as_str()) == Some(id))
-            .ok_or(RestError::NotFound)?;
+        Ok(DeleteResponse::success())
+    }
 
-        let record = &records[idx];
-        self.rls.validate_write(collection, record, ctx)?;
+    fn transaction(
+        &self,
+        operations: Vec<TransactionOperation>,
+        ctx: &RlsContext,
+    ) -> RestResult<TransactionResponse<Value>> {
+        let mut store = self
+            .data
+            .write()
+            .map_err(|_| RestError::Internal("Lock poisoned".to_string()))?;
 
-        records.remove(idx);
+        // Apply to a copy, published only if every operation succeeds
+        let mut staged = store.clone();
+        let mut results = Vec::with_capacity(operations.len());
+        for operation in operations {
+            let result = match operation {
+                TransactionOperation::Insert { collection, data } => {
+                    self.insert_into(&mut staged, &collection, data, ctx)?
+                }
+                TransactionOperation::Update {
+                    collection,
+                    id,
+                    data,
+                } => self.update_in(&mut staged, &collection, &id, data, ctx)?,
+                TransactionOperation::Delete { collection, id } => {
+                    self.delete_from(&mut staged, &collection, &id, ctx)?;
+                    serde_json::json!({ "id": id, "deleted": true })
+                }
+            };
+            results.push(result);
+        }
+        *store = staged;
 
-        Ok(DeleteResponse::success())
+        Ok(TransactionResponse::new(results))
     }
 }
 
@@ -390,6 +564,103 @@ mod tests {
         assert_eq!(all.count, 2);
     }
 
+    #[test]
+    fn test_list_applies_query_string_filters() {
+        let handler = create_test_handler();
+        let ctx = RlsContext::service_role();
+
+        for (name, age) in [("alice", 30), ("bob", 10), ("carol", 20)] {
+            handler
+                .insert(
+                    "people",
+                    serde_json::json!({"name": name, "age": age}),
+                    &ctx,
+                )
+                .unwrap();
+        }
+
+        let list_names = |key: &str, value: &str| -> Vec<String> {
+            let mut query = HashMap::new();
+            query.insert(key.to_string(), value.to_string());
+            query.insert("order".to_string(), "name.asc".to_string());
+            let params = QueryParams::parse(&query).unwrap();
+            handler
+                .list("people", params, &ctx)
+                .unwrap()
+                .data
+                .iter()
+                .map(|r| r["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        assert_eq!(list_names("name", "eq.bob"), vec!["bob"]);
+        assert_eq!(list_names("age", "gt.15"), vec!["alice", "carol"]);
+        assert_eq!(list_names("age", "lt.25"), vec!["bob", "carol"]);
+        assert!(list_names("age", "gt.30").is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_query_string() {
+        let memory = Arc::new(crate::resource_limits::MemoryTracker::new(1 << 20));
+        let handler = create_test_handler().with_aggregate_limits(AggregateLimits {
+            memory: memory.clone(),
+            max_groups: 4,
+        });
+        let ctx = RlsContext::service_role();
+
+        for order in [
+            serde_json::json!({"region": "us", "status": "paid", "amount": 5}),
+            serde_json::json!({"region": "eu", "status": "paid", "amount": 10}),
+            serde_json::json!({"region": "eu", "status": "paid", "amount": 20}),
+            serde_json::json!({"status": "paid", "amount": 1}),
+            serde_json::json!({"region": "eu", "status": "open", "amount": 7}),
+        ] {
+            handler.insert("orders", order, &ctx).unwrap();
+        }
+
+        let aggregate = |pairs: &[(&str, &str)]| {
+            let query: HashMap<String, String> = pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            handler.aggregate("orders", QueryParams::parse(&query)?, &ctx)
+        };
+
+        // Filtered, grouped by region; the missing region groups as null
+        let groups = aggregate(&[
+            ("aggregate", "total:sum(amount),n:count(*)"),
+            ("group_by", "region"),
+            ("status", "eq.paid"),
+        ])
+        .unwrap();
+        assert_eq!(
+            groups.data,
+            vec![
+                serde_json::json!({"region": null, "total": 1, "n": 1}),
+                serde_json::json!({"region": "eu", "total": 30, "n": 2}),
+                serde_json::json!({"region": "us", "total": 5, "n": 1}),
+            ]
+        );
+
+        // Multi-field grouping, paged
+        let groups = aggregate(&[
+            ("aggregate", "high:max(amount)"),
+            ("group_by", "region,status"),
+            ("limit", "1"),
+            ("offset", "1"),
+        ])
+        .unwrap();
+        assert_eq!(
+            groups.data,
+            vec![serde_json::json!({"region": "eu", "status": "open", "high": 7})]
+        );
+
+        // Five groups exceed the four allowed
+        let err = aggregate(&[("aggregate", "n:count(*)"), ("group_by", "amount")]).unwrap_err();
+        assert!(matches!(err, RestError::AggregationLimit(_)));
+        assert_eq!(memory.current(), 0);
+    }
+
     #[test]
     fn test_get_by_id() {
         let handler = create_test_handler();
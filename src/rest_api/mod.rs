@@ -3,6 +3,8 @@
 //! Provides HTTP endpoints for CRUD operations on all collections,
 //! with RLS enforcement through the core pipeline.
 
+pub mod aggregate;
+pub mod bindings;
 pub mod database;
 pub mod errors;
 pub mod filter;
@@ -15,6 +17,8 @@ pub mod response;
 pub mod server;
 pub mod unified_api;
 
+pub use aggregate::AggregateLimits;
+pub use bindings::RestBindings;
 pub use database::DatabaseFacade;
 pub use errors::{RestError, RestResult};
 pub use filter::{FilterExpr, FilterOperator};
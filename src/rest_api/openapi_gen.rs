@@ -9,7 +9,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
+use super::filter::FilterOperator;
+use crate::executor::AggregateFunction;
 use super::generator::{EndpointRegistry, FieldType, SchemaDef};
+use super::parser::{DEFAULT_LIMIT, MAX_LIMIT};
 
 /// OpenAPI 3.0 specification generator
 pub struct OpenApiGenerator {
@@ -64,6 +67,7 @@ impl OpenApiGenerator {
                 paths.insert(format!("/rest/v1/{}/{{id}}", collection), item_path);
             }
         }
+        paths.extend(Self::admin_paths());
 
         json!({
             "openapi": "3.0.3",
@@ -79,7 +83,16 @@ impl OpenApiGenerator {
                 }
             ],
             "paths": paths,
+            "tags": [
+                {
+                    "name": "admin",
+                    "description": "Read-only database statistics (admin or service_role JWT)"
+                }
+            ],
             "components": {
+                "schemas": {
+                    "FilterOperator": self.filter_operator_schema()
+                },
                 "securitySchemes": {
                     "bearerAuth": {
                         "type": "http",
@@ -114,32 +127,7 @@ impl OpenApiGenerator {
                 "summary": format!("List all {}", collection),
                 "operationId": format!("list_{}", collection),
                 "tags": [collection],
-                "parameters": [
-                    {
-                        "name": "limit",
-                        "in": "query",
-                        "required": false,
-                        "schema": { "type": "integer", "default": 100 }
-                    },
-                    {
-                        "name": "offset",
-                        "in": "query",
-                        "required": false,
-                        "schema": { "type": "integer", "default": 0 }
-                    },
-                    {
-                        "name": "select",
-                        "in": "query",
-                        "required": false,
-                        "schema": { "type": "string" }
-                    },
-                    {
-                        "name": "order",
-                        "in": "query",
-                        "required": false,
-                        "schema": { "type": "string" }
-                    }
-                ],
+                "parameters": self.list_parameters(schema),
                 "responses": {
                     "200": {
                         "description": format!("List of {}", collection),
@@ -307,6 +295,209 @@ impl OpenApiGenerator {
         (list_path, item_path)
     }
 
+    /// Paths of the `/admin/v1` statistics endpoints
+    fn admin_paths() -> Vec<(String, Value)> {
+        let responses = |description: &str| {
+            json!({
+                "200": {
+                    "description": description,
+                    "content": { "application/json": { "schema": { "type": "object" } } }
+                },
+                "401": { "description": "Unauthorized" },
+                "403": { "description": "Forbidden: not an admin or service_role token" },
+                "503": { "description": "Statistics are not available on this node" }
+            })
+        };
+        let operation = |id: &str, summary: &str, responses: Value| {
+            json!({
+                "get": {
+                    "summary": summary,
+                    "operationId": id,
+                    "tags": ["admin"],
+                    "security": [{ "bearerAuth": [] }],
+                    "responses": responses
+                }
+            })
+        };
+
+        let mut collection = responses("Collection with field statistics");
+        collection["404"] = json!({ "description": "Not Found" });
+        let mut item = operation("admin_get_collection", "Get collection statistics", collection);
+        item["get"]["parameters"] = json!([
+            {
+                "name": "name",
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" }
+            }
+        ]);
+
+        vec![
+            (
+                "/admin/v1/collections".to_string(),
+                operation(
+                    "admin_list_collections",
+                    "List collections with document counts, sizes and indexes",
+                    responses("Collection summaries"),
+                ),
+            ),
+            ("/admin/v1/collections/{name}".to_string(), item),
+            (
+                "/admin/v1/system".to_string(),
+                operation(
+                    "admin_system",
+                    "Resources, WAL, checkpoint, backup, replication and uptime",
+                    responses("System summary"),
+                ),
+            ),
+        ]
+    }
+
+    /// Query parameters for the list endpoint
+    ///
+    /// Pagination, projection, ordering and aggregation, followed by one
+    /// PostgREST-style filter parameter per non-primary field (`?age=gt.10`).
+    /// The primary key is addressed through the item path instead.
+    fn list_parameters(&self, schema: &SchemaDef) -> Vec<Value> {
+        let mut parameters = vec![
+            json!({
+                "name": "limit",
+                "in": "query",
+                "required": false,
+                "schema": { "type": "integer", "default": DEFAULT_LIMIT, "maximum": MAX_LIMIT }
+            }),
+            json!({
+                "name": "offset",
+                "in": "query",
+                "required": false,
+                "schema": { "type": "integer", "default": 0 }
+            }),
+            self.select_parameter(schema),
+            json!({
+                "name": "order",
+                "in": "query",
+                "required": false,
+                "schema": { "type": "string" }
+            }),
+            json!({
+                "name": "aggregate",
+                "in": "query",
+                "required": false,
+                "description": format!(
+                    "Aggregate the matching records instead of listing them, as \
+                     comma-separated `<name>:<function>(<field>)`, e.g. \
+                     `total:sum(amount),n:count(*)`. Functions: {}. `*` is accepted by \
+                     `count` only. Requires `group_by`; cannot be combined with \
+                     `select` or `order`. Returns one record per group (group fields \
+                     and named aggregates), ordered by group key; `limit` and `offset` \
+                     page through groups.",
+                    Self::aggregate_function_names().join(", ")
+                ),
+                "schema": {
+                    "type": "string",
+                    "pattern": Self::aggregate_pattern()
+                }
+            }),
+            json!({
+                "name": "group_by",
+                "in": "query",
+                "required": false,
+                "description": "Comma-separated fields to group by when `aggregate` is set. \
+                                A missing field groups as null.",
+                "schema": { "type": "string" }
+            }),
+        ];
+
+        for field in schema.fields.iter().filter(|f| !f.primary) {
+            parameters.push(json!({
+                "name": field.name,
+                "in": "query",
+                "required": false,
+                "description": format!(
+                    "Filter on `{}` as `<operator>.<value>`, e.g. `{}=eq.value`. \
+                     Operators: see #/components/schemas/FilterOperator.",
+                    field.name, field.name
+                ),
+                "style": "form",
+                "explode": false,
+                "schema": {
+                    "type": "string",
+                    "pattern": Self::filter_pattern()
+                }
+            }));
+        }
+
+        parameters
+    }
+
+    /// The `select` parameter, noting the relations that can be embedded
+    fn select_parameter(&self, schema: &SchemaDef) -> Value {
+        let embeddable: Vec<Value> = schema
+            .relations
+            .iter()
+            .map(|r| json!({ "collection": r.collection, "field": r.field }))
+            .collect();
+        let mut description = "Comma-separated fields to return, or `*`. Embed a referenced \
+                               record as `<alias>:<collection>(<fields>)`, or \
+                               `<collection>!<field>(<fields>)` to pick the relation; records \
+                               hidden by RLS embed as null."
+            .to_string();
+        if embeddable.is_empty() {
+            description.push_str(" No relations are embeddable.");
+        } else {
+            let names: Vec<String> = schema
+                .relations
+                .iter()
+                .map(|r| format!("`{}` (via `{}`)", r.collection, r.field))
+                .collect();
+            description.push_str(&format!(" Embeddable: {}.", names.join(", ")));
+        }
+
+        json!({
+            "name": "select",
+            "in": "query",
+            "required": false,
+            "description": description,
+            "schema": { "type": "string" },
+            "x-embeddable": embeddable
+        })
+    }
+
+    /// Regex accepted by filter parameters: `<operator>.<value>`
+    fn filter_pattern() -> String {
+        let ops: Vec<&str> = FilterOperator::ALL.iter().map(|op| op.as_str()).collect();
+        format!("^({})\\..*$", ops.join("|"))
+    }
+
+    /// Names of supported aggregate functions
+    fn aggregate_function_names() -> Vec<&'static str> {
+        AggregateFunction::ALL.iter().map(|f| f.as_str()).collect()
+    }
+
+    /// Regex accepted by the aggregate parameter
+    fn aggregate_pattern() -> String {
+        let item = format!(
+            "[^,:]+:({})\\([^,()]+\\)",
+            Self::aggregate_function_names().join("|")
+        );
+        format!("^{}(,{})*$", item, item)
+    }
+
+    /// Enum schema of supported filter operators
+    fn filter_operator_schema(&self) -> Value {
+        let names: Vec<&str> = FilterOperator::ALL.iter().map(|op| op.as_str()).collect();
+        let descriptions: Vec<String> = FilterOperator::ALL
+            .iter()
+            .map(|op| format!("`{}`: {}", op.as_str(), op.description()))
+            .collect();
+
+        json!({
+            "type": "string",
+            "enum": names,
+            "description": format!("Filter operators.\n\n{}", descriptions.join("\n"))
+        })
+    }
+
     /// Convert schema to JSON Schema format
     fn field_type_to_json_schema(&self, schema: &SchemaDef) -> Value {
         let mut properties = HashMap::new();
@@ -411,7 +602,7 @@ pub fn generate_routes(registry: &EndpointRegistry) -> Vec<RouteInfo> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::super::generator::{FieldDef, SchemaDef, SchemaEndpoint};
+    use super::super::generator::{FieldDef, RelationDef, SchemaDef, SchemaEndpoint};
 
     fn create_test_schema() -> SchemaDef {
         SchemaDef {
@@ -440,6 +631,8 @@ mod tests {
                 },
             ],
             rls_policy: None,
+            anon_read: false,
+            relations: Vec::new(),
         }
     }
 
@@ -457,6 +650,93 @@ mod tests {
         assert!(spec["paths"].as_object().is_some());
     }
 
+    #[test]
+    fn test_list_path_has_filter_param_per_non_primary_field() {
+        let registry = EndpointRegistry::new();
+        let schema = create_test_schema();
+        registry
+            .register(SchemaEndpoint::from_schema(schema.clone()))
+            .unwrap();
+
+        let spec = OpenApiGenerator::new().generate(&registry);
+        let params = spec["paths"]["/rest/v1/users"]["get"]["parameters"]
+            .as_array()
+            .unwrap();
+
+        for field in schema.fields.iter().filter(|f| !f.primary) {
+            let param = params
+                .iter()
+                .find(|p| p["name"] == field.name.as_str())
+                .unwrap_or_else(|| panic!("missing filter param for {}", field.name));
+            assert_eq!(param["in"], "query");
+            assert_eq!(param["style"], "form");
+            assert_eq!(param["explode"], false);
+            assert!(param["schema"]["pattern"].as_str().unwrap().contains("gt"));
+        }
+
+        // Primary key is not a filter parameter
+        assert!(!params.iter().any(|p| p["name"] == "id"));
+
+        let aggregate = params.iter().find(|p| p["name"] == "aggregate").unwrap();
+        assert!(aggregate["description"].as_str().unwrap().contains("count, sum, avg, min, max"));
+        let pattern = regex::Regex::new(aggregate["schema"]["pattern"].as_str().unwrap()).unwrap();
+        assert!(pattern.is_match("total:sum(amount),n:count(*)"));
+        assert!(!pattern.is_match("total:median(amount)"));
+        assert!(params.iter().any(|p| p["name"] == "group_by"));
+
+        let select = params.iter().find(|p| p["name"] == "select").unwrap();
+        assert_eq!(select["x-embeddable"], json!([]));
+
+        let ops = spec["components"]["schemas"]["FilterOperator"]["enum"]
+            .as_array()
+            .unwrap();
+        assert_eq!(ops.len(), FilterOperator::ALL.len());
+        assert!(ops.contains(&json!("eq")));
+        assert!(ops.contains(&json!("lt")));
+    }
+
+    #[test]
+    fn test_select_param_lists_embeddable_relations() {
+        let registry = EndpointRegistry::new();
+        let mut schema = create_test_schema();
+        schema.name = "posts".to_string();
+        schema.relations.push(RelationDef {
+            field: "author_id".to_string(),
+            collection: "users".to_string(),
+        });
+        registry.register(SchemaEndpoint::from_schema(schema)).unwrap();
+
+        let spec = OpenApiGenerator::new().generate(&registry);
+        let params = spec["paths"]["/rest/v1/posts"]["get"]["parameters"]
+            .as_array()
+            .unwrap();
+        let select = params.iter().find(|p| p["name"] == "select").unwrap();
+        assert_eq!(
+            select["x-embeddable"],
+            json!([{ "collection": "users", "field": "author_id" }])
+        );
+        assert!(select["description"]
+            .as_str()
+            .unwrap()
+            .contains("`users` (via `author_id`)"));
+    }
+
+    #[test]
+    fn test_admin_paths_are_tagged() {
+        let spec = OpenApiGenerator::new().generate(&EndpointRegistry::new());
+
+        for path in [
+            "/admin/v1/collections",
+            "/admin/v1/collections/{name}",
+            "/admin/v1/system",
+        ] {
+            let get = &spec["paths"][path]["get"];
+            assert_eq!(get["tags"], json!(["admin"]), "{}", path);
+            assert!(get["responses"]["403"].is_object(), "{}", path);
+        }
+        assert_eq!(spec["tags"][0]["name"], "admin");
+    }
+
     #[test]
     fn test_routes_generation() {
         let registry = EndpointRegistry::new();
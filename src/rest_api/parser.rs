@@ -6,6 +6,7 @@ use std::collections::HashMap;
 
 use super::errors::{RestError, RestResult};
 use super::filter::{FilterExpr, FilterOperator};
+use crate::executor::{Aggregate, AggregateFunction, AggregateSpec};
 
 /// Maximum number of records that can be returned
 pub const MAX_LIMIT: usize = 1000;
@@ -13,12 +14,18 @@ pub const MAX_LIMIT: usize = 1000;
 /// Default limit if not specified
 pub const DEFAULT_LIMIT: usize = 100;
 
+/// How deeply `select` may nest embedded resources
+pub const MAX_EMBED_DEPTH: usize = 1;
+
 /// Parsed query parameters
 #[derive(Debug, Clone)]
 pub struct QueryParams {
     /// Fields to select (None = all)
     pub select: Option<Vec<String>>,
 
+    /// Referenced records to embed, from `select`
+    pub embeds: Vec<Embed>,
+
     /// Filter expressions
     pub filters: Vec<FilterExpr>,
 
@@ -30,20 +37,73 @@ pub struct QueryParams {
 
     /// Number of records to skip
     pub offset: usize,
+
+    /// Aggregation (`aggregate` with `group_by`); `limit` and `offset` then
+    /// page through groups
+    pub aggregate: Option<AggregateSpec>,
+
+    /// Staleness bound when served by a replica (`max_staleness_ms`)
+    pub max_staleness_ms: Option<u64>,
+
+    /// Also return soft-deleted records (`include_deleted`, service role
+    /// only)
+    pub include_deleted: bool,
 }
 
 impl Default for QueryParams {
     fn default() -> Self {
         Self {
             select: None,
+            embeds: Vec::new(),
             filters: Vec::new(),
             order: Vec::new(),
             limit: DEFAULT_LIMIT,
             offset: 0,
+            aggregate: None,
+            max_staleness_ms: None,
+            include_deleted: false,
         }
     }
 }
 
+/// A referenced record embedded by `select`: `alias:collection(fields)`
+///
+/// `collection!field` names the referencing field when a collection has
+/// more than one relation to `collection`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Embed {
+    /// Key the embedded record is nested under (default: `collection`)
+    pub alias: String,
+
+    /// Referenced collection
+    pub collection: String,
+
+    /// Referencing field, when given as `collection!field`
+    pub field: Option<String>,
+
+    /// Fields of the embedded record (`*` = all)
+    pub select: Vec<String>,
+
+    /// Records embedded in turn (at most `MAX_EMBED_DEPTH` levels)
+    pub embeds: Vec<Embed>,
+}
+
+impl Embed {
+    /// The error for a `collection` without a relation this embed can follow
+    pub fn no_relation(&self, collection: &str) -> RestError {
+        RestError::InvalidQueryParam(match &self.field {
+            Some(field) => format!(
+                "select: '{}' has no relation to '{}' through '{}'",
+                collection, self.collection, field
+            ),
+            None => format!(
+                "select: '{}' has no relation to '{}'",
+                collection, self.collection
+            ),
+        })
+    }
+}
+
 /// Order by clause
 #[derive(Debug, Clone)]
 pub struct OrderBy {
@@ -58,11 +118,15 @@ impl QueryParams {
             limit: DEFAULT_LIMIT,
             ..Default::default()
         };
+        let mut aggregate = None;
+        let mut group_by = None;
 
         for (key, value) in params {
             match key.as_str() {
                 "select" => {
-                    result.select = Some(parse_select(value)?);
+                    let (fields, embeds) = parse_select(value, 0)?;
+                    result.select = Some(fields);
+                    result.embeds = embeds;
                 }
                 "order" => {
                     result.order = parse_order(value)?;
@@ -73,6 +137,18 @@ impl QueryParams {
                 "offset" => {
                     result.offset = parse_offset(value)?;
                 }
+                "aggregate" => {
+                    aggregate = Some(value.as_str());
+                }
+                "group_by" => {
+                    group_by = Some(value.as_str());
+                }
+                "max_staleness_ms" => {
+                    result.max_staleness_ms = Some(parse_max_staleness(value)?);
+                }
+                "include_deleted" => {
+                    result.include_deleted = parse_flag(key, value)?;
+                }
                 _ => {
                     // Treat as filter
                     if let Some(filter) = parse_filter(key, value)? {
@@ -82,6 +158,19 @@ impl QueryParams {
             }
         }
 
+        result.aggregate = match (aggregate, group_by) {
+            (Some(aggregate), Some(group_by)) => Some(parse_aggregate(aggregate, group_by)?),
+            (Some(_), None) => return Err(RestError::MissingParam("group_by".to_string())),
+            (None, Some(_)) => return Err(RestError::MissingParam("aggregate".to_string())),
+            (None, None) => None,
+        };
+        if result.aggregate.is_some() && (result.select.is_some() || !result.order.is_empty()) {
+            return Err(RestError::InvalidQueryParam(
+                "select and order cannot be combined with aggregate; groups are ordered by group key"
+                    .to_string(),
+            ));
+        }
+
         // Enforce maximum limit
         if result.limit > MAX_LIMIT {
             return Err(RestError::LimitExceeded(result.limit, MAX_LIMIT));
@@ -96,25 +185,94 @@ impl QueryParams {
     }
 }
 
-/// Parse select parameter (comma-separated field list)
-fn parse_select(value: &str) -> RestResult<Vec<String>> {
-    if value == "*" {
-        return Ok(vec!["*".to_string()]);
+/// Parse select parameter (comma-separated fields and embeds)
+///
+/// Example: `select=id,title,author:users(name,email)`
+fn parse_select(value: &str, depth: usize) -> RestResult<(Vec<String>, Vec<Embed>)> {
+    let invalid = |reason: String| RestError::InvalidQueryParam(format!("select: {}", reason));
+
+    if value.trim() == "*" {
+        return Ok((vec!["*".to_string()], Vec::new()));
     }
 
-    let fields: Vec<String> = value
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect();
+    let mut fields = Vec::new();
+    let mut embeds = Vec::new();
+    for item in split_select(value).map_err(invalid)? {
+        let Some((head, inner)) = item.strip_suffix(')').and_then(|item| item.split_once('('))
+        else {
+            if item.contains(['(', ')', ':', '!']) {
+                return Err(invalid(format!("invalid field '{}'", item)));
+            }
+            fields.push(item.to_string());
+            continue;
+        };
+
+        if depth >= MAX_EMBED_DEPTH {
+            return Err(invalid(format!(
+                "'{}' is nested too deeply; embeds nest at most {} level(s)",
+                item, MAX_EMBED_DEPTH
+            )));
+        }
+        let (alias, target) = match head.split_once(':') {
+            Some((alias, target)) => (Some(alias.trim()), target.trim()),
+            None => (None, head.trim()),
+        };
+        let (collection, field) = match target.split_once('!') {
+            Some((collection, field)) => (collection.trim(), Some(field.trim().to_string())),
+            None => (target, None),
+        };
+        let alias = alias.unwrap_or(collection);
+        if alias.is_empty() || collection.is_empty() || field.as_deref() == Some("") {
+            return Err(invalid(format!(
+                "expected <alias>:<collection>(<fields>), got '{}'",
+                item
+            )));
+        }
+        let (select, nested) = parse_select(inner, depth + 1)?;
+        embeds.push(Embed {
+            alias: alias.to_string(),
+            collection: collection.to_string(),
+            field,
+            select,
+            embeds: nested,
+        });
+    }
 
-    if fields.is_empty() {
+    if fields.is_empty() && embeds.is_empty() {
         return Err(RestError::InvalidQueryParam(
             "select cannot be empty".to_string(),
         ));
     }
 
-    Ok(fields)
+    Ok((fields, embeds))
+}
+
+/// Split a select list on the commas outside parentheses
+fn split_select(value: &str) -> Result<Vec<&str>, String> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| format!("unbalanced ')' in '{}'", value))?;
+            }
+            ',' if depth == 0 => {
+                items.push(value[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        return Err(format!("unbalanced '(' in '{}'", value));
+    }
+    items.push(value[start..].trim());
+
+    Ok(items.into_iter().filter(|item| !item.is_empty()).collect())
 }
 
 /// Parse order parameter (comma-separated field.direction)
@@ -154,6 +312,44 @@ fn parse_order(value: &str) -> RestResult<Vec<OrderBy>> {
     Ok(orders)
 }
 
+/// Parse `aggregate` (`<name>:<function>(<field>)`, comma-separated) and
+/// `group_by` (comma-separated fields)
+///
+/// Example: `aggregate=total:sum(amount),n:count(*)&group_by=region`
+fn parse_aggregate(aggregate: &str, group_by: &str) -> RestResult<AggregateSpec> {
+    let invalid = |reason: String| RestError::InvalidQueryParam(format!("aggregate: {}", reason));
+
+    let mut aggregates = Vec::new();
+    for part in aggregate
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+    {
+        let (name, call) = part.split_once(':').ok_or_else(|| {
+            invalid(format!(
+                "expected <name>:<function>(<field>), got '{}'",
+                part
+            ))
+        })?;
+        let (function, field) = call
+            .strip_suffix(')')
+            .and_then(|call| call.split_once('('))
+            .ok_or_else(|| invalid(format!("expected <function>(<field>), got '{}'", call)))?;
+        let function = AggregateFunction::parse(function.trim())
+            .ok_or_else(|| invalid(format!("unknown function '{}'", function)))?;
+        let aggregate = Aggregate::new(function, field.trim()).map_err(invalid)?;
+        aggregates.push((name.trim().to_string(), aggregate));
+    }
+
+    let group_by = group_by
+        .split(',')
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty())
+        .collect();
+
+    AggregateSpec::new(group_by, aggregates).map_err(invalid)
+}
+
 /// Parse limit parameter
 fn parse_limit(value: &str) -> RestResult<usize> {
     value
@@ -168,6 +364,20 @@ fn parse_offset(value: &str) -> RestResult<usize> {
         .map_err(|_| RestError::InvalidQueryParam(format!("Invalid offset: {}", value)))
 }
 
+/// Parse max_staleness_ms parameter
+pub fn parse_max_staleness(value: &str) -> RestResult<u64> {
+    value
+        .parse()
+        .map_err(|_| RestError::InvalidQueryParam(format!("Invalid max_staleness_ms: {}", value)))
+}
+
+/// Parse a boolean flag (`true` or `false`)
+pub fn parse_flag(name: &str, value: &str) -> RestResult<bool> {
+    value
+        .parse()
+        .map_err(|_| RestError::InvalidQueryParam(format!("Invalid {}: {}", name, value)))
+}
+
 /// Parse a filter expression from key=value
 fn parse_filter(field: &str, value: &str) -> RestResult<Option<FilterExpr>> {
     // Check for operator prefix
@@ -175,17 +385,9 @@ fn parse_filter(field: &str, value: &str) -> RestResult<Option<FilterExpr>> {
         let op_str = &value[..dot_pos];
         let val = &value[dot_pos + 1..];
 
-        let op = match op_str {
-            "eq" => FilterOperator::Eq,
-            "neq" => FilterOperator::Neq,
-            "gt" => FilterOperator::Gt,
-            "gte" => FilterOperator::Gte,
-            "lt" => FilterOperator::Lt,
-            "lte" => FilterOperator::Lte,
-            "like" => FilterOperator::Like,
-            "in" => FilterOperator::In,
-            "is" => FilterOperator::Is,
-            _ => {
+        let op = match FilterOperator::parse(op_str) {
+            Some(op) => op,
+            None => {
                 // No known operator, treat as eq with the whole value
                 return Ok(Some(FilterExpr {
                     field: field.to_string(),
@@ -213,10 +415,10 @@ fn parse_filter_value(value: &str) -> RestResult<serde_json::Value> {
     // Check for list syntax: (a,b,c)
     if value.starts_with('(') && value.ends_with(')') {
         let inner = &value[1..value.len() - 1];
-        let items: Vec<serde_json::Value> = inner
+        let items = inner
             .split(',')
-            .map(|s| serde_json::Value::String(s.trim().to_string()))
-            .collect();
+            .map(|s| parse_filter_value(s.trim()))
+            .collect::<RestResult<Vec<_>>>()?;
         return Ok(serde_json::Value::Array(items));
     }
 
@@ -253,13 +455,84 @@ mod tests {
 
     #[test]
     fn test_parse_select() {
-        let fields = parse_select("id,name,email").unwrap();
+        let (fields, embeds) = parse_select("id,name,email", 0).unwrap();
         assert_eq!(fields, vec!["id", "name", "email"]);
+        assert!(embeds.is_empty());
 
-        let all = parse_select("*").unwrap();
+        let (all, _) = parse_select("*", 0).unwrap();
         assert_eq!(all, vec!["*"]);
     }
 
+    #[test]
+    fn test_parse_select_embeds() {
+        let (fields, embeds) =
+            parse_select("id,title,author:users(name,email),users!editor_id(*)", 0).unwrap();
+        assert_eq!(fields, vec!["id", "title"]);
+        assert_eq!(
+            embeds[0],
+            Embed {
+                alias: "author".to_string(),
+                collection: "users".to_string(),
+                field: None,
+                select: vec!["name".to_string(), "email".to_string()],
+                embeds: Vec::new(),
+            }
+        );
+        assert_eq!(embeds[1].alias, "users");
+        assert_eq!(embeds[1].field.as_deref(), Some("editor_id"));
+        assert_eq!(embeds[1].select, vec!["*"]);
+
+        // Embeds alone are a valid selection
+        let (fields, embeds) = parse_select("author:users(name)", 0).unwrap();
+        assert!(fields.is_empty());
+        assert_eq!(embeds.len(), 1);
+
+        for bad in [
+            "author:users(name",
+            "author:users)name(",
+            ":users(name)",
+            "author:(name)",
+            "author:users()",
+            "author:users(org:orgs(name))",
+            "na(me",
+        ] {
+            assert!(
+                matches!(parse_select(bad, 0), Err(RestError::InvalidQueryParam(_))),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_aggregate() {
+        let spec = parse_aggregate("total:sum(amount), n:count(*)", "region,status").unwrap();
+        assert_eq!(spec.group_by(), ["region", "status"]);
+        assert_eq!(spec.aggregates()[0].0, "total");
+        assert_eq!(spec.aggregates()[0].1.function, AggregateFunction::Sum);
+        assert_eq!(spec.aggregates()[0].1.field.as_deref(), Some("amount"));
+        assert_eq!(spec.aggregates()[1].1.field, None);
+
+        for bad in ["sum(amount)", "t:sum", "t:median(x)", "t:avg(*)", ""] {
+            assert!(parse_aggregate(bad, "region").is_err(), "{}", bad);
+        }
+        assert!(parse_aggregate("n:count(*)", "").is_err());
+
+        let mut params = HashMap::new();
+        params.insert("aggregate".to_string(), "n:count(*)".to_string());
+        assert!(matches!(
+            QueryParams::parse(&params),
+            Err(RestError::MissingParam(_))
+        ));
+        params.insert("group_by".to_string(), "region".to_string());
+        params.insert("status".to_string(), "eq.paid".to_string());
+        let parsed = QueryParams::parse(&params).unwrap();
+        assert!(parsed.aggregate.is_some());
+        assert_eq!(parsed.filters.len(), 1);
+        params.insert("order".to_string(), "region.desc".to_string());
+        assert!(QueryParams::parse(&params).is_err());
+    }
+
     #[test]
     fn test_parse_order() {
         let orders = parse_order("created_at.desc,name.asc").unwrap();
@@ -300,6 +573,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_in_filter_numbers() {
+        let filter = parse_filter("age", "in.(1, 2,3)").unwrap().unwrap();
+        assert_eq!(filter.value, serde_json::json!([1, 2, 3]));
+    }
+
+    #[test]
+    fn test_parse_unknown_operator_is_literal_eq() {
+        let filter = parse_filter("host", "example.com").unwrap().unwrap();
+        assert_eq!(filter.operator, FilterOperator::Eq);
+        assert_eq!(filter.value, serde_json::json!("example.com"));
+    }
+
     #[test]
     fn test_full_query_params() {
         let mut params = HashMap::new();
@@ -319,6 +605,38 @@ mod tests {
         assert_eq!(query.limit, 20);
         assert_eq!(query.offset, 10);
         assert_eq!(query.filters.len(), 1);
+        assert_eq!(query.max_staleness_ms, None);
+        assert!(!query.include_deleted);
+    }
+
+    #[test]
+    fn test_parse_include_deleted() {
+        let mut params = HashMap::new();
+        params.insert("include_deleted".to_string(), "true".to_string());
+        let query = QueryParams::parse(&params).unwrap();
+        assert!(query.include_deleted);
+        assert!(query.filters.is_empty());
+
+        params.insert("include_deleted".to_string(), "yes".to_string());
+        assert!(matches!(
+            QueryParams::parse(&params),
+            Err(RestError::InvalidQueryParam(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_max_staleness() {
+        let mut params = HashMap::new();
+        params.insert("max_staleness_ms".to_string(), "250".to_string());
+        let query = QueryParams::parse(&params).unwrap();
+        assert_eq!(query.max_staleness_ms, Some(250));
+        assert!(query.filters.is_empty());
+
+        params.insert("max_staleness_ms".to_string(), "soon".to_string());
+        assert!(matches!(
+            QueryParams::parse(&params),
+            Err(RestError::InvalidQueryParam(_))
+        ));
     }
 
     #[test]
@@ -12,6 +12,7 @@ use uuid::Uuid;
 use crate::auth::rls::RlsContext;
 use crate::core::{AuthContext, BridgeConfig, PipelineBridge, RequestContext};
 
+use super::aggregate::{aggregate_records, AggregateLimits};
 use super::errors::{RestError, RestResult};
 use super::filter::FilterSet;
 use super::parser::QueryParams;
@@ -26,21 +27,29 @@ use super::RestHandler;
 pub struct PipelineRestHandler {
     bridge: Arc<PipelineBridge>,
     runtime: Handle,
+    aggregate_limits: AggregateLimits,
 }
 
 impl PipelineRestHandler {
     /// Create a new pipeline handler with an existing bridge
     pub fn new(bridge: Arc<PipelineBridge>, runtime: Handle) -> Self {
-        Self { bridge, runtime }
+        Self {
+            bridge,
+            runtime,
+            aggregate_limits: AggregateLimits::default(),
+        }
     }
 
     /// Create with a new in-memory bridge (for testing)
     pub fn new_in_memory(runtime: Handle) -> Self {
         let bridge = PipelineBridge::new_in_memory(BridgeConfig::default());
-        Self {
-            bridge: Arc::new(bridge),
-            runtime,
-        }
+        Self::new(Arc::new(bridge), runtime)
+    }
+
+    /// Use `limits` for aggregations
+    pub fn with_aggregate_limits(mut self, limits: AggregateLimits) -> Self {
+        self.aggregate_limits = limits;
+        self
     }
 
     /// Convert RlsContext to core AuthContext
@@ -132,6 +141,11 @@ impl RestHandler for PipelineRestHandler {
         params: QueryParams,
         ctx: &RlsContext,
     ) -> RestResult<ListResponse<Value>> {
+        // No relations to embed through
+        if let Some(embed) = params.embeds.first() {
+            return Err(embed.no_relation(collection));
+        }
+
         let context = Self::to_request_context(ctx);
         let collection = collection.to_string();
         let limit = params.limit;
@@ -169,6 +183,43 @@ impl RestHandler for PipelineRestHandler {
         Ok(ListResponse::new(records, limit, offset))
     }
 
+    fn aggregate(
+        &self,
+        collection: &str,
+        params: QueryParams,
+        ctx: &RlsContext,
+    ) -> RestResult<ListResponse<Value>> {
+        let spec = params
+            .aggregate
+            .as_ref()
+            .ok_or_else(|| RestError::MissingParam("aggregate".to_string()))?;
+        let context = Self::to_request_context(ctx);
+
+        // Every record the pipeline returns (RLS applied by middleware)
+        let result = self
+            .runtime
+            .block_on(self.bridge.query(collection, None, usize::MAX, 0, context))
+            .map_err(|e| RestError::Internal(e.to_string()))?;
+        let records: Vec<Value> = result
+            .get("results")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        // Apply query filters
+        let records = Self::apply_query_filters(&records, &params);
+
+        // Group, then page through groups
+        let groups = aggregate_records(&records, spec, &self.aggregate_limits)?;
+        let groups: Vec<Value> = groups
+            .into_iter()
+            .skip(params.offset)
+            .take(params.limit)
+            .collect();
+
+        Ok(ListResponse::new(groups, params.limit, params.offset))
+    }
+
     fn get(
         &self,
         collection: &str,
@@ -82,6 +82,20 @@ impl DeleteResponse {
     }
 }
 
+/// Transaction response: each operation's record, in order
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionResponse<T: Serialize> {
+    pub data: Vec<T>,
+    pub count: usize,
+}
+
+impl<T: Serialize> TransactionResponse<T> {
+    pub fn new(data: Vec<T>) -> Self {
+        let count = data.len();
+        Self { data, count }
+    }
+}
+
 /// Count-only response (for HEAD requests)
 #[derive(Debug, Clone, Serialize)]
 pub struct CountResponse {
@@ -1,9 +1,14 @@
 //! # REST API HTTP Server
 //!
 //! Axum-based HTTP server for REST endpoints.
+//!
+//! The caller's role comes from the bearer token's `role` claim: `anon`
+//! and `authenticated` are subject to RLS, `service_role` bypasses it.
+//! Every operation is recorded in the operation log with that role.
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 
 use axum::{
     extract::{Path, Query, State},
@@ -11,22 +16,33 @@ use axum::{
     routing::{delete, get, patch, post},
     Json, Router,
 };
+use serde::Deserialize;
 use serde_json::Value;
 
+use crate::admission_control::{
+    AdmissionControlConfig, AdmissionController, AdmissionPermit, OperationClass,
+};
 use crate::auth::jwt::{JwtConfig, JwtManager};
 use crate::auth::rls::RlsContext;
+use crate::auth::AuthError;
+use crate::observability::{OperationLog, OperationLogEntry, OperationType};
+use crate::replication::ReplicaGate;
 
 use super::errors::{RestError, RestResult};
-use super::handler::RestHandler;
-use super::parser::QueryParams;
+use super::handler::{RestHandler, TransactionOperation};
+use super::parser::{parse_flag, parse_max_staleness, QueryParams};
 use super::response::{
-    DeleteResponse, InsertResponse, ListResponse, SingleResponse, UpdateResponse,
+    DeleteResponse, InsertResponse, ListResponse, SingleResponse, TransactionResponse,
+    UpdateResponse,
 };
 
 /// REST API server state
 pub struct RestServer<H: RestHandler> {
     handler: Arc<H>,
     jwt_manager: JwtManager,
+    replica_gate: ReplicaGate,
+    admission: Arc<AdmissionController>,
+    operation_log: Option<Arc<OperationLog>>,
 }
 
 impl<H: RestHandler + 'static> RestServer<H> {
@@ -34,7 +50,54 @@ impl<H: RestHandler + 'static> RestServer<H> {
         Self {
             handler: Arc::new(handler),
             jwt_manager: JwtManager::new(jwt_config),
+            replica_gate: ReplicaGate::default(),
+            admission: Arc::new(AdmissionController::new(AdmissionControlConfig::default())),
+            operation_log: None,
+        }
+    }
+
+    /// Serve as a replica: reads within their staleness bound, no writes
+    pub fn with_replica_gate(mut self, replica_gate: ReplicaGate) -> Self {
+        self.replica_gate = replica_gate;
+        self
+    }
+
+    /// Bound concurrent reads and writes with a shared admission controller
+    pub fn with_admission(mut self, admission: Arc<AdmissionController>) -> Self {
+        self.admission = admission;
+        self
+    }
+
+    /// Record operations, with the caller's role, in `operation_log`
+    pub fn with_operation_log(mut self, operation_log: Arc<OperationLog>) -> Self {
+        self.operation_log = Some(operation_log);
+        self
+    }
+
+    /// Record an operation and its outcome in the operation log, if any
+    fn log_operation<T>(
+        &self,
+        operation: OperationType,
+        collection: &str,
+        ctx: &RlsContext,
+        started: Instant,
+        result: &RestResult<T>,
+    ) {
+        let Some(log) = &self.operation_log else {
+            return;
+        };
+        let mut entry = OperationLogEntry::builder(operation)
+            .collection(collection)
+            .role(ctx.role())
+            .duration(started.elapsed())
+            .slow_threshold_ms(log.slow_threshold_ms());
+        if let Some(user_id) = ctx.user_id {
+            entry = entry.user_id(user_id);
         }
+        if let Err(err) = result {
+            entry = entry.error(err.status_code().as_str(), err.to_string());
+        }
+        log.log(entry.build());
     }
 
     /// Build the Axum router
@@ -47,6 +110,7 @@ impl<H: RestHandler + 'static> RestServer<H> {
             .route("/rest/v1/{collection}/{id}", get(get_handler))
             .route("/rest/v1/{collection}/{id}", patch(update_handler))
             .route("/rest/v1/{collection}/{id}", delete(delete_handler))
+            .route("/rest/v1/rpc/transaction", post(transaction_handler))
             .with_state(state)
     }
 }
@@ -55,27 +119,18 @@ impl<H: RestHandler + 'static> RestServer<H> {
 type ServerState<H> = Arc<RestServer<H>>;
 
 /// Extract RLS context from headers
+///
+/// A bearer token's `role` claim picks the context; service-role tokens
+/// validate only against the service-role secret. Without a token the
+/// caller is anonymous.
 fn extract_context<H: RestHandler>(
     server: &RestServer<H>,
     headers: &HeaderMap,
 ) -> RestResult<RlsContext> {
-    // Check for service role (apikey header)
-    if let Some(apikey) = headers.get("apikey").and_then(|v| v.to_str().ok()) {
-        // In production, validate the API key
-        if apikey.starts_with("service_") {
-            return Ok(RlsContext::service_role());
-        }
-    }
-
-    // Check for bearer token
     if let Some(auth) = headers.get("authorization").and_then(|v| v.to_str().ok()) {
         if let Some(token) = auth.strip_prefix("Bearer ") {
-            let claims = server
-                .jwt_manager
-                .validate_token(token)
-                .map_err(|e| RestError::Auth(e))?;
-            let user_id = JwtManager::get_user_id(&claims).map_err(|e| RestError::Auth(e))?;
-            return Ok(RlsContext::authenticated(user_id));
+            let claims = server.jwt_manager.validate_token(token)?;
+            return Ok(RlsContext::from_claims(&claims)?);
         }
     }
 
@@ -83,6 +138,21 @@ fn extract_context<H: RestHandler>(
     Ok(RlsContext::anonymous())
 }
 
+/// Take an admission permit for `class`, queueing off the async runtime
+async fn admit<H: RestHandler>(
+    server: &RestServer<H>,
+    class: OperationClass,
+) -> RestResult<AdmissionPermit> {
+    if let Some(permit) = server.admission.try_acquire(class) {
+        return Ok(permit);
+    }
+    let admission = Arc::clone(&server.admission);
+    let admitted = tokio::task::spawn_blocking(move || admission.acquire(class))
+        .await
+        .map_err(|e| RestError::Internal(format!("admission wait failed: {}", e)))?;
+    Ok(admitted?)
+}
+
 /// List records handler
 async fn list_handler<H: RestHandler + 'static>(
     State(server): State<ServerState<H>>,
@@ -92,21 +162,41 @@ async fn list_handler<H: RestHandler + 'static>(
 ) -> Result<Json<ListResponse<Value>>, RestError> {
     let ctx = extract_context(&server, &headers)?;
     let params = QueryParams::parse(&query)?;
+    if params.include_deleted && !ctx.is_service_role {
+        return Err(RestError::Auth(AuthError::Unauthorized));
+    }
+    server.replica_gate.check_read(params.max_staleness_ms)?;
+    let _permit = admit(&server, OperationClass::Read).await?;
 
-    let result = server.handler.list(&collection, params, &ctx)?;
-    Ok(Json(result))
+    let started = Instant::now();
+    let result = if params.aggregate.is_some() {
+        server.handler.aggregate(&collection, params, &ctx)
+    } else {
+        server.handler.list(&collection, params, &ctx)
+    };
+    server.log_operation(OperationType::Find, &collection, &ctx, started, &result);
+    Ok(Json(result?))
 }
 
 /// Get single record handler
 async fn get_handler<H: RestHandler + 'static>(
     State(server): State<ServerState<H>>,
     Path((collection, id)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
 ) -> Result<Json<SingleResponse<Value>>, RestError> {
     let ctx = extract_context(&server, &headers)?;
+    let max_staleness_ms = query
+        .get("max_staleness_ms")
+        .map(|value| parse_max_staleness(value))
+        .transpose()?;
+    server.replica_gate.check_read(max_staleness_ms)?;
+    let _permit = admit(&server, OperationClass::Read).await?;
 
-    let result = server.handler.get(&collection, &id, &ctx)?;
-    Ok(Json(result))
+    let started = Instant::now();
+    let result = server.handler.get(&collection, &id, &ctx);
+    server.log_operation(OperationType::Find, &collection, &ctx, started, &result);
+    Ok(Json(result?))
 }
 
 /// Insert record handler
@@ -117,9 +207,13 @@ async fn insert_handler<H: RestHandler + 'static>(
     Json(body): Json<Value>,
 ) -> Result<(StatusCode, Json<InsertResponse<Value>>), RestError> {
     let ctx = extract_context(&server, &headers)?;
+    server.replica_gate.check_write()?;
+    let _permit = admit(&server, OperationClass::Write).await?;
 
-    let result = server.handler.insert(&collection, body, &ctx)?;
-    Ok((StatusCode::CREATED, Json(result)))
+    let started = Instant::now();
+    let result = server.handler.insert(&collection, body, &ctx);
+    server.log_operation(OperationType::Insert, &collection, &ctx, started, &result);
+    Ok((StatusCode::CREATED, Json(result?)))
 }
 
 /// Update record handler
@@ -130,21 +224,75 @@ async fn update_handler<H: RestHandler + 'static>(
     Json(body): Json<Value>,
 ) -> Result<Json<UpdateResponse<Value>>, RestError> {
     let ctx = extract_context(&server, &headers)?;
+    server.replica_gate.check_write()?;
+    let _permit = admit(&server, OperationClass::Write).await?;
 
-    let result = server.handler.update(&collection, &id, body, &ctx)?;
-    Ok(Json(result))
+    let started = Instant::now();
+    let result = server.handler.update(&collection, &id, body, &ctx);
+    server.log_operation(OperationType::Update, &collection, &ctx, started, &result);
+    Ok(Json(result?))
 }
 
-/// Delete record handler
+/// Delete record handler; `purge=true` removes the record for good
 async fn delete_handler<H: RestHandler + 'static>(
     State(server): State<ServerState<H>>,
     Path((collection, id)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
     headers: HeaderMap,
 ) -> Result<Json<DeleteResponse>, RestError> {
     let ctx = extract_context(&server, &headers)?;
+    let purge = query
+        .get("purge")
+        .map(|value| parse_flag("purge", value))
+        .transpose()?
+        .unwrap_or(false);
+    server.replica_gate.check_write()?;
+    let _permit = admit(&server, OperationClass::Write).await?;
 
-    let result = server.handler.delete(&collection, &id, &ctx)?;
-    Ok(Json(result))
+    let started = Instant::now();
+    let result = if purge {
+        server.handler.purge(&collection, &id, &ctx)
+    } else {
+        server.handler.delete(&collection, &id, &ctx)
+    };
+    server.log_operation(OperationType::Delete, &collection, &ctx, started, &result);
+    Ok(Json(result?))
+}
+
+/// Body of a transaction batch
+#[derive(Debug, Deserialize)]
+struct TransactionBody {
+    operations: Vec<TransactionOperation>,
+}
+
+/// Transaction batch handler: every operation applies, or none does
+async fn transaction_handler<H: RestHandler + 'static>(
+    State(server): State<ServerState<H>>,
+    headers: HeaderMap,
+    Json(body): Json<TransactionBody>,
+) -> Result<Json<TransactionResponse<Value>>, RestError> {
+    let ctx = extract_context(&server, &headers)?;
+    if body.operations.is_empty() {
+        return Err(RestError::MissingParam("operations".to_string()));
+    }
+    server.replica_gate.check_write()?;
+    let _permit = admit(&server, OperationClass::Write).await?;
+
+    let mut collections: Vec<&str> = body.operations.iter().map(|op| op.collection()).collect();
+    collections.sort_unstable();
+    collections.dedup();
+    let collections = collections.join(",");
+
+    let started = Instant::now();
+    let result = server.handler.transaction(body.operations, &ctx);
+    server.log_operation(
+        OperationType::Transaction,
+        &collections,
+        &ctx,
+        started,
+        &result,
+    );
+    Ok(Json(result?))
 }
 
 #[cfg(test)]
@@ -152,10 +300,28 @@ mod tests {
     use super::super::handler::InMemoryRestHandler;
     use super::*;
     use crate::auth::rls::DefaultRlsEnforcer;
+    use crate::replication::ReplicaFreshness;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn test_jwt_config() -> JwtConfig {
+        JwtConfig {
+            service_role_secret: Some("service_secret_for_testing".to_string()),
+            ..JwtConfig::default()
+        }
+    }
 
     fn create_test_server() -> RestServer<InMemoryRestHandler<DefaultRlsEnforcer>> {
         let handler = InMemoryRestHandler::new(DefaultRlsEnforcer::new());
-        RestServer::new(handler, JwtConfig::default())
+        RestServer::new(handler, test_jwt_config())
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
     }
 
     #[test]
@@ -164,4 +330,319 @@ mod tests {
         let _router = server.router();
         // Server creates successfully
     }
+
+    #[test]
+    fn test_tenant_token_sets_tenant_claim() {
+        use crate::auth::crypto::PasswordPolicy;
+        use crate::auth::user::User;
+
+        let server = create_test_server();
+        let user = User::new(
+            "tenant@example.com".to_string(),
+            "password123",
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        let tenant_id = uuid::Uuid::new_v4();
+        let token = server
+            .jwt_manager
+            .generate_tenant_token(&user, tenant_id)
+            .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        let ctx = extract_context(&server, &headers).unwrap();
+        assert_eq!(ctx.user_id, Some(user.id));
+        assert_eq!(ctx.claims["tenant_id"], tenant_id.to_string());
+    }
+
+    fn replica_server(
+        current_as_of_ms: u64,
+    ) -> ServerState<InMemoryRestHandler<DefaultRlsEnforcer>> {
+        let gate = ReplicaGate::replica(
+            "db-primary:7000",
+            Arc::new(ReplicaFreshness::new(current_as_of_ms)),
+        );
+        Arc::new(create_test_server().with_replica_gate(gate))
+    }
+
+    fn service_headers() -> HeaderMap {
+        let jwt = JwtManager::new(test_jwt_config());
+        bearer(&jwt.generate_service_role_token().unwrap())
+    }
+
+    fn bounded(max_staleness_ms: u64) -> Query<HashMap<String, String>> {
+        let mut query = HashMap::new();
+        query.insert("max_staleness_ms".to_string(), max_staleness_ms.to_string());
+        Query(query)
+    }
+
+    #[tokio::test]
+    async fn test_include_deleted_requires_service_role() {
+        let server = Arc::new(create_test_server());
+        let mut query = HashMap::new();
+        query.insert("include_deleted".to_string(), "true".to_string());
+
+        let err = list_handler(
+            State(Arc::clone(&server)),
+            Path("users".to_string()),
+            Query(query.clone()),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, RestError::Auth(AuthError::Unauthorized)));
+
+        let list = list_handler(
+            State(server),
+            Path("users".to_string()),
+            Query(query),
+            service_headers(),
+        )
+        .await
+        .unwrap();
+        assert!(list.data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_saturated_writes_leave_reads_admitted() {
+        let admission = Arc::new(AdmissionController::new(AdmissionControlConfig {
+            max_concurrent_writes: 1,
+            max_queued_writes: 0,
+            ..AdmissionControlConfig::default()
+        }));
+        let server = Arc::new(create_test_server().with_admission(Arc::clone(&admission)));
+        let _held = admission.acquire(OperationClass::Write).unwrap();
+
+        let list = list_handler(
+            State(Arc::clone(&server)),
+            Path("users".to_string()),
+            Query(HashMap::new()),
+            service_headers(),
+        )
+        .await
+        .unwrap();
+        assert!(list.data.is_empty());
+
+        let err = insert_handler(
+            State(server),
+            Path("users".to_string()),
+            service_headers(),
+            Json(serde_json::json!({"name": "a"})),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, RestError::AdmissionRejected { .. }));
+        assert_eq!(admission.stats().writes.rejected, 1);
+        assert_eq!(admission.stats().reads.in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_batch_is_atomic() {
+        let server = Arc::new(create_test_server());
+        let batch = |last_id: &str| {
+            let body = serde_json::json!({"operations": [
+                {"op": "insert", "collection": "users", "data": {"id": "u1", "name": "a"}},
+                {"op": "update", "collection": "users", "id": "u1", "data": {"name": "b"}},
+                {"op": "delete", "collection": "users", "id": last_id}
+            ]});
+            Json(serde_json::from_value::<TransactionBody>(body).unwrap())
+        };
+
+        let err = transaction_handler(State(Arc::clone(&server)), service_headers(), batch("u2"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RestError::NotFound));
+        let list = list_handler(
+            State(Arc::clone(&server)),
+            Path("users".to_string()),
+            Query(HashMap::new()),
+            service_headers(),
+        )
+        .await
+        .unwrap();
+        assert!(list.data.is_empty());
+
+        let result = transaction_handler(State(server), service_headers(), batch("u1"))
+            .await
+            .unwrap();
+        assert_eq!(result.count, 3);
+        assert_eq!(result.data[1]["name"], "b");
+    }
+
+    #[tokio::test]
+    async fn test_replica_rejects_writes() {
+        let server = replica_server(1);
+        let err = insert_handler(
+            State(Arc::clone(&server)),
+            Path("users".to_string()),
+            service_headers(),
+            Json(serde_json::json!({"name": "a"})),
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(&err, RestError::NotPrimary(message) if message.contains("db-primary:7000"))
+        );
+
+        let err = delete_handler(
+            State(server),
+            Path(("users".to_string(), "1".to_string())),
+            Query(HashMap::new()),
+            service_headers(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, RestError::NotPrimary(_)));
+    }
+
+    #[tokio::test]
+    async fn test_replica_reads_within_staleness_bound() {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        let fresh = replica_server(now_ms);
+        let list = list_handler(
+            State(fresh),
+            Path("users".to_string()),
+            bounded(60_000),
+            service_headers(),
+        )
+        .await
+        .unwrap();
+        assert!(list.data.is_empty());
+
+        let stale = replica_server(now_ms - 120_000);
+        let err = list_handler(
+            State(Arc::clone(&stale)),
+            Path("users".to_string()),
+            bounded(60_000),
+            service_headers(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, RestError::ReplicaTooStale(_)));
+
+        let err = get_handler(
+            State(stale),
+            Path(("users".to_string(), "1".to_string())),
+            bounded(60_000),
+            service_headers(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, RestError::ReplicaTooStale(_)));
+    }
+
+    #[tokio::test]
+    async fn test_role_claims_enforce_rls() {
+        use crate::auth::crypto::PasswordPolicy;
+        use crate::auth::user::User;
+        use crate::auth::AuthError;
+        use crate::observability::OperationLogConfig;
+
+        let log = Arc::new(OperationLog::new(OperationLogConfig {
+            enabled: true,
+            ..OperationLogConfig::default()
+        }));
+        let server = Arc::new(create_test_server().with_operation_log(Arc::clone(&log)));
+        let list = |headers: HeaderMap| {
+            list_handler(
+                State(Arc::clone(&server)),
+                Path("posts".to_string()),
+                Query(HashMap::new()),
+                headers,
+            )
+        };
+        let user = |email: &str| {
+            User::new(email.to_string(), "password123", &PasswordPolicy::default()).unwrap()
+        };
+
+        // Each user inserts a row, owned by them
+        let (alice, bob) = (user("alice@example.com"), user("bob@example.com"));
+        for owner in [&alice, &bob] {
+            let token = server.jwt_manager.generate_access_token(owner).unwrap();
+            let (status, Json(inserted)) = insert_handler(
+                State(Arc::clone(&server)),
+                Path("posts".to_string()),
+                bearer(&token),
+                Json(serde_json::json!({"title": owner.email})),
+            )
+            .await
+            .unwrap();
+            assert_eq!(status, StatusCode::CREATED);
+            assert_eq!(inserted.count, 1);
+        }
+
+        // Anonymous callers are blocked from a table with the default policy
+        let anon_key = server.jwt_manager.generate_anon_token().unwrap();
+        for headers in [HeaderMap::new(), bearer(&anon_key)] {
+            let err = list(headers).await.unwrap_err();
+            assert!(matches!(
+                err,
+                RestError::Auth(AuthError::AuthenticationRequired)
+            ));
+        }
+
+        // An authenticated user sees only their own rows
+        let token = server.jwt_manager.generate_access_token(&alice).unwrap();
+        let rows = list(bearer(&token)).await.unwrap();
+        assert_eq!(rows.data.len(), 1);
+        assert_eq!(rows.data[0]["title"], "alice@example.com");
+
+        // The service role sees everything, and is logged as such
+        let rows = list(service_headers()).await.unwrap();
+        assert_eq!(rows.data.len(), 2);
+
+        let entries = log.entries();
+        let roles: Vec<(OperationType, Option<&str>)> = entries
+            .iter()
+            .map(|e| (e.operation, e.role.as_deref()))
+            .collect();
+        assert_eq!(
+            roles,
+            vec![
+                (OperationType::Insert, Some("authenticated")),
+                (OperationType::Insert, Some("authenticated")),
+                (OperationType::Find, Some("anon")),
+                (OperationType::Find, Some("anon")),
+                (OperationType::Find, Some("authenticated")),
+                (OperationType::Find, Some("service_role")),
+            ]
+        );
+        let service = entries.last().unwrap();
+        assert_eq!(service.user_id, None);
+        assert_eq!(service.collection.as_deref(), Some("posts"));
+        assert!(matches!(
+            &entries[2].result_status,
+            crate::observability::OperationResult::Error { code, .. } if code == "401"
+        ));
+    }
+
+    #[test]
+    fn test_service_role_needs_service_signed_token() {
+        let server = create_test_server();
+
+        // The retired `service_` API key prefix is just anonymous now
+        let mut headers = HeaderMap::new();
+        headers.insert("apikey", "service_test".parse().unwrap());
+        let ctx = extract_context(&server, &headers).unwrap();
+        assert!(!ctx.can_bypass_rls());
+
+        // A token claiming the service role under the user secret is refused
+        let forger = JwtManager::new(JwtConfig {
+            service_role_secret: Some(JwtConfig::default().secret),
+            ..JwtConfig::default()
+        });
+        let forged = forger.generate_service_role_token().unwrap();
+        assert!(extract_context(&server, &bearer(&forged)).is_err());
+
+        let ctx = extract_context(&server, &service_headers()).unwrap();
+        assert!(ctx.can_bypass_rls());
+    }
 }
@@ -188,19 +188,17 @@ fn build_context(
 ) -> Result<RequestContext, crate::auth::AuthError> {
     use crate::core::context::AuthContext;
 
-    // Check for service role (apikey header)
-    if let Some(apikey) = headers.get("apikey").and_then(|v| v.to_str().ok()) {
-        if apikey.starts_with("service_") {
-            return Ok(RequestContext::new(AuthContext::service_role()));
-        }
-    }
-
-    // Check for bearer token
+    // Check for bearer token; its role claim picks the context
     if let Some(auth) = headers.get("authorization").and_then(|v| v.to_str().ok()) {
         if let Some(token) = auth.strip_prefix("Bearer ") {
             let claims = jwt_manager.validate_token(token)?;
-            let user_id = JwtManager::get_user_id(&claims)?;
-            return Ok(RequestContext::new(AuthContext::authenticated(user_id)));
+            let rls = RlsContext::from_claims(&claims)?;
+            return Ok(RequestContext::new(AuthContext {
+                user_id: rls.user_id,
+                is_authenticated: rls.is_authenticated,
+                is_service_role: rls.is_service_role,
+                claims: rls.claims,
+            }));
         }
     }
 
@@ -210,10 +208,11 @@ fn build_context(
 
 /// Build RLS context from request context
 fn build_rls_context(ctx: &RequestContext) -> RlsContext {
-    if let Some(user_id) = ctx.auth.user_id {
-        RlsContext::authenticated(user_id)
-    } else {
-        RlsContext::anonymous()
+    RlsContext {
+        user_id: ctx.auth.user_id,
+        is_authenticated: ctx.auth.is_authenticated,
+        is_service_role: ctx.auth.is_service_role,
+        claims: ctx.auth.claims.clone(),
     }
 }
 
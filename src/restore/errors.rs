@@ -37,6 +37,8 @@ pub enum RestoreErrorCode {
     AeroRestoreCorruption,
     /// Invalid backup format
     AeroRestoreInvalidBackup,
+    /// Point-in-time target outside the range covered by snapshot and WAL
+    AeroRestoreTargetOutOfRange,
 }
 
 impl RestoreErrorCode {
@@ -47,6 +49,7 @@ impl RestoreErrorCode {
             RestoreErrorCode::AeroRestoreIo => "AERO_RESTORE_IO",
             RestoreErrorCode::AeroRestoreCorruption => "AERO_RESTORE_CORRUPTION",
             RestoreErrorCode::AeroRestoreInvalidBackup => "AERO_RESTORE_INVALID_BACKUP",
+            RestoreErrorCode::AeroRestoreTargetOutOfRange => "AERO_RESTORE_TARGET_OUT_OF_RANGE",
         }
     }
 
@@ -123,6 +126,11 @@ impl RestoreError {
         )
     }
 
+    /// Creates an error for a point-in-time target that can't be reached
+    pub fn target_out_of_range(message: impl Into<String>) -> Self {
+        Self::new(RestoreErrorCode::AeroRestoreTargetOutOfRange, message, None)
+    }
+
     /// Returns the error code
     pub fn code(&self) -> RestoreErrorCode {
         self.code
@@ -191,6 +199,10 @@ mod tests {
             RestoreErrorCode::AeroRestoreInvalidBackup.as_str(),
             "AERO_RESTORE_INVALID_BACKUP"
         );
+        assert_eq!(
+            RestoreErrorCode::AeroRestoreTargetOutOfRange.as_str(),
+            "AERO_RESTORE_TARGET_OUT_OF_RANGE"
+        );
     }
 
     #[test]
@@ -200,6 +212,7 @@ mod tests {
             RestoreErrorCode::AeroRestoreIo,
             RestoreErrorCode::AeroRestoreCorruption,
             RestoreErrorCode::AeroRestoreInvalidBackup,
+            RestoreErrorCode::AeroRestoreTargetOutOfRange,
         ];
 
         for code in codes {
@@ -32,15 +32,41 @@
 //! Restore does NOT replay WAL.
 //! Restore does NOT rebuild indexes.
 //! Restore prepares data for next `aerodb start`.
+//!
+//! # Restore Report
+//!
+//! Before the atomic replacement, every restore verifies the restored
+//! storage against the snapshot's recorded checksum and writes a
+//! `RestoreReport` to `restore_report.json` in the data directory. See
+//! `report`.
+//!
+//! # Point-in-Time Restore
+//!
+//! `restore_to_timestamp` and `restore_to_offset` are the one exception to
+//! "does NOT replay WAL": after reorganizing the backup they replay WAL
+//! records up to a target time or WAL offset into the restored storage,
+//! leave the restored WAL empty and mark a clean shutdown. See
+//! `point_in_time` for the rules.
 
 mod errors;
 mod extractor;
+mod point_in_time;
+mod report;
 mod restorer;
 mod validator;
 
 pub use errors::{RestoreError, RestoreErrorCode, RestoreResult, Severity};
+pub use point_in_time::{PointInTimeReport, WalOffset, WalPosition};
+pub use report::{RestoreReport, RESTORE_REPORT_FILE};
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
 
-use std::path::Path;
+use crate::backup::BackupManifest;
+
+use point_in_time::RestoreTarget;
+use report::{storage_checksum, verify_storage_checksum, write_report};
 
 use extractor::{
     cleanup_old_dir, cleanup_temp_dir, create_temp_restore_dir, extract_archive,
@@ -59,7 +85,7 @@ use validator::{
 /// # Usage
 ///
 /// ```ignore
-/// RestoreManager::restore_from_backup(
+/// let report = RestoreManager::restore_from_backup(
 ///     &data_dir,
 ///     &backup_path,
 /// )?;
@@ -79,7 +105,8 @@ impl RestoreManager {
     /// 7. Validate WAL
     /// 8. fsync temp directory
     /// 9. Reorganize files to data_dir structure
-    /// 10. Atomic directory replacement
+    /// 10. Verify the restored storage checksum and write the report
+    /// 11. Atomic directory replacement
     ///
     /// # Arguments
     ///
@@ -88,7 +115,8 @@ impl RestoreManager {
     ///
     /// # Returns
     ///
-    /// `Ok(())` on success.
+    /// The restore report, also written to `restore_report.json` in the
+    /// data directory.
     ///
     /// # Errors
     ///
@@ -96,7 +124,8 @@ impl RestoreManager {
     /// - AeroDB is running
     /// - Backup file not found
     /// - Invalid backup format
-    /// - Corruption detected
+    /// - Corruption detected, including a restored storage checksum that
+    ///   does not match the snapshot manifest
     /// - I/O error
     ///
     /// All errors are FATAL. Original data is preserved on failure.
@@ -111,7 +140,12 @@ impl RestoreManager {
     /// - Truncate WAL
     /// - Spawn threads
     /// - Perform async IO
-    pub fn restore_from_backup(data_dir: &Path, backup_path: &Path) -> Result<(), RestoreError> {
+    pub fn restore_from_backup(
+        data_dir: &Path,
+        backup_path: &Path,
+    ) -> Result<RestoreReport, RestoreError> {
+        let started_at = Utc::now();
+
         // Step 1: Validate preconditions
         validate_preconditions(data_dir, backup_path)?;
 
@@ -119,20 +153,107 @@ impl RestoreManager {
         let temp_dir = create_temp_restore_dir(data_dir)?;
 
         // All remaining operations must clean up temp_dir on failure
-        let result = Self::restore_inner(data_dir, backup_path, &temp_dir);
+        let result = Self::restore_inner(data_dir, backup_path, &temp_dir, started_at);
+
+        if result.is_err() {
+            Self::cleanup_after_failure(&temp_dir);
+        }
+
+        result
+    }
+
+    /// Restore from a backup, then replay WAL up to a point in time.
+    ///
+    /// Follows `restore_from_backup`, but before the atomic directory
+    /// replacement it replays WAL records from the backup and from
+    /// `wal_archive_dir` into the restored storage, up to and including
+    /// the last record with a commit timestamp ≤ `target_time`, and marks
+    /// a clean shutdown. The returned report, with `point_in_time` set, is
+    /// also written to `restore_report.json` in the data directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - Root data directory to restore to
+    /// * `backup_dir` - Directory holding `<backup_id>.tar`
+    /// * `backup_id` - Backup to restore
+    /// * `target_time` - Point in time to restore to
+    /// * `wal_archive_dir` - Archived WAL segments, if archiving is enabled
+    ///
+    /// # Errors
+    ///
+    /// Everything `restore_from_backup` returns, plus
+    /// `AERO_RESTORE_TARGET_OUT_OF_RANGE` if `target_time` predates the
+    /// snapshot or is after the last available WAL record. Original data
+    /// is preserved on failure.
+    pub fn restore_to_timestamp(
+        data_dir: &Path,
+        backup_dir: &Path,
+        backup_id: &str,
+        target_time: DateTime<Utc>,
+        wal_archive_dir: Option<&Path>,
+    ) -> RestoreResult<RestoreReport> {
+        Self::restore_to_target(
+            data_dir,
+            backup_dir,
+            backup_id,
+            RestoreTarget::Time(target_time),
+            wal_archive_dir,
+        )
+    }
+
+    /// Restore from a backup, then replay WAL up to a WAL offset.
+    ///
+    /// Like `restore_to_timestamp`, but replay stops before the record
+    /// starting at `target_offset` (as listed by `aerodb wal inspect`).
+    /// Records need no commit timestamps.
+    ///
+    /// # Errors
+    ///
+    /// Everything `restore_from_backup` returns, plus
+    /// `AERO_RESTORE_TARGET_OUT_OF_RANGE` if `target_offset` is not the
+    /// start of a record or the end of the WAL, lies within the backup's
+    /// WAL, or is after the last available WAL record. Original data is
+    /// preserved on failure.
+    pub fn restore_to_offset(
+        data_dir: &Path,
+        backup_dir: &Path,
+        backup_id: &str,
+        target_offset: WalOffset,
+        wal_archive_dir: Option<&Path>,
+    ) -> RestoreResult<RestoreReport> {
+        Self::restore_to_target(
+            data_dir,
+            backup_dir,
+            backup_id,
+            RestoreTarget::Offset(target_offset),
+            wal_archive_dir,
+        )
+    }
+
+    fn restore_to_target(
+        data_dir: &Path,
+        backup_dir: &Path,
+        backup_id: &str,
+        target: RestoreTarget,
+        wal_archive_dir: Option<&Path>,
+    ) -> RestoreResult<RestoreReport> {
+        let started_at = Utc::now();
+        let backup_path = backup_dir.join(format!("{}.tar", backup_id));
+        validate_preconditions(data_dir, &backup_path)?;
+
+        let temp_dir = create_temp_restore_dir(data_dir)?;
+
+        let result = Self::restore_to_target_inner(
+            data_dir,
+            &backup_path,
+            &temp_dir,
+            target,
+            wal_archive_dir,
+            started_at,
+        );
 
         if result.is_err() {
-            // Clean up temp directory
-            cleanup_temp_dir(&temp_dir);
-
-            // Clean up reorganized directory if it exists
-            if let Some(parent) = temp_dir.parent() {
-                let reorganized = parent.join(format!(
-                    "{}.reorganized",
-                    temp_dir.file_name().unwrap().to_string_lossy()
-                ));
-                cleanup_temp_dir(&reorganized);
-            }
+            Self::cleanup_after_failure(&temp_dir);
         }
 
         result
@@ -142,7 +263,81 @@ impl RestoreManager {
         data_dir: &Path,
         backup_path: &Path,
         temp_dir: &Path,
-    ) -> Result<(), RestoreError> {
+        started_at: DateTime<Utc>,
+    ) -> Result<RestoreReport, RestoreError> {
+        let (manifest, reorganized) = Self::prepare_restore_dir(backup_path, temp_dir)?;
+
+        let report = RestoreReport {
+            backup_id: manifest.backup_id,
+            snapshot_id: manifest.snapshot_id,
+            wal_records_applied: 0,
+            final_offset: point_in_time::restored_wal_end(&reorganized)?,
+            started_at,
+            completed_at: Utc::now(),
+            checksum: storage_checksum(&reorganized)?,
+            point_in_time: None,
+        };
+        write_report(&reorganized, &report)?;
+
+        // Step 10-13: Atomic directory replacement
+        atomic_replace(data_dir, &reorganized)?;
+
+        Ok(report)
+    }
+
+    fn restore_to_target_inner(
+        data_dir: &Path,
+        backup_path: &Path,
+        temp_dir: &Path,
+        target: RestoreTarget,
+        wal_archive_dir: Option<&Path>,
+        started_at: DateTime<Utc>,
+    ) -> RestoreResult<RestoreReport> {
+        let (manifest, reorganized) = Self::prepare_restore_dir(backup_path, temp_dir)?;
+
+        let snapshot_created_at = DateTime::parse_from_rfc3339(&manifest.created_at)
+            .map_err(|e| {
+                RestoreError::invalid_backup(format!(
+                    "Invalid created_at in backup manifest: {}: {}",
+                    manifest.created_at, e
+                ))
+            })?
+            .with_timezone(&Utc);
+
+        let (point_in_time, final_offset) = point_in_time::replay_to_target(
+            &reorganized,
+            &manifest.backup_id,
+            &manifest.snapshot_id,
+            snapshot_created_at,
+            target,
+            wal_archive_dir,
+        )?;
+
+        let report = RestoreReport {
+            backup_id: manifest.backup_id,
+            snapshot_id: manifest.snapshot_id,
+            wal_records_applied: point_in_time.records_replayed,
+            final_offset,
+            started_at,
+            completed_at: Utc::now(),
+            checksum: storage_checksum(&reorganized)?,
+            point_in_time: Some(point_in_time),
+        };
+        write_report(&reorganized, &report)?;
+
+        atomic_replace(data_dir, &reorganized)?;
+
+        Ok(report)
+    }
+
+    /// Steps 3-9: extract, validate and reorganize a backup, then verify
+    /// the restored storage against the snapshot's checksum.
+    ///
+    /// Returns the backup manifest and the reorganized directory.
+    fn prepare_restore_dir(
+        backup_path: &Path,
+        temp_dir: &Path,
+    ) -> RestoreResult<(BackupManifest, PathBuf)> {
         // Step 3: Extract backup.tar
         extract_archive(backup_path, temp_dir)?;
 
@@ -164,25 +359,55 @@ impl RestoreManager {
         // Step 9: Reorganize files to data_dir structure
         let reorganized = reorganize_extracted_files(temp_dir, &manifest.snapshot_id)?;
 
+        // Verify the restored storage before anything builds on it (SNAPSHOT.md §6)
+        verify_storage_checksum(&reorganized, &manifest.snapshot_id)?;
+
         // Clean up original temp directory (we have reorganized now)
         cleanup_temp_dir(temp_dir);
 
-        // Step 10-13: Atomic directory replacement
-        atomic_replace(data_dir, &reorganized)?;
+        Ok((manifest, reorganized))
+    }
 
-        Ok(())
+    /// Removes the temp and reorganized directories after a failed restore.
+    fn cleanup_after_failure(temp_dir: &Path) {
+        cleanup_temp_dir(temp_dir);
+
+        if let Some(parent) = temp_dir.parent() {
+            let reorganized = parent.join(format!(
+                "{}.reorganized",
+                temp_dir.file_name().unwrap().to_string_lossy()
+            ));
+            cleanup_temp_dir(&reorganized);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::snapshot::format_checksum;
+    use crate::wal::{WalPayload, WalRecord};
     use std::fs::{self, File};
     use std::io::Write;
     use tar::Builder;
     use tempfile::TempDir;
 
+    const STORAGE: &[u8] = b"test storage data";
+
+    fn storage_checksum() -> String {
+        format_checksum(crc32fast::hash(STORAGE))
+    }
+
+    fn wal_record() -> Vec<u8> {
+        let payload = WalPayload::new("users", "user_1", "users", "v1", b"{}".to_vec());
+        WalRecord::insert(1, payload).serialize()
+    }
+
     fn create_test_backup_archive(archive_path: &Path) {
+        create_backup_archive_with_checksum(archive_path, &storage_checksum());
+    }
+
+    fn create_backup_archive_with_checksum(archive_path: &Path, storage_checksum: &str) {
         let file = File::create(archive_path).unwrap();
         let mut builder = Builder::new(file);
 
@@ -193,11 +418,15 @@ mod tests {
         fs::create_dir_all(&snapshot_dir).unwrap();
 
         let mut f = File::create(snapshot_dir.join("manifest.json")).unwrap();
-        f.write_all(br#"{"snapshot_id":"20260204T163000Z"}"#)
-            .unwrap();
+        write!(
+            f,
+            r#"{{"snapshot_id":"20260204T163000Z","storage_checksum":"{}"}}"#,
+            storage_checksum
+        )
+        .unwrap();
 
         let mut f = File::create(snapshot_dir.join("storage.dat")).unwrap();
-        f.write_all(b"test storage data").unwrap();
+        f.write_all(STORAGE).unwrap();
 
         let schemas_dir = snapshot_dir.join("schemas");
         fs::create_dir_all(&schemas_dir).unwrap();
@@ -208,7 +437,7 @@ mod tests {
         let wal_dir = temp.path().join("wal");
         fs::create_dir_all(&wal_dir).unwrap();
         let mut f = File::create(wal_dir.join("wal.log")).unwrap();
-        f.write_all(b"wal data").unwrap();
+        f.write_all(&wal_record()).unwrap();
 
         // Create backup manifest
         let mut f = File::create(temp.path().join("backup_manifest.json")).unwrap();
@@ -232,7 +461,7 @@ mod tests {
         fs::create_dir_all(data_dir.join("wal")).unwrap();
         fs::create_dir_all(data_dir.join("metadata").join("schemas")).unwrap();
 
-        let mut f = File::create(data_dir.join("data").join("storage.dat")).unwrap();
+        let mut f = File::create(data_dir.join("data").join("documents.dat")).unwrap();
         f.write_all(b"old data").unwrap();
     }
 
@@ -252,12 +481,68 @@ mod tests {
         assert!(result.is_ok());
 
         // Verify restored structure
-        assert!(data_dir.join("data").join("storage.dat").exists());
+        assert!(data_dir.join("data").join("documents.dat").exists());
         assert!(data_dir.join("wal").exists());
         assert!(data_dir.join("metadata").join("schemas").exists());
         assert!(data_dir.join("snapshots").join("20260204T163000Z").exists());
     }
 
+    #[test]
+    fn test_restore_report_matches_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        create_existing_data_dir(&data_dir);
+        let backup_path = temp_dir.path().join("backup.tar");
+        create_test_backup_archive(&backup_path);
+
+        let before = Utc::now();
+        let report = RestoreManager::restore_from_backup(&data_dir, &backup_path).unwrap();
+
+        assert_eq!(report.backup_id, "20260204T163000Z");
+        assert_eq!(report.snapshot_id, "20260204T163000Z");
+        assert_eq!(report.wal_records_applied, 0);
+        assert_eq!(
+            report.final_offset,
+            Some(WalOffset {
+                segment: None,
+                offset: wal_record().len() as u64
+            })
+        );
+        assert_eq!(report.checksum, storage_checksum());
+        assert!(before <= report.started_at);
+        assert!(report.started_at <= report.completed_at);
+        assert!(report.point_in_time.is_none());
+
+        let saved: RestoreReport =
+            serde_json::from_slice(&fs::read(data_dir.join(RESTORE_REPORT_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(saved, report);
+    }
+
+    #[test]
+    fn test_restore_checksum_mismatch_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        create_existing_data_dir(&data_dir);
+
+        let backup_path = temp_dir.path().join("backup.tar");
+        create_backup_archive_with_checksum(&backup_path, "crc32:deadbeef");
+        let err = RestoreManager::restore_from_backup(&data_dir, &backup_path).unwrap_err();
+        assert_eq!(err.code(), RestoreErrorCode::AeroRestoreCorruption);
+        assert!(err.message().contains("does not match snapshot"));
+
+        create_backup_archive_with_checksum(&backup_path, "unknown");
+        let err = RestoreManager::restore_from_backup(&data_dir, &backup_path).unwrap_err();
+        assert_eq!(err.code(), RestoreErrorCode::AeroRestoreCorruption);
+
+        // Original data is untouched and no report is written
+        assert_eq!(
+            fs::read(data_dir.join("data").join("documents.dat")).unwrap(),
+            b"old data"
+        );
+        assert!(!data_dir.join(RESTORE_REPORT_FILE).exists());
+    }
+
     #[test]
     fn test_restore_invalid_backup_rejected() {
         let temp_dir = TempDir::new().unwrap();
@@ -276,7 +561,7 @@ mod tests {
         assert!(result.is_err());
 
         // Original data should be preserved
-        assert!(data_dir.join("data").join("storage.dat").exists());
+        assert!(data_dir.join("data").join("documents.dat").exists());
     }
 
     #[test]
@@ -357,7 +642,7 @@ mod tests {
         create_existing_data_dir(&data_dir);
 
         // Make a backup of original content
-        let original_content = fs::read(data_dir.join("data").join("storage.dat")).unwrap();
+        let original_content = fs::read(data_dir.join("data").join("documents.dat")).unwrap();
 
         // Create invalid backup
         let backup_path = temp_dir.path().join("backup.tar");
@@ -368,7 +653,7 @@ mod tests {
         let _ = RestoreManager::restore_from_backup(&data_dir, &backup_path);
 
         // Original data should be preserved
-        let current_content = fs::read(data_dir.join("data").join("storage.dat")).unwrap();
+        let current_content = fs::read(data_dir.join("data").join("documents.dat")).unwrap();
         assert_eq!(original_content, current_content);
     }
 }
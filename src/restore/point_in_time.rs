@@ -0,0 +1,737 @@
+//! Point-in-time restore
+//!
+//! Restores a backup's snapshot and then replays WAL records up to a target
+//! time or WAL offset. Records come from the backup's WAL and, for anything
+//! written after the backup, from the WAL archive (`wal_archive_dir`).
+//!
+//! Rules:
+//! - Segments are read in index order, starting at the backup's first
+//!   segment. An archived copy of a segment supersedes the backup's copy,
+//!   which may have been taken while the segment was still being written
+//! - Replay stops before the first record whose commit timestamp is after
+//!   the target time, or before the record starting at the target offset,
+//!   so the replayed records are always a prefix of the WAL
+//! - A target offset must be the start of a record (as listed by
+//!   `aerodb wal inspect`) or the end of the WAL
+//! - The target must not precede the snapshot, which already contains
+//!   every record in the backup's WAL
+//! - The target must not be after the last available record
+//! - Replayed records are written to storage and the restored WAL is left
+//!   empty, so the next start cannot replay past the target. The restore
+//!   directory is then marked as cleanly shut down
+//! - The restored storage is verified against the snapshot's checksum
+//!   before any record is replayed
+//!
+//! Everything happens in the reorganized restore directory, before the
+//! atomic replacement of the data directory.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::recovery::RecoveryManager;
+use crate::storage::StorageWriter;
+use crate::wal::{
+    detect_layout, list_segments, parse_segment_file_name, read_epoch, write_epoch, WalError,
+    WalLayout, WalReader, WalRecord, WAL_EPOCH_FILE,
+};
+
+use super::errors::{RestoreError, RestoreResult};
+use super::restorer::fsync_dir;
+
+/// Position of a WAL record: the segment it was read from, its byte
+/// offset there and its sequence number within that checkpoint epoch (its
+/// LSN).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalPosition {
+    /// Segment file name (e.g. `0000003.log`, or `wal.log`)
+    pub segment: String,
+    /// Byte offset of the record within its segment
+    #[serde(default)]
+    pub offset: u64,
+    /// Sequence number of the record
+    pub sequence_number: u64,
+    /// Commit timestamp of the record, if it was written with one
+    pub commit_time: Option<DateTime<Utc>>,
+}
+
+impl WalPosition {
+    /// The record's offset, in the form `aerodb wal inspect` lists it.
+    fn wal_offset(&self) -> WalOffset {
+        WalOffset {
+            segment: parse_segment_file_name(&self.segment),
+            offset: self.offset,
+        }
+    }
+
+    fn require_commit_time(&self) -> RestoreResult<DateTime<Utc>> {
+        self.commit_time.ok_or_else(|| {
+            RestoreError::failed(format!(
+                "WAL record {} in {} has no commit timestamp; point-in-time restore \
+                 needs a WAL written with commit timestamps",
+                self.sequence_number, self.segment
+            ))
+        })
+    }
+}
+
+/// A byte offset in the WAL, as listed by `aerodb wal inspect`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalOffset {
+    /// Segment index (`None` for a legacy `wal.log`)
+    pub segment: Option<u64>,
+    /// Byte offset within the segment
+    pub offset: u64,
+}
+
+impl WalOffset {
+    /// Sort key; segments are read in index order.
+    fn key(&self) -> (u64, u64) {
+        (self.segment.unwrap_or(0), self.offset)
+    }
+}
+
+impl fmt::Display for WalOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.segment {
+            Some(segment) => write!(f, "segment {} offset {}", segment, self.offset),
+            None => write!(f, "wal.log offset {}", self.offset),
+        }
+    }
+}
+
+/// Where a point-in-time restore stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RestoreTarget {
+    /// After the last record committed at or before this time
+    Time(DateTime<Utc>),
+    /// Before the record starting at this offset
+    Offset(WalOffset),
+}
+
+impl RestoreTarget {
+    /// Whether replay stops before the record at `position`.
+    fn stops_before(&self, position: &WalPosition) -> RestoreResult<bool> {
+        match self {
+            RestoreTarget::Time(target_time) => Ok(position.require_commit_time()? > *target_time),
+            RestoreTarget::Offset(target) => {
+                let at = position.wal_offset();
+                if at.key() > target.key() {
+                    return Err(not_a_record_start(target, &at));
+                }
+                Ok(at == *target)
+            }
+        }
+    }
+}
+
+/// Target and stopping point of a point-in-time restore, part of its
+/// [`RestoreReport`](super::RestoreReport).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PointInTimeReport {
+    /// Backup the snapshot was taken from
+    pub backup_id: String,
+    /// Snapshot that was restored
+    pub snapshot_id: String,
+    /// Requested target time, for a restore to a time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_time: Option<DateTime<Utc>>,
+    /// Requested target offset, for a restore to a WAL offset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_offset: Option<WalOffset>,
+    /// Earliest reachable target: the snapshot, or the last record in the
+    /// backup's WAL if that is later
+    pub snapshot_time: DateTime<Utc>,
+    /// Number of WAL records replayed on top of the snapshot
+    pub records_replayed: u64,
+    /// Last record replayed, `None` if there was none
+    pub stopped_at: Option<WalPosition>,
+    /// First record not replayed, `None` if the WAL ended first
+    pub next_record: Option<WalPosition>,
+}
+
+/// Replays the restored WAL up to `target` and marks a clean shutdown.
+///
+/// `restore_dir` is the reorganized restore directory (data_dir layout),
+/// whose `wal/` holds the backup's WAL. Returns the report and the offset
+/// just past the last record replayed.
+pub(crate) fn replay_to_target(
+    restore_dir: &Path,
+    backup_id: &str,
+    snapshot_id: &str,
+    snapshot_created_at: DateTime<Utc>,
+    target: RestoreTarget,
+    archive_dir: Option<&Path>,
+) -> RestoreResult<(PointInTimeReport, Option<WalOffset>)> {
+    let wal_dir = restore_dir.join("wal");
+    let (backup_files, replay_files) = collect_wal_files(&wal_dir, archive_dir)?;
+
+    // The snapshot includes every record in the backup's WAL
+    let (backup_last_time, backup_end) = match wal_end(&backup_files)? {
+        Some((last, end)) => (last.commit_time, Some(end)),
+        None => (None, None),
+    };
+    let snapshot_time =
+        backup_last_time.map_or(snapshot_created_at, |last| last.max(snapshot_created_at));
+    match target {
+        RestoreTarget::Time(target_time) if target_time < snapshot_time => {
+            return Err(RestoreError::target_out_of_range(format!(
+                "Target time {} predates snapshot {} of backup {} (taken at {})",
+                target_time.to_rfc3339(),
+                snapshot_id,
+                backup_id,
+                snapshot_time.to_rfc3339()
+            )));
+        }
+        RestoreTarget::Offset(target_offset) => {
+            check_offset_layout(&target_offset, &replay_files)?;
+            if let Some(end) = backup_end.filter(|end| target_offset.key() < end.key()) {
+                return Err(RestoreError::target_out_of_range(format!(
+                    "Target {} predates snapshot {} of backup {}, whose WAL ends at {}",
+                    target_offset, snapshot_id, backup_id, end
+                )));
+            }
+        }
+        RestoreTarget::Time(_) => {}
+    }
+
+    let mut storage = StorageWriter::open(restore_dir)
+        .map_err(|e| RestoreError::failed(format!("Failed to open restored storage: {}", e)))?;
+
+    let mut records_replayed = 0;
+    let mut epoch = read_epoch(&wal_dir).map_err(wal_error)?;
+    let mut stopped_at = None;
+    let mut next_record = None;
+    let mut replayed_end = None;
+
+    if !replay_files.is_empty() {
+        let mut reader = WalReader::open_history(replay_files).map_err(wal_error)?;
+        while let Some(record) = reader.read_next().map_err(wal_error)? {
+            let position = position_of(&record, &reader);
+            // The end of a segment is also the start of the next one's first record
+            let at_segment_end =
+                matches!(target, RestoreTarget::Offset(t) if replayed_end == Some(t));
+            if at_segment_end || target.stops_before(&position)? {
+                next_record = Some(position);
+                break;
+            }
+            storage.apply_wal_record(&record).map_err(|e| {
+                RestoreError::failed(format!(
+                    "Failed to replay WAL record {} from {}: {}",
+                    position.sequence_number, position.segment, e
+                ))
+            })?;
+            records_replayed += 1;
+            epoch = epoch.max(record.epoch);
+            replayed_end = Some(WalOffset {
+                segment: position.wal_offset().segment,
+                offset: reader.current_offset(),
+            });
+            stopped_at = Some(position);
+        }
+    }
+
+    if next_record.is_none() {
+        match target {
+            RestoreTarget::Time(target_time) => {
+                let available_until = stopped_at
+                    .as_ref()
+                    .and_then(|p| p.commit_time)
+                    .map_or(snapshot_time, |t| t.max(snapshot_time));
+                if target_time > available_until {
+                    return Err(RestoreError::target_out_of_range(format!(
+                        "Target time {} is after the last available WAL record ({})",
+                        target_time.to_rfc3339(),
+                        available_until.to_rfc3339()
+                    )));
+                }
+            }
+            RestoreTarget::Offset(target_offset) => {
+                let end = replayed_end.or(backup_end);
+                match end {
+                    Some(end) if end == target_offset => {}
+                    None if target_offset.offset == 0 => {}
+                    Some(end) if target_offset.key() < end.key() => {
+                        return Err(not_a_record_start(&target_offset, &end));
+                    }
+                    _ => {
+                        return Err(RestoreError::target_out_of_range(format!(
+                            "Target {} is after the last available WAL record (the WAL ends at {})",
+                            target_offset,
+                            end.map_or_else(|| "its start".to_string(), |end| end.to_string())
+                        )));
+                    }
+                }
+            }
+        }
+    }
+
+    clear_wal_dir(&wal_dir)?;
+    if epoch > 0 {
+        write_epoch(&wal_dir, epoch).map_err(wal_error)?;
+    }
+    RecoveryManager::new(restore_dir)
+        .mark_clean_shutdown()
+        .map_err(|e| RestoreError::failed(e.to_string()))?;
+
+    let (target_time, target_offset) = match target {
+        RestoreTarget::Time(target_time) => (Some(target_time), None),
+        RestoreTarget::Offset(target_offset) => (None, Some(target_offset)),
+    };
+    let report = PointInTimeReport {
+        backup_id: backup_id.to_string(),
+        snapshot_id: snapshot_id.to_string(),
+        target_time,
+        target_offset,
+        snapshot_time,
+        records_replayed,
+        stopped_at,
+        next_record,
+    };
+
+    Ok((report, replayed_end))
+}
+
+/// Offset just past the last record of the WAL in `restore_dir`, `None`
+/// if it has no records.
+///
+/// # Errors
+///
+/// `AERO_RESTORE_CORRUPTION` if the WAL cannot be read to its end.
+pub(crate) fn restored_wal_end(restore_dir: &Path) -> RestoreResult<Option<WalOffset>> {
+    let (backup_files, _) = collect_wal_files(&restore_dir.join("wal"), None)?;
+    Ok(wal_end(&backup_files)?.map(|(_, end)| end))
+}
+
+/// Returns the backup's WAL files and the files to replay.
+///
+/// The replay list starts with the backup's first segment and continues
+/// with every later archived segment; archived copies replace the backup's
+/// copy of the same segment. A legacy `wal.log` has no segment index to
+/// line up with the archive, so it is replayed on its own.
+fn collect_wal_files(
+    wal_dir: &Path,
+    archive_dir: Option<&Path>,
+) -> RestoreResult<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let segments = match detect_layout(wal_dir).map_err(wal_error)? {
+        WalLayout::Empty => return Ok((Vec::new(), Vec::new())),
+        WalLayout::Legacy(path) => return Ok((vec![path.clone()], vec![path])),
+        WalLayout::Segmented(segments) => segments,
+    };
+
+    let first_index = segments[0].index;
+    let backup_files = segments.iter().map(|s| s.path.clone()).collect();
+
+    let mut by_index: BTreeMap<u64, PathBuf> =
+        segments.into_iter().map(|s| (s.index, s.path)).collect();
+    if let Some(archive_dir) = archive_dir {
+        for segment in list_segments(archive_dir).map_err(wal_error)? {
+            if segment.index >= first_index {
+                by_index.insert(segment.index, segment.path);
+            }
+        }
+    }
+
+    Ok((backup_files, by_index.into_values().collect()))
+}
+
+/// Returns the last record in `files` and the offset just past it.
+fn wal_end(files: &[PathBuf]) -> RestoreResult<Option<(WalPosition, WalOffset)>> {
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let mut reader = WalReader::open_history(files.to_vec()).map_err(wal_error)?;
+    let mut last = None;
+    while let Some(record) = reader.read_next().map_err(wal_error)? {
+        let position = position_of(&record, &reader);
+        let end = WalOffset {
+            segment: position.wal_offset().segment,
+            offset: reader.current_offset(),
+        };
+        last = Some((position, end));
+    }
+    Ok(last)
+}
+
+fn position_of(record: &WalRecord, reader: &WalReader) -> WalPosition {
+    let segment = reader
+        .path()
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    WalPosition {
+        segment,
+        offset: reader.last_record_offset(),
+        sequence_number: record.sequence_number,
+        commit_time: record
+            .commit_timestamp_ms
+            .and_then(|ms| DateTime::from_timestamp_millis(ms as i64)),
+    }
+}
+
+/// A target offset names a segment iff the WAL is segmented.
+fn check_offset_layout(target: &WalOffset, files: &[PathBuf]) -> RestoreResult<()> {
+    let segmented = files
+        .first()
+        .and_then(|path| path.file_name())
+        .and_then(|name| name.to_str())
+        .and_then(parse_segment_file_name)
+        .is_some();
+    match (segmented, target.segment) {
+        (true, None) => Err(RestoreError::target_out_of_range(format!(
+            "Target {} names no segment, but the WAL is segmented",
+            target
+        ))),
+        (false, Some(_)) if !files.is_empty() => Err(RestoreError::target_out_of_range(format!(
+            "Target {} names a segment, but the WAL is a single wal.log",
+            target
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn not_a_record_start(target: &WalOffset, next: &WalOffset) -> RestoreError {
+    RestoreError::target_out_of_range(format!(
+        "Target {} is not the start of a WAL record (the next record boundary is {})",
+        target, next
+    ))
+}
+
+/// Removes the replayed WAL from the restore directory, keeping its epoch.
+fn clear_wal_dir(wal_dir: &Path) -> RestoreResult<()> {
+    let entries = fs::read_dir(wal_dir).map_err(|e| RestoreError::io_error_at_path(wal_dir, e))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| RestoreError::io_error_at_path(wal_dir, e))?
+            .path();
+        if path.is_file() && !path.ends_with(WAL_EPOCH_FILE) {
+            fs::remove_file(&path).map_err(|e| RestoreError::io_error_at_path(&path, e))?;
+        }
+    }
+    fsync_dir(wal_dir)
+}
+
+fn wal_error(e: WalError) -> RestoreError {
+    RestoreError::corruption(format!("Invalid WAL in restored backup: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::restore::{RestoreErrorCode, RestoreManager, RestoreReport, RESTORE_REPORT_FILE};
+    use crate::snapshot::{compute_file_checksum, format_checksum};
+    use crate::storage::StorageReader;
+    use crate::wal::{segment_file_name, WalPayload};
+    use std::fs::File;
+    use tar::Builder;
+    use tempfile::TempDir;
+
+    /// 2026-02-07T13:45:00Z, the backup's `created_at`
+    const SNAPSHOT_MS: u64 = 1_770_471_900_000;
+    const BACKUP_ID: &str = "backup_20260207T134500Z";
+
+    fn at(ms: u64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(ms as i64).unwrap()
+    }
+
+    fn record(sequence_number: u64, doc_id: &str, timestamp_ms: u64) -> WalRecord {
+        let payload = WalPayload::new("users", doc_id, "users", "v1", b"{}".to_vec());
+        WalRecord::insert(sequence_number, payload).with_commit_timestamp(timestamp_ms)
+    }
+
+    /// Every fixture record has the same encoded length.
+    fn record_len() -> u64 {
+        record(1, "a", SNAPSHOT_MS).serialize().len() as u64
+    }
+
+    fn write_segment(dir: &Path, index: u64, records: &[WalRecord]) {
+        fs::create_dir_all(dir).unwrap();
+        let bytes: Vec<u8> = records.iter().flat_map(|r| r.serialize()).collect();
+        fs::write(dir.join(segment_file_name(index)), bytes).unwrap();
+    }
+
+    /// Backup WAL: segment 1 with two records before the snapshot.
+    ///
+    /// Archive: the completed segment 1 (two more records after the
+    /// snapshot) and segment 2, written after a checkpoint reset the
+    /// sequence to 1.
+    struct Fixture {
+        temp: TempDir,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let temp = TempDir::new().unwrap();
+            let before = [
+                record(1, "a", SNAPSHOT_MS - 2_000),
+                record(2, "b", SNAPSHOT_MS - 1_000),
+            ];
+            let after = [
+                record(3, "c", SNAPSHOT_MS + 1_000),
+                record(4, "d", SNAPSHOT_MS + 2_000),
+            ];
+
+            let staging = temp.path().join("staging");
+            fs::create_dir_all(staging.join("snapshot")).unwrap();
+            fs::write(
+                staging.join("snapshot").join("manifest.json"),
+                // CRC32 of the empty storage.dat
+                br#"{"snapshot_id":"20260207T134500Z","storage_checksum":"crc32:00000000"}"#,
+            )
+            .unwrap();
+            fs::write(staging.join("snapshot").join("storage.dat"), b"").unwrap();
+            write_segment(&staging.join("wal"), 1, &before);
+            fs::write(
+                staging.join("backup_manifest.json"),
+                format!(
+                    r#"{{"backup_id":"{}","snapshot_id":"20260207T134500Z","created_at":"2026-02-07T13:45:00Z","wal_present":true,"format_version":1}}"#,
+                    BACKUP_ID
+                ),
+            )
+            .unwrap();
+
+            let backups = temp.path().join("backups");
+            fs::create_dir_all(&backups).unwrap();
+            let archive_file = File::create(backups.join(format!("{}.tar", BACKUP_ID))).unwrap();
+            let mut builder = Builder::new(archive_file);
+            builder.append_dir_all(".", &staging).unwrap();
+            builder.finish().unwrap();
+
+            let archive = temp.path().join("wal_archive");
+            let complete: Vec<WalRecord> = before.iter().chain(&after).cloned().collect();
+            write_segment(&archive, 1, &complete);
+            write_segment(&archive, 2, &[record(1, "e", SNAPSHOT_MS + 3_000)]);
+
+            let data_dir = temp.path().join("data");
+            fs::create_dir_all(data_dir.join("wal")).unwrap();
+            fs::write(data_dir.join("current"), b"live data").unwrap();
+
+            Self { temp }
+        }
+
+        fn data_dir(&self) -> PathBuf {
+            self.temp.path().join("data")
+        }
+
+        fn restore(&self, target_ms: u64) -> RestoreResult<PointInTimeReport> {
+            self.restore_report(target_ms)
+                .map(|report| report.point_in_time.unwrap())
+        }
+
+        fn restore_report(&self, target_ms: u64) -> RestoreResult<RestoreReport> {
+            let archive = self.temp.path().join("wal_archive");
+            RestoreManager::restore_to_timestamp(
+                &self.data_dir(),
+                &self.temp.path().join("backups"),
+                BACKUP_ID,
+                at(target_ms),
+                Some(&archive),
+            )
+        }
+
+        fn restore_offset(&self, segment: u64, offset: u64) -> RestoreResult<PointInTimeReport> {
+            let archive = self.temp.path().join("wal_archive");
+            let report = RestoreManager::restore_to_offset(
+                &self.data_dir(),
+                &self.temp.path().join("backups"),
+                BACKUP_ID,
+                WalOffset {
+                    segment: Some(segment),
+                    offset,
+                },
+                Some(&archive),
+            )?;
+            Ok(report.point_in_time.unwrap())
+        }
+
+        fn restored_documents(&self) -> Vec<String> {
+            let mut reader = StorageReader::open_from_data_dir(&self.data_dir()).unwrap();
+            let mut ids = Vec::new();
+            while let Some(record) = reader.read_next().unwrap() {
+                ids.push(record.document_id);
+            }
+            ids
+        }
+    }
+
+    #[test]
+    fn test_target_between_records_stops_at_earlier_record() {
+        let fixture = Fixture::new();
+        let restore_report = fixture.restore_report(SNAPSHOT_MS + 1_500).unwrap();
+        let report = restore_report.point_in_time.clone().unwrap();
+
+        assert_eq!(report.records_replayed, 3);
+        assert_eq!(
+            report.stopped_at,
+            Some(WalPosition {
+                segment: "0000001.log".to_string(),
+                offset: record_len() * 2,
+                sequence_number: 3,
+                commit_time: Some(at(SNAPSHOT_MS + 1_000)),
+            })
+        );
+        assert_eq!(report.next_record.as_ref().unwrap().sequence_number, 4);
+        assert_eq!(report.snapshot_time, at(SNAPSHOT_MS));
+
+        let documents = fixture.restored_documents();
+        assert!(documents.iter().any(|id| id.ends_with('c')));
+        assert!(!documents.iter().any(|id| id.ends_with('d')));
+
+        assert_eq!(restore_report.backup_id, BACKUP_ID);
+        assert_eq!(restore_report.snapshot_id, "20260207T134500Z");
+        assert_eq!(restore_report.wal_records_applied, 3);
+        assert_eq!(
+            restore_report.final_offset,
+            Some(WalOffset {
+                segment: Some(1),
+                offset: record_len() * 3
+            })
+        );
+        let storage = fixture.data_dir().join("data").join("documents.dat");
+        assert_eq!(
+            restore_report.checksum,
+            format_checksum(compute_file_checksum(&storage).unwrap())
+        );
+        assert_ne!(restore_report.checksum, "crc32:00000000");
+
+        // The report is in the data dir and the WAL can't replay past the target
+        let saved: RestoreReport = serde_json::from_slice(
+            &fs::read(fixture.data_dir().join(RESTORE_REPORT_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(saved, restore_report);
+        assert_eq!(
+            fs::read_dir(fixture.data_dir().join("wal"))
+                .unwrap()
+                .count(),
+            0
+        );
+        assert!(!fixture.data_dir().join("current").exists());
+    }
+
+    #[test]
+    fn test_target_equal_to_record_includes_it() {
+        let fixture = Fixture::new();
+        let report = fixture.restore(SNAPSHOT_MS + 2_000).unwrap();
+
+        assert_eq!(report.records_replayed, 4);
+        assert_eq!(report.stopped_at.unwrap().sequence_number, 4);
+        assert_eq!(report.next_record.unwrap().segment, "0000002.log");
+    }
+
+    #[test]
+    fn test_replay_continues_across_checkpoint_epochs() {
+        let fixture = Fixture::new();
+        let report = fixture.restore(SNAPSHOT_MS + 3_000).unwrap();
+
+        assert_eq!(report.records_replayed, 5);
+        let stop = report.stopped_at.unwrap();
+        assert_eq!(
+            (stop.segment.as_str(), stop.sequence_number),
+            ("0000002.log", 1)
+        );
+        assert!(report.next_record.is_none());
+    }
+
+    #[test]
+    fn test_target_at_snapshot_replays_only_backup_wal() {
+        let fixture = Fixture::new();
+        let report = fixture.restore(SNAPSHOT_MS).unwrap();
+
+        assert_eq!(report.records_replayed, 2);
+        assert_eq!(report.stopped_at.unwrap().sequence_number, 2);
+    }
+
+    #[test]
+    fn test_target_before_snapshot_is_refused() {
+        let fixture = Fixture::new();
+        let err = fixture.restore(SNAPSHOT_MS - 1).unwrap_err();
+
+        assert_eq!(err.code(), RestoreErrorCode::AeroRestoreTargetOutOfRange);
+        assert!(err.message().contains("predates snapshot"));
+        assert!(fixture.data_dir().join("current").exists());
+    }
+
+    #[test]
+    fn test_target_after_available_wal_is_refused() {
+        let fixture = Fixture::new();
+        let err = fixture.restore(SNAPSHOT_MS + 3_001).unwrap_err();
+
+        assert_eq!(err.code(), RestoreErrorCode::AeroRestoreTargetOutOfRange);
+        assert!(err
+            .message()
+            .contains("after the last available WAL record"));
+        assert!(fixture.data_dir().join("current").exists());
+        assert!(!fixture.data_dir().join(RESTORE_REPORT_FILE).exists());
+    }
+
+    #[test]
+    fn test_offset_target_excludes_later_writes() {
+        let fixture = Fixture::new();
+        let target = record_len() * 3;
+        let report = fixture.restore_offset(1, target).unwrap();
+
+        assert_eq!(report.records_replayed, 3);
+        assert_eq!(report.stopped_at.unwrap().sequence_number, 3);
+        let next = report.next_record.unwrap();
+        assert_eq!((next.sequence_number, next.offset), (4, target));
+        assert_eq!(
+            report.target_offset,
+            Some(WalOffset {
+                segment: Some(1),
+                offset: target
+            })
+        );
+        assert_eq!(report.target_time, None);
+
+        let documents = fixture.restored_documents();
+        assert!(documents.iter().any(|id| id.ends_with('c')));
+        assert!(!documents
+            .iter()
+            .any(|id| id.ends_with('d') || id.ends_with('e')));
+        assert!(fixture.data_dir().join("clean_shutdown").exists());
+    }
+
+    #[test]
+    fn test_offset_target_at_segment_end_or_wal_end() {
+        let fixture = Fixture::new();
+        let report = fixture.restore_offset(1, record_len() * 4).unwrap();
+        assert_eq!(report.records_replayed, 4);
+        assert_eq!(report.next_record.unwrap().segment, "0000002.log");
+
+        let fixture = Fixture::new();
+        let report = fixture.restore_offset(2, record_len()).unwrap();
+        assert_eq!(report.records_replayed, 5);
+        assert!(report.next_record.is_none());
+    }
+
+    #[test]
+    fn test_offset_target_inside_record_is_refused() {
+        let fixture = Fixture::new();
+        let err = fixture.restore_offset(1, record_len() * 3 + 1).unwrap_err();
+
+        assert_eq!(err.code(), RestoreErrorCode::AeroRestoreTargetOutOfRange);
+        assert!(err.message().contains("not the start of a WAL record"));
+        assert!(fixture.data_dir().join("current").exists());
+    }
+
+    #[test]
+    fn test_offset_target_outside_available_wal_is_refused() {
+        let fixture = Fixture::new();
+        let err = fixture.restore_offset(1, record_len()).unwrap_err();
+        assert_eq!(err.code(), RestoreErrorCode::AeroRestoreTargetOutOfRange);
+        assert!(err.message().contains("predates snapshot"));
+
+        let err = fixture.restore_offset(3, 0).unwrap_err();
+        assert!(err
+            .message()
+            .contains("after the last available WAL record"));
+        assert!(fixture.data_dir().join("current").exists());
+    }
+}
@@ -0,0 +1,129 @@
+//! Restore report
+//!
+//! Every successful restore writes a [`RestoreReport`] to
+//! `restore_report.json` in the restored data directory, recording which
+//! snapshot was restored, how much WAL was applied on top of it, when the
+//! restore ran and the checksum of the restored storage.
+//!
+//! Per SNAPSHOT.md §6, checksums are verified during restore: before any WAL
+//! is replayed, the restored storage must match the `storage_checksum`
+//! recorded in the snapshot manifest. A mismatch fails the restore with
+//! `AERO_RESTORE_CORRUPTION` and leaves the original data directory intact.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::snapshot::{compute_file_checksum, format_checksum, parse_checksum};
+
+use super::errors::{RestoreError, RestoreResult};
+use super::point_in_time::{PointInTimeReport, WalOffset};
+use super::restorer::fsync_dir;
+
+/// File name of the report written into the data directory.
+pub const RESTORE_REPORT_FILE: &str = "restore_report.json";
+
+/// Outcome of a restore, also written to [`RESTORE_REPORT_FILE`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestoreReport {
+    /// Backup the snapshot was taken from
+    pub backup_id: String,
+    /// Snapshot that was restored
+    pub snapshot_id: String,
+    /// WAL records replayed into the restored storage; 0 for a full
+    /// restore, which leaves the backup's WAL for startup recovery
+    pub wal_records_applied: u64,
+    /// Offset just past the last WAL record the restored data directory
+    /// covers: the last record replayed, or the end of the WAL left in
+    /// `wal/` by a full restore. `None` if there is no WAL
+    pub final_offset: Option<WalOffset>,
+    /// When the restore started
+    pub started_at: DateTime<Utc>,
+    /// When the restore finished, just before the data directory was
+    /// replaced
+    pub completed_at: DateTime<Utc>,
+    /// Checksum of the restored storage (`crc32:XXXXXXXX`)
+    pub checksum: String,
+    /// Target and stopping point, for a point-in-time restore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub point_in_time: Option<PointInTimeReport>,
+}
+
+/// Checks the restored storage against the snapshot manifest's checksum.
+///
+/// `restore_dir` is the reorganized restore directory (data_dir layout).
+///
+/// # Errors
+///
+/// `AERO_RESTORE_CORRUPTION` if the manifest records no valid storage
+/// checksum or the restored storage does not match it.
+pub(crate) fn verify_storage_checksum(restore_dir: &Path, snapshot_id: &str) -> RestoreResult<()> {
+    let manifest_path = restore_dir
+        .join("snapshots")
+        .join(snapshot_id)
+        .join("manifest.json");
+    let contents =
+        fs::read(&manifest_path).map_err(|e| RestoreError::io_error_at_path(&manifest_path, e))?;
+    let manifest: serde_json::Value = serde_json::from_slice(&contents).map_err(|e| {
+        RestoreError::corruption(format!(
+            "Invalid snapshot manifest {}: {}",
+            manifest_path.display(),
+            e
+        ))
+    })?;
+
+    let recorded = manifest
+        .get("storage_checksum")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| {
+            RestoreError::corruption(format!(
+                "Snapshot {} records no storage checksum; cannot verify the restored storage",
+                snapshot_id
+            ))
+        })?;
+    let expected = parse_checksum(recorded).ok_or_else(|| {
+        RestoreError::corruption(format!(
+            "Snapshot {} has an invalid storage checksum: {}",
+            snapshot_id, recorded
+        ))
+    })?;
+
+    let actual = storage_checksum(restore_dir)?;
+    if actual != format_checksum(expected) {
+        return Err(RestoreError::corruption(format!(
+            "Restored storage checksum {} does not match snapshot {} checksum {}",
+            actual, snapshot_id, recorded
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checksum of the storage file in `restore_dir`.
+pub(crate) fn storage_checksum(restore_dir: &Path) -> RestoreResult<String> {
+    let path = restore_dir.join("data").join("documents.dat");
+    let checksum = compute_file_checksum(&path).map_err(|e| {
+        RestoreError::failed(format!(
+            "Failed to checksum restored storage {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    Ok(format_checksum(checksum))
+}
+
+/// Writes `report` to [`RESTORE_REPORT_FILE`] in `restore_dir`.
+pub(crate) fn write_report(restore_dir: &Path, report: &RestoreReport) -> RestoreResult<()> {
+    let path = restore_dir.join(RESTORE_REPORT_FILE);
+    let contents = serde_json::to_string_pretty(report)
+        .map_err(|e| RestoreError::failed(format!("Failed to serialize restore report: {}", e)))?;
+
+    let mut file = File::create(&path).map_err(|e| RestoreError::io_error_at_path(&path, e))?;
+    file.write_all(contents.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| RestoreError::io_error_at_path(&path, e))?;
+    fsync_dir(restore_dir)
+}
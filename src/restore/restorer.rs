@@ -121,14 +121,14 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> RestoreResult<()> {
 /// - snapshot/storage.dat
 /// - snapshot/manifest.json
 /// - snapshot/schemas/
-/// - wal/wal.log
+/// - wal/wal.log or wal/000000N.log segments
 /// - backup_manifest.json
 ///
 /// Data dir structure:
-/// - data/storage.dat
+/// - data/documents.dat (the live storage file, per STORAGE.md)
 /// - metadata/schemas/
 /// - snapshots/<snapshot_id>/
-/// - wal/wal.log
+/// - wal/wal.log or wal/000000N.log segments
 /// - checkpoint.json (optional, created from backup manifest)
 pub fn reorganize_extracted_files(temp_dir: &Path, snapshot_id: &str) -> RestoreResult<PathBuf> {
     let reorganized = temp_dir
@@ -152,7 +152,7 @@ pub fn reorganize_extracted_files(temp_dir: &Path, snapshot_id: &str) -> Restore
     let snapshot_src = temp_dir.join("snapshot");
     let wal_src = temp_dir.join("wal");
 
-    // 1. Copy storage.dat to data/storage.dat
+    // 1. Copy storage.dat to data/documents.dat, where storage reads it
     let data_dir = reorganized.join("data");
     fs::create_dir_all(&data_dir).map_err(|e| {
         RestoreError::io_error(format!("Failed to create {}", data_dir.display()), e)
@@ -160,7 +160,7 @@ pub fn reorganize_extracted_files(temp_dir: &Path, snapshot_id: &str) -> Restore
 
     let storage_src = snapshot_src.join("storage.dat");
     if storage_src.exists() {
-        let storage_dst = data_dir.join("storage.dat");
+        let storage_dst = data_dir.join("documents.dat");
         copy_file_with_fsync(&storage_src, &storage_dst)?;
     }
 
@@ -344,7 +344,7 @@ mod tests {
         let reorganized = result.unwrap();
 
         // Verify structure
-        assert!(reorganized.join("data").join("storage.dat").exists());
+        assert!(reorganized.join("data").join("documents.dat").exists());
         assert!(reorganized.join("metadata").join("schemas").exists());
         assert!(reorganized
             .join("snapshots")
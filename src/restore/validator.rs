@@ -13,6 +13,7 @@ use std::io::Read;
 use std::path::Path;
 
 use crate::backup::BackupManifest;
+use crate::wal::{detect_layout, WalLayout};
 
 use super::errors::{RestoreError, RestoreResult};
 
@@ -132,7 +133,7 @@ pub fn validate_snapshot(restore_dir: &Path) -> RestoreResult<()> {
 ///
 /// Per RESTORE.md §5:
 /// - WAL directory readable
-/// - WAL files accessible
+/// - WAL files accessible (legacy `wal.log` or every numbered segment)
 pub fn validate_wal(restore_dir: &Path) -> RestoreResult<()> {
     let wal_dir = restore_dir.join("wal");
 
@@ -142,11 +143,16 @@ pub fn validate_wal(restore_dir: &Path) -> RestoreResult<()> {
         ));
     }
 
-    // Check wal.log exists (may be empty)
-    let wal_log = wal_dir.join("wal.log");
-    if wal_log.exists() {
-        // Try to open it to verify it's readable
-        File::open(&wal_log).map_err(|e| RestoreError::io_error_at_path(&wal_log, e))?;
+    // WAL files may be absent or empty; those present must be readable
+    let files = match detect_layout(&wal_dir)
+        .map_err(|e| RestoreError::invalid_backup(format!("Invalid WAL in backup: {}", e)))?
+    {
+        WalLayout::Empty => Vec::new(),
+        WalLayout::Legacy(path) => vec![path],
+        WalLayout::Segmented(segments) => segments.into_iter().map(|s| s.path).collect(),
+    };
+    for path in files {
+        File::open(&path).map_err(|e| RestoreError::io_error_at_path(&path, e))?;
     }
 
     Ok(())
@@ -350,6 +356,16 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_validate_wal_rejects_mixed_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        create_valid_backup_structure(temp_dir.path());
+        File::create(temp_dir.path().join("wal").join("0000001.log")).unwrap();
+
+        let result = validate_wal(temp_dir.path());
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_validate_preconditions_data_dir_missing() {
         let temp_dir = TempDir::new().unwrap();
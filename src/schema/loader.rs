@@ -196,6 +196,33 @@ impl SchemaLoader {
 
         Ok(path)
     }
+
+    /// Removes a schema version from the registry and deletes its file.
+    ///
+    /// A version that was only registered in memory has no file to delete.
+    pub fn remove(
+        &mut self,
+        schema_id: &str,
+        schema_version: &str,
+    ) -> Result<(), Box<SchemaError>> {
+        let filename = format!("schema_{}_{}.json", schema_id, schema_version);
+        let path = self.schema_dir.join(&filename);
+
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(Box::new(SchemaError::malformed_schema(
+                    path.display().to_string(),
+                    format!("Failed to remove file: {}", e),
+                )))
+            }
+        }
+
+        self.schemas
+            .remove(&(schema_id.to_string(), schema_version.to_string()));
+        Ok(())
+    }
 }
 
 // Implement planner's SchemaRegistry trait
@@ -262,6 +289,23 @@ mod tests {
         assert!(loader2.exists("users", "v1"));
     }
 
+    #[test]
+    fn test_remove_deletes_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut loader = SchemaLoader::new(temp_dir.path());
+
+        let schema = sample_schema();
+        let path = loader.save_schema(&schema).unwrap();
+        loader.register(schema).unwrap();
+
+        loader.remove("users", "v1").unwrap();
+        assert!(!loader.exists("users", "v1"));
+        assert!(!path.exists());
+
+        // Removing again is a no-op
+        loader.remove("users", "v1").unwrap();
+    }
+
     #[test]
     fn test_unknown_schema() {
         let temp_dir = TempDir::new().unwrap();
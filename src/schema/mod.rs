@@ -18,5 +18,7 @@ mod validator;
 
 pub use errors::{SchemaError, SchemaErrorCode, SchemaResult};
 pub use loader::SchemaLoader;
-pub use types::{FieldDef, FieldType, Schema};
+pub use types::{
+    is_soft_deleted, FieldDef, FieldType, Schema, TtlConfig, DELETED_AT_FIELD, EXPIRES_AT_FIELD,
+};
 pub use validator::SchemaValidator;
@@ -9,8 +9,71 @@
 //! - array: Homogeneous array with element type
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
+/// Field a soft delete sets to the time of deletion (RFC 3339)
+pub const DELETED_AT_FIELD: &str = "_deleted_at";
+
+/// Whether a stored document body has been soft-deleted
+pub fn is_soft_deleted(body: &Value) -> bool {
+    body.get(DELETED_AT_FIELD).is_some_and(|at| !at.is_null())
+}
+
+/// Field a `ttl_seconds` collection sets to the document's expiry time
+/// (Unix seconds)
+pub const EXPIRES_AT_FIELD: &str = "_expires_at";
+
+/// When documents of a schema expire
+///
+/// Exactly one of `field` and `ttl_seconds` is set. With `field`, a
+/// document expires at the Unix time (seconds) held in that declared int
+/// field; a document without it never expires. With `ttl_seconds`, inserts
+/// set `_expires_at` that many seconds after insert time, and updates keep
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TtlConfig {
+    /// Int field holding each document's expiry time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// Lifetime of every document, counted from its insert
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
+}
+
+impl TtlConfig {
+    /// Expire documents at the time held in `field`
+    pub fn at_field(field: impl Into<String>) -> Self {
+        Self {
+            field: Some(field.into()),
+            ttl_seconds: None,
+        }
+    }
+
+    /// Expire documents `ttl_seconds` after insert
+    pub fn after_seconds(ttl_seconds: u64) -> Self {
+        Self {
+            field: None,
+            ttl_seconds: Some(ttl_seconds),
+        }
+    }
+
+    /// Field holding the expiry time: `field`, or `_expires_at`
+    pub fn expiry_field(&self) -> &str {
+        self.field.as_deref().unwrap_or(EXPIRES_AT_FIELD)
+    }
+
+    /// Expiry time of a document body, if it has one
+    pub fn expires_at(&self, body: &Value) -> Option<i64> {
+        body.get(self.expiry_field()).and_then(Value::as_i64)
+    }
+
+    /// Whether a document body had expired at `now` (Unix seconds)
+    pub fn is_expired(&self, body: &Value, now: i64) -> bool {
+        self.expires_at(body).is_some_and(|at| at <= now)
+    }
+}
+
 /// Supported field types as defined in SCHEMA.md §136-153
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
@@ -158,6 +221,13 @@ pub struct Schema {
     pub description: Option<String>,
     /// Field definitions
     pub fields: HashMap<String, FieldDef>,
+    /// Deletes set `_deleted_at` instead of removing the document; `purge`
+    /// removes it
+    #[serde(default)]
+    pub soft_delete: bool,
+    /// Documents expire, and are removed by the expiry sweeper
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<TtlConfig>,
 }
 
 impl Schema {
@@ -172,9 +242,23 @@ impl Schema {
             schema_version: schema_version.into(),
             description: None,
             fields,
+            soft_delete: false,
+            ttl: None,
         }
     }
 
+    /// Soft-delete documents of this schema
+    pub fn with_soft_delete(mut self) -> Self {
+        self.soft_delete = true;
+        self
+    }
+
+    /// Expire documents of this schema
+    pub fn with_ttl(mut self, ttl: TtlConfig) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
     /// Returns the unique key for this schema (id, version)
     pub fn key(&self) -> (&str, &str) {
         (&self.schema_id, &self.schema_version)
@@ -194,6 +278,29 @@ impl Schema {
             }
         }
 
+        // Only a soft delete may set _deleted_at
+        if self.fields.contains_key(DELETED_AT_FIELD) {
+            return Err(format!("'{}' is reserved for soft deletes", DELETED_AT_FIELD));
+        }
+
+        // Only a ttl_seconds schema may set _expires_at
+        if self.fields.contains_key(EXPIRES_AT_FIELD) {
+            return Err(format!("'{}' is reserved for document TTL", EXPIRES_AT_FIELD));
+        }
+
+        if let Some(ttl) = &self.ttl {
+            match (&ttl.field, ttl.ttl_seconds) {
+                (Some(field), None) => match self.fields.get(field) {
+                    Some(def) if def.field_type == FieldType::Int => {}
+                    Some(_) => return Err(format!("TTL field '{}' must be an int", field)),
+                    None => return Err(format!("TTL field '{}' is not declared", field)),
+                },
+                (None, Some(seconds)) if seconds > 0 => {}
+                (None, Some(_)) => return Err("'ttl_seconds' must be > 0".into()),
+                _ => return Err("'ttl' must set exactly one of 'field' and 'ttl_seconds'".into()),
+            }
+        }
+
         Ok(())
     }
 }
@@ -237,6 +344,55 @@ mod tests {
         assert!(result.unwrap_err().contains("required"));
     }
 
+    #[test]
+    fn test_soft_delete_flag() {
+        let schema: Schema = serde_json::from_str(
+            r#"{"schema_id": "users", "schema_version": "v1", "fields": {}}"#,
+        )
+        .unwrap();
+        assert!(!schema.soft_delete);
+        assert!(sample_schema().with_soft_delete().soft_delete);
+
+        let mut schema = sample_schema();
+        schema
+            .fields
+            .insert(DELETED_AT_FIELD.into(), FieldDef::optional_string());
+        assert!(schema.validate_structure().unwrap_err().contains("reserved"));
+
+        assert!(is_soft_deleted(&serde_json::json!({"_deleted_at": "2026-01-01T00:00:00Z"})));
+        assert!(!is_soft_deleted(&serde_json::json!({"_deleted_at": null})));
+    }
+
+    #[test]
+    fn test_ttl_config() {
+        let schema: Schema = serde_json::from_str(
+            r#"{"schema_id": "users", "schema_version": "v1", "fields": {},
+                "ttl": {"ttl_seconds": 60}}"#,
+        )
+        .unwrap();
+        assert_eq!(schema.ttl, Some(TtlConfig::after_seconds(60)));
+        assert_eq!(TtlConfig::after_seconds(60).expiry_field(), EXPIRES_AT_FIELD);
+
+        assert!(sample_schema().with_ttl(TtlConfig::at_field("age")).validate_structure().is_ok());
+        let err = sample_schema().with_ttl(TtlConfig::at_field("name")).validate_structure();
+        assert!(err.unwrap_err().contains("must be an int"));
+        let err = sample_schema().with_ttl(TtlConfig::at_field("missing")).validate_structure();
+        assert!(err.unwrap_err().contains("not declared"));
+        let err = sample_schema().with_ttl(TtlConfig::after_seconds(0)).validate_structure();
+        assert!(err.unwrap_err().contains("> 0"));
+        let both = TtlConfig {
+            field: Some("age".into()),
+            ttl_seconds: Some(60),
+        };
+        let err = sample_schema().with_ttl(both).validate_structure();
+        assert!(err.unwrap_err().contains("exactly one"));
+
+        let ttl = TtlConfig::at_field("age");
+        assert!(ttl.is_expired(&serde_json::json!({"age": 100}), 100));
+        assert!(!ttl.is_expired(&serde_json::json!({"age": 101}), 100));
+        assert!(!ttl.is_expired(&serde_json::json!({}), 100));
+    }
+
     #[test]
     fn test_nested_object_type() {
         let mut address_fields = HashMap::new();
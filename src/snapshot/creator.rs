@@ -1,17 +1,22 @@
 //! Core snapshot creation logic
 //!
-//! Per SNAPSHOT.md §4, snapshot creation must follow this exact sequence:
+//! Per SNAPSHOT.md §4, snapshot creation runs in two phases.
 //!
-//! 1. Pause writes (acquire global execution lock) - done by caller
-//! 2. fsync WAL
-//! 3. Copy storage.dat → snapshot/storage.dat
-//! 4. fsync snapshot/storage.dat
-//! 5. Copy schemas → snapshot/schemas/
-//! 6. fsync snapshot/schemas directory
-//! 7. Generate manifest.json
+//! Phase 1, under the global execution lock (held by caller):
+//! 1. fsync WAL - done by caller
+//! 2. Record the fence: storage length, last WAL sequence and WAL end
+//! 3. Copy schemas → snapshot/schemas/
+//! 4. fsync snapshot/schemas directory
+//!
+//! Phase 2, after the lock is released:
+//! 5. Copy storage.dat up to the fence → snapshot/storage.dat (throttled)
+//! 6. fsync snapshot/storage.dat
+//! 7. Generate manifest.json, recording the fence
 //! 8. fsync manifest.json
 //! 9. fsync snapshot directory
-//! 10. Release global lock - done by caller
+//!
+//! An incremental snapshot writes only the blocks changed since its base
+//! in step 5; see `incremental`.
 //!
 //! Any failure aborts snapshot and cleans up partial directory.
 
@@ -19,13 +24,21 @@ use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
 
+use crate::crash_point::{maybe_crash, points};
+
 use super::checksum::{compute_file_checksum, format_checksum};
 use super::errors::{SnapshotError, SnapshotResult};
-use super::manifest::SnapshotManifest;
-use super::SnapshotId;
+use super::incremental;
+use super::manifest::{SnapshotFence, SnapshotManifest, WalEnd};
+use super::{SnapshotConfig, SnapshotId};
+
+/// Chunk size for the storage copy, and the granularity of throttling
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
 
 /// Generates a snapshot ID in RFC3339 basic format.
 ///
@@ -46,7 +59,7 @@ fn generate_created_at() -> String {
 /// fsync a directory to ensure durability.
 ///
 /// On Unix, this opens the directory and calls fsync on it.
-fn fsync_dir(path: &Path) -> SnapshotResult<()> {
+pub(super) fn fsync_dir(path: &Path) -> SnapshotResult<()> {
     let dir = OpenOptions::new()
         .read(true)
         .open(path)
@@ -101,7 +114,7 @@ fn copy_file_with_fsync(src: &Path, dst: &Path) -> SnapshotResult<()> {
 /// Per SNAPSHOT.md §3.2:
 /// - copied recursively
 /// - filenames preserved
-fn copy_dir_recursive(src: &Path, dst: &Path) -> SnapshotResult<()> {
+pub(super) fn copy_dir_recursive(src: &Path, dst: &Path) -> SnapshotResult<()> {
     fs::create_dir_all(dst).map_err(|e| {
         SnapshotError::io_error(format!("Failed to create directory: {}", dst.display()), e)
     })?;
@@ -133,49 +146,260 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> SnapshotResult<()> {
 }
 
 /// Remove a snapshot directory (cleanup on failure).
-fn cleanup_snapshot(path: &Path) {
+pub(super) fn cleanup_snapshot(path: &Path) {
     if path.exists() {
         // Best effort removal - we're already in an error path
         let _ = fs::remove_dir_all(path);
     }
 }
 
-/// Create a snapshot.
+/// A snapshot that has been fenced but whose storage is not yet copied.
 ///
-/// This function implements the snapshot creation algorithm per SNAPSHOT.md §4.
+/// Returned by phase 1 of snapshot creation, which runs under the global
+/// execution lock. [`complete`](Self::complete) runs phase 2 and does not
+/// need the lock: storage is append-only, so the bytes below the fence are
+/// never rewritten while the copy reads them.
+///
+/// Dropping a pending snapshot without completing it removes its directory.
+pub struct PendingSnapshot {
+    snapshot_id: SnapshotId,
+    created_at: String,
+    snapshot_dir: PathBuf,
+    storage_path: PathBuf,
+    fence: SnapshotFence,
+    commit_boundary: Option<u64>,
+    /// Base snapshot directory, for an incremental snapshot
+    base_dir: Option<PathBuf>,
+    completed: bool,
+}
+
+impl PendingSnapshot {
+    /// Returns the ID the snapshot will have once completed.
+    pub fn snapshot_id(&self) -> &str {
+        &self.snapshot_id
+    }
+
+    /// Returns the fence recorded in phase 1.
+    pub fn fence(&self) -> SnapshotFence {
+        self.fence
+    }
+
+    /// Copies storage up to the fence and writes the manifest.
+    ///
+    /// The copy is paced to `config.io_throttle_mbps`. Fails if storage is
+    /// shorter than the fence, which means it was replaced mid-copy.
+    pub fn complete(mut self, config: &SnapshotConfig) -> SnapshotResult<SnapshotId> {
+        let result = self.write_contents(config);
+        if result.is_ok() {
+            self.completed = true;
+        }
+        result
+    }
+
+    fn write_contents(&self, config: &SnapshotConfig) -> SnapshotResult<SnapshotId> {
+        let mut throttle = Throttle::new(config);
+        let (storage_checksum_str, delta) = match &self.base_dir {
+            // Write the blocks changed since the base and fsync
+            Some(base_dir) => {
+                let (checksum, delta) = incremental::write_delta(
+                    base_dir,
+                    &self.storage_path,
+                    self.fence.storage_length,
+                    &self.snapshot_dir,
+                    &mut throttle,
+                )?;
+                (checksum, Some(delta))
+            }
+            // Copy storage.dat up to the fence and fsync
+            None => {
+                let snapshot_storage = self.snapshot_dir.join("storage.dat");
+                copy_prefix_with_fsync(
+                    &self.storage_path,
+                    &snapshot_storage,
+                    self.fence.storage_length,
+                    &mut throttle,
+                )?;
+                let storage_checksum = compute_file_checksum(&snapshot_storage)?;
+                (format_checksum(storage_checksum), None)
+            }
+        };
+
+        // Storage is copied and fsynced but nothing marks it complete yet
+        maybe_crash(points::SNAPSHOT_AFTER_COPY_BEFORE_MANIFEST);
+
+        // Compute checksums
+
+        let schema_checksums = compute_schema_checksums(&self.snapshot_dir.join("schemas"))?;
+
+        // Generate and write manifest with fsync
+        // Use Phase-2 manifest if commit_boundary is provided
+        let manifest = match self.commit_boundary {
+            Some(boundary) => SnapshotManifest::with_mvcc_boundary(
+                &self.snapshot_id,
+                &self.created_at,
+                storage_checksum_str,
+                schema_checksums,
+                boundary,
+            ),
+            None => SnapshotManifest::new(
+                &self.snapshot_id,
+                &self.created_at,
+                storage_checksum_str,
+                schema_checksums,
+            ),
+        }
+        .with_fence(self.fence);
+        let manifest = match delta {
+            Some(delta) => manifest.with_delta(delta),
+            None => manifest,
+        };
+
+        // Without a manifest the copied files are not a snapshot
+        maybe_crash(points::SNAPSHOT_BEFORE_MANIFEST);
+
+        let manifest_path = self.snapshot_dir.join("manifest.json");
+        manifest.write_to_file(&manifest_path)?;
+
+        // fsync snapshot directory
+        fsync_dir(&self.snapshot_dir)?;
+
+        Ok(self.snapshot_id.clone())
+    }
+}
+
+impl Drop for PendingSnapshot {
+    fn drop(&mut self) {
+        if !self.completed {
+            cleanup_snapshot(&self.snapshot_dir);
+        }
+    }
+}
+
+/// Paces a copy to a configured rate.
+pub(super) struct Throttle {
+    bytes_per_sec: u64,
+    started: Instant,
+    copied: u64,
+}
+
+impl Throttle {
+    pub(super) fn new(config: &SnapshotConfig) -> Self {
+        Self {
+            bytes_per_sec: u64::from(config.io_throttle_mbps) * 1024 * 1024,
+            started: Instant::now(),
+            copied: 0,
+        }
+    }
+
+    /// Records `bytes` as copied, sleeping if the copy is ahead of the rate.
+    pub(super) fn consume(&mut self, bytes: usize) {
+        self.copied += bytes as u64;
+        if self.bytes_per_sec == 0 {
+            return;
+        }
+
+        let due = Duration::from_secs_f64(self.copied as f64 / self.bytes_per_sec as f64);
+        let elapsed = self.started.elapsed();
+        if due > elapsed {
+            thread::sleep(due - elapsed);
+        }
+    }
+}
+
+/// Copy the first `length` bytes of a file with fsync.
+fn copy_prefix_with_fsync(
+    src: &Path,
+    dst: &Path,
+    length: u64,
+    throttle: &mut Throttle,
+) -> SnapshotResult<()> {
+    let src_file = File::open(src).map_err(|e| {
+        SnapshotError::io_error(format!("Failed to open source file: {}", src.display()), e)
+    })?;
+
+    let mut dst_file = File::create(dst).map_err(|e| {
+        SnapshotError::io_error(
+            format!("Failed to create destination file: {}", dst.display()),
+            e,
+        )
+    })?;
+
+    let mut src_file = src_file.take(length);
+    let mut copied = 0u64;
+    let mut buffer = vec![0u8; COPY_CHUNK_SIZE];
+    loop {
+        let bytes_read = src_file.read(&mut buffer).map_err(|e| {
+            SnapshotError::io_error(format!("Failed to read from: {}", src.display()), e)
+        })?;
+
+        if bytes_read == 0 {
+            break;
+        }
+
+        dst_file.write_all(&buffer[..bytes_read]).map_err(|e| {
+            SnapshotError::io_error(format!("Failed to write to: {}", dst.display()), e)
+        })?;
+        copied += bytes_read as u64;
+        throttle.consume(bytes_read);
+    }
+
+    if copied != length {
+        return Err(SnapshotError::snapshot_failed(format!(
+            "{} shrank below the snapshot fence: expected {} bytes, found {}",
+            src.display(),
+            length,
+            copied
+        )));
+    }
+
+    // fsync is mandatory
+    dst_file
+        .sync_all()
+        .map_err(|e| SnapshotError::io_error(format!("fsync failed for: {}", dst.display()), e))
+}
+
+/// Phase 1 of snapshot creation: fence the snapshot and copy schemas.
+///
+/// Must run under the global execution lock. Records the storage length,
+/// `wal_sequence` and `wal_end`, creates the snapshot directory and copies
+/// the schemas, which are small and may change once the lock is released.
 ///
 /// # Arguments
 ///
 /// * `data_dir` - Root data directory (contains snapshots/)
 /// * `storage_path` - Path to storage.dat file
 /// * `schema_dir` - Path to schema directory
-///
-/// # Returns
-///
-/// The snapshot ID on success.
+/// * `wal_sequence` - Last durable WAL sequence number
+/// * `wal_end` - End of the durable WAL, if known
+/// * `commit_boundary` - MVCC commit boundary, for Phase-2 snapshots
 ///
 /// # Errors
 ///
 /// Returns `SnapshotError` on any failure. The snapshot directory is cleaned up
 /// on failure (no partial snapshots left behind).
-///
-/// # Panics
-///
-/// This function does not panic.
-pub fn create_snapshot_impl(
+pub fn begin_snapshot_impl(
     data_dir: &Path,
     storage_path: &Path,
     schema_dir: &Path,
-) -> SnapshotResult<SnapshotId> {
+    wal_sequence: u64,
+    wal_end: Option<WalEnd>,
+    commit_boundary: Option<u64>,
+) -> SnapshotResult<PendingSnapshot> {
+    let storage_length = fs::metadata(storage_path)
+        .map_err(|e| {
+            SnapshotError::io_error(
+                format!("Failed to read storage file: {}", storage_path.display()),
+                e,
+            )
+        })?
+        .len();
+
     // Generate snapshot ID and timestamp
     let snapshot_id = generate_snapshot_id();
     let created_at = generate_created_at();
 
-    // Create snapshot directory path
-    let snapshots_dir = data_dir.join("snapshots");
-    let snapshot_dir = snapshots_dir.join(&snapshot_id);
-
     // Create snapshot directory
+    let snapshot_dir = snapshot_path(data_dir, &snapshot_id);
     fs::create_dir_all(&snapshot_dir).map_err(|e| {
         SnapshotError::io_error(
             format!(
@@ -186,43 +410,26 @@ pub fn create_snapshot_impl(
         )
     })?;
 
-    // From here on, any error must clean up the snapshot directory
-    let result = create_snapshot_contents(
-        &snapshot_dir,
-        storage_path,
-        schema_dir,
-        &snapshot_id,
-        &created_at,
-        None, // Phase-1: no MVCC boundary
-    );
-
-    if result.is_err() {
-        cleanup_snapshot(&snapshot_dir);
-    }
-
-    result
-}
-
-/// Create snapshot contents (storage, schemas, manifest).
-///
-/// This is separated to make cleanup easier on failure.
-fn create_snapshot_contents(
-    snapshot_dir: &Path,
-    storage_path: &Path,
-    schema_dir: &Path,
-    snapshot_id: &str,
-    created_at: &str,
-    commit_boundary: Option<u64>,
-) -> SnapshotResult<SnapshotId> {
-    // Step 3-4: Copy storage.dat and fsync
-    let snapshot_storage = snapshot_dir.join("storage.dat");
-    copy_file_with_fsync(storage_path, &snapshot_storage)?;
+    // From here on, dropping the pending snapshot cleans up the directory
+    let pending = PendingSnapshot {
+        snapshot_id,
+        created_at,
+        snapshot_dir,
+        storage_path: storage_path.to_path_buf(),
+        fence: SnapshotFence {
+            storage_length,
+            wal_sequence,
+            wal_end,
+        },
+        commit_boundary,
+        base_dir: None,
+        completed: false,
+    };
 
-    // Step 5-6: Copy schemas recursively and fsync directory
-    let snapshot_schemas = snapshot_dir.join("schemas");
+    // Copy schemas recursively and fsync directory
+    let snapshot_schemas = pending.snapshot_dir.join("schemas");
     if schema_dir.exists() && schema_dir.is_dir() {
         copy_dir_recursive(schema_dir, &snapshot_schemas)?;
-        fsync_dir(&snapshot_schemas)?;
     } else {
         // Create empty schemas directory if source doesn't exist
         fs::create_dir_all(&snapshot_schemas).map_err(|e| {
@@ -234,40 +441,90 @@ fn create_snapshot_contents(
                 e,
             )
         })?;
-        fsync_dir(&snapshot_schemas)?;
     }
+    fsync_dir(&snapshot_schemas)?;
 
-    // Compute checksums
-    let storage_checksum = compute_file_checksum(&snapshot_storage)?;
-    let storage_checksum_str = format_checksum(storage_checksum);
-
-    let schema_checksums = compute_schema_checksums(&snapshot_schemas)?;
-
-    // Step 7-8: Generate and write manifest with fsync
-    // Use Phase-2 manifest if commit_boundary is provided
-    let manifest = match commit_boundary {
-        Some(boundary) => SnapshotManifest::with_mvcc_boundary(
-            snapshot_id,
-            created_at,
-            storage_checksum_str,
-            schema_checksums,
-            boundary,
-        ),
-        None => SnapshotManifest::new(
-            snapshot_id,
-            created_at,
-            storage_checksum_str,
-            schema_checksums,
-        ),
-    };
+    Ok(pending)
+}
 
-    let manifest_path = snapshot_dir.join("manifest.json");
-    manifest.write_to_file(&manifest_path)?;
+/// Create a snapshot in one pass.
+///
+/// Runs both phases back to back without throttling, so the caller's
+/// lock covers the whole copy.
+///
+/// # Arguments
+///
+/// * `data_dir` - Root data directory (contains snapshots/)
+/// * `storage_path` - Path to storage.dat file
+/// * `schema_dir` - Path to schema directory
+/// * `wal_sequence` - Last durable WAL sequence number
+/// * `wal_end` - End of the durable WAL, if known
+///
+/// # Returns
+///
+/// The snapshot ID on success.
+///
+/// # Errors
+///
+/// Returns `SnapshotError` on any failure. The snapshot directory is cleaned up
+/// on failure (no partial snapshots left behind).
+pub fn create_snapshot_impl(
+    data_dir: &Path,
+    storage_path: &Path,
+    schema_dir: &Path,
+    wal_sequence: u64,
+    wal_end: Option<WalEnd>,
+) -> SnapshotResult<SnapshotId> {
+    begin_snapshot_impl(
+        data_dir,
+        storage_path,
+        schema_dir,
+        wal_sequence,
+        wal_end,
+        None,
+    )?
+    .complete(&SnapshotConfig::default())
+}
 
-    // Step 9: fsync snapshot directory
-    fsync_dir(snapshot_dir)?;
+/// Create an incremental snapshot against `base_snapshot_id` in one pass.
+///
+/// Like [`create_snapshot_impl`], but storage is written as the blocks
+/// changed since the base (see `incremental`).
+///
+/// # Errors
+///
+/// `AERO_SNAPSHOT_CHAIN_BROKEN` if the base snapshot is missing or
+/// incomplete; otherwise as [`create_snapshot_impl`].
+pub fn create_incremental_snapshot_impl(
+    data_dir: &Path,
+    storage_path: &Path,
+    schema_dir: &Path,
+    wal_sequence: u64,
+    wal_end: Option<WalEnd>,
+    base_snapshot_id: &str,
+) -> SnapshotResult<SnapshotId> {
+    let base_dir = snapshot_path(data_dir, base_snapshot_id);
+    incremental::read_base_manifest(&base_dir)?;
+
+    // IDs have one-second resolution and only grow, so checking now keeps
+    // the new snapshot from landing in the base's directory
+    if generate_snapshot_id().as_str() <= base_snapshot_id {
+        return Err(SnapshotError::snapshot_failed(format!(
+            "Incremental snapshot would not be newer than its base {}; retry in a second",
+            base_snapshot_id
+        )));
+    }
 
-    Ok(snapshot_id.to_string())
+    let mut pending = begin_snapshot_impl(
+        data_dir,
+        storage_path,
+        schema_dir,
+        wal_sequence,
+        wal_end,
+        None,
+    )?;
+    pending.base_dir = Some(base_dir);
+    pending.complete(&SnapshotConfig::default())
 }
 
 /// Create an MVCC-aware snapshot with commit boundary.
@@ -281,6 +538,8 @@ fn create_snapshot_contents(
 /// * `data_dir` - Root data directory (contains snapshots/)
 /// * `storage_path` - Path to storage.dat file
 /// * `schema_dir` - Path to schema directory
+/// * `wal_sequence` - Last durable WAL sequence number
+/// * `wal_end` - End of the durable WAL, if known
 /// * `commit_boundary` - The MVCC commit identity boundary
 ///
 /// # Returns
@@ -290,42 +549,19 @@ pub fn create_mvcc_snapshot_impl(
     data_dir: &Path,
     storage_path: &Path,
     schema_dir: &Path,
+    wal_sequence: u64,
+    wal_end: Option<WalEnd>,
     commit_boundary: u64,
 ) -> SnapshotResult<SnapshotId> {
-    // Generate snapshot ID and timestamp
-    let snapshot_id = generate_snapshot_id();
-    let created_at = generate_created_at();
-
-    // Create snapshot directory path
-    let snapshots_dir = data_dir.join("snapshots");
-    let snapshot_dir = snapshots_dir.join(&snapshot_id);
-
-    // Create snapshot directory
-    fs::create_dir_all(&snapshot_dir).map_err(|e| {
-        SnapshotError::io_error(
-            format!(
-                "Failed to create snapshot directory: {}",
-                snapshot_dir.display()
-            ),
-            e,
-        )
-    })?;
-
-    // From here on, any error must clean up the snapshot directory
-    let result = create_snapshot_contents(
-        &snapshot_dir,
+    begin_snapshot_impl(
+        data_dir,
         storage_path,
         schema_dir,
-        &snapshot_id,
-        &created_at,
+        wal_sequence,
+        wal_end,
         Some(commit_boundary),
-    );
-
-    if result.is_err() {
-        cleanup_snapshot(&snapshot_dir);
-    }
-
-    result
+    )?
+    .complete(&SnapshotConfig::default())
 }
 
 /// Compute checksums for all schema files.
@@ -424,7 +660,8 @@ mod tests {
         let (temp_dir, storage_path, schema_dir) = setup_test_environment();
         let data_dir = temp_dir.path();
 
-        let snapshot_id = create_snapshot_impl(data_dir, &storage_path, &schema_dir).unwrap();
+        let snapshot_id =
+            create_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None).unwrap();
 
         let snapshot_dir = data_dir.join("snapshots").join(&snapshot_id);
         assert!(snapshot_dir.exists());
@@ -436,7 +673,8 @@ mod tests {
         let (temp_dir, storage_path, schema_dir) = setup_test_environment();
         let data_dir = temp_dir.path();
 
-        let snapshot_id = create_snapshot_impl(data_dir, &storage_path, &schema_dir).unwrap();
+        let snapshot_id =
+            create_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None).unwrap();
 
         let snapshot_dir = data_dir.join("snapshots").join(&snapshot_id);
 
@@ -451,7 +689,8 @@ mod tests {
         let (temp_dir, storage_path, schema_dir) = setup_test_environment();
         let data_dir = temp_dir.path();
 
-        let snapshot_id = create_snapshot_impl(data_dir, &storage_path, &schema_dir).unwrap();
+        let snapshot_id =
+            create_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None).unwrap();
 
         let manifest_path = data_dir
             .join("snapshots")
@@ -471,7 +710,8 @@ mod tests {
         let data_dir = temp_dir.path();
 
         // Create snapshot
-        let snapshot_id = create_snapshot_impl(data_dir, &storage_path, &schema_dir).unwrap();
+        let snapshot_id =
+            create_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None).unwrap();
 
         // Read checksums
         let manifest_path = data_dir
@@ -492,7 +732,8 @@ mod tests {
         let (temp_dir, storage_path, schema_dir) = setup_test_environment();
         let data_dir = temp_dir.path();
 
-        let snapshot_id = create_snapshot_impl(data_dir, &storage_path, &schema_dir).unwrap();
+        let snapshot_id =
+            create_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None).unwrap();
 
         // Verify schema file copied
         let snapshot_schemas = data_dir
@@ -515,7 +756,8 @@ mod tests {
         let (temp_dir, storage_path, schema_dir) = setup_test_environment();
         let data_dir = temp_dir.path();
 
-        let snapshot_id = create_snapshot_impl(data_dir, &storage_path, &schema_dir).unwrap();
+        let snapshot_id =
+            create_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None).unwrap();
 
         // Compare original and copied storage
         let original = fs::read(&storage_path).unwrap();
@@ -540,7 +782,7 @@ mod tests {
         let schema_dir = data_dir.join("schemas");
         fs::create_dir_all(&schema_dir).unwrap();
 
-        let result = create_snapshot_impl(data_dir, &storage_path, &schema_dir);
+        let result = create_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None);
 
         // Should fail
         assert!(result.is_err());
@@ -566,7 +808,8 @@ mod tests {
         let schema_dir = data_dir.join("schemas");
         fs::create_dir_all(&schema_dir).unwrap();
 
-        let snapshot_id = create_snapshot_impl(data_dir, &storage_path, &schema_dir).unwrap();
+        let snapshot_id =
+            create_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None).unwrap();
 
         // Verify manifest has empty schema checksums
         let manifest_path = data_dir
@@ -589,7 +832,8 @@ mod tests {
         // Non-existent schema directory
         let schema_dir = data_dir.join("nonexistent_schemas");
 
-        let snapshot_id = create_snapshot_impl(data_dir, &storage_path, &schema_dir).unwrap();
+        let snapshot_id =
+            create_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None).unwrap();
 
         // Should succeed with empty schemas
         let manifest_path = data_dir
@@ -599,4 +843,37 @@ mod tests {
         let manifest = SnapshotManifest::read_from_file(&manifest_path).unwrap();
         assert!(manifest.schema_checksums.is_empty());
     }
+
+    #[test]
+    fn test_throttled_copy_is_paced() {
+        let (temp_dir, storage_path, schema_dir) = setup_test_environment();
+        let data_dir = temp_dir.path();
+        fs::write(&storage_path, vec![7u8; 512 * 1024]).unwrap();
+
+        let pending =
+            begin_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None, None).unwrap();
+        let started = Instant::now();
+        let snapshot_id = pending
+            .complete(&SnapshotConfig::default().with_io_throttle_mbps(2))
+            .unwrap();
+
+        // 512 KiB at 2 MiB/s takes at least 250ms
+        assert!(started.elapsed() >= Duration::from_millis(240));
+        let copied = fs::read(snapshot_path(data_dir, &snapshot_id).join("storage.dat")).unwrap();
+        assert_eq!(copied.len(), 512 * 1024);
+    }
+
+    #[test]
+    fn test_complete_fails_if_storage_shrank() {
+        let (temp_dir, storage_path, schema_dir) = setup_test_environment();
+        let data_dir = temp_dir.path();
+
+        let pending =
+            begin_snapshot_impl(data_dir, &storage_path, &schema_dir, 0, None, None).unwrap();
+        fs::write(&storage_path, b"short").unwrap();
+
+        assert!(pending.complete(&SnapshotConfig::default()).is_err());
+        let entries: Vec<_> = fs::read_dir(snapshots_dir(data_dir)).unwrap().collect();
+        assert!(entries.is_empty(), "Partial snapshot should be cleaned up");
+    }
 }
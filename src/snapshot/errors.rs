@@ -4,6 +4,7 @@
 //! - AERO_SNAPSHOT_FAILED (ERROR severity)
 //! - AERO_SNAPSHOT_IO (ERROR severity)
 //! - AERO_SNAPSHOT_MANIFEST (ERROR severity)
+//! - AERO_SNAPSHOT_CHAIN_BROKEN (ERROR severity)
 
 use std::fmt;
 use std::io;
@@ -32,6 +33,8 @@ pub enum SnapshotErrorCode {
     AeroSnapshotIo,
     /// Manifest generation/write failure
     AeroSnapshotManifest,
+    /// Incremental snapshot chain is missing a link or fails verification
+    AeroSnapshotChainBroken,
 }
 
 impl SnapshotErrorCode {
@@ -41,6 +44,7 @@ impl SnapshotErrorCode {
             SnapshotErrorCode::AeroSnapshotFailed => "AERO_SNAPSHOT_FAILED",
             SnapshotErrorCode::AeroSnapshotIo => "AERO_SNAPSHOT_IO",
             SnapshotErrorCode::AeroSnapshotManifest => "AERO_SNAPSHOT_MANIFEST",
+            SnapshotErrorCode::AeroSnapshotChainBroken => "AERO_SNAPSHOT_CHAIN_BROKEN",
         }
     }
 
@@ -138,6 +142,16 @@ impl SnapshotError {
         }
     }
 
+    /// Create a broken incremental chain error
+    pub fn chain_broken(message: impl Into<String>) -> Self {
+        Self {
+            code: SnapshotErrorCode::AeroSnapshotChainBroken,
+            message: message.into(),
+            details: None,
+            source: None,
+        }
+    }
+
     /// Add details to an error
     pub fn with_details(mut self, details: impl Into<String>) -> Self {
         self.details = Some(details.into());
@@ -218,6 +232,10 @@ mod tests {
             SnapshotErrorCode::AeroSnapshotManifest.code(),
             "AERO_SNAPSHOT_MANIFEST"
         );
+        assert_eq!(
+            SnapshotErrorCode::AeroSnapshotChainBroken.code(),
+            "AERO_SNAPSHOT_CHAIN_BROKEN"
+        );
     }
 
     #[test]
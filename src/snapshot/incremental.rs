@@ -0,0 +1,430 @@
+//! Incremental snapshots
+//!
+//! An incremental snapshot stores only the storage blocks that changed
+//! since a base snapshot, plus a pointer to that base. Bases may themselves
+//! be incremental, forming a chain that ends at a full snapshot:
+//!
+//! ```text
+//! full ← delta ← delta
+//! ```
+//!
+//! Layout of an incremental snapshot directory:
+//! - storage.delta (changed blocks, in block order)
+//! - schemas/ (full copy; schemas are small)
+//! - manifest.json (with a `delta` section)
+//!
+//! Rules:
+//! - Storage is split into `BLOCK_SIZE` blocks. A block is changed if its
+//!   CRC32 differs from the parent's block at the same index, or the
+//!   parent has no such block
+//! - `storage_checksum` is the checksum of the full storage, not of
+//!   storage.delta
+//! - Before materializing, the whole chain is verified: every link exists,
+//!   the full base and every delta match their checksums, and every parent
+//!   still has the storage checksum its child was taken against
+//! - A materialized snapshot is a full snapshot and is verified against the
+//!   target's `storage_checksum`
+//!
+//! Any verification failure is `AERO_SNAPSHOT_CHAIN_BROKEN`.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crc32fast::Hasher;
+
+use super::checksum::{compute_checksum, compute_file_checksum, format_checksum};
+use super::creator::{cleanup_snapshot, copy_dir_recursive, fsync_dir, snapshot_path, Throttle};
+use super::errors::{SnapshotError, SnapshotResult};
+use super::manifest::{SnapshotDelta, SnapshotManifest};
+
+/// Size of a storage block tracked by incremental snapshots
+pub const BLOCK_SIZE: u64 = 64 * 1024;
+
+/// File holding an incremental snapshot's changed blocks
+pub const DELTA_FILE: &str = "storage.delta";
+
+/// Reads an incremental snapshot's base manifest.
+///
+/// Called under the global execution lock, before the snapshot directory is
+/// created, so a missing base fails the snapshot early.
+pub(super) fn read_base_manifest(base_dir: &Path) -> SnapshotResult<SnapshotManifest> {
+    SnapshotManifest::read_from_file(&base_dir.join("manifest.json")).map_err(|e| {
+        SnapshotError::chain_broken(format!(
+            "Base snapshot {} is missing or incomplete: {}",
+            base_dir.display(),
+            e.message()
+        ))
+    })
+}
+
+/// Writes the blocks of the first `length` bytes of `storage_path` that
+/// differ from the base snapshot to `snapshot_dir/storage.delta`.
+///
+/// Returns the checksum of the full storage prefix and the manifest's
+/// delta section.
+pub(super) fn write_delta(
+    base_dir: &Path,
+    storage_path: &Path,
+    length: u64,
+    snapshot_dir: &Path,
+    throttle: &mut Throttle,
+) -> SnapshotResult<(String, SnapshotDelta)> {
+    let base = read_base_manifest(base_dir)?;
+    let base_blocks = block_checksums(base_dir, &base)?;
+
+    let src = File::open(storage_path).map_err(|e| {
+        SnapshotError::io_error(
+            format!("Failed to open source file: {}", storage_path.display()),
+            e,
+        )
+    })?;
+    let mut src = src.take(length);
+
+    let delta_path = snapshot_dir.join(DELTA_FILE);
+    let mut delta_file = File::create(&delta_path).map_err(|e| {
+        SnapshotError::io_error(
+            format!("Failed to create delta file: {}", delta_path.display()),
+            e,
+        )
+    })?;
+
+    let mut storage_hasher = Hasher::new();
+    let mut block_checksums = Vec::new();
+    let mut changed_blocks = Vec::new();
+    let mut copied = 0u64;
+    let mut buffer = vec![0u8; BLOCK_SIZE as usize];
+    loop {
+        let bytes_read = read_block(&mut src, &mut buffer).map_err(|e| {
+            SnapshotError::io_error(
+                format!("Failed to read from: {}", storage_path.display()),
+                e,
+            )
+        })?;
+        if bytes_read == 0 {
+            break;
+        }
+
+        let block = &buffer[..bytes_read];
+        storage_hasher.update(block);
+        let checksum = format_checksum(compute_checksum(block));
+        let index = block_checksums.len();
+        if base_blocks.get(index) != Some(&checksum) {
+            delta_file.write_all(block).map_err(|e| {
+                SnapshotError::io_error(format!("Failed to write to: {}", delta_path.display()), e)
+            })?;
+            changed_blocks.push(index as u64);
+        }
+        block_checksums.push(checksum);
+        copied += bytes_read as u64;
+        throttle.consume(bytes_read);
+    }
+
+    if copied != length {
+        return Err(SnapshotError::snapshot_failed(format!(
+            "{} shrank below the snapshot fence: expected {} bytes, found {}",
+            storage_path.display(),
+            length,
+            copied
+        )));
+    }
+
+    // fsync is mandatory
+    delta_file.sync_all().map_err(|e| {
+        SnapshotError::io_error(format!("fsync failed for: {}", delta_path.display()), e)
+    })?;
+    let delta_checksum = compute_file_checksum(&delta_path)?;
+
+    let delta = SnapshotDelta {
+        parent_snapshot_id: base.snapshot_id,
+        parent_storage_checksum: base.storage_checksum,
+        block_size: BLOCK_SIZE,
+        storage_length: length,
+        changed_blocks,
+        block_checksums,
+        delta_checksum: format_checksum(delta_checksum),
+    };
+    Ok((format_checksum(storage_hasher.finalize()), delta))
+}
+
+/// Verifies the chain ending at `snapshot_id`.
+///
+/// Returns each link's directory and manifest, from `snapshot_id` back to
+/// its full base.
+pub(super) fn verify_chain(
+    data_dir: &Path,
+    snapshot_id: &str,
+) -> SnapshotResult<Vec<(PathBuf, SnapshotManifest)>> {
+    let mut chain = Vec::new();
+    let mut seen = HashSet::new();
+    let mut id = snapshot_id.to_string();
+    let mut expected_checksum: Option<(String, String)> = None;
+
+    loop {
+        if !seen.insert(id.clone()) {
+            return Err(SnapshotError::chain_broken(format!(
+                "Snapshot chain of {} loops back to {}",
+                snapshot_id, id
+            )));
+        }
+
+        let dir = snapshot_path(data_dir, &id);
+        let manifest =
+            SnapshotManifest::read_from_file(&dir.join("manifest.json")).map_err(|e| {
+                SnapshotError::chain_broken(format!(
+                    "Snapshot {} in the chain of {} is missing or unreadable: {}",
+                    id,
+                    snapshot_id,
+                    e.message()
+                ))
+            })?;
+        if manifest.snapshot_id != id {
+            return Err(SnapshotError::chain_broken(format!(
+                "Snapshot directory {} holds the manifest of {}",
+                id, manifest.snapshot_id
+            )));
+        }
+        if let Some((child, checksum)) = &expected_checksum {
+            if manifest.storage_checksum != *checksum {
+                return Err(SnapshotError::chain_broken(format!(
+                    "Snapshot {} has storage checksum {}, but {} was taken against {}",
+                    id, manifest.storage_checksum, child, checksum
+                )));
+            }
+        }
+
+        match &manifest.delta {
+            None => {
+                verify_file_checksum(&dir.join("storage.dat"), &manifest.storage_checksum)?;
+                chain.push((dir, manifest));
+                return Ok(chain);
+            }
+            Some(delta) => {
+                verify_delta(&dir, &id, delta)?;
+                id = delta.parent_snapshot_id.clone();
+                expected_checksum = Some((
+                    manifest.snapshot_id.clone(),
+                    delta.parent_storage_checksum.clone(),
+                ));
+                chain.push((dir, manifest));
+            }
+        }
+    }
+}
+
+/// Reconstructs the full snapshot `snapshot_id` in `dest`.
+///
+/// `dest` must not exist. It receives storage.dat, schemas/ and a manifest
+/// without a `delta` section. On failure `dest` is removed.
+pub(super) fn materialize_impl(
+    data_dir: &Path,
+    snapshot_id: &str,
+    dest: &Path,
+) -> SnapshotResult<SnapshotManifest> {
+    if dest.exists() {
+        return Err(SnapshotError::snapshot_failed(format!(
+            "Cannot materialize snapshot {} into {}: it already exists",
+            snapshot_id,
+            dest.display()
+        )));
+    }
+
+    let chain = verify_chain(data_dir, snapshot_id)?;
+
+    fs::create_dir_all(dest).map_err(|e| {
+        SnapshotError::io_error(format!("Failed to create directory: {}", dest.display()), e)
+    })?;
+    let result = write_materialized(&chain, dest);
+    if result.is_err() {
+        cleanup_snapshot(dest);
+    }
+    result
+}
+
+fn write_materialized(
+    chain: &[(PathBuf, SnapshotManifest)],
+    dest: &Path,
+) -> SnapshotResult<SnapshotManifest> {
+    let (base_dir, _) = chain
+        .last()
+        .expect("a verified chain ends at a full snapshot");
+    let (target_dir, target) = &chain[0];
+
+    let storage_path = dest.join("storage.dat");
+    fs::copy(base_dir.join("storage.dat"), &storage_path).map_err(|e| {
+        SnapshotError::io_error(
+            format!("Failed to copy base storage to: {}", storage_path.display()),
+            e,
+        )
+    })?;
+
+    let mut storage = OpenOptions::new()
+        .write(true)
+        .open(&storage_path)
+        .map_err(|e| SnapshotError::io_error_at_path(&storage_path, e))?;
+    for (dir, manifest) in chain.iter().rev().skip(1) {
+        if let Some(delta) = &manifest.delta {
+            apply_delta(&mut storage, &storage_path, &dir.join(DELTA_FILE), delta)?;
+        }
+    }
+    storage.sync_all().map_err(|e| {
+        SnapshotError::io_error(format!("fsync failed for: {}", storage_path.display()), e)
+    })?;
+
+    let checksum = format_checksum(compute_file_checksum(&storage_path)?);
+    if checksum != target.storage_checksum {
+        return Err(SnapshotError::chain_broken(format!(
+            "Materialized storage of snapshot {} has checksum {}, expected {}",
+            target.snapshot_id, checksum, target.storage_checksum
+        )));
+    }
+
+    let schemas = dest.join("schemas");
+    copy_dir_recursive(&target_dir.join("schemas"), &schemas)?;
+    fsync_dir(&schemas)?;
+
+    let mut manifest = target.clone();
+    manifest.delta = None;
+    manifest.write_to_file(&dest.join("manifest.json"))?;
+    fsync_dir(dest)?;
+
+    Ok(manifest)
+}
+
+/// Overwrites `storage` with a delta's changed blocks and sets its length.
+fn apply_delta(
+    storage: &mut File,
+    storage_path: &Path,
+    delta_path: &Path,
+    delta: &SnapshotDelta,
+) -> SnapshotResult<()> {
+    storage
+        .set_len(delta.storage_length)
+        .map_err(|e| SnapshotError::io_error_at_path(storage_path, e))?;
+
+    let mut delta_file =
+        File::open(delta_path).map_err(|e| SnapshotError::io_error_at_path(delta_path, e))?;
+    let mut buffer = vec![0u8; delta.block_size as usize];
+    for &index in &delta.changed_blocks {
+        let len = block_len(delta, index) as usize;
+        delta_file
+            .read_exact(&mut buffer[..len])
+            .map_err(|e| SnapshotError::io_error_at_path(delta_path, e))?;
+        storage
+            .seek(SeekFrom::Start(index * delta.block_size))
+            .and_then(|_| storage.write_all(&buffer[..len]))
+            .map_err(|e| SnapshotError::io_error_at_path(storage_path, e))?;
+    }
+    Ok(())
+}
+
+/// Checks a delta's shape and its storage.delta checksum.
+fn verify_delta(dir: &Path, id: &str, delta: &SnapshotDelta) -> SnapshotResult<()> {
+    let block_count = if delta.block_size == 0 {
+        None
+    } else {
+        Some(delta.storage_length.div_ceil(delta.block_size))
+    };
+    let ascending = delta.changed_blocks.windows(2).all(|w| w[0] < w[1]);
+    let in_range = delta
+        .changed_blocks
+        .last()
+        .zip(block_count)
+        .is_none_or(|(&last, count)| last < count);
+    if block_count != Some(delta.block_checksums.len() as u64) || !ascending || !in_range {
+        return Err(SnapshotError::chain_broken(format!(
+            "Snapshot {} has an inconsistent delta: {} changed of {} blocks of {} bytes \
+             covering {} bytes",
+            id,
+            delta.changed_blocks.len(),
+            delta.block_checksums.len(),
+            delta.block_size,
+            delta.storage_length
+        )));
+    }
+
+    let delta_path = dir.join(DELTA_FILE);
+    let expected_len: u64 = delta
+        .changed_blocks
+        .iter()
+        .map(|&index| block_len(delta, index))
+        .sum();
+    let actual_len = fs::metadata(&delta_path)
+        .map_err(|e| {
+            SnapshotError::chain_broken(format!("Snapshot {} is missing {}: {}", id, DELTA_FILE, e))
+        })?
+        .len();
+    if actual_len != expected_len {
+        return Err(SnapshotError::chain_broken(format!(
+            "Snapshot {} {} is {} bytes, expected {}",
+            id, DELTA_FILE, actual_len, expected_len
+        )));
+    }
+    verify_file_checksum(&delta_path, &delta.delta_checksum)
+}
+
+fn verify_file_checksum(path: &Path, expected: &str) -> SnapshotResult<()> {
+    let actual = compute_file_checksum(path).map_err(|e| {
+        SnapshotError::chain_broken(format!("Cannot read {}: {}", path.display(), e.message()))
+    })?;
+    if format_checksum(actual) != expected {
+        return Err(SnapshotError::chain_broken(format!(
+            "{} has checksum {}, expected {}",
+            path.display(),
+            format_checksum(actual),
+            expected
+        )));
+    }
+    Ok(())
+}
+
+/// Block checksums of a snapshot's full storage.
+///
+/// Recorded in an incremental snapshot's manifest; computed from
+/// storage.dat for a full one.
+fn block_checksums(dir: &Path, manifest: &SnapshotManifest) -> SnapshotResult<Vec<String>> {
+    if let Some(delta) = &manifest.delta {
+        if delta.block_size != BLOCK_SIZE {
+            return Err(SnapshotError::chain_broken(format!(
+                "Base snapshot {} uses {}-byte blocks, expected {}",
+                manifest.snapshot_id, delta.block_size, BLOCK_SIZE
+            )));
+        }
+        return Ok(delta.block_checksums.clone());
+    }
+
+    let path = dir.join("storage.dat");
+    let mut file = File::open(&path).map_err(|e| SnapshotError::io_error_at_path(&path, e))?;
+    let mut checksums = Vec::new();
+    let mut buffer = vec![0u8; BLOCK_SIZE as usize];
+    loop {
+        let bytes_read = read_block(&mut file, &mut buffer)
+            .map_err(|e| SnapshotError::io_error_at_path(&path, e))?;
+        if bytes_read == 0 {
+            return Ok(checksums);
+        }
+        checksums.push(format_checksum(compute_checksum(&buffer[..bytes_read])));
+    }
+}
+
+/// Length of block `index` of a delta's full storage.
+fn block_len(delta: &SnapshotDelta, index: u64) -> u64 {
+    delta
+        .block_size
+        .min(delta.storage_length - index * delta.block_size)
+}
+
+/// Fills `buffer` unless the reader ends first; returns the bytes read.
+fn read_block(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
@@ -12,9 +12,16 @@
 //!   "schema_checksums": {
 //!     "user_v1.json": "crc32:abcd1234"
 //!   },
-//!   "format_version": 1
+//!   "format_version": 1,
+//!   "fence": {
+//!     "storage_length": 4096,
+//!     "wal_sequence": 42
+//!   }
 //! }
 //! ```
+//!
+//! An incremental snapshot also has a `delta` section naming its parent;
+//! see [`SnapshotDelta`].
 
 use std::collections::HashMap;
 use std::fs::File;
@@ -25,6 +32,66 @@ use serde::{Deserialize, Serialize};
 
 use super::errors::{SnapshotError, SnapshotResult};
 
+/// Point at which a snapshot's contents were fixed.
+///
+/// Recorded under the global execution lock before the storage copy
+/// starts. The snapshot holds exactly the first `storage_length` bytes of
+/// storage, which reflect every WAL record up to `wal_sequence` and none
+/// after it; the WAL past `wal_end` is not reflected.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotFence {
+    /// Length of storage.dat when the snapshot was fenced
+    pub storage_length: u64,
+
+    /// Last WAL sequence number durable when the snapshot was fenced
+    pub wal_sequence: u64,
+
+    /// End of the WAL when the snapshot was fenced (absent in manifests
+    /// written before it was recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wal_end: Option<WalEnd>,
+}
+
+/// A position just past the last WAL record.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WalEnd {
+    /// Active segment index (`None` for a legacy `wal.log`)
+    pub segment: Option<u64>,
+
+    /// Length of the active segment in bytes
+    pub offset: u64,
+}
+
+/// Storage of an incremental snapshot, as changes to its parent.
+///
+/// Storage is split into fixed-size blocks. `storage.delta` holds the
+/// blocks listed in `changed_blocks`, in order; every other block is the
+/// parent's. The manifest's `storage_checksum` is still that of the full
+/// storage, so a materialized snapshot can be verified like any other.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotDelta {
+    /// Snapshot this delta applies to
+    pub parent_snapshot_id: String,
+
+    /// Parent's `storage_checksum` when the delta was taken
+    pub parent_storage_checksum: String,
+
+    /// Block size in bytes
+    pub block_size: u64,
+
+    /// Length of the full storage in bytes
+    pub storage_length: u64,
+
+    /// Indices of the blocks stored in storage.delta, ascending
+    pub changed_blocks: Vec<u64>,
+
+    /// CRC32 of every block of the full storage, in order
+    pub block_checksums: Vec<String>,
+
+    /// CRC32 checksum of storage.delta (format: "crc32:XXXXXXXX")
+    pub delta_checksum: String,
+}
+
 /// Snapshot manifest per SNAPSHOT.md §3.3
 ///
 /// This is the authoritative snapshot descriptor containing:
@@ -61,6 +128,20 @@ pub struct SnapshotManifest {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub commit_boundary: Option<u64>,
+
+    /// Storage length and WAL position the snapshot was taken at
+    ///
+    /// None for snapshots written before fences were recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub fence: Option<SnapshotFence>,
+
+    /// Parent and changed blocks, for an incremental snapshot
+    ///
+    /// None for a full snapshot, which has storage.dat instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub delta: Option<SnapshotDelta>,
 }
 
 impl SnapshotManifest {
@@ -85,6 +166,8 @@ impl SnapshotManifest {
             schema_checksums,
             format_version: 1,
             commit_boundary: None,
+            fence: None,
+            delta: None,
         }
     }
 
@@ -115,9 +198,28 @@ impl SnapshotManifest {
             schema_checksums,
             format_version: 2,
             commit_boundary: Some(commit_boundary),
+            fence: None,
+            delta: None,
         }
     }
 
+    /// Records the fence the snapshot was taken at.
+    pub fn with_fence(mut self, fence: SnapshotFence) -> Self {
+        self.fence = Some(fence);
+        self
+    }
+
+    /// Records the parent and changed blocks of an incremental snapshot.
+    pub fn with_delta(mut self, delta: SnapshotDelta) -> Self {
+        self.delta = Some(delta);
+        self
+    }
+
+    /// Returns true if storage is stored as a delta against a parent.
+    pub fn is_incremental(&self) -> bool {
+        self.delta.is_some()
+    }
+
     /// Returns the commit boundary if this is an MVCC-aware snapshot.
     pub fn commit_boundary(&self) -> Option<u64> {
         self.commit_boundary
@@ -443,4 +545,28 @@ mod tests {
         assert_eq!(loaded.commit_boundary(), Some(12345));
         assert!(loaded.is_mvcc_snapshot());
     }
+
+    #[test]
+    fn test_fence_roundtrip() {
+        let fence = SnapshotFence {
+            storage_length: 4096,
+            wal_sequence: 42,
+            wal_end: Some(WalEnd {
+                segment: Some(3),
+                offset: 8192,
+            }),
+        };
+        let manifest = create_test_manifest().with_fence(fence);
+
+        let json = manifest.to_json().unwrap();
+        assert!(json.contains("\"storage_length\": 4096"));
+
+        let parsed = SnapshotManifest::from_json(&json).unwrap();
+        assert_eq!(parsed.fence, Some(fence));
+
+        // Fences recorded before the WAL end was
+        let legacy: SnapshotFence =
+            serde_json::from_str(r#"{"storage_length": 4096, "wal_sequence": 42}"#).unwrap();
+        assert_eq!(legacy.wal_end, None);
+    }
 }
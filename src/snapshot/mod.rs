@@ -24,6 +24,13 @@
 //!
 //! Indexes are NOT included - they are always rebuilt.
 //!
+//! An incremental snapshot stores storage.delta (the blocks changed since
+//! a base snapshot) instead of storage.dat. `SnapshotManager::materialize`
+//! rebuilds a full snapshot from the chain.
+//!
+//! `SnapshotManager::verify` recomputes the checksums of a snapshot's files
+//! and reports any that differ from the manifest or are missing.
+//!
 //! # Important
 //!
 //! Snapshot is NOT checkpoint. This module does NOT truncate WAL.
@@ -32,15 +39,21 @@
 mod checksum;
 mod creator;
 mod errors;
+mod incremental;
 mod manifest;
+mod verify;
 
 pub use checksum::{compute_file_checksum, format_checksum, parse_checksum};
-pub use creator::{generate_snapshot_id, snapshot_path, snapshots_dir};
+pub use creator::{generate_snapshot_id, snapshot_path, snapshots_dir, PendingSnapshot};
 pub use errors::{Severity, SnapshotError, SnapshotErrorCode, SnapshotResult};
-pub use manifest::SnapshotManifest;
+pub use incremental::{BLOCK_SIZE, DELTA_FILE};
+pub use manifest::{SnapshotDelta, SnapshotFence, SnapshotManifest, WalEnd};
+pub use verify::{ChecksumMismatch, VerifyReport};
 
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
+
 use crate::wal::WalWriter;
 
 /// Snapshot ID type (RFC3339 basic format: YYYYMMDDTHHMMSSZ)
@@ -76,6 +89,22 @@ impl Default for GlobalExecutionLock {
     }
 }
 
+/// Snapshot I/O configuration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotConfig {
+    /// Maximum storage copy rate in MiB/s (0 = unthrottled)
+    #[serde(default)]
+    pub io_throttle_mbps: u32,
+}
+
+impl SnapshotConfig {
+    /// Limits the storage copy to `mbps` MiB/s.
+    pub fn with_io_throttle_mbps(mut self, mbps: u32) -> Self {
+        self.io_throttle_mbps = mbps;
+        self
+    }
+}
+
 /// Snapshot manager for creating point-in-time database snapshots.
 ///
 /// This struct provides the public API for snapshot operations as specified
@@ -93,6 +122,16 @@ impl Default for GlobalExecutionLock {
 ///     &lock,
 /// )?;
 /// ```
+///
+/// To keep the lock only while the snapshot is fenced:
+///
+/// ```ignore
+/// let pending = {
+///     let _guard = execution_mutex.lock();
+///     SnapshotManager::begin_snapshot(&data_dir, &storage_path, &schema_dir, &wal, &lock)?
+/// };
+/// let snapshot_id = pending.complete(&SnapshotConfig::default().with_io_throttle_mbps(50))?;
+/// ```
 pub struct SnapshotManager;
 
 impl SnapshotManager {
@@ -165,7 +204,126 @@ impl SnapshotManager {
         let _ = wal; // Acknowledge the wal parameter
 
         // Steps 3-9: Create snapshot (all operations in strict order)
-        creator::create_snapshot_impl(data_dir, storage_path, schema_dir)
+        creator::create_snapshot_impl(
+            data_dir,
+            storage_path,
+            schema_dir,
+            wal.last_sequence_number(),
+            Some(wal_end(wal)?),
+        )
+    }
+
+    /// Create an incremental snapshot against `base_snapshot_id`.
+    ///
+    /// Follows `create_snapshot`, but instead of copying storage.dat it
+    /// writes the storage blocks that differ from the base's to
+    /// storage.delta and records the base as the snapshot's parent. The
+    /// base may itself be incremental. Use [`materialize`](Self::materialize)
+    /// to rebuild a full snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - Root data directory (contains snapshots/)
+    /// * `storage_path` - Path to the storage.dat file
+    /// * `schema_dir` - Path to the schema directory
+    /// * `wal` - WAL writer (fsynced, and the source of the WAL fence)
+    /// * `base_snapshot_id` - Completed snapshot in `data_dir` to diff against
+    /// * `_lock` - Marker proving the caller holds the global execution lock
+    ///
+    /// # Errors
+    ///
+    /// `AERO_SNAPSHOT_CHAIN_BROKEN` if the base is missing or incomplete,
+    /// and everything `create_snapshot` returns. Any partial snapshot
+    /// directory is cleaned up.
+    pub fn create_incremental_snapshot(
+        data_dir: &Path,
+        storage_path: &Path,
+        schema_dir: &Path,
+        wal: &WalWriter,
+        base_snapshot_id: &str,
+        _lock: &GlobalExecutionLock,
+    ) -> Result<SnapshotId, SnapshotError> {
+        wal.fsync()
+            .map_err(|e| SnapshotError::snapshot_failed(format!("WAL fsync failed: {}", e)))?;
+
+        creator::create_incremental_snapshot_impl(
+            data_dir,
+            storage_path,
+            schema_dir,
+            wal.last_sequence_number(),
+            Some(wal_end(wal)?),
+            base_snapshot_id,
+        )
+    }
+
+    /// Rebuild the full snapshot `snapshot_id` in `dest`.
+    ///
+    /// Verifies the chain from `snapshot_id` back to its full base, then
+    /// layers each delta over the base's storage, oldest first. `dest`
+    /// receives storage.dat, schemas/ and manifest.json, like a full
+    /// snapshot directory, and must not exist. A full snapshot is simply
+    /// copied.
+    ///
+    /// # Returns
+    ///
+    /// The manifest written to `dest`.
+    ///
+    /// # Errors
+    ///
+    /// `AERO_SNAPSHOT_CHAIN_BROKEN` if a link is missing, a file fails its
+    /// checksum, a parent changed after its child was taken, or the result
+    /// does not match the snapshot's storage checksum. `dest` is removed
+    /// on failure.
+    pub fn materialize(
+        data_dir: &Path,
+        snapshot_id: &str,
+        dest: &Path,
+    ) -> Result<SnapshotManifest, SnapshotError> {
+        incremental::materialize_impl(data_dir, snapshot_id, dest)
+    }
+
+    /// Fence a snapshot without copying storage.
+    ///
+    /// Phase 1 of a two-phase snapshot. Under the global execution lock this:
+    /// 1. fsyncs the WAL
+    /// 2. records the storage length, last WAL sequence and WAL end as the
+    ///    fence
+    /// 3. creates the snapshot directory and copies schemas
+    ///
+    /// The lock may be released as soon as this returns. Storage is
+    /// append-only, so writes made afterwards land beyond the fence and
+    /// [`PendingSnapshot::complete`] copies exactly the fenced prefix.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - Root data directory (will contain snapshots/ subdirectory)
+    /// * `storage_path` - Path to the storage.dat file
+    /// * `schema_dir` - Path to the schema directory
+    /// * `wal` - WAL writer (fsynced, and the source of the WAL fence)
+    /// * `_lock` - Marker proving the caller holds the global execution lock
+    ///
+    /// # Errors
+    ///
+    /// Returns `SnapshotError` on any failure. Any partial snapshot directory
+    /// is cleaned up before returning the error.
+    pub fn begin_snapshot(
+        data_dir: &Path,
+        storage_path: &Path,
+        schema_dir: &Path,
+        wal: &WalWriter,
+        _lock: &GlobalExecutionLock,
+    ) -> Result<PendingSnapshot, SnapshotError> {
+        wal.fsync()
+            .map_err(|e| SnapshotError::snapshot_failed(format!("WAL fsync failed: {}", e)))?;
+
+        creator::begin_snapshot_impl(
+            data_dir,
+            storage_path,
+            schema_dir,
+            wal.last_sequence_number(),
+            Some(wal_end(wal)?),
+            None,
+        )
     }
 
     /// Create an MVCC-aware snapshot with commit boundary.
@@ -204,10 +362,50 @@ impl SnapshotManager {
             .map(|c| c.value())
             .unwrap_or(0);
 
-        creator::create_mvcc_snapshot_impl(data_dir, storage_path, schema_dir, boundary)
+        creator::create_mvcc_snapshot_impl(
+            data_dir,
+            storage_path,
+            schema_dir,
+            wal.last_sequence_number(),
+            Some(wal_end(wal)?),
+            boundary,
+        )
+    }
+
+    /// Verify snapshot `snapshot_id` in `data_dir` against its manifest.
+    ///
+    /// Recomputes the checksum of storage.dat (storage.delta for an
+    /// incremental snapshot) and of every schema file, and reports each
+    /// file that differs from the manifest or is missing. An incremental
+    /// snapshot's parents are not followed; verify them separately, or use
+    /// [`materialize`](Self::materialize) to check the whole chain.
+    ///
+    /// # Errors
+    ///
+    /// `AERO_SNAPSHOT_FAILED` if the snapshot does not exist,
+    /// `AERO_SNAPSHOT_MANIFEST` if its manifest is missing or invalid, and
+    /// `AERO_SNAPSHOT_IO` if a present file cannot be read.
+    pub fn verify(data_dir: &Path, snapshot_id: &str) -> Result<VerifyReport, SnapshotError> {
+        verify::verify_impl(data_dir, snapshot_id)
+    }
+
+    /// Completed snapshots in `data_dir`, oldest first.
+    pub fn list_snapshots(data_dir: &Path) -> Result<Vec<SnapshotId>, SnapshotError> {
+        verify::list_impl(data_dir)
     }
 }
 
+/// End of the WAL `wal` writes to.
+fn wal_end(wal: &WalWriter) -> Result<WalEnd, SnapshotError> {
+    let offset = std::fs::metadata(wal.path())
+        .map_err(|e| SnapshotError::io_error_at_path(wal.path(), e))?
+        .len();
+    Ok(WalEnd {
+        segment: wal.active_segment_index(),
+        offset,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -262,6 +460,57 @@ mod tests {
         assert!(snapshot_dir.join("manifest.json").exists());
     }
 
+    #[test]
+    fn test_verify_healthy_snapshot() {
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        let data_dir = temp_dir.path();
+        let lock = GlobalExecutionLock::new();
+
+        let snapshot_id =
+            SnapshotManager::create_snapshot(data_dir, &storage_path, &schema_dir, &wal, &lock)
+                .unwrap();
+        assert_eq!(
+            SnapshotManager::list_snapshots(data_dir).unwrap(),
+            vec![snapshot_id.clone()]
+        );
+
+        let report = SnapshotManager::verify(data_dir, &snapshot_id).unwrap();
+        assert!(report.ok);
+        assert_eq!(report.files_checked, 2);
+        assert!(report.mismatches.is_empty());
+        assert!(report.missing.is_empty());
+    }
+
+    #[test]
+    fn test_verify_reports_corrupted_and_missing_files() {
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        let data_dir = temp_dir.path();
+        let lock = GlobalExecutionLock::new();
+
+        let snapshot_id =
+            SnapshotManager::create_snapshot(data_dir, &storage_path, &schema_dir, &wal, &lock)
+                .unwrap();
+        let snapshot_dir = snapshot_path(data_dir, &snapshot_id);
+
+        // Flip a byte in storage.dat, lose the schema file
+        let copied = snapshot_dir.join("storage.dat");
+        let mut bytes = fs::read(&copied).unwrap();
+        bytes[0] ^= 0xFF;
+        fs::write(&copied, bytes).unwrap();
+        fs::remove_file(snapshot_dir.join("schemas").join("user_v1.json")).unwrap();
+
+        let report = SnapshotManager::verify(data_dir, &snapshot_id).unwrap();
+        assert!(!report.ok);
+        assert_eq!(report.files_checked, 1);
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].file, "storage.dat");
+        assert_ne!(report.mismatches[0].expected, report.mismatches[0].actual);
+        assert_eq!(report.missing, vec!["schemas/user_v1.json".to_string()]);
+
+        let err = SnapshotManager::verify(data_dir, "20000101T000000Z").unwrap_err();
+        assert_eq!(err.code(), SnapshotErrorCode::AeroSnapshotFailed);
+    }
+
     #[test]
     fn test_lock_required() {
         // This test verifies the API requires the lock parameter
@@ -357,6 +606,311 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_begin_snapshot_excludes_writes_after_fence() {
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        let data_dir = temp_dir.path();
+        let lock = GlobalExecutionLock::new();
+
+        let pending =
+            SnapshotManager::begin_snapshot(data_dir, &storage_path, &schema_dir, &wal, &lock)
+                .unwrap();
+        assert_eq!(pending.fence().storage_length, 17);
+        let wal_end = WalEnd {
+            segment: wal.active_segment_index(),
+            offset: fs::metadata(wal.path()).unwrap().len(),
+        };
+
+        // Lock released: a write lands beyond the fence
+        let mut storage_file = fs::OpenOptions::new()
+            .append(true)
+            .open(&storage_path)
+            .unwrap();
+        storage_file.write_all(b" appended later").unwrap();
+
+        let snapshot_id = pending.complete(&SnapshotConfig::default()).unwrap();
+
+        let snapshot_dir = data_dir.join("snapshots").join(&snapshot_id);
+        let copied = fs::read(snapshot_dir.join("storage.dat")).unwrap();
+        assert_eq!(copied, b"test storage data");
+
+        let manifest =
+            SnapshotManifest::read_from_file(&snapshot_dir.join("manifest.json")).unwrap();
+        assert_eq!(
+            manifest.fence,
+            Some(SnapshotFence {
+                storage_length: 17,
+                wal_sequence: 0,
+                wal_end: Some(wal_end),
+            })
+        );
+    }
+
+    #[test]
+    fn test_writes_proceed_while_snapshot_copies() {
+        use crate::storage::{StoragePayload, StorageReader, StorageWriter};
+        use crate::wal::{RecordType, WalPayload};
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+        use std::time::Instant;
+
+        fn write_document(wal: &mut WalWriter, storage: &mut StorageWriter, id: &str) {
+            let body = vec![b'x'; 4096];
+            wal.append(
+                RecordType::Insert,
+                WalPayload::new("c", id, "s", "v1", body.clone()),
+            )
+            .unwrap();
+            storage
+                .write(&StoragePayload::new("c", id, "s", "v1", body))
+                .unwrap();
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
+        let schema_dir = data_dir.join("metadata").join("schemas");
+        fs::create_dir_all(&schema_dir).unwrap();
+
+        let engine = Arc::new(Mutex::new((
+            WalWriter::open(data_dir).unwrap(),
+            StorageWriter::open(data_dir).unwrap(),
+        )));
+        {
+            let (wal, storage) = &mut *engine.lock().unwrap();
+            for i in 0..128 {
+                write_document(wal, storage, &format!("before{}", i));
+            }
+        }
+
+        let (pending, wal_end) = {
+            let (wal, storage) = &*engine.lock().unwrap();
+            let lock = GlobalExecutionLock::new();
+            let pending =
+                SnapshotManager::begin_snapshot(data_dir, storage.path(), &schema_dir, wal, &lock)
+                    .unwrap();
+            let wal_end = WalEnd {
+                segment: wal.active_segment_index(),
+                offset: fs::metadata(wal.path()).unwrap().len(),
+            };
+            (pending, wal_end)
+        };
+        let fence = pending.fence();
+        assert_eq!(fence.wal_sequence, 128);
+        assert_eq!(fence.wal_end, Some(wal_end));
+
+        // The engine lock is free: writes run while ~512 KiB is copied at 1 MiB/s
+        let writer = {
+            let engine = Arc::clone(&engine);
+            thread::spawn(move || {
+                for i in 0..8 {
+                    let (wal, storage) = &mut *engine.lock().unwrap();
+                    write_document(wal, storage, &format!("after{}", i));
+                }
+                Instant::now()
+            })
+        };
+        let snapshot_id = pending
+            .complete(&SnapshotConfig::default().with_io_throttle_mbps(1))
+            .unwrap();
+        let completed_at = Instant::now();
+        let writes_done_at = writer.join().unwrap();
+        assert!(writes_done_at < completed_at);
+
+        {
+            let (wal, storage) = &*engine.lock().unwrap();
+            assert_eq!(wal.last_sequence_number(), 136);
+            assert!(fs::metadata(wal.path()).unwrap().len() > wal_end.offset);
+            assert!(storage.current_offset() > fence.storage_length);
+        }
+
+        let copied = data_dir
+            .join("snapshots")
+            .join(&snapshot_id)
+            .join("storage.dat");
+        assert_eq!(fs::metadata(&copied).unwrap().len(), fence.storage_length);
+        let mut reader = StorageReader::open(&copied).unwrap();
+        let mut documents = 0;
+        while let Some(record) = reader.read_next().unwrap() {
+            assert!(record.document_id.contains("before"));
+            documents += 1;
+        }
+        assert_eq!(documents, 128);
+    }
+
+    #[test]
+    fn test_dropped_pending_snapshot_is_removed() {
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        let data_dir = temp_dir.path();
+        let lock = GlobalExecutionLock::new();
+
+        let pending =
+            SnapshotManager::begin_snapshot(data_dir, &storage_path, &schema_dir, &wal, &lock)
+                .unwrap();
+        let snapshot_dir = data_dir.join("snapshots").join(pending.snapshot_id());
+        assert!(snapshot_dir.exists());
+
+        drop(pending);
+        assert!(!snapshot_dir.exists());
+    }
+
+    /// Snapshot IDs have one-second resolution
+    fn next_second() {
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+    }
+
+    fn block(fill: u8, len: u64) -> Vec<u8> {
+        vec![fill; len as usize]
+    }
+
+    #[test]
+    fn test_incremental_chain_materializes_to_full_state() {
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        let data_dir = temp_dir.path();
+        let lock = GlobalExecutionLock::new();
+
+        // Base: two full blocks and a partial one
+        let mut storage = [
+            block(b'a', BLOCK_SIZE),
+            block(b'b', BLOCK_SIZE),
+            block(b'c', 100),
+        ]
+        .concat();
+        fs::write(&storage_path, &storage).unwrap();
+        let base =
+            SnapshotManager::create_snapshot(data_dir, &storage_path, &schema_dir, &wal, &lock)
+                .unwrap();
+
+        // Delta 1: rewrite part of block 1 and grow block 2
+        next_second();
+        storage[BLOCK_SIZE as usize + 10] = b'x';
+        storage.extend(block(b'd', 1000));
+        fs::write(&storage_path, &storage).unwrap();
+        let first = SnapshotManager::create_incremental_snapshot(
+            data_dir,
+            &storage_path,
+            &schema_dir,
+            &wal,
+            &base,
+            &lock,
+        )
+        .unwrap();
+        let first_state = storage.clone();
+
+        // Delta 2: fill block 2 and start block 3
+        next_second();
+        storage.extend(block(b'e', BLOCK_SIZE));
+        fs::write(&storage_path, &storage).unwrap();
+        let second = SnapshotManager::create_incremental_snapshot(
+            data_dir,
+            &storage_path,
+            &schema_dir,
+            &wal,
+            &first,
+            &lock,
+        )
+        .unwrap();
+
+        let second_dir = snapshot_path(data_dir, &second);
+        let manifest = SnapshotManifest::read_from_file(&second_dir.join("manifest.json")).unwrap();
+        let delta = manifest.delta.as_ref().unwrap();
+        assert_eq!(delta.parent_snapshot_id, first);
+        assert_eq!(delta.changed_blocks, vec![2, 3]);
+        assert_eq!(delta.block_checksums.len(), 4);
+        assert!(!second_dir.join("storage.dat").exists());
+        let delta_len = fs::metadata(second_dir.join(DELTA_FILE)).unwrap().len();
+        assert_eq!(delta_len, storage.len() as u64 - 2 * BLOCK_SIZE);
+
+        let first_manifest = SnapshotManifest::read_from_file(
+            &snapshot_path(data_dir, &first).join("manifest.json"),
+        )
+        .unwrap();
+        assert_eq!(first_manifest.delta.unwrap().changed_blocks, vec![1, 2]);
+
+        let full = temp_dir.path().join("materialized");
+        let materialized = SnapshotManager::materialize(data_dir, &second, &full).unwrap();
+        assert_eq!(fs::read(full.join("storage.dat")).unwrap(), storage);
+        assert!(full.join("schemas").join("user_v1.json").exists());
+        assert_eq!(materialized.snapshot_id, second);
+        assert!(!materialized.is_incremental());
+        assert_eq!(
+            SnapshotManifest::read_from_file(&full.join("manifest.json")).unwrap(),
+            materialized
+        );
+        assert_eq!(
+            materialized.storage_checksum,
+            format_checksum(compute_file_checksum(&full.join("storage.dat")).unwrap())
+        );
+
+        let middle = temp_dir.path().join("middle");
+        SnapshotManager::materialize(data_dir, &first, &middle).unwrap();
+        assert_eq!(fs::read(middle.join("storage.dat")).unwrap(), first_state);
+    }
+
+    #[test]
+    fn test_materialize_rejects_broken_chain() {
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        let data_dir = temp_dir.path();
+        let lock = GlobalExecutionLock::new();
+
+        let base =
+            SnapshotManager::create_snapshot(data_dir, &storage_path, &schema_dir, &wal, &lock)
+                .unwrap();
+        next_second();
+        let mut storage_file = fs::OpenOptions::new()
+            .append(true)
+            .open(&storage_path)
+            .unwrap();
+        storage_file.write_all(b" appended later").unwrap();
+        let delta = SnapshotManager::create_incremental_snapshot(
+            data_dir,
+            &storage_path,
+            &schema_dir,
+            &wal,
+            &base,
+            &lock,
+        )
+        .unwrap();
+
+        // A corrupt delta
+        let delta_path = snapshot_path(data_dir, &delta).join(DELTA_FILE);
+        let original = fs::read(&delta_path).unwrap();
+        let mut corrupt = original.clone();
+        corrupt[0] ^= 0xff;
+        fs::write(&delta_path, &corrupt).unwrap();
+        let dest = temp_dir.path().join("materialized");
+        let err = SnapshotManager::materialize(data_dir, &delta, &dest).unwrap_err();
+        assert_eq!(err.code(), SnapshotErrorCode::AeroSnapshotChainBroken);
+        assert!(err.message().contains("has checksum"));
+        assert!(!dest.exists());
+
+        // A missing base
+        fs::write(&delta_path, &original).unwrap();
+        fs::remove_dir_all(snapshot_path(data_dir, &base)).unwrap();
+        let err = SnapshotManager::materialize(data_dir, &delta, &dest).unwrap_err();
+        assert_eq!(err.code(), SnapshotErrorCode::AeroSnapshotChainBroken);
+        assert!(err.message().contains(&base));
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_incremental_snapshot_requires_base() {
+        let (temp_dir, storage_path, schema_dir, wal) = setup_test_environment();
+        let data_dir = temp_dir.path();
+        let lock = GlobalExecutionLock::new();
+
+        let err = SnapshotManager::create_incremental_snapshot(
+            data_dir,
+            &storage_path,
+            &schema_dir,
+            &wal,
+            "20260101T000000Z",
+            &lock,
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), SnapshotErrorCode::AeroSnapshotChainBroken);
+        assert!(!snapshots_dir(data_dir).exists());
+    }
+
     #[test]
     fn test_global_execution_lock_default() {
         // Verify Default trait works
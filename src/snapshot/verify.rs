@@ -0,0 +1,120 @@
+//! Snapshot integrity verification.
+//!
+//! Recomputes the CRC32 of every file the manifest records a checksum for
+//! and compares it with the recorded value:
+//! - storage.dat against `storage_checksum` (full snapshots)
+//! - storage.delta against `delta.delta_checksum` (incremental snapshots)
+//! - each schema file against `schema_checksums`
+//!
+//! Mismatched and missing files are collected rather than returned as
+//! errors, so one pass reports every problem with the snapshot. Only a
+//! missing snapshot or an unreadable manifest fails verification outright.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::checksum::{compute_file_checksum, format_checksum};
+use super::creator::{snapshot_path, snapshots_dir};
+use super::errors::{SnapshotError, SnapshotResult};
+use super::incremental::DELTA_FILE;
+use super::manifest::SnapshotManifest;
+use super::SnapshotId;
+
+/// A file whose checksum differs from the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecksumMismatch {
+    /// Path relative to the snapshot directory (e.g. `schemas/user_v1.json`)
+    pub file: String,
+    /// Checksum recorded in the manifest
+    pub expected: String,
+    /// Checksum of the file on disk
+    pub actual: String,
+}
+
+/// Outcome of [`SnapshotManager::verify`](super::SnapshotManager::verify).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifyReport {
+    /// Snapshot that was verified
+    pub snapshot_id: SnapshotId,
+    /// True when every recorded file is present and matches its checksum
+    pub ok: bool,
+    /// Number of files whose checksum was recomputed
+    pub files_checked: usize,
+    /// Files whose checksum differs from the manifest
+    pub mismatches: Vec<ChecksumMismatch>,
+    /// Files recorded in the manifest but absent on disk
+    pub missing: Vec<String>,
+}
+
+pub(super) fn verify_impl(data_dir: &Path, snapshot_id: &str) -> SnapshotResult<VerifyReport> {
+    let dir = snapshot_path(data_dir, snapshot_id);
+    if !dir.is_dir() {
+        return Err(SnapshotError::snapshot_failed(format!(
+            "Snapshot {} not found in {}",
+            snapshot_id,
+            snapshots_dir(data_dir).display()
+        )));
+    }
+    let manifest = SnapshotManifest::read_from_file(&dir.join("manifest.json"))?;
+
+    let mut expected = Vec::new();
+    match &manifest.delta {
+        Some(delta) => expected.push((DELTA_FILE.to_string(), delta.delta_checksum.clone())),
+        None => expected.push(("storage.dat".to_string(), manifest.storage_checksum.clone())),
+    }
+    let mut schemas: Vec<_> = manifest.schema_checksums.iter().collect();
+    schemas.sort();
+    for (name, checksum) in schemas {
+        expected.push((format!("schemas/{}", name), checksum.clone()));
+    }
+
+    let mut report = VerifyReport {
+        snapshot_id: snapshot_id.to_string(),
+        ok: false,
+        files_checked: 0,
+        mismatches: Vec::new(),
+        missing: Vec::new(),
+    };
+    for (file, checksum) in expected {
+        let path = dir.join(&file);
+        if !path.is_file() {
+            report.missing.push(file);
+            continue;
+        }
+        let actual = format_checksum(compute_file_checksum(&path)?);
+        report.files_checked += 1;
+        if actual != checksum {
+            report.mismatches.push(ChecksumMismatch {
+                file,
+                expected: checksum,
+                actual,
+            });
+        }
+    }
+    report.ok = report.mismatches.is_empty() && report.missing.is_empty();
+    Ok(report)
+}
+
+/// Completed snapshots in `data_dir`, oldest first.
+///
+/// A snapshot is complete once its manifest has been written; directories
+/// without one (in progress or abandoned) are skipped.
+pub(super) fn list_impl(data_dir: &Path) -> SnapshotResult<Vec<SnapshotId>> {
+    let dir = snapshots_dir(data_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| SnapshotError::io_error_at_path(&dir, e))? {
+        let entry = entry.map_err(|e| SnapshotError::io_error_at_path(&dir, e))?;
+        if entry.path().join("manifest.json").is_file() {
+            ids.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    // IDs are RFC3339 basic timestamps, so lexical order is creation order
+    ids.sort();
+    Ok(ids)
+}
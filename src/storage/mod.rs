@@ -29,4 +29,4 @@ pub use checksum::compute_checksum;
 pub use errors::{StorageError, StorageResult};
 pub use reader::StorageReader;
 pub use record::{DocumentRecord, StoragePayload};
-pub use writer::StorageWriter;
+pub use writer::{CollectionUsage, PreparedRecord, StorageWriter};
@@ -182,7 +182,16 @@ impl StorageReader {
     /// Reads a single record at the specified offset.
     ///
     /// Validates checksum. Returns AERO_DATA_CORRUPTION if invalid.
+    ///
+    /// The file length is re-read first, so records appended since the
+    /// reader was opened (offsets handed out by the index) can be read.
     pub fn read_at(&mut self, offset: u64) -> StorageResult<DocumentRecord> {
+        self.file_size = self
+            .reader
+            .get_ref()
+            .metadata()
+            .map_err(|e| StorageError::read_failed("Failed to read file metadata", e))?
+            .len();
         self.seek_to(offset)?;
         match self.read_next()? {
             Some(record) => Ok(record),
@@ -245,6 +254,32 @@ impl StorageReader {
 
         Ok(map)
     }
+
+    /// Builds the document map as it stood when the file ended at `end`.
+    ///
+    /// Records at or after `end` were appended later and are ignored, so
+    /// this is a read of the snapshot taken at that offset.
+    pub fn build_document_map_before(
+        &mut self,
+        end: u64,
+    ) -> StorageResult<std::collections::HashMap<String, DocumentRecord>> {
+        use std::collections::HashMap;
+
+        self.reset()?;
+
+        let mut map: HashMap<String, DocumentRecord> = HashMap::new();
+
+        while self.current_offset < end {
+            match self.read_next()? {
+                Some(record) => {
+                    map.insert(record.document_id.clone(), record);
+                }
+                None => break,
+            }
+        }
+
+        Ok(map)
+    }
 }
 
 #[cfg(test)]
@@ -417,6 +452,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_document_map_before_offset() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let second_offset;
+        {
+            let mut writer = StorageWriter::open(temp_dir.path()).unwrap();
+            writer.write(&create_test_payload("doc1")).unwrap();
+            second_offset = writer.write(&create_test_payload("doc2")).unwrap();
+            writer
+                .write_tombstone("test_collection", "doc1", "test_schema", "v1")
+                .unwrap();
+        }
+
+        let storage_path = temp_dir.path().join("data").join("documents.dat");
+        let mut reader = StorageReader::open(&storage_path).unwrap();
+
+        // The snapshot at the second record holds only the first
+        let map = reader.build_document_map_before(second_offset).unwrap();
+        assert_eq!(map.len(), 1);
+        assert!(!map.get("test_collection:doc1").unwrap().is_tombstone);
+
+        let map = reader.build_document_map_before(u64::MAX).unwrap();
+        assert_eq!(map.len(), 2);
+        assert!(map.get("test_collection:doc1").unwrap().is_tombstone);
+    }
+
     #[test]
     fn test_read_at_offset() {
         let temp_dir = TempDir::new().unwrap();
@@ -6,15 +6,96 @@
 //! - Operation must not be acknowledged unless storage write completes
 //!
 //! The storage is append-only with no in-place updates (§6.1).
+//!
+//! A record whose write or fsync fails is cut back off the file, so record
+//! offsets stay where the in-memory index expects them.
+//!
+//! The writer also keeps per-collection usage counters (bytes and records
+//! in the file, last write time), so dashboards never scan the file.
 
-use std::collections::HashMap;
-use std::fs::{self, File, OpenOptions};
-use std::io::Write;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 
 use super::errors::{StorageError, StorageResult};
 use super::record::{DocumentRecord, StoragePayload};
-use crate::wal::WalRecord;
+use crate::crash_point::{maybe_crash, points};
+use crate::storage_io::{StorageFile, StorageIo};
+use crate::wal::{RecordType, WalRecord};
+
+/// Space one collection takes in the storage file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CollectionUsage {
+    /// Documents whose latest record is not a tombstone
+    pub documents: u64,
+    /// Bytes of the collection's records, superseded versions and
+    /// tombstones included
+    pub bytes: u64,
+    /// Records of the collection in the file
+    pub records: u64,
+    /// When this writer last wrote a record of the collection; `None`
+    /// until the first write after opening
+    pub last_write: Option<DateTime<Utc>>,
+}
+
+/// Usage by collection (schema id), and the collection of every live
+/// document so a later record can move it
+#[derive(Debug, Default)]
+struct UsageCounters {
+    collections: BTreeMap<String, CollectionUsage>,
+    live: HashMap<String, String>,
+}
+
+impl UsageCounters {
+    /// Count `record`, `len` bytes long, as the document's latest record
+    fn count(&mut self, record: &DocumentRecord, len: u64) -> &mut CollectionUsage {
+        if let Some(previous) = self.live.remove(&record.document_id) {
+            if let Some(usage) = self.collections.get_mut(&previous) {
+                usage.documents = usage.documents.saturating_sub(1);
+            }
+        }
+        let usage = self
+            .collections
+            .entry(record.schema_id.clone())
+            .or_default();
+        usage.bytes += len;
+        usage.records += 1;
+        if !record.is_tombstone {
+            usage.documents += 1;
+            self.live
+                .insert(record.document_id.clone(), record.schema_id.clone());
+        }
+        usage
+    }
+}
+
+/// A record encoded for the storage file, not yet written
+#[derive(Debug, Clone)]
+pub struct PreparedRecord {
+    record: DocumentRecord,
+    serialized: Vec<u8>,
+}
+
+impl PreparedRecord {
+    /// Encodes the record a WAL record puts in storage
+    pub fn from_wal_record(wal_record: &WalRecord) -> Self {
+        Self::from_payload(&StoragePayload::from_wal_record(wal_record))
+    }
+
+    fn from_payload(payload: &StoragePayload) -> Self {
+        Self::from_record(DocumentRecord::from_payload(payload))
+    }
+
+    fn from_record(record: DocumentRecord) -> Self {
+        let serialized = record.serialize();
+        Self { record, serialized }
+    }
+}
 
 /// Storage writer that maintains the documents.dat file.
 ///
@@ -24,12 +105,20 @@ pub struct StorageWriter {
     /// Path to the storage file
     storage_path: PathBuf,
     /// Underlying file handle
-    file: File,
+    file: Box<dyn StorageFile>,
+    /// A failed write left bytes that could not be cut off the file
+    torn_tail: bool,
     /// Current file offset (for tracking)
     current_offset: u64,
     /// In-memory index of document_id -> latest offset (for lookups)
     /// This is rebuilt on startup and maintained during writes
     document_offsets: HashMap<String, u64>,
+    /// Usage by collection (schema id), rebuilt on startup with the offsets
+    usage: UsageCounters,
+    /// Writes leave the fsync to [`StorageWriter::sync_deferred`]
+    deferred_sync: bool,
+    /// Records written since the last fsync, when syncs are deferred
+    unsynced: bool,
 }
 
 impl StorageWriter {
@@ -78,26 +167,34 @@ impl StorageWriter {
             .len();
 
         // Build in-memory index by scanning existing records
-        let document_offsets = Self::build_offset_index(&storage_path)?;
+        let (document_offsets, usage) = Self::build_offset_index(&storage_path)?;
 
         Ok(Self {
             storage_path,
-            file,
+            file: Box::new(file),
+            torn_tail: false,
             current_offset,
             document_offsets,
+            usage,
+            deferred_sync: false,
+            unsynced: false,
         })
     }
 
-    /// Builds the in-memory offset index by scanning the storage file.
-    fn build_offset_index(storage_path: &Path) -> StorageResult<HashMap<String, u64>> {
+    /// Builds the in-memory offset index and the collection usage by
+    /// scanning the storage file.
+    fn build_offset_index(
+        storage_path: &Path,
+    ) -> StorageResult<(HashMap<String, u64>, UsageCounters)> {
         use super::reader::StorageReader;
 
         let mut offsets = HashMap::new();
+        let mut usage = UsageCounters::default();
 
-        // If file doesn't exist or is empty, return empty map
+        // If file doesn't exist or is empty, return empty maps
         let metadata = match fs::metadata(storage_path) {
             Ok(m) => m,
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(offsets),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((offsets, usage)),
             Err(e) => {
                 return Err(StorageError::read_failed(
                     "Failed to read storage metadata",
@@ -107,7 +204,7 @@ impl StorageWriter {
         };
 
         if metadata.len() == 0 {
-            return Ok(offsets);
+            return Ok((offsets, usage));
         }
 
         // Scan all records to build index
@@ -116,15 +213,32 @@ impl StorageWriter {
             let offset = reader.current_offset();
             match reader.read_next() {
                 Ok(Some(record)) => {
+                    usage.count(&record, reader.current_offset() - offset);
                     // Latest record wins (by file order)
-                    offsets.insert(record.document_id.clone(), offset);
+                    offsets.insert(record.document_id, offset);
                 }
                 Ok(None) => break,
                 Err(e) => return Err(e),
             }
         }
 
-        Ok(offsets)
+        Ok((offsets, usage))
+    }
+
+    /// Appends through a file opened by `io` from now on.
+    ///
+    /// Used by tests to inject I/O faults.
+    pub fn with_io(mut self, io: Arc<dyn StorageIo>) -> StorageResult<Self> {
+        self.file = io.open_append(&self.storage_path).map_err(|e| {
+            StorageError::write_failed(
+                format!(
+                    "Failed to open storage file: {}",
+                    self.storage_path.display()
+                ),
+                e,
+            )
+        })?;
+        Ok(self)
     }
 
     /// Returns the path to the storage file.
@@ -142,6 +256,17 @@ impl StorageWriter {
         self.document_offsets.len()
     }
 
+    /// Returns the usage of a collection, if the file holds any of its
+    /// records.
+    pub fn collection_usage(&self, collection: &str) -> Option<&CollectionUsage> {
+        self.usage.collections.get(collection)
+    }
+
+    /// Returns the usage of every collection in the file, by name.
+    pub fn collections_usage(&self) -> &BTreeMap<String, CollectionUsage> {
+        &self.usage.collections
+    }
+
     /// Writes a document record to storage with fsync enforcement.
     ///
     /// # Arguments
@@ -154,41 +279,95 @@ impl StorageWriter {
     ///
     /// # Errors
     ///
-    /// Returns `AERO_STORAGE_WRITE_FAILED` if write or fsync fails.
+    /// Returns `AERO_STORAGE_WRITE_FAILED` if write or fsync fails. The
+    /// record is then removed from the file; if it cannot be, every later
+    /// write fails too, until the storage is reopened.
     pub fn write(&mut self, payload: &StoragePayload) -> StorageResult<u64> {
-        let record = DocumentRecord::from_payload(payload);
-        let serialized = record.serialize();
+        self.append(&PreparedRecord::from_payload(payload))
+    }
+
+    /// Appends an encoded record, with fsync, as [`Self::write`] does.
+    fn append(&mut self, prepared: &PreparedRecord) -> StorageResult<u64> {
+        let PreparedRecord { record, serialized } = prepared;
         let offset = self.current_offset;
 
+        if self.torn_tail {
+            return Err(StorageError::write_failed(
+                "Storage ends in a partial record; reopen storage to discard it",
+                io::Error::other("torn storage tail"),
+            ));
+        }
+
         // Write to file
-        self.file.write_all(&serialized).map_err(|e| {
-            StorageError::write_failed(
+        if let Err(e) = self.file.append(serialized) {
+            self.discard_from(offset);
+            return Err(StorageError::write_failed(
                 format!("Failed to write document: {}", record.document_id),
                 e,
-            )
-        })?;
+            ));
+        }
 
-        // fsync - mandatory for durability
-        self.file.sync_all().map_err(|e| {
-            StorageError::write_failed(
+        // fsync - mandatory for durability, here or in sync_deferred
+        if self.deferred_sync {
+            self.unsynced = true;
+        } else if let Err(e) = self.file.sync_all() {
+            self.discard_from(offset);
+            return Err(StorageError::write_failed(
                 format!(
                     "fsync failed after writing document: {}",
                     record.document_id
                 ),
                 e,
-            )
-        })?;
+            ));
+        }
 
         // Update offset tracking
         self.current_offset += serialized.len() as u64;
+        self.usage.count(record, serialized.len() as u64).last_write = Some(Utc::now());
 
         // Update in-memory index (latest record wins)
         self.document_offsets
             .insert(record.document_id.clone(), offset);
 
+        // Durable in storage but not yet reflected in the caller's indexes
+        maybe_crash(points::STORAGE_AFTER_WRITE);
+
         Ok(offset)
     }
 
+    /// Leaves the fsync of every write to [`StorageWriter::sync_deferred`].
+    ///
+    /// For group commit: the WAL records of the writes are synced first, so
+    /// storage never becomes durable ahead of the WAL.
+    pub fn with_deferred_sync(mut self) -> Self {
+        self.deferred_sync = true;
+        self
+    }
+
+    /// Fsyncs the records written since the last fsync, if any.
+    ///
+    /// # Errors
+    ///
+    /// `AERO_STORAGE_WRITE_FAILED` if the fsync fails.
+    pub fn sync_deferred(&mut self) -> StorageResult<()> {
+        if !self.unsynced {
+            return Ok(());
+        }
+        self.file.sync_all().map_err(|e| {
+            StorageError::write_failed(
+                format!("fsync of {} failed", self.storage_path.display()),
+                e,
+            )
+        })?;
+        self.unsynced = false;
+        Ok(())
+    }
+
+    /// Cuts off whatever a failed write left past `offset`.
+    fn discard_from(&mut self, offset: u64) {
+        self.torn_tail = self.file.set_len(offset).is_err();
+    }
+
     /// Writes a tombstone (DELETE) record.
     ///
     /// Tombstones are preserved forever in Phase 0.
@@ -214,9 +393,80 @@ impl StorageWriter {
     /// This operation is idempotent: applying the same WAL record twice
     /// results in the same final state (the later write is simply appended,
     /// and latest record wins during reads).
+    ///
+    /// A COLLECTION_RENAME record moves the collection's documents with
+    /// [`Self::rename_collection`]; a COLLECTION_COPY record writes nothing,
+    /// the inserts following it do. Both return the end of the file.
     pub fn apply_wal_record(&mut self, wal_record: &WalRecord) -> StorageResult<u64> {
-        let payload = StoragePayload::from_wal_record(wal_record);
-        self.write(&payload)
+        if let Some((from, to)) = wal_record.collection_change() {
+            if wal_record.record_type == RecordType::CollectionRename {
+                self.rename_collection(&wal_record.payload.collection_id, from, to)?;
+                maybe_crash(points::STORAGE_AFTER_APPLY);
+            }
+            return Ok(self.current_offset);
+        }
+        self.apply_prepared(&PreparedRecord::from_wal_record(wal_record))
+    }
+
+    /// Applies a WAL record encoded beforehand with
+    /// [`PreparedRecord::from_wal_record`].
+    ///
+    /// Encoding does not touch the file, so parallel replay encodes records
+    /// on several threads and appends them here in WAL order: the file is
+    /// the one [`Self::apply_wal_record`] would produce.
+    pub fn apply_prepared(&mut self, prepared: &PreparedRecord) -> StorageResult<u64> {
+        let offset = self.append(prepared)?;
+
+        // Applied, but the caller has not yet recorded replay progress
+        maybe_crash(points::STORAGE_AFTER_APPLY);
+
+        Ok(offset)
+    }
+
+    /// Moves every live document of `collection_id` stored under schema
+    /// `from` to schema `to`, by appending its latest record again under
+    /// the new schema id, in document id order.
+    ///
+    /// Returns each record written with its offset. A rename already
+    /// partly applied moves the documents left under `from`, so replaying
+    /// the rename finishes it.
+    pub fn rename_collection(
+        &mut self,
+        collection_id: &str,
+        from: &str,
+        to: &str,
+    ) -> StorageResult<Vec<(DocumentRecord, u64)>> {
+        use super::reader::StorageReader;
+
+        let prefix = format!("{}:", collection_id);
+        let mut moving: Vec<(&String, u64)> = self
+            .usage
+            .live
+            .iter()
+            .filter(|(id, schema_id)| id.starts_with(&prefix) && schema_id.as_str() == from)
+            .filter_map(|(id, _)| Some((id, *self.document_offsets.get(id)?)))
+            .collect();
+        moving.sort();
+        if moving.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut reader = StorageReader::open(&self.storage_path)?;
+        let records = moving
+            .into_iter()
+            .map(|(_, offset)| reader.read_at(offset))
+            .collect::<StorageResult<Vec<_>>>()?;
+        let mut moved = Vec::with_capacity(records.len());
+        for mut record in records {
+            if moved.len() == 1 {
+                // Some documents moved, the others not yet
+                maybe_crash(points::COLLECTION_RENAME_MID_APPLY);
+            }
+            record.schema_id = to.to_string();
+            let offset = self.append(&PreparedRecord::from_record(record.clone()))?;
+            moved.push((record, offset));
+        }
+        Ok(moved)
     }
 
     /// Returns the offset for a document, if it exists.
@@ -437,4 +687,131 @@ mod tests {
             writer.write(&create_test_payload("doc3")).unwrap();
         }
     }
+
+    #[test]
+    fn test_collection_usage_maintained_and_rebuilt() {
+        let temp_dir = TempDir::new().unwrap();
+        let written = {
+            let mut writer = StorageWriter::open(temp_dir.path()).unwrap();
+            writer.write(&create_test_payload("doc1")).unwrap();
+            writer.write(&create_test_payload("doc1")).unwrap();
+            writer
+                .write_tombstone("test_collection", "doc1", "test_schema", "v1")
+                .unwrap();
+
+            writer.write(&create_test_payload("doc2")).unwrap();
+            writer.write(&create_test_payload("doc3")).unwrap();
+
+            // A document moved to another collection, as a rename does
+            let moved = StoragePayload::new(
+                "test_collection",
+                "doc3",
+                "renamed_schema",
+                "v1",
+                br#"{"id": "doc3"}"#.to_vec(),
+            );
+            writer.write(&moved).unwrap();
+            assert_eq!(
+                writer.collection_usage("renamed_schema").unwrap().documents,
+                1
+            );
+
+            let usage = writer.collection_usage("test_schema").unwrap().clone();
+            assert_eq!(usage.documents, 1);
+            assert_eq!(usage.records, 5);
+            let renamed = writer.collection_usage("renamed_schema").unwrap().bytes;
+            assert_eq!(usage.bytes + renamed, writer.current_offset());
+            assert!(usage.last_write.is_some());
+            usage
+        };
+
+        let writer = StorageWriter::open(temp_dir.path()).unwrap();
+        let usage = writer.collection_usage("test_schema").unwrap();
+        assert_eq!(usage.documents, written.documents);
+        assert_eq!(usage.records, written.records);
+        assert_eq!(usage.bytes, written.bytes);
+        assert!(usage.last_write.is_none());
+        assert!(writer.collection_usage("other").is_none());
+    }
+
+    #[test]
+    fn test_rename_collection_moves_live_documents() {
+        use super::super::reader::StorageReader;
+        use crate::wal::WalPayload;
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = StorageWriter::open(temp_dir.path()).unwrap();
+        writer.write(&create_test_payload("doc2")).unwrap();
+        writer.write(&create_test_payload("doc1")).unwrap();
+        writer.write(&create_test_payload("doc3")).unwrap();
+        writer
+            .write_tombstone("test_collection", "doc3", "test_schema", "v1")
+            .unwrap();
+        let other = StoragePayload::new("other", "doc4", "test_schema", "v1", b"{}".to_vec());
+        writer.write(&other).unwrap();
+
+        let record = WalRecord::new(
+            RecordType::CollectionRename,
+            9,
+            WalPayload::collection_change("test_collection", "test_schema", "renamed"),
+        );
+        let end = writer.apply_wal_record(&record).unwrap();
+        assert_eq!(end, writer.current_offset());
+
+        let usage = writer.collection_usage("renamed").unwrap();
+        assert_eq!(usage.documents, 2);
+        // Only the other collection's document is left under the old name
+        assert_eq!(writer.collection_usage("test_schema").unwrap().documents, 1);
+
+        let mut reader = StorageReader::open(writer.path()).unwrap();
+        let offset = writer.get_document_offset("test_collection:doc1").unwrap();
+        let moved = reader.read_at(offset).unwrap();
+        assert_eq!(moved.schema_id, "renamed");
+        assert_eq!(moved.document_body, br#"{"id": "doc1"}"#.to_vec());
+
+        // Replaying the rename moves nothing more
+        let again = writer.rename_collection("test_collection", "test_schema", "renamed");
+        assert!(again.unwrap().is_empty());
+        assert_eq!(writer.current_offset(), end);
+
+        // A copy record writes nothing
+        let copy = WalRecord::new(
+            RecordType::CollectionCopy,
+            10,
+            WalPayload::collection_change("test_collection", "renamed", "copied"),
+        );
+        assert_eq!(writer.apply_wal_record(&copy).unwrap(), end);
+    }
+
+    #[test]
+    fn test_failed_write_is_refused_and_cut_off() {
+        use super::super::errors::StorageErrorCode;
+        use super::super::reader::StorageReader;
+        use crate::storage_io::FaultyFs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let fs = FaultyFs::new();
+        let mut writer = StorageWriter::open(temp_dir.path())
+            .unwrap()
+            .with_io(Arc::new(fs.clone()))
+            .unwrap();
+        writer.write(&create_test_payload("doc1")).unwrap();
+        let clean_len = writer.current_offset();
+
+        fs.limit_bytes(8);
+        let err = writer.write(&create_test_payload("doc2")).unwrap_err();
+        assert_eq!(err.code(), StorageErrorCode::AeroStorageWriteFailed);
+        assert_eq!(writer.current_offset(), clean_len);
+        assert!(!writer.has_document("test_collection:doc2"));
+
+        // The next record lands where the index says it does
+        fs.heal();
+        let offset = writer.write(&create_test_payload("doc2")).unwrap();
+        assert_eq!(offset, clean_len);
+
+        let mut reader = StorageReader::open(writer.path()).unwrap();
+        let record = reader.read_at(offset).unwrap();
+        assert_eq!(record.document_id, "test_collection:doc2");
+        assert_eq!(reader.read_all().unwrap().len(), 0);
+    }
 }
@@ -0,0 +1,272 @@
+//! File I/O seam for the WAL and storage writers
+//!
+//! `WalWriter` and `StorageWriter` append and sync through a
+//! [`StorageFile`] opened by a [`StorageIo`]. Production uses [`OsIo`],
+//! which opens plain files. Tests swap in [`FaultyFs`] to make writes fail
+//! deterministically, the way a full or failing disk would.
+//!
+//! # Usage
+//!
+//! ```ignore
+//! use aerodb::storage_io::{Fault, FaultyFs};
+//!
+//! let fs = FaultyFs::new();
+//! let mut wal = WalWriter::open(data_dir)?.with_io(Arc::new(fs.clone()))?;
+//! fs.fail_nth_write(1, Fault::Io);
+//! assert!(wal.append(RecordType::Insert, payload).is_err());
+//! ```
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// An open file that records are appended to
+pub trait StorageFile: Send + Sync + fmt::Debug {
+    /// Write all of `buf` at the end of the file
+    ///
+    /// On error, part of `buf` may have been written.
+    fn append(&mut self, buf: &[u8]) -> io::Result<()>;
+
+    /// Flush data and metadata to disk
+    fn sync_all(&self) -> io::Result<()>;
+
+    /// Flush data to disk
+    fn sync_data(&self) -> io::Result<()>;
+
+    /// Truncate or extend the file to `len` bytes
+    fn set_len(&self, len: u64) -> io::Result<()>;
+
+    /// Current length of the file in bytes
+    fn size(&self) -> io::Result<u64>;
+}
+
+impl StorageFile for File {
+    fn append(&mut self, buf: &[u8]) -> io::Result<()> {
+        Write::write_all(self, buf)
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        File::sync_all(self)
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        File::sync_data(self)
+    }
+
+    fn set_len(&self, len: u64) -> io::Result<()> {
+        File::set_len(self, len)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+/// Opens the files the writers append to
+pub trait StorageIo: Send + Sync + fmt::Debug {
+    /// Open an existing file for appending
+    fn open_append(&self, path: &Path) -> io::Result<Box<dyn StorageFile>>;
+}
+
+/// The operating system's filesystem
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsIo;
+
+impl StorageIo for OsIo {
+    fn open_append(&self, path: &Path) -> io::Result<Box<dyn StorageFile>> {
+        let file = OpenOptions::new().append(true).open(path)?;
+        Ok(Box::new(file))
+    }
+}
+
+/// Error returned by a failing write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// `ENOSPC`: no space left on device
+    DiskFull,
+    /// `EIO`: input/output error
+    Io,
+}
+
+impl Fault {
+    fn error(self) -> io::Error {
+        match self {
+            Fault::DiskFull => io::Error::from_raw_os_error(libc::ENOSPC),
+            Fault::Io => io::Error::from_raw_os_error(libc::EIO),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct FaultState {
+    /// Writes attempted through any file
+    writes: u64,
+    /// Bytes written through any file
+    bytes: u64,
+    /// Attempt number that fails, and how
+    fail_at: Option<(u64, Fault)>,
+    /// Value of `bytes` past which writes fail with `ENOSPC`
+    byte_limit: Option<u64>,
+}
+
+/// Test filesystem that fails writes on demand
+///
+/// Files are real files on disk; only writes are intercepted. Faults are
+/// counted across every file opened through the same `FaultyFs` (clones
+/// share state), so a test can arm a fault after setting up and know
+/// exactly which write it hits:
+///
+/// - [`FaultyFs::fail_nth_write`] fails one write outright; nothing of it
+///   reaches the file
+/// - [`FaultyFs::limit_bytes`] models a disk filling up: the write that
+///   crosses the budget is cut short, then fails with `ENOSPC`, as does
+///   every later write until [`FaultyFs::heal`]
+#[derive(Debug, Clone, Default)]
+pub struct FaultyFs {
+    state: Arc<Mutex<FaultState>>,
+}
+
+impl FaultyFs {
+    /// A filesystem with no faults armed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail the `n`th write from now (1 is the next write)
+    pub fn fail_nth_write(&self, n: u64, fault: Fault) {
+        let mut state = self.state.lock().unwrap();
+        state.fail_at = Some((state.writes + n.max(1), fault));
+    }
+
+    /// Let only `bytes` more bytes be written
+    pub fn limit_bytes(&self, bytes: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.byte_limit = Some(state.bytes + bytes);
+    }
+
+    /// Disarm every fault, as when space is freed
+    pub fn heal(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.fail_at = None;
+        state.byte_limit = None;
+    }
+
+    /// Writes attempted so far
+    pub fn writes(&self) -> u64 {
+        self.state.lock().unwrap().writes
+    }
+
+    /// Bytes written so far
+    pub fn bytes_written(&self) -> u64 {
+        self.state.lock().unwrap().bytes
+    }
+}
+
+impl StorageIo for FaultyFs {
+    fn open_append(&self, path: &Path) -> io::Result<Box<dyn StorageFile>> {
+        let file = OpenOptions::new().append(true).open(path)?;
+        Ok(Box::new(FaultyFile {
+            file,
+            state: Arc::clone(&self.state),
+        }))
+    }
+}
+
+/// A file opened through [`FaultyFs`]
+#[derive(Debug)]
+struct FaultyFile {
+    file: File,
+    state: Arc<Mutex<FaultState>>,
+}
+
+impl StorageFile for FaultyFile {
+    fn append(&mut self, buf: &[u8]) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.writes += 1;
+
+        if let Some((at, fault)) = state.fail_at {
+            if state.writes == at {
+                state.fail_at = None;
+                return Err(fault.error());
+            }
+        }
+
+        let allowed = match state.byte_limit {
+            Some(limit) => limit.saturating_sub(state.bytes).min(buf.len() as u64) as usize,
+            None => buf.len(),
+        };
+        Write::write_all(&mut self.file, &buf[..allowed])?;
+        state.bytes += allowed as u64;
+
+        if allowed < buf.len() {
+            return Err(Fault::DiskFull.error());
+        }
+        Ok(())
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        self.file.sync_all()
+    }
+
+    fn sync_data(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    fn set_len(&self, len: u64) -> io::Result<()> {
+        self.file.set_len(len)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.file.metadata()?.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn open(faulty: &FaultyFs, temp: &TempDir) -> Box<dyn StorageFile> {
+        let path = temp.path().join("file");
+        fs::write(&path, b"").unwrap();
+        faulty.open_append(&path).unwrap()
+    }
+
+    #[test]
+    fn test_nth_write_fails_without_writing() {
+        let temp = TempDir::new().unwrap();
+        let faulty = FaultyFs::new();
+        let mut file = open(&faulty, &temp);
+
+        faulty.fail_nth_write(2, Fault::Io);
+        file.append(b"one").unwrap();
+        let err = file.append(b"two").unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EIO));
+        file.append(b"three").unwrap();
+
+        assert_eq!(faulty.writes(), 3);
+        assert_eq!(fs::read(temp.path().join("file")).unwrap(), b"onethree");
+    }
+
+    #[test]
+    fn test_byte_budget_short_writes_then_stays_full() {
+        let temp = TempDir::new().unwrap();
+        let faulty = FaultyFs::new();
+        let mut file = open(&faulty, &temp);
+
+        faulty.limit_bytes(5);
+        file.append(b"abc").unwrap();
+        let err = file.append(b"defg").unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOSPC));
+        assert!(file.append(b"h").is_err());
+        assert_eq!(file.size().unwrap(), 5);
+
+        faulty.heal();
+        file.append(b"h").unwrap();
+        assert_eq!(fs::read(temp.path().join("file")).unwrap(), b"abcdeh");
+        assert_eq!(faulty.bytes_written(), 6);
+    }
+}
@@ -0,0 +1,117 @@
+//! Collection export
+
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{TransferError, TransferFormat, TransferResult};
+use crate::api::{ApiHandler, Subsystems};
+use crate::schema::Schema;
+
+/// What to export
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportOptions {
+    pub collection: String,
+    /// Schema version whose fields are the CSV columns
+    pub schema_version: String,
+    pub format: TransferFormat,
+    /// Query filter; None exports every live document
+    #[serde(default)]
+    pub filter: Option<Value>,
+}
+
+impl ExportOptions {
+    pub fn new(
+        collection: impl Into<String>,
+        schema_version: impl Into<String>,
+        format: TransferFormat,
+    ) -> Self {
+        Self {
+            collection: collection.into(),
+            schema_version: schema_version.into(),
+            format,
+            filter: None,
+        }
+    }
+
+    /// Export only documents matching `filter`
+    pub fn with_filter(mut self, filter: Value) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+}
+
+/// Outcome of an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ExportReport {
+    pub exported: usize,
+}
+
+/// Write the collection's live documents to `out`, in primary key order
+pub fn export(
+    options: &ExportOptions,
+    handler: &ApiHandler,
+    sys: &mut Subsystems<'_>,
+    out: impl Write,
+) -> TransferResult<ExportReport> {
+    let schema = sys
+        .schema_loader
+        .get(&options.collection, &options.schema_version)
+        .cloned()
+        .ok_or_else(|| {
+            TransferError::invalid(format!(
+                "Unknown schema {} {}",
+                options.collection, options.schema_version
+            ))
+        })?;
+    let filter = options.filter.as_ref();
+
+    let exported = match options.format {
+        TransferFormat::Jsonl => {
+            let mut out = out;
+            let exported = handler.scan(&options.collection, filter, sys, |doc| {
+                serde_json::to_writer(&mut out, &doc)?;
+                out.write_all(b"\n").map_err(TransferError::from)
+            })?;
+            out.flush()?;
+            exported
+        }
+        TransferFormat::Csv => {
+            let columns = csv_columns(&schema);
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(&columns)?;
+            let exported = handler.scan(&options.collection, filter, sys, |doc| {
+                let cells = columns.iter().map(|column| csv_cell(doc.get(column)));
+                writer.write_record(cells).map_err(TransferError::from)
+            })?;
+            writer.flush()?;
+            exported
+        }
+    };
+
+    Ok(ExportReport { exported })
+}
+
+/// `_id`, then the schema's other fields by name
+pub(super) fn csv_columns(schema: &Schema) -> Vec<String> {
+    let mut columns: Vec<String> = schema
+        .fields
+        .keys()
+        .filter(|field| *field != "_id")
+        .cloned()
+        .collect();
+    columns.sort();
+    columns.insert(0, "_id".to_string());
+    columns
+}
+
+/// A field as a CSV cell: strings as they are, nested values as JSON, and
+/// a missing or null field as an empty cell
+fn csv_cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
@@ -0,0 +1,774 @@
+//! Collection import
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::{TransferError, TransferFormat, TransferResult};
+use crate::api::{ApiError, ApiHandler, ErrorResponse, Response, Subsystems};
+use crate::schema::{FieldType, Schema, SchemaValidator};
+
+/// Default number of writes committed per transaction
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// How imported rows are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Insert every row; a row whose `_id` exists is rejected
+    #[default]
+    Insert,
+    /// Insert new rows and replace the documents of existing ones
+    Upsert,
+}
+
+impl FromStr for ImportMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "insert" => Ok(Self::Insert),
+            "upsert" => Ok(Self::Upsert),
+            other => Err(format!(
+                "Unknown mode '{}' (expected insert or upsert)",
+                other
+            )),
+        }
+    }
+}
+
+/// What to import, and how
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportOptions {
+    pub collection: String,
+    /// Schema version rows are validated against and written with
+    pub schema_version: String,
+    pub format: TransferFormat,
+    #[serde(default)]
+    pub mode: ImportMode,
+    /// Writes committed per transaction
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Stop at the first rejected row instead of reporting it and going on
+    #[serde(default)]
+    pub abort_on_error: bool,
+    /// CSV header to field name, for headers that are not field names
+    #[serde(default)]
+    pub column_map: BTreeMap<String, String>,
+}
+
+fn default_batch_size() -> usize {
+    DEFAULT_BATCH_SIZE
+}
+
+impl ImportOptions {
+    pub fn new(
+        collection: impl Into<String>,
+        schema_version: impl Into<String>,
+        format: TransferFormat,
+    ) -> Self {
+        Self {
+            collection: collection.into(),
+            schema_version: schema_version.into(),
+            format,
+            mode: ImportMode::default(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            abort_on_error: false,
+            column_map: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: ImportMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn with_abort_on_error(mut self, abort_on_error: bool) -> Self {
+        self.abort_on_error = abort_on_error;
+        self
+    }
+
+    /// Read the CSV column `header` into `field`
+    pub fn with_column(mut self, header: impl Into<String>, field: impl Into<String>) -> Self {
+        self.column_map.insert(header.into(), field.into());
+        self
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Rows read from the input (blank JSON Lines lines are not rows)
+    pub read: usize,
+    pub inserted: usize,
+    pub updated: usize,
+    pub rejected: usize,
+}
+
+/// A row that was not written, as reported to the rejects stream (one
+/// JSON object per line)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RejectedRow {
+    /// Line of the input the row starts on
+    pub line: u64,
+    pub code: String,
+    pub message: String,
+    /// The line as read (JSON Lines) or the row's cells by header (CSV)
+    pub row: Value,
+}
+
+/// Import rows from `input` into the collection through `handler`
+///
+/// Every row is checked against the schema, and in insert mode against
+/// existing documents, before it joins a batch; each batch of
+/// `batch_size` rows is committed as one transaction. Rejected rows are
+/// written to `rejects`. If a batch fails to commit, its rows are retried
+/// one at a time so only the failing rows are rejected.
+///
+/// With `abort_on_error` the import stops with
+/// [`TransferError::RowRejected`] at the first rejected row; batches
+/// committed before it stay written, the rows of its own batch are not.
+pub fn import(
+    options: &ImportOptions,
+    handler: &ApiHandler,
+    sys: &mut Subsystems<'_>,
+    input: impl Read,
+    mut rejects: impl Write,
+) -> TransferResult<ImportReport> {
+    if options.batch_size == 0 {
+        return Err(TransferError::invalid("batch_size must be at least 1"));
+    }
+    let schema = sys
+        .schema_loader
+        .get(&options.collection, &options.schema_version)
+        .cloned()
+        .ok_or_else(|| {
+            TransferError::invalid(format!(
+                "Unknown schema {} {}",
+                options.collection, options.schema_version
+            ))
+        })?;
+
+    let mut importer = Importer {
+        options,
+        handler,
+        rejects: &mut rejects,
+        report: ImportReport::default(),
+        batch: Vec::new(),
+        batch_ids: HashSet::new(),
+    };
+
+    match options.format {
+        TransferFormat::Jsonl => {
+            for (i, line) in BufReader::new(input).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let parsed = parse_jsonl(&line);
+                importer.push(i as u64 + 1, Value::String(line), parsed, sys)?;
+            }
+        }
+        TransferFormat::Csv => {
+            let mut reader = csv::Reader::from_reader(input);
+            let headers = reader.headers()?.clone();
+            let columns = map_columns(&headers, &schema, &options.column_map)?;
+            for record in reader.records() {
+                let record = match record {
+                    Ok(record) => record,
+                    Err(e) if matches!(e.kind(), csv::ErrorKind::UnequalLengths { .. }) => {
+                        let line = e.position().map_or(0, |p| p.line());
+                        let error = ApiError::invalid_request(e.to_string());
+                        importer.push(line, Value::Null, Err(error), sys)?;
+                        continue;
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                let line = record.position().map_or(0, |p| p.line());
+                let raw: Map<String, Value> = headers
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(header, cell)| (header.to_string(), json!(cell)))
+                    .collect();
+                let parsed = coerce_row(&columns, &record);
+                importer.push(line, Value::Object(raw), parsed, sys)?;
+            }
+        }
+    }
+
+    importer.flush(sys)?;
+    Ok(importer.report)
+}
+
+/// A row checked and waiting for its batch to commit
+struct PendingRow {
+    line: u64,
+    raw: Value,
+    document: Value,
+    update: bool,
+}
+
+struct Importer<'a, W: Write> {
+    options: &'a ImportOptions,
+    handler: &'a ApiHandler,
+    rejects: &'a mut W,
+    report: ImportReport,
+    batch: Vec<PendingRow>,
+    /// `_id`s of the batch, which exist once it commits
+    batch_ids: HashSet<String>,
+}
+
+impl<W: Write> Importer<'_, W> {
+    /// Check a parsed row and add it to the batch, committing the batch
+    /// once it is full
+    fn push(
+        &mut self,
+        line: u64,
+        raw: Value,
+        parsed: Result<Value, ApiError>,
+        sys: &mut Subsystems<'_>,
+    ) -> TransferResult<()> {
+        self.report.read += 1;
+        match parsed.and_then(|document| self.check(document, sys)) {
+            Ok((document, update)) => self.batch.push(PendingRow {
+                line,
+                raw,
+                document,
+                update,
+            }),
+            Err(e) => return self.reject(line, raw, e.code(), e.message()),
+        }
+        if self.batch.len() >= self.options.batch_size {
+            self.flush(sys)?;
+        }
+        Ok(())
+    }
+
+    /// Validate a document, returning it and whether it updates an
+    /// existing document
+    fn check(
+        &mut self,
+        document: Value,
+        sys: &mut Subsystems<'_>,
+    ) -> Result<(Value, bool), ApiError> {
+        let (collection, version) = (&self.options.collection, &self.options.schema_version);
+        SchemaValidator::new(sys.schema_loader)
+            .validate_document(collection, version, &document)
+            .map_err(ApiError::from_schema_error)?;
+        let id = document
+            .get("_id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ApiError::invalid_request("Document missing _id"))?
+            .to_string();
+
+        let exists = self.batch_ids.contains(&id) || self.handler.document_exists(&id, sys)?;
+        if exists && self.options.mode == ImportMode::Insert {
+            return Err(ApiError::invalid_request(format!(
+                "Document already exists: {}",
+                id
+            )));
+        }
+        self.batch_ids.insert(id);
+        Ok((document, exists))
+    }
+
+    /// Commit the batch as one transaction
+    fn flush(&mut self, sys: &mut Subsystems<'_>) -> TransferResult<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let rows = std::mem::take(&mut self.batch);
+        self.batch_ids.clear();
+
+        let begun =
+            execute(self.handler, sys, json!({"op": "begin"})).map_err(|e| api_error(*e))?;
+        let tx_id = begun["tx_id"].clone();
+
+        let mut buffered = Vec::with_capacity(rows.len());
+        for row in rows {
+            match execute(self.handler, sys, self.write_request(&row, Some(&tx_id))) {
+                Ok(_) => buffered.push(row),
+                Err(e) => {
+                    if self.options.abort_on_error {
+                        let _ =
+                            execute(self.handler, sys, json!({"op": "rollback", "tx_id": tx_id}));
+                    }
+                    self.reject(row.line, row.raw, &e.code, &e.message)?;
+                }
+            }
+        }
+
+        match execute(self.handler, sys, json!({"op": "commit", "tx_id": tx_id})) {
+            Ok(_) => {
+                for row in &buffered {
+                    self.count(row);
+                }
+            }
+            Err(e) if self.options.abort_on_error => {
+                let first = buffered.first().map_or(0, |row| row.line);
+                return Err(TransferError::RowRejected {
+                    line: first,
+                    code: e.code,
+                    message: format!("batch starting at this row failed to commit: {}", e.message),
+                });
+            }
+            // Find the rows the batch failed on
+            Err(_) => {
+                for row in buffered {
+                    match execute(self.handler, sys, self.write_request(&row, None)) {
+                        Ok(_) => self.count(&row),
+                        Err(e) => self.reject(row.line, row.raw, &e.code, &e.message)?,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_request(&self, row: &PendingRow, tx_id: Option<&Value>) -> Value {
+        let mut request = json!({
+            "op": if row.update { "update" } else { "insert" },
+            "schema_id": self.options.collection,
+            "schema_version": self.options.schema_version,
+            "document": row.document,
+        });
+        if let Some(tx_id) = tx_id {
+            request["tx_id"] = tx_id.clone();
+        }
+        request
+    }
+
+    fn count(&mut self, row: &PendingRow) {
+        if row.update {
+            self.report.updated += 1;
+        } else {
+            self.report.inserted += 1;
+        }
+    }
+
+    /// Report a row to the rejects stream, failing the import if
+    /// `abort_on_error` is set
+    fn reject(&mut self, line: u64, row: Value, code: &str, message: &str) -> TransferResult<()> {
+        self.report.rejected += 1;
+        let rejected = RejectedRow {
+            line,
+            code: code.to_string(),
+            message: message.to_string(),
+            row,
+        };
+        serde_json::to_writer(&mut *self.rejects, &rejected)?;
+        self.rejects.write_all(b"\n")?;
+
+        if self.options.abort_on_error {
+            self.rejects.flush()?;
+            return Err(TransferError::RowRejected {
+                line,
+                code: rejected.code,
+                message: rejected.message,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn execute(
+    handler: &ApiHandler,
+    sys: &mut Subsystems<'_>,
+    request: Value,
+) -> Result<Value, Box<ErrorResponse>> {
+    match handler.handle(&request.to_string(), sys) {
+        Response::Success(success) => Ok(success.data),
+        Response::Error(e) => Err(Box::new(e)),
+    }
+}
+
+fn api_error(e: ErrorResponse) -> TransferError {
+    TransferError::Api {
+        code: e.code,
+        message: e.message,
+    }
+}
+
+fn parse_jsonl(line: &str) -> Result<Value, ApiError> {
+    match serde_json::from_str(line) {
+        Ok(document @ Value::Object(_)) => Ok(document),
+        Ok(_) => Err(ApiError::invalid_request("Row is not a JSON object")),
+        Err(e) => Err(ApiError::invalid_request(format!("Invalid JSON: {}", e))),
+    }
+}
+
+/// The field and type each CSV column fills
+fn map_columns(
+    headers: &csv::StringRecord,
+    schema: &Schema,
+    column_map: &BTreeMap<String, String>,
+) -> TransferResult<Vec<(String, FieldType)>> {
+    let mut seen = HashSet::new();
+    headers
+        .iter()
+        .map(|header| {
+            let field = column_map.get(header).map_or(header, String::as_str);
+            let def = schema
+                .fields
+                .get(field)
+                .ok_or_else(|| TransferError::UnknownColumn {
+                    column: header.to_string(),
+                })?;
+            if !seen.insert(field) {
+                return Err(TransferError::invalid(format!(
+                    "More than one column fills field '{}'",
+                    field
+                )));
+            }
+            Ok((field.to_string(), def.field_type.clone()))
+        })
+        .collect()
+}
+
+/// Build a document from CSV cells, converting each to its field's type
+fn coerce_row(
+    columns: &[(String, FieldType)],
+    record: &csv::StringRecord,
+) -> Result<Value, ApiError> {
+    let mut document = Map::new();
+    for ((field, field_type), cell) in columns.iter().zip(record.iter()) {
+        if cell.is_empty() {
+            continue;
+        }
+        let value = coerce_cell(cell, field_type).ok_or_else(|| {
+            ApiError::invalid_request(format!(
+                "Column '{}': expected {}, got '{}'",
+                field,
+                field_type.type_name(),
+                cell
+            ))
+        })?;
+        document.insert(field.clone(), value);
+    }
+    Ok(Value::Object(document))
+}
+
+fn coerce_cell(cell: &str, field_type: &FieldType) -> Option<Value> {
+    match field_type {
+        FieldType::String => Some(json!(cell)),
+        FieldType::Int => cell.trim().parse::<i64>().ok().map(Value::from),
+        FieldType::Float => cell
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        FieldType::Bool => match cell.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" => Some(json!(true)),
+            "false" | "0" => Some(json!(false)),
+            _ => None,
+        },
+        FieldType::Object { .. } => serde_json::from_str(cell).ok().filter(Value::is_object),
+        FieldType::Array { .. } => serde_json::from_str(cell).ok().filter(Value::is_array),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admission_control::{AdmissionControlConfig, AdmissionController};
+    use crate::backpressure::{BackpressureConfig, BackpressureManager};
+    use crate::index::IndexManager;
+    use crate::planner::{Statistics, StatisticsConfig};
+    use crate::query_limits::QueryLimitsConfig;
+    use crate::resource_limits::{ResourceLimitsConfig, ResourceManager};
+    use crate::schema::{FieldDef, SchemaLoader};
+    use crate::storage::{StorageReader, StorageWriter};
+    use crate::transfer::{export, ExportOptions};
+    use crate::wal::WalWriter;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    struct Env {
+        _temp: TempDir,
+        loader: SchemaLoader,
+        wal: WalWriter,
+        storage_w: StorageWriter,
+        storage_r: StorageReader,
+        index: IndexManager,
+        statistics: Statistics,
+        rm: ResourceManager,
+        bpm: BackpressureManager,
+        ac: AdmissionController,
+        ql: QueryLimitsConfig,
+        handler: ApiHandler,
+    }
+
+    impl Env {
+        fn new() -> Self {
+            let temp = TempDir::new().unwrap();
+            let data_dir = temp.path();
+
+            let mut loader = SchemaLoader::new(data_dir);
+            let mut fields = HashMap::new();
+            fields.insert("_id".to_string(), FieldDef::required_string());
+            fields.insert("name".to_string(), FieldDef::required_string());
+            fields.insert("age".to_string(), FieldDef::optional_int());
+            fields.insert(
+                "score".to_string(),
+                FieldDef {
+                    field_type: FieldType::Float,
+                    required: false,
+                },
+            );
+            fields.insert(
+                "active".to_string(),
+                FieldDef {
+                    field_type: FieldType::Bool,
+                    required: false,
+                },
+            );
+            fields.insert(
+                "tags".to_string(),
+                FieldDef {
+                    field_type: FieldType::Array {
+                        element_type: Box::new(FieldType::String),
+                    },
+                    required: false,
+                },
+            );
+            loader.register(Schema::new("users", "v1", fields)).unwrap();
+
+            let resource_config = ResourceLimitsConfig {
+                min_free_disk_bytes: 0,
+                ..Default::default()
+            };
+            Self {
+                wal: WalWriter::open(data_dir).unwrap(),
+                storage_w: StorageWriter::open(data_dir).unwrap(),
+                storage_r: StorageReader::open_from_data_dir(data_dir).unwrap(),
+                index: IndexManager::new(HashSet::new()),
+                statistics: Statistics::in_memory(StatisticsConfig::default()),
+                rm: ResourceManager::new(resource_config, data_dir),
+                bpm: BackpressureManager::new(BackpressureConfig::default()),
+                ac: AdmissionController::new(AdmissionControlConfig::default()),
+                ql: QueryLimitsConfig::default(),
+                handler: ApiHandler::new("default"),
+                loader,
+                _temp: temp,
+            }
+        }
+
+        fn with<T>(&mut self, f: impl FnOnce(&ApiHandler, &mut Subsystems<'_>) -> T) -> T {
+            let mut sys = Subsystems {
+                schema_loader: &mut self.loader,
+                wal_writer: &mut self.wal,
+                storage_writer: &mut self.storage_w,
+                storage_reader: &mut self.storage_r,
+                index_manager: &mut self.index,
+                statistics: &mut self.statistics,
+                resource_manager: &self.rm,
+                backpressure_manager: &self.bpm,
+                admission_controller: &self.ac,
+                query_limits: &self.ql,
+            };
+            f(&self.handler, &mut sys)
+        }
+
+        fn import(
+            &mut self,
+            options: &ImportOptions,
+            input: &str,
+        ) -> (TransferResult<ImportReport>, Vec<RejectedRow>) {
+            let mut rejects = Vec::new();
+            let report = self
+                .with(|handler, sys| import(options, handler, sys, input.as_bytes(), &mut rejects));
+            let rejects = String::from_utf8(rejects)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            (report, rejects)
+        }
+
+        fn export(&mut self, options: &ExportOptions) -> String {
+            let mut out = Vec::new();
+            self.with(|handler, sys| export(options, handler, sys, &mut out))
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        }
+
+        /// Every user, as the query API returns them
+        fn query(&mut self, filter: Value) -> Vec<Value> {
+            let request = json!({
+                "op": "query",
+                "schema_id": "users",
+                "schema_version": "v1",
+                "filter": filter,
+                "sort": "_id",
+                "limit": 100,
+            });
+            let result = self.with(|handler, sys| execute(handler, sys, request));
+            serde_json::from_value(result.unwrap()).unwrap()
+        }
+    }
+
+    const USERS: &str = r#"{"_id":"u1","name":"Ada","age":36,"score":9.5,"active":true,"tags":["math"]}
+{"_id":"u2","name":"Grace, \"Amazing\"","age":85,"active":false}
+
+{"_id":"u3","name":"Linus","score":7.25,"tags":[]}
+"#;
+
+    fn jsonl() -> ImportOptions {
+        ImportOptions::new("users", "v1", TransferFormat::Jsonl)
+    }
+
+    fn seeded() -> Env {
+        let mut env = Env::new();
+        let (report, rejects) = env.import(&jsonl().with_batch_size(2), USERS);
+        assert_eq!(
+            report.unwrap(),
+            ImportReport {
+                read: 3,
+                inserted: 3,
+                updated: 0,
+                rejected: 0
+            }
+        );
+        assert!(rejects.is_empty());
+        env
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let mut source = seeded();
+        let dump = source.export(&ExportOptions::new("users", "v1", TransferFormat::Jsonl));
+        assert_eq!(dump.lines().count(), 3);
+
+        let mut target = Env::new();
+        let (report, _) = target.import(&jsonl(), &dump);
+        assert_eq!(report.unwrap().inserted, 3);
+        assert_eq!(target.query(json!({})), source.query(json!({})));
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let mut source = seeded();
+        let dump = source.export(&ExportOptions::new("users", "v1", TransferFormat::Csv));
+        assert_eq!(dump.lines().next(), Some("_id,active,age,name,score,tags"));
+
+        let mut target = Env::new();
+        let options = ImportOptions::new("users", "v1", TransferFormat::Csv);
+        let (report, rejects) = target.import(&options, &dump);
+        assert!(rejects.is_empty(), "{:?}", rejects);
+        assert_eq!(report.unwrap().inserted, 3);
+        assert_eq!(target.query(json!({})), source.query(json!({})));
+    }
+
+    #[test]
+    fn test_export_filter() {
+        let mut env = seeded();
+        let options = ExportOptions::new("users", "v1", TransferFormat::Jsonl)
+            .with_filter(json!({"age": {"$gt": 40}}));
+        let dump = env.export(&options);
+        let ids: Vec<Value> = dump
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["_id"].clone())
+            .collect();
+        assert_eq!(ids, vec![json!("u2")]);
+    }
+
+    #[test]
+    fn test_rejected_rows_are_reported_and_the_rest_imported() {
+        let mut env = seeded();
+        let input = r#"{"_id":"u4","name":"Ken"}
+not json
+{"_id":"u5","age":"old"}
+{"_id":"u1","name":"Ada again"}
+{"_id":"u6","name":"Dennis"}
+"#;
+        let (report, rejects) = env.import(&jsonl(), input);
+        assert_eq!(
+            report.unwrap(),
+            ImportReport {
+                read: 5,
+                inserted: 2,
+                updated: 0,
+                rejected: 3
+            }
+        );
+        let lines: Vec<u64> = rejects.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![2, 3, 4]);
+        assert_eq!(rejects[0].row, json!("not json"));
+        assert!(rejects[2].message.contains("already exists"));
+        assert_eq!(env.query(json!({})).len(), 5);
+    }
+
+    #[test]
+    fn test_upsert_updates_existing_documents() {
+        let mut env = seeded();
+        let input = r#"{"_id":"u1","name":"Ada Lovelace","age":36}
+{"_id":"u7","name":"Barbara"}
+"#;
+        let options = jsonl().with_mode(ImportMode::Upsert);
+        let (report, _) = env.import(&options, input);
+        let report = report.unwrap();
+        assert_eq!((report.inserted, report.updated), (1, 1));
+
+        let result = env.query(json!({"_id": {"$eq": "u1"}}));
+        assert_eq!(result[0]["name"], "Ada Lovelace");
+    }
+
+    #[test]
+    fn test_abort_on_error_writes_nothing_of_the_failing_batch() {
+        let mut env = Env::new();
+        let input = r#"{"_id":"a","name":"A"}
+{"_id":"b","name":"B"}
+{"_id":"c","name":"C"}
+{"_id":"d"}
+"#;
+        let options = jsonl().with_batch_size(2).with_abort_on_error(true);
+        let (report, rejects) = env.import(&options, input);
+        match report {
+            Err(TransferError::RowRejected { line, .. }) => assert_eq!(line, 4),
+            other => panic!("expected RowRejected, got {:?}", other),
+        }
+        assert_eq!(rejects.len(), 1);
+
+        // The first batch committed; "c" shared the failing batch
+        let ids: Vec<Value> = env
+            .query(json!({}))
+            .iter()
+            .map(|doc| doc["_id"].clone())
+            .collect();
+        assert_eq!(ids, vec![json!("a"), json!("b")]);
+    }
+
+    #[test]
+    fn test_csv_column_map_and_coercion() {
+        let mut env = Env::new();
+        let input = "ID,Full Name,age,active\nx1,Ann,41,1\nx2,Bob,forty,true\n";
+        let options = ImportOptions::new("users", "v1", TransferFormat::Csv)
+            .with_column("ID", "_id")
+            .with_column("Full Name", "name");
+        let (report, rejects) = env.import(&options, input);
+        assert_eq!(report.unwrap().inserted, 1);
+        assert_eq!(rejects.len(), 1);
+        assert_eq!(rejects[0].line, 3);
+        assert_eq!(rejects[0].row["age"], "forty");
+        assert!(rejects[0].message.contains("expected int"));
+
+        let result = env.query(json!({"_id": {"$eq": "x1"}}));
+        assert_eq!(result[0]["age"], 41);
+        assert_eq!(result[0]["active"], true);
+
+        let options = ImportOptions::new("users", "v1", TransferFormat::Csv);
+        let (report, _) = env.import(&options, "_id,nickname\nx3,Al\n");
+        assert!(
+            matches!(report, Err(TransferError::UnknownColumn { column }) if column == "nickname")
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Bulk import and export of collections
+//!
+//! Export streams a collection's live documents, optionally filtered with
+//! the query filter syntax, as JSON Lines (one document per line) or CSV
+//! (one column per schema field). Documents are read from storage one at a
+//! time, so exporting a large collection needs little memory.
+//!
+//! Import reads the same formats and writes only as a client of the
+//! ordinary API: each row is checked against the schema, then rows are
+//! committed in transactions of `batch_size` writes (one WAL append per
+//! batch). A row that cannot be written is reported to a rejects stream
+//! and the import carries on, unless `abort_on_error` is set.
+//!
+//! CSV cells are converted to the schema's field types: `int`, `float`
+//! and `bool` cells are parsed, `object` and `array` cells hold JSON, and
+//! an empty cell leaves the field out. Columns are named after fields;
+//! `column_map` renames the columns of files that use other headers.
+
+mod export;
+mod import;
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::ApiError;
+
+pub use export::{export, ExportOptions, ExportReport};
+pub use import::{
+    import, ImportMode, ImportOptions, ImportReport, RejectedRow, DEFAULT_BATCH_SIZE,
+};
+
+/// Result type for import and export
+pub type TransferResult<T> = Result<T, TransferError>;
+
+/// Import and export errors
+#[derive(Debug)]
+pub enum TransferError {
+    /// The options are unusable (unknown schema, bad mapping, ...)
+    InvalidOptions { reason: String },
+
+    /// A CSV column names no field of the schema
+    UnknownColumn { column: String },
+
+    /// Reading the input or writing the output failed
+    Io { message: String },
+
+    /// The database refused a request that concerns no single row
+    Api { code: String, message: String },
+
+    /// A row was rejected and `abort_on_error` is set
+    RowRejected {
+        line: u64,
+        code: String,
+        message: String,
+    },
+}
+
+impl TransferError {
+    fn invalid(reason: impl Into<String>) -> Self {
+        Self::InvalidOptions {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidOptions { reason } => write!(f, "Invalid transfer options: {}", reason),
+            Self::UnknownColumn { column } => {
+                write!(f, "Column '{}' is not a field of the schema", column)
+            }
+            Self::Io { message } => write!(f, "Transfer I/O failed: {}", message),
+            Self::Api { code, message } => write!(f, "{}: {}", code, message),
+            Self::RowRejected {
+                line,
+                code,
+                message,
+            } => write!(f, "Row at line {} rejected: {}: {}", line, code, message),
+        }
+    }
+}
+
+impl std::error::Error for TransferError {}
+
+impl From<io::Error> for TransferError {
+    fn from(e: io::Error) -> Self {
+        Self::Io {
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<csv::Error> for TransferError {
+    fn from(e: csv::Error) -> Self {
+        Self::Io {
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for TransferError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Io {
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<ApiError> for TransferError {
+    fn from(e: ApiError) -> Self {
+        Self::Api {
+            code: e.code().to_string(),
+            message: e.message().to_string(),
+        }
+    }
+}
+
+/// File format of an import or export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferFormat {
+    /// JSON Lines: one document per line
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+impl TransferFormat {
+    /// The format a file name implies: CSV for `.csv`, JSON Lines otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => Self::Csv,
+            _ => Self::Jsonl,
+        }
+    }
+
+    /// MIME type of the format
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Jsonl => "application/x-ndjson",
+            Self::Csv => "text/csv",
+        }
+    }
+}
+
+impl FromStr for TransferFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jsonl" => Ok(Self::Jsonl),
+            "csv" => Ok(Self::Csv),
+            other => Err(format!(
+                "Unknown format '{}' (expected jsonl or csv)",
+                other
+            )),
+        }
+    }
+}
+
+/// Something that can import and export collections
+///
+/// Implemented by the CLI over a booted database; the admin HTTP endpoints
+/// only need this trait.
+pub trait DataTransfer: Send + Sync {
+    fn export(&self, options: &ExportOptions, out: &mut dyn Write) -> TransferResult<ExportReport>;
+
+    fn import(
+        &self,
+        options: &ImportOptions,
+        input: &mut dyn Read,
+        rejects: &mut dyn Write,
+    ) -> TransferResult<ImportReport>;
+}
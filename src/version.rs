@@ -8,6 +8,14 @@
 //! - Schema format version
 //!
 //! On startup, compares versions and refuses to start if incompatible.
+//!
+//! The marker also records the process that last opened the data directory
+//! (PID and system boot time). Startup is refused while that process is
+//! still alive, so two processes never write the same data directory.
+//!
+//! Data written with an older WAL format is upgraded in place with
+//! `upgrade_wal_format` (`aerodb upgrade`), which applies one registered
+//! transform per format version.
 
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -16,12 +24,15 @@ use std::path::{Path, PathBuf};
 
 /// Current versions - update these when formats change
 pub const BINARY_VERSION: &str = env!("CARGO_PKG_VERSION");
-pub const WAL_FORMAT_VERSION: u16 = 1;
+pub const WAL_FORMAT_VERSION: u16 = 3;
 pub const SCHEMA_FORMAT_VERSION: u16 = 1;
 
 /// Version marker file name
 const VERSION_FILE: &str = ".aerodb_version";
 
+/// Temporary file for atomic version marker writes
+const VERSION_TEMP_FILE: &str = ".aerodb_version.tmp";
+
 /// Initialization marker file name (for atomic init detection)
 const INIT_MARKER_FILE: &str = ".aerodb_initialized";
 
@@ -62,6 +73,17 @@ pub enum VersionError {
     PartialInitialization {
         message: String,
     },
+    /// Another live process holds the data directory
+    AlreadyRunning {
+        pid: u32,
+        acquired_at: String,
+    },
+    /// WAL format upgrade could not be applied
+    UpgradeFailed {
+        from: u16,
+        to: u16,
+        message: String,
+    },
     /// IO error reading/writing version file
     IoError(String),
 }
@@ -95,10 +117,27 @@ impl std::fmt::Display for VersionError {
                 write!(
                     f,
                     "Partial initialization detected: {}. \
-                     Run 'aerodb init --force' to reinitialize (WARNING: destroys existing data).",
+                     Run 'aerodb init --force --confirm-destroy <data_dir>' to reinitialize \
+                     (WARNING: destroys existing data).",
                     message
                 )
             }
+            VersionError::AlreadyRunning { pid, acquired_at } => {
+                write!(
+                    f,
+                    "Data directory is in use by process {} (since {}). \
+                     Stop that process first: two processes writing the same data directory \
+                     corrupt it.",
+                    pid, acquired_at
+                )
+            }
+            VersionError::UpgradeFailed { from, to, message } => {
+                write!(
+                    f,
+                    "WAL format upgrade from v{} to v{} failed: {}",
+                    from, to, message
+                )
+            }
             VersionError::IoError(msg) => {
                 write!(f, "Version file error: {}", msg)
             }
@@ -122,6 +161,98 @@ pub struct VersionMarker {
     /// Last binary version that accessed this data
     #[serde(default)]
     pub last_accessed_by: Option<String>,
+    /// Process currently using this data (None after a clean release)
+    #[serde(default)]
+    pub process_lock: Option<ProcessLock>,
+}
+
+/// Process holding a data directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessLock {
+    /// Process ID
+    pub pid: u32,
+    /// System boot time (seconds since the epoch) when the lock was taken,
+    /// if known. A PID recorded before a reboot refers to no process.
+    pub boot_time: Option<u64>,
+    /// When the lock was taken (RFC 3339)
+    pub acquired_at: String,
+}
+
+impl ProcessLock {
+    /// Lock for the current process
+    pub fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            boot_time: system_boot_time(),
+            acquired_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Whether the locking process is still running
+    pub fn is_alive(&self) -> bool {
+        if let (Some(recorded), Some(current)) = (self.boot_time, system_boot_time()) {
+            if recorded != current {
+                return false;
+            }
+        }
+        process_alive(self.pid)
+    }
+
+    /// Whether another running process holds the lock
+    ///
+    /// A lock naming this process is left over from an earlier one that
+    /// exited uncleanly with the same PID: in a container the server is
+    /// always PID 1, and the boot time is the host's.
+    pub fn held_by_other(&self) -> bool {
+        self.pid != std::process::id() && self.is_alive()
+    }
+}
+
+/// System boot time in seconds since the epoch (`btime` in /proc/stat)
+fn system_boot_time() -> Option<u64> {
+    let stat = fs::read_to_string("/proc/stat").ok()?;
+    stat.lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Whether a process with this PID exists (`kill(pid, 0)`)
+///
+/// An exited child its parent has not reaped yet counts as gone.
+#[cfg(unix)]
+pub(crate) fn process_alive(pid: u32) -> bool {
+    // 0 and negative PIDs address process groups, not a process
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+
+    // SAFETY: signal 0 performs only the existence and permission checks
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return !is_zombie(pid);
+    }
+    // EPERM: the process exists but belongs to another user
+    io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Whether the process is a zombie (state `Z` in /proc/<pid>/stat)
+#[cfg(unix)]
+fn is_zombie(pid: libc::pid_t) -> bool {
+    fs::read_to_string(format!("/proc/{}/stat", pid))
+        .ok()
+        .and_then(|stat| {
+            stat.rsplit_once(')')
+                .map(|(_, rest)| rest.trim_start().starts_with('Z'))
+        })
+        .unwrap_or(false)
+}
+
+/// Liveness cannot be checked: treat the lock as stale
+#[cfg(not(unix))]
+pub(crate) fn process_alive(_pid: u32) -> bool {
+    false
 }
 
 impl VersionMarker {
@@ -133,6 +264,7 @@ impl VersionMarker {
             schema_format_version: SCHEMA_FORMAT_VERSION,
             created_at: chrono::Utc::now().to_rfc3339(),
             last_accessed_by: None,
+            process_lock: None,
         }
     }
 
@@ -153,21 +285,34 @@ impl VersionMarker {
     }
 
     /// Save version marker to data directory
+    ///
+    /// Written to a temporary file, fsynced and renamed over the marker, so
+    /// a crash leaves either the old or the new marker.
     pub fn save(&self, data_dir: &Path) -> Result<(), VersionError> {
         let path = data_dir.join(VERSION_FILE);
+        let temp_path = data_dir.join(VERSION_TEMP_FILE);
         
         let content = serde_json::to_string_pretty(self)
             .map_err(|e| VersionError::IoError(format!("Failed to serialize: {}", e)))?;
 
-        fs::write(&path, content)
-            .map_err(|e| VersionError::IoError(format!("Failed to write {}: {}", path.display(), e)))?;
+        fs::write(&temp_path, content)
+            .map_err(|e| VersionError::IoError(format!("Failed to write {}: {}", temp_path.display(), e)))?;
 
         // fsync for durability
-        let file = fs::File::open(&path)
+        let file = fs::File::open(&temp_path)
             .map_err(|e| VersionError::IoError(format!("Failed to open for fsync: {}", e)))?;
         file.sync_all()
             .map_err(|e| VersionError::IoError(format!("Failed to fsync: {}", e)))?;
 
+        fs::rename(&temp_path, &path)
+            .map_err(|e| VersionError::IoError(format!("Failed to replace {}: {}", path.display(), e)))?;
+
+        // fsync directory so the rename is durable
+        let dir = fs::File::open(data_dir)
+            .map_err(|e| VersionError::IoError(format!("Failed to open data dir for fsync: {}", e)))?;
+        dir.sync_all()
+            .map_err(|e| VersionError::IoError(format!("Failed to fsync data dir: {}", e)))?;
+
         Ok(())
     }
 
@@ -176,6 +321,12 @@ impl VersionMarker {
         self.last_accessed_by = Some(BINARY_VERSION.to_string());
         self.save(data_dir)
     }
+
+    /// Record the current process as the user of this data and save
+    pub fn mark_access(&mut self, data_dir: &Path) -> Result<(), VersionError> {
+        self.process_lock = Some(ProcessLock::current());
+        self.touch(data_dir)
+    }
 }
 
 impl Default for VersionMarker {
@@ -216,6 +367,16 @@ impl VersionChecker {
             Err(e) => return VersionCheck::Incompatible(e),
         };
 
+        // Refuse to open data another live process is using
+        if let Some(lock) = &marker.process_lock {
+            if lock.held_by_other() {
+                return VersionCheck::Incompatible(VersionError::AlreadyRunning {
+                    pid: lock.pid,
+                    acquired_at: lock.acquired_at.clone(),
+                });
+            }
+        }
+
         // Check WAL format compatibility
         if marker.wal_format_version != WAL_FORMAT_VERSION {
             if marker.wal_format_version > WAL_FORMAT_VERSION {
@@ -236,7 +397,7 @@ impl VersionChecker {
                     found: marker.wal_format_version,
                     message: format!(
                         "Data was written with WAL format v{} but this binary requires v{}. \
-                         Run 'aerodb migrate' to upgrade the data format.",
+                         Run 'aerodb upgrade' to upgrade the data format.",
                         marker.wal_format_version, WAL_FORMAT_VERSION
                     ),
                 });
@@ -326,13 +487,150 @@ impl VersionChecker {
         Ok(())
     }
 
-    /// Update version marker after successful startup
+    /// Update version marker after successful startup, taking the
+    /// process lock
     pub fn update_access(&self) -> Result<(), VersionError> {
         if let Some(mut marker) = VersionMarker::load(&self.data_dir)? {
-            marker.touch(&self.data_dir)?;
+            marker.mark_access(&self.data_dir)?;
         }
         Ok(())
     }
+
+    /// Release the process lock on clean shutdown (only if this process
+    /// holds it)
+    pub fn release_access(&self) -> Result<(), VersionError> {
+        if let Some(mut marker) = VersionMarker::load(&self.data_dir)? {
+            let held = marker
+                .process_lock
+                .as_ref()
+                .is_some_and(|lock| lock.pid == std::process::id());
+            if held {
+                marker.process_lock = None;
+                marker.save(&self.data_dir)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Transform moving the data in a directory from one WAL format version to
+/// the next. It must leave every file it writes fsynced: the marker records
+/// the new version only after it returns.
+pub type WalFormatTransform = fn(&Path) -> Result<(), VersionError>;
+
+/// Registered WAL format transforms, keyed by the version they upgrade from
+/// (each upgrades to the next version)
+const WAL_FORMAT_TRANSFORMS: &[(u16, WalFormatTransform)] =
+    &[(1, wal_v1_to_v2), (2, wal_v2_to_v3)];
+
+/// v1 -> v2: record layout is unchanged.
+///
+/// Template for later transforms: rewrite segments, then fsync every file
+/// and the WAL directory before returning.
+fn wal_v1_to_v2(data_dir: &Path) -> Result<(), VersionError> {
+    sync_wal_files(data_dir)
+}
+
+/// v2 -> v3: records may carry a trailing authority epoch. Existing
+/// records are epoch 0, which is not written, so their layout is unchanged.
+fn wal_v2_to_v3(data_dir: &Path) -> Result<(), VersionError> {
+    sync_wal_files(data_dir)
+}
+
+/// fsync every file in the WAL directory and the directory itself
+fn sync_wal_files(data_dir: &Path) -> Result<(), VersionError> {
+    let wal_dir = data_dir.join("wal");
+    if !wal_dir.exists() {
+        return Ok(());
+    }
+
+    let entries = fs::read_dir(&wal_dir)
+        .map_err(|e| VersionError::IoError(format!("Failed to read {}: {}", wal_dir.display(), e)))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| VersionError::IoError(format!("Failed to read {}: {}", wal_dir.display(), e)))?
+            .path();
+        if path.is_file() {
+            sync_path(&path)?;
+        }
+    }
+    sync_path(&wal_dir)
+}
+
+/// fsync a file or directory
+fn sync_path(path: &Path) -> Result<(), VersionError> {
+    fs::File::open(path)
+        .and_then(|file| file.sync_all())
+        .map_err(|e| VersionError::IoError(format!("Failed to fsync {}: {}", path.display(), e)))
+}
+
+/// Upgrade the WAL format of a data directory from `from` to `to`
+///
+/// Applies the registered transform for each version in turn. After each
+/// transform the marker is rewritten (atomically, fsynced) with the version
+/// reached, so an interrupted upgrade resumes from the last completed step.
+///
+/// Returns the number of transforms applied: 0 if the marker is already at
+/// `to`. Fails if the marker is at any other version, if no transform is
+/// registered for a step, or while another process holds the directory.
+pub fn upgrade_wal_format(data_dir: &Path, from: u16, to: u16) -> Result<usize, VersionError> {
+    let failed = |message: String| VersionError::UpgradeFailed { from, to, message };
+
+    if to < from {
+        return Err(failed("WAL format downgrades are not supported".to_string()));
+    }
+    if to > WAL_FORMAT_VERSION {
+        return Err(failed(format!(
+            "this binary supports WAL format up to v{}",
+            WAL_FORMAT_VERSION
+        )));
+    }
+
+    let mut marker = VersionMarker::load(data_dir)?.ok_or_else(|| {
+        failed(format!("no version marker in {}", data_dir.display()))
+    })?;
+
+    if let Some(lock) = &marker.process_lock {
+        if lock.held_by_other() {
+            return Err(VersionError::AlreadyRunning {
+                pid: lock.pid,
+                acquired_at: lock.acquired_at.clone(),
+            });
+        }
+    }
+
+    if marker.wal_format_version == to {
+        return Ok(0);
+    }
+    if marker.wal_format_version != from {
+        return Err(failed(format!(
+            "data is at WAL format v{}",
+            marker.wal_format_version
+        )));
+    }
+
+    let mut applied = 0;
+    for version in from..to {
+        let transform = WAL_FORMAT_TRANSFORMS
+            .iter()
+            .find(|(source, _)| *source == version)
+            .map(|(_, transform)| *transform)
+            .ok_or_else(|| {
+                failed(format!(
+                    "no transform registered for v{} to v{}",
+                    version,
+                    version + 1
+                ))
+            })?;
+
+        transform(data_dir)?;
+
+        marker.wal_format_version = version + 1;
+        marker.save(data_dir)?;
+        applied += 1;
+    }
+
+    Ok(applied)
 }
 
 #[cfg(test)]
@@ -379,6 +677,169 @@ mod tests {
         assert_eq!(loaded.schema_format_version, SCHEMA_FORMAT_VERSION);
     }
 
+    #[test]
+    fn test_live_process_lock_refuses_startup() {
+        let temp = TempDir::new().unwrap();
+        let checker = VersionChecker::new(temp.path());
+        checker.mark_initialized().unwrap();
+
+        // Another running process
+        let mut server = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let mut marker = VersionMarker::load(temp.path()).unwrap().unwrap();
+        marker.process_lock = Some(ProcessLock {
+            pid: server.id(),
+            ..ProcessLock::current()
+        });
+        marker.save(temp.path()).unwrap();
+        let result = checker.check();
+        server.kill().unwrap();
+        server.wait().unwrap();
+        match result {
+            VersionCheck::Incompatible(VersionError::AlreadyRunning { pid, .. }) => {
+                assert_eq!(pid, server.id());
+            }
+            other => panic!("Expected AlreadyRunning, got {:?}", other),
+        }
+
+        // A lock with our own PID is left over from an unclean exit (PID 1
+        // in a container), not held by a live server
+        checker.update_access().unwrap();
+        let marker = VersionMarker::load(temp.path()).unwrap().unwrap();
+        assert_eq!(marker.process_lock.as_ref().unwrap().pid, std::process::id());
+        assert_eq!(marker.last_accessed_by.as_deref(), Some(BINARY_VERSION));
+        assert!(matches!(checker.check(), VersionCheck::Compatible));
+
+        // A clean release removes it
+        checker.release_access().unwrap();
+        assert!(VersionMarker::load(temp.path()).unwrap().unwrap().process_lock.is_none());
+        assert!(matches!(checker.check(), VersionCheck::Compatible));
+    }
+
+    #[test]
+    fn test_stale_process_lock_ignored() {
+        let temp = TempDir::new().unwrap();
+        let checker = VersionChecker::new(temp.path());
+        checker.mark_initialized().unwrap();
+
+        // No process has this PID (above any pid_max)
+        let mut marker = VersionMarker::load(temp.path()).unwrap().unwrap();
+        marker.process_lock = Some(ProcessLock {
+            pid: i32::MAX as u32,
+            ..ProcessLock::current()
+        });
+        marker.save(temp.path()).unwrap();
+        assert!(matches!(checker.check(), VersionCheck::Compatible));
+
+        // Someone else's lock is not released by this process
+        checker.release_access().unwrap();
+        assert!(VersionMarker::load(temp.path()).unwrap().unwrap().process_lock.is_some());
+
+        // A live PID from before a reboot is stale too
+        if let Some(boot_time) = system_boot_time() {
+            marker.process_lock = Some(ProcessLock {
+                boot_time: Some(boot_time - 1),
+                ..ProcessLock::current()
+            });
+            marker.save(temp.path()).unwrap();
+            assert!(matches!(checker.check(), VersionCheck::Compatible));
+        }
+    }
+
+    #[test]
+    fn test_marker_without_process_lock_loads() {
+        let temp = TempDir::new().unwrap();
+        let legacy = r#"{
+            "binary_version": "0.1.0",
+            "wal_format_version": 1,
+            "schema_format_version": 1,
+            "created_at": "2026-01-01T00:00:00Z"
+        }"#;
+        fs::write(temp.path().join(VERSION_FILE), legacy).unwrap();
+
+        let marker = VersionMarker::load(temp.path()).unwrap().unwrap();
+        assert!(marker.process_lock.is_none());
+    }
+
+    #[test]
+    fn test_upgrade_wal_format() {
+        let temp = TempDir::new().unwrap();
+        let checker = VersionChecker::new(temp.path());
+        checker.mark_initialized().unwrap();
+        fs::create_dir_all(temp.path().join("wal")).unwrap();
+        fs::write(temp.path().join("wal").join("wal.log"), b"records").unwrap();
+
+        let mut marker = VersionMarker::load(temp.path()).unwrap().unwrap();
+        marker.wal_format_version = 1;
+        marker.save(temp.path()).unwrap();
+        match checker.check() {
+            VersionCheck::Incompatible(VersionError::WalFormatMismatch { found: 1, message, .. }) => {
+                assert!(message.contains("aerodb upgrade"));
+            }
+            other => panic!("Expected WalFormatMismatch, got {:?}", other),
+        }
+
+        assert_eq!(
+            upgrade_wal_format(temp.path(), 1, WAL_FORMAT_VERSION).unwrap(),
+            2
+        );
+        let upgraded = VersionMarker::load(temp.path()).unwrap().unwrap();
+        assert_eq!(upgraded.wal_format_version, 3);
+        assert_eq!(upgraded.created_at, marker.created_at);
+        assert_eq!(
+            fs::read(temp.path().join("wal").join("wal.log")).unwrap(),
+            b"records"
+        );
+        assert!(matches!(checker.check(), VersionCheck::Compatible));
+
+        // Already current: nothing to do
+        assert_eq!(upgrade_wal_format(temp.path(), 1, 3).unwrap(), 0);
+        assert_eq!(
+            upgrade_wal_format(temp.path(), WAL_FORMAT_VERSION, WAL_FORMAT_VERSION).unwrap(),
+            0
+        );
+        assert_eq!(
+            VersionMarker::load(temp.path()).unwrap().unwrap().wal_format_version,
+            3
+        );
+    }
+
+    #[test]
+    fn test_upgrade_wal_format_rejections() {
+        let temp = TempDir::new().unwrap();
+
+        // No marker
+        assert!(matches!(
+            upgrade_wal_format(temp.path(), 1, 2),
+            Err(VersionError::UpgradeFailed { .. })
+        ));
+
+        VersionChecker::new(temp.path()).mark_initialized().unwrap();
+
+        // Beyond what this binary supports, downgrade, wrong source version
+        for (from, to) in [(1, WAL_FORMAT_VERSION + 1), (2, 1), (0, 1)] {
+            assert!(matches!(
+                upgrade_wal_format(temp.path(), from, to),
+                Err(VersionError::UpgradeFailed { .. })
+            ));
+        }
+
+        // No transform registered for v0
+        let mut marker = VersionMarker::load(temp.path()).unwrap().unwrap();
+        marker.wal_format_version = 0;
+        marker.save(temp.path()).unwrap();
+        assert!(matches!(
+            upgrade_wal_format(temp.path(), 0, 2),
+            Err(VersionError::UpgradeFailed { .. })
+        ));
+        assert_eq!(
+            VersionMarker::load(temp.path()).unwrap().unwrap().wal_format_version,
+            0
+        );
+    }
+
     #[test]
     fn test_partial_init_detection() {
         let temp = TempDir::new().unwrap();
@@ -14,12 +14,26 @@
 //!
 //! Per §6: "The only difference is which commits wait on which fsync,
 //!          which is not observable."
+//!
+//! `GroupCommitWriter` is the `wal_sync_mode = "group_commit"` write path
+//! for writers appending concurrently. Unlike the arrival-only grouping
+//! above, its leader waits up to the group commit window
+//! (`group_commit_window_us`, or `group_commit_interval_ms`) or until
+//! `group_commit_max_records` records are pending before the shared fsync.
+//! Acknowledgment still strictly follows the fsync.
+//!
+//! `GroupCommitBatch` applies the same window to the server, whose single
+//! executor writes one request at a time: it holds the responses until
+//! one sync covers all of their writes.
 
 use std::collections::VecDeque;
 use std::sync::{Arc, Condvar, Mutex};
+use std::time::Instant;
 
 use super::errors::{WalError, WalResult};
 use super::record::{RecordType, WalPayload};
+use super::sync_mode::WalSyncConfig;
+use super::writer::WalWriter;
 
 /// Configuration for group commit.
 ///
@@ -396,6 +410,264 @@ impl CommitPath {
     }
 }
 
+/// Batching counters for a `GroupCommitWriter`.
+///
+/// Passive metrics only, per GROUP_COMMIT.md §10.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GroupCommitStats {
+    /// Number of batch syncs performed.
+    pub batches_synced: u64,
+    /// Number of records made durable by those syncs.
+    pub records_synced: u64,
+    /// Size of the largest batch synced so far.
+    pub largest_batch: usize,
+}
+
+/// Shared WAL writer that batches concurrent appends into one fsync.
+///
+/// The first writer to arrive in a batch becomes its leader. The leader
+/// waits until either `group_commit_max_records` records are pending or
+/// `group_commit_interval` has elapsed, then syncs once and wakes every
+/// writer of the batch. No writer returns a sequence number before the
+/// sync covering its record has succeeded (D-1).
+///
+/// A failed batch sync is FATAL: every writer of that batch gets the error
+/// and all later appends are refused.
+#[derive(Debug)]
+pub struct GroupCommitWriter {
+    /// Window parameters.
+    config: WalSyncConfig,
+    /// Writer and batch bookkeeping.
+    state: Mutex<GroupCommitWriterState>,
+    /// Wakes the leader when the batch fills up.
+    batch_full: Condvar,
+    /// Wakes followers when a batch sync completes.
+    batch_synced: Condvar,
+}
+
+#[derive(Debug)]
+struct GroupCommitWriterState {
+    /// Underlying writer.
+    writer: WalWriter,
+    /// Batch currently accepting records.
+    open_batch: u64,
+    /// Records written to the open batch but not yet synced.
+    pending: usize,
+    /// Whether the open batch already has a leader.
+    leader_present: bool,
+    /// All batches below this number are durable.
+    synced_through: u64,
+    /// Fatal sync failure, if any.
+    failure: Option<String>,
+    /// Batching counters.
+    stats: GroupCommitStats,
+}
+
+impl GroupCommitWriter {
+    /// Wrap a writer. The window is taken from `config`, not from the
+    /// writer's own sync configuration.
+    pub fn new(writer: WalWriter, config: WalSyncConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(GroupCommitWriterState {
+                writer,
+                open_batch: 0,
+                pending: 0,
+                leader_present: false,
+                synced_through: 0,
+                failure: None,
+                stats: GroupCommitStats::default(),
+            }),
+            batch_full: Condvar::new(),
+            batch_synced: Condvar::new(),
+        }
+    }
+
+    /// Append a record and block until the batch containing it is durable.
+    ///
+    /// # Errors
+    ///
+    /// - `AERO_WAL_APPEND_FAILED` if the write fails
+    /// - `AERO_WAL_FSYNC_FAILED` if the batch sync failed, now or earlier (FATAL)
+    pub fn append(&self, record_type: RecordType, payload: WalPayload) -> WalResult<u64> {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(ref error) = state.failure {
+            return Err(Self::failure_error(error));
+        }
+
+        let sequence_number = state.writer.write_unsynced(record_type, payload)?;
+        let batch = state.open_batch;
+        state.pending += 1;
+
+        if state.leader_present {
+            if state.pending >= self.config.group_commit_max_records {
+                self.batch_full.notify_one();
+            }
+
+            // Follower: wait for the leader's sync of this batch
+            while state.synced_through <= batch && state.failure.is_none() {
+                state = self.batch_synced.wait(state).unwrap();
+            }
+
+            return match state.failure {
+                Some(ref error) if state.synced_through <= batch => Err(Self::failure_error(error)),
+                _ => Ok(sequence_number),
+            };
+        }
+
+        // Leader: collect records until the batch is full or the window closes
+        state.leader_present = true;
+        let deadline = Instant::now() + self.config.group_commit_interval;
+        while state.pending < self.config.group_commit_max_records {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = self
+                .batch_full
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+
+        // Close the batch; later arrivals start the next one
+        let batch_size = state.pending;
+        state.open_batch += 1;
+        state.pending = 0;
+
+        let context = format!(
+            "group commit of {} records through sequence {}",
+            batch_size,
+            state.writer.last_sequence_number()
+        );
+        let result = state.writer.sync_for_mode(&context);
+
+        state.leader_present = false;
+        match result {
+            Ok(()) => {
+                state.synced_through = batch + 1;
+                state.stats.batches_synced += 1;
+                state.stats.records_synced += batch_size as u64;
+                state.stats.largest_batch = state.stats.largest_batch.max(batch_size);
+            }
+            Err(ref e) => state.failure = Some(e.to_string()),
+        }
+        self.batch_synced.notify_all();
+
+        result.map(|()| sequence_number)
+    }
+
+    /// Appends an INSERT record.
+    pub fn append_insert(&self, payload: WalPayload) -> WalResult<u64> {
+        self.append(RecordType::Insert, payload)
+    }
+
+    /// Appends an UPDATE record.
+    pub fn append_update(&self, payload: WalPayload) -> WalResult<u64> {
+        self.append(RecordType::Update, payload)
+    }
+
+    /// Appends a DELETE record.
+    pub fn append_delete(&self, payload: WalPayload) -> WalResult<u64> {
+        self.append(RecordType::Delete, payload)
+    }
+
+    /// Batching counters so far.
+    pub fn stats(&self) -> GroupCommitStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// Last sequence number written (not necessarily synced yet).
+    pub fn last_sequence_number(&self) -> u64 {
+        self.state.lock().unwrap().writer.last_sequence_number()
+    }
+
+    /// Unwrap the underlying writer.
+    pub fn into_inner(self) -> WalWriter {
+        self.state.into_inner().unwrap().writer
+    }
+
+    fn failure_error(error: &str) -> WalError {
+        WalError::fsync_failed(
+            format!("Group commit fsync failed: {}", error),
+            std::io::Error::other(error.to_string()),
+        )
+    }
+}
+
+/// Acknowledgments of a single executor held back until one WAL sync
+/// covers the writes made before them.
+///
+/// The executor applies requests one at a time through a writer with
+/// deferred syncs ([`WalWriter::with_deferred_sync`]), so no writer ever
+/// waits on another's append as in [`GroupCommitWriter`]. Instead, once a
+/// write is pending every response is held, reads included, so nothing is
+/// observed ahead of the disk. The batch is due when the WAL holds
+/// `group_commit_max_records` unsynced records or when the group commit
+/// window opened by its first response closes; [`Self::release`] then syncs
+/// once and hands back the responses in order (D-1).
+#[derive(Debug)]
+pub struct GroupCommitBatch<T> {
+    /// Window parameters.
+    config: WalSyncConfig,
+    /// Responses waiting for the sync, in order.
+    held: Vec<T>,
+    /// When the window of the open batch closes.
+    deadline: Option<Instant>,
+}
+
+impl<T> GroupCommitBatch<T> {
+    /// An empty batch with the window of `config`.
+    pub fn new(config: WalSyncConfig) -> Self {
+        Self {
+            config,
+            held: Vec::new(),
+            deadline: None,
+        }
+    }
+
+    /// Whether `response` must wait for a sync: something written before
+    /// it is not durable yet, or responses before it are held.
+    pub fn must_hold(&self, wal: &WalWriter) -> bool {
+        !self.held.is_empty() || wal.unsynced_records() > 0
+    }
+
+    /// Hold `response` until the batch is released. The first response
+    /// held opens the window.
+    pub fn hold(&mut self, response: T) {
+        if self.held.is_empty() {
+            self.deadline = Some(Instant::now() + self.config.group_commit_interval);
+        }
+        self.held.push(response);
+    }
+
+    /// When the window of the open batch closes, if one is open.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Whether the batch is full or its window has closed.
+    pub fn is_due(&self, wal: &WalWriter) -> bool {
+        wal.unsynced_records() >= self.config.group_commit_max_records
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Sync the WAL once and return the held responses, in order.
+    ///
+    /// # Errors
+    ///
+    /// `AERO_WAL_FSYNC_FAILED` if the sync fails (FATAL): none of the held
+    /// responses may be sent.
+    pub fn release(&mut self, wal: &mut WalWriter) -> WalResult<Vec<T>> {
+        wal.sync_deferred()?;
+        self.deadline = None;
+        Ok(std::mem::take(&mut self.held))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -639,4 +911,191 @@ mod tests {
         assert!(!group.is_fsync_complete());
         // On recovery, this record would be dropped because no fsync
     }
+
+    // ==================== GroupCommitWriter Tests ====================
+
+    fn open_group_writer(
+        dir: &std::path::Path,
+        interval: std::time::Duration,
+        max_records: usize,
+    ) -> GroupCommitWriter {
+        let config = WalSyncConfig::group_commit(interval, max_records);
+        let writer = WalWriter::open_with_sync(dir, config.clone()).unwrap();
+        GroupCommitWriter::new(writer, config)
+    }
+
+    #[test]
+    fn test_group_writer_batches_concurrent_appends() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        // Long window: the batch can only close by filling up, so all
+        // eight writers must share exactly one fsync.
+        let writer = Arc::new(open_group_writer(
+            temp_dir.path(),
+            std::time::Duration::from_secs(30),
+            8,
+        ));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let writer = Arc::clone(&writer);
+                std::thread::spawn(move || writer.append_insert(test_payload()).unwrap())
+            })
+            .collect();
+        let mut seqs: Vec<u64> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        seqs.sort_unstable();
+
+        assert_eq!(seqs, (1..=8).collect::<Vec<_>>());
+        let stats = writer.stats();
+        assert_eq!(stats.batches_synced, 1);
+        assert_eq!(stats.records_synced, 8);
+        assert_eq!(stats.largest_batch, 8);
+    }
+
+    #[test]
+    fn test_group_writer_burst_needs_fewer_fsyncs() {
+        const WRITERS: usize = 32;
+
+        // Run WRITERS concurrent appends; returns the sorted sequence numbers
+        fn burst(append: impl Fn() -> u64 + Send + Sync + 'static) -> Vec<u64> {
+            let append = Arc::new(append);
+            let start = Arc::new(std::sync::Barrier::new(WRITERS));
+            let handles: Vec<_> = (0..WRITERS)
+                .map(|_| {
+                    let append = Arc::clone(&append);
+                    let start = Arc::clone(&start);
+                    std::thread::spawn(move || {
+                        start.wait();
+                        append()
+                    })
+                })
+                .collect();
+            let mut seqs: Vec<u64> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+            seqs.sort_unstable();
+            seqs
+        }
+
+        // Baseline: one fsync per record
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let writer = Arc::new(Mutex::new(WalWriter::open(temp_dir.path()).unwrap()));
+        let shared = Arc::clone(&writer);
+        let seqs = burst(move || {
+            shared
+                .lock()
+                .unwrap()
+                .append_insert(test_payload())
+                .unwrap()
+        });
+        assert_eq!(seqs, (1..=WRITERS as u64).collect::<Vec<_>>());
+        assert_eq!(writer.lock().unwrap().sync_count(), WRITERS as u64);
+
+        // Group commit: every batch but the last holds at least 8 records
+        // (late arrivals can join before the leader wakes)
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let writer = Arc::new(open_group_writer(
+            temp_dir.path(),
+            std::time::Duration::from_secs(1),
+            8,
+        ));
+        let shared = Arc::clone(&writer);
+        let seqs = burst(move || shared.append_insert(test_payload()).unwrap());
+        assert_eq!(seqs, (1..=WRITERS as u64).collect::<Vec<_>>());
+
+        let writer = Arc::try_unwrap(writer).unwrap();
+        let stats = writer.stats();
+        assert_eq!(stats.records_synced, WRITERS as u64);
+        assert!(stats.batches_synced <= (WRITERS / 8) as u64);
+        assert_eq!(writer.into_inner().sync_count(), stats.batches_synced);
+    }
+
+    #[test]
+    fn test_group_writer_window_closes_partial_batch() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let writer = open_group_writer(temp_dir.path(), std::time::Duration::from_millis(1), 64);
+
+        // Sequential writers never overlap, so each batch holds one record
+        for expected in 1..=3 {
+            assert_eq!(writer.append_insert(test_payload()).unwrap(), expected);
+        }
+
+        let stats = writer.stats();
+        assert_eq!(stats.batches_synced, 3);
+        assert_eq!(stats.records_synced, 3);
+        assert_eq!(stats.largest_batch, 1);
+    }
+
+    #[test]
+    fn test_group_writer_records_readable_after_reopen() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        {
+            let writer = open_group_writer(temp_dir.path(), std::time::Duration::from_millis(1), 4);
+            writer.append_insert(test_payload()).unwrap();
+            writer.append_update(test_payload()).unwrap();
+            writer.append_delete(test_payload()).unwrap();
+            assert_eq!(writer.into_inner().next_sequence_number(), 4);
+        }
+
+        let wal_path = temp_dir.path().join("wal").join("wal.log");
+        let mut reader = super::super::reader::WalReader::open(&wal_path).unwrap();
+        let mut types = Vec::new();
+        while let Some(record) = reader.read_next().unwrap() {
+            types.push(record.record_type);
+        }
+        assert_eq!(
+            types,
+            vec![RecordType::Insert, RecordType::Update, RecordType::Delete]
+        );
+    }
+
+    #[test]
+    fn test_batch_holds_responses_until_one_sync() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = WalSyncConfig::group_commit(std::time::Duration::from_secs(60), 3);
+        let mut wal = WalWriter::open_with_sync(temp_dir.path(), config.clone())
+            .unwrap()
+            .with_deferred_sync();
+        let mut batch = GroupCommitBatch::new(config);
+
+        // Nothing pending: a response goes out at once
+        assert!(!batch.must_hold(&wal));
+        assert!(batch.deadline().is_none());
+
+        // A write opens the batch; the read after it waits too
+        wal.append_insert(test_payload()).unwrap();
+        assert!(batch.must_hold(&wal));
+        batch.hold("insert 1");
+        assert!(batch.deadline().is_some());
+        batch.hold("query");
+        wal.append_insert(test_payload()).unwrap();
+        batch.hold("insert 2");
+        assert!(!batch.is_due(&wal));
+
+        wal.append_insert(test_payload()).unwrap();
+        batch.hold("insert 3");
+        assert!(batch.is_due(&wal));
+        assert_eq!(wal.sync_count(), 0);
+
+        let released = batch.release(&mut wal).unwrap();
+        assert_eq!(released, vec!["insert 1", "query", "insert 2", "insert 3"]);
+        assert_eq!(wal.sync_count(), 1);
+        assert!(!batch.must_hold(&wal));
+        assert!(batch.deadline().is_none());
+    }
+
+    #[test]
+    fn test_batch_window_closes_partial_batch() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = WalSyncConfig::group_commit(std::time::Duration::from_millis(5), 64);
+        let mut wal = WalWriter::open_with_sync(temp_dir.path(), config.clone())
+            .unwrap()
+            .with_deferred_sync();
+        let mut batch = GroupCommitBatch::new(config);
+
+        wal.append_insert(test_payload()).unwrap();
+        batch.hold(1);
+        assert!(!batch.is_due(&wal));
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert!(batch.is_due(&wal));
+        assert_eq!(batch.release(&mut wal).unwrap(), vec![1]);
+        assert_eq!(wal.sync_count(), 1);
+    }
 }
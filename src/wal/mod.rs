@@ -23,7 +23,10 @@
 //! # Phase 3 Optimizations
 //!
 //! - Group Commit: Multiple commits share fsync (optional, disabled by default)
+//! - Sync modes: `fsync` (default), `fdatasync`, `group_commit` via `WalSyncMode`
 //! - WAL Batching: Multiple records in single write() (optional, disabled by default)
+//! - Segmentation: `wal/0000001.log`, `0000002.log`, ... rolled at a size limit
+//!   (via `WalWriter::open_segmented`; the library default remains a single `wal.log`)
 
 mod batching;
 mod checksum;
@@ -31,18 +34,27 @@ mod errors;
 mod group_commit;
 mod reader;
 mod record;
+mod segment;
+mod sync_mode;
 mod writer;
 
 pub use batching::{BatchWriteResult, WalBatch, WalBatchConfig, WalBatcher, WritePath};
 pub use checksum::compute_checksum;
 pub use errors::{WalError, WalResult};
 pub use group_commit::{
-    CommitGroup, CommitPath, GroupCommitConfig, GroupCommitManager, GroupCommitResult,
-    PendingCommit, PendingCommitState,
+    CommitGroup, CommitPath, GroupCommitBatch, GroupCommitConfig, GroupCommitManager,
+    GroupCommitResult, GroupCommitStats, GroupCommitWriter, PendingCommit, PendingCommitState,
 };
-pub use reader::WalReader;
+pub use reader::{DiscardedTail, PositionedRecord, WalReader};
 pub use record::{
     MvccCommitPayload, MvccCommitRecord, MvccVersionPayload, MvccVersionRecord, RecordType,
     WalPayload, WalRecord,
 };
+pub use segment::{
+    clear_base_sequence, detect_layout, list_segments, parse_segment_file_name,
+    read_base_sequence, read_epoch, segment_file_name, write_base_sequence, write_epoch,
+    WalLayout, WalSegment, WalSegmentConfig, DEFAULT_SEGMENT_BYTES, LEGACY_WAL_FILE,
+    WAL_BASE_FILE, WAL_EPOCH_FILE,
+};
+pub use sync_mode::{WalSyncConfig, WalSyncMode};
 pub use writer::WalWriter;
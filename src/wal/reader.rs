@@ -10,20 +10,41 @@
 //! - WAL records are replayed strictly in sequence number order
 //! - Replay always starts from the first record
 //! - Replay is single-threaded
+//!
+//! A segmented WAL is replayed segment by segment in index order, with
+//! sequence numbers contiguous across segments. The one exception to the
+//! zero tolerance policy is a torn record at the tail of the *last*
+//! segment: it was never acknowledged (the sync did not complete), so the
+//! reader stops cleanly before it and reports its offset via
+//! [`WalReader::torn_tail`]. A torn record anywhere else is corruption.
+//!
+//! A damaged record that runs to the end of the last file (a failed
+//! checksum, or a truncated record in a legacy `wal.log`) is still an
+//! error, but is reported by [`WalReader::corrupt_tail`] so that recovery
+//! may choose to discard it with [`WalReader::discard_corrupt_tail`].
+//!
+//! [`WalReader::open_history`] reads segments that span checkpoints, such
+//! as the WAL archive, where sequence numbers restart at 1 at the first
+//! segment written after each checkpoint.
 
-use std::fs::File;
+use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use super::errors::{WalError, WalResult};
 use super::record::WalRecord;
+use super::segment::{
+    detect_layout, list_segments, parse_segment_file_name, read_base_sequence, WalLayout,
+    LEGACY_WAL_FILE,
+};
 
 /// WAL reader for sequential replay.
 ///
 /// Reads records from the WAL in strict order, validating checksums
 /// and record structure. Any corruption causes immediate failure.
 pub struct WalReader {
-    /// Path to the WAL file
+    /// Path to the WAL file currently being read
     wal_path: PathBuf,
     /// Buffered reader for efficient sequential reads
     reader: BufReader<File>,
@@ -33,6 +54,63 @@ pub struct WalReader {
     file_size: u64,
     /// Last successfully read sequence number
     last_sequence: u64,
+    /// All files in replay order (one entry for a legacy WAL)
+    files: Vec<PathBuf>,
+    /// Index into `files` of the file currently being read
+    file_pos: usize,
+    /// Whether `files` are numbered segments
+    segmented: bool,
+    /// Offset of a torn record at the tail of the last segment
+    torn_tail: Option<u64>,
+    /// Offset of a damaged record running to the end of the last file
+    corrupt_tail: Option<u64>,
+    /// Whether sequence numbers may restart at a segment boundary
+    history: bool,
+    /// Highest sequence number the first record may have
+    base_sequence: u64,
+    /// Offset at which the last record read starts
+    record_offset: u64,
+}
+
+/// A record together with where it was read from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionedRecord {
+    /// Segment index of the file holding the record (`None` for `wal.log`)
+    pub segment: Option<u64>,
+    /// Byte offset of the record within its file
+    pub offset: u64,
+    /// The decoded record
+    pub record: WalRecord,
+}
+
+/// Bytes cut from the end of the WAL by [`WalReader::discard_corrupt_tail`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscardedTail {
+    /// File the bytes were cut from
+    pub path: PathBuf,
+    /// Byte range discarded, from the end of the last good record
+    pub range: Range<u64>,
+}
+
+/// Opens a WAL file and returns a buffered reader and its size.
+fn open_wal_file(wal_path: &Path) -> WalResult<(BufReader<File>, u64)> {
+    let file = File::open(wal_path).map_err(|e| {
+        if e.kind() == io::ErrorKind::NotFound {
+            WalError::corruption(format!("WAL file not found: {}", wal_path.display()))
+        } else {
+            WalError::corruption(format!(
+                "Failed to open WAL file: {}: {}",
+                wal_path.display(),
+                e
+            ))
+        }
+    })?;
+
+    let metadata = file
+        .metadata()
+        .map_err(|e| WalError::corruption(format!("Failed to read WAL metadata: {}", e)))?;
+
+    Ok((BufReader::new(file), metadata.len()))
 }
 
 impl WalReader {
@@ -46,42 +124,88 @@ impl WalReader {
     ///
     /// Returns `WalError` if the file cannot be opened.
     pub fn open(wal_path: &Path) -> WalResult<Self> {
-        let file = File::open(wal_path).map_err(|e| {
-            if e.kind() == io::ErrorKind::NotFound {
-                WalError::corruption(format!("WAL file not found: {}", wal_path.display()))
-            } else {
-                WalError::corruption(format!(
-                    "Failed to open WAL file: {}: {}",
-                    wal_path.display(),
-                    e
-                ))
-            }
-        })?;
+        Self::open_files(vec![wal_path.to_path_buf()], false)
+    }
+
+    /// Opens all segments in a WAL directory for reading.
+    ///
+    /// Files that don't match the segment naming pattern are ignored.
+    /// If a checkpoint removed leading segments, the first record may be
+    /// any sequence up to the recorded base sequence.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AERO_WAL_CORRUPTION` if the directory holds no segments.
+    pub fn open_segments(wal_dir: &Path) -> WalResult<Self> {
+        let files: Vec<PathBuf> = list_segments(wal_dir)?
+            .into_iter()
+            .map(|segment| segment.path)
+            .collect();
+        if files.is_empty() {
+            return Err(WalError::corruption(format!(
+                "No WAL segments found in {}",
+                wal_dir.display()
+            )));
+        }
+        let mut reader = Self::open_files(files, true)?;
+        reader.base_sequence = read_base_sequence(wal_dir)?;
+        Ok(reader)
+    }
 
-        let metadata = file
-            .metadata()
-            .map_err(|e| WalError::corruption(format!("Failed to read WAL metadata: {}", e)))?;
+    /// Opens segments that may span several checkpoints.
+    ///
+    /// `files` must be in segment index order. Sequence numbers may
+    /// restart at 1 at the start of a segment (a checkpoint reset them),
+    /// and the first segment may start at any sequence number since older
+    /// segments may have been pruned. Otherwise the same rules as
+    /// [`WalReader::open_segments`] apply.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AERO_WAL_CORRUPTION` if `files` is empty.
+    pub fn open_history(files: Vec<PathBuf>) -> WalResult<Self> {
+        if files.is_empty() {
+            return Err(WalError::corruption("No WAL segments to read"));
+        }
+        let mut reader = Self::open_files(files, true)?;
+        reader.history = true;
+        Ok(reader)
+    }
 
-        let file_size = metadata.len();
+    fn open_files(files: Vec<PathBuf>, segmented: bool) -> WalResult<Self> {
+        let (reader, file_size) = open_wal_file(&files[0])?;
 
         Ok(Self {
-            wal_path: wal_path.to_path_buf(),
-            reader: BufReader::new(file),
+            wal_path: files[0].clone(),
+            reader,
             current_offset: 0,
             file_size,
             last_sequence: 0,
+            files,
+            file_pos: 0,
+            segmented,
+            torn_tail: None,
+            corrupt_tail: None,
+            history: false,
+            base_sequence: 1,
+            record_offset: 0,
         })
     }
 
-    /// Opens a WAL file from a data directory.
+    /// Opens the WAL of a data directory.
     ///
-    /// Expects the WAL at `<data_dir>/wal/wal.log`.
+    /// Reads numbered segments from `<data_dir>/wal/` if present,
+    /// otherwise the legacy `<data_dir>/wal/wal.log`.
     pub fn open_from_data_dir(data_dir: &Path) -> WalResult<Self> {
-        let wal_path = data_dir.join("wal").join("wal.log");
-        Self::open(&wal_path)
+        let wal_dir = data_dir.join("wal");
+        match detect_layout(&wal_dir)? {
+            WalLayout::Segmented(_) => Self::open_segments(&wal_dir),
+            WalLayout::Legacy(path) => Self::open(&path),
+            WalLayout::Empty => Self::open(&wal_dir.join(LEGACY_WAL_FILE)),
+        }
     }
 
-    /// Returns the path to the WAL file.
+    /// Returns the path to the WAL file currently being read.
     pub fn path(&self) -> &Path {
         &self.wal_path
     }
@@ -91,11 +215,141 @@ impl WalReader {
         self.current_offset
     }
 
+    /// Returns the total size in bytes of every file being read.
+    pub fn total_bytes(&self) -> u64 {
+        self.files
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    /// Returns the number of bytes read so far, across files.
+    pub fn bytes_read(&self) -> u64 {
+        let earlier: u64 = self.files[..self.file_pos]
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        earlier + self.current_offset
+    }
+
+    /// Returns the offset at which the last record read starts.
+    pub fn last_record_offset(&self) -> u64 {
+        self.record_offset
+    }
+
     /// Returns the last successfully read sequence number.
     pub fn last_sequence_number(&self) -> u64 {
         self.last_sequence
     }
 
+    /// Returns the offset of a torn record at the tail of the last segment.
+    ///
+    /// `Some` only after the reader stopped before such a record. Bytes from
+    /// this offset on were never acknowledged and may be discarded.
+    pub fn torn_tail(&self) -> Option<u64> {
+        self.torn_tail
+    }
+
+    /// Returns the offset of a damaged record that ends the WAL.
+    ///
+    /// `Some` only after [`WalReader::read_next`] failed on a record in the
+    /// last file that runs to its end, so no record follows it.
+    pub fn corrupt_tail(&self) -> Option<u64> {
+        self.corrupt_tail
+    }
+
+    /// Cuts the damaged record reported by [`WalReader::corrupt_tail`] off
+    /// the end of the WAL and fsyncs the file. The reader then ends after
+    /// the last good record.
+    ///
+    /// Returns `None`, changing nothing, if the last failure was not at the
+    /// tail.
+    ///
+    /// # Errors
+    ///
+    /// `AERO_WAL_APPEND_FAILED` / `AERO_WAL_FSYNC_FAILED` on I/O failure.
+    pub fn discard_corrupt_tail(&mut self) -> WalResult<Option<DiscardedTail>> {
+        let Some(offset) = self.corrupt_tail.take() else {
+            return Ok(None);
+        };
+        let file = OpenOptions::new()
+            .write(true)
+            .open(&self.wal_path)
+            .map_err(|e| {
+                WalError::append_failed(
+                    format!("Failed to open WAL file: {}", self.wal_path.display()),
+                    e,
+                )
+            })?;
+        file.set_len(offset).map_err(|e| {
+            WalError::append_failed(
+                format!(
+                    "Failed to discard corrupt tail at offset {} of {}",
+                    offset,
+                    self.wal_path.display()
+                ),
+                e,
+            )
+        })?;
+        file.sync_all().map_err(|e| {
+            WalError::fsync_failed(
+                format!("Failed to fsync WAL file: {}", self.wal_path.display()),
+                e,
+            )
+        })?;
+
+        let discarded = DiscardedTail {
+            path: self.wal_path.clone(),
+            range: offset..self.file_size,
+        };
+        self.current_offset = offset;
+        self.file_size = offset;
+        Ok(Some(discarded))
+    }
+
+    /// Whether the current file is the last segment of a segmented WAL.
+    fn on_last_segment(&self) -> bool {
+        self.segmented && self.on_last_file()
+    }
+
+    /// Whether the current file is the last one to be read.
+    fn on_last_file(&self) -> bool {
+        self.file_pos + 1 == self.files.len()
+    }
+
+    /// Moves to the next file. Returns `false` if there is none.
+    fn advance_file(&mut self) -> WalResult<bool> {
+        if self.file_pos + 1 >= self.files.len() {
+            return Ok(false);
+        }
+        self.file_pos += 1;
+        let (reader, file_size) = open_wal_file(&self.files[self.file_pos])?;
+        self.wal_path = self.files[self.file_pos].clone();
+        self.reader = reader;
+        self.file_size = file_size;
+        self.current_offset = 0;
+        Ok(true)
+    }
+
+    /// Handles an incomplete record at the current offset.
+    ///
+    /// At the tail of the last segment this is a torn write and ends the
+    /// WAL; anywhere else it is corruption (a corrupt tail if it is in the
+    /// last file).
+    fn incomplete_record(&mut self, reason: String) -> WalResult<Option<WalRecord>> {
+        if self.on_last_segment() {
+            self.torn_tail = Some(self.current_offset);
+            self.file_size = self.current_offset;
+            return Ok(None);
+        }
+        if self.on_last_file() {
+            self.corrupt_tail = Some(self.current_offset);
+        }
+        Err(WalError::corruption_at_offset(self.current_offset, reason))
+    }
+
     /// Reads the next record from the WAL.
     ///
     /// Per WAL.md §243-252:
@@ -114,12 +368,16 @@ impl WalReader {
     /// Returns `AERO_WAL_CORRUPTION` if:
     /// - Checksum validation fails
     /// - Record structure is invalid
-    /// - File is truncated mid-record
+    /// - File is truncated mid-record (except at the tail of the last segment)
     /// - Sequence numbers are not strictly increasing
     pub fn read_next(&mut self) -> WalResult<Option<WalRecord>> {
-        // Check if we've reached end of file
-        if self.current_offset >= self.file_size {
-            return Ok(None);
+        self.corrupt_tail = None;
+
+        // Check if we've reached end of file, moving on to the next segment
+        while self.current_offset >= self.file_size {
+            if self.torn_tail.is_some() || !self.advance_file()? {
+                return Ok(None);
+            }
         }
 
         let remaining = self.file_size - self.current_offset;
@@ -127,12 +385,9 @@ impl WalReader {
         // Minimum record size check
         const MIN_RECORD_SIZE: u64 = 4 + 1 + 8 + 20 + 4; // len + type + seq + min_payload + checksum
         if remaining < MIN_RECORD_SIZE {
-            return Err(WalError::corruption_at_offset(
-                self.current_offset,
-                format!(
-                    "Truncated WAL: {} bytes remaining, minimum record size is {}",
-                    remaining, MIN_RECORD_SIZE
-                ),
+            return self.incomplete_record(format!(
+                "Truncated WAL: {} bytes remaining, minimum record size is {}",
+                remaining, MIN_RECORD_SIZE
             ));
         }
 
@@ -155,15 +410,11 @@ impl WalReader {
         }
 
         if record_length > remaining {
-            return Err(WalError::corruption_at_offset(
-                self.current_offset,
-                format!(
-                    "Record length {} exceeds remaining file size {}",
-                    record_length, remaining
-                ),
+            return self.incomplete_record(format!(
+                "Record length {} exceeds remaining file size {}",
+                record_length, remaining
             ));
         }
-
         // Read the rest of the record
         let mut record_buf = vec![0u8; record_length as usize];
         record_buf[0..4].copy_from_slice(&len_buf);
@@ -176,11 +427,27 @@ impl WalReader {
         })?;
 
         // Parse and validate record (includes checksum verification)
-        let (record, bytes_consumed) = WalRecord::deserialize(&record_buf)
-            .map_err(|e| WalError::corruption_at_offset(self.current_offset, e.to_string()))?;
+        let (record, bytes_consumed) = match WalRecord::deserialize(&record_buf) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                if self.on_last_file() && record_length == remaining {
+                    self.corrupt_tail = Some(self.current_offset);
+                }
+                return Err(WalError::corruption_at_offset(
+                    self.current_offset,
+                    e.to_string(),
+                ));
+            }
+        };
+
+        // A checkpoint restarts the sequence at the start of a segment
+        let epoch_start = self.history && self.current_offset == 0 && record.sequence_number == 1;
 
         // Validate sequence number ordering
-        if self.last_sequence > 0 && record.sequence_number != self.last_sequence + 1 {
+        if self.last_sequence > 0
+            && !epoch_start
+            && record.sequence_number != self.last_sequence + 1
+        {
             return Err(WalError::corruption_at_sequence(
                 record.sequence_number,
                 format!(
@@ -191,18 +458,28 @@ impl WalReader {
             ));
         }
 
-        // Validate sequence number starts at 1
-        if self.last_sequence == 0 && record.sequence_number != 1 {
+        // Validate sequence number starts at 1, or at most at the base
+        // sequence when leading segments were removed
+        if self.last_sequence == 0 && !self.history && record.sequence_number > self.base_sequence {
             return Err(WalError::corruption_at_sequence(
                 record.sequence_number,
-                format!(
-                    "First sequence number must be 1, got {}",
-                    record.sequence_number
-                ),
+                if self.base_sequence == 1 {
+                    format!(
+                        "First sequence number must be 1, got {}",
+                        record.sequence_number
+                    )
+                } else {
+                    format!(
+                        "First sequence number must be at most {}, got {}; \
+                         WAL segments are missing",
+                        self.base_sequence, record.sequence_number
+                    )
+                },
             ));
         }
 
         // Update state
+        self.record_offset = self.current_offset;
         self.current_offset += bytes_consumed as u64;
         self.last_sequence = record.sequence_number;
 
@@ -236,17 +513,84 @@ impl WalReader {
 
     /// Resets the reader to the beginning of the WAL.
     pub fn reset(&mut self) -> WalResult<()> {
-        self.reader
-            .seek(SeekFrom::Start(0))
-            .map_err(|e| WalError::corruption(format!("Failed to seek to start of WAL: {}", e)))?;
+        if self.file_pos == 0 && self.torn_tail.is_none() {
+            self.reader.seek(SeekFrom::Start(0)).map_err(|e| {
+                WalError::corruption(format!("Failed to seek to start of WAL: {}", e))
+            })?;
+        } else {
+            let (reader, file_size) = open_wal_file(&self.files[0])?;
+            self.wal_path = self.files[0].clone();
+            self.reader = reader;
+            self.file_size = file_size;
+            self.file_pos = 0;
+            self.torn_tail = None;
+        }
+        self.corrupt_tail = None;
         self.current_offset = 0;
         self.last_sequence = 0;
         Ok(())
     }
 
+    /// Iterates over the remaining records with their positions.
+    ///
+    /// Yields the first error and then stops.
+    pub fn iter_records(&mut self) -> impl Iterator<Item = WalResult<PositionedRecord>> + '_ {
+        let mut failed = false;
+        std::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            match self.read_next() {
+                Ok(Some(record)) => Some(Ok(PositionedRecord {
+                    segment: self
+                        .wal_path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .and_then(parse_segment_file_name),
+                    offset: self.record_offset,
+                    record,
+                })),
+                Ok(None) => None,
+                Err(e) => {
+                    failed = true;
+                    Some(Err(e))
+                }
+            }
+        })
+    }
+
+    /// Positions the reader at `offset` in the first file, as if every
+    /// record before it had been read and the last one was `last_sequence`.
+    ///
+    /// Used to resume tailing the WAL from a stored position. The next
+    /// record must continue from `last_sequence`, so a stale position is
+    /// detected rather than silently skipping records.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AERO_WAL_CORRUPTION` if `offset` is past the end of the file.
+    pub fn seek_to(&mut self, offset: u64, last_sequence: u64) -> WalResult<()> {
+        self.reset()?;
+        if offset > self.file_size {
+            return Err(WalError::corruption(format!(
+                "Offset {} is past the end of {} ({} bytes)",
+                offset,
+                self.wal_path.display(),
+                self.file_size
+            )));
+        }
+        self.reader.seek(SeekFrom::Start(offset)).map_err(|e| {
+            WalError::corruption(format!("Failed to seek to offset {}: {}", offset, e))
+        })?;
+        self.current_offset = offset;
+        self.last_sequence = last_sequence;
+        Ok(())
+    }
+
     /// Returns whether there are more records to read.
     pub fn has_more(&self) -> bool {
         self.current_offset < self.file_size
+            || (self.torn_tail.is_none() && self.file_pos + 1 < self.files.len())
     }
 }
 
@@ -579,4 +923,182 @@ mod tests {
             assert_eq!(r1.payload, r2.payload);
         }
     }
+
+    fn write_segmented(data_dir: &Path, count: usize) -> PathBuf {
+        use super::super::segment::WalSegmentConfig;
+        use super::super::sync_mode::WalSyncConfig;
+
+        let mut writer =
+            WalWriter::open_segmented(data_dir, WalSyncConfig::default(), WalSegmentConfig::new(1))
+                .unwrap();
+        for i in 1..=count {
+            writer
+                .append_insert(create_test_payload(&format!("doc{}", i)))
+                .unwrap();
+        }
+        data_dir.join("wal")
+    }
+
+    fn chop(path: &Path, bytes: u64) {
+        let file = std::fs::OpenOptions::new().write(true).open(path).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len - bytes).unwrap();
+    }
+
+    #[test]
+    fn test_segments_replay_in_order_ignoring_stray_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let wal_dir = write_segmented(temp_dir.path(), 3);
+        std::fs::write(wal_dir.join("0000009.log.tmp"), b"garbage").unwrap();
+        std::fs::write(wal_dir.join("README"), b"garbage").unwrap();
+
+        let mut reader = WalReader::open_from_data_dir(temp_dir.path()).unwrap();
+        let records = reader.read_all().unwrap();
+        let ids: Vec<_> = records
+            .iter()
+            .map(|r| r.payload.document_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["doc1", "doc2", "doc3"]);
+        assert!(reader.torn_tail().is_none());
+
+        reader.reset().unwrap();
+        assert_eq!(reader.read_all().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_torn_tail_of_last_segment_ends_wal() {
+        let temp_dir = TempDir::new().unwrap();
+        let wal_dir = write_segmented(temp_dir.path(), 3);
+        let last = wal_dir.join("0000003.log");
+        chop(&last, 5);
+
+        let mut reader = WalReader::open_segments(&wal_dir).unwrap();
+        let records = reader.read_all().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(reader.torn_tail(), Some(0));
+        assert!(!reader.has_more());
+    }
+
+    #[test]
+    fn test_torn_record_in_earlier_segment_is_corruption() {
+        let temp_dir = TempDir::new().unwrap();
+        let wal_dir = write_segmented(temp_dir.path(), 3);
+        chop(&wal_dir.join("0000002.log"), 5);
+
+        let mut reader = WalReader::open_segments(&wal_dir).unwrap();
+        let err = reader.read_all().unwrap_err();
+        assert_eq!(err.code().code(), "AERO_WAL_CORRUPTION");
+    }
+
+    #[test]
+    fn test_missing_middle_segment_is_corruption() {
+        let temp_dir = TempDir::new().unwrap();
+        let wal_dir = write_segmented(temp_dir.path(), 3);
+        std::fs::remove_file(wal_dir.join("0000002.log")).unwrap();
+
+        let mut reader = WalReader::open_segments(&wal_dir).unwrap();
+        assert!(reader.read_all().is_err());
+    }
+
+    #[test]
+    fn test_history_allows_sequence_restart_at_segment_start() {
+        use super::super::segment::list_segments;
+
+        let temp_dir = TempDir::new().unwrap();
+        let wal_dir = write_segmented(temp_dir.path(), 3);
+        let mut files: Vec<PathBuf> = list_segments(&wal_dir)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.path)
+            .collect();
+
+        // Older segments may have been pruned
+        let tail = files[1..].to_vec();
+        assert_eq!(
+            WalReader::open_history(tail)
+                .unwrap()
+                .read_all()
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // A second epoch: another WAL whose sequence starts again at 1
+        let other = TempDir::new().unwrap();
+        let other_wal = write_segmented(other.path(), 2);
+        let restarted = wal_dir.join("0000004.log");
+        std::fs::copy(other_wal.join("0000001.log"), &restarted).unwrap();
+        files.push(restarted);
+
+        let records = WalReader::open_history(files.clone())
+            .unwrap()
+            .read_all()
+            .unwrap();
+        let seqs: Vec<u64> = records.iter().map(|r| r.sequence_number).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 1]);
+        assert!(WalReader::open_segments(&wal_dir)
+            .unwrap()
+            .read_all()
+            .is_err());
+
+        // Out-of-order segments are still corruption
+        files.swap(0, 1);
+        assert!(WalReader::open_history(files).unwrap().read_all().is_err());
+    }
+    #[test]
+    fn test_seek_to_resumes_after_stored_position() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let mut writer = WalWriter::open(temp_dir.path()).unwrap();
+            for i in 0..3 {
+                writer
+                    .append_insert(create_test_payload(&format!("doc{}", i)))
+                    .unwrap();
+            }
+        }
+        let wal_path = temp_dir.path().join("wal").join("wal.log");
+
+        let mut reader = WalReader::open(&wal_path).unwrap();
+        reader.read_next().unwrap().unwrap();
+        let (offset, sequence) = (reader.current_offset(), reader.last_sequence_number());
+
+        let mut resumed = WalReader::open(&wal_path).unwrap();
+        resumed.seek_to(offset, sequence).unwrap();
+        let seqs: Vec<u64> = resumed
+            .read_all()
+            .unwrap()
+            .iter()
+            .map(|r| r.sequence_number)
+            .collect();
+        assert_eq!(seqs, vec![2, 3]);
+
+        // A position that does not continue the stored sequence is stale
+        let mut stale = WalReader::open(&wal_path).unwrap();
+        stale.seek_to(offset, 5).unwrap();
+        assert!(stale.read_next().is_err());
+        assert!(stale.seek_to(u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_iter_records_reports_positions() {
+        let temp_dir = TempDir::new().unwrap();
+        let wal_dir = write_segmented(temp_dir.path(), 3);
+
+        let mut reader = WalReader::open_segments(&wal_dir).unwrap();
+        let positions: Vec<(Option<u64>, u64, u64)> = reader
+            .iter_records()
+            .map(|r| r.unwrap())
+            .map(|r| (r.segment, r.offset, r.record.sequence_number))
+            .collect();
+        assert_eq!(positions.len(), 3);
+        assert!(positions.iter().all(|&(_, offset, _)| offset == 0));
+        assert_eq!(positions[2], (Some(3), 0, 3));
+
+        // Corruption is yielded once, then iteration stops
+        chop(&wal_dir.join("0000001.log"), 3);
+        let mut reader = WalReader::open_segments(&wal_dir).unwrap();
+        let results: Vec<_> = reader.iter_records().collect();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
 }
@@ -35,6 +35,10 @@ pub enum RecordType {
     /// MVCC garbage collection record
     /// Per MVCC_GC.md: GC events must be WAL-recorded for deterministic replay
     MvccGc = 5,
+    /// Rename of a collection: every live document moves to the new name
+    CollectionRename = 6,
+    /// Copy of a collection; the inserts of the copies follow it
+    CollectionCopy = 7,
 }
 
 impl RecordType {
@@ -47,6 +51,8 @@ impl RecordType {
             3 => Some(RecordType::MvccCommit),
             4 => Some(RecordType::MvccVersion),
             5 => Some(RecordType::MvccGc),
+            6 => Some(RecordType::CollectionRename),
+            7 => Some(RecordType::CollectionCopy),
             _ => None,
         }
     }
@@ -59,10 +65,32 @@ impl RecordType {
         )
     }
 
+    /// Returns true if this record renames or copies a whole collection
+    pub fn is_collection_change(self) -> bool {
+        matches!(
+            self,
+            RecordType::CollectionRename | RecordType::CollectionCopy
+        )
+    }
+
     /// Convert to u8
     pub fn as_u8(self) -> u8 {
         self as u8
     }
+
+    /// Lowercase name, as shown by diagnostics
+    pub fn name(self) -> &'static str {
+        match self {
+            RecordType::Insert => "insert",
+            RecordType::Update => "update",
+            RecordType::Delete => "delete",
+            RecordType::MvccCommit => "mvcc_commit",
+            RecordType::MvccVersion => "mvcc_version",
+            RecordType::MvccGc => "mvcc_gc",
+            RecordType::CollectionRename => "collection_rename",
+            RecordType::CollectionCopy => "collection_copy",
+        }
+    }
 }
 
 /// MVCC Commit payload per MVCC_WAL_INTERACTION.md
@@ -240,7 +268,8 @@ pub struct WalPayload {
     /// Schema version identifier
     pub schema_version: String,
     /// Full document body (post-operation state)
-    /// For DELETE operations, this is empty (tombstone)
+    /// For DELETE operations, this is empty (tombstone), except for a
+    /// purge, which carries the removed document
     pub document_body: Vec<u8>,
 }
 
@@ -278,6 +307,36 @@ impl WalPayload {
         }
     }
 
+    /// Create the tombstone payload of a purge, carrying the removed
+    /// document so change streams can tell it from a delete. Storage
+    /// replay drops the body like that of any tombstone.
+    pub fn purge(
+        collection_id: impl Into<String>,
+        document_id: impl Into<String>,
+        schema_id: impl Into<String>,
+        document_body: Vec<u8>,
+    ) -> Self {
+        Self {
+            document_body,
+            ..Self::tombstone(collection_id, document_id, schema_id, "")
+        }
+    }
+
+    /// Whether a DELETE record's payload is that of a purge
+    pub fn is_purge(&self) -> bool {
+        !self.document_body.is_empty()
+    }
+
+    /// Create the payload of a COLLECTION_RENAME or COLLECTION_COPY
+    /// record: the source collection is the schema id, the target the body
+    pub fn collection_change(
+        collection_id: impl Into<String>,
+        from: impl Into<String>,
+        to: &str,
+    ) -> Self {
+        Self::new(collection_id, "", from, "", to.as_bytes().to_vec())
+    }
+
     /// Serialize payload to bytes
     ///
     /// Format:
@@ -379,6 +438,12 @@ pub struct WalRecord {
     pub sequence_number: u64,
     /// Operation payload
     pub payload: WalPayload,
+    /// Commit time in milliseconds since the Unix epoch, stamped by the
+    /// writer. `None` for records written before timestamps were recorded.
+    pub commit_timestamp_ms: Option<u64>,
+    /// Authority epoch of the primary that wrote the record. 0 until the
+    /// first promotion.
+    pub epoch: u64,
 }
 
 impl WalRecord {
@@ -388,9 +453,23 @@ impl WalRecord {
             record_type,
             sequence_number,
             payload,
+            commit_timestamp_ms: None,
+            epoch: 0,
         }
     }
 
+    /// Sets the commit timestamp (milliseconds since the Unix epoch).
+    pub fn with_commit_timestamp(mut self, timestamp_ms: u64) -> Self {
+        self.commit_timestamp_ms = Some(timestamp_ms);
+        self
+    }
+
+    /// Sets the authority epoch of the writing primary.
+    pub fn with_epoch(mut self, epoch: u64) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
     /// Create an INSERT record
     pub fn insert(sequence_number: u64, payload: WalPayload) -> Self {
         Self::new(RecordType::Insert, sequence_number, payload)
@@ -406,6 +485,16 @@ impl WalRecord {
         Self::new(RecordType::Delete, sequence_number, payload)
     }
 
+    /// Source and target collection of a COLLECTION_RENAME or
+    /// COLLECTION_COPY record, or `None` for any other record
+    pub fn collection_change(&self) -> Option<(&str, &str)> {
+        if !self.record_type.is_collection_change() {
+            return None;
+        }
+        let to = std::str::from_utf8(&self.payload.document_body).ok()?;
+        Some((&self.payload.schema_id, to))
+    }
+
     /// Serialize the record body (everything except length prefix and checksum)
     /// This is the data over which the checksum is computed.
     ///
@@ -413,14 +502,22 @@ impl WalRecord {
     /// - Record Type (u8)
     /// - Sequence Number (u64 LE)
     /// - Payload (variable)
+    /// - Commit Timestamp (u64 LE, optional)
+    /// - Epoch (u64 LE, optional; only after a commit timestamp)
     fn serialize_body(&self) -> Vec<u8> {
         let payload_bytes = self.payload.serialize();
-        let body_len = 1 + 8 + payload_bytes.len();
+        let body_len = 1 + 8 + payload_bytes.len() + 16;
         let mut buf = Vec::with_capacity(body_len);
 
         buf.push(self.record_type.as_u8());
         buf.extend_from_slice(&self.sequence_number.to_le_bytes());
         buf.extend_from_slice(&payload_bytes);
+        if self.epoch > 0 {
+            buf.extend_from_slice(&self.commit_timestamp_ms.unwrap_or(0).to_le_bytes());
+            buf.extend_from_slice(&self.epoch.to_le_bytes());
+        } else if let Some(timestamp_ms) = self.commit_timestamp_ms {
+            buf.extend_from_slice(&timestamp_ms.to_le_bytes());
+        }
 
         buf
     }
@@ -432,7 +529,13 @@ impl WalRecord {
     /// - Record Type (u8)
     /// - Sequence Number (u64 LE)
     /// - Payload (variable)
+    /// - Commit Timestamp (u64 LE, optional)
+    /// - Epoch (u64 LE, optional)
     /// - Checksum (u32 LE)
+    ///
+    /// The commit timestamp and epoch are trailing fields after the
+    /// payload, so records without them keep their original layout. The
+    /// epoch is written only when non-zero.
     pub fn serialize(&self) -> Vec<u8> {
         let body = self.serialize_body();
 
@@ -526,15 +629,32 @@ impl WalRecord {
             data[5], data[6], data[7], data[8], data[9], data[10], data[11], data[12],
         ]);
 
-        // Parse payload
+        // Parse payload, followed by the optional commit timestamp and epoch
         let payload_data = &data[13..checksum_offset];
-        let payload = WalPayload::deserialize(payload_data)?;
+        let mut cursor = io::Cursor::new(payload_data);
+        let payload = WalPayload::read_from(&mut cursor)?;
+
+        let trailing = &payload_data[cursor.position() as usize..];
+        let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+        let (commit_timestamp_ms, epoch) = match trailing.len() {
+            0 => (None, 0),
+            8 => (Some(read_u64(trailing)), 0),
+            16 => (Some(read_u64(&trailing[..8])), read_u64(&trailing[8..])),
+            n => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unexpected {} trailing bytes after payload", n),
+                ))
+            }
+        };
 
         Ok((
             WalRecord {
                 record_type,
                 sequence_number,
                 payload,
+                commit_timestamp_ms,
+                epoch,
             },
             record_length,
         ))
@@ -835,11 +955,28 @@ mod tests {
 
     #[test]
     fn test_invalid_record_type() {
-        // 5 is now valid (MvccGc), so test 6 and 255
-        assert!(RecordType::from_u8(6).is_none());
+        // 6 and 7 are now valid (collection changes), so test 8 and 255
+        assert!(RecordType::from_u8(8).is_none());
         assert!(RecordType::from_u8(255).is_none());
     }
 
+    #[test]
+    fn test_collection_change_record() {
+        let payload = WalPayload::collection_change("default", "users", "members");
+        let record = WalRecord::new(RecordType::CollectionRename, 7, payload);
+
+        let bytes = record.serialize();
+        let (recovered, _) = WalRecord::deserialize(&bytes).unwrap();
+
+        assert_eq!(recovered.record_type, RecordType::CollectionRename);
+        assert!(recovered.record_type.is_collection_change());
+        assert_eq!(recovered.collection_change(), Some(("users", "members")));
+        assert_eq!(RecordType::from_u8(7), Some(RecordType::CollectionCopy));
+        assert!(WalRecord::insert(1, sample_payload())
+            .collection_change()
+            .is_none());
+    }
+
     #[test]
     fn test_mvcc_commit_record_type() {
         assert_eq!(RecordType::MvccCommit.as_u8(), 3);
@@ -876,6 +1013,38 @@ mod tests {
         assert_eq!(bytes_consumed, serialized.len());
     }
 
+    #[test]
+    fn test_commit_timestamp_roundtrip() {
+        let untimed = WalRecord::insert(1, sample_payload());
+        let record = untimed.clone().with_commit_timestamp(1_700_000_000_123);
+        let serialized = record.serialize();
+        assert_eq!(serialized.len(), untimed.serialize().len() + 8);
+
+        let (deserialized, _) = WalRecord::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized.commit_timestamp_ms, Some(1_700_000_000_123));
+        assert_eq!(deserialized, record);
+
+        let (deserialized, _) = WalRecord::deserialize(&untimed.serialize()).unwrap();
+        assert_eq!(deserialized.commit_timestamp_ms, None);
+    }
+
+    #[test]
+    fn test_epoch_roundtrip() {
+        let timed = WalRecord::insert(1, sample_payload()).with_commit_timestamp(1_700_000_000_123);
+        let record = timed.clone().with_epoch(3);
+        let serialized = record.serialize();
+        assert_eq!(serialized.len(), timed.serialize().len() + 8);
+
+        let (deserialized, _) = WalRecord::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized.epoch, 3);
+        assert_eq!(deserialized, record);
+
+        // Epoch 0 keeps the layout of records written before epochs
+        assert_eq!(timed.clone().with_epoch(0).serialize(), timed.serialize());
+        let (deserialized, _) = WalRecord::deserialize(&timed.serialize()).unwrap();
+        assert_eq!(deserialized.epoch, 0);
+    }
+
     #[test]
     fn test_record_sequence_number_preserved() {
         let record = WalRecord::update(42, sample_payload());
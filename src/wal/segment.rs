@@ -0,0 +1,399 @@
+//! WAL segment naming and discovery
+//!
+//! A segmented WAL lives in `<data_dir>/wal/` as a series of files named
+//! `0000001.log`, `0000002.log`, ... Records are replayed in segment
+//! index order and sequence numbers are contiguous across segments.
+//!
+//! Rules:
+//! - Only the highest-indexed segment (the active segment) is written to
+//! - A segment is fsynced before the next one is created
+//! - Segment indices are never reused
+//! - Files that do not match the naming pattern are ignored
+//!
+//! A legacy `wal.log` and numbered segments never coexist: the writer
+//! renames `wal.log` to the first segment when it switches to segmented
+//! mode.
+//!
+//! After a checkpoint removes the segments below a snapshot fence, the
+//! first retained sequence number is recorded in `base_sequence`. The WAL
+//! may then start at any sequence up to the base; without the file it
+//! must start at 1.
+//!
+//! The authority epoch the WAL is written in is recorded in `epoch`, so it
+//! survives checkpoints that remove every record carrying it.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::errors::{WalError, WalResult};
+
+/// File name of the legacy single-file WAL.
+pub const LEGACY_WAL_FILE: &str = "wal.log";
+
+/// File recording the lowest sequence number the WAL must still hold.
+pub const WAL_BASE_FILE: &str = "base_sequence";
+
+/// File recording the authority epoch of the WAL.
+pub const WAL_EPOCH_FILE: &str = "epoch";
+
+/// Number of digits in a segment file name.
+const SEGMENT_INDEX_DIGITS: usize = 7;
+
+/// Default segment size (64 MiB).
+pub const DEFAULT_SEGMENT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Segmentation settings for a WAL writer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalSegmentConfig {
+    /// The active segment is rolled once it reaches this size.
+    ///
+    /// A single append never spans segments, so a segment may exceed this
+    /// by at most one record (or one batch).
+    pub segment_bytes: u64,
+    /// Completed segments are copied here before a checkpoint deletes them.
+    pub archive_dir: Option<PathBuf>,
+}
+
+impl Default for WalSegmentConfig {
+    fn default() -> Self {
+        Self {
+            segment_bytes: DEFAULT_SEGMENT_BYTES,
+            archive_dir: None,
+        }
+    }
+}
+
+impl WalSegmentConfig {
+    /// Segment config with the given size and no archive directory.
+    pub fn new(segment_bytes: u64) -> Self {
+        Self {
+            segment_bytes,
+            archive_dir: None,
+        }
+    }
+
+    /// Sets the archive directory for completed segments.
+    pub fn with_archive_dir(mut self, archive_dir: impl Into<PathBuf>) -> Self {
+        self.archive_dir = Some(archive_dir.into());
+        self
+    }
+}
+
+/// A numbered WAL segment on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalSegment {
+    /// Segment index (1-based, never reused)
+    pub index: u64,
+    /// Full path to the segment file
+    pub path: PathBuf,
+}
+
+/// On-disk layout of a WAL directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalLayout {
+    /// No WAL files
+    Empty,
+    /// A single `wal.log`
+    Legacy(PathBuf),
+    /// Numbered segments in index order
+    Segmented(Vec<WalSegment>),
+}
+
+/// Returns the file name for a segment index, e.g. `0000001.log`.
+pub fn segment_file_name(index: u64) -> String {
+    format!("{:0width$}.log", index, width = SEGMENT_INDEX_DIGITS)
+}
+
+/// Parses a segment index from a file name.
+///
+/// Returns `None` for anything other than exactly seven ASCII digits
+/// followed by `.log`.
+pub fn parse_segment_file_name(name: &str) -> Option<u64> {
+    let digits = name.strip_suffix(".log")?;
+    if digits.len() != SEGMENT_INDEX_DIGITS || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().filter(|&index| index > 0)
+}
+
+/// Lists the segments in a WAL directory, sorted by index.
+///
+/// Stray files and subdirectories are ignored. A missing directory has
+/// no segments.
+pub fn list_segments(wal_dir: &Path) -> WalResult<Vec<WalSegment>> {
+    let entries = match fs::read_dir(wal_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(WalError::corruption(format!(
+                "Failed to list WAL directory {}: {}",
+                wal_dir.display(),
+                e
+            )))
+        }
+    };
+
+    let mut segments = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| {
+            WalError::corruption(format!(
+                "Failed to list WAL directory {}: {}",
+                wal_dir.display(),
+                e
+            ))
+        })?;
+        let Some(index) = entry.file_name().to_str().and_then(parse_segment_file_name) else {
+            continue;
+        };
+        if !entry.path().is_file() {
+            continue;
+        }
+        segments.push(WalSegment {
+            index,
+            path: entry.path(),
+        });
+    }
+
+    segments.sort_by_key(|s| s.index);
+    Ok(segments)
+}
+
+/// Determines whether a WAL directory holds a legacy or segmented WAL.
+///
+/// # Errors
+///
+/// Returns `AERO_WAL_CORRUPTION` if both `wal.log` and numbered segments
+/// exist, since their relative order is unknown.
+pub fn detect_layout(wal_dir: &Path) -> WalResult<WalLayout> {
+    let segments = list_segments(wal_dir)?;
+    let legacy = wal_dir.join(LEGACY_WAL_FILE);
+
+    match (legacy.is_file(), segments.is_empty()) {
+        (false, true) => Ok(WalLayout::Empty),
+        (true, true) => Ok(WalLayout::Legacy(legacy)),
+        (false, false) => Ok(WalLayout::Segmented(segments)),
+        (true, false) => Err(WalError::corruption(format!(
+            "WAL directory {} contains both {} and numbered segments",
+            wal_dir.display(),
+            LEGACY_WAL_FILE
+        ))),
+    }
+}
+
+/// Reads the base sequence of a WAL directory.
+///
+/// A missing file means the WAL starts at sequence 1.
+pub fn read_base_sequence(wal_dir: &Path) -> WalResult<u64> {
+    let path = wal_dir.join(WAL_BASE_FILE);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(1),
+        Err(e) => {
+            return Err(WalError::corruption(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    contents
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&sequence| sequence > 0)
+        .ok_or_else(|| {
+            WalError::corruption(format!(
+                "Invalid base sequence in {}: {:?}",
+                path.display(),
+                contents.trim()
+            ))
+        })
+}
+
+/// Durably records the base sequence of a WAL directory.
+///
+/// Written to a temporary file, fsynced and renamed into place, so a crash
+/// leaves either the old or the new base.
+pub fn write_base_sequence(wal_dir: &Path, sequence: u64) -> WalResult<()> {
+    write_number(wal_dir, WAL_BASE_FILE, sequence)
+}
+
+/// Reads the authority epoch of a WAL directory.
+///
+/// A missing file means epoch 0: no promotion has happened.
+pub fn read_epoch(wal_dir: &Path) -> WalResult<u64> {
+    let path = wal_dir.join(WAL_EPOCH_FILE);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => {
+            return Err(WalError::corruption(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    contents.trim().parse().map_err(|_| {
+        WalError::corruption(format!(
+            "Invalid epoch in {}: {:?}",
+            path.display(),
+            contents.trim()
+        ))
+    })
+}
+
+/// Durably records the authority epoch of a WAL directory.
+///
+/// Written like [`write_base_sequence`].
+pub fn write_epoch(wal_dir: &Path, epoch: u64) -> WalResult<()> {
+    write_number(wal_dir, WAL_EPOCH_FILE, epoch)
+}
+
+/// Writes `value` to `wal_dir/name` through a fsynced temporary file.
+fn write_number(wal_dir: &Path, name: &str, value: u64) -> WalResult<()> {
+    let path = wal_dir.join(name);
+    let temp_path = wal_dir.join(format!("{}.tmp", name));
+
+    let mut file = fs::File::create(&temp_path).map_err(|e| {
+        WalError::append_failed(format!("Failed to create {}", temp_path.display()), e)
+    })?;
+    io::Write::write_all(&mut file, format!("{}\n", value).as_bytes()).map_err(|e| {
+        WalError::append_failed(format!("Failed to write {}", temp_path.display()), e)
+    })?;
+    file.sync_all().map_err(|e| {
+        WalError::fsync_failed(format!("Failed to fsync {}", temp_path.display()), e)
+    })?;
+    fs::rename(&temp_path, &path).map_err(|e| {
+        WalError::append_failed(format!("Failed to rename {}", temp_path.display()), e)
+    })?;
+    fsync_dir(wal_dir)
+}
+
+/// Removes the base sequence, so the WAL must again start at 1.
+pub fn clear_base_sequence(wal_dir: &Path) -> WalResult<()> {
+    let path = wal_dir.join(WAL_BASE_FILE);
+    match fs::remove_file(&path) {
+        Ok(()) => fsync_dir(wal_dir),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(WalError::append_failed(
+            format!("Failed to remove {}", path.display()),
+            e,
+        )),
+    }
+}
+
+/// fsyncs a directory so that file creation, rename and removal are durable.
+pub(crate) fn fsync_dir(dir: &Path) -> WalResult<()> {
+    let handle = fs::File::open(dir).map_err(|e| {
+        WalError::append_failed(
+            format!("Failed to open WAL directory for fsync: {}", dir.display()),
+            e,
+        )
+    })?;
+    handle.sync_all().map_err(|e| {
+        WalError::fsync_failed(
+            format!("Failed to fsync WAL directory: {}", dir.display()),
+            e,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_segment_name_roundtrip() {
+        assert_eq!(segment_file_name(1), "0000001.log");
+        assert_eq!(parse_segment_file_name("0000001.log"), Some(1));
+        assert_eq!(
+            parse_segment_file_name(&segment_file_name(4242)),
+            Some(4242)
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_non_matching_names() {
+        for name in [
+            "wal.log",
+            "0000001.log.tmp",
+            "000001.log",
+            "00000001.log",
+            "000000a.log",
+            "0000000.log",
+            ".0000001.log",
+            "0000001.LOG",
+        ] {
+            assert_eq!(parse_segment_file_name(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_list_segments_sorted_and_ignores_strays() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        for name in ["0000003.log", "0000001.log", "0000002.log", "notes.txt"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+        fs::create_dir(dir.join("0000004.log")).unwrap();
+
+        let indices: Vec<u64> = list_segments(dir)
+            .unwrap()
+            .iter()
+            .map(|s| s.index)
+            .collect();
+        assert_eq!(indices, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_base_sequence_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(read_base_sequence(temp.path()).unwrap(), 1);
+
+        write_base_sequence(temp.path(), 42).unwrap();
+        assert_eq!(read_base_sequence(temp.path()).unwrap(), 42);
+        assert!(list_segments(temp.path()).unwrap().is_empty());
+
+        clear_base_sequence(temp.path()).unwrap();
+        clear_base_sequence(temp.path()).unwrap();
+        assert_eq!(read_base_sequence(temp.path()).unwrap(), 1);
+
+        fs::write(temp.path().join(WAL_BASE_FILE), "zero").unwrap();
+        assert!(read_base_sequence(temp.path()).is_err());
+    }
+
+    #[test]
+    fn test_epoch_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(read_epoch(temp.path()).unwrap(), 0);
+
+        write_epoch(temp.path(), 7).unwrap();
+        assert_eq!(read_epoch(temp.path()).unwrap(), 7);
+        assert_eq!(detect_layout(temp.path()).unwrap(), WalLayout::Empty);
+
+        fs::write(temp.path().join(WAL_EPOCH_FILE), "seven").unwrap();
+        assert!(read_epoch(temp.path()).is_err());
+    }
+
+    #[test]
+    fn test_detect_layout() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        assert_eq!(detect_layout(dir).unwrap(), WalLayout::Empty);
+
+        fs::write(dir.join(LEGACY_WAL_FILE), b"").unwrap();
+        assert!(matches!(detect_layout(dir).unwrap(), WalLayout::Legacy(_)));
+
+        fs::write(dir.join("0000001.log"), b"").unwrap();
+        assert!(detect_layout(dir).is_err());
+
+        fs::remove_file(dir.join(LEGACY_WAL_FILE)).unwrap();
+        assert!(matches!(
+            detect_layout(dir).unwrap(),
+            WalLayout::Segmented(ref s) if s.len() == 1
+        ));
+    }
+}
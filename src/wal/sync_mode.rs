@@ -0,0 +1,143 @@
+//! WAL sync modes
+//!
+//! Per WAL.md §175-198, the baseline durability rule is "fsync before
+//! acknowledgment". This module makes the *kind* of sync configurable
+//! without relaxing that rule for acknowledged writes:
+//!
+//! - `fsync`: `sync_all()` after every append. Default. No caveats.
+//! - `fdatasync`: `sync_data()` after every append. File data (and the
+//!   size needed to read it back) is durable, but other inode metadata
+//!   such as mtime is not. Some filesystems implement it as a full fsync.
+//! - `group_commit`: writes share one `sync_all()` per batch, whether from
+//!   concurrent writers or from requests held until the batch closes.
+//!   Acknowledged writes are exactly as durable as in `fsync` mode; the
+//!   trade-off is added commit latency (up to the batch window) and that a
+//!   crash before the batch sync loses the whole unacknowledged batch.
+
+use std::fmt;
+use std::time::Duration;
+
+/// How the WAL makes appended records durable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WalSyncMode {
+    /// `fsync` (sync_all) after every append. Baseline behavior.
+    #[default]
+    Fsync,
+    /// `fdatasync` (sync_data) after every append.
+    Fdatasync,
+    /// One `fsync` per batch of concurrently arriving records.
+    GroupCommit,
+}
+
+impl WalSyncMode {
+    /// All accepted configuration names, in documentation order.
+    pub const NAMES: [&'static str; 3] = ["fsync", "fdatasync", "group_commit"];
+
+    /// Parse a mode from its configuration name.
+    ///
+    /// The error message lists every accepted value together with its
+    /// durability trade-off so a typo in the config is self-explanatory.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "fsync" => Ok(Self::Fsync),
+            "fdatasync" => Ok(Self::Fdatasync),
+            "group_commit" => Ok(Self::GroupCommit),
+            other => Err(format!(
+                "Invalid wal_sync_mode: '{}'. Allowed values: \
+                 'fsync' (default; full fsync after every write, no caveats), \
+                 'fdatasync' (syncs file data only; inode metadata such as mtime may be lost on crash), \
+                 'group_commit' (one fsync per batch of concurrent writes; acknowledged writes are \
+                 as durable as 'fsync', but commits wait up to the group commit window and a crash \
+                 before the batch sync loses the whole unacknowledged batch).",
+                other
+            )),
+        }
+    }
+
+    /// Configuration name of this mode.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fsync => "fsync",
+            Self::Fdatasync => "fdatasync",
+            Self::GroupCommit => "group_commit",
+        }
+    }
+}
+
+impl fmt::Display for WalSyncMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Sync configuration for a WAL writer.
+///
+/// The group commit parameters are ignored unless `mode` is
+/// [`WalSyncMode::GroupCommit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalSyncConfig {
+    /// Sync mode.
+    pub mode: WalSyncMode,
+    /// Maximum time the first record of a batch waits for more records.
+    pub group_commit_interval: Duration,
+    /// A batch is synced as soon as it holds this many records.
+    pub group_commit_max_records: usize,
+}
+
+impl Default for WalSyncConfig {
+    fn default() -> Self {
+        Self {
+            mode: WalSyncMode::Fsync,
+            group_commit_interval: Duration::from_millis(2),
+            group_commit_max_records: 64,
+        }
+    }
+}
+
+impl WalSyncConfig {
+    /// Config for the given mode with default group commit parameters.
+    pub fn new(mode: WalSyncMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    /// Config for group commit with an explicit window.
+    pub fn group_commit(interval: Duration, max_records: usize) -> Self {
+        Self {
+            mode: WalSyncMode::GroupCommit,
+            group_commit_interval: interval,
+            group_commit_max_records: max_records,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_fsync() {
+        assert_eq!(WalSyncMode::default(), WalSyncMode::Fsync);
+        assert_eq!(WalSyncConfig::default().mode, WalSyncMode::Fsync);
+    }
+
+    #[test]
+    fn test_parse_roundtrip() {
+        for name in WalSyncMode::NAMES {
+            let mode = WalSyncMode::parse(name).unwrap();
+            assert_eq!(mode.as_str(), name);
+        }
+    }
+
+    #[test]
+    fn test_parse_typo_explains_tradeoffs() {
+        let err = WalSyncMode::parse("fsnyc").unwrap_err();
+        assert!(err.contains("'fsnyc'"));
+        for name in WalSyncMode::NAMES {
+            assert!(err.contains(name));
+        }
+        assert!(err.contains("unacknowledged batch"));
+    }
+}
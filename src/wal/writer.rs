@@ -1,34 +1,101 @@
 //! WAL writer with fsync enforcement per WAL.md
 //!
 //! Per WAL.md §175-198:
-//! - Every WAL append is followed by fsync
-//! - No batching
-//! - No group commit
+//! - Every WAL append is followed by a sync
 //! - No async durability
 //!
-//! Acknowledgment before fsync is forbidden.
+//! Acknowledgment before sync is forbidden.
+//!
+//! The kind of sync is selected by `WalSyncMode`. In `group_commit` mode a
+//! single `WalWriter` still syncs every append (a batch of one); batching
+//! across writers happens in `GroupCommitWriter`, which wraps this writer.
+//! A single executor batches instead by deferring the syncs (see
+//! [`WalWriter::with_deferred_sync`]) and acknowledging its writes only
+//! after [`WalWriter::sync_deferred`], as `GroupCommitBatch` does.
+//!
+//! A writer opened with [`WalWriter::open_segmented`] appends to the
+//! highest-numbered segment in `wal/` and rolls to a new segment once the
+//! active one reaches `segment_bytes`. Rolling happens before an append,
+//! never after, so a failed roll can't turn an acknowledged write into an
+//! error.
+//!
+//! Every record is stamped with a commit timestamp (milliseconds since the
+//! Unix epoch). Timestamps never decrease within a WAL, even if the system
+//! clock steps backwards, so point-in-time restore can cut the WAL at the
+//! first record past its target.
+//!
+//! Writes go through a [`StorageFile`] (see [`WalWriter::with_io`]). A write
+//! that fails part way is cut back off the file, so the next append still
+//! starts on a record boundary.
 
-use std::fs::{self, File, OpenOptions};
-use std::io::{self, Write};
+use std::fs::{self, OpenOptions};
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::crash_point::{maybe_crash, points};
+use crate::storage_io::{OsIo, StorageFile, StorageIo};
 
 use super::errors::{WalError, WalResult};
 use super::record::{RecordType, WalPayload, WalRecord};
+use super::segment::{
+    clear_base_sequence, detect_layout, fsync_dir, list_segments, read_base_sequence, read_epoch,
+    segment_file_name, write_epoch, WalLayout, WalSegment, WalSegmentConfig, LEGACY_WAL_FILE,
+};
+use super::sync_mode::{WalSyncConfig, WalSyncMode};
 
 /// WAL writer that enforces fsync after every append.
 ///
 /// Per WAL.md §69-74:
 /// - Append-only
-/// - Single file
+/// - Single file (or, when segmented, a single active segment)
 /// - Never truncated in Phase 0
 /// - Opened with exclusive write access
 pub struct WalWriter {
-    /// Path to the WAL file
+    /// Path to the WAL file (the active segment when segmented)
     wal_path: PathBuf,
     /// Underlying file handle
-    file: File,
+    file: Box<dyn StorageFile>,
+    /// Opens the files appended to
+    io: Arc<dyn StorageIo>,
+    /// A failed write left bytes that could not be cut off the file
+    torn_tail: bool,
     /// Next sequence number to assign (starts at 1, never reused)
     next_sequence: u64,
+    /// How appends are made durable
+    sync_config: WalSyncConfig,
+    /// Active segment state; `None` for a legacy single-file WAL
+    segment: Option<ActiveSegment>,
+    /// Commit timestamp of the last record written, in ms since the epoch
+    last_commit_timestamp_ms: u64,
+    /// Authority epoch stamped on new records
+    epoch: u64,
+    /// Successful syncs performed by appends since open
+    sync_count: u64,
+    /// Appends leave the sync to [`WalWriter::sync_deferred`]
+    deferred_sync: bool,
+    /// Records written since the last sync, when syncs are deferred
+    unsynced_records: usize,
+}
+
+/// Bookkeeping for the active segment of a segmented WAL.
+#[derive(Debug)]
+struct ActiveSegment {
+    config: WalSegmentConfig,
+    index: u64,
+    len: u64,
+}
+
+impl std::fmt::Debug for WalWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalWriter")
+            .field("wal_path", &self.wal_path)
+            .field("next_sequence", &self.next_sequence)
+            .field("sync_config", &self.sync_config)
+            .field("segment", &self.segment)
+            .finish()
+    }
 }
 
 impl WalWriter {
@@ -45,8 +112,15 @@ impl WalWriter {
     ///
     /// Returns `WalError::append_failed` if the file cannot be created or opened.
     pub fn open(data_dir: &Path) -> WalResult<Self> {
+        Self::open_with_sync(data_dir, WalSyncConfig::default())
+    }
+
+    /// Opens or creates a WAL file using the given sync configuration.
+    ///
+    /// Identical to [`WalWriter::open`] except for how appends are synced.
+    pub fn open_with_sync(data_dir: &Path, sync_config: WalSyncConfig) -> WalResult<Self> {
         let wal_dir = data_dir.join("wal");
-        let wal_path = wal_dir.join("wal.log");
+        let wal_path = wal_dir.join(LEGACY_WAL_FILE);
 
         // Create directories if missing
         if !wal_dir.exists() {
@@ -71,47 +145,213 @@ impl WalWriter {
             })?;
 
         // Determine next sequence number by reading existing WAL
-        let next_sequence = Self::determine_next_sequence(&wal_path)?;
+        let (next_sequence, last_commit_timestamp_ms, record_epoch) =
+            Self::scan_existing(&wal_path)?;
+        let epoch = read_epoch(&wal_dir)?.max(record_epoch);
 
         Ok(Self {
             wal_path,
-            file,
+            file: Box::new(file),
+            io: Arc::new(OsIo),
+            torn_tail: false,
             next_sequence,
+            sync_config,
+            segment: None,
+            last_commit_timestamp_ms,
+            epoch,
+            sync_count: 0,
+            deferred_sync: false,
+            unsynced_records: 0,
         })
     }
 
-    /// Determines the next sequence number by scanning existing WAL.
+    /// Opens or creates a segmented WAL in `<data_dir>/wal/`.
     ///
-    /// Returns 1 if WAL is empty or does not exist.
-    fn determine_next_sequence(wal_path: &Path) -> WalResult<u64> {
+    /// - An existing legacy `wal.log` is renamed to the first segment.
+    /// - An empty directory gets a first segment. Its index follows the
+    ///   highest index in the archive directory, so names are never reused.
+    /// - A torn record at the tail of the last segment was never
+    ///   acknowledged and is cut off before appending resumes.
+    ///
+    /// # Errors
+    ///
+    /// - `AERO_WAL_CORRUPTION` if existing segments fail validation
+    /// - `AERO_WAL_APPEND_FAILED` / `AERO_WAL_FSYNC_FAILED` on I/O failure
+    pub fn open_segmented(
+        data_dir: &Path,
+        sync_config: WalSyncConfig,
+        segment_config: WalSegmentConfig,
+    ) -> WalResult<Self> {
+        use super::reader::WalReader;
+
+        let wal_dir = data_dir.join("wal");
+        fs::create_dir_all(&wal_dir).map_err(|e| {
+            WalError::append_failed(
+                format!("Failed to create WAL directory: {}", wal_dir.display()),
+                e,
+            )
+        })?;
+
+        let layout = detect_layout(&wal_dir)?;
+        if !matches!(layout, WalLayout::Segmented(_)) {
+            let first_index = match &segment_config.archive_dir {
+                Some(archive_dir) => list_segments(archive_dir)?
+                    .last()
+                    .map_or(1, |segment| segment.index + 1),
+                None => 1,
+            };
+            let first_path = wal_dir.join(segment_file_name(first_index));
+
+            if let WalLayout::Legacy(legacy_path) = layout {
+                fs::rename(&legacy_path, &first_path).map_err(|e| {
+                    WalError::append_failed(
+                        format!(
+                            "Failed to rename {} to {}",
+                            legacy_path.display(),
+                            first_path.display()
+                        ),
+                        e,
+                    )
+                })?;
+            } else {
+                Self::create_segment_file(&first_path)?;
+            }
+            fsync_dir(&wal_dir)?;
+        }
+
+        let active = list_segments(&wal_dir)?
+            .pop()
+            .expect("segmented WAL directory has at least one segment");
+
+        // Validate all segments and find the last sequence number
+        let mut reader = WalReader::open_segments(&wal_dir)?;
+        let mut last_commit_timestamp_ms = 0;
+        let mut epoch = read_epoch(&wal_dir)?;
+        while let Some(record) = reader.read_next()? {
+            last_commit_timestamp_ms = record
+                .commit_timestamp_ms
+                .unwrap_or(0)
+                .max(last_commit_timestamp_ms);
+            epoch = epoch.max(record.epoch);
+        }
+        // Every retained segment may be empty right after a checkpoint
+        let next_sequence = (reader.last_sequence_number() + 1).max(read_base_sequence(&wal_dir)?);
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(&active.path)
+            .map_err(|e| {
+                WalError::append_failed(
+                    format!("Failed to open WAL segment: {}", active.path.display()),
+                    e,
+                )
+            })?;
+
+        if let Some(torn_offset) = reader.torn_tail() {
+            file.set_len(torn_offset).map_err(|e| {
+                WalError::append_failed(
+                    format!(
+                        "Failed to discard torn record at offset {} of {}",
+                        torn_offset,
+                        active.path.display()
+                    ),
+                    e,
+                )
+            })?;
+            file.sync_all().map_err(|e| {
+                WalError::fsync_failed(
+                    format!("Failed to fsync WAL segment: {}", active.path.display()),
+                    e,
+                )
+            })?;
+        }
+
+        let len = file
+            .metadata()
+            .map_err(|e| WalError::append_failed("Failed to read WAL metadata", e))?
+            .len();
+
+        Ok(Self {
+            wal_path: active.path,
+            file: Box::new(file),
+            io: Arc::new(OsIo),
+            torn_tail: false,
+            next_sequence,
+            sync_config,
+            segment: Some(ActiveSegment {
+                config: segment_config,
+                index: active.index,
+                len,
+            }),
+            last_commit_timestamp_ms,
+            epoch,
+            sync_count: 0,
+            deferred_sync: false,
+            unsynced_records: 0,
+        })
+    }
+
+    /// Creates an empty segment file and fsyncs it.
+    ///
+    /// The caller is responsible for fsyncing the directory.
+    fn create_segment_file(path: &Path) -> WalResult<()> {
+        let file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                WalError::append_failed(
+                    format!("Failed to create WAL segment: {}", path.display()),
+                    e,
+                )
+            })?;
+        file.sync_all().map_err(|e| {
+            WalError::fsync_failed(
+                format!("Failed to fsync WAL segment: {}", path.display()),
+                e,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Determines the next sequence number, the last commit timestamp and
+    /// the highest record epoch by scanning existing WAL.
+    ///
+    /// Returns `(1, 0, 0)` if WAL is empty or does not exist.
+    fn scan_existing(wal_path: &Path) -> WalResult<(u64, u64, u64)> {
         use super::reader::WalReader;
 
         // If file doesn't exist or is empty, start at 1
         let metadata = match fs::metadata(wal_path) {
             Ok(m) => m,
-            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(1),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((1, 0, 0)),
             Err(e) => return Err(WalError::append_failed("Failed to read WAL metadata", e)),
         };
 
         if metadata.len() == 0 {
-            return Ok(1);
+            return Ok((1, 0, 0));
         }
 
-        // Read through WAL to find highest sequence number
+        // Read through WAL to find highest sequence number and timestamp
         let mut reader = WalReader::open(wal_path)?;
         let mut max_sequence = 0u64;
+        let mut max_timestamp_ms = 0u64;
+        let mut max_epoch = 0u64;
 
         loop {
             match reader.read_next() {
                 Ok(Some(record)) => {
                     max_sequence = max_sequence.max(record.sequence_number);
+                    max_timestamp_ms =
+                        max_timestamp_ms.max(record.commit_timestamp_ms.unwrap_or(0));
+                    max_epoch = max_epoch.max(record.epoch);
                 }
                 Ok(None) => break,
                 Err(e) => return Err(e),
             }
         }
 
-        Ok(max_sequence + 1)
+        Ok((max_sequence + 1, max_timestamp_ms, max_epoch))
     }
 
     /// Returns the path to the WAL file.
@@ -119,6 +359,150 @@ impl WalWriter {
         &self.wal_path
     }
 
+    /// Returns the sync configuration of this writer.
+    pub fn sync_config(&self) -> &WalSyncConfig {
+        &self.sync_config
+    }
+
+    /// Returns the segment configuration, or `None` for a legacy WAL.
+    pub fn segment_config(&self) -> Option<&WalSegmentConfig> {
+        self.segment.as_ref().map(|segment| &segment.config)
+    }
+
+    /// Returns the index of the active segment, or `None` for a legacy WAL.
+    pub fn active_segment_index(&self) -> Option<u64> {
+        self.segment.as_ref().map(|segment| segment.index)
+    }
+
+    /// Returns the active segment, or `None` for a legacy WAL.
+    pub fn current_segment(&self) -> Option<WalSegment> {
+        self.segment.as_ref().map(|segment| WalSegment {
+            index: segment.index,
+            path: self.wal_path.clone(),
+        })
+    }
+
+    /// Lists every segment in the WAL directory, in replay order.
+    ///
+    /// Empty for a legacy WAL.
+    pub fn list_segments(&self) -> WalResult<Vec<WalSegment>> {
+        if self.segment.is_none() {
+            return Ok(Vec::new());
+        }
+        list_segments(self.wal_dir())
+    }
+
+    /// Closes the active segment and starts the next one.
+    ///
+    /// The active segment is fsynced before the new segment is created, and
+    /// the directory is fsynced after. Does nothing for a legacy WAL or if
+    /// the active segment is still empty.
+    pub fn roll_segment(&mut self) -> WalResult<()> {
+        let Some(active) = self.segment.as_mut() else {
+            return Ok(());
+        };
+        if active.len == 0 {
+            return Ok(());
+        }
+        if self.torn_tail {
+            return Err(Self::torn_tail_error());
+        }
+
+        self.file.sync_all().map_err(|e| {
+            WalError::fsync_failed(
+                format!(
+                    "Failed to fsync WAL segment before rolling: {}",
+                    self.wal_path.display()
+                ),
+                e,
+            )
+        })?;
+
+        let wal_dir = self
+            .wal_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        let next_index = active.index + 1;
+        let next_path = wal_dir.join(segment_file_name(next_index));
+        Self::create_segment_file(&next_path)?;
+        fsync_dir(&wal_dir)?;
+
+        self.file = Self::open_append(self.io.as_ref(), &next_path)?;
+        self.wal_path = next_path;
+        active.index = next_index;
+        active.len = 0;
+
+        Ok(())
+    }
+
+    /// Rolls the active segment if it has reached the segment size.
+    fn roll_if_full(&mut self) -> WalResult<()> {
+        match &self.segment {
+            Some(active) if active.len >= active.config.segment_bytes => self.roll_segment(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the commit timestamp for the next write.
+    ///
+    /// Uses the system clock, but never goes below the previous timestamp.
+    fn next_commit_timestamp(&mut self) -> u64 {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        self.last_commit_timestamp_ms = self.last_commit_timestamp_ms.max(now_ms);
+        self.last_commit_timestamp_ms
+    }
+
+    /// Writes serialized records at the end of the active file.
+    ///
+    /// A failed write may have left part of `bytes` behind (a short write
+    /// on a full disk); the file is cut back to its previous length. If
+    /// even that fails, later appends are refused until the WAL is
+    /// reopened, which discards the torn record.
+    fn write_records(&mut self, bytes: &[u8], context: impl FnOnce() -> String) -> WalResult<()> {
+        if self.torn_tail {
+            return Err(Self::torn_tail_error());
+        }
+        let len = self
+            .file
+            .size()
+            .map_err(|e| WalError::append_failed("Failed to read WAL metadata", e))?;
+
+        if let Err(e) = self.file.append(bytes) {
+            self.torn_tail = self.file.set_len(len).is_err();
+            return Err(WalError::append_failed(context(), e));
+        }
+        if let Some(active) = self.segment.as_mut() {
+            active.len += bytes.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn torn_tail_error() -> WalError {
+        WalError::append_failed(
+            "WAL ends in a partial record; reopen the WAL to discard it",
+            io::Error::other("torn WAL tail"),
+        )
+    }
+
+    /// Opens `path` for appending through `io`.
+    fn open_append(io: &dyn StorageIo, path: &Path) -> WalResult<Box<dyn StorageFile>> {
+        io.open_append(path).map_err(|e| {
+            WalError::append_failed(format!("Failed to open WAL file: {}", path.display()), e)
+        })
+    }
+
+    /// Appends through files opened by `io` from now on.
+    ///
+    /// Reopens the active file; used by tests to inject I/O faults.
+    pub fn with_io(mut self, io: Arc<dyn StorageIo>) -> WalResult<Self> {
+        self.file = Self::open_append(io.as_ref(), &self.wal_path)?;
+        self.io = io;
+        Ok(self)
+    }
+
     /// Returns the next sequence number that will be assigned.
     pub fn next_sequence_number(&self) -> u64 {
         self.next_sequence
@@ -133,13 +517,45 @@ impl WalWriter {
         }
     }
 
+    /// Returns the highest commit timestamp in the WAL, in ms since the
+    /// epoch, or 0 if no record carries one.
+    pub fn last_commit_timestamp_ms(&self) -> u64 {
+        self.last_commit_timestamp_ms
+    }
+
+    /// Returns the authority epoch stamped on new records: the highest of
+    /// the recorded epoch and every record's epoch.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Starts authority epoch `epoch`, on promotion to primary.
+    ///
+    /// The epoch is recorded durably before this returns, and every record
+    /// written afterwards carries it. Replicas reject records with a lower
+    /// epoch, so a former primary that keeps writing is fenced. Does
+    /// nothing if the WAL is already at `epoch` or later.
+    ///
+    /// # Errors
+    ///
+    /// `AERO_WAL_APPEND_FAILED` / `AERO_WAL_FSYNC_FAILED` if the epoch
+    /// cannot be recorded; the epoch is then unchanged.
+    pub fn begin_epoch(&mut self, epoch: u64) -> WalResult<()> {
+        if epoch > self.epoch {
+            write_epoch(self.wal_dir(), epoch)?;
+            self.epoch = epoch;
+        }
+        Ok(())
+    }
+
     /// Appends a record to the WAL with fsync enforcement.
     ///
     /// Per WAL.md §175-198:
     /// 1. Construct WAL record
     /// 2. Append record to wal.log
-    /// 3. Flush WAL to disk using fsync
-    /// 4. Only after fsync may the operation proceed
+    /// 3. Flush WAL to disk using the configured sync mode (unless syncs
+    ///    are deferred, see [`WalWriter::with_deferred_sync`])
+    /// 4. Only after the sync may the operation proceed
     ///
     /// # Arguments
     ///
@@ -155,35 +571,238 @@ impl WalWriter {
     /// - `AERO_WAL_APPEND_FAILED` if write fails
     /// - `AERO_WAL_FSYNC_FAILED` if fsync fails (FATAL)
     pub fn append(&mut self, record_type: RecordType, payload: WalPayload) -> WalResult<u64> {
+        self.roll_if_full()?;
+
         let sequence_number = self.next_sequence;
-        let record = WalRecord::new(record_type, sequence_number, payload);
+        let record = WalRecord::new(record_type, sequence_number, payload)
+            .with_commit_timestamp(self.next_commit_timestamp())
+            .with_epoch(self.epoch);
         let serialized = record.serialize();
 
         // Write to file
-        self.file.write_all(&serialized).map_err(|e| {
-            WalError::append_failed(
-                format!("Failed to write WAL record at sequence {}", sequence_number),
-                e,
+        self.write_records(&serialized, || {
+            format!("Failed to write WAL record at sequence {}", sequence_number)
+        })?;
+
+        // Written but not yet durable; a power loss here drops the record
+        maybe_crash(points::WAL_AFTER_APPEND_BEFORE_FSYNC);
+
+        if self.deferred_sync {
+            self.next_sequence += 1;
+            self.unsynced_records += 1;
+            return Ok(sequence_number);
+        }
+
+        // Sync - this is mandatory and FATAL if it fails
+        self.sync_for_mode(&format!("WAL append at sequence {}", sequence_number))?;
+
+        // Only increment after successful sync
+        self.next_sequence += 1;
+
+        // Durable but not yet applied to storage by the caller
+        maybe_crash(points::WAL_AFTER_APPEND);
+
+        Ok(sequence_number)
+    }
+
+    /// Appends several records with a single sync.
+    ///
+    /// All records are serialized into one buffer and written with one
+    /// `write()`, then synced once (or, with deferred syncs, not yet). None
+    /// of the returned sequence numbers may be acknowledged unless this
+    /// returns `Ok`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`WalWriter::append`].
+    pub fn append_batch(&mut self, records: Vec<(RecordType, WalPayload)>) -> WalResult<Vec<u64>> {
+        if records.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.roll_if_full()?;
+
+        let first = self.next_sequence;
+        let commit_timestamp_ms = self.next_commit_timestamp();
+        let mut buffer = Vec::new();
+        let mut sequences = Vec::with_capacity(records.len());
+        for (offset, (record_type, payload)) in records.into_iter().enumerate() {
+            let sequence_number = first + offset as u64;
+            buffer.extend(
+                WalRecord::new(record_type, sequence_number, payload)
+                    .with_commit_timestamp(commit_timestamp_ms)
+                    .with_epoch(self.epoch)
+                    .serialize(),
+            );
+            sequences.push(sequence_number);
+        }
+        let last = first + sequences.len() as u64 - 1;
+
+        self.write_records(&buffer, || {
+            format!(
+                "Failed to write WAL batch at sequences {}..={}",
+                first, last
             )
         })?;
 
-        // fsync - this is mandatory and FATAL if it fails
-        self.file.sync_all().map_err(|e| {
-            WalError::fsync_failed(
+        maybe_crash(points::WAL_AFTER_APPEND_BEFORE_FSYNC);
+
+        if self.deferred_sync {
+            self.next_sequence = last + 1;
+            self.unsynced_records += sequences.len();
+            return Ok(sequences);
+        }
+
+        self.sync_for_mode(&format!("WAL batch at sequences {}..={}", first, last))?;
+
+        self.next_sequence = last + 1;
+
+        maybe_crash(points::WAL_AFTER_APPEND);
+
+        Ok(sequences)
+    }
+
+    /// Appends a record received from the primary, keeping its sequence
+    /// number, commit timestamp and epoch.
+    ///
+    /// A record from a later epoch advances this WAL's epoch, recorded
+    /// durably before the record is written.
+    ///
+    /// The record must continue this WAL: its sequence number must be
+    /// [`WalWriter::next_sequence_number`]. Synced like [`WalWriter::append`].
+    ///
+    /// # Errors
+    ///
+    /// - `AERO_WAL_CORRUPTION` if the record does not continue the WAL
+    /// - `AERO_WAL_APPEND_FAILED` if write fails
+    /// - `AERO_WAL_FSYNC_FAILED` if fsync fails (FATAL)
+    pub fn append_replicated(&mut self, record: &WalRecord) -> WalResult<u64> {
+        let sequence_number = record.sequence_number;
+        if sequence_number != self.next_sequence {
+            return Err(WalError::corruption_at_sequence(
+                sequence_number,
                 format!(
-                    "fsync failed after WAL append at sequence {}",
-                    sequence_number
+                    "Replicated record does not continue the WAL at sequence {}",
+                    self.next_sequence
                 ),
-                e,
+            ));
+        }
+
+        self.roll_if_full()?;
+
+        if record.epoch > self.epoch {
+            write_epoch(self.wal_dir(), record.epoch)?;
+            self.epoch = record.epoch;
+        }
+
+        let serialized = record.serialize();
+        self.write_records(&serialized, || {
+            format!(
+                "Failed to write replicated WAL record at sequence {}",
+                sequence_number
             )
         })?;
 
-        // Only increment after successful fsync
+        self.sync_for_mode(&format!(
+            "replicated WAL append at sequence {}",
+            sequence_number
+        ))?;
+
+        self.next_sequence += 1;
+        if let Some(timestamp_ms) = record.commit_timestamp_ms {
+            self.last_commit_timestamp_ms = self.last_commit_timestamp_ms.max(timestamp_ms);
+        }
+
+        Ok(sequence_number)
+    }
+
+    /// Writes a record without syncing and advances the sequence number.
+    ///
+    /// Used by `GroupCommitWriter`, which syncs once per batch via
+    /// [`WalWriter::sync_for_mode`]. The record must not be acknowledged
+    /// until that sync succeeds.
+    pub(crate) fn write_unsynced(
+        &mut self,
+        record_type: RecordType,
+        payload: WalPayload,
+    ) -> WalResult<u64> {
+        self.roll_if_full()?;
+
+        let sequence_number = self.next_sequence;
+        let record = WalRecord::new(record_type, sequence_number, payload)
+            .with_commit_timestamp(self.next_commit_timestamp())
+            .with_epoch(self.epoch);
+        let serialized = record.serialize();
+
+        self.write_records(&serialized, || {
+            format!("Failed to write WAL record at sequence {}", sequence_number)
+        })?;
+
         self.next_sequence += 1;
 
         Ok(sequence_number)
     }
 
+    /// Leaves the sync of [`WalWriter::append`] and
+    /// [`WalWriter::append_batch`] to the caller, who must call
+    /// [`WalWriter::sync_deferred`] before acknowledging anything written.
+    ///
+    /// For a single executor in `group_commit` mode: one sync then covers
+    /// every write made since the last one. Replicated appends still sync.
+    pub fn with_deferred_sync(mut self) -> Self {
+        self.deferred_sync = true;
+        self
+    }
+
+    /// Records written since the last sync, with deferred syncs.
+    pub fn unsynced_records(&self) -> usize {
+        self.unsynced_records
+    }
+
+    /// Syncs the records written since the last sync, if any.
+    ///
+    /// # Errors
+    ///
+    /// `AERO_WAL_FSYNC_FAILED` if the sync fails (FATAL: none of the
+    /// records may be acknowledged).
+    pub fn sync_deferred(&mut self) -> WalResult<()> {
+        if self.unsynced_records == 0 {
+            return Ok(());
+        }
+        let context = format!(
+            "group commit of {} records through sequence {}",
+            self.unsynced_records,
+            self.last_sequence_number()
+        );
+        self.sync_for_mode(&context)?;
+        self.unsynced_records = 0;
+        Ok(())
+    }
+
+    /// Syncs the WAL file according to the configured sync mode.
+    ///
+    /// `fdatasync` uses `sync_data()`; `fsync` and `group_commit` use
+    /// `sync_all()`.
+    pub(crate) fn sync_for_mode(&mut self, context: &str) -> WalResult<()> {
+        maybe_crash(points::WAL_BEFORE_FSYNC);
+
+        let result = match self.sync_config.mode {
+            WalSyncMode::Fdatasync => self.file.sync_data(),
+            WalSyncMode::Fsync | WalSyncMode::GroupCommit => self.file.sync_all(),
+        };
+        result.map_err(|e| {
+            WalError::fsync_failed(
+                format!("{} failed after {}", self.sync_config.mode, context),
+                e,
+            )
+        })?;
+        self.sync_count += 1;
+
+        maybe_crash(points::WAL_AFTER_FSYNC);
+
+        Ok(())
+    }
+
     /// Appends an INSERT record.
     pub fn append_insert(&mut self, payload: WalPayload) -> WalResult<u64> {
         self.append(RecordType::Insert, payload)
@@ -209,6 +828,15 @@ impl WalWriter {
             .map_err(|e| WalError::fsync_failed("Explicit WAL fsync failed", e))
     }
 
+    /// Number of syncs appends have performed since open.
+    ///
+    /// One per append in `fsync`/`fdatasync` mode; one per batch through a
+    /// `GroupCommitWriter` or [`WalWriter::sync_deferred`]. Explicit
+    /// [`WalWriter::fsync`] calls are not counted.
+    pub fn sync_count(&self) -> u64 {
+        self.sync_count
+    }
+
     /// Returns the WAL directory path.
     pub fn wal_dir(&self) -> &Path {
         self.wal_path.parent().unwrap_or(Path::new("."))
@@ -224,11 +852,20 @@ impl WalWriter {
     /// This operation is atomic: the old file is removed and a new empty
     /// file is created with fsync.
     ///
+    /// For a segmented WAL, the active segment is rolled and sequence
+    /// numbers restart at 1 in the new, empty segment. All earlier segments
+    /// must already have been removed (see `checkpoint`), otherwise replay
+    /// would see sequence numbers restart mid-WAL.
+    ///
     /// # Errors
     ///
     /// Returns `WalError` if truncation fails. If truncation fails,
     /// the WAL is left in its original state.
     pub fn truncate(&mut self) -> WalResult<()> {
+        if self.segment.is_some() {
+            return self.truncate_segmented();
+        }
+
         // Close current file by dropping and reopening
         let wal_dir = self.wal_path.parent().unwrap_or(Path::new("."));
 
@@ -288,21 +925,44 @@ impl WalWriter {
         })?;
 
         // Reopen file for append
-        let file = OpenOptions::new()
-            .append(true)
-            .open(&self.wal_path)
-            .map_err(|e| {
-                WalError::append_failed(
-                    format!(
-                        "Failed to reopen WAL file after truncation: {}",
-                        self.wal_path.display()
-                    ),
-                    e,
-                )
-            })?;
+        let file = self.io.open_append(&self.wal_path).map_err(|e| {
+            WalError::append_failed(
+                format!(
+                    "Failed to reopen WAL file after truncation: {}",
+                    self.wal_path.display()
+                ),
+                e,
+            )
+        })?;
 
         // Update internal state
         self.file = file;
+        self.torn_tail = false;
+        self.next_sequence = 1;
+
+        Ok(())
+    }
+
+    fn truncate_segmented(&mut self) -> WalResult<()> {
+        self.roll_segment()?;
+
+        let active_index = self.active_segment_index().unwrap_or(0);
+        let remaining = list_segments(self.wal_dir())?
+            .into_iter()
+            .filter(|segment| segment.index < active_index)
+            .count();
+        if remaining > 0 {
+            return Err(WalError::append_failed(
+                format!(
+                    "Cannot reset WAL sequence: {} segment(s) before {} still exist",
+                    remaining,
+                    segment_file_name(active_index)
+                ),
+                io::Error::other("covered segments must be removed first"),
+            ));
+        }
+
+        clear_base_sequence(self.wal_dir())?;
         self.next_sequence = 1;
 
         Ok(())
@@ -311,6 +971,7 @@ impl WalWriter {
 
 #[cfg(test)]
 mod tests {
+    use super::super::segment::DEFAULT_SEGMENT_BYTES;
     use super::*;
     use tempfile::TempDir;
 
@@ -447,6 +1108,115 @@ mod tests {
         assert!(writer.fsync().is_ok());
     }
 
+    #[test]
+    fn test_fdatasync_mode_records_durable() {
+        use super::super::reader::WalReader;
+
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let mut writer = WalWriter::open_with_sync(
+                temp_dir.path(),
+                WalSyncConfig::new(WalSyncMode::Fdatasync),
+            )
+            .unwrap();
+            assert_eq!(writer.sync_config().mode, WalSyncMode::Fdatasync);
+            writer.append_insert(create_test_payload("doc1")).unwrap();
+            writer.append_insert(create_test_payload("doc2")).unwrap();
+        }
+
+        let wal_path = temp_dir.path().join("wal").join("wal.log");
+        let mut reader = WalReader::open(&wal_path).unwrap();
+        assert_eq!(reader.read_next().unwrap().unwrap().sequence_number, 1);
+        assert_eq!(reader.read_next().unwrap().unwrap().sequence_number, 2);
+        assert!(reader.read_next().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_deferred_syncs_share_one_sync() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = WalWriter::open_with_sync(
+            temp_dir.path(),
+            WalSyncConfig::new(WalSyncMode::GroupCommit),
+        )
+        .unwrap()
+        .with_deferred_sync();
+
+        writer.append_insert(create_test_payload("doc1")).unwrap();
+        writer
+            .append_batch(vec![
+                (RecordType::Insert, create_test_payload("doc2")),
+                (RecordType::Insert, create_test_payload("doc3")),
+            ])
+            .unwrap();
+        assert_eq!(writer.unsynced_records(), 3);
+        assert_eq!(writer.sync_count(), 0);
+
+        writer.sync_deferred().unwrap();
+        assert_eq!(writer.unsynced_records(), 0);
+        assert_eq!(writer.sync_count(), 1);
+
+        // Nothing left to sync
+        writer.sync_deferred().unwrap();
+        assert_eq!(writer.sync_count(), 1);
+        assert_eq!(writer.last_sequence_number(), 3);
+    }
+
+    #[test]
+    fn test_append_batch_assigns_contiguous_sequences() {
+        use super::super::reader::WalReader;
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut writer = WalWriter::open(temp_dir.path()).unwrap();
+        writer.append_insert(create_test_payload("doc0")).unwrap();
+
+        let seqs = writer
+            .append_batch(vec![
+                (RecordType::Insert, create_test_payload("doc1")),
+                (RecordType::Update, create_test_payload("doc1")),
+                (RecordType::Insert, create_test_payload("doc2")),
+            ])
+            .unwrap();
+        assert_eq!(seqs, vec![2, 3, 4]);
+        assert_eq!(writer.next_sequence_number(), 5);
+        assert!(writer.append_batch(Vec::new()).unwrap().is_empty());
+
+        let wal_path = temp_dir.path().join("wal").join("wal.log");
+        let mut reader = WalReader::open(&wal_path).unwrap();
+        let mut read = Vec::new();
+        while let Some(record) = reader.read_next().unwrap() {
+            read.push(record.sequence_number);
+        }
+        assert_eq!(read, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_commit_timestamps_never_decrease() {
+        use super::super::reader::WalReader;
+
+        let temp_dir = TempDir::new().unwrap();
+        let future_ms = u64::MAX / 2;
+        {
+            let mut writer = WalWriter::open(temp_dir.path()).unwrap();
+            writer.append_insert(create_test_payload("doc0")).unwrap();
+            // Simulate the clock having been ahead when the last record was written
+            writer.last_commit_timestamp_ms = future_ms;
+            writer.append_insert(create_test_payload("doc1")).unwrap();
+        }
+
+        // Reopening picks up the last timestamp from the WAL
+        let mut writer = WalWriter::open(temp_dir.path()).unwrap();
+        writer.append_insert(create_test_payload("doc2")).unwrap();
+
+        let wal_path = temp_dir.path().join("wal").join("wal.log");
+        let records = WalReader::open(&wal_path).unwrap().read_all().unwrap();
+        let timestamps: Vec<u64> = records
+            .iter()
+            .map(|r| r.commit_timestamp_ms.unwrap())
+            .collect();
+        assert!(timestamps[0] > 0 && timestamps[0] < future_ms);
+        assert_eq!(timestamps[1..], [future_ms, future_ms]);
+    }
+
     #[test]
     fn test_wal_dir() {
         let temp_dir = TempDir::new().unwrap();
@@ -555,4 +1325,251 @@ mod tests {
             assert!(reader.read_next().unwrap().is_none());
         }
     }
+
+    fn open_segmented(data_dir: &Path, segment_bytes: u64) -> WalWriter {
+        WalWriter::open_segmented(
+            data_dir,
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(segment_bytes),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_segmented_rolls_at_segment_bytes() {
+        use super::super::reader::WalReader;
+
+        let temp_dir = TempDir::new().unwrap();
+        let record_len = WalRecord::new(RecordType::Insert, 1, create_test_payload("doc0"))
+            .with_commit_timestamp(0)
+            .serialize()
+            .len() as u64;
+
+        // Two records fit in a segment; the third rolls
+        let mut writer = open_segmented(temp_dir.path(), 2 * record_len);
+        for i in 0..5 {
+            writer
+                .append_insert(create_test_payload(&format!("doc{}", i)))
+                .unwrap();
+        }
+        assert_eq!(writer.active_segment_index(), Some(3));
+
+        let wal_dir = temp_dir.path().join("wal");
+        let segments = list_segments(&wal_dir).unwrap();
+        assert_eq!(writer.list_segments().unwrap(), segments);
+        assert_eq!(writer.current_segment().as_ref(), segments.last());
+        let sizes: Vec<u64> = segments
+            .iter()
+            .map(|s| fs::metadata(&s.path).unwrap().len())
+            .collect();
+        assert_eq!(sizes, vec![2 * record_len, 2 * record_len, record_len]);
+        assert!(!wal_dir.join(LEGACY_WAL_FILE).exists());
+
+        let records = WalReader::open_segments(&wal_dir)
+            .unwrap()
+            .read_all()
+            .unwrap();
+        let sequences: Vec<u64> = records.iter().map(|r| r.sequence_number).collect();
+        assert_eq!(sequences, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_segmented_reopen_continues_in_last_segment() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let mut writer = open_segmented(temp_dir.path(), 1);
+            writer.append_insert(create_test_payload("doc1")).unwrap();
+            writer.append_insert(create_test_payload("doc2")).unwrap();
+        }
+
+        let mut writer = open_segmented(temp_dir.path(), 1);
+        assert_eq!(writer.active_segment_index(), Some(2));
+        assert_eq!(writer.next_sequence_number(), 3);
+        writer.append_insert(create_test_payload("doc3")).unwrap();
+        assert_eq!(writer.active_segment_index(), Some(3));
+    }
+
+    #[test]
+    fn test_segmented_adopts_legacy_wal() {
+        let temp_dir = TempDir::new().unwrap();
+        {
+            let mut writer = WalWriter::open(temp_dir.path()).unwrap();
+            writer.append_insert(create_test_payload("doc1")).unwrap();
+        }
+
+        let writer = open_segmented(temp_dir.path(), DEFAULT_SEGMENT_BYTES);
+        let wal_dir = temp_dir.path().join("wal");
+        assert!(!wal_dir.join(LEGACY_WAL_FILE).exists());
+        assert_eq!(writer.path(), wal_dir.join("0000001.log"));
+        assert_eq!(writer.next_sequence_number(), 2);
+    }
+
+    #[test]
+    fn test_segmented_open_discards_torn_tail() {
+        use super::super::reader::WalReader;
+        use std::io::Write;
+
+        let temp_dir = TempDir::new().unwrap();
+        let active_path = {
+            let mut writer = open_segmented(temp_dir.path(), 1);
+            writer.append_insert(create_test_payload("doc1")).unwrap();
+            writer.append_insert(create_test_payload("doc2")).unwrap();
+            writer.path().to_path_buf()
+        };
+
+        // Simulate a crash mid-write of a third record
+        let torn = WalRecord::new(RecordType::Insert, 3, create_test_payload("doc3")).serialize();
+        let clean_len = fs::metadata(&active_path).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&active_path).unwrap();
+        file.write_all(&torn[..torn.len() / 2]).unwrap();
+        drop(file);
+
+        let mut writer = open_segmented(temp_dir.path(), 1);
+        assert_eq!(fs::metadata(&active_path).unwrap().len(), clean_len);
+        assert_eq!(writer.next_sequence_number(), 3);
+        writer.append_insert(create_test_payload("doc3")).unwrap();
+
+        let records = WalReader::open_from_data_dir(temp_dir.path())
+            .unwrap()
+            .read_all()
+            .unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].payload.document_id, "doc3");
+    }
+
+    #[test]
+    fn test_append_replicated_keeps_primary_sequence() {
+        use super::super::reader::WalReader;
+
+        let primary_dir = TempDir::new().unwrap();
+        let mut primary = WalWriter::open(primary_dir.path()).unwrap();
+        primary.append_insert(create_test_payload("doc1")).unwrap();
+        primary.append_insert(create_test_payload("doc2")).unwrap();
+        let shipped = WalReader::open_from_data_dir(primary_dir.path())
+            .unwrap()
+            .read_all()
+            .unwrap();
+
+        let replica_dir = TempDir::new().unwrap();
+        let mut replica = open_segmented(replica_dir.path(), DEFAULT_SEGMENT_BYTES);
+        assert!(replica.append_replicated(&shipped[1]).is_err());
+        for record in &shipped {
+            replica.append_replicated(record).unwrap();
+        }
+        assert_eq!(replica.next_sequence_number(), 3);
+        assert!(replica.append_replicated(&shipped[1]).is_err());
+
+        let copied = WalReader::open_from_data_dir(replica_dir.path())
+            .unwrap()
+            .read_all()
+            .unwrap();
+        assert_eq!(copied, shipped);
+    }
+
+    #[test]
+    fn test_epoch_survives_truncation_and_is_replicated() {
+        use super::super::reader::WalReader;
+
+        let primary_dir = TempDir::new().unwrap();
+        let mut primary = WalWriter::open(primary_dir.path()).unwrap();
+        primary.append_insert(create_test_payload("doc1")).unwrap();
+        primary.begin_epoch(1).unwrap();
+        primary.append_insert(create_test_payload("doc2")).unwrap();
+        let shipped = WalReader::open_from_data_dir(primary_dir.path())
+            .unwrap()
+            .read_all()
+            .unwrap();
+        let epochs: Vec<u64> = shipped.iter().map(|r| r.epoch).collect();
+        assert_eq!(epochs, [0, 1]);
+
+        // The recorded epoch outlives the records carrying it
+        primary.truncate().unwrap();
+        drop(primary);
+        let mut primary = WalWriter::open(primary_dir.path()).unwrap();
+        assert_eq!(primary.epoch(), 1);
+        primary.begin_epoch(1).unwrap();
+        assert_eq!(primary.epoch(), 1);
+
+        let replica_dir = TempDir::new().unwrap();
+        let mut replica = open_segmented(replica_dir.path(), DEFAULT_SEGMENT_BYTES);
+        for record in &shipped {
+            replica.append_replicated(record).unwrap();
+        }
+        assert_eq!(replica.epoch(), 1);
+        drop(replica);
+        let replica = WalWriter::open(replica_dir.path()).unwrap();
+        assert_eq!(replica.epoch(), 1);
+    }
+
+    #[test]
+    fn test_disk_full_append_is_refused_and_cut_off() {
+        use super::super::reader::WalReader;
+        use crate::storage_io::FaultyFs;
+
+        let temp_dir = TempDir::new().unwrap();
+        let fs = FaultyFs::new();
+        let mut writer = WalWriter::open(temp_dir.path())
+            .unwrap()
+            .with_io(Arc::new(fs.clone()))
+            .unwrap();
+        writer.append_insert(create_test_payload("doc1")).unwrap();
+        let clean_len = fs::metadata(writer.path()).unwrap().len();
+
+        // Room for half a record: the write is cut short
+        fs.limit_bytes(10);
+        let err = writer
+            .append_insert(create_test_payload("doc2"))
+            .unwrap_err();
+        assert_eq!(
+            err.code(),
+            super::super::errors::WalErrorCode::AeroWalAppendFailed
+        );
+        assert_eq!(fs::metadata(writer.path()).unwrap().len(), clean_len);
+        assert_eq!(writer.next_sequence_number(), 2);
+
+        fs.heal();
+        assert_eq!(
+            writer.append_insert(create_test_payload("doc2")).unwrap(),
+            2
+        );
+
+        let records = WalReader::open_from_data_dir(temp_dir.path())
+            .unwrap()
+            .read_all()
+            .unwrap();
+        let ids: Vec<_> = records
+            .iter()
+            .map(|r| r.payload.document_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["doc1", "doc2"]);
+    }
+
+    #[test]
+    fn test_io_error_on_batch_keeps_segmented_wal_clean() {
+        use super::super::reader::WalReader;
+        use crate::storage_io::{Fault, FaultyFs};
+
+        let temp_dir = TempDir::new().unwrap();
+        let fs = FaultyFs::new();
+        let mut writer = open_segmented(temp_dir.path(), DEFAULT_SEGMENT_BYTES)
+            .with_io(Arc::new(fs.clone()))
+            .unwrap();
+
+        fs.fail_nth_write(1, Fault::Io);
+        let batch = vec![
+            (RecordType::Insert, create_test_payload("doc1")),
+            (RecordType::Insert, create_test_payload("doc2")),
+        ];
+        assert!(writer.append_batch(batch.clone()).is_err());
+        assert_eq!(writer.append_batch(batch).unwrap(), vec![1, 2]);
+        drop(writer);
+
+        let reopened = open_segmented(temp_dir.path(), DEFAULT_SEGMENT_BYTES);
+        assert_eq!(reopened.next_sequence_number(), 3);
+        let records = WalReader::open_segments(&temp_dir.path().join("wal"))
+            .unwrap()
+            .read_all()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+    }
 }
@@ -0,0 +1,260 @@
+//! CLI Backup and Restore Tests
+//!
+//! Drives the `aerodb` binary through a full round-trip:
+//! init → insert → backup create → backup list → insert → restore,
+//! and checks the restored directory holds exactly the data that was
+//! present when the backup was taken, and that restoring over existing
+//! data takes a confirmation token.
+
+use aerodb::schema::{FieldDef, Schema, SchemaLoader};
+use aerodb::storage::StorageReader;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use tempfile::TempDir;
+
+// =============================================================================
+// Test Utilities
+// =============================================================================
+
+struct Env {
+    temp: TempDir,
+}
+
+impl Env {
+    fn new() -> Self {
+        let temp = TempDir::new().unwrap();
+        let config = format!(
+            "[server]\ndata_dir = '{}'\n\n[backup]\nbackup_dir = '{}'\n",
+            temp.path().join("data").display(),
+            temp.path().join("backups").display(),
+        );
+        fs::write(temp.path().join("aerodb.toml"), config).unwrap();
+        Self { temp }
+    }
+
+    fn config(&self) -> PathBuf {
+        self.temp.path().join("aerodb.toml")
+    }
+
+    fn data_dir(&self) -> PathBuf {
+        self.temp.path().join("data")
+    }
+
+    /// Runs `aerodb <command> --config <config> <args>` and returns its
+    /// last response line.
+    fn run(&self, command: &str, args: &[&str], stdin: &str) -> Value {
+        let output = self.output(command, args, stdin);
+        assert!(
+            output.status.success(),
+            "aerodb {} {:?} failed: {}",
+            command,
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let last_line = stdout.lines().last().unwrap_or("null");
+        serde_json::from_str(last_line).unwrap()
+    }
+
+    /// Runs a command that must fail and returns its error.
+    fn fail(&self, command: &str, args: &[&str]) -> String {
+        let output = self.output(command, args, "");
+        assert!(
+            !output.status.success(),
+            "aerodb {} {:?} succeeded",
+            command,
+            args
+        );
+        String::from_utf8(output.stderr).unwrap()
+    }
+
+    fn output(&self, command: &str, args: &[&str], stdin: &str) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_aerodb"))
+            .arg(command)
+            .arg("--config")
+            .arg(self.config())
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    fn insert(&self, id: &str, name: &str) {
+        let request = json!({
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": id, "name": name}
+        });
+        let response = self.run("start", &[], &format!("{}\n", request));
+        assert_eq!(response["status"], "ok", "insert failed: {}", response);
+    }
+}
+
+fn save_users_schema(data_dir: &Path) {
+    let mut fields = HashMap::new();
+    fields.insert("_id".into(), FieldDef::required_string());
+    fields.insert("name".into(), FieldDef::required_string());
+    SchemaLoader::new(data_dir)
+        .save_schema(&Schema::new("users", "v1", fields))
+        .unwrap();
+}
+
+fn stored_document_ids(data_dir: &Path) -> Vec<String> {
+    let mut reader = StorageReader::open_from_data_dir(data_dir).unwrap();
+    let mut ids = Vec::new();
+    while let Some(record) = reader.read_next().unwrap() {
+        ids.push(record.document_id);
+    }
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+// =============================================================================
+// Round-trip
+// =============================================================================
+
+#[test]
+fn test_backup_create_list_restore_round_trip() {
+    let env = Env::new();
+    env.run("init", &[], "");
+    save_users_schema(&env.data_dir());
+    env.insert("1", "alice");
+
+    let created = env.run("backup", &["create", "--description", "before bob"], "");
+    assert_eq!(created["status"], "ok");
+    let backup_id = created["data"]["backup"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let listed = env.run("backup", &["list"], "");
+    assert_eq!(listed["data"]["count"], 1);
+    assert_eq!(listed["data"]["backups"][0]["id"], backup_id.as_str());
+    assert_eq!(listed["data"]["backups"][0]["description"], "before bob");
+
+    let verified = env.run("backup", &["verify", "--id", &backup_id], "");
+    assert_eq!(verified["data"]["ok"], true, "{}", verified);
+
+    // Written after the backup, so absent from the restore
+    env.insert("2", "bob");
+    let live = stored_document_ids(&env.data_dir());
+    assert!(live.iter().any(|id| id.ends_with('2')));
+
+    let target = env.temp.path().join("restored");
+    let target_arg = target.to_string_lossy().to_string();
+    let restored = env.run(
+        "restore",
+        &["--backup-id", &backup_id, "--target-dir", &target_arg],
+        "",
+    );
+    assert_eq!(restored["data"]["restored"], true);
+    let report = &restored["data"]["report"];
+    assert_eq!(report["backup_id"], backup_id.as_str());
+    assert_eq!(report["wal_records_applied"], 0);
+    assert!(report["checksum"].as_str().unwrap().starts_with("crc32:"));
+    assert!(target.join("restore_report.json").exists());
+
+    let documents = stored_document_ids(&target);
+    assert_eq!(documents.len(), 1, "restored: {:?}", documents);
+    assert!(documents[0].ends_with('1'));
+
+    // The configured data directory is untouched
+    assert_eq!(stored_document_ids(&env.data_dir()), live);
+}
+
+#[test]
+fn test_restore_over_data_requires_confirmation_token() {
+    let env = Env::new();
+    env.run("init", &[], "");
+    save_users_schema(&env.data_dir());
+    env.insert("1", "alice");
+    let created = env.run("backup", &["create"], "");
+    let backup_id = created["data"]["backup"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    env.insert("2", "bob");
+    let live = stored_document_ids(&env.data_dir());
+    assert_eq!(live.len(), 2);
+
+    // The first run only issues a token
+    let requested = env.run("restore", &["--backup-id", &backup_id], "");
+    assert_eq!(requested["data"]["restored"], false);
+    assert_eq!(requested["data"]["outcome"], "ConfirmationRequired");
+    let token = requested["data"]["confirmation_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(stored_document_ids(&env.data_dir()), live);
+
+    // The token confirms this restore only
+    let err = env.fail(
+        "restore",
+        &[
+            "--backup-id",
+            &backup_id,
+            "--to-offset",
+            "0",
+            "--confirm",
+            &token,
+        ],
+    );
+    assert!(err.contains("different command or arguments"), "{}", err);
+    assert_eq!(stored_document_ids(&env.data_dir()), live);
+
+    let restored = env.run(
+        "restore",
+        &["--backup-id", &backup_id, "--confirm", &token],
+        "",
+    );
+    assert_eq!(restored["data"]["restored"], true);
+    let documents = stored_document_ids(&env.data_dir());
+    assert_eq!(documents.len(), 1, "restored: {:?}", documents);
+
+    // The restored directory's audit log records the restore
+    let audit = fs::read_to_string(env.data_dir().join("system").join("audit.log")).unwrap();
+    let last = audit.lines().last().unwrap();
+    assert!(
+        last.contains("COMMAND_EXECUTED") && last.contains("restore"),
+        "{}",
+        last
+    );
+
+    // Restoring again takes a new token
+    let err = env.fail("restore", &["--backup-id", &backup_id, "--confirm", &token]);
+    assert!(err.contains("new confirmation"), "{}", err);
+}
+
+#[test]
+fn test_backup_delete_removes_backup() {
+    let env = Env::new();
+    env.run("init", &[], "");
+    save_users_schema(&env.data_dir());
+
+    let created = env.run("backup", &["create"], "");
+    let backup_id = created["data"]["backup"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let deleted = env.run("backup", &["delete", "--id", &backup_id], "");
+    assert_eq!(deleted["data"]["deleted"], true);
+
+    let listed = env.run("backup", &["list"], "");
+    assert_eq!(listed["data"]["count"], 0);
+}
@@ -0,0 +1,210 @@
+//! CLI Group Commit Tests
+//!
+//! Drives `aerodb start` with `wal.sync_mode = "group_commit"` and checks
+//! that pipelined writes share one WAL sync, that no write is acknowledged
+//! before that sync, and that a window closes a partial batch.
+
+#![cfg(unix)]
+
+use aerodb::schema::{FieldDef, Schema, SchemaLoader};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+// =============================================================================
+// Test Utilities
+// =============================================================================
+
+fn write_config(temp: &TempDir, window_us: u64, max_records: u64) -> PathBuf {
+    let config = format!(
+        "[server]\ndata_dir = '{}'\n\n[wal]\nsync_mode = 'group_commit'\n\
+         group_commit_window_us = {}\ngroup_commit_max_records = {}\n",
+        temp.path().join("data").display(),
+        window_us,
+        max_records,
+    );
+    let path = temp.path().join("aerodb.toml");
+    fs::write(&path, config).unwrap();
+    path
+}
+
+fn aerodb(command: &str, config: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_aerodb"));
+    cmd.arg(command).arg("--config").arg(config);
+    cmd
+}
+
+fn init(config: &Path, data_dir: &Path) {
+    let status = aerodb("init", config)
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success(), "aerodb init failed");
+
+    let mut fields = HashMap::new();
+    fields.insert("_id".into(), FieldDef::required_string());
+    fields.insert("name".into(), FieldDef::required_string());
+    SchemaLoader::new(data_dir)
+        .save_schema(&Schema::new("users", "v1", fields))
+        .unwrap();
+}
+
+fn insert_request(id: usize) -> String {
+    json!({
+        "op": "insert",
+        "schema_id": "users",
+        "schema_version": "v1",
+        "document": {"_id": format!("u{}", id), "name": format!("user {}", id)}
+    })
+    .to_string()
+}
+
+fn query_request(id: usize) -> String {
+    json!({
+        "op": "query",
+        "schema_id": "users",
+        "schema_version": "v1",
+        "filter": {"_id": {"$eq": format!("u{}", id)}},
+        "limit": 10
+    })
+    .to_string()
+}
+
+/// Runs `aerodb start` on `requests` and returns its exit status and
+/// responses (log events, also written to stdout, are skipped).
+fn run_start(
+    config: &Path,
+    crash_point: Option<&str>,
+    requests: &[String],
+) -> (std::process::ExitStatus, Vec<Value>) {
+    let mut cmd = aerodb("start", config);
+    match crash_point {
+        Some(point) => cmd.env("AERODB_CRASH_POINT", point),
+        None => cmd.env_remove("AERODB_CRASH_POINT"),
+    };
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    for request in requests {
+        writeln!(stdin, "{}", request).unwrap();
+    }
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    let responses = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .filter(|line| line.get("event").is_none())
+        .collect();
+    (output.status, responses)
+}
+
+/// Number of documents a query response returned
+fn found(response: &Value) -> usize {
+    assert_eq!(response["status"], "ok", "query failed: {}", response);
+    response["data"]
+        .as_array()
+        .expect("query data is not an array")
+        .len()
+}
+
+/// Which of documents `u0..u<count>` a fresh `aerodb start` finds
+fn stored(config: &Path, count: usize) -> Vec<bool> {
+    let queries: Vec<String> = (0..count).map(query_request).collect();
+    let (status, responses) = run_start(config, None, &queries);
+    assert!(status.success(), "start exited with {}", status);
+    assert_eq!(responses.len(), count);
+    responses
+        .iter()
+        .map(|response| found(response) == 1)
+        .collect()
+}
+
+// =============================================================================
+// Batching
+// =============================================================================
+
+/// A crash in the first WAL sync happens after all four appends of the
+/// batch and before any of them is acknowledged; recovery keeps all four
+#[test]
+fn test_batch_shares_one_sync_and_acks_after_it() {
+    let temp = TempDir::new().unwrap();
+    let config = write_config(&temp, 60_000_000, 4);
+    init(&config, &temp.path().join("data"));
+
+    let inserts: Vec<String> = (0..4).map(insert_request).collect();
+    let (status, responses) = run_start(&config, Some("wal_after_fsync"), &inserts);
+
+    assert!(!status.success(), "start did not crash in the batch sync");
+    assert!(
+        responses.is_empty(),
+        "acknowledged before the sync: {:?}",
+        responses
+    );
+    assert_eq!(stored(&config, 4), vec![true; 4]);
+}
+
+#[test]
+fn test_pipelined_writes_all_acknowledged_in_order() {
+    let temp = TempDir::new().unwrap();
+    let config = write_config(&temp, 50_000, 4);
+    init(&config, &temp.path().join("data"));
+
+    let mut requests: Vec<String> = (0..10).map(insert_request).collect();
+    requests.push(query_request(9));
+    let (status, responses) = run_start(&config, None, &requests);
+
+    assert!(status.success(), "start exited with {}", status);
+    assert_eq!(responses.len(), 11);
+    for response in &responses[..10] {
+        assert_eq!(response["status"], "ok", "insert failed: {}", response);
+    }
+    // The query, held behind the last batch, sees its write
+    assert_eq!(found(&responses[10]), 1);
+}
+
+/// A batch below `group_commit_max_records` is released when its window
+/// ends, without waiting for more requests or for stdin to close
+#[test]
+fn test_window_releases_partial_batch() {
+    let temp = TempDir::new().unwrap();
+    let config = write_config(&temp, 100_000, 64);
+    init(&config, &temp.path().join("data"));
+
+    let mut child = aerodb("start", &config)
+        .env_remove("AERODB_CRASH_POINT")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    let started = Instant::now();
+    writeln!(stdin, "{}", insert_request(0)).unwrap();
+    let response = loop {
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let line: Value = serde_json::from_str(&line).unwrap();
+        if line.get("event").is_none() {
+            break line;
+        }
+    };
+    assert_eq!(response["status"], "ok", "insert failed: {}", response);
+    assert!(started.elapsed() < Duration::from_secs(30));
+
+    drop(stdin);
+    assert!(child.wait().unwrap().success());
+}
@@ -0,0 +1,141 @@
+//! CLI Graceful Shutdown Tests
+//!
+//! Sends SIGTERM to a running `aerodb start` and `aerodb serve` and checks
+//! that each exits 0 with the `clean_shutdown` marker written.
+
+#![cfg(unix)]
+
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+// =============================================================================
+// Test Utilities
+// =============================================================================
+
+fn write_config(temp: &TempDir) -> PathBuf {
+    let config = format!(
+        "[server]\ndata_dir = '{}'\nshutdown_grace_ms = 5000\n\n[backup]\nbackup_dir = '{}'\n",
+        temp.path().join("data").display(),
+        temp.path().join("backups").display(),
+    );
+    let path = temp.path().join("aerodb.toml");
+    fs::write(&path, config).unwrap();
+    path
+}
+
+fn aerodb(command: &str, config: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_aerodb"));
+    cmd.arg(command).arg("--config").arg(config);
+    cmd
+}
+
+fn init(config: &Path) {
+    let status = aerodb("init", config)
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success(), "aerodb init failed");
+}
+
+fn terminate(child: &Child) {
+    let rc = unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) };
+    assert_eq!(rc, 0, "kill failed");
+}
+
+/// Wait for `child` to exit, killing it after `timeout`
+fn wait_exit(mut child: Child, timeout: Duration) -> std::process::ExitStatus {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            return status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            panic!("process did not exit within {:?}", timeout);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+// =============================================================================
+// SIGTERM
+// =============================================================================
+
+#[test]
+fn test_sigterm_stops_start_cleanly() {
+    let temp = TempDir::new().unwrap();
+    let config = write_config(&temp);
+    init(&config);
+    let marker = temp.path().join("data").join("clean_shutdown");
+
+    let mut child = aerodb("start", &config)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // One answered request shows the serving loop is up; stdin stays open
+    let mut stdin = child.stdin.take().unwrap();
+    writeln!(stdin, "{}", json!({"op": "query", "schema_id": "users"})).unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let response: Value = serde_json::from_str(&line).unwrap();
+    assert!(response.get("status").is_some(), "no response: {}", line);
+    assert!(!marker.exists());
+
+    terminate(&child);
+    let status = wait_exit(child, Duration::from_secs(30));
+    drop(stdin);
+
+    assert!(status.success(), "start exited with {}", status);
+    assert!(marker.exists(), "clean_shutdown marker missing");
+}
+
+#[test]
+fn test_sigterm_stops_serve_cleanly() {
+    let temp = TempDir::new().unwrap();
+    let config = write_config(&temp);
+    init(&config);
+    let marker = temp.path().join("data").join("clean_shutdown");
+
+    let port = free_port();
+    let child = aerodb("serve", &config)
+        .arg("--port")
+        .arg(port.to_string())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // Serving once the port accepts connections
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while TcpStream::connect(("127.0.0.1", port)).is_err() {
+        assert!(Instant::now() < deadline, "serve did not start listening");
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(!marker.exists());
+
+    terminate(&child);
+    let status = wait_exit(child, Duration::from_secs(30));
+
+    assert!(status.success(), "serve exited with {}", status);
+    assert!(marker.exists(), "clean_shutdown marker missing");
+}
@@ -43,7 +43,7 @@ fn test_no_execution_without_confirmation() {
 
     // Execute - should return awaiting confirmation, not success
     let response = handler.handle_command(request).unwrap();
-    assert_eq!(response.outcome, CommandOutcome::AwaitingConfirmation);
+    assert_eq!(response.outcome, CommandOutcome::ConfirmationRequired);
     assert!(response.confirmation_token.is_some());
 }
 
@@ -113,15 +113,15 @@ fn test_confirmation_not_reusable() {
     let mut flow = ConfirmationFlow::new();
     let target = Uuid::new_v4();
 
-    let token = flow.request_confirmation("request_promotion", Some(target));
+    let token = flow.request_confirmation("request_promotion", Some(target), "promote");
     let token_id = token.id();
 
     // First confirm succeeds
-    let result1 = flow.confirm(token_id, "request_promotion", Some(target));
+    let result1 = flow.confirm(token_id, "request_promotion", Some(target), "promote");
     assert!(matches!(result1, ConfirmationResult::Proceed { .. }));
 
     // Second confirm fails (token consumed)
-    let result2 = flow.confirm(token_id, "request_promotion", Some(target));
+    let result2 = flow.confirm(token_id, "request_promotion", Some(target), "promote");
     assert!(matches!(result2, ConfirmationResult::Abort { .. }));
 }
 
@@ -132,11 +132,11 @@ fn test_confirmation_command_specific() {
     let target = Uuid::new_v4();
 
     // Token for request_promotion
-    let token = flow.request_confirmation("request_promotion", Some(target));
+    let token = flow.request_confirmation("request_promotion", Some(target), "promote");
     let token_id = token.id();
 
     // Try to use for request_demotion - should fail
-    let result = flow.confirm(token_id, "request_demotion", Some(target));
+    let result = flow.confirm(token_id, "request_demotion", Some(target), "promote");
     assert!(matches!(result, ConfirmationResult::Abort { .. }));
 }
 
@@ -347,7 +347,7 @@ fn test_full_confirmation_lifecycle() {
     let request1 = CommandRequest::new(cmd.clone(), AuthorityContext::operator());
 
     let response1 = handler.handle_command(request1).unwrap();
-    assert_eq!(response1.outcome, CommandOutcome::AwaitingConfirmation);
+    assert_eq!(response1.outcome, CommandOutcome::ConfirmationRequired);
     let token_id = response1.confirmation_token.unwrap();
 
     // Step 2: Issue command with confirmation token
@@ -358,6 +358,72 @@ fn test_full_confirmation_lifecycle() {
     assert_eq!(response2.outcome, CommandOutcome::Success);
 }
 
+/// Test: A token confirms exactly one submission of exactly one payload.
+///
+/// Per PHASE7_CONFIRMATION_MODEL.md §4.3 and §7.
+#[test]
+fn test_confirmation_token_reuse_and_tampering_rejected() {
+    let mut handler = ControlPlaneHandler::new();
+    let force = |risks: &[&str]| {
+        ControlPlaneCommand::Control(ControlCommand::ForcePromotion {
+            replica_id: Uuid::nil(),
+            reason: "Primary lost".to_string(),
+            acknowledged_risks: risks.iter().map(|r| r.to_string()).collect(),
+        })
+    };
+    let submit = |handler: &mut ControlPlaneHandler, cmd, token| {
+        let request =
+            CommandRequest::new(cmd, AuthorityContext::operator()).with_confirmation(token);
+        handler.handle_command(request)
+    };
+
+    let first = handler
+        .handle_command(CommandRequest::new(
+            force(&["split_brain"]),
+            AuthorityContext::operator(),
+        ))
+        .unwrap();
+    assert_eq!(first.outcome, CommandOutcome::ConfirmationRequired);
+    let token = first.confirmation_token.unwrap();
+
+    // Same command, different arguments
+    let err = submit(&mut handler, force(&["accept_data_loss"]), token).unwrap_err();
+    assert_eq!(err.code(), "PHASE7_CONFIRMATION_MISMATCH");
+
+    // The identical command runs once
+    assert!(submit(&mut handler, force(&["split_brain"]), token).is_ok());
+    let err = submit(&mut handler, force(&["split_brain"]), token).unwrap_err();
+    assert_eq!(err.code(), "PHASE7_CONFIRMATION_REUSED");
+
+    let err = submit(&mut handler, force(&["split_brain"]), Uuid::new_v4()).unwrap_err();
+    assert_eq!(err.code(), "PHASE7_CONFIRMATION_NOT_FOUND");
+}
+
+/// Test: A token is refused once its TTL has passed.
+#[test]
+fn test_confirmation_token_expiry_rejected() {
+    let flow = ConfirmationFlow::new().with_max_token_age(std::time::Duration::ZERO);
+    let mut handler = ControlPlaneHandler::new().with_confirmation_flow(flow);
+    let cmd = ControlPlaneCommand::Control(ControlCommand::RequestDemotion {
+        node_id: Uuid::new_v4(),
+        reason: None,
+    });
+
+    let first = handler
+        .handle_command(CommandRequest::new(
+            cmd.clone(),
+            AuthorityContext::operator(),
+        ))
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(2));
+
+    let request = CommandRequest::new(cmd, AuthorityContext::operator())
+        .with_confirmation(first.confirmation_token.unwrap());
+    let err = handler.handle_command(request).unwrap_err();
+    assert_eq!(err.code(), "PHASE7_CONFIRMATION_EXPIRED");
+    assert!(err.definitely_not_executed());
+}
+
 // =============================================================================
 // OBSERVABILITY IS PASSIVE
 // =============================================================================
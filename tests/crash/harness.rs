@@ -5,7 +5,7 @@
 //! - Injects crashes via env var
 //! - Validates post-crash state
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
 
 /// Result of a crash test execution
@@ -69,6 +69,31 @@ pub fn execute_with_crash_point(
     }
 }
 
+/// Rerun this test binary's `child_test` with `crash_point` enabled
+///
+/// `child_test` is the full path of a test that returns early unless
+/// [`crash_child_data_dir`] yields a data directory.
+pub fn spawn_crash_child(crash_point: &str, child_test: &str, data_dir: &Path) -> CrashTestResult {
+    let exe = std::env::current_exe().expect("test binary path");
+    execute_with_crash_point(
+        crash_point,
+        exe.to_str().expect("test binary path is UTF-8"),
+        &["--exact", child_test, "--nocapture"],
+        data_dir,
+    )
+}
+
+/// The data directory of a child spawned by [`spawn_crash_child`]
+///
+/// `None` unless `AERODB_CRASH_POINT` is `crash_point`, so child tests are
+/// no-ops in a normal test run.
+pub fn crash_child_data_dir(crash_point: &str) -> Option<PathBuf> {
+    if std::env::var("AERODB_CRASH_POINT").as_deref() != Ok(crash_point) {
+        return None;
+    }
+    std::env::var_os("AERODB_DATA_DIR").map(PathBuf::from)
+}
+
 /// Validate that data directory is in a consistent state after crash
 pub fn validate_post_crash_state(data_dir: &Path) -> Result<(), Vec<String>> {
     let mut errors = Vec::new();
@@ -7,7 +7,9 @@ use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
+use crate::crash::harness::{crash_child_data_dir, spawn_crash_child};
 use crate::crash::utils::{cleanup_temp_data_dir, create_temp_data_dir, validate_backup_integrity};
+use aerodb::backup::{BackupConfig, BackupManager};
 use aerodb::crash_point::points;
 
 /// Helper to create a valid backup archive
@@ -86,4 +88,78 @@ fn test_backup_crash_points_defined() {
     );
     assert_eq!(points::BACKUP_AFTER_WAL_COPY, "backup_after_wal_copy");
     assert_eq!(points::BACKUP_BEFORE_ARCHIVE, "backup_before_archive");
+    assert_eq!(
+        points::BACKUP_BEFORE_ARCHIVE_FSYNC,
+        "backup_before_archive_fsync"
+    );
+}
+
+fn backup_config(data_dir: &Path) -> BackupConfig {
+    BackupConfig {
+        backup_dir: data_dir.join("backups").to_string_lossy().into_owned(),
+        ..BackupConfig::default()
+    }
+}
+
+/// Child half of `test_backup_crash_before_archive_fsync_is_not_listed`
+#[test]
+fn backup_before_archive_fsync_child() {
+    use aerodb::snapshot::GlobalExecutionLock;
+    use aerodb::wal::{WalPayload, WalWriter};
+
+    let Some(data_dir) = crash_child_data_dir(points::BACKUP_BEFORE_ARCHIVE_FSYNC) else {
+        return;
+    };
+    let mut wal = WalWriter::open(&data_dir).unwrap();
+    wal.append_insert(WalPayload::new("c", "doc1", "s", "v1", b"{}".to_vec()))
+        .unwrap();
+
+    let manager = BackupManager::new(backup_config(&data_dir)).unwrap();
+    let lock = GlobalExecutionLock::new();
+    manager
+        .create_backup(
+            &data_dir,
+            &data_dir.join("data/documents.dat"),
+            &data_dir.join("metadata/schemas"),
+            &wal,
+            None,
+            &lock,
+        )
+        .unwrap();
+}
+
+/// Test: an archive written but not yet fsynced is never listed as a backup
+#[test]
+fn test_backup_crash_before_archive_fsync_is_not_listed() {
+    let data_dir = create_temp_data_dir("backup_before_archive_fsync");
+    std::fs::write(data_dir.join("data/documents.dat"), b"storage contents").unwrap();
+
+    let result = spawn_crash_child(
+        points::BACKUP_BEFORE_ARCHIVE_FSYNC,
+        "crash::scenarios::backup::backup_before_archive_fsync_child",
+        &data_dir,
+    );
+    assert!(result.crashed, "child must abort at the crash point");
+    assert!(result.stderr.contains("[CRASH]"), "{}", result.stderr);
+
+    // The tar was fully written, but only under its partial name
+    let names: Vec<String> = std::fs::read_dir(data_dir.join("backups"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert!(
+        names.iter().any(|name| name.ends_with(".tar.partial")),
+        "{:?}",
+        names
+    );
+    assert!(
+        !names.iter().any(|name| name.ends_with(".tar")),
+        "{:?}",
+        names
+    );
+
+    let manager = BackupManager::new(backup_config(&data_dir)).unwrap();
+    assert!(manager.list_backups().unwrap().is_empty());
+
+    cleanup_temp_data_dir(&data_dir);
 }
@@ -0,0 +1,307 @@
+//! Collection rename crash test scenarios
+//!
+//! A rename interrupted at any point must recover with the collection
+//! wholly under its old name or wholly under its new one, never split
+//! between them, and retrying the rename must finish it.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::crash::harness::{crash_child_data_dir, spawn_crash_child};
+use crate::crash::utils::{cleanup_temp_data_dir, create_temp_data_dir};
+use aerodb::admission_control::{AdmissionControlConfig, AdmissionController};
+use aerodb::api::{ApiHandler, Subsystems};
+use aerodb::backpressure::{BackpressureConfig, BackpressureManager};
+use aerodb::crash_point::points;
+use aerodb::index::IndexManager;
+use aerodb::planner::{Statistics, StatisticsConfig};
+use aerodb::query_limits::QueryLimitsConfig;
+use aerodb::recovery::{RecoveryManager, RecoveryStorage};
+use aerodb::resource_limits::{ResourceLimitsConfig, ResourceManager};
+use aerodb::schema::{FieldDef, Schema, SchemaLoader};
+use aerodb::storage::{StorageReader, StorageWriter};
+use aerodb::wal::{WalReader, WalWriter};
+
+/// The subsystems of one database, serving requests through the API
+struct Engine {
+    handler: ApiHandler,
+    schema_loader: SchemaLoader,
+    wal_writer: WalWriter,
+    storage_writer: StorageWriter,
+    storage_reader: StorageReader,
+    index_manager: IndexManager,
+    statistics: Statistics,
+    resource_manager: ResourceManager,
+    backpressure_manager: BackpressureManager,
+    admission_controller: AdmissionController,
+    query_limits: QueryLimitsConfig,
+}
+
+impl Engine {
+    /// A new database with a `users` collection
+    fn create(data_dir: &Path) -> Self {
+        let mut schema_loader = SchemaLoader::new(data_dir);
+        let mut fields = HashMap::new();
+        fields.insert("_id".to_string(), FieldDef::required_string());
+        fields.insert("name".to_string(), FieldDef::required_string());
+        let schema = Schema::new("users", "v1", fields);
+        schema_loader.save_schema(&schema).unwrap();
+        schema_loader.register(schema).unwrap();
+
+        let storage_writer = StorageWriter::open(data_dir).unwrap();
+        let storage_reader = StorageReader::open_from_data_dir(data_dir).unwrap();
+        Self::serve(
+            data_dir,
+            schema_loader,
+            storage_writer,
+            storage_reader,
+            name_index(),
+        )
+    }
+
+    /// Restart after a crash: replay the WAL, then rebuild the indexes
+    fn recover(data_dir: &Path) -> Self {
+        let mut schema_loader = SchemaLoader::new(data_dir);
+        schema_loader.load_all().unwrap();
+
+        let mut wal = WalReader::open_from_data_dir(data_dir).unwrap();
+        let mut storage = RecoveryStorage::open(data_dir).unwrap();
+        let mut index_manager = name_index();
+        RecoveryManager::new(data_dir)
+            .recover(&mut wal, &mut storage, &mut index_manager, &schema_loader)
+            .unwrap();
+        let (storage_writer, _) = storage.into_parts();
+
+        let mut storage_reader = StorageReader::open_from_data_dir(data_dir).unwrap();
+        let mut index_manager = name_index();
+        index_manager
+            .rebuild_from_storage(&mut storage_reader)
+            .unwrap();
+        Self::serve(
+            data_dir,
+            schema_loader,
+            storage_writer,
+            storage_reader,
+            index_manager,
+        )
+    }
+
+    fn serve(
+        data_dir: &Path,
+        schema_loader: SchemaLoader,
+        storage_writer: StorageWriter,
+        storage_reader: StorageReader,
+        index_manager: IndexManager,
+    ) -> Self {
+        let resource_config = ResourceLimitsConfig {
+            min_free_disk_bytes: 0,
+            ..Default::default()
+        };
+        Self {
+            handler: ApiHandler::new("default"),
+            schema_loader,
+            wal_writer: WalWriter::open(data_dir).unwrap(),
+            storage_writer,
+            storage_reader,
+            index_manager,
+            statistics: Statistics::in_memory(StatisticsConfig::default()),
+            resource_manager: ResourceManager::new(resource_config, data_dir),
+            backpressure_manager: BackpressureManager::new(BackpressureConfig::default()),
+            admission_controller: AdmissionController::new(AdmissionControlConfig::default()),
+            query_limits: QueryLimitsConfig::default(),
+        }
+    }
+
+    fn call(&mut self, request: Value) -> Value {
+        let mut subsystems = Subsystems {
+            schema_loader: &mut self.schema_loader,
+            wal_writer: &mut self.wal_writer,
+            storage_writer: &mut self.storage_writer,
+            storage_reader: &mut self.storage_reader,
+            index_manager: &mut self.index_manager,
+            statistics: &mut self.statistics,
+            resource_manager: &self.resource_manager,
+            backpressure_manager: &self.backpressure_manager,
+            admission_controller: &self.admission_controller,
+            query_limits: &self.query_limits,
+        };
+        let response = self.handler.handle(&request.to_string(), &mut subsystems);
+        serde_json::from_str(&response.to_json()).unwrap()
+    }
+
+    /// Documents of a collection, or 0 if it does not exist
+    fn count(&mut self, collection: &str) -> usize {
+        let response = self.call(json!({
+            "op": "query",
+            "schema_id": collection,
+            "schema_version": "v1",
+            "filter": {"name": {"$eq": "Alice"}},
+            "limit": 10
+        }));
+        response["data"].as_array().map_or(0, Vec::len)
+    }
+
+    /// Rename `users` to `members`, confirming with the token the first
+    /// request is refused with
+    fn rename(&mut self) -> Value {
+        let mut request = json!({
+            "op": "rename_collection",
+            "from": "users",
+            "to": "members"
+        });
+        let response = self.call(request.clone());
+        request["confirm"] = response["confirmation_token"].clone();
+        self.call(request)
+    }
+}
+
+fn name_index() -> IndexManager {
+    IndexManager::new(HashSet::from(["name".to_string()]))
+}
+
+/// Collections holding the documents in storage
+fn stored_collections(data_dir: &Path) -> HashSet<String> {
+    StorageReader::open_from_data_dir(data_dir)
+        .unwrap()
+        .build_document_map()
+        .unwrap()
+        .into_values()
+        .filter(|record| !record.is_tombstone)
+        .map(|record| record.schema_id)
+        .collect()
+}
+
+/// Child half of the rename crash tests
+#[test]
+fn rename_child() {
+    let Some(data_dir) = crash_child_data_dir(points::COLLECTION_RENAME_AFTER_SCHEMA_COPY)
+        .or_else(|| crash_child_data_dir(points::COLLECTION_RENAME_MID_APPLY))
+    else {
+        return;
+    };
+    let mut engine = Engine::create(&data_dir);
+    for id in ["u1", "u2", "u3"] {
+        let response = engine.call(json!({
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": id, "name": "Alice"}
+        }));
+        assert_eq!(response["status"], "ok", "{}", response);
+    }
+    engine.rename();
+}
+
+/// Crash a rename of `users` to `members` at `crash_point`, then recover
+fn crash_rename(crash_point: &str, prefix: &str) -> (PathBuf, Engine) {
+    let data_dir = create_temp_data_dir(prefix);
+    let result = spawn_crash_child(
+        crash_point,
+        "crash::scenarios::collection::rename_child",
+        &data_dir,
+    );
+    assert!(result.crashed, "child must abort at the crash point");
+    assert!(result.stderr.contains("[CRASH]"), "{}", result.stderr);
+
+    let engine = Engine::recover(&data_dir);
+    (data_dir, engine)
+}
+
+/// Test: a rename that crashed before its WAL append recovers wholly
+/// under the old name, and a retry finishes it
+#[test]
+fn test_rename_crash_before_wal_append_recovers_old_collection() {
+    let (data_dir, mut engine) = crash_rename(
+        points::COLLECTION_RENAME_AFTER_SCHEMA_COPY,
+        "collection_rename_after_schema_copy",
+    );
+    assert_eq!(
+        stored_collections(&data_dir),
+        HashSet::from(["users".to_string()])
+    );
+    assert_eq!(engine.count("users"), 3);
+    assert_eq!(engine.count("members"), 0);
+
+    let response = engine.rename();
+    assert_eq!(response["data"]["documents"], 3, "{}", response);
+    assert_eq!(engine.count("members"), 3);
+    assert!(!engine.schema_loader.schema_id_exists("users"));
+    assert_eq!(
+        stored_collections(&data_dir),
+        HashSet::from(["members".to_string()])
+    );
+
+    cleanup_temp_data_dir(&data_dir);
+}
+
+/// Test: a rename that crashed after moving some documents in storage
+/// recovers wholly under the new name, and a retry removes the old one
+#[test]
+fn test_rename_crash_mid_apply_recovers_new_collection() {
+    let (data_dir, mut engine) = crash_rename(
+        points::COLLECTION_RENAME_MID_APPLY,
+        "collection_rename_mid_apply",
+    );
+    assert_eq!(
+        stored_collections(&data_dir),
+        HashSet::from(["members".to_string()])
+    );
+    assert_eq!(engine.count("members"), 3);
+    assert_eq!(engine.count("users"), 0);
+
+    let response = engine.rename();
+    assert_eq!(response["data"]["documents"], 0, "{}", response);
+    assert!(!engine.schema_loader.schema_id_exists("users"));
+    assert_eq!(engine.count("members"), 3);
+
+    cleanup_temp_data_dir(&data_dir);
+}
+
+/// Test: a completed rename and copy are replayed from the WAL on restart
+#[test]
+fn test_completed_rename_and_copy_recover() {
+    let data_dir = create_temp_data_dir("collection_rename_completed");
+    let mut engine = Engine::create(&data_dir);
+    for id in ["u1", "u2", "u3"] {
+        let response = engine.call(json!({
+            "op": "insert",
+            "schema_id": "users",
+            "schema_version": "v1",
+            "document": {"_id": id, "name": "Alice"}
+        }));
+        assert_eq!(response["status"], "ok", "{}", response);
+    }
+    let response = engine.rename();
+    assert_eq!(response["data"]["documents"], 3, "{}", response);
+    let mut copy = json!({"op": "copy_collection", "from": "members", "to": "archive"});
+    copy["confirm"] = engine.call(copy.clone())["confirmation_token"].clone();
+    let response = engine.call(copy);
+    assert_eq!(response["data"]["documents"], 3, "{}", response);
+    drop(engine);
+
+    let mut engine = Engine::recover(&data_dir);
+    assert_eq!(engine.count("users"), 0);
+    assert_eq!(engine.count("members"), 3);
+    assert_eq!(engine.count("archive"), 3);
+    assert_eq!(
+        stored_collections(&data_dir),
+        HashSet::from(["members".to_string(), "archive".to_string()])
+    );
+
+    cleanup_temp_data_dir(&data_dir);
+}
+
+/// Test: Collection rename crash points defined
+#[test]
+fn test_collection_rename_crash_points_defined() {
+    assert_eq!(
+        points::COLLECTION_RENAME_AFTER_SCHEMA_COPY,
+        "collection_rename_after_schema_copy"
+    );
+    assert_eq!(
+        points::COLLECTION_RENAME_MID_APPLY,
+        "collection_rename_mid_apply"
+    );
+}
@@ -0,0 +1,104 @@
+//! Migration crash test scenarios
+//!
+//! A migration interrupted after its start was recorded must never be
+//! reported as applied.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::crash::harness::{crash_child_data_dir, spawn_crash_child};
+use crate::crash::utils::{cleanup_temp_data_dir, create_temp_data_dir};
+use aerodb::crash_point::points;
+use aerodb::migrations::checksum::generate_checksum_for_file;
+use aerodb::migrations::operations::InMemoryExecutor;
+use aerodb::migrations::{
+    Migration, MigrationOperation, MigrationRunner, MigrationState, MigrationStatus,
+};
+
+fn write_migration(migrations_dir: &Path) {
+    let mut migration = Migration {
+        version: 1,
+        name: "create_users".to_string(),
+        checksum: String::new(),
+        timestamp: chrono::Utc::now(),
+        file_path: None,
+        up: vec![MigrationOperation::CreateCollection {
+            name: "users".to_string(),
+            schema: serde_json::json!({}),
+        }],
+        down: vec![MigrationOperation::DropCollection {
+            name: "users".to_string(),
+        }],
+    };
+    migration.checksum = generate_checksum_for_file(&serde_yaml::to_string(&migration).unwrap());
+    fs::create_dir_all(migrations_dir).unwrap();
+    fs::write(
+        migrations_dir.join("001_create_users.yaml"),
+        serde_yaml::to_string(&migration).unwrap(),
+    )
+    .unwrap();
+}
+
+fn runner(data_dir: &Path) -> MigrationRunner {
+    MigrationRunner::new(
+        data_dir.join("migrations"),
+        data_dir.to_path_buf(),
+        Arc::new(InMemoryExecutor::new()),
+    )
+    .unwrap()
+}
+
+/// Child half of `test_migration_crash_after_record_start_is_marked_failed`
+#[test]
+fn migration_after_record_start_child() {
+    let Some(data_dir) = crash_child_data_dir(points::MIGRATION_AFTER_RECORD_START) else {
+        return;
+    };
+    runner(&data_dir).migrate_up().unwrap();
+}
+
+/// Test: a migration that crashed before its operations ran shows as
+/// failed, not applied, once the runner starts again
+#[test]
+fn test_migration_crash_after_record_start_is_marked_failed() {
+    let data_dir = create_temp_data_dir("migration_after_record_start");
+    write_migration(&data_dir.join("migrations"));
+
+    let result = spawn_crash_child(
+        points::MIGRATION_AFTER_RECORD_START,
+        "crash::scenarios::migration::migration_after_record_start_child",
+        &data_dir,
+    );
+    assert!(result.crashed, "child must abort at the crash point");
+    assert!(result.stderr.contains("[CRASH]"), "{}", result.stderr);
+
+    // The crash left the migration recorded as running
+    let state = MigrationState::new(data_dir.clone());
+    state.load().unwrap();
+    assert_eq!(state.get_all()[0].status, MigrationStatus::Running);
+
+    // Starting a runner marks it failed, and it is still pending
+    let status = runner(&data_dir).status().unwrap();
+    assert_eq!(status.current_version, 0);
+    assert_eq!(status.applied_count, 0);
+    assert_eq!(status.pending_count, 1);
+
+    let state = MigrationState::new(data_dir.clone());
+    state.load().unwrap();
+    let record = &state.get_all()[0];
+    assert_eq!(record.status, MigrationStatus::Failed);
+    assert!(record.error.is_some());
+    assert!(!state.is_applied(1));
+
+    cleanup_temp_data_dir(&data_dir);
+}
+
+/// Test: Migration crash points defined
+#[test]
+fn test_migration_crash_points_defined() {
+    assert_eq!(
+        points::MIGRATION_AFTER_RECORD_START,
+        "migration_after_record_start"
+    );
+}
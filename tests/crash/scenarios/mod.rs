@@ -2,6 +2,9 @@
 
 pub mod backup;
 pub mod checkpoint;
+pub mod collection;
+pub mod migration;
+pub mod panic;
 pub mod recovery;
 pub mod restore;
 pub mod snapshot;
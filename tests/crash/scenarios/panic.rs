@@ -0,0 +1,112 @@
+//! Panic crash report scenarios
+//!
+//! A panic in a process with a `CrashReporter` installed must leave a
+//! parseable crash report and a crash marker behind before the process
+//! aborts, and the next recovery must detect the marker.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use crate::crash::utils::{cleanup_temp_data_dir, create_temp_data_dir};
+use aerodb::index::IndexManager;
+use aerodb::observability::{OperationLog, OperationLogConfig, OperationLogEntry, OperationType};
+use aerodb::panic_handler::{list_crash_reports, CrashMarker, CrashReporter};
+use aerodb::recovery::{RecoveryManager, RecoveryStorage};
+use aerodb::schema::SchemaLoader;
+use aerodb::wal::{WalReader, WalWriter};
+
+/// Set for the child process; names its data directory
+const CHILD_DATA_DIR: &str = "AERODB_PANIC_CHILD_DATA_DIR";
+
+/// Child half of the panic scenarios
+#[test]
+fn panic_child() {
+    let Some(data_dir) = std::env::var_os(CHILD_DATA_DIR).map(PathBuf::from) else {
+        return;
+    };
+    let operation_log = Arc::new(OperationLog::new(OperationLogConfig {
+        enabled: true,
+        ..Default::default()
+    }));
+    operation_log.log(
+        OperationLogEntry::builder(OperationType::Find)
+            .collection("users")
+            .build(),
+    );
+    let _wal = WalWriter::open(&data_dir).unwrap();
+    CrashReporter::new(&data_dir)
+        .with_operation_log(operation_log)
+        .with_replication_role("standalone")
+        .install();
+
+    // Panic on an operation thread, as a request handler would
+    let _ = std::thread::spawn(|| panic!("injected panic")).join();
+}
+
+/// Run `panic_child` against `data_dir` and return its stderr
+fn spawn_panic_child(data_dir: &Path) -> String {
+    let output = Command::new(std::env::current_exe().expect("test binary path"))
+        .args([
+            "--exact",
+            "crash::scenarios::panic::panic_child",
+            "--nocapture",
+        ])
+        .env(CHILD_DATA_DIR, data_dir)
+        .output()
+        .expect("spawn child");
+    assert!(!output.status.success(), "child must abort");
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Test: the report exists after the child aborts, parses, and carries
+/// the panic message and recent operations
+#[test]
+fn test_panic_writes_crash_report() {
+    let data_dir = create_temp_data_dir("panic_report");
+
+    let stderr = spawn_panic_child(&data_dir);
+    assert!(stderr.contains(r#""event":"PANIC""#), "{}", stderr);
+
+    let reports = list_crash_reports(&data_dir).unwrap();
+    assert_eq!(reports.len(), 1);
+    let report = reports[0].report.as_ref().expect("report parses");
+    assert_eq!(report["message"], "injected panic");
+    assert_eq!(report["version"], aerodb::version::BINARY_VERSION);
+    assert_eq!(report["replication_role"], "standalone");
+    assert_eq!(report["recent_operations"][0]["collection"], "users");
+    assert!(stderr.contains(&reports[0].id), "{}", stderr);
+
+    cleanup_temp_data_dir(&data_dir);
+}
+
+/// Test: the child leaves a crash marker, and the next boot's recovery
+/// reports it and removes it
+#[test]
+fn test_panic_leaves_crash_marker_detected_at_boot() {
+    let data_dir = create_temp_data_dir("panic_marker");
+
+    spawn_panic_child(&data_dir);
+    let marker = CrashMarker::load(&data_dir)
+        .unwrap()
+        .expect("crash marker written");
+    assert_eq!(marker.message, "injected panic");
+    assert!(marker.location.unwrap().contains("panic.rs"));
+    assert!(!marker.backtrace.is_empty());
+
+    let mut schema_loader = SchemaLoader::new(&data_dir);
+    schema_loader.load_all().unwrap();
+    let mut wal = WalReader::open_from_data_dir(&data_dir).unwrap();
+    let mut storage = RecoveryStorage::open(&data_dir).unwrap();
+    let mut index_manager = IndexManager::new(HashSet::new());
+    let state = RecoveryManager::new(&data_dir)
+        .recover(&mut wal, &mut storage, &mut index_manager, &schema_loader)
+        .unwrap();
+
+    assert!(!state.was_clean_shutdown);
+    assert_eq!(state.crash_marker.unwrap().message, "injected panic");
+    assert!(CrashMarker::load(&data_dir).unwrap().is_none());
+
+    cleanup_temp_data_dir(&data_dir);
+}
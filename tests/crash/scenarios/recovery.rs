@@ -3,10 +3,19 @@
 //! Per CRASH_TESTING.md:
 //! - Same crash + same data → identical final state
 
+use std::collections::HashSet;
+
+use serde_json::json;
+
+use crate::crash::harness::{crash_child_data_dir, spawn_crash_child};
 use crate::crash::utils::{
     cleanup_temp_data_dir, create_temp_data_dir, validate_wal_integrity, write_test_wal_record,
 };
 use aerodb::crash_point::points;
+use aerodb::index::{DocumentInfo, IndexManager};
+use aerodb::recovery::{RecoveryManager, RecoveryStorage, SchemaCheck};
+use aerodb::storage::{StoragePayload, StorageReader, StorageWriter};
+use aerodb::wal::{WalPayload, WalReader, WalWriter};
 
 /// Test: Recovery produces deterministic state
 #[test]
@@ -52,3 +61,196 @@ fn test_recovery_crash_points_defined() {
         "recovery_after_index_rebuild"
     );
 }
+
+/// Accepts every schema; the write path tests do not load schemas
+struct AnySchema;
+
+impl SchemaCheck for AnySchema {
+    fn schema_exists(&self, _schema_id: &str) -> bool {
+        true
+    }
+
+    fn schema_version_exists(&self, _schema_id: &str, _version: &str) -> bool {
+        true
+    }
+}
+
+const USER: &[u8] = br#"{"_id":"user_1","email":"a@x.io"}"#;
+
+fn email_index() -> IndexManager {
+    IndexManager::new(HashSet::from(["email".to_string()]))
+}
+
+/// Child half of the write path tests below
+///
+/// Inserts one document in the write path's order: WAL append, storage
+/// apply, index update. Aborts at whichever point the parent enabled.
+#[test]
+fn write_path_child() {
+    let Some(data_dir) = crash_child_data_dir(points::WAL_AFTER_APPEND)
+        .or_else(|| crash_child_data_dir(points::STORAGE_AFTER_WRITE))
+    else {
+        return;
+    };
+
+    let mut wal = WalWriter::open(&data_dir).unwrap();
+    wal.append_insert(WalPayload::new(
+        "users",
+        "user_1",
+        "users",
+        "v1",
+        USER.to_vec(),
+    ))
+    .unwrap();
+
+    let mut storage = StorageWriter::open(&data_dir).unwrap();
+    let offset = storage
+        .write(&StoragePayload::new(
+            "users",
+            "user_1",
+            "users",
+            "v1",
+            USER.to_vec(),
+        ))
+        .unwrap();
+
+    let mut index = email_index();
+    index.apply_write(&DocumentInfo {
+        document_id: "user_1".to_string(),
+        schema_id: "users".to_string(),
+        schema_version: "v1".to_string(),
+        is_tombstone: false,
+        body: serde_json::from_slice(USER).unwrap(),
+        offset,
+    });
+}
+
+/// Crash the write path at `crash_point`, then recover and check that the
+/// interrupted insert is in storage and in the rebuilt index
+fn assert_recovery_completes_interrupted_insert(crash_point: &str, prefix: &str) {
+    let data_dir = create_temp_data_dir(prefix);
+
+    let result = spawn_crash_child(
+        crash_point,
+        "crash::scenarios::recovery::write_path_child",
+        &data_dir,
+    );
+    assert!(result.crashed, "child must abort at the crash point");
+    assert!(result.stderr.contains("[CRASH]"), "{}", result.stderr);
+
+    let mut wal = WalReader::open_from_data_dir(&data_dir).unwrap();
+    let mut storage = RecoveryStorage::open(&data_dir).unwrap();
+    let mut index = email_index();
+    let state = RecoveryManager::new(&data_dir)
+        .recover(&mut wal, &mut storage, &mut index, &AnySchema)
+        .unwrap();
+    assert_eq!(state.replay_stats.records_replayed, 1);
+
+    let (writer, _) = storage.into_parts();
+    assert!(writer.has_document("users:user_1"));
+
+    let mut reader = StorageReader::open_from_data_dir(&data_dir).unwrap();
+    index.rebuild_from_storage(&mut reader).unwrap();
+    assert_eq!(index.lookup_eq("email", &json!("a@x.io")).len(), 1);
+    assert_eq!(index.lookup_eq("_id", &json!("user_1")).len(), 1);
+
+    cleanup_temp_data_dir(&data_dir);
+}
+
+/// Test: a record made durable in the WAL but never applied to storage is
+/// replayed by recovery
+#[test]
+fn test_crash_after_wal_append_replays_record() {
+    assert_recovery_completes_interrupted_insert(points::WAL_AFTER_APPEND, "wal_after_append");
+}
+
+/// Test: a record applied to storage but never indexed is indexed after
+/// recovery
+#[test]
+fn test_crash_after_storage_write_rebuilds_index() {
+    assert_recovery_completes_interrupted_insert(
+        points::STORAGE_AFTER_WRITE,
+        "storage_after_write",
+    );
+}
+
+const UNSYNCED: &[u8] = br#"{"_id":"user_2","email":"b@x.io"}"#;
+
+/// Child half of `test_crash_before_wal_fsync_drops_unsynced_record`
+///
+/// Appends a second insert, which aborts after the write but before the
+/// fsync that would acknowledge it.
+#[test]
+fn unsynced_append_child() {
+    let Some(data_dir) = crash_child_data_dir(points::WAL_AFTER_APPEND_BEFORE_FSYNC) else {
+        return;
+    };
+
+    let mut wal = WalWriter::open(&data_dir).unwrap();
+    wal.append_insert(WalPayload::new(
+        "users",
+        "user_2",
+        "users",
+        "v1",
+        UNSYNCED.to_vec(),
+    ))
+    .unwrap();
+    println!("ACK user_2");
+}
+
+/// Test: a record written to the WAL but never fsynced is not visible after
+/// restart; the acknowledged record before it is
+#[test]
+fn test_crash_before_wal_fsync_drops_unsynced_record() {
+    let data_dir = create_temp_data_dir("wal_after_append_before_fsync");
+    let wal_path = data_dir.join("wal").join("wal.log");
+
+    let mut wal = WalWriter::open(&data_dir).unwrap();
+    wal.append_insert(WalPayload::new(
+        "users",
+        "user_1",
+        "users",
+        "v1",
+        USER.to_vec(),
+    ))
+    .unwrap();
+    drop(wal);
+    let synced_len = std::fs::metadata(&wal_path).unwrap().len();
+
+    let result = spawn_crash_child(
+        points::WAL_AFTER_APPEND_BEFORE_FSYNC,
+        "crash::scenarios::recovery::unsynced_append_child",
+        &data_dir,
+    );
+    assert!(result.crashed, "child must abort at the crash point");
+    assert!(result.stderr.contains("[CRASH]"), "{}", result.stderr);
+    assert!(!result.stdout.contains("ACK "), "{}", result.stdout);
+
+    // A process abort leaves the write in the page cache; drop everything
+    // past the last fsync as a power loss would.
+    assert!(std::fs::metadata(&wal_path).unwrap().len() > synced_len);
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(&wal_path)
+        .unwrap()
+        .set_len(synced_len)
+        .unwrap();
+
+    let mut wal = WalReader::open_from_data_dir(&data_dir).unwrap();
+    let mut storage = RecoveryStorage::open(&data_dir).unwrap();
+    let mut index = email_index();
+    let state = RecoveryManager::new(&data_dir)
+        .recover(&mut wal, &mut storage, &mut index, &AnySchema)
+        .unwrap();
+    assert_eq!(state.replay_stats.records_replayed, 1);
+
+    let (writer, _) = storage.into_parts();
+    assert!(writer.has_document("users:user_1"));
+    assert!(!writer.has_document("users:user_2"));
+
+    // The lost sequence number is reused by the next append
+    let wal = WalWriter::open(&data_dir).unwrap();
+    assert_eq!(wal.next_sequence_number(), 2);
+
+    cleanup_temp_data_dir(&data_dir);
+}
@@ -6,6 +6,7 @@
 use std::fs::{self, File};
 use std::io::Write;
 
+use crate::crash::harness::{crash_child_data_dir, spawn_crash_child};
 use crate::crash::utils::{
     cleanup_temp_data_dir, create_temp_data_dir, validate_snapshot_integrity,
 };
@@ -67,3 +68,58 @@ fn test_snapshot_crash_points_defined() {
     assert_eq!(points::SNAPSHOT_BEFORE_MANIFEST, "snapshot_before_manifest");
     assert_eq!(points::SNAPSHOT_AFTER_MANIFEST, "snapshot_after_manifest");
 }
+
+/// Child half of `test_snapshot_crash_before_manifest_is_not_listed`
+#[test]
+fn snapshot_before_manifest_child() {
+    use aerodb::snapshot::{GlobalExecutionLock, SnapshotManager};
+    use aerodb::wal::WalWriter;
+
+    let Some(data_dir) = crash_child_data_dir(points::SNAPSHOT_BEFORE_MANIFEST) else {
+        return;
+    };
+    let wal = WalWriter::open(&data_dir).unwrap();
+    let lock = GlobalExecutionLock::new();
+    SnapshotManager::create_snapshot(
+        &data_dir,
+        &data_dir.join("data/documents.dat"),
+        &data_dir.join("metadata/schemas"),
+        &wal,
+        &lock,
+    )
+    .unwrap();
+}
+
+/// Test: a snapshot whose storage was copied but whose manifest was never
+/// written is not listed after the crash
+#[test]
+fn test_snapshot_crash_before_manifest_is_not_listed() {
+    use aerodb::snapshot::SnapshotManager;
+
+    let data_dir = create_temp_data_dir("snapshot_before_manifest");
+    fs::write(data_dir.join("data/documents.dat"), b"storage contents").unwrap();
+
+    let result = spawn_crash_child(
+        points::SNAPSHOT_BEFORE_MANIFEST,
+        "crash::scenarios::snapshot::snapshot_before_manifest_child",
+        &data_dir,
+    );
+    assert!(result.crashed, "child must abort at the crash point");
+    assert!(result.stderr.contains("[CRASH]"), "{}", result.stderr);
+
+    // The copied storage is on disk, but without a manifest
+    let partial: Vec<_> = fs::read_dir(data_dir.join("snapshots"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(partial.len(), 1);
+    assert!(partial[0].join("storage.dat").exists());
+    assert!(!partial[0].join("manifest.json").exists());
+
+    assert!(SnapshotManager::list_snapshots(&data_dir)
+        .unwrap()
+        .is_empty());
+    assert!(validate_snapshot_integrity(&data_dir).is_ok());
+
+    cleanup_temp_data_dir(&data_dir);
+}
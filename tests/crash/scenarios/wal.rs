@@ -69,3 +69,110 @@ fn test_wal_crash_points_defined() {
     assert_eq!(points::WAL_BEFORE_TRUNCATE, "wal_before_truncate");
     assert_eq!(points::WAL_AFTER_TRUNCATE, "wal_after_truncate");
 }
+
+/// Child half of `test_group_commit_crash_before_fsync_loses_only_unacked_batch`.
+///
+/// Does nothing unless spawned by the parent test with `AERODB_CRASH_POINT`
+/// and `AERODB_DATA_DIR` set. Appends one more group commit batch, which
+/// aborts at `wal_before_fsync` before any writer is acknowledged.
+#[test]
+fn group_commit_crash_child() {
+    use aerodb::wal::{GroupCommitWriter, WalPayload, WalSyncConfig, WalWriter};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    if std::env::var("AERODB_CRASH_POINT").as_deref() != Ok(points::WAL_BEFORE_FSYNC) {
+        return;
+    }
+    let Some(data_dir) = std::env::var_os("AERODB_DATA_DIR") else {
+        return;
+    };
+
+    let config = WalSyncConfig::group_commit(Duration::from_secs(30), 4);
+    let writer = WalWriter::open_with_sync(data_dir.as_ref(), config.clone()).unwrap();
+    let writer = Arc::new(GroupCommitWriter::new(writer, config));
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let writer = Arc::clone(&writer);
+            std::thread::spawn(move || {
+                let payload = WalPayload::new("c", format!("unacked{}", i), "s", "v1", vec![]);
+                let seq = writer.append_insert(payload).unwrap();
+                println!("ACK {}", seq);
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+/// Test: group commit crash before the batch fsync loses at most the
+/// unacknowledged batch; every previously acknowledged record survives.
+#[test]
+fn test_group_commit_crash_before_fsync_loses_only_unacked_batch() {
+    use crate::crash::harness::execute_with_crash_point;
+    use aerodb::wal::{GroupCommitWriter, WalPayload, WalReader, WalSyncConfig, WalWriter};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let data_dir = create_temp_data_dir("wal_group_commit_crash");
+
+    // Acknowledged batch: four concurrent writers share one fsync
+    let config = WalSyncConfig::group_commit(Duration::from_secs(30), 4);
+    let writer = WalWriter::open_with_sync(&data_dir, config.clone()).unwrap();
+    let writer = Arc::new(GroupCommitWriter::new(writer, config));
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let writer = Arc::clone(&writer);
+            std::thread::spawn(move || {
+                let payload = WalPayload::new("c", format!("acked{}", i), "s", "v1", vec![]);
+                writer.append_insert(payload).unwrap()
+            })
+        })
+        .collect();
+    let mut acked: Vec<u64> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    acked.sort_unstable();
+    assert_eq!(acked, vec![1, 2, 3, 4]);
+    assert_eq!(writer.stats().batches_synced, 1);
+    drop(writer);
+
+    // Second batch in a child process that aborts before its fsync
+    let exe = std::env::current_exe().unwrap();
+    let result = execute_with_crash_point(
+        points::WAL_BEFORE_FSYNC,
+        exe.to_str().unwrap(),
+        &[
+            "--exact",
+            "crash::scenarios::wal::group_commit_crash_child",
+            "--nocapture",
+        ],
+        &data_dir,
+    );
+    assert!(result.crashed, "child must abort at the crash point");
+    assert!(result.stderr.contains("[CRASH]"), "{}", result.stderr);
+    assert!(
+        !result.stdout.contains("ACK "),
+        "no write may be acknowledged before the batch fsync: {}",
+        result.stdout
+    );
+
+    // Every acknowledged record survives; anything beyond it can only be
+    // part of the single unacknowledged batch.
+    let mut reader = WalReader::open(&data_dir.join("wal").join("wal.log")).unwrap();
+    let mut recovered = Vec::new();
+    while let Some(record) = reader.read_next().unwrap() {
+        recovered.push((record.sequence_number, record.payload.document_id));
+    }
+    assert!(recovered.len() >= 4 && recovered.len() <= 8);
+    for (i, (seq, _)) in recovered.iter().enumerate() {
+        assert_eq!(*seq, i as u64 + 1);
+    }
+    let acked_ids: Vec<_> = recovered[..4].iter().map(|(_, id)| id.as_str()).collect();
+    assert!(acked_ids.iter().all(|id| id.starts_with("acked")));
+    assert!(recovered[4..]
+        .iter()
+        .all(|(_, id)| id.starts_with("unacked")));
+
+    cleanup_temp_data_dir(&data_dir);
+}
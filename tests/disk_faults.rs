@@ -0,0 +1,168 @@
+//! Disk Fault Tests
+//!
+//! Tests for invariants:
+//! - D1: A write is acknowledged only once it is durable
+//! - D2: A failed write never corrupts what is already stored
+//! - Recovery after the disk is freed
+//!
+//! Writes go through `FaultyFs`, which fails them with `ENOSPC` or `EIO`
+//! on demand. The write path is the API's: WAL append, then storage write.
+
+use aerodb::recovery::{RecoveryStorage, WalReplayer};
+use aerodb::storage::{StoragePayload, StorageReader, StorageWriter};
+use aerodb::storage_io::{Fault, FaultyFs};
+use aerodb::wal::{WalPayload, WalReader, WalWriter};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+// =============================================================================
+// Test Utilities
+// =============================================================================
+
+fn create_test_payload(doc_id: &str) -> WalPayload {
+    WalPayload::new(
+        "test_collection",
+        doc_id,
+        "test_schema",
+        "v1",
+        format!(r#"{{"id": "{}"}}"#, doc_id).into_bytes(),
+    )
+}
+
+fn create_storage_payload(doc_id: &str) -> StoragePayload {
+    StoragePayload::new(
+        "test_collection",
+        doc_id,
+        "test_schema",
+        "v1",
+        format!(r#"{{"id": "{}"}}"#, doc_id).into_bytes(),
+    )
+}
+
+/// WAL and storage writers sharing one faulty filesystem
+fn open_faulty(data_dir: &Path, fs: &FaultyFs) -> (WalWriter, StorageWriter) {
+    let wal = WalWriter::open(data_dir)
+        .unwrap()
+        .with_io(Arc::new(fs.clone()))
+        .unwrap();
+    let storage = StorageWriter::open(data_dir)
+        .unwrap()
+        .with_io(Arc::new(fs.clone()))
+        .unwrap();
+    (wal, storage)
+}
+
+/// The write path: acknowledged only if both steps succeed
+fn write(wal: &mut WalWriter, storage: &mut StorageWriter, doc_id: &str) -> Result<(), String> {
+    wal.append_insert(create_test_payload(doc_id))
+        .map_err(|e| e.to_string())?;
+    storage
+        .write(&create_storage_payload(doc_id))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn stored_ids(data_dir: &Path) -> Vec<String> {
+    let mut ids: Vec<String> = StorageReader::open_from_data_dir(data_dir)
+        .unwrap()
+        .build_document_map()
+        .unwrap()
+        .into_keys()
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn wal_ids(data_dir: &Path) -> Vec<String> {
+    WalReader::open_from_data_dir(data_dir)
+        .unwrap()
+        .read_all()
+        .unwrap()
+        .into_iter()
+        .map(|record| record.payload.document_id)
+        .collect()
+}
+
+// =============================================================================
+// Disk full during the WAL append
+// =============================================================================
+
+/// D1/D2: A WAL append cut short by a full disk is refused and leaves no
+/// trace; writes resume once space is freed.
+#[test]
+fn test_disk_full_during_wal_append_refuses_write() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path();
+    let fs = FaultyFs::new();
+    let (mut wal, mut storage) = open_faulty(data_dir, &fs);
+    write(&mut wal, &mut storage, "doc1").unwrap();
+
+    fs.limit_bytes(16);
+    let err = write(&mut wal, &mut storage, "doc2").unwrap_err();
+    assert!(err.contains("AERO_WAL_APPEND_FAILED"), "{}", err);
+    assert!(write(&mut wal, &mut storage, "doc3").is_err());
+
+    // Nothing but the acknowledged write is on disk, and it is readable
+    assert_eq!(wal_ids(data_dir), vec!["doc1"]);
+    assert_eq!(stored_ids(data_dir), vec!["test_collection:doc1"]);
+
+    fs.heal();
+    write(&mut wal, &mut storage, "doc2").unwrap();
+    assert_eq!(wal_ids(data_dir), vec!["doc1", "doc2"]);
+    assert_eq!(wal.next_sequence_number(), 3);
+}
+
+/// An I/O error on one write fails that write only.
+#[test]
+fn test_io_error_fails_one_write() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path();
+    let fs = FaultyFs::new();
+    let (mut wal, mut storage) = open_faulty(data_dir, &fs);
+
+    fs.fail_nth_write(1, Fault::Io);
+    assert!(write(&mut wal, &mut storage, "doc1").is_err());
+    write(&mut wal, &mut storage, "doc1").unwrap();
+    write(&mut wal, &mut storage, "doc2").unwrap();
+
+    assert_eq!(wal_ids(data_dir), vec!["doc1", "doc2"]);
+    assert_eq!(
+        stored_ids(data_dir),
+        vec!["test_collection:doc1", "test_collection:doc2"]
+    );
+}
+
+// =============================================================================
+// Disk full during the storage write
+// =============================================================================
+
+/// D2 + recovery: storage filling up after the WAL append leaves storage
+/// clean, and replay applies the durable WAL record.
+#[test]
+fn test_disk_full_during_storage_write_is_recovered_from_wal() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path();
+    let fs = FaultyFs::new();
+    let (mut wal, mut storage) = open_faulty(data_dir, &fs);
+    write(&mut wal, &mut storage, "doc1").unwrap();
+
+    // The WAL append is the first of the next two writes
+    fs.fail_nth_write(2, Fault::DiskFull);
+    let err = write(&mut wal, &mut storage, "doc2").unwrap_err();
+    assert!(err.contains("AERO_STORAGE_WRITE_FAILED"), "{}", err);
+    assert_eq!(stored_ids(data_dir), vec!["test_collection:doc1"]);
+    drop((wal, storage));
+
+    // Restart: the WAL record is durable, so replay applies it
+    let mut reader = WalReader::open_from_data_dir(data_dir).unwrap();
+    let mut recovery = RecoveryStorage::open(data_dir).unwrap();
+    let stats = WalReplayer::replay(&mut reader, &mut recovery).unwrap();
+    assert_eq!(stats.records_replayed, 2);
+    drop(recovery);
+
+    assert_eq!(
+        stored_ids(data_dir),
+        vec!["test_collection:doc1", "test_collection:doc2"]
+    );
+}
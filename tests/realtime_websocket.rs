@@ -0,0 +1,362 @@
+//! Realtime WebSocket Endpoint Tests
+//!
+//! Exercises `/realtime/v1` over a real socket:
+//! - Connections must present a valid JWT
+//! - Subscribed clients receive matching change events in sequence
+//! - Clients that stop answering pings are dropped
+//! - Reconnecting clients resume from their last sequence
+//! - Backpressure losses are surfaced as `messages_dropped`
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aerodb::auth::jwt::{JwtClaims, JwtConfig};
+use aerodb::auth::user::SignupRequest;
+use aerodb::http_server::auth_routes::AuthState;
+use aerodb::http_server::realtime_routes::{realtime_routes, RealtimeState};
+use aerodb::http_server::RealtimeConfig;
+use aerodb::realtime::{BackpressureConfig, DatabaseEvent, DropPolicy};
+use axum::Router;
+use futures_util::{SinkExt, StreamExt};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+// =============================================================================
+// Test Utilities
+// =============================================================================
+
+async fn start_server(config: RealtimeConfig) -> (SocketAddr, Arc<RealtimeState>) {
+    start_with_state(RealtimeState::with_config(config)).await
+}
+
+async fn start_with_state(state: RealtimeState) -> (SocketAddr, Arc<RealtimeState>) {
+    let state = Arc::new(state);
+    let app = Router::new().nest("/realtime", realtime_routes(Arc::clone(&state)));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (addr, state)
+}
+
+fn token_for(user_id: Uuid) -> String {
+    let config = JwtConfig::default();
+    let now = chrono::Utc::now().timestamp();
+    let claims = JwtClaims {
+        sub: user_id.to_string(),
+        email: "user@example.com".to_string(),
+        iat: now,
+        exp: now + 900,
+        aud: config.audience.clone(),
+        iss: config.issuer.clone(),
+        email_verified: true,
+        tenant_id: None,
+        role: None,
+        sid: None,
+        jti: None,
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(config.secret.as_bytes()),
+    )
+    .unwrap()
+}
+
+/// Connect and consume the `connected` frame
+async fn connect(addr: SocketAddr, user_id: Uuid) -> Client {
+    let url = format!("ws://{}/realtime/v1?token={}", addr, token_for(user_id));
+    let (mut client, _) = connect_async(url).await.unwrap();
+    assert_eq!(next_frame(&mut client).await["type"], "connected");
+    client
+}
+
+async fn send(client: &mut Client, frame: Value) {
+    client.send(Message::Text(frame.to_string())).await.unwrap();
+}
+
+/// Next JSON frame, skipping control frames
+async fn next_frame(client: &mut Client) -> Value {
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .expect("timed out waiting for a frame")
+            .expect("connection closed")
+            .unwrap();
+        if let Message::Text(text) = message {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+async fn subscribe(client: &mut Client, frame: Value) -> String {
+    send(client, frame).await;
+    let reply = next_frame(client).await;
+    assert_eq!(reply["type"], "subscribed", "{}", reply);
+    reply["subscription_id"].as_str().unwrap().to_string()
+}
+
+fn message(record_id: &str, room: &str) -> DatabaseEvent {
+    DatabaseEvent::insert(
+        1,
+        "messages".to_string(),
+        record_id.to_string(),
+        json!({"room": room}),
+        None,
+    )
+}
+
+async fn wait_for_connections(state: &RealtimeState, expected: usize) {
+    for _ in 0..200 {
+        if state.hub.connection_count() == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("expected {} connections", expected);
+}
+
+// =============================================================================
+// Authentication
+// =============================================================================
+
+#[tokio::test]
+async fn test_connection_requires_valid_token() {
+    let (addr, _state) = start_server(RealtimeConfig::default()).await;
+
+    assert!(connect_async(format!("ws://{}/realtime/v1", addr))
+        .await
+        .is_err());
+    assert!(
+        connect_async(format!("ws://{}/realtime/v1?token=garbage", addr))
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_revoked_token_is_rejected() {
+    let auth = AuthState::new().service;
+    let state = RealtimeState::new().with_jwt_manager(auth.jwt_manager().clone());
+    let (addr, _state) = start_with_state(state).await;
+    let (_, tokens) = auth
+        .signup(SignupRequest {
+            email: "revoked@example.com".to_string(),
+            password: "password123".to_string(),
+            metadata: None,
+        })
+        .unwrap();
+    let url = format!("ws://{}/realtime/v1?token={}", addr, tokens.access_token);
+
+    let (mut client, _) = connect_async(url.as_str()).await.unwrap();
+    assert_eq!(next_frame(&mut client).await["type"], "connected");
+
+    auth.logout(&tokens.refresh_token).unwrap();
+    match connect_async(url.as_str()).await {
+        Err(WsError::Http(response)) => assert_eq!(response.status(), 401),
+        other => panic!("revoked token accepted: {:?}", other.map(|_| ())),
+    }
+}
+
+// =============================================================================
+// Subscribe and Event Delivery
+// =============================================================================
+
+#[tokio::test]
+async fn test_subscribe_delivers_matching_events_in_sequence() {
+    let (addr, state) = start_server(RealtimeConfig::default()).await;
+    let mut client = connect(addr, Uuid::new_v4()).await;
+
+    let id = subscribe(
+        &mut client,
+        json!({"type": "subscribe", "collection": "messages", "filter": {"room": {"eq": "a"}}}),
+    )
+    .await;
+
+    state.hub.publish(&message("m1", "a"));
+    state.hub.publish(&message("m2", "b"));
+    state.hub.publish(&message("m3", "a"));
+
+    for (seq, record_id) in [(1, "m1"), (2, "m3")] {
+        let frame = next_frame(&mut client).await;
+        assert_eq!(frame["type"], "event");
+        assert_eq!(frame["subscription_id"], id.as_str());
+        assert_eq!(frame["seq"], seq);
+        assert_eq!(frame["event"]["record_id"], record_id);
+    }
+
+    send(
+        &mut client,
+        json!({"type": "unsubscribe", "subscription_id": id}),
+    )
+    .await;
+    assert_eq!(next_frame(&mut client).await["type"], "unsubscribed");
+    assert_eq!(state.hub.subscription_count(), 0);
+
+    send(
+        &mut client,
+        json!({"type": "subscribe", "collection": "messages", "filter": {"room": {"like": "a%"}}}),
+    )
+    .await;
+    let reply = next_frame(&mut client).await;
+    assert_eq!(reply["type"], "error");
+    assert_eq!(reply["code"], 4005);
+}
+
+// =============================================================================
+// Heartbeat
+// =============================================================================
+
+#[tokio::test]
+async fn test_heartbeat_drops_client_that_misses_pongs() {
+    let config = RealtimeConfig {
+        heartbeat_interval_ms: 50,
+        max_missed_pongs: 2,
+        ..RealtimeConfig::default()
+    };
+    let (addr, state) = start_server(config).await;
+    let mut silent = connect(addr, Uuid::new_v4()).await;
+    let mut responsive = connect(addr, Uuid::new_v4()).await;
+
+    // Reading answers pings; the silent client never reads until the end
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(500);
+    while tokio::time::Instant::now() < deadline {
+        let _ = tokio::time::timeout_at(deadline, responsive.next()).await;
+    }
+    wait_for_connections(&state, 1).await;
+
+    subscribe(
+        &mut responsive,
+        json!({"type": "subscribe", "collection": "messages"}),
+    )
+    .await;
+
+    // The silent client sees the close frame, unless answering the queued
+    // pings fails first because the server already hung up
+    loop {
+        match tokio::time::timeout(Duration::from_secs(5), silent.next()).await {
+            Ok(Some(Ok(Message::Close(frame)))) => {
+                assert_eq!(frame.unwrap().code, CloseCode::Away);
+                break;
+            }
+            Ok(Some(Ok(_))) => continue,
+            Ok(Some(Err(_))) | Ok(None) => break,
+            Err(_) => panic!("silent client was not disconnected"),
+        }
+    }
+}
+
+// =============================================================================
+// Resume
+// =============================================================================
+
+#[tokio::test]
+async fn test_reconnect_resumes_from_last_seq() {
+    let config = RealtimeConfig {
+        resume_buffer_size: 4,
+        ..RealtimeConfig::default()
+    };
+    let (addr, state) = start_server(config).await;
+    let user_id = Uuid::new_v4();
+
+    let mut first = connect(addr, user_id).await;
+    let id = subscribe(
+        &mut first,
+        json!({"type": "subscribe", "collection": "messages"}),
+    )
+    .await;
+    state.hub.publish(&message("m1", "a"));
+    assert_eq!(next_frame(&mut first).await["seq"], 1);
+    drop(first);
+    wait_for_connections(&state, 0).await;
+
+    // Missed while disconnected
+    state.hub.publish(&message("m2", "a"));
+    state.hub.publish(&message("m3", "a"));
+
+    let mut second = connect(addr, user_id).await;
+    send(
+        &mut second,
+        json!({"type": "resume", "subscription_id": id, "last_seq": 1}),
+    )
+    .await;
+    let resumed = next_frame(&mut second).await;
+    assert_eq!(resumed["type"], "resumed");
+    assert_eq!(resumed["replayed"], 2);
+    assert_eq!(next_frame(&mut second).await["event"]["record_id"], "m2");
+    assert_eq!(next_frame(&mut second).await["event"]["record_id"], "m3");
+
+    // Then live
+    state.hub.publish(&message("m4", "a"));
+    let live = next_frame(&mut second).await;
+    assert_eq!(live["seq"], 4);
+    assert_eq!(live["event"]["record_id"], "m4");
+
+    // Another user cannot take the subscription over
+    let mut stranger = connect(addr, Uuid::new_v4()).await;
+    send(
+        &mut stranger,
+        json!({"type": "resume", "subscription_id": id, "last_seq": 4}),
+    )
+    .await;
+    assert_eq!(next_frame(&mut stranger).await["code"], 4003);
+
+    // Events older than the ring cannot be replayed
+    drop(second);
+    wait_for_connections(&state, 1).await;
+    for i in 5..=9 {
+        state.hub.publish(&message(&format!("m{}", i), "a"));
+    }
+    let mut third = connect(addr, user_id).await;
+    send(
+        &mut third,
+        json!({"type": "resume", "subscription_id": id, "last_seq": 4}),
+    )
+    .await;
+    let reply = next_frame(&mut third).await;
+    assert_eq!(reply["type"], "error");
+    assert_eq!(reply["code"], 4006);
+}
+
+// =============================================================================
+// Backpressure
+// =============================================================================
+
+#[tokio::test]
+async fn test_backpressure_reports_dropped_messages() {
+    let config = RealtimeConfig {
+        backpressure: BackpressureConfig {
+            max_pending_messages: 2,
+            drop_policy: DropPolicy::OldestFirst,
+        },
+        ..RealtimeConfig::default()
+    };
+    let (addr, state) = start_server(config).await;
+    let mut client = connect(addr, Uuid::new_v4()).await;
+    subscribe(
+        &mut client,
+        json!({"type": "subscribe", "collection": "messages"}),
+    )
+    .await;
+
+    // The test runtime is single-threaded, so the connection cannot drain
+    // its buffer until all ten are published
+    for i in 1..=10 {
+        state.hub.publish(&message(&format!("m{}", i), "a"));
+    }
+
+    let notice = next_frame(&mut client).await;
+    assert_eq!(notice["type"], "messages_dropped");
+    assert_eq!(notice["count"], 8);
+    assert_eq!(next_frame(&mut client).await["seq"], 9);
+    assert_eq!(next_frame(&mut client).await["seq"], 10);
+}
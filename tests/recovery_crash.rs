@@ -8,10 +8,13 @@
 //! Per WAL.md, any corruption detected during recovery halts immediately
 //! with no partial replay and no repair attempts.
 
-use aerodb::recovery::{RecoveryStorage, WalReplayer};
+use aerodb::recovery::{
+    CorruptTailPolicy, RecoveryResult, RecoveryStorage, ReplayStats, WalReplayer,
+};
 use aerodb::storage::StorageReader;
 use aerodb::wal::{WalPayload, WalReader, WalWriter};
 use std::fs;
+use std::path::Path;
 use tempfile::TempDir;
 
 // =============================================================================
@@ -142,6 +145,112 @@ fn test_corruption_prevents_later_records() {
     assert!(result.is_err(), "Corruption must halt, no partial replay");
 }
 
+// =============================================================================
+// Corrupt Tail Policy Tests
+// =============================================================================
+
+/// Write `count` records to a legacy WAL, returning the file length after
+/// each record.
+fn write_records(data_dir: &Path, count: usize) -> Vec<u64> {
+    let wal_path = data_dir.join("wal/wal.log");
+    let mut writer = WalWriter::open(data_dir).unwrap();
+    (1..=count)
+        .map(|i| {
+            writer
+                .append_insert(create_test_payload(&format!("doc{}", i)))
+                .unwrap();
+            fs::metadata(&wal_path).unwrap().len()
+        })
+        .collect()
+}
+
+fn replay_with(data_dir: &Path, policy: CorruptTailPolicy) -> RecoveryResult<ReplayStats> {
+    let mut wal = WalReader::open_from_data_dir(data_dir).unwrap();
+    let mut storage = RecoveryStorage::open(data_dir).unwrap();
+    WalReplayer::replay_with_policy(&mut wal, &mut storage, policy)
+}
+
+/// An intact WAL replays fully and nothing is discarded.
+#[test]
+fn test_truncate_policy_keeps_good_wal() {
+    let temp_dir = create_temp_data_dir();
+    let data_dir = temp_dir.path();
+    let lengths = write_records(data_dir, 3);
+
+    let stats = replay_with(data_dir, CorruptTailPolicy::TruncateAndContinue).unwrap();
+    assert_eq!(stats.records_replayed, 3);
+    assert!(stats.discarded_tail.is_none());
+    assert_eq!(
+        fs::metadata(data_dir.join("wal/wal.log")).unwrap().len(),
+        lengths[2]
+    );
+}
+
+/// A torn final record fails by default and is cut off when truncating.
+#[test]
+fn test_torn_final_record_fails_or_truncates() {
+    let temp_dir = create_temp_data_dir();
+    let data_dir = temp_dir.path();
+    let wal_path = data_dir.join("wal/wal.log");
+    let lengths = write_records(data_dir, 4);
+    let torn_len = lengths[3] - 10;
+    let contents = fs::read(&wal_path).unwrap();
+    fs::write(&wal_path, &contents[..torn_len as usize]).unwrap();
+
+    assert!(replay_with(data_dir, CorruptTailPolicy::Fail).is_err());
+    assert_eq!(fs::metadata(&wal_path).unwrap().len(), torn_len);
+
+    let stats = replay_with(data_dir, CorruptTailPolicy::TruncateAndContinue).unwrap();
+    assert_eq!(stats.records_replayed, 3);
+    assert_eq!(stats.final_sequence, 3);
+    let discarded = stats.discarded_tail.unwrap();
+    assert_eq!(discarded.path, wal_path);
+    assert_eq!(discarded.range, lengths[2]..torn_len);
+    assert_eq!(fs::metadata(&wal_path).unwrap().len(), lengths[2]);
+
+    // The WAL now ends cleanly and appends continue the sequence
+    let mut writer = WalWriter::open(data_dir).unwrap();
+    assert_eq!(
+        writer.append_insert(create_test_payload("doc4")).unwrap(),
+        4
+    );
+}
+
+/// A complete final record that fails its checksum is a corrupt tail too.
+#[test]
+fn test_checksum_failure_in_final_record_truncates() {
+    let temp_dir = create_temp_data_dir();
+    let data_dir = temp_dir.path();
+    let wal_path = data_dir.join("wal/wal.log");
+    let lengths = write_records(data_dir, 3);
+    let mut contents = fs::read(&wal_path).unwrap();
+    let last = contents.len() - 1;
+    contents[last] ^= 0xFF;
+    fs::write(&wal_path, contents).unwrap();
+
+    assert!(replay_with(data_dir, CorruptTailPolicy::Fail).is_err());
+    let stats = replay_with(data_dir, CorruptTailPolicy::TruncateAndContinue).unwrap();
+    assert_eq!(stats.records_replayed, 2);
+    assert_eq!(stats.discarded_tail.unwrap().range, lengths[1]..lengths[2]);
+}
+
+/// Corruption followed by more records fails under either policy and the
+/// WAL is left untouched.
+#[test]
+fn test_mid_stream_corruption_fails_under_truncate_policy() {
+    let temp_dir = create_temp_data_dir();
+    let data_dir = temp_dir.path();
+    let wal_path = data_dir.join("wal/wal.log");
+    let lengths = write_records(data_dir, 4);
+    let mut contents = fs::read(&wal_path).unwrap();
+    contents[lengths[0] as usize + 20] ^= 0xFF;
+    fs::write(&wal_path, &contents).unwrap();
+
+    assert!(replay_with(data_dir, CorruptTailPolicy::Fail).is_err());
+    assert!(replay_with(data_dir, CorruptTailPolicy::TruncateAndContinue).is_err());
+    assert_eq!(fs::read(&wal_path).unwrap(), contents);
+}
+
 // =============================================================================
 // Recovery Error Handling Tests
 // =============================================================================
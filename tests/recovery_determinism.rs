@@ -10,7 +10,10 @@
 //! - Validate checksums
 //! - Produce identical state on repeated replay
 
-use aerodb::recovery::{RecoveryManager, RecoveryStorage, WalReplayer};
+use std::collections::HashSet;
+
+use aerodb::index::IndexManager;
+use aerodb::recovery::{RecoveryManager, RecoveryStorage, SchemaCheck, WalReplayer};
 use aerodb::wal::{WalPayload, WalReader, WalWriter};
 use tempfile::TempDir;
 
@@ -141,6 +144,86 @@ fn test_r2_replay_produces_identical_storage() {
     );
 }
 
+/// Any schema is known, so verification passes on the test payloads
+struct AnySchema;
+
+impl SchemaCheck for AnySchema {
+    fn schema_exists(&self, _schema_id: &str) -> bool {
+        true
+    }
+
+    fn schema_version_exists(&self, _schema_id: &str, _version: &str) -> bool {
+        true
+    }
+}
+
+/// Recover a copy of `wal_dir`'s WAL with `parallelism` replay threads and
+/// return the resulting storage file
+fn recover_copy(wal_dir: &std::path::Path, parallelism: usize) -> Vec<u8> {
+    let temp_dir = create_temp_data_dir();
+    let data_dir = temp_dir.path();
+    std::fs::create_dir_all(data_dir.join("wal")).unwrap();
+    std::fs::copy(wal_dir.join("wal/wal.log"), data_dir.join("wal/wal.log")).unwrap();
+
+    let mut wal = WalReader::open_from_data_dir(data_dir).unwrap();
+    let mut storage = RecoveryStorage::open(data_dir).unwrap();
+    let mut index = IndexManager::new(HashSet::from(["id".to_string()]));
+    let state = RecoveryManager::new(data_dir)
+        .with_replay_parallelism(parallelism)
+        .recover(&mut wal, &mut storage, &mut index, &AnySchema)
+        .unwrap();
+    assert_eq!(state.replay_stats.records_replayed, 200);
+    drop(storage);
+
+    std::fs::read(data_dir.join("data/documents.dat")).unwrap()
+}
+
+/// R2: Parallel startup recovery produces the storage sequential recovery
+/// does, byte for byte.
+#[test]
+fn test_r2_parallel_and_serial_recovery_identical_storage() {
+    let temp_dir = create_temp_data_dir();
+    let data_dir = temp_dir.path();
+
+    // Interleave inserts, updates and deletes across several collections
+    {
+        let mut writer = WalWriter::open(data_dir).unwrap();
+        for i in 0..50 {
+            for collection in ["users", "orders", "events", "tags"] {
+                let doc_id = format!("doc{}", i % 20);
+                let payload = WalPayload::new(
+                    collection,
+                    &doc_id,
+                    "test_schema",
+                    "v1",
+                    format!(r#"{{"id": "{}", "round": {}}}"#, doc_id, i).into_bytes(),
+                );
+                match i % 5 {
+                    4 => writer
+                        .append_delete(WalPayload::tombstone(
+                            collection,
+                            &doc_id,
+                            "test_schema",
+                            "v1",
+                        ))
+                        .unwrap(),
+                    _ if i >= 20 => writer.append_update(payload).unwrap(),
+                    _ => writer.append_insert(payload).unwrap(),
+                };
+            }
+        }
+    }
+
+    let serial = recover_copy(data_dir, 1);
+    let parallel = recover_copy(data_dir, 4);
+
+    assert!(!serial.is_empty());
+    assert_eq!(
+        serial, parallel,
+        "R2 VIOLATION: Parallel recovery laid storage out differently"
+    );
+}
+
 // =============================================================================
 // INVARIANT R3: Recovery Completeness Is Verifiable
 // =============================================================================
@@ -0,0 +1,367 @@
+//! Replica Bootstrap Tests
+//!
+//! A Primary taking writes and a Replica with an empty data directory in
+//! one process, over 127.0.0.1.
+//!
+//! - The Replica is seeded from a snapshot of the Primary, then streams
+//!   the WAL after the snapshot and converges with the Primary
+//! - The Primary keeps that WAL until the Replica starts streaming
+//! - A failed bootstrap leaves the directory marked incomplete, and the
+//!   retry starts clean
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use aerodb::observability::VecLogger;
+use aerodb::replication::{
+    bootstrap_marker_path, BaseSnapshotSource, ReplicaBootstrap, ReplicaTracker, ReplicationError,
+    ReplicationErrorKind, ReplicationResult, ReplicationStreamConfig, WalFollower, WalStreamer,
+};
+use aerodb::snapshot::{GlobalExecutionLock, SnapshotConfig, SnapshotId, SnapshotManager};
+use aerodb::storage::{StoragePayload, StorageReader, StorageWriter};
+use aerodb::wal::{WalPayload, WalReader, WalSegmentConfig, WalSyncConfig, WalWriter};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+const SECRET: &str = "0123456789abcdef";
+
+// =============================================================================
+// Helpers
+// =============================================================================
+
+fn stream_config() -> ReplicationStreamConfig {
+    let mut config = ReplicationStreamConfig::new("127.0.0.1:0", SECRET);
+    config.keepalive_interval = Duration::from_millis(50);
+    config.poll_interval = Duration::from_millis(5);
+    config.reconnect_interval = Duration::from_millis(20);
+    config
+}
+
+/// Streaming requires a segmented WAL.
+fn open_wal(data_dir: &Path) -> WalWriter {
+    WalWriter::open_segmented(
+        data_dir,
+        WalSyncConfig::default(),
+        WalSegmentConfig::new(4096),
+    )
+    .unwrap()
+}
+
+fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// The Primary's WAL and storage, written together as the API would.
+struct Database {
+    wal: WalWriter,
+    storage: StorageWriter,
+}
+
+impl Database {
+    fn insert(&mut self) {
+        let id = Uuid::new_v4().to_string();
+        let body = format!("{{\"name\":\"{}\"}}", id).into_bytes();
+        let payload = WalPayload::new("users", &id, "user", "v1", body.clone());
+        self.wal.append_insert(payload).unwrap();
+        let payload = StoragePayload::new("users", &id, "user", "v1", body);
+        self.storage.write(&payload).unwrap();
+    }
+}
+
+/// Snapshots taken on demand under the database lock, failing the first
+/// `failures` requests.
+struct OnDemand {
+    data_dir: PathBuf,
+    database: Arc<Mutex<Database>>,
+    failures: AtomicUsize,
+}
+
+impl fmt::Debug for OnDemand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnDemand")
+            .field("data_dir", &self.data_dir)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BaseSnapshotSource for OnDemand {
+    fn base_snapshot(&self) -> ReplicationResult<SnapshotId> {
+        if self.failures.load(Ordering::Acquire) > 0 {
+            self.failures.fetch_sub(1, Ordering::AcqRel);
+            return Err(ReplicationError::bootstrap_failed("No snapshot yet"));
+        }
+        // Snapshot ids have one-second granularity
+        thread::sleep(Duration::from_millis(1100));
+        let pending = {
+            let database = self.database.lock().unwrap();
+            SnapshotManager::begin_snapshot(
+                &self.data_dir,
+                database.storage.path(),
+                &self.data_dir.join("metadata").join("schemas"),
+                &database.wal,
+                &GlobalExecutionLock::new(),
+            )
+            .unwrap()
+        };
+        Ok(pending.complete(&SnapshotConfig::default()).unwrap())
+    }
+}
+
+struct Primary {
+    data_dir: TempDir,
+    database: Arc<Mutex<Database>>,
+    address: String,
+    tracker: Arc<ReplicaTracker>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Primary {
+    /// A Primary holding `documents` documents, whose snapshot source fails
+    /// `failures` times.
+    fn start(documents: usize, failures: usize) -> Self {
+        let data_dir = TempDir::new().unwrap();
+        let mut database = Database {
+            wal: open_wal(data_dir.path()),
+            storage: StorageWriter::open(data_dir.path()).unwrap(),
+        };
+        for _ in 0..documents {
+            database.insert();
+        }
+        let database = Arc::new(Mutex::new(database));
+
+        let source = OnDemand {
+            data_dir: data_dir.path().to_path_buf(),
+            database: Arc::clone(&database),
+            failures: AtomicUsize::new(failures),
+        };
+        let streamer = WalStreamer::bind(data_dir.path(), stream_config())
+            .unwrap()
+            .with_snapshot_source(Arc::new(source));
+        let address = streamer.local_addr().unwrap().to_string();
+        let tracker = streamer.tracker();
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let handle = thread::spawn(move || streamer.run(&flag).unwrap());
+        Self {
+            data_dir,
+            database,
+            address,
+            tracker,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    fn last_sequence(&self) -> u64 {
+        self.database.lock().unwrap().wal.last_sequence_number()
+    }
+
+    /// Insert documents on a thread until the returned flag is set.
+    fn spawn_writer(&self) -> (Arc<AtomicBool>, JoinHandle<()>) {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let database = Arc::clone(&self.database);
+        let handle = thread::spawn(move || {
+            while !flag.load(Ordering::Acquire) {
+                database.lock().unwrap().insert();
+                thread::sleep(Duration::from_millis(2));
+            }
+        });
+        (stop, handle)
+    }
+}
+
+impl Drop for Primary {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn bootstrap(data_dir: &Path, replica_id: Uuid, primary: &Primary) -> ReplicaBootstrap {
+    ReplicaBootstrap::new(
+        data_dir,
+        replica_id,
+        primary.address.clone(),
+        stream_config(),
+    )
+}
+
+/// Follow `primary` on a thread until the returned flag is set.
+fn spawn_follower(
+    data_dir: &Path,
+    replica_id: Uuid,
+    primary: &Primary,
+) -> (Arc<AtomicBool>, JoinHandle<u64>) {
+    let mut follower = WalFollower::open(
+        data_dir,
+        replica_id,
+        primary.address.clone(),
+        stream_config(),
+        open_wal(data_dir),
+        StorageWriter::open(data_dir).unwrap(),
+    )
+    .unwrap();
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&stop);
+    let handle = thread::spawn(move || {
+        follower.run(&flag).unwrap();
+        follower.applied_sequence()
+    });
+    (stop, handle)
+}
+
+/// Live documents by id.
+fn documents(data_dir: &Path) -> BTreeMap<String, Vec<u8>> {
+    let mut reader = StorageReader::open_from_data_dir(data_dir).unwrap();
+    reader
+        .read_all()
+        .unwrap()
+        .into_iter()
+        .filter(|record| !record.is_tombstone)
+        .map(|record| (record.document_id, record.document_body))
+        .collect()
+}
+
+fn wal_sequences(data_dir: &Path) -> Vec<u64> {
+    let mut reader = WalReader::open_segments(&data_dir.join("wal")).unwrap();
+    let mut sequences = Vec::new();
+    while let Some(record) = reader.read_next().unwrap() {
+        sequences.push(record.sequence_number);
+    }
+    sequences
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[test]
+fn test_bootstrap_from_live_primary_converges() {
+    let primary = Primary::start(50, 0);
+    let (writing, writer) = primary.spawn_writer();
+
+    let replica_dir = TempDir::new().unwrap();
+    let replica_id = Uuid::new_v4();
+    assert!(ReplicaBootstrap::is_needed(replica_dir.path()).unwrap());
+    let logger = Arc::new(VecLogger::new());
+    let report = bootstrap(replica_dir.path(), replica_id, &primary)
+        .with_logger(logger.clone())
+        .run()
+        .unwrap();
+    assert!(report.wal_sequence >= 50);
+    assert!(report.bytes > 0);
+    assert!(!bootstrap_marker_path(replica_dir.path()).exists());
+    assert!(!ReplicaBootstrap::is_needed(replica_dir.path()).unwrap());
+    let events = logger.events();
+    assert_eq!(events.first().unwrap(), "REPLICA_BOOTSTRAP_BEGIN");
+    assert_eq!(events.last().unwrap(), "REPLICA_BOOTSTRAP_COMPLETE");
+
+    // The WAL after the snapshot stays on the Primary for the Replica
+    let pinned = primary.tracker.replicas();
+    assert_eq!(pinned.len(), 1);
+    assert_eq!(pinned[0].replica_id, replica_id);
+    assert_eq!(pinned[0].acked_sequence, report.wal_sequence);
+    assert!(pinned[0].retains_wal());
+
+    // Streaming resumes after the snapshot while writes continue
+    let (following, follower) = spawn_follower(replica_dir.path(), replica_id, &primary);
+    thread::sleep(Duration::from_millis(200));
+    writing.store(true, Ordering::Release);
+    writer.join().unwrap();
+    let last = primary.last_sequence();
+    assert!(last > report.wal_sequence);
+    wait_until("the replica to catch up", || {
+        primary
+            .tracker
+            .replicas()
+            .first()
+            .is_some_and(|progress| progress.acked_sequence == last)
+    });
+    following.store(true, Ordering::Release);
+    assert_eq!(follower.join().unwrap(), last);
+
+    let progress = primary.tracker.replicas();
+    assert_eq!(progress.len(), 1);
+    assert!(progress[0].pinned_until_ms.is_none());
+
+    let replica_wal = wal_sequences(replica_dir.path());
+    let expected: Vec<u64> = (report.wal_sequence + 1..=last).collect();
+    assert_eq!(replica_wal, expected);
+    assert_eq!(
+        documents(replica_dir.path()),
+        documents(primary.data_dir.path())
+    );
+    assert_eq!(documents(replica_dir.path()).len() as u64, last);
+}
+
+#[test]
+fn test_failed_bootstrap_stays_marked_and_retry_starts_clean() {
+    let primary = Primary::start(20, 1);
+    let replica_dir = TempDir::new().unwrap();
+    let replica_id = Uuid::new_v4();
+
+    let err = bootstrap(replica_dir.path(), replica_id, &primary)
+        .run()
+        .unwrap_err();
+    assert_eq!(err.kind, ReplicationErrorKind::BootstrapFailed);
+    assert!(err.message.contains("No snapshot yet"));
+    assert!(bootstrap_marker_path(replica_dir.path()).exists());
+    assert!(primary.tracker.replicas().is_empty());
+
+    // Whatever an interrupted attempt left is discarded by the next one
+    let data = replica_dir.path().join("data");
+    fs::create_dir_all(&data).unwrap();
+    fs::write(data.join("documents.dat"), b"partial").unwrap();
+    assert!(ReplicaBootstrap::is_needed(replica_dir.path()).unwrap());
+
+    let report = bootstrap(replica_dir.path(), replica_id, &primary)
+        .run()
+        .unwrap();
+    assert_eq!(report.wal_sequence, 20);
+    assert!(!bootstrap_marker_path(replica_dir.path()).exists());
+    assert_eq!(
+        documents(replica_dir.path()),
+        documents(primary.data_dir.path())
+    );
+
+    // A directory holding data is never overwritten
+    let err = bootstrap(replica_dir.path(), replica_id, &primary)
+        .run()
+        .unwrap_err();
+    assert_eq!(err.kind, ReplicationErrorKind::BootstrapFailed);
+    assert_eq!(documents(replica_dir.path()).len(), 20);
+}
+
+#[test]
+fn test_bootstrap_refused_with_wrong_secret() {
+    let primary = Primary::start(5, 0);
+    let replica_dir = TempDir::new().unwrap();
+    let mut config = stream_config();
+    config.shared_secret = "fedcba9876543210".to_string();
+
+    let err = ReplicaBootstrap::new(
+        replica_dir.path(),
+        Uuid::new_v4(),
+        primary.address.clone(),
+        config,
+    )
+    .run()
+    .unwrap_err();
+    assert_eq!(err.kind, ReplicationErrorKind::Unauthenticated);
+    assert!(bootstrap_marker_path(replica_dir.path()).exists());
+    assert!(primary.tracker.replicas().is_empty());
+}
@@ -0,0 +1,398 @@
+//! Replication Streaming Tests
+//!
+//! A Primary and a Replica in one process, streaming over 127.0.0.1.
+//!
+//! Per REPLICATION_LOG_FLOW.md:
+//! - The Replica receives the Primary's records in order, with the
+//!   Primary's sequence numbers, and never applies one twice
+//! - A Replica resumes after its stored applied offset
+//! - A Replica whose offset is no longer on the Primary must re-seed
+//! - A following Replica serves reads within a staleness bound, never writes
+//! - After a promotion, records from the superseded Primary are rejected
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use aerodb::checkpoint::truncate_wal_before;
+use aerodb::observability::VecLogger;
+use aerodb::promotion::{AuthorityTransitionManager, CatchUpReference, PromotionPreconditions};
+use aerodb::replication::{
+    replica_lag, replication_offset_path, AppliedOffset, ReplicaGate, ReplicaTracker,
+    ReplicationErrorKind, ReplicationState, ReplicationStreamConfig, WalFollower, WalPosition,
+    WalStreamer,
+};
+use aerodb::storage::{StorageReader, StorageWriter};
+use aerodb::wal::{WalPayload, WalReader, WalSegmentConfig, WalSyncConfig, WalWriter};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+const SECRET: &str = "0123456789abcdef";
+
+// =============================================================================
+// Helpers
+// =============================================================================
+
+fn stream_config(secret: &str) -> ReplicationStreamConfig {
+    let mut config = ReplicationStreamConfig::new("127.0.0.1:0", secret);
+    config.keepalive_interval = Duration::from_millis(50);
+    config.poll_interval = Duration::from_millis(5);
+    config.reconnect_interval = Duration::from_millis(20);
+    config
+}
+
+/// A segmented WAL rolling after every record.
+fn open_wal(data_dir: &Path) -> WalWriter {
+    WalWriter::open_segmented(data_dir, WalSyncConfig::default(), WalSegmentConfig::new(1)).unwrap()
+}
+
+fn insert(wal: &mut WalWriter, count: usize) {
+    for _ in 0..count {
+        let id = Uuid::new_v4().to_string();
+        let payload = WalPayload::new("users", id, "user", "v1", b"{\"name\":\"a\"}".to_vec());
+        wal.append_insert(payload).unwrap();
+    }
+}
+
+fn wal_sequences(data_dir: &Path) -> Vec<u64> {
+    let mut reader = WalReader::open_segments(&data_dir.join("wal")).unwrap();
+    let mut sequences = Vec::new();
+    while let Some(record) = reader.read_next().unwrap() {
+        sequences.push(record.sequence_number);
+    }
+    sequences
+}
+
+fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+struct Primary {
+    address: String,
+    tracker: Arc<ReplicaTracker>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Primary {
+    fn start(data_dir: &Path) -> Self {
+        Self::start_with_logger(data_dir, Arc::new(VecLogger::new()))
+    }
+
+    fn start_with_logger(data_dir: &Path, logger: Arc<VecLogger>) -> Self {
+        let streamer = WalStreamer::bind(data_dir, stream_config(SECRET))
+            .unwrap()
+            .with_logger(logger);
+        let address = streamer.local_addr().unwrap().to_string();
+        let tracker = streamer.tracker();
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let handle = thread::spawn(move || streamer.run(&flag).unwrap());
+        Self {
+            address,
+            tracker,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    fn acked(&self) -> u64 {
+        self.tracker
+            .replicas()
+            .first()
+            .map_or(0, |progress| progress.acked_sequence)
+    }
+}
+
+impl Drop for Primary {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn open_follower(
+    data_dir: &Path,
+    replica_id: Uuid,
+    primary: &str,
+    secret: &str,
+) -> WalFollower<StorageWriter> {
+    WalFollower::open(
+        data_dir,
+        replica_id,
+        primary,
+        stream_config(secret),
+        open_wal(data_dir),
+        StorageWriter::open(data_dir).unwrap(),
+    )
+    .unwrap()
+}
+
+/// Run a follower on its own thread until the returned flag is set.
+fn spawn_follower(mut follower: WalFollower<StorageWriter>) -> (Arc<AtomicBool>, JoinHandle<u64>) {
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&stop);
+    let handle = thread::spawn(move || {
+        follower.run(&flag).unwrap();
+        follower.applied_sequence()
+    });
+    (stop, handle)
+}
+
+// =============================================================================
+// Streaming Tests
+// =============================================================================
+
+/// A Replica catches up, follows live writes and resumes after restart
+/// without applying any record twice.
+#[test]
+fn test_replica_catches_up_follows_and_resumes() {
+    let primary_dir = TempDir::new().unwrap();
+    let replica_dir = TempDir::new().unwrap();
+    let mut wal = open_wal(primary_dir.path());
+    insert(&mut wal, 3);
+    let primary = Primary::start(primary_dir.path());
+    let replica_id = Uuid::new_v4();
+
+    let follower = open_follower(replica_dir.path(), replica_id, &primary.address, SECRET);
+    let (stop, handle) = spawn_follower(follower);
+    wait_until("catch-up", || primary.acked() == 3);
+    insert(&mut wal, 2);
+    wait_until("live records", || primary.acked() == 5);
+
+    let lag = replica_lag(&primary_dir.path().join("wal"), &primary.tracker.replicas()).unwrap();
+    assert_eq!(lag.len(), 1);
+    assert_eq!(lag[0].replica_id, replica_id);
+    assert!(lag[0].connected);
+    assert_eq!((lag[0].lag_records, lag[0].lag_bytes), (0, 0));
+
+    stop.store(true, Ordering::Release);
+    assert_eq!(handle.join().unwrap(), 5);
+    let stored = fs::read_to_string(replication_offset_path(replica_dir.path())).unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&stored).unwrap()["applied_sequence"],
+        5
+    );
+    wait_until("disconnect", || !primary.tracker.replicas()[0].connected);
+
+    // Written while the Replica is away
+    insert(&mut wal, 2);
+    let lag = replica_lag(&primary_dir.path().join("wal"), &primary.tracker.replicas()).unwrap();
+    assert_eq!(lag[0].lag_records, 2);
+    assert!(lag[0].lag_bytes > 0);
+
+    let follower = open_follower(replica_dir.path(), replica_id, &primary.address, SECRET);
+    assert_eq!(follower.applied_sequence(), 5);
+    let (stop, handle) = spawn_follower(follower);
+    wait_until("resume", || primary.acked() == 7);
+    stop.store(true, Ordering::Release);
+    assert_eq!(handle.join().unwrap(), 7);
+
+    assert_eq!(
+        wal_sequences(replica_dir.path()),
+        (1..=7).collect::<Vec<u64>>()
+    );
+    assert_eq!(
+        wal_sequences(replica_dir.path()),
+        wal_sequences(primary_dir.path())
+    );
+    let documents = StorageReader::open_from_data_dir(replica_dir.path())
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(documents.len(), 7);
+}
+
+/// A Replica with the wrong secret is refused before any record is sent.
+#[test]
+fn test_wrong_secret_is_refused() {
+    let primary_dir = TempDir::new().unwrap();
+    let replica_dir = TempDir::new().unwrap();
+    let mut wal = open_wal(primary_dir.path());
+    insert(&mut wal, 2);
+    let primary = Primary::start(primary_dir.path());
+
+    let mut follower = open_follower(
+        replica_dir.path(),
+        Uuid::new_v4(),
+        &primary.address,
+        "fedcba9876543210",
+    );
+    let err = follower.follow_once(&AtomicBool::new(false)).unwrap_err();
+    assert_eq!(err.kind, ReplicationErrorKind::Unauthenticated);
+    assert!(wal_sequences(replica_dir.path()).is_empty());
+    assert!(primary.tracker.replicas().is_empty());
+}
+
+/// A Replica logs each lost connection to the Primary, and the Primary
+/// each stream that ends in an error.
+#[test]
+fn test_stream_failures_are_logged() {
+    let primary_dir = TempDir::new().unwrap();
+    let replica_dir = TempDir::new().unwrap();
+    let mut wal = open_wal(primary_dir.path());
+    insert(&mut wal, 2);
+    let primary_log = Arc::new(VecLogger::new());
+    let primary = Primary::start_with_logger(primary_dir.path(), Arc::clone(&primary_log));
+    let replica_id = Uuid::new_v4();
+    let logged = |log: &VecLogger, event: &str| log.events().iter().any(|e| e == event);
+
+    // A refused Replica ends its stream on the Primary
+    let secret = "fedcba9876543210";
+    let mut refused = open_follower(replica_dir.path(), replica_id, &primary.address, secret);
+    refused.follow_once(&AtomicBool::new(false)).unwrap_err();
+    drop(refused);
+    wait_until("ended stream logged", || {
+        logged(&primary_log, "REPLICATION_STREAM_ENDED")
+    });
+    let record = &primary_log.records()[0];
+    assert!(record.field("replica").unwrap().starts_with("127.0.0.1:"));
+    assert!(record
+        .field("error")
+        .unwrap()
+        .contains("failed authentication"));
+
+    // The Primary going away interrupts the Replica, which keeps retrying
+    let replica_log = Arc::new(VecLogger::new());
+    let follower = open_follower(replica_dir.path(), replica_id, &primary.address, SECRET)
+        .with_logger(replica_log.clone());
+    let (stop, handle) = spawn_follower(follower);
+    wait_until("catch-up", || primary.acked() == 2);
+    drop(primary);
+    wait_until("interruption logged", || {
+        logged(&replica_log, "REPLICATION_STREAM_INTERRUPTED")
+    });
+    stop.store(true, Ordering::Release);
+    assert_eq!(handle.join().unwrap(), 2);
+    let record = &replica_log.records()[0];
+    assert_eq!(record.field("resume_after"), Some("2"));
+}
+
+/// A Replica whose next record was truncated from the Primary must
+/// re-seed from a snapshot.
+#[test]
+fn test_truncated_offset_requires_reseed() {
+    let primary_dir = TempDir::new().unwrap();
+    let replica_dir = TempDir::new().unwrap();
+    let mut wal = open_wal(primary_dir.path());
+    insert(&mut wal, 4);
+    truncate_wal_before(&mut wal, 3).unwrap();
+    let primary = Primary::start(primary_dir.path());
+
+    let mut follower = open_follower(replica_dir.path(), Uuid::new_v4(), &primary.address, SECRET);
+    let err = follower.follow_once(&AtomicBool::new(false)).unwrap_err();
+    assert_eq!(err.kind, ReplicationErrorKind::OffsetUnavailable);
+    assert!(err.message.contains("re-seed"));
+    assert!(wal_sequences(replica_dir.path()).is_empty());
+}
+
+/// Records and keepalives keep a following Replica fresh; once it stops
+/// following, bounded reads are refused.
+#[test]
+fn test_following_replica_stays_fresh() {
+    let primary_dir = TempDir::new().unwrap();
+    let replica_dir = TempDir::new().unwrap();
+    let mut wal = open_wal(primary_dir.path());
+    insert(&mut wal, 2);
+    let primary = Primary::start(primary_dir.path());
+
+    let follower = open_follower(replica_dir.path(), Uuid::new_v4(), &primary.address, SECRET);
+    assert_eq!(follower.freshness().current_as_of_ms(), None);
+    let gate = ReplicaGate::replica(primary.address.clone(), follower.freshness());
+    let (stop, handle) = spawn_follower(follower);
+    wait_until("catch-up", || primary.acked() == 2);
+
+    assert_eq!(
+        gate.check_write().unwrap_err().kind,
+        ReplicationErrorKind::NotPrimary
+    );
+    // Keepalives arrive every 50ms while the Primary is idle
+    thread::sleep(Duration::from_millis(1_200));
+    gate.check_read(Some(1_000)).unwrap();
+
+    stop.store(true, Ordering::Release);
+    handle.join().unwrap();
+    let stored = AppliedOffset::load(replica_dir.path()).unwrap().unwrap();
+    assert_eq!(stored.applied_sequence, 2);
+    assert!(stored.current_as_of_ms > 0);
+
+    thread::sleep(Duration::from_millis(1_200));
+    assert_eq!(
+        gate.check_read(Some(1_000)).unwrap_err().kind,
+        ReplicationErrorKind::ReplicaTooStale
+    );
+    assert!(gate.check_read(None).is_ok());
+}
+
+/// Once a Replica has followed a promoted Primary, the old Primary's
+/// records are rejected even though it keeps writing.
+#[test]
+fn test_zombie_primary_is_fenced_after_promotion() {
+    let zombie_dir = TempDir::new().unwrap();
+    let promoted_dir = TempDir::new().unwrap();
+    let replica_dir = TempDir::new().unwrap();
+    let mut zombie_wal = open_wal(zombie_dir.path());
+    insert(&mut zombie_wal, 2);
+    let zombie = Primary::start(zombie_dir.path());
+
+    // Both Replicas catch up with the original Primary
+    let promoted_id = Uuid::new_v4();
+    for (dir, id) in [
+        (promoted_dir.path(), promoted_id),
+        (replica_dir.path(), Uuid::new_v4()),
+    ] {
+        let (stop, handle) = spawn_follower(open_follower(dir, id, &zombie.address, SECRET));
+        wait_until("catch-up", || wal_sequences(dir).len() == 2);
+        stop.store(true, Ordering::Release);
+        assert_eq!(handle.join().unwrap(), 2);
+    }
+
+    // Promote one Replica; it writes in epoch 1
+    let mut promoted_wal = open_wal(promoted_dir.path());
+    let acknowledged = WalPosition::new(2, 0);
+    let preconditions = PromotionPreconditions::new(
+        promoted_id,
+        acknowledged,
+        CatchUpReference::PrimaryAcknowledged(acknowledged),
+    );
+    let promotion = AuthorityTransitionManager::new(promoted_dir.path())
+        .promote(
+            &ReplicationState::ReplicaActive {
+                replica_id: promoted_id,
+            },
+            &preconditions,
+            None,
+            &mut promoted_wal,
+        )
+        .unwrap();
+    assert_eq!(promotion.epoch, 1);
+    insert(&mut promoted_wal, 1);
+    let new_primary = Primary::start(promoted_dir.path());
+
+    let (stop, handle) = spawn_follower(open_follower(
+        replica_dir.path(),
+        Uuid::new_v4(),
+        &new_primary.address,
+        SECRET,
+    ));
+    wait_until("new primary", || new_primary.acked() == 3);
+    stop.store(true, Ordering::Release);
+    assert_eq!(handle.join().unwrap(), 3);
+
+    // The old Primary keeps writing in epoch 0
+    insert(&mut zombie_wal, 2);
+    let mut follower = open_follower(replica_dir.path(), Uuid::new_v4(), &zombie.address, SECRET);
+    let err = follower.follow_once(&AtomicBool::new(false)).unwrap_err();
+    assert_eq!(err.kind, ReplicationErrorKind::FencedPrimary);
+    assert!(err.message.contains("epoch 0"));
+    assert_eq!(wal_sequences(replica_dir.path()), vec![1, 2, 3]);
+}
@@ -0,0 +1,290 @@
+//! Synchronous Replication Tests
+//!
+//! A Primary and two Replicas in one process, streaming over 127.0.0.1,
+//! with the Primary waiting for a quorum of acknowledgments per write.
+//!
+//! - A write commits once `min_sync_replicas` Replicas have acknowledged
+//!   its exact WAL position
+//! - A Replica down within the quorum does not block writes
+//! - Without a quorum, a write fails with `ReplicationTimeout` (`fail`) or
+//!   the Primary degrades to async and says so (`degrade`)
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use aerodb::observability::VecLogger;
+use aerodb::replication::{
+    ReplicaTracker, ReplicationErrorKind, ReplicationStreamConfig, SyncMode, SyncReplication,
+    SyncReplicationConfig, SyncState, SyncTimeoutPolicy, WalFollower, WalStreamer,
+};
+use aerodb::storage::StorageWriter;
+use aerodb::wal::{WalPayload, WalSegmentConfig, WalSyncConfig, WalWriter};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+const SECRET: &str = "0123456789abcdef";
+
+// =============================================================================
+// Helpers
+// =============================================================================
+
+fn stream_config() -> ReplicationStreamConfig {
+    let mut config = ReplicationStreamConfig::new("127.0.0.1:0", SECRET);
+    config.keepalive_interval = Duration::from_millis(50);
+    config.poll_interval = Duration::from_millis(5);
+    config.reconnect_interval = Duration::from_millis(20);
+    config
+}
+
+/// Streaming requires a segmented WAL.
+fn open_wal(data_dir: &Path) -> WalWriter {
+    WalWriter::open_segmented(
+        data_dir,
+        WalSyncConfig::default(),
+        WalSegmentConfig::new(4096),
+    )
+    .unwrap()
+}
+
+fn quorum(min_sync_replicas: usize, on_timeout: SyncTimeoutPolicy) -> SyncReplicationConfig {
+    SyncReplicationConfig {
+        mode: SyncMode::Quorum,
+        min_sync_replicas,
+        timeout: Duration::from_millis(300),
+        on_timeout,
+    }
+}
+
+fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done() {
+        assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+struct Primary {
+    data_dir: TempDir,
+    wal: WalWriter,
+    address: String,
+    tracker: Arc<ReplicaTracker>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Primary {
+    fn start() -> Self {
+        let data_dir = TempDir::new().unwrap();
+        let wal = open_wal(data_dir.path());
+        let streamer = WalStreamer::bind(data_dir.path(), stream_config()).unwrap();
+        let address = streamer.local_addr().unwrap().to_string();
+        let tracker = streamer.tracker();
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let handle = thread::spawn(move || streamer.run(&flag).unwrap());
+        Self {
+            data_dir,
+            wal,
+            address,
+            tracker,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    fn sync(&self, config: SyncReplicationConfig) -> SyncReplication {
+        SyncReplication::new(self.data_dir.path(), config, Arc::clone(&self.tracker))
+    }
+
+    /// Append a record, returning its sequence number.
+    fn write(&mut self) -> u64 {
+        let id = Uuid::new_v4().to_string();
+        let payload = WalPayload::new("users", id, "user", "v1", b"{\"name\":\"a\"}".to_vec());
+        self.wal.append_insert(payload).unwrap();
+        self.wal.last_sequence_number()
+    }
+}
+
+impl Drop for Primary {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// A Replica following the Primary on its own thread.
+struct Replica {
+    _data_dir: TempDir,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<u64>>,
+}
+
+impl Replica {
+    fn start(primary: &Primary) -> Self {
+        let data_dir = TempDir::new().unwrap();
+        let mut follower = WalFollower::open(
+            data_dir.path(),
+            Uuid::new_v4(),
+            primary.address.clone(),
+            stream_config(),
+            open_wal(data_dir.path()),
+            StorageWriter::open(data_dir.path()).unwrap(),
+        )
+        .unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            follower.run(&flag).unwrap();
+            follower.applied_sequence()
+        });
+        Self {
+            _data_dir: data_dir,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Stop following, returning the last applied sequence.
+    fn stop(mut self) -> u64 {
+        self.stop.store(true, Ordering::Release);
+        self.handle.take().unwrap().join().unwrap()
+    }
+}
+
+impl Drop for Replica {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Start two Replicas and wait until both are streaming.
+fn start_replicas(primary: &Primary) -> (Replica, Replica) {
+    let replicas = (Replica::start(primary), Replica::start(primary));
+    wait_until("both replicas to connect", || {
+        primary.tracker.connected_replicas() == 2
+    });
+    replicas
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[test]
+fn test_quorum_commit_waits_for_both_replicas() {
+    let mut primary = Primary::start();
+    let (first, second) = start_replicas(&primary);
+    let sync = primary.sync(quorum(2, SyncTimeoutPolicy::Fail));
+
+    for _ in 0..5 {
+        let sequence = primary.write();
+        sync.wait_for(sequence).unwrap();
+        // Every acknowledgment names the exact record
+        let acked: Vec<u64> = primary
+            .tracker
+            .replicas()
+            .iter()
+            .map(|progress| progress.acked_sequence)
+            .collect();
+        assert_eq!(acked, [sequence, sequence]);
+    }
+    assert!(!sync.is_degraded());
+
+    assert_eq!(first.stop(), 5);
+    assert_eq!(second.stop(), 5);
+}
+
+#[test]
+fn test_one_replica_down_within_quorum_still_commits() {
+    let mut primary = Primary::start();
+    let (first, second) = start_replicas(&primary);
+    let sync = primary.sync(quorum(1, SyncTimeoutPolicy::Fail));
+    sync.wait_for(primary.write()).unwrap();
+
+    assert_eq!(second.stop(), 1);
+    wait_until("the replica to disconnect", || {
+        primary.tracker.connected_replicas() == 1
+    });
+    for _ in 0..3 {
+        sync.wait_for(primary.write()).unwrap();
+    }
+    assert!(!sync.is_degraded());
+    assert_eq!(first.stop(), 4);
+}
+
+#[test]
+fn test_both_replicas_down_strict_times_out() {
+    let mut primary = Primary::start();
+    let (first, second) = start_replicas(&primary);
+    let sync = primary.sync(quorum(1, SyncTimeoutPolicy::Fail));
+    sync.wait_for(primary.write()).unwrap();
+
+    first.stop();
+    second.stop();
+    wait_until("both replicas to disconnect", || {
+        primary.tracker.connected_replicas() == 0
+    });
+
+    let sequence = primary.write();
+    let err = sync.wait_for(sequence).unwrap_err();
+    assert_eq!(err.kind, ReplicationErrorKind::ReplicationTimeout);
+    assert!(err.message.contains(&format!("Record {}", sequence)));
+    assert!(err.message.contains("0 of 1"));
+    assert!(!sync.is_degraded());
+
+    // The record stays on the Primary and reaches a Replica that returns
+    let _back = Replica::start(&primary);
+    wait_until("the returning replica to catch up", || {
+        primary
+            .tracker
+            .replicas()
+            .iter()
+            .any(|progress| progress.acked_sequence == sequence)
+    });
+}
+
+#[test]
+fn test_both_replicas_down_degrades_and_restores() {
+    let mut primary = Primary::start();
+    let (first, second) = start_replicas(&primary);
+    let logger = Arc::new(VecLogger::new());
+    let sync = primary
+        .sync(quorum(1, SyncTimeoutPolicy::Degrade))
+        .with_logger(logger.clone());
+    sync.wait_for(primary.write()).unwrap();
+
+    first.stop();
+    second.stop();
+    wait_until("both replicas to disconnect", || {
+        primary.tracker.connected_replicas() == 0
+    });
+
+    sync.wait_for(primary.write()).unwrap();
+    assert!(sync.is_degraded());
+    assert!(SyncState::load(primary.data_dir.path()).degraded);
+
+    // Degraded writes do not wait while no Replica is connected
+    let started = Instant::now();
+    sync.wait_for(primary.write()).unwrap();
+    assert!(started.elapsed() < Duration::from_millis(300));
+
+    let _back = Replica::start(&primary);
+    wait_until("the returning replica to connect", || {
+        primary.tracker.connected_replicas() == 1
+    });
+    sync.wait_for(primary.write()).unwrap();
+    assert!(!sync.is_degraded());
+    assert!(!SyncState::load(primary.data_dir.path()).degraded);
+    assert_eq!(
+        logger.events(),
+        ["REPLICATION_SYNC_DEGRADED", "REPLICATION_SYNC_RESTORED"]
+    );
+}
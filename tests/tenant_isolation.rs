@@ -0,0 +1,143 @@
+//! Tenant Isolation Tests
+//!
+//! Tests that a pipeline with tenant isolation confines every caller to its
+//! own tenant's rows in a shared collection:
+//! - Queries only return the caller's tenant, whatever filter it sends
+//! - Another tenant's documents cannot be read, updated or deleted
+//! - Writes are stamped with the caller's tenant and cannot forge another
+//! - A request without a tenant claim is refused
+//! - The service role sees every tenant
+
+use aerodb::auth::crypto::PasswordPolicy;
+use aerodb::auth::jwt::JwtConfig;
+use aerodb::auth::{JwtManager, RlsContext, User};
+use aerodb::core::{AuthContext, BridgeConfig, CoreError, PipelineBridge, RequestContext};
+use serde_json::json;
+use uuid::Uuid;
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+fn jwt() -> JwtManager {
+    JwtManager::new(JwtConfig {
+        service_role_secret: Some("service-role-secret".to_string()),
+        ..Default::default()
+    })
+}
+
+fn bridge() -> PipelineBridge {
+    PipelineBridge::new_in_memory(BridgeConfig {
+        enable_rls: false,
+        tenant_isolation: true,
+        ..Default::default()
+    })
+}
+
+/// Context for a validated token, as the unified API builds it
+fn context(jwt: &JwtManager, token: &str) -> RequestContext {
+    let rls = RlsContext::from_claims(&jwt.validate_token(token).unwrap()).unwrap();
+    RequestContext::new(AuthContext {
+        user_id: rls.user_id,
+        is_authenticated: rls.is_authenticated,
+        is_service_role: rls.is_service_role,
+        claims: rls.claims,
+    })
+}
+
+fn tenant_context(jwt: &JwtManager, email: &str, tenant_id: Uuid) -> RequestContext {
+    let user = User::new(email.to_string(), "password123", &PasswordPolicy::default()).unwrap();
+    context(jwt, &jwt.generate_tenant_token(&user, tenant_id).unwrap())
+}
+
+async fn insert(bridge: &PipelineBridge, body: &str, ctx: &RequestContext) -> String {
+    let result = bridge
+        .write("notes", json!({"body": body}), "notes", ctx.clone())
+        .await
+        .unwrap();
+    result["id"].as_str().unwrap().to_string()
+}
+
+// =============================================================================
+// Cross-Tenant Access Tests
+// =============================================================================
+
+/// A tenant cannot reach another tenant's rows, even when it asks for them.
+#[tokio::test]
+async fn test_cross_tenant_access_is_refused() {
+    let bridge = bridge();
+    let jwt = jwt();
+    let (acme, globex) = (Uuid::new_v4(), Uuid::new_v4());
+    let alice = tenant_context(&jwt, "alice@acme.test", acme);
+    let bob = tenant_context(&jwt, "bob@globex.test", globex);
+
+    let acme_note = insert(&bridge, "acme", &alice).await;
+    insert(&bridge, "globex", &bob).await;
+
+    // A crafted tenant filter does not widen the query
+    let filter = json!({"tenant_id": acme.to_string()});
+    let rows = bridge
+        .query("notes", Some(filter), 10, 0, bob.clone())
+        .await
+        .unwrap();
+    assert_eq!(rows["count"], 1);
+    assert_eq!(rows["data"][0]["body"], "globex");
+    assert_eq!(rows["data"][0]["tenant_id"], globex.to_string());
+
+    let read = bridge.read("notes", &acme_note, bob.clone()).await;
+    assert!(matches!(read, Err(CoreError::NotFound(_))));
+
+    let update = bridge
+        .update("notes", &acme_note, json!({"body": "taken"}), bob.clone())
+        .await;
+    assert!(matches!(update, Err(CoreError::AccessDenied(_))));
+
+    let delete = bridge.delete("notes", &acme_note, bob.clone()).await;
+    assert!(matches!(delete, Err(CoreError::AccessDenied(_))));
+
+    let note = bridge.read("notes", &acme_note, alice).await.unwrap();
+    assert_eq!(note["body"], "acme");
+}
+
+/// Writes cannot claim or move into another tenant.
+#[tokio::test]
+async fn test_writes_cannot_forge_tenant() {
+    let bridge = bridge();
+    let jwt = jwt();
+    let (acme, globex) = (Uuid::new_v4(), Uuid::new_v4());
+    let bob = tenant_context(&jwt, "bob@globex.test", globex);
+
+    let forged = json!({"body": "forged", "tenant_id": acme.to_string()});
+    let write = bridge.write("notes", forged, "notes", bob.clone()).await;
+    assert!(matches!(write, Err(CoreError::AccessDenied(_))));
+
+    let note = insert(&bridge, "globex", &bob).await;
+    let moved = json!({"tenant_id": acme.to_string()});
+    let update = bridge.update("notes", &note, moved, bob.clone()).await;
+    assert!(matches!(update, Err(CoreError::AccessDenied(_))));
+}
+
+/// A caller without a tenant claim is refused; the service role is not scoped.
+#[tokio::test]
+async fn test_tenant_claim_required() {
+    let bridge = bridge();
+    let jwt = jwt();
+    let alice = tenant_context(&jwt, "alice@acme.test", Uuid::new_v4());
+    let bob = tenant_context(&jwt, "bob@globex.test", Uuid::new_v4());
+    insert(&bridge, "acme", &alice).await;
+    insert(&bridge, "globex", &bob).await;
+
+    let user = User::new(
+        "carol@example.test".to_string(),
+        "password123",
+        &PasswordPolicy::default(),
+    )
+    .unwrap();
+    let carol = context(&jwt, &jwt.generate_access_token(&user).unwrap());
+    let rows = bridge.query("notes", None, 10, 0, carol).await;
+    assert!(matches!(rows, Err(CoreError::AccessDenied(_))));
+
+    let service = context(&jwt, &jwt.generate_service_role_token().unwrap());
+    let rows = bridge.query("notes", None, 10, 0, service).await.unwrap();
+    assert_eq!(rows["count"], 2);
+}
@@ -8,7 +8,7 @@
 //! Per WAL.md §216-229, any corruption detected during recovery
 //! must halt immediately with no partial replay and no repair attempts.
 
-use aerodb::wal::{RecordType, WalPayload, WalReader, WalWriter};
+use aerodb::wal::{RecordType, WalPayload, WalReader, WalSegmentConfig, WalSyncConfig, WalWriter};
 use std::fs;
 use tempfile::TempDir;
 
@@ -447,6 +447,60 @@ fn test_partial_write_at_eof_not_durable() {
     assert!(second.is_err(), "Partial record at EOF must cause halt");
 }
 
+/// Segmented WAL: a partial record at the tail of the last segment was never
+/// acknowledged, so recovery stops cleanly before it and the writer discards it.
+#[test]
+fn test_segmented_partial_write_at_tail_discarded() {
+    let temp_dir = create_temp_data_dir();
+    let data_dir = temp_dir.path();
+    let open_segmented = || {
+        WalWriter::open_segmented(
+            data_dir,
+            WalSyncConfig::default(),
+            WalSegmentConfig::new(256),
+        )
+        .unwrap()
+    };
+
+    let last_segment = {
+        let mut writer = open_segmented();
+        for i in 1..=6 {
+            writer
+                .append_insert(create_test_payload(&format!("doc{}", i)))
+                .unwrap();
+        }
+        assert!(writer.active_segment_index().unwrap() > 1);
+        writer.path().to_path_buf()
+    };
+
+    // Partial record header at the tail of the last segment, plus a stray file
+    {
+        use std::io::Write;
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&last_segment)
+            .unwrap();
+        file.write_all(&[0x50, 0x00, 0x00, 0x00, 0x01]).unwrap();
+        fs::write(data_dir.join("wal/backup-copy.log"), b"ignored").unwrap();
+    }
+
+    let mut reader = WalReader::open_from_data_dir(data_dir).unwrap();
+    assert_eq!(reader.read_all().unwrap().len(), 6);
+    assert!(reader.torn_tail().is_some());
+
+    // Restart: the torn bytes are cut off and appends continue
+    {
+        let mut writer = open_segmented();
+        assert_eq!(writer.next_sequence_number(), 7);
+        writer.append_insert(create_test_payload("doc7")).unwrap();
+    }
+
+    let mut reader = WalReader::open_from_data_dir(data_dir).unwrap();
+    let records = reader.read_all().unwrap();
+    assert_eq!(records.len(), 7);
+    assert!(reader.torn_tail().is_none());
+}
+
 // =============================================================================
 // Recovery Restart Tests
 // =============================================================================
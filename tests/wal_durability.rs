@@ -127,6 +127,71 @@ fn test_d1_multiple_reopens_preserve_data() {
     );
 }
 
+/// D1 under group commit: every acknowledged record survives a crash.
+///
+/// Concurrent writers share fsyncs over a sub-millisecond window. The
+/// process then "crashes": the writer is abandoned without shutdown and a
+/// torn, never-acknowledged record is left at the WAL tail.
+#[test]
+fn test_d1_group_commit_acknowledged_writes_survive_crash() {
+    use aerodb::wal::{GroupCommitWriter, WalSegmentConfig, WalSyncConfig};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let temp_dir = create_temp_data_dir();
+    let data_dir = temp_dir.path();
+    let config = WalSyncConfig::group_commit(Duration::from_micros(500), 8);
+
+    let writer =
+        WalWriter::open_segmented(data_dir, config.clone(), WalSegmentConfig::new(64 * 1024))
+            .unwrap();
+    let writer = Arc::new(GroupCommitWriter::new(writer, config.clone()));
+
+    let handles: Vec<_> = (0..16)
+        .map(|i| {
+            let writer = Arc::clone(&writer);
+            std::thread::spawn(move || {
+                writer
+                    .append_insert(create_test_payload(&format!("doc{}", i)))
+                    .unwrap()
+            })
+        })
+        .collect();
+    let mut acked: Vec<u64> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    acked.sort_unstable();
+    assert_eq!(acked, (1..=16).collect::<Vec<_>>());
+
+    // Crash: no orderly shutdown, and a partial record at the tail
+    let writer = Arc::try_unwrap(writer).unwrap().into_inner();
+    let active_segment = writer.path().to_path_buf();
+    std::mem::forget(writer);
+    {
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&active_segment)
+            .unwrap();
+        file.write_all(&[0x50, 0x00, 0x00, 0x00, 0x01]).unwrap();
+    }
+
+    let mut reader = WalReader::open_from_data_dir(data_dir).unwrap();
+    let recovered: Vec<u64> = reader
+        .read_all()
+        .unwrap()
+        .iter()
+        .map(|r| r.sequence_number)
+        .collect();
+    assert_eq!(
+        recovered, acked,
+        "D1 VIOLATION: Acknowledged group commit writes were lost after crash"
+    );
+    assert!(reader.torn_tail().is_some());
+
+    // Restart continues after the last acknowledged record
+    let writer =
+        WalWriter::open_segmented(data_dir, config, WalSegmentConfig::new(64 * 1024)).unwrap();
+    assert_eq!(writer.next_sequence_number(), 17);
+}
+
 // =============================================================================
 // INVARIANT R1: WAL Precedes Acknowledgment
 // =============================================================================